@@ -47,8 +47,8 @@ fn specialize_expr<'a>(
     can_problems.retain(|prob| {
         !matches!(
             prob,
-            roc_problem::can::Problem::UnusedDef(_, _)
-                | roc_problem::can::Problem::UnusedBranchDef(..)
+            roc_problem::can::Problem::UnusedDef(..)
+                | roc_problem::can::Problem::UnusedPatternBinding(..)
         )
     });
 
@@ -496,6 +496,30 @@ pub fn desugar_expr<'a>(
     env: &mut SoloEnv<'a>,
     scope: &mut SoloScope,
     loc_expr: &'a Loc<Expr<'a>>,
+) -> &'a Loc<Expr<'a>> {
+    // Spaces are dropped here, so canonicalization never sees them. Counting everything else
+    // means canonicalization is always at least as deep as we were, so it reaches its own
+    // nesting limit before it could get to anything we left undesugared.
+    if let SpaceBefore(..) | SpaceAfter(..) = loc_expr.value {
+        return desugar_expr_help(env, scope, loc_expr);
+    }
+
+    // Machine-generated code can be nested deeply enough to overflow the stack
+    if env.nesting_depth >= env.max_nesting_depth {
+        return loc_expr;
+    }
+
+    env.nesting_depth += 1;
+    let desugared = desugar_expr_help(env, scope, loc_expr);
+    env.nesting_depth -= 1;
+
+    desugared
+}
+
+fn desugar_expr_help<'a>(
+    env: &mut SoloEnv<'a>,
+    scope: &mut SoloScope,
+    loc_expr: &'a Loc<Expr<'a>>,
 ) -> &'a Loc<Expr<'a>> {
     match &loc_expr.value {
         Float(..)
@@ -513,6 +537,7 @@ pub fn desugar_expr<'a>(
         | Tag(_)
         | OpaqueRef(_)
         | Crash
+        | TypedHole(_)
         | Try => loc_expr,
 
         Str(str_literal) => match str_literal {
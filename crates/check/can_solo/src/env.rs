@@ -2,6 +2,7 @@ use std::path::Path;
 
 use bumpalo::Bump;
 use roc_module::symbol::ModuleId;
+use roc_parse::nesting::max_nesting_depth;
 use roc_problem::can::Problem;
 use roc_region::all::LineInfo;
 
@@ -23,6 +24,13 @@ pub struct SoloEnv<'a> {
     /// otherwise we can leave it as `None` and never pay the cost of scanning the source an extra
     /// time.
     pub lazy_line_info: &'a mut Option<LineInfo>,
+
+    /// How many expressions deep desugaring currently is, not counting spaces
+    pub(crate) nesting_depth: usize,
+
+    /// Expressions nested deeper than this are left as they are, so that deeply nested (usually
+    /// machine-generated) code can't overflow the stack. Canonicalization turns them into errors.
+    pub max_nesting_depth: usize,
 }
 
 impl<'a> SoloEnv<'a> {
@@ -35,6 +43,8 @@ impl<'a> SoloEnv<'a> {
             src,
             problems: Vec::new(),
             lazy_line_info: arena.alloc(None),
+            nesting_depth: 0,
+            max_nesting_depth: max_nesting_depth(),
         }
     }
 
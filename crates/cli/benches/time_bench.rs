@@ -1,7 +1,7 @@
 use std::time::Duration;
 
 use cli_test_utils::bench_utils::{
-    bench_cfold, bench_deriv, bench_nqueens, bench_quicksort, bench_rbtree_ck,
+    bench_cfold, bench_deriv, bench_list_sort, bench_nqueens, bench_quicksort, bench_rbtree_ck,
 };
 use criterion::{measurement::WallTime, BenchmarkGroup, Criterion, SamplingMode};
 
@@ -31,6 +31,7 @@ fn bench_group_wall_time(c: &mut Criterion) {
         bench_rbtree_ck, // ms = makeMap 5 80000
         // bench_rbtree_delete, // m = makeMap 100000
         bench_quicksort, // list size 10000
+        bench_list_sort, // list size 10000, with the builtin sort
     ];
 
     for bench_func in bench_funcs.iter() {
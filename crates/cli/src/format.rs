@@ -6,11 +6,13 @@ use std::path::{Path, PathBuf};
 use bumpalo::{collections::String as BumpString, Bump};
 use roc_can::abilities::{IAbilitiesStore, Resolved};
 use roc_can::expr::{DeclarationTag, Declarations, Expr};
+use roc_collections::{ArenaStats, MutMap};
 use roc_error_macros::{internal_error, user_error};
 use roc_fmt::def::fmt_defs;
 use roc_fmt::header::fmt_header;
+use roc_fmt::transform::{apply_transforms, AppliedChange};
 use roc_fmt::Buf;
-use roc_fmt::MigrationFlags;
+use roc_fmt::{DbgSummary, MigrationFlags};
 use roc_load::{ExecutionMode, FunctionKind, LoadConfig, LoadedModule, LoadingProblem, Threading};
 use roc_module::symbol::{Interns, ModuleId};
 use roc_packaging::cache::{self, RocCacheDir};
@@ -31,7 +33,7 @@ pub enum FormatMode {
     CheckOnly,
 }
 
-fn flatten_directories(files: std::vec::Vec<PathBuf>) -> std::vec::Vec<PathBuf> {
+pub(crate) fn flatten_directories(files: std::vec::Vec<PathBuf>) -> std::vec::Vec<PathBuf> {
     let mut to_flatten = files;
     let mut files = vec![];
 
@@ -75,19 +77,26 @@ fn is_roc_file(path: &Path) -> bool {
     matches!(path.extension().and_then(OsStr::to_str), Some("roc"))
 }
 
+/// Formats the files, and returns how much arena memory formatting each of them used.
+/// The arena is reset after every file, so each file's stats only count its own allocations.
 pub fn format_files(
     files: std::vec::Vec<PathBuf>,
     mode: FormatMode,
     flags: MigrationFlags,
-) -> Result<(), String> {
-    let arena = Bump::new();
+) -> Result<MutMap<PathBuf, ArenaStats>, String> {
+    let mut arena = Bump::new();
+    let mut arena_stats = MutMap::default();
     let mut files_to_reformat = Vec::new(); // to track which files failed `roc format --check`
 
     for file in flatten_directories(files) {
         let src = std::fs::read_to_string(&file).unwrap();
 
-        match format_src(&arena, &src, flags) {
-            Ok(buf) => {
+        match format_src_with_summary(&arena, &src, flags) {
+            Ok((buf, summary)) => {
+                if !matches!(mode, FormatMode::CheckOnly) {
+                    report_summary(&file, &src, &summary);
+                }
+
                 match mode {
                     FormatMode::CheckOnly => {
                         // If a file fails `format --check`, add it to the file
@@ -168,8 +177,24 @@ pub fn format_files(
                         unstable_2_file.display()
                     );
                 }
+                FormatProblem::NestingTooDeep { max_depth } => {
+                    user_error!(
+                        "I couldn't format {} because it has expressions nested more than {} levels deep, \
+                        so I left it unchanged.\n\n\
+                        Try splitting the outer expressions into smaller defs, or set the {} environment \
+                        variable to raise the limit.",
+                        file.display(),
+                        max_depth,
+                        roc_parse::nesting::MAX_NESTING_DEPTH_ENV_VAR
+                    );
+                }
             },
         }
+
+        let mut stats = ArenaStats::default();
+
+        stats.reset(&mut arena);
+        arena_stats.insert(file, stats);
     }
     // After processing all files, check if any files failed `format --check`
     if !files_to_reformat.is_empty() {
@@ -179,7 +204,7 @@ pub fn format_files(
             file_list
         ));
     }
-    Ok(())
+    Ok(arena_stats)
 }
 
 #[derive(Debug)]
@@ -197,15 +222,44 @@ pub enum FormatProblem {
         formatted_src: String,
         reformatted_src: String,
     },
+    /// Some expression was nested too deeply to format without risking a stack overflow
+    NestingTooDeep { max_depth: usize },
 }
 
 pub fn format_src(arena: &Bump, src: &str, flags: MigrationFlags) -> Result<String, FormatProblem> {
-    let ast = arena.alloc(parse_all(arena, src).unwrap_or_else(|e| {
+    format_src_with_summary(arena, src, flags).map(|(formatted, _)| formatted)
+}
+
+/// What the migrations in [MigrationFlags] changed while formatting a file.
+#[derive(Debug, Default)]
+pub struct FormatSummary {
+    /// How many `dbg`s the [roc_fmt::DbgMigration] changed
+    pub dbg: DbgSummary,
+    /// Where each of the [roc_fmt::transform::SourceTransform]s changed the source
+    pub changes: Vec<AppliedChange>,
+}
+
+/// Like [format_src], but also says what the migrations in `flags` changed.
+pub fn format_src_with_summary(
+    arena: &Bump,
+    src: &str,
+    flags: MigrationFlags,
+) -> Result<(String, FormatSummary), FormatProblem> {
+    let parsed = parse_all(arena, src).unwrap_or_else(|e| {
         user_error!("Unexpected parse failure when parsing this formatting:\n\n{src}\n\nParse error was:\n\n{:#?}\n\n", e)
-    }));
+    });
+    let (parsed, changes) = apply_transforms(arena, &parsed, flags.transforms);
+    let ast = arena.alloc(parsed);
     let mut buf = Buf::new_in(arena, flags);
+    buf.set_source(src);
     fmt_all(&mut buf, ast);
 
+    if buf.nesting_too_deep() {
+        return Err(FormatProblem::NestingTooDeep {
+            max_depth: buf.max_nesting_depth(),
+        });
+    }
+
     let reparsed_ast = match arena.alloc(parse_all(arena, buf.as_str())) {
         Ok(ast) => ast,
         Err(e) => {
@@ -236,6 +290,7 @@ pub fn format_src(arena: &Bump, src: &str, flags: MigrationFlags) -> Result<Stri
 
     // Now verify that the resultant formatting is _stable_ - i.e. that it doesn't change again if re-formatted
     let mut reformatted_buf = Buf::new_in(arena, flags);
+    reformatted_buf.set_source(buf.as_str());
 
     fmt_all(&mut reformatted_buf, reparsed_ast);
 
@@ -246,7 +301,161 @@ pub fn format_src(arena: &Bump, src: &str, flags: MigrationFlags) -> Result<Stri
         });
     }
 
-    Ok(buf.as_str().to_string())
+    let summary = FormatSummary {
+        dbg: buf.dbg_summary(),
+        changes,
+    };
+
+    Ok((buf.as_str().to_string(), summary))
+}
+
+fn report_summary(file: &Path, src: &str, summary: &FormatSummary) {
+    if !summary.dbg.is_empty() {
+        eprintln!(
+            "{}: removed {} and lifted {} `dbg`s",
+            file.display(),
+            summary.dbg.removed,
+            summary.dbg.lifted
+        );
+    }
+
+    let line_info = LineInfo::new(src);
+
+    for change in summary.changes.iter() {
+        let LineColumn { line, column } = line_info.convert_pos(change.region.start());
+
+        eprintln!(
+            "{}:{}:{}: applied {}",
+            file.display(),
+            line + 1,
+            column + 1,
+            change.transform
+        );
+    }
+}
+
+#[derive(Debug)]
+pub enum FormatProjectError {
+    ParsingFailed {
+        file: PathBuf,
+        parse_err: String,
+    },
+    /// Formatting the file would have changed what it means, or wasn't stable
+    FormattingFailed {
+        file: PathBuf,
+        problem: FormatProblem,
+    },
+    Io {
+        file: PathBuf,
+        error: std::io::Error,
+    },
+}
+
+impl std::fmt::Display for FormatProjectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FormatProjectError::ParsingFailed { file, parse_err } => write!(
+                f,
+                "I couldn't parse {}, so I didn't format any files:\n\n{parse_err}",
+                file.display()
+            ),
+            FormatProjectError::FormattingFailed { file, problem } => write!(
+                f,
+                "Formatting bug in {}, so I didn't format any files:\n\n{problem:?}",
+                file.display()
+            ),
+            FormatProjectError::Io { file, error } => write!(
+                f,
+                "I couldn't write {}, so I left all the files as they were: {error}",
+                file.display()
+            ),
+        }
+    }
+}
+
+/// Format every Roc file in `paths` (and the directories in it), and return the files that
+/// changed.
+///
+/// All the files are formatted and checked before any of them is written, and each one is written
+/// to a temporary file that then replaces it. If any of that fails, the files that were already
+/// replaced get their original contents back, so a failure never leaves a project half-formatted.
+pub fn format_project(
+    paths: std::vec::Vec<PathBuf>,
+    flags: MigrationFlags,
+) -> Result<std::vec::Vec<PathBuf>, FormatProjectError> {
+    let arena = Bump::new();
+    let mut changed = Vec::new();
+
+    for file in flatten_directories(paths) {
+        let io_error = |error| FormatProjectError::Io {
+            file: file.clone(),
+            error,
+        };
+
+        let src = std::fs::read_to_string(&file).map_err(io_error)?;
+
+        if let Err(err) = parse_all(&arena, &src) {
+            return Err(FormatProjectError::ParsingFailed {
+                file,
+                parse_err: format!("{err:?}"),
+            });
+        }
+
+        match format_src_with_summary(&arena, &src, flags) {
+            Ok((formatted, summary)) if formatted != src => {
+                report_summary(&file, &src, &summary);
+                changed.push((file, src, formatted));
+            }
+            Ok(_) => {}
+            Err(problem) => return Err(FormatProjectError::FormattingFailed { file, problem }),
+        }
+    }
+
+    // Nothing has been written yet, so if writing a temporary file fails, dropping the ones that
+    // were written deletes them and leaves the project as it was.
+    let mut temp_files = Vec::with_capacity(changed.len());
+
+    for (file, _, formatted) in changed.iter() {
+        temp_files.push(write_temp_file(file, formatted).map_err(|error| {
+            FormatProjectError::Io {
+                file: file.clone(),
+                error,
+            }
+        })?);
+    }
+
+    for (index, temp_file) in temp_files.into_iter().enumerate() {
+        let file = &changed[index].0;
+
+        if let Err(err) = temp_file.persist(file) {
+            for (file, original, _) in changed[..index].iter() {
+                let _ = std::fs::write(file, original);
+            }
+
+            return Err(FormatProjectError::Io {
+                file: file.clone(),
+                error: err.error,
+            });
+        }
+    }
+
+    Ok(changed.into_iter().map(|(file, _, _)| file).collect())
+}
+
+/// A temporary file next to `file`, with the same permissions, that holds `contents`.
+fn write_temp_file(file: &Path, contents: &str) -> std::io::Result<tempfile::NamedTempFile> {
+    let dir = match file.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    let mut temp_file = tempfile::NamedTempFile::new_in(dir)?;
+    temp_file.write_all(contents.as_bytes())?;
+    temp_file
+        .as_file()
+        .set_permissions(std::fs::metadata(file)?.permissions())?;
+
+    Ok(temp_file)
 }
 
 fn parse_all<'a>(arena: &'a Bump, src: &'a str) -> Result<FullAst<'a>, SyntaxError<'a>> {
@@ -294,6 +503,11 @@ pub fn annotate_file(arena: &Bump, file: PathBuf) -> Result<(), AnnotationProble
         palette: DEFAULT_PALETTE,
         threading: Threading::AllAvailable,
         exec_mode: ExecutionMode::Check,
+        emit_match_trees: false,
+        trace_abilities: false,
+        profile: None,
+        doc_examples: 0,
+        comptime_limits: roc_load::ComptimeLimits::default(),
     };
 
     let mut loaded = roc_load::load_and_typecheck(
@@ -444,6 +658,7 @@ pub fn annotation_edit(
 mod tests {
     use super::*;
     use indoc::indoc;
+    use roc_fmt::DbgMigration;
     use std::fs::{read_to_string, File};
     use std::io::Write;
     use tempfile::{tempdir, TempDir};
@@ -479,6 +694,53 @@ main =
         assert!(result.is_ok(), "Failed to delete temp directory");
     }
 
+    #[test]
+    fn test_format_project_writes_all_files() {
+        let dir = tempdir().unwrap();
+        let formatted = setup_test_file(dir.path(), "formatted.roc", FORMATTED_ROC);
+        let unformatted = setup_test_file(dir.path(), "test1.roc", UNFORMATTED_ROC);
+        let flags = MigrationFlags {
+            snakify: false,
+            parens_and_commas: false,
+            dbg: DbgMigration::Keep,
+            transforms: &[],
+        };
+
+        let changed = format_project(vec![dir.path().to_path_buf()], flags).unwrap();
+        assert_eq!(changed, vec![unformatted.clone()]);
+
+        let result = format_files(vec![formatted, unformatted], FormatMode::CheckOnly, flags);
+        assert!(result.is_ok());
+
+        cleanup_temp_dir(dir);
+    }
+
+    #[test]
+    fn test_format_project_leaves_files_alone_on_failure() {
+        let dir = tempdir().unwrap();
+        let unformatted = setup_test_file(dir.path(), "test1.roc", UNFORMATTED_ROC);
+        let broken = setup_test_file(dir.path(), "test2.roc", "app [main] {");
+        let flags = MigrationFlags {
+            snakify: false,
+            parens_and_commas: false,
+            dbg: DbgMigration::Keep,
+            transforms: &[],
+        };
+
+        let result = format_project(vec![unformatted.clone(), broken.clone()], flags);
+        assert!(matches!(
+            result,
+            Err(FormatProjectError::ParsingFailed { file, .. }) if file == broken
+        ));
+
+        assert_eq!(
+            read_to_string(&unformatted).unwrap(),
+            format!("{UNFORMATTED_ROC}\n")
+        );
+
+        cleanup_temp_dir(dir);
+    }
+
     #[test]
     fn test_single_file_needs_reformatting() {
         let dir = tempdir().unwrap();
@@ -486,6 +748,8 @@ main =
         let flags = MigrationFlags {
             snakify: false,
             parens_and_commas: false,
+            dbg: DbgMigration::Keep,
+            transforms: &[],
         };
 
         let result = format_files(vec![file_path.clone()], FormatMode::CheckOnly, flags);
@@ -509,6 +773,8 @@ main =
         let flags = MigrationFlags {
             snakify: false,
             parens_and_commas: false,
+            dbg: DbgMigration::Keep,
+            transforms: &[],
         };
 
         let result = format_files(vec![file1, file2], FormatMode::CheckOnly, flags);
@@ -526,6 +792,8 @@ main =
         let flags = MigrationFlags {
             snakify: false,
             parens_and_commas: false,
+            dbg: DbgMigration::Keep,
+            transforms: &[],
         };
 
         let result = format_files(vec![file_path], FormatMode::CheckOnly, flags);
@@ -534,6 +802,40 @@ main =
         cleanup_temp_dir(dir);
     }
 
+    #[test]
+    fn test_arena_stats_per_file() {
+        let dir = tempdir().unwrap();
+        let small = setup_test_file(dir.path(), "small.roc", FORMATTED_ROC);
+        let large = setup_test_file(
+            dir.path(),
+            "large.roc",
+            &format!("{FORMATTED_ROC}\n{}", "x = [1, 2, 3]\n".repeat(200)),
+        );
+        let flags = MigrationFlags {
+            snakify: false,
+            parens_and_commas: false,
+            dbg: DbgMigration::Keep,
+            transforms: &[],
+        };
+
+        let stats = format_files(
+            vec![small.clone(), large.clone()],
+            FormatMode::WriteToFile,
+            flags,
+        )
+        .unwrap();
+
+        // The arena is reset after every file, so the smaller file doesn't count the larger one's
+        // memory, even if it was formatted second.
+        assert_eq!(stats[&small].resets, 1);
+        assert_eq!(stats[&large].resets, 1);
+        assert!(stats[&small].allocated_bytes > 0);
+        assert!(stats[&large].allocated_bytes > stats[&small].allocated_bytes);
+        assert_eq!(stats[&small].high_water_mark, stats[&small].allocated_bytes);
+
+        cleanup_temp_dir(dir);
+    }
+
     #[test]
     fn test_some_files_need_reformatting() {
         let dir = tempdir().unwrap();
@@ -543,6 +845,8 @@ main =
         let flags = MigrationFlags {
             snakify: false,
             parens_and_commas: false,
+            dbg: DbgMigration::Keep,
+            transforms: &[],
         };
 
         let result = format_files(
@@ -558,6 +862,52 @@ main =
         cleanup_temp_dir(dir);
     }
 
+    #[test]
+    fn test_transforms_report_changes() {
+        let src = indoc! {r#"
+            module [parse]
+
+            parse = |str|
+                n = try Str.to_u64 str
+                m = try(Str.to_u64, "2")
+                Ok(n + m)
+        "#};
+        let flags = MigrationFlags {
+            snakify: false,
+            parens_and_commas: false,
+            dbg: DbgMigration::Keep,
+            transforms: roc_fmt::transform::BUILTIN_TRANSFORMS,
+        };
+
+        let arena = Bump::new();
+        let (formatted, summary) = format_src_with_summary(&arena, src, flags).unwrap();
+
+        assert_eq!(
+            formatted,
+            indoc! {r#"
+                module [parse]
+
+                parse = |str|
+                    n = Str.to_u64? str
+                    m = Str.to_u64("2")?
+                    Ok(n + m)
+            "#}
+        );
+
+        let changes: Vec<_> = summary
+            .changes
+            .iter()
+            .map(|change| (change.transform, change.region.start().offset))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                ("try-suffix", src.find("try Str").unwrap() as u32),
+                ("try-suffix", src.find("try(").unwrap() as u32),
+            ]
+        );
+    }
+
     const HEADER: &str = indoc! {r#"
         interface Test
             exposes []
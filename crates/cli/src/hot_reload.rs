@@ -0,0 +1,264 @@
+//! Hot code reloading for `roc run --hot`.
+//!
+//! Instead of replacing itself with the program, `roc` starts it as a child process and keeps
+//! watching the `.roc` files next to the app. When one of them changes, the app is rebuilt as a
+//! shared library, and the host learns where to find it from a manifest file whose path is in
+//! the `ROC_HOT_RELOAD` environment variable:
+//!
+//! ```text
+//! # roc hot reload v1
+//! {generation}\t{path to the shared library}
+//! ```
+//!
+//! A host that supports hot reloading reads the manifest at a safe point, where no Roc code is
+//! running and it holds no Roc values (between two frames of a game, or two requests of a
+//! server). When the generation went up, it loads the library and calls the functions the new
+//! version of the app exposes from then on. Whatever state the host keeps across safe points
+//! survives the reload. Hosts without such a hook keep running the app they started with.
+//! Rust hosts can use the `hot_reload` module of `roc_embed`, which reads this manifest.
+//!
+//! The library doesn't contain the functions the host provides to the app, like `roc_alloc`,
+//! so the host has to export them to the libraries it loads (e.g. by linking with `-rdynamic`).
+//!
+//! Every generation gets a library of its own, since dynamic loaders cache libraries by path,
+//! and the manifest is replaced atomically, so a host never reads half of it. Rebuilds that
+//! have errors are reported, and the host keeps running the last version that built.
+use std::ffi::OsStr;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use roc_target::Target;
+
+/// The environment variable that tells the host where the manifest is.
+pub const HOT_RELOAD_ENV_VAR: &str = "ROC_HOT_RELOAD";
+
+/// The first line of every manifest, so hosts can reject files in an unknown format.
+pub const MANIFEST_HEADER: &str = "# roc hot reload v1";
+
+/// How often to check whether the program exited or a source file changed.
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Run `executable` with `args`, and rebuild the app into a new shared library whenever a source
+/// file under `watch_root` changes, until the program exits. `rebuild` writes the library to the
+/// given path and returns whether that succeeded. Returns the exit code of the program.
+pub fn run<I, S>(
+    executable: &Path,
+    args: I,
+    watch_root: &Path,
+    target: Target,
+    mut rebuild: impl FnMut(&Path) -> bool,
+) -> io::Result<i32>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let dir = tempfile::tempdir()?;
+    let manifest_path = dir.path().join("manifest");
+
+    write_manifest(&manifest_path, None)?;
+
+    let mut child = Command::new(executable)
+        .args(args)
+        .env(HOT_RELOAD_ENV_VAR, &manifest_path)
+        .spawn()?;
+
+    let mut sources = source_mtimes(watch_root);
+    let mut generation = 0;
+
+    loop {
+        if let Some(status) = child.try_wait()? {
+            // A program killed by a signal has no exit code.
+            return Ok(status.code().unwrap_or(1));
+        }
+
+        thread::sleep(POLL_INTERVAL);
+
+        let new_sources = source_mtimes(watch_root);
+
+        if new_sources == sources {
+            continue;
+        }
+
+        sources = new_sources;
+        generation += 1;
+
+        let lib_path = dir.path().join(format!(
+            "app-{generation}.{}",
+            target.dynamic_library_file_ext()
+        ));
+
+        if rebuild(&lib_path) {
+            write_manifest(&manifest_path, Some((generation, &lib_path)))?;
+            println!("\x1B[32mReloaded\x1B[39m the app (generation {generation}).");
+        } else {
+            println!("The program keeps running the last version of the app that built.");
+        }
+    }
+}
+
+/// Atomically replace the manifest, so the host never sees a partially written one.
+fn write_manifest(manifest_path: &Path, latest: Option<(u64, &Path)>) -> io::Result<()> {
+    let mut contents = format!("{MANIFEST_HEADER}\n");
+
+    if let Some((generation, lib_path)) = latest {
+        contents.push_str(&format!("{generation}\t{}\n", lib_path.display()));
+    }
+
+    let tmp_path = manifest_path.with_extension("tmp");
+
+    std::fs::write(&tmp_path, contents)?;
+    std::fs::rename(&tmp_path, manifest_path)
+}
+
+/// The modification time of every `.roc` file under `root`, sorted by path. Hidden directories
+/// (like `.git`) are skipped.
+fn source_mtimes(root: &Path) -> Vec<(PathBuf, SystemTime)> {
+    let mut mtimes = Vec::new();
+    let mut stack = vec![root.to_path_buf()];
+
+    while let Some(dir) = stack.pop() {
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            // The directory may have been removed since we found it.
+            Err(_) => continue,
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let is_hidden = entry.file_name().to_string_lossy().starts_with('.');
+
+            match entry.file_type() {
+                Ok(file_type) if file_type.is_dir() && !is_hidden => stack.push(path),
+                Ok(file_type) if file_type.is_file() => {
+                    let mtime = entry.metadata().and_then(|metadata| metadata.modified());
+
+                    // Like the directory, the file may be gone already.
+                    if let (Some("roc"), Ok(mtime)) =
+                        (path.extension().and_then(OsStr::to_str), mtime)
+                    {
+                        mtimes.push((path, mtime));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    mtimes.sort();
+
+    mtimes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn manifest_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest_path = dir.path().join("manifest");
+        let lib_path = dir.path().join("app-3.so");
+
+        write_manifest(&manifest_path, None).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&manifest_path).unwrap(),
+            "# roc hot reload v1\n"
+        );
+
+        write_manifest(&manifest_path, Some((3, &lib_path))).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&manifest_path).unwrap(),
+            format!("# roc hot reload v1\n3\t{}\n", lib_path.display())
+        );
+    }
+
+    #[test]
+    fn manifest_is_replaced_atomically() {
+        use std::io::Read;
+
+        let dir = tempfile::tempdir().unwrap();
+        let manifest_path = dir.path().join("manifest");
+        let first_lib = dir.path().join("app-1.so");
+        let second_lib = dir.path().join("app-2.so");
+
+        write_manifest(&manifest_path, Some((1, &first_lib))).unwrap();
+
+        // A host that's in the middle of reading the manifest keeps reading the old one.
+        let mut reader = fs::File::open(&manifest_path).unwrap();
+
+        write_manifest(&manifest_path, Some((2, &second_lib))).unwrap();
+
+        let mut old = String::new();
+        reader.read_to_string(&mut old).unwrap();
+
+        if cfg!(unix) {
+            assert_eq!(
+                old,
+                format!("# roc hot reload v1\n1\t{}\n", first_lib.display())
+            );
+        }
+
+        assert_eq!(
+            fs::read_to_string(&manifest_path).unwrap(),
+            format!("# roc hot reload v1\n2\t{}\n", second_lib.display())
+        );
+
+        let files: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+
+        assert_eq!(files, ["manifest"], "the temporary file was left behind");
+    }
+
+    #[test]
+    fn only_roc_files_outside_hidden_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+
+        for path in [
+            "main.roc",
+            "notes.txt",
+            "lib/Parser.roc",
+            "lib/Parser.roc.orig",
+            ".git/Stale.roc",
+            "lib/.cache/Cached.roc",
+        ] {
+            let path = root.join(path);
+
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+
+        let paths: Vec<PathBuf> = source_mtimes(root)
+            .into_iter()
+            .map(|(path, _)| path.strip_prefix(root).unwrap().to_path_buf())
+            .collect();
+
+        assert_eq!(
+            paths,
+            [PathBuf::from("lib/Parser.roc"), PathBuf::from("main.roc")]
+        );
+    }
+
+    #[test]
+    fn changed_sources_change_mtimes() {
+        let dir = tempfile::tempdir().unwrap();
+        let main = dir.path().join("main.roc");
+
+        fs::write(&main, "app [main] {}").unwrap();
+        let before = source_mtimes(dir.path());
+
+        let file = fs::File::options().write(true).open(&main).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(10))
+            .unwrap();
+
+        assert_ne!(source_mtimes(dir.path()), before);
+    }
+}
@@ -0,0 +1,216 @@
+//! Scaffolding for new Roc projects, for `roc init`.
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The platform new apps use, unless another one is chosen.
+pub const DEFAULT_PLATFORM_URL: &str = "https://github.com/roc-lang/basic-cli/releases/download/0.17.0/lZFLstMUCUvd5bjnnpYromZJXkQUrdhbva4xdBInicE.tar.br";
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ProjectKind {
+    App,
+    Package,
+}
+
+#[derive(Clone, Debug)]
+pub struct InitOptions {
+    pub name: String,
+    pub kind: ProjectKind,
+    /// Only used for apps. The URL includes the hash of the platform's contents, which pins its version.
+    pub platform_url: String,
+}
+
+const APP_MAIN: &str = r#"app [main] { pf: platform "{{platform}}" }
+
+import pf.Stdout
+
+main =
+    Stdout.line! (greeting "{{name}}")
+
+greeting : Str -> Str
+greeting = |name| "Hello from ${name}!"
+
+expect greeting "{{name}}" == "Hello from {{name}}!"
+"#;
+
+const PACKAGE_MAIN: &str = r#"package [Greeting] {}
+"#;
+
+const PACKAGE_GREETING: &str = r#"module [greet]
+
+## Greets someone on behalf of {{name}}.
+greet : Str -> Str
+greet = |name| "Hello, ${name}! Greetings from {{name}}."
+
+expect greet "Roc" == "Hello, Roc! Greetings from {{name}}."
+"#;
+
+const GITIGNORE: &str = r#"# Build output
+/main
+/main.exe
+*.o
+*.obj
+*.tar
+*.tar.br
+*.tar.gz
+
+# `roc docs` output
+/generated-docs
+"#;
+
+/// `roc format` always uses 4 spaces of indentation; this keeps editors consistent with it.
+const EDITORCONFIG: &str = r#"root = true
+
+[*.roc]
+charset = utf-8
+end_of_line = lf
+indent_style = space
+indent_size = 4
+insert_final_newline = true
+trim_trailing_whitespace = true
+"#;
+
+/// The files of a new project, as paths relative to the project's directory.
+pub fn project_files(options: &InitOptions) -> Vec<(&'static str, String)> {
+    let render = |template: &str| {
+        template
+            .replace("{{name}}", &options.name)
+            .replace("{{platform}}", &options.platform_url)
+    };
+
+    let mut files = match options.kind {
+        ProjectKind::App => vec![("main.roc", render(APP_MAIN))],
+        ProjectKind::Package => vec![
+            ("main.roc", render(PACKAGE_MAIN)),
+            ("Greeting.roc", render(PACKAGE_GREETING)),
+        ],
+    };
+
+    files.push((".gitignore", GITIGNORE.to_string()));
+    files.push((".editorconfig", EDITORCONFIG.to_string()));
+
+    files
+}
+
+/// Create a new project in `dir`, which is created if it doesn't exist yet.
+/// Fails without writing anything if any of the project's files exist already.
+///
+/// Returns the paths of the files that were written.
+pub fn init_project(dir: &Path, options: &InitOptions) -> io::Result<Vec<PathBuf>> {
+    if options.name.is_empty()
+        || !options
+            .name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "{:?} is not a valid project name. Project names may only contain letters, digits, `-` and `_`.",
+                options.name
+            ),
+        ));
+    }
+
+    let files = project_files(options);
+
+    for (relative_path, _) in files.iter() {
+        let path = dir.join(relative_path);
+
+        if path.exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} already exists", path.display()),
+            ));
+        }
+    }
+
+    fs::create_dir_all(dir)?;
+
+    files
+        .into_iter()
+        .map(|(relative_path, contents)| {
+            let path = dir.join(relative_path);
+
+            fs::write(&path, contents)?;
+
+            Ok(path)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format_src;
+    use bumpalo::Bump;
+    use roc_fmt::{DbgMigration, MigrationFlags};
+
+    fn options(kind: ProjectKind) -> InitOptions {
+        InitOptions {
+            name: "my-project".to_string(),
+            kind,
+            platform_url: DEFAULT_PLATFORM_URL.to_string(),
+        }
+    }
+
+    #[test]
+    fn templates_are_formatted() {
+        let flags = MigrationFlags {
+            snakify: false,
+            parens_and_commas: false,
+            dbg: DbgMigration::Keep,
+            transforms: &[],
+        };
+
+        for kind in [ProjectKind::App, ProjectKind::Package] {
+            for (path, contents) in project_files(&options(kind)) {
+                if path.ends_with(".roc") {
+                    let arena = Bump::new();
+                    let formatted = format_src(&arena, &contents, flags).unwrap();
+
+                    assert_eq!(formatted, contents, "{path} is not formatted");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn init_app() {
+        let dir = tempfile::tempdir().unwrap();
+        let project_dir = dir.path().join("my-project");
+        let written = init_project(&project_dir, &options(ProjectKind::App)).unwrap();
+
+        assert_eq!(written.len(), 3);
+
+        let main = fs::read_to_string(project_dir.join("main.roc")).unwrap();
+
+        assert!(main.contains(DEFAULT_PLATFORM_URL));
+        assert!(main.contains("Hello from my-project!"));
+        assert!(!main.contains("{{"));
+    }
+
+    #[test]
+    fn init_does_not_overwrite() {
+        let dir = tempfile::tempdir().unwrap();
+
+        fs::write(dir.path().join("Greeting.roc"), "module []").unwrap();
+
+        let result = init_project(dir.path(), &options(ProjectKind::Package));
+
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::AlreadyExists);
+        assert!(!dir.path().join("main.roc").exists());
+    }
+
+    #[test]
+    fn invalid_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut options = options(ProjectKind::App);
+
+        options.name = "../escape".to_string();
+
+        let result = init_project(dir.path(), &options);
+
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidInput);
+    }
+}
@@ -18,13 +18,23 @@ use roc_collections::MutMap;
 use roc_error_macros::{internal_error, user_error};
 use roc_gen_dev::AssemblyBackendMode;
 use roc_gen_llvm::llvm::build::LlvmBackendMode;
-use roc_load::{ExpectMetadata, Threading};
+use roc_load::call_hierarchy::{render_call_tree, CallDirection};
+use roc_load::occurrences::OccurrenceIndex;
+use roc_load::rename::{rename, ConflictKind, RenameError};
+use roc_load::{ComptimeLimits, ExecutionMode, ExpectMetadata, LoadConfig, Threading};
 #[cfg(not(windows))]
 use roc_module::symbol::ModuleId;
 use roc_mono::ir::OptLevel;
+use roc_mono::profile::Profile;
 use roc_packaging::cache::RocCacheDir;
 use roc_packaging::tarball::Compression;
 #[cfg(not(windows))]
+use roc_repl_expect::bench::{bench_results_to_json, render_bench_results, BenchConfig};
+#[cfg(not(windows))]
+use roc_repl_expect::results::{
+    results_to_json, results_to_junit_xml, ExpectFilter, ExpectOutcome, ExpectResult,
+};
+#[cfg(not(windows))]
 use roc_reporting::report::ANSI_STYLE_CODES;
 use roc_target::{Architecture, Target};
 use std::env;
@@ -42,9 +52,13 @@ use strum::IntoEnumIterator;
 use tempfile::TempDir;
 
 mod format;
+pub mod hot_reload;
+pub mod init;
+pub mod lock;
+pub mod publish;
 pub use format::{
-    annotate_file, annotation_edit, annotation_edits, format_files, format_src, AnnotationProblem,
-    FormatMode,
+    annotate_file, annotation_edit, annotation_edits, format_files, format_project, format_src,
+    AnnotationProblem, FormatMode, FormatProblem, FormatProjectError,
 };
 
 pub const CMD_BUILD: &str = "build";
@@ -59,8 +73,28 @@ pub const CMD_FORMAT_ANNOTATE: &str = "annotate";
 pub const CMD_TEST: &str = "test";
 pub const CMD_GLUE: &str = "glue";
 pub const CMD_PREPROCESS_HOST: &str = "preprocess-host";
+pub const CMD_INIT: &str = "init";
+pub const CMD_PUBLISH: &str = "publish";
+pub const CMD_LOCK: &str = "lock";
+pub const CMD_PROFILE: &str = "profile";
+pub const CMD_PROFILE_MERGE: &str = "merge";
+pub const CMD_DEMANGLE: &str = "demangle";
+pub const CMD_LAYOUT: &str = "layout";
+pub const CMD_CALLS: &str = "calls";
+pub const CMD_RENAME: &str = "rename";
 
 pub const FLAG_EMIT_LLVM_IR: &str = "emit-llvm-ir";
+pub const FLAG_EMIT_MONO_IR: &str = "emit-mono-ir";
+pub const FLAG_SPECIALIZATION_REPORT: &str = "specialization-report";
+pub const FLAG_WARN_CLOSURE_CAPTURES: &str = "warn-closure-captures";
+pub const FLAG_BORROW_REPORT: &str = "borrow-report";
+pub const FLAG_DEAD_FIELD_REPORT: &str = "dead-field-report";
+pub const FLAG_PROFILE_USE: &str = "profile-use";
+pub const FLAG_PROFILE_GENERATE: &str = "profile-generate";
+pub const FLAG_COMPTIME_FUEL: &str = "comptime-fuel";
+pub const FLAG_TRACE_ABILITIES: &str = "trace-abilities";
+pub const FLAG_AUDIT_DETERMINISM: &str = "audit-determinism";
+pub const FLAG_EMIT: &str = "emit";
 pub const FLAG_PROFILING: &str = "profiling";
 pub const FLAG_BUNDLE: &str = "bundle";
 pub const FLAG_DEV: &str = "dev";
@@ -83,20 +117,46 @@ pub const FLAG_STDOUT: &str = "stdout";
 pub const FLAG_WASM_STACK_SIZE_KB: &str = "wasm-stack-size-kb";
 pub const FLAG_OUTPUT: &str = "output";
 pub const FLAG_FUZZ: &str = "fuzz";
+pub const FLAG_HOT: &str = "hot";
+pub const FLAG_DETERMINISTIC: &str = "deterministic";
+pub const FLAG_JS_GLUE: &str = "js-glue";
+pub const FLAG_STACK_TRACES: &str = "stack-traces";
+pub const FLAG_PROFILE_COMPILE: &str = "profile-compile";
 pub const FLAG_MAIN: &str = "main";
 pub const ROC_FILE: &str = "ROC_FILE";
 pub const GLUE_DIR: &str = "GLUE_DIR";
 pub const GLUE_SPEC: &str = "GLUE_SPEC";
 pub const DIRECTORY_OR_FILES: &str = "DIRECTORY_OR_FILES";
+pub const PROFILE_FILES: &str = "PROFILE_FILES";
+pub const MANGLED_NAMES: &str = "MANGLED_NAMES";
+pub const ROC_TYPE: &str = "ROC_TYPE";
+pub const FUNCTION_NAME: &str = "FUNCTION_NAME";
+pub const OLD_NAME: &str = "OLD_NAME";
+pub const NEW_NAME: &str = "NEW_NAME";
 pub const ARGS_FOR_APP: &str = "ARGS_FOR_APP";
 pub const FLAG_PP_HOST: &str = "host";
 pub const FLAG_PP_PLATFORM: &str = "platform";
 pub const FLAG_PP_DYLIB: &str = "lib";
 pub const FLAG_MIGRATE: &str = "migrate";
+pub const FLAG_DBG: &str = "dbg";
 pub const FLAG_DOCS_ROOT: &str = "root-dir";
+pub const FLAG_DOCS_EXAMPLES: &str = "examples";
+pub const FLAG_FILTER: &str = "filter";
+pub const FLAG_FILTER_MODULE: &str = "filter-module";
+pub const FLAG_REPORT_FORMAT: &str = "report-format";
+pub const FLAG_COVERAGE: &str = "coverage";
+pub const FLAG_BENCH: &str = "bench";
+pub const FLAG_PACKAGE: &str = "package";
+pub const FLAG_PLATFORM: &str = "platform";
+pub const FLAG_INDEX: &str = "index";
+pub const FLAG_REGISTRY: &str = "registry";
+pub const FLAG_CALLERS: &str = "callers";
+pub const FLAG_DEPTH: &str = "depth";
+pub const PROJECT_DIR: &str = "PROJECT_DIR";
 
 pub const VERSION: &str = env!("ROC_VERSION");
 const DEFAULT_GENERATED_DOCS_DIR: &str = "generated-docs";
+const DEFAULT_PUBLISH_DIR: &str = "publish";
 
 pub fn build_app() -> Command {
     let flag_optimize = Arg::new(FLAG_OPTIMIZE)
@@ -129,6 +189,56 @@ pub fn build_app() -> Command {
         .action(ArgAction::SetTrue)
         .required(false);
 
+    let flag_emit_mono_ir = Arg::new(FLAG_EMIT_MONO_IR)
+        .long(FLAG_EMIT_MONO_IR)
+        .help("Emit a `.mono` file containing the specialized IR of the program")
+        .action(ArgAction::SetTrue)
+        .required(false);
+
+    let flag_specialization_report = Arg::new(FLAG_SPECIALIZATION_REPORT)
+        .long(FLAG_SPECIALIZATION_REPORT)
+        .help("Emit a `.specializations` file listing the layouts each function was specialized for, and how much code each specialization generated")
+        .action(ArgAction::SetTrue)
+        .required(false);
+
+    let flag_warn_closure_captures = Arg::new(FLAG_WARN_CLOSURE_CAPTURES)
+        .long(FLAG_WARN_CLOSURE_CAPTURES)
+        .help("Warn about closures whose captures are large, or have to be allocated on the heap")
+        .action(ArgAction::SetTrue)
+        .required(false);
+
+    let flag_borrow_report = Arg::new(FLAG_BORROW_REPORT)
+        .long(FLAG_BORROW_REPORT)
+        .help("Emit a `.borrows` file listing which parameters of each function are borrowed or owned, and why\n(Set ROC_BORROW_INFERENCE=owned to make every parameter owned.)")
+        .action(ArgAction::SetTrue)
+        .required(false);
+
+    let flag_dead_field_report = Arg::new(FLAG_DEAD_FIELD_REPORT)
+        .long(FLAG_DEAD_FIELD_REPORT)
+        .help("Emit a `.dead-fields` file listing the record fields that were removed because they're never read")
+        .action(ArgAction::SetTrue)
+        .required(false);
+
+    let flag_profile_use = Arg::new(FLAG_PROFILE_USE)
+        .long(FLAG_PROFILE_USE)
+        .help("Use the call counts in this profile (from `roc test --profile-generate`) to decide which functions to inline")
+        .value_parser(value_parser!(PathBuf))
+        .required(false);
+
+    let flag_comptime_fuel = Arg::new(FLAG_COMPTIME_FUEL)
+        .long(FLAG_COMPTIME_FUEL)
+        .help("How many steps evaluating each `# @comptime` def or expect may take before it's reported as not finishing\n(The default is 10000000.)")
+        .value_parser(value_parser!(usize))
+        .required(false);
+
+    let flag_emit = Arg::new(FLAG_EMIT)
+        .long(FLAG_EMIT)
+        .help("Emit a debugging artifact next to the source file\n(match-tree: a `.match-trees.dot` file with the decision tree that each `when` is compiled to, which Graphviz can draw, e.g. with `dot -Tsvg`;\nsize-report: a `.sizes` file with how many bytes each app and platform symbol adds to the executable, and which unused app symbols the surgical linker removed.)")
+        .value_parser(["match-tree", "size-report"])
+        .action(ArgAction::Append)
+        .require_equals(true)
+        .required(false);
+
     let flag_profiling = Arg::new(FLAG_PROFILING)
         .long(FLAG_PROFILING)
         .help("Keep debug info in the final generated program even in optimized builds")
@@ -171,6 +281,32 @@ pub fn build_app() -> Command {
         .action(ArgAction::SetTrue)
         .required(false);
 
+    let flag_deterministic = Arg::new(FLAG_DETERMINISTIC)
+        .long(FLAG_DETERMINISTIC)
+        .help("Produce byte-identical binaries for identical source, e.g. to verify reproducible builds\n(Symbols are emitted in a stable order, and linkers are asked to leave out timestamps and temporary paths. Always uses the legacy linker, and isn't supported by the dev backends or for wasm.)")
+        .action(ArgAction::SetTrue)
+        .required(false);
+
+    let flag_js_glue = Arg::new(FLAG_JS_GLUE)
+        .long(FLAG_JS_GLUE)
+        .help("When building for wasm32, also write a JavaScript file that loads the wasm module and exposes its entry points\n(The file is written next to the .wasm file, with a .js extension.)")
+        .action(ArgAction::SetTrue)
+        .required(false);
+
+    let flag_stack_traces = Arg::new(FLAG_STACK_TRACES)
+        .long(FLAG_STACK_TRACES)
+        .help("Also write a frame table, which hosts can use to print a Roc stack trace when the app crashes\n(The file is written next to the binary, with .roc-frames appended to its name.)")
+        .action(ArgAction::SetTrue)
+        .required(false);
+
+    let flag_profile_compile = Arg::new(FLAG_PROFILE_COMPILE)
+        .long(FLAG_PROFILE_COMPILE)
+        .help("Print how long each compilation phase took, how much memory it used, and which modules took longest\n(Give a path, as in --profile-compile=profile.json, to also write the full report as JSON.)")
+        .value_parser(value_parser!(PathBuf))
+        .num_args(0..=1)
+        .require_equals(true)
+        .required(false);
+
     let flag_main = Arg::new(FLAG_MAIN)
         .long(FLAG_MAIN)
         .help("The .roc file of the main app/package module to resolve dependencies from")
@@ -215,12 +351,24 @@ pub fn build_app() -> Command {
             .arg(flag_opt_size.clone())
             .arg(flag_dev.clone())
             .arg(flag_emit_llvm_ir.clone())
+            .arg(flag_emit_mono_ir.clone())
+            .arg(flag_specialization_report.clone())
+            .arg(flag_warn_closure_captures.clone())
+            .arg(flag_borrow_report.clone())
+            .arg(flag_dead_field_report.clone())
+            .arg(flag_profile_use.clone())
+            .arg(flag_comptime_fuel.clone())
+            .arg(flag_emit.clone())
             .arg(flag_profiling.clone())
             .arg(flag_time.clone())
             .arg(flag_linker.clone())
             .arg(flag_build_host.clone())
             .arg(flag_suppress_build_host_warning.clone())
             .arg(flag_fuzz.clone())
+            .arg(flag_deterministic)
+            .arg(flag_js_glue)
+            .arg(flag_stack_traces)
+            .arg(flag_profile_compile)
             .arg(flag_wasm_stack_size_kb)
             .arg(
                 Arg::new(FLAG_TARGET)
@@ -275,6 +423,12 @@ pub fn build_app() -> Command {
             .arg(flag_opt_size.clone())
             .arg(flag_dev.clone())
             .arg(flag_emit_llvm_ir.clone())
+            .arg(flag_emit_mono_ir.clone())
+            .arg(flag_specialization_report.clone())
+            .arg(flag_warn_closure_captures.clone())
+            .arg(flag_borrow_report.clone())
+            .arg(flag_dead_field_report.clone())
+            .arg(flag_emit.clone())
             .arg(flag_profiling.clone())
             .arg(flag_time.clone())
             .arg(flag_linker.clone())
@@ -288,6 +442,49 @@ pub fn build_app() -> Command {
                     .action(ArgAction::SetTrue)
                     .required(false)
             )
+            .arg(
+                Arg::new(FLAG_FILTER)
+                    .long(FLAG_FILTER)
+                    .help("Only run the `expect`s whose first line contains this text")
+                    .value_parser(value_parser!(String))
+                    .required(false)
+            )
+            .arg(
+                Arg::new(FLAG_FILTER_MODULE)
+                    .long(FLAG_FILTER_MODULE)
+                    .help("Only run the `expect`s in the module with this name")
+                    .value_parser(value_parser!(String))
+                    .required(false)
+            )
+            .arg(
+                Arg::new(FLAG_REPORT_FORMAT)
+                    .long(FLAG_REPORT_FORMAT)
+                    .help("Print the test results in a machine-readable format, for use in CI")
+                    .value_parser(["json", "junit"])
+                    .required(false)
+            )
+            .arg(
+                Arg::new(FLAG_BENCH)
+                    .long(FLAG_BENCH)
+                    .help("Only run the `expect`s marked with a `# bench` comment, repeatedly, and report how long they take. Combine with --optimize for realistic numbers.")
+                    .action(ArgAction::SetTrue)
+                    .required(false)
+            )
+            .arg(
+                Arg::new(FLAG_COVERAGE)
+                    .long(FLAG_COVERAGE)
+                    .help("Record which defs and branches the expects executed, and write an lcov report to the given file")
+                    .value_parser(value_parser!(PathBuf))
+                    .required(false)
+            )
+            .arg(flag_comptime_fuel.clone())
+            .arg(
+                Arg::new(FLAG_PROFILE_GENERATE)
+                    .long(FLAG_PROFILE_GENERATE)
+                    .help("Count how often the expects called each function, and write a profile for `--profile-use` to the given file")
+                    .value_parser(value_parser!(PathBuf))
+                    .required(false)
+            )
             .arg(
                 Arg::new(ROC_FILE)
                     .help("The .roc file to test")
@@ -317,11 +514,26 @@ pub fn build_app() -> Command {
         )
         .subcommand(Command::new(CMD_RUN)
             .about("Run a .roc file even if it has build errors")
+            .arg(
+                Arg::new(FLAG_HOT)
+                    .long(FLAG_HOT)
+                    .help("Keep the program running, and reload the app into it whenever a .roc file changes\n(The platform's host has to support hot reloading; see `roc_cli::hot_reload`.)")
+                    .action(ArgAction::SetTrue)
+                    .required(false)
+            )
             .arg(flag_optimize.clone())
             .arg(flag_max_threads.clone())
             .arg(flag_opt_size.clone())
             .arg(flag_dev.clone())
             .arg(flag_emit_llvm_ir.clone())
+            .arg(flag_emit_mono_ir.clone())
+            .arg(flag_specialization_report.clone())
+            .arg(flag_warn_closure_captures.clone())
+            .arg(flag_borrow_report.clone())
+            .arg(flag_dead_field_report.clone())
+            .arg(flag_profile_use.clone())
+            .arg(flag_comptime_fuel.clone())
+            .arg(flag_emit.clone())
             .arg(flag_profiling.clone())
             .arg(flag_time.clone())
             .arg(flag_linker.clone())
@@ -338,6 +550,14 @@ pub fn build_app() -> Command {
             .arg(flag_opt_size.clone())
             .arg(flag_dev.clone())
             .arg(flag_emit_llvm_ir.clone())
+            .arg(flag_emit_mono_ir.clone())
+            .arg(flag_specialization_report.clone())
+            .arg(flag_warn_closure_captures.clone())
+            .arg(flag_borrow_report.clone())
+            .arg(flag_dead_field_report.clone())
+            .arg(flag_profile_use.clone())
+            .arg(flag_comptime_fuel.clone())
+            .arg(flag_emit.clone())
             .arg(flag_profiling.clone())
             .arg(flag_time.clone())
             .arg(flag_linker.clone())
@@ -369,6 +589,14 @@ pub fn build_app() -> Command {
                     .action(ArgAction::SetTrue)
                     .required(false),
             )
+            .arg(
+                Arg::new(FLAG_DBG)
+                    .long(FLAG_DBG)
+                    .help("What to do with `dbg`s while formatting: `lift` turns `x = dbg expr` into `x = expr` followed by `dbg x`, and `remove` deletes them all. Prints how many were changed in each file.")
+                    .value_parser(["keep", "lift", "remove"])
+                    .default_value("keep")
+                    .required(false),
+            )
             .arg(
                 Arg::new(FLAG_STDIN)
                     .long(FLAG_STDIN)
@@ -395,6 +623,174 @@ pub fn build_app() -> Command {
                 )
             )
         )
+        .subcommand(Command::new(CMD_PROFILE)
+            .about("Work with the profiles written by `roc test --profile-generate`")
+            .subcommand_required(true)
+            .subcommand(Command::new(CMD_PROFILE_MERGE)
+                .about("Combine several profiles into one by adding up their call counts")
+                .arg(
+                    Arg::new(PROFILE_FILES)
+                        .help("The profiles to merge")
+                        .value_parser(value_parser!(PathBuf))
+                        .num_args(1..)
+                        .required(true)
+                )
+                .arg(
+                    Arg::new(FLAG_OUTPUT)
+                        .long(FLAG_OUTPUT)
+                        .help("The file to write the merged profile to")
+                        .value_parser(value_parser!(PathBuf))
+                        .required(true)
+                )
+            )
+        )
+        .subcommand(Command::new(CMD_DEMANGLE)
+            .about("Turn the symbol names in generated code back into the Roc names they came from")
+            .arg(
+                Arg::new(MANGLED_NAMES)
+                    .help("The names to demangle. If there are none, every name in stdin is demangled, e.g. `perf report | roc demangle`")
+                    .num_args(0..)
+                    .required(false)
+            )
+        )
+        .subcommand(Command::new(CMD_LAYOUT)
+            .about("Show how values of a type are laid out in memory: their size and alignment, the offsets of their fields, and where tag unions keep their tag ids")
+            .arg(
+                Arg::new(ROC_TYPE)
+                    .help("The type, written like in a type annotation in the .roc file, e.g. `{ name : Str, age : U8 }` or the name of a type the file defines")
+                    .required(true)
+            )
+            .arg(
+                Arg::new(ROC_FILE)
+                    .help("The .roc file the type is in")
+                    .value_parser(value_parser!(PathBuf))
+                    .required(false)
+                    .default_value(DEFAULT_ROC_FILENAME)
+            )
+            .arg(
+                Arg::new(FLAG_TARGET)
+                    .long(FLAG_TARGET)
+                    .help("Choose a different target")
+                    .default_value(Into::<&'static str>::into(Target::default()))
+                    .value_parser(build_target_values_parser.clone())
+                    .required(false),
+            )
+        )
+        .subcommand(Command::new(CMD_CALLS)
+            .about("Show the functions a function calls, and the functions those call, as a tree")
+            .arg(
+                Arg::new(FUNCTION_NAME)
+                    .help("The top-level def to start from, e.g. `main`, or `Parser.parse` for one in another module")
+                    .required(true)
+            )
+            .arg(
+                Arg::new(ROC_FILE)
+                    .help("The .roc file to load")
+                    .value_parser(value_parser!(PathBuf))
+                    .required(false)
+                    .default_value(DEFAULT_ROC_FILENAME)
+            )
+            .arg(
+                Arg::new(FLAG_CALLERS)
+                    .long(FLAG_CALLERS)
+                    .help("Show the functions that call the function instead, and the functions that call those")
+                    .action(ArgAction::SetTrue)
+                    .required(false)
+            )
+            .arg(
+                Arg::new(FLAG_DEPTH)
+                    .long(FLAG_DEPTH)
+                    .help("How many calls away from the function to go")
+                    .value_parser(value_parser!(usize))
+                    .default_value("3")
+                    .required(false)
+            )
+        )
+        .subcommand(Command::new(CMD_RENAME)
+            .about("Rename a top-level def everywhere it's used, in every module that uses it")
+            .arg(
+                Arg::new(OLD_NAME)
+                    .help("The top-level def to rename, e.g. `parse`, or `Parser.parse` for one in another module")
+                    .required(true)
+            )
+            .arg(
+                Arg::new(NEW_NAME)
+                    .help("The def's new name")
+                    .required(true)
+            )
+            .arg(
+                Arg::new(ROC_FILE)
+                    .help("The .roc file to load")
+                    .value_parser(value_parser!(PathBuf))
+                    .required(false)
+                    .default_value(DEFAULT_ROC_FILENAME)
+            )
+        )
+        .subcommand(Command::new(CMD_INIT)
+            .about("Create a new Roc app or package")
+            .arg(
+                Arg::new(PROJECT_DIR)
+                    .help("The directory to create the project in. Its name is used as the project's name.")
+                    .value_parser(value_parser!(PathBuf))
+                    .required(true)
+            )
+            .arg(
+                Arg::new(FLAG_PACKAGE)
+                    .long(FLAG_PACKAGE)
+                    .help("Create a package instead of an app")
+                    .action(ArgAction::SetTrue)
+                    .required(false)
+            )
+            .arg(
+                Arg::new(FLAG_PLATFORM)
+                    .long(FLAG_PLATFORM)
+                    .help("The URL of the platform the app should use")
+                    .value_parser(value_parser!(String))
+                    .conflicts_with(FLAG_PACKAGE)
+                    .required(false)
+                    .default_value(init::DEFAULT_PLATFORM_URL)
+            )
+        )
+        .subcommand(Command::new(CMD_PUBLISH)
+            .about("Check a package and bundle it, with a metadata file, for publishing")
+            .arg(flag_max_threads.clone())
+            .arg(Arg::new(FLAG_OUTPUT)
+                .long(FLAG_OUTPUT)
+                .help("The directory to write the bundle and its metadata to")
+                .value_parser(value_parser!(PathBuf))
+                .required(false)
+                .default_value(DEFAULT_PUBLISH_DIR),
+            )
+            .arg(Arg::new(ROC_FILE)
+                .help("The package's main .roc file")
+                .value_parser(value_parser!(PathBuf))
+                .required(false)
+                .default_value(DEFAULT_ROC_FILENAME),
+            )
+        )
+        .subcommand(Command::new(CMD_LOCK)
+            .about("Lock the contents of every package an app or package depends on, in a roc.lock file next to it")
+            .arg(Arg::new(FLAG_INDEX)
+                .long(FLAG_INDEX)
+                .help("A directory of the metadata files `roc publish` wrote for every package in the registry, to resolve the package manifest's dependencies with")
+                .value_parser(value_parser!(PathBuf))
+                .requires(FLAG_REGISTRY)
+                .required(false),
+            )
+            .arg(Arg::new(FLAG_REGISTRY)
+                .long(FLAG_REGISTRY)
+                .help("The URL the registry's bundles are downloaded from")
+                .value_parser(value_parser!(String))
+                .requires(FLAG_INDEX)
+                .required(false),
+            )
+            .arg(Arg::new(ROC_FILE)
+                .help("The app or package's main .roc file")
+                .value_parser(value_parser!(PathBuf))
+                .required(false)
+                .default_value(DEFAULT_ROC_FILENAME),
+            )
+        )
         .subcommand(Command::new(CMD_VERSION)
             .about(concatcp!("Print the Roc compiler’s version, which is currently ", VERSION)))
         .subcommand(Command::new(CMD_CHECK)
@@ -402,6 +798,20 @@ pub fn build_app() -> Command {
             .arg(flag_main.clone())
             .arg(flag_time.clone())
             .arg(flag_max_threads.clone())
+            .arg(
+                Arg::new(FLAG_TRACE_ABILITIES)
+                    .long(FLAG_TRACE_ABILITIES)
+                    .help("Print why `implements` constraints weren't met, and how uses of ability members were resolved, as JSON lines on stderr")
+                    .action(ArgAction::SetTrue)
+                    .required(false)
+            )
+            .arg(
+                Arg::new(FLAG_AUDIT_DETERMINISM)
+                    .long(FLAG_AUDIT_DETERMINISM)
+                    .help("Check the code twice, on one thread and on many, and report the first difference in the interned ids or problems")
+                    .action(ArgAction::SetTrue)
+                    .required(false)
+            )
             .arg(
                 Arg::new(ROC_FILE)
                     .help("The .roc file to check")
@@ -427,6 +837,13 @@ pub fn build_app() -> Command {
                     .default_value(DEFAULT_ROC_FILENAME),
                 )
                 .arg(flag_docs_root_dir)
+                .arg(Arg::new(FLAG_DOCS_EXAMPLES)
+                    .long(FLAG_DOCS_EXAMPLES)
+                    .help("Show up to this many top-level `expect`s that use an exposed def as examples in its documentation.")
+                    .value_parser(value_parser!(usize))
+                    .required(false)
+                    .default_value("0"),
+                )
         )
         .subcommand(Command::new(CMD_GLUE)
             .about("Generate glue code between a platform's Roc API and its host language")
@@ -493,6 +910,14 @@ pub fn build_app() -> Command {
         .arg(flag_opt_size)
         .arg(flag_dev)
         .arg(flag_emit_llvm_ir)
+        .arg(flag_emit_mono_ir)
+        .arg(flag_specialization_report)
+        .arg(flag_warn_closure_captures)
+        .arg(flag_borrow_report)
+        .arg(flag_dead_field_report)
+        .arg(flag_profile_use)
+        .arg(flag_comptime_fuel)
+        .arg(flag_emit)
         .arg(flag_profiling)
         .arg(flag_time)
         .arg(flag_linker)
@@ -510,6 +935,195 @@ pub enum BuildConfig {
     BuildAndRunIfNoErrors,
 }
 
+fn read_profile(path: &Path) -> Profile {
+    let src = std::fs::read_to_string(path)
+        .unwrap_or_else(|err| user_error!("Could not read the profile {}: {err}", path.display()));
+
+    Profile::parse(&src)
+        .unwrap_or_else(|err| user_error!("The profile {} is invalid, {err}", path.display()))
+}
+
+/// `roc profile merge`: add up the call counts of several profiles.
+pub fn merge_profiles(paths: &[&PathBuf], output: &Path) -> io::Result<i32> {
+    let mut merged = Profile::default();
+
+    for path in paths {
+        merged.merge(&read_profile(path));
+    }
+
+    std::fs::write(output, merged.render())?;
+
+    Ok(0)
+}
+
+/// `roc calls`: print the call hierarchy of a top-level def.
+pub fn print_call_hierarchy(
+    roc_file_path: &Path,
+    function_name: &str,
+    direction: CallDirection,
+    max_depth: usize,
+    roc_cache_dir: RocCacheDir<'_>,
+) -> io::Result<i32> {
+    let arena = Bump::new();
+    let load_config = LoadConfig {
+        exec_mode: ExecutionMode::Check,
+        ..standard_load_config(
+            Target::default(),
+            BuildOrdering::AlwaysBuild,
+            Threading::AllAvailable,
+        )
+    };
+
+    let loaded = match roc_load::load_and_typecheck(
+        &arena,
+        roc_file_path.to_path_buf(),
+        None,
+        roc_cache_dir,
+        load_config,
+    ) {
+        Ok(loaded) => loaded,
+        Err(problem) => return handle_loading_problem(problem),
+    };
+
+    let Some(symbol) = loaded.top_level_def_named(function_name) else {
+        eprintln!(
+            "There is no top-level def named `{function_name}` in {} or the modules it imports.",
+            roc_file_path.display()
+        );
+
+        return Ok(1);
+    };
+
+    let tree = loaded.call_graph().tree(symbol, direction, max_depth);
+
+    print!(
+        "{}",
+        render_call_tree(
+            &tree,
+            direction,
+            loaded.module_id,
+            &loaded.interns,
+            &loaded.sources
+        )
+    );
+
+    Ok(0)
+}
+
+pub fn rename_def(
+    roc_file_path: &Path,
+    old_name: &str,
+    new_name: &str,
+    roc_cache_dir: RocCacheDir<'_>,
+) -> io::Result<i32> {
+    let arena = Bump::new();
+    let load_config = LoadConfig {
+        exec_mode: ExecutionMode::Check,
+        ..standard_load_config(
+            Target::default(),
+            BuildOrdering::AlwaysBuild,
+            Threading::AllAvailable,
+        )
+    };
+
+    let loaded = match roc_load::load_and_typecheck(
+        &arena,
+        roc_file_path.to_path_buf(),
+        None,
+        roc_cache_dir,
+        load_config,
+    ) {
+        Ok(loaded) => loaded,
+        Err(problem) => return handle_loading_problem(problem),
+    };
+
+    let Some(symbol) = loaded.top_level_def_named(old_name) else {
+        eprintln!(
+            "There is no top-level def named `{old_name}` in {} or the modules it imports.",
+            roc_file_path.display()
+        );
+
+        return Ok(1);
+    };
+
+    let index = OccurrenceIndex::new(&loaded);
+
+    let files = match rename(&index, symbol, new_name) {
+        Ok(files) => files,
+        Err(RenameError::InvalidName { name }) => {
+            eprintln!("`{name}` can't be the new name of `{old_name}`. A def's name has to start with a lowercase letter, and end with `!` only if the def is effectful.");
+
+            return Ok(1);
+        }
+        Err(RenameError::NotRenamable { .. }) => {
+            eprintln!("`{old_name}` can't be renamed, because it's an ability member or the platform uses it.");
+
+            return Ok(1);
+        }
+        Err(RenameError::Conflicts(conflicts)) => {
+            eprintln!("Renaming `{old_name}` to `{new_name}` would break these:\n");
+
+            for conflict in conflicts {
+                let location = match (
+                    index.path(conflict.module_id),
+                    index.line_info(conflict.module_id),
+                ) {
+                    (Some(path), Some(line_info)) => {
+                        let position = line_info.convert_pos(conflict.region.start());
+
+                        format!(
+                            "{}:{}:{}",
+                            path.display(),
+                            position.line + 1,
+                            position.column + 1
+                        )
+                    }
+                    _ => String::from("?"),
+                };
+                let reason = match conflict.kind {
+                    ConflictKind::AlreadyDefined => "there's already a def with that name",
+                    ConflictKind::Shadowing => "a value with that name would shadow it, or be shadowed by it",
+                    ConflictKind::ImportedName => "a value with that name is already imported",
+                    ConflictKind::ExposedNameCollision => {
+                        "it's imported with `exposing`, and there's already a def with that name"
+                    }
+                    ConflictKind::OptionalFieldShorthand => {
+                        "it's destructured from an optional record field, which has to have the same name"
+                    }
+                };
+
+                eprintln!("    {location}: {reason}");
+            }
+
+            return Ok(1);
+        }
+    };
+
+    for file in files.iter() {
+        let src = std::fs::read_to_string(&file.path)?;
+
+        std::fs::write(&file.path, file.apply(&src))?;
+
+        println!(
+            "Renamed {} occurrence(s) in {}",
+            file.edits.len(),
+            file.path.display()
+        );
+    }
+
+    Ok(0)
+}
+
+fn comptime_limits_from_flags(matches: &ArgMatches) -> ComptimeLimits {
+    let mut limits = ComptimeLimits::default();
+
+    if let Ok(Some(fuel)) = matches.try_get_one::<usize>(FLAG_COMPTIME_FUEL) {
+        limits.fuel = *fuel;
+    }
+
+    limits
+}
+
 fn opt_level_from_flags(matches: &ArgMatches) -> OptLevel {
     match (
         matches.get_flag(FLAG_OPTIMIZE),
@@ -540,7 +1154,8 @@ struct ModuleTestResults {
 #[cfg(not(windows))]
 pub fn test(matches: &ArgMatches, target: Target) -> io::Result<i32> {
     use roc_build::program::report_problems_monomorphized;
-    use roc_load::{ExecutionMode, FunctionKind, LoadConfig, LoadMonomorphizedError};
+    use roc_load::{FunctionKind, LoadMonomorphizedError};
+    use roc_mono::coverage::CoverageMap;
     use roc_packaging::cache;
 
     let start_time = Instant::now();
@@ -586,6 +1201,37 @@ pub fn test(matches: &ArgMatches, target: Target) -> io::Result<i32> {
         flatten_paths
     };
 
+    let filter = ExpectFilter {
+        name: matches.get_one::<String>(FLAG_FILTER).cloned(),
+        module: matches.get_one::<String>(FLAG_FILTER_MODULE).cloned(),
+    };
+    let report_format = matches.get_one::<String>(FLAG_REPORT_FORMAT);
+    let bench = matches.get_flag(FLAG_BENCH);
+
+    if bench && report_format.map(String::as_str) == Some("junit") {
+        user_error!("Benchmark results can only be reported as json, not junit.");
+    }
+
+    // Filtering and machine-readable reports both need a result for each individual expect.
+    let structured_results =
+        filter.name.is_some() || filter.module.is_some() || report_format.is_some();
+
+    // Reports for CI shouldn't have ANSI color codes in them.
+    let render_target = if report_format.is_some() {
+        roc_reporting::report::RenderTarget::Generic
+    } else {
+        roc_reporting::report::RenderTarget::ColorTerminal
+    };
+
+    let coverage_path = matches.get_one::<PathBuf>(FLAG_COVERAGE);
+    let mut lcov = String::new();
+
+    // Profiles are made from the same counters as coverage.
+    let profile_path = matches.get_one::<PathBuf>(FLAG_PROFILE_GENERATE);
+    let mut profile = Profile::default();
+
+    let mut all_results = Vec::new();
+    let mut all_bench_results = Vec::new();
     let mut all_files_total_failed_count = 0;
     let mut all_files_total_passed_count = 0;
 
@@ -604,6 +1250,11 @@ pub fn test(matches: &ArgMatches, target: Target) -> io::Result<i32> {
             palette: roc_reporting::report::DEFAULT_PALETTE,
             threading,
             exec_mode: ExecutionMode::Test,
+            emit_match_trees: false,
+            trace_abilities: false,
+            profile: None,
+            doc_examples: 0,
+            comptime_limits: comptime_limits_from_flags(matches),
         };
         let load_result = roc_load::load_and_monomorphize(
             arena,
@@ -629,6 +1280,11 @@ pub fn test(matches: &ArgMatches, target: Target) -> io::Result<i32> {
         let interns = loaded.interns.clone();
         let sources = loaded.sources.clone();
 
+        let coverage: Option<&CoverageMap> = (coverage_path.is_some() || profile_path.is_some())
+            .then(|| &*arena.alloc(CoverageMap::new(loaded.procedures.values())));
+        let def_regions = std::mem::take(&mut loaded.def_regions);
+        let branch_regions = std::mem::take(&mut loaded.branch_regions);
+
         let (dyn_lib, expects_by_module, layout_interner) =
             roc_repl_expect::run::expect_mono_module_to_dylib(
                 arena,
@@ -636,6 +1292,7 @@ pub fn test(matches: &ArgMatches, target: Target) -> io::Result<i32> {
                 loaded,
                 opt_level,
                 LlvmBackendMode::CliTest,
+                coverage,
             )
             .unwrap();
 
@@ -668,17 +1325,74 @@ pub fn test(matches: &ArgMatches, target: Target) -> io::Result<i32> {
         for (module_id, expects) in expects_by_module.into_iter() {
             let test_start_time = Instant::now();
 
-            let (failed_count, passed_count) = roc_repl_expect::run::run_toplevel_expects(
-                &mut writer,
-                roc_reporting::report::RenderTarget::ColorTerminal,
-                arena,
-                interns,
-                &global_layout_interner,
-                &dyn_lib,
-                &mut expectations,
-                expects,
-            )
-            .unwrap();
+            let (failed_count, passed_count) = if bench {
+                let results = roc_repl_expect::run::run_toplevel_benchmarks(
+                    render_target,
+                    arena,
+                    interns,
+                    &global_layout_interner,
+                    &dyn_lib,
+                    &mut expectations,
+                    expects,
+                    &filter,
+                    &BenchConfig::default(),
+                )
+                .unwrap();
+
+                let passed_count = results
+                    .iter()
+                    .filter(|result| result.outcome == ExpectOutcome::Passed)
+                    .count();
+                let failed_count = results.len() - passed_count;
+
+                if report_format.is_none() {
+                    for failure in results.iter().filter_map(|result| result.failure.as_ref()) {
+                        println!("{failure}\n");
+                    }
+                }
+
+                all_bench_results.extend(results);
+
+                (failed_count, passed_count)
+            } else if structured_results {
+                let results = roc_repl_expect::run::run_toplevel_expects_with_results(
+                    render_target,
+                    arena,
+                    interns,
+                    &global_layout_interner,
+                    &dyn_lib,
+                    &mut expectations,
+                    expects,
+                    &filter,
+                )
+                .unwrap();
+
+                let passed_count = results
+                    .iter()
+                    .filter(|result| result.outcome == ExpectOutcome::Passed)
+                    .count();
+                let failed_count = results.len() - passed_count;
+
+                if report_format.is_none() {
+                    print_expect_results(&results);
+                }
+
+                all_results.extend(results);
+
+                (failed_count, passed_count)
+            } else {
+                roc_repl_expect::run::run_toplevel_expects(
+                    &mut writer,
+                    render_target,
+                    arena,
+                    interns,
+                    &global_layout_interner,
+                    &dyn_lib,
+                    &mut expectations,
+                    expects,
+                )
+                .unwrap()
+            };
 
             let tests_duration = test_start_time.elapsed();
 
@@ -693,12 +1407,28 @@ pub fn test(matches: &ArgMatches, target: Target) -> io::Result<i32> {
             total_passed_count += passed_count;
         }
 
+        if let Some(coverage) = coverage {
+            let counts = roc_repl_expect::run::coverage_counts(&dyn_lib, coverage);
+
+            lcov.push_str(&coverage.to_lcov(
+                &counts,
+                &def_regions,
+                &branch_regions,
+                &sources,
+                interns,
+            ));
+            profile.merge(&coverage.to_profile(&counts, interns));
+        }
+
         let total_duration = start_time.elapsed();
         all_files_total_failed_count += total_failed_count;
         all_files_total_passed_count += total_passed_count;
         if total_failed_count == 0 && total_passed_count == 0 {
             // Only report no expectations found once.
             continue;
+        } else if report_format.is_some() {
+            // The machine-readable report gets printed once all the files have been tested.
+            continue;
         } else if matches.get_flag(FLAG_VERBOSE) {
             println!("Compiled in {} ms.", compilation_duration.as_millis());
             for module_test_results in results_by_module {
@@ -710,9 +1440,31 @@ pub fn test(matches: &ArgMatches, target: Target) -> io::Result<i32> {
             println!("{test_summary_str}");
         }
     }
+    match report_format.map(String::as_str) {
+        Some("json") if bench => println!("{}", bench_results_to_json(&all_bench_results)),
+        None if bench && !all_bench_results.is_empty() => {
+            print!("\n{}", render_bench_results(&all_bench_results))
+        }
+        Some("json") => println!("{}", results_to_json(&all_results)),
+        Some("junit") => print!("{}", results_to_junit_xml(&all_results)),
+        _ => {}
+    }
+
+    if let Some(coverage_path) = coverage_path {
+        std::fs::write(coverage_path, lcov)?;
+    }
+
+    if let Some(profile_path) = profile_path {
+        std::fs::write(profile_path, profile.render())?;
+    }
+
     if all_files_total_failed_count == 0 && all_files_total_passed_count == 0 {
         // TODO print this in a more nicely formatted way!
-        println!("No expectations were found.");
+        if bench {
+            println!("No benchmarks were found. Benchmarks are top-level `expect`s with a `# bench` comment on the line above them.");
+        } else {
+            println!("No expectations were found.");
+        }
 
         // If no tests ran, treat that as an error. This is perhaps
         // briefly annoying at the very beginning of a project when
@@ -770,6 +1522,20 @@ fn print_test_results(
     println!("\n{module_name}:\n    {test_summary_str}",);
 }
 
+/// Print the failures and `dbg` output of individually-run expects.
+#[cfg(not(windows))]
+fn print_expect_results(results: &[ExpectResult]) {
+    for result in results {
+        if !result.dbg_output.is_empty() {
+            eprint!("{}", result.dbg_output);
+        }
+
+        if let Some(failure) = &result.failure {
+            println!("{failure}\n");
+        }
+    }
+}
+
 #[cfg(not(windows))]
 fn test_summary(failed_count: usize, passed_count: usize, tests_duration: Duration) -> String {
     let failed_color = if failed_count == 0 {
@@ -928,7 +1694,21 @@ pub fn build(
 
     let opt_level = opt_level_from_flags(matches);
 
+    // Only `roc run` has this flag
+    let hot_reload = matches
+        .try_get_one::<bool>(FLAG_HOT)
+        .ok()
+        .flatten()
+        .copied()
+        .unwrap_or(false);
+    if hot_reload && matches!(target.architecture(), Architecture::Wasm32) {
+        user_error!("Hot reloading is only supported for native targets.");
+    }
+
     let should_run_expects = matches!(opt_level, OptLevel::Development | OptLevel::Normal) &&
+        // Expects are reported through memory shared with the program, which only works when
+        // the program runs in place of `roc`.
+        !hot_reload &&
         // TODO: once expect is decoupled from roc launching the executable, remove this part of the conditional.
         matches!(
             config,
@@ -955,6 +1735,17 @@ pub fn build(
     if emit_llvm_ir && !matches!(code_gen_backend, CodeGenBackend::Llvm(_)) {
         user_error!("Cannot emit llvm ir while using a dev backend.");
     }
+    let emit_mono_ir = matches.get_flag(FLAG_EMIT_MONO_IR);
+    let emit_specialization_report = matches.get_flag(FLAG_SPECIALIZATION_REPORT);
+    let warn_closure_captures = matches.get_flag(FLAG_WARN_CLOSURE_CAPTURES);
+    let emit_borrow_report = matches.get_flag(FLAG_BORROW_REPORT);
+    let emit_dead_field_report = matches.get_flag(FLAG_DEAD_FIELD_REPORT);
+    let emit_match_trees = matches
+        .get_many::<String>(FLAG_EMIT)
+        .is_some_and(|mut emit| emit.any(|artifact| artifact == "match-tree"));
+    let emit_size_report = matches
+        .get_many::<String>(FLAG_EMIT)
+        .is_some_and(|mut emit| emit.any(|artifact| artifact == "size-report"));
 
     let emit_debug_info = matches.get_flag(FLAG_PROFILING)
         || matches!(opt_level, OptLevel::Development | OptLevel::Normal);
@@ -969,8 +1760,31 @@ pub fn build(
 
     let wasm_dev_backend = matches!(code_gen_backend, CodeGenBackend::Wasm);
 
+    // Only `roc build` has this flag
+    let deterministic = matches
+        .try_get_one::<bool>(FLAG_DETERMINISTIC)
+        .ok()
+        .flatten()
+        .copied()
+        .unwrap_or(false);
+
+    if deterministic && !matches!(code_gen_backend, CodeGenBackend::Llvm(_)) {
+        user_error!("Cannot make a deterministic build while using a dev backend.");
+    }
+    if deterministic && matches!(target.architecture(), Architecture::Wasm32) {
+        user_error!("Deterministic builds are only supported for native targets.");
+    }
+
     let linking_strategy = if wasm_dev_backend {
         LinkingStrategy::Additive
+    } else if deterministic {
+        // The surgical linker copies the preprocessed host, which isn't built deterministically.
+        match matches.get_one::<String>(FLAG_LINKER).map(AsRef::as_ref) {
+            Some("surgical") => user_error!(
+                "Cannot make a deterministic build with the surgical linker, use `--{FLAG_LINKER}=legacy` instead."
+            ),
+            _ => LinkingStrategy::Legacy,
+        }
     } else {
         default_linking_strategy(matches, link_type, target)
     };
@@ -985,6 +1799,31 @@ pub fn build(
         user_error!("Cannot instrument binary for fuzzing while using a dev backend.");
     }
 
+    // Only `roc build` has this flag
+    let emit_js_glue = matches
+        .try_get_one::<bool>(FLAG_JS_GLUE)
+        .ok()
+        .flatten()
+        .copied()
+        .unwrap_or(false);
+
+    // Only `roc build` has this flag
+    let emit_frame_table = matches
+        .try_get_one::<bool>(FLAG_STACK_TRACES)
+        .ok()
+        .flatten()
+        .copied()
+        .unwrap_or(false);
+
+    // Only `roc build` has this flag
+    let profile_compile = matches
+        .try_contains_id(FLAG_PROFILE_COMPILE)
+        .unwrap_or(false);
+    let profile_json_path = matches
+        .try_get_one::<PathBuf>(FLAG_PROFILE_COMPILE)
+        .ok()
+        .flatten();
+
     let wasm_dev_stack_bytes: Option<u32> = matches
         .try_get_one::<u32>(FLAG_WASM_STACK_SIZE_KB)
         .ok()
@@ -1002,9 +1841,80 @@ pub fn build(
         emit_debug_info,
         emit_llvm_ir,
         fuzz,
+        deterministic,
+        emit_js_glue,
+        emit_frame_table,
+        emit_mono_ir,
+        emit_specialization_report,
+        warn_closure_captures,
+        emit_borrow_report,
+        emit_dead_field_report,
+        emit_match_trees,
+        emit_size_report,
+    };
+
+    let profile = matches
+        .try_get_one::<PathBuf>(FLAG_PROFILE_USE)
+        .ok()
+        .flatten()
+        .map(|path| read_profile(path));
+
+    let comptime_limits = comptime_limits_from_flags(matches);
+
+    let load_config = roc_load::LoadConfig {
+        emit_match_trees,
+        profile: profile.clone(),
+        comptime_limits,
+        ..standard_load_config(target, build_ordering, threading)
     };
 
-    let load_config = standard_load_config(target, build_ordering, threading);
+    // With `--hot`, every rebuild links just the app, into a shared library for the host to load.
+    let rebuild_app = |lib_path: &Path| {
+        let arena = Bump::new();
+        let load_config = roc_load::LoadConfig {
+            profile: profile.clone(),
+            comptime_limits,
+            ..standard_load_config(target, BuildOrdering::BuildIfChecks, threading)
+        };
+
+        match roc_build::program::build_file(
+            &arena,
+            target,
+            path.to_owned(),
+            code_gen_options,
+            emit_timings,
+            LinkType::Dylib,
+            LinkingStrategy::Legacy,
+            false,
+            true,
+            wasm_dev_stack_bytes,
+            roc_cache_dir,
+            load_config,
+            Some(lib_path),
+            verbose,
+        ) {
+            Ok(BuiltFile {
+                problems,
+                total_time,
+                ..
+            }) => {
+                if problems.warnings > 0 {
+                    problems.print_error_warning_count(total_time);
+                    println!(".");
+                }
+
+                !problems.fatally_errored
+            }
+            Err(BuildFileError::ErrorModule { module, total_time }) => {
+                handle_error_module(module, total_time, path.as_os_str(), false).ok();
+                false
+            }
+            Err(BuildFileError::LoadingProblem(problem)) => {
+                handle_loading_problem(problem).ok();
+                false
+            }
+        }
+    };
 
     let res_binary_path = roc_build::program::build_file(
         &arena,
@@ -1029,7 +1939,18 @@ pub fn build(
             problems,
             total_time,
             expect_metadata,
+            profile,
         }) => {
+            if profile_compile {
+                const MAX_MODULES: usize = 10;
+
+                println!("{}", profile.summary(MAX_MODULES));
+
+                if let Some(json_path) = profile_json_path {
+                    std::fs::write(json_path, profile.to_json())?;
+                }
+            }
+
             match config {
                 BuildOnly => {
                     // If possible, report the generated executable name relative to the current dir.
@@ -1073,6 +1994,21 @@ pub fn build(
                         .unwrap_or_default()
                         .map(|s| s.as_os_str());
 
+                    if hot_reload {
+                        let watch_root = match path.parent() {
+                            Some(dir) if !dir.as_os_str().is_empty() => dir,
+                            _ => Path::new("."),
+                        };
+
+                        return hot_reload::run(
+                            &binary_path,
+                            args,
+                            watch_root,
+                            target,
+                            rebuild_app,
+                        );
+                    }
+
                     // don't waste time deallocating; the process ends anyway
                     // ManuallyDrop will leak the bytes because we don't drop manually
                     let bytes = &ManuallyDrop::new(std::fs::read(&binary_path).unwrap());
@@ -1277,6 +2213,9 @@ fn roc_run_native<I: IntoIterator<Item = S>, S: AsRef<OsStr>>(
     if should_run_expects {
         roc_dev_native(arena, executable, argv, envp, expect_metadata);
     } else {
+        // The compiler is replaced by the program, without running destructors.
+        roc_tracing::finish_chrome_trace();
+
         unsafe { roc_run_native_fast(executable, &argv, &envp) };
     }
 
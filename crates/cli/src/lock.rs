@@ -0,0 +1,116 @@
+//! Writing the `roc.lock` next to an app or package, for `roc lock`.
+//!
+//! The lockfile pins every package the module depends on by URL, including its platform. A
+//! package's manifest can also depend on packages through version constraints; those are
+//! resolved against the index of a package registry, and the lockfile records the version each
+//! one was resolved to. The lockfile is checked against the package cache whenever the module
+//! is loaded.
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use bumpalo::Bump;
+use roc_load::manifest::PackageManifest;
+use roc_packaging::cache::RocCacheDir;
+use roc_packaging::lockfile::{Lockfile, LOCKFILE_NAME};
+use roc_packaging::resolve::{read_index, resolve};
+use roc_parse::ast::{ExtractSpaces, Header};
+use roc_parse::header::parse_header;
+use roc_parse::state::State;
+use roc_reporting::report::to_lockfile_problem_report_string;
+
+/// Installs every package `roc_file_path` depends on, and locks their current contents in the
+/// `roc.lock` next to it, which replaces the previous one. The dependencies in a package
+/// manifest are resolved with the index in `registry`, which is the directory of the index
+/// and the URL its bundles are downloaded from.
+///
+/// Returns the path of the lockfile, or a message explaining why it couldn't be written.
+pub fn lock(
+    roc_file_path: &Path,
+    roc_cache_dir: RocCacheDir<'_>,
+    registry: Option<(&Path, &str)>,
+) -> Result<PathBuf, String> {
+    let arena = Bump::new();
+    let src = fs::read(roc_file_path)
+        .map_err(|err| format!("{} could not be read: {err}", roc_file_path.display()))?;
+    let (header, _) = parse_header(&arena, State::new(arena.alloc(src))).map_err(|fail| {
+        format!(
+            "the header of {} could not be parsed: {:?}",
+            roc_file_path.display(),
+            fail.problem
+        )
+    })?;
+
+    let mut dependencies = Vec::new();
+
+    let packages = match header.item {
+        Header::App(header) => header.packages.value.items,
+        Header::Package(header) => {
+            let manifest = PackageManifest::from_header(&header)
+                .map_err(|problem| format!("the package manifest is invalid: {problem}"))?;
+
+            if let Some(manifest) = manifest {
+                dependencies = manifest.dependencies;
+            }
+
+            header.packages.value.items
+        }
+        Header::Platform(header) => header.packages.item.items,
+        Header::Module(_) | Header::Hosted(_) => {
+            return Err(format!(
+                "{} is not an app, package or platform, so it has no packages to lock",
+                roc_file_path.display()
+            ))
+        }
+    };
+
+    let urls = packages
+        .iter()
+        .map(|package| {
+            package
+                .value
+                .extract_spaces()
+                .item
+                .package_name
+                .value
+                .to_str()
+        })
+        .filter(|src| src.starts_with("https://"));
+    let lockfile_path = roc_file_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(LOCKFILE_NAME);
+    let report = |problem| {
+        to_lockfile_problem_report_string(problem, &lockfile_path, roc_file_path.to_path_buf())
+    };
+
+    let lockfile = if dependencies.is_empty() {
+        Lockfile::update(roc_cache_dir, urls).map_err(report)?
+    } else {
+        let Some((index_dir, registry_url)) = registry else {
+            return Err(format!(
+                "the package manifest has dependencies, so I need the registry's --{} and --{} to resolve them",
+                crate::FLAG_INDEX,
+                crate::FLAG_REGISTRY
+            ));
+        };
+
+        let index = read_index(index_dir, registry_url).map_err(|err| {
+            format!(
+                "the index in {} could not be read: {err}",
+                index_dir.display()
+            )
+        })?;
+        // Keep the versions the previous lockfile resolved to, if they're still allowed.
+        let locked = Lockfile::read(&lockfile_path)
+            .map(|lockfile| lockfile.locked_versions())
+            .unwrap_or_default();
+        let resolution =
+            resolve(&index, &dependencies, &locked).map_err(|problem| problem.to_string())?;
+
+        Lockfile::update_resolved(roc_cache_dir, urls, &resolution).map_err(report)?
+    };
+
+    lockfile.write(&lockfile_path).map_err(report)?;
+
+    Ok(lockfile_path)
+}
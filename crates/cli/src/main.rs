@@ -3,17 +3,22 @@ use bumpalo::Bump;
 use roc_build::link::LinkType;
 use roc_build::program::{check_file, CodeGenBackend};
 use roc_cli::{
-    annotate_file, build_app, default_linking_strategy, format_files, format_src, test,
-    AnnotationProblem, BuildConfig, FormatMode, CMD_BUILD, CMD_CHECK, CMD_DEV, CMD_DOCS,
-    CMD_FORMAT, CMD_FORMAT_ANNOTATE, CMD_GLUE, CMD_PREPROCESS_HOST, CMD_REPL, CMD_RUN, CMD_TEST,
-    CMD_VERSION, DIRECTORY_OR_FILES, FLAG_CHECK, FLAG_DEV, FLAG_DOCS_ROOT, FLAG_LIB, FLAG_MAIN,
-    FLAG_MIGRATE, FLAG_NO_COLOR, FLAG_NO_HEADER, FLAG_NO_LINK, FLAG_OUTPUT, FLAG_PP_DYLIB,
-    FLAG_PP_HOST, FLAG_PP_PLATFORM, FLAG_STDIN, FLAG_STDOUT, FLAG_TARGET, FLAG_TIME, FLAG_VERBOSE,
-    GLUE_DIR, GLUE_SPEC, ROC_FILE, VERSION,
+    annotate_file, build_app, default_linking_strategy, format_files, format_project, format_src,
+    merge_profiles, print_call_hierarchy, rename_def, test, AnnotationProblem, BuildConfig,
+    FormatMode, CMD_BUILD, CMD_CALLS, CMD_CHECK, CMD_DEMANGLE, CMD_DEV, CMD_DOCS, CMD_FORMAT,
+    CMD_FORMAT_ANNOTATE, CMD_GLUE, CMD_INIT, CMD_LAYOUT, CMD_LOCK, CMD_PREPROCESS_HOST,
+    CMD_PROFILE, CMD_PROFILE_MERGE, CMD_PUBLISH, CMD_RENAME, CMD_REPL, CMD_RUN, CMD_TEST,
+    CMD_VERSION, DIRECTORY_OR_FILES, FLAG_CALLERS, FLAG_CHECK, FLAG_DBG, FLAG_DEPTH, FLAG_DEV,
+    FLAG_DOCS_EXAMPLES, FLAG_DOCS_ROOT, FLAG_INDEX, FLAG_LIB, FLAG_MAIN, FLAG_MIGRATE,
+    FLAG_NO_COLOR, FLAG_NO_HEADER, FLAG_NO_LINK, FLAG_OUTPUT, FLAG_PACKAGE, FLAG_PLATFORM,
+    FLAG_PP_DYLIB, FLAG_PP_HOST, FLAG_PP_PLATFORM, FLAG_REGISTRY, FLAG_STDIN, FLAG_STDOUT,
+    FLAG_TARGET, FLAG_TIME, FLAG_VERBOSE, FUNCTION_NAME, GLUE_DIR, GLUE_SPEC, MANGLED_NAMES,
+    NEW_NAME, OLD_NAME, PROFILE_FILES, PROJECT_DIR, ROC_FILE, ROC_TYPE, VERSION,
 };
 use roc_docs::generate_docs_html;
 use roc_error_macros::{internal_error, user_error};
-use roc_fmt::MigrationFlags;
+use roc_fmt::transform::BUILTIN_TRANSFORMS;
+use roc_fmt::{DbgMigration, MigrationFlags};
 use roc_gen_dev::AssemblyBackendMode;
 use roc_gen_llvm::llvm::build::LlvmBackendMode;
 use roc_load::{LoadingProblem, Threading};
@@ -35,7 +40,7 @@ use std::ffi::{OsStr, OsString};
 use roc_cli::build;
 
 fn main() -> io::Result<()> {
-    let _tracing_guards = roc_tracing::setup_tracing!();
+    let tracing_guards = roc_tracing::setup_tracing!();
 
     let app = build_app();
     let subcommands: Vec<String> = app
@@ -216,6 +221,7 @@ fn main() -> io::Result<()> {
             let arena = Bump::new();
 
             let emit_timings = matches.get_flag(FLAG_TIME);
+            let trace_abilities = matches.get_flag(roc_cli::FLAG_TRACE_ABILITIES);
             let roc_file_path = matches.get_one::<PathBuf>(ROC_FILE).unwrap();
             let threading = match matches.get_one::<usize>(roc_cli::FLAG_MAX_THREADS) {
                 None => Threading::AllAvailable,
@@ -226,91 +232,130 @@ fn main() -> io::Result<()> {
 
             let opt_main_path = matches.get_one::<PathBuf>(FLAG_MAIN);
 
-            match roc_file_path.extension().and_then(OsStr::to_str) {
-                Some("md") => {
-                    // Extract the blocks of roc code
-                    let file = fs::File::open(roc_file_path.as_path())?;
-                    let markdown_file_reader = io::BufReader::new(file);
-                    let mut roc_blocks: Vec<String> = Vec::new();
-                    let mut in_roc_block: bool = false;
-                    let mut current_block = String::new();
-
-                    for line in markdown_file_reader.lines() {
-                        let line = line.unwrap();
-                        if line == "```roc" {
-                            in_roc_block = true;
-                        } else if (line == "```") & in_roc_block {
-                            in_roc_block = false;
-                            roc_blocks.push(current_block);
-                            current_block = String::new();
-                        } else if in_roc_block {
-                            current_block.push_str(&line);
-                            current_block.push('\n');
+            if matches.get_flag(roc_cli::FLAG_AUDIT_DETERMINISM) {
+                match roc_build::program::audit_determinism(
+                    &arena,
+                    roc_file_path.to_owned(),
+                    opt_main_path.cloned(),
+                    RocCacheDir::Persistent(cache::roc_cache_packages_dir().as_path()),
+                    threading,
+                ) {
+                    Ok(audit) => {
+                        if audit.threads == 1 {
+                            println!("Only one thread is available, so both checks ran on a single thread.");
+                        }
+
+                        match audit.divergence {
+                            Some(divergence) => {
+                                println!("Checking on 1 thread and on {} threads gave different results.\n\n{divergence}", audit.threads);
+
+                                Ok(1)
+                            }
+                            None => {
+                                println!("Checking on 1 thread and on {} threads gave the same {} ids and problems.", audit.threads, audit.entries);
+
+                                Ok(0)
+                            }
                         }
                     }
+                    Err(LoadingProblem::FormattedReport(report, _)) => {
+                        print!("{report}");
+
+                        Ok(1)
+                    }
+                    Err(other) => {
+                        panic!("audit_determinism failed with error:\n{other:?}");
+                    }
+                }
+            } else {
+                match roc_file_path.extension().and_then(OsStr::to_str) {
+                    Some("md") => {
+                        // Extract the blocks of roc code
+                        let file = fs::File::open(roc_file_path.as_path())?;
+                        let markdown_file_reader = io::BufReader::new(file);
+                        let mut roc_blocks: Vec<String> = Vec::new();
+                        let mut in_roc_block: bool = false;
+                        let mut current_block = String::new();
+
+                        for line in markdown_file_reader.lines() {
+                            let line = line.unwrap();
+                            if line == "```roc" {
+                                in_roc_block = true;
+                            } else if (line == "```") & in_roc_block {
+                                in_roc_block = false;
+                                roc_blocks.push(current_block);
+                                current_block = String::new();
+                            } else if in_roc_block {
+                                current_block.push_str(&line);
+                                current_block.push('\n');
+                            }
+                        }
+
+                        // now check each block, we exit early if any single block does not check
+                        let mut exit_code = 0;
+
+                        for block in roc_blocks.iter() {
+                            let mut file = Builder::new().suffix(".roc").tempfile()?;
+                            write!(file, "{}", block)?;
+
+                            match check_file(
+                                &arena,
+                                file.path().to_owned(),
+                                opt_main_path.cloned(),
+                                emit_timings,
+                                RocCacheDir::Persistent(cache::roc_cache_packages_dir().as_path()),
+                                threading,
+                                trace_abilities,
+                            ) {
+                                Ok((problems, total_time)) => {
+                                    problems.print_error_warning_count(total_time);
+                                    println!(".\n");
+
+                                    exit_code = problems.exit_code();
+                                }
 
-                    // now check each block, we exit early if any single block does not check
-                    let mut exit_code = 0;
+                                Err(LoadingProblem::FormattedReport(report, _)) => {
+                                    print!("{report}");
 
-                    for block in roc_blocks.iter() {
-                        let mut file = Builder::new().suffix(".roc").tempfile()?;
-                        write!(file, "{}", block)?;
+                                    exit_code = 1;
+                                }
+                                Err(other) => {
+                                    panic!("build_file failed with error:\n{other:?}");
+                                }
+                            }
+
+                            if exit_code != 0 {
+                                break;
+                            }
+                        }
 
+                        Ok(exit_code)
+                    }
+                    _ => {
                         match check_file(
                             &arena,
-                            file.path().to_owned(),
+                            roc_file_path.to_owned(),
                             opt_main_path.cloned(),
                             emit_timings,
                             RocCacheDir::Persistent(cache::roc_cache_packages_dir().as_path()),
                             threading,
+                            trace_abilities,
                         ) {
                             Ok((problems, total_time)) => {
                                 problems.print_error_warning_count(total_time);
                                 println!(".\n");
-
-                                exit_code = problems.exit_code();
+                                Ok(problems.exit_code())
                             }
 
                             Err(LoadingProblem::FormattedReport(report, _)) => {
                                 print!("{report}");
 
-                                exit_code = 1;
+                                Ok(1)
                             }
                             Err(other) => {
                                 panic!("build_file failed with error:\n{other:?}");
                             }
                         }
-
-                        if exit_code != 0 {
-                            break;
-                        }
-                    }
-
-                    Ok(exit_code)
-                }
-                _ => {
-                    match check_file(
-                        &arena,
-                        roc_file_path.to_owned(),
-                        opt_main_path.cloned(),
-                        emit_timings,
-                        RocCacheDir::Persistent(cache::roc_cache_packages_dir().as_path()),
-                        threading,
-                    ) {
-                        Ok((problems, total_time)) => {
-                            problems.print_error_warning_count(total_time);
-                            println!(".\n");
-                            Ok(problems.exit_code())
-                        }
-
-                        Err(LoadingProblem::FormattedReport(report, _)) => {
-                            print!("{report}");
-
-                            Ok(1)
-                        }
-                        Err(other) => {
-                            panic!("build_file failed with error:\n{other:?}");
-                        }
                     }
                 }
             }
@@ -324,6 +369,7 @@ fn main() -> io::Result<()> {
         Some((CMD_DOCS, matches)) => {
             let root_path = matches.get_one::<PathBuf>(ROC_FILE).unwrap();
             let out_dir = matches.get_one::<OsString>(FLAG_OUTPUT).unwrap();
+            let max_examples = *matches.get_one::<usize>(FLAG_DOCS_EXAMPLES).unwrap();
 
             let maybe_root_dir: Option<String> = {
                 if let Ok(root_dir) = std::env::var("ROC_DOCS_URL_ROOT") {
@@ -343,6 +389,7 @@ fn main() -> io::Result<()> {
                 root_path.to_owned(),
                 out_dir.as_ref(),
                 maybe_root_dir.clone(),
+                max_examples,
             );
 
             Ok(0)
@@ -387,6 +434,11 @@ fn main() -> io::Result<()> {
             let from_stdin = matches.get_flag(FLAG_STDIN);
             let to_stdout = matches.get_flag(FLAG_STDOUT);
             let migrate = matches.get_flag(FLAG_MIGRATE);
+            let dbg = match matches.get_one::<String>(FLAG_DBG).map(String::as_str) {
+                Some("lift") => DbgMigration::Lift,
+                Some("remove") => DbgMigration::Remove,
+                _ => DbgMigration::Keep,
+            };
             let format_mode = if to_stdout {
                 FormatMode::WriteToStdout
             } else {
@@ -398,6 +450,8 @@ fn main() -> io::Result<()> {
             let flags = MigrationFlags {
                 snakify: migrate,
                 parens_and_commas: migrate,
+                dbg,
+                transforms: if migrate { BUILTIN_TRANSFORMS } else { &[] },
             };
 
             if from_stdin && matches!(format_mode, FormatMode::WriteToFile) {
@@ -482,9 +536,17 @@ fn main() -> io::Result<()> {
                         1
                     }
                 }
+            } else if matches!(format_mode, FormatMode::WriteToFile) {
+                match format_project(roc_files, flags) {
+                    Ok(_) => 0,
+                    Err(problem) => {
+                        eprintln!("`roc format` failed: {problem}");
+                        1
+                    }
+                }
             } else {
                 match format_files(roc_files, format_mode, flags) {
-                    Ok(()) => 0,
+                    Ok(_) => 0,
                     Err(message) => {
                         eprintln!("{message}");
                         1
@@ -494,6 +556,219 @@ fn main() -> io::Result<()> {
 
             Ok(format_exit_code)
         }
+        Some((CMD_PROFILE, pmatches)) => match pmatches.subcommand() {
+            Some((CMD_PROFILE_MERGE, matches)) => {
+                let paths: Vec<_> = matches
+                    .get_many::<PathBuf>(PROFILE_FILES)
+                    .unwrap()
+                    .collect();
+                let output = matches.get_one::<PathBuf>(FLAG_OUTPUT).unwrap();
+
+                merge_profiles(&paths, output)
+            }
+            _ => unreachable!(),
+        },
+        Some((CMD_DEMANGLE, matches)) => {
+            use roc_mono::mangle::demangle_text;
+
+            match matches.get_many::<String>(MANGLED_NAMES) {
+                Some(names) => {
+                    for name in names {
+                        println!("{}", demangle_text(name));
+                    }
+                }
+                None => {
+                    let stdout = io::stdout();
+                    let mut stdout = stdout.lock();
+
+                    for line in io::stdin().lock().lines() {
+                        writeln!(stdout, "{}", demangle_text(&line?))?;
+                    }
+                }
+            }
+
+            Ok(0)
+        }
+        Some((CMD_LAYOUT, matches)) => {
+            use roc_load::LayoutQueryError;
+
+            let arena = Bump::new();
+            let type_annotation = matches.get_one::<String>(ROC_TYPE).unwrap();
+            let roc_file_path = matches.get_one::<PathBuf>(ROC_FILE).unwrap();
+            let target = matches
+                .get_one::<String>(FLAG_TARGET)
+                .and_then(|s| Target::from_str(s).ok())
+                .unwrap_or_default();
+
+            match roc_build::program::query_layout(
+                &arena,
+                roc_file_path.to_owned(),
+                type_annotation,
+                target,
+                RocCacheDir::Persistent(cache::roc_cache_packages_dir().as_path()),
+                Threading::AllAvailable,
+            ) {
+                Ok(layout) => {
+                    print!("{layout}");
+
+                    Ok(0)
+                }
+                Err(LayoutQueryError::Problems(reports)) => {
+                    for report in reports {
+                        eprint!("{report}");
+                    }
+
+                    Ok(1)
+                }
+                Err(LayoutQueryError::NotConcrete { type_variables }) => {
+                    if type_variables.is_empty() {
+                        eprintln!("The type `{type_annotation}` has no layout of its own, because values of it can have different layouts.");
+                    } else {
+                        eprintln!(
+                            "The type `{type_annotation}` has no layout of its own, because it has the type variables {}. Try it with concrete types in their place.",
+                            type_variables.join(", ")
+                        );
+                    }
+
+                    Ok(1)
+                }
+                Err(LayoutQueryError::Loading(LoadingProblem::FormattedReport(report, _))) => {
+                    print!("{report}");
+
+                    Ok(1)
+                }
+                Err(LayoutQueryError::Loading(other)) => {
+                    panic!("query_layout failed with error:\n{other:?}");
+                }
+            }
+        }
+        Some((CMD_CALLS, matches)) => {
+            use roc_load::call_hierarchy::CallDirection;
+
+            let function_name = matches.get_one::<String>(FUNCTION_NAME).unwrap();
+            let roc_file_path = matches.get_one::<PathBuf>(ROC_FILE).unwrap();
+            let max_depth = *matches.get_one::<usize>(FLAG_DEPTH).unwrap();
+            let direction = if matches.get_flag(FLAG_CALLERS) {
+                CallDirection::Incoming
+            } else {
+                CallDirection::Outgoing
+            };
+
+            print_call_hierarchy(
+                roc_file_path,
+                function_name,
+                direction,
+                max_depth,
+                RocCacheDir::Persistent(cache::roc_cache_packages_dir().as_path()),
+            )
+        }
+        Some((CMD_RENAME, matches)) => {
+            let old_name = matches.get_one::<String>(OLD_NAME).unwrap();
+            let new_name = matches.get_one::<String>(NEW_NAME).unwrap();
+            let roc_file_path = matches.get_one::<PathBuf>(ROC_FILE).unwrap();
+
+            rename_def(
+                roc_file_path,
+                old_name,
+                new_name,
+                RocCacheDir::Persistent(cache::roc_cache_packages_dir().as_path()),
+            )
+        }
+        Some((CMD_INIT, matches)) => {
+            use roc_cli::init::{init_project, InitOptions, ProjectKind};
+
+            let project_dir = matches.get_one::<PathBuf>(PROJECT_DIR).unwrap();
+            let kind = if matches.get_flag(FLAG_PACKAGE) {
+                ProjectKind::Package
+            } else {
+                ProjectKind::App
+            };
+            let name = project_dir
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let options = InitOptions {
+                name,
+                kind,
+                platform_url: matches.get_one::<String>(FLAG_PLATFORM).unwrap().clone(),
+            };
+
+            match init_project(project_dir, &options) {
+                Ok(written) => {
+                    for path in written {
+                        println!("Created {}", path.display());
+                    }
+
+                    Ok(0)
+                }
+                Err(err) => {
+                    eprintln!("Could not create the project: {err}");
+
+                    Ok(1)
+                }
+            }
+        }
+        Some((CMD_PUBLISH, matches)) => {
+            use roc_cli::publish::build_bundle;
+
+            let roc_file_path = matches.get_one::<PathBuf>(ROC_FILE).unwrap();
+            let out_dir = matches.get_one::<PathBuf>(FLAG_OUTPUT).unwrap();
+            let threading = match matches.get_one::<usize>(roc_cli::FLAG_MAX_THREADS) {
+                None => Threading::AllAvailable,
+                Some(0) => user_error!("cannot build with at most 0 threads"),
+                Some(1) => Threading::Single,
+                Some(n) => Threading::AtMost(*n),
+            };
+
+            match build_bundle(
+                roc_file_path,
+                out_dir,
+                RocCacheDir::Persistent(cache::roc_cache_packages_dir().as_path()),
+                threading,
+            ) {
+                Ok(bundle) => {
+                    println!(
+                        "Bundled {} {} into:\n\n\t{}\n\t{}\n",
+                        bundle.manifest.name,
+                        bundle.manifest.version,
+                        bundle.bundle_path.display(),
+                        bundle.metadata_path.display()
+                    );
+
+                    Ok(0)
+                }
+                Err(problem) => {
+                    eprintln!("Could not publish the package: {problem}");
+
+                    Ok(1)
+                }
+            }
+        }
+        Some((CMD_LOCK, matches)) => {
+            use roc_cli::lock::lock;
+
+            let roc_file_path = matches.get_one::<PathBuf>(ROC_FILE).unwrap();
+            let registry = matches
+                .get_one::<PathBuf>(FLAG_INDEX)
+                .zip(matches.get_one::<String>(FLAG_REGISTRY));
+
+            match lock(
+                roc_file_path,
+                RocCacheDir::Persistent(cache::roc_cache_packages_dir().as_path()),
+                registry.map(|(index_dir, url)| (index_dir.as_path(), url.as_str())),
+            ) {
+                Ok(lockfile_path) => {
+                    println!("Locked the packages in {}", lockfile_path.display());
+
+                    Ok(0)
+                }
+                Err(report) => {
+                    eprintln!("{report}");
+
+                    Ok(1)
+                }
+            }
+        }
         Some((CMD_VERSION, _)) => {
             println!("roc {}", VERSION);
             Ok(0)
@@ -501,6 +776,9 @@ fn main() -> io::Result<()> {
         _ => unreachable!(),
     }?;
 
+    // `process::exit` doesn't run destructors, so finish writing logs and traces first.
+    drop(tracing_guards);
+
     std::process::exit(exit_code);
 }
 
@@ -0,0 +1,263 @@
+//! Building the bundle a package is published as, for `roc publish`.
+//!
+//! A package is only bundled if it has a valid manifest, type-checks without errors, and every
+//! one of its modules is already formatted. The bundle is the same `.tar.br` that
+//! `roc build --bundle` makes, and it's written next to a JSON file with the package's metadata,
+//! for a package registry to index.
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use bumpalo::Bump;
+use roc_build::program::check_file;
+use roc_fmt::{DbgMigration, MigrationFlags};
+use roc_load::manifest::{read_package_manifest, PackageManifest, ReadManifestError};
+use roc_load::{LoadingProblem, Threading};
+use roc_packaging::cache::RocCacheDir;
+use roc_packaging::tarball::{self, Compression};
+use serde::Serialize;
+
+use crate::format::{flatten_directories, format_src};
+
+#[derive(Debug)]
+pub struct PublishedBundle {
+    /// The `.tar.br` file, whose name is the hash that goes in the package's URL
+    pub bundle_path: PathBuf,
+    pub metadata_path: PathBuf,
+    pub hash: String,
+    pub manifest: PackageManifest,
+}
+
+#[derive(Debug)]
+pub enum PublishProblem {
+    Io(io::Error),
+    Manifest(ReadManifestError),
+    /// Only packages with a `manifest` section in their header can be published, since a
+    /// registry needs at least their name and version.
+    MissingManifest,
+    /// The package didn't load; this is the report explaining why.
+    Loading(String),
+    TypeErrors {
+        errors: usize,
+    },
+    Unformatted(Vec<PathBuf>),
+}
+
+impl fmt::Display for PublishProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PublishProblem::Io(err) => write!(f, "{err}"),
+            PublishProblem::Manifest(ReadManifestError::Io(err)) => write!(f, "{err}"),
+            PublishProblem::Manifest(ReadManifestError::Parse(problem)) => {
+                write!(f, "the package header could not be parsed: {problem}")
+            }
+            PublishProblem::Manifest(ReadManifestError::NotAPackage) => {
+                write!(f, "only packages can be published, and this is not a package")
+            }
+            PublishProblem::Manifest(ReadManifestError::Invalid(problem)) => {
+                write!(f, "the package manifest is invalid: {problem}")
+            }
+            PublishProblem::MissingManifest => write!(
+                f,
+                "the package header needs a `manifest` with at least a name and version to be published"
+            ),
+            PublishProblem::Loading(report) => write!(f, "{report}"),
+            PublishProblem::TypeErrors { errors } => {
+                write!(f, "the package has {errors} error(s), which must be fixed before it can be published")
+            }
+            PublishProblem::Unformatted(paths) => {
+                writeln!(f, "these files need to be formatted with `roc format` first:")?;
+
+                for path in paths {
+                    writeln!(f, "    {}", path.display())?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+/// What the metadata JSON file contains.
+#[derive(Serialize)]
+struct Metadata<'a> {
+    name: &'a str,
+    version: &'a str,
+    license: Option<&'a str>,
+    description: Option<&'a str>,
+    homepage: Option<&'a str>,
+    repository: Option<&'a str>,
+    authors: &'a [String],
+    keywords: &'a [String],
+    exposes: &'a [String],
+    /// Each is a package name and a version constraint, e.g. `json ^1.2`, which
+    /// [roc_packaging::resolve::read_index] reads back.
+    dependencies: Vec<String>,
+    /// The hash at the end of the package's URL
+    hash: &'a str,
+    /// The bundle's filename
+    bundle: &'a str,
+    /// The bundle's size in bytes
+    size: u64,
+}
+
+/// Check the package whose root module is at `path_to_main`, then write its bundle and metadata
+/// to `out_dir`, which is created if it doesn't exist yet.
+pub fn build_bundle(
+    path_to_main: &Path,
+    out_dir: &Path,
+    roc_cache_dir: RocCacheDir<'_>,
+    threading: Threading,
+) -> Result<PublishedBundle, PublishProblem> {
+    let manifest = read_package_manifest(path_to_main)
+        .map_err(PublishProblem::Manifest)?
+        .ok_or(PublishProblem::MissingManifest)?;
+
+    let arena = Bump::new();
+
+    match check_file(
+        &arena,
+        path_to_main.to_path_buf(),
+        None,
+        false,
+        roc_cache_dir,
+        threading,
+        false,
+    ) {
+        Ok((problems, _)) if problems.errors > 0 => {
+            return Err(PublishProblem::TypeErrors {
+                errors: problems.errors,
+            })
+        }
+        Ok(_) => {}
+        Err(LoadingProblem::FormattedReport(report, _)) => {
+            return Err(PublishProblem::Loading(report))
+        }
+        Err(other) => return Err(PublishProblem::Loading(format!("{other:?}"))),
+    }
+
+    let root_dir = match path_to_main.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let unformatted = unformatted_files(root_dir);
+
+    if !unformatted.is_empty() {
+        return Err(PublishProblem::Unformatted(unformatted));
+    }
+
+    fs::create_dir_all(out_dir).map_err(PublishProblem::Io)?;
+
+    let filename = tarball::build_in(path_to_main, Compression::Brotli, out_dir)
+        .map_err(PublishProblem::Io)?;
+    let hash = filename
+        .strip_suffix(".tar.br")
+        .expect("brotli bundles end in .tar.br")
+        .to_string();
+    let bundle_path = out_dir.join(&filename);
+    let size = fs::metadata(&bundle_path)
+        .map_err(PublishProblem::Io)?
+        .len();
+
+    let metadata = Metadata {
+        name: &manifest.name,
+        version: &manifest.version,
+        license: manifest.license.as_deref(),
+        description: manifest.description.as_deref(),
+        homepage: manifest.homepage.as_deref(),
+        repository: manifest.repository.as_deref(),
+        authors: &manifest.authors,
+        keywords: &manifest.keywords,
+        exposes: &manifest.exposes,
+        dependencies: manifest
+            .dependencies
+            .iter()
+            .map(|dependency| dependency.to_string())
+            .collect(),
+        hash: &hash,
+        bundle: &filename,
+        size,
+    };
+    let json = serde_json::to_string_pretty(&metadata)
+        .expect("metadata only contains strings and numbers");
+    let metadata_path = out_dir.join(format!("{hash}.json"));
+
+    fs::write(&metadata_path, json + "\n").map_err(PublishProblem::Io)?;
+
+    Ok(PublishedBundle {
+        bundle_path,
+        metadata_path,
+        hash,
+        manifest,
+    })
+}
+
+/// The .roc files in the package that `roc format` would change. Files that don't parse are
+/// included too, although type-checking would already have reported them.
+fn unformatted_files(root_dir: &Path) -> Vec<PathBuf> {
+    let flags = MigrationFlags {
+        snakify: false,
+        parens_and_commas: false,
+        dbg: DbgMigration::Keep,
+        transforms: &[],
+    };
+    let mut unformatted = Vec::new();
+
+    for path in flatten_directories(vec![root_dir.to_path_buf()]) {
+        let arena = Bump::new();
+        let formatted = fs::read_to_string(&path)
+            .ok()
+            .and_then(|src| Some(format_src(&arena, &src, flags).ok()? == src));
+
+        if formatted != Some(true) {
+            unformatted.push(path);
+        }
+    }
+
+    unformatted.sort();
+
+    unformatted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_unformatted_files() {
+        let dir = tempfile::tempdir().unwrap();
+
+        fs::create_dir(dir.path().join("Sub")).unwrap();
+        fs::write(dir.path().join("main.roc"), "package [Sub.Foo] {}\n").unwrap();
+        fs::write(
+            dir.path().join("Sub/Foo.roc"),
+            "module [foo]\n\nfoo =    1\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("notes.txt"), "not   roc").unwrap();
+
+        assert_eq!(
+            unformatted_files(dir.path()),
+            [dir.path().join("Sub/Foo.roc")]
+        );
+    }
+
+    #[test]
+    fn requires_a_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let main = dir.path().join("main.roc");
+
+        fs::write(&main, "package [] {}\n").unwrap();
+
+        let result = build_bundle(
+            &main,
+            &dir.path().join("out"),
+            RocCacheDir::Disallowed,
+            Threading::Single,
+        );
+
+        assert!(matches!(result, Err(PublishProblem::MissingManifest)));
+        assert!(!dir.path().join("out").exists());
+    }
+}
@@ -0,0 +1,78 @@
+app [main!] { pf: platform "platform/main.roc" }
+
+import pf.Host
+
+# Sorts the same kind of workload as quicksort_app.roc, but with the builtin (stable) sort,
+# so the two benchmarks can be compared. The keys have lots of ties, so the sort is only
+# correct if it is stable.
+
+main! : {} => {}
+main! = \{} ->
+    { value, is_error } = Host.get_int!({})
+    input_result =
+        if is_error then
+            Err(GetIntError)
+        else
+            Ok(value)
+
+    when input_result is
+        Ok(n) ->
+            sorted = sort(people(Num.to_u64(n)))
+
+            if is_sorted_stably(sorted) then
+                Host.put_line!("checksum: ${Num.to_str(checksum(sorted))}")
+            else
+                Host.put_line!("Error: the sort was not stable.")
+
+        Err(GetIntError) ->
+            Host.put_line!("Error: Failed to get Integer from stdin.")
+
+Person : { id : U64, age : U64, score : U64 }
+
+# Youngest first, and highest score first among people of the same age
+sort : List Person -> List Person
+sort = \list ->
+    List.sort_by_keys(
+        list,
+        [
+            \a, b -> Num.compare(a.age, b.age),
+            \a, b -> Num.compare(b.score, a.score),
+        ],
+    )
+
+# Pseudo-random people, with ids in increasing order
+people : U64 -> List Person
+people = \count ->
+    List.range({ start: At(0), end: Length(count) })
+    |> List.walk(
+        { state: 42, list: List.with_capacity(count) },
+        \{ state, list }, id ->
+            next = Num.add_wrap(Num.mul_wrap(state, 6364136223846793005), 1442695040888963407)
+            person = {
+                id,
+                age: Num.shift_right_zf_by(next, 33) % 100,
+                score: Num.shift_right_zf_by(next, 40) % 10,
+            }
+
+            { state: next, list: List.append(list, person) },
+    )
+    |> .list
+
+is_sorted_stably : List Person -> Bool
+is_sorted_stably = \sorted ->
+    List.map2(
+        sorted,
+        List.drop_first(sorted, 1),
+        \a, b ->
+            if a.age != b.age then
+                a.age < b.age
+            else if a.score != b.score then
+                a.score > b.score
+            else
+                a.id < b.id,
+    )
+    |> List.all(\in_order -> in_order)
+
+checksum : List Person -> U64
+checksum = \sorted ->
+    List.walk(sorted, 0, \sum, person -> Num.add_wrap(Num.mul_wrap(sum, 31), person.id))
@@ -38,6 +38,7 @@ mod cli_tests {
     const SUPPRESS_BUILD_HOST_WARNING_FLAG: &str =
         concatcp!("--", roc_cli::FLAG_SUPPRESS_BUILD_HOST_WARNING);
     const CHECK_FLAG: &str = concatcp!("--", roc_cli::FLAG_CHECK);
+    const DETERMINISTIC_FLAG: &str = concatcp!("--", roc_cli::FLAG_DETERMINISTIC);
     #[allow(dead_code)]
     const TARGET_FLAG: &str = concatcp!("--", roc_cli::FLAG_TARGET);
 
@@ -527,6 +528,37 @@ mod cli_tests {
             );
         }
 
+        #[test]
+        #[cfg_attr(windows, ignore)]
+        fn deterministic_build_is_reproducible() {
+            build_platform_host();
+
+            let out_dir = tempfile::tempdir().unwrap();
+            let out_path = out_dir.path().join("main");
+
+            let cli_build = ExecCli::new(
+                CMD_BUILD,
+                file_from_root(
+                    "crates/cli/tests/test-projects/fixtures/multi-dep-str",
+                    "main.roc",
+                ),
+            )
+            .arg(DETERMINISTIC_FLAG)
+            .arg(format!("--{}={}", roc_cli::FLAG_OUTPUT, out_path.display()));
+
+            cli_build.run().assert_clean_success();
+            let first = std::fs::read(&out_path).unwrap();
+
+            cli_build.run().assert_clean_success();
+            let second = std::fs::read(&out_path).unwrap();
+
+            // assert! rather than assert_eq!, so that the binaries aren't printed
+            assert!(
+                first == second,
+                "building the same app twice with {DETERMINISTIC_FLAG} gave different binaries"
+            );
+        }
+
         #[test]
         #[cfg_attr(windows, ignore)]
         fn run_multi_dep_thunk() {
@@ -1299,6 +1331,18 @@ mod cli_tests {
                 UseValgrind::Yes,
             );
         }
+
+        #[test]
+        #[cfg_attr(windows, ignore = "Command failed Exit Code: exit code: 0xc0000005")]
+        fn list_sort_app() {
+            let expected_output = "Please enter an integer\nchecksum: 13429554817756906242\n";
+            test_benchmark(
+                "list_sort_app.roc",
+                expected_output,
+                Some("20"),
+                UseValgrind::Yes,
+            );
+        }
     }
 
     #[test]
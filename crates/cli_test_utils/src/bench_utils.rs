@@ -131,3 +131,12 @@ pub fn bench_quicksort<T: Measurement>(bench_group_opt: Option<&mut BenchmarkGro
         bench_group_opt,
     );
 }
+
+pub fn bench_list_sort<T: Measurement>(bench_group_opt: Option<&mut BenchmarkGroup<T>>) {
+    exec_bench_w_input(
+        &file_from_root("crates/cli/tests/benchmarks", "list_sort_app.roc"),
+        "10000",
+        "Please enter an integer\nchecksum: 654234623294837820\n",
+        bench_group_opt,
+    );
+}
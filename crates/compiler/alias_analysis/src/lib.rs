@@ -1109,6 +1109,16 @@ fn lowlevel_spec<'a>(
 
             list_append(builder, block, update_mode_var, list, to_insert)
         }
+        ListContainsNum => {
+            // the elements are numbers, so only the list itself is read
+            let list = env.symbols[&arguments[0]];
+
+            let cell = builder.add_get_tuple_field(block, list, LIST_CELL_INDEX)?;
+
+            let _unit = builder.add_touch(block, cell)?;
+
+            builder.add_make_tuple(block, &[])
+        }
         StrToUtf8 => {
             let string = env.symbols[&arguments[0]];
 
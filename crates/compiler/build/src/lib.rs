@@ -4,5 +4,7 @@
 #![allow(clippy::large_enum_variant)]
 pub mod link;
 pub mod llvm_passes;
+pub mod profile;
 pub mod program;
 pub mod target;
+pub mod wasm_glue;
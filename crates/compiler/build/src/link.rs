@@ -27,17 +27,26 @@ pub enum LinkingStrategy {
 }
 
 /// input_paths can include the host as well as the app. e.g. &["host.o", "roc_app.o"]
+///
+/// When `deterministic` is set, the linker is asked not to embed anything that depends on
+/// when or where the build happened, e.g. timestamps or the paths of temporary object files.
 pub fn link(
     target: Target,
     output_path: PathBuf,
     input_paths: &[&str],
     link_type: LinkType,
+    deterministic: bool,
 ) -> io::Result<(Child, PathBuf)> {
     match target.arch_os() {
         (Architecture::Wasm32, _) => link_wasm32(target, output_path, input_paths, link_type),
+        // GNU ld's output only depends on its inputs already
         (_, OperatingSystem::Linux) => link_linux(target, output_path, input_paths, link_type),
-        (_, OperatingSystem::Mac) => link_macos(target, output_path, input_paths, link_type),
-        (_, OperatingSystem::Windows) => link_windows(output_path, input_paths, link_type),
+        (_, OperatingSystem::Mac) => {
+            link_macos(target, output_path, input_paths, link_type, deterministic)
+        }
+        (_, OperatingSystem::Windows) => {
+            link_windows(output_path, input_paths, link_type, deterministic)
+        }
         _ => internal_error!("TODO gracefully handle unsupported target: {:?}", target),
     }
 }
@@ -1007,6 +1016,7 @@ fn link_macos(
     output_path: PathBuf,
     input_paths: &[&str],
     link_type: LinkType,
+    deterministic: bool,
 ) -> io::Result<(Child, PathBuf)> {
     let (link_type_args, output_path) = match link_type {
         LinkType::Executable => (vec!["-execute"], output_path),
@@ -1059,6 +1069,13 @@ fn link_macos(
         ld_command.arg("-ld_classic");
     }
 
+    if deterministic {
+        // ld64 records the modification time of every object file in the debug map,
+        // unless ZERO_AR_DATE is set. The debug map also contains the absolute paths
+        // of the (temporary) object files, so leave it out altogether.
+        ld_command.env("ZERO_AR_DATE", "1").arg("-S");
+    }
+
     let sdk_path = "/Library/Developer/CommandLineTools/SDKs/MacOSX.sdk/usr/lib";
     if Path::new(sdk_path).exists() {
         ld_command.arg(format!("-L{sdk_path}"));
@@ -1194,10 +1211,19 @@ fn link_windows(
     output_path: PathBuf,
     input_paths: &[&str],
     link_type: LinkType,
+    deterministic: bool,
 ) -> io::Result<(Child, PathBuf)> {
+    let mut zig_cmd = zig();
+
+    if deterministic {
+        // zig links with lld-link, which also reads options from the `_LINK_` environment
+        // variable. `/Brepro` replaces the timestamp in the PE header with a hash of the output.
+        zig_cmd.env("_LINK_", "/Brepro");
+    }
+
     match link_type {
         LinkType::Dylib => {
-            let child = zig()
+            let child = zig_cmd
                 .args(["build-lib"])
                 .args(input_paths)
                 .args([
@@ -1214,7 +1240,7 @@ fn link_windows(
             Ok((child, output_path))
         }
         LinkType::Executable => {
-            let child = zig()
+            let child = zig_cmd
                 .args(["build-exe"])
                 .args(input_paths)
                 .args([
@@ -1263,6 +1289,7 @@ pub fn llvm_module_to_dylib(
         app_o_file.clone(),
         &[app_o_file.to_str().unwrap()],
         LinkType::Dylib,
+        false,
     )
     .unwrap();
 
@@ -0,0 +1,349 @@
+//! A breakdown of where the time and memory of a build went, for `roc build --profile-compile`.
+use roc_collections::MutMap;
+use roc_load::ModuleTiming;
+use roc_module::symbol::{Interns, ModuleId};
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use std::fmt::Write;
+use std::time::Duration;
+
+/// The front-end phases, in the order they run for each module.
+pub const FRONTEND_PHASES: [&str; 5] =
+    ["parse", "canonicalize", "constrain", "solve", "specialize"];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StageProfile {
+    pub name: &'static str,
+    /// Wall time from the start to the end of this stage.
+    #[serde(rename = "duration_ms", serialize_with = "serialize_ms")]
+    pub duration: Duration,
+    /// The number of bytes the compiler's arenas had allocated at the end of this stage.
+    /// Arenas are only freed once the build is done, so this is also their high-water mark.
+    pub peak_arena_bytes: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModuleProfile {
+    pub name: String,
+    /// Time spent in each of the [FRONTEND_PHASES], in that order.
+    #[serde(rename = "phases_ms", serialize_with = "serialize_phases_ms")]
+    pub phases: [Duration; FRONTEND_PHASES.len()],
+    /// Wall time from when the module was first read until it was done,
+    /// including time spent waiting on its dependencies.
+    #[serde(rename = "total_ms", serialize_with = "serialize_ms")]
+    pub total: Duration,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CompileProfile {
+    pub stages: Vec<StageProfile>,
+    /// Sorted so that the modules that took longest to compile come first.
+    pub modules: Vec<ModuleProfile>,
+    pub total: Duration,
+}
+
+impl CompileProfile {
+    pub fn new(interns: &Interns, timings: &MutMap<ModuleId, ModuleTiming>) -> Self {
+        let mut modules: Vec<_> = timings
+            .iter()
+            .map(|(module_id, timing)| {
+                let name = match interns.module_name(*module_id).as_str() {
+                    "" => "Application Module".to_string(),
+                    name => name.to_string(),
+                };
+
+                ModuleProfile {
+                    name,
+                    phases: [
+                        timing.read_roc_file + timing.parse_header + timing.parse_body,
+                        timing.canonicalize_solo + timing.canonicalize,
+                        timing.constrain,
+                        timing.solve,
+                        timing.find_specializations
+                            + timing.make_specializations.iter().sum::<Duration>(),
+                    ],
+                    total: timing.total(),
+                }
+            })
+            .collect();
+
+        modules.sort_by(|a, b| {
+            let a_time: Duration = a.phases.iter().sum();
+            let b_time: Duration = b.phases.iter().sum();
+
+            b_time.cmp(&a_time).then_with(|| a.name.cmp(&b.name))
+        });
+
+        CompileProfile {
+            stages: Vec::new(),
+            modules,
+            total: Duration::default(),
+        }
+    }
+
+    pub fn add_stage(&mut self, name: &'static str, duration: Duration, peak_arena_bytes: usize) {
+        self.stages.push(StageProfile {
+            name,
+            duration,
+            peak_arena_bytes,
+        });
+    }
+
+    /// How long each front-end phase took, summed over all modules.
+    /// Modules are compiled in parallel, so this can be more than the front-end's wall time.
+    pub fn frontend_phases(&self) -> [Duration; FRONTEND_PHASES.len()] {
+        let mut totals = [Duration::default(); FRONTEND_PHASES.len()];
+
+        for module in self.modules.iter() {
+            for (total, duration) in totals.iter_mut().zip(module.phases) {
+                *total += duration;
+            }
+        }
+
+        totals
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("profiles always serialize")
+    }
+
+    /// A human-readable summary, which only lists the `max_modules` slowest modules.
+    pub fn summary(&self, max_modules: usize) -> String {
+        let mut buf = String::new();
+
+        writeln!(
+            buf,
+            "Compilation profile ({} ms total)\n",
+            self.total.as_millis()
+        )
+        .unwrap();
+
+        for stage in self.stages.iter() {
+            writeln!(
+                buf,
+                "    {:<24}{:>8} ms{:>12} arena",
+                stage.name,
+                stage.duration.as_millis(),
+                format_bytes(stage.peak_arena_bytes)
+            )
+            .unwrap();
+        }
+
+        writeln!(buf, "\nFront-end phases, summed over all modules:\n").unwrap();
+
+        for (name, duration) in FRONTEND_PHASES.iter().zip(self.frontend_phases()) {
+            writeln!(buf, "    {:<24}{:>8} ms", name, duration.as_millis()).unwrap();
+        }
+
+        if !self.modules.is_empty() {
+            writeln!(buf, "\nSlowest modules:\n").unwrap();
+
+            for module in self.modules.iter().take(max_modules) {
+                let (slowest_phase, slowest_duration) = FRONTEND_PHASES
+                    .iter()
+                    .zip(module.phases)
+                    .max_by_key(|(_, duration)| *duration)
+                    .unwrap();
+
+                writeln!(
+                    buf,
+                    "    {:<24}{:>8} ms    (mostly {} at {} ms)",
+                    module.name,
+                    module.phases.iter().sum::<Duration>().as_millis(),
+                    slowest_phase,
+                    slowest_duration.as_millis()
+                )
+                .unwrap();
+            }
+
+            if self.modules.len() > max_modules {
+                writeln!(buf, "    …and {} more", self.modules.len() - max_modules).unwrap();
+            }
+        }
+
+        buf
+    }
+}
+
+impl Serialize for CompileProfile {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut profile = serializer.serialize_struct("CompileProfile", 4)?;
+
+        profile.serialize_field("total_ms", &as_ms(self.total))?;
+        profile.serialize_field("stages", &self.stages)?;
+        profile.serialize_field("frontend_phases_ms", &PhasesMs(&self.frontend_phases()))?;
+        profile.serialize_field("modules", &self.modules)?;
+
+        profile.end()
+    }
+}
+
+/// Durations of the [FRONTEND_PHASES], as a map from each phase's name to its milliseconds.
+struct PhasesMs<'a>(&'a [Duration; FRONTEND_PHASES.len()]);
+
+impl Serialize for PhasesMs<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(
+            FRONTEND_PHASES
+                .iter()
+                .zip(self.0)
+                .map(|(name, duration)| (name, as_ms(*duration))),
+        )
+    }
+}
+
+fn as_ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+fn serialize_ms<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(as_ms(*duration))
+}
+
+fn serialize_phases_ms<S: Serializer>(
+    phases: &[Duration; FRONTEND_PHASES.len()],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    PhasesMs(phases).serialize(serializer)
+}
+
+fn format_bytes(bytes: usize) -> String {
+    const KIB: usize = 1024;
+    const MIB: usize = 1024 * KIB;
+
+    if bytes >= MIB {
+        format!("{:.1} MiB", bytes as f64 / MIB as f64)
+    } else if bytes >= KIB {
+        format!("{:.1} KiB", bytes as f64 / KIB as f64)
+    } else {
+        format!("{bytes} B")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn module(name: &str, phases: [u64; FRONTEND_PHASES.len()]) -> ModuleProfile {
+        ModuleProfile {
+            name: name.to_string(),
+            phases: phases.map(ms),
+            total: ms(phases.iter().sum::<u64>() + 250),
+        }
+    }
+
+    #[test]
+    fn json_shape() {
+        let mut profile = CompileProfile {
+            stages: Vec::new(),
+            modules: vec![
+                module("Parser", [500, 250, 0, 1000, 0]),
+                module("Main", [125, 0, 0, 250, 375]),
+            ],
+            total: ms(3000),
+        };
+        profile.add_stage("load", ms(2500), 4096);
+
+        let json: serde_json::Value = serde_json::from_str(&profile.to_json()).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "total_ms": 3000.0,
+                "stages": [
+                    { "name": "load", "duration_ms": 2500.0, "peak_arena_bytes": 4096 },
+                ],
+                "frontend_phases_ms": {
+                    "parse": 625.0,
+                    "canonicalize": 250.0,
+                    "constrain": 0.0,
+                    "solve": 1250.0,
+                    "specialize": 375.0,
+                },
+                "modules": [
+                    {
+                        "name": "Parser",
+                        "total_ms": 2000.0,
+                        "phases_ms": {
+                            "parse": 500.0,
+                            "canonicalize": 250.0,
+                            "constrain": 0.0,
+                            "solve": 1000.0,
+                            "specialize": 0.0,
+                        },
+                    },
+                    {
+                        "name": "Main",
+                        "total_ms": 1000.0,
+                        "phases_ms": {
+                            "parse": 125.0,
+                            "canonicalize": 0.0,
+                            "constrain": 0.0,
+                            "solve": 250.0,
+                            "specialize": 375.0,
+                        },
+                    },
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn slowest_modules_come_first() {
+        let mut interns = Interns::default();
+        let mut timings = MutMap::default();
+
+        for (name, solve) in [
+            ("Fast", 10),
+            ("Slow", 30),
+            ("Medium", 20),
+            ("AlsoMedium", 20),
+        ] {
+            let module_id = interns.module_ids.get_or_insert(&name.into());
+            let mut timing = ModuleTiming::new(Instant::now());
+            timing.solve = ms(solve);
+
+            timings.insert(module_id, timing);
+        }
+
+        let profile = CompileProfile::new(&interns, &timings);
+        let names: Vec<&str> = profile.modules.iter().map(|m| m.name.as_str()).collect();
+
+        // Ties are broken by name, so that the order doesn't depend on the hash map's.
+        assert_eq!(names, ["Slow", "AlsoMedium", "Medium", "Fast"]);
+    }
+
+    #[test]
+    fn summary_truncates_modules() {
+        let profile = CompileProfile {
+            stages: Vec::new(),
+            modules: vec![
+                module("Slow", [0, 0, 0, 30, 0]),
+                module("Medium", [5, 0, 0, 10, 0]),
+                module("Fast", [0, 0, 0, 1, 0]),
+            ],
+            total: ms(100),
+        };
+
+        let summary = profile.summary(2);
+        let modules: Vec<&str> = summary
+            .lines()
+            .skip_while(|line| *line != "Slowest modules:")
+            .skip(2)
+            .collect();
+
+        assert_eq!(
+            modules,
+            [
+                "    Slow                          30 ms    (mostly solve at 30 ms)",
+                "    Medium                        15 ms    (mostly solve at 10 ms)",
+                "    …and 1 more",
+            ]
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use crate::link::{link, preprocess_host_wasm32, rebuild_host, LinkType, LinkingStrategy};
+use crate::profile::CompileProfile;
 use bumpalo::collections::CollectIn;
 use bumpalo::Bump;
 use inkwell::memory_buffer::MemoryBuffer;
@@ -10,10 +11,13 @@ use roc_load::{
     EntryPoint, ExecutionMode, ExpectMetadata, FunctionKind, LoadConfig, LoadMonomorphizedError,
     LoadedModule, LoadingProblem, MonomorphizedModule, Threading,
 };
-use roc_mono::ir::{OptLevel, SingleEntryPoint};
+use roc_mono::borrow_report::BorrowReport;
+use roc_mono::ir::{match_trees_to_dot, OptLevel, SingleEntryPoint};
+use roc_mono::layout::describe::LayoutDescription;
+use roc_mono::specialization_report::SpecializationReport;
 use roc_packaging::cache::RocCacheDir;
 use roc_reporting::{
-    cli::{report_problems, Problems},
+    cli::{report_mono_problems, report_problems, Problems},
     report::{RenderTarget, DEFAULT_PALETTE},
 };
 use roc_target::{Architecture, Target};
@@ -85,6 +89,36 @@ pub struct CodeGenOptions {
     pub emit_debug_info: bool,
     pub emit_llvm_ir: bool,
     pub fuzz: bool,
+    /// Make the output binary depend only on the source code, so that identical inputs
+    /// produce byte-identical binaries (e.g. for reproducible builds and caching).
+    pub deterministic: bool,
+    /// When building for wasm32, also write a JavaScript file next to the wasm module that
+    /// instantiates it and exposes its entry points. See [crate::wasm_glue].
+    pub emit_js_glue: bool,
+    /// Also write a frame table next to the binary, which hosts can use to print a Roc stack
+    /// trace when the app crashes. See [roc_mono::frames].
+    pub emit_frame_table: bool,
+    /// Also write the specialized IR next to the source file, in the textual form described in
+    /// [roc_mono::ir::text].
+    pub emit_mono_ir: bool,
+    /// Also write a report of which layouts each def was specialized for, and how much code that
+    /// generated, next to the source file. See [roc_mono::specialization_report].
+    pub emit_specialization_report: bool,
+    /// Warn about closures whose captures are large or stored on the heap.
+    /// See [roc_mono::closure_captures].
+    pub warn_closure_captures: bool,
+    /// Also write a report of which parameters borrow inference made owned or borrowed, next to
+    /// the source file. See [roc_mono::borrow_report].
+    pub emit_borrow_report: bool,
+    /// Also write a report of the record fields that were removed because they're never read,
+    /// next to the source file. See [roc_mono::dead_fields].
+    pub emit_dead_field_report: bool,
+    /// Also write the decision tree that each `when` was compiled to, as a DOT graph, next to the
+    /// source file. Takes effect through [LoadConfig::emit_match_trees], which must be set too.
+    pub emit_match_trees: bool,
+    /// Also write how many bytes each symbol of the app and the platform adds to the executable,
+    /// next to the source file. Only supported by the surgical linker. See [roc_linker::SizeReport].
+    pub emit_size_report: bool,
 }
 
 type GenFromMono<'a> = (CodeObject, CodeGenTiming, ExpectMetadata<'a>);
@@ -99,13 +133,76 @@ pub fn gen_from_mono_module<'a>(
     built_host_opt: &BuiltHostOpt,
     wasm_dev_stack_bytes: Option<u32>,
 ) -> GenFromMono<'a> {
+    let _span = roc_tracing::phase_span!("codegen", backend = ?code_gen_options.backend);
     let path = roc_file_path;
     let debug = code_gen_options.emit_debug_info;
     let emit_llvm_ir = code_gen_options.emit_llvm_ir;
     let fuzz = code_gen_options.fuzz;
+    let deterministic = code_gen_options.deterministic;
     let opt = code_gen_options.opt_level;
 
-    match code_gen_options.backend {
+    if code_gen_options.emit_mono_ir {
+        let mono_ir_file = roc_file_path.with_extension("mono");
+        let text = roc_mono::ir::text::render_procs(
+            loaded.procedures.values(),
+            &loaded.layout_interner,
+            None,
+        );
+
+        eprintln!("Emitting mono IR to {}", mono_ir_file.display());
+
+        std::fs::write(&mono_ir_file, text).expect("Failed to write mono IR file");
+    }
+
+    if code_gen_options.emit_borrow_report {
+        let report_file = roc_file_path.with_extension("borrows");
+        let report = BorrowReport::new(
+            &loaded.procedures,
+            &loaded.inferred_borrows,
+            &loaded.layout_interner,
+            &loaded.interns,
+        );
+
+        eprintln!(
+            "Emitting borrow report to {}\n\n{}",
+            report_file.display(),
+            report.summary()
+        );
+
+        std::fs::write(&report_file, report.to_string()).expect("Failed to write borrow report");
+    }
+
+    if code_gen_options.emit_dead_field_report {
+        let report_file = roc_file_path.with_extension("dead-fields");
+        let report = roc_mono::dead_fields::dead_field_report(
+            &loaded.dead_fields,
+            &loaded.layout_interner,
+            &loaded.interns,
+        );
+
+        eprintln!(
+            "Emitting dead field report to {}\n\n{}",
+            report_file.display(),
+            roc_mono::dead_fields::dead_field_summary(&loaded.dead_fields)
+        );
+
+        std::fs::write(&report_file, report).expect("Failed to write dead field report");
+    }
+
+    if code_gen_options.emit_match_trees {
+        let dot_file = roc_file_path.with_extension("match-trees.dot");
+        let dot = match_trees_to_dot(&loaded.match_trees, &loaded.sources, &loaded.interns);
+
+        eprintln!("Emitting match trees to {}", dot_file.display());
+
+        std::fs::write(&dot_file, dot).expect("Failed to write match trees");
+    }
+
+    let mut specialization_report = code_gen_options.emit_specialization_report.then(|| {
+        SpecializationReport::new(&loaded.procedures, &loaded.layout_interner, &loaded.interns)
+    });
+
+    let gen_from_mono = match code_gen_options.backend {
         CodeGenBackend::Wasm => {
             assert_ne!(
                 *built_host_opt,
@@ -140,8 +237,25 @@ pub fn gen_from_mono_module<'a>(
             debug,
             emit_llvm_ir,
             fuzz,
+            deterministic,
+            specialization_report.as_mut(),
         ),
+    };
+
+    if let Some(report) = specialization_report {
+        let report_file = roc_file_path.with_extension("specializations");
+
+        eprintln!(
+            "Emitting specialization report to {}\n\n{}",
+            report_file.display(),
+            report.summary(10)
+        );
+
+        std::fs::write(&report_file, report.to_string())
+            .expect("Failed to write specialization report");
     }
+
+    gen_from_mono
 }
 
 // TODO how should imported modules factor into this? What if those use builtins too?
@@ -158,6 +272,8 @@ fn gen_from_mono_module_llvm<'a>(
     emit_debug_info: bool,
     emit_llvm_ir: bool,
     fuzz: bool,
+    deterministic: bool,
+    specialization_report: Option<&mut SpecializationReport>,
 ) -> GenFromMono<'a> {
     use crate::target::{self, convert_opt_level};
     use inkwell::attributes::{Attribute, AttributeLoc};
@@ -223,6 +339,10 @@ fn gen_from_mono_module_llvm<'a>(
             .keys()
             .copied()
             .collect(),
+        coverage: None,
+        profile_hints: Some(arena.alloc(loaded.profile_hints)),
+        deterministic,
+        proc_names: Default::default(),
     };
 
     // does not add any externs for this mode (we have a host) but cleans up some functions around
@@ -265,6 +385,17 @@ fn gen_from_mono_module_llvm<'a>(
 
     crate::llvm_passes::optimize_llvm_ir(&env, target, opt_level, emit_debug_info, &app_ll_file);
 
+    if let Some(report) = specialization_report {
+        let function_names: Vec<_> = env
+            .module
+            .get_functions()
+            .filter(|function| function.count_basic_blocks() > 0)
+            .map(|function| function.get_name().to_string_lossy().into_owned())
+            .collect();
+
+        report.mark_inlined(function_names.iter().map(String::as_str));
+    }
+
     let gen_sanitizers = cfg!(feature = "sanitizers") && std::env::var("ROC_SANITIZERS").is_ok();
     let memory_buffer = if fuzz || gen_sanitizers {
         let dir = tempfile::tempdir().unwrap();
@@ -275,9 +406,6 @@ fn gen_from_mono_module_llvm<'a>(
         let temp_app_processed_file_str = temp_app_processed_file.to_str().unwrap().to_owned();
         let temp_app_o_file = dir.join("app.o");
 
-        // write the ll code to a file, so we can modify it
-        module.print_to_file(&temp_app_ll_file).unwrap();
-
         // Apply coverage passes.
         // Note, this is specifically tailored for `cargo afl` and afl++.
         // It most likely will not work with other fuzzer setups without modification.
@@ -299,12 +427,32 @@ fn gen_from_mono_module_llvm<'a>(
                 .split(',')
                 .map(|x| x.trim())
             {
-                match sanitizer {
-                    "address" => passes.push("asan-module"),
-                    "memory" => passes.push("msan-module"),
-                    "thread" => passes.push("tsan-module"),
-                    x => unrecognized.push(x.to_owned()),
+                // The sanitizer passes only instrument functions that have this attribute.
+                // Binaries and libraries don't use setjmp/longjmp (only test runs do), so the
+                // instrumented code doesn't switch stacks behind the sanitizer's back.
+                let (attribute, sanitizer_passes): (&str, &[&str]) = match sanitizer {
+                    "address" => ("sanitize_address", &["asan"]),
+                    "memory" => ("sanitize_memory", &["msan"]),
+                    "thread" => ("sanitize_thread", &["tsan-module", "function(tsan)"]),
+                    x => {
+                        unrecognized.push(x.to_owned());
+                        continue;
+                    }
+                };
+
+                let kind_id = Attribute::get_named_enum_kind_id(attribute);
+                debug_assert!(kind_id > 0);
+
+                for function in module.get_functions() {
+                    if function.count_basic_blocks() > 0 {
+                        function.add_attribute(
+                            AttributeLoc::Function,
+                            context.create_enum_attribute(kind_id, 0),
+                        );
+                    }
                 }
+
+                passes.extend_from_slice(sanitizer_passes);
             }
         }
         if !unrecognized.is_empty() {
@@ -317,6 +465,9 @@ fn gen_from_mono_module_llvm<'a>(
             eprintln!("Note: \"cargo-fuzz\" and \"afl.rs\" both enable sanitizer coverage for fuzzing. They just use different parameters to match the respective libraries.")
         }
 
+        // write the ll code to a file, so we can modify it
+        module.print_to_file(&temp_app_ll_file).unwrap();
+
         use std::process::Command;
 
         // apply passes to app.ll
@@ -615,6 +766,7 @@ pub struct BuiltFile<'a> {
     pub problems: Problems,
     pub total_time: Duration,
     pub expect_metadata: ExpectMetadata<'a>,
+    pub profile: CompileProfile,
 }
 
 pub enum BuildOrdering {
@@ -707,6 +859,11 @@ pub fn standard_load_config(
         palette: DEFAULT_PALETTE,
         threading,
         exec_mode,
+        emit_match_trees: false,
+        trace_abilities: false,
+        profile: None,
+        doc_examples: 0,
+        comptime_limits: roc_load::ComptimeLimits::default(),
     }
 }
 
@@ -831,6 +988,9 @@ fn build_loaded_file<'a>(
     out_path: Option<&Path>,
     verbose: bool,
 ) -> Result<BuiltFile<'a>, BuildFileError<'a>> {
+    let frontend_end = Instant::now();
+    let frontend_worker_arena_bytes = loaded.worker_arena_bytes;
+
     // get the platform path from the app header
     let platform_main_roc_path = match &loaded.entry_point {
         EntryPoint::Executable { platform_path, .. } => platform_path.to_path_buf(),
@@ -877,6 +1037,14 @@ fn build_loaded_file<'a>(
             }
         };
 
+    let mut profile = CompileProfile::new(&loaded.interns, &loaded.timings);
+
+    profile.add_stage(
+        "frontend",
+        frontend_end.duration_since(compilation_start),
+        arena.allocated_bytes() + frontend_worker_arena_bytes,
+    );
+
     let buf = &mut String::with_capacity(1024);
 
     let mut it = loaded.timings.iter().peekable();
@@ -905,9 +1073,49 @@ fn build_loaded_file<'a>(
     // This only needs to be mutable for report_problems. This can't be done
     // inside a nested scope without causing a borrow error!
     let mut loaded = loaded;
-    let problems = report_problems_monomorphized(&mut loaded);
+    let mut problems = report_problems_monomorphized(&mut loaded);
     let loaded = loaded;
 
+    let mut mono_problems = roc_mono::tail_recursion::tail_call_problems(
+        &loaded.procedures,
+        &loaded.tail_recursive_defs,
+        &loaded.interns,
+    );
+
+    mono_problems.extend(roc_mono::comptime::comptime_problems(
+        &loaded.comptime_failures,
+        &loaded.interns,
+    ));
+
+    problems.errors += report_mono_problems(&loaded.sources, &loaded.interns, mono_problems);
+
+    if code_gen_options.warn_closure_captures {
+        let captures = roc_mono::closure_captures::closure_captures(
+            &loaded.procedures,
+            &loaded.layout_interner,
+            &loaded.closures,
+            &loaded.interns,
+        );
+
+        for warning in roc_mono::closure_captures::capture_warnings(
+            &captures,
+            &loaded.closures,
+            &loaded.sources,
+            &loaded.interns,
+        ) {
+            eprintln!("{warning}");
+        }
+    }
+
+    let frame_table = code_gen_options.emit_frame_table.then(|| {
+        roc_mono::frames::frame_table(
+            loaded.procedures.keys().map(|(symbol, _)| symbol),
+            &loaded.def_regions,
+            &loaded.sources,
+            &loaded.interns,
+        )
+    });
+
     let (roc_app_bytes, code_gen_timing, expect_metadata) = gen_from_mono_module(
         arena,
         loaded,
@@ -932,6 +1140,12 @@ fn build_loaded_file<'a>(
     buf.push('\n');
     report_timing(buf, "Total", code_gen_timing.total);
 
+    profile.add_stage(
+        "codegen",
+        code_gen_timing.total,
+        arena.allocated_bytes() + frontend_worker_arena_bytes,
+    );
+
     let compilation_end = compilation_start.elapsed();
     let size = roc_app_bytes.len();
 
@@ -949,18 +1163,37 @@ fn build_loaded_file<'a>(
 
     // link the prebuilt platform and compiled app
     let link_start = Instant::now();
+    let link_span = roc_tracing::phase_span!("link", strategy = ?linking_strategy);
 
     match (linking_strategy, link_type) {
         (LinkingStrategy::Surgical, _) => {
             let metadata_file = platform_main_roc_path.with_file_name(target.metadata_file_name());
 
-            roc_linker::link_preprocessed_host(
+            let size_report = roc_linker::link_preprocessed_host(
                 target,
                 &roc_app_bytes,
                 &output_exe_path,
                 metadata_file,
                 verbose,
+                code_gen_options.emit_size_report,
             );
+
+            if let Some(report) = size_report {
+                let report_file = app_module_path.with_extension("sizes");
+
+                eprintln!(
+                    "Emitting size report to {}\n\n{}",
+                    report_file.display(),
+                    report.summary()
+                );
+
+                std::fs::write(&report_file, report.to_string())
+                    .expect("Failed to write size report");
+            } else if code_gen_options.emit_size_report {
+                eprintln!(
+                    "Size reports are only supported when surgically linking ELF executables."
+                );
+            }
         }
         (LinkingStrategy::Additive, _) | (LinkingStrategy::Legacy, LinkType::None) => {
             // Just copy the object file to the output folder.
@@ -1022,8 +1255,14 @@ fn build_loaded_file<'a>(
                 inputs.push(builtins_host_tempfile.path().to_str().unwrap());
             }
 
-            let (mut child, _) = link(target, output_exe_path.clone(), &inputs, link_type)
-                .map_err(|_| todo!("linker failed to spawn."))?;
+            let (mut child, _) = link(
+                target,
+                output_exe_path.clone(),
+                &inputs,
+                link_type,
+                code_gen_options.deterministic,
+            )
+            .map_err(|_| todo!("linker failed to spawn."))?;
 
             let exit_status = child
                 .wait()
@@ -1039,19 +1278,53 @@ fn build_loaded_file<'a>(
         }
     }
 
+    if target == Target::Wasm32
+        && code_gen_options.emit_js_glue
+        && matches!(link_type, LinkType::Executable)
+    {
+        if let Err(err) = write_js_glue(&output_exe_path) {
+            internal_error!(
+                "Failed to generate JavaScript glue for {}: {err}",
+                output_exe_path.display()
+            );
+        }
+    }
+
+    if let Some(frame_table) = frame_table {
+        let frame_table_path = roc_mono::frames::frame_table_path(&output_exe_path);
+
+        if let Err(err) = std::fs::write(&frame_table_path, frame_table) {
+            internal_error!(
+                "Failed to write the frame table to {}: {err}",
+                frame_table_path.display()
+            );
+        }
+    }
+
+    drop(link_span);
+
     let linking_time = link_start.elapsed();
 
     if emit_timings {
         println!("Finished linking in {} ms\n", linking_time.as_millis());
     }
 
+    profile.add_stage(
+        "link",
+        linking_time,
+        arena.allocated_bytes() + frontend_worker_arena_bytes,
+    );
+
     let total_time = compilation_start.elapsed();
 
+    profile.total = total_time;
+
     Ok(BuiltFile {
         binary_path: output_exe_path,
         problems,
         total_time,
         expect_metadata,
+        profile,
     })
 }
 
@@ -1090,6 +1363,7 @@ fn determine_built_host_path(
                     Ok(legacy_host_path) => BuiltHostOpt::Legacy(legacy_host_path),
                     Err(err_msg) => {
                         eprintln!("Legacy linking failed: {}", err_msg);
+                        report_unsupported_target(platform_main_roc_path, target);
                         #[cfg(target_os = "linux")]
                         eprintln!(
                             "\n    TIP: Maybe try surgical linking with the flag --linker=surgical"
@@ -1110,6 +1384,7 @@ fn determine_built_host_path(
                             I tried to find the surgical host at any of these paths {} but it does not exist.",
                             paths_str
                         );
+                        report_unsupported_target(platform_main_roc_path, target);
                         std::process::exit(1);
                     }
                 }
@@ -1121,6 +1396,22 @@ fn determine_built_host_path(
     }
 }
 
+/// Writes the JavaScript wrapper for a wasm module next to it, e.g. `app.js` for `app.wasm`.
+fn write_js_glue(wasm_path: &Path) -> Result<(), String> {
+    let bytes = std::fs::read(wasm_path).map_err(|err| err.to_string())?;
+    let interface = crate::wasm_glue::WasmInterface::parse(&bytes)?;
+    let wasm_file_name = wasm_path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+
+    std::fs::write(
+        wasm_path.with_extension("js"),
+        crate::wasm_glue::js_glue(&wasm_file_name, &interface),
+    )
+    .map_err(|err| err.to_string())
+}
+
 /// Get outut path for the executable.
 ///
 /// If you specified a path that ends in in a directory separator, then
@@ -1167,6 +1458,35 @@ fn get_exe_path(
     }
 }
 
+/// Explain which targets the platform does support, e.g. when cross-compiling to a target it wasn't built for.
+fn report_unsupported_target(platform_main_roc_path: &Path, target: Target) {
+    let available = Target::targets_with_prebuilt_host(platform_main_roc_path);
+
+    if available.is_empty() {
+        return;
+    }
+
+    if available.contains(&target) {
+        eprintln!(
+            "\n    TIP: This platform has a prebuilt host for {target}, \
+            but not for this linking strategy. Try the other one with the --linker flag."
+        );
+    } else {
+        let available = available
+            .iter()
+            .map(|target| target.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        eprintln!(
+            "\n    This platform does not support the target {target}. \
+            It has prebuilt hosts for: {available}\n\
+            \n    TIP: Choose one of those with the --target flag, \
+            or ask the platform's author to publish a host for {target}."
+        );
+    }
+}
+
 #[allow(dead_code)]
 fn report_rebuilding_existing_host(host_path: &str) {
     eprintln!(
@@ -1350,6 +1670,7 @@ pub fn check_file<'a>(
     emit_timings: bool,
     roc_cache_dir: RocCacheDir<'_>,
     threading: Threading,
+    trace_abilities: bool,
 ) -> Result<(Problems, Duration), LoadingProblem<'a>> {
     let compilation_start = Instant::now();
 
@@ -1367,6 +1688,11 @@ pub fn check_file<'a>(
         palette: DEFAULT_PALETTE,
         threading,
         exec_mode: ExecutionMode::Check,
+        emit_match_trees: false,
+        trace_abilities,
+        profile: None,
+        doc_examples: 0,
+        comptime_limits: roc_load::ComptimeLimits::default(),
     };
     let mut loaded = roc_load::load_and_typecheck(
         arena,
@@ -1376,6 +1702,10 @@ pub fn check_file<'a>(
         load_config,
     )?;
 
+    if trace_abilities {
+        eprint!("{}", loaded.ability_trace.to_json_lines(&loaded.interns));
+    }
+
     let buf = &mut String::with_capacity(1024);
 
     let mut it = loaded.timings.iter().peekable();
@@ -1421,6 +1751,72 @@ pub fn check_file<'a>(
     Ok((report_problems_typechecked(&mut loaded), compilation_end))
 }
 
+/// Check the file twice, on one thread and on `threading`, and compare the results, for
+/// `roc check --audit-determinism`. See [roc_load::audit_determinism].
+pub fn audit_determinism<'a>(
+    arena: &'a Bump,
+    roc_file_path: PathBuf,
+    opt_main_path: Option<PathBuf>,
+    roc_cache_dir: RocCacheDir<'_>,
+    threading: Threading,
+) -> Result<roc_load::DeterminismAudit, LoadingProblem<'a>> {
+    // Like in `check_file`, the target only matters for errors.
+    let load_config = LoadConfig {
+        target: Target::LinuxX64,
+        function_kind: FunctionKind::from_env(),
+        render: RenderTarget::ColorTerminal,
+        palette: DEFAULT_PALETTE,
+        threading,
+        exec_mode: ExecutionMode::Check,
+        emit_match_trees: false,
+        trace_abilities: false,
+        profile: None,
+        doc_examples: 0,
+        comptime_limits: roc_load::ComptimeLimits::default(),
+    };
+
+    roc_load::audit_determinism(
+        arena,
+        roc_file_path,
+        opt_main_path,
+        roc_cache_dir,
+        load_config,
+    )
+}
+
+/// Find the memory layout of `type_annotation` in the module at `roc_file_path` on `target`,
+/// for `roc layout`. See [roc_load::query_layout].
+pub fn query_layout<'a>(
+    arena: &'a Bump,
+    roc_file_path: PathBuf,
+    type_annotation: &str,
+    target: Target,
+    roc_cache_dir: RocCacheDir<'_>,
+    threading: Threading,
+) -> Result<LayoutDescription, roc_load::LayoutQueryError<'a>> {
+    let load_config = LoadConfig {
+        target,
+        function_kind: FunctionKind::from_env(),
+        render: RenderTarget::ColorTerminal,
+        palette: DEFAULT_PALETTE,
+        threading,
+        exec_mode: ExecutionMode::Check,
+        emit_match_trees: false,
+        trace_abilities: false,
+        profile: None,
+        doc_examples: 0,
+        comptime_limits: roc_load::ComptimeLimits::default(),
+    };
+
+    roc_load::query_layout(
+        arena,
+        roc_file_path,
+        type_annotation,
+        roc_cache_dir,
+        load_config,
+    )
+}
+
 pub fn build_str_test<'a>(
     arena: &'a Bump,
     app_module_path: &Path,
@@ -1435,6 +1831,16 @@ pub fn build_str_test<'a>(
         emit_debug_info: false,
         emit_llvm_ir: false,
         fuzz: false,
+        deterministic: false,
+        emit_js_glue: false,
+        emit_frame_table: false,
+        emit_mono_ir: false,
+        emit_specialization_report: false,
+        warn_closure_captures: false,
+        emit_borrow_report: false,
+        emit_dead_field_report: false,
+        emit_match_trees: false,
+        emit_size_report: false,
     };
 
     let emit_timings = false;
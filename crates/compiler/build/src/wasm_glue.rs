@@ -0,0 +1,392 @@
+//! Generates a JavaScript wrapper for a wasm32 build, so that it can be used from a web project
+//! without hand-written glue code. The wrapper is derived from the imports and exports of the final
+//! wasm module, so it works for any platform.
+
+/// A function the wasm module imports from its host.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WasmImport {
+    pub module: String,
+    pub name: String,
+}
+
+/// The function imports and the exports (of any kind) of a wasm module.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WasmInterface {
+    pub imports: Vec<WasmImport>,
+    pub exports: Vec<String>,
+}
+
+const SECTION_IMPORT: u8 = 2;
+const SECTION_EXPORT: u8 = 7;
+
+const IMPORT_FUNCTION: u8 = 0;
+const IMPORT_TABLE: u8 = 1;
+const IMPORT_MEMORY: u8 = 2;
+const IMPORT_GLOBAL: u8 = 3;
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn byte(&mut self) -> Result<u8, String> {
+        let byte = *self
+            .bytes
+            .get(self.offset)
+            .ok_or_else(|| format!("Unexpected end of wasm module at byte {}", self.offset))?;
+
+        self.offset += 1;
+
+        Ok(byte)
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        let mut result = 0u32;
+
+        for shift in (0..35).step_by(7) {
+            let byte = self.byte()?;
+
+            result |= ((byte & 0x7f) as u32) << shift;
+
+            if byte & 0x80 == 0 {
+                return Ok(result);
+            }
+        }
+
+        Err(format!("Invalid LEB128 integer at byte {}", self.offset))
+    }
+
+    fn slice(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let slice = self
+            .bytes
+            .get(self.offset..self.offset + len)
+            .ok_or_else(|| format!("Unexpected end of wasm module at byte {}", self.offset))?;
+
+        self.offset += len;
+
+        Ok(slice)
+    }
+
+    fn name(&mut self) -> Result<String, String> {
+        let len = self.u32()? as usize;
+
+        String::from_utf8(self.slice(len)?.to_vec())
+            .map_err(|_| format!("Invalid UTF-8 name at byte {}", self.offset))
+    }
+
+    fn limits(&mut self) -> Result<(), String> {
+        let has_max = self.byte()? & 1 == 1;
+
+        self.u32()?;
+
+        if has_max {
+            self.u32()?;
+        }
+
+        Ok(())
+    }
+}
+
+impl WasmInterface {
+    pub fn parse(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.get(0..4) != Some(b"\0asm") {
+            return Err("Not a wasm module".to_string());
+        }
+
+        let mut reader = Reader { bytes, offset: 8 };
+        let mut interface = WasmInterface::default();
+
+        while reader.offset < bytes.len() {
+            let section_id = reader.byte()?;
+            let section_len = reader.u32()? as usize;
+            let section_end = reader.offset + section_len;
+
+            match section_id {
+                SECTION_IMPORT => {
+                    for _ in 0..reader.u32()? {
+                        let module = reader.name()?;
+                        let name = reader.name()?;
+
+                        match reader.byte()? {
+                            IMPORT_FUNCTION => {
+                                reader.u32()?;
+                                interface.imports.push(WasmImport { module, name });
+                            }
+                            IMPORT_TABLE => {
+                                reader.byte()?;
+                                reader.limits()?;
+                            }
+                            IMPORT_MEMORY => reader.limits()?,
+                            IMPORT_GLOBAL => {
+                                reader.byte()?;
+                                reader.byte()?;
+                            }
+                            other => return Err(format!("Unknown import kind {other}")),
+                        }
+                    }
+                }
+                SECTION_EXPORT => {
+                    for _ in 0..reader.u32()? {
+                        interface.exports.push(reader.name()?);
+                        reader.byte()?;
+                        reader.u32()?;
+                    }
+                }
+                _ => {}
+            }
+
+            reader.offset = section_end;
+        }
+
+        Ok(interface)
+    }
+}
+
+/// Default implementations of imports that every Roc wasm module may have.
+/// Platforms' own imports have to be passed in by the page that uses the wrapper.
+const DEFAULT_IMPORTS: &str = r#"  const defaults = {
+    wasi_snapshot_preview1: {
+      proc_exit: (code) => {
+        exit_code = code;
+      },
+    },
+    env: {
+      roc_panic: (_pointer, _tag_id) => {
+        throw new Error("Roc panicked!");
+      },
+      roc_dbg: (_loc, _msg) => {
+        throw new Error("Roc dbg is not supported in the browser!");
+      },
+    },
+  };"#;
+
+/// A JavaScript file that loads the wasm module and exposes its exports.
+///
+/// The generated `roc_init` function takes the URL or bytes of the wasm module, and an object
+/// with the host's implementations of the module's imports (in the same shape as a
+/// `WebAssembly.Imports` object). Imports that the host doesn't provide, and that have no default,
+/// throw an error when called.
+pub fn js_glue(wasm_file_name: &str, interface: &WasmInterface) -> String {
+    let mut js = String::new();
+
+    js.push_str(&format!(
+        "// Generated by `roc build` for {wasm_file_name}. Do not edit by hand.\n//\n"
+    ));
+    js.push_str("// Imports (pass these to roc_init as host_imports):\n");
+
+    for import in interface.imports.iter() {
+        js.push_str(&format!("//   {}.{}\n", import.module, import.name));
+    }
+
+    js.push_str("// Exports:\n");
+
+    for export in interface.exports.iter() {
+        js.push_str(&format!("//   {export}\n"));
+    }
+
+    js.push_str("\nasync function roc_init(wasm_source, host_imports = {}) {\n");
+    js.push_str("  let exit_code;\n\n");
+    js.push_str(DEFAULT_IMPORTS);
+    js.push_str("\n\n  const import_names = [\n");
+
+    for import in interface.imports.iter() {
+        js.push_str(&format!("    [{:?}, {:?}],\n", import.module, import.name));
+    }
+
+    js.push_str(
+        r#"  ];
+  // Start from everything the host provides, so non-function imports (e.g. memory) are kept
+  const import_obj = {};
+  for (const module of Object.keys(host_imports)) {
+    import_obj[module] = { ...host_imports[module] };
+  }
+
+  for (const [module, name] of import_names) {
+    import_obj[module] = import_obj[module] || {};
+    import_obj[module][name] =
+      (host_imports[module] && host_imports[module][name]) ||
+      (defaults[module] && defaults[module][name]) ||
+      (() => {
+        throw new Error(`The host does not implement ${module}.${name}`);
+      });
+  }
+
+  let wasm;
+  if (wasm_source instanceof ArrayBuffer || ArrayBuffer.isView(wasm_source)) {
+    wasm = await WebAssembly.instantiate(wasm_source, import_obj);
+  } else {
+    const fetch_promise = fetch(wasm_source);
+
+    if (WebAssembly.instantiateStreaming) {
+      // The streaming API can start compiling before all the bytes have been fetched
+      wasm = await WebAssembly.instantiateStreaming(fetch_promise, import_obj);
+    } else {
+      const response = await fetch_promise;
+      wasm = await WebAssembly.instantiate(await response.arrayBuffer(), import_obj);
+    }
+  }
+
+  const exports = wasm.instance.exports;
+
+  return {
+    exports,
+"#,
+    );
+
+    if interface.exports.iter().any(|export| export == "memory") {
+        js.push_str(
+            "    memory_bytes: () => new Uint8Array(exports.memory.buffer),
+",
+        );
+    }
+
+    if interface.exports.iter().any(|export| export == "_start") {
+        js.push_str(
+            r#"    // Runs the app's main function, and returns its exit code.
+    run: () => {
+      try {
+        exports._start();
+      } catch (e) {
+        // proc_exit is implemented by trapping. Browsers word the trap's message differently,
+        // so only check that it was a trap, and that proc_exit set the exit code before it.
+        if (!(e instanceof WebAssembly.RuntimeError && exit_code !== undefined)) {
+          throw e;
+        }
+      }
+
+      return exit_code || 0;
+    },
+"#,
+        );
+    }
+
+    js.push_str(
+        r#"  };
+}
+
+if (typeof module !== "undefined") {
+  module.exports = { roc_init };
+}
+"#,
+    );
+
+    js
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn name(bytes: &mut Vec<u8>, name: &str) {
+        bytes.push(name.len() as u8);
+        bytes.extend_from_slice(name.as_bytes());
+    }
+
+    fn section(bytes: &mut Vec<u8>, id: u8, contents: &[u8]) {
+        bytes.push(id);
+        bytes.push(contents.len() as u8);
+        bytes.extend_from_slice(contents);
+    }
+
+    /// A module that imports a function and a memory, and exports `_start` and `memory`
+    fn module() -> Vec<u8> {
+        let mut bytes = b"\0asm\x01\0\0\0".to_vec();
+
+        // A type section, which should be skipped: one function type, () -> ()
+        section(&mut bytes, 1, &[1, 0x60, 0, 0]);
+
+        let mut imports = vec![2];
+        name(&mut imports, "env");
+        name(&mut imports, "js_log");
+        imports.extend_from_slice(&[IMPORT_FUNCTION, 0]);
+        name(&mut imports, "env");
+        name(&mut imports, "memory");
+        imports.extend_from_slice(&[IMPORT_MEMORY, 1, 1, 0x80, 0x01]);
+        section(&mut bytes, SECTION_IMPORT, &imports);
+
+        let mut exports = vec![2];
+        name(&mut exports, "_start");
+        exports.extend_from_slice(&[0, 1]);
+        name(&mut exports, "memory");
+        exports.extend_from_slice(&[2, 0]);
+        section(&mut bytes, SECTION_EXPORT, &exports);
+
+        bytes
+    }
+
+    #[test]
+    fn parse_imports_and_exports() {
+        let interface = WasmInterface::parse(&module()).unwrap();
+
+        assert_eq!(
+            interface,
+            WasmInterface {
+                imports: vec![WasmImport {
+                    module: "env".to_string(),
+                    name: "js_log".to_string(),
+                }],
+                exports: vec!["_start".to_string(), "memory".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn parse_rejects_other_files() {
+        assert!(WasmInterface::parse(b"\x7fELF\x02\x01\x01\0").is_err());
+    }
+
+    #[test]
+    fn parse_rejects_truncated_module() {
+        let bytes = module();
+
+        assert!(WasmInterface::parse(&bytes[..bytes.len() - 3]).is_err());
+    }
+
+    #[test]
+    fn js_lists_imports_and_exports() {
+        let js = js_glue("app.wasm", &WasmInterface::parse(&module()).unwrap());
+
+        assert!(js.starts_with("// Generated by `roc build` for app.wasm."));
+        assert!(js.contains("//   env.js_log\n"));
+        assert!(js.contains("//   _start\n//   memory\n"));
+        assert!(js.contains("    [\"env\", \"js_log\"],\n"));
+    }
+
+    #[test]
+    fn js_run_catches_proc_exit_trap_in_any_browser() {
+        let js = js_glue("app.wasm", &WasmInterface::parse(&module()).unwrap());
+
+        assert!(js.contains("run: () => {"));
+        assert!(js.contains("e instanceof WebAssembly.RuntimeError && exit_code !== undefined"));
+        assert!(!js.contains("e.message"));
+        assert!(js.contains("memory_bytes: () => new Uint8Array(exports.memory.buffer)"));
+    }
+
+    #[test]
+    fn js_without_start_or_memory() {
+        let interface = WasmInterface {
+            imports: Vec::new(),
+            exports: vec!["roc__main_for_host_1_exposed".to_string()],
+        };
+        let js = js_glue("lib.wasm", &interface);
+
+        assert!(!js.contains("run: () =>"));
+        assert!(!js.contains("memory_bytes"));
+        assert!(js.contains("module.exports = { roc_init };"));
+    }
+
+    #[test]
+    fn js_names_are_escaped() {
+        let interface = WasmInterface {
+            imports: vec![WasmImport {
+                module: "env".to_string(),
+                name: "quote\"d".to_string(),
+            }],
+            exports: Vec::new(),
+        };
+        let js = js_glue("lib.wasm", &interface);
+
+        assert!(js.contains(r#"    ["env", "quote\"d"],"#));
+    }
+}
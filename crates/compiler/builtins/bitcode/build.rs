@@ -138,8 +138,12 @@ fn cp_unless_zig_cache(src_dir: &Path, target_dir: &Path) -> io::Result<()> {
         let src_path = entry?.path();
         let src_filename = src_path.file_name().unwrap();
 
-        // Only copy individual files if they have the .zig extension
-        if src_path.extension().unwrap_or_default() == "zig" {
+        // Only copy individual files if they have the .zig extension, or are data files that
+        // the .zig files embed
+        if matches!(
+            src_path.extension().unwrap_or_default().to_str(),
+            Some("zig" | "txt")
+        ) {
             let dest = target_dir.join(src_filename);
 
             fs::copy(&src_path, &dest).unwrap_or_else(|err| {
@@ -209,7 +213,7 @@ fn get_zig_files(dir: &Path, cb: &dyn Fn(&Path)) -> io::Result<()> {
                 let path = path_buf.as_path();
 
                 match path.extension() {
-                    Some(osstr) if osstr == "zig" => {
+                    Some(osstr) if osstr == "zig" || osstr == "txt" => {
                         cb(path);
                     }
                     _ => {}
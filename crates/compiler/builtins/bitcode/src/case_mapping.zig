@@ -0,0 +1,132 @@
+const std = @import("std");
+const ascii = std.ascii;
+const testing = std.testing;
+const expectEqual = testing.expectEqual;
+
+const CaseMapping = struct {
+    code_point: u21,
+    upper: u21,
+    lower: u21,
+    fold: u21,
+};
+
+// Parsed at comptime from case_mappings.txt, sorted by code point.
+const mappings: []const CaseMapping = parseMappings(@embedFile("case_mappings.txt"));
+
+fn parseMappings(comptime text: []const u8) []const CaseMapping {
+    @setEvalBranchQuota(10_000_000);
+
+    comptime var count: usize = 0;
+    comptime {
+        var lines = std.mem.tokenizeScalar(u8, text, '\n');
+        while (lines.next()) |line| {
+            if (line[0] != '#') {
+                count += 1;
+            }
+        }
+    }
+
+    comptime var result: [count]CaseMapping = undefined;
+    comptime {
+        var lines = std.mem.tokenizeScalar(u8, text, '\n');
+        var index: usize = 0;
+        while (lines.next()) |line| {
+            if (line[0] == '#') {
+                continue;
+            }
+
+            var fields = std.mem.splitScalar(u8, line, ';');
+            const code_point = parseCodePoint(fields.next().?, 0);
+            const upper = parseCodePoint(fields.next().?, code_point);
+            const lower = parseCodePoint(fields.next().?, code_point);
+            const fold = parseCodePoint(fields.next().?, code_point);
+
+            if (index > 0 and result[index - 1].code_point >= code_point) {
+                @compileError("case_mappings.txt must be sorted by code point");
+            }
+
+            result[index] = .{ .code_point = code_point, .upper = upper, .lower = lower, .fold = fold };
+            index += 1;
+        }
+    }
+
+    const final = result;
+    return &final;
+}
+
+fn parseCodePoint(comptime field: []const u8, comptime default: u21) u21 {
+    if (field.len == 0) {
+        return default;
+    }
+
+    return std.fmt.parseInt(u21, field, 16) catch @compileError("invalid code point in case_mappings.txt: " ++ field);
+}
+
+fn find(code_point: u21) ?CaseMapping {
+    var low: usize = 0;
+    var high: usize = mappings.len;
+
+    while (low < high) {
+        const middle = low + (high - low) / 2;
+        const mapping = mappings[middle];
+
+        if (code_point < mapping.code_point) {
+            high = middle;
+        } else if (code_point > mapping.code_point) {
+            low = middle + 1;
+        } else {
+            return mapping;
+        }
+    }
+
+    return null;
+}
+
+pub fn toUpper(code_point: u21) u21 {
+    if (code_point < 0x80) {
+        return ascii.toUpper(@intCast(code_point));
+    }
+
+    return if (find(code_point)) |mapping| mapping.upper else code_point;
+}
+
+pub fn toLower(code_point: u21) u21 {
+    if (code_point < 0x80) {
+        return ascii.toLower(@intCast(code_point));
+    }
+
+    return if (find(code_point)) |mapping| mapping.lower else code_point;
+}
+
+pub fn fold(code_point: u21) u21 {
+    if (code_point < 0x80) {
+        return ascii.toLower(@intCast(code_point));
+    }
+
+    return if (find(code_point)) |mapping| mapping.fold else code_point;
+}
+
+test "toUpper" {
+    try expectEqual(@as(u21, 'A'), toUpper('a'));
+    try expectEqual(@as(u21, '!'), toUpper('!'));
+    try expectEqual(@as(u21, 'É'), toUpper('é'));
+    try expectEqual(@as(u21, 'Σ'), toUpper('ς'));
+    // ß has no single code point uppercase
+    try expectEqual(@as(u21, 'ß'), toUpper('ß'));
+    try expectEqual(@as(u21, 0x1E921), toUpper(0x1E943));
+}
+
+test "toLower" {
+    try expectEqual(@as(u21, 'a'), toLower('A'));
+    try expectEqual(@as(u21, 'é'), toLower('É'));
+    try expectEqual(@as(u21, 'i'), toLower('İ'));
+    try expectEqual(@as(u21, 'ǆ'), toLower('ǅ'));
+}
+
+test "fold" {
+    try expectEqual(@as(u21, 'σ'), fold('Σ'));
+    try expectEqual(@as(u21, 'σ'), fold('ς'));
+    try expectEqual(@as(u21, 'ß'), fold('ẞ'));
+    try expectEqual(@as(u21, 'Ꭰ'), fold('ꭰ'));
+    try expectEqual(@as(u21, 'İ'), fold('İ'));
+}
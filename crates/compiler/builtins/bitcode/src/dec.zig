@@ -417,9 +417,35 @@ pub const RocDec = extern struct {
     fn pow(base: RocDec, exponent: RocDec) RocDec {
         if (exponent.trunc().num == exponent.num) {
             return base.powInt(@divTrunc(exponent.num, RocDec.one_point_zero_i128));
-        } else {
-            return fromF64(std.math.pow(f64, base.toF64(), exponent.toF64())).?;
         }
+
+        if (base.num < 0) {
+            roc_panic("Decimal exponentiation of a negative number by a non-integer!", 0);
+        }
+
+        if (base.num == 0) {
+            if (exponent.num < 0) {
+                roc_panic("Decimal division by 0!", 0);
+            }
+
+            return RocDec{ .num = 0 };
+        }
+
+        // base^exponent = e^(exponent * ln(base))
+        const log_base = fixedLog(base.num);
+        const product = div_u256_by_u128(mul_u128(@abs(exponent.num), @abs(log_base)), one_point_zero_u128);
+        const is_negative = (exponent.num < 0) != (log_base < 0);
+
+        if (product.hi != 0 or product.lo > @as(u128, fixed_exp_limit)) {
+            if (is_negative) {
+                return RocDec{ .num = 0 };
+            }
+
+            roc_panic("Decimal exponentiation overflowed!", 0);
+        }
+
+        const magnitude: i128 = @intCast(product.lo);
+        return fixedExp(if (is_negative) -magnitude else magnitude);
     }
 
     pub fn mul(self: RocDec, other: RocDec) RocDec {
@@ -550,8 +576,60 @@ pub const RocDec = extern struct {
         return RocDec{ .num = out };
     }
 
+    // The square root, log, exp, and pow functions don't round-trip through F64. Their answers
+    // are the exact answer rounded to the nearest Dec, apart from answers with more than about
+    // 32 significant digits, where the digits past that may be off.
+    pub fn sqrt(self: RocDec) RocDec {
+        if (self.num < 0) {
+            roc_panic("Decimal square root of a negative number!", 0);
+        }
+
+        if (self.num == 0) {
+            return self;
+        }
+
+        // The answer is sqrt(self.num * 10^18), rounded to the nearest integer. Start from the
+        // F64 answer, which is off by at most a few parts in 10^16, and refine it with Newton's method.
+        const squared = mul_u128(@intCast(self.num), one_point_zero_u128);
+        var root: u128 = @max(@as(u128, @intFromFloat(@sqrt(self.toF64()) * 1e18)), 1);
+        for (0..2) |_| {
+            root = (root + div_u256_by_u128(squared, root).lo) / 2;
+        }
+
+        while (lessThanU256(squared, mul_u128(root, root))) {
+            root -= 1;
+        }
+
+        while (!lessThanU256(squared, mul_u128(root + 1, root + 1))) {
+            root += 1;
+        }
+
+        // squared - root^2 is at most 2 * root, so the low halves are enough to compute it
+        if (squared.lo -% mul_u128(root, root).lo > root) {
+            root += 1;
+        }
+
+        return RocDec{ .num = @intCast(root) };
+    }
+
     pub fn log(self: RocDec) RocDec {
-        return fromF64(@log(self.toF64())).?;
+        if (self.num <= 0) {
+            roc_panic("Decimal logarithm of a non-positive number!", 0);
+        }
+
+        return fromFixed(fixedLog(self.num));
+    }
+
+    pub fn exp(self: RocDec) RocDec {
+        if (self.num > fixed_exp_limit_whole * one_point_zero_i128) {
+            roc_panic("Decimal exponentiation overflowed!", 0);
+        }
+
+        if (self.num < -fixed_exp_limit_whole * one_point_zero_i128) {
+            return RocDec{ .num = 0 };
+        }
+
+        return fixedExp(toFixed(self.num));
     }
 
     // I belive the output of the trig functions is always in range of Dec.
@@ -742,6 +820,131 @@ fn mul_and_decimalize(a: u128, b: u128) WithOverflow(i128) {
 // When translating this to Zig, we often have to use math.shr/shl instead of >>/<<
 // This is because casting to the right types for Zig can be kind of tricky.
 // See https://github.com/ziglang/zig/issues/7605
+fn lessThanU256(a: U256, b: U256) bool {
+    return a.hi < b.hi or (a.hi == b.hi and a.lo < b.lo);
+}
+
+fn shlU256(value: u128, shift: i16) U256 {
+    return .{
+        .hi = math.shl(u128, value, shift - 128),
+        .lo = math.shl(u128, value, shift),
+    };
+}
+
+// Shifts right, rounding to the nearest integer. The shift must be between 1 and 255.
+fn shrRoundU256(value: U256, shift: i16) U256 {
+    const half = shlU256(1, shift - 1);
+    const lo = value.lo +% half.lo;
+    const hi = value.hi + half.hi + @intFromBool(lo < value.lo);
+
+    return .{
+        .hi = math.shr(u128, hi, shift),
+        .lo = math.shr(u128, lo, shift) | math.shl(u128, hi, 128 - shift),
+    };
+}
+
+// The transcendental functions work on fixed-point numbers with 120 fractional bits internally,
+// so that their rounding errors stay far below the 18 decimal places a Dec has. These hold
+// values with a magnitude below 128.
+const fixed_fraction_bits = 120;
+const fixed_one: i128 = 1 << fixed_fraction_bits;
+// ln(2), rounded to the nearest fixed-point number
+const fixed_ln_2: i128 = 921350637599661305226344307672478455;
+// e^64 doesn't fit in a Dec, and e^-64 rounds to 0.
+const fixed_exp_limit_whole = 64;
+const fixed_exp_limit: i128 = fixed_exp_limit_whole << fixed_fraction_bits;
+const one_point_zero_u128: u128 = math.pow(u128, 10, RocDec.decimal_places);
+
+// Only valid for Decs with a magnitude below 128.
+fn toFixed(num: i128) i128 {
+    const quotient = div_u256_by_u128(shlU256(@abs(num), fixed_fraction_bits), one_point_zero_u128);
+    const magnitude: i128 = @intCast(quotient.lo);
+
+    return if (num < 0) -magnitude else magnitude;
+}
+
+fn fromFixed(fixed: i128) RocDec {
+    const rounded = shrRoundU256(mul_u128(@abs(fixed), one_point_zero_u128), fixed_fraction_bits);
+    const magnitude: i128 = @intCast(rounded.lo);
+
+    return RocDec{ .num = if (fixed < 0) -magnitude else magnitude };
+}
+
+fn fixedMul(a: i128, b: i128) i128 {
+    const product = mul_u128(@abs(a), @abs(b));
+    const magnitude: i128 = @intCast(math.shl(u128, product.hi, 128 - fixed_fraction_bits) | math.shr(u128, product.lo, fixed_fraction_bits));
+
+    return if ((a < 0) != (b < 0)) -magnitude else magnitude;
+}
+
+// Only valid for positive numbers.
+fn fixedDiv(a: i128, b: i128) i128 {
+    return @intCast(div_u256_by_u128(shlU256(@intCast(a), fixed_fraction_bits), @intCast(b)).lo);
+}
+
+// ln(num / 10^18), for a positive num.
+fn fixedLog(num: i128) i128 {
+    // Write the number as m * 2^k, with 1 <= m < 2, so that ln(num) = ln(m) + k * ln(2).
+    const shift: i16 = 180 - (128 - @as(i16, @clz(num)));
+    var m: i128 = @intCast(div_u256_by_u128(shlU256(@intCast(num), shift), one_point_zero_u128).lo);
+    var k: i128 = fixed_fraction_bits - shift;
+
+    while (m >= 2 * fixed_one) {
+        m >>= 1;
+        k += 1;
+    }
+
+    while (m < fixed_one) {
+        m <<= 1;
+        k -= 1;
+    }
+
+    // ln(m) = 2 * atanh(s) = 2 * (s + s^3/3 + s^5/5 + ...), where s = (m - 1) / (m + 1) < 1/3.
+    const s = fixedDiv(m - fixed_one, m + fixed_one);
+    const s_squared = fixedMul(s, s);
+
+    var sum: i128 = 0;
+    var term = s;
+    var divisor: i128 = 1;
+    while (term != 0) : (divisor += 2) {
+        sum += @divTrunc(term, divisor);
+        term = fixedMul(term, s_squared);
+    }
+
+    return 2 * sum + k * fixed_ln_2;
+}
+
+// e^fixed, rounded to the nearest Dec.
+fn fixedExp(fixed: i128) RocDec {
+    if (fixed > fixed_exp_limit) {
+        roc_panic("Decimal exponentiation overflowed!", 0);
+    }
+
+    if (fixed < -fixed_exp_limit) {
+        return RocDec{ .num = 0 };
+    }
+
+    // Write e^fixed as e^r * 2^k, with |r| <= ln(2) / 2, so the Taylor series for e^r converges quickly.
+    const half_ln_2 = fixed_ln_2 >> 1;
+    const k = @divTrunc(if (fixed < 0) fixed - half_ln_2 else fixed + half_ln_2, fixed_ln_2);
+    const r = fixed - k * fixed_ln_2;
+
+    var sum: i128 = fixed_one;
+    var term: i128 = fixed_one;
+    var n: i128 = 1;
+    while (term != 0) : (n += 1) {
+        term = @divTrunc(fixedMul(term, r), n);
+        sum += term;
+    }
+
+    const answer = shrRoundU256(mul_u128(@intCast(sum), one_point_zero_u128), @intCast(fixed_fraction_bits - k));
+    if (answer.hi != 0 or answer.lo > math.maxInt(i128)) {
+        roc_panic("Decimal exponentiation overflowed!", 0);
+    }
+
+    return RocDec{ .num = @intCast(answer.lo) };
+}
+
 fn div_u256_by_u128(numer: U256, denom: u128) U256 {
     const N_UDWORD_BITS: u8 = 128;
     const N_UTWORD_BITS: u9 = 256;
@@ -1286,6 +1489,46 @@ test "log: 1" {
     try expectEqual(RocDec.fromU64(0), RocDec.log(RocDec.fromU64(1)));
 }
 
+test "log: 2" {
+    try expectEqual(RocDec{ .num = 693147180559945309 }, RocDec.log(RocDec.fromU64(2)));
+}
+
+test "log: smallest positive" {
+    try expectEqual(RocDec{ .num = -41446531673892822312 }, RocDec.log(RocDec{ .num = 1 }));
+}
+
+test "log: 10^20" {
+    try expectEqual(RocDec{ .num = 46051701859880913680 }, RocDec.log(RocDec{ .num = 100_000_000_000_000_000_000 * RocDec.one_point_zero_i128 }));
+}
+
+test "exp: 1" {
+    try expectEqual(RocDec{ .num = 2718281828459045235 }, RocDec.exp(RocDec.fromU64(1)));
+}
+
+test "exp: -1.5" {
+    try expectEqual(RocDec{ .num = 223130160148429829 }, RocDec.exp(RocDec{ .num = -1_500_000_000_000_000_000 }));
+}
+
+test "exp: -100" {
+    try expectEqual(RocDec{ .num = 0 }, RocDec.exp(RocDec{ .num = -100 * RocDec.one_point_zero_i128 }));
+}
+
+test "sqrt: 0" {
+    try expectEqual(RocDec.fromU64(0), RocDec.sqrt(RocDec.fromU64(0)));
+}
+
+test "sqrt: 2" {
+    try expectEqual(RocDec{ .num = 1414213562373095049 }, RocDec.sqrt(RocDec.fromU64(2)));
+}
+
+test "sqrt: 16" {
+    try expectEqual(RocDec.fromU64(4), RocDec.sqrt(RocDec.fromU64(16)));
+}
+
+test "sqrt: 10^17" {
+    try expectEqual(RocDec{ .num = 316227766016837933199889354 }, RocDec.sqrt(RocDec.fromU64(100_000_000_000_000_000)));
+}
+
 test "fract: 0" {
     const roc_str = RocStr.init("0", 1);
     var dec = RocDec.fromStr(roc_str).?;
@@ -1433,6 +1676,21 @@ test "pow: 0.5 ^ 2.0" {
     try expectEqual(dec, RocDec.zero_point_five.pow(RocDec.two_point_zero));
 }
 
+test "pow: 2 ^ 0.5" {
+    try expectEqual(RocDec{ .num = 1414213562373095049 }, RocDec.fromU64(2).pow(RocDec.zero_point_five));
+}
+
+test "pow: 1.05 ^ 10.5" {
+    const base = RocDec{ .num = 1_050_000_000_000_000_000 };
+    const exponent = RocDec{ .num = 10_500_000_000_000_000_000 };
+
+    try expectEqual(RocDec{ .num = 1669120304352457735 }, base.pow(exponent));
+}
+
+test "pow: 0 ^ 0.5" {
+    try expectEqual(RocDec.fromU64(0), RocDec.fromU64(0).pow(RocDec.zero_point_five));
+}
+
 // exports
 
 pub fn fromStr(arg: RocStr) callconv(.C) num_.NumParseResult(i128) {
@@ -1541,6 +1799,14 @@ pub fn powC(arg1: RocDec, arg2: RocDec) callconv(.C) i128 {
     return @call(.always_inline, RocDec.pow, .{ arg1, arg2 }).num;
 }
 
+pub fn sqrtC(arg: RocDec) callconv(.C) i128 {
+    return @call(.always_inline, RocDec.sqrt, .{arg}).num;
+}
+
+pub fn expC(arg: RocDec) callconv(.C) i128 {
+    return @call(.always_inline, RocDec.exp, .{arg}).num;
+}
+
 pub fn sinC(arg: RocDec) callconv(.C) i128 {
     return @call(.always_inline, RocDec.sin, .{arg}).num;
 }
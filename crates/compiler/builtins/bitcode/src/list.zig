@@ -2,6 +2,7 @@ const std = @import("std");
 const utils = @import("utils.zig");
 const str = @import("str.zig");
 const sort = @import("sort.zig");
+const simd = @import("simd.zig");
 const UpdateMode = utils.UpdateMode;
 const mem = std.mem;
 const math = std.math;
@@ -944,6 +945,20 @@ pub fn listIsUnique(
     return list.isEmpty() or list.isUnique();
 }
 
+// List.contains, for lists of numbers. Other element types use the implementation in List.roc,
+// which calls the element type's `isEq`.
+pub fn exportContainsNum(comptime T: type, comptime name: []const u8) void {
+    const f = struct {
+        fn func(list: RocList, needle: T) callconv(.C) bool {
+            const elements = list.elements(T) orelse return false;
+
+            return simd.contains(T, elements[0..list.len()], needle);
+        }
+    }.func;
+
+    @export(f, .{ .name = name ++ @typeName(T), .linkage = .strong });
+}
+
 pub fn listClone(
     list: RocList,
     alignment: u32,
@@ -31,6 +31,7 @@ comptime {
     exportDecFn(dec.cosC, "cos");
     exportDecFn(dec.divC, "div");
     exportDecFn(dec.eqC, "eq");
+    exportDecFn(dec.expC, "exp");
     exportDecFn(dec.fromF32C, "from_float.f32");
     exportDecFn(dec.fromF64C, "from_float.f64");
     exportDecFn(dec.fromStr, "from_str");
@@ -43,6 +44,7 @@ comptime {
     exportDecFn(dec.negateC, "negate");
     exportDecFn(dec.neqC, "neq");
     exportDecFn(dec.sinC, "sin");
+    exportDecFn(dec.sqrtC, "sqrt");
     exportDecFn(dec.subC, "sub_with_overflow");
     exportDecFn(dec.subOrPanicC, "sub_or_panic");
     exportDecFn(dec.subSaturatedC, "sub_saturated");
@@ -84,6 +86,10 @@ comptime {
     exportListFn(list.listConcatUtf8, "concat_utf8");
     exportListFn(list.listIncref, "incref");
     exportListFn(list.listDecref, "decref");
+
+    for (NUMBERS) |T| {
+        list.exportContainsNum(T, ROC_BUILTINS ++ ".list.contains_num.");
+    }
 }
 
 // Num Module
@@ -185,6 +191,7 @@ comptime {
 
 // Str Module
 const str = @import("str.zig");
+const case_mapping = @import("case_mapping.zig");
 comptime {
     exportStrFn(str.init, "init");
     exportStrFn(str.strSplitOn, "str_split_on");
@@ -195,6 +202,7 @@ comptime {
     exportStrFn(str.startsWith, "starts_with");
     exportStrFn(str.endsWith, "ends_with");
     exportStrFn(str.strConcatC, "concat");
+    exportStrFn(str.strConcatAmortizedC, "concat_amortized");
     exportStrFn(str.strJoinWithC, "joinWith");
     exportStrFn(str.strNumberOfBytes, "number_of_bytes");
     exportStrFn(str.strEqual, "equal");
@@ -215,6 +223,9 @@ comptime {
     exportStrFn(str.strWithAsciiLowercased, "with_ascii_lowercased");
     exportStrFn(str.strWithAsciiUppercased, "with_ascii_uppercased");
     exportStrFn(str.strCaselessAsciiEquals, "caseless_ascii_equals");
+    exportStrFn(str.strWithUppercased, "with_uppercased");
+    exportStrFn(str.strWithLowercased, "with_lowercased");
+    exportStrFn(str.strCaselessEquals, "caseless_equals");
 
     for (INTEGERS) |T| {
         str.exportFromInt(T, ROC_BUILTINS ++ "." ++ STR ++ ".from_int.");
@@ -0,0 +1,118 @@
+//! Vectorized searches, for the builtins that scan through a whole string or list.
+//!
+//! These use Zig's vector types, which LLVM lowers to the SIMD instructions of the target
+//! (or to scalar code on targets without them, e.g. wasm32 without simd128).
+const std = @import("std");
+const mem = std.mem;
+const testing = std.testing;
+
+const BYTE_LANES = std.simd.suggestVectorLength(u8) orelse 16;
+const ByteMask = std.meta.Int(.unsigned, BYTE_LANES);
+
+/// The index of the first occurrence of `needle` in `haystack` that starts at or after `start`,
+/// like `std.mem.indexOfPos`.
+pub fn indexOfPos(haystack: []const u8, start: usize, needle: []const u8) ?usize {
+    if (needle.len == 0) {
+        return start;
+    }
+
+    if (needle.len > haystack.len or start > haystack.len - needle.len) {
+        return null;
+    }
+
+    // Finds the positions where both the first and the last byte of the needle match, and only
+    // compares the rest of the needle at those.
+    const first: @Vector(BYTE_LANES, u8) = @splat(needle[0]);
+    const last: @Vector(BYTE_LANES, u8) = @splat(needle[needle.len - 1]);
+
+    var i = start;
+
+    while (i + BYTE_LANES + needle.len - 1 <= haystack.len) : (i += BYTE_LANES) {
+        const firsts: @Vector(BYTE_LANES, u8) = haystack[i..][0..BYTE_LANES].*;
+        const lasts: @Vector(BYTE_LANES, u8) = haystack[i + needle.len - 1 ..][0..BYTE_LANES].*;
+
+        const first_matches: ByteMask = @bitCast(firsts == first);
+        const last_matches: ByteMask = @bitCast(lasts == last);
+        var candidates = first_matches & last_matches;
+
+        while (candidates != 0) {
+            const index = i + @ctz(candidates);
+
+            if (mem.eql(u8, haystack[index..][0..needle.len], needle)) {
+                return index;
+            }
+
+            candidates &= candidates - 1;
+        }
+    }
+
+    return mem.indexOfPos(u8, haystack, i, needle);
+}
+
+/// Whether any of the items is equal to `needle`, using the same equality as `==` (so for
+/// floats, NaN is never found, and 0.0 and -0.0 are equal).
+pub fn contains(comptime T: type, items: []const T, needle: T) bool {
+    const lanes = std.simd.suggestVectorLength(T) orelse 4;
+    const needles: @Vector(lanes, T) = @splat(needle);
+
+    var i: usize = 0;
+
+    while (i + lanes <= items.len) : (i += lanes) {
+        const chunk: @Vector(lanes, T) = items[i..][0..lanes].*;
+
+        if (@reduce(.Or, chunk == needles)) {
+            return true;
+        }
+    }
+
+    while (i < items.len) : (i += 1) {
+        if (items[i] == needle) {
+            return true;
+        }
+    }
+
+    return false;
+}
+
+test "indexOfPos: matches std.mem.indexOfPos" {
+    const haystack = "the quick brown fox jumps over the lazy dog, and then the quick brown fox naps";
+    const needles = [_][]const u8{ "", "t", "the", "fox", "naps", "dog, and", "cat", haystack, haystack ++ "!" };
+
+    for (needles) |needle| {
+        var start: usize = 0;
+
+        while (start <= haystack.len + 1) : (start += 1) {
+            try testing.expectEqual(mem.indexOfPos(u8, haystack, start, needle), indexOfPos(haystack, start, needle));
+        }
+    }
+}
+
+test "indexOfPos: match in the last vector" {
+    var haystack = [_]u8{'a'} ** 100;
+    haystack[97] = 'b';
+    haystack[98] = 'c';
+
+    try testing.expectEqual(@as(?usize, 97), indexOfPos(&haystack, 0, "bc"));
+    try testing.expectEqual(@as(?usize, null), indexOfPos(&haystack, 0, "cb"));
+}
+
+test "contains: integers" {
+    var items: [37]i64 = undefined;
+
+    for (&items, 0..) |*item, i| {
+        item.* = @as(i64, @intCast(i)) * 3;
+    }
+
+    try testing.expect(contains(i64, &items, 0));
+    try testing.expect(contains(i64, &items, 108));
+    try testing.expect(!contains(i64, &items, 109));
+    try testing.expect(!contains(i64, items[0..0], 0));
+}
+
+test "contains: floats" {
+    const items = [_]f64{ 1.5, -0.0, std.math.nan(f64), 4.0, 5.0, 6.0, 7.0, 8.0, 9.0 };
+
+    try testing.expect(contains(f64, &items, 0.0));
+    try testing.expect(contains(f64, &items, 9.0));
+    try testing.expect(!contains(f64, &items, std.math.nan(f64)));
+}
@@ -32,6 +32,9 @@ comptime {
 // ================ Fluxsort ==================================================
 // The high level fluxsort functions.
 
+/// Sorts the array in place. The sort is stable, which `List.sort_with` guarantees,
+/// so every path through here (quadsort, direct, and indirect) must keep elements that compare
+/// as equal in their original order.
 pub fn fluxsort(
     array: [*]u8,
     len: usize,
@@ -92,6 +95,56 @@ pub fn fluxsort(
     }
 }
 
+fn StableSortTest(comptime padding_size: usize) type {
+    return struct {
+        const Element = struct {
+            key: i64,
+            index: i64,
+            padding: [padding_size]u8,
+        };
+
+        fn compare(_: Opaque, a_ptr: Opaque, b_ptr: Opaque) callconv(.C) u8 {
+            const a = @as(*Element, @alignCast(@ptrCast(a_ptr))).key;
+            const b = @as(*Element, @alignCast(@ptrCast(b_ptr))).key;
+
+            const gt = @as(u8, @intFromBool(a > b));
+            const lt = @as(u8, @intFromBool(a < b));
+
+            return lt + lt + gt;
+        }
+
+        fn copy(dst_ptr: Opaque, src_ptr: Opaque) callconv(.C) void {
+            @as(*Element, @alignCast(@ptrCast(dst_ptr))).* = @as(*Element, @alignCast(@ptrCast(src_ptr))).*;
+        }
+
+        fn run(comptime len: usize) !void {
+            var arr: [len]Element = undefined;
+
+            // Only a few distinct keys, so there are lots of ties to keep in order.
+            var state: u64 = 42;
+            for (&arr, 0..) |*element, i| {
+                state = state *% 6364136223846793005 +% 1442695040888963407;
+                element.* = .{ .key = @intCast((state >> 33) % 7), .index = @intCast(i), .padding = undefined };
+            }
+
+            fluxsort(@ptrCast(&arr), len, &compare, null, false, &test_inc_n_data, @sizeOf(Element), @alignOf(Element), &copy);
+
+            for (arr[0 .. len - 1], arr[1..]) |a, b| {
+                try testing.expect(a.key < b.key or (a.key == b.key and a.index < b.index));
+            }
+        }
+    };
+}
+
+test "fluxsort: stable" {
+    // quadsort
+    try StableSortTest(0).run(100);
+    // direct
+    try StableSortTest(0).run(1000);
+    // indirect, since the elements are bigger than MAX_ELEMENT_BUFFER_SIZE
+    try StableSortTest(MAX_ELEMENT_BUFFER_SIZE).run(1000);
+}
+
 fn fluxsort_direct(
     array: [*]u8,
     len: usize,
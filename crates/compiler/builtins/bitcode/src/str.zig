@@ -1,5 +1,7 @@
 const utils = @import("utils.zig");
 const RocList = @import("list.zig").RocList;
+const simd = @import("simd.zig");
+const case_mapping = @import("case_mapping.zig");
 const UpdateMode = utils.UpdateMode;
 const std = @import("std");
 const ascii = std.ascii;
@@ -625,19 +627,22 @@ fn strSplitOnHelp(array: [*]RocStr, string: RocStr, delimiter: RocStr) void {
         return;
     }
 
-    var it = std.mem.split(u8, string.asSlice(), delimiter.asSlice());
+    const haystack = string.asSlice();
+    const needle = delimiter.asSlice();
 
     var i: usize = 0;
     var offset: usize = 0;
 
-    while (it.next()) |zig_slice| {
-        const roc_slice = substringUnsafe(string, offset, zig_slice.len);
-        array[i] = roc_slice;
+    while (simd.indexOfPos(haystack, offset, needle)) |index| {
+        array[i] = substringUnsafe(string, offset, index - offset);
 
         i += 1;
-        offset += zig_slice.len + delimiter.len();
+        offset = index + needle.len;
     }
 
+    array[i] = substringUnsafe(string, offset, haystack.len - offset);
+    i += 1;
+
     // Correct refcount for all of the splits made.
     string.incref(i); // i == array.len()
 }
@@ -969,10 +974,16 @@ pub fn countSegments(string: RocStr, delimiter: RocStr) callconv(.C) usize {
         return 1;
     }
 
-    var it = std.mem.split(u8, string.asSlice(), delimiter.asSlice());
-    var count: usize = 0;
+    const haystack = string.asSlice();
+    const needle = delimiter.asSlice();
+
+    var count: usize = 1;
+    var offset: usize = 0;
 
-    while (it.next()) |_| : (count += 1) {}
+    while (simd.indexOfPos(haystack, offset, needle)) |index| {
+        count += 1;
+        offset = index + needle.len;
+    }
 
     return count;
 }
@@ -1321,6 +1332,59 @@ test "RocStr.concat: small concat small" {
     try expect(roc_str3.eq(result));
 }
 
+// Str.concat, for a string that is built up by appending to it over and over (e.g. in a loop).
+// The compiler uses this instead of strConcat when the result replaces the first argument.
+pub fn strConcatAmortizedC(arg1: RocStr, arg2: RocStr) callconv(.C) RocStr {
+    return @call(.always_inline, strConcatAmortized, .{ arg1, arg2 });
+}
+
+fn strConcatAmortized(arg1: RocStr, arg2: RocStr) RocStr {
+    if (arg2.isEmpty()) {
+        return arg1;
+    }
+
+    const combined_length = arg1.len() + arg2.len();
+
+    if (arg1.getCapacity() >= combined_length) {
+        return strConcat(arg1, arg2);
+    }
+
+    // strConcat reallocates to exactly the combined length, so appending to the same string n
+    // times would copy it n times. Growing the capacity geometrically makes that linear.
+    const capacity = @max(combined_length, 2 * arg1.len());
+    const reserved = reserve(arg1, capacity - arg1.len());
+
+    return strConcat(reserved, arg2);
+}
+
+test "RocStr.concat amortized: grows capacity geometrically" {
+    var result = RocStr.empty();
+    var expected_len: usize = 0;
+    var reallocations: usize = 0;
+
+    const piece = RocStr.fromSlice("abcdefghijklmnopqrstuvwxyz");
+    defer piece.decref();
+
+    var i: usize = 0;
+    while (i < 1000) : (i += 1) {
+        const capacity_before = result.getCapacity();
+
+        piece.incref(1);
+        result = strConcatAmortized(result, piece);
+        expected_len += piece.len();
+
+        if (result.getCapacity() != capacity_before) {
+            reallocations += 1;
+        }
+    }
+
+    defer result.decref();
+
+    try expectEqual(expected_len, result.len());
+    try expect(reallocations < 20);
+    try testing.expectEqualSlices(u8, "abcdefghijklmnopqrstuvwxyz", result.asSlice()[expected_len - 26 ..]);
+}
+
 pub const RocListStr = extern struct {
     list_elements: ?[*]RocStr,
     list_length: usize,
@@ -2323,6 +2387,158 @@ test "caselessAsciiEquals: seamless slice" {
     try expect(are_equal);
 }
 
+// Str.with_uppercased
+pub fn strWithUppercased(string: RocStr) callconv(.C) RocStr {
+    return mapCodePoints(string, case_mapping.toUpper);
+}
+
+// Str.with_lowercased
+pub fn strWithLowercased(string: RocStr) callconv(.C) RocStr {
+    return mapCodePoints(string, case_mapping.toLower);
+}
+
+fn mapCodePoints(string: RocStr, comptime map: fn (u21) u21) RocStr {
+    const bytes = string.asU8ptr()[0..string.len()];
+
+    // Some mappings change how many bytes a code point takes up, like `ı` becoming `I`.
+    // Only when none do can we safely write over the bytes we are reading.
+    var new_len: usize = 0;
+    var same_lengths = true;
+    var iter = unicode.Utf8View.initUnchecked(bytes).iterator();
+    while (true) {
+        const start = iter.i;
+        const code_point = iter.nextCodepoint() orelse break;
+        const mapped_len = unicode.utf8CodepointSequenceLength(map(code_point)) catch unreachable;
+
+        same_lengths = same_lengths and mapped_len == iter.i - start;
+        new_len += mapped_len;
+    }
+
+    const in_place = same_lengths and string.isUnique();
+    var new_str = if (in_place) string else RocStr.allocate(new_len);
+    const new_bytes = new_str.asU8ptrMut()[0..new_len];
+
+    var index: usize = 0;
+    iter = unicode.Utf8View.initUnchecked(bytes).iterator();
+    while (iter.nextCodepoint()) |code_point| {
+        index += unicode.utf8Encode(map(code_point), new_bytes[index..]) catch unreachable;
+    }
+
+    if (!in_place) {
+        string.decref();
+    }
+
+    return new_str;
+}
+
+test "withUppercased: small str" {
+    const original = RocStr.fromSlice("straße ǆ");
+    try expect(original.isSmallStr());
+
+    const expected = RocStr.fromSlice("STRAßE Ǆ");
+    defer expected.decref();
+
+    const str_result = strWithUppercased(original);
+    defer str_result.decref();
+
+    try expect(str_result.eq(expected));
+}
+
+test "withUppercased: non small str" {
+    const original = RocStr.fromSlice("ελληνικά café ελληνικά café ελληνικά café");
+    defer original.decref();
+    try expect(!original.isSmallStr());
+
+    const expected = RocStr.fromSlice("ΕΛΛΗΝΙΚΆ CAFÉ ΕΛΛΗΝΙΚΆ CAFÉ ΕΛΛΗΝΙΚΆ CAFÉ");
+    defer expected.decref();
+
+    const str_result = strWithUppercased(original);
+
+    try expect(!str_result.isSmallStr());
+    try expect(str_result.eq(expected));
+}
+
+test "withUppercased: changes byte length" {
+    const original = RocStr.fromSlice("ıⱥıⱥıⱥıⱥıⱥıⱥıⱥıⱥıⱥıⱥıⱥıⱥ");
+    defer original.decref();
+    try expect(!original.isSmallStr());
+
+    const expected = RocStr.fromSlice("IȺIȺIȺIȺIȺIȺIȺIȺIȺIȺIȺIȺ");
+    defer expected.decref();
+
+    const str_result = strWithUppercased(original);
+
+    try expect(str_result.eq(expected));
+}
+
+test "withLowercased: seamless slice" {
+    const l = RocStr.fromSlice("ÇAFÉ ÇAFÉ ÇAFÉ ÇAFÉ ÇAFÉ ÇAFÉ ÇAFÉ");
+    const original = substringUnsafeC(l, 2, l.len() - 2);
+    defer original.decref();
+
+    try expect(original.isSeamlessSlice());
+
+    const expected = RocStr.fromSlice("afé çafé çafé çafé çafé çafé çafé");
+    defer expected.decref();
+
+    const str_result = strWithLowercased(original);
+
+    try expect(str_result.eq(expected));
+}
+
+// Str.caseless_equals
+pub fn strCaselessEquals(self: RocStr, other: RocStr) callconv(.C) bool {
+    if (self.bytes == other.bytes and self.length == other.length) {
+        return true;
+    }
+
+    var self_iter = unicode.Utf8View.initUnchecked(self.asSlice()).iterator();
+    var other_iter = unicode.Utf8View.initUnchecked(other.asSlice()).iterator();
+
+    while (true) {
+        const self_code_point = self_iter.nextCodepoint();
+        const other_code_point = other_iter.nextCodepoint();
+
+        if (self_code_point == null or other_code_point == null) {
+            return self_code_point == null and other_code_point == null;
+        }
+
+        if (case_mapping.fold(self_code_point.?) != case_mapping.fold(other_code_point.?)) {
+            return false;
+        }
+    }
+}
+
+test "caselessEquals: non-ascii" {
+    const str1 = RocStr.fromSlice("Ὀδυσσεύς");
+    defer str1.decref();
+
+    const str2 = RocStr.fromSlice("ὈΔΥΣΣΕΎΣ");
+    defer str2.decref();
+
+    try expect(strCaselessEquals(str1, str2));
+}
+
+test "caselessEquals: different lengths" {
+    const str1 = RocStr.fromSlice("café");
+    defer str1.decref();
+
+    const str2 = RocStr.fromSlice("CAFÉS");
+    defer str2.decref();
+
+    try expect(!strCaselessEquals(str1, str2));
+}
+
+test "caselessEquals: different byte lengths" {
+    const str1 = RocStr.fromSlice("ſ");
+    defer str1.decref();
+
+    const str2 = RocStr.fromSlice("S");
+    defer str2.decref();
+
+    try expect(strCaselessEquals(str1, str2));
+}
+
 fn rcNone(_: ?[*]u8) callconv(.C) void {}
 
 fn decStr(ptr: ?[*]u8) callconv(.C) void {
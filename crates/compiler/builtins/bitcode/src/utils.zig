@@ -5,6 +5,12 @@ const DEBUG_INCDEC = false;
 const DEBUG_TESTING_ALLOC = false;
 const DEBUG_ALLOC = false;
 
+// In debug builds, the refcount (and element count) of an allocation is overwritten right before
+// it's freed, so that a use after free reads an obviously wrong refcount instead of often still
+// working. Hosts can poison the rest of the allocation in their roc_dealloc.
+const POISON_FREED = builtin.mode == .Debug;
+pub const FREED_POISON: u8 = 0xDD;
+
 pub fn WithOverflow(comptime T: type) type {
     return extern struct { value: T, has_overflowed: bool };
 }
@@ -119,6 +125,10 @@ fn testing_roc_realloc(c_ptr: *anyopaque, new_size: usize, old_size: usize, nomi
     return new_ptr;
 }
 
+// A copy of the first bytes of the last allocation that was freed, so tests can check what
+// the builtins did to it before freeing it.
+var testing_last_freed: [16]u8 = undefined;
+
 fn testing_roc_dealloc(c_ptr: *anyopaque, _: u32) callconv(.C) void {
     const alignment = 16;
     const size_of_size = @sizeOf(usize);
@@ -131,6 +141,9 @@ fn testing_roc_dealloc(c_ptr: *anyopaque, _: u32) callconv(.C) void {
     const full_size = size_of_data_and_size + offset_from_allocation_to_size;
     const slice = allocation_ptr[0..full_size];
 
+    const freed_len = @min(testing_last_freed.len, size_of_data_and_size - size_of_size);
+    @memcpy(testing_last_freed[0..freed_len], @as([*]u8, @ptrCast(c_ptr))[0..freed_len]);
+
     if (DEBUG_TESTING_ALLOC and builtin.target.cpu.arch != .wasm32) {
         std.debug.print("💀 dealloc {*}\n", .{slice.ptr});
     }
@@ -352,6 +365,11 @@ inline fn free_ptr_to_refcount(
     const extra_bytes = @max(required_space, alignment);
     const allocation_ptr = @as([*]u8, @ptrCast(refcount_ptr)) - (extra_bytes - @sizeOf(usize));
 
+    if (POISON_FREED) {
+        // Everything before the data: the refcount, and the element count if there is one
+        @memset(allocation_ptr[0..extra_bytes], FREED_POISON);
+    }
+
     // NOTE: we don't even check whether the refcount is "infinity" here!
     dealloc(allocation_ptr, alignment);
 
@@ -600,14 +618,112 @@ test "increfC, static data" {
     try std.testing.expectEqual(mock_rc, REFCOUNT_MAX_ISIZE);
 }
 
-// This returns a compilation dependent pseudo random seed for dictionaries.
-// The seed is the address of this function.
+test "decref poisons the refcount of a freed allocation" {
+    if (!POISON_FREED) return error.SkipZigTest;
+
+    const data = allocateWithRefcount(8, 8, false);
+    decref(data, 8, 8, false);
+
+    const poisoned = [_]u8{FREED_POISON} ** @sizeOf(usize);
+    try std.testing.expectEqualSlices(u8, &poisoned, testing_last_freed[0..@sizeOf(usize)]);
+}
+
+test "decref poisons the refcount and element count of a freed allocation" {
+    if (!POISON_FREED) return error.SkipZigTest;
+
+    const data = allocateWithRefcount(8, 8, true);
+    decref(data, 8, 8, true);
+
+    const poisoned = [_]u8{FREED_POISON} ** (2 * @sizeOf(usize));
+    try std.testing.expectEqualSlices(u8, &poisoned, testing_last_freed[0 .. 2 * @sizeOf(usize)]);
+}
+
+// The name of the environment variable that overrides the seed of every Dict in the process,
+// as a decimal or 0x-prefixed hex U64. This makes hashing, and so the iteration order of
+// Dicts and Sets, reproducible between runs, e.g. to reproduce a bug.
+// Never set it for a server that hashes untrusted input.
+pub const DICT_SEED_ENV_VAR = "ROC_DICT_SEED";
+
+// Zero means the seed hasn't been picked yet. A picked seed is never zero.
+var dict_seed: u64 = 0;
+
+// This returns a per-process random seed for dictionaries.
 // This avoids all roc Dicts using a known seed and being trivial to DOS.
-// Still not as secure as true random, but a lot better.
-// This value must not change between calls unless Dict is changed to store the seed on creation.
-// Note: On esstentially all OSes, this will be affected by ASLR and different each run.
-// In wasm, the value will be constant to the build as a whole.
-// Either way, it can not be know by an attacker unless they get access to the executable.
+// The seed is picked the first time this is called and then stays the same.
+// It must not change between calls unless Dict is changed to store the seed on creation.
+// On Linux and macOS the seed comes from the OS random number generator.
+// Elsewhere, it is mixed from addresses that ASLR makes different each run.
+// In wasm, there is no ASLR, so the value will be constant to the build as a whole.
+// Either way, it can not be known by an attacker unless they get access to the process.
 pub fn dictPseudoSeed() callconv(.C) u64 {
-    return @as(u64, @intCast(@intFromPtr(&dictPseudoSeed)));
+    if (builtin.target.cpu.arch == .wasm32) {
+        // wasm is single threaded, and 64 bit atomics might not be available.
+        if (dict_seed == 0) {
+            dict_seed = pickDictSeed();
+        }
+        return dict_seed;
+    }
+
+    const seed = @atomicLoad(u64, &dict_seed, .acquire);
+    if (seed != 0) {
+        return seed;
+    }
+
+    // If another thread picked a seed in the meantime, everyone must use that one.
+    const picked = pickDictSeed();
+    return @cmpxchgStrong(u64, &dict_seed, 0, picked, .acq_rel, .acquire) orelse picked;
+}
+
+fn pickDictSeed() u64 {
+    const seed = seedFromEnv() orelse randomSeed();
+
+    // Zero marks the seed as not picked yet, so it can't be used.
+    return if (seed == 0) 0x526F_6352_616E_643F else seed;
+}
+
+fn seedFromEnv() ?u64 {
+    if (builtin.os.tag != .macos and builtin.os.tag != .linux) {
+        return null;
+    }
+
+    // getenv is only there if the host links libc, so it's a weak symbol that is null otherwise.
+    const getenv = @extern(?*const fn ([*:0]const u8) callconv(.C) ?[*:0]const u8, .{ .name = "getenv", .linkage = .weak }) orelse return null;
+    const value = getenv(DICT_SEED_ENV_VAR) orelse return null;
+
+    return std.fmt.parseInt(u64, std.mem.span(value), 0) catch null;
+}
+
+fn randomSeed() u64 {
+    var bytes: [8]u8 = undefined;
+
+    if (builtin.os.tag == .linux) {
+        // A raw syscall, since the host might not link libc.
+        if (std.os.linux.getrandom(&bytes, bytes.len, 0) == bytes.len) {
+            return std.mem.readInt(u64, &bytes, .little);
+        }
+    } else if (builtin.os.tag == .macos) {
+        // libSystem is always linked on macOS.
+        if (getentropy(&bytes, bytes.len) == 0) {
+            return std.mem.readInt(u64, &bytes, .little);
+        }
+    }
+
+    var on_stack: u8 = 0;
+    const code = @as(u64, @intCast(@intFromPtr(&dictPseudoSeed)));
+    const stack = @as(u64, @intCast(@intFromPtr(&on_stack)));
+    return mixSeed(code, stack);
+}
+
+extern fn getentropy(buf: [*]u8, len: usize) c_int;
+
+// The wyhash mix, so every bit of both inputs affects every bit of the seed.
+fn mixSeed(a: u64, b: u64) u64 {
+    const r = std.math.mulWide(u64, a ^ 0xa076_1d64_78bd_642f, b ^ 0xe703_7ed1_a0b4_28db);
+    return @as(u64, @truncate(r)) ^ @as(u64, @truncate(r >> 64));
+}
+
+test "dictPseudoSeed, stays the same" {
+    const seed = dictPseudoSeed();
+    try std.testing.expect(seed != 0);
+    try std.testing.expectEqual(seed, dictPseudoSeed());
 }
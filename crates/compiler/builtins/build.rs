@@ -0,0 +1,52 @@
+use std::fmt::Write;
+use std::path::PathBuf;
+
+fn main() {
+    write_case_mappings();
+}
+
+/// Turn the case mappings the Zig builtins embed into a Rust table, so the
+/// compile-time interpreter maps case exactly like compiled programs do.
+fn write_case_mappings() {
+    let input_path = PathBuf::from("bitcode")
+        .join("src")
+        .join("case_mappings.txt");
+    println!("cargo:rerun-if-changed={}", input_path.to_str().unwrap());
+
+    let text = std::fs::read_to_string(&input_path)
+        .unwrap_or_else(|err| panic!("Failed to read {}: {err}", input_path.display()));
+
+    let mut output = String::from("&[\n");
+
+    for line in text.lines() {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut fields = line.split(';');
+        let code_point = parse_code_point(fields.next().unwrap(), None);
+        let upper = parse_code_point(fields.next().unwrap(), Some(code_point));
+        let lower = parse_code_point(fields.next().unwrap(), Some(code_point));
+        let fold = parse_code_point(fields.next().unwrap(), Some(code_point));
+
+        writeln!(
+            output,
+            "    ('\\u{{{code_point:x}}}', '\\u{{{upper:x}}}', '\\u{{{lower:x}}}', '\\u{{{fold:x}}}'),"
+        )
+        .unwrap();
+    }
+
+    output.push(']');
+
+    let output_path = PathBuf::from(std::env::var("OUT_DIR").unwrap()).join("case_mappings.rs");
+    std::fs::write(output_path, output).unwrap();
+}
+
+fn parse_code_point(field: &str, default: Option<u32>) -> u32 {
+    match (field, default) {
+        ("", Some(default)) => default,
+        _ => u32::from_str_radix(field, 16).unwrap_or_else(|err| {
+            panic!("Invalid code point {field:?} in case_mappings.txt: {err}")
+        }),
+    }
+}
@@ -0,0 +1,597 @@
+module [
+    Binary,
+    binary,
+    format_version,
+    to_bytes,
+    from_bytes,
+    RawTag,
+    raw_tag,
+]
+
+import List
+import Str
+import Result exposing [Result]
+import Bool exposing [Bool]
+import Num exposing [
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    F32,
+    F64,
+    Dec,
+]
+import Encode exposing [Encoder, Encoding, EncoderFormatting]
+import Decode exposing [Decoder, Decoding, DecoderFormatting, DecodeError, DecodeResult]
+
+## A compact binary format for [Encode] and [Decode], so values can be saved
+## to disk or passed to a platform host without writing the byte packing by
+## hand.
+##
+## ```roc
+## Point : { x : I32, y : I32 }
+##
+## expect
+##     point : Point
+##     point = { x: -1, y: 2 }
+##
+##     Binary.from_bytes(Binary.to_bytes(point)) == Ok(point)
+## ```
+##
+## ## Format
+##
+## - Integers are stored in little-endian order using their full width, so an
+## [I32] always takes 4 bytes.
+## - [F32] and [F64] are stored as their little-endian IEEE 754 bits, and [Dec]
+## as the little-endian [I128] from [Num.without_decimal_point].
+## - A [Bool] is a single byte, `0` or `1`.
+## - Lengths and counts are stored as unsigned [LEB128](https://en.wikipedia.org/wiki/LEB128)
+## numbers, which take a single byte when they are below 128.
+## - A [Str] is its length in bytes followed by its UTF-8 bytes.
+## - A [List] or tuple is its number of elements followed by the elements.
+## - A record is its number of fields followed by, for each field, the name of
+## the field as a [Str] and the length of the encoded value followed by the
+## value itself.
+## - A tag is its name as a [Str] and the number of payload values, followed by
+## each payload value prefixed with its length.
+##
+## Because fields and tags are identified by name rather than position, data
+## stays readable when fields are added or reordered, or tags are added to a
+## union: a decoder skips the fields it does not know about.
+##
+## [to_bytes] puts [format_version] in front of the encoded value, and
+## [from_bytes] rejects data written by a different version of the format.
+## Use [binary] directly with [Encode.to_bytes] and [Decode.from_bytes] to
+## leave the version out.
+Binary := {}
+    implements [
+        EncoderFormatting {
+            u8: encode_u8,
+            u16: encode_u16,
+            u32: encode_u32,
+            u64: encode_u64,
+            u128: encode_u128,
+            i8: encode_i8,
+            i16: encode_i16,
+            i32: encode_i32,
+            i64: encode_i64,
+            i128: encode_i128,
+            f32: encode_f32,
+            f64: encode_f64,
+            dec: encode_dec,
+            bool: encode_bool,
+            string: encode_string,
+            list: encode_list,
+            record: encode_record,
+            tuple: encode_tuple,
+            tag: encode_tag,
+        },
+        DecoderFormatting {
+            u8: decode_u8,
+            u16: decode_u16,
+            u32: decode_u32,
+            u64: decode_u64,
+            u128: decode_u128,
+            i8: decode_i8,
+            i16: decode_i16,
+            i32: decode_i32,
+            i64: decode_i64,
+            i128: decode_i128,
+            f32: decode_f32,
+            f64: decode_f64,
+            dec: decode_dec,
+            bool: decode_bool,
+            string: decode_string,
+            list: decode_list,
+            record: decode_record,
+            tuple: decode_tuple,
+        },
+    ]
+
+## The binary format, for use with [Encode] and [Decode].
+binary : Binary
+binary = @Binary({})
+
+## The version of the format written by [to_bytes]. It changes whenever the
+## encoding of some value changes.
+format_version : U8
+format_version = 1
+
+## Encode a value, preceded by the [format_version].
+to_bytes : val -> List U8 where val implements Encoding
+to_bytes = |val| Encode.append([format_version], val, binary)
+
+## Decode a value written by [to_bytes]. All the bytes must be used.
+from_bytes : List U8 -> Result val [Leftover (List U8), UnsupportedVersion U8]DecodeError where val implements Decoding
+from_bytes = |bytes|
+    when bytes is
+        [version, .. as rest] if version == format_version ->
+            Decode.from_bytes(rest, binary)
+            |> Result.map_err(
+                |err|
+                    when err is
+                        Leftover(leftover) -> Leftover(leftover)
+                        TooShort -> TooShort,
+            )
+
+        [version, ..] ->
+            Err(UnsupportedVersion(version))
+
+        [] ->
+            Err(TooShort)
+
+## A tag as it is stored in the binary format: its name, and the bytes of each
+## of its payload values.
+##
+## Decoding cannot be derived for tag unions, so [raw_tag] is the starting
+## point for decoding one by hand:
+## ```roc
+## Shape : [Circle F64, Rect F64 F64]
+##
+## decode_shape : List U8 -> Result Shape _
+## decode_shape = |bytes|
+##     { name, payload } = Decode.decode_with(bytes, Binary.raw_tag, Binary.binary).result?
+##     when (name, payload) is
+##         ("Circle", [r]) -> Ok(Circle(Decode.from_bytes(r, Binary.binary)?))
+##         ("Rect", [w, h]) -> Ok(Rect(Decode.from_bytes(w, Binary.binary)?, Decode.from_bytes(h, Binary.binary)?))
+##         _ -> Err(UnknownShape)
+## ```
+RawTag : { name : Str, payload : List (List U8) }
+
+## Decode a tag without knowing the types of its payload values. See [RawTag].
+raw_tag : Decoder RawTag Binary
+raw_tag = Decode.custom(|bytes, @Binary({})| from_read_result(read_raw_tag(bytes), bytes))
+
+read_raw_tag : List U8 -> Result (RawTag, List U8) DecodeError
+read_raw_tag = |bytes|
+    Result.try(
+        read_string(bytes),
+        |(name, after_name)|
+            Result.try(
+                read_varint(after_name),
+                |(count, after_count)|
+                    read_payloads(after_count, count, List.with_capacity(Num.min(count, List.len(after_count))))
+                    |> Result.map_ok(|(payload, rest)| ({ name, payload }, rest)),
+            ),
+    )
+
+read_payloads : List U8, U64, List (List U8) -> Result (List (List U8), List U8) DecodeError
+read_payloads = |bytes, remaining, payload|
+    if remaining == 0 then
+        Ok((payload, bytes))
+    else
+        when read_length_prefixed(bytes) is
+            Ok((value_bytes, rest)) -> read_payloads(rest, Num.sub_wrap(remaining, 1), List.append(payload, value_bytes))
+            Err(TooShort) -> Err(TooShort)
+
+# ENCODE
+
+encode_u8 : U8 -> Encoder Binary
+encode_u8 = |n| Encode.custom(|bytes, @Binary({})| List.append(bytes, n))
+
+encode_u16 : U16 -> Encoder Binary
+encode_u16 = |n| encode_le(Num.to_u128(n), 2)
+
+encode_u32 : U32 -> Encoder Binary
+encode_u32 = |n| encode_le(Num.to_u128(n), 4)
+
+encode_u64 : U64 -> Encoder Binary
+encode_u64 = |n| encode_le(Num.to_u128(n), 8)
+
+encode_u128 : U128 -> Encoder Binary
+encode_u128 = |n| encode_le(n, 16)
+
+encode_i8 : I8 -> Encoder Binary
+encode_i8 = |n| encode_le(Num.to_u128(n), 1)
+
+encode_i16 : I16 -> Encoder Binary
+encode_i16 = |n| encode_le(Num.to_u128(n), 2)
+
+encode_i32 : I32 -> Encoder Binary
+encode_i32 = |n| encode_le(Num.to_u128(n), 4)
+
+encode_i64 : I64 -> Encoder Binary
+encode_i64 = |n| encode_le(Num.to_u128(n), 8)
+
+encode_i128 : I128 -> Encoder Binary
+encode_i128 = |n| encode_le(Num.to_u128(n), 16)
+
+encode_f32 : F32 -> Encoder Binary
+encode_f32 = |n|
+    { sign, exponent, fraction } = Num.f32_to_parts(n)
+    bits =
+        Num.to_u128(fraction)
+        |> Num.bitwise_or(Num.shift_left_by(Num.to_u128(exponent), 23))
+        |> Num.bitwise_or(if sign then Num.shift_left_by(1, 31) else 0)
+
+    encode_le(bits, 4)
+
+encode_f64 : F64 -> Encoder Binary
+encode_f64 = |n|
+    { sign, exponent, fraction } = Num.f64_to_parts(n)
+    bits =
+        Num.to_u128(fraction)
+        |> Num.bitwise_or(Num.shift_left_by(Num.to_u128(exponent), 52))
+        |> Num.bitwise_or(if sign then Num.shift_left_by(1, 63) else 0)
+
+    encode_le(bits, 8)
+
+encode_dec : Dec -> Encoder Binary
+encode_dec = |n| encode_i128(Num.without_decimal_point(n))
+
+encode_bool : Bool -> Encoder Binary
+encode_bool = |b| encode_u8(if b then 1 else 0)
+
+encode_string : Str -> Encoder Binary
+encode_string = |str| Encode.custom(|bytes, @Binary({})| append_string(bytes, str))
+
+encode_list : List elem, (elem -> Encoder Binary) -> Encoder Binary
+encode_list = |list, encode_elem|
+    Encode.custom(
+        |bytes, @Binary({})|
+            List.walk(
+                list,
+                append_varint(bytes, List.len(list)),
+                |buf, elem| Encode.append_with(buf, encode_elem(elem), @Binary({})),
+            ),
+    )
+
+encode_record : List { key : Str, value : Encoder Binary } -> Encoder Binary
+encode_record = |fields|
+    Encode.custom(
+        |bytes, @Binary({})|
+            List.walk(
+                fields,
+                append_varint(bytes, List.len(fields)),
+                |buf, { key, value }|
+                    append_string(buf, key)
+                    |> append_length_prefixed(value),
+            ),
+    )
+
+encode_tuple : List (Encoder Binary) -> Encoder Binary
+encode_tuple = |elems| encode_list(elems, |elem| elem)
+
+encode_tag : Str, List (Encoder Binary) -> Encoder Binary
+encode_tag = |name, payload|
+    Encode.custom(
+        |bytes, @Binary({})|
+            List.walk(
+                payload,
+                append_string(bytes, name) |> append_varint(List.len(payload)),
+                append_length_prefixed,
+            ),
+    )
+
+encode_le : U128, U64 -> Encoder Binary
+encode_le = |value, width| Encode.custom(|bytes, @Binary({})| append_le(bytes, value, width))
+
+append_le : List U8, U128, U64 -> List U8
+append_le = |bytes, value, width|
+    if width == 0 then
+        bytes
+    else
+        List.append(bytes, Num.to_u8(value))
+        |> append_le(Num.shift_right_zf_by(value, 8), Num.sub_wrap(width, 1))
+
+append_varint : List U8, U64 -> List U8
+append_varint = |bytes, n|
+    if n < 0x80 then
+        List.append(bytes, Num.to_u8(n))
+    else
+        List.append(bytes, Num.to_u8(Num.bitwise_or(Num.bitwise_and(n, 0x7F), 0x80)))
+        |> append_varint(Num.shift_right_zf_by(n, 7))
+
+append_string : List U8, Str -> List U8
+append_string = |bytes, str|
+    append_varint(bytes, Str.count_utf8_bytes(str))
+    |> List.concat(Str.to_utf8(str))
+
+append_length_prefixed : List U8, Encoder Binary -> List U8
+append_length_prefixed = |bytes, encoder|
+    value_bytes = Encode.append_with([], encoder, @Binary({}))
+
+    append_varint(bytes, List.len(value_bytes))
+    |> List.concat(value_bytes)
+
+# DECODE
+
+decode_u8 : Decoder U8 Binary
+decode_u8 = decode_le(1, Num.to_u8)
+
+decode_u16 : Decoder U16 Binary
+decode_u16 = decode_le(2, Num.to_u16)
+
+decode_u32 : Decoder U32 Binary
+decode_u32 = decode_le(4, Num.to_u32)
+
+decode_u64 : Decoder U64 Binary
+decode_u64 = decode_le(8, Num.to_u64)
+
+decode_u128 : Decoder U128 Binary
+decode_u128 = decode_le(16, |n| n)
+
+decode_i8 : Decoder I8 Binary
+decode_i8 = decode_le(1, Num.to_i8)
+
+decode_i16 : Decoder I16 Binary
+decode_i16 = decode_le(2, Num.to_i16)
+
+decode_i32 : Decoder I32 Binary
+decode_i32 = decode_le(4, Num.to_i32)
+
+decode_i64 : Decoder I64 Binary
+decode_i64 = decode_le(8, Num.to_i64)
+
+decode_i128 : Decoder I128 Binary
+decode_i128 = decode_le(16, Num.to_i128)
+
+decode_f32 : Decoder F32 Binary
+decode_f32 = decode_le(
+    4,
+    |bits|
+        Num.f32_from_parts(
+            {
+                sign: Num.shift_right_zf_by(bits, 31) == 1,
+                exponent: Num.to_u8(Num.shift_right_zf_by(bits, 23)),
+                fraction: Num.to_u32(Num.bitwise_and(bits, 0x007F_FFFF)),
+            },
+        ),
+)
+
+decode_f64 : Decoder F64 Binary
+decode_f64 = decode_le(
+    8,
+    |bits|
+        Num.f64_from_parts(
+            {
+                sign: Num.shift_right_zf_by(bits, 63) == 1,
+                exponent: Num.to_u16(Num.bitwise_and(Num.shift_right_zf_by(bits, 52), 0x07FF)),
+                fraction: Num.to_u64(Num.bitwise_and(bits, 0x000F_FFFF_FFFF_FFFF)),
+            },
+        ),
+)
+
+decode_dec : Decoder Dec Binary
+decode_dec = decode_le(16, |bits| Num.with_decimal_point(Num.to_i128(bits)))
+
+decode_bool : Decoder Bool Binary
+decode_bool = Decode.custom(
+    |bytes, @Binary({})|
+        when bytes is
+            [0, .. as rest] -> { result: Ok(Bool.false), rest }
+            [1, .. as rest] -> { result: Ok(Bool.true), rest }
+            _ -> { result: Err(TooShort), rest: bytes },
+)
+
+decode_string : Decoder Str Binary
+decode_string = Decode.custom(|bytes, @Binary({})| from_read_result(read_string(bytes), bytes))
+
+decode_list : Decoder elem Binary -> Decoder (List elem) Binary
+decode_list = |elem_decoder|
+    Decode.custom(
+        |bytes, @Binary({})|
+            when read_varint(bytes) is
+                Ok((count, rest)) ->
+                    # The count is not trusted for the allocation, every element takes at least a byte
+                    # unless it is empty, in which case the list needs no space anyway.
+                    decode_elems(rest, elem_decoder, count, List.with_capacity(Num.min(count, List.len(rest))))
+
+                Err(TooShort) ->
+                    { result: Err(TooShort), rest: bytes },
+    )
+
+decode_elems : List U8, Decoder elem Binary, U64, List elem -> DecodeResult (List elem)
+decode_elems = |bytes, elem_decoder, remaining, elems|
+    if remaining == 0 then
+        { result: Ok(elems), rest: bytes }
+    else
+        { result, rest } = Decode.decode_with(bytes, elem_decoder, @Binary({}))
+        when result is
+            Ok(elem) -> decode_elems(rest, elem_decoder, Num.sub_wrap(remaining, 1), List.append(elems, elem))
+            Err(TooShort) -> { result: Err(TooShort), rest }
+
+decode_record : state, (state, Str -> [Keep (Decoder state Binary), Skip]), (state, Binary -> Result val DecodeError) -> Decoder val Binary
+decode_record = |initial_state, step_field, finalizer|
+    Decode.custom(
+        |bytes, @Binary({})|
+            result =
+                read_varint(bytes)
+                |> Result.try(|(count, after_count)| decode_fields(after_count, initial_state, step_field, count))
+                |> Result.try(|(state, rest)| Result.map_ok(finalizer(state, @Binary({})), |value| (value, rest)))
+
+            from_read_result(result, bytes),
+    )
+
+decode_fields : List U8, state, (state, Str -> [Keep (Decoder state Binary), Skip]), U64 -> Result (state, List U8) DecodeError
+decode_fields = |bytes, state, step_field, remaining|
+    if remaining == 0 then
+        Ok((state, bytes))
+    else
+        field =
+            read_string(bytes)
+            |> Result.try(|(name, after_name)| Result.map_ok(read_length_prefixed(after_name), |value| (name, value)))
+
+        when field is
+            Ok((name, (value_bytes, rest))) ->
+                when step_field(state, name) is
+                    Keep(value_decoder) ->
+                        # The value has to use up exactly the bytes that were set aside for it.
+                        when Decode.decode_with(value_bytes, value_decoder, @Binary({})) is
+                            { result: Ok(new_state), rest: [] } ->
+                                decode_fields(rest, new_state, step_field, Num.sub_wrap(remaining, 1))
+
+                            _ ->
+                                Err(TooShort)
+
+                    Skip ->
+                        decode_fields(rest, state, step_field, Num.sub_wrap(remaining, 1))
+
+            Err(TooShort) ->
+                Err(TooShort)
+
+decode_tuple : state, (state, U64 -> [Next (Decoder state Binary), TooLong]), (state -> Result val DecodeError) -> Decoder val Binary
+decode_tuple = |initial_state, step_elem, finalizer|
+    Decode.custom(
+        |bytes, @Binary({})|
+            result =
+                read_varint(bytes)
+                |> Result.try(|(count, after_count)| decode_tuple_elems(after_count, initial_state, step_elem, 0, count))
+                |> Result.try(|(state, rest)| Result.map_ok(finalizer(state), |value| (value, rest)))
+
+            from_read_result(result, bytes),
+    )
+
+decode_tuple_elems : List U8, state, (state, U64 -> [Next (Decoder state Binary), TooLong]), U64, U64 -> Result (state, List U8) DecodeError
+decode_tuple_elems = |bytes, state, step_elem, index, count|
+    if index == count then
+        Ok((state, bytes))
+    else
+        when step_elem(state, index) is
+            Next(elem_decoder) ->
+                when Decode.decode_with(bytes, elem_decoder, @Binary({})) is
+                    { result: Ok(new_state), rest } ->
+                        decode_tuple_elems(rest, new_state, step_elem, Num.add_wrap(index, 1), count)
+
+                    { result: Err(TooShort) } ->
+                        Err(TooShort)
+
+            TooLong ->
+                Err(TooShort)
+
+decode_le : U64, (U128 -> a) -> Decoder a Binary
+decode_le = |width, convert|
+    Decode.custom(
+        |bytes, @Binary({})|
+            if List.len(bytes) < width then
+                { result: Err(TooShort), rest: bytes }
+            else
+                { before, others } = List.split_at(bytes, width)
+                value = List.walk_backwards(before, 0, |acc, byte| Num.bitwise_or(Num.shift_left_by(acc, 8), Num.to_u128(byte)))
+
+                { result: Ok(convert(value)), rest: others },
+    )
+
+# The helpers below return the rest of the input along with the value, so
+# they can be chained with [Result.try]. A failed decode keeps all of its input.
+from_read_result : Result (a, List U8) DecodeError, List U8 -> DecodeResult a
+from_read_result = |result, bytes|
+    when result is
+        Ok((value, rest)) -> { result: Ok(value), rest }
+        Err(TooShort) -> { result: Err(TooShort), rest: bytes }
+
+read_varint : List U8 -> Result (U64, List U8) DecodeError
+read_varint = |bytes| read_varint_help(bytes, 0, 0, 0)
+
+read_varint_help : List U8, U64, U8, U64 -> Result (U64, List U8) DecodeError
+read_varint_help = |bytes, index, shift, value|
+    when List.get(bytes, index) is
+        Ok(byte) ->
+            new_value = Num.bitwise_or(value, Num.shift_left_by(Num.to_u64(Num.bitwise_and(byte, 0x7F)), shift))
+            if byte < 0x80 then
+                Ok((new_value, List.drop_first(bytes, Num.add_wrap(index, 1))))
+            else if shift >= 63 then
+                Err(TooShort)
+            else
+                read_varint_help(bytes, Num.add_wrap(index, 1), Num.add_wrap(shift, 7), new_value)
+
+        Err(OutOfBounds) ->
+            Err(TooShort)
+
+read_length_prefixed : List U8 -> Result (List U8, List U8) DecodeError
+read_length_prefixed = |bytes|
+    when read_varint(bytes) is
+        Ok((len, rest)) if List.len(rest) >= len ->
+            { before, others } = List.split_at(rest, len)
+            Ok((before, others))
+
+        _ ->
+            Err(TooShort)
+
+read_string : List U8 -> Result (Str, List U8) DecodeError
+read_string = |bytes|
+    when read_length_prefixed(bytes) is
+        Ok((utf8, rest)) ->
+            when Str.from_utf8(utf8) is
+                Ok(str) -> Ok((str, rest))
+                Err(_) -> Err(TooShort)
+
+        Err(TooShort) ->
+            Err(TooShort)
+
+expect
+    actual = Encode.to_bytes(300u16, binary)
+    actual == [0x2C, 0x01]
+
+expect
+    actual = Encode.to_bytes(-2i32, binary)
+    actual == [0xFE, 0xFF, 0xFF, 0xFF]
+
+expect
+    actual = Encode.to_bytes("hey", binary)
+    actual == [3, 'h', 'e', 'y']
+
+expect
+    actual = Encode.to_bytes(List.repeat(Bool.true, 200), binary)
+    List.take_first(actual, 3) == [0xC8, 0x01, 1] and List.len(actual) == 202
+
+expect
+    actual = Encode.to_bytes({ a: 1u8, bc: "x" }, binary)
+    actual == [2, 1, 'a', 1, 1, 2, 'b', 'c', 2, 1, 'x']
+
+expect
+    actual = Encode.to_bytes(1.0f64, binary)
+    actual == [0, 0, 0, 0, 0, 0, 0xF0, 0x3F]
+
+expect
+    value : Result U8 Str
+    value = Ok(5)
+    actual = Encode.to_bytes(value, binary)
+    actual == [2, 'O', 'k', 1, 1, 5]
+
+expect
+    input = { foo: (1u64, "abc", [Bool.false, Bool.true]), bar: { baz: -0.32dec, qux: -7i16 } }
+    decoded = from_bytes(to_bytes(input))
+    decoded == Ok(input)
+
+expect
+    decoded : Result { a : U8 } _
+    decoded = Decode.from_bytes(Encode.to_bytes({ a: 1u8, b: "ignored" }, binary), binary)
+    decoded == Ok({ a: 1 })
+
+expect
+    decoded : Result U32 _
+    decoded = from_bytes([2, 0, 0, 0, 0])
+    decoded == Err(UnsupportedVersion(2))
+
+expect
+    value : Result {} (Str, U8)
+    value = Err(("a", 7))
+    decoded = Decode.decode_with(Encode.to_bytes(value, binary), raw_tag, binary)
+    decoded.result == Ok({ name: "Err", payload: [[2, 1, 'a', 7]] }) and List.is_empty(decoded.rest)
@@ -28,6 +28,11 @@ module [
     from_bytes_partial,
     from_bytes,
     map_result,
+    DecodePartial,
+    partial,
+    partial_with,
+    feed,
+    finish,
 ]
 
 import List
@@ -166,3 +171,76 @@ from_bytes = |bytes, fmt|
 ## Transform the `val` of a [DecodeResult]
 map_result : DecodeResult a, (a -> b) -> DecodeResult b
 map_result = |{ result, rest }, mapper| { result: Result.map_ok(result, mapper), rest }
+
+## The state of a decoder that is consuming its input one chunk at a time.
+##
+## This lets a platform host decode a long stream of values, such as the rows
+## of a large CSV file or newline-delimited JSON, as the bytes arrive instead
+## of buffering the whole input first. Each call to [feed] returns the values
+## that are complete so far, and only the bytes of the value still being
+## received are kept around.
+##
+## ```roc
+## state0 = Decode.partial(Json.json)
+## { values: first, partial: state1 } = Decode.feed(state0, Str.to_utf8("1 2 3"))
+## { values: second, partial: state2 } = Decode.feed(state1, Str.to_utf8("4 5"))
+##
+## expect first == [1, 2]
+## expect second == [34]
+## expect Decode.finish(state2) == Ok([5])
+## ```
+DecodePartial val fmt := { decoder : Decoder val fmt, fmt : fmt, pending : List U8 } where fmt implements DecoderFormatting
+
+## Start decoding a stream of values using the [Decoding] ability of `val`.
+partial : fmt -> DecodePartial val fmt where val implements Decoding, fmt implements DecoderFormatting
+partial = |fmt| partial_with(decoder, fmt)
+
+## Start decoding a stream of values using a specific [Decoder].
+partial_with : Decoder val fmt, fmt -> DecodePartial val fmt where fmt implements DecoderFormatting
+partial_with = |value_decoder, fmt| @DecodePartial({ decoder: value_decoder, fmt, pending: [] })
+
+## Add the next chunk of input, and decode every value that is now complete.
+##
+## A value that runs right up to the end of the input so far is not returned
+## yet, because the next chunk might continue it (the `12` in `"12"` could
+## turn out to be the start of `"123"`). Values that fail to decode are also
+## held back on the assumption that the rest of them has not arrived; [finish]
+## reports the error if it never does.
+feed : DecodePartial val fmt, List U8 -> { values : List val, partial : DecodePartial val fmt } where fmt implements DecoderFormatting
+feed = |@DecodePartial({ decoder: value_decoder, fmt, pending }), chunk|
+    { values, rest } = decode_complete(List.concat(pending, chunk), value_decoder, fmt, [])
+
+    { values, partial: @DecodePartial({ decoder: value_decoder, fmt, pending: rest }) }
+
+decode_complete : List U8, Decoder val fmt, fmt, List val -> { values : List val, rest : List U8 } where fmt implements DecoderFormatting
+decode_complete = |bytes, value_decoder, fmt, values|
+    { result, rest } = decode_with(bytes, value_decoder, fmt)
+    when result is
+        Ok(val) if !(List.is_empty(rest)) and List.len(rest) < List.len(bytes) ->
+            decode_complete(rest, value_decoder, fmt, List.append(values, val))
+
+        _ ->
+            { values, rest: bytes }
+
+## Signal the end of the input, and decode the values that are still pending.
+##
+## Returns an error if the remaining bytes are not a sequence of whole values.
+finish : DecodePartial val fmt -> Result (List val) [Leftover (List U8)]DecodeError where fmt implements DecoderFormatting
+finish = |@DecodePartial({ decoder: value_decoder, fmt, pending })|
+    finish_help(pending, value_decoder, fmt, [])
+
+finish_help : List U8, Decoder val fmt, fmt, List val -> Result (List val) [Leftover (List U8)]DecodeError where fmt implements DecoderFormatting
+finish_help = |bytes, value_decoder, fmt, values|
+    if List.is_empty(bytes) then
+        Ok(values)
+    else
+        { result, rest } = decode_with(bytes, value_decoder, fmt)
+        when result is
+            Ok(val) if List.len(rest) < List.len(bytes) ->
+                finish_help(rest, value_decoder, fmt, List.append(values, val))
+
+            Ok(_) ->
+                Err(Leftover(bytes))
+
+            Err(TooShort) ->
+                Err(TooShort)
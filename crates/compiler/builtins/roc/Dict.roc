@@ -25,8 +25,12 @@ module [
     insert_all,
     keep_shared,
     remove_all,
+    union,
+    intersect,
+    difference,
     map,
     join_map,
+    hash_problems,
 ]
 
 import Bool exposing [Bool, Eq]
@@ -65,9 +69,11 @@ import Inspect exposing [Inspect, Inspector, InspectFormatter]
 ## We can use [Dict.keys] and [Dict.values] functions to get only the keys or
 ## only the values.
 ##
-## You may notice that these lists have the same order as the original insertion
-## order. This will be true if all you ever do is [Dict.insert] and [Dict.get] operations
-## on the dictionary, but [Dict.remove] operations can change this order.
+## These lists are always in insertion order: an entry appears in the position
+## where its key was first inserted. Inserting a new value for an existing key
+## keeps the key where it was, and removing an entry leaves the relative order
+## of the others unchanged. Every function that walks a dictionary, such as
+## [Dict.walk] or [Dict.to_list], visits the entries in this same order.
 ##
 ## ## Removing
 ##
@@ -77,16 +83,13 @@ import Inspect exposing [Inspect, Inspector, InspectFormatter]
 ##     |> Dict.remove("Philadelphia")
 ##     |> Dict.keys
 ##     ==
-##     ["London", "Amsterdam", "Shanghai", "Delhi"]
+##     ["London", "Shanghai", "Delhi", "Amsterdam"]
 ## ```
-## Notice that the order has changed. Philadelphia was not only removed from the
-## list, but Amsterdam - the last entry we inserted - has been moved into the
-## spot where Philadelphia was previously. This is exactly what [Dict.remove]
-## does. It removes an element and moves the most recent insertion into the
-## vacated spot.
-##
-## This move is done as a performance optimization, and it lets [remove] have
-## [constant time complexity](https://en.wikipedia.org/wiki/Time_complexity#Constant_time).
+## Keeping the order means the entries after Philadelphia have to move down by
+## one, so [Dict.remove] takes time proportional to the size of the dictionary.
+## When removing many entries at once, prefer [Dict.keep_if], [Dict.drop_if], or
+## [Dict.difference], which do the work of a single removal no matter how many
+## entries they drop.
 ##
 ## Dict is inspired by [IndexMap](https://docs.rs/indexmap/latest/indexmap/map/struct.IndexMap.html).
 ## The internal implementation of a dictionary is almost identical to [ankerl::unordered_dense](https://github.com/martinus/unordered_dense).
@@ -388,19 +391,8 @@ walk_until = |@Dict({ data }), initial_state, transform|
 ##     |> Bool.is_eq(2)
 ## ```
 keep_if : Dict k v, ((k, v) -> Bool) -> Dict k v
-keep_if = |dict, predicate|
-    keep_if_help(dict, predicate, 0, Dict.len(dict))
-
-keep_if_help : Dict k v, ((k, v) -> Bool), U64, U64 -> Dict k v
-keep_if_help = |@Dict(dict), predicate, index, length|
-    if index < length then
-        (key, value) = list_get_unsafe(dict.data, index)
-        if predicate((key, value)) then
-            keep_if_help(@Dict(dict), predicate, Num.add_wrap(index, 1), length)
-        else
-            keep_if_help(Dict.remove(@Dict(dict), key), predicate, index, Num.sub_wrap(length, 1))
-    else
-        @Dict(dict)
+keep_if = |@Dict({ buckets, data, max_bucket_capacity, max_load_factor, shifts }), predicate|
+    rebuild_buckets(@Dict({ buckets, data: List.keep_if(data, predicate), max_bucket_capacity, max_load_factor, shifts }))
 
 ## Run the given function on each key-value pair of a dictionary, and return
 ## a dictionary with just the pairs for which the function returned `Bool.false`.
@@ -492,7 +484,8 @@ insert_helper = |buckets0, data0, bucket_index0, dist_and_fingerprint0, key, val
         dist_and_fingerprint1 = increment_dist(dist_and_fingerprint0)
         insert_helper(buckets0, data0, bucket_index1, dist_and_fingerprint1, key, value, max_bucket_capacity, max_load_factor, shifts)
 
-## Remove a value from the dictionary for a specified key.
+## Remove a value from the dictionary for a specified key. The remaining entries
+## keep their insertion order.
 ## ```roc
 ## expect
 ##     Dict.empty({})
@@ -642,6 +635,7 @@ values = |@Dict({ data })|
 ## both dictionaries will be combined. Note that where there are pairs
 ## with the same key, the value contained in the second input will be
 ## retained, and the value in the first input will be removed.
+## This is the same as [Dict.union].
 ## ```roc
 ## first =
 ##     Dict.single(1, "Not Me")
@@ -663,14 +657,12 @@ values = |@Dict({ data })|
 ## ```
 insert_all : Dict k v, Dict k v -> Dict k v
 insert_all = |xs, ys|
-    if len(ys) > len(xs) then
-        insert_all(ys, xs)
-    else
-        walk(ys, xs, insert)
+    union(xs, ys)
 
 ## Combine two dictionaries by keeping the [intersection](https://en.wikipedia.org/wiki/Intersection_(set_theory))
 ## of all the key-value pairs. This means that we keep only those pairs
 ## that are in both dictionaries. Both the key and value must match to be kept.
+## The result keeps the insertion order of the first dictionary.
 ## ```roc
 ## first =
 ##     Dict.single(1, "Keep Me")
@@ -690,29 +682,22 @@ insert_all = |xs, ys|
 ## expect Dict.keep_shared(first, second) == expected
 ## ```
 keep_shared : Dict k v, Dict k v -> Dict k v where v implements Eq
-keep_shared = |xs0, ys0|
-    (xs1, ys1) =
-        if len(ys0) < len(xs0) then
-            (ys0, xs0)
-        else
-            (xs0, ys0)
-
-    walk(
-        xs1,
-        with_capacity(len(xs1)),
-        |state, k, v|
-            when get(ys1, k) is
-                Ok(yv) if v == yv ->
-                    insert(state, k, v)
-
-                _ ->
-                    state,
+keep_shared = |@Dict({ buckets, data, max_bucket_capacity, max_load_factor, shifts }), ys|
+    shared = List.keep_if(
+        data,
+        |(k, v)|
+            when get(ys, k) is
+                Ok(yv) -> v == yv
+                Err(KeyNotFound) -> Bool.false,
     )
 
+    rebuild_buckets(@Dict({ buckets, data: shared, max_bucket_capacity, max_load_factor, shifts }))
+
 ## Remove the key-value pairs in the first input that are also in the second
 ## using the [set difference](https://en.wikipedia.org/wiki/Complement_(set_theory)#Relative_complement)
 ## of the values. This means that we will be left with only those pairs that
 ## are in the first dictionary and whose keys are not in the second.
+## This is the same as [Dict.difference].
 ## ```roc
 ## first =
 ##     Dict.single(1, "Keep Me")
@@ -731,7 +716,96 @@ keep_shared = |xs0, ys0|
 ## ```
 remove_all : Dict k v, Dict k v -> Dict k v
 remove_all = |xs, ys|
-    walk(ys, xs, |state, k, _| remove(state, k))
+    difference(xs, ys)
+
+## Combine two dictionaries into one containing every key from either of them.
+## Where both dictionaries contain a key, the value from the second one is kept.
+##
+## The result is in insertion order: first the keys of the first dictionary in
+## their original order, followed by the keys that only the second dictionary
+## has, in its order.
+## ```roc
+## first =
+##     Dict.single(1, "Not Me")
+##     |> Dict.insert(2, "And Me")
+##
+## second =
+##     Dict.single(3, "Me Too")
+##     |> Dict.insert(1, "Keep Me")
+##
+## expect
+##     Dict.union(first, second)
+##     |> Dict.to_list
+##     |> Bool.is_eq([(1, "Keep Me"), (2, "And Me"), (3, "Me Too")])
+## ```
+##
+## ## Performance Details
+##
+## This builds the combined entries directly and then fills in the hash table
+## once, rather than inserting the entries of the second dictionary one by one.
+union : Dict k v, Dict k v -> Dict k v
+union = |xs, ys|
+    if is_empty(ys) then
+        xs
+    else
+        added = List.drop_if(to_list(ys), |(k, _)| contains(xs, k))
+        @Dict({ buckets, data, max_bucket_capacity, max_load_factor, shifts }) = xs
+        updated = List.map(
+            data,
+            |(k, v)|
+                when get(ys, k) is
+                    Ok(yv) -> (k, yv)
+                    Err(KeyNotFound) -> (k, v),
+        )
+
+        rebuild_buckets(@Dict({ buckets, data: List.concat(updated, added), max_bucket_capacity, max_load_factor, shifts }))
+
+## Keep only the keys of the first dictionary that are also keys of the second,
+## along with their values from the first dictionary. Unlike [Dict.keep_shared],
+## the values do not need to match, or even be comparable.
+##
+## The result keeps the insertion order of the first dictionary.
+## ```roc
+## first =
+##     Dict.single(1, "Keep Me")
+##     |> Dict.insert(2, "Not Me")
+##     |> Dict.insert(3, "And Me")
+##
+## second =
+##     Dict.single(3, Bool.true)
+##     |> Dict.insert(1, Bool.false)
+##
+## expect
+##     Dict.intersect(first, second)
+##     |> Dict.to_list
+##     |> Bool.is_eq([(1, "Keep Me"), (3, "And Me")])
+## ```
+intersect : Dict k a, Dict k b -> Dict k a
+intersect = |@Dict({ buckets, data, max_bucket_capacity, max_load_factor, shifts }), ys|
+    rebuild_buckets(@Dict({ buckets, data: List.keep_if(data, |(k, _)| contains(ys, k)), max_bucket_capacity, max_load_factor, shifts }))
+
+## Remove every key of the first dictionary that is also a key of the second.
+## The values in the second dictionary are ignored.
+##
+## The result keeps the insertion order of the first dictionary.
+## ```roc
+## first =
+##     Dict.single(1, "Keep Me")
+##     |> Dict.insert(2, "Remove Me")
+##     |> Dict.insert(3, "And Keep Me")
+##
+## second =
+##     Dict.single(2, {})
+##     |> Dict.insert(4, {})
+##
+## expect
+##     Dict.difference(first, second)
+##     |> Dict.to_list
+##     |> Bool.is_eq([(1, "Keep Me"), (3, "And Keep Me")])
+## ```
+difference : Dict k a, Dict k b -> Dict k a
+difference = |@Dict({ buckets, data, max_bucket_capacity, max_load_factor, shifts }), ys|
+    rebuild_buckets(@Dict({ buckets, data: List.drop_if(data, |(k, _)| contains(ys, k)), max_bucket_capacity, max_load_factor, shifts }))
 
 # Below here is a list of generic helpers and internal data types for Dict
 Bucket : {
@@ -821,21 +895,21 @@ remove_bucket = |@Dict({ buckets: buckets0, data: data0, max_bucket_capacity, ma
 
     last_data_index = List.len(data0) |> Num.sub_wrap(1)
     if data_index_to_remove_u64 != last_data_index then
-        # Swap removed item to the end
-        data1 = List.swap(data0, data_index_to_remove_u64, last_data_index)
-        (key, _) = list_get_unsafe(data1, data_index_to_remove_u64)
-
-        # Update the data index of the new value.
-        hash = hash_key(key)
-        bucket_index2 = bucket_index_from_hash(hash, shifts)
-
-        bucket_index3 = scan_for_index(buckets2, bucket_index2, Num.to_u32(last_data_index))
-        swap_bucket = list_get_unsafe(buckets2, bucket_index3)
+        # Shift every later entry down by one to keep the insertion order,
+        # and point their buckets at the new positions.
+        buckets3 = List.map(
+            buckets2,
+            |bucket|
+                if bucket.dist_and_fingerprint != 0 and bucket.data_index > data_index_to_remove then
+                    { bucket & data_index: Num.sub_wrap(bucket.data_index, 1) }
+                else
+                    bucket,
+        )
 
         @Dict(
             {
-                buckets: List.set(buckets2, bucket_index3, { swap_bucket & data_index: data_index_to_remove }),
-                data: List.drop_last(data1, 1),
+                buckets: buckets3,
+                data: List.drop_at(data0, data_index_to_remove_u64),
                 max_bucket_capacity,
                 max_load_factor,
                 shifts,
@@ -852,14 +926,6 @@ remove_bucket = |@Dict({ buckets: buckets0, data: data0, max_bucket_capacity, ma
             },
         )
 
-scan_for_index : List Bucket, U64, U32 -> U64
-scan_for_index = |buckets, bucket_index, data_index|
-    bucket = list_get_unsafe(buckets, bucket_index)
-    if bucket.data_index != data_index then
-        scan_for_index(buckets, next_bucket_index(bucket_index, List.len(buckets)), data_index)
-    else
-        bucket_index
-
 remove_bucket_helper : List Bucket, U64 -> (List Bucket, U64)
 remove_bucket_helper = |buckets, bucket_index|
     next_index = next_bucket_index(bucket_index, List.len(buckets))
@@ -889,6 +955,21 @@ increase_size = |@Dict({ data, max_bucket_capacity, max_load_factor, shifts })|
     else
         crash("Dict hit limit of ${Num.to_str(max_bucket_count)} elements. Unable to grow more.")
 
+# Recompute the buckets after the data has been replaced wholesale.
+# The keys in the data must already be unique.
+# The existing buckets are reused unless there are now too many entries for them.
+rebuild_buckets : Dict k v -> Dict k v
+rebuild_buckets = |@Dict({ buckets, data, max_bucket_capacity: original_max_bucket_capacity, max_load_factor, shifts: original_shifts })|
+    requested_shifts = calc_shifts_for_size(List.len(data), max_load_factor)
+    if requested_shifts < original_shifts or (List.is_empty(buckets) and !(List.is_empty(data))) then
+        (buckets0, max_bucket_capacity) = alloc_buckets_from_shift(requested_shifts, max_load_factor)
+        buckets1 = fill_buckets_from_data(buckets0, data, requested_shifts)
+        @Dict({ buckets: buckets1, data, max_bucket_capacity, max_load_factor, shifts: requested_shifts })
+    else
+        buckets0 = List.map(buckets, |_| empty_bucket)
+        buckets1 = fill_buckets_from_data(buckets0, data, original_shifts)
+        @Dict({ buckets: buckets1, data, max_bucket_capacity: original_max_bucket_capacity, max_load_factor, shifts: original_shifts })
+
 alloc_buckets_from_shift : U8, F32 -> (List Bucket, U64)
 alloc_buckets_from_shift = |shifts, max_load_factor|
     bucket_count = calc_num_buckets(shifts)
@@ -1105,7 +1186,7 @@ expect
         |> remove(3)
         |> keys
 
-    list == [0, 2]
+    list == [2, 0]
 
 # Reach capacity, no rehash.
 expect
@@ -1271,6 +1352,82 @@ expect
 
     all_inserted_correctly
 
+## Checks that the [Hash] implementation of a key type agrees with its [Eq] implementation,
+## the way [Dict] and [Set] rely on, using the given values. It returns a description of
+## each problem it finds.
+##
+## Values that are equal must hash the same, and values that aren't should hash differently.
+## The values are also hashed two in a row, like the fields of a record are, to check that the
+## end of one value can't be confused with the start of the next.
+##
+## Pass values that are equal to each other, and values that are only a little different:
+## ```roc
+## expect
+##     Dict.hash_problems([@Id(1), @Id(2), @Id(1), @Id(0)])
+##     |> List.is_empty
+## ```
+## Hashing uses a fixed seed, so the result is the same on every run.
+hash_problems : List k -> List Str where k implements Hash & Eq
+hash_problems = |values|
+    indexes = List.map_with_index(values, |_, index| index)
+    hashes = List.map(values, |value| hash_in_order([value]))
+    equal_at = |xs, ys|
+        List.map2(xs, ys, |x, y| list_get_unsafe(values, x) == list_get_unsafe(values, y))
+        |> List.all(|equal| equal)
+
+    hashed_differently =
+        List.join_map(
+            indexes,
+            |a|
+                List.drop_first(indexes, Num.add_wrap(a, 1))
+                |> List.join_map(
+                    |b|
+                        if equal_at([a], [b]) and list_get_unsafe(hashes, a) != list_get_unsafe(hashes, b) then
+                            ["Values ${Num.to_str(a)} and ${Num.to_str(b)} are equal, but hash differently."]
+                        else
+                            [],
+                ),
+        )
+
+    singles = List.map(indexes, |a| { at: [a], hash: list_get_unsafe(hashes, a) })
+    pairs =
+        List.join_map(
+            indexes,
+            |a|
+                List.map(
+                    indexes,
+                    |b| { at: [a, b], hash: hash_in_order([list_get_unsafe(values, a), list_get_unsafe(values, b)]) },
+                ),
+        )
+    # Hashes that are the same end up next to each other.
+    sorted = List.concat(singles, pairs) |> List.sort_with(|x, y| Num.compare(x.hash, y.hash))
+
+    hashed_the_same =
+        List.map2(
+            sorted,
+            List.drop_first(sorted, 1),
+            |x, y|
+                if x.hash == y.hash and List.len(x.at) == List.len(y.at) and !(equal_at(x.at, y.at)) then
+                    ["Hashing ${describe_hashed(x.at)} and hashing ${describe_hashed(y.at)} give the same hash, but they are not equal."]
+                else
+                    [],
+        )
+        |> List.join
+
+    List.concat(hashed_differently, hashed_the_same)
+
+hash_in_order : List k -> U64 where k implements Hash
+hash_in_order = |values|
+    create_low_level_hasher(test_seed)
+    |> Hash.hash_list(values)
+    |> complete
+
+describe_hashed : List U64 -> Str
+describe_hashed = |at|
+    when at is
+        [a] -> "value ${Num.to_str(a)}"
+        _ -> "values ${Str.join_with(List.map(at, Num.to_str), " then ")}"
+
 # Note, there are a number of places we should probably use set and replace unsafe.
 # unsafe primitive that does not perform a bounds check
 list_get_unsafe : List a, U64 -> a
@@ -1294,8 +1451,9 @@ LowLevelHasher := { initialized_seed : U64, state : U64 } implements [
         },
     ]
 
-# Returns a application specific pseudo random seed for Dict.
-# This avoids trivial DOS attacks.
+# Returns a per-process random seed for Dict, the same one for every call.
+# This avoids trivial DOS attacks. Setting the ROC_DICT_SEED environment variable
+# to a U64 fixes the seed, to make hashing reproducible between runs.
 pseudo_seed : {} -> U64
 
 create_low_level_hasher : [PseudoRandSeed, WithSeed U64] -> LowLevelHasher
@@ -1685,6 +1843,57 @@ expect
 
     hash1 != hash2
 
+expect
+    hash1 =
+        create_low_level_hasher(WithSeed(1))
+        |> add_u64(42)
+        |> complete
+
+    hash2 =
+        create_low_level_hasher(WithSeed(2))
+        |> add_u64(42)
+        |> complete
+
+    hash1 != hash2
+
+# Flipping any one bit of a key changes about half of the bits of its hash.
+expect
+    hash_u64 = |n|
+        create_low_level_hasher(test_seed)
+        |> add_u64(n)
+        |> complete
+
+    key = 0x0123_4567_89AB_CDEF
+    changed_bits =
+        List.range({ start: At(0u8), end: Before(64u8) })
+        |> List.map(
+            |bit|
+                Num.bitwise_xor(hash_u64(key), hash_u64(Num.bitwise_xor(key, Num.shift_left_by(1, bit))))
+                |> Num.count_one_bits
+                |> Num.to_u64,
+        )
+    total = List.sum(changed_bits)
+
+    List.all(changed_bits, |n| n >= 12 and n <= 52) and total >= 1792 and total <= 2304
+
+expect
+    hash_problems([0u64, 1, 2, 0, 0xFFFF_FFFF_FFFF_FFFF, 1, 0x8000_0000_0000_0000])
+    |> List.is_empty
+
+expect
+    hash_problems(["", "a", "ab", "b", "ba", "", "a", "a longer string that is hashed in chunks of many bytes"])
+    |> List.is_empty
+
+expect
+    hash_problems([(1u8, "a"), (1u8, "b"), (2u8, "a"), (1u8, "a"), (0u8, "")])
+    |> List.is_empty
+
+# Lists don't hash their length, so [1] then [2, 3] hashes like [1, 2] then [3].
+expect
+    problems = hash_problems([[1u8], [2u8, 3u8], [1u8, 2u8], [3u8]])
+
+    List.len(problems) == 1
+
 expect
     empty({})
     |> len
@@ -1774,3 +1983,25 @@ expect
         |> Dict.insert(3, 3)
 
     d1 == d2
+
+expect
+    d1 =
+        Dict.from_list([(0, 0), (1, 1), (2, 2), (3, 3), (4, 4)])
+        |> Dict.remove(1)
+        |> Dict.remove(3)
+
+    Dict.keys(d1) == [0, 2, 4] and Dict.get(d1, 4) == Ok(4) and !(Dict.contains(d1, 3))
+
+expect
+    d1 = Dict.from_list([(1, "a"), (2, "b")])
+    d2 = Dict.from_list([(3, "c"), (2, "B"), (4, "d"), (5, "e"), (6, "f"), (7, "g")])
+    combined = Dict.union(d1, d2)
+
+    Dict.to_list(combined) == [(1, "a"), (2, "B"), (3, "c"), (4, "d"), (5, "e"), (6, "f"), (7, "g")]
+    and Dict.get(combined, 7) == Ok("g")
+
+expect
+    d1 = Dict.from_list([(1, "a"), (2, "b"), (3, "c")])
+    d2 = Dict.from_list([(3, {}), (1, {})])
+
+    Dict.keys(Dict.intersect(d1, d2)) == [1, 3] and Dict.keys(Dict.difference(d1, d2)) == [2]
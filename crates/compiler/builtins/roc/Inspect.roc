@@ -101,9 +101,11 @@ to_str = |val|
     |> to_dbg_str
 
 # The current default formatter for inspect.
-# This just returns a simple string for debugging.
-# More powerful formatters will likely be wanted in the future.
-DbgFormatter := { data : Str }
+# It renders values like Roc source code. A list, record, tuple, or tag that
+# fits in the remaining width stays on one line; otherwise each of its items
+# goes on its own indented line, the same way the formatter lays out
+# multiline collections.
+DbgFormatter := { tokens : List DbgToken }
     implements [
         InspectFormatter {
             init: dbg_init,
@@ -133,78 +135,75 @@ DbgFormatter := { data : Str }
         },
     ]
 
+# Every `Open` is matched by a `Close`, and each item of the group starts with an `Item`.
+DbgToken : [
+    Text Str,
+    Open DbgGroup,
+    Item,
+    Close,
+]
+
+# How a group renders on a single line: `open`, then `lead` before the
+# first item and `sep` between items, then `close`. When it is split over
+# several lines, `trailing` follows every item instead.
+DbgGroup : { open : Str, close : Str, lead : Str, sep : Str, trailing : Str }
+
+dbg_max_width = 80
+dbg_indent = 4
+
+dbg_collection : Str, Str -> DbgGroup
+dbg_collection = |open, close| { open, close, lead: "", sep: ", ", trailing: "," }
+
 dbg_init : {} -> DbgFormatter
-dbg_init = |{}| @DbgFormatter({ data: "" })
+dbg_init = |{}| @DbgFormatter({ tokens: [] })
 
-dbg_list : list, ElemWalker (DbgFormatter, Bool) list elem, (elem -> Inspector DbgFormatter) -> Inspector DbgFormatter
+dbg_list : list, ElemWalker DbgFormatter list elem, (elem -> Inspector DbgFormatter) -> Inspector DbgFormatter
 dbg_list = |content, walk_fn, to_dbg_inspector|
     custom_list_dbg = |f0|
-        f1 = dbg_write(f0, "[")
-        (f5, _) = walk_fn(
+        f1 = dbg_push(f0, Open(dbg_collection("[", "]")))
+        f2 = walk_fn(
             content,
-            (f1, Bool.false),
-            |(f2, prepend_sep), elem|
-                f3 =
-                    if prepend_sep then
-                        dbg_write(f2, ", ")
-                    else
-                        f2
-
-                elem
-                |> to_dbg_inspector
-                |> apply(f3)
-                |> |f4| (f4, Bool.true),
+            f1,
+            |f, elem|
+                dbg_push(f, Item)
+                |> |x| apply(to_dbg_inspector(elem), x),
         )
 
-        dbg_write(f5, "]")
+        dbg_push(f2, Close)
 
     custom(custom_list_dbg)
 
-dbg_set : set, ElemWalker (DbgFormatter, Bool) set elem, (elem -> Inspector DbgFormatter) -> Inspector DbgFormatter
+dbg_set : set, ElemWalker DbgFormatter set elem, (elem -> Inspector DbgFormatter) -> Inspector DbgFormatter
 dbg_set = |content, walk_fn, to_dbg_inspector|
     custom_dbg_set = |f0|
-        f1 = dbg_write(f0, "{")
-        (f5, _) = walk_fn(
+        f1 = dbg_push(f0, Open(dbg_collection("{", "}")))
+        f2 = walk_fn(
             content,
-            (f1, Bool.false),
-            |(f2, prepend_sep), elem|
-                f3 =
-                    if prepend_sep then
-                        dbg_write(f2, ", ")
-                    else
-                        f2
-
-                elem
-                |> to_dbg_inspector
-                |> apply(f3)
-                |> |f4| (f4, Bool.true),
+            f1,
+            |f, elem|
+                dbg_push(f, Item)
+                |> |x| apply(to_dbg_inspector(elem), x),
         )
 
-        dbg_write(f5, "}")
+        dbg_push(f2, Close)
 
     custom(custom_dbg_set)
 
-dbg_dict : dict, KeyValWalker (DbgFormatter, Bool) dict key value, (key -> Inspector DbgFormatter), (value -> Inspector DbgFormatter) -> Inspector DbgFormatter
+dbg_dict : dict, KeyValWalker DbgFormatter dict key value, (key -> Inspector DbgFormatter), (value -> Inspector DbgFormatter) -> Inspector DbgFormatter
 dbg_dict = |d, walk_fn, key_to_inspector, value_to_inspector|
     custom_dbg_dict = |f0|
-        f1 = dbg_write(f0, "{")
-        (f5, _) = walk_fn(
+        f1 = dbg_push(f0, Open(dbg_collection("{", "}")))
+        f2 = walk_fn(
             d,
-            (f1, Bool.false),
-            |(f2, prepend_sep), key, value|
-                f3 =
-                    if prepend_sep then
-                        dbg_write(f2, ", ")
-                    else
-                        f2
-
-                apply(key_to_inspector(key), f3)
+            f1,
+            |f, key, value|
+                dbg_push(f, Item)
+                |> |x| apply(key_to_inspector(key), x)
                 |> dbg_write(": ")
-                |> |x| apply(value_to_inspector(value), x)
-                |> |f4| (f4, Bool.true),
+                |> |x| apply(value_to_inspector(value), x),
         )
 
-        dbg_write(f5, "}")
+        dbg_push(f2, Close)
 
     custom(custom_dbg_dict)
 
@@ -214,65 +213,50 @@ dbg_tag = |name, fields|
         custom(|f0| dbg_write(f0, name))
     else
         custom_dbg_tag = |f0|
-            f1 =
-                dbg_write(f0, "(")
-                |> dbg_write(name)
-
-            f3 = List.walk(
+            f1 = dbg_push(f0, Open({ open: Str.concat("(", name), close: ")", lead: " ", sep: " ", trailing: "" }))
+            f2 = List.walk(
                 fields,
                 f1,
-                |f2, inspector|
-                    dbg_write(f2, " ")
+                |f, inspector|
+                    dbg_push(f, Item)
                     |> |x| apply(inspector, x),
             )
 
-            dbg_write(f3, ")")
+            dbg_push(f2, Close)
 
         custom(custom_dbg_tag)
 
 dbg_tuple : List (Inspector DbgFormatter) -> Inspector DbgFormatter
 dbg_tuple = |fields|
     custom_dbg_tuple = |f0|
-        f1 = dbg_write(f0, "(")
-        (f5, _) = List.walk(
+        f1 = dbg_push(f0, Open(dbg_collection("(", ")")))
+        f2 = List.walk(
             fields,
-            (f1, Bool.false),
-            |(f2, prepend_sep), inspector|
-                f3 =
-                    if prepend_sep then
-                        dbg_write(f2, ", ")
-                    else
-                        f2
-
-                apply(inspector, f3)
-                |> |f4| (f4, Bool.true),
+            f1,
+            |f, inspector|
+                dbg_push(f, Item)
+                |> |x| apply(inspector, x),
         )
 
-        dbg_write(f5, ")")
+        dbg_push(f2, Close)
 
     custom(custom_dbg_tuple)
 
 dbg_record : List { key : Str, value : Inspector DbgFormatter } -> Inspector DbgFormatter
 dbg_record = |fields|
     custom_dbg_record = |f0|
-        f1 = dbg_write(f0, "{")
-        (f5, _) = List.walk(
+        f1 = dbg_push(f0, Open(dbg_collection("{", "}")))
+        f2 = List.walk(
             fields,
-            (f1, Bool.false),
-            |(f2, prepend_sep), { key, value }|
-                f3 =
-                    if prepend_sep then
-                        dbg_write(f2, ", ")
-                    else
-                        f2
-
-                dbg_write(f3, key)
+            f1,
+            |f, { key, value }|
+                dbg_push(f, Item)
+                |> dbg_write(key)
                 |> dbg_write(": ")
-                |> |x| apply(value, x)
-                |> |f4| (f4, Bool.true),
+                |> |x| apply(value, x),
         )
 
-        dbg_write(f5, "}")
+        dbg_push(f2, Close)
 
     custom(custom_dbg_record)
 
@@ -358,8 +342,121 @@ dbg_dec = |num|
     custom(|f0| dbg_write(f0, Num.to_str(num)))
 
 dbg_write : DbgFormatter, Str -> DbgFormatter
-dbg_write = |@DbgFormatter({ data }), added|
-    @DbgFormatter({ data: Str.concat(data, added) })
+dbg_write = |f, added| dbg_push(f, Text(added))
+
+dbg_push : DbgFormatter, DbgToken -> DbgFormatter
+dbg_push = |@DbgFormatter({ tokens }), token|
+    @DbgFormatter({ tokens: List.append(tokens, token) })
 
 to_dbg_str : DbgFormatter -> Str
-to_dbg_str = |@DbgFormatter({ data })| data
+to_dbg_str = |@DbgFormatter({ tokens })|
+    List.walk_with_index(
+        tokens,
+        { out: "", column: 0, groups: [] },
+        |state, token, index| dbg_render_token(state, token, index, tokens),
+    )
+    |> .out
+
+# A group that is being rendered, and whether it was split over several lines.
+DbgRenderGroup : { group : DbgGroup, multiline : Bool, indent : U64, items : U64 }
+
+dbg_render_token : { out : Str, column : U64, groups : List DbgRenderGroup }, DbgToken, U64, List DbgToken -> { out : Str, column : U64, groups : List DbgRenderGroup }
+dbg_render_token = |{ out, column, groups }, token, index, tokens|
+    outer_indent =
+        when List.last(groups) is
+            Ok(outer) -> outer.indent
+            Err(ListWasEmpty) -> 0
+
+    when token is
+        Text(text) ->
+            { out: Str.concat(out, text), column: Num.add_wrap(column, Str.count_utf8_bytes(text)), groups }
+
+        Open(group) ->
+            available = Num.sub_saturated(dbg_max_width, column)
+            multiline = !(dbg_fits(tokens, Num.add_wrap(index, 1), available, [{ group, items: 0 }], Str.count_utf8_bytes(group.open)))
+            indent = if multiline then Num.add_wrap(outer_indent, dbg_indent) else outer_indent
+
+            {
+                out: Str.concat(out, group.open),
+                column: Num.add_wrap(column, Str.count_utf8_bytes(group.open)),
+                groups: List.append(groups, { group, multiline, indent, items: 0 }),
+            }
+
+        Item ->
+            when List.last(groups) is
+                Ok(current) ->
+                    added =
+                        if current.multiline then
+                            before = if current.items == 0 then "" else current.group.trailing
+                            Str.concat(before, dbg_newline(current.indent))
+                        else if current.items == 0 then
+                            current.group.lead
+                        else
+                            current.group.sep
+
+                    {
+                        out: Str.concat(out, added),
+                        column: dbg_column_after(column, added),
+                        groups: List.set(groups, Num.sub_wrap(List.len(groups), 1), { current & items: Num.add_wrap(current.items, 1) }),
+                    }
+
+                Err(ListWasEmpty) ->
+                    { out, column, groups }
+
+        Close ->
+            when List.last(groups) is
+                Ok(current) ->
+                    remaining = List.drop_last(groups, 1)
+                    parent_indent =
+                        when List.last(remaining) is
+                            Ok(parent) -> parent.indent
+                            Err(ListWasEmpty) -> 0
+
+                    added =
+                        if current.multiline and current.items > 0 then
+                            Str.concat(current.group.trailing, dbg_newline(parent_indent))
+                            |> Str.concat(current.group.close)
+                        else
+                            current.group.close
+
+                    { out: Str.concat(out, added), column: dbg_column_after(column, added), groups: remaining }
+
+                Err(ListWasEmpty) ->
+                    { out, column, groups }
+
+# Whether the group that starts just before `index` fits on one line in `available` columns.
+dbg_fits : List DbgToken, U64, U64, List { group : DbgGroup, items : U64 }, U64 -> Bool
+dbg_fits = |tokens, index, available, open_groups, width|
+    if width > available then
+        Bool.false
+    else
+        when (List.get(tokens, index), List.last(open_groups)) is
+            (Ok(Text(text)), _) ->
+                dbg_fits(tokens, Num.add_wrap(index, 1), available, open_groups, Num.add_wrap(width, Str.count_utf8_bytes(text)))
+
+            (Ok(Open(group)), _) ->
+                dbg_fits(tokens, Num.add_wrap(index, 1), available, List.append(open_groups, { group, items: 0 }), Num.add_wrap(width, Str.count_utf8_bytes(group.open)))
+
+            (Ok(Item), Ok(current)) ->
+                added = if current.items == 0 then current.group.lead else current.group.sep
+                updated = List.set(open_groups, Num.sub_wrap(List.len(open_groups), 1), { current & items: Num.add_wrap(current.items, 1) })
+                dbg_fits(tokens, Num.add_wrap(index, 1), available, updated, Num.add_wrap(width, Str.count_utf8_bytes(added)))
+
+            (Ok(Close), Ok(current)) ->
+                new_width = Num.add_wrap(width, Str.count_utf8_bytes(current.group.close))
+                if List.len(open_groups) == 1 then
+                    new_width <= available
+                else
+                    dbg_fits(tokens, Num.add_wrap(index, 1), available, List.drop_last(open_groups, 1), new_width)
+
+            _ ->
+                Bool.true
+
+dbg_newline : U64 -> Str
+dbg_newline = |indent| Str.concat("\n", Str.repeat(" ", indent))
+
+dbg_column_after : U64, Str -> U64
+dbg_column_after = |column, added|
+    when Str.split_last(added, "\n") is
+        Ok({ after }) -> Str.count_utf8_bytes(after)
+        Err(NotFound) -> Num.add_wrap(column, Str.count_utf8_bytes(added))
@@ -76,6 +76,7 @@ module [
     for_each_try!,
     walk!,
     walk_try!,
+    sort_by_keys,
 ]
 
 import Bool exposing [Bool, Eq]
@@ -997,16 +998,28 @@ expect
 expect
     List.range({ start: At(4), end: At(0) }) == [4, 3, 2, 1, 0]
 
-## Sort with a custom comparison function
+## Sort with a custom comparison function.
+##
+## The sort is stable: elements the function says are `EQ` stay in the same order
+## relative to each other as they were in the original list.
+## ```roc
+## expect
+##     List.sort_with(["bb", "a", "cc", "b"], |a, b| Num.compare(Str.count_utf8_bytes(a), Str.count_utf8_bytes(b)))
+##     == ["a", "b", "bb", "cc"]
+## ```
+##
+## To sort by more than one key, see [List.sort_by_keys].
 sort_with : List a, (a, a -> [LT, EQ, GT]) -> List a
 
-## Sorts a list of numbers in ascending order (lowest to highest).
+## Sorts a list of numbers in ascending order (lowest to highest). Like [List.sort_with],
+## the sort is stable.
 ##
 ## To sort in descending order (highest to lowest), use [List.sort_desc] instead.
 sort_asc : List (Num a) -> List (Num a)
 sort_asc = |list| List.sort_with(list, Num.compare)
 
-## Sorts a list of numbers in descending order (highest to lowest).
+## Sorts a list of numbers in descending order (highest to lowest). Like [List.sort_with],
+## the sort is stable.
 ##
 ## To sort in ascending order (lowest to highest), use [List.sort_asc] instead.
 sort_desc : List (Num a) -> List (Num a)
@@ -1552,3 +1565,31 @@ walk_try! = |list, state, func!|
 
                 Err(err) ->
                     Err(err)
+
+## Sort by several keys, using a list of comparison functions. Elements are compared with
+## the first function, then elements it says are `EQ` are compared with the second, and so on.
+## ```roc
+## expect
+##     List.sort_by_keys(
+##         [{ name: "Sam", age: 30 }, { name: "Ana", age: 25 }, { name: "Bo", age: 30 }],
+##         [|a, b| Num.compare(b.age, a.age), |a, b| Num.compare(Str.count_utf8_bytes(a.name), Str.count_utf8_bytes(b.name))],
+##     )
+##     == [{ name: "Bo", age: 30 }, { name: "Sam", age: 30 }, { name: "Ana", age: 25 }]
+## ```
+##
+## Like [List.sort_with], the sort is stable, so elements that every function says are `EQ`
+## stay in their original order.
+sort_by_keys : List a, List (a, a -> [LT, EQ, GT]) -> List a
+sort_by_keys = |list, comparators|
+    List.sort_with(
+        list,
+        |a, b|
+            List.walk_until(
+                comparators,
+                EQ,
+                |_, compare|
+                    when compare(a, b) is
+                        EQ -> Continue(EQ)
+                        order -> Break(order),
+            ),
+    )
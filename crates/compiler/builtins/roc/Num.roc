@@ -160,6 +160,10 @@ module [
     nan_f64,
     infinity_f32,
     infinity_f64,
+    sqrt_dec,
+    log_dec,
+    exp_dec,
+    pow_dec,
 ]
 
 import Bool exposing [Bool]
@@ -1065,6 +1069,50 @@ ceiling : Frac * -> Int *
 ## For an [Int] alternative to this function, see [Num.pow_int]
 pow : Frac a, Frac a -> Frac a
 
+## The square root of a [Dec], rounded to the nearest [Dec].
+##
+## This is [Num.sqrt] for [Dec] only. Like the other `_dec` functions, it computes the answer
+## directly rather than converting to [F64] and back, so the answer is exact to all 18 decimal
+## places. (Answers with more than about 32 significant digits, which are only possible above
+## 10^14, may be off past the 32nd digit.)
+##
+## Calling [sqrt_dec] on a negative number will cause a panic.
+## ```roc
+## expect Num.sqrt_dec(2) == 1.414213562373095049
+## ```
+sqrt_dec : Dec -> Dec
+
+## The natural logarithm of a [Dec], rounded to the nearest [Dec].
+##
+## This is [Num.log] for [Dec] only, with the same precision as [Num.sqrt_dec].
+##
+## Calling [log_dec] on zero or a negative number will cause a panic.
+## ```roc
+## expect Num.log_dec(2) == 0.693147180559945309
+## ```
+log_dec : Dec -> Dec
+
+## Raises [Num.e] to the power of a [Dec], rounded to the nearest [Dec].
+##
+## This has the same precision as [Num.sqrt_dec]. Answers smaller than 0.000000000000000001
+## round to zero, and calling [exp_dec] with an answer too big for a [Dec] will cause a panic.
+## ```roc
+## expect Num.exp_dec(1) == 2.718281828459045235
+## ```
+exp_dec : Dec -> Dec
+
+## Raises a [Dec] to the power of another [Dec], rounded to the nearest [Dec].
+##
+## This is [Num.pow] for [Dec] only. When the exponent is a whole number, the answer is
+## computed by repeated multiplication. Otherwise it has the same precision as [Num.sqrt_dec].
+##
+## Raising a negative number to a power that isn't a whole number, raising zero to a negative
+## power, or getting an answer too big for a [Dec] will cause a panic.
+## ```roc
+## expect Num.pow_dec(1.05, 10.5) == 1.669120304352457735
+## ```
+pow_dec : Dec, Dec -> Dec
+
 ## Raises an integer to the power of another, by multiplying the integer by
 ## itself the given number of times.
 ##
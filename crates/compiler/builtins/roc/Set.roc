@@ -32,7 +32,11 @@ import Hash exposing [Hash, Hasher]
 import Inspect exposing [Inspect, Inspector, InspectFormatter]
 
 ## Provides a [set](https://en.wikipedia.org/wiki/Set_(abstract_data_type))
-## type which stores a collection of unique values, without any ordering
+## type which stores a collection of unique values.
+##
+## Equality of sets does not depend on order, but walking a set or converting
+## it to a [List] always visits the values in the order they were first
+## inserted. Removing a value leaves the order of the others unchanged.
 Set k := Dict.Dict k {} where k implements Hash & Eq
     implements [
         Eq {
@@ -264,7 +268,8 @@ from_list = |list|
 ## Combine two `Set` collection by keeping the
 ## [union](https://en.wikipedia.org/wiki/Union_(set_theory))
 ## of all the values pairs. This means that all of the values in both `Set`s
-## will be combined.
+## will be combined. The values of the first `Set` come first, followed by the
+## values only the second one has.
 ## ```roc
 ## set1 = Set.single(Left)
 ## set2 = Set.single(Right)
@@ -273,7 +278,7 @@ from_list = |list|
 ## ```
 union : Set k, Set k -> Set k
 union = |@Set(dict1), @Set(dict2)|
-    Dict.insert_all(dict1, dict2) |> @Set
+    Dict.union(dict1, dict2) |> @Set
 
 ## Combine two `Set`s by keeping the [intersection](https://en.wikipedia.org/wiki/Intersection_(set_theory))
 ## of all the values pairs. This means that we keep only those values that are
@@ -286,7 +291,7 @@ union = |@Set(dict1), @Set(dict2)|
 ## ```
 intersection : Set k, Set k -> Set k
 intersection = |@Set(dict1), @Set(dict2)|
-    Dict.keep_shared(dict1, dict2) |> @Set
+    Dict.intersect(dict1, dict2) |> @Set
 
 ## Remove the values in the first `Set` that are also in the second `Set`
 ## using the [set difference](https://en.wikipedia.org/wiki/Complement_(set_theory)#Relative_complement)
@@ -300,7 +305,7 @@ intersection = |@Set(dict1), @Set(dict2)|
 ## ```
 difference : Set k, Set k -> Set k
 difference = |@Set(dict1), @Set(dict2)|
-    Dict.remove_all(dict1, dict2) |> @Set
+    Dict.difference(dict1, dict2) |> @Set
 
 ## Iterate through the values of a given `Set` and build a value.
 ## ```roc
@@ -1,6 +1,6 @@
 ## Strings represent text. For example, `"Hi!"` is a string.
 ##
-## This guide starts at a high level and works down to the in-memory representation of strings and their [performance characteristics](#performance). For reasons that will be explained later in this guide, some string operations are in the `Str` module while others (notably [capitalization](#capitalization), [code points](#code-points), [graphemes](#graphemes), and sorting) are in separate packages. There's also a list of recommendations for [when to use code points, graphemes, and UTF-8](#when-to-use).
+## This guide starts at a high level and works down to the in-memory representation of strings and their [performance characteristics](#performance). For reasons that will be explained later in this guide, some string operations are in the `Str` module while others (notably language-specific [capitalization](#capitalization), [code points](#code-points), and sorting) are in separate packages, and only the most common [grapheme](#graphemes) operations are built in. There's also a list of recommendations for [when to use code points, graphemes, and UTF-8](#when-to-use).
 ##
 ## ## Syntax
 ##
@@ -121,7 +121,7 @@
 ## * `鹏`
 ## * `👩‍👩‍👦‍👦`
 ##
-## Note that although *grapheme* is less ambiguous than *character*, its definition is still open to interpretation. To address this, Unicode has formally specified [text segmentation rules](https://www.unicode.org/reports/tr29/) which define grapheme boundaries in precise technical terms. We won't get into those rules here, but since they can change with new Unicode releases, functions for working with graphemes are in the [roc-lang/unicode](https://github.com/roc-lang/unicode) package rather than in the builtin [`Str`](https://www.roc-lang.org/builtins/Str) module. This allows them to be updated without being blocked on a new release of the Roc language.
+## Note that although *grapheme* is less ambiguous than *character*, its definition is still open to interpretation. To address this, Unicode has formally specified [text segmentation rules](https://www.unicode.org/reports/tr29/) which define grapheme boundaries in precise technical terms. We won't get into those rules here. [`graphemes`](https://www.roc-lang.org/builtins/Str#graphemes), [`count_graphemes`](https://www.roc-lang.org/builtins/Str#count_graphemes), and [`truncate`](https://www.roc-lang.org/builtins/Str#truncate) follow them as of Unicode 14. Since the rules can change with new Unicode releases, the [roc-lang/unicode](https://github.com/roc-lang/unicode) package, which can be updated without being blocked on a new release of the Roc language, has the rest of the grapheme functions and may track newer releases.
 ##
 ## ### Code Points
 ##
@@ -207,7 +207,7 @@
 ##
 ## ### Capitalization
 ##
-## We've already seen two examples of Unicode definitions that can change with new Unicode releases: graphemes and normalization. Another is capitalization; these rules can change with new Unicode releases (most often in the form of additions of new languages, but breaking changes to capitalization rules for existing languages are also possible), and so they are not included in builtin [`Str`](https://www.roc-lang.org/builtins/Str).
+## We've already seen two examples of Unicode definitions that can change with new Unicode releases: graphemes and normalization. Another is capitalization; these rules can change with new Unicode releases (most often in the form of additions of new languages, but breaking changes to capitalization rules for existing languages are also possible), and so language-specific capitalization is not included in builtin [`Str`](https://www.roc-lang.org/builtins/Str).
 ##
 ## This might seem particularly surprising, since capitalization functions are commonly included in standard libraries. However, it turns out that "capitalizing an arbitrary string" is impossible to do correctly without additional information.
 ##
@@ -228,7 +228,7 @@
 ##
 ## In general, Roc programs should give the same answers for the same inputs even when run on different machines. There are exceptions to this (e.g. a program running out of system resources on one machine, while being able to make more progress on a machine that has more resources), but operating system's language localization is not among them.
 ##
-## For these reasons, capitalization functions are not in [`Str`](https://www.roc-lang.org/builtins/Str). There is a planned `roc-lang` package to handle use cases like capitalization and sorting—sorting can also vary by language as well as by things like country—but implementation work has not yet started on this package.
+## For these reasons, language-specific capitalization functions are not in [`Str`](https://www.roc-lang.org/builtins/Str). What [`Str`](https://www.roc-lang.org/builtins/Str) does have is [`with_uppercased`](https://www.roc-lang.org/builtins/Str#with_uppercased), [`with_lowercased`](https://www.roc-lang.org/builtins/Str#with_lowercased), and [`caseless_equals`](https://www.roc-lang.org/builtins/Str#caseless_equals), which use Unicode's default, language-independent mappings. They give the same answer on every machine (`"i"` always uppercases to `"I"`), which is right for things like identifiers and search, but not for displaying text in a particular language. There is a planned `roc-lang` package to handle use cases like capitalization and sorting—sorting can also vary by language as well as by things like country—but implementation work has not yet started on this package.
 ##
 ## ### UTF-8
 ##
@@ -258,7 +258,7 @@
 ## * If you are implementing a Unicode library like [roc-lang/unicode](https://github.com/roc-lang/unicode), working in terms of code points will be unavoidable. Aside from basic readability considerations like `\u(...)` in string literals, if you have the option to avoid working in terms of code points, it is almost always correct to avoid them.
 ## * If it seems like a good idea to split a string into "characters" (graphemes), you should definitely stop and reconsider whether this is really the best design. Almost always, doing this is some combination of more error-prone or slower (usually both) than doing something else that does not require taking graphemes into consideration.
 ##
-## For this reason (among others), grapheme functions live in [roc-lang/unicode](https://github.com/roc-lang/unicode) rather than in [`Str`](https://www.roc-lang.org/builtins/Str). They are more niche than they seem, so they should not be reached for all the time!
+## For this reason (among others), [`Str`](https://www.roc-lang.org/builtins/Str) only has a few grapheme functions, and the rest live in [roc-lang/unicode](https://github.com/roc-lang/unicode). They are more niche than they seem, so they should not be reached for all the time!
 ##
 ## ## Performance
 ##
@@ -376,6 +376,12 @@ module [
     with_ascii_lowercased,
     with_ascii_uppercased,
     caseless_ascii_equals,
+    graphemes,
+    count_graphemes,
+    truncate,
+    with_uppercased,
+    with_lowercased,
+    caseless_equals,
 ]
 
 import Bool exposing [Bool]
@@ -1350,7 +1356,8 @@ drop_suffix = |haystack, suffix|
 ## for Unicode capitalization that can be upgraded independently from the language's builtins.
 ##
 ## To do a case-insensitive comparison of the ASCII characters in a string,
-## you can use [Str.caseless_ascii_equals].
+## you can use [Str.caseless_ascii_equals]. To lowercase every character that has a
+## language-independent lowercase form, you can use [Str.with_lowercased].
 with_ascii_lowercased : Str -> Str
 
 expect Str.with_ascii_lowercased("CAFÉ") == "cafÉ"
@@ -1378,7 +1385,8 @@ expect Str.with_ascii_lowercased("CAFÉ") == "cafÉ"
 ## that can be upgraded independently from the language's builtins.
 ##
 ## To do a case-insensitive comparison of the ASCII characters in a string,
-## you can use [Str.caseless_ascii_equals].
+## you can use [Str.caseless_ascii_equals]. To uppercase every character that has a
+## language-independent uppercase form, you can use [Str.with_uppercased].
 with_ascii_uppercased : Str -> Str
 
 expect Str.with_ascii_uppercased("café") == "CAFé"
@@ -1413,8 +1421,972 @@ expect Str.with_ascii_uppercased("café") == "CAFé"
 ## for Unicode capitalization that can be upgraded independently from the language's builtins.
 ##
 ## To convert a string's ASCII characters to uppercase or lowercase, you can use [Str.with_ascii_uppercased]
-## or [Str.with_ascii_lowercased].
+## or [Str.with_ascii_lowercased]. To compare strings ignoring differences in capitalization
+## of non-ASCII characters too, you can use [Str.caseless_equals].
 caseless_ascii_equals : Str, Str -> Bool
 
 expect Str.caseless_ascii_equals("café", "CAFé")
 expect !Str.caseless_ascii_equals("café", "CAFÉ")
+
+## Returns a version of the string with every character uppercased, using the simple,
+## language-independent mappings from the [Unicode Character Database](https://www.unicode.org/ucd/)
+## (version 14). For example:
+##
+## ```roc
+## expect Str.with_uppercased("café") == "CAFÉ"
+## expect Str.with_uppercased("ελληνικά") == "ΕΛΛΗΝΙΚΆ"
+## ```
+##
+## Each character maps to exactly one character, so characters whose uppercase form is
+## several characters long are left unmodified. For example, `"ß"` stays `"ß"` rather
+## than becoming `"SS"`.
+##
+## Since the mappings don't depend on a language, `"i"` always uppercases to `"I"`, even though
+## it uppercases to `"İ"` in Turkish. See [capitalization](#capitalization) for why, and the
+## [`unicode` package](https://github.com/roc-lang/unicode) for language-specific capitalization.
+##
+## If you know the string only contains ASCII characters, [Str.with_ascii_uppercased]
+## has better performance.
+with_uppercased : Str -> Str
+
+expect Str.with_uppercased("café") == "CAFÉ"
+expect Str.with_uppercased("straße") == "STRAßE"
+expect Str.with_uppercased("ǆ ı") == "Ǆ I"
+
+## Returns a version of the string with every character lowercased, using the simple,
+## language-independent mappings from the [Unicode Character Database](https://www.unicode.org/ucd/)
+## (version 14). For example:
+##
+## ```roc
+## expect Str.with_lowercased("CAFÉ") == "café"
+## expect Str.with_lowercased("ΕΛΛΗΝΙΚΆ") == "ελληνικά"
+## ```
+##
+## Each character maps to exactly one character. Since the mappings don't depend on a language,
+## `"I"` always lowercases to `"i"`, even though it lowercases to `"ı"` in Turkish. See
+## [capitalization](#capitalization) for why, and the [`unicode` package](https://github.com/roc-lang/unicode)
+## for language-specific capitalization.
+##
+## If you know the string only contains ASCII characters, [Str.with_ascii_lowercased]
+## has better performance.
+with_lowercased : Str -> Str
+
+expect Str.with_lowercased("CAFÉ") == "café"
+expect Str.with_lowercased("İ") == "i"
+
+## Returns `Bool.true` if the two strings are the same when ignoring differences in capitalization,
+## using the simple case folding from the [Unicode Character Database](https://www.unicode.org/ucd/)
+## (version 14). For example:
+##
+## ```roc
+## expect Str.caseless_equals("café", "CAFÉ")
+## expect Str.caseless_equals("ΣΊΣΥΦΟΣ", "σίσυφος")
+## expect !Str.caseless_equals("straße", "STRASSE")
+## ```
+##
+## Case folding maps every character to exactly one character, and then the strings are compared
+## character by character. That's why `"ß"` doesn't equal `"SS"`, and why
+## `"σ"` and `"ς"` (the form of `"σ"` at the end of a word) are equal.
+##
+## This doesn't [normalize](#string-equality-and-normalization) the strings first, so `"caf\u(e9)"` and `"cafe\u(301)"`
+## aren't equal, even though they look the same.
+##
+## If you know the strings only contain ASCII characters, [Str.caseless_ascii_equals]
+## has better performance.
+caseless_equals : Str, Str -> Bool
+
+expect Str.caseless_equals("café", "CAFÉ")
+expect Str.caseless_equals("ΣΊΣΥΦΟΣ", "σίσυφος")
+expect Str.caseless_equals("ſ", "S")
+expect !Str.caseless_equals("straße", "STRASSE")
+expect !Str.caseless_equals("café", "cafe")
+
+## Splits a [Str] into its [graphemes](#graphemes), the user-perceived characters that a text
+## cursor moves over. The boundaries follow the extended grapheme cluster rules of
+## [Unicode Standard Annex #29](https://www.unicode.org/reports/tr29/), as of Unicode 14.
+## ```roc
+## expect Str.graphemes("Roc!") == ["R", "o", "c", "!"]
+## expect Str.graphemes("🇨🇦👩‍👩‍👦‍👦") == ["🇨🇦", "👩‍👩‍👦‍👦"]
+## expect Str.graphemes("\r\n") == ["\r\n"]
+## ```
+graphemes : Str -> List Str
+graphemes = |str|
+    walk_graphemes(
+        str,
+        [],
+        |list, start, len| Continue(List.append(list, Str.substring_unsafe(str, start, len))),
+    )
+
+## Counts the [graphemes](#graphemes) in a [Str], following the same rules as [Str.graphemes].
+## ```roc
+## expect Str.count_graphemes("Roc!") == 4
+## expect Str.count_graphemes("👩‍👩‍👦‍👦") == 1
+## expect Str.count_graphemes("") == 0
+## ```
+count_graphemes : Str -> U64
+count_graphemes = |str|
+    walk_graphemes(str, 0, |count, _, _| Continue(Num.add_wrap(count, 1)))
+
+## Keeps the first `max` [graphemes](#graphemes) of a [Str], so that a character made of several
+## code points, like an emoji or a letter with an accent, is never cut in half.
+## ```roc
+## expect Str.truncate("Hello, World!", 5) == "Hello"
+## expect Str.truncate("👩‍👩‍👦‍👦 family", 1) == "👩‍👩‍👦‍👦"
+## expect Str.truncate("short", 10) == "short"
+## ```
+truncate : Str, U64 -> Str
+truncate = |str, max|
+    if max == 0 then
+        ""
+    else
+        kept = walk_graphemes(
+            str,
+            { count: 0, end: 0 },
+            |state, start, len|
+                count = Num.add_wrap(state.count, 1)
+                next_state = { count, end: Num.add_wrap(start, len) }
+
+                if count >= max then
+                    Break(next_state)
+                else
+                    Continue(next_state),
+        )
+
+        if kept.end == Str.count_utf8_bytes(str) then
+            str
+        else
+            Str.substring_unsafe(str, 0, kept.end)
+
+expect graphemes("") == []
+expect graphemes("e\u(301)x") == ["e\u(301)", "x"]
+expect graphemes("\u(1100)\u(1161)\u(11A8)가") == ["\u(1100)\u(1161)\u(11A8)", "가"]
+expect graphemes("🇨🇦🇫🇷🇩") == ["🇨🇦", "🇫🇷", "🇩"]
+expect graphemes("👍🏽!") == ["👍🏽", "!"]
+expect graphemes("a\u(200D)👍") == ["a\u(200D)", "👍"]
+expect graphemes("\r\n\n") == ["\r\n", "\n"]
+expect count_graphemes("नमस्ते") == 4
+expect truncate("e\u(301)e\u(301)", 1) == "e\u(301)"
+
+## Calls `step` with the byte offset and length of each grapheme in `str`, in order, until it
+## returns `Break`.
+walk_graphemes : Str, state, (state, U64, U64 -> [Continue state, Break state]) -> state
+walk_graphemes = |str, initial, step|
+    walk_graphemes_help(
+        str,
+        initial,
+        step,
+        {
+            start: 0,
+            index: 0,
+            length: Str.count_utf8_bytes(str),
+            prev: StartOfText,
+            regional_indicators: 0,
+            pictographic: NotPictographic,
+        },
+    )
+
+walk_graphemes_help = |str, state, step, cluster|
+    { start, index, length, prev, regional_indicators, pictographic } = cluster
+
+    if index >= length then
+        if index == start then
+            state
+        else
+            when step(state, start, Num.sub_wrap(index, start)) is
+                Continue(new_state) -> new_state
+                Break(new_state) -> new_state
+    else
+        { code_point, len } = next_code_point(str, index)
+        class = grapheme_break_class(code_point)
+
+        next_cluster = {
+            start,
+            index: Num.add_wrap(index, len),
+            length,
+            prev: class,
+            regional_indicators: if class == RegionalIndicator then Num.add_wrap(regional_indicators, 1) else 0,
+            pictographic: next_pictographic(pictographic, class),
+        }
+
+        if is_grapheme_break(prev, class, regional_indicators, pictographic) then
+            when step(state, start, Num.sub_wrap(index, start)) is
+                Continue(new_state) -> walk_graphemes_help(str, new_state, step, { next_cluster & start: index })
+                Break(new_state) -> new_state
+        else
+            walk_graphemes_help(str, state, step, next_cluster)
+
+## Whether there's a grapheme boundary between two code points, given how many regional
+## indicators came right before the second one, and whether the first one ends an
+## emoji sequence like `👩‍`.
+is_grapheme_break = |prev, next, regional_indicators, pictographic|
+    when (prev, next) is
+        # GB1: there's no grapheme before the first one
+        (StartOfText, _) -> Bool.false
+        # GB3
+        (CR, LF) -> Bool.false
+        # GB4, GB5
+        (CR, _) | (LF, _) | (Control, _) | (_, CR) | (_, LF) | (_, Control) -> Bool.true
+        # GB6, GB7, GB8: Hangul syllables
+        (L, L) | (L, V) | (L, LV) | (L, LVT) -> Bool.false
+        (LV, V) | (LV, T) | (V, V) | (V, T) -> Bool.false
+        (LVT, T) | (T, T) -> Bool.false
+        # GB9, GB9a, GB9b
+        (_, Extend) | (_, ZWJ) | (_, SpacingMark) | (Prepend, _) -> Bool.false
+        # GB11: emoji ZWJ sequences
+        (ZWJ, ExtendedPictographic) -> pictographic != PictographicZwj
+        # GB12, GB13: flags are pairs of regional indicators
+        (RegionalIndicator, RegionalIndicator) -> Num.is_even(regional_indicators)
+        # GB999
+        _ -> Bool.true
+
+## Tracks whether the code points so far end with `ExtendedPictographic Extend*`, or with
+## that followed by a ZWJ, for rule GB11.
+next_pictographic = |pictographic, class|
+    when class is
+        ExtendedPictographic -> Pictographic
+        Extend if pictographic == Pictographic -> Pictographic
+        ZWJ if pictographic == Pictographic -> PictographicZwj
+        _ -> NotPictographic
+
+## Decodes the code point that starts at the given byte offset. A [Str] is always valid UTF-8,
+## so this doesn't check for malformed sequences.
+next_code_point : Str, U64 -> { code_point : U32, len : U64 }
+next_code_point = |str, index|
+    byte = Num.to_u32(Str.get_unsafe(str, index))
+    continuation = |offset|
+        Num.to_u32(Num.bitwise_and(Str.get_unsafe(str, Num.add_wrap(index, offset)), 0b11_1111))
+
+    if byte < 0b1000_0000 then
+        { code_point: byte, len: 1 }
+    else if byte < 0b1110_0000 then
+        code_point =
+            Num.shift_left_by(Num.bitwise_and(byte, 0b1_1111), 6)
+            |> Num.bitwise_or(continuation(1))
+
+        { code_point, len: 2 }
+    else if byte < 0b1111_0000 then
+        code_point =
+            Num.shift_left_by(Num.bitwise_and(byte, 0b1111), 12)
+            |> Num.bitwise_or(Num.shift_left_by(continuation(1), 6))
+            |> Num.bitwise_or(continuation(2))
+
+        { code_point, len: 3 }
+    else
+        code_point =
+            Num.shift_left_by(Num.bitwise_and(byte, 0b111), 18)
+            |> Num.bitwise_or(Num.shift_left_by(continuation(1), 12))
+            |> Num.bitwise_or(Num.shift_left_by(continuation(2), 6))
+            |> Num.bitwise_or(continuation(3))
+
+        { code_point, len: 4 }
+
+GraphemeBreak : [
+    StartOfText,
+    Other,
+    CR,
+    LF,
+    Control,
+    Extend,
+    ZWJ,
+    RegionalIndicator,
+    Prepend,
+    SpacingMark,
+    L,
+    V,
+    T,
+    LV,
+    LVT,
+    ExtendedPictographic,
+]
+
+## The Grapheme_Cluster_Break property of a code point, or [ExtendedPictographic] for the
+## emoji that rule GB11 joins.
+grapheme_break_class : U32 -> GraphemeBreak
+grapheme_break_class = |code_point|
+    if code_point < 0x80 then
+        if code_point == 0x0D then
+            CR
+        else if code_point == 0x0A then
+            LF
+        else if code_point < 0x20 or code_point == 0x7F then
+            Control
+        else
+            Other
+    else if code_point >= 0xAC00 and code_point <= 0xD7A3 then
+        # Precomposed Hangul syllables are ordered so that every 28th one has no trailing consonant.
+        if Num.is_multiple_of(Num.sub_wrap(code_point, 0xAC00), 28) then
+            LV
+        else
+            LVT
+    else
+        search_grapheme_break_table(code_point, 0, List.len(grapheme_break_table))
+
+search_grapheme_break_table : U32, U64, U64 -> GraphemeBreak
+search_grapheme_break_table = |code_point, low, high|
+    if low >= high then
+        Other
+    else
+        middle = Num.add_wrap(low, Num.shift_right_zf_by(Num.sub_wrap(high, low), 1))
+
+        when List.get(grapheme_break_table, middle) is
+            Ok(entry) ->
+                start = Num.to_u32(Num.shift_right_zf_by(entry, 40))
+                end = Num.to_u32(Num.bitwise_and(Num.shift_right_zf_by(entry, 16), 0xFF_FFFF))
+
+                if code_point < start then
+                    search_grapheme_break_table(code_point, low, middle)
+                else if code_point > end then
+                    search_grapheme_break_table(code_point, Num.add_wrap(middle, 1), high)
+                else
+                    when Num.bitwise_and(entry, 0xFFFF) is
+                        1 -> Control
+                        2 -> Extend
+                        3 -> ZWJ
+                        4 -> RegionalIndicator
+                        5 -> Prepend
+                        6 -> SpacingMark
+                        7 -> L
+                        8 -> V
+                        9 -> T
+                        10 -> ExtendedPictographic
+                        _ -> Other
+
+            Err(OutOfBounds) -> Other
+
+## The ranges of non-ASCII code points whose grapheme break class isn't `Other`, sorted and
+## derived from the Unicode 14 character database. Each entry is `0xSSSSSS_EEEEEE_CCCC`: the
+## first and last code point of the range, and the class, numbered as in
+## [search_grapheme_break_table]. Hangul syllables are classified by [grapheme_break_class].
+grapheme_break_table : List U64
+grapheme_break_table = [
+    0x000080_00009F_0001,
+    0x0000A9_0000A9_000A,
+    0x0000AD_0000AD_0001,
+    0x0000AE_0000AE_000A,
+    0x000300_00036F_0002,
+    0x000483_000489_0002,
+    0x000591_0005BD_0002,
+    0x0005BF_0005BF_0002,
+    0x0005C1_0005C2_0002,
+    0x0005C4_0005C5_0002,
+    0x0005C7_0005C7_0002,
+    0x000600_000605_0005,
+    0x000610_00061A_0002,
+    0x00061C_00061C_0001,
+    0x00064B_00065F_0002,
+    0x000670_000670_0002,
+    0x0006D6_0006DC_0002,
+    0x0006DD_0006DD_0005,
+    0x0006DF_0006E4_0002,
+    0x0006E7_0006E8_0002,
+    0x0006EA_0006ED_0002,
+    0x00070F_00070F_0005,
+    0x000711_000711_0002,
+    0x000730_00074A_0002,
+    0x0007A6_0007B0_0002,
+    0x0007EB_0007F3_0002,
+    0x0007FD_0007FD_0002,
+    0x000816_000819_0002,
+    0x00081B_000823_0002,
+    0x000825_000827_0002,
+    0x000829_00082D_0002,
+    0x000859_00085B_0002,
+    0x000890_000891_0005,
+    0x000898_00089F_0002,
+    0x0008CA_0008E1_0002,
+    0x0008E2_0008E2_0005,
+    0x0008E3_000902_0002,
+    0x000903_000903_0006,
+    0x00093A_00093A_0002,
+    0x00093B_00093B_0006,
+    0x00093C_00093C_0002,
+    0x00093E_000940_0006,
+    0x000941_000948_0002,
+    0x000949_00094C_0006,
+    0x00094D_00094D_0002,
+    0x00094E_00094F_0006,
+    0x000951_000957_0002,
+    0x000962_000963_0002,
+    0x000981_000981_0002,
+    0x000982_000983_0006,
+    0x0009BC_0009BC_0002,
+    0x0009BE_0009BE_0002,
+    0x0009BF_0009C0_0006,
+    0x0009C1_0009C4_0002,
+    0x0009C7_0009C8_0006,
+    0x0009CB_0009CC_0006,
+    0x0009CD_0009CD_0002,
+    0x0009D7_0009D7_0002,
+    0x0009E2_0009E3_0002,
+    0x0009FE_0009FE_0002,
+    0x000A01_000A02_0002,
+    0x000A03_000A03_0006,
+    0x000A3C_000A3C_0002,
+    0x000A3E_000A40_0006,
+    0x000A41_000A42_0002,
+    0x000A47_000A48_0002,
+    0x000A4B_000A4D_0002,
+    0x000A51_000A51_0002,
+    0x000A70_000A71_0002,
+    0x000A75_000A75_0002,
+    0x000A81_000A82_0002,
+    0x000A83_000A83_0006,
+    0x000ABC_000ABC_0002,
+    0x000ABE_000AC0_0006,
+    0x000AC1_000AC5_0002,
+    0x000AC7_000AC8_0002,
+    0x000AC9_000AC9_0006,
+    0x000ACB_000ACC_0006,
+    0x000ACD_000ACD_0002,
+    0x000AE2_000AE3_0002,
+    0x000AFA_000AFF_0002,
+    0x000B01_000B01_0002,
+    0x000B02_000B03_0006,
+    0x000B3C_000B3C_0002,
+    0x000B3E_000B3F_0002,
+    0x000B40_000B40_0006,
+    0x000B41_000B44_0002,
+    0x000B47_000B48_0006,
+    0x000B4B_000B4C_0006,
+    0x000B4D_000B4D_0002,
+    0x000B55_000B57_0002,
+    0x000B62_000B63_0002,
+    0x000B82_000B82_0002,
+    0x000BBE_000BBE_0002,
+    0x000BBF_000BBF_0006,
+    0x000BC0_000BC0_0002,
+    0x000BC1_000BC2_0006,
+    0x000BC6_000BC8_0006,
+    0x000BCA_000BCC_0006,
+    0x000BCD_000BCD_0002,
+    0x000BD7_000BD7_0002,
+    0x000C00_000C00_0002,
+    0x000C01_000C03_0006,
+    0x000C04_000C04_0002,
+    0x000C3C_000C3C_0002,
+    0x000C3E_000C40_0002,
+    0x000C41_000C44_0006,
+    0x000C46_000C48_0002,
+    0x000C4A_000C4D_0002,
+    0x000C55_000C56_0002,
+    0x000C62_000C63_0002,
+    0x000C81_000C81_0002,
+    0x000C82_000C83_0006,
+    0x000CBC_000CBC_0002,
+    0x000CBE_000CBE_0006,
+    0x000CBF_000CBF_0002,
+    0x000CC0_000CC1_0006,
+    0x000CC2_000CC2_0002,
+    0x000CC3_000CC4_0006,
+    0x000CC6_000CC6_0002,
+    0x000CC7_000CC8_0006,
+    0x000CCA_000CCB_0006,
+    0x000CCC_000CCD_0002,
+    0x000CD5_000CD6_0002,
+    0x000CE2_000CE3_0002,
+    0x000D00_000D01_0002,
+    0x000D02_000D03_0006,
+    0x000D3B_000D3C_0002,
+    0x000D3E_000D3E_0002,
+    0x000D3F_000D40_0006,
+    0x000D41_000D44_0002,
+    0x000D46_000D48_0006,
+    0x000D4A_000D4C_0006,
+    0x000D4D_000D4D_0002,
+    0x000D4E_000D4E_0005,
+    0x000D57_000D57_0002,
+    0x000D62_000D63_0002,
+    0x000D81_000D81_0002,
+    0x000D82_000D83_0006,
+    0x000DCA_000DCA_0002,
+    0x000DCF_000DCF_0002,
+    0x000DD0_000DD1_0006,
+    0x000DD2_000DD4_0002,
+    0x000DD6_000DD6_0002,
+    0x000DD8_000DDE_0006,
+    0x000DDF_000DDF_0002,
+    0x000DF2_000DF3_0006,
+    0x000E31_000E31_0002,
+    0x000E33_000E33_0006,
+    0x000E34_000E3A_0002,
+    0x000E47_000E4E_0002,
+    0x000EB1_000EB1_0002,
+    0x000EB3_000EB3_0006,
+    0x000EB4_000EBC_0002,
+    0x000EC8_000ECD_0002,
+    0x000F18_000F19_0002,
+    0x000F35_000F35_0002,
+    0x000F37_000F37_0002,
+    0x000F39_000F39_0002,
+    0x000F3E_000F3F_0006,
+    0x000F71_000F7E_0002,
+    0x000F7F_000F7F_0006,
+    0x000F80_000F84_0002,
+    0x000F86_000F87_0002,
+    0x000F8D_000F97_0002,
+    0x000F99_000FBC_0002,
+    0x000FC6_000FC6_0002,
+    0x00102D_001030_0002,
+    0x001031_001031_0006,
+    0x001032_001037_0002,
+    0x001039_00103A_0002,
+    0x00103B_00103C_0006,
+    0x00103D_00103E_0002,
+    0x001056_001057_0006,
+    0x001058_001059_0002,
+    0x00105E_001060_0002,
+    0x001071_001074_0002,
+    0x001082_001082_0002,
+    0x001084_001084_0006,
+    0x001085_001086_0002,
+    0x00108D_00108D_0002,
+    0x00109D_00109D_0002,
+    0x001100_00115F_0007,
+    0x001160_0011A7_0008,
+    0x0011A8_0011FF_0009,
+    0x00135D_00135F_0002,
+    0x001712_001714_0002,
+    0x001715_001715_0006,
+    0x001732_001733_0002,
+    0x001734_001734_0006,
+    0x001752_001753_0002,
+    0x001772_001773_0002,
+    0x0017B4_0017B5_0002,
+    0x0017B6_0017B6_0006,
+    0x0017B7_0017BD_0002,
+    0x0017BE_0017C5_0006,
+    0x0017C6_0017C6_0002,
+    0x0017C7_0017C8_0006,
+    0x0017C9_0017D3_0002,
+    0x0017DD_0017DD_0002,
+    0x00180B_00180D_0002,
+    0x00180E_00180E_0001,
+    0x00180F_00180F_0002,
+    0x001885_001886_0002,
+    0x0018A9_0018A9_0002,
+    0x001920_001922_0002,
+    0x001923_001926_0006,
+    0x001927_001928_0002,
+    0x001929_00192B_0006,
+    0x001930_001931_0006,
+    0x001932_001932_0002,
+    0x001933_001938_0006,
+    0x001939_00193B_0002,
+    0x001A17_001A18_0002,
+    0x001A19_001A1A_0006,
+    0x001A1B_001A1B_0002,
+    0x001A55_001A55_0006,
+    0x001A56_001A56_0002,
+    0x001A57_001A57_0006,
+    0x001A58_001A5E_0002,
+    0x001A60_001A60_0002,
+    0x001A62_001A62_0002,
+    0x001A65_001A6C_0002,
+    0x001A6D_001A72_0006,
+    0x001A73_001A7C_0002,
+    0x001A7F_001A7F_0002,
+    0x001AB0_001ACE_0002,
+    0x001B00_001B03_0002,
+    0x001B04_001B04_0006,
+    0x001B34_001B3A_0002,
+    0x001B3B_001B3B_0006,
+    0x001B3C_001B3C_0002,
+    0x001B3D_001B41_0006,
+    0x001B42_001B42_0002,
+    0x001B43_001B44_0006,
+    0x001B6B_001B73_0002,
+    0x001B80_001B81_0002,
+    0x001B82_001B82_0006,
+    0x001BA1_001BA1_0006,
+    0x001BA2_001BA5_0002,
+    0x001BA6_001BA7_0006,
+    0x001BA8_001BA9_0002,
+    0x001BAA_001BAA_0006,
+    0x001BAB_001BAD_0002,
+    0x001BE6_001BE6_0002,
+    0x001BE7_001BE7_0006,
+    0x001BE8_001BE9_0002,
+    0x001BEA_001BEC_0006,
+    0x001BED_001BED_0002,
+    0x001BEE_001BEE_0006,
+    0x001BEF_001BF1_0002,
+    0x001BF2_001BF3_0006,
+    0x001C24_001C2B_0006,
+    0x001C2C_001C33_0002,
+    0x001C34_001C35_0006,
+    0x001C36_001C37_0002,
+    0x001CD0_001CD2_0002,
+    0x001CD4_001CE0_0002,
+    0x001CE1_001CE1_0006,
+    0x001CE2_001CE8_0002,
+    0x001CED_001CED_0002,
+    0x001CF4_001CF4_0002,
+    0x001CF7_001CF7_0006,
+    0x001CF8_001CF9_0002,
+    0x001DC0_001DFF_0002,
+    0x00200B_00200B_0001,
+    0x00200C_00200C_0002,
+    0x00200D_00200D_0003,
+    0x00200E_00200F_0001,
+    0x002028_00202E_0001,
+    0x00203C_00203C_000A,
+    0x002049_002049_000A,
+    0x002060_002064_0001,
+    0x002066_00206F_0001,
+    0x0020D0_0020F0_0002,
+    0x002122_002122_000A,
+    0x002139_002139_000A,
+    0x002194_002199_000A,
+    0x0021A9_0021AA_000A,
+    0x00231A_00231B_000A,
+    0x002328_002328_000A,
+    0x002388_002388_000A,
+    0x0023CF_0023CF_000A,
+    0x0023E9_0023F3_000A,
+    0x0023F8_0023FA_000A,
+    0x0024C2_0024C2_000A,
+    0x0025AA_0025AB_000A,
+    0x0025B6_0025B6_000A,
+    0x0025C0_0025C0_000A,
+    0x0025FB_0025FE_000A,
+    0x002600_002605_000A,
+    0x002607_002612_000A,
+    0x002614_002685_000A,
+    0x002690_002705_000A,
+    0x002708_002712_000A,
+    0x002714_002714_000A,
+    0x002716_002716_000A,
+    0x00271D_00271D_000A,
+    0x002721_002721_000A,
+    0x002728_002728_000A,
+    0x002733_002734_000A,
+    0x002744_002744_000A,
+    0x002747_002747_000A,
+    0x00274C_00274C_000A,
+    0x00274E_00274E_000A,
+    0x002753_002755_000A,
+    0x002757_002757_000A,
+    0x002763_002767_000A,
+    0x002795_002797_000A,
+    0x0027A1_0027A1_000A,
+    0x0027B0_0027B0_000A,
+    0x0027BF_0027BF_000A,
+    0x002934_002935_000A,
+    0x002B05_002B07_000A,
+    0x002B1B_002B1C_000A,
+    0x002B50_002B50_000A,
+    0x002B55_002B55_000A,
+    0x002CEF_002CF1_0002,
+    0x002D7F_002D7F_0002,
+    0x002DE0_002DFF_0002,
+    0x00302A_00302F_0002,
+    0x003030_003030_000A,
+    0x00303D_00303D_000A,
+    0x003099_00309A_0002,
+    0x003297_003297_000A,
+    0x003299_003299_000A,
+    0x00A66F_00A672_0002,
+    0x00A674_00A67D_0002,
+    0x00A69E_00A69F_0002,
+    0x00A6F0_00A6F1_0002,
+    0x00A802_00A802_0002,
+    0x00A806_00A806_0002,
+    0x00A80B_00A80B_0002,
+    0x00A823_00A824_0006,
+    0x00A825_00A826_0002,
+    0x00A827_00A827_0006,
+    0x00A82C_00A82C_0002,
+    0x00A880_00A881_0006,
+    0x00A8B4_00A8C3_0006,
+    0x00A8C4_00A8C5_0002,
+    0x00A8E0_00A8F1_0002,
+    0x00A8FF_00A8FF_0002,
+    0x00A926_00A92D_0002,
+    0x00A947_00A951_0002,
+    0x00A952_00A953_0006,
+    0x00A960_00A97C_0007,
+    0x00A980_00A982_0002,
+    0x00A983_00A983_0006,
+    0x00A9B3_00A9B3_0002,
+    0x00A9B4_00A9B5_0006,
+    0x00A9B6_00A9B9_0002,
+    0x00A9BA_00A9BB_0006,
+    0x00A9BC_00A9BD_0002,
+    0x00A9BE_00A9C0_0006,
+    0x00A9E5_00A9E5_0002,
+    0x00AA29_00AA2E_0002,
+    0x00AA2F_00AA30_0006,
+    0x00AA31_00AA32_0002,
+    0x00AA33_00AA34_0006,
+    0x00AA35_00AA36_0002,
+    0x00AA43_00AA43_0002,
+    0x00AA4C_00AA4C_0002,
+    0x00AA4D_00AA4D_0006,
+    0x00AA7C_00AA7C_0002,
+    0x00AAB0_00AAB0_0002,
+    0x00AAB2_00AAB4_0002,
+    0x00AAB7_00AAB8_0002,
+    0x00AABE_00AABF_0002,
+    0x00AAC1_00AAC1_0002,
+    0x00AAEB_00AAEB_0006,
+    0x00AAEC_00AAED_0002,
+    0x00AAEE_00AAEF_0006,
+    0x00AAF5_00AAF5_0006,
+    0x00AAF6_00AAF6_0002,
+    0x00ABE3_00ABE4_0006,
+    0x00ABE5_00ABE5_0002,
+    0x00ABE6_00ABE7_0006,
+    0x00ABE8_00ABE8_0002,
+    0x00ABE9_00ABEA_0006,
+    0x00ABEC_00ABEC_0006,
+    0x00ABED_00ABED_0002,
+    0x00D7B0_00D7C6_0008,
+    0x00D7CB_00D7FB_0009,
+    0x00D800_00DFFF_0001,
+    0x00FB1E_00FB1E_0002,
+    0x00FE00_00FE0F_0002,
+    0x00FE20_00FE2F_0002,
+    0x00FEFF_00FEFF_0001,
+    0x00FF9E_00FF9F_0002,
+    0x00FFF9_00FFFB_0001,
+    0x0101FD_0101FD_0002,
+    0x0102E0_0102E0_0002,
+    0x010376_01037A_0002,
+    0x010A01_010A03_0002,
+    0x010A05_010A06_0002,
+    0x010A0C_010A0F_0002,
+    0x010A38_010A3A_0002,
+    0x010A3F_010A3F_0002,
+    0x010AE5_010AE6_0002,
+    0x010D24_010D27_0002,
+    0x010EAB_010EAC_0002,
+    0x010F46_010F50_0002,
+    0x010F82_010F85_0002,
+    0x011000_011000_0006,
+    0x011001_011001_0002,
+    0x011002_011002_0006,
+    0x011038_011046_0002,
+    0x011070_011070_0002,
+    0x011073_011074_0002,
+    0x01107F_011081_0002,
+    0x011082_011082_0006,
+    0x0110B0_0110B2_0006,
+    0x0110B3_0110B6_0002,
+    0x0110B7_0110B8_0006,
+    0x0110B9_0110BA_0002,
+    0x0110BD_0110BD_0005,
+    0x0110C2_0110C2_0002,
+    0x0110CD_0110CD_0005,
+    0x011100_011102_0002,
+    0x011127_01112B_0002,
+    0x01112C_01112C_0006,
+    0x01112D_011134_0002,
+    0x011145_011146_0006,
+    0x011173_011173_0002,
+    0x011180_011181_0002,
+    0x011182_011182_0006,
+    0x0111B3_0111B5_0006,
+    0x0111B6_0111BE_0002,
+    0x0111BF_0111C0_0006,
+    0x0111C2_0111C3_0005,
+    0x0111C9_0111CC_0002,
+    0x0111CE_0111CE_0006,
+    0x0111CF_0111CF_0002,
+    0x01122C_01122E_0006,
+    0x01122F_011231_0002,
+    0x011232_011233_0006,
+    0x011234_011234_0002,
+    0x011235_011235_0006,
+    0x011236_011237_0002,
+    0x01123E_01123E_0002,
+    0x0112DF_0112DF_0002,
+    0x0112E0_0112E2_0006,
+    0x0112E3_0112EA_0002,
+    0x011300_011301_0002,
+    0x011302_011303_0006,
+    0x01133B_01133C_0002,
+    0x01133E_01133E_0002,
+    0x01133F_01133F_0006,
+    0x011340_011340_0002,
+    0x011341_011344_0006,
+    0x011347_011348_0006,
+    0x01134B_01134D_0006,
+    0x011357_011357_0002,
+    0x011362_011363_0006,
+    0x011366_01136C_0002,
+    0x011370_011374_0002,
+    0x011435_011437_0006,
+    0x011438_01143F_0002,
+    0x011440_011441_0006,
+    0x011442_011444_0002,
+    0x011445_011445_0006,
+    0x011446_011446_0002,
+    0x01145E_01145E_0002,
+    0x0114B0_0114B0_0002,
+    0x0114B1_0114B2_0006,
+    0x0114B3_0114B8_0002,
+    0x0114B9_0114B9_0006,
+    0x0114BA_0114BA_0002,
+    0x0114BB_0114BC_0006,
+    0x0114BD_0114BD_0002,
+    0x0114BE_0114BE_0006,
+    0x0114BF_0114C0_0002,
+    0x0114C1_0114C1_0006,
+    0x0114C2_0114C3_0002,
+    0x0115AF_0115AF_0002,
+    0x0115B0_0115B1_0006,
+    0x0115B2_0115B5_0002,
+    0x0115B8_0115BB_0006,
+    0x0115BC_0115BD_0002,
+    0x0115BE_0115BE_0006,
+    0x0115BF_0115C0_0002,
+    0x0115DC_0115DD_0002,
+    0x011630_011632_0006,
+    0x011633_01163A_0002,
+    0x01163B_01163C_0006,
+    0x01163D_01163D_0002,
+    0x01163E_01163E_0006,
+    0x01163F_011640_0002,
+    0x0116AB_0116AB_0002,
+    0x0116AC_0116AC_0006,
+    0x0116AD_0116AD_0002,
+    0x0116AE_0116AF_0006,
+    0x0116B0_0116B5_0002,
+    0x0116B6_0116B6_0006,
+    0x0116B7_0116B7_0002,
+    0x01171D_01171F_0002,
+    0x011722_011725_0002,
+    0x011726_011726_0006,
+    0x011727_01172B_0002,
+    0x01182C_01182E_0006,
+    0x01182F_011837_0002,
+    0x011838_011838_0006,
+    0x011839_01183A_0002,
+    0x011930_011930_0002,
+    0x011931_011935_0006,
+    0x011937_011938_0006,
+    0x01193B_01193C_0002,
+    0x01193D_01193D_0006,
+    0x01193E_01193E_0002,
+    0x01193F_01193F_0005,
+    0x011940_011940_0006,
+    0x011941_011941_0005,
+    0x011942_011942_0006,
+    0x011943_011943_0002,
+    0x0119D1_0119D3_0006,
+    0x0119D4_0119D7_0002,
+    0x0119DA_0119DB_0002,
+    0x0119DC_0119DF_0006,
+    0x0119E0_0119E0_0002,
+    0x0119E4_0119E4_0006,
+    0x011A01_011A0A_0002,
+    0x011A33_011A38_0002,
+    0x011A39_011A39_0006,
+    0x011A3A_011A3A_0005,
+    0x011A3B_011A3E_0002,
+    0x011A47_011A47_0002,
+    0x011A51_011A56_0002,
+    0x011A57_011A58_0006,
+    0x011A59_011A5B_0002,
+    0x011A84_011A89_0005,
+    0x011A8A_011A96_0002,
+    0x011A97_011A97_0006,
+    0x011A98_011A99_0002,
+    0x011C2F_011C2F_0006,
+    0x011C30_011C36_0002,
+    0x011C38_011C3D_0002,
+    0x011C3E_011C3E_0006,
+    0x011C3F_011C3F_0002,
+    0x011C92_011CA7_0002,
+    0x011CA9_011CA9_0006,
+    0x011CAA_011CB0_0002,
+    0x011CB1_011CB1_0006,
+    0x011CB2_011CB3_0002,
+    0x011CB4_011CB4_0006,
+    0x011CB5_011CB6_0002,
+    0x011D31_011D36_0002,
+    0x011D3A_011D3A_0002,
+    0x011D3C_011D3D_0002,
+    0x011D3F_011D45_0002,
+    0x011D46_011D46_0005,
+    0x011D47_011D47_0002,
+    0x011D8A_011D8E_0006,
+    0x011D90_011D91_0002,
+    0x011D93_011D94_0006,
+    0x011D95_011D95_0002,
+    0x011D96_011D96_0006,
+    0x011D97_011D97_0002,
+    0x011EF3_011EF4_0002,
+    0x011EF5_011EF6_0006,
+    0x013430_013438_0001,
+    0x016AF0_016AF4_0002,
+    0x016B30_016B36_0002,
+    0x016F4F_016F4F_0002,
+    0x016F51_016F87_0006,
+    0x016F8F_016F92_0002,
+    0x016FE4_016FE4_0002,
+    0x016FF0_016FF1_0006,
+    0x01BC9D_01BC9E_0002,
+    0x01BCA0_01BCA3_0001,
+    0x01CF00_01CF2D_0002,
+    0x01CF30_01CF46_0002,
+    0x01D165_01D165_0002,
+    0x01D166_01D166_0006,
+    0x01D167_01D169_0002,
+    0x01D16D_01D16D_0006,
+    0x01D16E_01D172_0002,
+    0x01D173_01D17A_0001,
+    0x01D17B_01D182_0002,
+    0x01D185_01D18B_0002,
+    0x01D1AA_01D1AD_0002,
+    0x01D242_01D244_0002,
+    0x01DA00_01DA36_0002,
+    0x01DA3B_01DA6C_0002,
+    0x01DA75_01DA75_0002,
+    0x01DA84_01DA84_0002,
+    0x01DA9B_01DA9F_0002,
+    0x01DAA1_01DAAF_0002,
+    0x01E000_01E006_0002,
+    0x01E008_01E018_0002,
+    0x01E01B_01E021_0002,
+    0x01E023_01E024_0002,
+    0x01E026_01E02A_0002,
+    0x01E130_01E136_0002,
+    0x01E2AE_01E2AE_0002,
+    0x01E2EC_01E2EF_0002,
+    0x01E8D0_01E8D6_0002,
+    0x01E944_01E94A_0002,
+    0x01F000_01F0FF_000A,
+    0x01F10D_01F10F_000A,
+    0x01F12F_01F12F_000A,
+    0x01F16C_01F171_000A,
+    0x01F17E_01F17F_000A,
+    0x01F18E_01F18E_000A,
+    0x01F191_01F19A_000A,
+    0x01F1AD_01F1E5_000A,
+    0x01F1E6_01F1FF_0004,
+    0x01F201_01F20F_000A,
+    0x01F21A_01F21A_000A,
+    0x01F22F_01F22F_000A,
+    0x01F232_01F23A_000A,
+    0x01F23C_01F23F_000A,
+    0x01F249_01F3FA_000A,
+    0x01F3FB_01F3FF_0002,
+    0x01F400_01F53D_000A,
+    0x01F546_01F64F_000A,
+    0x01F680_01F6FF_000A,
+    0x01F774_01F77F_000A,
+    0x01F7D5_01F7FF_000A,
+    0x01F80C_01F80F_000A,
+    0x01F848_01F84F_000A,
+    0x01F85A_01F85F_000A,
+    0x01F888_01F88F_000A,
+    0x01F8AE_01F8FF_000A,
+    0x01F90C_01F93A_000A,
+    0x01F93C_01F945_000A,
+    0x01F947_01FAFF_000A,
+    0x01FC00_01FFFD_000A,
+    0x0E0000_0E00FF_0001,
+    0x0E0100_0E01EF_0002,
+    0x0E01F0_0E0FFF_0001,
+]
@@ -331,6 +331,7 @@ pub const NUM_F64_FROM_PARTS: &str = "roc_builtins.num.f64_from_parts";
 pub const STR_INIT: &str = "roc_builtins.str.init";
 pub const STR_COUNT_SEGMENTS: &str = "roc_builtins.str.count_segments";
 pub const STR_CONCAT: &str = "roc_builtins.str.concat";
+pub const STR_CONCAT_AMORTIZED: &str = "roc_builtins.str.concat_amortized";
 pub const STR_JOIN_WITH: &str = "roc_builtins.str.joinWith";
 pub const STR_SPLIT_ON: &str = "roc_builtins.str.str_split_on";
 pub const STR_COUNT_UTF8_BYTES: &str = "roc_builtins.str.count_utf8_bytes";
@@ -362,6 +363,9 @@ pub const STR_RELEASE_EXCESS_CAPACITY: &str = "roc_builtins.str.release_excess_c
 pub const STR_WITH_ASCII_LOWERCASED: &str = "roc_builtins.str.with_ascii_lowercased";
 pub const STR_WITH_ASCII_UPPERCASED: &str = "roc_builtins.str.with_ascii_uppercased";
 pub const STR_CASELESS_ASCII_EQUALS: &str = "roc_builtins.str.caseless_ascii_equals";
+pub const STR_WITH_UPPERCASED: &str = "roc_builtins.str.with_uppercased";
+pub const STR_WITH_LOWERCASED: &str = "roc_builtins.str.with_lowercased";
+pub const STR_CASELESS_EQUALS: &str = "roc_builtins.str.caseless_equals";
 
 pub const LIST_MAP: &str = "roc_builtins.list.map";
 pub const LIST_MAP2: &str = "roc_builtins.list.map2";
@@ -386,6 +390,8 @@ pub const LIST_RELEASE_EXCESS_CAPACITY: &str = "roc_builtins.list.release_excess
 pub const LIST_CONCAT_UTF8: &str = "roc_builtins.list.concat_utf8";
 pub const LIST_INCREF: &str = "roc_builtins.list.incref";
 pub const LIST_DECREF: &str = "roc_builtins.list.decref";
+pub const LIST_CONTAINS_INT: IntrinsicName = int_intrinsic!("roc_builtins.list.contains_num");
+pub const LIST_CONTAINS_FLOAT: IntrinsicName = float_intrinsic!("roc_builtins.list.contains_num");
 
 pub const DEC_ABS: &str = "roc_builtins.dec.abs";
 pub const DEC_ACOS: &str = "roc_builtins.dec.acos";
@@ -397,6 +403,7 @@ pub const DEC_ATAN: &str = "roc_builtins.dec.atan";
 pub const DEC_COS: &str = "roc_builtins.dec.cos";
 pub const DEC_DIV: &str = "roc_builtins.dec.div";
 pub const DEC_EQ: &str = "roc_builtins.dec.eq";
+pub const DEC_EXP: &str = "roc_builtins.dec.exp";
 pub const DEC_FROM_F64: &str = "roc_builtins.dec.from_f64";
 pub const DEC_FROM_FLOAT: IntrinsicName = float_intrinsic!("roc_builtins.dec.from_float");
 pub const DEC_FROM_INT: IntrinsicName = int_intrinsic!("roc_builtins.dec.from_int");
@@ -410,6 +417,7 @@ pub const DEC_MUL_WITH_OVERFLOW: &str = "roc_builtins.dec.mul_with_overflow";
 pub const DEC_NEGATE: &str = "roc_builtins.dec.negate";
 pub const DEC_NEQ: &str = "roc_builtins.dec.neq";
 pub const DEC_SIN: &str = "roc_builtins.dec.sin";
+pub const DEC_SQRT: &str = "roc_builtins.dec.sqrt";
 pub const DEC_SUB_OR_PANIC: &str = "roc_builtins.dec.sub_or_panic";
 pub const DEC_SUB_SATURATED: &str = "roc_builtins.dec.sub_saturated";
 pub const DEC_SUB_WITH_OVERFLOW: &str = "roc_builtins.dec.sub_with_overflow";
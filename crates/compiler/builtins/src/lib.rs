@@ -4,3 +4,4 @@
 #![allow(clippy::large_enum_variant)]
 pub mod bitcode;
 pub mod roc;
+pub mod unicode;
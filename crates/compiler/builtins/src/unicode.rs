@@ -0,0 +1,26 @@
+//! Simple Unicode case mappings, matching the ones `Str.with_uppercased`,
+//! `Str.with_lowercased`, and `Str.caseless_equals` use in the Zig builtins.
+
+/// `(code point, uppercase, lowercase, case folding)`, sorted by code point. Code points
+/// that map to themselves in every column are left out.
+static CASE_MAPPINGS: &[(char, char, char, char)] =
+    include!(concat!(env!("OUT_DIR"), "/case_mappings.rs"));
+
+fn case_mapping(c: char) -> Option<&'static (char, char, char, char)> {
+    CASE_MAPPINGS
+        .binary_search_by_key(&c, |(code_point, _, _, _)| *code_point)
+        .ok()
+        .map(|index| &CASE_MAPPINGS[index])
+}
+
+pub fn to_upper(c: char) -> char {
+    case_mapping(c).map_or(c, |(_, upper, _, _)| *upper)
+}
+
+pub fn to_lower(c: char) -> char {
+    case_mapping(c).map_or(c, |(_, _, lower, _)| *lower)
+}
+
+pub fn fold(c: char) -> char {
+    case_mapping(c).map_or(c, |(_, _, _, fold)| *fold)
+}
@@ -93,6 +93,8 @@ macro_rules! map_symbol_to_lowlevel_and_arity {
                 LowLevel::RefCountIsUnique => unimplemented!(),
                 LowLevel::ListIncref => unimplemented!(),
                 LowLevel::ListDecref => unimplemented!(),
+                LowLevel::ListContainsNum => unimplemented!(),
+                LowLevel::StrConcatAmortized => unimplemented!(),
 
                 LowLevel::SetJmp => unimplemented!(),
                 LowLevel::LongJmp => unimplemented!(),
@@ -134,6 +136,9 @@ map_symbol_to_lowlevel_and_arity! {
     StrWithAsciiLowercased; STR_WITH_ASCII_LOWERCASED; 1,
     StrWithAsciiUppercased; STR_WITH_ASCII_UPPERCASED; 1,
     StrCaselessAsciiEquals; STR_CASELESS_ASCII_EQUALS; 2,
+    StrWithUppercased; STR_WITH_UPPERCASED; 1,
+    StrWithLowercased; STR_WITH_LOWERCASED; 1,
+    StrCaselessEquals; STR_CASELESS_EQUALS; 2,
 
     ListLenUsize; LIST_LEN_USIZE; 1,
     ListLenU64; LIST_LEN_U64; 1,
@@ -185,12 +190,16 @@ map_symbol_to_lowlevel_and_arity! {
     NumTan; NUM_TAN; 1,
     NumSqrtUnchecked; NUM_SQRT; 1,
     NumLogUnchecked; NUM_LOG; 1,
+    NumSqrtUnchecked; NUM_SQRT_DEC; 1,
+    NumLogUnchecked; NUM_LOG_DEC; 1,
+    NumExpDec; NUM_EXP_DEC; 1,
     NumRound; NUM_ROUND; 1,
     NumToFrac; NUM_TO_FRAC; 1,
     NumIsNan; NUM_IS_NAN; 1,
     NumIsInfinite; NUM_IS_INFINITE; 1,
     NumIsFinite; NUM_IS_FINITE; 1,
     NumPow; NUM_POW; 2,
+    NumPow; NUM_POW_DEC; 2,
     NumCeiling; NUM_CEILING; 1,
     NumPowInt; NUM_POW_INT; 2,
     NumFloor; NUM_FLOOR; 1,
@@ -7,7 +7,7 @@ use crate::exhaustive::{ExhaustiveContext, SketchedRows};
 use crate::expected::{Expected, PExpected};
 use crate::expr::TryKind;
 use roc_collections::soa::{index_push_new, slice_extend_new};
-use roc_module::ident::{IdentSuffix, TagName};
+use roc_module::ident::{IdentSuffix, Lowercase, TagName};
 use roc_module::symbol::{ModuleId, Symbol};
 use roc_region::all::{Loc, Region};
 use roc_types::subs::{ExhaustiveMark, IllegalCycleMark, Variable};
@@ -34,6 +34,7 @@ pub struct Constraints {
     pub fx_call_constraints: Vec<FxCallConstraint>,
     pub fx_suffix_constraints: Vec<FxSuffixConstraint>,
     pub try_target_constraints: Vec<TryTargetConstraint>,
+    pub typed_hole_constraints: Vec<TypedHoleConstraint>,
 }
 
 impl std::fmt::Debug for Constraints {
@@ -58,6 +59,7 @@ impl std::fmt::Debug for Constraints {
             .field("cycles", &self.cycles)
             .field("fx_call_constraints", &self.fx_call_constraints)
             .field("fx_suffix_constraints", &self.fx_suffix_constraints)
+            .field("typed_hole_constraints", &self.typed_hole_constraints)
             .finish()
     }
 }
@@ -93,6 +95,7 @@ impl Constraints {
         let fx_call_constraints = Vec::with_capacity(16);
         let fx_suffix_constraints = Vec::new();
         let result_type_constraints = Vec::new();
+        let typed_hole_constraints = Vec::new();
 
         categories.extend([
             Category::Record,
@@ -145,6 +148,7 @@ impl Constraints {
             fx_call_constraints,
             fx_suffix_constraints,
             try_target_constraints: result_type_constraints,
+            typed_hole_constraints,
         }
     }
 
@@ -600,6 +604,18 @@ impl Constraints {
         Constraint::Lookup(symbol, expected_index, region)
     }
 
+    pub fn not_in_scope(
+        &mut self,
+        expected_index: ExpectedTypeIndex,
+        region: Region,
+    ) -> Constraint {
+        Constraint::NotInScope(expected_index, region)
+    }
+
+    pub fn accessed_field(&mut self, field_var: Variable, region: Region) -> Constraint {
+        Constraint::AccessedField(field_var, region)
+    }
+
     pub fn fx_call(
         &mut self,
         call_fx_var: Variable,
@@ -657,6 +673,19 @@ impl Constraints {
         Constraint::TryTarget(constraint_index)
     }
 
+    pub fn typed_hole(
+        &mut self,
+        var: Variable,
+        name: Option<Lowercase>,
+        region: Region,
+    ) -> Constraint {
+        let constraint = TypedHoleConstraint { var, name, region };
+
+        let constraint_index = index_push_new(&mut self.typed_hole_constraints, constraint);
+
+        Constraint::TypedHole(constraint_index)
+    }
+
     pub fn contains_save_the_environment(&self, constraint: &Constraint) -> bool {
         match constraint {
             Constraint::SaveTheEnvironment => true,
@@ -680,9 +709,12 @@ impl Constraints {
             Constraint::Eq(..)
             | Constraint::Store(..)
             | Constraint::Lookup(..)
+            | Constraint::NotInScope(..)
+            | Constraint::AccessedField(..)
             | Constraint::Pattern(..)
             | Constraint::ExpectEffectful(..)
             | Constraint::TryTarget(_)
+            | Constraint::TypedHole(_)
             | Constraint::FxCall(_)
             | Constraint::FxSuffix(_)
             | Constraint::FlexToPure(_)
@@ -860,6 +892,12 @@ pub enum Constraint {
     Eq(Eq),
     Store(TypeOrVar, Variable, Index<&'static str>, u32),
     Lookup(Symbol, ExpectedTypeIndex, Region),
+    /// A lookup of a name that isn't in scope. Used to find the values in scope
+    /// that would fit in its place, so they can be suggested first.
+    NotInScope(ExpectedTypeIndex, Region),
+    /// The type a record field access is used at. If the record turns out not to have
+    /// that field, the fields with a fitting type are suggested first.
+    AccessedField(Variable, Region),
     Pattern(
         TypeOrVar,
         PExpectedTypeIndex,
@@ -876,6 +914,8 @@ pub enum Constraint {
     ExpectEffectful(Variable, ExpectEffectfulReason, Region),
     /// Expect value to be some kind of Result
     TryTarget(Index<TryTargetConstraint>),
+    /// Report the type that's expected in place of a typed hole
+    TypedHole(Index<TypedHoleConstraint>),
     /// Used for things that always unify, e.g. blanks and runtime errors
     True,
     SaveTheEnvironment,
@@ -951,6 +991,13 @@ pub struct TryTargetConstraint {
     pub kind: TryKind,
 }
 
+#[derive(Debug, Clone)]
+pub struct TypedHoleConstraint {
+    pub var: Variable,
+    pub name: Option<Lowercase>,
+    pub region: Region,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct Cycle {
     pub def_names: Slice<(Symbol, Region)>,
@@ -1027,6 +1074,12 @@ impl std::fmt::Debug for Constraint {
             Self::Lookup(arg0, arg1, arg2) => {
                 write!(f, "Lookup({arg0:?}, {arg1:?}, {arg2:?})")
             }
+            Self::NotInScope(arg0, arg1) => {
+                write!(f, "NotInScope({arg0:?}, {arg1:?})")
+            }
+            Self::AccessedField(arg0, arg1) => {
+                write!(f, "AccessedField({arg0:?}, {arg1:?})")
+            }
             Self::Pattern(arg0, arg1, arg2, arg3) => {
                 write!(f, "Pattern({arg0:?}, {arg1:?}, {arg2:?}, {arg3:?})")
             }
@@ -1045,6 +1098,9 @@ impl std::fmt::Debug for Constraint {
             Self::TryTarget(arg0) => {
                 write!(f, "ExpectResultType({arg0:?})")
             }
+            Self::TypedHole(arg0) => {
+                write!(f, "TypedHole({arg0:?})")
+            }
             Self::True => write!(f, "True"),
             Self::SaveTheEnvironment => write!(f, "SaveTheEnvironment"),
             Self::Let(arg0, arg1) => f.debug_tuple("Let").field(arg0).field(arg1).finish(),
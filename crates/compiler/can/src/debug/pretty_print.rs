@@ -432,6 +432,10 @@ fn expr<'a>(c: &Ctx, p: EPrec, f: &'a Arena<'a>, e: &'a Expr) -> DocBuilder<'a,
                 .group()
         ),
         Crash { .. } => todo!(),
+        TypedHole { name, .. } => match name {
+            Some(name) => text!(f, "?{}", name),
+            None => f.text("???"),
+        },
         ZeroArgumentTag { .. } => todo!(),
         OpaqueRef { name, argument, .. } => maybe_paren!(
             Free,
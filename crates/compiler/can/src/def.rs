@@ -18,12 +18,13 @@ use crate::expr::Declarations;
 use crate::expr::Expr::{self, *};
 use crate::expr::StructAccessorData;
 use crate::expr::{canonicalize_expr, Output, Recursive};
-use crate::pattern::{canonicalize_def_header_pattern, BindingsFromPattern, Pattern};
+use crate::pattern::{binding_kind, canonicalize_def_header_pattern, BindingsFromPattern, Pattern};
 use crate::procedure::QualifiedReference;
 use crate::procedure::References;
 use crate::scope::create_alias;
 use crate::scope::SymbolLookup;
 use crate::scope::{PendingAbilitiesInScope, Scope};
+use crate::traverse::rename_uses;
 use roc_collections::ReferenceMatrix;
 use roc_collections::VecMap;
 use roc_collections::VecSet;
@@ -44,7 +45,8 @@ use roc_parse::ast::TypeHeader;
 use roc_parse::ident::Accessor;
 use roc_parse::pattern::PatternType;
 use roc_problem::can::ShadowKind;
-use roc_problem::can::{CycleEntry, Problem, RuntimeError};
+use roc_problem::can::{BindingKind, CycleEntry, Problem, RuntimeError};
+use roc_problem::fix::{Edit, Fix};
 use roc_region::all::{Loc, Region};
 use roc_types::subs::IllegalCycleMark;
 use roc_types::subs::{VarStore, Variable};
@@ -949,9 +951,54 @@ fn canonicalize_opaque<'a>(
                 derived_abilities.push(Loc::at(ability_region, ability));
             } else {
                 // There was no record specified of functions to use for
-                // members, but also this isn't a builtin ability, so we don't
-                // know how to auto-derive it.
-                env.problem(Problem::IllegalDerivedAbility(region));
+                // members, and this isn't a builtin ability, so it can only be derived
+                // through the deriving hooks defined next to the ability.
+                match derive::find_derive_hooks(
+                    env,
+                    scope,
+                    ability,
+                    &members.into_vec(),
+                    &ann.value,
+                ) {
+                    Ok(hooks) => {
+                        derived_defs.reserve(hooks.len());
+
+                        let mut impls = Vec::with_capacity(hooks.len());
+                        for member_hooks in hooks.iter() {
+                            let (derived_impl, impl_pat, impl_body) =
+                                derive::synthesize_hooked_member_impl(
+                                    env,
+                                    scope,
+                                    name_str,
+                                    member_hooks,
+                                    ann,
+                                    ability_region,
+                                );
+
+                            let derived_def = Loc::at(
+                                derive::DERIVED_REGION,
+                                PendingValue::Def(PendingValueDef::Body(impl_pat, impl_body)),
+                            );
+
+                            impls.push((member_hooks.member, MemberImpl::Impl(derived_impl)));
+                            derived_defs.push(derived_def);
+                        }
+
+                        scope
+                            .abilities_store
+                            .register_declared_implementations(name.value, impls);
+                    }
+                    Err(hooks) if !hooks.is_empty() => {
+                        env.problem(Problem::MissingDeriveHooks {
+                            region,
+                            ability,
+                            hooks,
+                        });
+                    }
+                    Err(_) => {
+                        env.problem(Problem::IllegalDerivedAbility(region));
+                    }
+                }
             }
         }
 
@@ -1011,7 +1058,7 @@ pub(crate) fn canonicalize_defs<'a>(
 ) -> (
     CanDefs,
     Output,
-    MutMap<Symbol, Region>,
+    MutMap<Symbol, IntroducedSymbol>,
     Vec<IntroducedImport>,
 ) {
     // Canonicalizing defs while detecting shadowing involves a multi-step process:
@@ -1092,8 +1139,8 @@ pub(crate) fn canonicalize_defs<'a>(
     );
 
     // Add the derived ASTs, so that we create proper canonicalized defs for them.
-    // They can go at the end, and derived defs should never reference anything other than builtin
-    // ability members.
+    // They can go at the end, and derived defs should never reference anything other than ability
+    // members and their deriving hooks.
     pending_value_defs.extend(derived_defs);
 
     // Now that we have the scope completely assembled, and shadowing resolved,
@@ -1119,17 +1166,18 @@ fn canonicalize_value_defs<'a>(
     value_defs: Vec<Loc<PendingValue<'a>>>,
     pattern_type: PatternType,
     mut aliases: VecMap<Symbol, Alias>,
-    mut symbols_introduced: MutMap<Symbol, Region>,
+    mut symbols_introduced: MutMap<Symbol, IntroducedSymbol>,
 ) -> (
     CanDefs,
     Output,
-    MutMap<Symbol, Region>,
+    MutMap<Symbol, IntroducedSymbol>,
     Vec<IntroducedImport>,
 ) {
     // Canonicalize all the patterns, record shadowing problems, and store
     // the ast::Expr values in pending_exprs for further canonicalization
     // once we've finished assembling the entire scope.
     let mut pending_value_defs = Vec::with_capacity(value_defs.len());
+    let mut def_regions = Vec::with_capacity(value_defs.len());
     let mut pending_dbgs = Vec::with_capacity(value_defs.len());
     let mut pending_expects = Vec::with_capacity(value_defs.len());
 
@@ -1143,6 +1191,7 @@ fn canonicalize_value_defs<'a>(
                 // the exprs right now, they wouldn't have symbols in scope from defs
                 // that get would have gotten added later in the defs list!
                 pending_value_defs.push(pending_def);
+                def_regions.push(loc_pending_def.region);
             }
             PendingValue::SignatureDefMismatch => { /* skip */ }
             PendingValue::Dbg(pending_dbg) => {
@@ -1170,6 +1219,7 @@ fn canonicalize_value_defs<'a>(
                     opt_provided: params.opt_provided,
                     module_id,
                 });
+                def_regions.push(loc_pending_def.region);
             }
             PendingValue::InvalidIngestedFile => { /* skip */ }
             PendingValue::ImportNameConflict => { /* skip */ }
@@ -1192,7 +1242,18 @@ fn canonicalize_value_defs<'a>(
                 env.top_level_symbols.insert(s);
             }
 
-            symbols_introduced.insert(s, r);
+            let introduced = match loc_pattern.value {
+                Pattern::Identifier(_) | Pattern::AbilityMemberSpecialization { .. } => {
+                    IntroducedSymbol::Def(r, None)
+                }
+                _ => {
+                    let kind = binding_kind(&loc_pattern.value, s).unwrap_or(BindingKind::Other);
+
+                    IntroducedSymbol::Destructured(r, kind)
+                }
+            };
+
+            symbols_introduced.insert(s, introduced);
 
             debug_assert_eq!(env.home, s.module_id());
             debug_assert!(
@@ -1202,14 +1263,27 @@ fn canonicalize_value_defs<'a>(
 
             symbol_to_index.push((s.ident_id(), def_index as u32));
         }
+
+        // A def like `x = x + 1` can give its name to the symbol of its shadow, which later defs
+        // then depend on.
+        if let Pattern::Shadowed(_, _, shadow_symbol) = loc_pattern.value {
+            symbol_to_index.push((shadow_symbol.ident_id(), def_index as u32));
+        }
     }
 
     let capacity = pending_value_defs.len();
     let mut defs = Vec::with_capacity(capacity);
     let mut def_ordering = DefOrdering::from_symbol_to_id(env.home, symbol_to_index, capacity);
 
-    for (def_id, pending_def) in pending_value_defs.into_iter().enumerate() {
-        let temp_output = canonicalize_pending_value_def(
+    let pending_value_defs = pending_value_defs.into_iter().zip(def_regions);
+
+    for (def_id, (pending_def, def_region)) in pending_value_defs.enumerate() {
+        let shadowed = match pattern_type {
+            PatternType::DefExpr => shadowed_local(scope, &pending_def),
+            _ => None,
+        };
+
+        let mut temp_output = canonicalize_pending_value_def(
             env,
             pending_def,
             output,
@@ -1221,6 +1295,30 @@ fn canonicalize_value_defs<'a>(
 
         output = temp_output.output;
 
+        if let Some(shadowed) = shadowed {
+            let uses_shadowed = match &temp_output.references {
+                DefReferences::Value(references) | DefReferences::Function(references) => {
+                    references.has_value_lookup(shadowed.symbol)
+                }
+                DefReferences::AnnotationWithoutBody => false,
+            };
+
+            if uses_shadowed {
+                let symbol = rebind_shadowed_local(env, scope, &mut temp_output.def, shadowed);
+                let region = temp_output.def.loc_pattern.region;
+
+                symbols_introduced.insert(symbol, IntroducedSymbol::Def(region, None));
+            }
+        }
+
+        if let Pattern::Identifier(symbol) = temp_output.def.loc_pattern.value {
+            if let Some(IntroducedSymbol::Def(_, removable)) = symbols_introduced.get_mut(&symbol) {
+                if has_no_effects(&temp_output.def.loc_expr.value) {
+                    *removable = Some(def_region);
+                }
+            }
+        }
+
         if let (PatternType::TopLevelDef, DefKind::Ignored(_)) =
             (pattern_type, temp_output.def.kind)
         {
@@ -1278,7 +1376,7 @@ fn canonicalize_value_defs<'a>(
 
 struct CanonicalizedTypeDefs<'a> {
     aliases: VecMap<Symbol, Alias>,
-    symbols_introduced: MutMap<Symbol, Region>,
+    symbols_introduced: MutMap<Symbol, IntroducedSymbol>,
     derived_defs: Vec<DerivedDef<'a>>,
 }
 
@@ -1314,7 +1412,14 @@ fn canonicalize_type_defs<'a>(
 
     for pending_def in pending_type_defs.into_iter() {
         if let Some((symbol, region)) = pending_def.introduction() {
-            symbols_introduced.insert(symbol, region);
+            // The region of an alias or opaque type is all of its def.
+            let def_region = matches!(
+                pending_def,
+                PendingTypeDef::Alias { .. } | PendingTypeDef::Opaque { .. }
+            )
+            .then_some(region);
+
+            symbols_introduced.insert(symbol, IntroducedSymbol::Def(region, def_region));
         }
 
         match pending_def {
@@ -2738,6 +2843,132 @@ fn canonicalize_pending_body<'a>(
     }
 }
 
+/// How a def introduced a symbol, which decides what to report when nothing uses it.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum IntroducedSymbol {
+    /// The name of a def like `x = ...` or `Age : U8`, and all of the def if removing it can't
+    /// change what the program does
+    Def(Region, Option<Region>),
+    /// A name in the pattern of a destructuring def like `{ x, y } = ...`
+    Destructured(Region, BindingKind),
+}
+
+impl IntroducedSymbol {
+    pub(crate) fn unused(self, symbol: Symbol) -> Problem {
+        match self {
+            IntroducedSymbol::Def(region, def_region) => {
+                Problem::UnusedDef(symbol, region, def_region)
+            }
+            IntroducedSymbol::Destructured(region, kind) => {
+                Problem::UnusedPatternBinding(symbol, region, kind)
+            }
+        }
+    }
+}
+
+/// Whether evaluating an expression can't have an effect, so that removing an unused def of it
+/// can't change what the program does.
+fn has_no_effects(expr: &Expr) -> bool {
+    matches!(
+        expr,
+        Closure(_)
+            | Num(..)
+            | Int(..)
+            | Float(..)
+            | Str(_)
+            | SingleQuote(..)
+            | Var(..)
+            | EmptyRecord
+    )
+}
+
+/// A local that the only name of a def shadows.
+struct ShadowedLocal {
+    symbol: Symbol,
+    region: Region,
+    /// The name in the annotation of the def, which has to be renamed along with the def
+    annotation_name: Option<Region>,
+}
+
+fn shadowed_local(scope: &Scope, pending_def: &PendingValueDef<'_>) -> Option<ShadowedLocal> {
+    let (loc_pattern, annotation_name) = match pending_def {
+        PendingValueDef::Body(loc_pattern, _) => (loc_pattern, None),
+        PendingValueDef::TypedBody(ann_pattern, loc_pattern, _, _) => {
+            (loc_pattern, Some(ann_pattern.region))
+        }
+        _ => return None,
+    };
+
+    let Pattern::Shadowed(_, shadow, _) = &loc_pattern.value else {
+        return None;
+    };
+
+    let (symbol, region) = scope.lookup_local(shadow.value.as_str())?;
+
+    Some(ShadowedLocal {
+        symbol,
+        region,
+        annotation_name,
+    })
+}
+
+/// A def like `x = x + 1` gives a local a new value computed from the old one, which isn't a
+/// mistake like other shadowing, so give the name to the symbol of the def for the rest of the
+/// scope, and replace the error about the shadowing with a warning. Returns the new symbol.
+fn rebind_shadowed_local(
+    env: &mut Env<'_>,
+    scope: &mut Scope,
+    def: &mut Def,
+    shadowed: ShadowedLocal,
+) -> Symbol {
+    let Pattern::Shadowed(_, shadow, symbol) = &def.loc_pattern.value else {
+        internal_error!("Only the def of a shadow can rebind a local");
+    };
+    let (shadow, symbol) = (shadow.clone(), *symbol);
+
+    let new_name = scope.unused_name_like(shadow.value.as_str());
+    let mut fix = Fix::replace(
+        format!("Rename the new `{}` to `{new_name}`", shadow.value.as_str()),
+        shadow.region,
+        new_name.clone(),
+    );
+
+    if let Some(region) = shadowed.annotation_name {
+        fix.edits.push(Edit {
+            region,
+            replacement: new_name,
+        });
+    }
+
+    let warning = Problem::IntentionalShadowing {
+        original_region: shadowed.region,
+        shadow: shadow.clone(),
+        symbol,
+        fix,
+    };
+
+    // The shadowing was reported as an error when the name of the def was introduced.
+    let error = env.problems.iter().position(|problem| {
+        matches!(
+            problem,
+            Problem::RuntimeError(RuntimeError::Shadowing { shadow: other, .. })
+                if other.region == shadow.region
+        )
+    });
+
+    match error {
+        Some(index) => env.problems[index] = warning,
+        None => env.problem(warning),
+    }
+
+    // A shadow binds nothing, so its def was taken for one that only runs its body.
+    def.loc_pattern.value = Pattern::Identifier(symbol);
+    def.kind = DefKind::Let;
+    scope.rebind(shadowed.symbol, symbol);
+
+    symbol
+}
+
 #[inline(always)]
 pub fn can_defs_with_return<'a>(
     env: &mut Env<'a>,
@@ -2746,6 +2977,8 @@ pub fn can_defs_with_return<'a>(
     loc_defs: &'a mut Defs<'a>,
     loc_ret: &'a Loc<ast::Expr<'a>>,
 ) -> (Expr, Output) {
+    let problems_before = env.problems.len();
+
     let (unsorted, defs_output, symbols_introduced, imports_introduced) = canonicalize_defs(
         env,
         Output::default(),
@@ -2772,11 +3005,11 @@ pub fn can_defs_with_return<'a>(
 
     // Now that we've collected all the references, check to see if any of the new idents
     // we defined went unused by the return expression or any other def.
-    for (symbol, region) in symbols_introduced {
+    for (symbol, introduced) in symbols_introduced {
         if !output.references.has_type_or_value_lookup(symbol)
             && !scope.abilities_store.is_specialization_name(symbol)
         {
-            env.problem(Problem::UnusedDef(symbol, region));
+            env.problem(introduced.unused(symbol));
         }
     }
 
@@ -2788,6 +3021,22 @@ pub fn can_defs_with_return<'a>(
         loc_expr = decl_to_let(declaration, loc_expr);
     }
 
+    // The fix for a def that gives its name a new value renames the new value, so it has to
+    // rename every use of it, which are all in this block. Blocks nested in this one have
+    // already added their uses.
+    for problem in &mut env.problems[problems_before..] {
+        if let Problem::IntentionalShadowing { symbol, fix, .. } = problem {
+            // The first edit renames the def itself.
+            let new_name = fix.edits[0].replacement.clone();
+
+            for edit in rename_uses(&loc_expr, *symbol, &new_name) {
+                if !fix.edits.contains(&edit) {
+                    fix.edits.push(edit);
+                }
+            }
+        }
+    }
+
     (loc_expr.value, output)
 }
 
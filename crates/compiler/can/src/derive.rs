@@ -6,9 +6,10 @@
 //!   - Derived impls for opaques are typically very small, effectively deferring the
 //!     implementation to the value they wrap.
 
+use bumpalo::Bump;
 use roc_error_macros::internal_error;
-use roc_module::{called_via::CalledVia, symbol::Symbol};
-use roc_parse::ast::{self, Collection};
+use roc_module::{called_via::CalledVia, ident::Lowercase, symbol::Symbol};
+use roc_parse::ast::{self, Collection, ExtractSpaces};
 use roc_region::all::{Loc, Region};
 
 use crate::{env::Env, pattern::Pattern, scope::Scope};
@@ -345,3 +346,358 @@ pub(crate) fn synthesize_member_impl<'a>(
         env.arena.alloc(Loc::at(DERIVED_REGION, def_body)),
     )
 }
+
+/// Deriving hooks let abilities that aren't builtin be derived for opaque types wrapping records
+/// and tag unions. The hooks of an ability member `m` are functions defined next to the ability,
+/// which combine the results of `m` on the parts of a value:
+///
+///   - `m_record : List (Str, r) -> r` gets the name of each field, and `m` of its value.
+///   - `m_tag : Str, List r -> r` gets the name of the tag, and `m` of each of its payloads.
+///
+/// For example, given
+///
+///   Show implements show : a -> Str where a implements Show
+///   show_record = \fields -> ...
+///   show_tag = \name, payloads -> ...
+///
+///   Person := { name : Name, nick : [None, Some Name] } implements [Show]
+///
+/// the derived `show` for `Person` is
+///
+///   \@Person payload -> show_record [
+///       ("name", show payload.name),
+///       ("nick", when payload.nick is
+///           None -> show_tag "None" []
+///           Some p -> show_tag "Some" [show p]),
+///   ]
+///
+/// Records and tag unions nested in the opaque type are folded the same way, and any other type
+/// is passed to `m`, so it has to implement the ability itself. Only members whose one argument
+/// is the value implementing the ability can be derived this way.
+pub(crate) struct DeriveHooks<'a> {
+    /// How the ability's module is referred to, or "" for the home module.
+    module_name: &'a str,
+    pub member: Symbol,
+    member_name: &'a str,
+    record_hook: &'a str,
+    tag_hook: &'a str,
+}
+
+const RECORD_HOOK_SUFFIX: &str = "_record";
+const TAG_HOOK_SUFFIX: &str = "_tag";
+
+#[derive(Default)]
+struct Shapes {
+    has_record: bool,
+    has_tag_union: bool,
+}
+
+impl Shapes {
+    fn of(&mut self, ann: &ast::TypeAnnotation<'_>) {
+        match ann.without_spaces() {
+            ast::TypeAnnotation::Record { fields, .. } => {
+                self.has_record = true;
+                for field in fields.iter() {
+                    if let Some(typ) = field.value.value() {
+                        self.of(&typ.value);
+                    }
+                }
+            }
+            ast::TypeAnnotation::TagUnion { tags, .. } => {
+                self.has_tag_union = true;
+                for tag in tags.iter() {
+                    if let ast::Tag::Apply { args, .. } = tag.value.without_spaces() {
+                        for arg in args {
+                            self.of(&arg.value);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The deriving hooks of each member of `ability` needed to derive it for an opaque type wrapping
+/// `ann`. If the ability has no hooks at all, the error is empty; otherwise it has the names of
+/// the hooks that are missing.
+pub(crate) fn find_derive_hooks<'a>(
+    env: &Env<'a>,
+    scope: &Scope,
+    ability: Symbol,
+    members: &[Symbol],
+    ann: &ast::TypeAnnotation<'a>,
+) -> Result<Vec<DeriveHooks<'a>>, Vec<Lowercase>> {
+    let module = ability.module_id();
+    let module_name = if module == env.home {
+        Some("")
+    } else {
+        scope
+            .modules
+            .name_of(module)
+            .map(|name| &*env.arena.alloc_str(name.as_str()))
+    };
+
+    let ident_name = |symbol: Symbol| {
+        if module == env.home {
+            scope.locals.ident_ids.get_name(symbol.ident_id())
+        } else {
+            env.dep_idents
+                .get(&module)
+                .and_then(|ident_ids| ident_ids.get_name(symbol.ident_id()))
+        }
+    };
+
+    let has_hook = |hook: &str| {
+        if module == env.home {
+            matches!(scope.lookup_str(hook, DERIVED_REGION), Ok(lookup) if lookup.symbol.module_id() == module)
+        } else {
+            module_name.is_some()
+                && env
+                    .dep_idents
+                    .get(&module)
+                    .is_some_and(|ident_ids| ident_ids.get_id(hook).is_some())
+        }
+    };
+
+    let mut shapes = Shapes::default();
+    shapes.of(ann);
+
+    let mut has_any_hook = false;
+    let mut missing = Vec::new();
+    let mut hooks = Vec::with_capacity(members.len());
+
+    for &member in members {
+        let Some(member_name) = ident_name(member) else {
+            return Err(Vec::new());
+        };
+
+        let record_hook = format!("{member_name}{RECORD_HOOK_SUFFIX}");
+        let tag_hook = format!("{member_name}{TAG_HOOK_SUFFIX}");
+
+        let has_record_hook = has_hook(&record_hook);
+        let has_tag_hook = has_hook(&tag_hook);
+        has_any_hook |= has_record_hook || has_tag_hook;
+
+        if shapes.has_record && !has_record_hook {
+            missing.push(record_hook.as_str().into());
+        }
+        if shapes.has_tag_union && !has_tag_hook {
+            missing.push(tag_hook.as_str().into());
+        }
+
+        hooks.push(DeriveHooks {
+            module_name: module_name.unwrap_or(""),
+            member,
+            member_name: env.arena.alloc_str(member_name),
+            record_hook: env.arena.alloc_str(&record_hook),
+            tag_hook: env.arena.alloc_str(&tag_hook),
+        });
+    }
+
+    if !has_any_hook {
+        Err(Vec::new())
+    } else if !missing.is_empty() {
+        Err(missing)
+    } else {
+        Ok(hooks)
+    }
+}
+
+struct HookFold<'a, 'h> {
+    arena: &'a Bump,
+    hooks: &'h DeriveHooks<'a>,
+    /// Where the derived ability is listed, so that a hook with the wrong type is reported there.
+    region: Region,
+    next_payload: usize,
+}
+
+impl<'a, 'h> HookFold<'a, 'h> {
+    fn alloc_expr(&self, expr: ast::Expr<'a>) -> &'a Loc<ast::Expr<'a>> {
+        self.arena.alloc(Loc::at(self.region, expr))
+    }
+
+    fn var(&self, module_name: &'a str, ident: &'a str) -> &'a Loc<ast::Expr<'a>> {
+        self.alloc_expr(ast::Expr::Var { module_name, ident })
+    }
+
+    fn call(&self, ident: &'a str, args: &'a [&'a Loc<ast::Expr<'a>>]) -> &'a Loc<ast::Expr<'a>> {
+        self.alloc_expr(ast::Expr::Apply(
+            self.var(self.hooks.module_name, ident),
+            args,
+            CalledVia::Space,
+        ))
+    }
+
+    fn list(&self, items: Vec<&'a Loc<ast::Expr<'a>>>) -> &'a Loc<ast::Expr<'a>> {
+        self.alloc_expr(ast::Expr::List(Collection::with_items(
+            self.arena.alloc_slice_copy(&items),
+        )))
+    }
+
+    fn str(&self, string: &'a str) -> &'a Loc<ast::Expr<'a>> {
+        self.alloc_expr(ast::Expr::Str(ast::StrLiteral::PlainLine(string)))
+    }
+
+    fn fresh_payload(&mut self) -> &'a str {
+        self.next_payload += 1;
+        self.arena
+            .alloc_str(&format!("#payload{}", self.next_payload))
+    }
+
+    /// The member applied to `value`, which has the type `ann`.
+    fn fold(
+        &mut self,
+        ann: &Loc<ast::TypeAnnotation<'a>>,
+        value: &'a Loc<ast::Expr<'a>>,
+    ) -> &'a Loc<ast::Expr<'a>> {
+        match ann.value.without_spaces() {
+            ast::TypeAnnotation::Record { fields, .. } => {
+                // m_record [("field", m value.field), ...]
+                let mut items = Vec::with_capacity(fields.len());
+                for field in fields.iter() {
+                    let (label, typ) = match field.value.without_spaces() {
+                        ast::AssignedField::RequiredValue(label, _, typ)
+                        | ast::AssignedField::OptionalValue(label, _, typ) => (label.value, typ),
+                        _ => continue,
+                    };
+
+                    let access = &*self.arena.alloc(Loc::at(
+                        typ.region,
+                        ast::Expr::RecordAccess(&value.value, label),
+                    ));
+                    let folded = self.fold(typ, access);
+                    let pair = self.alloc_expr(ast::Expr::Tuple(Collection::with_items(
+                        self.arena.alloc([self.str(label), folded]),
+                    )));
+
+                    items.push(pair);
+                }
+
+                let fields = self.list(items);
+                self.call(self.hooks.record_hook, self.arena.alloc([fields]))
+            }
+            ast::TypeAnnotation::TagUnion { tags, .. } => {
+                // when value is
+                //     Tag p1 p2 -> m_tag "Tag" [m p1, m p2]
+                //     ...
+                let mut branches = Vec::with_capacity(tags.len());
+                for tag in tags.iter() {
+                    let ast::Tag::Apply { name, args } = tag.value.without_spaces() else {
+                        continue;
+                    };
+
+                    let tag_pattern = ast::Pattern::Tag(name.value);
+                    let mut arg_patterns = Vec::with_capacity(args.len());
+                    let mut payloads = Vec::with_capacity(args.len());
+                    for arg in args {
+                        let ident = self.fresh_payload();
+                        arg_patterns
+                            .push(Loc::at(DERIVED_REGION, ast::Pattern::Identifier { ident }));
+                        let payload = self.arena.alloc(Loc::at(
+                            arg.region,
+                            ast::Expr::Var {
+                                module_name: "",
+                                ident,
+                            },
+                        ));
+                        payloads.push(self.fold(arg, payload));
+                    }
+
+                    let pattern = if arg_patterns.is_empty() {
+                        tag_pattern
+                    } else {
+                        ast::Pattern::Apply(
+                            self.arena.alloc(Loc::at(DERIVED_REGION, tag_pattern)),
+                            self.arena.alloc_slice_copy(&arg_patterns),
+                        )
+                    };
+
+                    let payloads = self.list(payloads);
+                    let body = self.call(
+                        self.hooks.tag_hook,
+                        self.arena.alloc([self.str(name.value), payloads]),
+                    );
+
+                    branches.push(&*self.arena.alloc(ast::WhenBranch {
+                        patterns: self.arena.alloc([Loc::at(DERIVED_REGION, pattern)]),
+                        value: *body,
+                        guard: None,
+                    }));
+                }
+
+                self.alloc_expr(ast::Expr::When(
+                    value,
+                    self.arena.alloc_slice_copy(&branches),
+                ))
+            }
+            _ => {
+                // m value
+                //
+                // This is located at the type of the value, which is reported if it doesn't
+                // implement the ability.
+                let member = self.arena.alloc(Loc::at(
+                    ann.region,
+                    ast::Expr::Var {
+                        module_name: self.hooks.module_name,
+                        ident: self.hooks.member_name,
+                    },
+                ));
+                self.arena.alloc(Loc::at(
+                    ann.region,
+                    ast::Expr::Apply(member, self.arena.alloc([value]), CalledVia::Space),
+                ))
+            }
+        }
+    }
+}
+
+/// Synthesizes `\@Opaq payload -> ...`, implementing an ability member through its deriving
+/// hooks for an opaque type wrapping `ann`. The ability is listed at `ability_region`.
+pub(crate) fn synthesize_hooked_member_impl<'a>(
+    env: &mut Env<'a>,
+    scope: &mut Scope,
+    opaque_name: &'a str,
+    hooks: &DeriveHooks<'a>,
+    ann: &Loc<ast::TypeAnnotation<'a>>,
+    ability_region: Region,
+) -> (Symbol, Loc<Pattern>, &'a Loc<ast::Expr<'a>>) {
+    let at_opaque = env.arena.alloc_str(&format!("@{opaque_name}"));
+    let payload = "#payload";
+
+    let mut fold = HookFold {
+        arena: env.arena,
+        hooks,
+        region: ability_region,
+        next_payload: 0,
+    };
+
+    // \@Opaq payload
+    let opaque_apply_pattern = ast::Pattern::Apply(
+        env.arena
+            .alloc(Loc::at(DERIVED_REGION, ast::Pattern::OpaqueRef(at_opaque))),
+        &*env.arena.alloc([Loc::at(
+            DERIVED_REGION,
+            ast::Pattern::Identifier { ident: payload },
+        )]),
+    );
+
+    let body = fold.fold(ann, fold.var("", payload));
+
+    let def_body = ast::Expr::Closure(
+        env.arena
+            .alloc([Loc::at(DERIVED_REGION, opaque_apply_pattern)]),
+        body,
+    );
+
+    let impl_name = format!("#{opaque_name}_{}", hooks.member_name);
+    let impl_symbol = scope
+        .introduce_str(&impl_name, DERIVED_REGION)
+        .expect("this name is not unique");
+
+    (
+        impl_symbol,
+        Loc::at(DERIVED_REGION, Pattern::Identifier(impl_symbol)),
+        env.arena.alloc(Loc::at(DERIVED_REGION, def_body)),
+    )
+}
@@ -0,0 +1,190 @@
+//! Directives, which are comments that change how the compiler treats the top-level def
+//! right below them, e.g.
+//!
+//! ```roc
+//! # @tailrec
+//! sum : List U64, U64 -> U64
+//! sum = \list, acc -> ...
+//! ```
+//!
+//! A directive can be above or below the def's annotation, and among other comments, as long as
+//! there's no blank line between it and the def. A top-level `expect` can be marked with
+//! [COMPTIME_DIRECTIVE] too, which checks it while compiling.
+use roc_module::symbol::Symbol;
+use roc_region::all::{LineInfo, Loc, Position, Region};
+
+use crate::expr::{DeclarationTag, Declarations};
+
+/// Requires every recursive call of the def to be a tail call, so that it compiles to a loop.
+pub const TAIL_RECURSIVE_DIRECTIVE: &str = "@tailrec";
+
+/// Evaluates the def while compiling, so its value is embedded in the program instead of being
+/// computed when the program runs. On an `expect`, fails the build when the expect doesn't hold.
+pub const COMPTIME_DIRECTIVE: &str = "@comptime";
+
+/// Calls a C function directly, instead of a `roc_fx_` function that the host has to provide.
+/// Only valid on the annotations in a hosted module. The C function's name follows the
+/// directive, e.g. `# @c sqrt`, and defaults to the name of the def without its `!`.
+pub const C_FUNCTION_DIRECTIVE: &str = "@c";
+
+/// Finds the top-level defs marked with [TAIL_RECURSIVE_DIRECTIVE].
+pub fn tail_recursive_defs(src: &str, decls: &Declarations) -> Vec<Loc<Symbol>> {
+    defs_with_directive(src, decls, TAIL_RECURSIVE_DIRECTIVE)
+}
+
+/// Finds the top-level defs marked with [COMPTIME_DIRECTIVE].
+pub fn comptime_defs(src: &str, decls: &Declarations) -> Vec<Loc<Symbol>> {
+    defs_with_directive(src, decls, COMPTIME_DIRECTIVE)
+}
+
+/// Finds the top-level expects marked with [COMPTIME_DIRECTIVE]. The region of each spans the
+/// `expect` keyword and the condition.
+pub fn comptime_expects(src: &str, decls: &Declarations) -> Vec<Loc<Symbol>> {
+    let line_info = LineInfo::new(src);
+    let lines: Vec<&str> = src.lines().collect();
+
+    (0..decls.len())
+        .filter(|index| matches!(decls.declarations[*index], DeclarationTag::Expectation))
+        .filter_map(|index| {
+            let condition = decls.expressions[index].region;
+            // The condition can start on the line after the keyword
+            let keyword = src[..condition.start().offset as usize].rfind("expect")?;
+            let region = Region::new(Position::new(keyword as u32), condition.end());
+            let line = line_info.convert_pos(region.start()).line as usize;
+
+            has_directive(&lines[..line], "expect", COMPTIME_DIRECTIVE)
+                .then(|| Loc::at(region, decls.symbols[index].value))
+        })
+        .collect()
+}
+
+/// The name of the C function that a hosted def marked with [C_FUNCTION_DIRECTIVE] binds to,
+/// where `def` is the region of the def's name.
+pub fn c_function_name(src: &str, def: Region) -> Option<&str> {
+    let line_info = LineInfo::new(src);
+    let lines: Vec<&str> = src.lines().collect();
+    let name = &src[def.start().offset as usize..def.end().offset as usize];
+    let line = line_info.convert_pos(def.start()).line as usize;
+
+    match directive_argument(&lines[..line], name, C_FUNCTION_DIRECTIVE)? {
+        "" => Some(name.trim_end_matches('!')),
+        c_name => Some(c_name),
+    }
+}
+
+fn defs_with_directive(src: &str, decls: &Declarations, directive: &str) -> Vec<Loc<Symbol>> {
+    let line_info = LineInfo::new(src);
+    let lines: Vec<&str> = src.lines().collect();
+
+    decls
+        .symbols
+        .iter()
+        .zip(decls.declarations.iter())
+        // An expect's symbol is located at the comments above it
+        .filter(|(_, tag)| !matches!(tag, DeclarationTag::Expectation))
+        .map(|(loc_symbol, _)| loc_symbol)
+        .filter(|loc_symbol| {
+            let region = loc_symbol.region;
+            let name = &src[region.start().offset as usize..region.end().offset as usize];
+            let line = line_info.convert_pos(region.start()).line as usize;
+
+            has_directive(&lines[..line], name, directive)
+        })
+        .copied()
+        .collect()
+}
+
+/// Whether the comments right above a def (whose line isn't in `lines_above`) contain the directive.
+fn has_directive(lines_above: &[&str], name: &str, directive: &str) -> bool {
+    directive_argument(lines_above, name, directive).is_some()
+}
+
+/// The text after the directive in the comments right above a def, e.g. `sqrt` for `# @c sqrt`.
+fn directive_argument<'s>(lines_above: &[&'s str], name: &str, directive: &str) -> Option<&'s str> {
+    for line in lines_above.iter().rev() {
+        let trimmed = line.trim();
+
+        if let Some(comment) = trimmed.strip_prefix('#') {
+            let comment = comment.strip_prefix('#').unwrap_or(comment).trim();
+
+            if let Some(argument) = comment.strip_prefix(directive) {
+                if argument.is_empty() || argument.starts_with(char::is_whitespace) {
+                    return Some(argument.trim());
+                }
+            }
+        } else if trimmed.is_empty() {
+            return None;
+        } else if line.starts_with(char::is_whitespace) {
+            // Part of a multiline annotation
+        } else {
+            let is_annotation = line
+                .strip_prefix(name)
+                .is_some_and(|rest| rest.trim_start().starts_with(':'));
+
+            if !is_annotation {
+                return None;
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn above(src: &str) -> Vec<&str> {
+        src.lines().collect()
+    }
+
+    #[test]
+    fn directive_above_annotation() {
+        let lines = above("# Sums a list\n# @tailrec\nsum : List U64,\n    U64 -> U64");
+
+        assert!(has_directive(&lines, "sum", TAIL_RECURSIVE_DIRECTIVE));
+    }
+
+    #[test]
+    fn doc_comment_directive() {
+        let lines = above("x = 1\n\n## @tailrec");
+
+        assert!(has_directive(&lines, "sum", TAIL_RECURSIVE_DIRECTIVE));
+    }
+
+    #[test]
+    fn directive_of_other_def() {
+        let lines = above("# @tailrec\nother = 1");
+
+        assert!(!has_directive(&lines, "sum", TAIL_RECURSIVE_DIRECTIVE));
+
+        let lines = above("# @tailrec\n\n# Sums a list");
+
+        assert!(!has_directive(&lines, "sum", TAIL_RECURSIVE_DIRECTIVE));
+    }
+    #[test]
+    fn directive_kinds() {
+        let lines = above("# @comptime");
+
+        assert!(has_directive(&lines, "table", COMPTIME_DIRECTIVE));
+        assert!(!has_directive(&lines, "table", TAIL_RECURSIVE_DIRECTIVE));
+        assert!(!has_directive(&lines, "table", C_FUNCTION_DIRECTIVE));
+    }
+
+    #[test]
+    fn c_function_names() {
+        let src =
+            "hosted [sqrt!, abs!]\n\n# @c c_sqrt\nsqrt! : F64 => F64\n\n# @c\nabs! : I64 => I64\n";
+        let def = |name: &str| {
+            let start = src.find(&format!("\n{name}")).unwrap() as u32 + 1;
+
+            Region::new(
+                roc_region::all::Position::new(start),
+                roc_region::all::Position::new(start + name.len() as u32),
+            )
+        };
+
+        assert_eq!(c_function_name(src, def("sqrt!")), Some("c_sqrt"));
+        assert_eq!(c_function_name(src, def("abs!")), Some("abs"));
+    }
+}
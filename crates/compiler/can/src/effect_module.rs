@@ -7,8 +7,9 @@ use roc_module::symbol::Symbol;
 use roc_problem::can::{Problem, RuntimeError};
 use roc_region::all::{Loc, Region};
 use roc_types::subs::{VarStore, Variable};
-use roc_types::types::Type;
+use roc_types::types::{AliasCommon, Type};
 
+#[allow(clippy::too_many_arguments)]
 pub fn build_host_exposed_def(
     scope: &mut Scope,
     symbol: Symbol,
@@ -17,6 +18,7 @@ pub fn build_host_exposed_def(
     var_store: &mut VarStore,
     problems: &mut Vec<Problem>,
     annotation: crate::annotation::Annotation,
+    c_name: Option<&str>,
 ) -> Def {
     let expr_var = var_store.fresh();
     let pattern = Pattern::Identifier(symbol);
@@ -34,7 +36,7 @@ pub fn build_host_exposed_def(
     } = annotation;
 
     let foreign_call = match typ.shallow_structural_dealias() {
-        Type::Function(args, _, _, _) => {
+        Type::Function(args, _, ret, _) => {
             for i in 0..args.len() {
                 let name = format!("{ident}_arg_{i}");
 
@@ -51,16 +53,32 @@ pub fn build_host_exposed_def(
                     Loc::at_zero(Pattern::Identifier(arg_symbol)),
                 ));
 
-                linked_symbol_arguments.push((arg_var, Expr::Var(arg_symbol, arg_var)));
+                // C functions without arguments take `{}` in Roc
+                if c_name.is_none() || !is_c_void(&args[i]) {
+                    linked_symbol_arguments.push((arg_var, Expr::Var(arg_symbol, arg_var)));
+                }
             }
 
-            let ident_without_bang = ident.trim_end_matches('!');
-            let foreign_symbol_name = format!("roc_fx_{ident_without_bang}");
+            let foreign_symbol_name = match c_name {
+                Some(c_name) => c_name.to_string(),
+                None => {
+                    let ident_without_bang = ident.trim_end_matches('!');
 
-            Expr::ForeignCall {
-                foreign_symbol: foreign_symbol_name.into(),
-                args: linked_symbol_arguments,
-                ret_var: var_store.fresh(),
+                    format!("roc_fx_{ident_without_bang}")
+                }
+            };
+
+            if c_name.is_some() && !args.iter().chain([&**ret]).all(is_c_type) {
+                let runtime_error = RuntimeError::UnsupportedCFunctionType(region);
+                problems.push(Problem::RuntimeError(runtime_error.clone()));
+
+                Expr::RuntimeError(runtime_error)
+            } else {
+                Expr::ForeignCall {
+                    foreign_symbol: foreign_symbol_name.into(),
+                    args: linked_symbol_arguments,
+                    ret_var: var_store.fresh(),
+                }
             }
         }
         _ => {
@@ -100,3 +118,37 @@ pub fn build_host_exposed_def(
         kind: DefKind::Let,
     }
 }
+
+/// Whether values of this type can be passed to and returned from a C function as they are,
+/// where `{}` stands for `void`.
+fn is_c_type(typ: &Type) -> bool {
+    let symbol = match typ {
+        Type::Alias { symbol, .. } => *symbol,
+        Type::DelayedAlias(AliasCommon { symbol, .. }) => *symbol,
+        Type::Apply(symbol, _, _) => *symbol,
+        _ => return is_c_void(typ),
+    };
+
+    matches!(
+        symbol,
+        Symbol::NUM_I8
+            | Symbol::NUM_U8
+            | Symbol::NUM_I16
+            | Symbol::NUM_U16
+            | Symbol::NUM_I32
+            | Symbol::NUM_U32
+            | Symbol::NUM_I64
+            | Symbol::NUM_U64
+            | Symbol::NUM_F32
+            | Symbol::NUM_F64
+            | Symbol::BOOL_BOOL
+    )
+}
+
+fn is_c_void(typ: &Type) -> bool {
+    match typ {
+        Type::EmptyRec => true,
+        Type::Record(fields, _) => fields.is_empty(),
+        _ => false,
+    }
+}
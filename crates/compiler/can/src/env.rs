@@ -3,9 +3,10 @@ use std::path::Path;
 use crate::procedure::References;
 use crate::scope::{ModuleLookup, Scope, SymbolLookup};
 use bumpalo::Bump;
-use roc_collections::{MutMap, VecSet};
+use roc_collections::{MutMap, MutSet, VecSet};
 use roc_module::ident::{Ident, ModuleName};
 use roc_module::symbol::{IdentIdsByModule, ModuleId, PQModuleName, PackageModuleIds, Symbol};
+use roc_parse::nesting::max_nesting_depth;
 use roc_problem::can::{Problem, RuntimeError};
 use roc_region::all::{LineInfo, Loc, Region};
 use roc_types::subs::Variable;
@@ -49,6 +50,16 @@ pub struct Env<'a> {
     /// otherwise we can leave it as `None` and never pay the cost of scanning the source an extra
     /// time.
     line_info: &'a mut Option<LineInfo>,
+
+    /// How many expressions deep canonicalization currently is
+    pub(crate) nesting_depth: usize,
+
+    /// Expressions nested deeper than this become runtime errors instead of being canonicalized,
+    /// so that deeply nested (usually machine-generated) code can't overflow the stack.
+    pub max_nesting_depth: usize,
+
+    /// Whether the nesting limit was already reported, so it's only reported once per module
+    pub(crate) reported_nesting_too_deep: bool,
 }
 
 impl<'a> Env<'a> {
@@ -79,6 +90,9 @@ impl<'a> Env<'a> {
             home_params_record: None,
             opt_shorthand,
             line_info,
+            nesting_depth: 0,
+            max_nesting_depth: max_nesting_depth(),
+            reported_nesting_too_deep: false,
         }
     }
 
@@ -107,6 +121,9 @@ impl<'a> Env<'a> {
             home_params_record: None,
             opt_shorthand,
             line_info: arena.alloc(None),
+            nesting_depth: 0,
+            max_nesting_depth: max_nesting_depth(),
+            reported_nesting_too_deep: false,
         }
     }
 
@@ -194,6 +211,7 @@ impl<'a> Env<'a> {
                             .map(|(_, string)| string.into())
                             .collect(),
                         underscored_suggestion_region: None,
+                        type_compatible_suggestions: MutSet::default(),
                     };
                     Err(error)
                 }
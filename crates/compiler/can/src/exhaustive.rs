@@ -49,10 +49,15 @@ pub fn check(
     } = sketched_rows.reify_to_non_redundant(subs, real_var)?;
     all_errors.extend(errors);
 
-    let exhaustive = match roc_exhaustive::check(overall_region, context, non_redundant_rows) {
+    let result = roc_exhaustive::check(overall_region, context, non_redundant_rows.clone());
+    let exhaustive = match result {
         Ok(()) => true,
         Err(errors) => {
-            all_errors.extend(errors);
+            all_errors.extend(
+                errors
+                    .into_iter()
+                    .map(|error| explain_guarded_patterns(error, &non_redundant_rows)),
+            );
             false
         }
     };
@@ -64,6 +69,56 @@ pub fn check(
     })
 }
 
+/// If the only values a `when` misses are ones its guarded branches would match, report the
+/// patterns that depend on guards rather than the `#Guard` rows the checker produced.
+fn explain_guarded_patterns(error: Error, rows: &[Vec<Pattern>]) -> Error {
+    let (region, missing) = match &error {
+        Error::Incomplete(region, ExhaustiveContext::BadCase, missing) => (*region, missing),
+        _ => return error,
+    };
+
+    // Pretend every guard is true: a missing pattern that is then covered is only handled by
+    // branches with a guard.
+    let without_guards = |pattern: &Pattern| match pattern {
+        Pattern::Ctor(union, tag_id, args) if union.render_as == RenderAs::Guard => {
+            Some(Pattern::Ctor(
+                union.clone(),
+                *tag_id,
+                vec![Pattern::Anything, args[1].clone()],
+            ))
+        }
+        _ => None,
+    };
+
+    let unguarded_rows: Option<Vec<Vec<Pattern>>> = rows
+        .iter()
+        .map(|row| without_guards(&row[0]).map(|pattern| vec![pattern]))
+        .collect();
+
+    let unguarded_rows = match unguarded_rows {
+        Some(rows) => rows,
+        None => return error,
+    };
+
+    let mut patterns = Vec::with_capacity(missing.len());
+
+    for pattern in missing {
+        match (without_guards(pattern), pattern) {
+            (Some(unguarded), Pattern::Ctor(_, _, args))
+                if !is_useful(unguarded_rows.clone(), vec![unguarded]) =>
+            {
+                patterns.push(args[1].clone());
+            }
+            _ => return error,
+        }
+    }
+
+    Error::OnlyGuarded {
+        overall_region: region,
+        patterns,
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 enum SketchedPattern {
     Anything,
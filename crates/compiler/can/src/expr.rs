@@ -7,7 +7,9 @@ use crate::num::{
     int_expr_from_result, num_expr_from_result, FloatBound, IntBound, NumBound,
 };
 use crate::params_in_abilities_unimplemented;
-use crate::pattern::{canonicalize_pattern, BindingsFromPattern, Pattern, PermitShadows};
+use crate::pattern::{
+    binding_kind, canonicalize_pattern, BindingsFromPattern, Pattern, PermitShadows,
+};
 use crate::procedure::{QualifiedReference, References};
 use crate::scope::{Scope, SymbolLookup};
 use crate::traverse::{walk_expr, Visitor};
@@ -22,7 +24,7 @@ use roc_module::symbol::{IdentId, ModuleId, Symbol};
 use roc_parse::ast::{self, Defs, PrecedenceConflict, ResultTryKind, StrLiteral};
 use roc_parse::ident::Accessor;
 use roc_parse::pattern::PatternType::*;
-use roc_problem::can::{PrecedenceProblem, Problem, RuntimeError};
+use roc_problem::can::{BindingKind, PrecedenceProblem, Problem, RuntimeError};
 use roc_region::all::{Loc, Region};
 use roc_types::num::SingleQuoteBound;
 use roc_types::subs::{ExhaustiveMark, IllegalCycleMark, RedundantMark, VarStore, Variable};
@@ -245,6 +247,13 @@ pub enum Expr {
         ret_var: Variable,
     },
 
+    /// A typed hole, e.g. `???` or `?name`, which crashes when it's evaluated.
+    /// The type checker reports the type that's expected in its place.
+    TypedHole {
+        name: Option<Lowercase>,
+        var: Variable,
+    },
+
     /// Look up exactly one field on a record, e.g. (expr).foo.
     RecordAccess {
         record_var: Variable,
@@ -413,6 +422,7 @@ impl Expr {
             }
             Self::Expect { .. } => Category::Expect,
             Self::Crash { .. } => Category::Crash,
+            Self::TypedHole { .. } => Category::TypedHole,
             Self::Return { .. } => Category::Return(EarlyReturnKind::Return),
 
             Self::Dbg { .. } => Category::Expect,
@@ -439,6 +449,7 @@ impl Expr {
             | Self::RecordAccessor(_)
             | Self::ZeroArgumentTag { .. }
             | Self::OpaqueWrapFunction(_)
+            | Self::TypedHole { .. }
             | Self::RuntimeError(..) => false,
             Self::Return { .. } | Self::Try { .. } => true,
             Self::List { loc_elems, .. } => loc_elems
@@ -927,6 +938,39 @@ pub fn canonicalize_expr<'a>(
     scope: &mut Scope,
     region: Region,
     expr: &'a ast::Expr<'a>,
+) -> (Loc<Expr>, Output) {
+    // Machine-generated code can be nested deeply enough to overflow the stack, so past a
+    // certain depth we give up on the expression rather than recursing any further.
+    if env.nesting_depth >= env.max_nesting_depth {
+        let error = RuntimeError::NestingTooDeep {
+            region,
+            max_depth: env.max_nesting_depth,
+        };
+
+        if !env.reported_nesting_too_deep {
+            env.reported_nesting_too_deep = true;
+            env.problem(Problem::RuntimeError(error.clone()));
+        }
+
+        return (
+            Loc::at(region, Expr::RuntimeError(error)),
+            Output::default(),
+        );
+    }
+
+    env.nesting_depth += 1;
+    let answer = canonicalize_expr_help(env, var_store, scope, region, expr);
+    env.nesting_depth -= 1;
+
+    answer
+}
+
+fn canonicalize_expr_help<'a>(
+    env: &mut Env<'a>,
+    var_store: &mut VarStore,
+    scope: &mut Scope,
+    region: Region,
+    expr: &'a ast::Expr<'a>,
 ) -> (Loc<Expr>, Output) {
     use Expr::*;
 
@@ -1187,6 +1231,13 @@ pub fn canonicalize_expr<'a>(
                 Output::default(),
             )
         }
+        ast::Expr::TypedHole(name) => (
+            TypedHole {
+                name: (!name.is_empty()).then(|| Lowercase::from(*name)),
+                var: var_store.fresh(),
+            },
+            Output::default(),
+        ),
         ast::Expr::Defs(loc_defs, loc_ret) => {
             // The body expression gets a new scope for canonicalization,
             scope.inner_def_scope(|inner_scope| {
@@ -1836,11 +1887,17 @@ fn canonicalize_closure_body<'a>(
     for (sub_symbol, region) in bound_by_argument_patterns {
         if !output.references.has_value_lookup(sub_symbol) {
             // The body never referenced this argument we declared. It's an unused argument!
+            let kind = can_args
+                .iter()
+                .find_map(|(_, _, loc_pattern)| binding_kind(&loc_pattern.value, sub_symbol))
+                .unwrap_or(BindingKind::Other);
+
             env.problem(Problem::UnusedArgument(
                 symbol,
                 is_anonymous,
                 sub_symbol,
                 region,
+                kind,
             ));
         } else {
             // We shouldn't ultimately count arguments as referenced locals. Otherwise,
@@ -2024,7 +2081,13 @@ fn canonicalize_when_branch<'a>(
         if output.references.has_value_lookup(symbol) {
             pattern_bound_symbols_body_needs.insert(symbol);
         } else {
-            env.problem(Problem::UnusedBranchDef(symbol, region));
+            let kind = patterns
+                .iter()
+                .filter(|pattern| pattern.pattern.region.contains(&region))
+                .find_map(|pattern| binding_kind(&pattern.pattern.value, symbol))
+                .unwrap_or(BindingKind::Other);
+
+            env.problem(Problem::UnusedPatternBinding(symbol, region, kind));
         }
     }
 
@@ -2309,6 +2372,7 @@ pub fn is_valid_interpolation(expr: &ast::Expr<'_>) -> bool {
         | ast::Expr::AccessorFunction(_)
         | ast::Expr::RecordUpdater(_)
         | ast::Expr::Crash
+        | ast::Expr::TypedHole(_)
         | ast::Expr::Dbg
         | ast::Expr::Try
         | ast::Expr::Underscore(_)
@@ -3235,6 +3299,7 @@ pub(crate) fn get_lookup_symbols(expr: &Expr) -> Vec<ExpectLookup> {
             | Expr::SingleQuote(..)
             | Expr::EmptyRecord
             | Expr::RuntimeError(_)
+            | Expr::TypedHole { .. }
             | Expr::ImportParams(_, _, None)
             | Expr::OpaqueWrapFunction(_) => {}
         }
@@ -0,0 +1,237 @@
+//! Repairs for module headers that are missing, or that expose names the module doesn't define.
+//!
+//! The repairs are [Fix]es, so an editor or tool can apply them like any other.
+
+use bumpalo::Bump;
+use roc_parse::ast::{Defs, ExtractSpaces, Pattern, TypeDef, ValueDef};
+use roc_parse::header::{parse_module_defs, ExposedName};
+use roc_parse::state::State;
+use roc_problem::fix::{Edit, Fix};
+use roc_region::all::{Loc, Position, Region};
+
+/// The names of the values, types, and ability members that a module defines at the top level,
+/// in the order they're defined. These are what a synthesized header exposes.
+pub fn top_level_names<'a>(defs: &Defs<'a>) -> Vec<&'a str> {
+    let mut names = Vec::new();
+
+    for def in defs.defs() {
+        match def {
+            Ok(TypeDef::Alias { header, .. } | TypeDef::Opaque { header, .. }) => {
+                names.push(header.name.value);
+            }
+            Ok(TypeDef::Ability {
+                header, members, ..
+            }) => {
+                names.push(header.name.value);
+                names.extend(
+                    members
+                        .iter()
+                        .map(|member| member.name.value.without_spaces()),
+                );
+            }
+            Err(ValueDef::Annotation(pattern, _)) => {
+                names.extend(identifier_name(pattern));
+            }
+            Err(ValueDef::Body(pattern, _)) => {
+                names.extend(identifier_name(pattern));
+            }
+            Err(ValueDef::AnnotatedBody { body_pattern, .. }) => {
+                names.extend(identifier_name(body_pattern));
+            }
+            Err(
+                ValueDef::Dbg { .. }
+                | ValueDef::Expect { .. }
+                | ValueDef::ModuleImport(_)
+                | ValueDef::IngestedFileImport(_)
+                | ValueDef::Stmt(_)
+                | ValueDef::StmtAfterExpr,
+            ) => {}
+        }
+    }
+
+    // An annotation and its body are separate defs when there's a blank line between them
+    let mut seen = Vec::with_capacity(names.len());
+    names.retain(|name| {
+        // Names that start with an underscore are meant to be private
+        let keep = !name.starts_with('_') && !seen.contains(name);
+        seen.push(*name);
+
+        keep
+    });
+
+    names
+}
+
+fn identifier_name<'a>(pattern: &Loc<Pattern<'a>>) -> Option<&'a str> {
+    match pattern.value.without_spaces() {
+        Pattern::Identifier { ident } => Some(ident),
+        _ => None,
+    }
+}
+
+/// A `module` header that exposes every top-level def in `src`, to put at the start of a file
+/// that doesn't have a header. Returns `None` if `src` doesn't parse as a list of defs, since
+/// then the missing header isn't the only problem.
+pub fn missing_header_fix(src: &str) -> Option<Fix> {
+    let arena = Bump::new();
+    let defs = parse_module_defs(&arena, State::new(src.as_bytes()), Defs::default()).ok()?;
+    let header = format!("module [{}]\n\n", top_level_names(&defs).join(", "));
+
+    Some(Fix::replace(
+        "Add a `module` header that exposes the top-level definitions".to_string(),
+        Region::new(Position::new(0), Position::new(0)),
+        header,
+    ))
+}
+
+/// Remove the names in `undefined` from a header's `exposes` list, leaving everything else in
+/// the header as it is. `exposes` are the located entries of that list, from the header parsed
+/// out of `src`.
+pub fn undefined_exposes_fix(
+    src: &str,
+    exposes: &[Loc<ExposedName>],
+    undefined: &[&str],
+) -> Option<Fix> {
+    let is_removed = |index: usize| {
+        let name = exposes[index].value.as_str();
+
+        undefined.iter().any(|undefined| *undefined == name)
+    };
+    let mut edits = Vec::new();
+    let mut index = 0;
+
+    while index < exposes.len() {
+        if !is_removed(index) {
+            index += 1;
+            continue;
+        }
+
+        // Remove each run of undefined entries along with the commas and spaces that separate
+        // them from the entries that are left
+        let first = index;
+        while index < exposes.len() && is_removed(index) {
+            index += 1;
+        }
+        let last = index - 1;
+
+        let region = if let Some(next) = exposes.get(index) {
+            Region::new(exposes[first].region.start(), next.region.start())
+        } else if first > 0 {
+            Region::new(exposes[first - 1].region.end(), exposes[last].region.end())
+        } else {
+            // Nothing is left, so take the trailing comma too, if there is one
+            let end = exposes[last].region.end();
+            let after = &src[end.offset as usize..];
+            let trimmed = after.trim_start();
+            let end = match trimmed.strip_prefix(',') {
+                Some(_) => end.bump_column((after.len() - trimmed.len()) as u32 + 1),
+                None => end,
+            };
+
+            Region::new(exposes[first].region.start(), end)
+        };
+
+        edits.push(Edit {
+            region,
+            replacement: String::new(),
+        });
+    }
+
+    if edits.is_empty() {
+        return None;
+    }
+
+    let names = undefined
+        .iter()
+        .map(|name| format!("`{name}`"))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    Some(Fix {
+        title: format!("Stop exposing {names}, which this module doesn't define"),
+        edits,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use roc_parse::ast::Header;
+    use roc_parse::header::parse_header;
+
+    fn apply(src: &str, fix: &Fix) -> String {
+        let mut edits = fix.edits.clone();
+        edits.sort_by_key(|edit| edit.region.start().offset);
+
+        let mut fixed = src.to_string();
+
+        for edit in edits.iter().rev() {
+            let start = edit.region.start().offset as usize;
+            let end = edit.region.end().offset as usize;
+
+            fixed.replace_range(start..end, &edit.replacement);
+        }
+
+        fixed
+    }
+
+    fn without_exposes(src: &str, undefined: &[&str]) -> String {
+        let arena = Bump::new();
+        let (module, _) = parse_header(&arena, State::new(src.as_bytes())).unwrap();
+        let exposes: Vec<_> = match module.item {
+            Header::Module(header) => header
+                .exposes
+                .iter()
+                .map(|loc| Loc::at(loc.region, loc.value.extract_spaces().item))
+                .collect(),
+            other => panic!("expected a module header, but got {other:?}"),
+        };
+
+        let fix = undefined_exposes_fix(src, &exposes, undefined).unwrap();
+
+        apply(src, &fix)
+    }
+
+    #[test]
+    fn synthesized_header_exposes_top_level_defs() {
+        let src = "Color : [Red, Green]\n\n_private = 1\n\nmain : Str\n\nmain = \"hi\"\n\nHash implements\n    hash : a -> U64 where a implements Hash\n";
+        let fix = missing_header_fix(src).unwrap();
+
+        assert_eq!(
+            apply(src, &fix),
+            format!("module [Color, main, Hash, hash]\n\n{src}")
+        );
+    }
+
+    #[test]
+    fn no_synthesized_header_for_broken_defs() {
+        assert_eq!(missing_header_fix("x = \n\n)"), None);
+    }
+
+    #[test]
+    fn remove_undefined_exposes() {
+        assert_eq!(
+            without_exposes("module [a, missing, b, other]\n", &["missing", "other"]),
+            "module [a, b]\n"
+        );
+    }
+
+    #[test]
+    fn remove_undefined_exposes_from_multiline_list() {
+        assert_eq!(
+            without_exposes(
+                "module [\n    missing,\n    a,\n    other,\n]\n",
+                &["missing", "other"]
+            ),
+            "module [\n    a,\n]\n"
+        );
+    }
+
+    #[test]
+    fn remove_every_expose() {
+        assert_eq!(
+            without_exposes("module [missing, other]\n", &["missing", "other"]),
+            "module []\n"
+        );
+    }
+}
@@ -14,11 +14,13 @@ pub mod copy;
 pub mod def;
 mod derive;
 pub mod desugar;
+pub mod directive;
 pub mod effect_module;
 pub mod env;
 pub mod exhaustive;
 pub mod expected;
 pub mod expr;
+pub mod header_repair;
 pub mod module;
 pub mod num;
 pub mod pattern;
@@ -3,6 +3,7 @@ use crate::annotation::{canonicalize_annotation, AnnotationFor};
 use crate::def::{canonicalize_defs, report_unused_imports, Def, DefKind};
 use crate::env::Env;
 use crate::expr::{ClosureData, Declarations, ExpectLookup, Expr, Output, PendingDerives};
+use crate::header_repair;
 use crate::pattern::{
     canonicalize_record_destructs, BindingsFromPattern, Pattern, PermitShadows, RecordDestruct,
 };
@@ -331,13 +332,13 @@ pub fn canonicalize_module_defs<'a>(
     //
     // We'll catch symbols that are only referenced due to (mutual) recursion later,
     // when sorting the defs.
-    for (symbol, region) in symbols_introduced {
+    for (symbol, introduced) in symbols_introduced {
         if !output.references.has_type_or_value_lookup(symbol)
             && !exposed_symbols.contains(&symbol)
             && !scope.abilities_store.is_specialization_name(symbol)
             && !symbol.is_exposed_for_builtin_derivers()
         {
-            env.problem(Problem::UnusedDef(symbol, region));
+            env.problem(introduced.unused(symbol));
         }
     }
 
@@ -482,6 +483,11 @@ pub fn canonicalize_module_defs<'a>(
                                 aliases: Default::default(),
                             };
 
+                            let c_name = crate::directive::c_function_name(
+                                env.src,
+                                declarations.symbols[index].region,
+                            );
+
                             let hosted_def = crate::effect_module::build_host_exposed_def(
                                 &mut scope,
                                 *symbol,
@@ -490,6 +496,7 @@ pub fn canonicalize_module_defs<'a>(
                                 var_store,
                                 &mut env.problems,
                                 annotation,
+                                c_name,
                             );
 
                             declarations.update_builtin_def(index, hosted_def);
@@ -541,6 +548,11 @@ pub fn canonicalize_module_defs<'a>(
                                 aliases: Default::default(),
                             };
 
+                            let c_name = crate::directive::c_function_name(
+                                env.src,
+                                declarations.symbols[index].region,
+                            );
+
                             let hosted_def = crate::effect_module::build_host_exposed_def(
                                 &mut scope,
                                 *symbol,
@@ -549,6 +561,7 @@ pub fn canonicalize_module_defs<'a>(
                                 var_store,
                                 &mut env.problems,
                                 annotation,
+                                c_name,
                             );
 
                             declarations.update_builtin_def(index, hosted_def);
@@ -600,8 +613,30 @@ pub fn canonicalize_module_defs<'a>(
     // exposed_symbols and added to exposed_vars_by_symbol. If any were
     // not, that means they were declared as exposed but there was
     // no actual declaration with that name!
+    let undefined_exposes_fix = if exposed_but_not_defined.is_empty() {
+        None
+    } else {
+        let undefined: Vec<&str> = exposed_but_not_defined
+            .iter()
+            .filter_map(|symbol| scope.locals.ident_ids.get_name(symbol.ident_id()))
+            .collect();
+
+        match header_type {
+            HeaderType::Module { exposes, .. } | HeaderType::Hosted { exposes, .. } => {
+                header_repair::undefined_exposes_fix(env.src, exposes, &undefined)
+            }
+            HeaderType::App { .. }
+            | HeaderType::Builtin { .. }
+            | HeaderType::Package { .. }
+            | HeaderType::Platform { .. } => None,
+        }
+    };
+
     for symbol in exposed_but_not_defined {
-        env.problem(Problem::ExposedButNotDefined(symbol));
+        env.problem(Problem::ExposedButNotDefined {
+            symbol,
+            fix: undefined_exposes_fix.clone(),
+        });
 
         // In case this exposed value is referenced by other modules,
         // create a decl for it whose implementation is a runtime error.
@@ -1023,6 +1058,7 @@ fn fix_values_captured_in_closure_expr(
         | AbilityMember(..)
         | EmptyRecord
         | RuntimeError(_)
+        | TypedHole { .. }
         | ZeroArgumentTag { .. }
         | RecordAccessor { .. } => {}
 
@@ -12,7 +12,7 @@ use roc_module::ident::{Ident, Lowercase, TagName};
 use roc_module::symbol::Symbol;
 use roc_parse::ast::{self, ExtractSpaces, StrLiteral, StrSegment};
 use roc_parse::pattern::PatternType;
-use roc_problem::can::{MalformedPatternProblem, Problem, RuntimeError, ShadowKind};
+use roc_problem::can::{BindingKind, MalformedPatternProblem, Problem, RuntimeError, ShadowKind};
 use roc_region::all::{Loc, Region};
 use roc_types::num::SingleQuoteBound;
 use roc_types::subs::{VarStore, Variable};
@@ -1133,6 +1133,59 @@ impl<'a> Iterator for BindingsFromPattern<'a> {
     }
 }
 
+/// How a pattern binds a symbol, if it does.
+pub fn binding_kind(pattern: &Pattern, symbol: Symbol) -> Option<BindingKind> {
+    use Pattern::*;
+
+    match pattern {
+        Identifier(bound)
+        | AbilityMemberSpecialization {
+            ident: bound,
+            specializes: _,
+        } => (*bound == symbol).then_some(BindingKind::Identifier),
+        As(_, bound) if *bound == symbol => Some(BindingKind::Other),
+        As(inner, _) => binding_kind(&inner.value, symbol),
+        AppliedTag { arguments, .. } => arguments
+            .iter()
+            .find_map(|(_, loc_arg)| binding_kind(&loc_arg.value, symbol)),
+        UnwrappedOpaque { argument, .. } => binding_kind(&argument.1.value, symbol),
+        TupleDestructure { destructs, .. } => destructs
+            .iter()
+            .find_map(|destruct| binding_kind(&destruct.value.typ.1.value, symbol)),
+        RecordDestructure { destructs, .. } => {
+            destructs
+                .iter()
+                .find_map(|destruct| match &destruct.value.typ {
+                    DestructType::Required if destruct.value.symbol == symbol => {
+                        Some(BindingKind::RecordField)
+                    }
+                    DestructType::Optional(..) if destruct.value.symbol == symbol => {
+                        Some(BindingKind::Other)
+                    }
+                    DestructType::Guard(_, inner) => binding_kind(&inner.value, symbol),
+                    DestructType::Required | DestructType::Optional(..) => None,
+                })
+        }
+        List { patterns, .. } => match &patterns.opt_rest {
+            Some((_, Some(rest))) if rest.value == symbol => Some(BindingKind::Identifier),
+            _ => patterns
+                .patterns
+                .iter()
+                .find_map(|loc_pattern| binding_kind(&loc_pattern.value, symbol)),
+        },
+        NumLiteral(..)
+        | IntLiteral(..)
+        | FloatLiteral(..)
+        | StrLiteral(_)
+        | SingleQuote(..)
+        | Underscore
+        | Shadowed(..)
+        | MalformedPattern(..)
+        | UnsupportedPattern(_)
+        | OpaqueNotInScope(..) => None,
+    }
+}
+
 fn flatten_str_literal(literal: &StrLiteral<'_>) -> Pattern {
     use ast::StrLiteral::*;
 
@@ -1,4 +1,4 @@
-use roc_collections::{VecMap, VecSet};
+use roc_collections::{MutSet, VecMap, VecSet};
 use roc_error_macros::internal_error;
 use roc_module::ident::{Ident, ModuleName};
 use roc_module::symbol::{IdentId, IdentIds, ModuleId, ModuleIds, Symbol};
@@ -50,6 +50,10 @@ pub struct Scope {
     ignored_locals: VecMap<String, Region>,
 
     pub early_returns: Vec<(Variable, Region, EarlyReturnKind)>,
+
+    /// Locals that a def like `x = x + 1` replaced with a new symbol of the same name, which are
+    /// back in scope when the def scope that replaced them ends.
+    rebound: Vec<IdentId>,
 }
 
 impl Scope {
@@ -76,6 +80,7 @@ impl Scope {
             imported_symbols: default_imports,
             ignored_locals: VecMap::default(),
             early_returns: Vec::default(),
+            rebound: Vec::default(),
         }
     }
 
@@ -110,6 +115,7 @@ impl Scope {
                     suggestion_options: self.idents_in_scope().map(|v| v.as_ref().into()).collect(),
                     // Check if the user just forgot to remove an underscore from an ignored identifier
                     underscored_suggestion_region: self.lookup_ignored_local(ident),
+                    type_compatible_suggestions: MutSet::default(),
                 };
 
                 Err(error)
@@ -377,6 +383,44 @@ impl Scope {
         self.shadows.get(&ability_member)
     }
 
+    /// The local symbol with this name that is in scope, and where it was introduced.
+    pub fn lookup_local(&self, ident: &str) -> Option<(Symbol, Region)> {
+        match self.scope_contains_ident(ident) {
+            ContainsIdent::InScope(lookup, region) if lookup.symbol.module_id() == self.home => {
+                Some((lookup.symbol, region))
+            }
+            _ => None,
+        }
+    }
+
+    /// Give a name a new symbol for the rest of the current def scope, for a def like
+    /// `x = x + 1` that shadows the local `old` with a value computed from it. The `new` symbol
+    /// must come from [Self::introduce], when it failed because of `old`.
+    pub fn rebind(&mut self, old: Symbol, new: Symbol) {
+        debug_assert_eq!(old.module_id(), self.home);
+        debug_assert_eq!(new.module_id(), self.home);
+
+        self.locals.in_scope.set(old.ident_id().index(), false);
+        self.locals.in_scope.set(new.ident_id().index(), true);
+        self.rebound.push(old.ident_id());
+    }
+
+    /// A name like `name` that nothing in the module has, to suggest for a new value of `name`.
+    pub fn unused_name_like(&self, name: &str) -> String {
+        let (base, suffix) = match name.strip_suffix('!') {
+            Some(base) => (base, "!"),
+            None => (name, ""),
+        };
+
+        (2..)
+            .map(|n| format!("{base}{n}{suffix}"))
+            .find(|candidate| {
+                self.locals.ident_ids.get_id(candidate).is_none()
+                    && self.has_imported_symbol(candidate).is_none()
+            })
+            .unwrap()
+    }
+
     /// Create a new symbol, but don't add it to the scope (yet)
     ///
     /// Used for record guards like { x: Just _ } where the `x` is not added to the scope,
@@ -450,6 +494,7 @@ impl Scope {
         let locals_snapshot = self.locals.in_scope.len();
         let imported_symbols_snapshot = self.imported_symbols.len();
         let imported_modules_snapshot = self.modules.len();
+        let rebound_snapshot = self.rebound.len();
 
         let result = f(self);
 
@@ -458,6 +503,10 @@ impl Scope {
         self.imported_symbols.truncate(imported_symbols_snapshot);
         self.modules.truncate(imported_modules_snapshot);
 
+        for ident_id in self.rebound.drain(rebound_snapshot..) {
+            self.locals.in_scope.set(ident_id.index(), true);
+        }
+
         // anything added in the inner scope is no longer in scope now
         for i in locals_snapshot..self.locals.in_scope.len() {
             self.locals.in_scope.set(i, false);
@@ -747,6 +796,14 @@ impl ScopeModules {
             })
     }
 
+    /// The alias or original name a module in scope can be referred to by.
+    pub fn name_of(&self, module_id: ModuleId) -> Option<&ModuleName> {
+        self.ids
+            .iter()
+            .position(|id| *id == module_id)
+            .map(|index| &self.names[index])
+    }
+
     pub fn available_names(&self) -> impl Iterator<Item = &ModuleName> {
         self.names.iter()
     }
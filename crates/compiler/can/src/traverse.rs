@@ -1,8 +1,16 @@
 //! Traversals over the can ast.
 
-use roc_module::{ident::Lowercase, symbol::Symbol};
+use roc_collections::MutSet;
+use roc_module::{
+    ident::Lowercase,
+    symbol::{ModuleId, Symbol},
+};
+use roc_problem::fix::Edit;
 use roc_region::all::{Loc, Position, Region};
-use roc_types::{subs::Variable, types::MemberImpl};
+use roc_types::{
+    subs::{Content, FlatType, Subs, Variable},
+    types::MemberImpl,
+};
 
 use crate::{
     abilities::AbilitiesStore,
@@ -416,6 +424,7 @@ pub fn walk_expr<V: Visitor>(visitor: &mut V, expr: &Expr, var: Variable) {
         } => {
             visitor.visit_expr(&return_value.value, return_value.region, *return_var);
         }
+        Expr::TypedHole { .. } => { /* terminal */ }
         Expr::RuntimeError(..) => { /* terminal */ }
     }
 }
@@ -537,6 +546,12 @@ pub fn walk_record_fields<'a, V: Visitor>(
     )
 }
 
+/// A traversal of the can ast. Each `visit_*` method decides whether to descend into a node, and
+/// descends with the matching `walk_*` function, which visits the node's children. Override a
+/// `visit_*` method to change how a node is traversed, or the `enter_*` and `exit_*` hooks to act
+/// on nodes before and after their children are visited. The hooks are called by the default
+/// `visit_*` methods, so they aren't called for the nodes of a `visit_*` method that is overridden
+/// without calling them.
 pub trait Visitor: Sized {
     /// Most default implementations will call [Visitor::should_visit] to decide whether they
     /// should descend into a node. Return `false` to skip visiting.
@@ -544,19 +559,39 @@ pub trait Visitor: Sized {
         true
     }
 
+    fn enter_decl(&mut self, _decl: &DeclarationInfo<'_>) {}
+
+    fn exit_decl(&mut self, _decl: &DeclarationInfo<'_>) {}
+
+    fn enter_def(&mut self, _def: &Def) {}
+
+    fn exit_def(&mut self, _def: &Def) {}
+
+    fn enter_expr(&mut self, _expr: &Expr, _region: Region, _var: Variable) {}
+
+    fn exit_expr(&mut self, _expr: &Expr, _region: Region, _var: Variable) {}
+
+    fn enter_pattern(&mut self, _pattern: &Pattern, _region: Region, _opt_var: Option<Variable>) {}
+
+    fn exit_pattern(&mut self, _pattern: &Pattern, _region: Region, _opt_var: Option<Variable>) {}
+
     fn visit_decls(&mut self, decls: &Declarations) {
         walk_decls(self, decls);
     }
 
     fn visit_decl(&mut self, decl: DeclarationInfo<'_>) {
         if self.should_visit(decl.region()) {
-            walk_decl(self, decl);
+            self.enter_decl(&decl);
+            walk_decl(self, decl.clone());
+            self.exit_decl(&decl);
         }
     }
 
     fn visit_def(&mut self, def: &Def) {
         if self.should_visit(def.region()) {
+            self.enter_def(def);
             walk_def(self, def);
+            self.exit_def(def);
         }
     }
 
@@ -566,13 +601,17 @@ pub trait Visitor: Sized {
 
     fn visit_expr(&mut self, expr: &Expr, region: Region, var: Variable) {
         if self.should_visit(region) {
+            self.enter_expr(expr, region, var);
             walk_expr(self, expr, var);
+            self.exit_expr(expr, region, var);
         }
     }
 
-    fn visit_pattern(&mut self, pattern: &Pattern, region: Region, _opt_var: Option<Variable>) {
+    fn visit_pattern(&mut self, pattern: &Pattern, region: Region, opt_var: Option<Variable>) {
         if self.should_visit(region) {
+            self.enter_pattern(pattern, region, opt_var);
             walk_pattern(self, pattern);
+            self.exit_pattern(pattern, region, opt_var);
         }
     }
 
@@ -593,6 +632,53 @@ pub trait Visitor: Sized {
     }
 }
 
+/// The solved types of the variables that a [Visitor] is given. Some nodes, like the continuation
+/// of an `expect`, don't have a variable of their own, and are visited with [Variable::NULL].
+#[derive(Clone, Copy)]
+pub struct TypeLookup<'a> {
+    subs: &'a Subs,
+}
+
+impl<'a> TypeLookup<'a> {
+    pub fn new(subs: &'a Subs) -> Self {
+        Self { subs }
+    }
+
+    pub fn subs(&self) -> &'a Subs {
+        self.subs
+    }
+
+    /// The type of a variable, unless it's [Variable::NULL].
+    pub fn content(&self, var: Variable) -> Option<&'a Content> {
+        if var == Variable::NULL {
+            None
+        } else {
+            Some(self.subs.get_content_without_compacting(var))
+        }
+    }
+
+    /// The structure of a variable's type, seeing through aliases and opaques. `None` when the
+    /// type isn't a structure, like a type variable or a ranged number.
+    pub fn structure(&self, var: Variable) -> Option<&'a FlatType> {
+        let mut content = self.content(var)?;
+
+        loop {
+            match content {
+                Content::Alias(_, _, real_var, _) => {
+                    content = self.subs.get_content_without_compacting(*real_var);
+                }
+                Content::Structure(flat_type) => return Some(flat_type),
+                _ => return None,
+            }
+        }
+    }
+
+    /// Whether a variable's type is a function.
+    pub fn is_function(&self, var: Variable) -> bool {
+        matches!(self.structure(var), Some(FlatType::Func(..)))
+    }
+}
+
 pub fn walk_pattern<V: Visitor>(visitor: &mut V, pattern: &Pattern) {
     use Pattern::*;
 
@@ -978,3 +1064,488 @@ pub fn find_declaration(symbol: Symbol, decls: &'_ Declarations) -> Option<Found
         }
     }
 }
+
+/// A closure, and the symbols it captures.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FoundClosure {
+    pub region: Region,
+    /// Sorted, which is the order the closure data stores them in.
+    pub captured_symbols: Vec<Symbol>,
+}
+
+/// Finds every closure that captures something, by the symbol it will be lifted to.
+pub fn find_capturing_closures(decls: &Declarations) -> Vec<(Symbol, FoundClosure)> {
+    let mut visitor = Collector {
+        closures: Vec::new(),
+    };
+    visitor.visit_decls(decls);
+    return visitor.closures;
+
+    struct Collector {
+        closures: Vec<(Symbol, FoundClosure)>,
+    }
+
+    impl Visitor for Collector {
+        fn visit_expr(&mut self, expr: &Expr, region: Region, var: Variable) {
+            if let Expr::Closure(closure_data) = expr {
+                if !closure_data.captured_symbols.is_empty() {
+                    let mut captured_symbols: Vec<_> = closure_data
+                        .captured_symbols
+                        .iter()
+                        .map(|(symbol, _)| *symbol)
+                        .collect();
+
+                    captured_symbols.sort();
+
+                    self.closures.push((
+                        closure_data.name,
+                        FoundClosure {
+                            region,
+                            captured_symbols,
+                        },
+                    ));
+                }
+            }
+
+            walk_expr(self, expr, var);
+        }
+    }
+}
+
+/// The edits that rename every use of `symbol` in an expression to `new_name`.
+pub fn rename_uses(loc_expr: &Loc<Expr>, symbol: Symbol, new_name: &str) -> Vec<Edit> {
+    let mut visitor = Renamer {
+        symbol,
+        new_name,
+        edits: Vec::new(),
+    };
+    visitor.visit_expr(&loc_expr.value, loc_expr.region, Variable::NULL);
+    // The fields of records are visited in no particular order.
+    visitor.edits.sort_by_key(|edit| edit.region.start());
+    return visitor.edits;
+
+    struct Renamer<'a> {
+        symbol: Symbol,
+        new_name: &'a str,
+        edits: Vec<Edit>,
+    }
+
+    impl Visitor for Renamer<'_> {
+        fn visit_expr(&mut self, expr: &Expr, region: Region, var: Variable) {
+            match expr {
+                Expr::Var(symbol, _) if *symbol == self.symbol => self.edits.push(Edit {
+                    region,
+                    replacement: self.new_name.to_string(),
+                }),
+                Expr::Record { fields, .. } => {
+                    for (label, field) in fields.iter() {
+                        let loc_expr = &field.loc_expr;
+
+                        match loc_expr.value {
+                            // The shorthand `{ x }` has to keep the field's name.
+                            Expr::Var(symbol, _)
+                                if symbol == self.symbol
+                                    && field.region.start() == loc_expr.region.start() =>
+                            {
+                                self.edits.push(Edit {
+                                    region: loc_expr.region,
+                                    replacement: format!("{label}: {}", self.new_name),
+                                })
+                            }
+                            _ => self.visit_expr(&loc_expr.value, loc_expr.region, field.var),
+                        }
+                    }
+                }
+                _ => walk_expr(self, expr, var),
+            }
+        }
+    }
+}
+
+/// A top-level def of a module, and how it is connected to the module's other top-level defs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DefDependencies {
+    pub symbol: Loc<Symbol>,
+    /// The other top-level defs this def refers to, sorted.
+    pub uses: Vec<Symbol>,
+    /// The other top-level defs that refer to this def, sorted.
+    pub used_by: Vec<Symbol>,
+    /// The defs that have to stay together with this one, including itself, sorted. These are
+    /// the members of its mutually recursive cycle, or the symbols bound by the same destructure.
+    pub group: Vec<Symbol>,
+}
+
+/// The dependencies between the top-level defs of a module, in declaration order.
+/// Expectations are not defs, so their lookups are not counted as uses.
+pub fn def_dependencies(decls: &Declarations) -> Vec<DefDependencies> {
+    use crate::expr::DeclarationTag::*;
+
+    // (declaration index, symbols bound by it, group id)
+    let mut defs: Vec<(usize, Vec<Loc<Symbol>>, usize)> = Vec::new();
+    let mut cycle: Option<(usize, usize)> = None;
+
+    for (index, tag) in decls.declarations.iter().enumerate() {
+        let symbols = match tag {
+            Value | Function(_) | Recursive(_) | TailRecursive(_) => vec![decls.symbols[index]],
+            Destructure(destructure_index) => {
+                let destructure = &decls.destructs[destructure_index.index()];
+                symbols_introduced_from_pattern(&destructure.loc_pattern).collect()
+            }
+            Expectation => continue,
+            MutualRecursion { length, .. } => {
+                // The members of the cycle are the next `length` declarations.
+                cycle = Some((*length as usize, defs.len()));
+                continue;
+            }
+        };
+
+        let group = match cycle {
+            Some((remaining, group)) if remaining > 0 => {
+                cycle = Some((remaining - 1, group));
+                group
+            }
+            _ => defs.len(),
+        };
+
+        defs.push((index, symbols, group));
+    }
+
+    let top_level: MutSet<Symbol> = defs
+        .iter()
+        .flat_map(|(_, symbols, _)| symbols.iter().map(|loc_symbol| loc_symbol.value))
+        .collect();
+
+    let uses_by_def: Vec<Vec<Symbol>> = defs
+        .iter()
+        .map(|(index, symbols, _)| {
+            let mut uses = symbols_looked_up(&decls.expressions[*index]);
+            uses.retain(|symbol| {
+                top_level.contains(symbol) && !symbols.iter().any(|bound| bound.value == *symbol)
+            });
+            uses
+        })
+        .collect();
+
+    let mut result = Vec::new();
+
+    for (def_index, (_, symbols, group)) in defs.iter().enumerate() {
+        let mut group_symbols: Vec<Symbol> = defs
+            .iter()
+            .filter(|(_, _, other_group)| other_group == group)
+            .flat_map(|(_, symbols, _)| symbols.iter().map(|loc_symbol| loc_symbol.value))
+            .collect();
+        group_symbols.sort();
+
+        for loc_symbol in symbols {
+            let mut used_by: Vec<Symbol> = defs
+                .iter()
+                .zip(uses_by_def.iter())
+                .filter(|(_, uses)| uses.contains(&loc_symbol.value))
+                .flat_map(|((_, symbols, _), _)| symbols.iter().map(|loc_symbol| loc_symbol.value))
+                .collect();
+            used_by.sort();
+
+            result.push(DefDependencies {
+                symbol: *loc_symbol,
+                uses: uses_by_def[def_index].clone(),
+                used_by,
+                group: group_symbols.clone(),
+            });
+        }
+    }
+
+    result
+}
+
+/// The symbols an expression looks up, sorted and without duplicates.
+pub fn symbols_looked_up(loc_expr: &Loc<Expr>) -> Vec<Symbol> {
+    let mut visitor = Collector {
+        symbols: Vec::new(),
+    };
+    visitor.visit_expr(&loc_expr.value, loc_expr.region, Variable::NULL);
+    visitor.symbols.sort();
+    visitor.symbols.dedup();
+    return visitor.symbols;
+
+    struct Collector {
+        symbols: Vec<Symbol>,
+    }
+
+    impl Visitor for Collector {
+        fn visit_expr(&mut self, expr: &Expr, _region: Region, var: Variable) {
+            match expr {
+                Expr::Var(symbol, _) | Expr::AbilityMember(symbol, _, _) => {
+                    self.symbols.push(*symbol);
+                }
+                _ => walk_expr(self, expr, var),
+            }
+        }
+    }
+}
+
+/// A call of a function by name, in the body of a top-level def.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CallSite {
+    /// The top-level def the call is in
+    pub caller: Symbol,
+    /// The function being called: a top-level def of this module, or a def of another module
+    pub callee: Symbol,
+    /// The region of the function's name at the call
+    pub region: Region,
+}
+
+/// The calls made by the top-level defs of a module, in the order they appear. Calls of local
+/// functions, and calls in expectations, are not included.
+pub fn call_sites(decls: &Declarations) -> Vec<CallSite> {
+    use crate::expr::DeclarationTag::*;
+
+    let mut top_level = MutSet::default();
+    let mut defs: Vec<(usize, Vec<Symbol>)> = Vec::new();
+
+    for (index, tag) in decls.declarations.iter().enumerate() {
+        let symbols = match tag {
+            Value | Function(_) | Recursive(_) | TailRecursive(_) => {
+                vec![decls.symbols[index].value]
+            }
+            Destructure(destructure_index) => {
+                let destructure = &decls.destructs[destructure_index.index()];
+                symbols_introduced_from_pattern(&destructure.loc_pattern)
+                    .map(|loc_symbol| loc_symbol.value)
+                    .collect()
+            }
+            Expectation | MutualRecursion { .. } => continue,
+        };
+
+        top_level.extend(symbols.iter().copied());
+        defs.push((index, symbols));
+    }
+
+    let mut result = Vec::new();
+
+    for (index, symbols) in defs {
+        let Some(home) = symbols.first().map(|symbol| symbol.module_id()) else {
+            continue;
+        };
+        let loc_expr = &decls.expressions[index];
+        let mut visitor = Collector {
+            home,
+            top_level: &top_level,
+            calls: Vec::new(),
+        };
+        visitor.visit_expr(&loc_expr.value, loc_expr.region, decls.variables[index]);
+
+        for caller in symbols {
+            result.extend(visitor.calls.iter().map(|&(callee, region)| CallSite {
+                caller,
+                callee,
+                region,
+            }));
+        }
+    }
+
+    return result;
+
+    struct Collector<'a> {
+        home: ModuleId,
+        top_level: &'a MutSet<Symbol>,
+        calls: Vec<(Symbol, Region)>,
+    }
+
+    impl Visitor for Collector<'_> {
+        fn visit_expr(&mut self, expr: &Expr, _region: Region, var: Variable) {
+            if let Expr::Call(f, _, _) = expr {
+                let loc_fn = &f.1;
+
+                if let Expr::Var(symbol, _) | Expr::AbilityMember(symbol, _, _) = loc_fn.value {
+                    if symbol.module_id() != self.home || self.top_level.contains(&symbol) {
+                        self.calls.push((symbol, loc_fn.region));
+                    }
+                }
+            }
+
+            walk_expr(self, expr, var);
+        }
+    }
+}
+
+/// An expression that could be moved into a new top-level function, found by [extractable_expr].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtractableExpr {
+    /// The type of the expression
+    pub var: Variable,
+    /// The region of the top-level declaration the expression is in
+    pub decl_region: Region,
+    /// The locals the expression uses that are defined outside of it, in the order of their
+    /// first use, with their type at that use
+    pub free_variables: Vec<(Symbol, Variable)>,
+    /// The expression makes effectful calls, not counting the ones in lambdas in it
+    pub effectful: bool,
+    /// The expression returns early from the function it's in, with `return` or `?`
+    pub returns_early: bool,
+}
+
+/// The expression at exactly `region`, and what it would take to move it into a new top-level
+/// function.
+pub fn extractable_expr(
+    region: Region,
+    decls: &Declarations,
+    subs: &Subs,
+) -> Option<ExtractableExpr> {
+    let mut top_level: MutSet<Symbol> = decls.symbols.iter().map(|s| s.value).collect();
+    for destructure in decls.destructs.iter() {
+        top_level.extend(
+            symbols_introduced_from_pattern(&destructure.loc_pattern)
+                .map(|loc_symbol| loc_symbol.value),
+        );
+    }
+
+    let mut visitor = Finder {
+        region,
+        types: TypeLookup::new(subs),
+        top_level: &top_level,
+        found: None,
+        decl_region: Region::zero(),
+        bound: MutSet::default(),
+        inside: None,
+    };
+    visitor.visit_decls(decls);
+
+    let (mut found, inside) = visitor.found?;
+    let Inside {
+        mut lookups,
+        bound: bound_inside,
+        ..
+    } = inside;
+
+    // The fields of records are visited in no particular order.
+    lookups.sort_by_key(|(_, _, region)| region.start());
+
+    for (symbol, var, _) in lookups {
+        let is_free = visitor.bound.contains(&symbol) && !bound_inside.contains(&symbol);
+
+        if is_free && !found.free_variables.iter().any(|(free, _)| *free == symbol) {
+            found.free_variables.push((symbol, var));
+        }
+    }
+
+    return Some(found);
+
+    struct Inside {
+        lookups: Vec<(Symbol, Variable, Region)>,
+        bound: MutSet<Symbol>,
+        lambda_depth: usize,
+    }
+
+    struct Finder<'a> {
+        region: Region,
+        types: TypeLookup<'a>,
+        top_level: &'a MutSet<Symbol>,
+        found: Option<(ExtractableExpr, Inside)>,
+        decl_region: Region,
+        /// The locals bound anywhere in the declaration the expression is in
+        bound: MutSet<Symbol>,
+        inside: Option<(ExtractableExpr, Inside)>,
+    }
+
+    impl Finder<'_> {
+        fn bind(&mut self, symbol: Symbol) {
+            if self.top_level.contains(&symbol) {
+                return;
+            }
+
+            self.bound.insert(symbol);
+
+            if let Some((_, inside)) = &mut self.inside {
+                inside.bound.insert(symbol);
+            }
+        }
+    }
+
+    impl Visitor for Finder<'_> {
+        fn visit_decl(&mut self, decl: DeclarationInfo<'_>) {
+            if self.found.is_none() && decl.region().contains(&self.region) {
+                self.decl_region = decl.region();
+                self.bound.clear();
+                walk_decl(self, decl);
+            }
+        }
+
+        fn visit_expr(&mut self, expr: &Expr, region: Region, var: Variable) {
+            if self.found.is_none() && self.inside.is_none() && region == self.region {
+                self.inside = Some((
+                    ExtractableExpr {
+                        var,
+                        decl_region: self.decl_region,
+                        free_variables: Vec::new(),
+                        effectful: false,
+                        returns_early: false,
+                    },
+                    Inside {
+                        lookups: Vec::new(),
+                        bound: MutSet::default(),
+                        lambda_depth: 0,
+                    },
+                ));
+
+                walk_expr(self, expr, var);
+
+                self.found = self.inside.take();
+                return;
+            }
+
+            let Some((found, inside)) = &mut self.inside else {
+                return walk_expr(self, expr, var);
+            };
+
+            match expr {
+                Expr::Var(symbol, var) => inside.lookups.push((*symbol, *var, region)),
+                Expr::Call(f, _, _) if inside.lambda_depth == 0 => {
+                    if let Some(Content::Effectful) = self.types.content(f.4) {
+                        found.effectful = true;
+                    }
+                }
+                Expr::Return { .. } | Expr::Try { .. } if inside.lambda_depth == 0 => {
+                    found.returns_early = true;
+                }
+                Expr::Closure(_) => {
+                    inside.lambda_depth += 1;
+                    walk_expr(self, expr, var);
+                    if let Some((_, inside)) = &mut self.inside {
+                        inside.lambda_depth -= 1;
+                    }
+                    return;
+                }
+                _ => {}
+            }
+
+            walk_expr(self, expr, var);
+        }
+
+        fn visit_pattern(
+            &mut self,
+            pattern: &Pattern,
+            _region: Region,
+            _opt_var: Option<Variable>,
+        ) {
+            match pattern {
+                Pattern::Identifier(symbol) | Pattern::As(_, symbol) => self.bind(*symbol),
+                Pattern::List { patterns, .. } => {
+                    if let Some((_, Some(rest))) = patterns.opt_rest {
+                        self.bind(rest.value);
+                    }
+                }
+                _ => {}
+            }
+
+            walk_pattern(self, pattern);
+        }
+
+        fn visit_record_destruct(&mut self, destruct: &RecordDestruct, _region: Region) {
+            if !matches!(destruct.typ, DestructType::Guard(..)) {
+                self.bind(destruct.symbol);
+            }
+
+            walk_record_destruct(self, destruct);
+        }
+    }
+}
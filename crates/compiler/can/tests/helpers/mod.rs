@@ -32,6 +32,21 @@ pub struct CanExprOut {
 
 #[allow(dead_code)]
 pub fn can_expr_with(arena: &Bump, home: ModuleId, expr_str: &str) -> CanExprOut {
+    can_expr_help(arena, home, expr_str, None)
+}
+
+/// Like [can_expr], but with a lower limit on how deeply expressions can be nested.
+#[allow(dead_code)]
+pub fn can_expr_with_max_nesting_depth(expr_str: &str, max_nesting_depth: usize) -> CanExprOut {
+    can_expr_help(&Bump::new(), test_home(), expr_str, Some(max_nesting_depth))
+}
+
+fn can_expr_help(
+    arena: &Bump,
+    home: ModuleId,
+    expr_str: &str,
+    max_nesting_depth: Option<usize>,
+) -> CanExprOut {
     let loc_expr = roc_parse::test_helpers::parse_loc_with(arena, expr_str).unwrap_or_else(|e| {
         panic!(
             "can_expr_with() got a parse error when attempting to canonicalize:\n\n{expr_str:?} {e:?}"
@@ -68,6 +83,12 @@ pub fn can_expr_with(arena: &Bump, home: ModuleId, expr_str: &str) -> CanExprOut
     // rules multiple times unnecessarily.
     let mut solo_env = SoloEnv::new(arena, expr_str, Path::new("Test.roc"));
     let mut solo_scope = SoloScope::new();
+
+    if let Some(max_nesting_depth) = max_nesting_depth {
+        env.max_nesting_depth = max_nesting_depth;
+        solo_env.max_nesting_depth = max_nesting_depth;
+    }
+
     let loc_expr = roc_can_solo::desugar::desugar_expr(&mut solo_env, &mut solo_scope, &loc_expr);
 
     scope.add_alias(
@@ -12,16 +12,18 @@ mod helpers;
 
 #[cfg(test)]
 mod test_can {
-    use crate::helpers::{can_expr_with, test_home, CanExprOut};
+    use crate::helpers::{can_expr_with, can_expr_with_max_nesting_depth, test_home, CanExprOut};
     use bumpalo::Bump;
     use core::panic;
+    use roc_can::def::Def;
     use roc_can::expr::Expr::{self, *};
     use roc_can::expr::{ClosureData, IntValue, Recursive, WhenBranch};
     use roc_can::pattern::Pattern;
+    use roc_can::traverse::{TypeLookup, Visitor};
     use roc_module::called_via::CalledVia;
     use roc_problem::can::{CycleEntry, FloatErrorKind, IntErrorKind, Problem, RuntimeError};
     use roc_region::all::{Loc, Position, Region};
-    use roc_types::subs::Variable;
+    use roc_types::subs::{Subs, Variable};
     use std::{f64, i64};
 
     fn assert_can_runtime_error(input: &str, expected: RuntimeError) {
@@ -543,7 +545,7 @@ mod test_can {
         assert_eq!(problems.len(), 1);
         assert!(problems
             .iter()
-            .all(|problem| matches!(problem, Problem::UnusedDef(_, _))));
+            .all(|problem| matches!(problem, Problem::UnusedDef(..))));
     }
 
     #[test]
@@ -565,7 +567,7 @@ mod test_can {
         assert_eq!(problems.len(), 2);
         assert!(problems
             .iter()
-            .all(|problem| matches!(problem, Problem::UnusedDef(_, _))));
+            .all(|problem| matches!(problem, Problem::UnusedDef(..))));
     }
     // LOCALS
 
@@ -1446,7 +1448,7 @@ mod test_can {
         assert_eq!(problems, Vec::new());
         assert!(problems
             .iter()
-            .all(|problem| matches!(problem, Problem::UnusedDef(_, _))));
+            .all(|problem| matches!(problem, Problem::UnusedDef(..))));
 
         let actual = loc_expr.value;
 
@@ -1459,6 +1461,49 @@ mod test_can {
         assert_eq!(p_detected, Recursive::TailRecursive);
     }
 
+    // NESTING
+
+    fn nesting_too_deep_problems(problems: &[Problem]) -> Vec<&RuntimeError> {
+        problems
+            .iter()
+            .filter_map(|problem| match problem {
+                Problem::RuntimeError(error @ RuntimeError::NestingTooDeep { .. }) => Some(error),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn nesting_too_deep() {
+        let CanExprOut { problems, .. } = can_expr_with_max_nesting_depth("[[[[[[1]]]]]]", 3);
+
+        // Only the outermost expression past the limit is reported
+        assert_eq!(
+            nesting_too_deep_problems(&problems),
+            vec![&RuntimeError::NestingTooDeep {
+                region: Region::new(Position::new(3), Position::new(10)),
+                max_depth: 3,
+            }]
+        );
+    }
+
+    #[test]
+    fn nesting_within_limit() {
+        let CanExprOut { problems, .. } = can_expr_with_max_nesting_depth("[[[1]]]", 4);
+
+        assert_eq!(problems, Vec::new());
+    }
+
+    #[test]
+    fn nesting_too_deep_in_operators() {
+        // Desugaring stops at the limit too, and canonicalization has to stop before it gets to
+        // the operators that weren't desugared
+        let CanExprOut { problems, .. } =
+            can_expr_with_max_nesting_depth("1 + (2 + (3 + (4 + (5 + 6))))", 3);
+
+        assert_eq!(nesting_too_deep_problems(&problems).len(), 1);
+    }
+
     // TODO restore this test! It should report two unused defs (h and p), but only reports 1.
     // #[test]
     // fn reproduce_incorrect_unused_defs() {
@@ -2274,6 +2319,107 @@ mod test_can {
         );
     }
 
+    // TRAVERSAL
+
+    #[derive(Default)]
+    struct HookRecorder {
+        events: Vec<String>,
+    }
+
+    impl HookRecorder {
+        fn describe_expr(expr: &Expr) -> &'static str {
+            match expr {
+                LetNonRec(..) | LetRec(..) => "let",
+                Closure(..) => "closure",
+                Call(..) => "call",
+                Var(..) => "var",
+                Num(..) | Int(..) => "number",
+                _ => "expr",
+            }
+        }
+    }
+
+    impl Visitor for HookRecorder {
+        fn enter_def(&mut self, _def: &Def) {
+            self.events.push("enter def".to_string());
+        }
+
+        fn exit_def(&mut self, _def: &Def) {
+            self.events.push("exit def".to_string());
+        }
+
+        fn enter_expr(&mut self, expr: &Expr, _region: Region, _var: Variable) {
+            self.events
+                .push(format!("enter {}", Self::describe_expr(expr)));
+        }
+
+        fn exit_expr(&mut self, expr: &Expr, _region: Region, _var: Variable) {
+            self.events
+                .push(format!("exit {}", Self::describe_expr(expr)));
+        }
+
+        fn enter_pattern(&mut self, _pattern: &Pattern, _region: Region, _var: Option<Variable>) {
+            self.events.push("enter pattern".to_string());
+        }
+
+        fn exit_pattern(&mut self, _pattern: &Pattern, _region: Region, _var: Option<Variable>) {
+            self.events.push("exit pattern".to_string());
+        }
+    }
+
+    #[test]
+    fn visitor_enter_and_exit_hooks() {
+        let arena = Bump::new();
+        let out = can_expr_with(
+            &arena,
+            test_home(),
+            indoc!(
+                r"
+                f = \y -> y
+
+                f 1
+                "
+            ),
+        );
+
+        let mut recorder = HookRecorder::default();
+        recorder.visit_expr(&out.loc_expr.value, out.loc_expr.region, Variable::NULL);
+
+        assert_eq!(
+            recorder.events,
+            vec![
+                "enter let",
+                "enter def",
+                "enter pattern",
+                "exit pattern",
+                "enter closure",
+                "enter pattern",
+                "exit pattern",
+                "enter var",
+                "exit var",
+                "exit closure",
+                "exit def",
+                "enter call",
+                "enter var",
+                "exit var",
+                "enter number",
+                "exit number",
+                "exit call",
+                "exit let",
+            ]
+        );
+    }
+
+    #[test]
+    fn type_lookup() {
+        let subs = Subs::new();
+        let types = TypeLookup::new(&subs);
+
+        assert!(types.content(Variable::NULL).is_none());
+        assert!(types.structure(Variable::STR).is_some());
+        assert!(!types.is_function(Variable::STR));
+    }
+
     //     #[test]
     //     fn string_with_too_large_unicode_escape() {
     //         // Should be too big - max size should be 10FFFF.
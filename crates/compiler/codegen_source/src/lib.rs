@@ -0,0 +1,707 @@
+//! Builds Roc modules programmatically and renders them as formatted source code.
+//!
+//! This is meant for build scripts that generate Roc bindings, e.g. a record type for each table
+//! of an SQL schema. The generated source is parsed and formatted with the same formatter as
+//! `roc format`, so it's always well-formatted, and an error is returned instead when it wouldn't
+//! parse, e.g. because a field is named like a keyword.
+//!
+//! ```
+//! use roc_codegen_source::{Def, Expr, Module, TypeAnn};
+//!
+//! let module = Module::new(["User", "guest"])
+//!     .def(Def::alias(
+//!         "User",
+//!         TypeAnn::record([("name", TypeAnn::named("Str")), ("age", TypeAnn::named("U8"))]),
+//!     ))
+//!     .def(Def::value(
+//!         "guest",
+//!         Some(TypeAnn::named("User")),
+//!         Expr::record([("name", Expr::str("Guest")), ("age", Expr::int(0))]),
+//!     ));
+//!
+//! assert_eq!(
+//!     module.render().unwrap(),
+//!     "module [User, guest]\n\nUser : { name : Str, age : U8 }\n\nguest : User\nguest = { name: \"Guest\", age: 0 }\n"
+//! );
+//! ```
+use bumpalo::Bump;
+use roc_fmt::def::fmt_defs;
+use roc_fmt::header::fmt_header;
+use roc_fmt::{Buf, DbgMigration, MigrationFlags};
+use roc_parse::ast::{Defs, Header, SpacesBefore};
+use roc_parse::header::{parse_header, parse_module_defs};
+use roc_parse::keyword::is_allowed_identifier;
+use roc_parse::state::State;
+use std::fmt::Write;
+
+/// A `module` with its exposed names, imports, and top-level defs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Module {
+    pub exposes: Vec<String>,
+    /// The modules to import, like `Dict` or `pf.Stdout`
+    pub imports: Vec<String>,
+    pub defs: Vec<Def>,
+}
+
+/// A top-level def, with an optional doc comment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Def {
+    pub doc: Option<String>,
+    pub kind: DefKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DefKind {
+    /// `Name a : ann`
+    Alias {
+        name: String,
+        vars: Vec<String>,
+        ann: TypeAnn,
+    },
+    /// `Name a := ann`
+    Opaque {
+        name: String,
+        vars: Vec<String>,
+        ann: TypeAnn,
+    },
+    /// `name : ann` (when there's one) and `name = body`
+    Value {
+        name: String,
+        ann: Option<TypeAnn>,
+        body: Expr,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeAnn {
+    /// A type applied to arguments, like `Str` or `List U8`
+    Apply(String, Vec<TypeAnn>),
+    /// A type variable, like `a`
+    Var(String),
+    /// `{ name : Str, age : U8 }`
+    Record(Vec<(String, TypeAnn)>),
+    /// `[Red, Custom U8 U8 U8]`
+    TagUnion(Vec<(String, Vec<TypeAnn>)>),
+    /// `Str, U64 -> Str`
+    Function(Vec<TypeAnn>, Box<TypeAnn>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    /// A number literal, like `42`, `-1.5`, or `0xff`
+    Num(String),
+    Str(String),
+    /// A lookup, like `name` or `List.len`
+    Var(String),
+    /// A tag applied to arguments, like `Ok 1` or `None`
+    Tag(String, Vec<Expr>),
+    Record(Vec<(String, Expr)>),
+    List(Vec<Expr>),
+    /// `record.field`
+    Access(Box<Expr>, String),
+    /// A function applied to arguments, like `List.len list`
+    Call(Box<Expr>, Vec<Expr>),
+    /// `|a, b| body`
+    Closure(Vec<String>, Box<Expr>),
+}
+
+/// Why a module couldn't be rendered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodegenError {
+    /// A name that can't be used where it is, like a lowercase tag or a field named `if`
+    InvalidName { name: String, expected: NameKind },
+    /// The source of the module doesn't parse
+    Syntax { src: String, problem: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameKind {
+    /// A def, field, argument, or type variable, like `user_name`
+    Lowercase,
+    /// A type or tag, like `User`
+    Uppercase,
+    /// A name that may be qualified with modules, like `List.len` or `Dict.Dict`
+    Qualified,
+    /// A module, like `Dict` or `pf.Stdout`
+    Module,
+}
+
+impl Module {
+    pub fn new<S: Into<String>>(exposes: impl IntoIterator<Item = S>) -> Self {
+        Module {
+            exposes: exposes.into_iter().map(Into::into).collect(),
+            imports: Vec::new(),
+            defs: Vec::new(),
+        }
+    }
+
+    pub fn import(mut self, module: impl Into<String>) -> Self {
+        self.imports.push(module.into());
+        self
+    }
+
+    pub fn def(mut self, def: Def) -> Self {
+        self.defs.push(def);
+        self
+    }
+
+    /// The formatted source code of the module.
+    pub fn render(&self) -> Result<String, CodegenError> {
+        format_module(&self.to_source()?)
+    }
+
+    /// The source code of the module, before it's formatted.
+    fn to_source(&self) -> Result<String, CodegenError> {
+        let mut src = String::new();
+
+        for name in self.exposes.iter() {
+            check_name(name, NameKind::Qualified)?;
+        }
+
+        writeln!(src, "module [{}]\n", self.exposes.join(", ")).unwrap();
+
+        for import in self.imports.iter() {
+            check_name(import, NameKind::Module)?;
+            writeln!(src, "import {import}").unwrap();
+        }
+
+        for def in self.defs.iter() {
+            src.push('\n');
+
+            if let Some(doc) = &def.doc {
+                for line in doc.lines() {
+                    writeln!(src, "## {line}").unwrap();
+                }
+            }
+
+            match &def.kind {
+                DefKind::Alias { name, vars, ann } | DefKind::Opaque { name, vars, ann } => {
+                    check_name(name, NameKind::Uppercase)?;
+                    src.push_str(name);
+
+                    for var in vars {
+                        check_name(var, NameKind::Lowercase)?;
+                        write!(src, " {var}").unwrap();
+                    }
+
+                    let operator = match def.kind {
+                        DefKind::Opaque { .. } => ":=",
+                        _ => ":",
+                    };
+
+                    writeln!(src, " {operator} {}", render_type(ann, Position::Top)?).unwrap();
+                }
+                DefKind::Value { name, ann, body } => {
+                    check_name(name, NameKind::Lowercase)?;
+
+                    if let Some(ann) = ann {
+                        writeln!(src, "{name} : {}", render_type(ann, Position::Top)?).unwrap();
+                    }
+
+                    writeln!(src, "{name} = {}", render_expr(body, Position::Top)?).unwrap();
+                }
+            }
+        }
+
+        Ok(src)
+    }
+}
+
+impl Def {
+    pub fn alias(name: impl Into<String>, ann: TypeAnn) -> Self {
+        Self::alias_with_vars(name, Vec::<String>::new(), ann)
+    }
+
+    pub fn alias_with_vars<S: Into<String>>(
+        name: impl Into<String>,
+        vars: impl IntoIterator<Item = S>,
+        ann: TypeAnn,
+    ) -> Self {
+        Self::from(DefKind::Alias {
+            name: name.into(),
+            vars: vars.into_iter().map(Into::into).collect(),
+            ann,
+        })
+    }
+
+    pub fn opaque(name: impl Into<String>, ann: TypeAnn) -> Self {
+        Self::from(DefKind::Opaque {
+            name: name.into(),
+            vars: Vec::new(),
+            ann,
+        })
+    }
+
+    pub fn value(name: impl Into<String>, ann: Option<TypeAnn>, body: Expr) -> Self {
+        Self::from(DefKind::Value {
+            name: name.into(),
+            ann,
+            body,
+        })
+    }
+
+    /// Add a doc comment, which can have several lines.
+    pub fn with_doc(mut self, doc: impl Into<String>) -> Self {
+        self.doc = Some(doc.into());
+        self
+    }
+}
+
+impl From<DefKind> for Def {
+    fn from(kind: DefKind) -> Self {
+        Def { doc: None, kind }
+    }
+}
+
+impl TypeAnn {
+    /// A type without arguments, like `Str`
+    pub fn named(name: impl Into<String>) -> Self {
+        TypeAnn::Apply(name.into(), Vec::new())
+    }
+
+    pub fn apply(name: impl Into<String>, args: impl IntoIterator<Item = TypeAnn>) -> Self {
+        TypeAnn::Apply(name.into(), args.into_iter().collect())
+    }
+
+    pub fn record<S: Into<String>>(fields: impl IntoIterator<Item = (S, TypeAnn)>) -> Self {
+        TypeAnn::Record(
+            fields
+                .into_iter()
+                .map(|(name, ann)| (name.into(), ann))
+                .collect(),
+        )
+    }
+
+    pub fn tag_union<S: Into<String>>(tags: impl IntoIterator<Item = (S, Vec<TypeAnn>)>) -> Self {
+        TypeAnn::TagUnion(
+            tags.into_iter()
+                .map(|(name, args)| (name.into(), args))
+                .collect(),
+        )
+    }
+
+    pub fn function(args: impl IntoIterator<Item = TypeAnn>, ret: TypeAnn) -> Self {
+        TypeAnn::Function(args.into_iter().collect(), Box::new(ret))
+    }
+}
+
+impl Expr {
+    pub fn int(value: impl Into<i128>) -> Self {
+        Expr::Num(value.into().to_string())
+    }
+
+    pub fn str(value: impl Into<String>) -> Self {
+        Expr::Str(value.into())
+    }
+
+    pub fn var(name: impl Into<String>) -> Self {
+        Expr::Var(name.into())
+    }
+
+    pub fn tag(name: impl Into<String>, args: impl IntoIterator<Item = Expr>) -> Self {
+        Expr::Tag(name.into(), args.into_iter().collect())
+    }
+
+    pub fn record<S: Into<String>>(fields: impl IntoIterator<Item = (S, Expr)>) -> Self {
+        Expr::Record(
+            fields
+                .into_iter()
+                .map(|(name, expr)| (name.into(), expr))
+                .collect(),
+        )
+    }
+
+    pub fn list(elems: impl IntoIterator<Item = Expr>) -> Self {
+        Expr::List(elems.into_iter().collect())
+    }
+
+    pub fn access(self, field: impl Into<String>) -> Self {
+        Expr::Access(Box::new(self), field.into())
+    }
+
+    pub fn call(function: Expr, args: impl IntoIterator<Item = Expr>) -> Self {
+        Expr::Call(Box::new(function), args.into_iter().collect())
+    }
+
+    pub fn closure<S: Into<String>>(args: impl IntoIterator<Item = S>, body: Expr) -> Self {
+        Expr::Closure(args.into_iter().map(Into::into).collect(), Box::new(body))
+    }
+}
+
+/// Format the source code of a module the way `roc format` does, and check that the result
+/// parses.
+pub fn format_module(src: &str) -> Result<String, CodegenError> {
+    let syntax_error = |problem: String| CodegenError::Syntax {
+        src: src.to_string(),
+        problem,
+    };
+
+    let arena = Bump::new();
+    let (header, defs) = parse_module(&arena, src).map_err(syntax_error)?;
+
+    let mut buf = Buf::new_in(
+        &arena,
+        MigrationFlags {
+            snakify: false,
+            parens_and_commas: false,
+            dbg: DbgMigration::Keep,
+            transforms: &[],
+        },
+    );
+
+    fmt_header(&mut buf, &header);
+    fmt_defs(&mut buf, &defs, 0);
+    buf.fmt_end_of_file();
+
+    let formatted = buf.as_str().to_string();
+
+    parse_module(&arena, &formatted).map_err(syntax_error)?;
+
+    Ok(formatted)
+}
+
+fn parse_module<'a>(
+    arena: &'a Bump,
+    src: &str,
+) -> Result<(SpacesBefore<'a, Header<'a>>, Defs<'a>), String> {
+    let src = arena.alloc_str(src);
+    let (header, state) = parse_header(arena, State::new(src.as_bytes()))
+        .map_err(|error| format!("{:?}", error.problem))?;
+
+    let (item, defs) = header.item.upgrade_header_imports(arena);
+    let defs = parse_module_defs(arena, state, defs).map_err(|error| format!("{error:?}"))?;
+
+    Ok((
+        SpacesBefore {
+            before: header.before,
+            item,
+        },
+        defs,
+    ))
+}
+
+/// Where a type or expression is, which decides whether it needs parens.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Position {
+    /// On its own, like the body of a def or a field of a record
+    Top,
+    /// An argument of a function (type) or tag
+    Arg,
+}
+
+fn render_type(ann: &TypeAnn, position: Position) -> Result<String, CodegenError> {
+    let rendered = match ann {
+        TypeAnn::Apply(name, args) => {
+            check_name(name, NameKind::Qualified)?;
+
+            if args.is_empty() {
+                return Ok(name.clone());
+            }
+
+            let args = render_all(args, |arg| render_type(arg, Position::Arg))?;
+
+            format!("{name} {}", args.join(" "))
+        }
+        TypeAnn::Var(name) => {
+            check_name(name, NameKind::Lowercase)?;
+
+            return Ok(name.clone());
+        }
+        TypeAnn::Record(fields) => {
+            let fields = render_all(fields, |(name, ann)| {
+                check_name(name, NameKind::Lowercase)?;
+
+                Ok(format!("{name} : {}", render_type(ann, Position::Top)?))
+            })?;
+
+            return Ok(format!("{{ {} }}", fields.join(", ")));
+        }
+        TypeAnn::TagUnion(tags) => {
+            let tags = render_all(tags, |(name, args)| {
+                check_name(name, NameKind::Uppercase)?;
+
+                let mut tag = name.clone();
+
+                for arg in args {
+                    write!(tag, " {}", render_type(arg, Position::Arg)?).unwrap();
+                }
+
+                Ok(tag)
+            })?;
+
+            return Ok(format!("[{}]", tags.join(", ")));
+        }
+        TypeAnn::Function(args, ret) => {
+            let args = render_all(args, |arg| render_type(arg, Position::Top))?;
+
+            format!(
+                "{} -> {}",
+                args.join(", "),
+                render_type(ret, Position::Top)?
+            )
+        }
+    };
+
+    Ok(parenthesize(rendered, position))
+}
+
+fn render_expr(expr: &Expr, position: Position) -> Result<String, CodegenError> {
+    let rendered = match expr {
+        Expr::Num(number) => {
+            if number.starts_with('-') {
+                number.clone()
+            } else {
+                return Ok(number.clone());
+            }
+        }
+        Expr::Str(string) => return Ok(escape_str(string)),
+        Expr::Var(name) => {
+            check_name(name, NameKind::Qualified)?;
+
+            return Ok(name.clone());
+        }
+        Expr::Tag(name, args) => {
+            check_name(name, NameKind::Uppercase)?;
+
+            if args.is_empty() {
+                return Ok(name.clone());
+            }
+
+            let args = render_all(args, |arg| render_expr(arg, Position::Arg))?;
+
+            format!("{name} {}", args.join(" "))
+        }
+        Expr::Record(fields) => {
+            if fields.is_empty() {
+                return Ok("{}".to_string());
+            }
+
+            let fields = render_all(fields, |(name, value)| {
+                check_name(name, NameKind::Lowercase)?;
+
+                Ok(format!("{name}: {}", render_expr(value, Position::Top)?))
+            })?;
+
+            return Ok(format!("{{ {} }}", fields.join(", ")));
+        }
+        Expr::List(elems) => {
+            let elems = render_all(elems, |elem| render_expr(elem, Position::Top))?;
+
+            return Ok(format!("[{}]", elems.join(", ")));
+        }
+        Expr::Access(record, field) => {
+            check_name(field, NameKind::Lowercase)?;
+
+            // Only a name or another access can be accessed without parens
+            let record = match record.as_ref() {
+                Expr::Var(_) | Expr::Access(..) => render_expr(record, Position::Top)?,
+                other => format!("({})", render_expr(other, Position::Top)?),
+            };
+
+            return Ok(format!("{record}.{field}"));
+        }
+        Expr::Call(function, args) => {
+            let mut call = render_expr(function, Position::Arg)?;
+
+            for arg in args {
+                write!(call, " {}", render_expr(arg, Position::Arg)?).unwrap();
+            }
+
+            call
+        }
+        Expr::Closure(args, body) => {
+            for arg in args {
+                check_name(arg, NameKind::Lowercase)?;
+            }
+
+            format!(
+                "|{}| {}",
+                args.join(", "),
+                render_expr(body, Position::Top)?
+            )
+        }
+    };
+
+    Ok(parenthesize(rendered, position))
+}
+
+fn render_all<T>(
+    items: &[T],
+    render: impl Fn(&T) -> Result<String, CodegenError>,
+) -> Result<Vec<String>, CodegenError> {
+    items.iter().map(render).collect()
+}
+
+fn parenthesize(rendered: String, position: Position) -> String {
+    match position {
+        Position::Top => rendered,
+        Position::Arg => format!("({rendered})"),
+    }
+}
+
+/// A string literal with the characters that would end it or start an interpolation escaped.
+fn escape_str(string: &str) -> String {
+    let mut escaped = String::with_capacity(string.len() + 2);
+
+    escaped.push('"');
+
+    for c in string.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '$' => escaped.push_str("\\$"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => write!(escaped, "\\u({:x})", c as u32).unwrap(),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped.push('"');
+
+    escaped
+}
+
+fn check_name(name: &str, expected: NameKind) -> Result<(), CodegenError> {
+    let is_lowercase = |segment: &str| {
+        let ident = segment.strip_suffix('!').unwrap_or(segment);
+
+        ident.starts_with(|c: char| c.is_ascii_lowercase())
+            && ident.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            && is_allowed_identifier(segment)
+    };
+    let is_uppercase = |segment: &str| {
+        segment.starts_with(|c: char| c.is_ascii_uppercase())
+            && segment.chars().all(|c| c.is_ascii_alphanumeric())
+    };
+
+    let segments: Vec<&str> = name.split('.').collect();
+    let (last, qualifiers) = segments.split_last().unwrap();
+
+    let is_valid = match expected {
+        NameKind::Lowercase => segments.len() == 1 && is_lowercase(name),
+        NameKind::Uppercase => segments.len() == 1 && is_uppercase(name),
+        NameKind::Qualified => {
+            qualifiers.iter().all(|segment| is_uppercase(segment))
+                && (is_lowercase(last) || is_uppercase(last))
+        }
+        NameKind::Module => match qualifiers {
+            // A module of a package, like `pf.Stdout`
+            [package, modules @ ..] if is_lowercase(package) => {
+                modules.iter().all(|segment| is_uppercase(segment)) && is_uppercase(last)
+            }
+            _ => qualifiers.iter().all(|segment| is_uppercase(segment)) && is_uppercase(last),
+        },
+    };
+
+    if is_valid {
+        Ok(())
+    } else {
+        Err(CodegenError::InvalidName {
+            name: name.to_string(),
+            expected,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bindings() {
+        let module = Module::new(["Color", "User", "users", "describe"])
+            .import("Dict")
+            .def(Def::alias(
+                "Color",
+                TypeAnn::tag_union([("Red", vec![]), ("Custom", vec![TypeAnn::named("U8"); 3])]),
+            ))
+            .def(
+                Def::alias(
+                    "User",
+                    TypeAnn::record([
+                        ("name", TypeAnn::named("Str")),
+                        ("tags", TypeAnn::apply("List", [TypeAnn::named("Str")])),
+                        ("favorite", TypeAnn::named("Color")),
+                    ]),
+                )
+                .with_doc("A row of the `users` table\nGenerated, don't edit!"),
+            )
+            .def(Def::value(
+                "users",
+                Some(TypeAnn::apply("List", [TypeAnn::named("User")])),
+                Expr::list([Expr::record([
+                    ("name", Expr::str("Ada \"$admin\"\n")),
+                    ("tags", Expr::list([])),
+                    (
+                        "favorite",
+                        Expr::tag("Custom", [Expr::int(1), Expr::int(-2), Expr::int(3)]),
+                    ),
+                ])]),
+            ))
+            .def(Def::value(
+                "describe",
+                Some(TypeAnn::function(
+                    [TypeAnn::named("User")],
+                    TypeAnn::named("U64"),
+                )),
+                Expr::closure(
+                    ["user"],
+                    Expr::call(Expr::var("List.len"), [Expr::var("user").access("tags")]),
+                ),
+            ));
+
+        assert_eq!(
+            module.render().unwrap(),
+            concat!(
+                "module [Color, User, users, describe]\n",
+                "\n",
+                "import Dict\n",
+                "\n",
+                "Color : [Red, Custom U8 U8 U8]\n",
+                "\n",
+                "## A row of the `users` table\n",
+                "## Generated, don't edit!\n",
+                "User : { name : Str, tags : List Str, favorite : Color }\n",
+                "\n",
+                "users : List User\n",
+                "users = [{ name: \"Ada \\\"\\$admin\\\"\\n\", tags: [], favorite: Custom 1 (-2) 3 }]\n",
+                "\n",
+                "describe : User -> U64\n",
+                "describe = |user| List.len user.tags\n",
+            )
+        );
+    }
+
+    #[test]
+    fn invalid_names() {
+        let module = Module::new(["if"]).def(Def::value("if", None, Expr::int(1)));
+
+        assert_eq!(
+            module.render(),
+            Err(CodegenError::InvalidName {
+                name: "if".to_string(),
+                expected: NameKind::Qualified,
+            })
+        );
+
+        let module = Module::new(["color"]).def(Def::value("color", None, Expr::tag("red", [])));
+
+        assert_eq!(
+            module.render(),
+            Err(CodegenError::InvalidName {
+                name: "red".to_string(),
+                expected: NameKind::Uppercase,
+            })
+        );
+    }
+
+    #[test]
+    fn unparseable_source() {
+        assert!(matches!(
+            format_module("module [x]\n\nx = (\n"),
+            Err(CodegenError::Syntax { .. })
+        ));
+    }
+}
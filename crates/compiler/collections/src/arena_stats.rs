@@ -0,0 +1,98 @@
+use bumpalo::Bump;
+
+/// How much memory a bump arena used, e.g. so that CI can detect memory regressions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ArenaStats {
+    /// The total number of bytes allocated, summed over all resets.
+    pub allocated_bytes: usize,
+    /// The most bytes the arena had in use at once.
+    pub high_water_mark: usize,
+    /// How often the arena was reset.
+    pub resets: u32,
+}
+
+impl ArenaStats {
+    /// The number of bytes currently allocated in the arena.
+    ///
+    /// Unlike [Bump::allocated_bytes], this doesn't count the unused capacity of the arena's chunks.
+    pub fn used_bytes(arena: &Bump) -> usize {
+        // Safety: we only look at the length of each chunk, never at the memory in it.
+        unsafe { arena.iter_allocated_chunks_raw() }
+            .map(|(_, len)| len)
+            .sum()
+    }
+
+    /// The stats of an arena that was never reset.
+    pub fn of(arena: &Bump) -> Self {
+        let used = Self::used_bytes(arena);
+
+        Self {
+            allocated_bytes: used,
+            high_water_mark: used,
+            resets: 0,
+        }
+    }
+
+    /// Record that `allocated` bytes were allocated, after which the arena had `in_use` bytes in use.
+    pub fn record(&mut self, allocated: usize, in_use: usize) {
+        self.allocated_bytes += allocated;
+        self.high_water_mark = self.high_water_mark.max(in_use);
+    }
+
+    /// Reset the arena, keeping track of what it had allocated so far.
+    pub fn reset(&mut self, arena: &mut Bump) {
+        let used = Self::used_bytes(arena);
+
+        self.record(used, used);
+        self.resets += 1;
+
+        arena.reset();
+    }
+
+    /// Combine the stats of two arenas, or of two uses of the same arena.
+    pub fn merge(&mut self, other: &Self) {
+        self.allocated_bytes += other.allocated_bytes;
+        self.high_water_mark = self.high_water_mark.max(other.high_water_mark);
+        self.resets += other.resets;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ArenaStats;
+    use bumpalo::Bump;
+
+    #[test]
+    fn used_bytes() {
+        let arena = Bump::new();
+
+        assert_eq!(ArenaStats::used_bytes(&arena), 0);
+
+        arena.alloc([0u8; 100]);
+        arena.alloc([0u8; 28]);
+
+        assert_eq!(ArenaStats::used_bytes(&arena), 128);
+    }
+
+    #[test]
+    fn reset() {
+        let mut arena = Bump::new();
+        let mut stats = ArenaStats::default();
+
+        arena.alloc([0u8; 100]);
+        stats.reset(&mut arena);
+
+        arena.alloc([0u8; 40]);
+        stats.reset(&mut arena);
+
+        assert_eq!(
+            stats,
+            ArenaStats {
+                allocated_bytes: 140,
+                high_water_mark: 100,
+                resets: 2,
+            }
+        );
+        assert_eq!(ArenaStats::used_bytes(&arena), 0);
+    }
+}
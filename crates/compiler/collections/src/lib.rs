@@ -4,6 +4,7 @@
 #![allow(clippy::large_enum_variant)]
 
 pub mod all;
+mod arena_stats;
 mod push;
 mod reference_matrix;
 mod small_string_interner;
@@ -13,6 +14,7 @@ mod vec_map;
 mod vec_set;
 
 pub use all::{default_hasher, BumpMap, ImEntry, ImMap, ImSet, MutMap, MutSet, SendMap};
+pub use arena_stats::ArenaStats;
 pub use push::Push;
 pub use reference_matrix::{ReferenceMatrix, Sccs, TopologicalSort};
 pub use small_string_interner::SmallStringInterner;
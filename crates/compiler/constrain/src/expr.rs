@@ -689,6 +689,15 @@ pub fn constrain_expr(
 
             constraints.exists([*ret_var], and)
         }
+        TypedHole { name, var } => {
+            let hole_is_expected =
+                constraints.equal_types_var(*var, expected, Category::TypedHole, region);
+            let report_hole = constraints.typed_hole(*var, name.clone(), region);
+
+            let and = constraints.and_constraint([hole_is_expected, report_hole]);
+
+            constraints.exists([*var], and)
+        }
         Var(symbol, variable)
         | ParamsVar {
             symbol,
@@ -1334,9 +1343,10 @@ pub fn constrain_expr(
             );
 
             let eq = constraints.equal_types_var(field_var, expected, category, region);
+            let accessed_field = constraints.accessed_field(field_var, region);
             constraints.exists_many(
                 [*record_var, field_var, ext_var],
-                [constraint, eq, record_con],
+                [constraint, eq, record_con, accessed_field],
             )
         }
         RecordAccessor(StructAccessorData {
@@ -1916,14 +1926,24 @@ pub fn constrain_expr(
             arg_cons.push(eq);
             constraints.exists_many(vars, arg_cons)
         }
-        RuntimeError(_) => {
+        RuntimeError(runtime_error) => {
             // Runtime Errors are always going to crash, so they don't introduce any new
             // constraints.
             // Instead, trivially equate the expected type to itself. This will never yield
             // unification errors but it will catch errors in type translation, including ability
             // obligations.
             let trivial_type = *constraints[expected].get_type_ref();
-            constraints.equal_types(trivial_type, expected, Category::Unknown, region)
+            let trivial =
+                constraints.equal_types(trivial_type, expected, Category::Unknown, region);
+
+            match runtime_error {
+                roc_problem::can::RuntimeError::LookupNotInScope { loc_name, .. } => {
+                    let not_in_scope = constraints.not_in_scope(expected, loc_name.region);
+
+                    constraints.and_constraint([trivial, not_in_scope])
+                }
+                _ => trivial,
+            }
         }
     }
 }
@@ -4455,6 +4475,10 @@ fn is_generalizable_expr(mut expr: &Expr) -> bool {
                 // Allow generalization of signatures with no implementation
                 return true;
             }
+            TypedHole { .. } => {
+                // A hole stands in for an implementation that hasn't been written yet
+                return true;
+            }
             OpaqueRef { argument, .. } => expr = &argument.1.value,
             ImportParams(_, _, Some((_, params))) => expr = params,
             ImportParams(_, _, None) => return false,
@@ -139,6 +139,13 @@ pub enum Literal {
 #[derive(Clone, Debug, PartialEq)]
 pub enum Error {
     Incomplete(Region, Context, Vec<Pattern>),
+    /// The `when` would be exhaustive if its `if` guards were ignored, but these patterns are
+    /// only handled by guarded branches, so a value matching them crashes when all the guards
+    /// are false.
+    OnlyGuarded {
+        overall_region: Region,
+        patterns: Vec<Pattern>,
+    },
     Redundant {
         overall_region: Region,
         branch_region: Region,
@@ -156,6 +163,7 @@ impl Error {
         use Severity::*;
         match self {
             Error::Incomplete(..) => RuntimeError,
+            Error::OnlyGuarded { .. } => RuntimeError,
             Error::Redundant { .. } => Warning,
             Error::Unmatchable { .. } => Warning,
         }
@@ -164,6 +172,7 @@ impl Error {
     pub fn region(&self) -> Region {
         match self {
             Error::Incomplete(region, _, _) => *region,
+            Error::OnlyGuarded { overall_region, .. } => *overall_region,
             Error::Redundant { branch_region, .. } => *branch_region,
             Error::Unmatchable { branch_region, .. } => *branch_region,
         }
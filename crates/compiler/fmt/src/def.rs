@@ -7,11 +7,12 @@ use crate::expr::{
     is_str_multiline, merge_spaces_conservative, sub_expr_requests_parens,
 };
 use crate::node::Nodify;
-use crate::pattern::{pattern_lift_spaces, pattern_lift_spaces_before};
+use crate::pattern::{pattern_lift_spaces, pattern_lift_spaces_before, snakify_camel_ident};
 use crate::spaces::{
-    fmt_comments_only, fmt_default_newline, fmt_default_spaces, fmt_spaces, NewlineAt, INDENT,
+    fmt_comments_only, fmt_default_newline, fmt_default_spaces, fmt_directive, fmt_spaces,
+    FmtDirective, NewlineAt, INDENT,
 };
-use crate::Buf;
+use crate::{Buf, DbgMigration};
 use bumpalo::Bump;
 use roc_error_macros::internal_error;
 use roc_parse::ast::Spaceable;
@@ -40,16 +41,53 @@ impl<'a> Formattable for Defs<'a> {
         indent: u16,
     ) {
         let mut prev_spaces = true;
+        let mut formatting_off = false;
         let arena = buf.text.bump();
 
         for (index, def) in self.defs().enumerate() {
             let spaces_before = &self.spaces[self.space_before[index].indices()];
             let spaces_after = &self.spaces[self.space_after[index].indices()];
 
-            let def = def_lift_spaces(buf.text.bump(), def);
+            let lifted = def_lift_spaces(buf.text.bump(), def);
 
-            let spaces_before = merge_spaces(arena, spaces_before, def.before);
-            let spaces_after = merge_spaces(arena, def.after, spaces_after);
+            let merged_before = merge_spaces(arena, spaces_before, lifted.before);
+
+            let directive = fmt_directive(merged_before);
+            match directive {
+                Some(FmtDirective::Off) => formatting_off = true,
+                Some(FmtDirective::On) => formatting_off = false,
+                Some(FmtDirective::Skip) | None => {}
+            }
+
+            let verbatim = if formatting_off || directive == Some(FmtDirective::Skip) {
+                buf.source_at(self.regions[index])
+            } else {
+                None
+            };
+
+            // Copied defs keep their own spaces, so only the spaces around them are formatted.
+            let (spaces_before, spaces_after) = match verbatim {
+                Some(_) => (spaces_before, spaces_after),
+                None => (
+                    merged_before,
+                    merge_spaces(arena, lifted.after, spaces_after),
+                ),
+            };
+
+            if verbatim.is_none()
+                && buf.flags().dbg == DbgMigration::Remove
+                && matches!(lifted.item, Err(ValueDef::Dbg { .. }))
+            {
+                buf.record_dbg_removed();
+
+                // Keep the comments around the `dbg`, but not the lines it took up
+                if spaces_before.iter().any(|s| s.is_comment()) {
+                    buf.ensure_ends_with_newline();
+                    fmt_comments_only(buf, spaces_before.iter(), NewlineAt::Bottom, indent);
+                }
+
+                continue;
+            }
 
             if prev_spaces {
                 fmt_spaces(buf, spaces_before.iter(), indent);
@@ -57,9 +95,13 @@ impl<'a> Formattable for Defs<'a> {
                 fmt_default_newline(buf, spaces_before, indent);
             }
 
-            match def.item {
-                Ok(type_def) => type_def.format(buf, indent),
-                Err(value_def) => value_def.format(buf, indent),
+            match (verbatim, lifted.item) {
+                (Some(src), _) => {
+                    buf.indent(indent);
+                    buf.push_verbatim(src);
+                }
+                (None, Ok(type_def)) => type_def.format(buf, indent),
+                (None, Err(value_def)) => value_def.format(buf, indent),
             }
 
             fmt_spaces(buf, spaces_after.iter(), indent);
@@ -832,7 +874,12 @@ impl<'a> Formattable for ValueDef<'a> {
                 );
             }
             Body(loc_pattern, loc_expr) => {
-                fmt_body(buf, &loc_pattern.value, &loc_expr.value, indent);
+                let lifted_dbg = buf.flags().dbg == DbgMigration::Lift
+                    && fmt_body_lifting_dbg(buf, loc_pattern, loc_expr, indent);
+
+                if !lifted_dbg {
+                    fmt_body(buf, &loc_pattern.value, &loc_expr.value, indent);
+                }
             }
             Dbg { condition, .. } => fmt_dbg_in_def(buf, condition, self.is_multiline(), indent),
             Expect { condition, .. } => fmt_expect(buf, condition, self.is_multiline(), indent),
@@ -919,6 +966,64 @@ fn fmt_general_def<L: Formattable>(
     fmt_comments_only(buf, rhs.after.iter(), NewlineAt::Bottom, indent);
 }
 
+/// Formats `x = dbg expr` as `x = expr` followed by a `dbg x` statement. Returns false, without
+/// formatting anything, if the def isn't an identifier bound to an inline `dbg`.
+fn fmt_body_lifting_dbg<'a>(
+    buf: &mut Buf,
+    loc_pattern: &'a Loc<Pattern<'a>>,
+    loc_expr: &'a Loc<Expr<'a>>,
+    indent: u16,
+) -> bool {
+    let ident = match loc_pattern.value.extract_spaces().item {
+        Pattern::Identifier { ident } => ident,
+        _ => return false,
+    };
+
+    let arg = match inline_dbg_arg(&loc_expr.value) {
+        Some(arg) => arg,
+        None => return false,
+    };
+
+    buf.record_dbg_lifted();
+    fmt_body(buf, &loc_pattern.value, &arg.value, indent);
+
+    buf.ensure_ends_with_newline();
+    buf.indent(indent);
+    buf.push_str("dbg");
+
+    if buf.flags().parens_and_commas {
+        buf.push('(');
+        snakify_camel_ident(buf, ident);
+        buf.push(')');
+    } else {
+        buf.spaces(1);
+        snakify_camel_ident(buf, ident);
+    }
+
+    true
+}
+
+/// The argument of `dbg expr` or `dbg(expr)` used as an expression.
+fn inline_dbg_arg<'a>(expr: &Expr<'a>) -> Option<&'a Loc<Expr<'a>>> {
+    match expr {
+        Expr::Apply(
+            Loc {
+                value: Expr::Dbg, ..
+            },
+            [arg],
+            _,
+        ) => Some(*arg),
+        Expr::PncApply(
+            Loc {
+                value: Expr::Dbg, ..
+            },
+            args,
+        ) if args.len() == 1 => Some(args.items[0]),
+        Expr::SpaceBefore(inner, _) | Expr::SpaceAfter(inner, _) => inline_dbg_arg(inner),
+        _ => None,
+    }
+}
+
 fn fmt_dbg_in_def<'a>(buf: &mut Buf, condition: &'a Loc<Expr<'a>>, _: bool, indent: u16) {
     buf.ensure_ends_with_newline();
     buf.indent(indent);
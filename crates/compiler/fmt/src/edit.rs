@@ -0,0 +1,535 @@
+//! Structural edits to a module, for code actions.
+//!
+//! An [AstEdit] says what to change in terms of the syntax tree, like "rename this field of the
+//! record at this region", instead of in terms of text. [apply_ast_edits] makes the changes to the
+//! tree and formats the top-level defs they touched, so the text edits it returns always leave the
+//! module syntactically valid, and the rest of the module exactly as it was.
+use crate::def::fmt_defs;
+use crate::transform::Rewriter;
+use crate::{Buf, DbgMigration, MigrationFlags};
+use bumpalo::{collections::Vec, Bump};
+use roc_parse::ast::{AssignedField, Collection, Defs, Expr, ExtractSpaces, ValueDef};
+use roc_parse::header::parse_module_defs;
+use roc_parse::ident::lowercase_ident;
+use roc_parse::parser::Parser;
+use roc_parse::state::State;
+use roc_region::all::{Loc, Region};
+
+/// A change to the syntax tree of a module. Each edit finds the expression it changes by its
+/// region, which has to be exactly the region of that expression in the parsed module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AstEdit<'e> {
+    /// Rename a field of a record literal, record update, or record builder. A field that's
+    /// only a label, like `{ x }`, keeps its value: it becomes `{ y: x }`.
+    RenameField {
+        record: Region,
+        from: &'e str,
+        to: &'e str,
+    },
+    /// Add a field to the end of a record literal, with `default` (Roc source) as its value
+    AddField {
+        record: Region,
+        name: &'e str,
+        default: &'e str,
+    },
+    /// Replace an expression with a call of `function` on it, like `x` to `f(x)`
+    WrapInCall { expr: Region, function: &'e str },
+}
+
+impl AstEdit<'_> {
+    /// The region of the expression this edit changes
+    pub fn region(&self) -> Region {
+        match self {
+            AstEdit::RenameField { record, .. } | AstEdit::AddField { record, .. } => *record,
+            AstEdit::WrapInCall { expr, .. } => *expr,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AstEditError {
+    /// No expression that the edit could change has exactly this region
+    NotFound { region: Region },
+    /// The record doesn't have the field to rename
+    MissingField { region: Region, field: String },
+    /// The record already has a field with the name being added or renamed to
+    DuplicateField { region: Region, field: String },
+    /// Not a name a record field can have
+    InvalidFieldName { name: String },
+    /// The default value of a new field doesn't parse as an expression
+    InvalidExpr { src: String },
+    /// The function to wrap an expression in isn't a (possibly qualified) variable
+    InvalidFunction { src: String },
+}
+
+/// A replacement of part of the source of a module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceEdit {
+    pub region: Region,
+    pub replacement: String,
+}
+
+/// Apply `edits` to the module's defs, and return the text edits that make the same changes to
+/// its source: one for each top-level def that changed, replacing it with the formatted new def.
+/// Either all the edits are made, or none are.
+pub fn apply_ast_edits<'a>(
+    arena: &'a Bump,
+    defs: &Defs<'a>,
+    edits: &[AstEdit<'_>],
+) -> Result<std::vec::Vec<SourceEdit>, AstEditError> {
+    let mut resolved = std::vec::Vec::with_capacity(edits.len());
+
+    for edit in edits {
+        resolved.push(resolve(arena, edit)?);
+    }
+
+    let mut applied = vec![false; resolved.len()];
+    let mut source_edits = std::vec::Vec::new();
+
+    for (index, tag) in defs.tags.iter().enumerate() {
+        let Err(value_index) = tag.split() else {
+            continue;
+        };
+        let def_region = defs.regions[index];
+
+        if !resolved
+            .iter()
+            .any(|edit| def_region.contains(&edit.region()))
+        {
+            continue;
+        }
+
+        let mut error = None;
+        let mut rewrite = |expr: &Expr<'a>, region: Region| {
+            let mut current = None;
+
+            for (edit, applied) in resolved.iter().zip(applied.iter_mut()) {
+                if *applied || edit.region() != region || error.is_some() {
+                    continue;
+                }
+
+                match edit.apply(arena, current.as_ref().unwrap_or(expr), region) {
+                    Ok(Some(replacement)) => {
+                        *applied = true;
+                        current = Some(replacement);
+                    }
+                    Ok(None) => {}
+                    Err(problem) => error = Some(problem),
+                }
+            }
+
+            current
+        };
+
+        let value_def =
+            Rewriter::new(arena, &mut rewrite).value_def(&defs.value_defs[value_index.index()]);
+
+        if let Some(problem) = error {
+            return Err(problem);
+        }
+
+        source_edits.push(SourceEdit {
+            region: def_region,
+            replacement: format_value_def(arena, value_def, def_region),
+        });
+    }
+
+    if let Some(index) = applied.iter().position(|applied| !applied) {
+        return Err(AstEditError::NotFound {
+            region: resolved[index].region(),
+        });
+    }
+
+    Ok(source_edits)
+}
+
+/// The source with `edits` made to it. The edits must not overlap.
+pub fn apply_source_edits(src: &str, edits: &[SourceEdit]) -> String {
+    let mut edits: std::vec::Vec<_> = edits.iter().collect();
+    edits.sort_by_key(|edit| edit.region.start());
+
+    let mut output = String::with_capacity(src.len());
+    let mut copied_up_to = 0;
+
+    for edit in edits {
+        output.push_str(&src[copied_up_to..edit.region.start().byte_offset()]);
+        output.push_str(&edit.replacement);
+        copied_up_to = edit.region.end().byte_offset();
+    }
+
+    output.push_str(&src[copied_up_to..]);
+
+    output
+}
+
+/// An edit with its names checked and its source parsed.
+enum ResolvedEdit<'a> {
+    RenameField {
+        record: Region,
+        from: &'a str,
+        to: &'a str,
+    },
+    AddField {
+        record: Region,
+        name: &'a str,
+        default: Expr<'a>,
+    },
+    WrapInCall {
+        expr: Region,
+        function: Expr<'a>,
+    },
+}
+
+fn resolve<'a>(arena: &'a Bump, edit: &AstEdit<'_>) -> Result<ResolvedEdit<'a>, AstEditError> {
+    match *edit {
+        AstEdit::RenameField { record, from, to } => Ok(ResolvedEdit::RenameField {
+            record,
+            from: arena.alloc_str(from),
+            to: field_name(arena, to)?,
+        }),
+        AstEdit::AddField {
+            record,
+            name,
+            default,
+        } => Ok(ResolvedEdit::AddField {
+            record,
+            name: field_name(arena, name)?,
+            default: parse_expr(arena, default)?,
+        }),
+        AstEdit::WrapInCall { expr, function } => match parse_expr(arena, function)? {
+            var @ Expr::Var { .. } => Ok(ResolvedEdit::WrapInCall {
+                expr,
+                function: var,
+            }),
+            _ => Err(AstEditError::InvalidFunction {
+                src: function.to_string(),
+            }),
+        },
+    }
+}
+
+fn field_name<'a>(arena: &'a Bump, name: &str) -> Result<&'a str, AstEditError> {
+    let name = arena.alloc_str(name);
+
+    match lowercase_ident().parse(arena, State::new(name.as_bytes()), 0) {
+        Ok((_, ident, _)) if ident.len() == name.len() => Ok(name),
+        _ => Err(AstEditError::InvalidFieldName {
+            name: name.to_string(),
+        }),
+    }
+}
+
+fn parse_expr<'a>(arena: &'a Bump, src: &str) -> Result<Expr<'a>, AstEditError> {
+    let state = State::new(arena.alloc_str(src).as_bytes());
+
+    match roc_parse::expr::test_parse_expr(0, arena, state) {
+        Ok(loc_expr) => Ok(loc_expr.value.without_spaces()),
+        Err(_) => Err(AstEditError::InvalidExpr {
+            src: src.to_string(),
+        }),
+    }
+}
+
+type Fields<'a> = Collection<'a, Loc<AssignedField<'a, Expr<'a>>>>;
+
+impl<'a> ResolvedEdit<'a> {
+    fn region(&self) -> Region {
+        match self {
+            ResolvedEdit::RenameField { record, .. } | ResolvedEdit::AddField { record, .. } => {
+                *record
+            }
+            ResolvedEdit::WrapInCall { expr, .. } => *expr,
+        }
+    }
+
+    /// What to replace `expr` with, or `None` if this edit doesn't apply to it. Expressions
+    /// without a [Loc] of their own share the region of the one around them, so this is called
+    /// with the innermost expression at the edit's region first.
+    fn apply(
+        &self,
+        arena: &'a Bump,
+        expr: &Expr<'a>,
+        region: Region,
+    ) -> Result<Option<Expr<'a>>, AstEditError> {
+        match *self {
+            ResolvedEdit::RenameField { from, to, .. } => {
+                let rename = |fields| rename_field(arena, fields, from, to, region);
+
+                match *expr {
+                    Expr::Record(fields) => Ok(Some(Expr::Record(rename(fields)?))),
+                    Expr::RecordUpdate { update, fields } => Ok(Some(Expr::RecordUpdate {
+                        update,
+                        fields: rename(fields)?,
+                    })),
+                    Expr::RecordBuilder { mapper, fields } => Ok(Some(Expr::RecordBuilder {
+                        mapper,
+                        fields: rename(fields)?,
+                    })),
+                    _ => Ok(None),
+                }
+            }
+            ResolvedEdit::AddField { name, default, .. } => match *expr {
+                Expr::Record(fields) => {
+                    if fields.iter().any(|field| label(&field.value) == Some(name)) {
+                        return Err(AstEditError::DuplicateField {
+                            region,
+                            field: name.to_string(),
+                        });
+                    }
+
+                    let mut items = Vec::from_iter_in(fields.iter().copied(), arena);
+                    items.push(Loc::at_zero(AssignedField::RequiredValue(
+                        Loc::at_zero(name),
+                        &[],
+                        arena.alloc(Loc::at_zero(default)),
+                    )));
+
+                    Ok(Some(Expr::Record(
+                        fields.replace_items(items.into_bump_slice()),
+                    )))
+                }
+                _ => Ok(None),
+            },
+            ResolvedEdit::WrapInCall { function, .. } => {
+                let arg: &Loc<Expr> = arena.alloc(Loc::at(region, *expr));
+
+                Ok(Some(Expr::PncApply(
+                    arena.alloc(Loc::at(region, function)),
+                    Collection::with_items(arena.alloc([arg])),
+                )))
+            }
+        }
+    }
+}
+
+fn rename_field<'a>(
+    arena: &'a Bump,
+    fields: Fields<'a>,
+    from: &'a str,
+    to: &'a str,
+    region: Region,
+) -> Result<Fields<'a>, AstEditError> {
+    if fields.iter().any(|field| label(&field.value) == Some(to)) {
+        return Err(AstEditError::DuplicateField {
+            region,
+            field: to.to_string(),
+        });
+    }
+
+    let mut renamed = false;
+    let mut items = Vec::with_capacity_in(fields.len(), arena);
+
+    for field in fields.iter() {
+        let value = if label(&field.value) == Some(from) {
+            renamed = true;
+            rename_label(arena, &field.value, to)
+        } else {
+            field.value
+        };
+
+        items.push(Loc::at(field.region, value));
+    }
+
+    if !renamed {
+        return Err(AstEditError::MissingField {
+            region,
+            field: from.to_string(),
+        });
+    }
+
+    Ok(fields.replace_items(items.into_bump_slice()))
+}
+
+fn label<'a>(field: &AssignedField<'a, Expr<'a>>) -> Option<&'a str> {
+    match field {
+        AssignedField::RequiredValue(label, _, _)
+        | AssignedField::OptionalValue(label, _, _)
+        | AssignedField::IgnoredValue(label, _, _)
+        | AssignedField::LabelOnly(label) => Some(label.value),
+        AssignedField::SpaceBefore(field, _) | AssignedField::SpaceAfter(field, _) => label(field),
+    }
+}
+
+fn rename_label<'a>(
+    arena: &'a Bump,
+    field: &AssignedField<'a, Expr<'a>>,
+    to: &'a str,
+) -> AssignedField<'a, Expr<'a>> {
+    match *field {
+        AssignedField::RequiredValue(label, spaces, value) => {
+            AssignedField::RequiredValue(label.with_value(to), spaces, value)
+        }
+        AssignedField::OptionalValue(label, spaces, value) => {
+            AssignedField::OptionalValue(label.with_value(to), spaces, value)
+        }
+        AssignedField::IgnoredValue(label, spaces, value) => {
+            AssignedField::IgnoredValue(label.with_value(to), spaces, value)
+        }
+        AssignedField::LabelOnly(label) => {
+            let value = Expr::Var {
+                module_name: "",
+                ident: label.value,
+            };
+
+            AssignedField::RequiredValue(
+                label.with_value(to),
+                &[],
+                arena.alloc(label.with_value(value)),
+            )
+        }
+        AssignedField::SpaceBefore(field, spaces) => {
+            AssignedField::SpaceBefore(arena.alloc(rename_label(arena, field, to)), spaces)
+        }
+        AssignedField::SpaceAfter(field, spaces) => {
+            AssignedField::SpaceAfter(arena.alloc(rename_label(arena, field, to)), spaces)
+        }
+    }
+}
+
+/// Format a top-level def on its own, to replace the source at `region`.
+fn format_value_def<'a>(arena: &'a Bump, value_def: ValueDef<'a>, region: Region) -> String {
+    let flags = MigrationFlags {
+        snakify: false,
+        parens_and_commas: false,
+        dbg: DbgMigration::Keep,
+        transforms: &[],
+    };
+    let mut defs = Defs::default();
+    defs.push_value_def(value_def, region, &[], &[]);
+
+    let mut buf = Buf::new_in(arena, flags);
+    fmt_defs(&mut buf, &defs, 0);
+    buf.fmt_end_of_file();
+
+    let formatted = buf.into_bump_str().trim_end();
+
+    debug_assert!(
+        parse_module_defs(arena, State::new(formatted.as_bytes()), Defs::default()).is_ok(),
+        "a def formatted after an edit doesn't parse:\n{formatted}"
+    );
+
+    formatted.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use roc_parse::test_helpers::parse_defs_with;
+    use roc_region::all::Position;
+
+    fn region_of(src: &str, snippet: &str) -> Region {
+        let start = src.find(snippet).expect("the snippet is in the source") as u32;
+
+        Region::new(
+            Position::new(start),
+            Position::new(start + snippet.len() as u32),
+        )
+    }
+
+    fn edit(src: &str, edits: &[AstEdit]) -> Result<String, AstEditError> {
+        let arena = Bump::new();
+        let defs = parse_defs_with(&arena, src).unwrap();
+        let source_edits = apply_ast_edits(&arena, &defs, edits)?;
+
+        Ok(apply_source_edits(src, &source_edits))
+    }
+
+    #[test]
+    fn rename_field() {
+        let src = "point = { x: 1, y }\n\nother = { x: 2 }\n";
+
+        assert_eq!(
+            edit(
+                src,
+                &[AstEdit::RenameField {
+                    record: region_of(src, "{ x: 1, y }"),
+                    from: "y",
+                    to: "z",
+                }]
+            ),
+            Ok("point = { x: 1, z: y }\n\nother = { x: 2 }\n".to_string())
+        );
+    }
+
+    #[test]
+    fn add_field_with_default() {
+        let src = "main =\n    config = { name: \"roc\" }\n    config\n";
+
+        assert_eq!(
+            edit(
+                src,
+                &[AstEdit::AddField {
+                    record: region_of(src, "{ name: \"roc\" }"),
+                    name: "verbose",
+                    default: "Bool.false",
+                }]
+            ),
+            Ok(
+                "main =\n    config = { name: \"roc\", verbose: Bool.false }\n    config\n"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn wrap_in_call() {
+        let src = "total = a + b\n\nother   =   1\n";
+
+        assert_eq!(
+            edit(
+                src,
+                &[AstEdit::WrapInCall {
+                    expr: region_of(src, "a + b"),
+                    function: "Num.to_str",
+                }]
+            ),
+            Ok("total = Num.to_str(a + b)\n\nother   =   1\n".to_string())
+        );
+    }
+
+    #[test]
+    fn edits_that_cannot_apply() {
+        let src = "point = { x: 1, y: 2 }\n";
+        let record = region_of(src, "{ x: 1, y: 2 }");
+
+        assert_eq!(
+            edit(
+                src,
+                &[AstEdit::RenameField {
+                    record,
+                    from: "x",
+                    to: "y",
+                }]
+            ),
+            Err(AstEditError::DuplicateField {
+                region: record,
+                field: "y".to_string(),
+            })
+        );
+        assert_eq!(
+            edit(
+                src,
+                &[AstEdit::AddField {
+                    record,
+                    name: "if",
+                    default: "0",
+                }]
+            ),
+            Err(AstEditError::InvalidFieldName {
+                name: "if".to_string(),
+            })
+        );
+        assert_eq!(
+            edit(
+                src,
+                &[AstEdit::WrapInCall {
+                    expr: region_of(src, "x: 1"),
+                    function: "f",
+                }]
+            ),
+            Err(AstEditError::NotFound {
+                region: region_of(src, "x: 1"),
+            })
+        );
+    }
+}
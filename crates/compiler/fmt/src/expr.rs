@@ -9,7 +9,7 @@ use crate::spaces::{
     count_leading_newlines, fmt_comments_only, fmt_spaces, fmt_spaces_no_blank_lines,
     fmt_spaces_with_newline_mode, NewlineAt, SpacesNewlineMode, INDENT,
 };
-use crate::Buf;
+use crate::{Buf, DbgMigration};
 use bumpalo::collections::Vec;
 use bumpalo::Bump;
 use roc_module::called_via::{self, BinOp, UnaryOp};
@@ -32,6 +32,11 @@ impl<'a> Formattable for Expr<'a> {
     }
 
     fn format_with_options(&self, buf: &mut Buf, parens: Parens, newlines: Newlines, indent: u16) {
+        // Machine-generated code can be nested deeply enough to overflow the stack
+        if !buf.enter_nesting() {
+            return;
+        }
+
         let me = expr_lift_spaces(parens, buf.text.bump(), self);
 
         if !me.before.is_empty() {
@@ -43,6 +48,8 @@ impl<'a> Formattable for Expr<'a> {
         if !me.after.is_empty() {
             format_spaces(buf, me.after, newlines, indent);
         }
+
+        buf.exit_nesting();
     }
 }
 
@@ -88,10 +95,41 @@ fn format_expr_only(
             buf.indent(indent);
             buf.push_str("crash");
         }
+        Expr::TypedHole(name) => {
+            buf.indent(indent);
+
+            if name.is_empty() {
+                buf.push_str("???");
+            } else {
+                buf.push('?');
+                buf.push_str(name);
+            }
+        }
         Expr::Try => {
             buf.indent(indent);
             buf.push_str("try");
         }
+        Expr::Apply(
+            Loc {
+                value: Expr::Dbg, ..
+            },
+            [arg],
+            _,
+        ) if buf.flags().dbg == DbgMigration::Remove => {
+            buf.record_dbg_removed();
+            arg.value.format_with_options(buf, parens, newlines, indent);
+        }
+        Expr::PncApply(
+            Loc {
+                value: Expr::Dbg, ..
+            },
+            loc_args,
+        ) if buf.flags().dbg == DbgMigration::Remove && loc_args.len() == 1 => {
+            buf.record_dbg_removed();
+            loc_args.items[0]
+                .value
+                .format_with_options(buf, parens, newlines, indent);
+        }
         Expr::PncApply(
             Loc {
                 value: Expr::TrySuffix(expr),
@@ -255,6 +293,10 @@ fn format_expr_only(
             buf.indent(indent);
             buf.push_str("dbg");
         }
+        Expr::DbgStmt { continuation, .. } if buf.flags().dbg == DbgMigration::Remove => {
+            buf.record_dbg_removed();
+            fmt_without_dbg_stmt(buf, continuation, indent);
+        }
         Expr::DbgStmt {
             first: condition,
             extra_args,
@@ -540,6 +582,7 @@ pub fn expr_is_multiline(me: &Expr<'_>, comments_only: bool) -> bool {
         | Expr::Tag(_)
         | Expr::OpaqueRef(_)
         | Expr::Crash
+        | Expr::TypedHole(_)
         | Expr::Dbg
         | Expr::Try => false,
         Expr::LowLevelTry(_, _) => {
@@ -1317,6 +1360,7 @@ pub fn expr_lift_spaces<'a, 'b: 'a>(
         | Expr::Var { .. }
         | Expr::Underscore(_)
         | Expr::Crash
+        | Expr::TypedHole(_)
         | Expr::Tag(_)
         | Expr::OpaqueRef(_)
         | Expr::Dbg
@@ -1447,6 +1491,7 @@ pub fn expr_prec(expr: Expr<'_>) -> Prec {
         | Expr::Var { .. }
         | Expr::Underscore(_)
         | Expr::Crash
+        | Expr::TypedHole(_)
         | Expr::Tag(_)
         | Expr::OpaqueRef(_)
         | Expr::Dbg
@@ -1910,6 +1955,27 @@ fn fmt_dbg_stmt<'a>(
     }
 }
 
+/// Formats what follows a `dbg` statement that is being removed.
+fn fmt_without_dbg_stmt<'a>(buf: &mut Buf, continuation: &'a Loc<Expr<'a>>, indent: u16) {
+    let cont_lifted = expr_lift_spaces(Parens::NotNeeded, buf.text.bump(), &continuation.value);
+
+    // Only keep the comments, since the newline after the `dbg` went with it
+    fmt_comments_only(buf, cont_lifted.before.iter(), NewlineAt::Bottom, indent);
+    buf.ensure_ends_with_newline();
+
+    format_expr_only(
+        &cont_lifted.item,
+        buf,
+        Parens::NotNeeded,
+        Newlines::Yes,
+        indent,
+    );
+
+    if !cont_lifted.after.is_empty() {
+        format_spaces(buf, cont_lifted.after, Newlines::Yes, indent);
+    }
+}
+
 fn fmt_return<'a>(
     buf: &mut Buf,
     return_value: &'a Loc<Expr<'a>>,
@@ -6,12 +6,15 @@ use crate::expr::fmt_str_literal;
 use crate::pattern::snakify_camel_ident;
 use crate::spaces::{fmt_comments_only, fmt_default_spaces, fmt_spaces, NewlineAt, INDENT};
 use crate::Buf;
-use roc_parse::ast::{Collection, CommentOrNewline, Header, Spaced, Spaces, SpacesBefore};
+use roc_parse::ast::{
+    Collection, CommentOrNewline, Header, Spaced, Spaces, SpacesBefore, StrLiteral,
+};
 use roc_parse::header::{
     AppHeader, ExposedName, ExposesKeyword, HostedHeader, ImportsEntry, ImportsKeyword, Keyword,
-    KeywordItem, ModuleHeader, ModuleName, PackageEntry, PackageHeader, PackageKeyword,
-    PackageName, PackagesKeyword, PlatformHeader, PlatformKeyword, PlatformRequires,
-    ProvidesKeyword, ProvidesTo, RequiresKeyword, To, ToKeyword, TypedIdent,
+    KeywordItem, ManifestField, ManifestKeyword, ManifestValue, ModuleHeader, ModuleName,
+    PackageEntry, PackageHeader, PackageKeyword, PackageName, PackagesKeyword, PlatformHeader,
+    PlatformKeyword, PlatformRequires, ProvidesKeyword, ProvidesTo, RequiresKeyword, To, ToKeyword,
+    TypedIdent,
 };
 use roc_parse::ident::UppercaseIdent;
 use roc_region::all::Loc;
@@ -69,6 +72,7 @@ keywords! {
     ProvidesKeyword,
     ToKeyword,
     PlatformKeyword,
+    ManifestKeyword,
 }
 
 impl<V: Formattable> Formattable for Option<V> {
@@ -236,6 +240,21 @@ pub fn fmt_package_header<'a>(buf: &mut Buf, header: &'a PackageHeader<'a>) {
 
     let indent = fmt_spaces_with_outdent(buf, header.before_packages, indent);
     fmt_packages(buf, header.packages.value, indent);
+
+    if let Some(manifest) = &header.manifest {
+        let indent = fmt_spaces_with_outdent(buf, manifest.keyword.before, indent);
+        buf.indent(indent);
+        buf.push_str(ManifestKeyword::KEYWORD);
+
+        let indent = fmt_spaces_with_outdent(buf, manifest.keyword.after, indent);
+        fmt_collection(
+            buf,
+            indent,
+            Braces::Curly,
+            manifest.item.value,
+            Newlines::No,
+        );
+    }
 }
 
 pub fn fmt_platform_header<'a>(buf: &mut Buf, header: &'a PlatformHeader<'a>) {
@@ -445,6 +464,51 @@ impl<'a> Formattable for PackageEntry<'a> {
     }
 }
 
+impl<'a> Formattable for ManifestField<'a> {
+    fn is_multiline(&self) -> bool {
+        match &self.value.value {
+            ManifestValue::Str(literal) => literal.is_multiline(),
+            ManifestValue::List(items) => is_collection_multiline(items),
+        }
+    }
+
+    fn format_with_options(
+        &self,
+        buf: &mut Buf,
+        _parens: Parens,
+        _newlines: Newlines,
+        indent: u16,
+    ) {
+        buf.indent(indent);
+        buf.push_str(self.name.value);
+        buf.push(':');
+        fmt_default_spaces(buf, self.spaces_after_name, indent);
+
+        match self.value.value {
+            ManifestValue::Str(literal) => fmt_str_literal(buf, literal, indent),
+            ManifestValue::List(items) => {
+                fmt_collection(buf, indent, Braces::Square, items, Newlines::No)
+            }
+        }
+    }
+}
+
+impl<'a> Formattable for StrLiteral<'a> {
+    fn is_multiline(&self) -> bool {
+        matches!(self, StrLiteral::Block(_))
+    }
+
+    fn format_with_options(
+        &self,
+        buf: &mut Buf,
+        _parens: Parens,
+        _newlines: Newlines,
+        indent: u16,
+    ) {
+        fmt_str_literal(buf, *self, indent);
+    }
+}
+
 impl<'a> Formattable for ImportsEntry<'a> {
     fn is_multiline(&self) -> bool {
         false
@@ -5,13 +5,18 @@
 pub mod annotation;
 pub mod collection;
 pub mod def;
+pub mod edit;
 pub mod expr;
 pub mod header;
 pub mod node;
 pub mod pattern;
 pub mod spaces;
+pub mod transform;
 
 use bumpalo::{collections::String, Bump};
+use roc_parse::nesting::max_nesting_depth;
+use roc_region::all::Region;
+use transform::SourceTransform;
 
 #[derive(Debug)]
 pub struct Buf<'a> {
@@ -21,17 +26,55 @@ pub struct Buf<'a> {
     beginning_of_line: bool,
     line_indent: u16,
     flags: MigrationFlags,
+    /// When set, the widest a line can get by aligning the trailing comments of a collection
+    comment_alignment_width: Option<usize>,
+    /// The source being formatted, for copying the defs that formatting is turned off for
+    source: Option<&'a str>,
+    dbg_summary: DbgSummary,
+    nesting_depth: usize,
+    max_nesting_depth: usize,
+    nesting_too_deep: bool,
 }
 
 #[derive(Debug, Copy, Clone)]
 pub struct MigrationFlags {
     pub snakify: bool,
     pub parens_and_commas: bool,
+    pub dbg: DbgMigration,
+    /// Rewrites of the syntax tree to run before formatting, in order. See [transform].
+    pub transforms: &'static [&'static dyn SourceTransform],
 }
 
 impl MigrationFlags {
     pub fn at_least_one_active(&self) -> bool {
-        self.snakify || self.parens_and_commas
+        self.snakify
+            || self.parens_and_commas
+            || self.dbg != DbgMigration::Keep
+            || !self.transforms.is_empty()
+    }
+}
+
+/// What formatting does with `dbg`, to clean it up before committing.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DbgMigration {
+    Keep,
+    /// Turn `x = dbg expr` into `x = expr` followed by a `dbg x` statement, so the value is
+    /// printed on its own line. Inline `dbg`s anywhere else are left alone.
+    Lift,
+    /// Remove `dbg` statements, and replace inline `dbg expr` with `expr`.
+    Remove,
+}
+
+/// How many `dbg`s formatting changed, for reporting after a migration.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct DbgSummary {
+    pub lifted: usize,
+    pub removed: usize,
+}
+
+impl DbgSummary {
+    pub fn is_empty(&self) -> bool {
+        self.lifted == 0 && self.removed == 0
     }
 }
 
@@ -44,6 +87,12 @@ impl<'a> Buf<'a> {
             newlines_to_flush: 0,
             beginning_of_line: true,
             flags,
+            comment_alignment_width: None,
+            source: None,
+            dbg_summary: DbgSummary::default(),
+            nesting_depth: 0,
+            max_nesting_depth: max_nesting_depth(),
+            nesting_too_deep: false,
         }
     }
 
@@ -51,6 +100,99 @@ impl<'a> Buf<'a> {
         self.flags
     }
 
+    pub fn dbg_summary(&self) -> DbgSummary {
+        self.dbg_summary
+    }
+
+    pub(crate) fn record_dbg_lifted(&mut self) {
+        self.dbg_summary.lifted += 1;
+    }
+
+    pub(crate) fn record_dbg_removed(&mut self) {
+        self.dbg_summary.removed += 1;
+    }
+
+    /// Stop formatting expressions nested deeper than this, instead of the default limit.
+    pub fn set_max_nesting_depth(&mut self, max_depth: usize) {
+        self.max_nesting_depth = max_depth;
+    }
+
+    pub fn max_nesting_depth(&self) -> usize {
+        self.max_nesting_depth
+    }
+
+    /// Whether some expression was nested too deeply to format. If so, the output is missing
+    /// whatever was nested past the limit, and shouldn't be used.
+    pub fn nesting_too_deep(&self) -> bool {
+        self.nesting_too_deep
+    }
+
+    /// Go one level deeper into an expression. Returns `false`, without going deeper, if that
+    /// would pass the nesting limit; otherwise every call must be paired with [Buf::exit_nesting].
+    pub(crate) fn enter_nesting(&mut self) -> bool {
+        if self.nesting_depth >= self.max_nesting_depth {
+            self.nesting_too_deep = true;
+
+            false
+        } else {
+            self.nesting_depth += 1;
+
+            true
+        }
+    }
+
+    pub(crate) fn exit_nesting(&mut self) {
+        self.nesting_depth -= 1;
+    }
+
+    /// How many more levels of nesting can be formatted from here.
+    pub(crate) fn remaining_nesting_depth(&self) -> usize {
+        self.max_nesting_depth - self.nesting_depth
+    }
+
+    /// Start the trailing comments on the items of a multiline record, tuple or tag union type in
+    /// the same column, as long as that column is within `max_width`.
+    pub fn align_trailing_comments(&mut self, max_width: usize) {
+        self.comment_alignment_width = Some(max_width);
+    }
+
+    pub fn comment_alignment_width(&self) -> Option<usize> {
+        self.comment_alignment_width
+    }
+
+    /// Keep a copy of the source being formatted, so that the defs that a `# fmt: skip` comment, or
+    /// a `# fmt: off` and `# fmt: on` pair, turn formatting off for are copied exactly as they are.
+    /// Without it, those comments have no effect.
+    pub fn set_source(&mut self, src: &str) {
+        self.source = Some(self.text.bump().alloc_str(src));
+    }
+
+    /// The source text in a region, if the source was set.
+    pub(crate) fn source_at(&self, region: Region) -> Option<&'a str> {
+        self.source?
+            .get(region.start().byte_offset()..region.end().byte_offset())
+    }
+
+    /// Push source text exactly as it is, including any newlines and spaces in it.
+    pub(crate) fn push_verbatim(&mut self, text: &str) {
+        debug_assert!(!self.beginning_of_line, "push_verbatim: `{text}`");
+
+        self.flush_spaces();
+
+        self.text.push_str(text);
+    }
+
+    /// How wide the current line is, counting the spaces that haven't been written yet.
+    pub(crate) fn line_width(&self) -> usize {
+        if self.newlines_to_flush > 0 {
+            return self.spaces_to_flush;
+        }
+
+        let line_start = self.text.rfind('\n').map_or(0, |i| i + 1);
+
+        self.text[line_start..].chars().count() + self.spaces_to_flush
+    }
+
     pub fn as_str(&'a self) -> &'a str {
         self.text.as_str()
     }
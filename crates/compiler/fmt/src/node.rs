@@ -383,7 +383,23 @@ impl<'a> Formattable for Node<'a> {
                     indent
                 };
 
-                for item in *lefts {
+                let comment_column = match buf.comment_alignment_width() {
+                    Some(max_width) if *indent_items => trailing_comment_column(
+                        buf,
+                        lefts,
+                        *right,
+                        parens,
+                        newlines,
+                        inner_indent,
+                        max_width,
+                    ),
+                    _ => None,
+                };
+
+                for (i, item) in lefts.iter().enumerate() {
+                    if i > 0 && starts_with_line_comment(item.before) {
+                        pad_to_column(buf, comment_column);
+                    }
                     fmt_spaces(buf, item.before.iter(), inner_indent);
                     if item.newline {
                         buf.ensure_ends_with_newline();
@@ -396,6 +412,9 @@ impl<'a> Formattable for Node<'a> {
                         buf.push(',');
                     }
                 }
+                if !lefts.is_empty() && starts_with_line_comment(right.comments) {
+                    pad_to_column(buf, comment_column);
+                }
                 fmt_sp(buf, *right, inner_indent);
 
                 buf.indent(indent);
@@ -501,3 +520,51 @@ impl<'a> NodeSequenceBuilder<'a> {
         }
     }
 }
+
+fn starts_with_line_comment(spaces: &[CommentOrNewline]) -> bool {
+    matches!(spaces.first(), Some(CommentOrNewline::LineComment(_)))
+}
+
+/// The column to start the trailing comments of a collection's items in, so that they line up:
+/// one past the widest item that has a trailing comment, leaving out the items that are too wide
+/// to fit in `max_width`.
+fn trailing_comment_column(
+    buf: &Buf,
+    items: &[DelimitedItem],
+    after: Sp,
+    parens: Parens,
+    newlines: Newlines,
+    indent: u16,
+    max_width: usize,
+) -> Option<usize> {
+    let arena = Bump::new();
+
+    items
+        .iter()
+        .enumerate()
+        .filter(|&(i, _)| match items.get(i + 1) {
+            Some(next) => starts_with_line_comment(next.before),
+            None => starts_with_line_comment(after.comments),
+        })
+        .map(|(_, item)| {
+            let mut scratch = Buf::new_in(&arena, buf.flags());
+            // Anything too deep to measure here is too deep to format, which `buf` will notice
+            scratch.set_max_nesting_depth(buf.remaining_nesting_depth());
+            scratch.indent(indent);
+            item.node
+                .format_with_options(&mut scratch, parens, newlines, indent);
+            if item.comma_after {
+                scratch.push(',');
+            }
+            scratch.line_width()
+        })
+        .filter(|&width| width < max_width)
+        .max()
+        .map(|width| width + 1)
+}
+
+fn pad_to_column(buf: &mut Buf, column: Option<usize>) {
+    if let Some(column) = column {
+        buf.spaces(column.saturating_sub(buf.line_width()));
+    }
+}
@@ -2,7 +2,7 @@ use crate::annotation::{Formattable, Newlines, Parens};
 use crate::expr::{
     expr_is_multiline, expr_lift_spaces_after, fmt_str_literal, format_sq_literal, is_str_multiline,
 };
-use crate::node::{Node, NodeInfo, NodeSequenceBuilder, Prec, Sp};
+use crate::node::{parens_around_node, Node, NodeInfo, NodeSequenceBuilder, Prec, Sp};
 use crate::spaces::{fmt_comments_only, fmt_spaces, NewlineAt, INDENT};
 use crate::Buf;
 use bumpalo::Bump;
@@ -421,24 +421,17 @@ fn fmt_pattern_only(
         }
 
         Pattern::As(pattern, pattern_as) => {
-            let needs_parens = parens == Parens::InAsPattern
-                || parens == Parens::InApply
-                || parens == Parens::InPncApplyFunc
-                || parens == Parens::InClosurePattern;
+            let arena = buf.text.bump();
+            let node = pattern_as_to_node(arena, pattern.value, pattern_as);
 
-            if needs_parens {
-                buf.indent(indent);
-                buf.push('(');
-            }
-
-            fmt_pattern(buf, &pattern.value, indent, Parens::InAsPattern);
-
-            pattern_as.format(buf, indent + INDENT);
+            // Closure arguments are separated by commas, but `\a as b, c ->` still reads badly
+            let node = if parens == Parens::InClosurePattern {
+                parens_around_node(arena, node, true)
+            } else {
+                node.add_parens(arena, parens)
+            };
 
-            if needs_parens {
-                buf.indent(indent);
-                buf.push(')');
-            }
+            node.format(buf, indent);
         }
 
         Pattern::SpaceBefore(..) | Pattern::SpaceAfter(..) => {
@@ -646,6 +639,51 @@ pub fn pattern_apply_to_node<'b, 'a: 'b>(
     }
 }
 
+/// Lays out `pattern as ident`. The inner pattern gets parens if it's an `as` pattern itself,
+/// and comments around the `as` stay where they were written.
+pub fn pattern_as_to_node<'b, 'a: 'b>(
+    arena: &'b Bump,
+    pattern: Pattern<'a>,
+    pattern_as: &PatternAs<'a>,
+) -> NodeInfo<'b> {
+    let lifted = pattern_lift_spaces(arena, &pattern);
+    let inner = NodeInfo {
+        before: lifted.before,
+        node: Node::Pattern(lifted.item),
+        after: lifted.after,
+        needs_indent: true,
+        prec: pattern_prec(lifted.item),
+    }
+    .add_parens(arena, Parens::InAsPattern);
+
+    let mut b = NodeSequenceBuilder::new(arena, inner.node, 2, true);
+    b.push(
+        Sp::with_space(only_if_commented(inner.after)),
+        Node::Literal("as"),
+    );
+    b.push(
+        Sp::with_space(only_if_commented(pattern_as.spaces_before)),
+        Node::Literal(pattern_as.identifier.value),
+    );
+
+    NodeInfo {
+        before: inner.before,
+        node: b.build(),
+        after: &[],
+        needs_indent: true,
+        prec: Prec::AsType,
+    }
+}
+
+/// Newlines around an `as` don't carry meaning, so they're only kept alongside comments.
+fn only_if_commented<'a>(spaces: &'a [CommentOrNewline<'a>]) -> &'a [CommentOrNewline<'a>] {
+    if spaces.iter().any(|s| s.is_comment()) {
+        spaces
+    } else {
+        &[]
+    }
+}
+
 fn pattern_prec(pat: Pattern<'_>) -> Prec {
     match pat {
         Pattern::Identifier { .. }
@@ -665,7 +703,8 @@ fn pattern_prec(pat: Pattern<'_>) -> Prec {
         | Pattern::List(..)
         | Pattern::ListRest(_)
         | Pattern::PncApply(_, _) => Prec::Term,
-        Pattern::Apply(_, _) | Pattern::As(_, _) => Prec::Apply,
+        Pattern::Apply(_, _) => Prec::Apply,
+        Pattern::As(_, _) => Prec::AsType,
         Pattern::SpaceBefore(inner, _) | Pattern::SpaceAfter(inner, _) => pattern_prec(*inner),
         Pattern::Malformed(_) | Pattern::MalformedIdent(..) | Pattern::MalformedExpr(_) => {
             Prec::Term
@@ -735,6 +774,18 @@ pub fn pattern_lift_spaces<'a, 'b: 'a>(
                 after: &[],
             }
         }
+        Pattern::As(inner, pattern_as) => {
+            let inner_lifted = pattern_lift_spaces_before(arena, &inner.value);
+
+            Spaces {
+                before: inner_lifted.before,
+                item: Pattern::As(
+                    arena.alloc(Loc::at(inner.region, inner_lifted.item)),
+                    *pattern_as,
+                ),
+                after: &[],
+            }
+        }
         Pattern::OptionalField(name, expr) => {
             let lifted = expr_lift_spaces_after(Parens::NotNeeded, arena, &expr.value);
             Spaces {
@@ -869,12 +920,14 @@ mod snakify_test {
     use bumpalo::Bump;
 
     use super::snakify_camel_ident;
-    use crate::{Buf, MigrationFlags};
+    use crate::{Buf, DbgMigration, MigrationFlags};
 
     fn check_snakify(arena: &Bump, original: &str) -> String {
         let flags = MigrationFlags {
             snakify: true,
             parens_and_commas: false,
+            dbg: DbgMigration::Keep,
+            transforms: &[],
         };
         let mut buf = Buf::new_in(arena, flags);
         buf.indent(0);
@@ -166,6 +166,29 @@ pub fn fmt_comments_only<'a, 'buf, I>(
     }
 }
 
+/// A comment that turns formatting off or on for the defs after it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FmtDirective {
+    /// `# fmt: skip`, which leaves the next def as it is
+    Skip,
+    /// `# fmt: off`, which leaves every def as it is until a `# fmt: on`
+    Off,
+    On,
+}
+
+/// The last formatting directive in these spaces, if any.
+pub(crate) fn fmt_directive(spaces: &[CommentOrNewline]) -> Option<FmtDirective> {
+    spaces.iter().rev().find_map(|space| match space {
+        CommentOrNewline::LineComment(comment) => match comment.trim() {
+            "fmt: skip" => Some(FmtDirective::Skip),
+            "fmt: off" => Some(FmtDirective::Off),
+            "fmt: on" => Some(FmtDirective::On),
+            _ => None,
+        },
+        _ => None,
+    })
+}
+
 fn fmt_comment(buf: &mut Buf, comment: &str) {
     // Format shebangs without whitespace. We look for " !" as well to fix incorrect formatting from
     // the past.
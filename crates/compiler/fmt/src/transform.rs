@@ -0,0 +1,440 @@
+//! Rewrites of the syntax tree that run before it's formatted.
+//!
+//! A syntax migration, like replacing a keyword or removing some sugar, is a [SourceTransform]:
+//! it says what to replace an expression with, and [apply_transforms] walks the whole module
+//! and reports every place that each transform changed.
+use bumpalo::{collections::Vec, Bump};
+use roc_parse::ast::{
+    AssignedField, Collection, Defs, Expr, ExtractSpaces, FullAst, StrLiteral, StrSegment,
+    ValueDef, WhenBranch,
+};
+use roc_region::all::{Loc, Region};
+
+/// An AST to AST rewrite that runs before a module is formatted.
+pub trait SourceTransform: Sync {
+    /// A short name for the transform, used to report what it changed
+    fn name(&self) -> &'static str;
+
+    /// What the transform changes, in a sentence
+    fn description(&self) -> &'static str;
+
+    /// What to replace an expression with, or `None` to leave it as it is. The expressions inside
+    /// it have already been rewritten.
+    fn rewrite_expr<'a>(&self, arena: &'a Bump, expr: &Expr<'a>) -> Option<Expr<'a>>;
+}
+
+impl std::fmt::Debug for dyn SourceTransform + '_ {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The transforms that `roc format --migrate` runs, in order.
+pub const BUILTIN_TRANSFORMS: &[&dyn SourceTransform] = &[&TryToSuffix];
+
+/// The built-in transform with this name.
+pub fn builtin_transform(name: &str) -> Option<&'static dyn SourceTransform> {
+    BUILTIN_TRANSFORMS
+        .iter()
+        .copied()
+        .find(|transform| transform.name() == name)
+}
+
+/// A place in the original source that a transform changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AppliedChange {
+    pub transform: &'static str,
+    pub region: Region,
+}
+
+/// Run `transforms` over the module, one after the other, and return the rewritten module along
+/// with every change they made.
+pub fn apply_transforms<'a>(
+    arena: &'a Bump,
+    ast: &FullAst<'a>,
+    transforms: &[&dyn SourceTransform],
+) -> (FullAst<'a>, std::vec::Vec<AppliedChange>) {
+    let mut changes = std::vec::Vec::new();
+    let mut defs = ast.defs.clone();
+
+    for &transform in transforms {
+        let mut rewrite = |expr: &Expr<'a>, region: Region| {
+            let replacement = transform.rewrite_expr(arena, expr)?;
+
+            changes.push(AppliedChange {
+                transform: transform.name(),
+                region,
+            });
+
+            Some(replacement)
+        };
+
+        defs = Rewriter::new(arena, &mut rewrite).defs(&defs);
+    }
+
+    let ast = FullAst {
+        header: ast.header.clone(),
+        defs,
+    };
+
+    (ast, changes)
+}
+
+/// What to replace an expression with, given the region of the closest enclosing [Loc].
+pub(crate) type RewriteExpr<'a, 'r> = dyn FnMut(&Expr<'a>, Region) -> Option<Expr<'a>> + 'r;
+
+/// Walks a syntax tree bottom-up, replacing each expression that `rewrite` returns a replacement
+/// for.
+pub(crate) struct Rewriter<'a, 'r> {
+    arena: &'a Bump,
+    rewrite: &'r mut RewriteExpr<'a, 'r>,
+}
+
+impl<'a, 'r> Rewriter<'a, 'r> {
+    pub(crate) fn new(arena: &'a Bump, rewrite: &'r mut RewriteExpr<'a, 'r>) -> Self {
+        Self { arena, rewrite }
+    }
+
+    fn defs(&mut self, defs: &Defs<'a>) -> Defs<'a> {
+        let mut defs = defs.clone();
+
+        for value_def in defs.value_defs.iter_mut() {
+            *value_def = self.value_def(value_def);
+        }
+
+        defs
+    }
+
+    pub(crate) fn value_def(&mut self, value_def: &ValueDef<'a>) -> ValueDef<'a> {
+        match *value_def {
+            ValueDef::Body(pattern, body) => ValueDef::Body(pattern, self.alloc_loc_expr(body)),
+            ValueDef::AnnotatedBody {
+                ann_pattern,
+                ann_type,
+                lines_between,
+                body_pattern,
+                body_expr,
+            } => ValueDef::AnnotatedBody {
+                ann_pattern,
+                ann_type,
+                lines_between,
+                body_pattern,
+                body_expr: self.alloc_loc_expr(body_expr),
+            },
+            ValueDef::Dbg {
+                condition,
+                preceding_comment,
+            } => ValueDef::Dbg {
+                condition: self.alloc_loc_expr(condition),
+                preceding_comment,
+            },
+            ValueDef::Expect {
+                condition,
+                preceding_comment,
+            } => ValueDef::Expect {
+                condition: self.alloc_loc_expr(condition),
+                preceding_comment,
+            },
+            ValueDef::Stmt(expr) => ValueDef::Stmt(self.alloc_loc_expr(expr)),
+            ValueDef::Annotation(..)
+            | ValueDef::ModuleImport(_)
+            | ValueDef::IngestedFileImport(_)
+            | ValueDef::StmtAfterExpr => *value_def,
+        }
+    }
+
+    fn loc_expr(&mut self, loc_expr: &Loc<Expr<'a>>) -> Loc<Expr<'a>> {
+        Loc::at(loc_expr.region, self.expr(&loc_expr.value, loc_expr.region))
+    }
+
+    fn alloc_loc_expr(&mut self, loc_expr: &Loc<Expr<'a>>) -> &'a Loc<Expr<'a>> {
+        let loc_expr = self.loc_expr(loc_expr);
+
+        self.arena.alloc(loc_expr)
+    }
+
+    fn alloc_expr(&mut self, expr: &Expr<'a>, region: Region) -> &'a Expr<'a> {
+        let expr = self.expr(expr, region);
+
+        self.arena.alloc(expr)
+    }
+
+    fn loc_exprs(&mut self, loc_exprs: &[&'a Loc<Expr<'a>>]) -> &'a [&'a Loc<Expr<'a>>] {
+        let mut rewritten = Vec::with_capacity_in(loc_exprs.len(), self.arena);
+
+        for loc_expr in loc_exprs {
+            rewritten.push(self.alloc_loc_expr(loc_expr));
+        }
+
+        rewritten.into_bump_slice()
+    }
+
+    fn fields(
+        &mut self,
+        fields: Collection<'a, Loc<AssignedField<'a, Expr<'a>>>>,
+    ) -> Collection<'a, Loc<AssignedField<'a, Expr<'a>>>> {
+        let mut rewritten = Vec::with_capacity_in(fields.len(), self.arena);
+
+        for field in fields.iter() {
+            rewritten.push(Loc::at(field.region, self.field(&field.value)));
+        }
+
+        fields.replace_items(rewritten.into_bump_slice())
+    }
+
+    fn field(&mut self, field: &AssignedField<'a, Expr<'a>>) -> AssignedField<'a, Expr<'a>> {
+        match *field {
+            AssignedField::RequiredValue(label, spaces, value) => {
+                AssignedField::RequiredValue(label, spaces, self.alloc_loc_expr(value))
+            }
+            AssignedField::OptionalValue(label, spaces, value) => {
+                AssignedField::OptionalValue(label, spaces, self.alloc_loc_expr(value))
+            }
+            AssignedField::IgnoredValue(label, spaces, value) => {
+                AssignedField::IgnoredValue(label, spaces, self.alloc_loc_expr(value))
+            }
+            AssignedField::LabelOnly(label) => AssignedField::LabelOnly(label),
+            AssignedField::SpaceBefore(field, spaces) => {
+                let field = self.field(field);
+
+                AssignedField::SpaceBefore(self.arena.alloc(field), spaces)
+            }
+            AssignedField::SpaceAfter(field, spaces) => {
+                let field = self.field(field);
+
+                AssignedField::SpaceAfter(self.arena.alloc(field), spaces)
+            }
+        }
+    }
+
+    fn str_literal(&mut self, literal: StrLiteral<'a>) -> StrLiteral<'a> {
+        match literal {
+            StrLiteral::PlainLine(_) => literal,
+            StrLiteral::Line(segments) => StrLiteral::Line(self.str_segments(segments)),
+            StrLiteral::Block(lines) => {
+                let mut rewritten = Vec::with_capacity_in(lines.len(), self.arena);
+
+                for segments in lines.iter() {
+                    rewritten.push(self.str_segments(segments));
+                }
+
+                StrLiteral::Block(rewritten.into_bump_slice())
+            }
+        }
+    }
+
+    fn str_segments(&mut self, segments: &[StrSegment<'a>]) -> &'a [StrSegment<'a>] {
+        let mut rewritten = Vec::with_capacity_in(segments.len(), self.arena);
+
+        for segment in segments {
+            rewritten.push(match *segment {
+                StrSegment::Interpolated(loc_expr) => StrSegment::Interpolated(Loc::at(
+                    loc_expr.region,
+                    self.alloc_expr(loc_expr.value, loc_expr.region),
+                )),
+                StrSegment::Plaintext(_) | StrSegment::Unicode(_) | StrSegment::EscapedChar(_) => {
+                    *segment
+                }
+            });
+        }
+
+        rewritten.into_bump_slice()
+    }
+
+    /// Rewrite the expressions inside `expr`, and then `expr` itself. `region` is the region of
+    /// the closest enclosing [Loc], since not every expression has one.
+    fn expr(&mut self, expr: &Expr<'a>, region: Region) -> Expr<'a> {
+        let rewritten = self.sub_exprs(expr, region);
+
+        (self.rewrite)(&rewritten, region).unwrap_or(rewritten)
+    }
+
+    fn sub_exprs(&mut self, expr: &Expr<'a>, region: Region) -> Expr<'a> {
+        let arena = self.arena;
+
+        match *expr {
+            Expr::Float(_)
+            | Expr::Num(_)
+            | Expr::NonBase10Int { .. }
+            | Expr::SingleQuote(_)
+            | Expr::AccessorFunction(_)
+            | Expr::RecordUpdater(_)
+            | Expr::Var { .. }
+            | Expr::Underscore(_)
+            | Expr::Crash
+            | Expr::TypedHole(_)
+            | Expr::Tag(_)
+            | Expr::OpaqueRef(_)
+            | Expr::Dbg
+            | Expr::Try
+            | Expr::MalformedIdent(..)
+            | Expr::PrecedenceConflict(_) => *expr,
+            Expr::Str(literal) => Expr::Str(self.str_literal(literal)),
+            Expr::RecordAccess(inner, field) => {
+                Expr::RecordAccess(self.alloc_expr(inner, region), field)
+            }
+            Expr::TupleAccess(inner, index) => {
+                Expr::TupleAccess(self.alloc_expr(inner, region), index)
+            }
+            Expr::TrySuffix(inner) => Expr::TrySuffix(self.alloc_expr(inner, region)),
+            Expr::List(items) => Expr::List(items.replace_items(self.loc_exprs(items.items))),
+            Expr::RecordUpdate { update, fields } => Expr::RecordUpdate {
+                update: self.alloc_loc_expr(update),
+                fields: self.fields(fields),
+            },
+            Expr::Record(fields) => Expr::Record(self.fields(fields)),
+            Expr::Tuple(items) => Expr::Tuple(items.replace_items(self.loc_exprs(items.items))),
+            Expr::RecordBuilder { mapper, fields } => Expr::RecordBuilder {
+                mapper: self.alloc_loc_expr(mapper),
+                fields: self.fields(fields),
+            },
+            Expr::Closure(patterns, body) => Expr::Closure(patterns, self.alloc_loc_expr(body)),
+            Expr::Defs(defs, final_expr) => {
+                let defs = self.defs(defs);
+
+                Expr::Defs(arena.alloc(defs), self.alloc_loc_expr(final_expr))
+            }
+            Expr::DbgStmt {
+                first,
+                extra_args,
+                continuation,
+                pnc_style,
+            } => Expr::DbgStmt {
+                first: self.alloc_loc_expr(first),
+                extra_args: self.loc_exprs(extra_args),
+                continuation: self.alloc_loc_expr(continuation),
+                pnc_style,
+            },
+            Expr::LowLevelTry(inner, kind) => Expr::LowLevelTry(self.alloc_loc_expr(inner), kind),
+            Expr::LowLevelDbg(info, message, continuation) => Expr::LowLevelDbg(
+                info,
+                self.alloc_loc_expr(message),
+                self.alloc_loc_expr(continuation),
+            ),
+            Expr::Apply(function, args, called_via) => Expr::Apply(
+                self.alloc_loc_expr(function),
+                self.loc_exprs(args),
+                called_via,
+            ),
+            Expr::PncApply(function, args) => Expr::PncApply(
+                self.alloc_loc_expr(function),
+                args.replace_items(self.loc_exprs(args.items)),
+            ),
+            Expr::BinOps(operands, last) => {
+                let mut rewritten = Vec::with_capacity_in(operands.len(), arena);
+
+                for (operand, op) in operands.iter() {
+                    rewritten.push((self.loc_expr(operand), *op));
+                }
+
+                Expr::BinOps(rewritten.into_bump_slice(), self.alloc_loc_expr(last))
+            }
+            Expr::UnaryOp(operand, op) => Expr::UnaryOp(self.alloc_loc_expr(operand), op),
+            Expr::If {
+                if_thens,
+                final_else,
+                indented_else,
+            } => {
+                let mut rewritten = Vec::with_capacity_in(if_thens.len(), arena);
+
+                for (condition, then) in if_thens.iter() {
+                    rewritten.push((self.loc_expr(condition), self.loc_expr(then)));
+                }
+
+                Expr::If {
+                    if_thens: rewritten.into_bump_slice(),
+                    final_else: self.alloc_loc_expr(final_else),
+                    indented_else,
+                }
+            }
+            Expr::When(condition, branches) => {
+                let condition = self.alloc_loc_expr(condition);
+                let mut rewritten = Vec::with_capacity_in(branches.len(), arena);
+
+                for branch in branches.iter() {
+                    let branch = WhenBranch {
+                        patterns: branch.patterns,
+                        value: self.loc_expr(&branch.value),
+                        guard: branch.guard.as_ref().map(|guard| self.loc_expr(guard)),
+                    };
+
+                    rewritten.push(&*arena.alloc(branch));
+                }
+
+                Expr::When(condition, rewritten.into_bump_slice())
+            }
+            Expr::Return(value, after) => Expr::Return(
+                self.alloc_loc_expr(value),
+                after.map(|after| self.alloc_loc_expr(after)),
+            ),
+            Expr::SpaceBefore(inner, spaces) => {
+                Expr::SpaceBefore(self.alloc_expr(inner, region), spaces)
+            }
+            Expr::SpaceAfter(inner, spaces) => {
+                Expr::SpaceAfter(self.alloc_expr(inner, region), spaces)
+            }
+            Expr::ParensAround(inner) => Expr::ParensAround(self.alloc_expr(inner, region)),
+            Expr::EmptyRecordBuilder(inner) => Expr::EmptyRecordBuilder(self.alloc_loc_expr(inner)),
+            Expr::SingleFieldRecordBuilder(inner) => {
+                Expr::SingleFieldRecordBuilder(self.alloc_loc_expr(inner))
+            }
+            Expr::OptionalFieldInRecordBuilder(label, inner) => {
+                Expr::OptionalFieldInRecordBuilder(label, self.alloc_loc_expr(inner))
+            }
+        }
+    }
+}
+
+/// Replaces the `try` keyword with the `?` suffix: `try f x` becomes `f? x`, and `try x` becomes
+/// `x?`. These mean the same thing, including after a `|>`.
+#[derive(Debug)]
+pub struct TryToSuffix;
+
+impl SourceTransform for TryToSuffix {
+    fn name(&self) -> &'static str {
+        "try-suffix"
+    }
+
+    fn description(&self) -> &'static str {
+        "Replace the `try` keyword with the `?` suffix"
+    }
+
+    fn rewrite_expr<'a>(&self, arena: &'a Bump, expr: &Expr<'a>) -> Option<Expr<'a>> {
+        let try_suffix = |loc_expr: &Loc<Expr<'a>>| {
+            Expr::TrySuffix(arena.alloc(loc_expr.value.without_spaces()))
+        };
+
+        match *expr {
+            Expr::Apply(
+                Loc {
+                    value: Expr::Try, ..
+                },
+                args,
+                called_via,
+            ) => match args {
+                [] => None,
+                [result] => Some(try_suffix(*result)),
+                [function, args @ ..] => Some(Expr::Apply(
+                    arena.alloc(Loc::at(function.region, try_suffix(*function))),
+                    args,
+                    called_via,
+                )),
+            },
+            Expr::PncApply(
+                Loc {
+                    value: Expr::Try, ..
+                },
+                args,
+            ) => match args.items {
+                [] => None,
+                [result] => Some(try_suffix(*result)),
+                [function, args @ ..] => {
+                    let apply = Expr::PncApply(*function, Collection::with_items(args));
+
+                    Some(Expr::TrySuffix(arena.alloc(apply)))
+                }
+            },
+            _ => None,
+        }
+    }
+}
@@ -885,6 +885,7 @@ impl AArch64CallLoadArgs {
             pointer_layouts!() => self.load_arg_general(storage_manager, sym),
             single_register_floats!() => self.load_arg_float(storage_manager, sym),
             LayoutRepr::Builtin(Int(IntWidth::U128 | IntWidth::I128) | Decimal) => {
+                self.align_for_16_byte_arg();
                 self.load_arg_general_128bit(buf, storage_manager, layout_interner, sym, in_layout);
             }
             _ if stack_size == 0 => {
@@ -957,6 +958,17 @@ impl AArch64CallLoadArgs {
         }
     }
 
+    /// Values with 16-byte alignment (I128, U128 and Dec) are passed in an even-numbered register
+    /// pair, or at a 16-byte aligned stack offset, after which no more arguments use registers.
+    fn align_for_16_byte_arg(&mut self) {
+        self.general_i += self.general_i % 2;
+
+        if self.general_i + 1 >= AArch64Call::GENERAL_PARAM_REGS.len() {
+            self.general_i = AArch64Call::GENERAL_PARAM_REGS.len();
+            self.argument_offset = (self.argument_offset + 15) & !15;
+        }
+    }
+
     fn load_arg_general(
         &mut self,
         storage_manager: &mut AArch64StorageManager<'_, '_>,
@@ -1071,6 +1083,7 @@ impl AArch64CallStoreArgs {
             pointer_layouts!() => self.store_arg_general(buf, storage_manager, sym),
             single_register_floats!() => self.store_arg_float(buf, storage_manager, sym),
             LayoutRepr::I128 | LayoutRepr::U128 | LayoutRepr::DEC => {
+                self.align_for_16_byte_arg();
                 self.store_arg_128bit(buf, storage_manager, sym)
             }
             _ if layout_interner.stack_size(in_layout) == 0 => {}
@@ -1126,6 +1139,16 @@ impl AArch64CallStoreArgs {
         }
     }
 
+    /// Must match [AArch64CallLoadArgs::align_for_16_byte_arg].
+    fn align_for_16_byte_arg(&mut self) {
+        self.general_i += self.general_i % 2;
+
+        if self.general_i + 1 >= Self::GENERAL_PARAM_REGS.len() {
+            self.general_i = Self::GENERAL_PARAM_REGS.len();
+            self.tmp_stack_offset = (self.tmp_stack_offset + 15) & !15;
+        }
+    }
+
     fn store_arg_64bit<'a>(
         &mut self,
         buf: &mut Vec<'a, u8>,
@@ -1264,10 +1287,18 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
     ) {
         if imm32 < 0 {
             Self::sub_reg64_reg64_imm32(buf, dst, src, -imm32);
-        } else if imm32 < 0xFFF {
+        } else if imm32 <= 0xFFF {
             add_reg64_reg64_imm12(buf, dst, src, imm32 as u16);
+        } else if imm32 <= 0xFF_FFFF {
+            // e.g. stack offsets in large frames. Unlike a register operand, this also works
+            // when src or dst is the stack pointer.
+            add_reg64_reg64_imm12_lsl12(buf, dst, src, (imm32 >> 12) as u16);
+
+            if imm32 & 0xFFF != 0 {
+                add_reg64_reg64_imm12(buf, dst, dst, (imm32 & 0xFFF) as u16);
+            }
         } else {
-            todo!("immediate additions with values greater than 12bits");
+            todo!("immediate additions with values greater than 24bits");
         }
     }
     #[inline(always)]
@@ -1713,7 +1744,7 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
     }
     #[inline(always)]
     fn mov_base32_freg32(buf: &mut Vec<'_, u8>, offset: i32, src: AArch64FloatReg) {
-        Self::mov_mem64_offset32_freg64(buf, AArch64GeneralReg::FP, offset, src)
+        Self::mov_mem_offset32_freg(buf, FloatWidth::F32, AArch64GeneralReg::FP, offset, src)
     }
     #[inline(always)]
     fn movesd_mem64_offset32_freg64(
@@ -1725,6 +1756,16 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
         Self::mov_mem64_offset32_freg64(buf, ptr, offset, src)
     }
 
+    #[inline(always)]
+    fn mov_mem32_offset32_freg32(
+        buf: &mut Vec<'_, u8>,
+        ptr: AArch64GeneralReg,
+        offset: i32,
+        src: AArch64FloatReg,
+    ) {
+        Self::mov_mem_offset32_freg(buf, FloatWidth::F32, ptr, offset, src)
+    }
+
     #[inline(always)]
     fn mov_base32_reg(
         buf: &mut Vec<'_, u8>,
@@ -1762,17 +1803,7 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
         offset: i32,
         src: AArch64FloatReg,
     ) {
-        if (-256..256).contains(&offset) {
-            stur_freg64_reg64_imm9(buf, src, dst, offset as i16)
-        } else if (0..=u16::MAX as i32).contains(&offset) {
-            debug_assert!(offset % 8 == 0);
-            str_freg64_reg64_imm12(buf, src, dst, (offset as u16) >> 3);
-        } else {
-            let tmp = AArch64GeneralReg::X15;
-            Self::mov_reg64_imm64(buf, tmp, offset as i64);
-            Self::add_reg64_reg64_reg64(buf, tmp, tmp, dst);
-            str_freg64_reg64_imm12(buf, src, tmp, 0);
-        }
+        Self::mov_mem_offset32_freg(buf, FloatWidth::F64, dst, offset, src)
     }
 
     #[inline(always)]
@@ -1875,10 +1906,18 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
     ) {
         if imm32 < 0 {
             Self::add_reg64_reg64_imm32(buf, dst, src, -imm32)
-        } else if imm32 < 0xFFF {
+        } else if imm32 <= 0xFFF {
             sub_reg64_reg64_imm12(buf, dst, src, imm32 as u16);
+        } else if imm32 <= 0xFF_FFFF {
+            // e.g. stack offsets in large frames. Unlike a register operand, this also works
+            // when src or dst is the stack pointer.
+            sub_reg64_reg64_imm12_lsl12(buf, dst, src, (imm32 >> 12) as u16);
+
+            if imm32 & 0xFFF != 0 {
+                sub_reg64_reg64_imm12(buf, dst, dst, (imm32 & 0xFFF) as u16);
+            }
         } else {
-            todo!("immediate subtractions with values greater than 12bits");
+            todo!("immediate subtractions with values greater than 24bits");
         }
     }
     #[inline(always)]
@@ -2114,38 +2153,65 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
         src: AArch64GeneralReg,
         offset: i32,
     ) {
+        Self::mov_freg_mem_offset32(buf, FloatWidth::F64, dst, src, offset)
+    }
+
+    fn mov_freg32_mem32_offset32(
+        buf: &mut Vec<'_, u8>,
+        dst: AArch64FloatReg,
+        src: AArch64GeneralReg,
+        offset: i32,
+    ) {
+        Self::mov_freg_mem_offset32(buf, FloatWidth::F32, dst, src, offset)
+    }
+}
+
+impl AArch64Assembler {
+    /// Loads a float of the given width, so that an F32 doesn't read the 4 bytes after it.
+    fn mov_freg_mem_offset32(
+        buf: &mut Vec<'_, u8>,
+        ftype: FloatWidth,
+        dst: AArch64FloatReg,
+        src: AArch64GeneralReg,
+        offset: i32,
+    ) {
+        let scale = float_width_bytes(ftype) as i32;
+
         if (-256..256).contains(&offset) {
-            ldur_freg64_reg64_imm9(buf, dst, src, offset as i16)
-        } else if (0..=u16::MAX as i32).contains(&offset) {
-            debug_assert!(offset % 8 == 0);
-            ldr_freg64_reg64_imm12(buf, dst, src, (offset as u16) >> 3);
+            ldur_freg_reg64_imm9(buf, ftype, dst, src, offset as i16)
+        } else if (0..=0xFFF * scale).contains(&offset) && offset % scale == 0 {
+            ldr_freg_reg64_imm12(buf, ftype, dst, src, (offset / scale) as u16);
         } else {
             let tmp = AArch64GeneralReg::X15;
             Self::mov_reg64_imm64(buf, tmp, offset as i64);
             Self::add_reg64_reg64_reg64(buf, tmp, tmp, src);
-            ldr_freg64_reg64_imm12(buf, dst, tmp, 0);
+            ldr_freg_reg64_imm12(buf, ftype, dst, tmp, 0);
         }
     }
 
-    fn mov_freg32_mem32_offset32(
+    /// Stores a float of the given width, so that an F32 doesn't overwrite the 4 bytes after it.
+    fn mov_mem_offset32_freg(
         buf: &mut Vec<'_, u8>,
-        dst: AArch64FloatReg,
-        src: AArch64GeneralReg,
+        ftype: FloatWidth,
+        dst: AArch64GeneralReg,
         offset: i32,
+        src: AArch64FloatReg,
     ) {
-        if offset < 0 {
-            ldur_freg64_reg64_imm9(buf, dst, src, offset as i16)
-        } else if (0..=u16::MAX as i32).contains(&offset) {
-            debug_assert!(offset % 8 == 0);
-            ldr_freg64_reg64_imm12(buf, dst, src, (offset as u16) >> 3);
+        let scale = float_width_bytes(ftype) as i32;
+
+        if (-256..256).contains(&offset) {
+            stur_freg_reg64_imm9(buf, ftype, src, dst, offset as i16)
+        } else if (0..=0xFFF * scale).contains(&offset) && offset % scale == 0 {
+            str_freg_reg64_imm12(buf, ftype, src, dst, (offset / scale) as u16);
         } else {
-            todo!("base offsets over 32k for AArch64");
+            let tmp = AArch64GeneralReg::X15;
+            Self::mov_reg64_imm64(buf, tmp, offset as i64);
+            Self::add_reg64_reg64_reg64(buf, tmp, tmp, dst);
+            str_freg_reg64_imm12(buf, ftype, src, tmp, 0);
         }
     }
 }
 
-impl AArch64Assembler {}
-
 // Instructions
 // ARM manual section C3
 // https://developer.arm.com/documentation/ddi0487/ga
@@ -2944,6 +3010,18 @@ impl AdvancedSimdModifiedImmediate {
     }
 }
 
+/// The `size` field of a load or store of a float with this width.
+fn encode_float_load_store_size(width: FloatWidth) -> u8 {
+    match width {
+        FloatWidth::F32 => 0b10,
+        FloatWidth::F64 => 0b11,
+    }
+}
+
+fn float_width_bytes(width: FloatWidth) -> u8 {
+    1 << encode_float_load_store_size(width)
+}
+
 fn encode_float_width(width: FloatWidth) -> u8 {
     match width {
         FloatWidth::F32 => 0b00,
@@ -3236,6 +3314,26 @@ fn add_reg64_reg64_imm12(
     buf.extend(inst.bytes());
 }
 
+/// `ADD Xd, Xn, imm12, LSL #12` -> Add Xn and imm12 shifted left by 12 bits and place the result into Xd.
+#[inline(always)]
+fn add_reg64_reg64_imm12_lsl12(
+    buf: &mut Vec<'_, u8>,
+    dst: AArch64GeneralReg,
+    src: AArch64GeneralReg,
+    imm12: u16,
+) {
+    let inst = ArithmeticImmediate::new(ArithmeticImmediateParams {
+        op: false,
+        s: false,
+        sh: true,
+        imm12,
+        rd: dst,
+        rn: src,
+    });
+
+    buf.extend(inst.bytes());
+}
+
 /// `ADD Xd, Xm, Xn` -> Add Xm and Xn and place the result into Xd.
 #[inline(always)]
 fn add_reg64_reg64_reg64(
@@ -3495,17 +3593,18 @@ fn ldur_reg_reg_imm9(
     buf.extend(inst.bytes());
 }
 
-/// `LDR Xt, [Xn, #offset]` -> Load Xn + Offset Xt. ZRSP is SP.
-/// Note: imm12 is the offest divided by 8.
+/// `LDR Dt, [Xn, #offset]` -> Load Xn + Offset Dt (or St for an F32). ZRSP is SP.
+/// Note: imm12 is the offest divided by the size of the float.
 #[inline(always)]
-fn ldr_freg64_reg64_imm12(
+fn ldr_freg_reg64_imm12(
     buf: &mut Vec<'_, u8>,
+    ftype: FloatWidth,
     dst: AArch64FloatReg,
     base: AArch64GeneralReg,
     imm12: u16,
 ) {
     let inst = LoadStoreRegisterImmediate {
-        size: 0b11.into(), // 64-bit
+        size: encode_float_load_store_size(ftype).into(),
         fixed: 0b111.into(),
         fixed2: true,
         fixed3: 0b01.into(),
@@ -3519,8 +3618,9 @@ fn ldr_freg64_reg64_imm12(
 }
 
 #[inline(always)]
-fn ldur_freg64_reg64_imm9(
+fn ldur_freg_reg64_imm9(
     buf: &mut Vec<'_, u8>,
+    ftype: FloatWidth,
     dst: AArch64FloatReg,
     base: AArch64GeneralReg,
     imm9: i16,
@@ -3532,7 +3632,7 @@ fn ldur_freg64_reg64_imm9(
     let imm12 = (imm9 & 0b0001_1111_1111) << 2;
 
     let inst = LoadStoreRegisterImmediate {
-        size: 0b11.into(), // 64-bit
+        size: encode_float_load_store_size(ftype).into(),
         fixed: 0b111.into(),
         fixed2: true,
         fixed3: 0b00.into(),
@@ -3740,8 +3840,9 @@ fn str_reg_reg_imm12(
 }
 
 #[inline(always)]
-fn stur_freg64_reg64_imm9(
+fn stur_freg_reg64_imm9(
     buf: &mut Vec<'_, u8>,
+    ftype: FloatWidth,
     src: AArch64FloatReg,
     base: AArch64GeneralReg,
     imm9: i16,
@@ -3753,7 +3854,7 @@ fn stur_freg64_reg64_imm9(
     let imm12 = (imm9 & 0b0001_1111_1111) << 2;
 
     let inst = LoadStoreRegisterImmediate {
-        size: 0b11.into(), // 64-bit
+        size: encode_float_load_store_size(ftype).into(),
         fixed: 0b111.into(),
         fixed2: true,
         fixed3: 0b00.into(),
@@ -3766,15 +3867,18 @@ fn stur_freg64_reg64_imm9(
     buf.extend(inst.bytes());
 }
 
+/// `STR Dt, [Xn, #offset]` -> Store Dt (or St for an F32) to Xn + Offset. ZRSP is SP.
+/// Note: imm12 is the offest divided by the size of the float.
 #[inline(always)]
-fn str_freg64_reg64_imm12(
+fn str_freg_reg64_imm12(
     buf: &mut Vec<'_, u8>,
+    ftype: FloatWidth,
     src: AArch64FloatReg,
     base: AArch64GeneralReg,
     imm12: u16,
 ) {
     let inst = LoadStoreRegisterImmediate {
-        size: 0b11.into(), // 64-bit
+        size: encode_float_load_store_size(ftype).into(),
         fixed: 0b111.into(),
         fixed2: true,
         fixed3: 0b01.into(),
@@ -3807,6 +3911,26 @@ fn sub_reg64_reg64_imm12(
     buf.extend(inst.bytes());
 }
 
+/// `SUB Xd, Xn, imm12, LSL #12` -> Subtract Xn and imm12 shifted left by 12 bits and place the result into Xd.
+#[inline(always)]
+fn sub_reg64_reg64_imm12_lsl12(
+    buf: &mut Vec<'_, u8>,
+    dst: AArch64GeneralReg,
+    src: AArch64GeneralReg,
+    imm12: u16,
+) {
+    let inst = ArithmeticImmediate::new(ArithmeticImmediateParams {
+        op: true,
+        s: false,
+        rd: dst,
+        rn: src,
+        imm12,
+        sh: true,
+    });
+
+    buf.extend(inst.bytes());
+}
+
 /// `SUB Xd, Xm, Xn` -> Subtract Xm and Xn and place the result into Xd.
 #[inline(always)]
 fn sub_reg64_reg64_reg64(
@@ -4547,6 +4671,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_add_reg64_reg64_imm12_lsl12() {
+        disassembler_test!(
+            add_reg64_reg64_imm12_lsl12,
+            |reg1: AArch64GeneralReg, reg2: AArch64GeneralReg, imm| format!(
+                "add {}, {}, #0x{:x}, lsl #12",
+                reg1.capstone_string(UsesSP),
+                reg2.capstone_string(UsesSP),
+                imm
+            ),
+            ALL_GENERAL_REGS,
+            ALL_GENERAL_REGS,
+            [0x123]
+        );
+    }
+
     #[test]
     fn test_and_reg64_reg64_reg64() {
         disassembler_test!(
@@ -4781,15 +4921,16 @@ mod tests {
     }
 
     #[test]
-    fn test_ldr_freg64_reg64_imm12() {
+    fn test_ldr_freg_reg64_imm12() {
         disassembler_test!(
-            ldr_freg64_reg64_imm12,
-            |reg1: AArch64FloatReg, reg2: AArch64GeneralReg, imm| format!(
+            ldr_freg_reg64_imm12,
+            |ftype: FloatWidth, reg1: AArch64FloatReg, reg2: AArch64GeneralReg, imm: u16| format!(
                 "ldr {}, [{}, #0x{:x}]",
-                reg1.capstone_string(FloatWidth::F64),
+                reg1.capstone_string(ftype),
                 reg2.capstone_string(UsesSP),
-                imm << 3
+                imm * float_width_bytes(ftype) as u16
             ),
+            ALL_FLOAT_TYPES,
             ALL_FLOAT_REGS,
             ALL_GENERAL_REGS,
             [0x123]
@@ -4797,15 +4938,16 @@ mod tests {
     }
 
     #[test]
-    fn test_ludr_freg64_reg64_imm9() {
+    fn test_ldur_freg_reg64_imm9() {
         disassembler_test!(
-            ldur_freg64_reg64_imm9,
-            |reg1: AArch64FloatReg, reg2: AArch64GeneralReg, imm| format!(
+            ldur_freg_reg64_imm9,
+            |ftype: FloatWidth, reg1: AArch64FloatReg, reg2: AArch64GeneralReg, imm| format!(
                 "ldur {}, [{}, {}]",
-                reg1.capstone_string(FloatWidth::F64),
+                reg1.capstone_string(ftype),
                 reg2.capstone_string(UsesSP),
                 signed_hex_i16(imm)
             ),
+            ALL_FLOAT_TYPES,
             ALL_FLOAT_REGS,
             ALL_GENERAL_REGS,
             [4, -4]
@@ -5033,15 +5175,16 @@ mod tests {
     }
 
     #[test]
-    fn test_str_freg64_reg64_imm12() {
+    fn test_str_freg_reg64_imm12() {
         disassembler_test!(
-            str_freg64_reg64_imm12,
-            |reg1: AArch64FloatReg, reg2: AArch64GeneralReg, imm| format!(
+            str_freg_reg64_imm12,
+            |ftype: FloatWidth, reg1: AArch64FloatReg, reg2: AArch64GeneralReg, imm: u16| format!(
                 "str {}, [{}, #0x{:x}]",
-                reg1.capstone_string(FloatWidth::F64),
+                reg1.capstone_string(ftype),
                 reg2.capstone_string(UsesSP),
-                imm << 3
+                imm * float_width_bytes(ftype) as u16
             ),
+            ALL_FLOAT_TYPES,
             ALL_FLOAT_REGS,
             ALL_GENERAL_REGS,
             [0x123]
@@ -5049,15 +5192,16 @@ mod tests {
     }
 
     #[test]
-    fn test_stur_freg64_reg64_imm9() {
+    fn test_stur_freg_reg64_imm9() {
         disassembler_test!(
-            stur_freg64_reg64_imm9,
-            |reg1: AArch64FloatReg, reg2: AArch64GeneralReg, imm| format!(
+            stur_freg_reg64_imm9,
+            |ftype: FloatWidth, reg1: AArch64FloatReg, reg2: AArch64GeneralReg, imm| format!(
                 "stur {}, [{}, {}]",
-                reg1.capstone_string(FloatWidth::F64),
+                reg1.capstone_string(ftype),
                 reg2.capstone_string(UsesSP),
-                signed_hex_i16(imm),
+                signed_hex_i16(imm)
             ),
+            ALL_FLOAT_TYPES,
             ALL_FLOAT_REGS,
             ALL_GENERAL_REGS,
             [4, -4]
@@ -5097,6 +5241,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_sub_reg64_reg64_imm12_lsl12() {
+        disassembler_test!(
+            sub_reg64_reg64_imm12_lsl12,
+            |reg1: AArch64GeneralReg, reg2: AArch64GeneralReg, imm| format!(
+                "sub {}, {}, #0x{:x}, lsl #12",
+                reg1.capstone_string(UsesSP),
+                reg2.capstone_string(UsesSP),
+                imm
+            ),
+            ALL_GENERAL_REGS,
+            ALL_GENERAL_REGS,
+            [0x123]
+        );
+    }
+
     #[test]
     fn test_sub_reg64_reg64_reg64() {
         disassembler_test!(
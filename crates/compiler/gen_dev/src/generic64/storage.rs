@@ -835,7 +835,7 @@ impl<
                     FloatWidth::F32 => {
                         debug_assert_eq!(to_offset % 4, 0);
                         let reg = self.load_to_float_reg(buf, sym);
-                        ASM::mov_base32_freg64(buf, to_offset, reg);
+                        ASM::mov_base32_freg32(buf, to_offset, reg);
                     }
                 },
                 Builtin::Bool => {
@@ -2478,6 +2478,16 @@ impl Assembler<X86_64GeneralReg, X86_64FloatReg> for X86_64Assembler {
         movsd_base64_offset32_freg64(buf, ptr, offset, src)
     }
 
+    #[inline(always)]
+    fn mov_mem32_offset32_freg32(
+        buf: &mut Vec<'_, u8>,
+        ptr: X86_64GeneralReg,
+        offset: i32,
+        src: X86_64FloatReg,
+    ) {
+        movss_base32_offset32_freg32(buf, ptr, offset, src)
+    }
+
     #[inline(always)]
     fn mov_base32_reg(
         buf: &mut Vec<'_, u8>,
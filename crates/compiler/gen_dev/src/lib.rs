@@ -346,32 +346,18 @@ trait Backend<'a> {
         &mut Vec<'a, CallerProc<'a>>,
     );
 
-    fn lambda_name_to_string<'b, I>(
+    fn lambda_name_to_string<I>(
         &self,
-        name: LambdaName,
+        name: LambdaName<'a>,
         arguments: I,
         _lambda_set: Option<InLayout>,
-        result: InLayout,
+        result: InLayout<'a>,
     ) -> String
     where
-        I: Iterator<Item = InLayout<'b>>,
+        I: Iterator<Item = InLayout<'a>>,
     {
-        use std::hash::{BuildHasher, Hash, Hasher};
-
         let symbol = name.name();
 
-        // NOTE: due to randomness, this will not be consistent between runs
-        let mut state = roc_collections::all::BuildHasher::default().build_hasher();
-        for a in arguments {
-            a.hash(&mut state);
-        }
-
-        // lambda set should not matter; it should already be added as an argument
-        // but the niche of the lambda name may be the only thing differentiating two different
-        // implementations of a function with the same symbol
-        name.niche().hash(&mut state);
-        result.hash(&mut state);
-
         let interns = self.interns();
         let ident_string = symbol.as_str(interns);
         let module_string = interns.module_ids.get_name(symbol.module_id()).unwrap();
@@ -381,7 +367,19 @@ trait Backend<'a> {
         if ident_string.contains("#help") {
             format!("{module_string}_{ident_string}_1")
         } else {
-            format!("{}_{}_{}", module_string, ident_string, state.finish())
+            // lambda set should not matter; it should already be added as an argument
+            // but the niche of the lambda name may be the only thing differentiating two different
+            // implementations of a function with the same symbol
+            let arguments: std::vec::Vec<_> = arguments.collect();
+
+            roc_mono::mangle::mangle_specialization(
+                self.interner(),
+                interns,
+                symbol,
+                &arguments,
+                result,
+                name.niche(),
+            )
         }
     }
 
@@ -1457,29 +1455,34 @@ trait Backend<'a> {
                 self.build_num_gte(sym, &args[0], &args[1], &arg_layouts[0])
             }
             LowLevel::NumLogUnchecked => {
-                let float_width = match arg_layouts[0] {
-                    Layout::F64 => FloatWidth::F64,
-                    Layout::F32 => FloatWidth::F32,
-                    _ => unreachable!("invalid layout for sqrt"),
+                let intrinsic = match arg_layouts[0] {
+                    Layout::F64 => &bitcode::NUM_LOG[FloatWidth::F64],
+                    Layout::F32 => &bitcode::NUM_LOG[FloatWidth::F32],
+                    Layout::DEC => bitcode::DEC_LOG,
+                    _ => unreachable!("invalid layout for log"),
                 };
 
-                self.build_fn_call(
+                self.build_fn_call(sym, intrinsic.to_string(), args, arg_layouts, ret_layout)
+            }
+            LowLevel::NumExpDec => self.build_fn_call(
+                sym,
+                bitcode::DEC_EXP.to_string(),
+                args,
+                arg_layouts,
+                ret_layout,
+            ),
+            LowLevel::NumSqrtUnchecked => match arg_layouts[0] {
+                Layout::F64 => self.build_num_sqrt(*sym, args[0], FloatWidth::F64),
+                Layout::F32 => self.build_num_sqrt(*sym, args[0], FloatWidth::F32),
+                Layout::DEC => self.build_fn_call(
                     sym,
-                    bitcode::NUM_LOG[float_width].to_string(),
+                    bitcode::DEC_SQRT.to_string(),
                     args,
                     arg_layouts,
                     ret_layout,
-                )
-            }
-            LowLevel::NumSqrtUnchecked => {
-                let float_width = match arg_layouts[0] {
-                    Layout::F64 => FloatWidth::F64,
-                    Layout::F32 => FloatWidth::F32,
-                    _ => unreachable!("invalid layout for sqrt"),
-                };
-
-                self.build_num_sqrt(*sym, args[0], float_width);
-            }
+                ),
+                _ => unreachable!("invalid layout for sqrt"),
+            },
             LowLevel::NumSin => {
                 let intrinsic = match arg_layouts[0] {
                     Layout::F64 => &bitcode::NUM_SIN[FloatWidth::F64],
@@ -1544,6 +1547,19 @@ trait Backend<'a> {
                 let elem_layout = list_element_layout!(self.interner(), *ret_layout);
                 self.build_list_clone(*sym, args[0], elem_layout, *ret_layout)
             }
+            LowLevel::ListContainsNum => {
+                let intrinsic = match self.interner().get_repr(arg_layouts[1]) {
+                    LayoutRepr::Builtin(Builtin::Int(int_width)) => {
+                        &bitcode::LIST_CONTAINS_INT[int_width]
+                    }
+                    LayoutRepr::Builtin(Builtin::Float(float_width)) => {
+                        &bitcode::LIST_CONTAINS_FLOAT[float_width]
+                    }
+                    _ => unreachable!("invalid element layout for ListContainsNum"),
+                };
+
+                self.build_fn_call(sym, intrinsic.to_string(), args, arg_layouts, ret_layout)
+            }
             LowLevel::ListReserve => {
                 debug_assert_eq!(
                     2,
@@ -1600,6 +1616,13 @@ trait Backend<'a> {
                 arg_layouts,
                 ret_layout,
             ),
+            LowLevel::StrConcatAmortized => self.build_fn_call(
+                sym,
+                bitcode::STR_CONCAT_AMORTIZED.to_string(),
+                args,
+                arg_layouts,
+                ret_layout,
+            ),
             LowLevel::StrJoinWith => self.build_fn_call(
                 sym,
                 bitcode::STR_JOIN_WITH.to_string(),
@@ -1740,6 +1763,27 @@ trait Backend<'a> {
                 arg_layouts,
                 ret_layout,
             ),
+            LowLevel::StrWithUppercased => self.build_fn_call(
+                sym,
+                bitcode::STR_WITH_UPPERCASED.to_string(),
+                args,
+                arg_layouts,
+                ret_layout,
+            ),
+            LowLevel::StrWithLowercased => self.build_fn_call(
+                sym,
+                bitcode::STR_WITH_LOWERCASED.to_string(),
+                args,
+                arg_layouts,
+                ret_layout,
+            ),
+            LowLevel::StrCaselessEquals => self.build_fn_call(
+                sym,
+                bitcode::STR_CASELESS_EQUALS.to_string(),
+                args,
+                arg_layouts,
+                ret_layout,
+            ),
             LowLevel::StrToNum => {
                 let number_layout = match self.interner().get_repr(*ret_layout) {
                     LayoutRepr::Struct(field_layouts) => field_layouts[0], // TODO: why is it sometimes a struct?
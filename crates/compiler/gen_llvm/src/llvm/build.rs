@@ -45,6 +45,7 @@ use roc_debug_flags::dbg_do;
 use roc_debug_flags::ROC_PRINT_LLVM_FN_VERIFICATION;
 use roc_error_macros::{internal_error, todo_lambda_erasure};
 use roc_module::symbol::{Interns, ModuleId, Symbol};
+use roc_mono::coverage::{CoverageMap, COVERAGE_COUNTERS};
 use roc_mono::ir::{
     BranchInfo, CallType, CrashTag, EntryPoint, GlueLayouts, HostExposedLambdaSet,
     HostExposedLambdaSets, ListLiteralElement, ModifyRc, OptLevel, ProcLayout, SingleEntryPoint,
@@ -53,8 +54,10 @@ use roc_mono::layout::{
     Builtin, InLayout, LambdaName, LambdaSet, Layout, LayoutIds, LayoutInterner, LayoutRepr, Niche,
     RawFunctionLayout, STLayoutInterner, TagIdIntType, UnionLayout,
 };
+use roc_mono::profile::{ProcHint, ProfileHints};
 use roc_std::RocDec;
 use roc_target::{PtrWidth, Target};
+use std::cell::RefCell;
 use std::convert::TryInto;
 use std::path::Path;
 
@@ -741,6 +744,16 @@ pub struct Env<'a, 'ctx, 'env> {
     pub target: Target,
     pub mode: LlvmBackendMode,
     pub exposed_to_host: MutSet<Symbol>,
+    /// When set, procs and switch branches increment coverage counters as they are executed.
+    pub coverage: Option<&'a CoverageMap>,
+    /// When set, procs are marked for inlining (or not) based on how often they were called
+    /// in a profiled run.
+    pub profile_hints: Option<&'a ProfileHints>,
+    /// Emit procs in an order that only depends on the source code, rather than on
+    /// e.g. hash map iteration order, so that builds are reproducible.
+    pub deterministic: bool,
+    /// The mangled names of the procs that have been declared so far.
+    pub proc_names: ProcNames,
 }
 
 impl<'a, 'ctx, 'env> Env<'a, 'ctx, 'env> {
@@ -954,6 +967,8 @@ impl<'a, 'ctx, 'env> Env<'a, 'ctx, 'env> {
             inkwell::module::FlagBehavior::Warning,
             debug_metadata_version,
         );
+        // The filename and directory are relative on purpose: absolute paths would make the
+        // binary depend on where it was built, which breaks reproducible builds.
         module.create_debug_info_builder(
             true,
             /* language */ inkwell::debug_info::DWARFSourceLanguage::C,
@@ -974,6 +989,16 @@ impl<'a, 'ctx, 'env> Env<'a, 'ctx, 'env> {
     }
 
     pub fn new_subprogram(&self, function_name: &str) -> DISubprogram<'ctx> {
+        self.new_subprogram_with_linkage_name(function_name, None)
+    }
+
+    /// A subprogram that debuggers show as `function_name`, for a function whose symbol is
+    /// `linkage_name`.
+    pub fn new_subprogram_with_linkage_name(
+        &self,
+        function_name: &str,
+        linkage_name: Option<&str>,
+    ) -> DISubprogram<'ctx> {
         let dibuilder = self.dibuilder;
         let compile_unit = self.compile_unit;
 
@@ -996,7 +1021,7 @@ impl<'a, 'ctx, 'env> Env<'a, 'ctx, 'env> {
         dibuilder.create_function(
             /* scope */ compile_unit.get_file().as_debug_info_scope(),
             /* func name */ function_name,
-            /* linkage_name */ None,
+            /* linkage_name */ linkage_name,
             /* file */ compile_unit.get_file(),
             /* line_no */ 0,
             /* DIType */ subroutine_type,
@@ -2904,9 +2929,10 @@ fn list_literal<'a, 'ctx>(
         global.set_unnamed_addr(true);
         global.set_linkage(inkwell::module::Linkage::Private);
 
-        // Refcount the global itself in a new allocation.
-        // Necessary because morphic MAY attempt to update the global in-place, which is
-        // illegal if the global is in the read-only section.
+        // The list points straight into the read-only global. The zeroed slot in front of the
+        // elements is the static-data refcount sentinel, so incref/decref skip the list and it
+        // is never considered unique: any mutation copies it to the heap first. Alias analysis
+        // models all-literal lists as static, so morphic never picks an in-place update for them.
         let with_rc_ptr = global.as_pointer_value();
 
         let const_data_ptr = unsafe {
@@ -2920,16 +2946,7 @@ fn list_literal<'a, 'ctx>(
             )
         };
 
-        let data_ptr = allocate_list(env, layout_interner, element_layout, list_length_intval);
-
-        let byte_size = env
-            .ptr_int()
-            .const_int(list_length as u64 * element_width as u64, false);
-        builder
-            .build_memcpy(data_ptr, alignment, const_data_ptr, alignment, byte_size)
-            .unwrap();
-
-        super::build_list::store_list(env, data_ptr, list_length_intval).into()
+        super::build_list::store_list(env, const_data_ptr, list_length_intval).into()
     } else {
         let ptr = allocate_list(env, layout_interner, element_layout, list_length_intval);
 
@@ -4001,6 +4018,12 @@ fn build_switch_ir<'a, 'ctx>(
                 {
                     builder.position_at_end(then_block);
 
+                    let tag = match branches {
+                        [(1, _, _)] => Some(1),
+                        _ => None,
+                    };
+                    increment_branch_counter(env, *cond_symbol, tag);
+
                     let branch_val = build_exp_stmt(
                         env,
                         layout_interner,
@@ -4020,6 +4043,12 @@ fn build_switch_ir<'a, 'ctx>(
                 {
                     builder.position_at_end(else_block);
 
+                    let tag = match branches {
+                        [(0, _, _)] => Some(0),
+                        _ => None,
+                    };
+                    increment_branch_counter(env, *cond_symbol, tag);
+
                     let branch_val = build_exp_stmt(
                         env,
                         layout_interner,
@@ -4071,9 +4100,11 @@ fn build_switch_ir<'a, 'ctx>(
 
         builder.new_build_switch(cond, default_block, &cases);
 
-        for ((_, _, branch_expr), (_, block)) in branches.iter().zip(cases) {
+        for ((tag, _, branch_expr), (_, block)) in branches.iter().zip(cases) {
             builder.position_at_end(block);
 
+            increment_branch_counter(env, *cond_symbol, Some(*tag));
+
             let branch_val = build_exp_stmt(
                 env,
                 layout_interner,
@@ -4093,6 +4124,8 @@ fn build_switch_ir<'a, 'ctx>(
         // The block for the conditional's default branch.
         builder.position_at_end(default_block);
 
+        increment_branch_counter(env, *cond_symbol, None);
+
         let default_val = build_exp_stmt(
             env,
             layout_interner,
@@ -5316,6 +5349,21 @@ pub(crate) fn build_proc_headers<'a, 'r, 'ctx>(
     roc_mono::ir::Proc<'a>,
     std::vec::Vec<(&'a FuncSpecSolutions, FunctionValue<'ctx>)>,
 )> {
+    let mut procedures: std::vec::Vec<_> = procedures.into_iter().collect();
+
+    if env.deterministic {
+        // The order in which headers are built determines both the order of the functions
+        // in the object file and the layout IDs in their names. Symbols are interned
+        // in whatever order the modules happened to be loaded in, so sort by name instead.
+        procedures.sort_by_cached_key(|((symbol, _), proc)| {
+            (
+                symbol.module_string(&env.interns).as_str(),
+                symbol.as_str(&env.interns),
+                roc_alias_analysis::func_name_bytes(proc),
+            )
+        });
+    }
+
     // Populate Procs further and get the low-level Expr from the canonical Expr
     let mut headers = std::vec::Vec::with_capacity(procedures.len());
     for ((symbol, layout), proc) in procedures {
@@ -5330,15 +5378,30 @@ pub(crate) fn build_proc_headers<'a, 'r, 'ctx>(
         let is_erased = proc.is_erased;
         debug_assert!(!is_erased || func_solutions.specs().count() == 1);
 
-        for specialization in it {
+        let mangled = roc_mono::mangle::mangle_proc(layout_interner, &env.interns, symbol, &layout);
+
+        // The specializations come out of a hash map, so put them in a consistent order for
+        // their names
+        let mut specializations: std::vec::Vec<_> = it.collect();
+        specializations.sort();
+        let spec_count = specializations.len();
+
+        for (index, specialization) in specializations.into_iter().enumerate() {
             let func_spec = if is_erased {
                 FuncBorrowSpec::Erased
             } else {
                 FuncBorrowSpec::Some(*specialization)
             };
 
+            let fn_name = env.proc_names.declare(
+                symbol,
+                func_spec,
+                &mangled,
+                (spec_count > 1).then_some(index),
+            );
+
             let fn_val =
-                build_proc_header(env, layout_interner, func_spec, symbol, &proc, layout_ids);
+                build_proc_header(env, layout_interner, &fn_name, symbol, &proc, layout_ids);
 
             if proc.args.is_empty() {
                 // this is a 0-argument thunk, i.e. a top-level constant definition
@@ -5678,6 +5741,7 @@ fn build_procedures_help<'a>(
     mod_solutions
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum FuncBorrowSpec {
     /// This function has an specialization due to alias analysis.
     Some(FuncSpec),
@@ -5686,36 +5750,58 @@ pub enum FuncBorrowSpec {
     Erased,
 }
 
-fn func_spec_name<'a>(
-    arena: &'a Bump,
-    interns: &Interns,
-    symbol: Symbol,
-    func_spec: FuncBorrowSpec,
-) -> bumpalo::collections::String<'a> {
-    use std::fmt::Write;
-
-    let mut buf = bumpalo::collections::String::with_capacity_in(1, arena);
+/// The names procs are declared with, by the specialization they implement.
+///
+/// Names are mangled with [roc_mono::mangle], so they stay the same between builds and can be
+/// read back with `roc demangle`.
+#[derive(Debug, Default)]
+pub struct ProcNames {
+    names: RefCell<MutMap<(Symbol, FuncBorrowSpec), String>>,
+    taken: RefCell<MutSet<String>>,
+}
 
-    let ident_string = symbol.as_str(interns);
-    let module_string = interns.module_ids.get_name(symbol.module_id()).unwrap();
-    write!(buf, "{module_string}_{ident_string}_").unwrap();
+impl ProcNames {
+    /// Pick the name for one specialization of a proc. `spec_index` tells apart the
+    /// specializations alias analysis made for the same layout, if there is more than one.
+    fn declare(
+        &self,
+        symbol: Symbol,
+        func_spec: FuncBorrowSpec,
+        mangled: &str,
+        spec_index: Option<usize>,
+    ) -> String {
+        let mut name = match spec_index {
+            Some(index) => format!("{mangled}${index}"),
+            None => mangled.to_string(),
+        };
 
-    match func_spec {
-        FuncBorrowSpec::Some(func_spec) => {
-            for byte in func_spec.0.iter() {
-                write!(buf, "{byte:x?}").unwrap();
-            }
+        // Two different layouts can only hash the same by accident, but LLVM would quietly
+        // rename the second function if that happened, and then we'd fail to find it.
+        let mut taken = self.taken.borrow_mut();
+        let base = name.clone();
+        let mut collisions = 0;
+        while taken.contains(&name) {
+            collisions += 1;
+            name = format!("{base}$c{collisions}");
         }
-        FuncBorrowSpec::Erased => write!(buf, "erased").unwrap(),
+        taken.insert(name.clone());
+
+        self.names
+            .borrow_mut()
+            .insert((symbol, func_spec), name.clone());
+
+        name
     }
 
-    buf
+    fn get(&self, symbol: Symbol, func_spec: FuncBorrowSpec) -> Option<String> {
+        self.names.borrow().get(&(symbol, func_spec)).cloned()
+    }
 }
 
 fn build_proc_header<'a, 'ctx>(
     env: &Env<'a, 'ctx, '_>,
     layout_interner: &STLayoutInterner<'a>,
-    func_spec: FuncBorrowSpec,
+    fn_name: &str,
     symbol: Symbol,
     proc: &roc_mono::ir::Proc<'a>,
     layout_ids: &mut LayoutIds<'a>,
@@ -5723,8 +5809,6 @@ fn build_proc_header<'a, 'ctx>(
     let args = proc.args;
     let arena = env.arena;
 
-    let fn_name = func_spec_name(env.arena, &env.interns, symbol, func_spec);
-
     let ret_type = basic_type_from_layout(
         env,
         layout_interner,
@@ -5743,15 +5827,13 @@ fn build_proc_header<'a, 'ctx>(
         RocReturn::from_layout(layout_interner, layout_interner.get_repr(proc.ret_layout));
     let fn_spec = FunctionSpec::fastcc(env, roc_return, ret_type, arg_basic_types);
 
-    let fn_val = add_func(
-        env.context,
-        env.module,
-        fn_name.as_str(),
-        fn_spec,
-        Linkage::Internal,
-    );
+    let fn_val = add_func(env.context, env.module, fn_name, fn_spec, Linkage::Internal);
 
-    let subprogram = env.new_subprogram(&fn_name);
+    let debug_name = match roc_mono::mangle::demangle(fn_name) {
+        Some(demangled) => demangled.to_string(),
+        None => fn_name.to_string(),
+    };
+    let subprogram = env.new_subprogram_with_linkage_name(&debug_name, Some(fn_name));
     fn_val.set_subprogram(subprogram);
 
     debug_info_init!(env, fn_val);
@@ -5770,15 +5852,17 @@ fn build_proc_header<'a, 'ctx>(
         );
     }
 
-    if false {
-        let kind_id = Attribute::get_named_enum_kind_id("alwaysinline");
-        debug_assert!(kind_id > 0);
-        let enum_attr = env.context.create_enum_attribute(kind_id, 0);
-        fn_val.add_attribute(AttributeLoc::Function, enum_attr);
-    }
+    let hint = env.profile_hints.and_then(|hints| hints.get(symbol));
+
+    let attribute_names: &[&str] = match hint {
+        None => &[],
+        Some(ProcHint::AlwaysInline) => &["alwaysinline"],
+        Some(ProcHint::InlineHint) => &["inlinehint"],
+        Some(ProcHint::Cold) => &["cold", "noinline", "optsize"],
+    };
 
-    if false {
-        let kind_id = Attribute::get_named_enum_kind_id("noinline");
+    for name in attribute_names {
+        let kind_id = Attribute::get_named_enum_kind_id(name);
         debug_assert!(kind_id > 0);
         let enum_attr = env.context.create_enum_attribute(kind_id, 0);
         fn_val.add_attribute(AttributeLoc::Function, enum_attr);
@@ -6048,6 +6132,13 @@ fn build_proc<'a, 'ctx>(
 
     debug_info_init!(env, fn_val);
 
+    if let Some(counter) = env
+        .coverage
+        .and_then(|map| map.proc_counter(proc.name.name()))
+    {
+        increment_coverage_counter(env, counter);
+    }
+
     // Add args to scope
     for (arg_val, (layout, arg_symbol)) in fn_val.get_param_iter().zip(args) {
         arg_val.set_name(arg_symbol.as_str(&env.interns));
@@ -6072,6 +6163,52 @@ fn build_proc<'a, 'ctx>(
     }
 }
 
+/// Add the global array of coverage counters that instrumented code increments.
+/// It's exported, so that whoever runs the program can read the counts afterwards.
+pub fn add_coverage_counters(env: &Env<'_, '_, '_>, coverage: &CoverageMap) {
+    let typ = env.context.i64_type().array_type(coverage.len() as u32);
+    let global = env.module.add_global(typ, None, COVERAGE_COUNTERS);
+
+    global.set_initializer(&typ.const_zero());
+    global.set_linkage(Linkage::External);
+}
+
+fn increment_coverage_counter(env: &Env<'_, '_, '_>, counter: u32) {
+    let i64_type = env.context.i64_type();
+    let global = env
+        .module
+        .get_global(COVERAGE_COUNTERS)
+        .expect("coverage counters were added before building procs");
+
+    let counter_ptr = unsafe {
+        env.builder.new_build_in_bounds_gep(
+            i64_type,
+            global.as_pointer_value(),
+            &[i64_type.const_int(counter as u64, false)],
+            "coverage_counter_ptr",
+        )
+    };
+
+    let count = env
+        .builder
+        .new_build_load(i64_type, counter_ptr, "coverage_count")
+        .into_int_value();
+    let incremented =
+        env.builder
+            .new_build_int_add(count, i64_type.const_int(1, false), "coverage_count_inc");
+
+    env.builder.new_build_store(counter_ptr, incremented);
+}
+
+fn increment_branch_counter(env: &Env<'_, '_, '_>, cond_symbol: Symbol, tag: Option<u64>) {
+    if let Some(counter) = env
+        .coverage
+        .and_then(|map| map.branch_counter(cond_symbol, tag))
+    {
+        increment_coverage_counter(env, counter);
+    }
+}
+
 pub fn verify_fn(fn_val: FunctionValue<'_>) {
     if !fn_val.verify(print_fn_verification_output()) {
         unsafe {
@@ -6087,10 +6224,12 @@ pub(crate) fn function_value_by_func_spec<'ctx>(
     func_spec: FuncBorrowSpec,
     symbol: Symbol,
 ) -> FunctionValue<'ctx> {
-    let fn_name = func_spec_name(env.arena, &env.interns, symbol, func_spec);
-    let fn_name = fn_name.as_str();
+    let fn_name = env
+        .proc_names
+        .get(symbol, func_spec)
+        .unwrap_or_else(|| internal_error!("No proc was declared for {symbol:?} ({func_spec:?})"));
 
-    function_value_by_name_help(env, symbol, fn_name)
+    function_value_by_name_help(env, symbol, &fn_name)
 }
 
 fn function_value_by_name_help<'ctx>(
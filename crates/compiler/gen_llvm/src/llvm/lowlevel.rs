@@ -126,6 +126,18 @@ pub(crate) fn run_low_level<'a, 'ctx>(
                 bitcode::STR_CONCAT,
             )
         }
+        StrConcatAmortized => {
+            // Str.concat : Str, Str -> Str, where the result replaces the first argument
+            arguments!(string1, string2);
+
+            call_str_bitcode_fn(
+                env,
+                &[string1, string2],
+                &[],
+                BitcodeReturns::Str,
+                bitcode::STR_CONCAT_AMORTIZED,
+            )
+        }
         StrJoinWith => {
             // Str.joinWith : List Str, Str -> Str
             arguments!(list, string);
@@ -648,6 +660,39 @@ pub(crate) fn run_low_level<'a, 'ctx>(
                 bitcode::STR_CASELESS_ASCII_EQUALS,
             )
         }
+        StrWithUppercased => {
+            arguments!(string);
+
+            call_str_bitcode_fn(
+                env,
+                &[string],
+                &[],
+                BitcodeReturns::Str,
+                bitcode::STR_WITH_UPPERCASED,
+            )
+        }
+        StrWithLowercased => {
+            arguments!(string);
+
+            call_str_bitcode_fn(
+                env,
+                &[string],
+                &[],
+                BitcodeReturns::Str,
+                bitcode::STR_WITH_LOWERCASED,
+            )
+        }
+        StrCaselessEquals => {
+            arguments!(string1, string2);
+
+            call_str_bitcode_fn(
+                env,
+                &[string1, string2],
+                &[],
+                BitcodeReturns::Basic,
+                bitcode::STR_CASELESS_EQUALS,
+            )
+        }
         ListConcat => {
             debug_assert_eq!(args.len(), 2);
 
@@ -843,6 +888,28 @@ pub(crate) fn run_low_level<'a, 'ctx>(
                 bitcode::LIST_IS_UNIQUE,
             )
         }
+        ListContainsNum => {
+            // List.contains : List a, a -> Bool, for lists of numbers
+            arguments_with_layouts!((list, _list_layout), (needle, needle_layout));
+
+            let intrinsic = match layout_interner.get_repr(needle_layout) {
+                LayoutRepr::Builtin(Builtin::Int(int_width)) => {
+                    &bitcode::LIST_CONTAINS_INT[int_width]
+                }
+                LayoutRepr::Builtin(Builtin::Float(float_width)) => {
+                    &bitcode::LIST_CONTAINS_FLOAT[float_width]
+                }
+                _ => unreachable!("invalid element layout for ListContainsNum"),
+            };
+
+            call_list_bitcode_fn(
+                env,
+                &[list.into_struct_value()],
+                &[needle],
+                BitcodeReturns::Basic,
+                intrinsic,
+            )
+        }
         ListClone => {
             // List.clone : List a -> List a
             arguments_with_layouts!((list, list_layout));
@@ -924,6 +991,7 @@ pub(crate) fn run_low_level<'a, 'ctx>(
         | NumRound
         | NumSqrtUnchecked
         | NumLogUnchecked
+        | NumExpDec
         | NumSin
         | NumCos
         | NumTan
@@ -2222,6 +2290,9 @@ fn build_dec_unary_op<'a, 'ctx>(
         NumCos => dec_unary_op(env, bitcode::DEC_COS, arg),
         NumSin => dec_unary_op(env, bitcode::DEC_SIN, arg),
         NumTan => dec_unary_op(env, bitcode::DEC_TAN, arg),
+        NumSqrtUnchecked => dec_unary_op(env, bitcode::DEC_SQRT, arg),
+        NumLogUnchecked => dec_unary_op(env, bitcode::DEC_LOG, arg),
+        NumExpDec => dec_unary_op(env, bitcode::DEC_EXP, arg),
 
         NumRound => dec_unary_op(env, &bitcode::DEC_ROUND[int_width()], arg),
         NumFloor => dec_unary_op(env, &bitcode::DEC_FLOOR[int_width()], arg),
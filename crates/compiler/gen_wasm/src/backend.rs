@@ -1132,9 +1132,15 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
     /// Create a string constant in the module data section
     /// Return the data we need for code gen: linker symbol index and memory address
     fn store_bytes_in_data_section(&mut self, bytes: &[u8]) -> u32 {
-        // Place the segment at a 4-byte aligned offset
-        let segment_addr = round_up_to_alignment!(self.module.data.end_addr, PTR_SIZE);
-        let elements_addr = segment_addr + PTR_SIZE;
+        self.store_aligned_bytes_in_data_section(bytes, PTR_SIZE)
+    }
+
+    /// Like `store_bytes_in_data_section`, but the elements start at an address that is a
+    /// multiple of `alignment`, with the refcount immediately before them.
+    fn store_aligned_bytes_in_data_section(&mut self, bytes: &[u8], alignment: u32) -> u32 {
+        let alignment = alignment.max(PTR_SIZE);
+        let elements_addr = round_up_to_alignment!(self.module.data.end_addr + 4, alignment);
+        let segment_addr = elements_addr - 4;
         let length_with_refcount = 4 + bytes.len();
         self.module.data.end_addr = segment_addr + length_with_refcount as u32;
 
@@ -1143,7 +1149,7 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
             init: Vec::with_capacity_in(length_with_refcount, self.env.arena),
         };
 
-        // Prefix the string bytes with "infinite" refcount
+        // Prefix the bytes with "infinite" refcount
         let refcount_max_bytes: [u8; 4] = (REFCOUNT_MAX as i32).to_le_bytes();
         segment.init.extend_from_slice(&refcount_max_bytes);
         segment.init.extend_from_slice(bytes);
@@ -1153,6 +1159,46 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
         elements_addr
     }
 
+    /// Serialize a list literal whose elements are all known at compile time.
+    /// Returns None if any element is a runtime value or the elements contain pointers.
+    fn static_list_bytes(
+        &self,
+        elem_layout: InLayout<'a>,
+        elems: &[ListLiteralElement<'a>],
+    ) -> Option<Vec<'a, u8>> {
+        if self.layout_interner.contains_refcounted(elem_layout) {
+            return None;
+        }
+
+        let elem_size = self.layout_interner.stack_size(elem_layout) as usize;
+        let repr = self.layout_interner.get_repr(elem_layout);
+        let mut bytes = Vec::with_capacity_in(elem_size * elems.len(), self.env.arena);
+
+        for elem in elems.iter() {
+            let lit = elem.get_literal()?;
+            match (lit, repr) {
+                (Literal::Int(x) | Literal::U128(x), LayoutRepr::Builtin(Builtin::Int(_))) => {
+                    let le_bytes = i128::from_ne_bytes(x).to_le_bytes();
+                    bytes.extend_from_slice(&le_bytes[..elem_size]);
+                }
+                (Literal::Decimal(x), LayoutRepr::Builtin(Builtin::Decimal)) => {
+                    bytes.extend_from_slice(&i128::from_ne_bytes(x).to_le_bytes());
+                }
+                (Literal::Float(x), LayoutRepr::Builtin(Builtin::Float(FloatWidth::F64))) => {
+                    bytes.extend_from_slice(&x.to_le_bytes());
+                }
+                (Literal::Float(x), LayoutRepr::Builtin(Builtin::Float(FloatWidth::F32))) => {
+                    bytes.extend_from_slice(&(x as f32).to_le_bytes());
+                }
+                (Literal::Bool(x), LayoutRepr::Builtin(Builtin::Bool)) => bytes.push(x as u8),
+                (Literal::Byte(x), _) if elem_size == 1 => bytes.push(x),
+                _ => return None,
+            }
+        }
+
+        Some(bytes)
+    }
+
     fn expr_null_pointer(&mut self) {
         self.code_builder.i32_const(0);
     }
@@ -1431,6 +1477,33 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
         elems: &'a [ListLiteralElement<'a>],
     ) {
         if let StoredValue::StackMemory { location, .. } = storage {
+            if let Some(bytes) = self.static_list_bytes(elem_layout, elems) {
+                // All elements are constants, so the list can live in the data section.
+                // Its refcount is the static sentinel, so mutations will copy it first.
+                let alignment = self.layout_interner.alignment_bytes(elem_layout);
+                let elements_addr = self.store_aligned_bytes_in_data_section(&bytes, alignment);
+                let (local_id, offset) =
+                    location.local_and_offset(self.storage.stack_frame_pointer);
+
+                // ptr
+                self.code_builder.get_local(local_id);
+                self.code_builder.i32_const(elements_addr as i32);
+                self.code_builder.i32_store(Align::Bytes4, offset);
+
+                // len
+                self.code_builder.get_local(local_id);
+                self.code_builder.i32_const(elems.len() as i32);
+                self.code_builder
+                    .i32_store(Align::Bytes4, offset + 4 * Builtin::WRAPPER_LEN);
+
+                // capacity
+                self.code_builder.get_local(local_id);
+                self.code_builder.i32_const(elems.len() as i32);
+                self.code_builder
+                    .i32_store(Align::Bytes4, offset + 4 * Builtin::WRAPPER_CAPACITY);
+                return;
+            }
+
             let size = self.layout_interner.stack_size(elem_layout) * (elems.len() as u32);
 
             // Allocate heap space and store its address in a local variable
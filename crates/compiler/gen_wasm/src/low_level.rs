@@ -185,6 +185,9 @@ impl<'a> LowLevelCall<'a> {
         match self.lowlevel {
             // Str
             StrConcat => self.load_args_and_call_zig(backend, bitcode::STR_CONCAT),
+            StrConcatAmortized => {
+                self.load_args_and_call_zig(backend, bitcode::STR_CONCAT_AMORTIZED)
+            }
             StrGetUnsafe => self.load_args_and_call_zig(backend, bitcode::STR_GET_UNSAFE),
             StrJoinWith => self.load_args_and_call_zig(backend, bitcode::STR_JOIN_WITH),
             StrIsEmpty => match backend.storage.get(&self.arguments[0]) {
@@ -268,6 +271,9 @@ impl<'a> LowLevelCall<'a> {
             StrCaselessAsciiEquals => {
                 self.load_args_and_call_zig(backend, bitcode::STR_CASELESS_ASCII_EQUALS)
             }
+            StrWithUppercased => self.load_args_and_call_zig(backend, bitcode::STR_WITH_UPPERCASED),
+            StrWithLowercased => self.load_args_and_call_zig(backend, bitcode::STR_WITH_LOWERCASED),
+            StrCaselessEquals => self.load_args_and_call_zig(backend, bitcode::STR_CASELESS_EQUALS),
 
             // List
             ListLenU64 => {
@@ -283,6 +289,19 @@ impl<'a> LowLevelCall<'a> {
 
             ListIsUnique => self.load_args_and_call_zig(backend, bitcode::LIST_IS_UNIQUE),
 
+            ListContainsNum => match backend
+                .layout_interner
+                .get_repr(backend.storage.symbol_layouts[&self.arguments[1]])
+            {
+                LayoutRepr::Builtin(Builtin::Int(width)) => {
+                    self.load_args_and_call_zig(backend, &bitcode::LIST_CONTAINS_INT[width]);
+                }
+                LayoutRepr::Builtin(Builtin::Float(width)) => {
+                    self.load_args_and_call_zig(backend, &bitcode::LIST_CONTAINS_FLOAT[width]);
+                }
+                x => todo!("{:?} for {:?}", self.lowlevel, x),
+            },
+
             ListClone => {
                 let input_list: Symbol = self.arguments[0];
                 let elem_in_layout = unwrap_list_elem_layout(self.ret_layout_raw);
@@ -1663,24 +1682,30 @@ impl<'a> LowLevelCall<'a> {
                 }
                 _ => panic_ret_type(),
             },
-            NumSqrtUnchecked => {
-                self.load_args(backend);
-                match self.ret_layout_raw {
-                    LayoutRepr::Builtin(Builtin::Float(FloatWidth::F32)) => {
-                        backend.code_builder.f32_sqrt()
-                    }
-                    LayoutRepr::Builtin(Builtin::Float(FloatWidth::F64)) => {
-                        backend.code_builder.f64_sqrt()
-                    }
-                    _ => panic_ret_type(),
+            NumSqrtUnchecked => match self.ret_layout_raw {
+                LayoutRepr::Builtin(Builtin::Float(FloatWidth::F32)) => {
+                    self.load_args(backend);
+                    backend.code_builder.f32_sqrt()
                 }
-            }
+                LayoutRepr::Builtin(Builtin::Float(FloatWidth::F64)) => {
+                    self.load_args(backend);
+                    backend.code_builder.f64_sqrt()
+                }
+                LayoutRepr::Builtin(Builtin::Decimal) => {
+                    self.load_args_and_call_zig(backend, bitcode::DEC_SQRT);
+                }
+                _ => panic_ret_type(),
+            },
             NumLogUnchecked => match self.ret_layout_raw {
                 LayoutRepr::Builtin(Builtin::Float(width)) => {
                     self.load_args_and_call_zig(backend, &bitcode::NUM_LOG[width]);
                 }
+                LayoutRepr::Builtin(Builtin::Decimal) => {
+                    self.load_args_and_call_zig(backend, bitcode::DEC_LOG);
+                }
                 _ => panic_ret_type(),
             },
+            NumExpDec => self.load_args_and_call_zig(backend, bitcode::DEC_EXP),
             NumToFrac => {
                 self.load_args(backend);
                 let ret_type = CodeGenNumType::from(self.ret_layout);
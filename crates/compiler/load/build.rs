@@ -25,6 +25,7 @@ const MODULES: &[(ModuleId, &str)] = &[
     (ModuleId::DECODE, "Decode.roc"),
     (ModuleId::HASH, "Hash.roc"),
     (ModuleId::INSPECT, "Inspect.roc"),
+    (ModuleId::BINARY, "Binary.roc"),
 ];
 
 fn main() {
@@ -16,14 +16,23 @@ const SKIP_SUBS_CACHE: bool = {
     }
 };
 
+pub use roc_load_internal::call_hierarchy;
+pub use roc_load_internal::determinism::{Divergence, Fingerprint};
 pub use roc_load_internal::docs;
+pub use roc_load_internal::extract;
 pub use roc_load_internal::file::{
-    ExecutionMode, ExpectMetadata, LoadConfig, LoadResult, LoadStart, LoadingProblem, Phase,
-    Threading,
+    ComptimeLimits, ExecutionMode, ExpectMetadata, LoadConfig, LoadResult, LoadStart,
+    LoadingProblem, Phase, Threading,
 };
+pub use roc_load_internal::layout_query::{with_query_def, LayoutQueryError, QUERY_DEF};
+pub use roc_load_internal::manifest;
 pub use roc_load_internal::module::{
-    CheckedModule, EntryPoint, Expectations, ExposedToHost, LoadedModule, MonomorphizedModule,
+    CheckedModule, EntryPoint, Expectations, ExposedToHost, LoadedModule, ModuleArenaStats,
+    ModuleTiming, MonomorphizedModule,
 };
+pub use roc_load_internal::occurrences;
+pub use roc_load_internal::platform_switch::{with_platform, Incompatibility, PlatformSwitch};
+pub use roc_load_internal::rename;
 pub use roc_solve::FunctionKind;
 
 #[allow(clippy::too_many_arguments)]
@@ -71,6 +80,11 @@ pub fn load_single_threaded<'a>(
         render,
         palette,
         exec_mode,
+        false,
+        false,
+        None,
+        0,
+        ComptimeLimits::default(),
         roc_cache_dir,
     )
 }
@@ -172,6 +186,110 @@ pub fn load_and_typecheck<'a>(
     }
 }
 
+/// What [audit_determinism] found.
+#[derive(Debug)]
+pub struct DeterminismAudit {
+    /// How many threads the second load used; the first one is always single-threaded. On a
+    /// machine with a single core, both are.
+    pub threads: usize,
+    /// How many ids, problems and procedures were compared
+    pub entries: usize,
+    pub divergence: Option<Divergence>,
+}
+
+/// Load the program twice, first on a single thread and then with `load_config.threading` (or all
+/// available threads, if that is single-threaded too), and compare the ids, problems and
+/// specializations of the two results. See [roc_load_internal::determinism].
+pub fn audit_determinism<'a>(
+    arena: &'a Bump,
+    filename: PathBuf,
+    opt_main_path: Option<PathBuf>,
+    roc_cache_dir: RocCacheDir<'_>,
+    load_config: LoadConfig,
+) -> Result<DeterminismAudit, LoadingProblem<'a>> {
+    let threading = match load_config.threading {
+        Threading::Single => Threading::AllAvailable,
+        threading => threading,
+    };
+
+    let mut fingerprints = Vec::with_capacity(2);
+
+    for threading in [Threading::Single, threading] {
+        let load_start = LoadStart::from_path(
+            arena,
+            filename.clone(),
+            opt_main_path.clone(),
+            load_config.render,
+            roc_cache_dir,
+            load_config.palette,
+        )?;
+        let load_config = LoadConfig {
+            threading,
+            ..load_config.clone()
+        };
+        let result = load(
+            arena,
+            load_start,
+            ExposedByModule::default(),
+            roc_cache_dir,
+            load_config,
+        )?;
+
+        fingerprints.push(Fingerprint::of_load_result(&result));
+    }
+
+    let threads = match (std::thread::available_parallelism(), threading) {
+        (Err(_), _) | (_, Threading::Single) => 1,
+        (Ok(available), Threading::AllAvailable) => available.get(),
+        (Ok(available), Threading::AtMost(at_most)) => available.get().min(at_most),
+    };
+
+    Ok(DeterminismAudit {
+        threads,
+        entries: fingerprints[0].len(),
+        divergence: fingerprints[0].first_divergence(&fingerprints[1]),
+    })
+}
+
+/// Type check the app at `app_path` with its own platform and with `candidate_platform`, to find
+/// what switching platforms would break before building anything. Returns `None` when the app's
+/// header doesn't name a platform package.
+pub fn check_platform_switch<'a>(
+    arena: &'a Bump,
+    app_path: PathBuf,
+    candidate_platform: &str,
+    roc_cache_dir: RocCacheDir<'_>,
+    load_config: LoadConfig,
+) -> Result<Option<PlatformSwitch>, LoadingProblem<'a>> {
+    roc_load_internal::platform_switch::check_platform_switch(
+        arena,
+        app_path,
+        candidate_platform,
+        read_cached_types,
+        roc_cache_dir,
+        load_config,
+    )
+}
+
+/// The memory layout of `type_annotation`, a type written like it would be in an annotation at
+/// the end of the module at `module_path`. See [roc_load_internal::layout_query].
+pub fn query_layout<'a>(
+    arena: &'a Bump,
+    module_path: PathBuf,
+    type_annotation: &str,
+    roc_cache_dir: RocCacheDir<'_>,
+    load_config: LoadConfig,
+) -> Result<roc_mono::layout::describe::LayoutDescription, LayoutQueryError<'a>> {
+    roc_load_internal::layout_query::query_layout(
+        arena,
+        module_path,
+        type_annotation,
+        read_cached_types(),
+        roc_cache_dir,
+        load_config,
+    )
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn load_and_typecheck_str<'a>(
     arena: &'a Bump,
@@ -256,6 +374,7 @@ fn read_cached_types() -> MutMap<ModuleId, TypeState> {
     let mod_decode = include_bytes_align_as!(u128, concat!(env!("OUT_DIR"), "/Decode.dat"));
     let mod_hash = include_bytes_align_as!(u128, concat!(env!("OUT_DIR"), "/Hash.dat"));
     let mod_inspect = include_bytes_align_as!(u128, concat!(env!("OUT_DIR"), "/Inspect.dat"));
+    let mod_binary = include_bytes_align_as!(u128, concat!(env!("OUT_DIR"), "/Binary.dat"));
 
     let mut output = MutMap::default();
 
@@ -279,6 +398,7 @@ fn read_cached_types() -> MutMap<ModuleId, TypeState> {
 
         output.insert(ModuleId::HASH, deserialize_help(mod_hash));
         output.insert(ModuleId::INSPECT, deserialize_help(mod_inspect));
+        output.insert(ModuleId::BINARY, deserialize_help(mod_binary));
     }
 
     output
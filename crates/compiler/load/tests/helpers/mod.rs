@@ -55,6 +55,7 @@ pub fn infer_expr(
         module_params: None,
         module_params_vars: Default::default(),
         host_exposed_symbols: None,
+        trace_abilities: false,
         #[cfg(debug_assertions)]
         checkmate: None,
     };
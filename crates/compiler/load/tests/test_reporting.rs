@@ -101,7 +101,13 @@ mod test_reporting {
         use std::fs::File;
         use std::io::Write;
 
-        let module_src = if src.starts_with("app") || src.starts_with("module") {
+        // Directives like `# @warnings` go in the comments above a module's header.
+        let header = src
+            .lines()
+            .find(|line| !line.starts_with('#'))
+            .unwrap_or_default();
+
+        let module_src = if header.starts_with("app") || header.starts_with("module") {
             maybe_save_parse_test_case(subdir, src, false);
             // this is already a module
             src.to_string()
@@ -130,6 +136,11 @@ mod test_reporting {
                 threading: Threading::Single,
                 exec_mode: ExecutionMode::Check,
                 function_kind: FunctionKind::LambdaSet,
+                emit_match_trees: false,
+                trace_abilities: false,
+                profile: None,
+                doc_examples: 0,
+                comptime_limits: roc_load::ComptimeLimits::default(),
             };
             let result = roc_load::load_and_typecheck(
                 arena,
@@ -193,7 +204,8 @@ mod test_reporting {
                 let src_lines: Vec<&str> = module_src.split('\n').collect();
                 let mut reports = Vec::new();
 
-                let alloc = RocDocAllocator::new(&src_lines, home, &interns);
+                // The reports are shown in order, one after another
+                let alloc = RocDocAllocator::new(&src_lines, home, &interns).with_shared_types();
 
                 for problem in can_problems {
                     let report = can_problem(&alloc, &lines, filename.clone(), problem.clone());
@@ -2357,7 +2369,6 @@ mod test_reporting {
             { x & foo: 3 }
             "
         ),
-        // TODO also suggest fields with the correct type
         @r"
     ── TYPE MISMATCH in /code/proj/Main.roc ────────────────────────────────────────
 
@@ -2390,7 +2401,6 @@ mod test_reporting {
             f
             "
         ),
-        // TODO also suggest fields with the correct type
         @r"
     ── TYPE MISMATCH in /code/proj/Main.roc ────────────────────────────────────────
 
@@ -2418,7 +2428,6 @@ mod test_reporting {
             { x & foo: 3 }
             "
         ),
-        // TODO also suggest fields with the correct type
         @r"
     ── TYPE MISMATCH in /code/proj/Main.roc ────────────────────────────────────────
 
@@ -8532,6 +8541,33 @@ All branches in an `if` must have the same type!
         @r#"
     ── UNSAFE PATTERN in /code/proj/Main.roc ───────────────────────────────────────
 
+    This `when` only handles some possibilities in branches with an `if`
+    guard:
+
+    5│>      when x is
+    6│>          A if Bool.true -> ""
+
+    These patterns are only handled by guarded branches:
+
+        A
+
+    If none of those guards are true, I would have to crash! Add a `_`
+    branch without a guard at the end to handle everything else.
+    "#
+    );
+
+    test_report!(
+        non_exhaustive_with_guard_and_missing_tag,
+        indoc!(
+            r#"
+            x : [A, B]
+            when x is
+                A if Bool.true -> ""
+            "#
+        ),
+        @r#"
+    ── UNSAFE PATTERN in /code/proj/Main.roc ───────────────────────────────────────
+
     This `when` does not cover all the possibilities:
 
     5│>      when x is
@@ -8539,7 +8575,7 @@ All branches in an `if` must have the same type!
 
     Other possibilities include:
 
-        A    (note the lack of an if clause)
+        B    (note the lack of an if clause)
 
     I would have to crash if I saw one of those! Add branches for them!
     "#
@@ -10149,12 +10185,76 @@ All branches in an `if` must have the same type!
     5│  A := {} implements [Ab]
                             ^^
 
-    Only builtin abilities can be derived.
+    Only builtin abilities, and abilities with deriving hooks, can be
+    derived.
 
     Note: The builtin abilities are `Encoding`, `Decoding`, `Hash`, `Eq`, `Inspect`
     "
     );
 
+    test_report!(
+        derive_ability_missing_hook,
+        indoc!(
+            r#"
+            app "test" provides [A, show_record] to "./platform"
+
+            Show implements show : a -> Str where a implements Show
+
+            show_record : List (Str, Str) -> Str
+            show_record = \_ -> ""
+
+            A := [B, C] implements [Show]
+            "#
+        ),
+        @r"
+    ── ILLEGAL DERIVE in /code/proj/Main.roc ───────────────────────────────────────
+
+    This ability cannot be derived for this type:
+
+    8│  A := [B, C] implements [Show]
+                                ^^^^
+
+    `Show` has deriving hooks, but deriving it for this type needs these
+    hooks too:
+
+        show_tag
+
+    Tip: Deriving hooks are functions defined next to the ability. For
+    each member `m`, `m_record` derives it for records, and `m_tag` for
+    tag unions.
+    "
+    );
+
+    test_report!(
+        derive_ability_with_hooks_field_not_implementing,
+        indoc!(
+            r#"
+            app "test" provides [A] to "./platform"
+
+            Show implements show : a -> Str where a implements Show
+
+            show_record : List (Str, Str) -> Str
+            show_record = \_ -> ""
+
+            A := { name : Str } implements [Show]
+            "#
+        ),
+        @r"
+    ── TYPE MISMATCH in /code/proj/Main.roc ────────────────────────────────────────
+
+    This expression has a type that does not implement the abilities it's expected to:
+
+    8│  A := { name : Str } implements [Show]
+                      ^^^
+
+    I can't generate an implementation of the `Show` ability for
+
+        Str
+
+    Only builtin abilities can have generated implementations!
+    "
+    );
+
     test_report!(
         has_encoding_for_function,
         indoc!(
@@ -10567,9 +10667,9 @@ All branches in an `if` must have the same type!
         of the branch. Otherwise, the program would crash when it tries to use
         an identifier that wasn't bound!
 
-        ── UNUSED DEFINITION in /code/proj/Main.roc ────────────────────────────────────
+        ── UNUSED PATTERN BINDING in /code/proj/Main.roc ───────────────────────────────
 
-        `y` is not used in this `when` branch.
+        `y` is not used anywhere in your code.
 
         5│          A x | B y -> x
                             ^
@@ -10899,6 +10999,63 @@ All branches in an `if` must have the same type!
     "#
     );
 
+    test_report!(
+        record_builder_mapper_with_wrong_arity,
+        indoc!(
+            r#"
+            combine = \a, b -> { a, b }
+
+            { combine <-
+                b: 123,
+                c: 456
+            }
+            "#
+        ),
+        @r#"
+    ── WRONG MAPPER ARITY in /code/proj/Main.roc ───────────────────────────────────
+
+    The `combine` mapper takes 2 arguments, but record builders call their
+    mapper with 3:
+
+    6│      { combine <-
+              ^^^^^^^
+
+    A mapper before the <- is given the values of two fields, and a
+    function that combines them into one value.
+    "#
+    );
+
+    test_report!(
+        record_builder_field_mismatch,
+        indoc!(
+            r#"
+            map2 : List I64, List I64, (I64, I64 -> I64) -> List I64
+            map2 = \xs, ys, f -> List.map2 xs ys f
+
+            { map2 <- a: [1], b: "two" }
+            "#
+        ),
+        @r#"
+    ── RECORD BUILDER MISMATCH in /code/proj/Main.roc ──────────────────────────────
+
+    This field's value doesn't have the type the `map2` mapper expects:
+
+    7│      { map2 <- a: [1], b: "two" }
+                                 ^^^^^
+
+    The field's value is a string of type:
+
+        Str
+
+    But the `map2` mapper needs its 2nd argument to be:
+
+        List I64
+
+    Tip: Every field in a record builder must be something the `map2`
+    mapper can combine, like all parsers or all decoders.
+    "#
+    );
+
     test_report!(
         destructure_assignment_introduces_no_variables_nested,
         indoc!(
@@ -11340,6 +11497,9 @@ All branches in an `if` must have the same type!
         [Good … *, …]
 
     The branches must be cases of the `when` condition's type!
+
+    Tip: The `Good` tag has 1 payload value here, but it should have
+    2 payload values.
     "#
     );
 
@@ -11422,9 +11582,9 @@ All branches in an `if` must have the same type!
             "#
         ),
     @r#"
-    ── UNUSED DEFINITION in /code/proj/Main.roc ────────────────────────────────────
+    ── UNUSED PATTERN BINDING in /code/proj/Main.roc ───────────────────────────────
 
-    `foo` is not used in this `when` branch.
+    `foo` is not used anywhere in your code.
 
     5│          A foo -> ""
                   ^^^
@@ -12212,7 +12372,7 @@ All branches in an `if` must have the same type!
     This 2nd argument to `contains` has an unexpected type:
 
     7│      List.contains digits '☃'
-                                 ^^^^^
+                                 ^^^
 
     The argument is a Unicode scalar value of type:
 
@@ -15115,6 +15275,497 @@ All branches in an `if` must have the same type!
     "
     );
 
+    test_report!(
+        typed_hole_with_completions,
+        indoc!(
+            r#"
+            greet : Str, U64 -> Str
+            greet = \name, count ->
+                shout = \word -> Str.concat word "!"
+                excited = Str.repeat "!" count
+
+                Str.join_with [shout name, ?greeting, excited] " "
+
+            greet "Hi" 3
+            "#
+        ),
+        @r#"
+    ── TYPED HOLE in /code/proj/Main.roc ───────────────────────────────────────────
+
+    The hole `?greeting` needs a value of type:
+
+    9│          Str.join_with [shout name, ?greeting, excited] " "
+                                           ^^^^^^^^^
+
+        Str
+
+    These values are in scope:
+
+        excited : Str
+        shout : Str -> Str
+        count : U64
+        name : Str
+
+    These would fit in the hole:
+
+        excited
+        shout(???)
+        name
+
+    Tip: The program still runs, but it crashes if it evaluates this hole.
+    "#
+    );
+
+    test_report!(
+        anonymous_typed_hole,
+        indoc!(
+            r#"
+            choose : a, a -> a
+            choose = \first, second ->
+                if ??? then first else second
+
+            choose 1 2
+            "#
+        ),
+        @r#"
+    ── TYPED HOLE in /code/proj/Main.roc ───────────────────────────────────────────
+
+    This hole needs a value of type:
+
+    6│          if ??? then first else second
+                   ^^^
+
+        Bool
+
+    These values are in scope:
+
+        second : a
+        first : a
+
+    Tip: The program still runs, but it crashes if it evaluates this hole.
+    "#
+    );
+
+    test_report!(
+        unrecognized_name_suggests_fitting_types_first,
+        indoc!(
+            r#"
+            total = 4
+            label = "Total"
+
+            { count: total, text: Str.concat label totl }
+            "#
+        ),
+        @r#"
+    ── UNRECOGNIZED NAME in /code/proj/Main.roc ────────────────────────────────────
+
+    Nothing is named `totl` in this scope.
+
+    7│      { count: total, text: Str.concat label totl }
+                                                   ^^^^
+
+    Did you mean one of these?
+
+        label
+        total
+        Bool
+        Box
+    "#
+    );
+
+    test_report!(
+        record_field_typo_suggests_fitting_types_first,
+        indoc!(
+            r#"
+            user = { num: 1, name: "Sam" }
+
+            Str.concat "Hi, " user.nam
+            "#
+        ),
+        @r#"
+    ── TYPE MISMATCH in /code/proj/Main.roc ────────────────────────────────────────
+
+    This `user` record doesn’t have a `nam` field:
+
+    6│      Str.concat "Hi, " user.nam
+                              ^^^^^^^^
+
+    There may be a typo. These `user` fields are the most similar:
+
+        {
+            name : Str,
+            num : Num *,
+        }
+
+    Maybe `.nam` should be `.name` instead?
+    "#
+    );
+
+    #[test]
+    fn explain_examples_get_their_reports() {
+        let render = |doc: RocDocBuilder<'_>, buf: &mut String| {
+            doc.1
+                .render_raw(70, &mut roc_reporting::report::CiWrite::new(buf))
+                .expect("list_reports")
+        };
+
+        for doc in roc_reporting::explain::explanations() {
+            assert_eq!(
+                roc_reporting::explain::explain(doc.title).map(|found| found.code),
+                Some(doc.code)
+            );
+
+            for (index, example) in doc.examples.iter().enumerate() {
+                let arena = Bump::new();
+                let subdir = format!("explain_{}_{index}", doc.code);
+                let reports = list_reports_new(&subdir, &arena, example.program, render);
+
+                assert!(
+                    reports.starts_with(&format!("── {} in", doc.title)),
+                    "example {index} of `{}` got the wrong report:\n{reports}",
+                    doc.code
+                );
+
+                if let Some(fixed) = example.fixed {
+                    let arena = Bump::new();
+                    let subdir = format!("explain_{}_{index}_fixed", doc.code);
+                    let reports = list_reports_new(&subdir, &arena, fixed, render);
+
+                    assert!(
+                        reports.is_empty(),
+                        "the fix for example {index} of `{}` still gets reports:\n{reports}",
+                        doc.code
+                    );
+                }
+            }
+        }
+    }
+
+    test_report!(
+        denied_warning,
+        indoc!(
+            r"
+            # @warnings deny unused-definition
+            # @warnings allow unused-argument
+            module [main]
+
+            main = \arg ->
+                x = 1
+                2
+            "
+        ),
+        @r"
+    ── UNUSED DEFINITION in /code/proj/Main.roc ────────────────────────────────────
+
+    `x` is not used anywhere in your code.
+
+    6│      x = 1
+            ^
+
+    If you didn't intend on using `x` then remove it so future readers of
+    your code don't wonder why it is there.
+
+    Note: This warning is an error, because `unused-definition` is denied in
+    the warning configuration of this package.
+    "
+    );
+
+    #[test]
+    fn warning_codes_match_report_titles() {
+        let programs = [
+            "x = 1\n\n2",
+            "f = \\a -> 1\n\nf 2",
+            "when 1 is\n    1 -> 2\n    1 -> 3\n    _ -> 4",
+            "import Num\n\n1",
+            "{ a, b } = { a: 1, b: 2 }\n\na",
+            "x = 1\ny =\n    x = x + 1\n    x\n\ny",
+        ];
+
+        for (index, program) in programs.into_iter().enumerate() {
+            let arena = Bump::new();
+            let subdir = format!("warning_codes_{index}");
+            let (module_src, type_problems, can_problems, home, interns) =
+                infer_expr_help_new(&subdir, &arena, program).unwrap();
+            let lines = LineInfo::new(&module_src);
+            let src_lines: Vec<&str> = module_src.split('\n').collect();
+            let alloc = RocDocAllocator::new(&src_lines, home, &interns);
+            let filename = filename_from_string(r"/code/proj/Main.roc");
+
+            let can_reports = can_problems.into_iter().map(|problem| {
+                let code = problem.warning_code();
+
+                (
+                    code,
+                    Some(can_problem(&alloc, &lines, filename.clone(), problem)),
+                )
+            });
+            let type_reports = type_problems.into_iter().map(|problem| {
+                let code = problem.warning_code();
+
+                (
+                    code,
+                    type_problem(&alloc, &lines, filename.clone(), problem),
+                )
+            });
+            let mut checked = 0;
+
+            for (code, report) in can_reports.chain(type_reports) {
+                if let (Some(code), Some(report)) = (code, report) {
+                    assert!(roc_problem::WARNING_CODES.contains(&code));
+                    assert_eq!(code, roc_reporting::explain::code_for_title(&report.title));
+                    checked += 1;
+                }
+            }
+
+            assert!(checked > 0, "program {index} has no warnings");
+        }
+    }
+
+    test_report!(
+        intentional_shadowing,
+        indoc!(
+            r"
+            f = \count ->
+                count = count + 1
+                count * 2
+
+            f 1
+            "
+        ),
+        @r"
+    ── INTENTIONAL SHADOWING in /code/proj/Main.roc ────────────────────────────────
+
+    The `count` name is first defined here:
+
+    4│      f = \count ->
+                 ^^^^^
+
+    Then it's given a new value, computed from the old one, here:
+
+    5│          count = count + 1
+                ^^^^^
+
+    From here on, `count` means the new value. Giving the new value a name
+    of its own, like `count2`, makes it clear which value each use of `count`
+    means.
+    "
+    );
+
+    test_report!(
+        unused_record_field_argument,
+        indoc!(
+            r"
+            f = \{ a, b } -> a
+
+            f { a: 1, b: 2 }
+            "
+        ),
+        @r#"
+    ── UNUSED ARGUMENT in /code/proj/Main.roc ──────────────────────────────────────
+
+    `f` doesn't use `b`.
+
+    4│      f = \{ a, b } -> a
+                      ^
+
+    If you don't need `b`, then you can just remove it. However, if you
+    really do need `b` as an argument of `f`, ignore its field, like this:
+    "`b`: _".
+    "#
+    );
+
+    test_report!(
+        unused_destructured_def,
+        indoc!(
+            r"
+            (a, b) = (1, 2)
+
+            a
+            "
+        ),
+        @r#"
+    ── UNUSED PATTERN BINDING in /code/proj/Main.roc ───────────────────────────────
+
+    `b` is not used anywhere in your code.
+
+    4│      (a, b) = (1, 2)
+                ^
+
+    If you don't need to use `b`, prefix it with an underscore, like "_b",
+    or replace it with just an "_".
+    "#
+    );
+
+    #[test]
+    fn warning_fixes_resolve_their_warnings() {
+        let programs = [
+            "x = 1\n\n2",
+            "f = \\a -> 1\n\nf 2",
+            "f = \\{ a, b } -> a\n\nf { a: 1, b: 2 }",
+            "when Ok 1 is\n    Ok x -> 1\n    Err _ -> 2",
+            "{ a, b } = { a: 1, b: 2 }\n\na",
+            "x = 1\ny =\n    x = x + 1\n    { x }\n\ny.x",
+        ];
+
+        for (index, program) in programs.into_iter().enumerate() {
+            let arena = Bump::new();
+            let subdir = format!("warning_fixes_{index}");
+            let (module_src, _, can_problems, _, interns) =
+                infer_expr_help_new(&subdir, &arena, program).unwrap();
+
+            let fixes: Vec<_> = can_problems
+                .iter()
+                .filter_map(|problem| problem.fix(&interns))
+                .collect();
+
+            assert_eq!(fixes.len(), 1, "program {index} has {} fixes", fixes.len());
+
+            let mut edits = fixes[0].edits.clone();
+            edits.sort_by_key(|edit| edit.region.start().offset);
+
+            let mut fixed = module_src.clone();
+
+            for edit in edits.iter().rev() {
+                let start = edit.region.start().offset as usize;
+                let end = edit.region.end().offset as usize;
+
+                fixed.replace_range(start..end, &edit.replacement);
+            }
+
+            let subdir = format!("warning_fixes_{index}_fixed");
+            let (_, result) = run_load_and_infer(&subdir, &arena, arena.alloc_str(&fixed));
+            let mut loaded = result.unwrap();
+            let can_problems = loaded
+                .can_problems
+                .remove(&loaded.module_id)
+                .unwrap_or_default();
+            let type_problems = loaded
+                .type_problems
+                .remove(&loaded.module_id)
+                .unwrap_or_default();
+
+            assert!(
+                can_problems.is_empty() && type_problems.is_empty(),
+                "applying \"{}\" to program {index} gives:\n{fixed}\n{can_problems:?}\n{type_problems:?}",
+                fixes[0].title
+            );
+        }
+    }
+
+    #[test]
+    fn exposed_but_not_defined_fix_repairs_header() {
+        let arena = Bump::new();
+        let (module_src, result) = run_load_and_infer(
+            "exposed_but_not_defined_fix",
+            &arena,
+            "module [a, missing, b, other]\n\na = 1\n\nb = 2\n",
+        );
+        let mut loaded = result.unwrap();
+        let can_problems = loaded
+            .can_problems
+            .remove(&loaded.module_id)
+            .unwrap_or_default();
+
+        let fixes: Vec<_> = can_problems
+            .iter()
+            .filter_map(|problem| problem.fix(&loaded.interns))
+            .collect();
+
+        // Each undefined name gets the same fix, which removes all of them
+        assert_eq!(fixes.len(), 2);
+        assert_eq!(fixes[0], fixes[1]);
+
+        let mut fixed = module_src.clone();
+        let mut edits = fixes[0].edits.clone();
+        edits.sort_by_key(|edit| edit.region.start().offset);
+
+        for edit in edits.iter().rev() {
+            let start = edit.region.start().offset as usize;
+            let end = edit.region.end().offset as usize;
+
+            fixed.replace_range(start..end, &edit.replacement);
+        }
+
+        assert!(fixed.starts_with("module [a, b]\n"), "{fixed}");
+
+        let (_, result) = run_load_and_infer(
+            "exposed_but_not_defined_fix_fixed",
+            &arena,
+            arena.alloc_str(&fixed),
+        );
+        let mut loaded = result.unwrap();
+
+        let can_problems = loaded
+            .can_problems
+            .remove(&loaded.module_id)
+            .unwrap_or_default();
+
+        assert_eq!(can_problems, Vec::new());
+    }
+
+    #[test]
+    fn alike_problems_are_grouped() {
+        let arena = Bump::new();
+        let (module_src, result) = run_load_and_infer(
+            "alike_problems_are_grouped",
+            &arena,
+            indoc!(
+                r"
+                x = undefined_name + 1
+                y = undefined_name + 2
+                z = undefined_name + 3
+
+                x + y + z
+                "
+            ),
+        );
+        let LoadedModule {
+            module_id: home,
+            mut can_problems,
+            mut type_problems,
+            interns,
+            ..
+        } = result.unwrap();
+
+        let groups = roc_reporting::group::group_problems(&mut can_problems, &mut type_problems);
+        let problems = &can_problems[&home];
+
+        assert_eq!(problems.len(), 1);
+
+        let group = groups.of_can_problem(home, &problems[0]).unwrap();
+
+        assert_eq!(group.folded, 2);
+        assert_eq!(group.locations.len(), 2);
+
+        let lines = LineInfo::new(&module_src);
+        let src_lines: Vec<&str> = module_src.split('\n').collect();
+        let alloc = RocDocAllocator::new(&src_lines, home, &interns);
+        let locate = |_, region: roc_region::all::Region| {
+            let start = lines.convert_pos(region.start());
+
+            format!("Test.roc:{}:{}", start.line + 1, start.column + 1)
+        };
+        let mut buf = String::new();
+
+        group
+            .note(&alloc, locate)
+            .1
+            .render_raw(70, &mut roc_reporting::report::CiWrite::new(&mut buf))
+            .unwrap();
+
+        assert_eq!(
+            buf,
+            indoc!(
+                r"
+                Note: I found 2 more problems like this one, which probably have the
+                same cause. For example:
+
+                    Test.roc:5:9
+                    Test.roc:6:9"
+            )
+        );
+    }
+
     test_report!(
         incompatible_try_errs,
         indoc!(
@@ -16055,4 +16706,316 @@ All branches in an `if` must have the same type!
     underscore _ instead.
     "###
     );
+
+    test_report!(
+        large_type_shared_between_reports,
+        indoc!(
+            r#"
+            module [x, y]
+
+            big = \s -> {
+                a: [s], b: [s], c: [s], d: [s], e: [s],
+                f: [s], g: [s], h: [s], i: [s], j: [s],
+                k: [s], l: [s], m: [s], n: [s], o: [s],
+                p: [s], q: [s], r: [s], s: [s], t: [s],
+            }
+
+            x : Str
+            x = big ""
+
+            y : Str
+            y = big ""
+            "#
+        ),
+        @r###"
+    ── TYPE MISMATCH in /code/proj/Main.roc ────────────────────────────────────────
+
+    Something is off with the body of the `x` definition:
+
+    10│  x : Str
+    11│  x = big ""
+             ^^^^^^
+
+    This `big` call produces:
+
+        {
+            a : List Str,
+            b : List Str,
+            c : List Str,
+            d : List Str,
+            e : List Str,
+            f : List Str,
+            g : List Str,
+            h : List Str,
+            i : List Str,
+            j : List Str,
+            k : List Str,
+            l : List Str,
+            m : List Str,
+            n : List Str,
+            o : List Str,
+            p : List Str,
+            q : List Str,
+            r : List Str,
+            s : List Str,
+            t : List Str,
+        }
+
+    But the type annotation on `x` says it should be:
+
+        Str
+
+    ── TYPE MISMATCH in /code/proj/Main.roc ────────────────────────────────────────
+
+    Something is off with the body of the `y` definition:
+
+    13│  y : Str
+    14│  y = big ""
+             ^^^^^^
+
+    This `big` call produces:
+
+        { …as in a previous error }
+
+    But the type annotation on `y` says it should be:
+
+        Str
+    "###
+    );
+
+    #[test]
+    fn large_type_printed_in_full_without_sharing() {
+        // E.g. the language server, which shows each report on its own
+        let program = indoc!(
+            r#"
+            module [x, y]
+
+            big = \s -> {
+                a: [s], b: [s], c: [s], d: [s], e: [s],
+                f: [s], g: [s], h: [s], i: [s], j: [s],
+                k: [s], l: [s], m: [s], n: [s], o: [s],
+                p: [s], q: [s], r: [s], s: [s], t: [s],
+            }
+
+            x : Str
+            x = big ""
+
+            y : Str
+            y = big ""
+            "#
+        );
+
+        let arena = Bump::new();
+        let (module_src, type_problems, _, home, interns) =
+            infer_expr_help_new("large_type_printed_in_full", &arena, program).unwrap();
+        let lines = LineInfo::new(&module_src);
+        let src_lines: Vec<&str> = module_src.split('\n').collect();
+        let alloc = RocDocAllocator::new(&src_lines, home, &interns);
+        let filename = filename_from_string(r"/code/proj/Main.roc");
+
+        let types: Vec<String> = type_problems
+            .into_iter()
+            .filter_map(|problem| type_problem(&alloc, &lines, filename.clone(), problem))
+            .map(|report| {
+                let mut buf = String::new();
+                report.render_ci(&mut buf, &alloc);
+
+                // The second report reuses the doc of the type that the first one built
+                let start = buf.find("produces:").unwrap();
+                let end = buf.find("But the type annotation").unwrap();
+
+                buf[start..end].to_string()
+            })
+            .collect();
+
+        assert_eq!(types.len(), 2);
+        assert!(types[0].contains("t : List Str,"), "{}", types[0]);
+        assert_eq!(types[0], types[1]);
+    }
+
+    test_report!(
+        large_record_mismatch_lists_differing_fields,
+        indoc!(
+            r#"
+            module [user]
+
+            user : {
+                name : Str,
+                age : U64,
+                email : Str,
+                phone : Str,
+                city : Str,
+                country : Str,
+                active : Bool,
+                address : { street : Str, zip : U64 },
+            }
+            user = {
+                name: "Ada",
+                age: 36,
+                emial: "ada@example.com",
+                phone: "",
+                city: "London",
+                country: "UK",
+                active: Bool.true,
+                address: { street: "", zip: "" },
+            }
+            "#
+        ),
+        @r###"
+    ── TYPE MISMATCH in /code/proj/Main.roc ────────────────────────────────────────
+
+    Something is off with the body of the `user` definition:
+
+     3│   user : {
+     4│       name : Str,
+     5│       age : U64,
+     6│       email : Str,
+     7│       phone : Str,
+     8│       city : Str,
+     9│       country : Str,
+    10│       active : Bool,
+    11│       address : { street : Str, zip : U64 },
+    12│   }
+    13│>  user = {
+    14│>      name: "Ada",
+    15│>      age: 36,
+    16│>      emial: "ada@example.com",
+    17│>      phone: "",
+    18│>      city: "London",
+    19│>      country: "UK",
+    20│>      active: Bool.true,
+    21│>      address: { street: "", zip: "" },
+    22│>  }
+
+    The body is a record of type:
+
+        {
+            address : { zip : Str, … },
+            age : Num *,
+            emial : Str,
+            …
+        }
+
+    But the type annotation on `user` says it should be:
+
+        {
+            address : { zip : U64, … },
+            age : U64,
+            email : Str,
+            …
+        }
+
+    Tip: Seems like a record field typo. Maybe `emial` should be `email`?
+
+    Tip: Can more type annotations be added? Type annotations always help
+    me give more specific messages, and I think they could help a lot in
+    this case
+    "###
+    );
+
+    test_report!(
+        tag_payload_arity_mismatch,
+        indoc!(
+            r#"
+            f : [Point U64 U64, Origin] -> Str
+            f = \_ -> ""
+
+            f (Point 1 2 3)
+            "#
+        ),
+        @r###"
+    ── TYPE MISMATCH in /code/proj/Main.roc ────────────────────────────────────────
+
+    This 1st argument to `f` has an unexpected type:
+
+    7│      f (Point 1 2 3)
+               ^^^^^^^^^^^
+
+    This `Point` tag application has the type:
+
+        [Point (Int Unsigned64) (Int Unsigned64) (Num *), …]
+
+    But `f` needs its 1st argument to be:
+
+        [Point U64 U64, …]
+
+    Tip: The `Point` tag has 3 payload values here, but it should have
+    2 payload values.
+    "###
+    );
+
+    test_report!(
+        tag_typo_picks_closest_tag,
+        indoc!(
+            r#"
+            f : [Circle, Square, Triangle] -> Str
+            f = \_ -> ""
+
+            g = \shape ->
+                when shape is
+                    Circel -> f shape
+                    Hexagon -> f shape
+                    _ -> ""
+
+            g
+            "#
+        ),
+        @r###"
+    ── TYPE MISMATCH in /code/proj/Main.roc ────────────────────────────────────────
+
+    This 1st argument to `f` has an unexpected type:
+
+    9│              Circel -> f shape
+                                ^^^^^
+
+    This `shape` value is a:
+
+        [
+            Circel,
+            Hexagon,
+        ]
+
+    But `f` needs its 1st argument to be:
+
+        [
+            Circle,
+            Square,
+            Triangle,
+        ]
+
+    Tip: Seems like a tag typo. Maybe `Circel` should be `Circle`?
+
+    Tip: Can more type annotations be added? Type annotations always help
+    me give more specific messages, and I think they could help a lot in
+    this case
+    "###
+    );
+
+    test_report!(
+        underline_after_non_ascii_text,
+        indoc!(
+            r#"
+            f : Str, Str -> Str
+            f = \a, _ -> a
+
+            f "🙂 café" 1
+            "#
+        ),
+        @r###"
+    ── TYPE MISMATCH in /code/proj/Main.roc ────────────────────────────────────────
+
+    This 2nd argument to `f` has an unexpected type:
+
+    7│      f "🙂 café" 1
+                       ^
+
+    The argument is a number of type:
+
+        Num *
+
+    But `f` needs its 2nd argument to be:
+
+        Str
+    "###
+    );
 }
@@ -0,0 +1,270 @@
+//! Which functions call which, across every loaded module, for call hierarchies in editors and
+//! `roc calls`.
+//!
+//! Only calls of a function by its name count, like `f x` or `x |> f`. Passing a function to
+//! another one, like `List.map list f`, is not a call of it.
+use crate::module::LoadedModule;
+use roc_can::expr::Declarations;
+use roc_can::traverse::{call_sites, CallSite};
+use roc_collections::MutMap;
+use roc_module::symbol::{Interns, ModuleId, Symbol};
+use roc_region::all::{LineInfo, Region};
+use std::fmt::Write;
+use std::path::PathBuf;
+
+/// Whether a [CallTree] goes from a function to its callers, or to the functions it calls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallDirection {
+    Incoming,
+    Outgoing,
+}
+
+/// The calls between the top-level defs of a set of modules.
+#[derive(Debug, Clone, Default)]
+pub struct CallGraph {
+    calls: Vec<CallSite>,
+}
+
+impl CallGraph {
+    pub fn new<'d>(modules: impl IntoIterator<Item = &'d Declarations>) -> Self {
+        let calls = modules.into_iter().flat_map(call_sites).collect();
+
+        Self { calls }
+    }
+
+    /// The calls of `symbol`, from anywhere
+    pub fn incoming(&self, symbol: Symbol) -> impl Iterator<Item = &CallSite> {
+        self.calls.iter().filter(move |call| call.callee == symbol)
+    }
+
+    /// The calls that `symbol` makes
+    pub fn outgoing(&self, symbol: Symbol) -> impl Iterator<Item = &CallSite> {
+        self.calls.iter().filter(move |call| call.caller == symbol)
+    }
+
+    /// The functions connected to `symbol` by calls in `direction`, grouped by function, in the
+    /// order of their first call.
+    pub fn neighbors(
+        &self,
+        symbol: Symbol,
+        direction: CallDirection,
+    ) -> Vec<(Symbol, Vec<Region>)> {
+        let mut neighbors: Vec<(Symbol, Vec<Region>)> = Vec::new();
+
+        let calls: Box<dyn Iterator<Item = &CallSite>> = match direction {
+            CallDirection::Incoming => Box::new(self.incoming(symbol)),
+            CallDirection::Outgoing => Box::new(self.outgoing(symbol)),
+        };
+
+        for call in calls {
+            let neighbor = match direction {
+                CallDirection::Incoming => call.caller,
+                CallDirection::Outgoing => call.callee,
+            };
+
+            match neighbors.iter_mut().find(|(symbol, _)| *symbol == neighbor) {
+                Some((_, regions)) => regions.push(call.region),
+                None => neighbors.push((neighbor, vec![call.region])),
+            }
+        }
+
+        neighbors
+    }
+
+    /// The callers of `root` and their callers, or the functions it calls and the functions they
+    /// call, up to `max_depth` calls away. A function that's already on the path from the root is
+    /// not expanded again.
+    pub fn tree(&self, root: Symbol, direction: CallDirection, max_depth: usize) -> CallTree {
+        let mut path = vec![root];
+
+        CallTree {
+            symbol: root,
+            calls: Vec::new(),
+            recursive: false,
+            truncated: max_depth == 0 && !self.neighbors(root, direction).is_empty(),
+            children: self.subtrees(&mut path, direction, max_depth),
+        }
+    }
+
+    fn subtrees(
+        &self,
+        path: &mut Vec<Symbol>,
+        direction: CallDirection,
+        depth_left: usize,
+    ) -> Vec<CallTree> {
+        if depth_left == 0 {
+            return Vec::new();
+        }
+
+        let symbol = *path.last().unwrap();
+        let mut children = Vec::new();
+
+        for (neighbor, calls) in self.neighbors(symbol, direction) {
+            let recursive = path.contains(&neighbor);
+            let expand = !recursive && depth_left > 1;
+
+            let subtrees = if expand {
+                path.push(neighbor);
+                let subtrees = self.subtrees(path, direction, depth_left - 1);
+                path.pop();
+
+                subtrees
+            } else {
+                Vec::new()
+            };
+
+            children.push(CallTree {
+                symbol: neighbor,
+                calls,
+                recursive,
+                truncated: !recursive && !expand && !self.neighbors(neighbor, direction).is_empty(),
+                children: subtrees,
+            });
+        }
+
+        children
+    }
+}
+
+/// A function and the functions that call it, or that it calls, transitively.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallTree {
+    pub symbol: Symbol,
+    /// Where the calls between this function and its parent in the tree are. They're in the
+    /// calling function: the parent for outgoing calls, or this function for incoming calls.
+    pub calls: Vec<Region>,
+    /// This function is already further up the tree, so its calls aren't repeated
+    pub recursive: bool,
+    /// This function has calls that aren't in the tree, because of the depth limit
+    pub truncated: bool,
+    pub children: Vec<CallTree>,
+}
+
+impl LoadedModule {
+    /// The calls between the top-level defs of every loaded module
+    pub fn call_graph(&self) -> CallGraph {
+        let checked = self.typechecked.values().map(|checked| &checked.decls);
+
+        CallGraph::new(self.declarations_by_id.values().chain(checked))
+    }
+
+    /// The top-level def with this name: `name` for a def of the root module, or `Module.name`
+    /// for a def of another loaded module
+    pub fn top_level_def_named(&self, name: &str) -> Option<Symbol> {
+        let checked = self.typechecked.values().map(|checked| &checked.decls);
+
+        self.declarations_by_id
+            .values()
+            .chain(checked)
+            .flat_map(|decls| decls.symbols.iter())
+            .map(|loc_symbol| loc_symbol.value)
+            .find(|symbol| &*symbol.fully_qualified(&self.interns, self.module_id) == name)
+    }
+}
+
+/// A call tree as text, one function per line, with where the first call to it is.
+///
+/// ```text
+/// main
+/// |-- is_even  Main.roc:3:8
+/// |   `-- is_odd  Main.roc:5:46 (recursive)
+/// `-- Num.to_str  Main.roc:3:30 (+1 more)
+/// ```
+pub fn render_call_tree(
+    tree: &CallTree,
+    direction: CallDirection,
+    home: ModuleId,
+    interns: &Interns,
+    sources: &MutMap<ModuleId, (PathBuf, Box<str>)>,
+) -> String {
+    let mut renderer = Renderer {
+        direction,
+        home,
+        interns,
+        sources,
+        line_infos: MutMap::default(),
+        output: String::new(),
+    };
+
+    renderer
+        .output
+        .push_str(&tree.symbol.fully_qualified(interns, home));
+    renderer.annotations(tree);
+
+    for (index, child) in tree.children.iter().enumerate() {
+        renderer.tree(tree.symbol, child, "", index + 1 == tree.children.len());
+    }
+
+    renderer.output
+}
+
+struct Renderer<'r> {
+    direction: CallDirection,
+    home: ModuleId,
+    interns: &'r Interns,
+    sources: &'r MutMap<ModuleId, (PathBuf, Box<str>)>,
+    line_infos: MutMap<ModuleId, LineInfo>,
+    output: String,
+}
+
+impl Renderer<'_> {
+    fn tree(&mut self, parent: Symbol, tree: &CallTree, prefix: &str, last: bool) {
+        let branch = if last { "`-- " } else { "|-- " };
+        let name = tree.symbol.fully_qualified(self.interns, self.home);
+        write!(self.output, "{prefix}{branch}{name}").unwrap();
+
+        let caller = match self.direction {
+            CallDirection::Incoming => tree.symbol,
+            CallDirection::Outgoing => parent,
+        };
+
+        if let Some(first) = tree.calls.first() {
+            if let Some(location) = self.location(caller.module_id(), *first) {
+                write!(self.output, "  {location}").unwrap();
+            }
+
+            if tree.calls.len() > 1 {
+                write!(self.output, " (+{} more)", tree.calls.len() - 1).unwrap();
+            }
+        }
+
+        self.annotations(tree);
+
+        let prefix = format!("{prefix}{}", if last { "    " } else { "|   " });
+
+        for (index, child) in tree.children.iter().enumerate() {
+            self.tree(
+                tree.symbol,
+                child,
+                &prefix,
+                index + 1 == tree.children.len(),
+            );
+        }
+    }
+
+    fn annotations(&mut self, tree: &CallTree) {
+        if tree.recursive {
+            self.output.push_str(" (recursive)");
+        } else if tree.truncated {
+            self.output.push_str(" ...");
+        }
+
+        self.output.push('\n');
+    }
+
+    fn location(&mut self, module_id: ModuleId, region: Region) -> Option<String> {
+        let (path, src) = self.sources.get(&module_id)?;
+        let line_info = self
+            .line_infos
+            .entry(module_id)
+            .or_insert_with(|| LineInfo::new(src));
+        let position = line_info.convert_pos(region.start());
+        let file_name = path.file_name()?.to_string_lossy();
+
+        Some(format!(
+            "{file_name}:{}:{}",
+            position.line + 1,
+            position.column + 1
+        ))
+    }
+}
@@ -0,0 +1,216 @@
+//! Checking that loading a program gives the same result no matter how many threads do it.
+//!
+//! Modules are loaded, solved and specialized by whichever worker gets to them first, so ids
+//! are interned and problems are found in an order that depends on scheduling. Before anything
+//! can be cached between compilations, that order must not leak into the result. A [Fingerprint]
+//! records everything about a loaded program that should be the same for two loads of the same
+//! source, and [Fingerprint::first_divergence] tells where two of them differ first.
+use crate::file::LoadResult;
+use crate::module::{LoadedModule, MonomorphizedModule};
+use roc_collections::MutMap;
+use roc_module::symbol::{Interns, ModuleId, Symbol};
+use roc_mono::layout::LayoutInterner;
+use roc_problem::can::Problem;
+use roc_solve_problem::TypeError;
+use std::fmt;
+
+/// Everything about a loaded program that must not depend on how it was loaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fingerprint {
+    artifacts: Vec<Artifact>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Artifact {
+    name: &'static str,
+    entries: Vec<String>,
+}
+
+/// The first entry in which two fingerprints differ. An entry is `None` when one of the
+/// fingerprints has fewer entries of this artifact than the other.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    pub artifact: &'static str,
+    pub index: usize,
+    pub first: Option<String>,
+    pub second: Option<String>,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let show = |entry: &Option<String>| match entry {
+            Some(entry) => entry.clone(),
+            None => "(nothing)".to_string(),
+        };
+
+        writeln!(f, "The {} differ at entry {}:", self.artifact, self.index)?;
+        writeln!(f, "    first:  {}", show(&self.first))?;
+        write!(f, "    second: {}", show(&self.second))
+    }
+}
+
+impl Fingerprint {
+    pub fn of_load_result(result: &LoadResult<'_>) -> Self {
+        match result {
+            LoadResult::TypeChecked(loaded) => Self::of_loaded(loaded),
+            LoadResult::Monomorphized(loaded) => Self::of_monomorphized(loaded),
+        }
+    }
+
+    pub fn of_loaded(loaded: &LoadedModule) -> Self {
+        Fingerprint {
+            artifacts: vec![
+                module_ids(&loaded.interns),
+                ident_ids(&loaded.interns),
+                problems(&loaded.can_problems, &loaded.type_problems),
+            ],
+        }
+    }
+
+    pub fn of_monomorphized(loaded: &MonomorphizedModule<'_>) -> Self {
+        let interns = &loaded.interns;
+        let mut procedures: Vec<String> = loaded
+            .procedures
+            .keys()
+            .map(|(symbol, proc_layout)| {
+                let interner = &loaded.layout_interner;
+
+                format!(
+                    "{} : {:?} -> {:?}",
+                    symbol_entry(*symbol, interns),
+                    interner.dbg_stable_iter(proc_layout.arguments),
+                    interner.dbg_stable(proc_layout.result),
+                )
+            })
+            .collect();
+
+        // The procedures are in a hash map, so only their set is meaningful.
+        procedures.sort();
+
+        Fingerprint {
+            artifacts: vec![
+                module_ids(interns),
+                ident_ids(interns),
+                problems(&loaded.can_problems, &loaded.type_problems),
+                Artifact {
+                    name: "specialized procedures",
+                    entries: procedures,
+                },
+            ],
+        }
+    }
+
+    /// The number of entries, over all artifacts.
+    pub fn len(&self) -> usize {
+        self.artifacts
+            .iter()
+            .map(|artifact| artifact.entries.len())
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The first entry that differs, comparing the artifacts in the order they are interned in:
+    /// a module id that differs makes everything after it differ too, so that is the one to fix.
+    pub fn first_divergence(&self, other: &Fingerprint) -> Option<Divergence> {
+        for (mine, theirs) in self.artifacts.iter().zip(other.artifacts.iter()) {
+            let longest = mine.entries.len().max(theirs.entries.len());
+
+            for index in 0..longest {
+                let first = mine.entries.get(index);
+                let second = theirs.entries.get(index);
+
+                if first != second {
+                    return Some(Divergence {
+                        artifact: mine.name,
+                        index,
+                        first: first.cloned(),
+                        second: second.cloned(),
+                    });
+                }
+            }
+        }
+
+        None
+    }
+}
+
+fn module_ids(interns: &Interns) -> Artifact {
+    let entries = interns
+        .module_ids
+        .iter()
+        .map(|(module_id, name)| format!("{} {}", module_id.to_zero_indexed(), name.as_str()))
+        .collect();
+
+    Artifact {
+        name: "module ids",
+        entries,
+    }
+}
+
+/// Every interned identifier, including the ones the compiler generates (like the names of
+/// closures and specializations), by the module it is interned in.
+fn ident_ids(interns: &Interns) -> Artifact {
+    let mut module_ids: Vec<ModuleId> = interns.all_ident_ids.keys().copied().collect();
+    module_ids.sort_by_key(|module_id| module_id.to_zero_indexed());
+
+    let mut entries = Vec::new();
+
+    for module_id in module_ids {
+        let module_name = interns.module_name(module_id);
+
+        for (ident_id, name) in interns.all_ident_ids.get(&module_id).unwrap().ident_strs() {
+            entries.push(format!("{module_name}.{name} {}", ident_id.index()));
+        }
+    }
+
+    Artifact {
+        name: "ident ids",
+        entries,
+    }
+}
+
+/// The problems of each module, in the order they are reported in.
+fn problems(
+    can_problems: &MutMap<ModuleId, Vec<Problem>>,
+    type_problems: &MutMap<ModuleId, Vec<TypeError>>,
+) -> Artifact {
+    let mut module_ids: Vec<ModuleId> = can_problems
+        .keys()
+        .chain(type_problems.keys())
+        .copied()
+        .collect();
+    module_ids.sort_by_key(|module_id| module_id.to_zero_indexed());
+    module_ids.dedup();
+
+    let mut entries = Vec::new();
+
+    for module_id in module_ids {
+        let index = module_id.to_zero_indexed();
+
+        for problem in can_problems.get(&module_id).into_iter().flatten() {
+            entries.push(format!("{index} {problem:?}"));
+        }
+
+        for problem in type_problems.get(&module_id).into_iter().flatten() {
+            entries.push(format!("{index} {problem:?}"));
+        }
+    }
+
+    Artifact {
+        name: "problems",
+        entries,
+    }
+}
+
+fn symbol_entry(symbol: Symbol, interns: &Interns) -> String {
+    format!(
+        "{}.{} ({}.{})",
+        symbol.module_string(interns),
+        symbol.as_str(interns),
+        symbol.module_id().to_zero_indexed(),
+        symbol.ident_id().index()
+    )
+}
@@ -1,12 +1,20 @@
 use crate::docs::DocEntry::DetachedDoc;
 use crate::docs::TypeAnnotation::{Apply, BoundVariable, Function, NoTypeAnn, Record, TagUnion};
+use crate::manifest::PackageManifest;
+use bumpalo::Bump;
+use roc_can::abilities::{AbilitiesStore, ImplKey};
+use roc_can::expr::{DeclarationTag, Declarations};
 use roc_can::scope::Scope;
-use roc_collections::VecSet;
+use roc_can::traverse::symbols_looked_up;
+use roc_collections::{MutMap, VecMap, VecSet};
+use roc_fmt::annotation::Formattable;
+use roc_fmt::{Buf, DbgMigration, MigrationFlags};
 use roc_module::ident::ModuleName;
-use roc_module::symbol::{IdentIds, ModuleId, ModuleIds, Symbol};
+use roc_module::symbol::{IdentIds, Interns, ModuleId, ModuleIds, Symbol};
 use roc_parse::ast::{self, ExtractSpaces, TypeHeader, TypeVar};
 use roc_parse::ast::{AssignedField, FunctionArrow};
 use roc_parse::ast::{CommentOrNewline, TypeDef, ValueDef};
+use roc_region::all::Region;
 
 // Documentation generation requirements
 
@@ -29,6 +37,68 @@ impl ModuleDocumentation {
     }
 }
 
+/// Where a package's docs came from and under what terms, taken from its manifest, so the docs
+/// can show their provenance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageMetadata {
+    pub name: String,
+    pub version: String,
+    /// An SPDX license expression, e.g. `"UPL-1.0"`
+    pub license: Option<String>,
+    /// The package's repository, or its homepage if it doesn't have one
+    pub source_url: Option<String>,
+    pub description: Option<String>,
+    pub authors: Vec<String>,
+}
+
+impl From<&PackageManifest> for PackageMetadata {
+    fn from(manifest: &PackageManifest) -> Self {
+        Self {
+            name: manifest.name.clone(),
+            version: manifest.version.clone(),
+            license: manifest.license.clone(),
+            source_url: manifest
+                .repository
+                .clone()
+                .or_else(|| manifest.homepage.clone()),
+            description: manifest.description.clone(),
+            authors: manifest.authors.clone(),
+        }
+    }
+}
+
+impl PackageMetadata {
+    /// This package's entry in a NOTICE file; see [notice_file].
+    pub fn notice(&self) -> String {
+        let mut buf = format!("{} {}\n", self.name, self.version);
+
+        let license = self.license.as_deref().unwrap_or("not specified");
+        buf.push_str(&format!("License: {license}\n"));
+
+        if let Some(source_url) = &self.source_url {
+            buf.push_str(&format!("Source: {source_url}\n"));
+        }
+
+        if !self.authors.is_empty() {
+            buf.push_str(&format!("Authors: {}\n", self.authors.join(", ")));
+        }
+
+        buf
+    }
+}
+
+/// The contents of a NOTICE file crediting the given packages, e.g. for an app that vendors them.
+pub fn notice_file(packages: &[PackageMetadata]) -> String {
+    let mut buf = String::from("This software includes the following Roc packages:\n");
+
+    for package in packages {
+        buf.push('\n');
+        buf.push_str(&package.notice());
+    }
+
+    buf
+}
+
 #[derive(Debug, Clone)]
 pub enum DocEntry {
     DocDef(DocDef),
@@ -43,6 +113,13 @@ pub struct DocDef {
     pub type_vars: Vec<String>,
     pub type_annotation: TypeAnnotation,
     pub docs: Option<String>,
+    /// For an ability, the opaque types in the loaded modules that implement it.
+    pub implemented_by: Vec<Symbol>,
+    /// For an opaque type, the abilities it implements.
+    pub implements: Vec<Symbol>,
+    /// Formatted top-level `expect`s that use this def, if it's exposed. How many are kept is set
+    /// by `LoadConfig::doc_examples`.
+    pub examples: Vec<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -87,6 +164,29 @@ pub enum TypeAnnotation {
     },
 }
 
+impl TypeAnnotation {
+    /// Whether this is the type of an effectful function, i.e. one with a `=>` arrow. Looks
+    /// through `where` clauses and `as` aliases, but not into the function's arguments.
+    pub fn is_effectful(&self) -> bool {
+        match self {
+            TypeAnnotation::Function { arrow, .. } => *arrow == FunctionArrow::Effectful,
+            TypeAnnotation::Where { ann, .. } | TypeAnnotation::As { ann, .. } => {
+                ann.is_effectful()
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether this is the type of a function, effectful or not; see [TypeAnnotation::is_effectful].
+    pub fn is_function(&self) -> bool {
+        match self {
+            TypeAnnotation::Function { .. } => true,
+            TypeAnnotation::Where { ann, .. } | TypeAnnotation::As { ann, .. } => ann.is_function(),
+            _ => false,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ImplementsClause {
     pub name: String,
@@ -132,8 +232,10 @@ pub fn generate_module_docs(
     exposed_module_ids: &[ModuleId],
     exposed_symbols: VecSet<Symbol>,
     header_comments: &[CommentOrNewline<'_>],
+    declarations: &Declarations,
+    max_examples: usize,
 ) -> ModuleDocumentation {
-    let entries = generate_entry_docs(
+    let mut entries = generate_entry_docs(
         home,
         &scope.locals.ident_ids,
         module_ids,
@@ -142,6 +244,16 @@ pub fn generate_module_docs(
         header_comments,
     );
 
+    if max_examples > 0 {
+        attach_examples(
+            &mut entries,
+            parsed_defs,
+            declarations,
+            &exposed_symbols,
+            max_examples,
+        );
+    }
+
     ModuleDocumentation {
         name: module_name.as_str().to_string(),
         scope,
@@ -150,6 +262,158 @@ pub fn generate_module_docs(
     }
 }
 
+/// Attaches the top-level `expect`s that use each exposed def to its docs, in source order.
+fn attach_examples(
+    entries: &mut [DocEntry],
+    parsed_defs: &roc_parse::ast::Defs,
+    declarations: &Declarations,
+    exposed_symbols: &VecSet<Symbol>,
+    max_examples: usize,
+) {
+    // Canonicalization keeps the region of each condition, so it links the parsed `expect`,
+    // which is what gets formatted, to the symbols it looks up.
+    let mut lookups_by_region: MutMap<Region, Vec<Symbol>> = MutMap::default();
+
+    for (index, tag) in declarations.declarations.iter().enumerate() {
+        if let DeclarationTag::Expectation = tag {
+            let loc_condition = &declarations.expressions[index];
+            lookups_by_region.insert(loc_condition.region, symbols_looked_up(loc_condition));
+        }
+    }
+
+    let arena = Bump::new();
+
+    for either_index in parsed_defs.tags.iter() {
+        let Err(value_index) = either_index.split() else {
+            continue;
+        };
+
+        let value_def = &parsed_defs.value_defs[value_index.index()];
+
+        let ValueDef::Expect { condition, .. } = value_def else {
+            continue;
+        };
+
+        let Some(lookups) = lookups_by_region.get(&condition.region) else {
+            continue;
+        };
+
+        let mut opt_example = None;
+
+        for entry in entries.iter_mut() {
+            if let DocEntry::DocDef(doc_def) = entry {
+                if doc_def.examples.len() < max_examples
+                    && exposed_symbols.contains(&doc_def.symbol)
+                    && lookups.binary_search(&doc_def.symbol).is_ok()
+                {
+                    let example =
+                        opt_example.get_or_insert_with(|| format_example(&arena, value_def));
+                    doc_def.examples.push(example.clone());
+                }
+            }
+        }
+    }
+}
+
+fn format_example(arena: &Bump, value_def: &ValueDef<'_>) -> String {
+    let mut buf = Buf::new_in(
+        arena,
+        MigrationFlags {
+            snakify: false,
+            parens_and_commas: false,
+            dbg: DbgMigration::Keep,
+            transforms: &[],
+        },
+    );
+    value_def.format(&mut buf, 0);
+
+    buf.as_str().trim().to_string()
+}
+
+/// Fills in [DocDef::implemented_by] and [DocDef::implements]. This has to wait until every
+/// module is solved, because an opaque type can implement an ability from any other module.
+pub fn link_ability_implementations<'a>(
+    documentation: &mut VecMap<ModuleId, ModuleDocumentation>,
+    implementations: impl Iterator<Item = &'a ImplKey>,
+    abilities_store: &AbilitiesStore,
+    interns: &Interns,
+) {
+    // The abilities declared in the documented modules, by their members.
+    let mut ability_of_member: MutMap<Symbol, Symbol> = MutMap::default();
+
+    for (module_id, module) in documentation.iter() {
+        let Some(ident_ids) = interns.all_ident_ids.get(module_id) else {
+            continue;
+        };
+
+        for entry in module.entries.iter() {
+            if let DocEntry::DocDef(DocDef {
+                symbol,
+                type_annotation: TypeAnnotation::Ability { members },
+                ..
+            }) = entry
+            {
+                for member in members {
+                    if let Some(ident_id) = ident_ids.get_id(&member.name) {
+                        ability_of_member.insert(Symbol::new(*module_id, ident_id), *symbol);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut implemented_by: MutMap<Symbol, VecSet<Symbol>> = MutMap::default();
+    let mut implements: MutMap<Symbol, VecSet<Symbol>> = MutMap::default();
+
+    for impl_key in implementations {
+        // Abilities that aren't documented here, like the builtin ones, are still worth listing
+        // on the opaque types that implement them.
+        let opt_ability = ability_of_member
+            .get(&impl_key.ability_member)
+            .copied()
+            .or_else(|| {
+                abilities_store
+                    .member_def(impl_key.ability_member)
+                    .map(|member| member.parent_ability)
+            });
+
+        if let Some(ability) = opt_ability {
+            implemented_by
+                .entry(ability)
+                .or_default()
+                .insert(impl_key.opaque);
+            implements
+                .entry(impl_key.opaque)
+                .or_default()
+                .insert(ability);
+        }
+    }
+
+    let sorted = |symbols: &VecSet<Symbol>| {
+        let mut symbols: Vec<Symbol> = symbols.iter().copied().collect();
+        symbols.sort_by_key(|symbol| {
+            (
+                symbol.module_string(interns).as_str(),
+                symbol.as_str(interns),
+            )
+        });
+        symbols
+    };
+
+    for (_, module) in documentation.iter_mut() {
+        for entry in module.entries.iter_mut() {
+            if let DocEntry::DocDef(doc_def) = entry {
+                if let Some(opaques) = implemented_by.get(&doc_def.symbol) {
+                    doc_def.implemented_by = sorted(opaques);
+                }
+                if let Some(abilities) = implements.get(&doc_def.symbol) {
+                    doc_def.implements = sorted(abilities);
+                }
+            }
+        }
+    }
+}
+
 fn detached_docs_from_comments_and_new_lines<'a>(
     comments_or_new_lines: impl Iterator<Item = &'a roc_parse::ast::CommentOrNewline<'a>>,
 ) -> Vec<String> {
@@ -223,6 +487,9 @@ fn generate_entry_docs(
                                 type_annotation: type_to_docs(false, loc_ann.value),
                                 type_vars: Vec::new(),
                                 docs,
+                                implemented_by: Vec::new(),
+                                implements: Vec::new(),
+                                examples: Vec::new(),
                             };
                             doc_entries.push(DocEntry::DocDef(doc_def));
                         }
@@ -243,6 +510,9 @@ fn generate_entry_docs(
                                 type_vars: Vec::new(),
                                 symbol: Symbol::new(home, ident_id),
                                 docs,
+                                implemented_by: Vec::new(),
+                                implements: Vec::new(),
+                                examples: Vec::new(),
                             };
                             doc_entries.push(DocEntry::DocDef(doc_def));
                         }
@@ -259,6 +529,9 @@ fn generate_entry_docs(
                                 type_vars: Vec::new(),
                                 symbol: Symbol::new(home, ident_id),
                                 docs,
+                                implemented_by: Vec::new(),
+                                implements: Vec::new(),
+                                examples: Vec::new(),
                             };
                             doc_entries.push(DocEntry::DocDef(doc_def));
                         }
@@ -297,6 +570,9 @@ fn generate_entry_docs(
                                 type_vars: Vec::new(),
                                 symbol: Symbol::new(home, ident_id),
                                 docs,
+                                implemented_by: Vec::new(),
+                                implements: Vec::new(),
+                                examples: Vec::new(),
                             };
                             doc_entries.push(DocEntry::DocDef(doc_def));
                         }
@@ -335,6 +611,9 @@ fn generate_entry_docs(
                         type_vars,
                         docs,
                         symbol: Symbol::new(home, ident_id),
+                        implemented_by: Vec::new(),
+                        implements: Vec::new(),
+                        examples: Vec::new(),
                     };
                     doc_entries.push(DocEntry::DocDef(doc_def));
                 }
@@ -358,6 +637,9 @@ fn generate_entry_docs(
                         type_vars,
                         docs,
                         symbol: Symbol::new(home, ident_id),
+                        implemented_by: Vec::new(),
+                        implements: Vec::new(),
+                        examples: Vec::new(),
                     };
                     doc_entries.push(DocEntry::DocDef(doc_def));
                 }
@@ -398,6 +680,9 @@ fn generate_entry_docs(
                         symbol: Symbol::new(home, ident_id),
                         type_vars,
                         docs,
+                        implemented_by: Vec::new(),
+                        implements: Vec::new(),
+                        examples: Vec::new(),
                     };
                     doc_entries.push(DocEntry::DocDef(doc_def));
                 }
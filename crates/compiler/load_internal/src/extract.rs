@@ -0,0 +1,218 @@
+//! Moving an expression into a new top-level function, for editors.
+use crate::module::LoadedModule;
+use crate::rename::{is_value_name, FileEdits};
+use roc_can::expr::Declarations;
+use roc_can::traverse::{extractable_expr, symbols_looked_up};
+use roc_module::symbol::{Interns, ModuleId};
+use roc_problem::fix::Edit;
+use roc_region::all::Region;
+use roc_types::pretty_print::{name_and_print_var, DebugPrint};
+use roc_types::subs::{Content, Descriptor, FlatType, Subs, Variable};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExtractError {
+    /// There's no expression with exactly this region in the module
+    NotAnExpression,
+    /// The expression returns early from the function it's in, with `return` or `?`, which it
+    /// can't do from a function of its own
+    ReturnsEarly,
+    /// The expression, or a value it uses, has a type error
+    TypeError,
+    /// The new name isn't a valid name for a value, or its `!` suffix doesn't say whether the new
+    /// function is effectful
+    InvalidName { name: String, effectful: bool },
+    /// The module already has something with the new name
+    NameTaken { name: String },
+}
+
+/// The edits that move the expression at `region` into a new top-level def named `name`, right
+/// after the top-level def it's in, and call that instead.
+///
+/// The locals the expression uses become the arguments of the new function, in the order the
+/// expression first uses them. An expression that uses no locals becomes a value, unless it's
+/// effectful, in which case it becomes a function that takes `{}`.
+pub fn extract_function(
+    src: &str,
+    decls: &Declarations,
+    subs: &Subs,
+    home: ModuleId,
+    interns: &Interns,
+    region: Region,
+    name: &str,
+) -> Result<Vec<Edit>, ExtractError> {
+    let found = extractable_expr(region, decls, subs).ok_or(ExtractError::NotAnExpression)?;
+
+    if found.returns_early {
+        return Err(ExtractError::ReturnsEarly);
+    }
+
+    if !is_value_name(name) || name.ends_with('!') != found.effectful {
+        return Err(ExtractError::InvalidName {
+            name: name.to_string(),
+            effectful: found.effectful,
+        });
+    }
+
+    if name_in_use(name, decls, home, interns) {
+        return Err(ExtractError::NameTaken {
+            name: name.to_string(),
+        });
+    }
+
+    let arguments: Vec<&str> = found
+        .free_variables
+        .iter()
+        .map(|(symbol, _)| symbol.as_str(interns))
+        .collect();
+
+    let mut subs = subs.clone();
+    let takes_unit = arguments.is_empty() && found.effectful;
+
+    let var = if arguments.is_empty() && !takes_unit {
+        found.var
+    } else {
+        let argument_vars = if takes_unit {
+            subs.insert_into_vars([Variable::EMPTY_RECORD])
+        } else {
+            subs.insert_into_vars(found.free_variables.iter().map(|(_, var)| *var))
+        };
+        let closure_var = subs.fresh_unnamed_flex_var();
+        let fx_var = if found.effectful {
+            Variable::EFFECTFUL
+        } else {
+            Variable::PURE
+        };
+
+        subs.fresh(Descriptor::from(Content::Structure(FlatType::Func(
+            argument_vars,
+            closure_var,
+            found.var,
+            fx_var,
+        ))))
+    };
+
+    if subs.var_contains_error(var) {
+        return Err(ExtractError::TypeError);
+    }
+
+    let signature = name_and_print_var(var, &mut subs, home, interns, DebugPrint::NOTHING);
+
+    let (lambda, call) = if takes_unit {
+        ("|{}|".to_string(), format!("{name}({{}})"))
+    } else if arguments.is_empty() {
+        (String::new(), name.to_string())
+    } else {
+        let arguments = arguments.join(", ");
+
+        (format!("|{arguments}|"), format!("{name}({arguments})"))
+    };
+
+    let head = if lambda.is_empty() {
+        format!("{name} =")
+    } else {
+        format!("{name} = {lambda}")
+    };
+    let body = reindented(src, region);
+    let definition = if body.contains('\n') {
+        format!("{head}\n    {body}")
+    } else {
+        format!("{head} {body}")
+    };
+
+    let end_of_decl = Region::new(found.decl_region.end(), found.decl_region.end());
+
+    Ok(vec![
+        Edit {
+            region,
+            replacement: call,
+        },
+        Edit {
+            region: end_of_decl,
+            replacement: format!("\n\n{name} : {signature}\n{definition}"),
+        },
+    ])
+}
+
+impl LoadedModule {
+    /// [extract_function] in one of the loaded modules.
+    pub fn extract_function(
+        &self,
+        module_id: ModuleId,
+        region: Region,
+        name: &str,
+    ) -> Result<FileEdits, ExtractError> {
+        let checked = if module_id == self.module_id {
+            self.declarations_by_id
+                .get(&module_id)
+                .map(|decls| (decls, self.solved.inner()))
+        } else {
+            self.typechecked
+                .get(&module_id)
+                .map(|checked| (&checked.decls, checked.solved_subs.inner()))
+        };
+
+        let (Some((decls, subs)), Some((path, src))) = (checked, self.sources.get(&module_id))
+        else {
+            return Err(ExtractError::NotAnExpression);
+        };
+
+        let imported = self.exposed_imports.get(&module_id).is_some_and(|imports| {
+            imports
+                .keys()
+                .any(|symbol| symbol.as_str(&self.interns) == name)
+        });
+
+        if imported {
+            return Err(ExtractError::NameTaken {
+                name: name.to_string(),
+            });
+        }
+
+        let edits = extract_function(src, decls, subs, module_id, &self.interns, region, name)?;
+
+        Ok(FileEdits {
+            module_id,
+            path: path.clone(),
+            edits,
+        })
+    }
+}
+
+fn name_in_use(name: &str, decls: &Declarations, home: ModuleId, interns: &Interns) -> bool {
+    let defined = interns
+        .all_ident_ids
+        .get(&home)
+        .is_some_and(|ident_ids| ident_ids.get_id(name).is_some());
+
+    defined
+        || decls.expressions.iter().any(|loc_expr| {
+            symbols_looked_up(loc_expr)
+                .iter()
+                .any(|symbol| symbol.as_str(interns) == name)
+        })
+}
+
+/// The source of the expression at `region`, with the lines after its first one indented by one
+/// level, relative to the line it starts on.
+fn reindented(src: &str, region: Region) -> String {
+    let start = region.start().byte_offset();
+    let end = region.end().byte_offset();
+    let line_start = src[..start].rfind('\n').map_or(0, |newline| newline + 1);
+    let base_indent = src[line_start..start].len() - src[line_start..start].trim_start().len();
+
+    let mut lines = src[start..end].lines();
+    let mut output = lines.next().unwrap_or_default().to_string();
+
+    for line in lines {
+        let indent = line.len() - line.trim_start().len();
+
+        output.push('\n');
+
+        if !line.trim().is_empty() {
+            output.push_str("    ");
+            output.push_str(&line[indent.min(base_indent)..]);
+        }
+    }
+
+    output
+}
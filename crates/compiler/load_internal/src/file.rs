@@ -1,12 +1,16 @@
 #![allow(clippy::too_many_arguments)]
 
 use crate::docs::ModuleDocumentation;
+use crate::import_cycle::{CycleImport, ImportCycle};
+use crate::manifest::{ManifestProblem, PackageManifest};
 use crate::module::{
     CheckedModule, ConstrainedModule, EntryPoint, Expectations, ExposedToHost,
-    FoundSpecializationsModule, LateSpecializationsModule, LoadedModule, ModuleHeader,
-    ModuleTiming, MonomorphizedModule, ParsedModule, ToplevelExpects, TypeCheckedModule,
+    FoundSpecializationsModule, LateSpecializationsModule, LoadedModule, ModuleArenaStats,
+    ModuleHeader, ModuleTiming, MonomorphizedModule, ParsedModule, ToplevelExpects,
+    TypeCheckedModule,
 };
 use crate::module_cache::ModuleCache;
+use crate::warnings::WarningConfig;
 use bumpalo::{collections::CollectIn, Bump};
 use crossbeam::channel::{bounded, Sender};
 use crossbeam::deque::{Injector, Worker};
@@ -15,6 +19,7 @@ use parking_lot::Mutex;
 use roc_builtins::roc::module_source;
 use roc_can::abilities::{AbilitiesStore, PendingAbilitiesStore, ResolvedImpl};
 use roc_can::constraint::{Constraint as ConstraintSoa, Constraints, TypeOrVar};
+use roc_can::directive::{comptime_defs, comptime_expects, tail_recursive_defs};
 use roc_can::env::Env;
 use roc_can::expr::{Declarations, ExpectLookup, PendingDerives};
 use roc_can::module::{
@@ -22,9 +27,10 @@ use roc_can::module::{
     ModuleParams, ResolvedImplementations, TypeState,
 };
 use roc_can::scope::Scope;
+use roc_can::traverse::{find_capturing_closures, FoundClosure};
 use roc_can_solo::module::{solo_canonicalize_module_defs, SoloCanOutput};
 use roc_collections::soa::slice_extend_new;
-use roc_collections::{default_hasher, BumpMap, MutMap, MutSet, VecMap, VecSet};
+use roc_collections::{default_hasher, ArenaStats, BumpMap, MutMap, MutSet, VecMap, VecSet};
 use roc_constrain::module::constrain_module;
 use roc_debug_flags::dbg_do;
 #[cfg(debug_assertions)]
@@ -41,16 +47,23 @@ use roc_module::symbol::{
     IdentIds, IdentIdsByModule, Interns, ModuleId, ModuleIds, PQModuleName, PackageModuleIds,
     PackageQualified, Symbol,
 };
+use roc_mono::borrow::{BorrowInference, InferredBorrows};
+use roc_mono::comptime::ComptimeFailure;
+pub use roc_mono::comptime::ComptimeLimits;
+use roc_mono::coverage::BranchRegions;
+use roc_mono::dead_fields::DeadFields;
 use roc_mono::ir::{
-    CapturedSymbols, ExternalSpecializations, GlueLayouts, HostExposedLambdaSets, PartialProc,
-    Proc, ProcLayout, Procs, ProcsBase, UpdateModeIds, UsageTrackingMap,
+    CapturedSymbols, ExternalSpecializations, GlueLayouts, HostExposedLambdaSets, MatchTree,
+    PartialProc, Proc, ProcLayout, Procs, ProcsBase, UpdateModeIds, UsageTrackingMap,
 };
 use roc_mono::layout::{
     GlobalLayoutInterner, LambdaName, Layout, LayoutCache, LayoutProblem, Niche, STLayoutInterner,
 };
+use roc_mono::profile::{Profile, ProfileHints};
 use roc_mono::reset_reuse;
 use roc_mono::{drop_specialization, inc_dec};
 use roc_packaging::cache::RocCacheDir;
+use roc_packaging::resolve::Dependency;
 use roc_parse::ast::{self, CommentOrNewline, ExtractSpaces, Spaced, ValueDef};
 use roc_parse::header::parse_module_defs;
 use roc_parse::header::{
@@ -61,10 +74,12 @@ use roc_parse::parser::{FileError, SourceError, SyntaxError};
 use roc_problem::Severity;
 use roc_region::all::{LineInfo, Loc, Region};
 use roc_reporting::error::r#type::suggest;
-#[cfg(not(target_family = "wasm"))]
-use roc_reporting::report::to_https_problem_report_string;
 use roc_reporting::report::{to_file_problem_report_string, Palette, RenderTarget};
+#[cfg(not(target_family = "wasm"))]
+use roc_reporting::report::{to_https_problem_report_string, to_lockfile_problem_report_string};
+use roc_solve::ability_trace::AbilityTrace;
 use roc_solve::module::{extract_module_owned_implementations, SolveConfig, Solved, SolvedModule};
+use roc_solve::solve::CompatibleSuggestions;
 use roc_solve::FunctionKind;
 use roc_solve_problem::TypeError;
 use roc_target::Target;
@@ -77,12 +92,14 @@ use std::io;
 use std::ops::ControlFlow;
 use std::path::{Path, PathBuf};
 use std::str::from_utf8_unchecked;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::{env, fs};
 #[cfg(not(target_family = "wasm"))]
 use {
     roc_packaging::cache::{self},
     roc_packaging::https::{PackageMetadata, Problem},
+    roc_packaging::lockfile::{Lockfile, LOCKFILE_NAME},
 };
 
 pub use roc_work::Phase;
@@ -108,7 +125,7 @@ macro_rules! log {
     ($($arg:tt)*) => (dbg_do!(ROC_PRINT_LOAD_LOG, println!($($arg)*)))
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct LoadConfig {
     pub target: Target,
     pub render: RenderTarget,
@@ -116,6 +133,18 @@ pub struct LoadConfig {
     pub threading: Threading,
     pub exec_mode: ExecutionMode,
     pub function_kind: FunctionKind,
+    /// Keep the decision tree that each `when` is compiled to, for `--emit=match-tree`
+    pub emit_match_trees: bool,
+    /// Trace how ability obligations are checked, for `--trace-abilities`.
+    /// See [roc_solve::ability_trace].
+    pub trace_abilities: bool,
+    /// Call counts from an instrumented run, for `--profile-use`. See [roc_mono::profile].
+    pub profile: Option<Profile>,
+    /// How many top-level `expect`s to attach to each exposed def's docs as usage examples.
+    /// See [crate::docs::DocDef::examples].
+    pub doc_examples: usize,
+    /// How much work evaluating each `# @comptime` def or expect may do.
+    pub comptime_limits: ComptimeLimits,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -331,6 +360,7 @@ fn start_phase<'a>(
                     skip_constraint_gen,
                     exposed_module_ids: state.exposed_modules,
                     exec_mode: state.exec_mode,
+                    doc_examples: state.doc_examples,
                     imported_module_params,
                     solo_can_output,
                 }
@@ -383,6 +413,7 @@ fn start_phase<'a>(
                     derived_module,
                     state.exec_mode,
                     is_host_exposed,
+                    state.ability_trace.is_some(),
                     //
                     #[cfg(debug_assertions)]
                     checkmate,
@@ -433,6 +464,12 @@ fn start_phase<'a>(
 
                 let build_expects =
                     matches!(state.exec_mode, ExecutionMode::Test) && expectations.is_some();
+                let comptime_expects = state
+                    .comptime_expects
+                    .keys()
+                    .filter(|symbol| symbol.module_id() == module_id)
+                    .copied()
+                    .collect();
 
                 BuildTask::BuildPendingSpecializations {
                     layout_cache,
@@ -449,6 +486,7 @@ fn start_phase<'a>(
                     derived_module,
                     expectations,
                     build_expects,
+                    comptime_expects,
                 }
             }
             Phase::MakeSpecializations => {
@@ -568,6 +606,8 @@ fn start_phase<'a>(
                     exposed_by_module: state.exposed_types.clone(),
                     derived_module,
                     expectations,
+                    emit_match_trees: state.match_trees.is_some(),
+                    record_branch_regions: state.branch_regions.is_some(),
                 }
             }
         }
@@ -644,6 +684,8 @@ enum Msg<'a> {
         module_timing: ModuleTiming,
         subs: Subs,
         expectations: Option<Expectations>,
+        match_trees: std::vec::Vec<MatchTree>,
+        branch_regions: BranchRegions,
     },
 
     /// The task is to only typecheck AND monomorphize modules
@@ -743,6 +785,56 @@ struct State<'a> {
     pub toplevel_expects: MutMap<ModuleId, ToplevelExpects>,
     pub exposed_to_host: ExposedToHost,
 
+    /// Where each top-level def in a module that gets specialized was defined
+    pub def_regions: MutMap<Symbol, Region>,
+
+    /// The capturing closures in the modules that get specialized
+    pub closures: MutMap<Symbol, FoundClosure>,
+
+    /// The top-level defs marked `# @tailrec`, which must only make tail calls to themselves
+    pub tail_recursive_defs: MutMap<Symbol, Region>,
+
+    /// The top-level defs marked `# @comptime`, which are evaluated while compiling
+    pub comptime_defs: MutMap<Symbol, Region>,
+
+    /// The top-level expects marked `# @comptime`, which are checked while compiling
+    pub comptime_expects: MutMap<Symbol, Region>,
+
+    /// The `# @comptime` defs that couldn't be evaluated while compiling, and expects that failed
+    pub comptime_failures: Vec<ComptimeFailure>,
+
+    /// How much work evaluating each `# @comptime` def or expect may do
+    pub comptime_limits: ComptimeLimits,
+
+    /// The fields that were removed from records because they're never read
+    pub dead_fields: Vec<DeadFields<'a>>,
+
+    /// Call counts from an instrumented run, which code generation uses to decide what to inline
+    pub profile: Option<Profile>,
+
+    /// The ownership of the parameters of every proc, decided by borrow inference
+    pub inferred_borrows: InferredBorrows<'a>,
+
+    /// The decision trees of the `when` expressions, or `None` if they aren't wanted
+    pub match_trees: Option<std::vec::Vec<MatchTree>>,
+
+    /// Where the branches of switches come from, or `None` if they aren't wanted. Only tests
+    /// can report coverage, so these are only recorded in [ExecutionMode::Test].
+    pub branch_regions: Option<BranchRegions>,
+
+    /// Why ability obligations weren't fulfilled, or `None` if abilities aren't traced
+    pub ability_trace: Option<AbilityTrace>,
+
+    /// How many usage examples to attach to each exposed def's docs
+    pub doc_examples: usize,
+
+    /// The levels of the warnings in the root package, from the root module's header
+    pub warnings: WarningConfig,
+
+    /// The `# @warnings` directives that don't parse, which are reported with the problems of
+    /// the root module
+    pub warning_directive_problems: Vec<roc_problem::can::Problem>,
+
     /// This is the "final" list of IdentIds, after canonicalization and constraint gen
     /// have completed for a given module.
     pub constrained_ident_ids: IdentIdsByModule,
@@ -805,6 +897,11 @@ impl<'a> State<'a> {
         palette: Palette,
         number_of_workers: usize,
         exec_mode: ExecutionMode,
+        emit_match_trees: bool,
+        trace_abilities: bool,
+        profile: Option<Profile>,
+        doc_examples: usize,
+        comptime_limits: ComptimeLimits,
     ) -> Self {
         let cache_dir = roc_packaging::cache::roc_cache_packages_dir();
         let dependencies = Dependencies::new(exec_mode.goal_phase());
@@ -826,6 +923,22 @@ impl<'a> State<'a> {
             host_exposed_lambda_sets: std::vec::Vec::new(),
             toplevel_expects: MutMap::default(),
             exposed_to_host: ExposedToHost::default(),
+            def_regions: MutMap::default(),
+            closures: MutMap::default(),
+            tail_recursive_defs: MutMap::default(),
+            comptime_defs: MutMap::default(),
+            comptime_expects: MutMap::default(),
+            comptime_failures: Vec::new(),
+            comptime_limits,
+            dead_fields: Vec::new(),
+            profile,
+            inferred_borrows: MutMap::default(),
+            match_trees: emit_match_trees.then(std::vec::Vec::new),
+            branch_regions: matches!(exec_mode, ExecutionMode::Test).then(MutMap::default),
+            ability_trace: trace_abilities.then(AbilityTrace::default),
+            doc_examples,
+            warnings: WarningConfig::default(),
+            warning_directive_problems: Vec::new(),
             exposed_modules: &[],
             exposed_types,
             arc_modules,
@@ -848,6 +961,22 @@ impl<'a> State<'a> {
     }
 }
 
+/// The name of a module that the warning configuration applies to, which are the modules of the
+/// root package, or `None` if it doesn't apply to this one.
+fn configured_module_name(state: &State, module_id: ModuleId) -> Option<String> {
+    if state.warnings.is_empty() || module_id.is_builtin() {
+        return None;
+    }
+
+    let modules = state.arc_modules.lock();
+
+    if !modules.package_eq(module_id, state.root_id)? {
+        return None;
+    }
+
+    Some(modules.get_name(module_id)?.as_inner().to_string())
+}
+
 fn report_timing(
     buf: &mut impl std::fmt::Write,
     label: &str,
@@ -922,6 +1051,7 @@ enum BuildTask<'a> {
         exposed_module_ids: &'a [ModuleId],
         skip_constraint_gen: bool,
         exec_mode: ExecutionMode,
+        doc_examples: usize,
         imported_module_params: VecMap<ModuleId, ModuleParams>,
         solo_can_output: SoloCanOutput<'a>,
     },
@@ -942,6 +1072,7 @@ enum BuildTask<'a> {
         derived_module: SharedDerivedModule,
         exec_mode: ExecutionMode,
         is_host_exposed: bool,
+        trace_abilities: bool,
 
         #[cfg(debug_assertions)]
         checkmate: Option<roc_checkmate::Collector>,
@@ -960,6 +1091,7 @@ enum BuildTask<'a> {
         derived_module: SharedDerivedModule,
         expectations: Option<Expectations>,
         build_expects: bool,
+        comptime_expects: MutSet<Symbol>,
     },
     MakeSpecializations {
         module_id: ModuleId,
@@ -973,6 +1105,8 @@ enum BuildTask<'a> {
         world_abilities: WorldAbilities,
         derived_module: SharedDerivedModule,
         expectations: Option<Expectations>,
+        emit_match_trees: bool,
+        record_branch_regions: bool,
     },
 }
 
@@ -1012,13 +1146,20 @@ pub enum LoadingProblem<'a> {
         available: AvailableShorthands<'a>,
     },
 
+    InvalidManifest {
+        filename: PathBuf,
+        module_id: ModuleId,
+        source: &'a [u8],
+        problem: ManifestProblem,
+    },
+
     ErrJoiningWorkerThreads,
     TriedToImportAppModule,
 
     /// a formatted report
     FormattedReport(String, Option<Region>),
 
-    ImportCycle(PathBuf, Vec<ModuleId>),
+    ImportCycle(PathBuf, ImportCycle),
     IncorrectModuleName(FileError<'a, IncorrectModuleName<'a>>),
     CouldNotFindCacheDir,
     ChannelProblem(ChannelProblem),
@@ -1051,6 +1192,7 @@ impl<'a> LoadingProblem<'a> {
                 filename: _,
                 error: _,
             } => None,
+            LoadingProblem::InvalidManifest { problem, .. } => Some(problem.region()),
             LoadingProblem::UnexpectedHeader(_) => None,
             LoadingProblem::ErrJoiningWorkerThreads => None,
             LoadingProblem::TriedToImportAppModule => None,
@@ -1144,6 +1286,11 @@ pub fn load_and_typecheck_str<'a>(
         threading,
         exec_mode: ExecutionMode::Check,
         function_kind,
+        emit_match_trees: false,
+        trace_abilities: false,
+        profile: None,
+        doc_examples: 0,
+        comptime_limits: ComptimeLimits::default(),
     };
 
     match load(
@@ -1422,9 +1569,17 @@ fn load_packages_from_main<'a>(
 
     use ast::Header::*;
 
+    // An invalid manifest is reported when the package header is built.
+    let mut dependencies = Vec::new();
+
     let packages = match parsed_header.item {
-        App(AppHeader { packages, .. }) | Package(PackageHeader { packages, .. }) => {
-            unspace(arena, packages.value.items)
+        App(AppHeader { packages, .. }) => unspace(arena, packages.value.items),
+        Package(header) => {
+            if let Ok(Some(manifest)) = PackageManifest::from_header(&header) {
+                dependencies = manifest.dependencies;
+            }
+
+            unspace(arena, header.packages.value.items)
         }
         Platform(PlatformHeader { packages, .. }) => unspace(arena, packages.item.items),
         Module(_) | Hosted(_) => todo!("expected {} to be an app or package", filename.display()),
@@ -1440,6 +1595,7 @@ fn load_packages_from_main<'a>(
         module_ids,
         ident_ids_by_module,
         filename.clone(),
+        Some(&dependencies),
     );
 
     let package_entries = packages
@@ -1563,6 +1719,11 @@ pub fn load<'a>(
             load_config.render,
             load_config.palette,
             load_config.exec_mode,
+            load_config.emit_match_trees,
+            load_config.trace_abilities,
+            load_config.profile,
+            load_config.doc_examples,
+            load_config.comptime_limits,
             roc_cache_dir,
         ),
         Threads::Many(threads) => load_multi_threaded(
@@ -1576,6 +1737,11 @@ pub fn load<'a>(
             load_config.palette,
             threads,
             load_config.exec_mode,
+            load_config.emit_match_trees,
+            load_config.trace_abilities,
+            load_config.profile,
+            load_config.doc_examples,
+            load_config.comptime_limits,
             roc_cache_dir,
         ),
     }
@@ -1592,6 +1758,11 @@ pub fn load_single_threaded<'a>(
     render: RenderTarget,
     palette: Palette,
     exec_mode: ExecutionMode,
+    emit_match_trees: bool,
+    trace_abilities: bool,
+    profile: Option<Profile>,
+    doc_examples: usize,
+    comptime_limits: ComptimeLimits,
     roc_cache_dir: RocCacheDir<'_>,
 ) -> Result<LoadResult<'a>, LoadingProblem<'a>> {
     let LoadStart {
@@ -1630,6 +1801,11 @@ pub fn load_single_threaded<'a>(
         palette,
         number_of_workers,
         exec_mode,
+        emit_match_trees,
+        trace_abilities,
+        profile,
+        doc_examples,
+        comptime_limits,
     );
 
     // We'll add tasks to this, and then worker threads will take tasks from it.
@@ -1643,10 +1819,14 @@ pub fn load_single_threaded<'a>(
     let stealer = worker.stealer();
     let stealers = &[stealer];
 
+    let arena_stats = Mutex::new(MutMap::default());
+
     // now we just manually interleave stepping the state "thread" and the worker "thread"
     loop {
         match state_thread_step(arena, state, &worker_wakers, &injector, &msg_tx, &msg_rx) {
-            Ok(ControlFlow::Break(done)) => return Ok(done),
+            Ok(ControlFlow::Break(done)) => {
+                return Ok(with_arena_stats(done, arena_stats.into_inner()));
+            }
             Ok(ControlFlow::Continue(new_state)) => {
                 state = new_state;
             }
@@ -1656,7 +1836,15 @@ pub fn load_single_threaded<'a>(
         // then check if the worker can step
         let control_flow =
             roc_worker::worker_task_step(&worker, &injector, stealers, &worker_wakup_rx, |task| {
-                run_task(task, arena, &src_dir, msg_tx.clone(), roc_cache_dir, target)
+                run_task_with_arena_stats(
+                    task,
+                    arena,
+                    &src_dir,
+                    msg_tx.clone(),
+                    roc_cache_dir,
+                    target,
+                    &arena_stats,
+                )
             });
 
         match control_flow {
@@ -1958,6 +2146,20 @@ pub fn report_loading_problem(
             available,
             render,
         ),
+        LoadingProblem::InvalidManifest {
+            filename,
+            module_id,
+            source,
+            problem,
+        } => to_invalid_manifest_report(
+            module_ids,
+            IdentIds::exposed_builtins(0),
+            module_id,
+            filename,
+            source,
+            problem,
+            render,
+        ),
         err => todo!("Loading error: {:?}", err),
     }
 }
@@ -1973,6 +2175,11 @@ fn load_multi_threaded<'a>(
     palette: Palette,
     available_threads: usize,
     exec_mode: ExecutionMode,
+    emit_match_trees: bool,
+    trace_abilities: bool,
+    profile: Option<Profile>,
+    doc_examples: usize,
+    comptime_limits: ComptimeLimits,
     roc_cache_dir: RocCacheDir<'_>,
 ) -> Result<LoadResult<'a>, LoadingProblem<'a>> {
     let LoadStart {
@@ -2026,6 +2233,11 @@ fn load_multi_threaded<'a>(
         palette,
         num_workers,
         exec_mode,
+        emit_match_trees,
+        trace_abilities,
+        profile,
+        doc_examples,
+        comptime_limits,
     );
 
     // an arena for every worker, stored in an arena-allocated bumpalo vec to make the lifetimes work
@@ -2054,7 +2266,13 @@ fn load_multi_threaded<'a>(
     let stealers = stealers.into_bump_slice();
     let it = worker_arenas.iter_mut();
 
-    {
+    // The worker arenas can't be shared with the main thread, so each worker reports its own size.
+    let worker_arena_bytes: Vec<AtomicUsize> = std::iter::repeat_with(AtomicUsize::default)
+        .take(num_workers)
+        .collect();
+    let arena_stats = Mutex::new(MutMap::default());
+
+    let load_result = {
         let thread_result = thread::scope(|thread_scope| {
             // Careful! It's important that worker listeners aren't allocated in the arena,
             // since they need to be correctly dropped if we have a panic in this thread::scope code.
@@ -2065,7 +2283,7 @@ fn load_multi_threaded<'a>(
             // causing the thread::scope block to hang while it waits for the worker threads to exit.
             let mut worker_wakers = Vec::with_capacity(num_workers);
 
-            for worker_arena in it {
+            for (worker_arena, arena_bytes) in it.zip(worker_arena_bytes.iter()) {
                 let msg_tx = msg_tx.clone();
                 let worker = worker_queues.pop().unwrap();
 
@@ -2077,6 +2295,7 @@ fn load_multi_threaded<'a>(
                 // (since other threads need to reference it too). Same with src_dir.
                 let injector = &injector;
                 let src_dir = &src_dir;
+                let arena_stats = &arena_stats;
 
                 // Record this thread's handle so the main thread can join it later.
                 let res_join_handle = thread_scope
@@ -2090,14 +2309,20 @@ fn load_multi_threaded<'a>(
                             stealers,
                             worker_wakup_rx,
                             |task| {
-                                run_task(
+                                let result = run_task_with_arena_stats(
                                     task,
                                     worker_arena,
                                     src_dir,
                                     msg_tx.clone(),
                                     roc_cache_dir,
                                     target,
-                                )
+                                    arena_stats,
+                                );
+
+                                arena_bytes
+                                    .store(worker_arena.allocated_bytes(), Ordering::Relaxed);
+
+                                result
                             },
                         )
                     });
@@ -2158,7 +2383,35 @@ fn load_multi_threaded<'a>(
                 .to_string(),
             None))
         })
+    };
+
+    // The worker arenas are only freed along with the main arena, so this is their high-water mark.
+    let worker_arena_bytes = worker_arena_bytes
+        .iter()
+        .map(|bytes| bytes.load(Ordering::Relaxed))
+        .sum();
+
+    let arena_stats = arena_stats.into_inner();
+
+    load_result.map(|mut load_result| {
+        if let LoadResult::Monomorphized(module) = &mut load_result {
+            module.worker_arena_bytes = worker_arena_bytes;
+        }
+
+        with_arena_stats(load_result, arena_stats)
+    })
+}
+
+fn with_arena_stats(
+    mut load_result: LoadResult<'_>,
+    arena_stats: MutMap<ModuleId, ModuleArenaStats>,
+) -> LoadResult<'_> {
+    match &mut load_result {
+        LoadResult::TypeChecked(module) => module.arena_stats = arena_stats,
+        LoadResult::Monomorphized(module) => module.arena_stats = arena_stats,
     }
+
+    load_result
 }
 
 fn start_tasks<'a>(
@@ -2255,6 +2508,11 @@ fn update<'a>(
             let home = header.module_id;
             let mut work = MutSet::default();
 
+            if header.is_root_module {
+                (state.warnings, state.warning_directive_problems) =
+                    WarningConfig::from_header_comments(header.header_comments);
+            }
+
             // Only lock shorthands if this header has packages
             if !header.packages.is_empty() {
                 let mut shorthands = state.arc_shorthands.lock();
@@ -2389,6 +2647,7 @@ fn update<'a>(
                 extend_module_with_builtin_import(parsed, ModuleId::DECODE);
                 extend_module_with_builtin_import(parsed, ModuleId::HASH);
                 extend_module_with_builtin_import(parsed, ModuleId::INSPECT);
+                extend_module_with_builtin_import(parsed, ModuleId::BINARY);
             }
             state
                 .module_cache
@@ -2411,9 +2670,19 @@ fn update<'a>(
             let work = match added_deps_result {
                 Ok(work) => work,
                 Err(DepCycle { cycle }) => {
+                    // The modules of the cycle were all parsed, and none of them can have been
+                    // canonicalized yet.
+                    let modules = state
+                        .module_cache
+                        .parsed
+                        .values()
+                        .filter(|module| module.module_id != module_id)
+                        .chain([&parsed]);
+                    let import_cycle = ImportCycle::new(cycle, modules);
+
                     return Err(LoadingProblem::ImportCycle(
                         parsed.module_path.clone(),
-                        cycle,
+                        import_cycle,
                     ));
                 }
             };
@@ -2453,11 +2722,22 @@ fn update<'a>(
 
         CanonicalizedAndConstrained(CanAndCon {
             constrained_module,
-            canonicalization_problems,
+            mut canonicalization_problems,
             module_docs,
         }) => {
             let module_id = constrained_module.module.module_id;
             log!("generated constraints for {:?}", module_id);
+
+            if module_id == state.root_id {
+                canonicalization_problems.append(&mut state.warning_directive_problems);
+            }
+
+            if let Some(module_name) = configured_module_name(&state, module_id) {
+                state
+                    .warnings
+                    .apply_to_can_problems(&module_name, &mut canonicalization_problems);
+            }
+
             state
                 .module_cache
                 .can_problems
@@ -2521,10 +2801,32 @@ fn update<'a>(
             log!("solved types for {:?}", module_id);
             module_timing.end_time = Instant::now();
 
+            let mut type_problems = solved_module.problems;
+
+            if let Some(module_name) = configured_module_name(&state, module_id) {
+                state
+                    .warnings
+                    .apply_to_type_problems(&module_name, &mut type_problems);
+            }
+
             state
                 .module_cache
                 .type_problems
-                .insert(module_id, solved_module.problems);
+                .insert(module_id, type_problems);
+
+            if let (Some(all_ability_traces), Some(ability_trace)) =
+                (state.ability_trace.as_mut(), solved_module.ability_trace)
+            {
+                all_ability_traces.extend(ability_trace);
+            }
+
+            if let Some(can_problems) = state.module_cache.can_problems.get_mut(&module_id) {
+                add_compatible_suggestions(
+                    can_problems,
+                    solved_module.compatible_suggestions,
+                    &ident_ids,
+                );
+            }
             state
                 .module_cache
                 .exposes
@@ -2660,6 +2962,32 @@ fn update<'a>(
                         LayoutCache::new(state.layout_interner.fork(), state.target)
                     });
 
+                    state.def_regions.extend(
+                        decls
+                            .symbols
+                            .iter()
+                            .map(|loc_symbol| (loc_symbol.value, loc_symbol.region)),
+                    );
+                    state.closures.extend(find_capturing_closures(&decls));
+
+                    if let Some((_, src)) = state.module_cache.sources.get(&module_id) {
+                        state.tail_recursive_defs.extend(
+                            tail_recursive_defs(src, &decls)
+                                .into_iter()
+                                .map(|loc_symbol| (loc_symbol.value, loc_symbol.region)),
+                        );
+                        state.comptime_defs.extend(
+                            comptime_defs(src, &decls)
+                                .into_iter()
+                                .map(|loc_symbol| (loc_symbol.value, loc_symbol.region)),
+                        );
+                        state.comptime_expects.extend(
+                            comptime_expects(src, &decls)
+                                .into_iter()
+                                .map(|loc_symbol| (loc_symbol.value, loc_symbol.region)),
+                        );
+                    }
+
                     let typechecked = TypeCheckedModule {
                         module_id,
                         layout_cache,
@@ -2769,6 +3097,8 @@ fn update<'a>(
             module_timing,
             layout_cache,
             expectations,
+            match_trees,
+            branch_regions,
             ..
         } => {
             debug_assert!(
@@ -2782,6 +3112,12 @@ fn update<'a>(
             let _ = layout_cache;
 
             state.procedures.extend(procedures);
+            if let Some(all_match_trees) = state.match_trees.as_mut() {
+                all_match_trees.extend(match_trees);
+            }
+            if let Some(all_branch_regions) = state.branch_regions.as_mut() {
+                all_branch_regions.extend(branch_regions);
+            }
             state
                 .host_exposed_lambda_sets
                 .extend(host_exposed_lambda_sets);
@@ -2900,6 +3236,52 @@ fn update<'a>(
                     debug_print_ir!(state, &layout_interner, ROC_PRINT_IR_AFTER_SPECIALIZATION);
                     debug_check_ir!(state, arena, layout_interner, ROC_CHECK_MONO_IR);
 
+                    if !state.comptime_defs.is_empty() {
+                        let ident_ids = state.constrained_ident_ids.get_mut(&module_id).unwrap();
+
+                        state.comptime_failures = roc_mono::comptime::evaluate_comptime_defs(
+                            arena,
+                            &layout_interner,
+                            module_id,
+                            ident_ids,
+                            &mut state.procedures,
+                            &state.comptime_defs,
+                            state.comptime_limits,
+                        );
+                    }
+
+                    if !state.comptime_expects.is_empty() {
+                        state.comptime_failures.extend(
+                            roc_mono::comptime::evaluate_comptime_expects(
+                                &layout_interner,
+                                &mut state.procedures,
+                                &state.comptime_expects,
+                                state.comptime_limits,
+                            ),
+                        );
+                    }
+
+                    state.dead_fields = roc_mono::dead_fields::eliminate_dead_fields(
+                        arena,
+                        &mut layout_interner,
+                        &mut state.procedures,
+                    );
+
+                    let borrow_inference = {
+                        let module_ids = (*state.arc_modules).lock();
+
+                        BorrowInference::from_env(&state.procedures, |symbol| {
+                            let module_id = symbol.module_id();
+                            let module_name = module_ids.get_name(module_id)?.as_inner();
+                            let ident = state
+                                .constrained_ident_ids
+                                .get(&module_id)?
+                                .get_name(symbol.ident_id())?;
+
+                            Some(format!("{}.{ident}", module_name.as_str()))
+                        })
+                    };
+
                     let ident_ids = state.constrained_ident_ids.get_mut(&module_id).unwrap();
 
                     roc_mono::tail_recursion::apply_trmc(
@@ -2912,10 +3294,19 @@ fn update<'a>(
 
                     debug_print_ir!(state, &layout_interner, ROC_PRINT_IR_AFTER_TRMC);
 
-                    inc_dec::insert_inc_dec_operations(
+                    roc_mono::vectorize::use_vectorized_builtins(
+                        arena,
+                        &layout_interner,
+                        &mut state.procedures,
+                    );
+
+                    roc_mono::str_builder::amortize_string_building(arena, &mut state.procedures);
+
+                    state.inferred_borrows = inc_dec::insert_inc_dec_operations(
                         arena,
                         &layout_interner,
                         &mut state.procedures,
+                        &borrow_inference,
                     );
 
                     debug_print_ir!(state, &layout_interner, ROC_PRINT_IR_AFTER_REFCOUNT);
@@ -3285,6 +3676,15 @@ fn finish_specialization<'a>(
 
     let State {
         toplevel_expects,
+        def_regions,
+        closures,
+        tail_recursive_defs,
+        comptime_failures,
+        dead_fields,
+        profile,
+        inferred_borrows,
+        match_trees,
+        branch_regions,
         procedures,
         host_exposed_lambda_sets,
         module_cache,
@@ -3312,6 +3712,11 @@ fn finish_specialization<'a>(
         None => false,
     };
 
+    let profile_hints = match profile {
+        Some(profile) => ProfileHints::new(&profile, procedures.values(), &interns),
+        None => ProfileHints::default(),
+    };
+
     Ok(MonomorphizedModule {
         can_problems,
         type_problems,
@@ -3326,9 +3731,20 @@ fn finish_specialization<'a>(
         entry_point,
         sources,
         timings: state.timings,
+        arena_stats: MutMap::default(),
         toplevel_expects,
+        def_regions,
+        closures,
+        tail_recursive_defs,
+        comptime_failures,
+        dead_fields,
+        profile_hints,
+        inferred_borrows,
+        match_trees: match_trees.unwrap_or_default(),
+        branch_regions: branch_regions.unwrap_or_default(),
         glue_layouts: GlueLayouts { getters: vec![] },
         needs_prebuilt_host,
+        worker_arena_bytes: 0,
     })
 }
 
@@ -3359,7 +3775,7 @@ fn finish(
     exposed_types_storage: ExposedTypesStorageSubs,
     resolved_implementations: ResolvedImplementations,
     dep_idents: IdentIdsByModule,
-    documentation: VecMap<ModuleId, ModuleDocumentation>,
+    mut documentation: VecMap<ModuleId, ModuleDocumentation>,
     abilities_store: AbilitiesStore,
     //
     #[cfg(debug_assertions)] checkmate: Option<roc_checkmate::Collector>,
@@ -3394,6 +3810,17 @@ fn finish(
 
     let exposed_values = exposed_vars_by_symbol.iter().map(|x| x.0).collect();
 
+    crate::docs::link_ability_implementations(
+        &mut documentation,
+        state
+            .exposed_types
+            .iter_all()
+            .flat_map(|(_, exposed)| exposed.resolved_implementations.keys())
+            .chain(resolved_implementations.keys()),
+        &abilities_store,
+        &interns,
+    );
+
     let declarations_by_id = state.declarations_by_id;
 
     roc_checkmate::dump_checkmate!(checkmate);
@@ -3416,11 +3843,13 @@ fn finish(
         resolved_implementations,
         sources,
         timings: state.timings,
+        arena_stats: MutMap::default(),
         docs_by_module: documentation,
         abilities_store,
         exposed_imports: state.module_cache.exposed_imports,
         imports: state.module_cache.imports,
         exposes: state.module_cache.exposes,
+        ability_trace: state.ability_trace.unwrap_or_default(),
     }
 }
 
@@ -3520,6 +3949,7 @@ fn load_package_from_disk<'a>(
                         module_ids,
                         ident_ids_by_module,
                         filename,
+                        None,
                     );
 
                     Ok(Msg::Many(messages))
@@ -3572,6 +4002,7 @@ fn load_package_from_disk<'a>(
                         module_ids,
                         ident_ids_by_module,
                         filename,
+                        None,
                     );
 
                     Ok(Msg::Many(messages))
@@ -3727,6 +4158,7 @@ fn load_module<'a>(
         "Decode", ModuleId::DECODE
         "Hash", ModuleId::HASH
         "Inspect", ModuleId::INSPECT
+        "Binary", ModuleId::BINARY
     }
 
     let (filename, opt_shorthand) = module_name_to_path(src_dir, &module_name, arc_shorthands);
@@ -4079,6 +4511,7 @@ fn parse_header<'a>(
                 module_ids,
                 ident_ids_by_module,
                 filename,
+                is_root_module.then_some(&[] as &[Dependency]),
             );
 
             Ok(HeaderOutput {
@@ -4166,7 +4599,38 @@ fn load_packages<'a>(
     ident_ids_by_module: SharedIdentIdsByModule,
     #[allow(unused_variables)] // for wasm
     filename: PathBuf,
+    // The dependencies in the root module's package manifest, or None if this isn't the root module
+    #[allow(unused_variables)] // for wasm
+    root_dependencies: Option<&[Dependency]>,
 ) {
+    // The packages the root module depends on by URL or through its package manifest are pinned
+    // by the `roc.lock` next to it. Packages only get one from their own root module, so theirs
+    // aren't checked.
+    #[cfg(not(target_family = "wasm"))]
+    let lockfile = if let Some(dependencies) = root_dependencies {
+        let lockfile_path = cwd.join(LOCKFILE_NAME);
+        let verified = Lockfile::read(&lockfile_path).and_then(|lockfile| {
+            lockfile
+                .verify_dependencies(roc_cache_dir, dependencies)
+                .map(|()| lockfile)
+        });
+
+        match verified {
+            Ok(lockfile) if lockfile.packages.is_empty() => None,
+            Ok(lockfile) => Some((lockfile, lockfile_path)),
+            Err(problem) => {
+                let buf = to_lockfile_problem_report_string(problem, &lockfile_path, filename);
+
+                load_messages.push(Msg::FailedToLoad(LoadingProblem::FormattedReport(
+                    buf, None,
+                )));
+                return;
+            }
+        }
+    } else {
+        None
+    };
+
     // Load all the packages
     for Loc { value: entry, .. } in packages.iter() {
         let PackageEntry {
@@ -4192,6 +4656,22 @@ fn load_packages<'a>(
                 // TODO we should do this async; however, with the current
                 // architecture of file.rs (which doesn't use async/await),
                 // this would be very difficult!
+                let verified = match &lockfile {
+                    Some((lockfile, lockfile_path)) => lockfile
+                        .verify(roc_cache_dir, [src])
+                        .map_err(|problem| (problem, lockfile_path)),
+                    None => Ok(()),
+                };
+
+                if let Err((problem, lockfile_path)) = verified {
+                    let buf = to_lockfile_problem_report_string(problem, lockfile_path, filename);
+
+                    load_messages.push(Msg::FailedToLoad(LoadingProblem::FormattedReport(
+                        buf, None,
+                    )));
+                    return;
+                }
+
                 match cache::install_package(roc_cache_dir, src) {
                     Ok((package_dir, opt_root_module)) => {
                         // You can optionally specify the root module using the URL fragment,
@@ -4406,11 +4886,15 @@ impl<'a> BuildTask<'a> {
         derived_module: SharedDerivedModule,
         exec_mode: ExecutionMode,
         is_host_exposed: bool,
+        trace_abilities: bool,
 
         #[cfg(debug_assertions)] checkmate: Option<roc_checkmate::Collector>,
     ) -> Self {
         let exposed_by_module = exposed_types.retain_modules(imported_modules.keys());
 
+        // The builtins' implementations aren't worth tracing
+        let trace_abilities = trace_abilities && !module.module_id.is_builtin();
+
         let exposed_for_module =
             ExposedForModule::new(module.referenced_values.iter(), exposed_by_module);
 
@@ -4431,6 +4915,7 @@ impl<'a> BuildTask<'a> {
             derived_module,
             exec_mode,
             is_host_exposed,
+            trace_abilities,
 
             #[cfg(debug_assertions)]
             checkmate,
@@ -4719,6 +5204,8 @@ struct SolveResult {
     solved_implementations: ResolvedImplementations,
     exposed_vars_by_symbol: Vec<(Symbol, Variable)>,
     problems: Vec<TypeError>,
+    compatible_suggestions: CompatibleSuggestions,
+    ability_trace: Option<AbilityTrace>,
     abilities_store: AbilitiesStore,
     imported_modules_with_params: Vec<ModuleId>,
 
@@ -4738,6 +5225,7 @@ fn run_solve_solve(
     module: Module,
     derived_module: SharedDerivedModule,
     is_host_exposed: bool,
+    trace_abilities: bool,
 
     #[cfg(debug_assertions)] checkmate: Option<roc_checkmate::Collector>,
 ) -> SolveResult {
@@ -4808,6 +5296,7 @@ fn run_solve_solve(
             module_params,
             module_params_vars: imported_param_vars,
             host_exposed_symbols: host_exposed_idents,
+            trace_abilities,
         };
 
         let solve_output = roc_solve::module::run_solve(
@@ -4849,7 +5338,9 @@ fn run_solve_solve(
         subs,
         scope: _,
         errors,
+        compatible_suggestions,
         resolved_abilities_store,
+        ability_trace,
 
         #[cfg(debug_assertions)]
         checkmate,
@@ -4860,6 +5351,8 @@ fn run_solve_solve(
         solved_implementations,
         exposed_vars_by_symbol,
         problems: errors,
+        compatible_suggestions,
+        ability_trace,
         abilities_store: resolved_abilities_store,
         imported_modules_with_params,
 
@@ -4885,6 +5378,7 @@ fn run_solve<'a>(
     derived_module: SharedDerivedModule,
     exec_mode: ExecutionMode,
     is_host_exposed: bool,
+    trace_abilities: bool,
 
     #[cfg(debug_assertions)] checkmate: Option<roc_checkmate::Collector>,
 ) -> Msg<'a> {
@@ -4917,6 +5411,7 @@ fn run_solve<'a>(
                     module,
                     derived_module,
                     is_host_exposed,
+                    trace_abilities,
                     //
                     #[cfg(debug_assertions)]
                     checkmate,
@@ -4931,6 +5426,8 @@ fn run_solve<'a>(
                     solved_implementations,
                     exposed_vars_by_symbol,
                     problems: vec![],
+                    compatible_suggestions: vec![],
+                    ability_trace: None,
                     abilities_store: abilities,
                     imported_modules_with_params: vec![],
 
@@ -4950,6 +5447,7 @@ fn run_solve<'a>(
                 module,
                 derived_module,
                 is_host_exposed,
+                trace_abilities,
                 //
                 #[cfg(debug_assertions)]
                 checkmate,
@@ -4962,6 +5460,8 @@ fn run_solve<'a>(
         solved_implementations,
         exposed_vars_by_symbol,
         mut problems,
+        compatible_suggestions,
+        ability_trace,
         abilities_store,
         imported_modules_with_params,
 
@@ -4995,6 +5495,8 @@ fn run_solve<'a>(
     let solved_module = SolvedModule {
         exposed_vars_by_symbol,
         problems,
+        compatible_suggestions,
+        ability_trace,
         aliases,
         solved_implementations,
         exposed_types,
@@ -5022,6 +5524,40 @@ fn run_solve<'a>(
     }
 }
 
+/// Now that the module is type checked, record which of the suggestions for names
+/// that aren't in scope have a type that fits where the name was used.
+fn add_compatible_suggestions(
+    can_problems: &mut [roc_problem::can::Problem],
+    compatible_suggestions: CompatibleSuggestions,
+    ident_ids: &IdentIds,
+) {
+    use roc_problem::can::{Problem, RuntimeError};
+
+    if compatible_suggestions.is_empty() {
+        return;
+    }
+
+    for problem in can_problems.iter_mut() {
+        if let Problem::RuntimeError(RuntimeError::LookupNotInScope {
+            loc_name,
+            type_compatible_suggestions,
+            ..
+        }) = problem
+        {
+            let symbols = compatible_suggestions
+                .iter()
+                .filter(|(region, _)| *region == loc_name.region)
+                .flat_map(|(_, symbols)| symbols);
+
+            for symbol in symbols {
+                if let Some(name) = ident_ids.get_name(symbol.ident_id()) {
+                    type_compatible_suggestions.insert(name.into());
+                }
+            }
+        }
+    }
+}
+
 fn unspace<'a, T: Copy>(arena: &'a Bump, items: &[Loc<Spaced<'a, T>>]) -> &'a [Loc<T>] {
     bumpalo::collections::Vec::from_iter_in(
         items
@@ -5062,6 +5598,9 @@ fn build_package_header<'a>(
         exposes_ids: exposes_ids.into_bump_slice(),
     };
 
+    let manifest_problem = PackageManifest::from_header(header).err();
+    let source = parse_state.original_bytes();
+
     let info = HeaderInfo {
         filename,
         is_root_module,
@@ -5072,7 +5611,18 @@ fn build_package_header<'a>(
         header_imports: None,
     };
 
-    build_header(info, parse_state, module_ids, module_timing)
+    let (module_id, module_name, module_header) =
+        build_header(info, parse_state, module_ids, module_timing)?;
+
+    match manifest_problem {
+        Some(problem) => Err(LoadingProblem::InvalidManifest {
+            filename: module_header.module_path,
+            module_id,
+            source,
+            problem,
+        }),
+        None => Ok((module_id, module_name, module_header)),
+    }
 }
 
 fn build_platform_header<'a>(
@@ -5168,6 +5718,7 @@ fn canonicalize_and_constrain<'a>(
     skip_constraint_gen: bool,
     exposed_module_ids: &[ModuleId],
     exec_mode: ExecutionMode,
+    doc_examples: usize,
     imported_module_params: VecMap<ModuleId, ModuleParams>,
     solo_can_output: SoloCanOutput<'a>,
 ) -> CanAndCon {
@@ -5275,6 +5826,8 @@ fn canonicalize_and_constrain<'a>(
                 exposed_module_ids,
                 module_output.exposed_symbols.clone(),
                 parsed.header_comments,
+                &module_output.declarations,
+                doc_examples,
             )
         })
     };
@@ -5351,6 +5904,7 @@ fn canonicalize_and_constrain<'a>(
                         | ModuleId::SET
                         | ModuleId::HASH
                         | ModuleId::INSPECT
+                        | ModuleId::BINARY
                 );
 
                 if !name.is_builtin() || should_include_builtin {
@@ -5711,6 +6265,8 @@ fn make_specializations<'a>(
     exposed_by_module: &ExposedByModule,
     derived_module: SharedDerivedModule,
     mut expectations: Option<Expectations>,
+    emit_match_trees: bool,
+    record_branch_regions: bool,
 ) -> Msg<'a> {
     let make_specializations_start = Instant::now();
     let mut update_mode_ids = UpdateModeIds::new();
@@ -5745,6 +6301,8 @@ fn make_specializations<'a>(
     procs.module_thunks = procs_base.module_thunks;
     procs.runtime_errors = procs_base.runtime_errors;
     procs.imported_module_thunks = procs_base.imported_module_thunks;
+    procs.match_trees = emit_match_trees.then(std::vec::Vec::new);
+    procs.branch_regions = record_branch_regions.then(MutMap::default);
 
     // TODO: for now this final specialization pass is sequential,
     // with no parallelization at all. We should try to parallelize
@@ -5758,6 +6316,8 @@ fn make_specializations<'a>(
     );
 
     let external_specializations_requested = procs.externals_we_need.clone();
+    let match_trees = procs.match_trees.take().unwrap_or_default();
+    let branch_regions = procs.branch_regions.take().unwrap_or_default();
     let (procedures, host_exposed_lambda_sets, restored_procs_base) =
         procs.get_specialized_procs_without_rc();
 
@@ -5781,6 +6341,8 @@ fn make_specializations<'a>(
         expectations,
         external_specializations_requested,
         module_timing,
+        match_trees,
+        branch_regions,
     }
 }
 
@@ -5800,6 +6362,7 @@ fn build_pending_specializations<'a>(
     derived_module: SharedDerivedModule,
     mut expectations: Option<Expectations>,
     build_expects: bool,
+    comptime_expects: &MutSet<Symbol>,
 ) -> Msg<'a> {
     let find_specializations_start = Instant::now();
 
@@ -6067,6 +6630,31 @@ fn build_pending_specializations<'a>(
             MutualRecursion { .. } => {
                 // the declarations of this group will be treaded individually by later iterations
             }
+            Expectation if comptime_expects.contains(&symbol) => {
+                // checked while compiling, so its condition becomes a thunk that the compiler
+                // evaluates (and then removes), whether or not the other expectations are run
+                module_thunks.push(symbol);
+
+                let expr_var = Variable::BOOL;
+
+                procs_base.host_specializations.insert_host_exposed(
+                    mono_env.subs,
+                    LambdaName::no_niche(symbol),
+                    None,
+                    expr_var,
+                );
+
+                let proc = PartialProc {
+                    annotation: expr_var,
+                    pattern_symbols: &[],
+                    captured_symbols: CapturedSymbols::None,
+                    body: body.value,
+                    body_var: expr_var,
+                    is_self_recursive: false,
+                };
+
+                procs_base.partial_procs.insert(symbol, proc);
+            }
             Expectation => {
                 // skip expectations if we're not going to run them
                 if !build_expects {
@@ -6299,6 +6887,75 @@ fn load_derived_partial_procs<'a>(
         load_derived_procs_end.duration_since(load_derived_procs_start);
 }
 
+/// Run the task, and keep track of how much arena memory it used if it's part of
+/// parsing or canonicalizing a module.
+fn run_task_with_arena_stats<'a>(
+    task: BuildTask<'a>,
+    arena: &'a Bump,
+    src_dir: &Path,
+    msg_tx: MsgSender<'a>,
+    roc_cache_dir: RocCacheDir<'_>,
+    target: Target,
+    arena_stats: &Mutex<MutMap<ModuleId, ModuleArenaStats>>,
+) -> Result<(), ChannelProblem> {
+    let opt_phase = match &task {
+        BuildTask::Parse { header, .. } => Some((header.module_id, Phase::Parse)),
+        BuildTask::SoloCanonicalize { parsed }
+        | BuildTask::CanonicalizeAndConstrain { parsed, .. } => {
+            Some((parsed.module_id, Phase::CanonicalizeAndConstrain))
+        }
+        _ => None,
+    };
+
+    let _span = task_span(&task);
+    let used_before = ArenaStats::used_bytes(arena);
+    let result = run_task(task, arena, src_dir, msg_tx, roc_cache_dir, target);
+
+    if let Some((module_id, phase)) = opt_phase {
+        let used_after = ArenaStats::used_bytes(arena);
+        let mut arena_stats = arena_stats.lock();
+        let module_stats = arena_stats.entry(module_id).or_default();
+
+        let stats = match phase {
+            Phase::Parse => &mut module_stats.parse,
+            _ => &mut module_stats.canonicalize,
+        };
+
+        // The arena is shared with the modules this thread handled before, so only count what
+        // this task added to it.
+        let used = used_after.saturating_sub(used_before);
+
+        stats.record(used, used);
+    }
+
+    result
+}
+
+/// A span covering the whole task, which shows up in traces of the build.
+fn task_span(task: &BuildTask<'_>) -> roc_tracing::PhaseSpan {
+    use roc_tracing::phase_span;
+
+    match task {
+        BuildTask::LoadModule { module_name, .. } => {
+            phase_span!("load_module", module = ?module_name)
+        }
+        BuildTask::Parse { header, .. } => phase_span!("parse", module = ?header.module_id),
+        BuildTask::SoloCanonicalize { parsed } => {
+            phase_span!("canonicalize", module = ?parsed.module_id)
+        }
+        BuildTask::CanonicalizeAndConstrain { parsed, .. } => {
+            phase_span!("canonicalize_and_constrain", module = ?parsed.module_id)
+        }
+        BuildTask::Solve { module, .. } => phase_span!("solve", module = ?module.module_id),
+        BuildTask::BuildPendingSpecializations { module_id, .. } => {
+            phase_span!("find_specializations", module = ?module_id)
+        }
+        BuildTask::MakeSpecializations { module_id, .. } => {
+            phase_span!("make_specializations", module = ?module_id)
+        }
+    }
+}
+
 fn run_task<'a>(
     task: BuildTask<'a>,
     arena: &'a Bump,
@@ -6355,6 +7012,7 @@ fn run_task<'a>(
             skip_constraint_gen,
             exposed_module_ids,
             exec_mode,
+            doc_examples,
             imported_module_params,
             solo_can_output,
         } => {
@@ -6369,6 +7027,7 @@ fn run_task<'a>(
                 skip_constraint_gen,
                 exposed_module_ids,
                 exec_mode,
+                doc_examples,
                 imported_module_params,
                 solo_can_output,
             );
@@ -6392,6 +7051,7 @@ fn run_task<'a>(
             derived_module,
             exec_mode,
             is_host_exposed,
+            trace_abilities,
 
             #[cfg(debug_assertions)]
             checkmate,
@@ -6412,6 +7072,7 @@ fn run_task<'a>(
             derived_module,
             exec_mode,
             is_host_exposed,
+            trace_abilities,
             //
             #[cfg(debug_assertions)]
             checkmate,
@@ -6430,6 +7091,7 @@ fn run_task<'a>(
             derived_module,
             expectations,
             build_expects,
+            comptime_expects,
         } => Ok(build_pending_specializations(
             arena,
             solved_subs,
@@ -6446,6 +7108,7 @@ fn run_task<'a>(
             derived_module,
             expectations,
             build_expects,
+            &comptime_expects,
         )),
         MakeSpecializations {
             module_id,
@@ -6459,6 +7122,8 @@ fn run_task<'a>(
             exposed_by_module,
             derived_module,
             expectations,
+            emit_match_trees,
+            record_branch_regions,
         } => Ok(make_specializations(
             arena,
             module_id,
@@ -6473,6 +7138,8 @@ fn run_task<'a>(
             &exposed_by_module,
             derived_module,
             expectations,
+            emit_match_trees,
+            record_branch_regions,
         )),
     };
 
@@ -6510,20 +7177,29 @@ fn run_task<'a>(
 fn to_import_cycle_report(
     module_ids: ModuleIds,
     all_ident_ids: IdentIdsByModule,
-    import_cycle: Vec<ModuleId>,
+    import_cycle: ImportCycle,
     filename: PathBuf,
     render: RenderTarget,
 ) -> String {
-    use roc_reporting::report::{Report, RocDocAllocator, DEFAULT_PALETTE};
+    use roc_reporting::report::{Report, RocDocAllocator, RocDocBuilder, DEFAULT_PALETTE};
     use ven_pretty::DocAllocator;
 
-    // import_cycle looks like CycleModule, Import1, ..., ImportN, CycleModule
+    // How many of the names an import is used for to list.
+    const MAX_USES: usize = 5;
+
+    let ImportCycle {
+        cycle,
+        imports,
+        suggested_removals,
+    } = import_cycle;
+
+    // cycle looks like CycleModule, Import1, ..., ImportN, CycleModule
     // In a self-referential case, it just looks like CycleModule, CycleModule.
-    debug_assert!(import_cycle.len() >= 2);
-    let source_of_cycle = import_cycle.first().unwrap();
+    debug_assert!(cycle.len() >= 2);
+    let source_of_cycle = cycle.first().unwrap();
 
     // We won't be printing any lines for this report, so this is okay.
-    // TODO: it would be nice to show how each module imports another in the cycle.
+    // The imports are in different modules, so they are pointed out by line instead.
     let src_lines = &[];
 
     let interns = Interns {
@@ -6532,7 +7208,71 @@ fn to_import_cycle_report(
     };
     let alloc = RocDocAllocator::new(src_lines, *source_of_cycle, &interns);
 
-    let doc = alloc.stack([
+    // e.g. "Age imports Person on line 3, for `name` and `greet`"
+    let describe = |import: &CycleImport| -> RocDocBuilder<'_> {
+        let uses = if import.uses.is_empty() {
+            alloc.reflow(", but doesn't use anything from it")
+        } else {
+            let mut names: Vec<RocDocBuilder<'_>> = import
+                .uses
+                .iter()
+                .take(MAX_USES)
+                .map(|name| alloc.ident(Ident::from(name.as_str())))
+                .collect();
+
+            if import.uses.len() > MAX_USES {
+                names.push(alloc.string(format!("{} more", import.uses.len() - MAX_USES)));
+            }
+
+            let last = names.pop().unwrap();
+
+            alloc.concat([
+                alloc.reflow(", for "),
+                alloc.intersperse(names, alloc.text(", ")),
+                if import.uses.len() > 1 {
+                    alloc.reflow(" and ")
+                } else {
+                    alloc.nil()
+                },
+                last,
+            ])
+        };
+
+        alloc.concat([
+            alloc.module(import.importer),
+            alloc.reflow(" imports "),
+            alloc.module(import.imported),
+            alloc.reflow(" on line "),
+            alloc.string(import.line.to_string()),
+            uses,
+        ])
+    };
+
+    let suggestion = match suggested_removals.as_slice() {
+        [] => alloc.reflow("Cyclic dependencies are not allowed in Roc! Can you restructure a module in this import chain so that it doesn't have to depend on itself?"),
+        [removal] => alloc.concat([
+            alloc.reflow("Cyclic dependencies are not allowed in Roc! The easiest way to break this cycle is to remove the import of "),
+            alloc.module(removal.imported),
+            alloc.reflow(" from "),
+            alloc.module(removal.importer),
+            if removal.uses.is_empty() {
+                alloc.reflow(". Nothing uses it, so it can just go.")
+            } else {
+                alloc.concat([
+                    alloc.reflow(", and to move what it's used for into "),
+                    alloc.module(removal.importer),
+                    alloc.reflow(", or into a new module that both of them can import."),
+                ])
+            },
+        ]),
+        removals => alloc.stack([
+            alloc.reflow("Cyclic dependencies are not allowed in Roc! These modules depend on each other in more than one way, so the fewest imports to remove to break every cycle between them are:"),
+            alloc.vcat(removals.iter().map(describe)).indent(4),
+            alloc.reflow("What each of them is used for would have to move into the module that imports it, or into a new module that both modules can import."),
+        ]),
+    };
+
+    let mut stack = vec![
         alloc.concat([
             alloc.reflow("I can't compile "),
             alloc.module(*source_of_cycle),
@@ -6544,14 +7284,22 @@ fn to_import_cycle_report(
             &alloc,
             4,
             alloc.module(*source_of_cycle),
-            import_cycle
+            cycle
                 .into_iter()
                 .skip(1)
                 .map(|module| alloc.module(module))
                 .collect(),
         ),
-        alloc.reflow("Cyclic dependencies are not allowed in Roc! Can you restructure a module in this import chain so that it doesn't have to depend on itself?")
-    ]);
+    ];
+
+    if !imports.is_empty() {
+        stack.push(alloc.reflow("This is what each import in the chain is used for:"));
+        stack.push(alloc.vcat(imports.iter().map(describe)).indent(4));
+    }
+
+    stack.push(suggestion);
+
+    let doc = alloc.stack(stack);
 
     let report = Report {
         filename,
@@ -6709,6 +7457,147 @@ fn to_multiple_platform_packages_report(
     buf
 }
 
+fn to_invalid_manifest_report(
+    module_ids: ModuleIds,
+    all_ident_ids: IdentIdsByModule,
+    module_id: ModuleId,
+    filename: PathBuf,
+    src: &[u8],
+    problem: ManifestProblem,
+    render: RenderTarget,
+) -> String {
+    use roc_reporting::report::{Report, RocDocAllocator, DEFAULT_PALETTE};
+    use ven_pretty::DocAllocator;
+    let severity = Severity::RuntimeError;
+
+    // SAFETY: if the module was not UTF-8, that would be reported as a parsing problem, rather
+    // than a manifest problem (the latter can happen only after parsing).
+    let src = unsafe { from_utf8_unchecked(src) };
+    let src_lines = src.lines().collect::<Vec<_>>();
+    let lines = LineInfo::new(src);
+
+    let interns = Interns {
+        module_ids,
+        all_ident_ids,
+    };
+    let alloc = RocDocAllocator::new(&src_lines, module_id, &interns);
+
+    let (title, doc) = match problem {
+        ManifestProblem::MissingField { field, region } => (
+            "MISSING MANIFEST FIELD",
+            alloc.stack([
+                alloc.concat([
+                    alloc.reflow("This package manifest is missing its "),
+                    alloc.record_field(field.into()),
+                    alloc.reflow(" field:"),
+                ]),
+                alloc.region(lines.convert_region(region), severity),
+                alloc.reflow("Every manifest needs at least a name and a version, like:"),
+                alloc.parser_suggestion("manifest { name: \"json\", version: \"1.0.0\" }").indent(4),
+            ]),
+        ),
+        ManifestProblem::UnknownField { field: _, region } => (
+            "UNKNOWN MANIFEST FIELD",
+            alloc.stack([
+                alloc.reflow("This package manifest has a field I don't recognize:"),
+                alloc.region(lines.convert_region(region), severity),
+                alloc.reflow("A manifest can have these fields: name, version, license, description, homepage, repository, authors, keywords and dependencies."),
+            ]),
+        ),
+        ManifestProblem::DuplicateField {
+            field,
+            region,
+            original_region,
+        } => (
+            "DUPLICATE MANIFEST FIELD",
+            alloc.stack([
+                alloc.concat([
+                    alloc.reflow("This package manifest sets its "),
+                    alloc.record_field(field.as_str().into()),
+                    alloc.reflow(" field more than once. It's first set here:"),
+                ]),
+                alloc.region(lines.convert_region(original_region), severity),
+                alloc.reflow("and then again here:"),
+                alloc.region(lines.convert_region(region), severity),
+            ]),
+        ),
+        ManifestProblem::ExpectedStr { field, region } => (
+            "WRONG MANIFEST FIELD TYPE",
+            alloc.stack([
+                alloc.concat([
+                    alloc.reflow("The "),
+                    alloc.record_field(field.as_str().into()),
+                    alloc.reflow(" field of a package manifest should be a string, but this is a list:"),
+                ]),
+                alloc.region(lines.convert_region(region), severity),
+            ]),
+        ),
+        ManifestProblem::ExpectedList { field, region } => (
+            "WRONG MANIFEST FIELD TYPE",
+            alloc.stack([
+                alloc.concat([
+                    alloc.reflow("The "),
+                    alloc.record_field(field.as_str().into()),
+                    alloc.reflow(" field of a package manifest should be a list of strings, but this is a single string:"),
+                ]),
+                alloc.region(lines.convert_region(region), severity),
+                alloc.concat([
+                    alloc.tip(),
+                    alloc.reflow("Put square brackets around it to make it a list with one string in it."),
+                ]),
+            ]),
+        ),
+        ManifestProblem::Interpolation { region } => (
+            "INTERPOLATION IN MANIFEST",
+            alloc.stack([
+                alloc.reflow("This package manifest uses string interpolation:"),
+                alloc.region(lines.convert_region(region), severity),
+                alloc.reflow("Manifests are read without running any Roc code, so their strings can't use interpolation."),
+            ]),
+        ),
+        ManifestProblem::InvalidName { name: _, region } => (
+            "INVALID PACKAGE NAME",
+            alloc.stack([
+                alloc.reflow("This package name isn't valid:"),
+                alloc.region(lines.convert_region(region), severity),
+                alloc.reflow("Package names can only contain lowercase letters, digits and dashes, and they have to start with a letter."),
+            ]),
+        ),
+        ManifestProblem::InvalidVersion { version: _, region } => (
+            "INVALID PACKAGE VERSION",
+            alloc.stack([
+                alloc.reflow("This package version isn't valid:"),
+                alloc.region(lines.convert_region(region), severity),
+                alloc.reflow("Versions look like `1.2.3`, optionally followed by a prerelease tag like `1.2.3-beta.1`."),
+            ]),
+        ),
+        ManifestProblem::InvalidDependency {
+            dependency: _,
+            region,
+        } => (
+            "INVALID PACKAGE DEPENDENCY",
+            alloc.stack([
+                alloc.reflow("This package dependency isn't valid:"),
+                alloc.region(lines.convert_region(region), severity),
+                alloc.reflow("Dependencies are a package name followed by the versions it can have, like:"),
+                alloc.parser_suggestion("dependencies: [\"json ^1.2\", \"csv >=0.3.0, <0.5.0\"]").indent(4),
+            ]),
+        ),
+    };
+
+    let report = Report {
+        filename,
+        doc,
+        title: title.to_string(),
+        severity,
+    };
+
+    let mut buf = String::new();
+    let palette = DEFAULT_PALETTE;
+    report.render(render, &mut buf, &alloc, &palette);
+    buf
+}
+
 fn to_unrecognized_package_shorthand_report(
     module_ids: ModuleIds,
     all_ident_ids: IdentIdsByModule,
@@ -0,0 +1,371 @@
+//! Finding the imports to remove to break an import cycle.
+//!
+//! A cycle is found as soon as the module that closes it is parsed, so its report can only use
+//! what the parser knows: which module imports which, and what each module refers to by the name
+//! of a module it imports, like `Person.name`, or lists in the `exposing` of an import. That is
+//! enough to tell what every import in the cycle is used for, and to suggest the fewest imports
+//! whose removal breaks every cycle between its modules. Among the ways to do that, the one whose
+//! imports use the fewest names is suggested, because those imports are the easiest to do without.
+use crate::module::ParsedModule;
+use roc_collections::{MutMap, MutSet};
+use roc_module::ident::QualifiedModuleName;
+use roc_module::symbol::ModuleId;
+use roc_parse::ast::{self, ValueDef};
+use roc_parse::highlight::{highlight, Token};
+use roc_region::all::{LineInfo, Loc};
+use std::path::PathBuf;
+
+/// How many sets of imports to try removing before settling for a set that may not be the
+/// smallest one, since trying them all takes exponential time.
+const MAX_CANDIDATES: usize = 100_000;
+
+/// An import of one module by another.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CycleImport {
+    pub importer: ModuleId,
+    pub imported: ModuleId,
+    /// The file of the importing module
+    pub filename: PathBuf,
+    /// The line of the `import`, counting from 1
+    pub line: u32,
+    /// The names the importing module uses from the imported one, like `name` for `Person.name`
+    pub uses: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportCycle {
+    /// The modules in the cycle, which starts and ends with the same module
+    pub cycle: Vec<ModuleId>,
+    /// The import of each module of the cycle by the one before it, when it's an `import` in the
+    /// source of that module
+    pub imports: Vec<CycleImport>,
+    /// The fewest imports whose removal breaks every cycle between the modules that depend on
+    /// each other through this one
+    pub suggested_removals: Vec<CycleImport>,
+}
+
+impl ImportCycle {
+    /// Analyze a cycle of the imports between the modules parsed so far.
+    pub fn new<'a: 'b, 'b>(
+        cycle: Vec<ModuleId>,
+        modules: impl IntoIterator<Item = &'b ParsedModule<'a>>,
+    ) -> Self {
+        let mut all_imports: Vec<CycleImport> = modules.into_iter().flat_map(imports_of).collect();
+
+        all_imports.sort_by_key(|import| {
+            (
+                import.importer.to_zero_indexed(),
+                import.line,
+                import.imported.to_zero_indexed(),
+            )
+        });
+
+        let imports = cycle
+            .windows(2)
+            .filter_map(|pair| {
+                all_imports
+                    .iter()
+                    .find(|import| import.importer == pair[0] && import.imported == pair[1])
+                    .cloned()
+            })
+            .collect();
+
+        // Every cycle between the modules of this one is within its strongly connected component.
+        let component = strongly_connected_component(cycle[0], &all_imports);
+        let mut edges: Vec<CycleImport> = Vec::new();
+
+        for import in all_imports {
+            let is_inside =
+                component.contains(&import.importer) && component.contains(&import.imported);
+            let is_duplicate = edges
+                .iter()
+                .any(|edge| edge.importer == import.importer && edge.imported == import.imported);
+
+            if is_inside && !is_duplicate {
+                edges.push(import);
+            }
+        }
+
+        let suggested_removals = fewest_removals(&edges)
+            .into_iter()
+            .map(|index| edges[index].clone())
+            .collect();
+
+        ImportCycle {
+            cycle,
+            imports,
+            suggested_removals,
+        }
+    }
+}
+
+/// The `import`s in the source of a module, and what the module uses from each of them.
+fn imports_of(module: &ParsedModule<'_>) -> Vec<CycleImport> {
+    let lines = LineInfo::new(module.src);
+    let tokens = highlight(module.src);
+    let mut imports = Vec::new();
+
+    for (def, region) in ast::RecursiveValueDefIter::new(&module.parsed_defs) {
+        let ValueDef::ModuleImport(import) = def else {
+            continue;
+        };
+
+        let name: QualifiedModuleName = import.name.value.into();
+        let alias = match import.alias {
+            Some(alias) => alias.item.value.as_str().to_string(),
+            None => name.module.as_str().to_string(),
+        };
+        let pq_module_name = name.into_pq_module_name(module.opt_shorthand);
+
+        let Some(imported) = module.deps_by_name.get(&pq_module_name) else {
+            continue;
+        };
+
+        let exposed_names = import
+            .exposed
+            .map(|keyword| keyword.item.items)
+            .unwrap_or_default();
+
+        let mut uses = qualified_uses(module.src, &tokens, &alias);
+        uses.extend(
+            exposed_names
+                .iter()
+                .map(|loc_name| loc_name.value.item().as_str().to_string()),
+        );
+        uses.sort();
+        uses.dedup();
+
+        imports.push(CycleImport {
+            importer: module.module_id,
+            imported: *imported,
+            filename: module.module_path.clone(),
+            line: lines.convert_pos(region.start()).line + 1,
+            uses,
+        });
+    }
+
+    imports
+}
+
+/// The names that are qualified with a module alias in a source, like `name` in `Person.name`.
+fn qualified_uses(src: &str, tokens: &[Loc<Token>], alias: &str) -> Vec<String> {
+    let text = |token: &Loc<Token>| {
+        &src[token.region.start().offset as usize..token.region.end().offset as usize]
+    };
+
+    let segments: Vec<&str> = alias.split('.').collect();
+    // Each segment of the alias is followed by a dot, and then comes the name.
+    let length = 2 * segments.len() + 1;
+    let mut uses = Vec::new();
+
+    for (start, window) in tokens.windows(length).enumerate() {
+        // The alias is not part of a longer name, like `Foo.Person` is for `Person`.
+        let follows_dot = start > 0
+            && tokens[start - 1].value == Token::Decimal
+            && tokens[start - 1].region.end() == window[0].region.start();
+
+        let matches_alias = segments.iter().enumerate().all(|(index, segment)| {
+            let segment_token = &window[2 * index];
+            let dot = &window[2 * index + 1];
+
+            segment_token.value == Token::UpperIdent
+                && text(segment_token) == *segment
+                && dot.value == Token::Decimal
+                && segment_token.region.end() == dot.region.start()
+        });
+
+        let name = &window[length - 1];
+        let is_name = matches!(name.value, Token::LowerIdent | Token::UpperIdent)
+            && window[length - 2].region.end() == name.region.start();
+
+        if !follows_dot && matches_alias && is_name {
+            uses.push(text(name).to_string());
+        }
+    }
+
+    uses
+}
+
+/// The modules that both depend on `module_id` and are depended on by it, including itself.
+fn strongly_connected_component(module_id: ModuleId, imports: &[CycleImport]) -> MutSet<ModuleId> {
+    let reachable = |forward: bool| {
+        let mut seen = MutSet::default();
+        let mut stack = vec![module_id];
+
+        while let Some(current) = stack.pop() {
+            if !seen.insert(current) {
+                continue;
+            }
+
+            for import in imports {
+                let (from, to) = if forward {
+                    (import.importer, import.imported)
+                } else {
+                    (import.imported, import.importer)
+                };
+
+                if from == current {
+                    stack.push(to);
+                }
+            }
+        }
+
+        seen
+    };
+
+    let depended_on = reachable(true);
+    let dependents = reachable(false);
+
+    depended_on.intersection(&dependents).copied().collect()
+}
+
+/// The indices of the fewest edges whose removal leaves no cycle. Of the sets of edges that are
+/// equally small, the one whose imports use the fewest names wins.
+fn fewest_removals(edges: &[CycleImport]) -> Vec<usize> {
+    let cost =
+        |indices: &[usize]| -> usize { indices.iter().map(|index| edges[*index].uses.len()).sum() };
+    let mut candidates = 0;
+
+    for size in 1..=edges.len() {
+        let mut best: Option<Vec<usize>> = None;
+        let mut indices: Vec<usize> = (0..size).collect();
+
+        loop {
+            candidates += 1;
+
+            if candidates > MAX_CANDIDATES {
+                return greedy_removals(edges);
+            }
+
+            let mut removed = vec![false; edges.len()];
+
+            for index in indices.iter() {
+                removed[*index] = true;
+            }
+
+            let is_better = match &best {
+                Some(best) => cost(&indices) < cost(best),
+                None => true,
+            };
+
+            if is_better && find_cycle(edges, &removed).is_none() {
+                best = Some(indices.clone());
+            }
+
+            if !next_combination(&mut indices, edges.len()) {
+                break;
+            }
+        }
+
+        if let Some(best) = best {
+            return best;
+        }
+    }
+
+    Vec::new()
+}
+
+/// Remove the edge that uses the fewest names from one cycle after another, until there are
+/// none left.
+fn greedy_removals(edges: &[CycleImport]) -> Vec<usize> {
+    let mut removed = vec![false; edges.len()];
+    let mut removals = Vec::new();
+
+    while let Some(cycle) = find_cycle(edges, &removed) {
+        let cheapest = cycle
+            .into_iter()
+            .min_by_key(|index| edges[*index].uses.len())
+            .unwrap();
+
+        removed[cheapest] = true;
+        removals.push(cheapest);
+    }
+
+    removals.sort();
+
+    removals
+}
+
+/// Move to the next set of `indices.len()` indices below `count`, in lexicographic order.
+/// Returns false when there is none.
+fn next_combination(indices: &mut [usize], count: usize) -> bool {
+    let size = indices.len();
+
+    for position in (0..size).rev() {
+        if indices[position] < count - size + position {
+            indices[position] += 1;
+
+            for next in position + 1..size {
+                indices[next] = indices[next - 1] + 1;
+            }
+
+            return true;
+        }
+    }
+
+    false
+}
+
+/// The indices of the edges of a cycle among the edges that aren't removed, if there is one.
+fn find_cycle(edges: &[CycleImport], removed: &[bool]) -> Option<Vec<usize>> {
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    enum Visit {
+        InProgress,
+        Done,
+    }
+
+    fn visit(
+        module_id: ModuleId,
+        edges: &[CycleImport],
+        removed: &[bool],
+        visits: &mut MutMap<ModuleId, Visit>,
+        path: &mut Vec<usize>,
+    ) -> Option<Vec<usize>> {
+        visits.insert(module_id, Visit::InProgress);
+
+        for (index, edge) in edges.iter().enumerate() {
+            if removed[index] || edge.importer != module_id {
+                continue;
+            }
+
+            path.push(index);
+
+            match visits.get(&edge.imported) {
+                Some(Visit::InProgress) => {
+                    let start = path
+                        .iter()
+                        .position(|index| edges[*index].importer == edge.imported)
+                        .unwrap();
+
+                    return Some(path[start..].to_vec());
+                }
+                Some(Visit::Done) => {}
+                None => {
+                    if let Some(cycle) = visit(edge.imported, edges, removed, visits, path) {
+                        return Some(cycle);
+                    }
+                }
+            }
+
+            path.pop();
+        }
+
+        visits.insert(module_id, Visit::Done);
+
+        None
+    }
+
+    let mut visits = MutMap::default();
+
+    for edge in edges {
+        if !visits.contains_key(&edge.importer) {
+            let mut path = Vec::new();
+
+            if let Some(cycle) = visit(edge.importer, edges, removed, &mut visits, &mut path) {
+                return Some(cycle);
+            }
+        }
+    }
+
+    None
+}
@@ -0,0 +1,124 @@
+//! Finding the memory layout of a Roc type, for platform authors, glue generators, and
+//! `roc layout`.
+//!
+//! The type is written like it would be in an annotation at the end of the module, so it can
+//! use the module's own types and imports. To find its layout, the module is type checked with
+//! one more def, annotated with the type, and the layout is the layout of that def.
+use crate::file::{ExecutionMode, LoadConfig, LoadStart, LoadingProblem};
+use crate::module::LoadedModule;
+use crate::platform_switch::{error_reports, load_checked};
+use bumpalo::Bump;
+use roc_can::module::TypeState;
+use roc_collections::MutMap;
+use roc_module::symbol::ModuleId;
+use roc_mono::layout::describe::{describe_layout, LayoutDescription};
+use roc_mono::layout::{GlobalLayoutInterner, LayoutCache};
+use roc_packaging::cache::RocCacheDir;
+use std::path::{Path, PathBuf};
+
+/// The name of the def the type is queried with. Nothing in the module may use it.
+pub const QUERY_DEF: &str = "rocLayoutQuery";
+
+#[derive(Debug)]
+pub enum LayoutQueryError<'a> {
+    Loading(LoadingProblem<'a>),
+    /// The module, or the type written for the query, has errors. These are their reports,
+    /// rendered like [LoadConfig::render] says.
+    Problems(Vec<String>),
+    /// The type has type variables, so its values don't all have the same layout
+    NotConcrete {
+        type_variables: Vec<String>,
+    },
+}
+
+impl<'a> From<LoadingProblem<'a>> for LayoutQueryError<'a> {
+    fn from(problem: LoadingProblem<'a>) -> Self {
+        Self::Loading(problem)
+    }
+}
+
+/// The layout of `type_annotation` in the module at `module_path`, on `load_config.target`.
+pub fn query_layout<'a>(
+    arena: &'a Bump,
+    module_path: PathBuf,
+    type_annotation: &str,
+    cached_types: MutMap<ModuleId, TypeState>,
+    roc_cache_dir: RocCacheDir<'_>,
+    load_config: LoadConfig,
+) -> Result<LayoutDescription, LayoutQueryError<'a>> {
+    let src =
+        std::fs::read_to_string(&module_path).map_err(|error| LoadingProblem::FileProblem {
+            filename: module_path.clone(),
+            error: error.kind(),
+        })?;
+
+    let (render, palette, target) = (load_config.render, load_config.palette, load_config.target);
+    let src_dir = module_path.parent().unwrap_or(Path::new("")).to_path_buf();
+    let load_start = LoadStart::from_str(
+        arena,
+        module_path,
+        None,
+        arena.alloc_str(&with_query_def(&src, type_annotation)),
+        roc_cache_dir,
+        src_dir,
+    )?;
+    let loaded = load_checked(
+        arena,
+        load_start,
+        cached_types,
+        roc_cache_dir,
+        LoadConfig {
+            exec_mode: ExecutionMode::Check,
+            ..load_config
+        },
+    )?;
+
+    let errors = error_reports(&loaded, render, palette);
+    if !errors.is_empty() {
+        return Err(LayoutQueryError::Problems(
+            errors.into_iter().map(|error| error.report).collect(),
+        ));
+    }
+
+    let LoadedModule {
+        module_id,
+        interns,
+        declarations_by_id,
+        solved,
+        ..
+    } = &loaded;
+    let decls = &declarations_by_id[module_id];
+    let index = decls
+        .symbols
+        .iter()
+        .position(|symbol| symbol.value.as_str(interns) == QUERY_DEF)
+        .expect("the query def is in the module, since it has no errors");
+
+    if let Some(annotation) = &decls.annotations[index] {
+        let type_variables: Vec<_> = annotation
+            .introduced_variables
+            .iter_named()
+            .map(|variable| variable.name().to_string())
+            .collect();
+
+        if !type_variables.is_empty() {
+            return Err(LayoutQueryError::NotConcrete { type_variables });
+        }
+    }
+
+    let layout_interner = GlobalLayoutInterner::with_capacity(64, target);
+    let mut layout_cache = LayoutCache::new(layout_interner.fork(), target);
+    let layout = layout_cache
+        .from_var(arena, decls.variables[index], solved.inner())
+        .map_err(|_| LayoutQueryError::NotConcrete {
+            type_variables: Vec::new(),
+        })?;
+
+    Ok(describe_layout(&layout_cache.interner, layout))
+}
+
+/// The source of a module, with the def that the layout of `type_annotation` is queried with
+/// added to the end.
+pub fn with_query_def(src: &str, type_annotation: &str) -> String {
+    format!("{src}\n\n{QUERY_DEF} : {type_annotation}\n{QUERY_DEF} = crash \"\"\n")
+}
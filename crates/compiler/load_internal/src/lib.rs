@@ -4,10 +4,20 @@
 #![allow(clippy::large_enum_variant)]
 
 use roc_module::symbol::ModuleId;
+pub mod call_hierarchy;
+pub mod determinism;
 pub mod docs;
+pub mod extract;
 pub mod file;
+pub mod import_cycle;
+pub mod layout_query;
+pub mod manifest;
 pub mod module;
 mod module_cache;
+pub mod occurrences;
+pub mod platform_switch;
+pub mod rename;
+pub mod warnings;
 
 #[cfg(target_family = "wasm")]
 mod wasm_instant;
@@ -25,4 +35,5 @@ pub const BUILTIN_MODULES: &[(ModuleId, &str)] = &[
     (ModuleId::DECODE, "Decode"),
     (ModuleId::HASH, "Hash"),
     (ModuleId::INSPECT, "Inspect"),
+    (ModuleId::BINARY, "Binary"),
 ];
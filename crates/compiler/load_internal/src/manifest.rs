@@ -0,0 +1,354 @@
+//! The manifest of a package: the name, version, license and other metadata in the `manifest`
+//! section of its header, e.g.
+//!
+//! ```roc
+//! package [Json, Json.Decode] {} manifest {
+//!     name: "json",
+//!     version: "1.2.0",
+//!     license: "UPL-1.0",
+//!     description: "Encode and decode JSON",
+//!     authors: ["Ada Lovelace"],
+//!     dependencies: ["parser ^0.4"],
+//! }
+//! ```
+//!
+//! Each dependency is a package name and the versions of it the package works with, which
+//! `roc lock` resolves with [roc_packaging::resolve] and pins in the package's `roc.lock`.
+//!
+//! Loading a package validates its manifest, and tooling (like docs generation or a package
+//! registry) can read it with [read_package_manifest] without loading the rest of the package.
+use std::fmt;
+use std::path::Path;
+
+use bumpalo::Bump;
+use roc_packaging::resolve::Dependency;
+use roc_packaging::semver::Version;
+use roc_parse::ast::{ExtractSpaces, Header, StrLiteral, StrSegment};
+use roc_parse::header::{ManifestValue, PackageHeader};
+use roc_region::all::{Loc, Region};
+
+/// Fields that hold a single string.
+const STR_FIELDS: &[&str] = &[
+    "name",
+    "version",
+    "license",
+    "description",
+    "homepage",
+    "repository",
+];
+
+/// Fields that hold a list of strings.
+const LIST_FIELDS: &[&str] = &["authors", "keywords", "dependencies"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageManifest {
+    pub name: String,
+    pub version: String,
+    /// An SPDX license expression, e.g. `"UPL-1.0"` or `"MIT OR Apache-2.0"`
+    pub license: Option<String>,
+    pub description: Option<String>,
+    pub homepage: Option<String>,
+    pub repository: Option<String>,
+    pub authors: Vec<String>,
+    pub keywords: Vec<String>,
+    /// The modules the package exposes, from the header's exposes list.
+    pub exposes: Vec<String>,
+    /// The packages this one depends on through a version constraint, rather than by URL.
+    pub dependencies: Vec<Dependency>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ManifestProblem {
+    MissingField {
+        field: &'static str,
+        region: Region,
+    },
+    UnknownField {
+        field: String,
+        region: Region,
+    },
+    DuplicateField {
+        field: String,
+        region: Region,
+        original_region: Region,
+    },
+    ExpectedStr {
+        field: String,
+        region: Region,
+    },
+    ExpectedList {
+        field: String,
+        region: Region,
+    },
+    /// Manifest strings are read without evaluating anything, so they can't use interpolation.
+    Interpolation {
+        region: Region,
+    },
+    InvalidName {
+        name: String,
+        region: Region,
+    },
+    InvalidVersion {
+        version: String,
+        region: Region,
+    },
+    InvalidDependency {
+        dependency: String,
+        region: Region,
+    },
+}
+
+impl ManifestProblem {
+    pub fn region(&self) -> Region {
+        match self {
+            ManifestProblem::MissingField { region, .. }
+            | ManifestProblem::UnknownField { region, .. }
+            | ManifestProblem::DuplicateField { region, .. }
+            | ManifestProblem::ExpectedStr { region, .. }
+            | ManifestProblem::ExpectedList { region, .. }
+            | ManifestProblem::Interpolation { region }
+            | ManifestProblem::InvalidName { region, .. }
+            | ManifestProblem::InvalidVersion { region, .. }
+            | ManifestProblem::InvalidDependency { region, .. } => *region,
+        }
+    }
+}
+
+impl fmt::Display for ManifestProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ManifestProblem::MissingField { field, .. } => {
+                write!(f, "the manifest is missing its `{field}` field")
+            }
+            ManifestProblem::UnknownField { field, .. } => {
+                write!(f, "`{field}` is not a manifest field")
+            }
+            ManifestProblem::DuplicateField { field, .. } => {
+                write!(f, "the `{field}` field is set more than once")
+            }
+            ManifestProblem::ExpectedStr { field, .. } => {
+                write!(f, "the `{field}` field should be a string")
+            }
+            ManifestProblem::ExpectedList { field, .. } => {
+                write!(f, "the `{field}` field should be a list of strings")
+            }
+            ManifestProblem::Interpolation { .. } => {
+                write!(f, "manifest strings can't use interpolation")
+            }
+            ManifestProblem::InvalidName { name, .. } => {
+                write!(f, "\"{name}\" is not a valid package name")
+            }
+            ManifestProblem::InvalidVersion { version, .. } => {
+                write!(f, "\"{version}\" is not a valid version")
+            }
+            ManifestProblem::InvalidDependency { dependency, .. } => {
+                write!(f, "\"{dependency}\" is not a valid dependency")
+            }
+        }
+    }
+}
+
+impl PackageManifest {
+    /// Reads and validates the manifest of a package header. Returns `Ok(None)` if the header
+    /// doesn't have a manifest.
+    pub fn from_header(header: &PackageHeader<'_>) -> Result<Option<Self>, ManifestProblem> {
+        let fields = match &header.manifest {
+            Some(manifest) => &manifest.item,
+            None => return Ok(None),
+        };
+
+        let mut seen: Vec<(&str, Region)> = Vec::with_capacity(fields.value.len());
+        let mut manifest = PackageManifest {
+            name: String::new(),
+            version: String::new(),
+            license: None,
+            description: None,
+            homepage: None,
+            repository: None,
+            authors: Vec::new(),
+            keywords: Vec::new(),
+            exposes: header
+                .exposes
+                .iter()
+                .map(|module| module.value.extract_spaces().item.as_str().to_string())
+                .collect(),
+            dependencies: Vec::new(),
+        };
+
+        for loc_field in fields.value.iter() {
+            let field = loc_field.value.extract_spaces().item;
+            let name = field.name.value;
+
+            if let Some((_, original_region)) = seen.iter().find(|(seen, _)| *seen == name) {
+                return Err(ManifestProblem::DuplicateField {
+                    field: name.to_string(),
+                    region: field.name.region,
+                    original_region: *original_region,
+                });
+            }
+            seen.push((name, field.name.region));
+
+            if STR_FIELDS.contains(&name) {
+                let value = match field.value.value {
+                    ManifestValue::Str(literal) => str_value(literal)?,
+                    ManifestValue::List(_) => {
+                        return Err(ManifestProblem::ExpectedStr {
+                            field: name.to_string(),
+                            region: field.value.region,
+                        })
+                    }
+                };
+
+                match name {
+                    "name" => {
+                        if !is_valid_name(&value) {
+                            return Err(ManifestProblem::InvalidName {
+                                name: value,
+                                region: field.value.region,
+                            });
+                        }
+                        manifest.name = value;
+                    }
+                    "version" => {
+                        if Version::parse(&value).is_err() {
+                            return Err(ManifestProblem::InvalidVersion {
+                                version: value,
+                                region: field.value.region,
+                            });
+                        }
+                        manifest.version = value;
+                    }
+                    "license" => manifest.license = Some(value),
+                    "description" => manifest.description = Some(value),
+                    "homepage" => manifest.homepage = Some(value),
+                    _ => manifest.repository = Some(value),
+                }
+            } else if LIST_FIELDS.contains(&name) {
+                let items = match field.value.value {
+                    ManifestValue::List(items) => items,
+                    ManifestValue::Str(_) => {
+                        return Err(ManifestProblem::ExpectedList {
+                            field: name.to_string(),
+                            region: field.value.region,
+                        })
+                    }
+                };
+
+                let mut values = Vec::with_capacity(items.len());
+
+                for item in items.iter() {
+                    let value = str_value(item.value.extract_spaces().item)?;
+
+                    if name == "dependencies" {
+                        match Dependency::parse(&value) {
+                            Ok(dependency) => manifest.dependencies.push(dependency),
+                            Err(_) => {
+                                return Err(ManifestProblem::InvalidDependency {
+                                    dependency: value,
+                                    region: item.region,
+                                })
+                            }
+                        }
+                    }
+
+                    values.push(value);
+                }
+
+                match name {
+                    "authors" => manifest.authors = values,
+                    "keywords" => manifest.keywords = values,
+                    _ => {}
+                }
+            } else {
+                return Err(ManifestProblem::UnknownField {
+                    field: name.to_string(),
+                    region: field.name.region,
+                });
+            }
+        }
+
+        for required in ["name", "version"] {
+            if !seen.iter().any(|(name, _)| *name == required) {
+                return Err(ManifestProblem::MissingField {
+                    field: required,
+                    region: fields.region,
+                });
+            }
+        }
+
+        Ok(Some(manifest))
+    }
+}
+
+/// Package names are lowercase ASCII letters, digits and dashes, starting with a letter.
+fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    chars.next().is_some_and(|c| c.is_ascii_lowercase())
+        && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+fn str_value(literal: StrLiteral<'_>) -> Result<String, ManifestProblem> {
+    match literal {
+        StrLiteral::PlainLine(text) => Ok(text.to_string()),
+        StrLiteral::Line(segments) => segments_value(segments),
+        StrLiteral::Block(lines) => {
+            let lines = lines
+                .iter()
+                .map(|segments| segments_value(segments))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(lines.join("\n"))
+        }
+    }
+}
+
+fn segments_value(segments: &[StrSegment<'_>]) -> Result<String, ManifestProblem> {
+    let mut value = String::new();
+
+    for segment in segments {
+        match segment {
+            StrSegment::Plaintext(text) => value.push_str(text),
+            StrSegment::Unicode(Loc { value: hex, .. }) => {
+                // The parser has already checked that this is a valid code point
+                if let Some(c) = u32::from_str_radix(hex, 16).ok().and_then(char::from_u32) {
+                    value.push(c);
+                }
+            }
+            StrSegment::EscapedChar(escaped) => value.push(escaped.unescape()),
+            StrSegment::Interpolated(loc_expr) => {
+                return Err(ManifestProblem::Interpolation {
+                    region: loc_expr.region,
+                })
+            }
+        }
+    }
+
+    Ok(value)
+}
+
+#[derive(Debug)]
+pub enum ReadManifestError {
+    Io(std::io::Error),
+    /// The file could not be parsed as a module header; the string describes the syntax error.
+    Parse(String),
+    NotAPackage,
+    Invalid(ManifestProblem),
+}
+
+/// Reads the manifest of the package whose root module (usually `main.roc`) is at `path`.
+/// Only the header is parsed, so this is cheap enough for tooling to call on any package.
+pub fn read_package_manifest(path: &Path) -> Result<Option<PackageManifest>, ReadManifestError> {
+    let src = std::fs::read(path).map_err(ReadManifestError::Io)?;
+    let arena = Bump::new();
+
+    let (header, _) = roc_parse::header::parse_header(&arena, roc_parse::state::State::new(&src))
+        .map_err(|err| ReadManifestError::Parse(format!("{:?}", err.problem)))?;
+
+    match header.item {
+        Header::Package(header) => {
+            PackageManifest::from_header(&header).map_err(ReadManifestError::Invalid)
+        }
+        _ => Err(ReadManifestError::NotAPackage),
+    }
+}
@@ -5,17 +5,26 @@ use roc_can::{
     abilities::AbilitiesStore,
     expr::{Declarations, PendingDerives},
     module::{Module, ResolvedImplementations},
+    traverse::FoundClosure,
 };
-use roc_collections::{MutMap, MutSet, VecMap};
+use roc_collections::{ArenaStats, MutMap, MutSet, VecMap};
 use roc_module::ident::Ident;
 use roc_module::symbol::{
     IdentIds, IdentIdsByModule, Interns, ModuleId, PQModuleName, PackageQualified, Symbol,
 };
-use roc_mono::ir::{GlueLayouts, HostExposedLambdaSets, LambdaSetId, Proc, ProcLayout, ProcsBase};
+use roc_mono::borrow::InferredBorrows;
+use roc_mono::comptime::ComptimeFailure;
+use roc_mono::coverage::BranchRegions;
+use roc_mono::dead_fields::DeadFields;
+use roc_mono::ir::{
+    GlueLayouts, HostExposedLambdaSets, LambdaSetId, MatchTree, Proc, ProcLayout, ProcsBase,
+};
 use roc_mono::layout::{LayoutCache, STLayoutInterner};
+use roc_mono::profile::ProfileHints;
 use roc_parse::ast::{CommentOrNewline, Defs, TypeAnnotation};
 use roc_parse::header::{HeaderType, PackageName};
 use roc_region::all::{Loc, Region};
+use roc_solve::ability_trace::AbilityTrace;
 use roc_solve::module::Solved;
 use roc_solve_problem::TypeError;
 use roc_types::subs::{ExposedTypesStorageSubs, Subs, VarStore, Variable};
@@ -45,6 +54,7 @@ pub struct LoadedModule {
     pub resolved_implementations: ResolvedImplementations,
     pub sources: MutMap<ModuleId, (PathBuf, Box<str>)>,
     pub timings: MutMap<ModuleId, ModuleTiming>,
+    pub arena_stats: MutMap<ModuleId, ModuleArenaStats>,
     pub docs_by_module: VecMap<ModuleId, ModuleDocumentation>,
     pub abilities_store: AbilitiesStore,
     pub typechecked: MutMap<ModuleId, CheckedModule>,
@@ -52,6 +62,9 @@ pub struct LoadedModule {
     pub imports: MutMap<ModuleId, MutSet<ModuleId>>,
     pub exposed_imports: MutMap<ModuleId, MutMap<Symbol, Region>>,
     pub exposes: MutMap<ModuleId, Vec<(Symbol, Variable)>>,
+    /// Why ability obligations weren't fulfilled, if [crate::file::LoadConfig::trace_abilities]
+    /// was set
+    pub ability_trace: AbilityTrace,
 }
 
 impl LoadedModule {
@@ -180,13 +193,35 @@ pub struct MonomorphizedModule<'a> {
     pub procedures: MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
     pub host_exposed_lambda_sets: HostExposedLambdaSets<'a>,
     pub toplevel_expects: MutMap<ModuleId, ToplevelExpects>,
+    /// Where each top-level def was defined, for mapping procs back to source code
+    pub def_regions: MutMap<Symbol, Region>,
+    /// Where each capturing closure was defined, and what it captures
+    pub closures: MutMap<Symbol, FoundClosure>,
+    /// The top-level defs marked `# @tailrec`
+    pub tail_recursive_defs: MutMap<Symbol, Region>,
+    /// The top-level defs marked `# @comptime` that couldn't be evaluated while compiling
+    pub comptime_failures: Vec<ComptimeFailure>,
+    /// The fields removed from records that are never read, see [roc_mono::dead_fields]
+    pub dead_fields: Vec<DeadFields<'a>>,
+    /// What to inline, from [crate::file::LoadConfig::profile], see [roc_mono::profile]
+    pub profile_hints: ProfileHints,
+    /// The ownership of the parameters of every proc, see [roc_mono::borrow_report]
+    pub inferred_borrows: InferredBorrows<'a>,
+    /// The decision tree of each `when`, if [crate::file::LoadConfig::emit_match_trees] was set
+    pub match_trees: Vec<MatchTree>,
+    /// Where the branches of switches come from, when running tests, for coverage reports
+    pub branch_regions: BranchRegions,
     pub entry_point: EntryPoint<'a>,
     pub exposed_to_host: ExposedToHost,
     pub sources: MutMap<ModuleId, (PathBuf, Box<str>)>,
     pub timings: MutMap<ModuleId, ModuleTiming>,
+    pub arena_stats: MutMap<ModuleId, ModuleArenaStats>,
     pub expectations: VecMap<ModuleId, Expectations>,
     pub needs_prebuilt_host: bool,
     pub glue_layouts: GlueLayouts<'a>,
+    /// How many bytes the worker threads' arenas allocated while loading.
+    /// This is 0 when loading single-threaded, because then everything goes in the main arena.
+    pub worker_arena_bytes: usize,
 }
 
 #[derive(Debug, Clone)]
@@ -237,6 +272,16 @@ pub struct ExposedToHost {
     pub getters: Vec<Symbol>,
 }
 
+/// How much arena memory the front-end used for one module.
+///
+/// Arenas are shared between the modules that a thread works on, and are never reset while
+/// loading, so each phase only counts the bytes it added to its thread's arena.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ModuleArenaStats {
+    pub parse: ArenaStats,
+    pub canonicalize: ArenaStats,
+}
+
 #[derive(Debug, Clone)]
 pub struct ModuleTiming {
     pub read_roc_file: Duration,
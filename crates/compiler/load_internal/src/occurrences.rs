@@ -0,0 +1,424 @@
+//! Every place a value is defined, annotated, used, exposed, or imported, across every loaded
+//! module, for finding references and renaming.
+use crate::module::LoadedModule;
+use roc_can::def::{Annotation, Def};
+use roc_can::expr::{Declarations, Expr};
+use roc_can::pattern::{DestructType, Pattern, RecordDestruct};
+use roc_can::traverse::{
+    symbols_introduced_from_pattern, walk_expr, walk_record_destruct, DeclarationInfo, Visitor,
+};
+use roc_collections::{MutMap, MutSet};
+use roc_module::symbol::{Interns, ModuleId, Symbol};
+use roc_parse::ast::Header;
+use roc_parse::header::parse_header;
+use roc_parse::state::State;
+use roc_region::all::{LineInfo, Position, Region};
+use roc_types::subs::Variable;
+use std::path::{Path, PathBuf};
+
+/// What a value's name is doing at an [Occurrence].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OccurrenceKind {
+    /// The pattern that binds the value
+    Definition,
+    /// The name before the `:` of its type annotation
+    Annotation,
+    /// A lookup of the value, qualified or not
+    Use,
+    /// A record field named after the value, like `x` in `{ x }`
+    RecordFieldShorthand,
+    /// A record field the value is destructured from, like `x` in `{ x } = point`
+    DestructureShorthand,
+    /// An optional record field the value is destructured from, like `x` in `{ x ? 0 }`
+    OptionalDestructureShorthand,
+    /// In the list of values the module header exposes
+    Exposed,
+    /// In the `exposing` list of an import
+    Imported,
+}
+
+/// A place in a module where a value's name is written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Occurrence {
+    pub symbol: Symbol,
+    /// The region of the name, without any module qualifier
+    pub region: Region,
+    pub kind: OccurrenceKind,
+}
+
+/// A value bound in a module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Binding {
+    pub symbol: Symbol,
+    pub region: Region,
+    /// Bound by a top-level def, rather than inside one
+    pub top_level: bool,
+    /// Which of the module's top-level declarations it's bound in
+    pub decl: usize,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct ModuleOccurrences {
+    pub path: PathBuf,
+    pub line_info: LineInfo,
+    pub occurrences: Vec<Occurrence>,
+    pub bindings: Vec<Binding>,
+    /// The values the module imports with `exposing`, so it can use them unqualified
+    pub exposed_imports: Vec<Symbol>,
+}
+
+/// Where the values of every loaded module that has source code are written.
+#[derive(Debug, Clone, Default)]
+pub struct OccurrenceIndex {
+    pub(crate) modules: MutMap<ModuleId, ModuleOccurrences>,
+    pub(crate) names: MutMap<Symbol, String>,
+    /// Values the host uses, which can't be renamed from the app alone
+    pub(crate) exposed_to_host: MutSet<Symbol>,
+}
+
+impl OccurrenceIndex {
+    pub fn new(loaded: &LoadedModule) -> Self {
+        let mut index = OccurrenceIndex {
+            exposed_to_host: loaded.exposed_to_host.keys().copied().collect(),
+            ..Default::default()
+        };
+
+        let checked = loaded
+            .typechecked
+            .iter()
+            .map(|(module_id, checked)| (module_id, &checked.decls));
+
+        for (module_id, decls) in loaded.declarations_by_id.iter().chain(checked) {
+            if module_id.is_builtin() {
+                continue;
+            }
+
+            let Some((path, src)) = loaded.sources.get(module_id) else {
+                continue;
+            };
+
+            let no_imports = MutMap::default();
+            let exposed_imports = loaded.exposed_imports.get(module_id).unwrap_or(&no_imports);
+            let module = index_module(
+                &loaded.interns,
+                decls,
+                path,
+                src,
+                exposed_imports,
+                &mut index.names,
+            );
+
+            index.modules.insert(*module_id, module);
+        }
+
+        index
+    }
+
+    /// Every occurrence of `symbol`, in any module
+    pub fn occurrences(&self, symbol: Symbol) -> impl Iterator<Item = (ModuleId, &Occurrence)> {
+        self.modules.iter().flat_map(move |(module_id, module)| {
+            module
+                .occurrences
+                .iter()
+                .filter(move |occurrence| occurrence.symbol == symbol)
+                .map(move |occurrence| (*module_id, occurrence))
+        })
+    }
+
+    /// The value whose name is written at `position` in the module
+    pub fn symbol_at(&self, module_id: ModuleId, position: Position) -> Option<Symbol> {
+        let module = self.modules.get(&module_id)?;
+
+        module
+            .occurrences
+            .iter()
+            .find(|occurrence| occurrence.region.contains_pos(position))
+            .map(|occurrence| occurrence.symbol)
+    }
+
+    pub fn path(&self, module_id: ModuleId) -> Option<&Path> {
+        Some(self.modules.get(&module_id)?.path.as_path())
+    }
+
+    pub fn line_info(&self, module_id: ModuleId) -> Option<&LineInfo> {
+        Some(&self.modules.get(&module_id)?.line_info)
+    }
+
+    /// The name of a value that occurs somewhere in the index
+    pub fn name(&self, symbol: Symbol) -> Option<&str> {
+        self.names.get(&symbol).map(String::as_str)
+    }
+}
+
+fn index_module(
+    interns: &Interns,
+    decls: &Declarations,
+    path: &Path,
+    src: &str,
+    exposed_imports: &MutMap<Symbol, Region>,
+    names: &mut MutMap<Symbol, String>,
+) -> ModuleOccurrences {
+    let top_level: MutSet<Symbol> = decls
+        .symbols
+        .iter()
+        .map(|loc_symbol| loc_symbol.value)
+        .chain(decls.destructs.iter().flat_map(|destructure| {
+            symbols_introduced_from_pattern(&destructure.loc_pattern)
+                .map(|loc_symbol| loc_symbol.value)
+        }))
+        .collect();
+
+    let mut collector = Collector {
+        interns,
+        src,
+        top_level: &top_level,
+        names,
+        decl: 0,
+        occurrences: Vec::new(),
+        bindings: Vec::new(),
+    };
+
+    collector.visit_decls(decls);
+
+    for (symbol, region) in exposed_imports {
+        collector
+            .names
+            .entry(*symbol)
+            .or_insert_with(|| symbol.as_str(interns).to_string());
+
+        if let Some(region) = collector.name_region(*symbol, *region) {
+            collector.push(*symbol, region, OccurrenceKind::Imported);
+        }
+    }
+
+    for (name, region) in header_exposes(src) {
+        let exposed = collector
+            .bindings
+            .iter()
+            .find(|binding| binding.top_level && binding.symbol.as_str(interns) == name);
+
+        if let Some(binding) = exposed {
+            let symbol = binding.symbol;
+            collector.push(symbol, region, OccurrenceKind::Exposed);
+        }
+    }
+
+    let Collector {
+        mut occurrences,
+        bindings,
+        ..
+    } = collector;
+
+    // Record fields are visited in no particular order.
+    occurrences.sort_by_key(|occurrence| occurrence.region.start());
+
+    ModuleOccurrences {
+        path: path.to_path_buf(),
+        line_info: LineInfo::new(src),
+        occurrences,
+        bindings,
+        exposed_imports: exposed_imports.keys().copied().collect(),
+    }
+}
+
+/// The names in the `exposes` list of a module or hosted module's header, and their regions.
+fn header_exposes(src: &str) -> Vec<(String, Region)> {
+    let arena = bumpalo::Bump::new();
+    let Ok((header, _)) = parse_header(&arena, State::new(src.as_bytes())) else {
+        return Vec::new();
+    };
+
+    let exposes = match header.item {
+        Header::Module(header) => header.exposes,
+        Header::Hosted(header) => header.exposes,
+        Header::App(_) | Header::Package(_) | Header::Platform(_) => return Vec::new(),
+    };
+
+    exposes
+        .iter()
+        .map(|loc_name| {
+            let name = loc_name.value.item().as_str();
+            let text = &src[loc_name.byte_range()];
+            let offset = text.find(name).unwrap_or(0) as u32;
+            let start = loc_name.region.start().bump_column(offset);
+
+            (
+                name.to_string(),
+                Region::new(start, start.bump_column(name.len() as u32)),
+            )
+        })
+        .collect()
+}
+
+struct Collector<'a> {
+    interns: &'a Interns,
+    src: &'a str,
+    top_level: &'a MutSet<Symbol>,
+    names: &'a mut MutMap<Symbol, String>,
+    /// The top-level declaration being visited
+    decl: usize,
+    occurrences: Vec<Occurrence>,
+    bindings: Vec<Binding>,
+}
+
+impl Collector<'_> {
+    fn push(&mut self, symbol: Symbol, region: Region, kind: OccurrenceKind) {
+        self.names
+            .entry(symbol)
+            .or_insert_with(|| symbol.as_str(self.interns).to_string());
+
+        self.occurrences.push(Occurrence {
+            symbol,
+            region,
+            kind,
+        });
+    }
+
+    fn bind(&mut self, symbol: Symbol, region: Region, kind: OccurrenceKind) {
+        self.push(symbol, region, kind);
+        self.bindings.push(Binding {
+            symbol,
+            region,
+            top_level: self.top_level.contains(&symbol),
+            decl: self.decl,
+        });
+    }
+
+    fn text(&self, region: Region) -> Option<&str> {
+        self.src
+            .get(region.start().byte_offset()..region.end().byte_offset())
+    }
+
+    /// The region of the symbol's name, at the end of `region`. Names the compiler made up, and
+    /// symbols whose region is of something else, like the function a `?` desugars to, have no
+    /// name there.
+    fn name_region(&self, symbol: Symbol, region: Region) -> Option<Region> {
+        let name = symbol.as_str(self.interns);
+        let text = self.text(region)?;
+        let before = text.strip_suffix(name)?;
+
+        if !(before.is_empty() || before.ends_with('.') || before.ends_with(char::is_whitespace)) {
+            return None;
+        }
+
+        let end = region.end();
+
+        Some(Region::new(end.sub(name.len() as u32), end))
+    }
+
+    /// The region of the name of an annotated def, which is just before the `:` in front of its
+    /// type annotation.
+    fn annotation_name_region(&self, symbol: Symbol, annotation: &Annotation) -> Option<Region> {
+        let name = symbol.as_str(self.interns);
+        let before = self.src.get(..annotation.region.start().byte_offset())?;
+        let before = before.trim_end().strip_suffix(':')?.trim_end();
+        let start = before.strip_suffix(name)?.len();
+
+        Some(Region::new(
+            Position::new(start as u32),
+            Position::new((start + name.len()) as u32),
+        ))
+    }
+
+    fn annotation(&mut self, symbol: Symbol, annotation: Option<&Annotation>) {
+        let region =
+            annotation.and_then(|annotation| self.annotation_name_region(symbol, annotation));
+
+        if let Some(region) = region {
+            self.push(symbol, region, OccurrenceKind::Annotation);
+        }
+    }
+}
+
+impl Visitor for Collector<'_> {
+    fn enter_decl(&mut self, decl: &DeclarationInfo<'_>) {
+        match decl {
+            DeclarationInfo::Value {
+                loc_symbol,
+                annotation,
+                ..
+            }
+            | DeclarationInfo::Function {
+                loc_symbol,
+                annotation,
+                ..
+            } => self.annotation(loc_symbol.value, *annotation),
+            DeclarationInfo::Expectation { .. } | DeclarationInfo::Destructure { .. } => {}
+        }
+    }
+
+    fn exit_decl(&mut self, _decl: &DeclarationInfo<'_>) {
+        self.decl += 1;
+    }
+
+    fn enter_def(&mut self, def: &Def) {
+        if let Pattern::Identifier(symbol) = def.loc_pattern.value {
+            self.annotation(symbol, def.annotation.as_ref());
+        }
+    }
+
+    fn enter_pattern(&mut self, pattern: &Pattern, region: Region, _opt_var: Option<Variable>) {
+        match pattern {
+            Pattern::Identifier(symbol) | Pattern::As(_, symbol) => {
+                if let Some(region) = self.name_region(*symbol, region) {
+                    self.bind(*symbol, region, OccurrenceKind::Definition);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_record_destruct(&mut self, destruct: &RecordDestruct, region: Region) {
+        let RecordDestruct {
+            label, symbol, typ, ..
+        } = destruct;
+        let start = region.start();
+        let label_region = Region::new(start, start.bump_column(label.as_str().len() as u32));
+
+        if self.text(label_region) == Some(label.as_str()) {
+            match typ {
+                DestructType::Required => {
+                    self.bind(*symbol, label_region, OccurrenceKind::DestructureShorthand);
+                }
+                DestructType::Optional(..) => {
+                    let kind = OccurrenceKind::OptionalDestructureShorthand;
+                    self.bind(*symbol, label_region, kind);
+                }
+                DestructType::Guard(..) => {}
+            }
+        }
+
+        walk_record_destruct(self, destruct);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr, region: Region, var: Variable) {
+        match expr {
+            Expr::Var(symbol, _) => {
+                if let Some(region) = self.name_region(*symbol, region) {
+                    self.push(*symbol, region, OccurrenceKind::Use);
+                }
+            }
+            Expr::Record { fields, .. } => {
+                for (_, field) in fields.iter() {
+                    let loc_expr = &field.loc_expr;
+
+                    match loc_expr.value {
+                        Expr::Var(symbol, _)
+                            if field.region.start() == loc_expr.region.start()
+                                && self.text(loc_expr.region)
+                                    == Some(symbol.as_str(self.interns)) =>
+                        {
+                            self.push(
+                                symbol,
+                                loc_expr.region,
+                                OccurrenceKind::RecordFieldShorthand,
+                            );
+                        }
+                        _ => self.visit_expr(&loc_expr.value, loc_expr.region, field.var),
+                    }
+                }
+            }
+            _ => walk_expr(self, expr, var),
+        }
+    }
+}
@@ -0,0 +1,230 @@
+//! Checking whether an app works with another platform, like a newer version of its own, without
+//! building it.
+//!
+//! The app is type checked twice: once with its own platform, and once with its header pointing
+//! at the candidate platform instead. Every error the second check finds that the first one
+//! doesn't is an incompatibility: a `main` (or other required value) whose type isn't the one the
+//! candidate requires, a function or module the app uses that the candidate doesn't expose, or
+//! a problem in the candidate itself. Errors the app already has are left out, so that they don't
+//! hide what the switch would change.
+use crate::file::{load, LoadConfig, LoadResult, LoadStart, LoadingProblem};
+use crate::module::LoadedModule;
+use bumpalo::Bump;
+use roc_can::module::{ExposedByModule, TypeState};
+use roc_collections::MutMap;
+use roc_module::symbol::ModuleId;
+use roc_packaging::cache::RocCacheDir;
+use roc_parse::ast::{Header, SpacesBefore};
+use roc_parse::header::parse_header;
+use roc_parse::state::State;
+use roc_problem::Severity;
+use roc_region::all::LineInfo;
+use roc_reporting::report::{can_problem, type_problem, Palette, RenderTarget, RocDocAllocator};
+use std::path::{Path, PathBuf};
+
+/// What switching an app to a candidate platform would break.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlatformSwitch {
+    pub incompatibilities: Vec<Incompatibility>,
+}
+
+impl PlatformSwitch {
+    pub fn is_compatible(&self) -> bool {
+        self.incompatibilities.is_empty()
+    }
+}
+
+/// An error that the app only has with the candidate platform.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Incompatibility {
+    /// The file of the module with the error, which is in the candidate platform when the error
+    /// is in the platform itself
+    pub filename: PathBuf,
+    /// The report of the error, rendered like [LoadConfig::render] says
+    pub report: String,
+}
+
+/// Type check the app at `app_path` with its own platform and with `candidate_platform`, which is
+/// written like a package in an app header: a path relative to the app, or a URL. Returns `None`
+/// when the app's header doesn't name a platform package.
+pub fn check_platform_switch<'a>(
+    arena: &'a Bump,
+    app_path: PathBuf,
+    candidate_platform: &str,
+    cached_types: impl Fn() -> MutMap<ModuleId, TypeState>,
+    roc_cache_dir: RocCacheDir<'_>,
+    load_config: LoadConfig,
+) -> Result<Option<PlatformSwitch>, LoadingProblem<'a>> {
+    let src = std::fs::read_to_string(&app_path).map_err(|error| LoadingProblem::FileProblem {
+        filename: app_path.clone(),
+        error: error.kind(),
+    })?;
+
+    let Some(switched_src) = with_platform(&src, candidate_platform) else {
+        return Ok(None);
+    };
+
+    let (render, palette) = (load_config.render, load_config.palette);
+    let current = load_checked(
+        arena,
+        LoadStart::from_path(
+            arena,
+            app_path.clone(),
+            None,
+            render,
+            roc_cache_dir,
+            palette,
+        )?,
+        cached_types(),
+        roc_cache_dir,
+        load_config.clone(),
+    )?;
+
+    let src_dir = app_path.parent().unwrap_or(Path::new("")).to_path_buf();
+    let switched = LoadStart::from_str(
+        arena,
+        app_path,
+        None,
+        arena.alloc_str(&switched_src),
+        roc_cache_dir,
+        src_dir,
+    )
+    .and_then(|load_start| {
+        load_checked(
+            arena,
+            load_start,
+            cached_types(),
+            roc_cache_dir,
+            load_config,
+        )
+    });
+
+    let incompatibilities = match switched {
+        Ok(switched) => {
+            let mut known = error_reports(&current, render, palette);
+
+            error_reports(&switched, render, palette)
+                .into_iter()
+                .filter(|incompatibility| {
+                    match known.iter().position(|error| error == incompatibility) {
+                        Some(index) => {
+                            known.swap_remove(index);
+                            false
+                        }
+                        None => true,
+                    }
+                })
+                .collect()
+        }
+        // The candidate can't be loaded at all, e.g. because it doesn't exist.
+        Err(LoadingProblem::FormattedReport(report, _)) => vec![Incompatibility {
+            filename: PathBuf::from(candidate_platform),
+            report,
+        }],
+        Err(problem) => return Err(problem),
+    };
+
+    Ok(Some(PlatformSwitch { incompatibilities }))
+}
+
+/// The source of an app, with the platform package in its header replaced by `platform`. Returns
+/// `None` when the source isn't an app with a platform package.
+pub fn with_platform(src: &str, platform: &str) -> Option<String> {
+    let arena = Bump::new();
+    let (SpacesBefore { item: header, .. }, _) =
+        parse_header(&arena, State::new(src.as_bytes())).ok()?;
+
+    let Header::App(header) = header else {
+        return None;
+    };
+
+    let package = header
+        .packages
+        .value
+        .items
+        .iter()
+        .map(|package| *package.value.item())
+        .find(|package| package.platform_marker.is_some())?;
+
+    let region = package.package_name.region;
+    let start = region.start().offset as usize;
+    let end = region.end().offset as usize;
+
+    Some(format!("{}\"{platform}\"{}", &src[..start], &src[end..]))
+}
+
+pub(crate) fn load_checked<'a>(
+    arena: &'a Bump,
+    load_start: LoadStart<'a>,
+    cached_types: MutMap<ModuleId, TypeState>,
+    roc_cache_dir: RocCacheDir<'_>,
+    load_config: LoadConfig,
+) -> Result<LoadedModule, LoadingProblem<'a>> {
+    match load(
+        arena,
+        load_start,
+        ExposedByModule::default(),
+        cached_types,
+        roc_cache_dir,
+        load_config,
+    )? {
+        LoadResult::TypeChecked(module) => Ok(module),
+        LoadResult::Monomorphized(_) => {
+            unreachable!("Modules are only checked here, so nothing is specialized")
+        }
+    }
+}
+
+/// The reports of the errors (but not the warnings) of every module, by file.
+pub(crate) fn error_reports(
+    loaded: &LoadedModule,
+    render: RenderTarget,
+    palette: Palette,
+) -> Vec<Incompatibility> {
+    let mut sources: Vec<_> = loaded.sources.iter().collect();
+    sources.sort_by(|(_, (a, _)), (_, (b, _))| a.cmp(b));
+
+    let mut errors = Vec::new();
+
+    for (module_id, (filename, src)) in sources {
+        let src_lines: Vec<&str> = src.split('\n').collect();
+        let lines = LineInfo::new(src);
+        let alloc = RocDocAllocator::new(&src_lines, *module_id, &loaded.interns);
+
+        let mut reports: Vec<_> = loaded
+            .can_problems
+            .get(module_id)
+            .into_iter()
+            .flatten()
+            .map(|problem| can_problem(&alloc, &lines, filename.clone(), problem.clone()))
+            .collect();
+
+        reports.extend(
+            loaded
+                .type_problems
+                .get(module_id)
+                .into_iter()
+                .flatten()
+                .filter_map(|problem| {
+                    type_problem(&alloc, &lines, filename.clone(), problem.clone())
+                }),
+        );
+
+        for report in reports {
+            if report.severity == Severity::Warning {
+                continue;
+            }
+
+            let mut buf = String::new();
+
+            report.render(render, &mut buf, &alloc, &palette);
+
+            errors.push(Incompatibility {
+                filename: filename.clone(),
+                report: buf,
+            });
+        }
+    }
+
+    errors
+}
@@ -0,0 +1,240 @@
+//! Renaming a value everywhere it occurs, in every loaded module, for editors and `roc rename`.
+use crate::occurrences::{Binding, OccurrenceIndex, OccurrenceKind};
+use roc_module::symbol::{ModuleId, Symbol};
+use roc_parse::ident::lowercase_ident;
+use roc_parse::parser::Parser;
+use roc_parse::state::State;
+use roc_problem::fix::Edit;
+use roc_region::all::Region;
+use std::path::PathBuf;
+
+/// The edits to make to one file, sorted, and without overlaps.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileEdits {
+    pub module_id: ModuleId,
+    pub path: PathBuf,
+    pub edits: Vec<Edit>,
+}
+
+impl FileEdits {
+    /// The file's source, with the edits made to it
+    pub fn apply(&self, src: &str) -> String {
+        let mut output = String::with_capacity(src.len());
+        let mut copied_up_to = 0;
+
+        for edit in self.edits.iter() {
+            output.push_str(&src[copied_up_to..edit.region.start().byte_offset()]);
+            output.push_str(&edit.replacement);
+            copied_up_to = edit.region.end().byte_offset();
+        }
+
+        output.push_str(&src[copied_up_to..]);
+
+        output
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RenameError {
+    /// The new name isn't a valid name for a value, or it changes whether the value is
+    /// effectful, which its `!` suffix has to say
+    InvalidName { name: String },
+    /// The value isn't defined in a module whose source was loaded, like a builtin, or it's an
+    /// ability member, or the host uses it
+    NotRenamable { symbol: Symbol },
+    /// Renaming the value would break the program
+    Conflicts(Vec<RenameConflict>),
+}
+
+/// Something named like the new name that's in the way of the rename.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenameConflict {
+    pub kind: ConflictKind,
+    pub module_id: ModuleId,
+    /// Where the name that's in the way is bound or imported
+    pub region: Region,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictKind {
+    /// The module already has a top-level def with the new name
+    AlreadyDefined,
+    /// A value with the new name would shadow the renamed one, or be shadowed by it
+    Shadowing,
+    /// The module already imports a value with the new name from another module
+    ImportedName,
+    /// The module imports the renamed value with `exposing`, and it already has a top-level def
+    /// with the new name
+    ExposedNameCollision,
+    /// The value is destructured from an optional record field of the same name, like
+    /// `{ x ? 0 }`, which can't be given a different name
+    OptionalFieldShorthand,
+}
+
+/// The edits that rename `symbol` to `new_name` everywhere in the index, or what stops it from
+/// being renamed.
+pub fn rename(
+    index: &OccurrenceIndex,
+    symbol: Symbol,
+    new_name: &str,
+) -> Result<Vec<FileEdits>, RenameError> {
+    let home = symbol.module_id();
+    let not_renamable = RenameError::NotRenamable { symbol };
+
+    let (Some(old_name), Some(module)) = (index.name(symbol), index.modules.get(&home)) else {
+        return Err(not_renamable);
+    };
+
+    let Some(definition) = module
+        .bindings
+        .iter()
+        .find(|binding| binding.symbol == symbol)
+    else {
+        return Err(not_renamable);
+    };
+
+    if index.exposed_to_host.contains(&symbol) {
+        return Err(not_renamable);
+    }
+
+    if !is_value_name(new_name) || old_name.ends_with('!') != new_name.ends_with('!') {
+        return Err(RenameError::InvalidName {
+            name: new_name.to_string(),
+        });
+    }
+
+    if new_name == old_name {
+        return Ok(Vec::new());
+    }
+
+    let conflicts = conflicts(index, symbol, definition, new_name);
+
+    if !conflicts.is_empty() {
+        return Err(RenameError::Conflicts(conflicts));
+    }
+
+    let mut files = Vec::new();
+
+    for (module_id, module) in index.modules.iter() {
+        let mut edits: Vec<Edit> = module
+            .occurrences
+            .iter()
+            .filter(|occurrence| occurrence.symbol == symbol)
+            .map(|occurrence| {
+                let replacement = match occurrence.kind {
+                    OccurrenceKind::RecordFieldShorthand | OccurrenceKind::DestructureShorthand => {
+                        format!("{old_name}: {new_name}")
+                    }
+                    _ => new_name.to_string(),
+                };
+
+                Edit {
+                    region: occurrence.region,
+                    replacement,
+                }
+            })
+            .collect();
+
+        if edits.is_empty() {
+            continue;
+        }
+
+        edits.sort_by_key(|edit| edit.region.start());
+        edits.dedup_by_key(|edit| edit.region);
+
+        files.push(FileEdits {
+            module_id: *module_id,
+            path: module.path.clone(),
+            edits,
+        });
+    }
+
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(files)
+}
+
+pub(crate) fn is_value_name(name: &str) -> bool {
+    let arena = bumpalo::Bump::new();
+    let ident = name.strip_suffix('!').unwrap_or(name);
+
+    match lowercase_ident().parse(&arena, State::new(ident.as_bytes()), 0) {
+        Ok((_, parsed, _)) => parsed.len() == ident.len(),
+        Err(_) => false,
+    }
+}
+
+fn conflicts(
+    index: &OccurrenceIndex,
+    symbol: Symbol,
+    definition: &Binding,
+    new_name: &str,
+) -> Vec<RenameConflict> {
+    let home = symbol.module_id();
+    let named_new = |other: Symbol| other != symbol && index.name(other) == Some(new_name);
+    let mut conflicts = Vec::new();
+
+    for (module_id, module) in index.modules.iter() {
+        let in_home = *module_id == home;
+        let imports_unqualified = module.exposed_imports.contains(&symbol);
+
+        if !in_home && !(definition.top_level && imports_unqualified) {
+            continue;
+        }
+
+        for binding in module.bindings.iter() {
+            if !named_new(binding.symbol) {
+                continue;
+            }
+
+            let kind = match (in_home, definition.top_level, binding.top_level) {
+                (true, true, true) => ConflictKind::AlreadyDefined,
+                // A local is only in scope inside the top-level def it's in.
+                (true, false, false) if binding.decl != definition.decl => continue,
+                (false, _, true) => ConflictKind::ExposedNameCollision,
+                _ => ConflictKind::Shadowing,
+            };
+
+            conflicts.push(RenameConflict {
+                kind,
+                module_id: *module_id,
+                region: binding.region,
+            });
+        }
+
+        for imported in module.exposed_imports.iter() {
+            if named_new(*imported) {
+                let region = index
+                    .occurrences(*imported)
+                    .find(|(in_module, occurrence)| {
+                        in_module == module_id && occurrence.kind == OccurrenceKind::Imported
+                    })
+                    .map_or(Region::zero(), |(_, occurrence)| occurrence.region);
+
+                conflicts.push(RenameConflict {
+                    kind: ConflictKind::ImportedName,
+                    module_id: *module_id,
+                    region,
+                });
+            }
+        }
+
+        if in_home {
+            for occurrence in module.occurrences.iter() {
+                if occurrence.symbol == symbol
+                    && occurrence.kind == OccurrenceKind::OptionalDestructureShorthand
+                {
+                    conflicts.push(RenameConflict {
+                        kind: ConflictKind::OptionalFieldShorthand,
+                        module_id: *module_id,
+                        region: occurrence.region,
+                    });
+                }
+            }
+        }
+    }
+
+    conflicts.sort_by_key(|conflict| (index.path(conflict.module_id), conflict.region.start()));
+
+    conflicts
+}
@@ -0,0 +1,195 @@
+//! The warning configuration of a package, which sets the level of each kind of warning.
+//!
+//! A warning can be allowed (so it isn't reported), left as a warning, or denied (so it's
+//! reported as an error, and `roc check` fails). The levels are declared by `# @warnings`
+//! directives in the comments above the header of the root module, which is the manifest of the
+//! package or app being built, e.g.
+//!
+//! ```roc
+//! # @warnings deny all
+//! # @warnings allow unused-argument
+//! # @warnings Parser.Internal allow unused-definition
+//! package [Parser] {}
+//! ```
+//!
+//! Warnings are named by their code (one of [WARNING_CODES]) or `all`, and a directive that
+//! starts with a module name only applies to that module. A module's level for a code beats its
+//! level for `all`, which beats the package's level for the code, which beats the package's level
+//! for `all`. When two directives are about the same thing, the last one wins.
+//!
+//! The configuration only applies to the modules of the root package, so that adopting a stricter
+//! policy doesn't turn the warnings in dependencies into errors.
+use roc_collections::MutMap;
+use roc_parse::ast::CommentOrNewline;
+use roc_problem::can::Problem;
+use roc_problem::WARNING_CODES;
+use roc_solve_problem::TypeError;
+
+/// Sets the level of a warning, e.g. `# @warnings deny unused-import`.
+pub const WARNINGS_DIRECTIVE: &str = "@warnings";
+
+/// Stands for every warning in a directive.
+const ALL_WARNINGS: &str = "all";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WarningLevel {
+    /// The warning isn't reported.
+    Allow,
+    /// The warning is reported as a warning, which is the default.
+    Warn,
+    /// The warning is reported as an error.
+    Deny,
+}
+
+impl WarningLevel {
+    fn from_str(level: &str) -> Option<Self> {
+        match level {
+            "allow" => Some(WarningLevel::Allow),
+            "warn" => Some(WarningLevel::Warn),
+            "deny" => Some(WarningLevel::Deny),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WarningConfig {
+    /// The level of each code (or of every warning, when the code is `None`), for one module or
+    /// (when the module is `None`) the whole package.
+    levels: MutMap<(Option<Box<str>>, Option<&'static str>), WarningLevel>,
+}
+
+impl WarningConfig {
+    /// The configuration declared by the `# @warnings` directives in the comments above the
+    /// header of a root module, and the problems with the directives that don't parse.
+    pub fn from_header_comments(comments: &[CommentOrNewline<'_>]) -> (Self, Vec<Problem>) {
+        let mut config = WarningConfig::default();
+        let mut problems = Vec::new();
+
+        for comment in comments {
+            let CommentOrNewline::LineComment(text) = comment else {
+                continue;
+            };
+
+            let Some(argument) = text.trim().strip_prefix(WARNINGS_DIRECTIVE) else {
+                continue;
+            };
+
+            let is_other_directive =
+                !argument.is_empty() && !argument.starts_with(char::is_whitespace);
+
+            if !is_other_directive && !config.add_directive(argument) {
+                problems.push(Problem::InvalidWarningDirective(
+                    format!("#{text}").into_boxed_str(),
+                ));
+            }
+        }
+
+        (config, problems)
+    }
+
+    /// Add a directive like `deny unused-import` or `Parser.Internal allow all`. Returns whether
+    /// it parsed.
+    pub fn add_directive(&mut self, directive: &str) -> bool {
+        let words: Vec<&str> = directive.split_whitespace().collect();
+        let (module, level, code) = match words.as_slice() {
+            [level, code] => (None, *level, *code),
+            [module, level, code] if module.starts_with(char::is_uppercase) => {
+                (Some(*module), *level, *code)
+            }
+            _ => return false,
+        };
+
+        let Some(level) = WarningLevel::from_str(level) else {
+            return false;
+        };
+
+        let code = if code == ALL_WARNINGS {
+            None
+        } else {
+            match WARNING_CODES.iter().find(|known| **known == code) {
+                Some(known) => Some(*known),
+                None => return false,
+            }
+        };
+
+        self.set(module, code, level);
+
+        true
+    }
+
+    /// Set the level of a warning code, or of every warning when `code` is `None`, for one
+    /// module or the whole package.
+    pub fn set(&mut self, module: Option<&str>, code: Option<&'static str>, level: WarningLevel) {
+        self.levels.insert((module.map(Box::from), code), level);
+    }
+
+    /// The level of the warnings with a code in a module of the package.
+    pub fn level(&self, module: &str, code: &str) -> WarningLevel {
+        let module: Option<Box<str>> = Some(module.into());
+        let code = WARNING_CODES.iter().find(|known| **known == code).copied();
+
+        [
+            (module.clone(), code),
+            (module, None),
+            (None, code),
+            (None, None),
+        ]
+        .into_iter()
+        .find_map(|key| self.levels.get(&key).copied())
+        .unwrap_or(WarningLevel::Warn)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.levels.is_empty()
+    }
+
+    /// Remove the allowed warnings from the canonicalization problems of a module, and turn the
+    /// denied ones into errors.
+    pub fn apply_to_can_problems(&self, module: &str, problems: &mut Vec<Problem>) {
+        self.apply(module, problems, Problem::warning_code, |code, warning| {
+            Problem::DeniedWarning {
+                code,
+                warning: Box::new(warning),
+            }
+        })
+    }
+
+    /// Remove the allowed warnings from the type problems of a module, and turn the denied ones
+    /// into errors.
+    pub fn apply_to_type_problems(&self, module: &str, problems: &mut Vec<TypeError>) {
+        self.apply(
+            module,
+            problems,
+            TypeError::warning_code,
+            |code, warning| TypeError::DeniedWarning {
+                code,
+                warning: Box::new(warning),
+            },
+        )
+    }
+
+    fn apply<P>(
+        &self,
+        module: &str,
+        problems: &mut Vec<P>,
+        warning_code: impl Fn(&P) -> Option<&'static str>,
+        deny: impl Fn(&'static str, P) -> P,
+    ) {
+        if self.is_empty() {
+            return;
+        }
+
+        let mut configured = Vec::with_capacity(problems.len());
+
+        for problem in problems.drain(..) {
+            match warning_code(&problem).map(|code| (code, self.level(module, code))) {
+                Some((_, WarningLevel::Allow)) => {}
+                Some((code, WarningLevel::Deny)) => configured.push(deny(code, problem)),
+                Some((_, WarningLevel::Warn)) | None => configured.push(problem),
+            }
+        }
+
+        *problems = configured;
+    }
+}
@@ -17,16 +17,27 @@ mod helpers;
 use crate::helpers::fixtures_dir;
 use bumpalo::Bump;
 use roc_can::module::ExposedByModule;
-use roc_load_internal::docs::DocDef;
+use roc_can::traverse::def_dependencies;
+use roc_load_internal::call_hierarchy::{render_call_tree, CallDirection};
+use roc_load_internal::determinism::Fingerprint;
+use roc_load_internal::docs::{DocDef, PackageMetadata};
+use roc_load_internal::extract::ExtractError;
 use roc_load_internal::file::{
     ExecutionMode, LoadConfig, LoadResult, LoadStart, LoadingProblem, Threading,
 };
+use roc_load_internal::layout_query::{query_layout, LayoutQueryError};
+use roc_load_internal::manifest::PackageManifest;
 use roc_load_internal::module::LoadedModule;
+use roc_load_internal::occurrences::OccurrenceIndex;
+use roc_load_internal::platform_switch::{check_platform_switch, with_platform};
+use roc_load_internal::rename::{rename, ConflictKind, RenameError};
 use roc_module::ident::ModuleName;
-use roc_module::symbol::{Interns, ModuleId};
+use roc_module::symbol::{Interns, ModuleId, Symbol};
+use roc_mono::layout::describe::{Shape, TagDiscriminant};
 use roc_packaging::cache::RocCacheDir;
+use roc_packaging::resolve::Dependency;
 use roc_problem::can::Problem;
-use roc_region::all::LineInfo;
+use roc_region::all::{LineInfo, Position, Region};
 use roc_reporting::report::{can_problem, DEFAULT_PALETTE};
 use roc_reporting::report::{strip_colors, RenderTarget};
 use roc_reporting::report::{type_problem, RocDocAllocator};
@@ -45,6 +56,24 @@ fn load_and_typecheck(
     exposed_types: ExposedByModule,
     target: Target,
     function_kind: FunctionKind,
+) -> Result<LoadedModule, LoadingProblem> {
+    load_and_typecheck_with_threading(
+        arena,
+        filename,
+        exposed_types,
+        target,
+        function_kind,
+        Threading::Single,
+    )
+}
+
+fn load_and_typecheck_with_threading(
+    arena: &Bump,
+    filename: PathBuf,
+    exposed_types: ExposedByModule,
+    target: Target,
+    function_kind: FunctionKind,
+    threading: Threading,
 ) -> Result<LoadedModule, LoadingProblem> {
     use LoadResult::*;
 
@@ -61,8 +90,13 @@ fn load_and_typecheck(
         function_kind,
         render: RenderTarget::Generic,
         palette: DEFAULT_PALETTE,
-        threading: Threading::Single,
+        threading,
         exec_mode: ExecutionMode::Check,
+        emit_match_trees: false,
+        trace_abilities: false,
+        profile: None,
+        doc_examples: 0,
+        comptime_limits: roc_load_internal::file::ComptimeLimits::default(),
     };
 
     match roc_load_internal::file::load(
@@ -476,6 +510,387 @@ fn module_with_deps() {
     assert_eq!(def_count, 10);
 }
 
+#[test]
+fn arena_stats_per_module() {
+    let subs_by_module = Default::default();
+    let loaded_module = load_fixture("module_with_deps", "Primary", subs_by_module);
+
+    let stats = loaded_module
+        .arena_stats
+        .get(&loaded_module.module_id)
+        .expect("the root module should have arena stats");
+
+    for phase_stats in [stats.parse, stats.canonicalize] {
+        assert!(phase_stats.allocated_bytes > 0);
+        // Only the bytes the module added to its thread's arena count, not the earlier modules'.
+        assert_eq!(phase_stats.high_water_mark, phase_stats.allocated_bytes);
+        assert_eq!(phase_stats.resets, 0);
+    }
+
+    // Every module that was parsed should have stats, not just the root module
+    let dep_id = loaded_module
+        .interns
+        .module_ids
+        .get_id(&"Dep1".into())
+        .expect("Dep1 should have been loaded");
+
+    assert!(loaded_module.arena_stats.contains_key(&dep_id));
+}
+
+#[test]
+fn def_dependency_graph() {
+    let modules = vec![(
+        "Main.roc",
+        indoc!(
+            r"
+                module [main, rest]
+
+                main = is_even 10 && answer == 42
+
+                is_even = \n -> if n == 0 then Bool.true else is_odd (n - 1)
+
+                is_odd = \n -> if n == 0 then Bool.false else is_even (n - 1)
+
+                { answer, rest } = { answer: base + 1, rest: base }
+
+                base = 41
+            "
+        ),
+    )];
+
+    let mut loaded_module = multiple_modules("def_dependency_graph", modules).unwrap();
+    let home = loaded_module.module_id;
+    let interns = &loaded_module.interns;
+    let decls = loaded_module.declarations_by_id.remove(&home).unwrap();
+
+    let names = |symbols: &[Symbol]| {
+        let mut names: Vec<&str> = symbols.iter().map(|s| s.as_str(interns)).collect();
+        names.sort();
+        names
+    };
+
+    let graph: HashMap<_, _> = def_dependencies(&decls)
+        .into_iter()
+        .map(|deps| {
+            (
+                deps.symbol.value.as_str(interns),
+                (names(&deps.uses), names(&deps.used_by), names(&deps.group)),
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        graph,
+        hashmap! {
+            "main" => (vec!["answer", "is_even"], vec![], vec!["main"]),
+            "is_even" => (vec!["is_odd"], vec!["is_odd", "main"], vec!["is_even", "is_odd"]),
+            "is_odd" => (vec!["is_even"], vec!["is_even"], vec!["is_even", "is_odd"]),
+            "answer" => (vec!["base"], vec!["main"], vec!["answer", "rest"]),
+            "rest" => (vec!["base"], vec![], vec!["answer", "rest"]),
+            "base" => (vec![], vec!["answer", "rest"], vec!["base"]),
+        }
+    );
+}
+
+#[test]
+fn call_hierarchy() {
+    let modules = vec![(
+        "Main.roc",
+        indoc!(
+            r#"
+                module [main]
+
+                main = \n -> if is_even n then Num.to_str n else "odd"
+
+                is_even = \n -> if n == 0 then Bool.true else is_odd (n - 1)
+
+                is_odd = \n -> if n == 0 then Bool.false else is_even (n - 1)
+            "#
+        ),
+    )];
+
+    let loaded_module = multiple_modules("call_hierarchy", modules).unwrap();
+    let graph = loaded_module.call_graph();
+    let is_odd = loaded_module.top_level_def_named("is_odd").unwrap();
+    let tree = graph.tree(is_odd, CallDirection::Incoming, 3);
+
+    assert_eq!(
+        render_call_tree(
+            &tree,
+            CallDirection::Incoming,
+            loaded_module.module_id,
+            &loaded_module.interns,
+            &loaded_module.sources,
+        ),
+        indoc!(
+            r"
+                is_odd
+                `-- is_even  Main.roc:5:47
+                    |-- main  Main.roc:3:17
+                    `-- is_odd  Main.roc:7:47 (recursive)
+            "
+        )
+    );
+
+    let shallow = graph.tree(is_odd, CallDirection::Incoming, 1);
+    assert_eq!(shallow.children.len(), 1);
+    assert!(shallow.children[0].truncated);
+    assert!(shallow.children[0].children.is_empty());
+}
+
+#[test]
+fn rename_across_modules() {
+    let modules = vec![
+        (
+            "Helpers.roc",
+            indoc!(
+                r"
+                    module [double, twice]
+
+                    double : U64 -> U64
+                    double = \n -> n * 2
+
+                    twice = \n -> double (double n)
+                "
+            ),
+        ),
+        (
+            "Main.roc",
+            indoc!(
+                r"
+                    module [main]
+
+                    import Helpers exposing [double]
+
+                    main =
+                        n = double 2
+                        { n, m: Helpers.double n }
+                "
+            ),
+        ),
+    ];
+
+    let loaded_module = multiple_modules("rename_across_modules", modules).unwrap();
+    let index = OccurrenceIndex::new(&loaded_module);
+    let renamed = |symbol, new_name| -> Result<Vec<(String, String)>, RenameError> {
+        Ok(rename(&index, symbol, new_name)?
+            .into_iter()
+            .map(|file| {
+                let src = std::fs::read_to_string(&file.path).unwrap();
+                let name = file.path.file_name().unwrap().to_string_lossy().to_string();
+
+                (name, file.apply(&src))
+            })
+            .collect())
+    };
+
+    let double = loaded_module.top_level_def_named("Helpers.double").unwrap();
+
+    assert_eq!(
+        renamed(double, "times_two").unwrap(),
+        vec![
+            (
+                "Helpers.roc".to_string(),
+                indoc!(
+                    r"
+                        module [times_two, twice]
+
+                        times_two : U64 -> U64
+                        times_two = \n -> n * 2
+
+                        twice = \n -> times_two (times_two n)
+
+                    "
+                )
+                .to_string()
+            ),
+            (
+                "Main.roc".to_string(),
+                indoc!(
+                    r"
+                        module [main]
+
+                        import Helpers exposing [times_two]
+
+                        main =
+                            n = times_two 2
+                            { n, m: Helpers.times_two n }
+
+                    "
+                )
+                .to_string()
+            ),
+        ]
+    );
+
+    let (_, main_src) = &loaded_module.sources[&loaded_module.module_id];
+    let n_position = Position::new(main_src.find("n = ").unwrap() as u32);
+    let n = index
+        .symbol_at(loaded_module.module_id, n_position)
+        .unwrap();
+
+    assert_eq!(
+        renamed(n, "count").unwrap()[0].1,
+        indoc!(
+            r"
+                module [main]
+
+                import Helpers exposing [double]
+
+                main =
+                    count = double 2
+                    { n: count, m: Helpers.double count }
+
+            "
+        )
+    );
+
+    let Err(RenameError::Conflicts(conflicts)) = renamed(double, "twice") else {
+        panic!("renaming `double` to `twice` should conflict with `twice`");
+    };
+    assert_eq!(
+        conflicts.iter().map(|c| c.kind).collect::<Vec<_>>(),
+        vec![ConflictKind::AlreadyDefined]
+    );
+
+    assert_eq!(
+        renamed(double, "if"),
+        Err(RenameError::InvalidName {
+            name: "if".to_string()
+        })
+    );
+}
+
+#[test]
+fn extract_function() {
+    let modules = vec![(
+        "Main.roc",
+        indoc!(
+            r"
+                module [area]
+
+                area : U64, U64 -> U64
+                area = |width, height|
+                    scale = 2
+                    width * height * scale
+            "
+        ),
+    )];
+
+    let loaded_module = multiple_modules("extract_function", modules).unwrap();
+    let home = loaded_module.module_id;
+    let (path, _) = &loaded_module.sources[&home];
+    let src = std::fs::read_to_string(path).unwrap();
+    let region_of = |text: &str| {
+        let start = src.find(text).unwrap() as u32;
+
+        Region::new(
+            Position::new(start),
+            Position::new(start + text.len() as u32),
+        )
+    };
+
+    let file = loaded_module
+        .extract_function(home, region_of("width * height"), "product")
+        .unwrap();
+
+    assert_eq!(
+        file.apply(&src),
+        indoc!(
+            r"
+                module [area]
+
+                area : U64, U64 -> U64
+                area = |width, height|
+                    scale = 2
+                    product(width, height) * scale
+
+                product : U64, U64 -> U64
+                product = |width, height| width * height
+
+            "
+        )
+    );
+
+    assert_eq!(
+        loaded_module.extract_function(home, region_of("width * height"), "area"),
+        Err(ExtractError::NameTaken {
+            name: "area".to_string()
+        })
+    );
+    assert_eq!(
+        loaded_module.extract_function(home, region_of("width * height"), "product!"),
+        Err(ExtractError::InvalidName {
+            name: "product!".to_string(),
+            effectful: false
+        })
+    );
+    assert_eq!(
+        loaded_module.extract_function(home, region_of("width *"), "product"),
+        Err(ExtractError::NotAnExpression)
+    );
+}
+
+#[test]
+fn warning_directives() {
+    let modules = vec![
+        (
+            "Dep.roc",
+            indoc!(
+                r"
+                    module [f]
+
+                    f = \x ->
+                        unused = 1
+                        x + 1
+                "
+            ),
+        ),
+        ("Other.roc", "module [x]\n\nx = 1"),
+        (
+            "Main.roc",
+            indoc!(
+                r"
+                    # @warnings deny unused-import
+                    # @warnings Dep allow all
+                    # @warnings forbid unused-import
+                    module [g]
+
+                    import Dep
+                    import Other
+
+                    g = Dep.f 1
+                "
+            ),
+        ),
+    ];
+
+    let arena = Bump::new();
+    let loaded_module = multiple_modules_help("warning_directives", &arena, modules)
+        .unwrap()
+        .unwrap();
+    let home = loaded_module.module_id;
+    let dep_id = loaded_module
+        .interns
+        .module_ids
+        .get_id(&"Dep".into())
+        .unwrap();
+
+    let problems = &loaded_module.can_problems[&home];
+
+    assert_eq!(problems.len(), 2);
+    assert!(matches!(
+        &problems[0],
+        Problem::DeniedWarning { code: "unused-import", warning }
+            if matches!(**warning, Problem::UnusedModuleImport(..))
+    ));
+    assert_eq!(
+        problems[1],
+        Problem::InvalidWarningDirective("# @warnings forbid unused-import".into())
+    );
+    assert_eq!(loaded_module.can_problems[&dep_id], []);
+}
+
 #[test]
 fn load_unit() {
     let subs_by_module = Default::default();
@@ -537,42 +952,242 @@ fn load_docs() {
 }
 
 #[test]
-fn import_alias() {
-    let subs_by_module = Default::default();
-    let loaded_module = load_fixture("module_with_deps", "ImportAlias", subs_by_module);
+fn load_docs_ability_implementations() {
+    let modules = vec![
+        (
+            "Shape.roc",
+            indoc!(
+                r"
+                    module [Shape, area, Square]
 
-    expect_types(
-        loaded_module,
-        hashmap! {
-            "unit" => "Dep1.Unit",
-        },
-    );
-}
+                    Shape implements
+                        area : a -> F64 where a implements Shape
 
-#[test]
-fn import_inside_def() {
-    let subs_by_module = Default::default();
-    let loaded_module = load_fixture("module_with_deps", "ImportInsideDef", subs_by_module);
+                    Square := F64 implements [Shape { area: square_area }]
 
-    expect_types(
-        loaded_module,
-        hashmap! {
-            "dep1_str" => "Str",
-            "dep2_two_dobuled" => "Frac *",
-        },
-    );
-}
+                    square_area = \@Square side -> side * side
+                "
+            ),
+        ),
+        (
+            "Main.roc",
+            indoc!(
+                r"
+                    module [Circle]
 
-#[test]
-#[should_panic(expected = "UNRECOGNIZED NAME")]
-fn exposed_used_outside_scope() {
-    let subs_by_module = Default::default();
-    load_fixture(
-        "module_with_deps",
-        "ExposedUsedOutsideScope",
-        subs_by_module,
-    );
-}
+                    import Shape exposing [Shape]
+
+                    Circle := U64 implements [Shape { area: circle_area }, Eq]
+
+                    circle_area = \@Circle radius -> Num.to_f64 (3 * radius * radius)
+                "
+            ),
+        ),
+    ];
+
+    let loaded_module = multiple_modules("load_docs_ability_implementations", modules).unwrap();
+    let interns = &loaded_module.interns;
+
+    let mut implementations = HashMap::new();
+    for module_docs in loaded_module.docs_by_module.values() {
+        for entry in module_docs.entries.iter() {
+            if let roc_load_internal::docs::DocEntry::DocDef(doc_def) = entry {
+                let names = |symbols: &[Symbol]| {
+                    symbols
+                        .iter()
+                        .map(|symbol| symbol.as_str(interns))
+                        .collect::<Vec<_>>()
+                };
+
+                implementations.insert(
+                    doc_def.name.clone(),
+                    (names(&doc_def.implemented_by), names(&doc_def.implements)),
+                );
+            }
+        }
+    }
+
+    assert_eq!(implementations["Shape"], (vec!["Circle", "Square"], vec![]));
+    assert_eq!(implementations["Square"], (vec![], vec!["Shape"]));
+    assert_eq!(implementations["Circle"], (vec![], vec!["Eq", "Shape"]));
+}
+
+#[test]
+fn load_docs_effectful_functions() {
+    let modules = vec![(
+        "Main.roc",
+        indoc!(
+            r"
+                module [run!, double, answer]
+
+                run! : ({} => {}) => {}
+                run! = \f! -> f! {}
+
+                double : I64 -> I64
+                double = \n -> n * 2
+
+                answer : I64
+                answer = 42
+            "
+        ),
+    )];
+
+    let loaded_module = multiple_modules("load_docs_effectful_functions", modules).unwrap();
+    let module_docs = loaded_module
+        .docs_by_module
+        .get(&loaded_module.module_id)
+        .unwrap();
+
+    let effects: HashMap<_, _> = module_docs
+        .entries
+        .iter()
+        .filter_map(|entry| match entry {
+            roc_load_internal::docs::DocEntry::DocDef(doc_def) => Some((
+                doc_def.name.as_str(),
+                (
+                    doc_def.type_annotation.is_function(),
+                    doc_def.type_annotation.is_effectful(),
+                ),
+            )),
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(effects["run!"], (true, true));
+    assert_eq!(effects["double"], (true, false));
+    assert_eq!(effects["answer"], (false, false));
+}
+
+#[test]
+fn load_docs_examples_from_expects() {
+    let dir = TmpDir::new("tmp/load_docs_examples_from_expects");
+    let filename = dir.path().join("Examples.roc");
+    std::fs::write(
+        &filename,
+        indoc!(
+            r"
+            module [double, triple]
+
+            double : I64 -> I64
+            double = \n -> n * 2
+
+            triple : I64 -> I64
+            triple = \n -> helper n
+
+            helper = \n -> n * 3
+
+            expect double 2 == 4
+
+            expect
+                # doubling twice
+                double (double  1) == 4
+
+            expect double 0 == 0
+
+            expect helper 1 == 3
+            "
+        ),
+    )
+    .unwrap();
+
+    let arena = Bump::new();
+    let load_start = LoadStart::from_path(
+        &arena,
+        filename,
+        None,
+        RenderTarget::Generic,
+        RocCacheDir::Disallowed,
+        DEFAULT_PALETTE,
+    )
+    .unwrap();
+    let load_config = LoadConfig {
+        target: TARGET,
+        function_kind: FunctionKind::LambdaSet,
+        render: RenderTarget::Generic,
+        palette: DEFAULT_PALETTE,
+        threading: Threading::Single,
+        exec_mode: ExecutionMode::Check,
+        emit_match_trees: false,
+        trace_abilities: false,
+        profile: None,
+        doc_examples: 2,
+        comptime_limits: roc_load_internal::file::ComptimeLimits::default(),
+    };
+
+    let loaded_module = match roc_load_internal::file::load(
+        &arena,
+        load_start,
+        Default::default(),
+        Default::default(),
+        RocCacheDir::Disallowed,
+        load_config,
+    ) {
+        Ok(LoadResult::TypeChecked(module)) => module,
+        _ => unreachable!(),
+    };
+
+    let examples: HashMap<_, _> = loaded_module
+        .docs_by_module
+        .get(&loaded_module.module_id)
+        .unwrap()
+        .entries
+        .iter()
+        .filter_map(|entry| match entry {
+            roc_load_internal::docs::DocEntry::DocDef(doc_def) => {
+                Some((doc_def.name.as_str(), doc_def.examples.clone()))
+            }
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(
+        examples["double"],
+        vec![
+            "expect double 2 == 4".to_string(),
+            "expect\n    # doubling twice\n    double (double 1) == 4".to_string(),
+        ]
+    );
+    // `helper` isn't exposed, so its `expect` doesn't show how to use `triple`.
+    assert_eq!(examples["triple"], Vec::<String>::new());
+}
+
+#[test]
+fn import_alias() {
+    let subs_by_module = Default::default();
+    let loaded_module = load_fixture("module_with_deps", "ImportAlias", subs_by_module);
+
+    expect_types(
+        loaded_module,
+        hashmap! {
+            "unit" => "Dep1.Unit",
+        },
+    );
+}
+
+#[test]
+fn import_inside_def() {
+    let subs_by_module = Default::default();
+    let loaded_module = load_fixture("module_with_deps", "ImportInsideDef", subs_by_module);
+
+    expect_types(
+        loaded_module,
+        hashmap! {
+            "dep1_str" => "Str",
+            "dep2_two_dobuled" => "Frac *",
+        },
+    );
+}
+
+#[test]
+#[should_panic(expected = "UNRECOGNIZED NAME")]
+fn exposed_used_outside_scope() {
+    let subs_by_module = Default::default();
+    load_fixture(
+        "module_with_deps",
+        "ExposedUsedOutsideScope",
+        subs_by_module,
+    );
+}
 
 #[test]
 #[should_panic(expected = "MODULE NOT IMPORTED")]
@@ -636,6 +1251,31 @@ fn load_astar() {
     );
 }
 
+#[test]
+fn load_is_deterministic_across_thread_counts() {
+    let filename = fixtures_dir().join("module_with_deps").join("AStar.roc");
+    let fingerprint = |threading| {
+        let arena = Bump::new();
+        let loaded = load_and_typecheck_with_threading(
+            &arena,
+            filename.clone(),
+            Default::default(),
+            TARGET,
+            FunctionKind::LambdaSet,
+            threading,
+        )
+        .unwrap();
+
+        Fingerprint::of_loaded(&loaded)
+    };
+
+    let single = fingerprint(Threading::Single);
+    let multi = fingerprint(Threading::AtMost(4));
+
+    assert!(!single.is_empty());
+    assert_eq!(single.first_divergence(&multi), None);
+}
+
 #[test]
 fn load_principal_types() {
     let subs_by_module = Default::default();
@@ -1949,6 +2589,94 @@ fn issue_2863_module_type_does_not_exist() {
     }
 }
 
+#[test]
+fn c_function_with_unsupported_type() {
+    let modules = vec![
+        (
+            "platform/main.roc",
+            indoc!(
+                r#"
+                    platform "testplatform"
+                        requires {} { main! : {} => {} }
+                        exposes []
+                        packages {}
+                        imports []
+                        provides [main_for_host!]
+
+                    main_for_host! : {} => {}
+                    main_for_host! = \{} -> main!({})
+                    "#
+            ),
+        ),
+        (
+            "platform/Host.roc",
+            indoc!(
+                r#"
+                    hosted [sqrt!, get_line!]
+
+                    # @c
+                    sqrt! : F64 => F64
+
+                    # @c getline
+                    get_line! : {} => Str
+                    "#
+            ),
+        ),
+        (
+            "main.roc",
+            indoc!(
+                r#"
+                    app [main!] { pf: platform "platform/main.roc" }
+
+                    import pf.Host
+
+                    main! = \{} ->
+                        _ = Host.sqrt!(2.0)
+                        _ = Host.get_line!({})
+                        {}
+                    "#
+            ),
+        ),
+    ];
+
+    let mut loaded_module = multiple_modules("c_function_with_unsupported_type", modules).unwrap();
+
+    // The problem is in the hosted module, rather than in the app
+    let (module_id, problems) = loaded_module
+        .can_problems
+        .drain()
+        .find(|(_, problems)| !problems.is_empty())
+        .unwrap();
+    let (filepath, src) = loaded_module.sources.get(&module_id).unwrap();
+    let report = format_can_problems(
+        problems,
+        module_id,
+        &loaded_module.interns,
+        filepath.clone(),
+        src,
+    );
+
+    assert_eq!(
+        report,
+        indoc!(
+            r"
+            ── UNSUPPORTED C FUNCTION TYPE in ...n_with_unsupported_type/platform/Host.roc ─
+
+            This function is marked `# @c`, but its type can't be passed to C:
+
+            7│  get_line! : {} => Str
+                            ^^^^^^^^^
+
+            C functions can only take and return integers of up to 64 bits, F32,
+            F64, and Bool, with {} for no arguments or no return value.
+
+            Tip: Remove the directive to have the host provide a `roc_fx_` function
+            instead, which can take any type.
+            "
+        )
+    );
+}
+
 #[test]
 fn import_builtin_in_platform_and_check_app() {
     let modules = vec![
@@ -2017,9 +2745,13 @@ fn module_cyclic_import_itself() {
                 │     Age
                 └─────┘
 
-            Cyclic dependencies are not allowed in Roc! Can you restructure a
-            module in this import chain so that it doesn't have to depend on
-            itself?"
+            This is what each import in the chain is used for:
+
+                Age imports Age on line 3, but doesn't use anything from it
+
+            Cyclic dependencies are not allowed in Roc! The easiest way to break
+            this cycle is to remove the import of Age from Age. Nothing uses it,
+            so it can just go."
         ),
         "\n{}",
         err
@@ -2068,9 +2800,109 @@ fn module_cyclic_import_transitive() {
                 │     Age
                 └─────┘
 
-            Cyclic dependencies are not allowed in Roc! Can you restructure a
-            module in this import chain so that it doesn't have to depend on
-            itself?"
+            This is what each import in the chain is used for:
+
+                Age imports Person on line 3, but doesn't use anything from it
+                Person imports Age on line 3, but doesn't use anything from it
+
+            Cyclic dependencies are not allowed in Roc! The easiest way to break
+            this cycle is to remove the import of Age from Person. Nothing uses
+            it, so it can just go."
+        ),
+        "\n{}",
+        err
+    );
+}
+
+#[test]
+fn module_cyclic_imports_suggested_removals() {
+    let modules = vec![
+        (
+            "Left.roc",
+            indoc!(
+                r"
+                module [x]
+
+                import Base
+
+                x = Base.a + Base.b
+                "
+            ),
+        ),
+        (
+            "Right.roc",
+            indoc!(
+                r"
+                module [right, other]
+
+                import Base
+
+                right = Base.a
+                other = 1
+                "
+            ),
+        ),
+        (
+            "Base.roc",
+            indoc!(
+                r"
+                module [a, b]
+
+                import Left as L
+                import Right exposing [right]
+
+                a = L.x + right + Right.other
+                b = 1
+                "
+            ),
+        ),
+        (
+            "Main.roc",
+            indoc!(
+                r"
+                module [main]
+
+                import Left
+                import Right
+
+                main = Left.x + Right.right
+                "
+            ),
+        ),
+    ];
+
+    let err = multiple_modules("module_cyclic_imports_suggested_removals", modules).unwrap_err();
+    assert_eq!(
+        err,
+        indoc!(
+            r"
+            ── IMPORT CYCLE in tmp/module_cyclic_imports_suggested_removals/Base.roc ───────
+
+            I can't compile Base because it depends on itself through the
+            following chain of module imports:
+
+                ┌─────┐
+                │     Base
+                │     ↓
+                │     Left
+                │     ↓
+                │     Base
+                └─────┘
+
+            This is what each import in the chain is used for:
+
+                Base imports Left on line 3, for `x`
+                Left imports Base on line 3, for `a` and `b`
+
+            Cyclic dependencies are not allowed in Roc! These modules depend on
+            each other in more than one way, so the fewest imports to remove to
+            break every cycle between them are:
+
+                Right imports Base on line 3, for `a`
+                Base imports Left on line 3, for `x`
+
+            What each of them is used for would have to move into the module that
+            imports it, or into a new module that both modules can import."
         ),
         "\n{}",
         err
@@ -2183,3 +3015,449 @@ fn roc_package_depends_on_other_package() {
 
     assert!(result.is_ok());
 }
+
+#[test]
+fn read_package_manifest() {
+    let dir = TmpDir::new("tmp/read_package_manifest");
+    let filename = dir.path().join("main.roc");
+    std::fs::write(
+        &filename,
+        indoc!(
+            r#"
+            package [Json, Json.Decode] {} manifest {
+                name: "json",
+                version: "1.2.0-beta.1",
+                license: "UPL-1.0",
+                authors: ["Ada Lovelace", "Grace Hopper"],
+                dependencies: ["parser ^0.4"],
+            }
+            "#
+        ),
+    )
+    .unwrap();
+
+    let manifest = roc_load_internal::manifest::read_package_manifest(&filename)
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(
+        manifest,
+        PackageManifest {
+            name: "json".to_string(),
+            version: "1.2.0-beta.1".to_string(),
+            license: Some("UPL-1.0".to_string()),
+            description: None,
+            homepage: None,
+            repository: None,
+            authors: vec!["Ada Lovelace".to_string(), "Grace Hopper".to_string()],
+            keywords: vec![],
+            exposes: vec!["Json".to_string(), "Json.Decode".to_string()],
+            dependencies: vec![Dependency::parse("parser ^0.4").unwrap()],
+        }
+    );
+}
+
+#[test]
+fn package_metadata_notice() {
+    let manifest = PackageManifest {
+        name: "json".to_string(),
+        version: "1.2.0".to_string(),
+        license: Some("UPL-1.0".to_string()),
+        description: None,
+        homepage: Some("https://json.example.com".to_string()),
+        repository: Some("https://github.com/example/json".to_string()),
+        authors: vec!["Ada Lovelace".to_string(), "Grace Hopper".to_string()],
+        keywords: vec![],
+        exposes: vec!["Json".to_string()],
+        dependencies: vec![],
+    };
+    let unlicensed = PackageMetadata {
+        name: "csv".to_string(),
+        version: "0.3.0".to_string(),
+        license: None,
+        source_url: None,
+        description: None,
+        authors: vec![],
+    };
+
+    assert_eq!(
+        roc_load_internal::docs::notice_file(&[PackageMetadata::from(&manifest), unlicensed]),
+        indoc!(
+            r#"
+            This software includes the following Roc packages:
+
+            json 1.2.0
+            License: UPL-1.0
+            Source: https://github.com/example/json
+            Authors: Ada Lovelace, Grace Hopper
+
+            csv 0.3.0
+            License: not specified
+            "#
+        )
+    );
+}
+
+#[test]
+fn package_manifest_invalid_version() {
+    let modules = vec![
+        (
+            "Json.roc",
+            indoc!(
+                r#"
+            module [encode]
+
+            encode = \str -> str
+            "#
+            ),
+        ),
+        (
+            "main.roc",
+            indoc!(
+                r#"
+            package [Json] {} manifest {
+                name: "json",
+                version: "1.2",
+            }
+            "#
+            ),
+        ),
+    ];
+
+    let err =
+        strip_colors(&multiple_modules("package_manifest_invalid_version", modules).unwrap_err());
+
+    assert_eq!(
+        err,
+        indoc!(
+            r#"
+            ── INVALID PACKAGE VERSION in tmp/package_manifest_invalid_version/main.roc ────
+
+            This package version isn't valid:
+
+            3│      version: "1.2",
+                             ^^^^^
+
+            Versions look like `1.2.3`, optionally followed by a prerelease tag
+            like `1.2.3-beta.1`."#
+        ),
+        "\n{}",
+        err
+    );
+}
+
+#[test]
+fn trace_unfulfilled_ability_obligations() {
+    let dir = TmpDir::new("tmp/trace_unfulfilled_ability_obligations");
+    let filename = dir.path().join("Trace.roc");
+    std::fs::write(
+        &filename,
+        indoc!(
+            r"
+            module [main]
+
+            MHash implements
+                m_hash : a -> U64 where a implements MHash
+
+            Id := U64
+
+            main = m_hash (@Id 1)
+            "
+        ),
+    )
+    .unwrap();
+
+    let arena = Bump::new();
+    let load_start = LoadStart::from_path(
+        &arena,
+        filename,
+        None,
+        RenderTarget::Generic,
+        RocCacheDir::Disallowed,
+        DEFAULT_PALETTE,
+    )
+    .unwrap();
+    let load_config = LoadConfig {
+        target: TARGET,
+        function_kind: FunctionKind::LambdaSet,
+        render: RenderTarget::Generic,
+        palette: DEFAULT_PALETTE,
+        threading: Threading::Single,
+        exec_mode: ExecutionMode::Check,
+        emit_match_trees: false,
+        trace_abilities: true,
+        profile: None,
+        doc_examples: 0,
+        comptime_limits: roc_load_internal::file::ComptimeLimits::default(),
+    };
+
+    let loaded_module = match roc_load_internal::file::load(
+        &arena,
+        load_start,
+        Default::default(),
+        Default::default(),
+        RocCacheDir::Disallowed,
+        load_config,
+    ) {
+        Ok(LoadResult::TypeChecked(module)) => module,
+        _ => unreachable!(),
+    };
+
+    let trace = loaded_module
+        .ability_trace
+        .to_json_lines(&loaded_module.interns);
+
+    assert_eq!(
+        trace,
+        concat!(
+            r#"{"ability":"Trace.MHash","candidates":["#,
+            r#"{"ability":"Trace.MHash","found":false,"kind":"declared-impl","opaque":"Trace.Id"},"#,
+            r#"{"ability":"Trace.MHash","found":false,"kind":"builtin-opaque-impl","opaque":"Trace.Id"}],"#,
+            r#""kind":"obligation","module":"Trace","region":{"end":111,"start":106},"type":{"opaque":"Trace.Id"}}"#,
+            "\n",
+            r#"{"decision":"unresolved","kind":"resolution","member":"Trace.m_hash","module":"Trace","type":"Id -> U64"}"#,
+            "\n",
+        )
+    );
+}
+
+#[test]
+fn derive_imported_ability_with_hooks() {
+    let modules = vec![
+        (
+            "Show.roc",
+            indoc!(
+                r#"
+                module [Show, show, show_record, show_tag]
+
+                Show implements show : a -> Str where a implements Show
+
+                show_record : List (Str, Str) -> Str
+                show_record = \fields ->
+                    List.map fields (\(name, value) -> Str.concat name value)
+                    |> Str.join_with ", "
+
+                show_tag : Str, List Str -> Str
+                show_tag = \name, payloads -> Str.join_with (List.prepend payloads name) " "
+                "#
+            ),
+        ),
+        (
+            "Main.roc",
+            indoc!(
+                r#"
+                module [main]
+
+                import Show as S
+
+                Name := Str implements [S.Show { show: show_name }]
+
+                show_name = \@Name name -> name
+
+                Person := { name : Name, pet : [None, Cat { name : Name }] } implements [S.Show]
+
+                main = S.show (@Person { name: @Name "Ada", pet: Cat { name: @Name "Tom" } })
+                "#
+            ),
+        ),
+    ];
+
+    let loaded_module = multiple_modules("derive_imported_ability_with_hooks", modules).unwrap();
+
+    expect_types(
+        loaded_module,
+        HashMap::from([
+            ("main", "Str"),
+            ("show_name", "Name -> Str"),
+            ("#Person_show", "Person -> Str"),
+        ]),
+    );
+}
+
+#[test]
+fn platform_switch_incompatibilities() {
+    let dir = TmpDir::new("tmp/platform_switch_incompatibilities");
+    let platform = |main_type: &str| {
+        format!(
+            indoc!(
+                r#"
+                platform "switch"
+                    requires {{}} {{ main : {} }}
+                    exposes []
+                    packages {{}}
+                    imports []
+                    provides [main_for_host]
+
+                main_for_host = main
+                "#
+            ),
+            main_type
+        )
+    };
+
+    for (subdir, main_type) in [("current", "Str"), ("same", "Str"), ("changed", "U64")] {
+        std::fs::create_dir_all(dir.path().join(subdir)).unwrap();
+        std::fs::write(
+            dir.path().join(subdir).join("main.roc"),
+            platform(main_type),
+        )
+        .unwrap();
+    }
+
+    let app_path = dir.path().join("main.roc");
+    std::fs::write(
+        &app_path,
+        indoc!(
+            r#"
+            app [main] { pf: platform "current/main.roc" }
+
+            main = "Hello, World!\n"
+            "#
+        ),
+    )
+    .unwrap();
+
+    let check = |candidate: &str| {
+        let arena = Bump::new();
+        let load_config = LoadConfig {
+            target: TARGET,
+            function_kind: FunctionKind::LambdaSet,
+            render: RenderTarget::Generic,
+            palette: DEFAULT_PALETTE,
+            threading: Threading::Single,
+            exec_mode: ExecutionMode::Check,
+            emit_match_trees: false,
+            trace_abilities: false,
+            profile: None,
+            doc_examples: 0,
+            comptime_limits: roc_load_internal::file::ComptimeLimits::default(),
+        };
+
+        check_platform_switch(
+            &arena,
+            app_path.clone(),
+            candidate,
+            Default::default,
+            RocCacheDir::Disallowed,
+            load_config,
+        )
+        .unwrap()
+        .unwrap()
+    };
+
+    assert!(check("same/main.roc").is_compatible());
+
+    let switch = check("changed/main.roc");
+
+    assert_eq!(switch.incompatibilities.len(), 1);
+    assert_eq!(
+        switch.incompatibilities[0].filename,
+        dir.path().join("changed/main.roc")
+    );
+    assert!(switch.incompatibilities[0]
+        .report
+        .contains("Something is off with the type annotation of the `main` required symbol"));
+}
+
+#[test]
+fn with_platform_replaces_the_platform_package() {
+    assert_eq!(
+        with_platform(
+            r#"app [main] { cli: "cli.roc", pf: platform "old/main.roc" }"#,
+            "https://example.com/new.tar.br"
+        ),
+        Some(
+            r#"app [main] { cli: "cli.roc", pf: platform "https://example.com/new.tar.br" }"#
+                .to_string()
+        )
+    );
+    assert_eq!(with_platform("module [main]", "new/main.roc"), None);
+}
+
+#[test]
+fn query_layouts_of_module_types() {
+    let dir = TmpDir::new("tmp/query_layouts_of_module_types");
+    let module_path = dir.path().join("Geometry.roc");
+    std::fs::write(
+        &module_path,
+        indoc!(
+            r#"
+            module [Shape]
+
+            Shape : [Circle F64, Rect { width : U32, height : U32 }]
+            "#
+        ),
+    )
+    .unwrap();
+
+    let query = |type_annotation: &str| {
+        let arena = Bump::new();
+        let load_config = LoadConfig {
+            target: TARGET,
+            function_kind: FunctionKind::LambdaSet,
+            render: RenderTarget::Generic,
+            palette: DEFAULT_PALETTE,
+            threading: Threading::Single,
+            exec_mode: ExecutionMode::Check,
+            emit_match_trees: false,
+            trace_abilities: false,
+            profile: None,
+            doc_examples: 0,
+            comptime_limits: roc_load_internal::file::ComptimeLimits::default(),
+        };
+
+        query_layout(
+            &arena,
+            module_path.clone(),
+            type_annotation,
+            Default::default(),
+            RocCacheDir::Disallowed,
+            load_config,
+        )
+        .map_err(|error| match error {
+            LayoutQueryError::Loading(problem) => panic!("could not load the module: {problem:?}"),
+            LayoutQueryError::Problems(_) => "problems",
+            LayoutQueryError::NotConcrete { .. } => "not concrete",
+        })
+    };
+
+    let shape = query("Shape").unwrap();
+    let Shape::TagUnion {
+        tags,
+        discriminant,
+        on_heap,
+    } = &shape.shape
+    else {
+        panic!("expected a tag union, but got {shape:?}");
+    };
+
+    assert_eq!((shape.size, shape.alignment), (16, 8));
+    assert_eq!(
+        *discriminant,
+        TagDiscriminant::Stored { offset: 8, size: 1 }
+    );
+    assert!(!on_heap);
+    assert_eq!(
+        tags.iter()
+            .map(|tag| tag.name.as_deref().unwrap())
+            .collect::<Vec<_>>(),
+        ["Circle", "Rect"]
+    );
+
+    let record = query("{ name : Str, age : U8 }").unwrap();
+    let Shape::Struct { fields } = &record.shape else {
+        panic!("expected a struct, but got {record:?}");
+    };
+
+    assert_eq!((record.size, record.alignment), (32, 8));
+    assert_eq!(
+        fields
+            .iter()
+            .map(|field| (field.name.as_deref().unwrap(), field.offset))
+            .collect::<Vec<_>>(),
+        [("name", 0), ("age", 24)]
+    );
+
+    assert_eq!(query("List a"), Err("not concrete"));
+    assert_eq!(query("Missing"), Err("problems"));
+}
@@ -401,6 +401,7 @@ impl<'a> LowerParams<'a> {
                 | OpaqueWrapFunction(_)
                 | EmptyRecord
                 | RuntimeError(_)
+                | TypedHole { .. }
                 | Num(_, _, _, _)
                 | Int(_, _, _, _, _)
                 | Float(_, _, _, _, _)
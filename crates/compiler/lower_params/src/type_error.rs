@@ -106,7 +106,9 @@ pub fn remove_module_param_arguments(
             | TypeError::UnsuffixedEffectfulFunction(_, _)
             | TypeError::SuffixedPureFunction(_, _)
             | TypeError::InvalidTryTarget(_, _, _)
-            | TypeError::TypeIsNotGeneralized(..) => {}
+            | TypeError::TypeIsNotGeneralized(..)
+            | TypeError::TypedHole { .. }
+            | TypeError::DeniedWarning { .. } => {}
         }
     }
 }
@@ -130,6 +130,7 @@ impl ModuleName {
     pub const DECODE: &'static str = "Decode";
     pub const HASH: &'static str = "Hash";
     pub const INSPECT: &'static str = "Inspect";
+    pub const BINARY: &'static str = "Binary";
 
     pub fn as_str(&self) -> &str {
         self.0.as_str()
@@ -6,6 +6,7 @@ use crate::symbol::Symbol;
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum LowLevel {
     StrConcat,
+    StrConcatAmortized,
     StrJoinWith,
     StrIsEmpty,
     StrStartsWith,
@@ -30,6 +31,9 @@ pub enum LowLevel {
     StrWithAsciiLowercased,
     StrWithAsciiUppercased,
     StrCaselessAsciiEquals,
+    StrWithUppercased,
+    StrWithLowercased,
+    StrCaselessEquals,
     ListLenUsize,
     ListLenU64,
     ListWithCapacity,
@@ -50,6 +54,7 @@ pub enum LowLevel {
     ListConcatUtf8,
     ListIncref,
     ListDecref,
+    ListContainsNum,
     NumAdd,
     NumAddWrap,
     NumAddChecked,
@@ -79,6 +84,7 @@ pub enum LowLevel {
     NumTan,
     NumSqrtUnchecked,
     NumLogUnchecked,
+    NumExpDec,
     NumRound,
     NumToFrac,
     NumPow,
@@ -233,6 +239,8 @@ macro_rules! map_symbol_to_lowlevel {
                 LowLevel::RefCountIsUnique => unimplemented!(),
                 LowLevel::ListIncref => unimplemented!(),
                 LowLevel::ListDecref => unimplemented!(),
+                LowLevel::ListContainsNum => unimplemented!(),
+                LowLevel::StrConcatAmortized => unimplemented!(),
 
                 LowLevel::SetJmp => unimplemented!(),
                 LowLevel::LongJmp => unimplemented!(),
@@ -273,6 +281,9 @@ map_symbol_to_lowlevel! {
     StrWithAsciiLowercased <= STR_WITH_ASCII_LOWERCASED;
     StrWithAsciiUppercased <= STR_WITH_ASCII_UPPERCASED;
     StrCaselessAsciiEquals <= STR_CASELESS_ASCII_EQUALS;
+    StrWithUppercased <= STR_WITH_UPPERCASED;
+    StrWithLowercased <= STR_WITH_LOWERCASED;
+    StrCaselessEquals <= STR_CASELESS_EQUALS;
     ListLenU64 <= LIST_LEN_U64;
     ListLenUsize <= LIST_LEN_USIZE;
     ListGetCapacity <= LIST_CAPACITY;
@@ -317,14 +328,15 @@ map_symbol_to_lowlevel! {
     NumSin <= NUM_SIN;
     NumCos <= NUM_COS;
     NumTan <= NUM_TAN;
-    NumSqrtUnchecked <= NUM_SQRT;
-    NumLogUnchecked <= NUM_LOG;
+    NumSqrtUnchecked <= NUM_SQRT, NUM_SQRT_DEC;
+    NumLogUnchecked <= NUM_LOG, NUM_LOG_DEC;
+    NumExpDec <= NUM_EXP_DEC;
     NumRound <= NUM_ROUND;
     NumToFrac <= NUM_TO_FRAC;
     NumIsNan <= NUM_IS_NAN;
     NumIsInfinite <= NUM_IS_INFINITE;
     NumIsFinite <= NUM_IS_FINITE;
-    NumPow <= NUM_POW;
+    NumPow <= NUM_POW, NUM_POW_DEC;
     NumCeiling <= NUM_CEILING;
     NumPowInt <= NUM_POW_INT;
     NumFloor <= NUM_FLOOR;
@@ -381,7 +381,7 @@ impl ModuleId {
         ModuleId(unsafe { NonZeroU32::new_unchecked(id as u32) })
     }
 
-    const fn to_zero_indexed(self) -> usize {
+    pub const fn to_zero_indexed(self) -> usize {
         (self.0.get() - 1) as usize
     }
 
@@ -1354,6 +1354,10 @@ define_builtins! {
         167 NUM_INFINITY_F32: "infinity_f32"
         168 NUM_INFINITY_F64: "infinity_f64"
         169 NUM_FROM_BOOL: "from_bool"
+        170 NUM_SQRT_DEC: "sqrt_dec"
+        171 NUM_LOG_DEC: "log_dec"
+        172 NUM_EXP_DEC: "exp_dec"
+        173 NUM_POW_DEC: "pow_dec"
     }
     4 BOOL: "Bool" => {
         0 BOOL_BOOL: "Bool" exposed_type=true // the Bool.Bool type alias
@@ -1427,6 +1431,12 @@ define_builtins! {
         55 STR_FROM_UTF16_LOSSY: "from_utf16_lossy"
         56 STR_FROM_UTF32: "from_utf32"
         57 STR_FROM_UTF32_LOSSY: "from_utf32_lossy"
+        58 STR_GRAPHEMES: "graphemes"
+        59 STR_COUNT_GRAPHEMES: "count_graphemes"
+        60 STR_TRUNCATE: "truncate"
+        61 STR_WITH_UPPERCASED: "with_uppercased"
+        62 STR_WITH_LOWERCASED: "with_lowercased"
+        63 STR_CASELESS_EQUALS: "caseless_equals"
     }
     6 LIST: "List" => {
         0 LIST_LIST: "List" exposed_apply_type=true // the List.List type alias
@@ -1525,6 +1535,7 @@ define_builtins! {
         93 LIST_SPLIT_ON: "split_on"
         94 LIST_SPLIT_ON_LIST: "split_on_list"
         95 LIST_WALK_TRY_FX: "walk_try!"
+        96 LIST_SORT_BY_KEYS: "sort_by_keys"
     }
     7 RESULT: "Result" => {
         0 RESULT_RESULT: "Result" exposed_type=true // the Result.Result type alias
@@ -1575,6 +1586,10 @@ define_builtins! {
         28 DICT_DROP_IF: "drop_if"
         29 DICT_RESERVE: "reserve"
         30 DICT_RELEASE_EXCESS_CAPACITY: "release_excess_capacity"
+        31 DICT_UNION: "union"
+        32 DICT_INTERSECT: "intersect"
+        33 DICT_DIFFERENCE: "difference"
+        34 DICT_HASH_PROBLEMS: "hash_problems"
     }
     9 SET: "Set" => {
         0 SET_SET: "Set" exposed_type=true // the Set.Set type alias
@@ -1667,6 +1682,11 @@ define_builtins! {
         26 DECODE_FROM_BYTES_PARTIAL: "from_bytes_partial"
         27 DECODE_FROM_BYTES: "from_bytes"
         28 DECODE_MAP_RESULT: "map_result"
+        29 DECODE_DECODE_PARTIAL: "DecodePartial" exposed_type=true
+        30 DECODE_PARTIAL: "partial"
+        31 DECODE_PARTIAL_WITH: "partial_with"
+        32 DECODE_FEED: "feed"
+        33 DECODE_FINISH: "finish"
     }
     13 HASH: "Hash" => {
         0 HASH_HASH_ABILITY: "Hash" exposed_type=true
@@ -1727,6 +1747,15 @@ define_builtins! {
         32 INSPECT_TO_INSPECTOR: "to_inspector"
         33 INSPECT_TO_STR: "to_str"
     }
+    15 BINARY: "Binary" => {
+        0 BINARY_BINARY: "Binary" exposed_type=true
+        1 BINARY_BINARY_FORMAT: "binary"
+        2 BINARY_FORMAT_VERSION: "format_version"
+        3 BINARY_TO_BYTES: "to_bytes"
+        4 BINARY_FROM_BYTES: "from_bytes"
+        5 BINARY_RAW_TAG_TYPE: "RawTag" exposed_type=true
+        6 BINARY_RAW_TAG: "raw_tag"
+    }
 
-    num_modules: 15 // Keep this count up to date by hand! (TODO: see the mut_map! macro for how we could determine this count correctly in the macro)
+    num_modules: 16 // Keep this count up to date by hand! (TODO: see the mut_map! macro for how we could determine this count correctly in the macro)
 }
@@ -2,7 +2,8 @@ use bumpalo::{
     collections::{CollectIn, Vec},
     Bump,
 };
-use roc_collections::{MutMap, ReferenceMatrix};
+use roc_collections::{MutMap, MutSet, ReferenceMatrix};
+use roc_module::low_level::LowLevel;
 use roc_module::symbol::Symbol;
 
 use crate::{
@@ -95,24 +96,172 @@ impl std::ops::Index<usize> for BorrowSignature {
     }
 }
 
+/// The environment variable that overrides borrow inference, see [BorrowInference::from_env].
+pub const BORROW_INFERENCE_ENV_VAR: &str = "ROC_BORROW_INFERENCE";
+
+/// How the ownership of parameters is decided. Forcing parameters to be owned is useful to find
+/// out whether borrow inference is responsible for unexpected refcounting. They can't be forced
+/// to be borrowed, because that isn't sound for a parameter that is returned or consumed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum BorrowInference {
+    #[default]
+    Infer,
+    /// Every parameter is owned, as if there was no borrow inference.
+    AllOwned,
+    /// The parameters of these functions are owned, and the others are inferred.
+    Owned(MutSet<Symbol>),
+}
+
+impl BorrowInference {
+    /// Reads [BORROW_INFERENCE_ENV_VAR], which can be
+    ///
+    /// - `infer` (or unset): infer the ownership of every parameter
+    /// - `owned`: make every parameter owned
+    /// - `owned:Main.parse,Main.render`: make the parameters of the given functions owned
+    ///
+    /// `qualified_name` gives the name of a proc in the same `Module.ident` form. Names that don't
+    /// match a proc, and other values of the variable, are ignored.
+    pub fn from_env<'a>(
+        procs: &MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+        qualified_name: impl Fn(Symbol) -> Option<String>,
+    ) -> Self {
+        match std::env::var(BORROW_INFERENCE_ENV_VAR) {
+            Ok(value) => Self::parse(
+                &value,
+                procs.keys().map(|(symbol, _)| *symbol),
+                qualified_name,
+            ),
+            Err(_) => BorrowInference::Infer,
+        }
+    }
+
+    fn parse(
+        value: &str,
+        symbols: impl Iterator<Item = Symbol>,
+        qualified_name: impl Fn(Symbol) -> Option<String>,
+    ) -> Self {
+        match value.trim() {
+            "owned" => BorrowInference::AllOwned,
+            value => match value.strip_prefix("owned:") {
+                Some(names) => {
+                    let names: MutSet<&str> = names.split(',').map(str::trim).collect();
+
+                    BorrowInference::Owned(
+                        symbols
+                            .filter(|symbol| {
+                                qualified_name(*symbol)
+                                    .is_some_and(|name| names.contains(name.as_str()))
+                            })
+                            .collect(),
+                    )
+                }
+                None => BorrowInference::Infer,
+            },
+        }
+    }
+
+    fn forces_owned(&self, symbol: Symbol) -> bool {
+        match self {
+            BorrowInference::Infer => false,
+            BorrowInference::AllOwned => true,
+            BorrowInference::Owned(symbols) => symbols.contains(&symbol),
+        }
+    }
+}
+
+/// What borrow inference decided for a parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamOwnership {
+    Borrowed,
+    Owned(OwnedBecause),
+}
+
+/// Why a parameter is owned. When several uses of a parameter require it to be owned, this is
+/// the first one that borrow inference came across.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OwnedBecause {
+    /// Only lists and strings can be borrowed; every other parameter is owned.
+    NotBorrowable,
+    /// It was forced to be owned with [BORROW_INFERENCE_ENV_VAR].
+    Forced,
+    /// It's returned.
+    Returned,
+    /// It's passed to a function that owns that parameter.
+    PassedToOwned(Symbol),
+    /// It's passed to a lowlevel that owns that argument.
+    PassedToLowLevel(LowLevel),
+    /// It's passed to a function whose borrow signature isn't known, like a function pointer, a
+    /// foreign function or the function passed to a higher-order lowlevel.
+    PassedToUnknown,
+    /// It's passed to a join point that owns that parameter, e.g. a loop that consumes it.
+    PassedToJoinPoint,
+}
+
+/// The ownership of the parameters of every proc, in order.
+pub type InferredBorrows<'a> = MutMap<(Symbol, ProcLayout<'a>), std::vec::Vec<ParamOwnership>>;
+
 pub(crate) struct BorrowSignatures<'a> {
     pub(crate) procs: MutMap<(Symbol, ProcLayout<'a>), BorrowSignature>,
+    /// Why each parameter that could have been borrowed (by index) is owned
+    owned_because: MutMap<((Symbol, ProcLayout<'a>), usize), OwnedBecause>,
+}
+
+impl<'a> BorrowSignatures<'a> {
+    pub(crate) fn inferred(
+        &self,
+        arena: &'a Bump,
+        procs: &MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+    ) -> InferredBorrows<'a> {
+        procs
+            .iter()
+            .map(|(proc_key, proc)| {
+                let key = (proc.name.name(), proc.proc_layout(arena));
+                let params = self.procs[&key]
+                    .iter()
+                    .enumerate()
+                    .map(|(index, ownership)| match ownership {
+                        Ownership::Borrowed => ParamOwnership::Borrowed,
+                        Ownership::Owned => ParamOwnership::Owned(
+                            self.owned_because
+                                .get(&(key, index))
+                                .copied()
+                                .unwrap_or(OwnedBecause::NotBorrowable),
+                        ),
+                    })
+                    .collect();
+
+                (*proc_key, params)
+            })
+            .collect()
+    }
 }
 
 pub(crate) fn infer_borrow_signatures<'a>(
     arena: &'a Bump,
     interner: &impl LayoutInterner<'a>,
     procs: &MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+    inference: &BorrowInference,
 ) -> BorrowSignatures<'a> {
+    let mut owned_because = MutMap::default();
     let mut borrow_signatures: BorrowSignatures = BorrowSignatures {
         procs: procs
             .iter()
             .map(|(_key, proc)| {
                 let key = (proc.name.name(), proc.proc_layout(arena));
-                let signature = BorrowSignature::from_layouts(interner, key.1.arguments.iter());
+                let mut signature = BorrowSignature::from_layouts(interner, key.1.arguments.iter());
+
+                if inference.forces_owned(key.0) {
+                    for index in 0..signature.len() {
+                        if signature.set(index, Ownership::Owned) {
+                            owned_because.insert((key, index), OwnedBecause::Forced);
+                        }
+                    }
+                }
+
                 (key, signature)
             })
             .collect(),
+        owned_because,
     };
 
     // for every proc (by index) a collection of its join points
@@ -158,6 +307,7 @@ pub(crate) fn infer_borrow_signatures<'a>(
                     join_point_stack,
                     join_points: proc_join_points,
                     modified: false,
+                    owned_because: std::vec::Vec::new(),
                 };
 
                 state.inspect_stmt(interner, &mut borrow_signatures, &proc.body);
@@ -181,6 +331,13 @@ pub(crate) fn infer_borrow_signatures<'a>(
                 );
                 modified |= state.modified;
 
+                for (index, because) in state.owned_because {
+                    borrow_signatures
+                        .owned_because
+                        .entry((key, index))
+                        .or_insert(because);
+                }
+
                 proc_join_points = state.join_points;
 
                 std::mem::swap(&mut proc_join_points, &mut join_points[index]);
@@ -206,6 +363,8 @@ struct State<'state, 'arena> {
     join_point_stack: Vec<'arena, (JoinPointId, &'state [Param<'arena>])>,
     join_points: MutMap<JoinPointId, BorrowSignature>,
     modified: bool,
+    /// The arguments (by index) that this pass made owned, and why
+    owned_because: std::vec::Vec<(usize, OwnedBecause)>,
 }
 
 fn layout_to_ownership<'a>(
@@ -226,9 +385,12 @@ impl<'state, 'a> State<'state, 'a> {
     /// Mark the given argument symbol as Owned if the symbol participates in borrow inference
     ///
     /// Currently argument symbols participate if `layout_to_ownership` returns `Borrowed` for their layout.
-    fn mark_owned(&mut self, symbol: Symbol) {
+    fn mark_owned(&mut self, symbol: Symbol, because: OwnedBecause) {
         if let Some(index) = self.args.iter().position(|(_, s)| *s == symbol) {
-            self.modified |= self.borrow_signature.set(index, Ownership::Owned);
+            if self.borrow_signature.set(index, Ownership::Owned) {
+                self.modified = true;
+                self.owned_because.push((index, because));
+            }
         }
 
         // theory: relevant modification to a join point borrow signature is always immediately
@@ -268,7 +430,7 @@ impl<'state, 'a> State<'state, 'a> {
             Stmt::Ret(s) => {
                 // to return a value we must own it
                 // (with the current implementation anyway)
-                self.mark_owned(*s);
+                self.mark_owned(*s, OwnedBecause::Returned);
             }
             Stmt::Refcounting(_, _) => unreachable!("not inserted yet"),
             Stmt::Expect { remainder, .. } => {
@@ -321,7 +483,7 @@ impl<'state, 'a> State<'state, 'a> {
 
                 for (argument, ownership) in arguments.iter().zip(borrow_signature.iter()) {
                     if let Ownership::Owned = ownership {
-                        self.mark_owned(*argument);
+                        self.mark_owned(*argument, OwnedBecause::PassedToJoinPoint);
                     }
                 }
             }
@@ -369,7 +531,7 @@ impl<'state, 'a> State<'state, 'a> {
 
                 for (argument, ownership) in arguments.iter().zip(borrow_signature.iter()) {
                     if let Ownership::Owned = ownership {
-                        self.mark_owned(*argument);
+                        self.mark_owned(*argument, OwnedBecause::PassedToOwned(name.name()));
                     }
                 }
             }
@@ -379,13 +541,13 @@ impl<'state, 'a> State<'state, 'a> {
 
                 for (argument, ownership) in arguments.iter().zip(borrow_signature) {
                     if ownership.is_owned() {
-                        self.mark_owned(*argument);
+                        self.mark_owned(*argument, OwnedBecause::PassedToLowLevel(*op));
                     }
                 }
             }
             CallType::ByPointer { .. } | CallType::Foreign { .. } | CallType::HigherOrder(_) => {
                 for argument in arguments.iter() {
-                    self.mark_owned(*argument)
+                    self.mark_owned(*argument, OwnedBecause::PassedToUnknown)
                 }
             }
         }
@@ -497,3 +659,35 @@ impl<'a> CallInfo<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(value: &str) -> BorrowInference {
+        let symbols = [Symbol::LIST_MAP, Symbol::STR_CONCAT];
+        let qualified_name = |symbol| match symbol {
+            Symbol::LIST_MAP => Some("List.map".to_string()),
+            Symbol::STR_CONCAT => Some("Str.concat".to_string()),
+            _ => None,
+        };
+
+        BorrowInference::parse(value, symbols.into_iter(), qualified_name)
+    }
+
+    #[test]
+    fn parse_borrow_inference() {
+        assert_eq!(parse("owned"), BorrowInference::AllOwned);
+        assert_eq!(parse("infer"), BorrowInference::Infer);
+        assert_eq!(parse("nonsense"), BorrowInference::Infer);
+
+        let owned = parse("owned:List.map, Main.unknown");
+
+        assert_eq!(
+            owned,
+            BorrowInference::Owned(MutSet::from_iter([Symbol::LIST_MAP]))
+        );
+        assert!(owned.forces_owned(Symbol::LIST_MAP));
+        assert!(!owned.forces_owned(Symbol::STR_CONCAT));
+    }
+}
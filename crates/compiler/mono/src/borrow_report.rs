@@ -0,0 +1,207 @@
+//! A report of the ownership that borrow inference decided for the parameters of each
+//! specialized function, and why parameters that could have been borrowed are owned.
+//!
+//! A caller increments the refcount of every value it passes as an owned argument, and a
+//! function decrements the refcount of every borrowed parameter it needs to own, so unexpected
+//! refcount traffic can usually be traced back to one of the owned parameters in this report.
+//! Only lists and strings can be borrowed.
+//!
+//! The report is written as tab-separated lines with one line per parameter:
+//!
+//! ```text
+//! {module}.{ident}
+//! {argument layouts} -> {result layout}
+//! {parameter index}
+//! {parameter name}
+//! {parameter layout}
+//! {owned or borrowed}
+//! {why it's owned, or empty}
+//! ```
+use std::fmt::{self, Write};
+
+use roc_collections::MutMap;
+use roc_module::symbol::{Interns, Symbol};
+
+use crate::borrow::{InferredBorrows, OwnedBecause, ParamOwnership, BORROW_INFERENCE_ENV_VAR};
+use crate::ir::{Proc, ProcLayout};
+use crate::layout::LayoutInterner;
+use crate::report_format::ReportFormat;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BorrowReport {
+    /// Sorted by name, then layout
+    pub procs: Vec<ProcBorrows>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcBorrows {
+    /// The qualified name of the def, e.g. `Main.parse`.
+    pub name: String,
+    /// e.g. `(List I64, I64) -> I64`
+    pub layout: String,
+    pub params: Vec<ParamBorrow>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParamBorrow {
+    pub name: String,
+    pub layout: String,
+    pub ownership: ParamOwnership,
+    /// Why the parameter is owned, e.g. `passed to Str.concat`, or `None` if it's borrowed.
+    pub owned_because: Option<String>,
+}
+
+impl BorrowReport {
+    pub fn new<'a, I>(
+        procedures: &MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+        inferred: &InferredBorrows<'a>,
+        interner: &I,
+        interns: &Interns,
+    ) -> Self
+    where
+        I: LayoutInterner<'a>,
+    {
+        let mut procs: Vec<_> = procedures
+            .iter()
+            .filter(|(_, proc)| !proc.args.is_empty())
+            .filter_map(|(key, proc)| {
+                let ownerships = inferred.get(key)?;
+                let (symbol, proc_layout) = key;
+
+                let arguments: Vec<_> = proc_layout
+                    .arguments
+                    .iter()
+                    .map(|layout| interner.dbg(*layout))
+                    .collect();
+
+                let params = proc
+                    .args
+                    .iter()
+                    .zip(ownerships.iter())
+                    .map(|((layout, param), ownership)| ParamBorrow {
+                        name: param.as_str(interns).to_string(),
+                        layout: interner.dbg(*layout),
+                        ownership: *ownership,
+                        owned_because: match ownership {
+                            ParamOwnership::Borrowed => None,
+                            ParamOwnership::Owned(because) => {
+                                Some(describe_owned_because(*because, interns))
+                            }
+                        },
+                    })
+                    .collect();
+
+                Some(ProcBorrows {
+                    name: qualified_name(*symbol, interns),
+                    layout: format!(
+                        "({}) -> {}",
+                        arguments.join(", "),
+                        interner.dbg(proc_layout.result)
+                    ),
+                    params,
+                })
+            })
+            .collect();
+
+        procs.sort_by(|a, b| (&a.name, &a.layout).cmp(&(&b.name, &b.layout)));
+
+        BorrowReport { procs }
+    }
+
+    /// How many of the parameters that could have been borrowed are, for each reason they aren't.
+    pub fn summary(&self) -> String {
+        let mut borrowed = 0;
+        let mut owned: Vec<(&str, usize)> = Vec::new();
+
+        for param in self.procs.iter().flat_map(|proc| proc.params.iter()) {
+            match param.ownership {
+                ParamOwnership::Borrowed => borrowed += 1,
+                ParamOwnership::Owned(OwnedBecause::NotBorrowable) => {}
+                ParamOwnership::Owned(because) => {
+                    let kind = owned_because_kind(because);
+
+                    match owned.iter_mut().find(|(k, _)| *k == kind) {
+                        Some((_, count)) => *count += 1,
+                        None => owned.push((kind, 1)),
+                    }
+                }
+            }
+        }
+
+        let total = borrowed + owned.iter().map(|(_, count)| count).sum::<usize>();
+        let mut buf = String::new();
+
+        writeln!(
+            buf,
+            "{borrowed} of {total} list and string parameters are borrowed"
+        )
+        .unwrap();
+
+        owned.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+
+        for (kind, count) in owned {
+            writeln!(buf, "{count:>8}  owned because {kind}").unwrap();
+        }
+
+        buf
+    }
+}
+
+impl fmt::Display for BorrowReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", ReportFormat::Borrows.header())?;
+
+        for proc in self.procs.iter() {
+            for (index, param) in proc.params.iter().enumerate() {
+                let ownership = match param.ownership {
+                    ParamOwnership::Borrowed => "borrowed",
+                    ParamOwnership::Owned(_) => "owned",
+                };
+
+                writeln!(
+                    f,
+                    "{}\t{}\t{index}\t{}\t{}\t{ownership}\t{}",
+                    proc.name,
+                    proc.layout,
+                    param.name,
+                    param.layout,
+                    param.owned_because.as_deref().unwrap_or_default()
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn qualified_name(symbol: Symbol, interns: &Interns) -> String {
+    let module_name = interns.module_ids.get_name(symbol.module_id()).unwrap();
+
+    format!("{module_name}.{}", symbol.as_str(interns))
+}
+
+fn owned_because_kind(because: OwnedBecause) -> &'static str {
+    match because {
+        OwnedBecause::NotBorrowable => "it's not a list or string",
+        OwnedBecause::Forced => "it was forced",
+        OwnedBecause::Returned => "it's returned",
+        OwnedBecause::PassedToOwned(_) => "it's passed to a function that owns it",
+        OwnedBecause::PassedToLowLevel(_) => "it's passed to a builtin that owns it",
+        OwnedBecause::PassedToUnknown => "it's passed to an unknown function",
+        OwnedBecause::PassedToJoinPoint => "it's passed to a join point that owns it",
+    }
+}
+
+fn describe_owned_because(because: OwnedBecause, interns: &Interns) -> String {
+    match because {
+        OwnedBecause::NotBorrowable => "not a list or string".to_string(),
+        OwnedBecause::Forced => format!("forced by {BORROW_INFERENCE_ENV_VAR}"),
+        OwnedBecause::Returned => "returned".to_string(),
+        OwnedBecause::PassedToOwned(callee) => {
+            format!("passed to {}", qualified_name(callee, interns))
+        }
+        OwnedBecause::PassedToLowLevel(op) => format!("passed to lowlevel {op:?}"),
+        OwnedBecause::PassedToUnknown => "passed to an unknown function".to_string(),
+        OwnedBecause::PassedToJoinPoint => "passed to a join point".to_string(),
+    }
+}
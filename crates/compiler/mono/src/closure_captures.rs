@@ -0,0 +1,254 @@
+//! What each closure captures, and what that costs at runtime.
+//!
+//! A closure's captures are stored in its closure data, which is passed around with the closure
+//! like any other value: usually on the stack, where a big capture is as expensive to copy as a
+//! big struct. Captures are stored on the heap instead when the closure's type is erased, or when
+//! its lambda set is recursive (e.g. a closure that captures another closure of the same type),
+//! which costs an allocation every time the closure is created.
+//!
+//! The lint reports the closures where either of these is likely to matter, so that hot lambdas
+//! can be restructured, e.g. to capture a single record field rather than the whole record.
+use std::fmt::Write;
+use std::path::PathBuf;
+
+use roc_can::traverse::FoundClosure;
+use roc_collections::{MutMap, MutSet};
+use roc_module::symbol::{Interns, ModuleId, Symbol};
+use roc_region::all::LineInfo;
+
+use crate::ir::{Expr, Proc, ProcLayout, Stmt};
+use crate::layout::{InLayout, LayoutInterner, LayoutRepr, UnionLayout};
+use crate::report_format::sort_by_symbol_name;
+
+/// Closures whose closure data is at least this big are reported even if it's on the stack.
+pub const LARGE_CAPTURES_BYTES: u32 = 64;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClosureCaptures {
+    /// The symbol the closure was lifted to
+    pub closure: Symbol,
+    pub captures: Vec<Capture>,
+    /// The size of the closure data, which for a lambda set with several lambdas is the size of
+    /// its biggest captures.
+    pub size: u32,
+    pub heap_allocated: Option<HeapAllocated>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Capture {
+    /// None if the closure wasn't found in the source (e.g. because it was derived)
+    pub symbol: Option<Symbol>,
+    /// The layout, rendered by the layout interner
+    pub layout: String,
+    pub size: u32,
+}
+
+/// Why the captures of a closure are stored on the heap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeapAllocated {
+    /// The closure is called through a function whose type is erased.
+    Erased,
+    /// The closure's lambda set refers to itself, so its closure data is a recursive union.
+    RecursiveLambdaSet,
+}
+
+impl ClosureCaptures {
+    /// Whether the lint should report this closure.
+    pub fn is_expensive(&self) -> bool {
+        self.heap_allocated.is_some() || self.size >= LARGE_CAPTURES_BYTES
+    }
+}
+
+/// Finds the captures of every specialization of a closure, sorted by the closure's name.
+/// Specializations that capture the same layouts are only reported once.
+pub fn closure_captures<'a, I>(
+    procedures: &MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+    interner: &I,
+    closures: &MutMap<Symbol, FoundClosure>,
+    interns: &Interns,
+) -> Vec<ClosureCaptures>
+where
+    I: LayoutInterner<'a>,
+{
+    let mut seen = MutSet::default();
+    let mut found = Vec::new();
+
+    for proc in procedures.values() {
+        let (capture_layouts, size, heap_allocated) = if proc.is_erased {
+            match erased_capture_layouts(&proc.body) {
+                Some(layouts) => {
+                    let size = layouts.iter().map(|l| interner.stack_size(*l)).sum();
+
+                    (layouts, size, Some(HeapAllocated::Erased))
+                }
+                None => continue,
+            }
+        } else {
+            let Some(closure_data_layout) = proc.closure_data_layout else {
+                continue;
+            };
+            let LayoutRepr::LambdaSet(lambda_set) = interner.get_repr(closure_data_layout) else {
+                continue;
+            };
+
+            let representation = lambda_set.runtime_representation();
+            let is_recursive = matches!(
+                interner.get_repr(representation),
+                LayoutRepr::Union(union_layout)
+                    if !matches!(union_layout, UnionLayout::NonRecursive(_))
+            );
+
+            (
+                proc.name.captures(),
+                interner.stack_size(representation),
+                is_recursive.then_some(HeapAllocated::RecursiveLambdaSet),
+            )
+        };
+
+        if capture_layouts.is_empty() || !seen.insert((proc.name.name(), capture_layouts)) {
+            continue;
+        }
+
+        let captured_symbols = closures
+            .get(&proc.name.name())
+            .map(|closure| closure.captured_symbols.as_slice())
+            .unwrap_or_default();
+        let captures = capture_layouts
+            .iter()
+            .enumerate()
+            .map(|(i, layout)| Capture {
+                symbol: captured_symbols.get(i).copied(),
+                layout: interner.dbg(*layout),
+                size: interner.stack_size(*layout),
+            })
+            .collect();
+
+        found.push(ClosureCaptures {
+            closure: proc.name.name(),
+            captures,
+            size,
+            heap_allocated,
+        });
+    }
+
+    sort_by_symbol_name(&mut found, interns, |closure| {
+        let captures: Vec<_> = closure
+            .captures
+            .iter()
+            .map(|capture| {
+                let name = capture.symbol.map(|symbol| symbol.as_str(interns));
+
+                (name, capture.layout.clone(), capture.size)
+            })
+            .collect();
+
+        (closure.closure, captures)
+    });
+
+    found
+}
+
+/// An erased closure starts by loading its captures from the heap, and then reading each of them
+/// from the loaded struct.
+fn erased_capture_layouts<'a>(body: &Stmt<'a>) -> Option<&'a [InLayout<'a>]> {
+    let mut stmt = body;
+
+    while let Stmt::Let(_, expr, _, cont) = stmt {
+        if let Expr::StructAtIndex { field_layouts, .. } = expr {
+            return Some(field_layouts);
+        }
+
+        stmt = cont;
+    }
+
+    None
+}
+
+/// Renders a warning for each closure in `captures` that is expensive, in the order given.
+pub fn capture_warnings(
+    captures: &[ClosureCaptures],
+    closures: &MutMap<Symbol, FoundClosure>,
+    sources: &MutMap<ModuleId, (PathBuf, Box<str>)>,
+    interns: &Interns,
+) -> Vec<String> {
+    let mut line_infos: MutMap<ModuleId, LineInfo> = MutMap::default();
+    let mut warnings = Vec::new();
+
+    for closure in captures.iter().filter(|closure| closure.is_expensive()) {
+        let module_id = closure.closure.module_id();
+        let location = match (closures.get(&closure.closure), sources.get(&module_id)) {
+            (Some(found), Some((path, src))) => {
+                let position = line_infos
+                    .entry(module_id)
+                    .or_insert_with(|| LineInfo::new(src))
+                    .convert_pos(found.region.start());
+
+                format!(
+                    "{}:{}:{}",
+                    path.display(),
+                    position.line + 1,
+                    position.column + 1
+                )
+            }
+            _ => {
+                let module_name = interns.module_ids.get_name(module_id).unwrap();
+
+                format!("{module_name}.{}", closure.closure.as_str(interns))
+            }
+        };
+
+        let mut buf = String::new();
+
+        writeln!(buf, "── EXPENSIVE CLOSURE ─ {location}\n").unwrap();
+
+        let count = closure.captures.len();
+        let values = if count == 1 { "value" } else { "values" };
+
+        match closure.heap_allocated {
+            Some(HeapAllocated::Erased) => writeln!(
+                buf,
+                "This closure captures {count} {values}, which are copied to a new heap allocation \
+                 of {} bytes every time it's created, because it's used where its type is erased.",
+                closure.size
+            ),
+            Some(HeapAllocated::RecursiveLambdaSet) => writeln!(
+                buf,
+                "This closure captures {count} {values}, which are copied to a new heap allocation \
+                 every time it's created, because it can capture other closures of its own type."
+            ),
+            None => writeln!(
+                buf,
+                "This closure captures {count} {values}, which take up {} bytes that get copied \
+                 every time the closure is passed around.",
+                closure.size
+            ),
+        }
+        .unwrap();
+
+        writeln!(buf).unwrap();
+
+        for capture in closure.captures.iter() {
+            let name = capture
+                .symbol
+                .map_or("<unknown>", |symbol| symbol.as_str(interns));
+
+            writeln!(
+                buf,
+                "    {name} : {} ({} bytes)",
+                capture.layout, capture.size
+            )
+            .unwrap();
+        }
+
+        writeln!(
+            buf,
+            "\nIf this closure is on a hot path, consider capturing less, e.g. a single field \
+             instead of a whole record."
+        )
+        .unwrap();
+
+        warnings.push(buf);
+    }
+
+    warnings
+}
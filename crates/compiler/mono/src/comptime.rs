@@ -0,0 +1,1454 @@
+//! Evaluates the top-level defs marked `# @comptime` while compiling, and replaces the body of
+//! their procs with the value they evaluated to. A lookup table then becomes a list literal, which
+//! the backends emit as static data, so building it doesn't cost any time when the program starts.
+//!
+//! This is an interpreter for the mono IR, run right after specialization (before reference
+//! counting is inserted). It supports the builtin operations that constants are usually built
+//! with: arithmetic, comparisons, and most string and list operations. A def that needs anything
+//! else, crashes, or runs out of fuel (see [ComptimeLimits]) is reported, see [comptime_problems].
+//!
+//! This doesn't reuse the REPL's evaluator, which compiles an expression with a backend and runs
+//! the machine code it generated: defs are evaluated during specialization, before any backend
+//! runs, and the program may be built for a target the compiler can't run code for (e.g. wasm,
+//! or another architecture). So the builtins are implemented a second time here, and the
+//! `gen_comptime` tests in `test_gen` check every one of them against the LLVM backend.
+//!
+//! A top-level `expect` marked `# @comptime` is evaluated the same way, and fails the build when
+//! its condition is false. Its condition can use other top-level defs, so it can check that a
+//! configuration is consistent, e.g. that a list of ports isn't empty.
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+use bumpalo::Bump;
+use roc_builtins::bitcode::{FloatWidth, IntWidth};
+use roc_builtins::unicode;
+use roc_collections::MutMap;
+use roc_error_macros::internal_error;
+use roc_module::low_level::{LowLevel, LowLevelWrapperType};
+use roc_module::symbol::{IdentIds, Interns, ModuleId, Symbol};
+use roc_problem::mono::Problem;
+use roc_region::all::Region;
+use roc_std::RocDec;
+
+use crate::ir::{
+    CallType, Expr, JoinPointId, ListLiteralElement, Literal, Param, Proc, ProcLayout, Stmt,
+};
+use crate::layout::{
+    Builtin, InLayout, LayoutInterner, LayoutRepr, STLayoutInterner, TagIdIntType, UnionLayout,
+};
+use crate::report_format::sort_by_symbol_name;
+
+pub use roc_problem::mono::{ComptimeFailure, ComptimeProblem};
+
+/// The number of statements an evaluation may run by default, see [ComptimeLimits::fuel].
+pub const DEFAULT_FUEL: usize = 10_000_000;
+
+/// The number of nested calls an evaluation may make by default, see [ComptimeLimits::max_depth].
+pub const DEFAULT_MAX_DEPTH: usize = 512;
+
+/// How much work evaluating a single def or expect may do before it's given up on, so that a
+/// huge (or infinite) computation can't hang the compiler.
+///
+/// The limits are counted in steps of the interpreter rather than in time, so whether a def can
+/// be evaluated doesn't depend on how fast or busy the machine compiling it is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ComptimeLimits {
+    /// The number of statements an evaluation may run, set with `--comptime-fuel`
+    pub fuel: usize,
+    /// The number of nested calls an evaluation may make
+    pub max_depth: usize,
+}
+
+impl Default for ComptimeLimits {
+    fn default() -> Self {
+        Self {
+            fuel: DEFAULT_FUEL,
+            max_depth: DEFAULT_MAX_DEPTH,
+        }
+    }
+}
+
+/// One `Dec` is stored as this many units, see [RocDec].
+const DEC_ONE: i128 = 1_000_000_000_000_000_000;
+
+/// Replaces the body of each (specialization of a) def in `comptime_defs` with its value. The
+/// defs that can't be evaluated keep their body, and are returned.
+pub fn evaluate_comptime_defs<'a>(
+    arena: &'a Bump,
+    interner: &STLayoutInterner<'a>,
+    home: ModuleId,
+    ident_ids: &mut IdentIds,
+    procedures: &mut MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+    comptime_defs: &MutMap<Symbol, Region>,
+    limits: ComptimeLimits,
+) -> Vec<ComptimeFailure> {
+    let mut failures = Vec::new();
+    let mut values = Vec::new();
+
+    for (key, proc) in procedures.iter() {
+        let Some(region) = comptime_defs.get(&key.0) else {
+            continue;
+        };
+
+        let result = if proc.args.is_empty() {
+            let mut interpreter = Interpreter {
+                procedures,
+                interner,
+                limits,
+                steps: 0,
+                depth: 0,
+            };
+
+            interpreter.call_proc(proc, Vec::new())
+        } else {
+            Err(ComptimeProblem::IsFunction)
+        };
+
+        let result = result.and_then(|value| {
+            let mut builder = Builder {
+                arena,
+                interner,
+                home,
+                ident_ids: &mut *ident_ids,
+                lets: Vec::new(),
+            };
+
+            let symbol = builder.build(&value, proc.ret_layout)?;
+
+            Ok(builder.into_stmt(symbol))
+        });
+
+        match result {
+            Ok(body) => values.push((*key, body)),
+            Err(problem) => failures.push(ComptimeFailure {
+                symbol: key.0,
+                region: *region,
+                problem,
+                is_expect: false,
+            }),
+        }
+    }
+
+    for (key, body) in values {
+        procedures.get_mut(&key).unwrap().body = body;
+    }
+
+    failures
+}
+
+/// Evaluates the condition of each expect in `comptime_expects`, and removes its procs, since
+/// there's nothing left to check when the program runs. Returns the expects that failed or
+/// couldn't be evaluated.
+pub fn evaluate_comptime_expects<'a>(
+    interner: &STLayoutInterner<'a>,
+    procedures: &mut MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+    comptime_expects: &MutMap<Symbol, Region>,
+    limits: ComptimeLimits,
+) -> Vec<ComptimeFailure> {
+    let mut failures = Vec::new();
+    let mut evaluated = Vec::new();
+
+    for (key, proc) in procedures.iter() {
+        let Some(region) = comptime_expects.get(&key.0) else {
+            continue;
+        };
+
+        let mut interpreter = Interpreter {
+            procedures,
+            interner,
+            limits,
+            steps: 0,
+            depth: 0,
+        };
+
+        let problem = match interpreter.call_proc(proc, Vec::new()) {
+            Ok(value) if value.as_bool() => None,
+            Ok(_) => Some(ComptimeProblem::ExpectFailed),
+            Err(problem) => Some(problem),
+        };
+
+        if let Some(problem) = problem {
+            failures.push(ComptimeFailure {
+                symbol: key.0,
+                region: *region,
+                problem,
+                is_expect: true,
+            });
+        }
+
+        evaluated.push(*key);
+    }
+
+    for key in evaluated {
+        procedures.remove(&key);
+    }
+
+    failures
+}
+
+/// A value computed by the interpreter. Unlike at runtime, values are never mutated in place.
+#[derive(Clone, Debug, PartialEq)]
+enum Value {
+    /// A `U128` is stored as the `i128` with the same bits
+    Int(i128, IntWidth),
+    Float(f64, FloatWidth),
+    /// In units of `1 / DEC_ONE`, like [RocDec]
+    Dec(i128),
+    Bool(bool),
+    Str(Rc<Vec<u8>>),
+    List(Rc<Vec<Value>>),
+    Struct(Rc<Vec<Value>>),
+    Tag(TagIdIntType, Rc<Vec<Value>>),
+}
+
+impl Value {
+    fn str(string: impl Into<Vec<u8>>) -> Self {
+        Value::Str(Rc::new(string.into()))
+    }
+
+    fn list(elements: Vec<Value>) -> Self {
+        Value::List(Rc::new(elements))
+    }
+
+    fn pair(value: Value, flag: bool) -> Self {
+        Value::Struct(Rc::new(vec![value, Value::Bool(flag)]))
+    }
+
+    fn as_int(&self) -> (i128, IntWidth) {
+        match self {
+            Value::Int(value, width) => (*value, *width),
+            other => internal_error!("expected an integer, found {other:?}"),
+        }
+    }
+
+    fn as_usize(&self) -> usize {
+        let (value, _) = self.as_int();
+
+        usize::try_from(value).unwrap_or(usize::MAX)
+    }
+
+    fn as_bool(&self) -> bool {
+        match self {
+            Value::Bool(value) => *value,
+            other => internal_error!("expected a Bool, found {other:?}"),
+        }
+    }
+
+    fn as_str(&self) -> &Rc<Vec<u8>> {
+        match self {
+            Value::Str(bytes) => bytes,
+            other => internal_error!("expected a Str, found {other:?}"),
+        }
+    }
+
+    fn as_list(&self) -> &Rc<Vec<Value>> {
+        match self {
+            Value::List(elements) => elements,
+            other => internal_error!("expected a List, found {other:?}"),
+        }
+    }
+
+    fn fields(&self) -> &[Value] {
+        match self {
+            Value::Struct(fields) | Value::Tag(_, fields) => fields,
+            other => internal_error!("expected a struct or tag, found {other:?}"),
+        }
+    }
+
+    /// The value a `Switch` compares its branches to
+    fn switch_value(&self) -> u64 {
+        match self {
+            Value::Bool(value) => *value as u64,
+            Value::Int(value, _) => *value as u64,
+            other => internal_error!("cannot switch on {other:?}"),
+        }
+    }
+}
+
+struct Frame<'p, 'a> {
+    values: MutMap<Symbol, Value>,
+    joins: MutMap<JoinPointId, (&'a [Param<'a>], &'p Stmt<'a>)>,
+}
+
+impl Frame<'_, '_> {
+    fn get(&self, symbol: Symbol) -> &Value {
+        match self.values.get(&symbol) {
+            Some(value) => value,
+            None => internal_error!("{symbol:?} is used before it's defined"),
+        }
+    }
+
+    fn get_all(&self, symbols: &[Symbol]) -> Vec<Value> {
+        symbols
+            .iter()
+            .map(|symbol| self.get(*symbol).clone())
+            .collect()
+    }
+}
+
+struct Interpreter<'p, 'a> {
+    procedures: &'p MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+    interner: &'p STLayoutInterner<'a>,
+    limits: ComptimeLimits,
+    steps: usize,
+    depth: usize,
+}
+
+type Eval<T = Value> = Result<T, ComptimeProblem>;
+
+impl<'p, 'a> Interpreter<'p, 'a> {
+    fn call_proc(&mut self, proc: &'p Proc<'a>, arguments: Vec<Value>) -> Eval {
+        if self.depth == self.limits.max_depth {
+            return Err(ComptimeProblem::TooDeep {
+                max_depth: self.limits.max_depth,
+            });
+        }
+
+        let mut frame = Frame {
+            values: proc
+                .args
+                .iter()
+                .map(|(_, symbol)| *symbol)
+                .zip(arguments)
+                .collect(),
+            joins: MutMap::default(),
+        };
+
+        self.depth += 1;
+        let result = self.eval_stmt(&mut frame, &proc.body);
+        self.depth -= 1;
+
+        result
+    }
+
+    fn eval_stmt(&mut self, frame: &mut Frame<'p, 'a>, mut stmt: &'p Stmt<'a>) -> Eval {
+        loop {
+            self.steps += 1;
+
+            if self.steps > self.limits.fuel {
+                return Err(ComptimeProblem::OutOfFuel {
+                    fuel: self.limits.fuel,
+                });
+            }
+
+            match stmt {
+                Stmt::Let(symbol, expr, layout, rest) => {
+                    let value = self.eval_expr(frame, expr, *layout)?;
+
+                    frame.values.insert(*symbol, value);
+                    stmt = rest;
+                }
+                Stmt::Switch {
+                    cond_symbol,
+                    branches,
+                    default_branch,
+                    ..
+                } => {
+                    let cond = frame.get(*cond_symbol).switch_value();
+
+                    stmt = match branches.iter().find(|(value, _, _)| *value == cond) {
+                        Some((_, _, branch)) => branch,
+                        None => default_branch.1,
+                    };
+                }
+                Stmt::Ret(symbol) => return Ok(frame.get(*symbol).clone()),
+                Stmt::Refcounting(_, rest) => stmt = rest,
+                Stmt::Expect { remainder, .. } | Stmt::Dbg { remainder, .. } => stmt = remainder,
+                Stmt::Join {
+                    id,
+                    parameters,
+                    body,
+                    remainder,
+                } => {
+                    frame.joins.insert(*id, (*parameters, *body));
+                    stmt = remainder;
+                }
+                Stmt::Jump(id, arguments) => {
+                    let (parameters, body) = frame.joins[id];
+                    let arguments = frame.get_all(arguments);
+
+                    for (parameter, argument) in parameters.iter().zip(arguments) {
+                        frame.values.insert(parameter.symbol, argument);
+                    }
+
+                    stmt = body;
+                }
+                Stmt::Crash(symbol, _) => {
+                    let message = String::from_utf8_lossy(frame.get(*symbol).as_str());
+
+                    return Err(ComptimeProblem::Crashed(message.into_owned()));
+                }
+            }
+        }
+    }
+
+    fn eval_expr(&mut self, frame: &Frame<'p, 'a>, expr: &Expr<'a>, layout: InLayout<'a>) -> Eval {
+        match expr {
+            Expr::Literal(literal) => Ok(self.literal(literal, layout)),
+            Expr::Call(call) => {
+                let arguments = frame.get_all(call.arguments);
+
+                match &call.call_type {
+                    CallType::ByName {
+                        name,
+                        ret_layout,
+                        arg_layouts,
+                        ..
+                    } => {
+                        let proc_layout = ProcLayout {
+                            arguments: arg_layouts,
+                            result: *ret_layout,
+                            niche: name.niche(),
+                        };
+
+                        match self.procedures.get(&(name.name(), proc_layout)) {
+                            Some(proc) => self.call_proc(proc, arguments),
+                            None => match LowLevelWrapperType::from_symbol(name.name()) {
+                                LowLevelWrapperType::CanBeReplacedBy(op) => {
+                                    self.lowlevel(op, &arguments, layout)
+                                }
+                                LowLevelWrapperType::NotALowLevelWrapper => {
+                                    Err(ComptimeProblem::UnsupportedCall(name.name()))
+                                }
+                            },
+                        }
+                    }
+                    CallType::LowLevel { op, .. } => self.lowlevel(*op, &arguments, layout),
+                    CallType::HigherOrder(higher_order) => Err(ComptimeProblem::Unsupported(
+                        format!("the builtin operation {:?}", higher_order.op),
+                    )),
+                    CallType::ByPointer { .. } => Err(ComptimeProblem::Unsupported(
+                        "a call through a function pointer".to_string(),
+                    )),
+                    CallType::Foreign { foreign_symbol, .. } => Err(ComptimeProblem::Unsupported(
+                        format!("the host function `{}`", foreign_symbol.as_str()),
+                    )),
+                }
+            }
+            Expr::Tag {
+                tag_id, arguments, ..
+            } => Ok(Value::Tag(*tag_id, Rc::new(frame.get_all(arguments)))),
+            Expr::Struct(fields) => Ok(Value::Struct(Rc::new(frame.get_all(fields)))),
+            Expr::StructAtIndex {
+                index, structure, ..
+            }
+            | Expr::UnionAtIndex {
+                index, structure, ..
+            } => Ok(frame.get(*structure).fields()[*index as usize].clone()),
+            Expr::GetTagId { structure, .. } => match frame.get(*structure) {
+                Value::Tag(tag_id, _) => Ok(self.int(*tag_id as i128, layout)),
+                other => internal_error!("expected a tag, found {other:?}"),
+            },
+            Expr::Array { elem_layout, elems } => Ok(Value::list(
+                elems
+                    .iter()
+                    .map(|elem| match elem {
+                        ListLiteralElement::Literal(literal) => self.literal(literal, *elem_layout),
+                        ListLiteralElement::Symbol(symbol) => frame.get(*symbol).clone(),
+                    })
+                    .collect(),
+            )),
+            Expr::EmptyArray => Ok(Value::list(Vec::new())),
+            Expr::FunctionPointer { .. } => Err(ComptimeProblem::Unsupported(
+                "a function pointer".to_string(),
+            )),
+            Expr::ErasedMake { .. } | Expr::ErasedLoad { .. } => Err(ComptimeProblem::Unsupported(
+                "a type-erased function".to_string(),
+            )),
+            Expr::NullPointer
+            | Expr::GetElementPointer { .. }
+            | Expr::Alloca { .. }
+            | Expr::Reset { .. }
+            | Expr::ResetRef { .. } => Err(ComptimeProblem::Unsupported("a pointer".to_string())),
+        }
+    }
+
+    fn literal(&self, literal: &Literal, layout: InLayout<'a>) -> Value {
+        match literal {
+            Literal::Int(bytes) => self.int(i128::from_ne_bytes(*bytes), layout),
+            Literal::U128(bytes) => Value::Int(i128::from_ne_bytes(*bytes), IntWidth::U128),
+            Literal::Float(float) => match self.interner.get_repr(layout) {
+                LayoutRepr::Builtin(Builtin::Float(width)) => Value::Float(*float, width),
+                _ => Value::Float(*float, FloatWidth::F64),
+            },
+            Literal::Decimal(bytes) => Value::Dec(i128::from_ne_bytes(*bytes)),
+            Literal::Str(string) => Value::str(string.as_bytes()),
+            Literal::Bool(value) => Value::Bool(*value),
+            Literal::Byte(byte) => Value::Int(*byte as i128, IntWidth::U8),
+        }
+    }
+
+    /// An integer of the given layout, which is wrapped if it doesn't fit
+    fn int(&self, value: i128, layout: InLayout<'a>) -> Value {
+        let width = self.int_width(layout);
+
+        Value::Int(wrap(width, value), width)
+    }
+
+    fn int_width(&self, layout: InLayout<'a>) -> IntWidth {
+        match self.interner.get_repr(layout) {
+            LayoutRepr::Builtin(Builtin::Int(width)) => width,
+            other => internal_error!("expected an integer layout, found {other:?}"),
+        }
+    }
+
+    fn lowlevel(&mut self, op: LowLevel, args: &[Value], layout: InLayout<'a>) -> Eval {
+        use LowLevel::*;
+
+        let value = match op {
+            StrConcat => {
+                let mut bytes = args[0].as_str().to_vec();
+                bytes.extend_from_slice(args[1].as_str());
+
+                Value::str(bytes)
+            }
+            StrJoinWith => {
+                let strings: Vec<&[u8]> = args[0]
+                    .as_list()
+                    .iter()
+                    .map(|string| string.as_str().as_slice())
+                    .collect();
+
+                Value::str(strings.join(args[1].as_str().as_slice()))
+            }
+            StrIsEmpty => Value::Bool(args[0].as_str().is_empty()),
+            StrStartsWith => Value::Bool(args[0].as_str().starts_with(args[1].as_str())),
+            StrEndsWith => Value::Bool(args[0].as_str().ends_with(args[1].as_str())),
+            StrCountUtf8Bytes => self.int(args[0].as_str().len() as i128, layout),
+            StrToUtf8 => Value::list(
+                args[0]
+                    .as_str()
+                    .iter()
+                    .map(|byte| Value::Int(*byte as i128, IntWidth::U8))
+                    .collect(),
+            ),
+            StrRepeat => Value::str(args[0].as_str().repeat(args[1].as_usize())),
+            StrSplitOn => {
+                let string = utf8(&args[0]);
+                let separator = utf8(&args[1]);
+
+                if separator.is_empty() {
+                    Value::list(vec![args[0].clone()])
+                } else {
+                    Value::list(string.split(separator).map(Value::str).collect())
+                }
+            }
+            StrTrim => Value::str(utf8(&args[0]).trim()),
+            StrTrimStart => Value::str(utf8(&args[0]).trim_start()),
+            StrTrimEnd => Value::str(utf8(&args[0]).trim_end()),
+            StrWithAsciiLowercased => Value::str(args[0].as_str().to_ascii_lowercase()),
+            StrWithAsciiUppercased => Value::str(args[0].as_str().to_ascii_uppercase()),
+            StrCaselessAsciiEquals => {
+                Value::Bool(args[0].as_str().eq_ignore_ascii_case(args[1].as_str()))
+            }
+            StrWithUppercased => Value::str(
+                utf8(&args[0])
+                    .chars()
+                    .map(unicode::to_upper)
+                    .collect::<String>(),
+            ),
+            StrWithLowercased => Value::str(
+                utf8(&args[0])
+                    .chars()
+                    .map(unicode::to_lower)
+                    .collect::<String>(),
+            ),
+            StrCaselessEquals => Value::Bool(
+                utf8(&args[0])
+                    .chars()
+                    .map(unicode::fold)
+                    .eq(utf8(&args[1]).chars().map(unicode::fold)),
+            ),
+            StrGetUnsafe => Value::Int(args[0].as_str()[args[1].as_usize()] as i128, IntWidth::U8),
+            StrSubstringUnsafe => {
+                let bytes = args[0].as_str();
+                let start = args[1].as_usize().min(bytes.len());
+                let end = start.saturating_add(args[2].as_usize()).min(bytes.len());
+
+                Value::str(&bytes[start..end])
+            }
+            StrWithCapacity => Value::str(Vec::new()),
+            StrReserve | StrReleaseExcessCapacity => args[0].clone(),
+            StrFromInt | NumToStr => match &args[0] {
+                Value::Int(value, IntWidth::U128) => Value::str((*value as u128).to_string()),
+                Value::Int(value, _) => Value::str(value.to_string()),
+                Value::Dec(value) => {
+                    Value::str(RocDec::from_ne_bytes(value.to_ne_bytes()).to_string())
+                }
+                _ => return Err(unsupported(op)),
+            },
+
+            ListLenU64 | ListLenUsize | ListGetCapacity => {
+                self.int(args[0].as_list().len() as i128, layout)
+            }
+            ListWithCapacity => Value::list(Vec::new()),
+            ListReserve | ListReleaseExcessCapacity | ListClone => args[0].clone(),
+            ListIsUnique => Value::Bool(true),
+            ListAppendUnsafe => {
+                let mut list = args[0].as_list().clone();
+                Rc::make_mut(&mut list).push(args[1].clone());
+
+                Value::List(list)
+            }
+            ListPrepend => {
+                let mut list = args[0].as_list().clone();
+                Rc::make_mut(&mut list).insert(0, args[1].clone());
+
+                Value::List(list)
+            }
+            ListGetUnsafe => match args[0].as_list().get(args[1].as_usize()) {
+                Some(element) => element.clone(),
+                None => return Err(ComptimeProblem::Crashed("List index out of bounds".into())),
+            },
+            ListReplaceUnsafe => {
+                let mut list = args[0].as_list().clone();
+                let index = args[1].as_usize();
+
+                let old = match Rc::make_mut(&mut list).get_mut(index) {
+                    Some(element) => std::mem::replace(element, args[2].clone()),
+                    None => args[2].clone(),
+                };
+
+                // Same as in the backends: the element comes first if it's aligned more than the
+                // list is, see `list_replace_unsafe` in gen_llvm
+                let element_first = match self.interner.get_repr(layout) {
+                    LayoutRepr::Struct([first, _]) => {
+                        self.interner.alignment_bytes(*first)
+                            > self.interner.target().ptr_width() as u32
+                    }
+                    other => internal_error!("unexpected layout for ListReplaceUnsafe: {other:?}"),
+                };
+
+                let fields = if element_first {
+                    vec![old, Value::List(list)]
+                } else {
+                    vec![Value::List(list), old]
+                };
+
+                Value::Struct(Rc::new(fields))
+            }
+            ListConcat => {
+                let mut list = args[0].as_list().clone();
+                Rc::make_mut(&mut list).extend(args[1].as_list().iter().cloned());
+
+                Value::List(list)
+            }
+            ListSublist => {
+                let list = args[0].as_list();
+                let start = args[1].as_usize().min(list.len());
+                let end = start.saturating_add(args[2].as_usize()).min(list.len());
+
+                Value::list(list[start..end].to_vec())
+            }
+            ListDropAt => {
+                let mut list = args[0].as_list().clone();
+                let index = args[1].as_usize();
+
+                if index < list.len() {
+                    Rc::make_mut(&mut list).remove(index);
+                }
+
+                Value::List(list)
+            }
+            ListSwap => {
+                let mut list = args[0].as_list().clone();
+                let (i, j) = (args[1].as_usize(), args[2].as_usize());
+
+                if i < list.len() && j < list.len() {
+                    Rc::make_mut(&mut list).swap(i, j);
+                }
+
+                Value::List(list)
+            }
+            ListConcatUtf8 => {
+                let mut list = args[0].as_list().clone();
+                Rc::make_mut(&mut list).extend(
+                    args[1]
+                        .as_str()
+                        .iter()
+                        .map(|byte| Value::Int(*byte as i128, IntWidth::U8)),
+                );
+
+                Value::List(list)
+            }
+
+            NumAdd | NumAddWrap | NumAddChecked | NumAddSaturated => {
+                arithmetic(Arith::Add, overflow_mode(op), &args[0], &args[1])?
+            }
+            NumSub | NumSubWrap | NumSubChecked | NumSubSaturated => {
+                arithmetic(Arith::Sub, overflow_mode(op), &args[0], &args[1])?
+            }
+            NumMul | NumMulWrap | NumMulChecked | NumMulSaturated => {
+                arithmetic(Arith::Mul, overflow_mode(op), &args[0], &args[1])?
+            }
+            NumGt | NumGte | NumLt | NumLte => {
+                let ordering = compare(&args[0], &args[1]);
+
+                Value::Bool(match op {
+                    NumGt => ordering == Some(Ordering::Greater),
+                    NumGte => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+                    NumLt => ordering == Some(Ordering::Less),
+                    _ => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+                })
+            }
+            NumCompare => match compare(&args[0], &args[1]) {
+                Some(Ordering::Equal) => Value::Int(0, IntWidth::U8),
+                Some(Ordering::Greater) => Value::Int(1, IntWidth::U8),
+                Some(Ordering::Less) => Value::Int(2, IntWidth::U8),
+                None => return Err(unsupported(op)),
+            },
+            NumDivTruncUnchecked | NumDivCeilUnchecked | NumRemUnchecked => {
+                let (a, width) = args[0].as_int();
+                let (b, _) = args[1].as_int();
+
+                if b == 0 {
+                    return Err(ComptimeProblem::Crashed("Integer division by 0!".into()));
+                }
+
+                let result = if width == IntWidth::U128 {
+                    let (a, b) = (a as u128, b as u128);
+
+                    let result = match op {
+                        NumDivTruncUnchecked => a / b,
+                        NumDivCeilUnchecked => a.div_ceil(b),
+                        _ => a % b,
+                    };
+
+                    result as i128
+                } else {
+                    match op {
+                        NumDivTruncUnchecked | NumDivCeilUnchecked => {
+                            let Some(quotient) = a.checked_div(b) else {
+                                return Err(ComptimeProblem::Crashed(
+                                    "Integer division overflowed!".into(),
+                                ));
+                            };
+
+                            let rounds_up =
+                                op == NumDivCeilUnchecked && a % b != 0 && (a < 0) == (b < 0);
+
+                            quotient + rounds_up as i128
+                        }
+                        _ => a.checked_rem(b).unwrap_or(0),
+                    }
+                };
+
+                Value::Int(wrap(width, result), width)
+            }
+            NumIsMultipleOf => {
+                let (a, width) = args[0].as_int();
+                let (b, _) = args[1].as_int();
+
+                Value::Bool(match (b, width) {
+                    (0, _) => a == 0,
+                    (_, IntWidth::U128) => (a as u128) % (b as u128) == 0,
+                    _ => a.wrapping_rem(b) == 0,
+                })
+            }
+            NumAbs | NumNeg => match args[0] {
+                Value::Int(a, width) => {
+                    let result = if op == NumAbs && (!width.is_signed() || a >= 0) {
+                        Some(a)
+                    } else {
+                        checked_int(Arith::Sub, width, 0, a)
+                    };
+
+                    match result {
+                        Some(result) => Value::Int(result, width),
+                        None if op == NumAbs => return Err(ComptimeProblem::Crashed(
+                            "Integer absolute overflowed because its argument is the minimum value"
+                                .into(),
+                        )),
+                        None => return Err(ComptimeProblem::Crashed(
+                            "Integer negation overflowed because its argument is the minimum value"
+                                .into(),
+                        )),
+                    }
+                }
+                Value::Float(a, width) if op == NumAbs => Value::Float(a.abs(), width),
+                Value::Float(a, width) => Value::Float(-a, width),
+                Value::Dec(a) if op == NumAbs => Value::Dec(a.abs()),
+                Value::Dec(a) => Value::Dec(-a),
+                _ => return Err(unsupported(op)),
+            },
+            NumDivFrac => match (&args[0], &args[1]) {
+                (Value::Float(a, width), Value::Float(b, _)) => float(a / b, *width),
+                (Value::Dec(a), Value::Dec(b)) => {
+                    if *b == 0 {
+                        return Err(ComptimeProblem::Crashed("Decimal division by 0!".into()));
+                    }
+
+                    match dec_div(*a, *b) {
+                        Some(result) => Value::Dec(result),
+                        None => {
+                            return Err(ComptimeProblem::Crashed(
+                                "Decimal division overflowed!".into(),
+                            ))
+                        }
+                    }
+                }
+                _ => return Err(unsupported(op)),
+            },
+            NumToFrac | NumToFloatCast => {
+                let target = self.interner.get_repr(layout);
+
+                match (&args[0], target) {
+                    (Value::Int(a, width), LayoutRepr::Builtin(Builtin::Float(target))) => {
+                        float(int_to_f64(*a, *width), target)
+                    }
+                    (Value::Float(a, _), LayoutRepr::Builtin(Builtin::Float(target))) => {
+                        float(*a, target)
+                    }
+                    (Value::Int(a, width), LayoutRepr::Builtin(Builtin::Decimal)) => {
+                        let scaled = if *width == IntWidth::U128 {
+                            i128::try_from(*a as u128)
+                                .ok()
+                                .and_then(|a| a.checked_mul(DEC_ONE))
+                        } else {
+                            a.checked_mul(DEC_ONE)
+                        };
+
+                        match scaled {
+                            Some(scaled) => Value::Dec(scaled),
+                            None => {
+                                return Err(ComptimeProblem::Crashed(
+                                    "Decimal conversion overflowed!".into(),
+                                ))
+                            }
+                        }
+                    }
+                    (Value::Dec(a), LayoutRepr::Builtin(Builtin::Decimal)) => Value::Dec(*a),
+                    _ => return Err(unsupported(op)),
+                }
+            }
+            NumIntCast => {
+                let (a, _) = args[0].as_int();
+
+                self.int(a, layout)
+            }
+            NumPowInt => {
+                let (base, width) = args[0].as_int();
+                let (exponent, _) = args[1].as_int();
+
+                match pow_int(width, base, exponent) {
+                    Some(result) => Value::Int(result, width),
+                    None => {
+                        return Err(ComptimeProblem::Crashed(
+                            "Integer raised to power overflowed!".into(),
+                        ))
+                    }
+                }
+            }
+            NumSqrtUnchecked | NumLogUnchecked | NumSin | NumCos | NumTan | NumAtan | NumAcos
+            | NumAsin => match args[0] {
+                Value::Float(a, width) => float(
+                    match op {
+                        NumSqrtUnchecked => a.sqrt(),
+                        NumLogUnchecked => a.ln(),
+                        NumSin => a.sin(),
+                        NumCos => a.cos(),
+                        NumTan => a.tan(),
+                        NumAtan => a.atan(),
+                        NumAcos => a.acos(),
+                        _ => a.asin(),
+                    },
+                    width,
+                ),
+                _ => return Err(unsupported(op)),
+            },
+            NumPow => match (&args[0], &args[1]) {
+                (Value::Float(a, width), Value::Float(b, _)) => float(a.powf(*b), *width),
+                _ => return Err(unsupported(op)),
+            },
+            NumRound | NumFloor | NumCeiling => match args[0] {
+                Value::Float(a, _) => {
+                    let rounded = match op {
+                        NumRound => a.round(),
+                        NumFloor => a.floor(),
+                        _ => a.ceil(),
+                    };
+
+                    self.int(rounded as i128, layout)
+                }
+                _ => return Err(unsupported(op)),
+            },
+            NumIsNan | NumIsInfinite | NumIsFinite => match args[0] {
+                Value::Float(a, _) => Value::Bool(match op {
+                    NumIsNan => a.is_nan(),
+                    NumIsInfinite => a.is_infinite(),
+                    _ => a.is_finite(),
+                }),
+                _ => return Err(unsupported(op)),
+            },
+            NumBitwiseAnd | NumBitwiseOr | NumBitwiseXor => {
+                let (a, width) = args[0].as_int();
+                let (b, _) = args[1].as_int();
+
+                let result = match op {
+                    NumBitwiseAnd => a & b,
+                    NumBitwiseOr => a | b,
+                    _ => a ^ b,
+                };
+
+                Value::Int(wrap(width, result), width)
+            }
+            NumShiftLeftBy | NumShiftRightBy | NumShiftRightZfBy => {
+                let (a, width) = args[0].as_int();
+                let (by, _) = args[1].as_int();
+
+                Value::Int(shift(op, width, a, by as u32), width)
+            }
+            NumCountLeadingZeroBits | NumCountTrailingZeroBits | NumCountOneBits => {
+                let (a, width) = args[0].as_int();
+                let bits = width.stack_size() * 8;
+                let unsigned = wrap(unsigned_width(width), a) as u128;
+
+                let count = match op {
+                    NumCountLeadingZeroBits => unsigned.leading_zeros() - (128 - bits),
+                    NumCountTrailingZeroBits => unsigned.trailing_zeros().min(bits),
+                    _ => unsigned.count_ones(),
+                };
+
+                self.int(count as i128, layout)
+            }
+
+            Eq => Value::Bool(args[0] == args[1]),
+            NotEq => Value::Bool(args[0] != args[1]),
+            Not => Value::Bool(!args[0].as_bool()),
+
+            _ => return Err(unsupported(op)),
+        };
+
+        Ok(value)
+    }
+}
+
+fn unsupported(op: LowLevel) -> ComptimeProblem {
+    ComptimeProblem::Unsupported(format!("the builtin operation {op:?}"))
+}
+
+fn utf8(value: &Value) -> &str {
+    match std::str::from_utf8(value.as_str()) {
+        Ok(string) => string,
+        Err(_) => internal_error!("a Str is not valid UTF-8"),
+    }
+}
+
+fn float(value: f64, width: FloatWidth) -> Value {
+    match width {
+        FloatWidth::F32 => Value::Float(value as f32 as f64, width),
+        FloatWidth::F64 => Value::Float(value, width),
+    }
+}
+
+fn int_to_f64(value: i128, width: IntWidth) -> f64 {
+    match width {
+        IntWidth::U128 => value as u128 as f64,
+        _ => value as f64,
+    }
+}
+
+/// Truncates the bits of `value` to the given width
+fn wrap(width: IntWidth, value: i128) -> i128 {
+    match width {
+        IntWidth::U8 => value as u8 as i128,
+        IntWidth::U16 => value as u16 as i128,
+        IntWidth::U32 => value as u32 as i128,
+        IntWidth::U64 => value as u64 as i128,
+        IntWidth::I8 => value as i8 as i128,
+        IntWidth::I16 => value as i16 as i128,
+        IntWidth::I32 => value as i32 as i128,
+        IntWidth::I64 => value as i64 as i128,
+        IntWidth::U128 | IntWidth::I128 => value,
+    }
+}
+
+fn unsigned_width(width: IntWidth) -> IntWidth {
+    match width {
+        IntWidth::I8 => IntWidth::U8,
+        IntWidth::I16 => IntWidth::U16,
+        IntWidth::I32 => IntWidth::U32,
+        IntWidth::I64 => IntWidth::U64,
+        IntWidth::I128 => IntWidth::U128,
+        unsigned => unsigned,
+    }
+}
+
+fn signed_width(width: IntWidth) -> IntWidth {
+    match width {
+        IntWidth::U8 => IntWidth::I8,
+        IntWidth::U16 => IntWidth::I16,
+        IntWidth::U32 => IntWidth::I32,
+        IntWidth::U64 => IntWidth::I64,
+        IntWidth::U128 => IntWidth::I128,
+        signed => signed,
+    }
+}
+
+fn shift(op: LowLevel, width: IntWidth, value: i128, by: u32) -> i128 {
+    let bits = width.stack_size() * 8;
+    let unsigned = wrap(unsigned_width(width), value) as u128;
+    let signed = wrap(signed_width(width), value);
+
+    let result = match op {
+        LowLevel::NumShiftLeftBy if by >= bits => 0,
+        LowLevel::NumShiftLeftBy => (unsigned << by) as i128,
+        LowLevel::NumShiftRightZfBy if by >= bits => 0,
+        LowLevel::NumShiftRightZfBy => (unsigned >> by) as i128,
+        _ => signed >> by.min(127),
+    };
+
+    wrap(width, result)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Arith {
+    Add,
+    Sub,
+    Mul,
+}
+
+impl Arith {
+    fn name(self) -> &'static str {
+        match self {
+            Arith::Add => "addition",
+            Arith::Sub => "subtraction",
+            Arith::Mul => "multiplication",
+        }
+    }
+}
+
+/// What an arithmetic operation does when its result doesn't fit
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Overflow {
+    Crash,
+    Wrap,
+    Saturate,
+    /// Returns `{ value, overflowed }`, like `Num.addChecked` expects
+    Report,
+}
+
+fn overflow_mode(op: LowLevel) -> Overflow {
+    use LowLevel::*;
+
+    match op {
+        NumAddWrap | NumSubWrap | NumMulWrap => Overflow::Wrap,
+        NumAddSaturated | NumSubSaturated | NumMulSaturated => Overflow::Saturate,
+        NumAddChecked | NumSubChecked | NumMulChecked => Overflow::Report,
+        _ => Overflow::Crash,
+    }
+}
+
+fn arithmetic(op: Arith, overflow: Overflow, a: &Value, b: &Value) -> Eval {
+    match (a, b) {
+        (Value::Int(a, width), Value::Int(b, _)) => {
+            let (a, b, width) = (*a, *b, *width);
+            let exact = checked_int(op, width, a, b);
+
+            Ok(match (exact, overflow) {
+                (Some(result), Overflow::Report) => Value::pair(Value::Int(result, width), false),
+                (Some(result), _) => Value::Int(result, width),
+                (None, Overflow::Crash) => {
+                    return Err(ComptimeProblem::Crashed(format!(
+                        "Integer {} overflowed!",
+                        op.name()
+                    )))
+                }
+                (None, Overflow::Wrap) => Value::Int(wrapping_int(op, width, a, b), width),
+                (None, Overflow::Report) => {
+                    Value::pair(Value::Int(wrapping_int(op, width, a, b), width), true)
+                }
+                (None, Overflow::Saturate) => {
+                    let negative = match op {
+                        Arith::Add => width.is_signed() && b < 0,
+                        Arith::Sub => !width.is_signed() || b > 0,
+                        Arith::Mul => (a < 0) != (b < 0),
+                    };
+
+                    Value::Int(int_bound(width, negative), width)
+                }
+            })
+        }
+        (Value::Float(a, width), Value::Float(b, _)) => {
+            let result = match op {
+                Arith::Add => a + b,
+                Arith::Sub => a - b,
+                Arith::Mul => a * b,
+            };
+
+            match float(result, *width) {
+                Value::Float(result, _) if overflow == Overflow::Report => Ok(Value::pair(
+                    Value::Float(result, *width),
+                    result.is_infinite(),
+                )),
+                result => Ok(result),
+            }
+        }
+        (Value::Dec(a), Value::Dec(b)) => {
+            let exact = match op {
+                Arith::Add => a.checked_add(*b),
+                Arith::Sub => a.checked_sub(*b),
+                Arith::Mul => dec_mul(*a, *b),
+            };
+
+            Ok(match (exact, overflow) {
+                (Some(result), Overflow::Report) => Value::pair(Value::Dec(result), false),
+                (Some(result), _) => Value::Dec(result),
+                (None, Overflow::Report) => Value::pair(Value::Dec(0), true),
+                (None, Overflow::Saturate) => {
+                    let negative = match op {
+                        Arith::Add => *b < 0,
+                        Arith::Sub => *b > 0,
+                        Arith::Mul => (*a < 0) != (*b < 0),
+                    };
+
+                    Value::Dec(if negative { i128::MIN } else { i128::MAX })
+                }
+                (None, _) => {
+                    return Err(ComptimeProblem::Crashed(format!(
+                        "Decimal {} overflowed!",
+                        op.name()
+                    )))
+                }
+            })
+        }
+        _ => internal_error!("arithmetic on mismatched numbers {a:?} and {b:?}"),
+    }
+}
+
+/// The result of the operation, if it fits in the width
+fn checked_int(op: Arith, width: IntWidth, a: i128, b: i128) -> Option<i128> {
+    if width == IntWidth::U128 {
+        let (a, b) = (a as u128, b as u128);
+
+        let result = match op {
+            Arith::Add => a.checked_add(b),
+            Arith::Sub => a.checked_sub(b),
+            Arith::Mul => a.checked_mul(b),
+        };
+
+        return result.map(|result| result as i128);
+    }
+
+    let result = match op {
+        Arith::Add => a.checked_add(b),
+        Arith::Sub => a.checked_sub(b),
+        Arith::Mul => a.checked_mul(b),
+    }?;
+
+    (wrap(width, result) == result).then_some(result)
+}
+
+fn wrapping_int(op: Arith, width: IntWidth, a: i128, b: i128) -> i128 {
+    let result = match op {
+        Arith::Add => a.wrapping_add(b),
+        Arith::Sub => a.wrapping_sub(b),
+        Arith::Mul => a.wrapping_mul(b),
+    };
+
+    wrap(width, result)
+}
+
+fn int_bound(width: IntWidth, min: bool) -> i128 {
+    let bits = width.stack_size() * 8;
+
+    match (width.is_signed(), min) {
+        (false, true) => 0,
+        (false, false) => wrap(width, -1),
+        (true, true) => wrap(width, 1 << (bits - 1)),
+        (true, false) => wrap(width, (1 << (bits - 1)) - 1),
+    }
+}
+
+fn pow_int(width: IntWidth, mut base: i128, exponent: i128) -> Option<i128> {
+    let mut exponent = u32::try_from(exponent).ok()?;
+    let mut result = 1;
+
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = checked_int(Arith::Mul, width, result, base)?;
+        }
+
+        exponent >>= 1;
+
+        if exponent > 0 {
+            base = checked_int(Arith::Mul, width, base, base)?;
+        }
+    }
+
+    Some(result)
+}
+
+fn compare(a: &Value, b: &Value) -> Option<Ordering> {
+    match (a, b) {
+        (Value::Int(a, IntWidth::U128), Value::Int(b, _)) => Some((*a as u128).cmp(&(*b as u128))),
+        (Value::Int(a, _), Value::Int(b, _)) => Some(a.cmp(b)),
+        (Value::Float(a, _), Value::Float(b, _)) => a.partial_cmp(b),
+        (Value::Dec(a), Value::Dec(b)) => Some(a.cmp(b)),
+        _ => internal_error!("comparison of mismatched numbers {a:?} and {b:?}"),
+    }
+}
+
+/// The full 256-bit product of two unsigned numbers, as `(high, low)`
+fn widening_mul(a: u128, b: u128) -> (u128, u128) {
+    let mask = u64::MAX as u128;
+    let (a_hi, a_lo) = (a >> 64, a & mask);
+    let (b_hi, b_lo) = (b >> 64, b & mask);
+
+    let lo_lo = a_lo * b_lo;
+    let hi_lo = a_hi * b_lo;
+    let lo_hi = a_lo * b_hi;
+    let hi_hi = a_hi * b_hi;
+
+    let middle = (lo_lo >> 64) + (hi_lo & mask) + (lo_hi & mask);
+
+    let low = (middle << 64) | (lo_lo & mask);
+    let high = hi_hi + (hi_lo >> 64) + (lo_hi >> 64) + (middle >> 64);
+
+    (high, low)
+}
+
+/// Divides the 256-bit number `(high, low)`, if the quotient fits in 128 bits
+fn narrowing_div((high, low): (u128, u128), divisor: u128) -> Option<u128> {
+    if high >= divisor {
+        return None;
+    }
+
+    let mut remainder = high;
+    let mut quotient = 0u128;
+
+    for bit in (0..128).rev() {
+        let carry = remainder >> 127;
+        remainder = (remainder << 1) | ((low >> bit) & 1);
+        quotient <<= 1;
+
+        if carry == 1 || remainder >= divisor {
+            remainder = remainder.wrapping_sub(divisor);
+            quotient |= 1;
+        }
+    }
+
+    Some(quotient)
+}
+
+fn signed_dec(magnitude: u128, negative: bool) -> Option<i128> {
+    if negative {
+        0i128.checked_sub_unsigned(magnitude)
+    } else {
+        i128::try_from(magnitude).ok()
+    }
+}
+
+fn dec_mul(a: i128, b: i128) -> Option<i128> {
+    let product = widening_mul(a.unsigned_abs(), b.unsigned_abs());
+    let magnitude = narrowing_div(product, DEC_ONE as u128)?;
+
+    signed_dec(magnitude, (a < 0) != (b < 0))
+}
+
+fn dec_div(a: i128, b: i128) -> Option<i128> {
+    let scaled = widening_mul(a.unsigned_abs(), DEC_ONE as u128);
+    let magnitude = narrowing_div(scaled, b.unsigned_abs())?;
+
+    signed_dec(magnitude, (a < 0) != (b < 0))
+}
+
+/// Turns a value back into IR that constructs it
+struct Builder<'r, 'a> {
+    arena: &'a Bump,
+    interner: &'r STLayoutInterner<'a>,
+    home: ModuleId,
+    ident_ids: &'r mut IdentIds,
+    lets: Vec<(Symbol, Expr<'a>, InLayout<'a>)>,
+}
+
+impl<'r, 'a> Builder<'r, 'a> {
+    fn into_stmt(self, result: Symbol) -> Stmt<'a> {
+        let mut stmt = Stmt::Ret(result);
+
+        for (symbol, expr, layout) in self.lets.into_iter().rev() {
+            stmt = Stmt::Let(symbol, expr, layout, self.arena.alloc(stmt));
+        }
+
+        stmt
+    }
+
+    fn build(&mut self, value: &Value, layout: InLayout<'a>) -> Eval<Symbol> {
+        let layout = self.interner.chase_recursive_in(layout);
+
+        let expr = match (value, self.interner.get_repr(layout)) {
+            (Value::List(elements), LayoutRepr::Builtin(Builtin::List(elem_layout))) => {
+                if elements.is_empty() {
+                    Expr::EmptyArray
+                } else {
+                    let mut elems = Vec::with_capacity(elements.len());
+
+                    for element in elements.iter() {
+                        elems.push(match scalar_literal(self.arena, element) {
+                            Some(literal) if !matches!(literal, Literal::Str(_)) => {
+                                ListLiteralElement::Literal(literal)
+                            }
+                            _ => ListLiteralElement::Symbol(self.build(element, elem_layout)?),
+                        });
+                    }
+
+                    Expr::Array {
+                        elem_layout,
+                        elems: self.arena.alloc_slice_copy(&elems),
+                    }
+                }
+            }
+            (Value::Struct(fields), LayoutRepr::Struct(field_layouts)) => {
+                let symbols = self.build_all(fields, field_layouts)?;
+
+                Expr::Struct(self.arena.alloc_slice_copy(&symbols))
+            }
+            (Value::Tag(tag_id, fields), LayoutRepr::Union(union_layout)) => {
+                let field_layouts = tag_field_layouts(union_layout, *tag_id);
+                let symbols = self.build_all(fields, field_layouts)?;
+
+                Expr::Tag {
+                    tag_layout: union_layout,
+                    tag_id: *tag_id,
+                    arguments: self.arena.alloc_slice_copy(&symbols),
+                    reuse: None,
+                }
+            }
+            (
+                _,
+                LayoutRepr::LambdaSet(_) | LayoutRepr::FunctionPointer(_) | LayoutRepr::Erased(_),
+            ) => return Err(ComptimeProblem::ContainsFunction),
+            (value, repr) => match scalar_literal(self.arena, value) {
+                Some(literal) => Expr::Literal(literal),
+                None => internal_error!("value {value:?} does not have layout {repr:?}"),
+            },
+        };
+
+        let symbol = Symbol::new(self.home, self.ident_ids.gen_unique());
+        self.lets.push((symbol, expr, layout));
+
+        Ok(symbol)
+    }
+
+    fn build_all(&mut self, values: &[Value], layouts: &[InLayout<'a>]) -> Eval<Vec<Symbol>> {
+        values
+            .iter()
+            .zip(layouts)
+            .map(|(value, layout)| self.build(value, *layout))
+            .collect()
+    }
+}
+
+fn scalar_literal<'a>(arena: &'a Bump, value: &Value) -> Option<Literal<'a>> {
+    match value {
+        Value::Int(value, IntWidth::U128) => Some(Literal::U128(value.to_ne_bytes())),
+        Value::Int(value, _) => Some(Literal::Int(value.to_ne_bytes())),
+        Value::Float(value, _) => Some(Literal::Float(*value)),
+        Value::Dec(value) => Some(Literal::Decimal(value.to_ne_bytes())),
+        Value::Bool(value) => Some(Literal::Bool(*value)),
+        Value::Str(_) => Some(Literal::Str(arena.alloc_str(utf8(value)))),
+        Value::List(_) | Value::Struct(_) | Value::Tag(_, _) => None,
+    }
+}
+
+fn tag_field_layouts<'a>(
+    union_layout: UnionLayout<'a>,
+    tag_id: TagIdIntType,
+) -> &'a [InLayout<'a>] {
+    match union_layout {
+        UnionLayout::NonRecursive(tags) | UnionLayout::Recursive(tags) => tags[tag_id as usize],
+        UnionLayout::NonNullableUnwrapped(fields) => fields,
+        UnionLayout::NullableWrapped {
+            nullable_id,
+            other_tags,
+        } => match tag_id.cmp(&nullable_id) {
+            Ordering::Equal => &[],
+            Ordering::Less => other_tags[tag_id as usize],
+            Ordering::Greater => other_tags[tag_id as usize - 1],
+        },
+        UnionLayout::NullableUnwrapped {
+            nullable_id,
+            other_fields,
+        } => {
+            if tag_id == nullable_id as TagIdIntType {
+                &[]
+            } else {
+                other_fields
+            }
+        }
+    }
+}
+
+/// The problems to report for the defs and expects that failed. Every specialization of a def is
+/// evaluated, but each problem is only reported once.
+pub fn comptime_problems(failures: &[ComptimeFailure], interns: &Interns) -> Vec<Problem> {
+    let mut unique: Vec<&ComptimeFailure> = Vec::new();
+
+    for failure in failures {
+        if !unique.contains(&failure) {
+            unique.push(failure);
+        }
+    }
+
+    sort_by_symbol_name(&mut unique, interns, |failure| {
+        (failure.symbol, failure.region.start().offset)
+    });
+
+    unique
+        .into_iter()
+        .map(|failure| Problem::Comptime(failure.clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn dec(value: &str) -> i128 {
+        i128::from_ne_bytes(RocDec::from_str(value).unwrap().to_ne_bytes())
+    }
+
+    #[test]
+    fn dec_arithmetic() {
+        assert_eq!(dec_mul(dec("1.5"), dec("-2.25")), Some(dec("-3.375")));
+        assert_eq!(
+            dec_mul(dec("1000000000"), dec("1000000000")),
+            Some(dec("1000000000000000000"))
+        );
+        assert_eq!(dec_mul(i128::MAX, dec("2")), None);
+
+        assert_eq!(dec_div(dec("1"), dec("4")), Some(dec("0.25")));
+        assert_eq!(dec_div(dec("-7"), dec("2")), Some(dec("-3.5")));
+        assert_eq!(dec_div(i128::MAX, dec("0.5")), None);
+    }
+
+    #[test]
+    fn int_overflow() {
+        assert_eq!(checked_int(Arith::Add, IntWidth::U8, 200, 55), Some(255));
+        assert_eq!(checked_int(Arith::Add, IntWidth::U8, 200, 56), None);
+        assert_eq!(wrapping_int(Arith::Add, IntWidth::U8, 200, 56), 0);
+        assert_eq!(checked_int(Arith::Sub, IntWidth::I8, -100, 28), Some(-128));
+        assert_eq!(checked_int(Arith::Mul, IntWidth::U128, -1, 2), None);
+
+        assert_eq!(int_bound(IntWidth::I16, true), i16::MIN as i128);
+        assert_eq!(int_bound(IntWidth::U64, false), u64::MAX as i128);
+        assert_eq!(pow_int(IntWidth::I32, 3, 19), Some(3i128.pow(19)));
+        assert_eq!(pow_int(IntWidth::I32, 3, 20), None);
+    }
+
+    #[test]
+    fn int_shifts() {
+        use LowLevel::*;
+
+        assert_eq!(
+            shift(NumShiftLeftBy, IntWidth::U8, 0b1100_0001, 1),
+            0b1000_0010
+        );
+        assert_eq!(
+            shift(NumShiftRightBy, IntWidth::U8, 0b1000_0000, 1),
+            0b1100_0000
+        );
+        assert_eq!(shift(NumShiftRightZfBy, IntWidth::I8, -128, 1), 64);
+        assert_eq!(shift(NumShiftLeftBy, IntWidth::I32, 1, 32), 0);
+    }
+}
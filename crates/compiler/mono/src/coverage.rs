@@ -0,0 +1,317 @@
+//! Coverage instrumentation, which records which defs and branches were executed.
+//!
+//! A [CoverageMap] assigns an index into an array of counters to every proc and to every
+//! branch of every `Switch` in user code. The backend increments the corresponding counter
+//! whenever the proc is entered or the branch is taken, and afterwards the counts get mapped
+//! back to source regions and rendered as an [lcov](https://github.com/linux-test-project/lcov)
+//! tracefile.
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::PathBuf;
+
+use roc_collections::{MutMap, VecMap};
+use roc_module::symbol::{Interns, ModuleId, Symbol};
+use roc_region::all::{LineInfo, Region};
+
+use crate::ir::{Proc, Stmt};
+use crate::profile::Profile;
+
+/// The symbol of the global array of counters, which has one `u64` per coverage point.
+pub const COVERAGE_COUNTERS: &str = "roc_coverage_counters";
+
+/// Where the branches of switches come from in the source, keyed like the branch counters of a
+/// [CoverageMap]. A branch of an `if` is the region of its body. A branch of a `when` is the
+/// region of the `when` branch it leads to, or of the whole `when` if it leads to several.
+pub type BranchRegions = MutMap<(Symbol, Option<u64>), Region>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoveragePoint {
+    /// The proc with this name was called.
+    Proc(Symbol),
+    /// A branch of a switch in the given proc was taken.
+    Branch {
+        proc: Symbol,
+        cond_symbol: Symbol,
+        /// The value this branch matches on, or None for the default branch.
+        tag: Option<u64>,
+    },
+}
+
+#[derive(Debug, Default)]
+pub struct CoverageMap {
+    points: Vec<CoveragePoint>,
+    procs: MutMap<Symbol, u32>,
+    /// Switch conditions are symbols that are unique to one proc, so they identify the switch.
+    branches: MutMap<(Symbol, Option<u64>), u32>,
+}
+
+impl CoverageMap {
+    /// Assign counters to all the procs (and their branches) which are defined in user code.
+    pub fn new<'a: 'p, 'p>(procedures: impl IntoIterator<Item = &'p Proc<'a>>) -> Self {
+        let mut map = Self::default();
+
+        // Sort so that the counter indices don't depend on hash map iteration order.
+        let mut procedures: Vec<_> = procedures
+            .into_iter()
+            .filter(|proc| !proc.name.name().module_id().is_builtin())
+            .collect();
+
+        procedures.sort_by_key(|proc| proc.name.name());
+
+        for proc in procedures {
+            let symbol = proc.name.name();
+
+            if !map.procs.contains_key(&symbol) {
+                map.procs.insert(symbol, map.points.len() as u32);
+                map.points.push(CoveragePoint::Proc(symbol));
+            }
+
+            map.add_branches(symbol, &proc.body);
+        }
+
+        map
+    }
+
+    fn add_branches(&mut self, proc: Symbol, stmt: &Stmt) {
+        use Stmt::*;
+
+        match stmt {
+            Switch {
+                cond_symbol,
+                branches,
+                default_branch,
+                ..
+            } => {
+                let tags = branches
+                    .iter()
+                    .map(|(tag, _, _)| Some(*tag))
+                    .chain(std::iter::once(None));
+
+                for tag in tags {
+                    let key = (*cond_symbol, tag);
+
+                    if !self.branches.contains_key(&key) {
+                        self.branches.insert(key, self.points.len() as u32);
+                        self.points.push(CoveragePoint::Branch {
+                            proc,
+                            cond_symbol: *cond_symbol,
+                            tag,
+                        });
+                    }
+                }
+
+                for (_, _, branch) in branches.iter() {
+                    self.add_branches(proc, branch);
+                }
+
+                self.add_branches(proc, default_branch.1);
+            }
+            Let(_, _, _, remainder)
+            | Refcounting(_, remainder)
+            | Expect { remainder, .. }
+            | Dbg { remainder, .. } => self.add_branches(proc, remainder),
+            Join {
+                body, remainder, ..
+            } => {
+                self.add_branches(proc, body);
+                self.add_branches(proc, remainder);
+            }
+            Ret(_) | Jump(_, _) | Crash(_, _) => {}
+        }
+    }
+
+    /// How many counters the instrumented program needs.
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// The counter to increment when the given proc is entered.
+    pub fn proc_counter(&self, proc: Symbol) -> Option<u32> {
+        self.procs.get(&proc).copied()
+    }
+
+    /// The counter to increment when the branch for `tag` (or the default branch, for None)
+    /// is taken in a switch on `cond_symbol`.
+    pub fn branch_counter(&self, cond_symbol: Symbol, tag: Option<u64>) -> Option<u32> {
+        self.branches.get(&(cond_symbol, tag)).copied()
+    }
+
+    /// How often each def was called, keyed by its qualified name. See [crate::profile].
+    pub fn to_profile(&self, counts: &[u64], interns: &Interns) -> Profile {
+        debug_assert_eq!(counts.len(), self.points.len());
+
+        let mut profile = Profile::default();
+
+        for (point, count) in self.points.iter().zip(counts.iter().copied()) {
+            if let CoveragePoint::Proc(symbol) = *point {
+                let module_name = interns.module_ids.get_name(symbol.module_id()).unwrap();
+
+                profile.add(&format!("{module_name}.{}", symbol.as_str(interns)), count);
+            }
+        }
+
+        profile
+    }
+
+    /// Render the counts as an lcov tracefile, with one record per source file.
+    ///
+    /// Only procs for top-level defs (i.e. those in `def_regions`) are reported. Each branch is
+    /// reported on the first line of its region in `branch_regions`, or on the first line of its
+    /// def if it has none. The lines are those of the defs and of the branches, where a line is
+    /// hit as often as the def on it was called, or as often as branches starting on it were taken.
+    pub fn to_lcov(
+        &self,
+        counts: &[u64],
+        def_regions: &MutMap<Symbol, Region>,
+        branch_regions: &BranchRegions,
+        sources: &MutMap<ModuleId, (PathBuf, Box<str>)>,
+        interns: &Interns,
+    ) -> String {
+        debug_assert_eq!(counts.len(), self.points.len());
+
+        // (line, name, count) for each def, and (line, block, branch, count) for each branch
+        let mut defs_by_module: VecMap<ModuleId, Vec<(u32, &str, u64)>> = VecMap::default();
+        let mut branches_by_module: VecMap<ModuleId, Vec<(u32, u32, u32, u64)>> = VecMap::default();
+        let mut line_infos: MutMap<ModuleId, LineInfo> = MutMap::default();
+
+        let mut line_of = |module_id: ModuleId, region: Region| -> Option<u32> {
+            let (_, src) = sources.get(&module_id)?;
+            let line_info = line_infos
+                .entry(module_id)
+                .or_insert_with(|| LineInfo::new(src));
+
+            Some(line_info.convert_region(region).start.line + 1)
+        };
+
+        let mut switch_numbers: MutMap<Symbol, u32> = MutMap::default();
+        let mut branch_numbers: MutMap<Symbol, u32> = MutMap::default();
+
+        for (point, count) in self.points.iter().zip(counts.iter().copied()) {
+            match *point {
+                CoveragePoint::Proc(symbol) => {
+                    let Some(line) = def_regions
+                        .get(&symbol)
+                        .and_then(|region| line_of(symbol.module_id(), *region))
+                    else {
+                        continue;
+                    };
+
+                    let module_defs = defs_by_module.get_or_insert(symbol.module_id(), Vec::new);
+
+                    // Several specializations of one def share its counter
+                    if !module_defs.iter().any(|(l, _, _)| *l == line) {
+                        module_defs.push((line, symbol.as_str(interns), count));
+                    }
+                }
+                CoveragePoint::Branch {
+                    proc,
+                    cond_symbol,
+                    tag,
+                } => {
+                    let Some(region) = branch_regions
+                        .get(&(cond_symbol, tag))
+                        .or_else(|| def_regions.get(&proc))
+                    else {
+                        continue;
+                    };
+                    let Some(line) = line_of(proc.module_id(), *region) else {
+                        continue;
+                    };
+
+                    let next_block = switch_numbers.len() as u32;
+                    let block = *switch_numbers.entry(cond_symbol).or_insert(next_block);
+                    let branch = branch_numbers.entry(cond_symbol).or_insert(0);
+
+                    branches_by_module
+                        .get_or_insert(proc.module_id(), Vec::new)
+                        .push((line, block, *branch, count));
+
+                    *branch += 1;
+                }
+            }
+        }
+
+        let mut module_ids: Vec<_> = defs_by_module.keys().copied().collect();
+
+        module_ids.sort_by_key(|module_id| sources.get(module_id).map(|(path, _)| path));
+
+        let mut buf = String::new();
+
+        for module_id in module_ids {
+            let (path, _) = &sources[&module_id];
+            let mut defs = defs_by_module.get(&module_id).cloned().unwrap_or_default();
+            let branches = branches_by_module
+                .get(&module_id)
+                .cloned()
+                .unwrap_or_default();
+
+            defs.sort();
+
+            writeln!(buf, "TN:").unwrap();
+            writeln!(buf, "SF:{}", path.display()).unwrap();
+
+            for (line, name, _) in defs.iter() {
+                writeln!(buf, "FN:{line},{name}").unwrap();
+            }
+
+            for (_, name, count) in defs.iter() {
+                writeln!(buf, "FNDA:{count},{name}").unwrap();
+            }
+
+            writeln!(buf, "FNF:{}", defs.len()).unwrap();
+            writeln!(
+                buf,
+                "FNH:{}",
+                defs.iter().filter(|(_, _, count)| *count > 0).count()
+            )
+            .unwrap();
+
+            for (line, block, branch, count) in branches.iter() {
+                writeln!(buf, "BRDA:{line},{block},{branch},{count}").unwrap();
+            }
+
+            writeln!(buf, "BRF:{}", branches.len()).unwrap();
+            writeln!(
+                buf,
+                "BRH:{}",
+                branches.iter().filter(|(.., count)| *count > 0).count()
+            )
+            .unwrap();
+
+            // How often each line was hit, as (def count, sum of branch counts)
+            let mut lines: BTreeMap<u32, (u64, u64)> = BTreeMap::new();
+
+            for (line, _, count) in defs.iter() {
+                let hits = &mut lines.entry(*line).or_default().0;
+
+                *hits = (*hits).max(*count);
+            }
+
+            for (line, .., count) in branches.iter() {
+                lines.entry(*line).or_default().1 += count;
+            }
+
+            for (line, (def_hits, branch_hits)) in lines.iter() {
+                writeln!(buf, "DA:{line},{}", def_hits.max(branch_hits)).unwrap();
+            }
+
+            writeln!(buf, "LF:{}", lines.len()).unwrap();
+            writeln!(
+                buf,
+                "LH:{}",
+                lines
+                    .values()
+                    .filter(|(def_hits, branch_hits)| *def_hits > 0 || *branch_hits > 0)
+                    .count()
+            )
+            .unwrap();
+            writeln!(buf, "end_of_record").unwrap();
+        }
+
+        buf
+    }
+}
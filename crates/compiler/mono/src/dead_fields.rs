@@ -0,0 +1,434 @@
+//! Removes the fields of records that are never read, so a big record that's only partially used
+//! takes less memory.
+//!
+//! Only records that are built and used within a single specialization are changed: a record whose
+//! fields are all accessed with `StructAtIndex`, and that is never passed to a function, returned,
+//! stored in another value or passed to a join point. Because such a record never leaves its
+//! proc, it can't reach the host either, so changing its layout is invisible outside of the proc.
+//! The values of the removed fields are still computed; they're just not stored in the record.
+//!
+//! [dead_field_report] lists the removed fields as tab-separated lines with one line per record:
+//!
+//! ```text
+//! {module}.{ident}
+//! {record layout}
+//! {removed fields}
+//! {bytes saved}
+//! ```
+//!
+//! Fields without a name (those of tuples) are shown as `#` and their index in the layout.
+use std::fmt::Write;
+
+use bumpalo::Bump;
+use roc_collections::{MutMap, MutSet};
+use roc_module::symbol::{Interns, Symbol};
+
+use crate::ir::{CallType, Expr, ListLiteralElement, Proc, ProcLayout, Stmt};
+use crate::layout::{InLayout, LayoutInterner, LayoutRepr, STLayoutInterner};
+use crate::report_format::{sort_by_symbol_name, ReportFormat};
+
+/// The fields that were removed from one record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeadFields<'a> {
+    /// The def whose specialization builds the record
+    pub proc: Symbol,
+    /// The layout the record had before the fields were removed
+    pub layout: InLayout<'a>,
+    /// The indices of the removed fields in `layout`
+    pub removed: Vec<usize>,
+    /// How much smaller the record got
+    pub bytes_saved: u32,
+}
+
+pub fn eliminate_dead_fields<'a>(
+    arena: &'a Bump,
+    interner: &mut STLayoutInterner<'a>,
+    procedures: &mut MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+) -> Vec<DeadFields<'a>> {
+    let mut eliminated = Vec::new();
+
+    for ((symbol, _), proc) in procedures.iter_mut() {
+        let shrunk = shrinkable_records(arena, interner, &proc.body);
+
+        if shrunk.is_empty() {
+            continue;
+        }
+
+        proc.body = shrink_stmt(arena, &shrunk, &proc.body);
+
+        for record in shrunk.into_values() {
+            eliminated.push(DeadFields {
+                proc: *symbol,
+                layout: record.old_layout,
+                bytes_saved: interner.stack_size(record.old_layout)
+                    - interner.stack_size(record.new_layout),
+                removed: record.removed,
+            });
+        }
+    }
+
+    eliminated
+}
+
+struct ShrunkRecord<'a> {
+    old_layout: InLayout<'a>,
+    new_layout: InLayout<'a>,
+    new_field_layouts: &'a [InLayout<'a>],
+    /// For each field of the old layout, its index in the new one, if it's kept
+    new_indices: Vec<Option<u64>>,
+    removed: Vec<usize>,
+}
+
+/// The records built in `stmt` that don't leave it and have fields that are never read, by the
+/// symbol they're bound to.
+fn shrinkable_records<'a>(
+    arena: &'a Bump,
+    interner: &mut STLayoutInterner<'a>,
+    stmt: &Stmt<'a>,
+) -> MutMap<Symbol, ShrunkRecord<'a>> {
+    let mut records: MutMap<Symbol, InLayout<'a>> = MutMap::default();
+    let mut read: MutMap<Symbol, MutSet<u64>> = MutMap::default();
+    let mut escaping: MutSet<Symbol> = MutSet::default();
+
+    let mut stack = vec![stmt];
+
+    while let Some(stmt) = stack.pop() {
+        match stmt {
+            Stmt::Let(symbol, expr, layout, cont) => {
+                match expr {
+                    Expr::Struct(fields) => {
+                        if matches!(interner.get_repr(*layout), LayoutRepr::Struct(field_layouts) if field_layouts.len() == fields.len())
+                        {
+                            records.insert(*symbol, *layout);
+                        }
+                    }
+                    Expr::StructAtIndex {
+                        index, structure, ..
+                    } => {
+                        read.entry(*structure).or_default().insert(*index);
+                    }
+                    _ => {}
+                }
+
+                expr_arguments(expr, |argument| {
+                    escaping.insert(argument);
+                });
+
+                stack.push(cont);
+            }
+            Stmt::Switch {
+                cond_symbol,
+                branches,
+                default_branch,
+                ..
+            } => {
+                escaping.insert(*cond_symbol);
+                stack.extend(branches.iter().map(|(_, _, branch)| branch));
+                stack.push(default_branch.1);
+            }
+            Stmt::Ret(symbol) | Stmt::Crash(symbol, _) => {
+                escaping.insert(*symbol);
+            }
+            Stmt::Refcounting(modify, cont) => {
+                escaping.insert(modify.get_symbol());
+                stack.push(cont);
+            }
+            Stmt::Expect {
+                condition,
+                lookups,
+                remainder,
+                ..
+            } => {
+                escaping.insert(*condition);
+                escaping.extend(lookups.iter().copied());
+                stack.push(remainder);
+            }
+            Stmt::Dbg {
+                symbol, remainder, ..
+            } => {
+                escaping.insert(*symbol);
+                stack.push(remainder);
+            }
+            Stmt::Join {
+                body, remainder, ..
+            } => {
+                stack.push(body);
+                stack.push(remainder);
+            }
+            Stmt::Jump(_, arguments) => escaping.extend(arguments.iter().copied()),
+        }
+    }
+
+    let mut shrunk = MutMap::default();
+
+    for (symbol, old_layout) in records {
+        if escaping.contains(&symbol) {
+            continue;
+        }
+
+        // A record that's never read at all is dead code, which isn't this pass's business
+        let Some(read) = read.get(&symbol) else {
+            continue;
+        };
+
+        let LayoutRepr::Struct(field_layouts) = interner.get_repr(old_layout) else {
+            continue;
+        };
+
+        if read.len() == field_layouts.len() {
+            continue;
+        }
+
+        let mut new_field_layouts = Vec::new();
+        let mut new_indices = Vec::new();
+        let mut removed = Vec::new();
+
+        for (index, field_layout) in field_layouts.iter().enumerate() {
+            if read.contains(&(index as u64)) {
+                new_indices.push(Some(new_field_layouts.len() as u64));
+                new_field_layouts.push(*field_layout);
+            } else {
+                new_indices.push(None);
+                removed.push(index);
+            }
+        }
+
+        let new_field_layouts = arena.alloc_slice_copy(&new_field_layouts);
+        let new_layout = interner.insert_direct_no_semantic(LayoutRepr::Struct(new_field_layouts));
+
+        shrunk.insert(
+            symbol,
+            ShrunkRecord {
+                old_layout,
+                new_layout,
+                new_field_layouts,
+                new_indices,
+                removed,
+            },
+        );
+    }
+
+    shrunk
+}
+
+/// Calls `f` on every symbol `expr` uses, other than the record it reads a field of.
+fn expr_arguments(expr: &Expr, mut f: impl FnMut(Symbol)) {
+    match expr {
+        Expr::Call(call) => {
+            call.arguments.iter().copied().for_each(&mut f);
+
+            if let CallType::ByPointer { pointer, .. } = call.call_type {
+                f(pointer);
+            }
+        }
+        Expr::Tag {
+            arguments, reuse, ..
+        } => {
+            arguments.iter().copied().for_each(&mut f);
+
+            if let Some(reuse) = reuse {
+                f(reuse.symbol);
+            }
+        }
+        Expr::Struct(fields) => fields.iter().copied().for_each(f),
+        Expr::Array { elems, .. } => elems
+            .iter()
+            .filter_map(ListLiteralElement::to_symbol)
+            .for_each(f),
+        Expr::GetTagId { structure, .. }
+        | Expr::UnionAtIndex { structure, .. }
+        | Expr::GetElementPointer { structure, .. } => f(*structure),
+        Expr::ErasedMake { value, callee } => {
+            value.iter().copied().for_each(&mut f);
+            f(*callee);
+        }
+        Expr::ErasedLoad { symbol, .. }
+        | Expr::Reset { symbol, .. }
+        | Expr::ResetRef { symbol, .. } => f(*symbol),
+        Expr::Alloca { initializer, .. } => initializer.iter().copied().for_each(f),
+        Expr::StructAtIndex { .. }
+        | Expr::Literal(_)
+        | Expr::NullPointer
+        | Expr::EmptyArray
+        | Expr::FunctionPointer { .. } => {}
+    }
+}
+
+fn shrink_stmt<'a>(
+    arena: &'a Bump,
+    shrunk: &MutMap<Symbol, ShrunkRecord<'a>>,
+    stmt: &Stmt<'a>,
+) -> Stmt<'a> {
+    match stmt {
+        Stmt::Let(symbol, expr, layout, cont) => {
+            let (expr, layout) = match (expr, shrunk.get(symbol)) {
+                (Expr::Struct(fields), Some(record)) => {
+                    let kept: Vec<Symbol> = fields
+                        .iter()
+                        .zip(record.new_indices.iter())
+                        .filter(|(_, new_index)| new_index.is_some())
+                        .map(|(field, _)| *field)
+                        .collect();
+
+                    (
+                        Expr::Struct(arena.alloc_slice_copy(&kept)),
+                        record.new_layout,
+                    )
+                }
+                (
+                    Expr::StructAtIndex {
+                        index, structure, ..
+                    },
+                    _,
+                ) if shrunk.contains_key(structure) => {
+                    let record = &shrunk[structure];
+
+                    (
+                        Expr::StructAtIndex {
+                            index: record.new_indices[*index as usize].unwrap(),
+                            field_layouts: record.new_field_layouts,
+                            structure: *structure,
+                        },
+                        *layout,
+                    )
+                }
+                _ => (expr.clone(), *layout),
+            };
+
+            Stmt::Let(
+                *symbol,
+                expr,
+                layout,
+                arena.alloc(shrink_stmt(arena, shrunk, cont)),
+            )
+        }
+        Stmt::Switch {
+            cond_symbol,
+            cond_layout,
+            branches,
+            default_branch,
+            ret_layout,
+        } => {
+            let branches =
+                arena.alloc_slice_fill_iter(branches.iter().map(|(value, info, branch)| {
+                    (*value, info.clone(), shrink_stmt(arena, shrunk, branch))
+                }));
+
+            Stmt::Switch {
+                cond_symbol: *cond_symbol,
+                cond_layout: *cond_layout,
+                branches,
+                default_branch: (
+                    default_branch.0.clone(),
+                    arena.alloc(shrink_stmt(arena, shrunk, default_branch.1)),
+                ),
+                ret_layout: *ret_layout,
+            }
+        }
+        Stmt::Refcounting(modify, cont) => {
+            Stmt::Refcounting(*modify, arena.alloc(shrink_stmt(arena, shrunk, cont)))
+        }
+        Stmt::Expect {
+            condition,
+            region,
+            lookups,
+            variables,
+            remainder,
+        } => Stmt::Expect {
+            condition: *condition,
+            region: *region,
+            lookups,
+            variables,
+            remainder: arena.alloc(shrink_stmt(arena, shrunk, remainder)),
+        },
+        Stmt::Dbg {
+            source_location,
+            source,
+            symbol,
+            variable,
+            remainder,
+        } => Stmt::Dbg {
+            source_location,
+            source,
+            symbol: *symbol,
+            variable: *variable,
+            remainder: arena.alloc(shrink_stmt(arena, shrunk, remainder)),
+        },
+        Stmt::Join {
+            id,
+            parameters,
+            body,
+            remainder,
+        } => Stmt::Join {
+            id: *id,
+            parameters,
+            body: arena.alloc(shrink_stmt(arena, shrunk, body)),
+            remainder: arena.alloc(shrink_stmt(arena, shrunk, remainder)),
+        },
+        Stmt::Ret(_) | Stmt::Jump(_, _) | Stmt::Crash(_, _) => stmt.clone(),
+    }
+}
+
+/// The report written by `--dead-field-report`, see the module docs for the format.
+pub fn dead_field_report<'a, I>(
+    dead_fields: &[DeadFields<'a>],
+    interner: &I,
+    interns: &Interns,
+) -> String
+where
+    I: LayoutInterner<'a>,
+{
+    let mut dead_fields: Vec<&DeadFields> = dead_fields.iter().collect();
+    sort_by_symbol_name(&mut dead_fields, interns, |dead| {
+        (dead.proc, (interner.dbg(dead.layout), dead.removed.clone()))
+    });
+
+    let mut buf = String::new();
+
+    writeln!(buf, "{}", ReportFormat::DeadFields.header()).unwrap();
+
+    for dead in dead_fields {
+        let module_name = interns.module_ids.get_name(dead.proc.module_id()).unwrap();
+        let names = interner.get_semantic(dead.layout).record_fields();
+        let field_layouts = match interner.get_repr(dead.layout) {
+            LayoutRepr::Struct(field_layouts) => field_layouts,
+            _ => &[],
+        };
+
+        let removed: Vec<String> = dead
+            .removed
+            .iter()
+            .map(|index| {
+                let layout = interner.dbg(field_layouts[*index]);
+
+                match names {
+                    Some(names) => format!("{} : {layout}", names[*index]),
+                    None => format!("#{index} : {layout}"),
+                }
+            })
+            .collect();
+
+        writeln!(
+            buf,
+            "{module_name}.{}\t{}\t{}\t{}",
+            dead.proc.as_str(interns),
+            interner.dbg(dead.layout),
+            removed.join(", "),
+            dead.bytes_saved
+        )
+        .unwrap();
+    }
+
+    buf
+}
+
+/// How many fields were removed, from how many records, and how many bytes that saved.
+pub fn dead_field_summary(dead_fields: &[DeadFields]) -> String {
+    let fields: usize = dead_fields.iter().map(|dead| dead.removed.len()).sum();
+    let bytes: u32 = dead_fields.iter().map(|dead| dead.bytes_saved).sum();
+
+    format!(
+        "Removed {fields} unread fields from {} records, which made them {bytes} bytes smaller in total",
+        dead_fields.len()
+    )
+}
@@ -1541,7 +1541,7 @@ fn low_level_no_rc(lowlevel: &LowLevel) -> RC {
         ListReplaceUnsafe => RC::Rc,
         StrGetUnsafe | ListGetUnsafe => RC::NoRc,
         ListConcat => RC::Rc,
-        StrConcat => RC::Rc,
+        StrConcat | StrConcatAmortized => RC::Rc,
         ListConcatUtf8 => RC::Rc,
         StrSubstringUnsafe => RC::Rc,
         StrReserve => RC::Rc,
@@ -1556,6 +1556,9 @@ fn low_level_no_rc(lowlevel: &LowLevel) -> RC {
         StrWithAsciiLowercased => RC::Rc,
         StrWithAsciiUppercased => RC::Rc,
         StrCaselessAsciiEquals => RC::NoRc,
+        StrWithUppercased => RC::Rc,
+        StrWithLowercased => RC::Rc,
+        StrCaselessEquals => RC::NoRc,
 
         ListAppendUnsafe
         | ListReserve
@@ -1584,6 +1587,7 @@ fn low_level_no_rc(lowlevel: &LowLevel) -> RC {
         | NumTan
         | NumSqrtUnchecked
         | NumLogUnchecked
+        | NumExpDec
         | NumRound
         | NumCeiling
         | NumFloor
@@ -1618,6 +1622,7 @@ fn low_level_no_rc(lowlevel: &LowLevel) -> RC {
 
         ListIsUnique => RC::Rc,
         ListClone => RC::Rc,
+        ListContainsNum => RC::NoRc,
 
         BoxExpr | UnboxExpr => {
             unreachable!("These lowlevel operations are turned into mono Expr's")
@@ -0,0 +1,88 @@
+//! Frame tables, which map the native functions of a compiled Roc program back to the defs
+//! they were generated from, so that a host can print a Roc-level stack trace when Roc crashes.
+//!
+//! The backends name the functions for procs as described in [crate::mangle]. A frame table has
+//! one line per top-level def, which has these two fields, separated by tabs:
+//!
+//! ```text
+//! {module}.{ident}
+//! {path}:{line}:{column}
+//! ```
+//!
+//! A host symbolicates a native stack frame by demangling its function's name, which gives the
+//! first field.
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use roc_collections::{MutMap, MutSet};
+use roc_module::symbol::{Interns, ModuleId, Symbol};
+use roc_region::all::{LineInfo, Region};
+
+use crate::report_format::ReportFormat;
+
+/// The file the frame table for the given executable or library is written to.
+pub fn frame_table_path(binary_path: &Path) -> PathBuf {
+    let mut path = binary_path.as_os_str().to_owned();
+
+    path.push(".roc-frames");
+
+    PathBuf::from(path)
+}
+
+/// Renders the frame table for the given procs. Procs that aren't for a top-level def
+/// (e.g. lifted closures and compiler-generated helpers) are left out.
+pub fn frame_table<'a>(
+    proc_symbols: impl Iterator<Item = &'a Symbol>,
+    def_regions: &MutMap<Symbol, Region>,
+    sources: &MutMap<ModuleId, (PathBuf, Box<str>)>,
+    interns: &Interns,
+) -> String {
+    let mut line_infos: MutMap<ModuleId, LineInfo> = MutMap::default();
+    let mut seen: MutSet<Symbol> = MutSet::default();
+    let mut lines = Vec::new();
+
+    for &symbol in proc_symbols {
+        let module_id = symbol.module_id();
+
+        let (Some(region), Some((path, src))) = (def_regions.get(&symbol), sources.get(&module_id))
+        else {
+            continue;
+        };
+
+        if !seen.insert(symbol) {
+            // Every specialization of a def has the same entry
+            continue;
+        }
+
+        let position = line_infos
+            .entry(module_id)
+            .or_insert_with(|| LineInfo::new(src))
+            .convert_pos(region.start());
+        let module_name = interns.module_ids.get_name(module_id).unwrap();
+        let location = format!(
+            "{}:{}:{}",
+            path.display(),
+            position.line + 1,
+            position.column + 1
+        );
+
+        // The same name [crate::mangle::demangle] gives for the def's functions
+        lines.push(format!(
+            "{module_name}.{}\t{location}",
+            symbol.as_str(interns)
+        ));
+    }
+
+    // The lines start with the names of the defs, so this sorts them by name.
+    lines.sort();
+
+    let mut buf = String::new();
+
+    writeln!(buf, "{}", ReportFormat::FrameTable.header()).unwrap();
+
+    for line in lines {
+        writeln!(buf, "{line}").unwrap();
+    }
+
+    buf
+}
@@ -14,6 +14,7 @@ use roc_error_macros::internal_error;
 use roc_module::low_level::LowLevel;
 use roc_module::{low_level::LowLevelWrapperType, symbol::Symbol};
 
+use crate::borrow::{BorrowInference, InferredBorrows};
 use crate::ir::ErasedField;
 use crate::{
     ir::{
@@ -26,15 +27,22 @@ use crate::{
 
 /**
 Insert the reference count operations for procedures.
+Returns the ownership of their parameters, as decided by borrow inference.
 */
 pub fn insert_inc_dec_operations<'a>(
     arena: &'a Bump,
     layout_interner: &STLayoutInterner<'a>,
     procedures: &mut HashMap<(Symbol, ProcLayout<'a>), Proc<'a>, BuildHasherDefault<WyHash>>,
-) {
-    let borrow_signatures =
-        crate::borrow::infer_borrow_signatures(arena, layout_interner, procedures);
+    borrow_inference: &BorrowInference,
+) -> InferredBorrows<'a> {
+    let borrow_signatures = crate::borrow::infer_borrow_signatures(
+        arena,
+        layout_interner,
+        procedures,
+        borrow_inference,
+    );
     let borrow_signatures = arena.alloc(borrow_signatures);
+    let inferred = borrow_signatures.inferred(arena, procedures);
 
     // All calls to lowlevels are wrapped in another function to help with type inference and return/parameter layouts.
     // But this lowlevel might get inlined into the caller of the wrapper and thus removing any reference counting operations.
@@ -50,6 +58,8 @@ pub fn insert_inc_dec_operations<'a>(
             insert_inc_dec_operations_proc(arena, symbol_rc_types_env, borrow_signatures, proc);
         }
     }
+
+    inferred
 }
 
 /// Enum indicating whether a symbol should be reference counted or not.
@@ -1237,7 +1247,7 @@ pub(crate) fn lowlevel_borrow_signature(op: LowLevel) -> &'static [Ownership] {
         ListReplaceUnsafe => &[OWNED, IRRELEVANT, IRRELEVANT],
         StrGetUnsafe | ListGetUnsafe => &[BORROWED, IRRELEVANT],
         ListConcat => &[OWNED, OWNED],
-        StrConcat => &[OWNED, BORROWED],
+        StrConcat | StrConcatAmortized => &[OWNED, BORROWED],
         ListConcatUtf8 => &[OWNED, BORROWED],
         StrSubstringUnsafe => &[OWNED, IRRELEVANT, IRRELEVANT],
         StrReserve => &[OWNED, IRRELEVANT],
@@ -1261,6 +1271,9 @@ pub(crate) fn lowlevel_borrow_signature(op: LowLevel) -> &'static [Ownership] {
         StrWithAsciiLowercased => &[OWNED],
         StrWithAsciiUppercased => &[OWNED],
         StrCaselessAsciiEquals => &[BORROWED, BORROWED],
+        StrWithUppercased => &[OWNED],
+        StrWithLowercased => &[OWNED],
+        StrCaselessEquals => &[BORROWED, BORROWED],
 
         Eq | NotEq => &[BORROWED, BORROWED],
 
@@ -1279,6 +1292,7 @@ pub(crate) fn lowlevel_borrow_signature(op: LowLevel) -> &'static [Ownership] {
         | NumTan
         | NumSqrtUnchecked
         | NumLogUnchecked
+        | NumExpDec
         | NumRound
         | NumCeiling
         | NumFloor
@@ -1313,6 +1327,7 @@ pub(crate) fn lowlevel_borrow_signature(op: LowLevel) -> &'static [Ownership] {
 
         ListIsUnique => &[BORROWED],
         ListClone => &[OWNED],
+        ListContainsNum => &[BORROWED, IRRELEVANT],
 
         BoxExpr | UnboxExpr => {
             unreachable!("These lowlevel operations are turned into mono Expr's")
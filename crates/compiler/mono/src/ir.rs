@@ -1,5 +1,6 @@
 #![allow(clippy::manual_map)]
 
+use crate::coverage::BranchRegions;
 use crate::ir::erased::{build_erased_function, ResolvedErasedLambda};
 use crate::ir::literal::{make_num_literal, IntOrFloatValue};
 use crate::layout::{
@@ -41,6 +42,7 @@ use ven_pretty::{text, BoxAllocator, DocAllocator, DocBuilder};
 
 use pattern::{from_can_pattern, store_pattern, Pattern};
 
+pub use decision_tree::{match_trees_to_dot, MatchTree};
 pub use literal::{ListLiteralElement, Literal};
 
 mod boxed;
@@ -48,6 +50,7 @@ mod decision_tree;
 mod erased;
 mod literal;
 mod pattern;
+pub mod text;
 
 #[inline(always)]
 pub fn pretty_print_ir_symbols() -> bool {
@@ -924,6 +927,12 @@ pub struct Procs<'a> {
     pub imported_module_thunks: &'a [Symbol],
     pub module_thunks: &'a [Symbol],
     pub host_exposed_symbols: &'a [Symbol],
+
+    /// The decision trees of the `when` expressions outside of the builtins, if they're wanted
+    pub match_trees: Option<std::vec::Vec<MatchTree>>,
+
+    /// Where the branches of switches come from in the source, if they're wanted for coverage
+    pub branch_regions: Option<BranchRegions>,
 }
 
 impl<'a> Procs<'a> {
@@ -942,6 +951,22 @@ impl<'a> Procs<'a> {
             imported_module_thunks: &[],
             module_thunks: &[],
             host_exposed_symbols: &[],
+
+            match_trees: None,
+            branch_regions: None,
+        }
+    }
+
+    /// Records where the branch for `tag` (or the default branch, for None) of a switch on
+    /// `cond_symbol` comes from. The first region recorded for a branch is kept.
+    pub(crate) fn record_branch_region(
+        &mut self,
+        cond_symbol: Symbol,
+        tag: Option<u64>,
+        region: Region,
+    ) {
+        if let Some(branch_regions) = self.branch_regions.as_mut() {
+            branch_regions.entry((cond_symbol, tag)).or_insert(region);
         }
     }
 
@@ -4061,8 +4086,11 @@ fn specialize_variable<'a>(
     procs.push_active_specialization(proc_name.name());
     roc_tracing::debug!(?proc_name, ?fn_var, fn_content = ?roc_types::subs::SubsFmtContent(env.subs.get_content_without_compacting(fn_var), env.subs), "specialization start");
 
-    let specialized =
-        specialize_proc_help(env, procs, proc_name, layout_cache, fn_var, partial_proc_id);
+    let specialized = {
+        let _span = roc_tracing::phase_span!("specialize", proc = ?DefName(env, proc_name.name()));
+
+        specialize_proc_help(env, procs, proc_name, layout_cache, fn_var, partial_proc_id)
+    };
 
     roc_tracing::debug!(
         ?proc_name,
@@ -4099,6 +4127,21 @@ fn specialize_variable<'a>(
     result
 }
 
+/// The name of a def in traces. Unlike the [Debug] of a [Symbol], this is readable in release
+/// builds too, for the defs of the module being specialized.
+struct DefName<'e, 'a, 'i>(&'e Env<'a, 'i>, Symbol);
+
+impl std::fmt::Debug for DefName<'_, '_, '_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let DefName(env, symbol) = self;
+
+        match env.ident_ids.get_name(symbol.ident_id()) {
+            Some(name) if symbol.module_id() == env.home => f.write_str(name),
+            _ => write!(f, "{symbol:?}"),
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct ProcLayout<'a> {
     pub arguments: &'a [InLayout<'a>],
@@ -4671,6 +4714,7 @@ pub fn with_hole<'a>(
                     if is_terminated {
                         let terminator = hole;
 
+                        let mut else_region = final_else.region;
                         let mut stmt = with_hole(
                             env,
                             final_else.value,
@@ -4684,6 +4728,7 @@ pub fn with_hole<'a>(
                         for (loc_cond, loc_then) in branches.into_iter().rev() {
                             let branching_symbol = env.unique_symbol();
 
+                            let then_region = loc_then.region;
                             let then = with_hole(
                                 env,
                                 loc_then.value,
@@ -4695,6 +4740,9 @@ pub fn with_hole<'a>(
                             );
 
                             stmt = cond(env, branching_symbol, cond_layout, then, stmt, ret_layout);
+                            procs.record_branch_region(branching_symbol, Some(1), then_region);
+                            procs.record_branch_region(branching_symbol, None, else_region);
+                            else_region = Region::span_across(&loc_cond.region, &else_region);
 
                             // add condition
                             stmt = with_hole(
@@ -4716,6 +4764,7 @@ pub fn with_hole<'a>(
                             .arena
                             .alloc(Stmt::Jump(id, env.arena.alloc([assigned_in_jump])));
 
+                        let mut else_region = final_else.region;
                         let mut stmt = with_hole(
                             env,
                             final_else.value,
@@ -4735,6 +4784,7 @@ pub fn with_hole<'a>(
                                 cond_var,
                             );
 
+                            let then_region = loc_then.region;
                             let then = with_hole(
                                 env,
                                 loc_then.value,
@@ -4746,6 +4796,9 @@ pub fn with_hole<'a>(
                             );
 
                             stmt = cond(env, branching_symbol, cond_layout, then, stmt, ret_layout);
+                            procs.record_branch_region(branching_symbol, Some(1), then_region);
+                            procs.record_branch_region(branching_symbol, None, else_region);
+                            else_region = Region::span_across(&loc_cond.region, &else_region);
 
                             // add condition
                             stmt = assign_to_symbol(
@@ -4786,7 +4839,7 @@ pub fn with_hole<'a>(
         When {
             cond_var,
             expr_var,
-            region: _,
+            region,
             loc_cond,
             branches,
             branches_cond_var: _,
@@ -4807,6 +4860,7 @@ pub fn with_hole<'a>(
                 cond_var,
                 expr_var,
                 cond_symbol,
+                region,
                 branches,
                 exhaustive,
                 layout_cache,
@@ -5974,6 +6028,21 @@ pub fn with_hole<'a>(
             )
         }
         RuntimeError(e) => runtime_error(env, env.arena.alloc(e.runtime_message())),
+        TypedHole { name, var: _ } => {
+            let msg = match name {
+                Some(name) => format!("Hit the typed hole ?{name}"),
+                None => "Hit a typed hole".to_string(),
+            };
+
+            let msg_sym = env.unique_symbol();
+
+            Stmt::Let(
+                msg_sym,
+                Expr::Literal(Literal::Str(env.arena.alloc(msg))),
+                Layout::STR,
+                env.arena.alloc(Stmt::Crash(msg_sym, CrashTag::User)),
+            )
+        }
         Crash { msg, ret_var: _ } => {
             let msg_sym = possible_reuse_symbol_or_specialize(
                 env,
@@ -7022,7 +7091,7 @@ pub fn from_can<'a>(
         When {
             cond_var,
             expr_var,
-            region: _,
+            region,
             loc_cond,
             branches,
             branches_cond_var: _,
@@ -7041,6 +7110,7 @@ pub fn from_can<'a>(
                 cond_var,
                 expr_var,
                 cond_symbol,
+                region,
                 branches,
                 exhaustive,
                 layout_cache,
@@ -7076,6 +7146,7 @@ pub fn from_can<'a>(
                 "invalid condition type in if expression"
             );
 
+            let mut else_region = final_else.region;
             let mut stmt = from_can(env, branch_var, final_else.value, procs, layout_cache);
 
             for (loc_cond, loc_then) in branches.into_iter().rev() {
@@ -7086,9 +7157,13 @@ pub fn from_can<'a>(
                     &loc_cond.value,
                     cond_var,
                 );
+                let then_region = loc_then.region;
                 let then = from_can(env, branch_var, loc_then.value, procs, layout_cache);
 
                 stmt = cond(env, branching_symbol, cond_layout, then, stmt, ret_layout);
+                procs.record_branch_region(branching_symbol, Some(1), then_region);
+                procs.record_branch_region(branching_symbol, None, else_region);
+                else_region = Region::span_across(&loc_cond.region, &else_region);
 
                 stmt = assign_to_symbol(
                     env,
@@ -7246,16 +7321,18 @@ fn store_specialized_expectation_lookups(
     }
 }
 
+/// The patterns, guards and bodies of the branches of the `when` at `region`.
 fn to_opt_branches<'a>(
     env: &mut Env<'a, '_>,
     procs: &mut Procs<'a>,
+    region: Region,
     branches: std::vec::Vec<roc_can::expr::WhenBranch>,
     exhaustive_mark: ExhaustiveMark,
     layout_cache: &mut LayoutCache<'a>,
 ) -> std::vec::Vec<(
     Pattern<'a>,
     Option<Loc<roc_can::expr::Expr>>,
-    roc_can::expr::Expr,
+    Loc<roc_can::expr::Expr>,
 )> {
     debug_assert!(!branches.is_empty());
 
@@ -7301,14 +7378,21 @@ fn to_opt_branches<'a>(
                     };
 
                     // TODO remove clone?
-                    opt_branches.push((mono_pattern, when_branch.guard.clone(), loc_expr.value));
+                    opt_branches.push((
+                        mono_pattern,
+                        when_branch.guard.clone(),
+                        Loc::at(when_branch.value.region, loc_expr.value),
+                    ));
                 }
                 Err(runtime_error) => {
                     // TODO remove clone?
                     opt_branches.push((
                         Pattern::Underscore,
                         when_branch.guard.clone(),
-                        roc_can::expr::Expr::RuntimeError(runtime_error),
+                        Loc::at(
+                            when_branch.value.region,
+                            roc_can::expr::Expr::RuntimeError(runtime_error),
+                        ),
                     ));
                 }
             }
@@ -7321,7 +7405,12 @@ fn to_opt_branches<'a>(
         opt_branches.push((
             Pattern::Underscore,
             None,
-            roc_can::expr::Expr::RuntimeError(roc_problem::can::RuntimeError::NonExhaustivePattern),
+            Loc::at(
+                region,
+                roc_can::expr::Expr::RuntimeError(
+                    roc_problem::can::RuntimeError::NonExhaustivePattern,
+                ),
+            ),
         ));
     }
 
@@ -7334,6 +7423,7 @@ fn from_can_when<'a>(
     cond_var: Variable,
     expr_var: Variable,
     cond_symbol: Symbol,
+    region: Region,
     branches: std::vec::Vec<roc_can::expr::WhenBranch>,
     exhaustive_mark: ExhaustiveMark,
     layout_cache: &mut LayoutCache<'a>,
@@ -7345,7 +7435,7 @@ fn from_can_when<'a>(
         // We can't know what to return!
         return runtime_error(env, "Hit a 0-branch when expression");
     }
-    let opt_branches = to_opt_branches(env, procs, branches, exhaustive_mark, layout_cache);
+    let opt_branches = to_opt_branches(env, procs, region, branches, exhaustive_mark, layout_cache);
 
     let cond_layout = return_on_layout_error!(
         env,
@@ -7360,9 +7450,15 @@ fn from_can_when<'a>(
     );
 
     let arena = env.arena;
+    let mut branch_regions = std::vec::Vec::with_capacity(opt_branches.len());
     let it = opt_branches
         .into_iter()
-        .filter_map(|(pattern, opt_guard, can_expr)| {
+        .filter_map(|(pattern, opt_guard, loc_can_expr)| {
+            let Loc {
+                region: branch_region,
+                value: can_expr,
+            } = loc_can_expr;
+
             // If the pattern has a void layout we can drop it; however, we must still perform the
             // work of building the body, because that may contain specializations we must
             // discover for use elsewhere. See
@@ -7413,6 +7509,7 @@ fn from_can_when<'a>(
                 procs.symbol_specializations = specialization_symbol_snapshot.unwrap();
                 None
             } else {
+                branch_regions.push(branch_region);
                 Some(result)
             }
         });
@@ -7423,6 +7520,8 @@ fn from_can_when<'a>(
         procs,
         layout_cache,
         cond_symbol,
+        region,
+        &branch_regions,
         cond_layout,
         ret_layout,
         mono_branches,
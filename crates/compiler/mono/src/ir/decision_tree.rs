@@ -14,7 +14,11 @@ use roc_error_macros::internal_error;
 use roc_exhaustive::{Ctor, CtorName, ListArity, RenderAs, TagId, Union};
 use roc_module::ident::TagName;
 use roc_module::low_level::LowLevel;
-use roc_module::symbol::Symbol;
+use roc_module::symbol::{Interns, ModuleId, Symbol};
+use roc_region::all::{LineInfo, Region};
+use roc_std::RocDec;
+use std::fmt::Write;
+use std::path::PathBuf;
 
 /// COMPILE CASES
 
@@ -1384,10 +1388,100 @@ enum Decider<'a, T> {
 
 #[derive(Clone, Debug, PartialEq)]
 enum Choice<'a> {
-    Inline(Stmt<'a>),
+    Inline(Label, Stmt<'a>),
     Jump(Label),
 }
 
+/// Where a `when` and its branches are, to record where the switches it compiles to come from.
+/// See [crate::coverage::BranchRegions].
+#[derive(Clone, Copy)]
+struct WhenRegions<'r> {
+    when: Region,
+    /// The region of each branch's body, indexed by its [Label]
+    branches: &'r [Region],
+}
+
+impl WhenRegions<'_> {
+    fn of(&self, decider: &Decider<'_, Choice<'_>>) -> Region {
+        match single_target(decider) {
+            Some(target) => self.branches[target as usize],
+            None => self.when,
+        }
+    }
+}
+
+/// The branch every path through `decider` leads to, if there is just one.
+fn single_target(decider: &Decider<'_, Choice<'_>>) -> Option<Label> {
+    use Decider::*;
+
+    match decider {
+        Leaf(Choice::Inline(target, _) | Choice::Jump(target)) => Some(*target),
+        Guarded {
+            success, failure, ..
+        }
+        | Chain {
+            success, failure, ..
+        } => {
+            let target = single_target(success)?;
+
+            (single_target(failure)? == target).then_some(target)
+        }
+        FanOut {
+            tests, fallback, ..
+        } => {
+            let target = single_target(fallback)?;
+
+            tests
+                .iter()
+                .all(|(_, decider)| single_target(decider) == Some(target))
+                .then_some(target)
+        }
+    }
+}
+
+/// The switch that `stmt` ends in, past the `let`s and join points that set it up.
+fn top_switch<'a>(mut stmt: &Stmt<'a>) -> Option<(Symbol, &'a [(u64, BranchInfo<'a>, Stmt<'a>)])> {
+    loop {
+        match stmt {
+            Stmt::Let(_, _, _, remainder) | Stmt::Join { remainder, .. } => stmt = remainder,
+            Stmt::Switch {
+                cond_symbol,
+                branches,
+                ..
+            } => return Some((*cond_symbol, branches)),
+            _ => return None,
+        }
+    }
+}
+
+/// Records the regions of the switches of a test chain, which are nested in the passing branches
+/// of each other. Stops at the switches of the branches of the chain, which were recorded before.
+fn record_test_chain(procs: &mut Procs, stmt: &Stmt, pass_region: Region, fail_region: Region) {
+    let mut opt_switch = top_switch(stmt);
+
+    while let Some((cond_symbol, branches)) = opt_switch {
+        let is_recorded = procs
+            .branch_regions
+            .as_ref()
+            .map_or(true, |regions| regions.contains_key(&(cond_symbol, None)));
+
+        if is_recorded {
+            break;
+        }
+
+        procs.record_branch_region(cond_symbol, None, fail_region);
+
+        for (tag, _, _) in branches.iter() {
+            procs.record_branch_region(cond_symbol, Some(*tag), pass_region);
+        }
+
+        opt_switch = match branches {
+            [(_, _, pass)] => top_switch(pass),
+            _ => None,
+        };
+    }
+}
+
 type StoresVec<'a> = bumpalo::collections::Vec<'a, (Symbol, InLayout<'a>, Expr<'a>)>;
 
 struct JumpSpec<'a> {
@@ -1406,6 +1500,8 @@ pub(crate) fn optimize_when<'a>(
     procs: &mut Procs<'a>,
     layout_cache: &mut LayoutCache<'a>,
     cond_symbol: Symbol,
+    region: Region,
+    branch_regions: &[Region],
     cond_layout: InLayout<'a>,
     ret_layout: InLayout<'a>,
     opt_branches: bumpalo::collections::Vec<'a, (Pattern<'a>, Guard<'a>, Stmt<'a>)>,
@@ -1429,6 +1525,24 @@ pub(crate) fn optimize_when<'a>(
     let mut target_counts = bumpalo::vec![in env.arena; 0; indexed_branches.len()];
     count_targets(&mut target_counts, &decider);
 
+    if procs.match_trees.is_some() {
+        let proc = procs.specialization_stack.0.last().copied();
+        let module = proc.map_or(env.home, |symbol| symbol.module_id());
+
+        if !module.is_builtin() {
+            let dot = decider_to_dot(&decider, &target_counts);
+
+            if let Some(match_trees) = procs.match_trees.as_mut() {
+                match_trees.push(MatchTree {
+                    module,
+                    proc,
+                    region,
+                    dot,
+                });
+            }
+        }
+    }
+
     let mut choices = MutMap::default();
     let mut jumps = Vec::new();
 
@@ -1495,7 +1609,7 @@ pub(crate) fn optimize_when<'a>(
         }
 
         let ((branch_index, choice), opt_jump) = if should_inline {
-            ((target, Choice::Inline(branch)), None)
+            ((target, Choice::Inline(target, branch)), None)
         } else {
             ((target, Choice::Jump(target)), Some((target, branch)))
         };
@@ -1515,6 +1629,10 @@ pub(crate) fn optimize_when<'a>(
     }
 
     let choice_decider = insert_choices(&choices, decider);
+    let regions = procs.branch_regions.is_some().then_some(WhenRegions {
+        when: region,
+        branches: branch_regions,
+    });
 
     let mut stmt = decide_to_branching(
         env,
@@ -1525,6 +1643,7 @@ pub(crate) fn optimize_when<'a>(
         ret_layout,
         choice_decider,
         &jumps,
+        regions,
     );
 
     for JumpSpec {
@@ -2053,6 +2172,7 @@ fn decide_to_branching<'a>(
     ret_layout: InLayout<'a>,
     decider: Decider<'a, Choice<'a>>,
     jumps: &[JumpSpec<'a>],
+    regions: Option<WhenRegions>,
 ) -> Stmt<'a> {
     use Choice::*;
     use Decider::*;
@@ -2067,7 +2187,7 @@ fn decide_to_branching<'a>(
 
             Stmt::Jump(jumps[index].id, jumps[index].jump_pattern_param_symbols)
         }
-        Leaf(Inline(expr)) => expr,
+        Leaf(Inline(_, expr)) => expr,
         Guarded {
             pattern,
             stmt_spec,
@@ -2078,6 +2198,11 @@ fn decide_to_branching<'a>(
             let test_symbol = env.unique_symbol();
             let arena = env.arena;
 
+            if let Some(regions) = regions {
+                procs.record_branch_region(test_symbol, Some(1), regions.of(&success));
+                procs.record_branch_region(test_symbol, None, regions.of(&failure));
+            }
+
             let pass_expr = decide_to_branching(
                 env,
                 procs,
@@ -2087,6 +2212,7 @@ fn decide_to_branching<'a>(
                 ret_layout,
                 *success,
                 jumps,
+                regions,
             );
 
             let fail_expr = decide_to_branching(
@@ -2098,6 +2224,7 @@ fn decide_to_branching<'a>(
                 ret_layout,
                 *failure,
                 jumps,
+                regions,
             );
 
             let decide = crate::ir::cond(
@@ -2134,6 +2261,8 @@ fn decide_to_branching<'a>(
             success,
             failure,
         } => {
+            let chain_regions = regions.map(|regions| (regions.of(&success), regions.of(&failure)));
+
             // generate a (nested) if-then-else
 
             let pass_expr = decide_to_branching(
@@ -2145,6 +2274,7 @@ fn decide_to_branching<'a>(
                 ret_layout,
                 *success,
                 jumps,
+                regions,
             );
 
             let fail_expr = decide_to_branching(
@@ -2156,6 +2286,7 @@ fn decide_to_branching<'a>(
                 ret_layout,
                 *failure,
                 jumps,
+                regions,
             );
 
             let chain_branch_info =
@@ -2174,7 +2305,7 @@ fn decide_to_branching<'a>(
             debug_assert!(number_of_tests > 0);
 
             let fail = env.arena.alloc(fail_expr);
-            if number_of_tests == 1 {
+            let stmt = if number_of_tests == 1 {
                 // if there is just one test, compile to a simple if-then-else
 
                 let (new_stores, (lhs, cmp, rhs), _cinfo) = tests.into_iter().next().unwrap();
@@ -2204,7 +2335,13 @@ fn decide_to_branching<'a>(
                     body: fail,
                     remainder: arena.alloc(test_stmt),
                 }
+            };
+
+            if let Some((pass_region, fail_region)) = chain_regions {
+                record_test_chain(procs, &stmt, pass_region, fail_region);
             }
+
+            stmt
         }
         FanOut {
             path,
@@ -2222,6 +2359,9 @@ fn decide_to_branching<'a>(
                 cond_layout,
             );
 
+            let fallback_region = regions.map(|regions| regions.of(&fallback));
+            let mut tag_regions = std::vec::Vec::new();
+
             let default_branch = decide_to_branching(
                 env,
                 procs,
@@ -2231,6 +2371,7 @@ fn decide_to_branching<'a>(
                 ret_layout,
                 *fallback,
                 jumps,
+                regions,
             );
 
             let mut branches = bumpalo::collections::Vec::with_capacity_in(tests.len(), env.arena);
@@ -2239,6 +2380,7 @@ fn decide_to_branching<'a>(
             let mut union_size: i64 = -1;
 
             for (test, decider) in tests {
+                let branch_region = regions.map(|regions| regions.of(&decider));
                 let branch = decide_to_branching(
                     env,
                     procs,
@@ -2248,6 +2390,7 @@ fn decide_to_branching<'a>(
                     ret_layout,
                     decider,
                     jumps,
+                    regions,
                 );
 
                 let tag = match test {
@@ -2266,6 +2409,10 @@ fn decide_to_branching<'a>(
                     Test::IsStr(_) => unreachable!("strings cannot be switched on"),
                 };
 
+                if let Some(region) = branch_region {
+                    tag_regions.push((tag, region));
+                }
+
                 // branch info is only useful for refcounted values
                 let branch_info = match test {
                     Test::IsCtor { tag_id, union, .. } => {
@@ -2372,6 +2519,16 @@ fn decide_to_branching<'a>(
                 switch = Stmt::Let(symbol, expr, layout, env.arena.alloc(switch));
             }
 
+            if let (Some(fallback_region), Some((cond_symbol, _))) =
+                (fallback_region, top_switch(&switch))
+            {
+                procs.record_branch_region(cond_symbol, None, fallback_region);
+
+                for (tag, region) in tag_regions {
+                    procs.record_branch_region(cond_symbol, Some(tag), region);
+                }
+            }
+
             // make a jump table based on the tests
             switch
         }
@@ -2669,6 +2826,284 @@ fn to_chain<'a>(
     }
 }
 
+/// The decision tree that a `when` expression was compiled to.
+#[derive(Clone, Debug)]
+pub struct MatchTree {
+    /// The module the `when` is written in
+    pub module: ModuleId,
+    /// The function the `when` is in, or `None` if it's in a top-level value
+    pub proc: Option<Symbol>,
+    pub region: Region,
+    /// The nodes and edges of the tree, as DOT statements
+    pub dot: String,
+}
+
+/// All of the trees as one DOT file, with a graph for each `when` that's named after where it is,
+/// e.g. `Main.parse (main.roc:12:5)`. A `when` in a function that was specialized more than once
+/// is drawn once for each distinct tree.
+pub fn match_trees_to_dot(
+    trees: &[MatchTree],
+    sources: &MutMap<ModuleId, (PathBuf, Box<str>)>,
+    interns: &Interns,
+) -> String {
+    let mut line_infos: MutMap<ModuleId, LineInfo> = MutMap::default();
+    let mut graphs: Vec<(String, u32, String, &str)> = Vec::with_capacity(trees.len());
+
+    for tree in trees {
+        let module_name = interns.module_ids.get_name(tree.module).unwrap().as_str();
+        let name = match tree.proc {
+            Some(symbol) => format!("{module_name}.{}", symbol.as_str(interns)),
+            None => module_name.to_string(),
+        };
+
+        let location = match sources.get(&tree.module) {
+            Some((path, src)) => {
+                let position = line_infos
+                    .entry(tree.module)
+                    .or_insert_with(|| LineInfo::new(src))
+                    .convert_pos(tree.region.start());
+                let file_name = path.file_name().unwrap_or(path.as_os_str());
+
+                format!(
+                    "{}:{}:{}",
+                    file_name.to_string_lossy(),
+                    position.line + 1,
+                    position.column + 1
+                )
+            }
+            None => format!("{:?}", tree.region),
+        };
+
+        graphs.push((
+            module_name.to_string(),
+            tree.region.start().offset,
+            format!("{name} ({location})"),
+            &tree.dot,
+        ));
+    }
+
+    // Trees come from every module's specializations, so sort them to make the output deterministic
+    graphs.sort();
+    graphs.dedup();
+
+    let mut buf = String::new();
+
+    for (_, _, name, dot) in graphs {
+        write!(
+            buf,
+            "digraph \"{}\" {{\n  node [fontname=\"monospace\"];\n{dot}}}\n",
+            escape_dot(&name)
+        )
+        .unwrap();
+    }
+
+    buf
+}
+
+/// Switches are drawn as hexagons, tests as diamonds, and the branches of the `when` as boxes.
+/// Tests in a chain are listed in the order they're checked in. A branch that can be reached in
+/// more than one way is compiled to a join point, which all of those ways jump to.
+fn decider_to_dot(decider: &Decider<u64>, target_counts: &[u64]) -> String {
+    let mut writer = DotWriter {
+        buf: String::new(),
+        next_node: 0,
+        target_counts,
+        drawn_targets: MutSet::default(),
+    };
+
+    writer.write_decider(decider);
+
+    writer.buf
+}
+
+struct DotWriter<'r> {
+    buf: String,
+    next_node: usize,
+    target_counts: &'r [u64],
+    drawn_targets: MutSet<u64>,
+}
+
+impl DotWriter<'_> {
+    /// Writes the node for `decider` and everything below it, and returns its name.
+    fn write_decider(&mut self, decider: &Decider<u64>) -> String {
+        match decider {
+            Decider::Leaf(target) => self.write_branch(*target),
+            Decider::Guarded {
+                success, failure, ..
+            } => {
+                let node = self.new_node();
+
+                writeln!(self.buf, "  {node} [shape=diamond, label=\"guard\"];").unwrap();
+                self.write_yes_no(&node, success, failure);
+
+                node
+            }
+            Decider::Chain {
+                test_chain,
+                success,
+                failure,
+            } => {
+                let node = self.new_node();
+
+                // The last test in the chain is the outermost `if`, so it's checked first
+                let tests: Vec<_> = test_chain
+                    .iter()
+                    .rev()
+                    .map(|(path, test)| escape_dot(&describe_test(path, test)))
+                    .collect();
+
+                writeln!(
+                    self.buf,
+                    "  {node} [shape=diamond, label=\"{}\"];",
+                    tests.join("\\n&& ")
+                )
+                .unwrap();
+                self.write_yes_no(&node, success, failure);
+
+                node
+            }
+            Decider::FanOut {
+                path,
+                tests,
+                fallback,
+            } => {
+                let node = self.new_node();
+
+                writeln!(
+                    self.buf,
+                    "  {node} [shape=hexagon, label=\"switch {}\"];",
+                    escape_dot(&describe_path(path))
+                )
+                .unwrap();
+
+                for (test, decider) in tests {
+                    let child = self.write_decider(decider);
+
+                    writeln!(
+                        self.buf,
+                        "  {node} -> {child} [label=\"{}\"];",
+                        escape_dot(&describe_case(test))
+                    )
+                    .unwrap();
+                }
+
+                let child = self.write_decider(fallback);
+
+                writeln!(self.buf, "  {node} -> {child} [label=\"_\", style=dashed];").unwrap();
+
+                node
+            }
+        }
+    }
+
+    fn new_node(&mut self) -> String {
+        let node = format!("n{}", self.next_node);
+        self.next_node += 1;
+
+        node
+    }
+
+    /// Each branch is drawn once, however many ways it can be reached.
+    fn write_branch(&mut self, target: u64) -> String {
+        let node = format!("b{target}");
+
+        if self.drawn_targets.insert(target) {
+            let join_point = if self.target_counts[target as usize] > 1 {
+                "\\n(join point)"
+            } else {
+                ""
+            };
+
+            writeln!(
+                self.buf,
+                "  {node} [shape=box, label=\"branch {}{join_point}\"];",
+                target + 1
+            )
+            .unwrap();
+        }
+
+        node
+    }
+
+    fn write_yes_no(&mut self, node: &str, success: &Decider<u64>, failure: &Decider<u64>) {
+        let success = self.write_decider(success);
+        writeln!(self.buf, "  {node} -> {success} [label=\"yes\"];").unwrap();
+
+        let failure = self.write_decider(failure);
+        writeln!(
+            self.buf,
+            "  {node} -> {failure} [label=\"no\", style=dashed];"
+        )
+        .unwrap();
+    }
+}
+
+/// e.g. `cond.0[1]` for the second element of a list in the first payload of a tag
+fn describe_path(path: &[PathInstruction]) -> String {
+    let mut buf = String::from("cond");
+
+    for instruction in path {
+        match instruction {
+            PathInstruction::NewType => {}
+            PathInstruction::TagIndex { index, .. } => write!(buf, ".{index}").unwrap(),
+            PathInstruction::ListIndex { index } => match index.offset() {
+                offset if offset >= 0 => write!(buf, "[{offset}]").unwrap(),
+                offset => write!(buf, "[len - {}]", -offset).unwrap(),
+            },
+        }
+    }
+
+    buf
+}
+
+fn describe_test(path: &[PathInstruction], test: &Test) -> String {
+    let path = describe_path(path);
+
+    match test {
+        Test::IsListLen { bound, len } => match bound {
+            ListLenBound::Exact => format!("List.len {path} == {len}"),
+            ListLenBound::AtLeast => format!("List.len {path} >= {len}"),
+        },
+        Test::IsCtor { ctor_name, .. } => format!("{path} is {}", describe_ctor(ctor_name)),
+        _ => format!("{path} == {}", describe_case(test)),
+    }
+}
+
+/// The value a switch compares with for this test
+fn describe_case(test: &Test) -> String {
+    match test {
+        Test::IsCtor { ctor_name, .. } => describe_ctor(ctor_name),
+        Test::IsInt(bytes, int_width) => {
+            if int_width.is_signed() {
+                i128::from_ne_bytes(*bytes).to_string()
+            } else {
+                u128::from_ne_bytes(*bytes).to_string()
+            }
+        }
+        Test::IsFloat(bits, _) => f64::from_bits(*bits).to_string(),
+        Test::IsDecimal(bytes) => RocDec::from_ne_bytes(*bytes).to_string(),
+        Test::IsStr(string) => format!("{string:?}"),
+        Test::IsBit(bit) => format!("Bool.{bit}"),
+        Test::IsByte { tag_id, .. } => format!("tag id {tag_id}"),
+        Test::IsListLen { bound, len } => match bound {
+            ListLenBound::Exact => format!("len {len}"),
+            ListLenBound::AtLeast => format!("len >= {len}"),
+        },
+    }
+}
+
+fn describe_ctor(ctor_name: &CtorName) -> String {
+    match ctor_name {
+        CtorName::Tag(tag_name) => tag_name.0.as_str().to_string(),
+        // Opaque types only have one constructor, so the test always succeeds and is never drawn
+        CtorName::Opaque(_) => "@opaque".to_string(),
+    }
+}
+
+fn escape_dot(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
 /// INSERT CHOICES
 ///
 /// If a target appears exactly once in a Decider, the corresponding expression
@@ -1382,6 +1382,11 @@ pub(crate) struct ListIndex(
 );
 
 impl ListIndex {
+    /// The index, counting back from the end of the list if it's negative.
+    pub fn offset(&self) -> i64 {
+        self.0
+    }
+
     pub fn from_pattern_index(index: usize, arity: ListArity) -> Self {
         match arity {
             ListArity::Exact(_) => Self(index as _),
@@ -0,0 +1,1051 @@
+//! The textual form of the specialized IR, as written by `roc build --emit-mono-ir` and used by
+//! the `test_mono` snapshots, and a parser that reads it back.
+//!
+//! The text is what [Proc::to_pretty] renders, with the procs sorted so that the output doesn't
+//! depend on hash map iteration order. It shows every statement and expression, and the layout
+//! of every `let`, but not everything the backends need: calls only name the proc they call, and
+//! not which specialization. So the parser produces the syntax tree defined here rather than
+//! [Proc]s, which is enough to write IR-level tests against, e.g. that a proc is tail recursive,
+//! or never increments a refcount. Rendering a parsed module gives back the exact same text.
+use std::fmt::{self, Write};
+
+use roc_module::symbol::Symbol;
+
+use crate::ir::Proc;
+use crate::layout::LayoutInterner;
+
+/// The width the IR is rendered with, which only affects how wide a line can get before the
+/// pretty printer breaks it. Nothing in the IR breaks, so this just has to be the same everywhere.
+const WIDTH: usize = 200;
+
+/// Renders procs in the textual form: sorted by their text, except that the proc named `last`
+/// (e.g. the main function of a test) comes at the end.
+pub fn render_procs<'a: 'p, 'p, I>(
+    procs: impl IntoIterator<Item = &'p Proc<'a>>,
+    interner: &I,
+    last: Option<Symbol>,
+) -> String
+where
+    I: LayoutInterner<'a>,
+{
+    let mut last_proc = None;
+    let mut rendered = Vec::new();
+
+    for proc in procs {
+        let text = proc.to_pretty(interner, WIDTH, false);
+
+        if last_proc.is_none() && Some(proc.name.name()) == last {
+            last_proc = Some(text);
+        } else {
+            rendered.push(text);
+        }
+    }
+
+    rendered.sort();
+    rendered.extend(last_proc);
+
+    rendered.join("\n")
+}
+
+/// A proc, e.g. `procedure Test.1 (Test.2, Test.3):` followed by its body.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextProc {
+    pub name: String,
+    pub args: Vec<String>,
+    pub body: TextStmt,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TextStmt {
+    /// `let Test.1 : U64 = CallByName Num.19 Test.2 Test.3;`
+    Let {
+        symbol: String,
+        /// The layout, as rendered by the layout interner, e.g. `{U64, Str}`.
+        layout: String,
+        expr: TextExpr,
+        cont: Box<TextStmt>,
+    },
+    /// `inc Test.1;`, `inc 2 Test.1;`, `dec Test.1;`, `decref Test.1;` or `free Test.1;`
+    Refcounting {
+        op: RefcountOp,
+        symbol: String,
+        cont: Box<TextStmt>,
+    },
+    /// `dbg Test.1;`
+    Dbg { symbol: String, cont: Box<TextStmt> },
+    /// `expect Test.1;`
+    Expect {
+        condition: String,
+        cont: Box<TextStmt>,
+    },
+    /// `ret Test.1;`
+    Ret(String),
+    /// A switch on a boolean, which has a branch for 1 and a default branch.
+    If {
+        cond: String,
+        then: Box<TextStmt>,
+        otherwise: Box<TextStmt>,
+    },
+    Switch {
+        cond: String,
+        branches: Vec<(u64, TextStmt)>,
+        default: Box<TextStmt>,
+    },
+    /// `Crash Test.1`
+    Crash(String),
+    /// `joinpoint Test.1 Test.2:` followed by its body, `in`, and the statement it's in scope for.
+    Join {
+        id: String,
+        params: Vec<String>,
+        body: Box<TextStmt>,
+        remainder: Box<TextStmt>,
+    },
+    /// `jump Test.1 Test.2;`
+    Jump { id: String, args: Vec<String> },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefcountOp {
+    Inc(u64),
+    Dec,
+    DecRef,
+    Free,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TextExpr {
+    Literal(TextLiteral),
+    CallByName {
+        name: String,
+        args: Vec<String>,
+    },
+    CallByPtr {
+        pointer: String,
+        args: Vec<String>,
+    },
+    /// A call to a lowlevel, or to a higher-order lowlevel like `ListSortWith { xs: ... }`.
+    LowLevel {
+        op: String,
+        args: Vec<String>,
+    },
+    Foreign {
+        name: String,
+        args: Vec<String>,
+    },
+    Tag {
+        tag_id: u16,
+        args: Vec<String>,
+    },
+    /// `Reuse Test.1 UpdateModeId { id: 2 } TagId(0) Test.3`
+    Reuse {
+        symbol: String,
+        update_mode: u32,
+        tag_id: u16,
+        args: Vec<String>,
+    },
+    Struct(Vec<String>),
+    NullPointer,
+    StructAtIndex {
+        index: u64,
+        structure: String,
+    },
+    GetTagId(String),
+    UnionAtIndex {
+        tag_id: u16,
+        index: u64,
+        structure: String,
+    },
+    GetElementPointer {
+        indices: Vec<u64>,
+        structure: String,
+    },
+    Array(Vec<TextArrayElem>),
+    ErasedMake {
+        value: Option<String>,
+        callee: String,
+    },
+    /// `ErasedLoad Test.1 .Callee`, where the field is `Value`, `ValuePtr` or `Callee`.
+    ErasedLoad {
+        symbol: String,
+        field: String,
+    },
+    FunctionPointer(String),
+    Alloca(Option<String>),
+    Reset {
+        symbol: String,
+        update_mode: u32,
+    },
+    ResetRef {
+        symbol: String,
+        update_mode: u32,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TextArrayElem {
+    Literal(TextLiteral),
+    Symbol(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TextLiteral {
+    Int(i128),
+    U128(u128),
+    Float(f64),
+    /// The decimal digits, e.g. `1.5`
+    Decimal(String),
+    Bool(bool),
+    Byte(u8),
+    Str(String),
+}
+
+impl TextProc {
+    /// The procs this one calls by name, in order of appearance.
+    pub fn callees(&self) -> Vec<&str> {
+        let mut callees = Vec::new();
+
+        self.body.walk(&mut |stmt| {
+            if let TextStmt::Let {
+                expr: TextExpr::CallByName { name, .. },
+                ..
+            } = stmt
+            {
+                callees.push(name.as_str());
+            }
+        });
+
+        callees
+    }
+}
+
+impl TextStmt {
+    /// Calls `f` on this statement and all the statements nested in it.
+    pub fn walk<'s>(&'s self, f: &mut impl FnMut(&'s TextStmt)) {
+        f(self);
+
+        match self {
+            TextStmt::Let { cont, .. }
+            | TextStmt::Refcounting { cont, .. }
+            | TextStmt::Dbg { cont, .. }
+            | TextStmt::Expect { cont, .. } => cont.walk(f),
+            TextStmt::If {
+                then, otherwise, ..
+            } => {
+                then.walk(f);
+                otherwise.walk(f);
+            }
+            TextStmt::Switch {
+                branches, default, ..
+            } => {
+                for (_, branch) in branches {
+                    branch.walk(f);
+                }
+
+                default.walk(f);
+            }
+            TextStmt::Join {
+                body, remainder, ..
+            } => {
+                body.walk(f);
+                remainder.walk(f);
+            }
+            TextStmt::Ret(_) | TextStmt::Crash(_) | TextStmt::Jump { .. } => {}
+        }
+    }
+}
+
+// Rendering
+
+/// Renders parsed procs the same way [render_procs] renders them, in the given order.
+pub fn render_text_procs(procs: &[TextProc]) -> String {
+    procs
+        .iter()
+        .map(|proc| proc.to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+impl fmt::Display for TextProc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "procedure {} ({}):", self.name, self.args.join(", "))?;
+        write!(f, "    ")?;
+        write_stmt(f, &self.body, 4)?;
+        writeln!(f)
+    }
+}
+
+fn newline(f: &mut impl Write, indent: usize) -> fmt::Result {
+    write!(f, "\n{:indent$}", "")
+}
+
+/// Writes a statement starting at the current position, which is at column `indent`.
+fn write_stmt(f: &mut impl Write, stmt: &TextStmt, indent: usize) -> fmt::Result {
+    match stmt {
+        TextStmt::Let {
+            symbol,
+            layout,
+            expr,
+            cont,
+        } => {
+            write!(f, "let {symbol} : {layout} = {expr};")?;
+            newline(f, indent)?;
+            write_stmt(f, cont, indent)
+        }
+        TextStmt::Refcounting { op, symbol, cont } => {
+            match op {
+                RefcountOp::Inc(1) => write!(f, "inc {symbol};")?,
+                RefcountOp::Inc(n) => write!(f, "inc {n} {symbol};")?,
+                RefcountOp::Dec => write!(f, "dec {symbol};")?,
+                RefcountOp::DecRef => write!(f, "decref {symbol};")?,
+                RefcountOp::Free => write!(f, "free {symbol};")?,
+            }
+            newline(f, indent)?;
+            write_stmt(f, cont, indent)
+        }
+        TextStmt::Dbg { symbol, cont } => {
+            write!(f, "dbg {symbol};")?;
+            newline(f, indent)?;
+            write_stmt(f, cont, indent)
+        }
+        TextStmt::Expect { condition, cont } => {
+            write!(f, "expect {condition};")?;
+            newline(f, indent)?;
+            write_stmt(f, cont, indent)
+        }
+        TextStmt::Ret(symbol) => write!(f, "ret {symbol};"),
+        TextStmt::If {
+            cond,
+            then,
+            otherwise,
+        } => {
+            write!(f, "if {cond} then")?;
+            newline(f, indent + 4)?;
+            write_stmt(f, then, indent + 4)?;
+            newline(f, indent)?;
+            write!(f, "else")?;
+            newline(f, indent + 4)?;
+            write_stmt(f, otherwise, indent + 4)
+        }
+        TextStmt::Switch {
+            cond,
+            branches,
+            default,
+        } => {
+            // Mirrors Stmt::to_doc, which separates the branches with a line that only has the
+            // indentation on it, and also ends the switch with one.
+            write!(f, "switch {cond}:")?;
+
+            for (tag, branch) in branches {
+                newline(f, indent + 4)?;
+                write!(f, "case {tag}:")?;
+                newline(f, indent + 8)?;
+                write_stmt(f, branch, indent + 8)?;
+                newline(f, indent)?;
+            }
+
+            newline(f, indent + 4)?;
+            write!(f, "default:")?;
+            newline(f, indent + 8)?;
+            write_stmt(f, default, indent + 8)?;
+            newline(f, indent)
+        }
+        TextStmt::Crash(symbol) => write!(f, "Crash {symbol}"),
+        TextStmt::Join {
+            id,
+            params,
+            body,
+            remainder,
+        } => {
+            write!(f, "joinpoint {id}")?;
+            write_args(f, params)?;
+            write!(f, ":")?;
+            newline(f, indent + 4)?;
+            write_stmt(f, body, indent + 4)?;
+            newline(f, indent)?;
+            write!(f, "in")?;
+            newline(f, indent)?;
+            write_stmt(f, remainder, indent)
+        }
+        TextStmt::Jump { id, args } => {
+            write!(f, "jump {id}")?;
+            write_args(f, args)?;
+            write!(f, ";")
+        }
+    }
+}
+
+/// Writes the arguments with a space before each of them.
+fn write_args(f: &mut impl Write, args: &[String]) -> fmt::Result {
+    for arg in args {
+        write!(f, " {arg}")?;
+    }
+
+    Ok(())
+}
+
+impl fmt::Display for TextExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextExpr::Literal(literal) => write!(f, "{literal}"),
+            TextExpr::CallByName { name, args } => {
+                write!(f, "CallByName {name}")?;
+                write_args(f, args)
+            }
+            TextExpr::CallByPtr { pointer, args } => {
+                write!(f, "CallByPtr {pointer}")?;
+                write_args(f, args)
+            }
+            // These always have a space after the name, even without arguments
+            TextExpr::LowLevel { op, args } => write!(f, "lowlevel {op} {}", args.join(" ")),
+            TextExpr::Foreign { name, args } => write!(f, "foreign {name:?} {}", args.join(" ")),
+            TextExpr::Tag { tag_id, args } => write!(f, "TagId({tag_id}) {}", args.join(" ")),
+            TextExpr::Reuse {
+                symbol,
+                update_mode,
+                tag_id,
+                args,
+            } => write!(
+                f,
+                "Reuse {symbol} UpdateModeId {{ id: {update_mode} }} TagId({tag_id}) {}",
+                args.join(" ")
+            ),
+            TextExpr::Struct(fields) => write!(f, "Struct {{{}}}", fields.join(", ")),
+            TextExpr::NullPointer => write!(f, "NullPointer"),
+            TextExpr::StructAtIndex { index, structure } => {
+                write!(f, "StructAtIndex {index} {structure}")
+            }
+            TextExpr::GetTagId(structure) => write!(f, "GetTagId {structure}"),
+            TextExpr::UnionAtIndex {
+                tag_id,
+                index,
+                structure,
+            } => write!(f, "UnionAtIndex (Id {tag_id}) (Index {index}) {structure}"),
+            TextExpr::GetElementPointer { indices, structure } => {
+                let indices: Vec<_> = indices.iter().map(|index| index.to_string()).collect();
+
+                write!(
+                    f,
+                    "GetElementPointer (Indices [{}]) {structure}",
+                    indices.join(", ")
+                )
+            }
+            TextExpr::Array(elems) => {
+                write!(f, "Array [")?;
+
+                for (i, elem) in elems.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+
+                    match elem {
+                        TextArrayElem::Literal(literal) => write!(f, "{literal}")?,
+                        TextArrayElem::Symbol(symbol) => write!(f, "{symbol}")?,
+                    }
+                }
+
+                write!(f, "]")
+            }
+            TextExpr::ErasedMake { value, callee } => write!(
+                f,
+                "ErasedMake {{ value: {}, callee: {callee} }}",
+                value.as_deref().unwrap_or("<null>")
+            ),
+            TextExpr::ErasedLoad { symbol, field } => write!(f, "ErasedLoad {symbol} .{field}"),
+            TextExpr::FunctionPointer(name) => write!(f, "FunctionPointer {name}"),
+            TextExpr::Alloca(initializer) => write!(
+                f,
+                "Alloca {}",
+                initializer.as_deref().unwrap_or("<uninitialized>")
+            ),
+            TextExpr::Reset {
+                symbol,
+                update_mode,
+            } => write!(
+                f,
+                "Reset {{ symbol: {symbol}, id: UpdateModeId {{ id: {update_mode} }} }}"
+            ),
+            TextExpr::ResetRef {
+                symbol,
+                update_mode,
+            } => write!(
+                f,
+                "ResetRef {{ symbol: {symbol}, id: UpdateModeId {{ id: {update_mode} }} }}"
+            ),
+        }
+    }
+}
+
+impl fmt::Display for TextLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextLiteral::Int(n) => write!(f, "{n}i64"),
+            TextLiteral::U128(n) => write!(f, "{n}u128"),
+            TextLiteral::Float(n) => write!(f, "{n}f64"),
+            TextLiteral::Decimal(digits) => write!(f, "{digits}dec"),
+            TextLiteral::Bool(b) => write!(f, "{b}"),
+            TextLiteral::Byte(b) => write!(f, "{b}u8"),
+            TextLiteral::Str(s) => write!(f, "{s:?}"),
+        }
+    }
+}
+
+// Parsing
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+type ParseResult<T> = Result<T, ParseError>;
+
+/// Parses the procs in the textual form, in order.
+pub fn parse(src: &str) -> ParseResult<Vec<TextProc>> {
+    let mut lines = Lines {
+        // The indentation is only there for readability; the keywords determine the structure.
+        lines: src
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty())
+            .collect(),
+        next: 0,
+    };
+    let mut procs = Vec::new();
+
+    while let Some((number, line)) = lines.next() {
+        let header = line
+            .strip_prefix("procedure ")
+            .and_then(|rest| rest.strip_suffix("):"))
+            .and_then(|rest| rest.split_once(" ("))
+            .ok_or_else(|| error(number, "expected `procedure <name> (<args>):`"))?;
+        let (name, args) = header;
+
+        procs.push(TextProc {
+            name: name.to_string(),
+            args: split_list(args, ", "),
+            body: lines.stmt()?,
+        });
+    }
+
+    Ok(procs)
+}
+
+fn error(line: usize, message: impl Into<String>) -> ParseError {
+    ParseError {
+        line,
+        message: message.into(),
+    }
+}
+
+fn split_list(list: &str, separator: &str) -> Vec<String> {
+    if list.is_empty() {
+        Vec::new()
+    } else {
+        list.split(separator).map(str::to_string).collect()
+    }
+}
+
+fn split_words(words: &str) -> Vec<String> {
+    words.split_whitespace().map(str::to_string).collect()
+}
+
+struct Lines<'s> {
+    lines: Vec<(usize, &'s str)>,
+    next: usize,
+}
+
+impl<'s> Lines<'s> {
+    fn next(&mut self) -> Option<(usize, &'s str)> {
+        let line = self.lines.get(self.next).copied();
+
+        self.next += 1;
+
+        line
+    }
+
+    fn expect(&mut self, expected: &str) -> ParseResult<()> {
+        match self.next() {
+            Some((_, line)) if line == expected => Ok(()),
+            Some((number, line)) => Err(error(
+                number,
+                format!("expected `{expected}`, found `{line}`"),
+            )),
+            None => Err(self.eof(expected)),
+        }
+    }
+
+    fn eof(&self, expected: &str) -> ParseError {
+        let last = self.lines.last().map_or(1, |(number, _)| *number);
+
+        error(
+            last,
+            format!("expected `{expected}`, found the end of the input"),
+        )
+    }
+
+    fn stmt(&mut self) -> ParseResult<TextStmt> {
+        let Some((number, line)) = self.next() else {
+            return Err(self.eof("a statement"));
+        };
+
+        let (keyword, rest) = line.split_once(' ').unwrap_or((line, ""));
+
+        let stmt = match keyword {
+            "let" => {
+                let rest = statement_body(number, rest)?;
+                let (symbol, rest) = rest
+                    .split_once(" : ")
+                    .ok_or_else(|| error(number, "expected `let <symbol> : <layout> = <expr>;`"))?;
+                // Layouts don't contain ` = `, but string literals in the expression might
+                let (layout, expr) = rest
+                    .split_once(" = ")
+                    .ok_or_else(|| error(number, "expected `let <symbol> : <layout> = <expr>;`"))?;
+
+                TextStmt::Let {
+                    symbol: symbol.to_string(),
+                    layout: layout.to_string(),
+                    expr: parse_expr(expr).map_err(|message| error(number, message))?,
+                    cont: Box::new(self.stmt()?),
+                }
+            }
+            "inc" | "dec" | "decref" | "free" => {
+                let rest = statement_body(number, rest)?;
+                let (op, symbol) = match (keyword, rest.split_once(' ')) {
+                    ("inc", Some((n, symbol))) => {
+                        let n = n
+                            .parse()
+                            .map_err(|_| error(number, format!("invalid increment `{n}`")))?;
+
+                        (RefcountOp::Inc(n), symbol)
+                    }
+                    ("inc", None) => (RefcountOp::Inc(1), rest),
+                    ("dec", _) => (RefcountOp::Dec, rest),
+                    ("decref", _) => (RefcountOp::DecRef, rest),
+                    _ => (RefcountOp::Free, rest),
+                };
+
+                TextStmt::Refcounting {
+                    op,
+                    symbol: symbol.to_string(),
+                    cont: Box::new(self.stmt()?),
+                }
+            }
+            "dbg" => TextStmt::Dbg {
+                symbol: statement_body(number, rest)?.to_string(),
+                cont: Box::new(self.stmt()?),
+            },
+            "expect" => TextStmt::Expect {
+                condition: statement_body(number, rest)?.to_string(),
+                cont: Box::new(self.stmt()?),
+            },
+            "ret" => TextStmt::Ret(statement_body(number, rest)?.to_string()),
+            "jump" => {
+                let mut words = split_words(statement_body(number, rest)?);
+
+                if words.is_empty() {
+                    return Err(error(number, "expected `jump <joinpoint> <args>;`"));
+                }
+
+                TextStmt::Jump {
+                    id: words.remove(0),
+                    args: words,
+                }
+            }
+            "Crash" => TextStmt::Crash(rest.to_string()),
+            "if" => {
+                let cond = rest
+                    .strip_suffix(" then")
+                    .ok_or_else(|| error(number, "expected `if <symbol> then`"))?;
+                let then = self.stmt()?;
+
+                self.expect("else")?;
+
+                TextStmt::If {
+                    cond: cond.to_string(),
+                    then: Box::new(then),
+                    otherwise: Box::new(self.stmt()?),
+                }
+            }
+            "switch" => {
+                let cond = rest
+                    .strip_suffix(':')
+                    .ok_or_else(|| error(number, "expected `switch <symbol>:`"))?;
+                let mut branches = Vec::new();
+
+                loop {
+                    match self.next() {
+                        Some((_, "default:")) => break,
+                        Some((number, line)) => {
+                            let tag = line
+                                .strip_prefix("case ")
+                                .and_then(|rest| rest.strip_suffix(':'))
+                                .and_then(|tag| tag.parse().ok())
+                                .ok_or_else(|| {
+                                    error(
+                                        number,
+                                        format!("expected `case <number>:`, found `{line}`"),
+                                    )
+                                })?;
+
+                            branches.push((tag, self.stmt()?));
+                        }
+                        None => return Err(self.eof("default:")),
+                    }
+                }
+
+                TextStmt::Switch {
+                    cond: cond.to_string(),
+                    branches,
+                    default: Box::new(self.stmt()?),
+                }
+            }
+            "joinpoint" => {
+                let header = rest
+                    .strip_suffix(':')
+                    .ok_or_else(|| error(number, "expected `joinpoint <id> <params>:`"))?;
+                let mut words = split_words(header);
+
+                if words.is_empty() {
+                    return Err(error(number, "expected `joinpoint <id> <params>:`"));
+                }
+
+                let id = words.remove(0);
+                let body = self.stmt()?;
+
+                self.expect("in")?;
+
+                TextStmt::Join {
+                    id,
+                    params: words,
+                    body: Box::new(body),
+                    remainder: Box::new(self.stmt()?),
+                }
+            }
+            _ => {
+                return Err(error(
+                    number,
+                    format!("expected a statement, found `{line}`"),
+                ))
+            }
+        };
+
+        Ok(stmt)
+    }
+}
+
+fn statement_body(number: usize, rest: &str) -> ParseResult<&str> {
+    rest.strip_suffix(';')
+        .ok_or_else(|| error(number, "expected the statement to end with `;`"))
+}
+
+fn parse_expr(src: &str) -> Result<TextExpr, String> {
+    let (head, rest) = src.split_once(' ').unwrap_or((src, ""));
+
+    let expr = match head {
+        "CallByName" | "CallByPtr" => {
+            let mut words = split_words(rest);
+
+            if words.is_empty() {
+                return Err(format!("expected the function of `{src}`"));
+            }
+
+            let function = words.remove(0);
+
+            if head == "CallByName" {
+                TextExpr::CallByName {
+                    name: function,
+                    args: words,
+                }
+            } else {
+                TextExpr::CallByPtr {
+                    pointer: function,
+                    args: words,
+                }
+            }
+        }
+        "lowlevel" => {
+            // Higher-order lowlevels have fields, e.g. `ListSortWith { xs: ... }`
+            let (op, args) = match rest.split_once(" } ") {
+                Some((op, args)) if op.contains(" { ") => (format!("{op} }}"), args),
+                _ => {
+                    let (op, args) = rest.split_once(' ').unwrap_or((rest, ""));
+
+                    (op.to_string(), args)
+                }
+            };
+
+            TextExpr::LowLevel {
+                op,
+                args: split_words(args),
+            }
+        }
+        "foreign" => {
+            let (name, args) = parse_str_literal(rest)?;
+
+            TextExpr::Foreign {
+                name,
+                args: split_words(args),
+            }
+        }
+        "Reuse" => {
+            let words = split_words(rest);
+
+            match words.as_slice() {
+                [symbol, update_mode_id, open, id, update_mode, close, tag, args @ ..]
+                    if update_mode_id == "UpdateModeId"
+                        && open == "{"
+                        && id == "id:"
+                        && close == "}" =>
+                {
+                    TextExpr::Reuse {
+                        symbol: symbol.clone(),
+                        update_mode: parse_number(update_mode)?,
+                        tag_id: parse_tag_id(tag)?,
+                        args: args.to_vec(),
+                    }
+                }
+                _ => return Err(format!("invalid reuse `{src}`")),
+            }
+        }
+        "Reset" | "ResetRef" => {
+            let (symbol, update_mode) = rest
+                .strip_prefix("{ symbol: ")
+                .and_then(|rest| rest.strip_suffix(" } }"))
+                .and_then(|rest| rest.split_once(", id: UpdateModeId { id: "))
+                .ok_or_else(|| format!("invalid reset `{src}`"))?;
+            let symbol = symbol.to_string();
+            let update_mode = parse_number(update_mode)?;
+
+            if head == "Reset" {
+                TextExpr::Reset {
+                    symbol,
+                    update_mode,
+                }
+            } else {
+                TextExpr::ResetRef {
+                    symbol,
+                    update_mode,
+                }
+            }
+        }
+        "Struct" => {
+            let fields = rest
+                .strip_prefix('{')
+                .and_then(|rest| rest.strip_suffix('}'))
+                .ok_or_else(|| format!("invalid struct `{src}`"))?;
+
+            TextExpr::Struct(split_list(fields, ", "))
+        }
+        "NullPointer" => TextExpr::NullPointer,
+        "StructAtIndex" => {
+            let (index, structure) = rest
+                .split_once(' ')
+                .ok_or_else(|| format!("invalid struct access `{src}`"))?;
+
+            TextExpr::StructAtIndex {
+                index: parse_number(index)?,
+                structure: structure.to_string(),
+            }
+        }
+        "GetTagId" => TextExpr::GetTagId(rest.to_string()),
+        "UnionAtIndex" => {
+            let (tag_id, index, structure) = rest
+                .strip_prefix("(Id ")
+                .and_then(|rest| rest.split_once(") (Index "))
+                .and_then(|(tag_id, rest)| {
+                    let (index, structure) = rest.split_once(") ")?;
+
+                    Some((tag_id, index, structure))
+                })
+                .ok_or_else(|| format!("invalid union access `{src}`"))?;
+
+            TextExpr::UnionAtIndex {
+                tag_id: parse_number(tag_id)?,
+                index: parse_number(index)?,
+                structure: structure.to_string(),
+            }
+        }
+        "GetElementPointer" => {
+            let (indices, structure) = rest
+                .strip_prefix("(Indices [")
+                .and_then(|rest| rest.split_once("]) "))
+                .ok_or_else(|| format!("invalid element pointer `{src}`"))?;
+
+            TextExpr::GetElementPointer {
+                indices: split_list(indices, ", ")
+                    .iter()
+                    .map(|index| parse_number(index))
+                    .collect::<Result<_, _>>()?,
+                structure: structure.to_string(),
+            }
+        }
+        "Array" => {
+            let mut elems = Vec::new();
+            let mut rest = rest
+                .strip_prefix('[')
+                .and_then(|rest| rest.strip_suffix(']'))
+                .ok_or_else(|| format!("invalid array `{src}`"))?;
+
+            while !rest.is_empty() {
+                let (elem, after) = if rest.starts_with('"') {
+                    let (s, after) = parse_str_literal(rest)?;
+
+                    (TextArrayElem::Literal(TextLiteral::Str(s)), after)
+                } else {
+                    let (elem, after) = rest.split_once(", ").unwrap_or((rest, ""));
+
+                    let elem = match parse_literal(elem) {
+                        Some(literal) => TextArrayElem::Literal(literal),
+                        None => TextArrayElem::Symbol(elem.to_string()),
+                    };
+
+                    (elem, after)
+                };
+
+                elems.push(elem);
+                rest = after.strip_prefix(", ").unwrap_or(after);
+            }
+
+            TextExpr::Array(elems)
+        }
+        "ErasedMake" => {
+            let (value, callee) = rest
+                .strip_prefix("{ value: ")
+                .and_then(|rest| rest.strip_suffix(" }"))
+                .and_then(|rest| rest.split_once(", callee: "))
+                .ok_or_else(|| format!("invalid erased value `{src}`"))?;
+
+            TextExpr::ErasedMake {
+                value: (value != "<null>").then(|| value.to_string()),
+                callee: callee.to_string(),
+            }
+        }
+        "ErasedLoad" => {
+            let (symbol, field) = rest
+                .split_once(" .")
+                .ok_or_else(|| format!("invalid erased load `{src}`"))?;
+
+            TextExpr::ErasedLoad {
+                symbol: symbol.to_string(),
+                field: field.to_string(),
+            }
+        }
+        "FunctionPointer" => TextExpr::FunctionPointer(rest.to_string()),
+        "Alloca" => TextExpr::Alloca((rest != "<uninitialized>").then(|| rest.to_string())),
+        _ if head.starts_with("TagId(") => TextExpr::Tag {
+            tag_id: parse_tag_id(head)?,
+            args: split_words(rest),
+        },
+        _ if src.starts_with('"') => {
+            let (s, rest) = parse_str_literal(src)?;
+
+            if !rest.is_empty() {
+                return Err(format!("unexpected `{rest}` after a string literal"));
+            }
+
+            TextExpr::Literal(TextLiteral::Str(s))
+        }
+        _ => match parse_literal(src) {
+            Some(literal) => TextExpr::Literal(literal),
+            None => return Err(format!("expected an expression, found `{src}`")),
+        },
+    };
+
+    Ok(expr)
+}
+
+fn parse_number<T: std::str::FromStr>(src: &str) -> Result<T, String> {
+    src.parse()
+        .map_err(|_| format!("expected a number, found `{src}`"))
+}
+
+fn parse_tag_id(src: &str) -> Result<u16, String> {
+    src.strip_prefix("TagId(")
+        .and_then(|rest| rest.strip_suffix(')'))
+        .ok_or_else(|| format!("expected `TagId(<number>)`, found `{src}`"))
+        .and_then(parse_number)
+}
+
+/// Parses a literal other than a string.
+fn parse_literal(src: &str) -> Option<TextLiteral> {
+    let literal = match src {
+        "true" => TextLiteral::Bool(true),
+        "false" => TextLiteral::Bool(false),
+        _ => {
+            if let Some(n) = src.strip_suffix("i64") {
+                TextLiteral::Int(n.parse().ok()?)
+            } else if let Some(n) = src.strip_suffix("u128") {
+                TextLiteral::U128(n.parse().ok()?)
+            } else if let Some(n) = src.strip_suffix("u8") {
+                TextLiteral::Byte(n.parse().ok()?)
+            } else if let Some(n) = src.strip_suffix("f64") {
+                TextLiteral::Float(n.parse().ok()?)
+            } else if let Some(digits) = src.strip_suffix("dec") {
+                digits.parse::<f64>().ok()?;
+
+                TextLiteral::Decimal(digits.to_string())
+            } else {
+                return None;
+            }
+        }
+    };
+
+    Some(literal)
+}
+
+/// Parses a string literal in Rust's debug format at the start of `src`, and returns it along
+/// with the rest of `src`.
+fn parse_str_literal(src: &str) -> Result<(String, &str), String> {
+    let mut chars = src
+        .strip_prefix('"')
+        .ok_or_else(|| format!("expected a string literal, found `{src}`"))?
+        .char_indices();
+    let mut string = String::new();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Ok((string, src[i + 2..].trim_start())),
+            '\\' => {
+                let escaped = match chars.next().map(|(_, c)| c) {
+                    Some('n') => '\n',
+                    Some('r') => '\r',
+                    Some('t') => '\t',
+                    Some('0') => '\0',
+                    Some(c @ ('\\' | '"' | '\'')) => c,
+                    Some('u') => {
+                        let mut hex = String::new();
+
+                        for (_, c) in chars.by_ref() {
+                            match c {
+                                '{' => {}
+                                '}' => break,
+                                _ => hex.push(c),
+                            }
+                        }
+
+                        u32::from_str_radix(&hex, 16)
+                            .ok()
+                            .and_then(char::from_u32)
+                            .ok_or_else(|| format!("invalid unicode escape in `{src}`"))?
+                    }
+                    _ => return Err(format!("invalid escape in `{src}`")),
+                };
+
+                string.push(escaped);
+            }
+            _ => string.push(c),
+        }
+    }
+
+    Err(format!("unterminated string literal `{src}`"))
+}
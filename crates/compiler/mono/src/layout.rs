@@ -26,6 +26,7 @@ use std::collections::HashMap;
 use std::hash::Hash;
 use ven_pretty::{DocAllocator, DocBuilder};
 
+pub mod describe;
 mod erased;
 mod intern;
 mod semantic;
@@ -1352,6 +1353,12 @@ impl<'a> Niche<'a> {
         let NichePriv::Captures(caps) = &self.0;
         interner.dbg_stable_iter(caps)
     }
+
+    /// The layouts of the values captured by the lambda this niche is for.
+    pub(crate) fn captures(&self) -> &'a [InLayout<'a>] {
+        let NichePriv::Captures(caps) = self.0;
+        caps
+    }
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
@@ -1378,6 +1385,14 @@ impl<'a> LambdaName<'a> {
         self.niche
     }
 
+    /// The layouts of the values this lambda captures, if it's part of a lambda set.
+    #[inline(always)]
+    pub(crate) fn captures(&self) -> &'a [InLayout<'a>] {
+        match self.niche.0 {
+            NichePriv::Captures(captures) => captures,
+        }
+    }
+
     #[inline(always)]
     pub(crate) fn no_captures(&self) -> bool {
         match self.niche.0 {
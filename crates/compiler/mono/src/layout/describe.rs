@@ -0,0 +1,453 @@
+//! Descriptions of how the values of a layout are laid out in memory: their size and alignment,
+//! the offsets of their fields, and how tag unions store which tag they are.
+//!
+//! These are for code that reads or writes Roc values from outside of Roc, like hosts and glue
+//! generators, so they describe the memory a value occupies rather than the Roc type it has.
+
+use std::fmt;
+
+use super::{
+    round_up_to_alignment, Builtin, Discriminant, Erased, InLayout, LayoutInterner, LayoutRepr,
+    UnionLayout,
+};
+
+/// How the values of a layout are laid out in memory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayoutDescription {
+    /// The size of a value in bytes, including the padding at its end
+    pub size: u32,
+    pub alignment: u32,
+    pub shape: Shape,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Shape {
+    /// A number or a `Bool`, named like its Roc type, e.g. `U8` or `Dec`
+    Number(&'static str),
+    /// A tag union without payloads, stored as the id of its tag
+    Enum {
+        tags: Vec<String>,
+    },
+    Str,
+    /// A pointer to the elements, the length, and the capacity
+    List {
+        element: Box<LayoutDescription>,
+    },
+    /// Fields stored one after another, largest alignment first. Records, tuples, and the
+    /// values a closure captures are all structs.
+    Struct {
+        fields: Vec<Field>,
+    },
+    TagUnion {
+        tags: Vec<Tag>,
+        discriminant: TagDiscriminant,
+        /// The tags are stored on the heap, and the value is a pointer to them. Recursive tag
+        /// unions are always stored this way.
+        on_heap: bool,
+    },
+    /// A pointer to a value on the heap, like the contents of a `Box`
+    Pointer {
+        pointee: Box<LayoutDescription>,
+    },
+    /// A pointer back to the recursive tag union this is in
+    RecursivePointer,
+    FunctionPointer,
+    /// A closure whose type is erased: a pointer to its captures and to its function
+    Erased,
+}
+
+/// A field of a struct or of a tag's payload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field {
+    /// The name of the field, for the fields of records
+    pub name: Option<String>,
+    /// Where the field starts, in bytes from the start of the struct or payload
+    pub offset: u32,
+    pub layout: LayoutDescription,
+}
+
+/// A tag of a tag union, along with its payload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tag {
+    pub id: u16,
+    /// The name of the tag, when it can be told from the layout
+    pub name: Option<String>,
+    /// This tag is stored as the null pointer, and has no payload
+    pub is_null: bool,
+    pub fields: Vec<Field>,
+}
+
+/// Where a tag union stores the id of its tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TagDiscriminant {
+    /// There is only one tag (besides the one for the null pointer, if any), so there's nothing
+    /// to store
+    None,
+    /// An unsigned integer of `size` bytes, `offset` bytes after the start of the payload
+    Stored { offset: u32, size: u32 },
+    /// The lowest `bits` bits of the pointer to the payload, which are always zero because of
+    /// the alignment of the payload
+    PointerBits { bits: u32 },
+}
+
+/// Describe how the values of `layout` are laid out in memory.
+pub fn describe_layout<'a, I>(interner: &I, layout: InLayout<'a>) -> LayoutDescription
+where
+    I: LayoutInterner<'a>,
+{
+    let (size, alignment) = interner.stack_size_and_alignment(layout);
+    let semantic = interner.get_semantic(layout);
+
+    let shape = match interner.get_repr(layout) {
+        LayoutRepr::Builtin(builtin) => match builtin {
+            Builtin::Int(_) | Builtin::Bool => match semantic.tag_names() {
+                Some(tags) => Shape::Enum {
+                    tags: tags.iter().map(|tag| tag.to_string()).collect(),
+                },
+                None => Shape::Number(number_name(builtin)),
+            },
+            Builtin::Float(_) | Builtin::Decimal => Shape::Number(number_name(builtin)),
+            Builtin::Str => Shape::Str,
+            Builtin::List(element) => Shape::List {
+                element: Box::new(describe_layout(interner, element)),
+            },
+        },
+        LayoutRepr::Struct(field_layouts) => Shape::Struct {
+            fields: describe_fields(interner, field_layouts, semantic.record_fields()),
+        },
+        LayoutRepr::Ptr(pointee) => Shape::Pointer {
+            pointee: Box::new(describe_layout(interner, pointee)),
+        },
+        LayoutRepr::Union(union_layout) => {
+            describe_union(interner, union_layout, semantic.tag_names())
+        }
+        LayoutRepr::LambdaSet(lambda_set) => {
+            // At runtime, a closure is just the values it captures
+            return describe_layout(interner, lambda_set.runtime_representation());
+        }
+        LayoutRepr::RecursivePointer(_) => Shape::RecursivePointer,
+        LayoutRepr::FunctionPointer(_) => Shape::FunctionPointer,
+        LayoutRepr::Erased(Erased) => Shape::Erased,
+    };
+
+    LayoutDescription {
+        size,
+        alignment,
+        shape,
+    }
+}
+
+fn number_name(builtin: Builtin<'_>) -> &'static str {
+    match builtin {
+        Builtin::Int(width) => width.type_name(),
+        Builtin::Float(width) => width.type_name(),
+        Builtin::Bool => "Bool",
+        Builtin::Decimal => "Dec",
+        Builtin::Str | Builtin::List(_) => unreachable!("{builtin:?} is not a number"),
+    }
+}
+
+fn describe_fields<'a, I>(
+    interner: &I,
+    field_layouts: &[InLayout<'a>],
+    names: Option<&[&str]>,
+) -> Vec<Field>
+where
+    I: LayoutInterner<'a>,
+{
+    let names = names.filter(|names| names.len() == field_layouts.len());
+    let mut offset = 0;
+
+    field_layouts
+        .iter()
+        .enumerate()
+        .map(|(index, field_layout)| {
+            let layout = describe_layout(interner, *field_layout);
+
+            offset = round_up_to_alignment(offset, layout.alignment);
+            let field = Field {
+                name: names.map(|names| names[index].to_string()),
+                offset,
+                layout,
+            };
+            offset += field.layout.size;
+
+            field
+        })
+        .collect()
+}
+
+fn describe_union<'a, I>(
+    interner: &I,
+    union_layout: UnionLayout<'a>,
+    names: Option<&[&str]>,
+) -> Shape
+where
+    I: LayoutInterner<'a>,
+{
+    let target = interner.target();
+    let number_of_tags = match union_layout {
+        UnionLayout::NonRecursive(tags) | UnionLayout::Recursive(tags) => tags.len(),
+        UnionLayout::NonNullableUnwrapped(_) => 1,
+        UnionLayout::NullableWrapped { other_tags, .. } => other_tags.len() + 1,
+        UnionLayout::NullableUnwrapped { .. } => 2,
+    };
+    let names = names.filter(|names| names.len() == number_of_tags);
+
+    let discriminant = if union_layout.stores_tag_id_in_pointer(target) {
+        let (bits, _) = UnionLayout::tag_id_pointer_bits_and_mask(target);
+
+        TagDiscriminant::PointerBits { bits: bits as u32 }
+    } else {
+        let size = match union_layout.discriminant() {
+            Discriminant::U0 => 0,
+            Discriminant::U1 | Discriminant::U8 => 1,
+            Discriminant::U16 => 2,
+        };
+
+        match union_layout.tag_id_offset(interner) {
+            Some(offset) if size > 0 && union_layout.stores_tag_id_as_data(target) => {
+                TagDiscriminant::Stored { offset, size }
+            }
+            _ => TagDiscriminant::None,
+        }
+    };
+
+    let tag = |id: usize, fields: &[InLayout<'a>]| Tag {
+        id: id as u16,
+        name: names.map(|names| names[id].to_string()),
+        is_null: false,
+        fields: describe_fields(interner, fields, None),
+    };
+    let null_tag = |id: usize| Tag {
+        id: id as u16,
+        name: names.map(|names| names[id].to_string()),
+        is_null: true,
+        fields: Vec::new(),
+    };
+
+    let tags = match union_layout {
+        UnionLayout::NonRecursive(tags) | UnionLayout::Recursive(tags) => tags
+            .iter()
+            .enumerate()
+            .map(|(id, fields)| tag(id, fields))
+            .collect(),
+        UnionLayout::NonNullableUnwrapped(fields) => vec![tag(0, fields)],
+        UnionLayout::NullableWrapped {
+            nullable_id,
+            other_tags,
+        } => {
+            let nullable_id = nullable_id as usize;
+            let mut other_tags = other_tags.iter();
+
+            (0..=other_tags.len())
+                .map(|id| {
+                    if id == nullable_id {
+                        null_tag(id)
+                    } else {
+                        tag(id, other_tags.next().unwrap())
+                    }
+                })
+                .collect()
+        }
+        UnionLayout::NullableUnwrapped {
+            nullable_id,
+            other_fields,
+        } => {
+            let nullable_id = nullable_id as usize;
+            let other_id = 1 - nullable_id;
+
+            let mut tags = vec![null_tag(nullable_id), tag(other_id, other_fields)];
+            tags.sort_by_key(|tag| tag.id);
+
+            tags
+        }
+    };
+
+    Shape::TagUnion {
+        tags,
+        discriminant,
+        on_heap: union_layout.is_recursive(),
+    }
+}
+
+impl fmt::Display for LayoutDescription {
+    /// An indented outline of the layout, e.g.
+    ///
+    /// ```text
+    /// struct (32 bytes, aligned to 8)
+    ///     offset 0, name: Str (24 bytes, aligned to 8)
+    ///     offset 24, age: U8 (1 byte, aligned to 1)
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_indented(f, 0)
+    }
+}
+
+impl LayoutDescription {
+    fn fmt_indented(&self, f: &mut fmt::Formatter<'_>, indent: usize) -> fmt::Result {
+        let bytes = if self.size == 1 { "byte" } else { "bytes" };
+
+        match &self.shape {
+            Shape::Number(name) => write!(f, "{name}")?,
+            Shape::Enum { tags } => write!(f, "enum [{}]", tags.join(", "))?,
+            Shape::Str => write!(f, "Str")?,
+            Shape::List { .. } => write!(f, "List")?,
+            Shape::Struct { .. } => write!(f, "struct")?,
+            Shape::TagUnion {
+                discriminant,
+                on_heap,
+                ..
+            } => {
+                write!(f, "tag union")?;
+
+                if *on_heap {
+                    write!(f, " on the heap")?;
+                }
+
+                match discriminant {
+                    TagDiscriminant::None => {}
+                    TagDiscriminant::Stored { offset, size } => {
+                        let size_bytes = if *size == 1 { "byte" } else { "bytes" };
+
+                        write!(f, ", tag id in {size} {size_bytes} at offset {offset}")?
+                    }
+                    TagDiscriminant::PointerBits { bits } => {
+                        write!(f, ", tag id in the lowest {bits} bits of the pointer")?
+                    }
+                }
+            }
+            Shape::Pointer { .. } => write!(f, "pointer")?,
+            Shape::RecursivePointer => write!(f, "pointer to the recursive tag union")?,
+            Shape::FunctionPointer => write!(f, "function pointer")?,
+            Shape::Erased => write!(f, "erased closure")?,
+        }
+
+        writeln!(f, " ({} {bytes}, aligned to {})", self.size, self.alignment)?;
+
+        let indent = indent + 4;
+
+        match &self.shape {
+            Shape::List { element } => {
+                write!(f, "{:indent$}element: ", "")?;
+                element.fmt_indented(f, indent)?;
+            }
+            Shape::Struct { fields } => fmt_fields(f, fields, indent)?,
+            Shape::TagUnion { tags, .. } => {
+                for tag in tags {
+                    write!(f, "{:indent$}tag {}", "", tag.id)?;
+
+                    if let Some(name) = &tag.name {
+                        write!(f, " {name}")?;
+                    }
+
+                    if tag.is_null {
+                        writeln!(f, ": null pointer")?;
+                    } else {
+                        writeln!(f)?;
+                        fmt_fields(f, &tag.fields, indent + 4)?;
+                    }
+                }
+            }
+            Shape::Pointer { pointee } => {
+                write!(f, "{:indent$}to: ", "")?;
+                pointee.fmt_indented(f, indent)?;
+            }
+            Shape::Number(_)
+            | Shape::Enum { .. }
+            | Shape::Str
+            | Shape::RecursivePointer
+            | Shape::FunctionPointer
+            | Shape::Erased => {}
+        }
+
+        Ok(())
+    }
+}
+
+fn fmt_fields(f: &mut fmt::Formatter<'_>, fields: &[Field], indent: usize) -> fmt::Result {
+    for field in fields {
+        write!(f, "{:indent$}offset {}", "", field.offset)?;
+
+        if let Some(name) = &field.name {
+            write!(f, ", {name}")?;
+        }
+
+        write!(f, ": ")?;
+        field.layout.fmt_indented(f, indent)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use roc_target::Target;
+
+    use super::*;
+    use crate::layout::{Layout, STLayoutInterner};
+
+    #[test]
+    fn struct_field_offsets() {
+        let mut interner = STLayoutInterner::with_capacity(4, Target::LinuxX64);
+        let layout = interner.insert_direct_no_semantic(LayoutRepr::Struct(&[
+            Layout::STR,
+            Layout::U64,
+            Layout::U8,
+        ]));
+
+        let description = describe_layout(&interner, layout);
+        let Shape::Struct { fields } = &description.shape else {
+            panic!("expected a struct, but got {description:?}");
+        };
+
+        assert_eq!((description.size, description.alignment), (40, 8));
+        assert_eq!(
+            fields.iter().map(|field| field.offset).collect::<Vec<_>>(),
+            [0, 24, 32]
+        );
+    }
+
+    #[test]
+    fn tag_id_after_payload() {
+        let mut interner = STLayoutInterner::with_capacity(4, Target::LinuxX64);
+        let tags: &[&[InLayout]] = &[&[Layout::U64], &[], &[Layout::U8, Layout::U8]];
+        let layout =
+            interner.insert_direct_no_semantic(LayoutRepr::Union(UnionLayout::NonRecursive(tags)));
+
+        let description = describe_layout(&interner, layout);
+        let Shape::TagUnion {
+            tags,
+            discriminant,
+            on_heap,
+        } = &description.shape
+        else {
+            panic!("expected a tag union, but got {description:?}");
+        };
+
+        assert_eq!((description.size, description.alignment), (16, 8));
+        assert_eq!(
+            *discriminant,
+            TagDiscriminant::Stored { offset: 8, size: 1 }
+        );
+        assert!(!on_heap);
+        assert_eq!(
+            tags.iter().map(|tag| tag.fields.len()).collect::<Vec<_>>(),
+            [1, 0, 2]
+        );
+        assert_eq!(tags[2].fields[1].offset, 1);
+    }
+
+    #[test]
+    fn outline() {
+        let mut interner = STLayoutInterner::with_capacity(4, Target::LinuxX64);
+        let element = interner.insert_direct_no_semantic(LayoutRepr::Struct(&[Layout::U8]));
+        let layout =
+            interner.insert_direct_no_semantic(LayoutRepr::Builtin(Builtin::List(element)));
+
+        assert_eq!(
+            describe_layout(&interner, layout).to_string(),
+            "List (24 bytes, aligned to 8)\n    element: struct (1 byte, aligned to 1)\n        offset 0: U8 (1 byte, aligned to 1)\n"
+        );
+    }
+}
@@ -10,13 +10,25 @@
 #![allow(clippy::too_many_arguments)]
 
 pub mod borrow;
+pub mod borrow_report;
+pub mod closure_captures;
 pub mod code_gen_help;
+pub mod comptime;
+pub mod coverage;
+pub mod dead_fields;
 pub mod drop_specialization;
+pub mod frames;
 pub mod inc_dec;
 pub mod ir;
 pub mod layout;
 pub mod low_level;
+pub mod mangle;
+pub mod profile;
+pub mod report_format;
 pub mod reset_reuse;
+pub mod specialization_report;
+pub mod str_builder;
 pub mod tail_recursion;
+pub mod vectorize;
 
 pub mod debug;
@@ -0,0 +1,503 @@
+//! Stable names for specialized procs in generated code, and a demangler to read them back.
+//!
+//! A mangled name looks like `_Roc4Json6decode_h0123456789abcdef`:
+//!
+//! - `_Roc` marks the start of a Roc name. Rust's v0 mangling also starts with `_R`, but the `o`
+//!   can't follow it there, so Rust demanglers (which profilers and debuggers try on every
+//!   symbol) reject our names straight away instead of misreading them.
+//! - the module name and the def name follow, each prefixed with its length
+//! - `_h` is followed by 16 hex digits that hash the layouts the proc was specialized for
+//!
+//! The hash only looks at the structure of the layouts and the names of the lambdas in them, never
+//! at interned indices, so a proc gets the same name in every build of the same program.
+//!
+//! Characters other than `[A-Za-z0-9_.]` are written as `$` followed by the two hex digits of each
+//! of their UTF-8 bytes, so the names are valid symbols in every object file format we emit.
+//! The length prefixes count the escaped text.
+//!
+//! Anything after a complete name that starts with `$` or `.` is a suffix: we use `$` suffixes to
+//! tell apart different specializations of a proc with the same layouts, and LLVM adds `.` suffixes
+//! when it clones functions. The demangler keeps suffixes out of the [Demangled] name.
+
+use std::fmt::{self, Write};
+
+use roc_module::symbol::{Interns, Symbol};
+
+use crate::ir::ProcLayout;
+use crate::layout::{
+    Builtin, Erased, InLayout, LambdaSet, LayoutInterner, LayoutRepr, Niche, SemanticRepr,
+    UnionLayout,
+};
+
+const PREFIX: &str = "_Roc";
+const HASH_MARKER: &str = "_h";
+const HASH_DIGITS: usize = 16;
+
+/// The mangled name of a specialization of `symbol` with the given layout.
+pub fn mangle_proc<'a, I>(
+    interner: &I,
+    interns: &Interns,
+    symbol: Symbol,
+    layout: &ProcLayout<'a>,
+) -> String
+where
+    I: LayoutInterner<'a>,
+{
+    mangle_specialization(
+        interner,
+        interns,
+        symbol,
+        layout.arguments,
+        layout.result,
+        layout.niche,
+    )
+}
+
+/// The mangled name of a specialization of `symbol` that takes `arguments` and returns `result`.
+/// The `niche` tells apart lambdas that only differ in what they capture.
+pub fn mangle_specialization<'a, I>(
+    interner: &I,
+    interns: &Interns,
+    symbol: Symbol,
+    arguments: &[InLayout<'a>],
+    result: InLayout<'a>,
+    niche: Niche<'a>,
+) -> String
+where
+    I: LayoutInterner<'a>,
+{
+    mangle(
+        symbol.module_string(interns).as_str(),
+        symbol.as_str(interns),
+        layouts_hash(interner, interns, arguments, result, niche),
+    )
+}
+
+/// The mangled name of `def` in `module`, for the specialization whose layouts hash to `hash`.
+pub fn mangle(module: &str, def: &str, hash: u64) -> String {
+    let module = escape(module);
+    let def = escape(def);
+
+    format!(
+        "{PREFIX}{}{module}{}{def}{HASH_MARKER}{hash:0width$x}",
+        module.len(),
+        def.len(),
+        width = HASH_DIGITS
+    )
+}
+
+/// The Roc name a mangled symbol was generated for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Demangled {
+    pub module: String,
+    pub def: String,
+    pub hash: u64,
+}
+
+impl fmt::Display for Demangled {
+    /// Writes `Module.def`, or `Module.def[hash]` with the alternate flag (`{:#}`).
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.module, self.def)?;
+
+        if f.alternate() {
+            write!(f, "[{:0width$x}]", self.hash, width = HASH_DIGITS)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Read back a mangled name, ignoring any suffix after it. Returns `None` if `name` isn't one.
+pub fn demangle(name: &str) -> Option<Demangled> {
+    let (demangled, rest) = demangle_prefix(name)?;
+
+    if rest.is_empty() || rest.starts_with(['$', '.']) {
+        Some(demangled)
+    } else {
+        None
+    }
+}
+
+/// Replace every mangled name in `text` (e.g. a stack trace or profiler output) with the Roc name
+/// it stands for. Suffixes are kept, so different specializations can still be told apart.
+pub fn demangle_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find(PREFIX) {
+        let (before, candidate) = rest.split_at(start);
+        out.push_str(before);
+
+        // A mangled name can't be the middle of an identifier
+        let in_identifier = out
+            .chars()
+            .next_back()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_');
+
+        match demangle_prefix(candidate) {
+            Some((demangled, after)) if !in_identifier => {
+                write!(out, "{demangled}").unwrap();
+                rest = after;
+            }
+            _ => {
+                out.push_str(PREFIX);
+                rest = &candidate[PREFIX.len()..];
+            }
+        }
+    }
+
+    out.push_str(rest);
+
+    out
+}
+
+fn demangle_prefix(name: &str) -> Option<(Demangled, &str)> {
+    let rest = name.strip_prefix(PREFIX)?;
+    let (module, rest) = length_prefixed(rest)?;
+    let (def, rest) = length_prefixed(rest)?;
+    let rest = rest.strip_prefix(HASH_MARKER)?;
+
+    let digits = rest.get(..HASH_DIGITS)?;
+    if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    let hash = u64::from_str_radix(digits, 16).ok()?;
+
+    let demangled = Demangled {
+        module: unescape(module)?,
+        def: unescape(def)?,
+        hash,
+    };
+
+    Some((demangled, &rest[HASH_DIGITS..]))
+}
+
+fn length_prefixed(text: &str) -> Option<(&str, &str)> {
+    let digits = text.bytes().take_while(u8::is_ascii_digit).count();
+    let len: usize = text[..digits].parse().ok()?;
+    let rest = &text[digits..];
+
+    if len == 0 {
+        return None;
+    }
+
+    let part = rest.get(..len)?;
+
+    Some((part, &rest[len..]))
+}
+
+fn is_plain(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '.'
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        if is_plain(c) {
+            out.push(c);
+        } else {
+            let mut bytes = [0; 4];
+
+            for byte in c.encode_utf8(&mut bytes).bytes() {
+                write!(out, "${byte:02x}").unwrap();
+            }
+        }
+    }
+
+    out
+}
+
+fn unescape(text: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+
+    while let Some((&byte, after)) = rest.split_first() {
+        if byte == b'$' {
+            let hex = std::str::from_utf8(after.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &after[2..];
+        } else if is_plain(byte as char) {
+            bytes.push(byte);
+            rest = after;
+        } else {
+            return None;
+        }
+    }
+
+    String::from_utf8(bytes).ok()
+}
+
+/// A hash of the layouts of a specialization that doesn't depend on how they were interned.
+pub fn layouts_hash<'a, I>(
+    interner: &I,
+    interns: &Interns,
+    arguments: &[InLayout<'a>],
+    result: InLayout<'a>,
+    niche: Niche<'a>,
+) -> u64
+where
+    I: LayoutInterner<'a>,
+{
+    let mut hasher = LayoutHasher {
+        interner,
+        interns,
+        state: StableHasher::new(),
+    };
+
+    hasher.layouts(arguments);
+    hasher.layout(result);
+    hasher.layouts(niche.captures());
+
+    hasher.state.finish()
+}
+
+/// 64-bit FNV-1a. The standard library's hashers may change between Rust releases, and mangled
+/// names need to stay the same.
+struct StableHasher(u64);
+
+impl StableHasher {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    fn new() -> Self {
+        Self(Self::OFFSET_BASIS)
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    fn usize(&mut self, n: usize) {
+        self.bytes(&(n as u64).to_le_bytes());
+    }
+
+    fn str(&mut self, s: &str) {
+        // Length-prefixed, so that `("ab", "c")` and `("a", "bc")` hash differently
+        self.usize(s.len());
+        self.bytes(s.as_bytes());
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+struct LayoutHasher<'r, I> {
+    interner: &'r I,
+    interns: &'r Interns,
+    state: StableHasher,
+}
+
+impl<'a, 'r, I: LayoutInterner<'a>> LayoutHasher<'r, I> {
+    fn layouts(&mut self, layouts: &[InLayout<'a>]) {
+        self.state.usize(layouts.len());
+
+        for layout in layouts {
+            self.layout(*layout);
+        }
+    }
+
+    fn tags(&mut self, tags: &[&[InLayout<'a>]]) {
+        self.state.usize(tags.len());
+
+        for fields in tags {
+            self.layouts(fields);
+        }
+    }
+
+    fn layout(&mut self, layout: InLayout<'a>) {
+        struct ConsistentSemanticRepr<'a>(SemanticRepr<'a>);
+
+        impl fmt::Debug for ConsistentSemanticRepr<'_> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.fmt_consistent(f)
+            }
+        }
+
+        let semantic = self.interner.get_semantic(layout);
+        self.state
+            .str(&format!("{:?}", ConsistentSemanticRepr(semantic)));
+
+        match self.interner.get_repr(layout) {
+            LayoutRepr::Builtin(builtin) => {
+                self.state.str("Builtin");
+                self.builtin(builtin);
+            }
+            LayoutRepr::Struct(fields) => {
+                self.state.str("Struct");
+                self.layouts(fields);
+            }
+            LayoutRepr::Ptr(inner) => {
+                self.state.str("Ptr");
+                self.layout(inner);
+            }
+            LayoutRepr::Union(union) => {
+                self.state.str("Union");
+                self.union(union);
+            }
+            LayoutRepr::LambdaSet(lambda_set) => {
+                self.state.str("LambdaSet");
+                self.lambda_set(lambda_set);
+            }
+            LayoutRepr::RecursivePointer(_) => {
+                // Points back at the enclosing recursive union, which is being hashed already
+                self.state.str("RecursivePointer");
+            }
+            LayoutRepr::FunctionPointer(function_pointer) => {
+                self.state.str("FunctionPointer");
+                self.layouts(function_pointer.args);
+                self.layout(function_pointer.ret);
+            }
+            LayoutRepr::Erased(Erased) => {
+                self.state.str("Erased");
+            }
+        }
+    }
+
+    fn builtin(&mut self, builtin: Builtin<'a>) {
+        match builtin {
+            Builtin::Int(width) => self.state.str(&format!("Int({width:?})")),
+            Builtin::Float(width) => self.state.str(&format!("Float({width:?})")),
+            Builtin::Bool => self.state.str("Bool"),
+            Builtin::Decimal => self.state.str("Decimal"),
+            Builtin::Str => self.state.str("Str"),
+            Builtin::List(element) => {
+                self.state.str("List");
+                self.layout(element);
+            }
+        }
+    }
+
+    fn union(&mut self, union: UnionLayout<'a>) {
+        match union {
+            UnionLayout::NonRecursive(tags) => {
+                self.state.str("NonRecursive");
+                self.tags(tags);
+            }
+            UnionLayout::Recursive(tags) => {
+                self.state.str("Recursive");
+                self.tags(tags);
+            }
+            UnionLayout::NonNullableUnwrapped(fields) => {
+                self.state.str("NonNullableUnwrapped");
+                self.layouts(fields);
+            }
+            UnionLayout::NullableWrapped {
+                nullable_id,
+                other_tags,
+            } => {
+                self.state.str("NullableWrapped");
+                self.state.usize(nullable_id as usize);
+                self.tags(other_tags);
+            }
+            UnionLayout::NullableUnwrapped {
+                nullable_id,
+                other_fields,
+            } => {
+                self.state.str("NullableUnwrapped");
+                self.state.usize(nullable_id as usize);
+                self.layouts(other_fields);
+            }
+        }
+    }
+
+    fn lambda_set(&mut self, lambda_set: LambdaSet<'a>) {
+        let LambdaSet {
+            args,
+            ret,
+            set,
+            representation,
+            full_layout: _,
+        } = lambda_set;
+
+        self.layouts(args);
+        self.layout(ret);
+
+        self.state.usize(set.len());
+        for (symbol, captures) in set.iter() {
+            self.state.str(symbol.module_string(self.interns).as_str());
+            self.state.str(symbol.as_str(self.interns));
+            self.layouts(captures);
+        }
+
+        self.layout(representation);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mangled = mangle("Json", "decode", 0x0123_4567_89ab_cdef);
+
+        assert_eq!(mangled, "_Roc4Json6decode_h0123456789abcdef");
+        assert_eq!(
+            demangle(&mangled),
+            Some(Demangled {
+                module: "Json".to_string(),
+                def: "decode".to_string(),
+                hash: 0x0123_4567_89ab_cdef,
+            })
+        );
+    }
+
+    #[test]
+    fn round_trip_escaped() {
+        let mangled = mangle("#UserApp", "read!", 7);
+
+        assert_eq!(mangled, "_Roc10$23UserApp7read$21_h0000000000000007");
+        assert_eq!(demangle(&mangled).unwrap().to_string(), "#UserApp.read!");
+        assert_eq!(
+            format!("{:#}", demangle(&mangled).unwrap()),
+            "#UserApp.read![0000000000000007]"
+        );
+    }
+
+    #[test]
+    fn demangle_with_suffix() {
+        let name = demangle("_Roc4Json6decode_h0123456789abcdef.llvm.42").unwrap();
+
+        assert_eq!(name.to_string(), "Json.decode");
+        assert_eq!(demangle("_Roc4Json6decode_h0123456789abcdef$1"), Some(name));
+    }
+
+    #[test]
+    fn not_mangled() {
+        assert_eq!(demangle("roc__main_for_host_1_exposed"), None);
+        assert_eq!(demangle("_Roc4Json6decode"), None);
+        assert_eq!(demangle("_Roc4Json6decode_h0123"), None);
+        assert_eq!(demangle("_Roc9Json6decode_h0123456789abcdef"), None);
+        assert_eq!(demangle("_Roc4Json6decode_h0123456789abcdefx"), None);
+    }
+
+    #[test]
+    fn rust_names_are_left_alone() {
+        // Rust's v0 and legacy manglings
+        assert_eq!(demangle("_RNvCs1234_7mycrate3foo"), None);
+        assert_eq!(demangle("_ZN7mycrate3foo17h0123456789abcdefE"), None);
+        assert_eq!(
+            demangle_text("_RNvCs1234_7mycrate3foo _R4Json6decode_h0123456789abcdef"),
+            "_RNvCs1234_7mycrate3foo _R4Json6decode_h0123456789abcdef"
+        );
+    }
+
+    #[test]
+    fn demangle_stack_trace() {
+        let trace = "#0 _Roc4Json6decode_h0123456789abcdef$2 at main.roc\n\
+                     #1 roc__main_for_host_1_exposed\n\
+                     #2 x_Roc4Json6decode_h0123456789abcdef";
+
+        assert_eq!(
+            demangle_text(trace),
+            "#0 Json.decode$2 at main.roc\n\
+             #1 roc__main_for_host_1_exposed\n\
+             #2 x_Roc4Json6decode_h0123456789abcdef"
+        );
+    }
+}
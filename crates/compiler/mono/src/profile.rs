@@ -0,0 +1,328 @@
+//! Profile-guided hints for code generation.
+//!
+//! An instrumented run (`roc test --profile-generate`) counts how often every def in user code
+//! was called, using the same counters as coverage. Those counts are saved as a profile, and a
+//! later build (`--profile-use`) turns them into a [ProcHint] for each specialized proc:
+//! specializations that account for most of the calls are inlined into their callers, and ones
+//! that were never called are kept out of line (and thus shared between call sites) and
+//! optimized for size.
+//!
+//! Profiles are keyed by the qualified name of the def rather than by symbol or layout, so that
+//! they stay valid while the program changes. The file format is one line per def:
+//!
+//! ```text
+//! # roc profile v1
+//! {call count}\t{module}.{ident}
+//! ```
+//!
+//! Lines are sorted by name, and several profiles can be combined with [Profile::merge],
+//! which adds up the counts.
+use std::collections::BTreeMap;
+use std::fmt::{self, Write};
+
+use roc_collections::MutMap;
+use roc_module::symbol::{Interns, Symbol};
+
+use crate::ir::{Call, CallType, Expr, Proc, Stmt};
+use crate::report_format::ReportFormat;
+
+/// The share of all calls (in percent) that the hottest defs must add up to.
+const HOT_CALLS_PERCENT: u64 = 90;
+
+/// Hot procs with at most this many statements are always inlined.
+const ALWAYS_INLINE_MAX_STMTS: usize = 32;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Profile {
+    /// Call counts by qualified def name, e.g. `Main.parse`
+    counts: BTreeMap<String, u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl Profile {
+    /// Record `count` more calls to the def with the given qualified name.
+    pub fn add(&mut self, name: &str, count: u64) {
+        let total = self.counts.entry(name.to_string()).or_insert(0);
+
+        *total = total.saturating_add(count);
+    }
+
+    /// The number of calls to the def with the given qualified name, if it was in the profile.
+    pub fn count(&self, name: &str) -> Option<u64> {
+        self.counts.get(name).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Add the counts of another profile to this one, e.g. to combine several runs.
+    pub fn merge(&mut self, other: &Profile) {
+        for (name, count) in other.counts.iter() {
+            self.add(name, *count);
+        }
+    }
+
+    pub fn parse(src: &str) -> Result<Self, ParseError> {
+        let mut lines = src.lines().enumerate().map(|(i, line)| (i + 1, line));
+
+        let header = ReportFormat::Profile.header();
+
+        match lines.next() {
+            Some((_, line)) if line == header => {}
+            _ => {
+                return Err(ParseError {
+                    line: 1,
+                    message: format!("expected the header `{header}`"),
+                })
+            }
+        }
+
+        let mut profile = Profile::default();
+
+        for (number, line) in lines {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let (count, name) = line
+                .split_once('\t')
+                .and_then(|(count, name)| Some((count.parse::<u64>().ok()?, name)))
+                .filter(|(_, name)| !name.is_empty())
+                .ok_or_else(|| ParseError {
+                    line: number,
+                    message: "expected `<call count>\\t<qualified name>`".to_string(),
+                })?;
+
+            profile.add(name, count);
+        }
+
+        Ok(profile)
+    }
+
+    pub fn render(&self) -> String {
+        let mut buf = String::new();
+
+        writeln!(buf, "{}", ReportFormat::Profile.header()).unwrap();
+
+        for (name, count) in self.counts.iter() {
+            writeln!(buf, "{count}\t{name}").unwrap();
+        }
+
+        buf
+    }
+
+    /// The smallest call count a def needs to be hot. The hot defs are the most frequently
+    /// called ones which together account for [HOT_CALLS_PERCENT] of all calls.
+    fn hot_threshold(&self) -> Option<u64> {
+        let mut counts: Vec<u64> = self.counts.values().copied().filter(|c| *c > 0).collect();
+        let total: u128 = counts.iter().map(|c| *c as u128).sum();
+
+        counts.sort_unstable_by(|a, b| b.cmp(a));
+
+        let mut sum = 0u128;
+
+        for count in counts {
+            sum += count as u128;
+
+            if sum * 100 >= total * HOT_CALLS_PERCENT as u128 {
+                return Some(count);
+            }
+        }
+
+        None
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcHint {
+    /// Hot and small: inline it into every caller.
+    AlwaysInline,
+    /// Hot, but large or recursive: let the optimizer know inlining is worthwhile.
+    InlineHint,
+    /// Never called in the profile: keep it out of line and optimize it for size.
+    Cold,
+}
+
+/// The [ProcHint] for each proc that the profile says something about.
+#[derive(Debug, Clone, Default)]
+pub struct ProfileHints {
+    hints: MutMap<Symbol, ProcHint>,
+}
+
+impl ProfileHints {
+    pub fn new<'a: 'p, 'p>(
+        profile: &Profile,
+        procedures: impl IntoIterator<Item = &'p Proc<'a>>,
+        interns: &Interns,
+    ) -> Self {
+        let mut hints = MutMap::default();
+        let threshold = profile.hot_threshold();
+
+        for proc in procedures {
+            let symbol = proc.name.name();
+            let module_name = interns.module_ids.get_name(symbol.module_id()).unwrap();
+            let name = format!("{module_name}.{}", symbol.as_str(interns));
+
+            let hint = match profile.count(&name) {
+                None => continue,
+                Some(0) => ProcHint::Cold,
+                Some(count) if threshold.is_some_and(|threshold| count >= threshold) => {
+                    // All specializations of a def share one count, so one that is large or
+                    // recursive makes the hint weaker for all of them.
+                    let weaker = hints.get(&symbol) == Some(&ProcHint::InlineHint);
+
+                    if !weaker
+                        && !calls_itself(symbol, &proc.body)
+                        && count_stmts(&proc.body) <= ALWAYS_INLINE_MAX_STMTS
+                    {
+                        ProcHint::AlwaysInline
+                    } else {
+                        ProcHint::InlineHint
+                    }
+                }
+                Some(_) => continue,
+            };
+
+            hints.insert(symbol, hint);
+        }
+
+        Self { hints }
+    }
+
+    pub fn get(&self, symbol: Symbol) -> Option<ProcHint> {
+        self.hints.get(&symbol).copied()
+    }
+}
+
+fn count_stmts(stmt: &Stmt) -> usize {
+    use Stmt::*;
+
+    match stmt {
+        Let(_, _, _, remainder)
+        | Refcounting(_, remainder)
+        | Expect { remainder, .. }
+        | Dbg { remainder, .. } => 1 + count_stmts(remainder),
+        Switch {
+            branches,
+            default_branch,
+            ..
+        } => {
+            1 + branches
+                .iter()
+                .map(|(_, _, branch)| count_stmts(branch))
+                .sum::<usize>()
+                + count_stmts(default_branch.1)
+        }
+        Join {
+            body, remainder, ..
+        } => 1 + count_stmts(body) + count_stmts(remainder),
+        Ret(_) | Jump(_, _) | Crash(_, _) => 1,
+    }
+}
+
+fn calls_itself(symbol: Symbol, stmt: &Stmt) -> bool {
+    use Stmt::*;
+
+    match stmt {
+        Let(_, expr, _, remainder) => {
+            let is_self_call = matches!(
+                expr,
+                Expr::Call(Call {
+                    call_type: CallType::ByName { name, .. },
+                    ..
+                }) if name.name() == symbol
+            );
+
+            is_self_call || calls_itself(symbol, remainder)
+        }
+        Refcounting(_, remainder) | Expect { remainder, .. } | Dbg { remainder, .. } => {
+            calls_itself(symbol, remainder)
+        }
+        Switch {
+            branches,
+            default_branch,
+            ..
+        } => {
+            branches
+                .iter()
+                .any(|(_, _, branch)| calls_itself(symbol, branch))
+                || calls_itself(symbol, default_branch.1)
+        }
+        Join {
+            body, remainder, ..
+        } => calls_itself(symbol, body) || calls_itself(symbol, remainder),
+        Ret(_) | Jump(_, _) | Crash(_, _) => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_render_roundtrip() {
+        let src = "# roc profile v1\n3\tMain.b\n12\tMain.a\n";
+        let profile = Profile::parse(src).unwrap();
+
+        assert_eq!(profile.count("Main.a"), Some(12));
+        assert_eq!(profile.count("Main.b"), Some(3));
+        assert_eq!(
+            profile.render(),
+            "# roc profile v1\n12\tMain.a\n3\tMain.b\n"
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(Profile::parse("3\tMain.a\n").unwrap_err().line, 1);
+        assert_eq!(
+            Profile::parse("# roc profile v1\n\nlots\tMain.a\n")
+                .unwrap_err()
+                .line,
+            3
+        );
+    }
+
+    #[test]
+    fn merge_adds_counts() {
+        let mut profile = Profile::parse("# roc profile v1\n1\tMain.a\n2\tMain.b\n").unwrap();
+        let other = Profile::parse("# roc profile v1\n5\tMain.b\n0\tMain.c\n").unwrap();
+
+        profile.merge(&other);
+
+        assert_eq!(
+            profile.render(),
+            "# roc profile v1\n1\tMain.a\n7\tMain.b\n0\tMain.c\n"
+        );
+    }
+
+    #[test]
+    fn hot_threshold() {
+        let profile =
+            Profile::parse("# roc profile v1\n800\tMain.a\n200\tMain.b\n50\tMain.c\n0\tMain.d\n")
+                .unwrap();
+
+        // 800 + 200 is the first prefix that covers 90% of the 1050 calls
+        assert_eq!(profile.hot_threshold(), Some(200));
+        assert_eq!(Profile::default().hot_threshold(), None);
+    }
+}
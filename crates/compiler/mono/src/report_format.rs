@@ -0,0 +1,53 @@
+//! What the text reports that the compiler writes for tools have in common: a header that names
+//! the format, and entries in the same order from one build to the next.
+use roc_module::symbol::{Interns, Symbol};
+
+/// A kind of text report that tools read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// See [crate::borrow_report]
+    Borrows,
+    /// See [crate::dead_fields]
+    DeadFields,
+    /// See [crate::frames]
+    FrameTable,
+    /// See [crate::profile]
+    Profile,
+    /// See [crate::specialization_report]
+    Specializations,
+}
+
+impl ReportFormat {
+    /// The first line of every report in this format, so tools can reject files in a format, or
+    /// a version of it, that they don't know.
+    pub const fn header(self) -> &'static str {
+        match self {
+            ReportFormat::Borrows => "# roc borrow report v1",
+            ReportFormat::DeadFields => "# roc dead field report v1",
+            ReportFormat::FrameTable => "# roc frame table v2",
+            ReportFormat::Profile => "# roc profile v1",
+            ReportFormat::Specializations => "# roc specialization report v1",
+        }
+    }
+}
+
+/// Sort `items` by the name of the symbol that `key` gives for each, and then by the rest of the
+/// key.
+///
+/// Sorting by [Symbol] itself would order them by the IDs of their modules, which are handed out
+/// in whatever order the loader's threads happen to reach the modules.
+pub fn sort_by_symbol_name<T, K: Ord>(
+    items: &mut [T],
+    interns: &Interns,
+    key: impl Fn(&T) -> (Symbol, K),
+) {
+    items.sort_by_cached_key(|item| {
+        let (symbol, rest) = key(item);
+
+        (
+            symbol.module_string(interns).as_str(),
+            symbol.as_str(interns),
+            rest,
+        )
+    });
+}
@@ -0,0 +1,206 @@
+//! A report of what monomorphization did to each def: which layouts it was specialized for, how
+//! big each specialization is, and (for the LLVM backend) whether any of its functions survived
+//! optimization, or were all inlined into their callers.
+//!
+//! A generic def that's called at many types can contribute a lot more code than its source
+//! suggests, so the report lists the defs by their total size, biggest first. Sizes are measured
+//! in statements of the specialized IR, which is only a rough proxy for machine code, but is the
+//! same for every backend and doesn't depend on the optimization level.
+//!
+//! The report is written as tab-separated lines with one line per specialization:
+//!
+//! ```text
+//! {module}.{ident}
+//! {argument layouts} -> {result layout}
+//! {size}
+//! {inlined: yes, no or unknown}
+//! ```
+use std::cmp::Reverse;
+use std::fmt::{self, Write};
+
+use roc_collections::{MutMap, MutSet};
+use roc_module::symbol::{Interns, Symbol};
+
+use crate::ir::{Proc, ProcLayout, Stmt};
+use crate::layout::LayoutInterner;
+use crate::mangle::demangle;
+use crate::report_format::ReportFormat;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpecializationReport {
+    /// Biggest first
+    pub defs: Vec<DefReport>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DefReport {
+    /// The qualified name of the def, e.g. `Main.parse`.
+    pub name: String,
+    /// Biggest first
+    pub specializations: Vec<SpecializationSize>,
+    /// Whether every function generated for this def was inlined, if the backend can tell.
+    pub inlined: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpecializationSize {
+    /// e.g. `(List I64, I64) -> I64`
+    pub layout: String,
+    /// The number of statements in the specialized IR.
+    pub size: usize,
+}
+
+impl DefReport {
+    pub fn total_size(&self) -> usize {
+        self.specializations.iter().map(|spec| spec.size).sum()
+    }
+}
+
+impl SpecializationReport {
+    pub fn new<'a, I>(
+        procedures: &MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+        interner: &I,
+        interns: &Interns,
+    ) -> Self
+    where
+        I: LayoutInterner<'a>,
+    {
+        let mut by_symbol: MutMap<Symbol, Vec<SpecializationSize>> = MutMap::default();
+
+        for ((symbol, proc_layout), proc) in procedures {
+            let arguments: Vec<_> = proc_layout
+                .arguments
+                .iter()
+                .map(|layout| interner.dbg(*layout))
+                .collect();
+            let layout = format!(
+                "({}) -> {}",
+                arguments.join(", "),
+                interner.dbg(proc_layout.result)
+            );
+
+            by_symbol
+                .entry(*symbol)
+                .or_default()
+                .push(SpecializationSize {
+                    layout,
+                    size: stmt_size(&proc.body),
+                });
+        }
+
+        let mut defs: Vec<_> = by_symbol
+            .into_iter()
+            .map(|(symbol, mut specializations)| {
+                specializations
+                    .sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.layout.cmp(&b.layout)));
+
+                let module_name = interns.module_ids.get_name(symbol.module_id()).unwrap();
+
+                DefReport {
+                    name: format!("{module_name}.{}", symbol.as_str(interns)),
+                    specializations,
+                    inlined: None,
+                }
+            })
+            .collect();
+
+        // Defs of the same size are sorted by name, which unlike their symbols doesn't depend on
+        // the order the modules were loaded in.
+        defs.sort_by(|a, b| {
+            Reverse(a.total_size())
+                .cmp(&Reverse(b.total_size()))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+
+        SpecializationReport { defs }
+    }
+
+    /// Records which defs were inlined, given the names of the functions that are left in the
+    /// final module. A def was inlined if none of them demangle (see [crate::mangle]) to its name.
+    pub fn mark_inlined<'n>(&mut self, function_names: impl IntoIterator<Item = &'n str>) {
+        let surviving: MutSet<String> = function_names
+            .into_iter()
+            .filter_map(demangle)
+            .map(|demangled| demangled.to_string())
+            .collect();
+
+        for def in self.defs.iter_mut() {
+            def.inlined = Some(!surviving.contains(&def.name));
+        }
+    }
+
+    /// Writes the `count` biggest defs as a human-readable table.
+    pub fn summary(&self, count: usize) -> String {
+        let mut buf = String::new();
+
+        writeln!(buf, "{:>8}  {:>5}  def", "size", "specs").unwrap();
+
+        for def in self.defs.iter().take(count) {
+            let inlined = if def.inlined == Some(true) {
+                " (inlined)"
+            } else {
+                ""
+            };
+
+            writeln!(
+                buf,
+                "{:>8}  {:>5}  {}{inlined}",
+                def.total_size(),
+                def.specializations.len(),
+                def.name
+            )
+            .unwrap();
+        }
+
+        buf
+    }
+}
+
+impl fmt::Display for SpecializationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", ReportFormat::Specializations.header())?;
+
+        for def in self.defs.iter() {
+            let inlined = match def.inlined {
+                Some(true) => "yes",
+                Some(false) => "no",
+                None => "unknown",
+            };
+
+            for spec in def.specializations.iter() {
+                writeln!(f, "{}\t{}\t{}\t{inlined}", def.name, spec.layout, spec.size)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The number of statements in a proc body, including the ones in nested branches and join points.
+fn stmt_size(stmt: &Stmt) -> usize {
+    match stmt {
+        Stmt::Let(_, _, _, cont)
+        | Stmt::Refcounting(_, cont)
+        | Stmt::Expect {
+            remainder: cont, ..
+        }
+        | Stmt::Dbg {
+            remainder: cont, ..
+        } => 1 + stmt_size(cont),
+        Stmt::Switch {
+            branches,
+            default_branch,
+            ..
+        } => {
+            1 + branches
+                .iter()
+                .map(|(_, _, branch)| stmt_size(branch))
+                .sum::<usize>()
+                + stmt_size(default_branch.1)
+        }
+        Stmt::Join {
+            body, remainder, ..
+        } => 1 + stmt_size(body) + stmt_size(remainder),
+        Stmt::Ret(_) | Stmt::Jump(_, _) | Stmt::Crash(_, _) => 1,
+    }
+}
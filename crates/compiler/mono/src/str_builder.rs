@@ -0,0 +1,296 @@
+//! Finds strings that are built up by appending to them in a loop, and makes those appends
+//! reserve spare capacity.
+//!
+//! `Str.concat` reallocates its first argument to exactly the combined length, so building a
+//! string with `acc = Str.concat acc piece` (or `"$(acc)$(piece)"`) on every iteration copies the
+//! whole string on every iteration, which is quadratic in the length of the result. The
+//! `StrConcatAmortized` builtin does the same concatenation, but grows the capacity
+//! geometrically when it has to reallocate, like `List.append` does.
+//!
+//! Spare capacity is wasted memory when the string isn't appended to again, so only the concats
+//! that build an accumulator are rewritten:
+//!
+//! - in a loop (a join point that jumps to itself), a chain of concats that starts with a
+//!   parameter of the join point, and whose result is passed back to that parameter;
+//! - in a function that is called in a loop (e.g. the function passed to `List.walk`), a chain
+//!   of concats that starts with a parameter, and whose result is returned. The wrapper proc of
+//!   `Str.concat` itself is excluded, because it's shared by every place that passes `Str.concat`
+//!   as a function.
+//!
+//! Concats whose first argument is a string literal, like most of an interpolation, are left
+//! alone: only the outermost concat of `"$(acc), $(piece)"` appends to the accumulator.
+use bumpalo::collections::{CollectIn, Vec};
+use bumpalo::Bump;
+use roc_collections::{MutMap, MutSet};
+use roc_module::low_level::{LowLevel, LowLevelWrapperType};
+use roc_module::symbol::Symbol;
+
+use crate::ir::{Call, CallType, Expr, JoinPointId, Param, Proc, ProcLayout, Stmt, UpdateModeId};
+
+pub fn amortize_string_building<'a>(
+    arena: &'a Bump,
+    procedures: &mut MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+) {
+    let mut called_in_loops = MutSet::default();
+
+    for proc in procedures.values() {
+        let loops = loop_join_points(&proc.body);
+
+        if !loops.is_empty() {
+            visit(
+                &proc.body,
+                &mut std::vec::Vec::new(),
+                &mut |stmt, enclosing| {
+                    if let Stmt::Let(_, Expr::Call(call), _, _) = stmt {
+                        if let CallType::ByName { name, .. } = call.call_type {
+                            if enclosing.iter().any(|id| loops.contains(id)) {
+                                called_in_loops.insert(name.name());
+                            }
+                        }
+                    }
+                },
+            );
+        }
+    }
+
+    for proc in procedures.values_mut() {
+        let symbol = proc.name.name();
+        let called_in_loop = called_in_loops.contains(&symbol)
+            && matches!(
+                LowLevelWrapperType::from_symbol(symbol),
+                LowLevelWrapperType::NotALowLevelWrapper
+            );
+
+        let amortized = amortized_concats(proc, called_in_loop);
+
+        if !amortized.is_empty() {
+            proc.body = amortize_stmt(arena, &amortized, &proc.body);
+        }
+    }
+}
+
+/// The join points that jump to themselves from their own body.
+fn loop_join_points(body: &Stmt<'_>) -> MutSet<JoinPointId> {
+    let mut loops = MutSet::default();
+
+    visit(body, &mut std::vec::Vec::new(), &mut |stmt, enclosing| {
+        if let Stmt::Jump(id, _) = stmt {
+            if enclosing.contains(id) {
+                loops.insert(*id);
+            }
+        }
+    });
+
+    loops
+}
+
+/// The symbols bound to concats that append to an accumulator of `proc`.
+fn amortized_concats(proc: &Proc<'_>, called_in_loop: bool) -> MutSet<Symbol> {
+    // the first argument of every concat, by the symbol its result is bound to
+    let mut appended_to = MutMap::default();
+    let mut join_params: MutMap<JoinPointId, &[Param]> = MutMap::default();
+    let mut back_edges = std::vec::Vec::new();
+    let mut returned = std::vec::Vec::new();
+
+    visit(
+        &proc.body,
+        &mut std::vec::Vec::new(),
+        &mut |stmt, enclosing| match stmt {
+            Stmt::Let(symbol, Expr::Call(call), _, _) => {
+                if let Some(first) = concat_first_argument(call) {
+                    appended_to.insert(*symbol, first);
+                }
+            }
+            Stmt::Join { id, parameters, .. } => {
+                join_params.insert(*id, parameters);
+            }
+            Stmt::Jump(id, arguments) if enclosing.contains(id) => {
+                back_edges.push((*id, *arguments));
+            }
+            Stmt::Ret(symbol) => returned.push(*symbol),
+            _ => {}
+        },
+    );
+
+    let mut amortized = MutSet::default();
+
+    let mut amortize_chain = |result: Symbol, is_accumulator: &dyn Fn(Symbol) -> bool| {
+        let mut chain = std::vec::Vec::new();
+        let mut current = result;
+
+        while let Some(first) = appended_to.get(&current) {
+            chain.push(current);
+            current = *first;
+        }
+
+        if !chain.is_empty() && is_accumulator(current) {
+            amortized.extend(chain);
+        }
+    };
+
+    for (id, arguments) in back_edges {
+        for (argument, param) in arguments.iter().zip(join_params[&id].iter()) {
+            amortize_chain(*argument, &|root| root == param.symbol);
+        }
+    }
+
+    if called_in_loop {
+        for symbol in returned {
+            amortize_chain(symbol, &|root| {
+                proc.args.iter().any(|(_, arg)| *arg == root)
+            });
+        }
+    }
+
+    amortized
+}
+
+fn concat_first_argument(call: &Call<'_>) -> Option<Symbol> {
+    if is_concat(call) {
+        call.arguments.first().copied()
+    } else {
+        None
+    }
+}
+
+fn is_concat(call: &Call<'_>) -> bool {
+    match call.call_type {
+        CallType::ByName { name, .. } => name.name() == Symbol::STR_CONCAT,
+        CallType::LowLevel { op, .. } => op == LowLevel::StrConcat,
+        _ => false,
+    }
+}
+
+/// Calls `f` on every statement in `stmt`, along with the join points whose body it's in.
+fn visit<'a, 'b>(
+    stmt: &'b Stmt<'a>,
+    enclosing: &mut std::vec::Vec<JoinPointId>,
+    f: &mut impl FnMut(&'b Stmt<'a>, &[JoinPointId]),
+) {
+    f(stmt, enclosing);
+
+    match stmt {
+        Stmt::Let(_, _, _, cont)
+        | Stmt::Refcounting(_, cont)
+        | Stmt::Expect {
+            remainder: cont, ..
+        }
+        | Stmt::Dbg {
+            remainder: cont, ..
+        } => visit(cont, enclosing, f),
+        Stmt::Switch {
+            branches,
+            default_branch,
+            ..
+        } => {
+            for (_, _, branch) in branches.iter() {
+                visit(branch, enclosing, f);
+            }
+
+            visit(default_branch.1, enclosing, f);
+        }
+        Stmt::Join {
+            id,
+            body,
+            remainder,
+            ..
+        } => {
+            enclosing.push(*id);
+            visit(body, enclosing, f);
+            enclosing.pop();
+
+            visit(remainder, enclosing, f);
+        }
+        Stmt::Ret(_) | Stmt::Jump(_, _) | Stmt::Crash(_, _) => {}
+    }
+}
+
+fn amortize_stmt<'a>(arena: &'a Bump, amortized: &MutSet<Symbol>, stmt: &Stmt<'a>) -> Stmt<'a> {
+    match stmt {
+        Stmt::Let(symbol, expr, layout, cont) => {
+            let expr = match expr {
+                Expr::Call(call) if is_concat(call) && amortized.contains(symbol) => {
+                    Expr::Call(Call {
+                        call_type: CallType::LowLevel {
+                            op: LowLevel::StrConcatAmortized,
+                            update_mode: UpdateModeId::BACKEND_DUMMY,
+                        },
+                        arguments: call.arguments,
+                    })
+                }
+                _ => expr.clone(),
+            };
+
+            Stmt::Let(
+                *symbol,
+                expr,
+                *layout,
+                arena.alloc(amortize_stmt(arena, amortized, cont)),
+            )
+        }
+        Stmt::Switch {
+            cond_symbol,
+            cond_layout,
+            branches,
+            default_branch,
+            ret_layout,
+        } => Stmt::Switch {
+            cond_symbol: *cond_symbol,
+            cond_layout: *cond_layout,
+            branches: branches
+                .iter()
+                .map(|(id, info, branch)| {
+                    (*id, info.clone(), amortize_stmt(arena, amortized, branch))
+                })
+                .collect_in::<Vec<_>>(arena)
+                .into_bump_slice(),
+            default_branch: (
+                default_branch.0.clone(),
+                arena.alloc(amortize_stmt(arena, amortized, default_branch.1)),
+            ),
+            ret_layout: *ret_layout,
+        },
+        Stmt::Refcounting(modify_rc, cont) => Stmt::Refcounting(
+            *modify_rc,
+            arena.alloc(amortize_stmt(arena, amortized, cont)),
+        ),
+        Stmt::Expect {
+            condition,
+            region,
+            lookups,
+            variables,
+            remainder,
+        } => Stmt::Expect {
+            condition: *condition,
+            region: *region,
+            lookups,
+            variables,
+            remainder: arena.alloc(amortize_stmt(arena, amortized, remainder)),
+        },
+        Stmt::Dbg {
+            source_location,
+            source,
+            symbol,
+            variable,
+            remainder,
+        } => Stmt::Dbg {
+            source_location,
+            source,
+            symbol: *symbol,
+            variable: *variable,
+            remainder: arena.alloc(amortize_stmt(arena, amortized, remainder)),
+        },
+        Stmt::Join {
+            id,
+            parameters,
+            body,
+            remainder,
+        } => Stmt::Join {
+            id: *id,
+            parameters,
+            body: arena.alloc(amortize_stmt(arena, amortized, body)),
+            remainder: arena.alloc(amortize_stmt(arena, amortized, remainder)),
+        },
+        Stmt::Ret(_) | Stmt::Jump(_, _) | Stmt::Crash(_, _) => stmt.clone(),
+    }
+}
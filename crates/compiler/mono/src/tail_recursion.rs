@@ -7,11 +7,16 @@ use crate::layout::{
     InLayout, LambdaName, Layout, LayoutInterner, LayoutRepr, STLayoutInterner, TagIdIntType,
     UnionLayout,
 };
+use crate::report_format::sort_by_symbol_name;
 use bumpalo::collections::Vec;
 use bumpalo::Bump;
 use roc_collections::{MutMap, VecMap};
 use roc_module::low_level::LowLevel;
-use roc_module::symbol::{IdentIds, ModuleId, Symbol};
+use roc_module::symbol::{IdentIds, Interns, ModuleId, Symbol};
+use roc_problem::mono::Problem;
+use roc_region::all::Region;
+
+pub use roc_problem::mono::NonTailCall;
 
 pub struct Env<'a, 'i> {
     arena: &'a Bump,
@@ -1105,3 +1110,110 @@ fn stmt_contains_symbol_nonrec(stmt: &Stmt, needle: Symbol) -> bool {
         Stmt::Crash(symbol, _) => needle == *symbol,
     }
 }
+
+/// Finds the calls that a proc makes to itself without them being tail calls. Tail calls are
+/// turned into jumps when specializing the proc, so any call left is one.
+pub fn non_tail_self_calls(proc: &Proc) -> std::vec::Vec<NonTailCall> {
+    let mut calls = std::vec::Vec::new();
+    let mut stack = vec![&proc.body];
+
+    while let Some(stmt) = stack.pop() {
+        match stmt {
+            Stmt::Let(symbol, expr, _, cont) => {
+                if let Expr::Call(Call {
+                    call_type: CallType::ByName { name, .. },
+                    ..
+                }) = expr
+                {
+                    if name.name() == proc.name.name() {
+                        calls.push(non_tail_reason(*symbol, cont));
+                    }
+                }
+
+                stack.push(cont);
+            }
+            Stmt::Refcounting(_, cont)
+            | Stmt::Expect {
+                remainder: cont, ..
+            }
+            | Stmt::Dbg {
+                remainder: cont, ..
+            } => stack.push(cont),
+            Stmt::Switch {
+                branches,
+                default_branch,
+                ..
+            } => {
+                stack.extend(branches.iter().map(|(_, _, branch)| branch));
+                stack.push(default_branch.1);
+            }
+            Stmt::Join {
+                body, remainder, ..
+            } => {
+                stack.push(body);
+                stack.push(remainder);
+            }
+            Stmt::Ret(_) | Stmt::Jump(_, _) | Stmt::Crash(_, _) => {}
+        }
+    }
+
+    calls
+}
+
+/// What happens to the result of a call before it's returned.
+fn non_tail_reason(result: Symbol, mut cont: &Stmt) -> NonTailCall {
+    let mut skipped_refcounting = false;
+
+    // Refcounting is inserted after tail calls are turned into jumps, so it's usually not the
+    // reason the call isn't a tail call.
+    while let Stmt::Refcounting(_, rest) = cont {
+        skipped_refcounting = true;
+        cont = rest;
+    }
+
+    match cont {
+        Stmt::Let(_, Expr::Call(call), _, _) if call.arguments.contains(&result) => {
+            match call.call_type {
+                CallType::ByName { name, .. } => NonTailCall::PassedTo(name.name()),
+                CallType::LowLevel { op, .. } => NonTailCall::PassedToLowLevel(op),
+                _ => NonTailCall::Other,
+            }
+        }
+        Stmt::Let(_, expr, _, _) if expr_contains_symbol(expr, result) => NonTailCall::Stored,
+        Stmt::Jump(_, arguments) if arguments.contains(&result) => NonTailCall::PassedToJoinPoint,
+        Stmt::Ret(symbol) if *symbol == result && skipped_refcounting => NonTailCall::Refcounting,
+        _ => NonTailCall::Other,
+    }
+}
+
+/// The problem to report for each def marked `# @tailrec` that makes a call to itself that isn't
+/// a tail call. Every specialization of a def is checked, but each problem is only reported once.
+pub fn tail_call_problems<'a>(
+    procedures: &MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+    tail_recursive_defs: &MutMap<Symbol, Region>,
+    interns: &Interns,
+) -> std::vec::Vec<Problem> {
+    let mut problems = std::vec::Vec::new();
+
+    for ((symbol, _), proc) in procedures {
+        if let Some(region) = tail_recursive_defs.get(symbol) {
+            for call in non_tail_self_calls(proc) {
+                let problem = Problem::NotTailRecursive {
+                    symbol: *symbol,
+                    region: *region,
+                    call,
+                };
+
+                if !problems.contains(&problem) {
+                    problems.push(problem);
+                }
+            }
+        }
+    }
+
+    sort_by_symbol_name(&mut problems, interns, |problem| {
+        (problem.symbol(), problem.region().start().offset)
+    });
+
+    problems
+}
@@ -0,0 +1,291 @@
+//! Replaces calls to builtins with their vectorized implementations in the zig builtins, for the
+//! specializations whose layouts allow it.
+//!
+//! At the moment that's `List.contains` on a list of numbers: the generic implementation calls
+//! `isEq` on every element through a closure, while the zig version compares a whole vector of
+//! elements at once. 128-bit integers and `Dec` keep using the generic implementation, because
+//! they don't fit in a SIMD lane on common targets.
+//!
+//! `List.sum` and `List.walk` are not vectorized: summing a vector at a time would change which
+//! intermediate result overflows (and so whether an integer sum crashes), and the order in which
+//! floats are rounded; and `walk` runs an arbitrary closure on each element.
+use bumpalo::collections::{CollectIn, Vec};
+use bumpalo::Bump;
+use roc_builtins::bitcode::IntWidth;
+use roc_collections::{MutMap, MutSet};
+use roc_module::low_level::{LowLevel, LowLevelWrapperType};
+use roc_module::symbol::Symbol;
+
+use crate::ir::{
+    Call, CallType, Expr, HigherOrderLowLevel, PassedFunction, Proc, ProcLayout, Stmt, UpdateModeId,
+};
+use crate::layout::{Builtin, InLayout, LayoutInterner, LayoutRepr, Niche, STLayoutInterner};
+
+pub fn use_vectorized_builtins<'a>(
+    arena: &'a Bump,
+    interner: &STLayoutInterner<'a>,
+    procedures: &mut MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+) {
+    let vectorizable = vectorizable_specializations(interner, procedures);
+
+    if vectorizable.is_empty() {
+        return;
+    }
+
+    for proc in procedures.values_mut() {
+        proc.body = vectorize_stmt(arena, &vectorizable, &proc.body);
+    }
+}
+
+/// The specializations of `List.contains` that can use the vectorized implementation.
+fn vectorizable_specializations<'a>(
+    interner: &STLayoutInterner<'a>,
+    procedures: &MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+) -> MutSet<ProcLayout<'a>> {
+    procedures
+        .keys()
+        .filter(|(symbol, proc_layout)| {
+            *symbol == Symbol::LIST_CONTAINS
+                && is_vectorizable_number(interner, proc_layout.arguments[1])
+                && only_uses_builtins(procedures, (*symbol, *proc_layout))
+        })
+        .map(|(_, proc_layout)| *proc_layout)
+        .collect()
+}
+
+fn is_vectorizable_number<'a>(interner: &STLayoutInterner<'a>, layout: InLayout<'a>) -> bool {
+    match interner.get_repr(layout) {
+        LayoutRepr::Builtin(Builtin::Int(int_width)) => {
+            !matches!(int_width, IntWidth::I128 | IntWidth::U128)
+        }
+        LayoutRepr::Builtin(Builtin::Float(_)) => true,
+        _ => false,
+    }
+}
+
+/// An opaque type can wrap a number and implement `Eq` itself, in which case its specialization
+/// has the layout of a number but calls the custom `isEq`, so it must not be replaced. Such a
+/// specialization (transitively) calls a function outside of the builtins.
+fn only_uses_builtins<'a>(
+    procedures: &MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+    start: (Symbol, ProcLayout<'a>),
+) -> bool {
+    let mut seen = MutSet::default();
+    let mut stack = vec![start];
+
+    while let Some(key) = stack.pop() {
+        if !key.0.is_builtin() {
+            return false;
+        }
+
+        if !seen.insert(key) {
+            continue;
+        }
+
+        let Some(proc) = procedures.get(&key) else {
+            // Calls to lowlevel wrappers are replaced by the lowlevel, and don't have a proc
+            match LowLevelWrapperType::from_symbol(key.0) {
+                LowLevelWrapperType::CanBeReplacedBy(_) => continue,
+                LowLevelWrapperType::NotALowLevelWrapper => return false,
+            }
+        };
+
+        if !push_callees(&proc.body, &mut stack) {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// Pushes the functions called in `stmt`, and returns false if it calls a function it can't know.
+fn push_callees<'a>(
+    stmt: &Stmt<'a>,
+    callees: &mut std::vec::Vec<(Symbol, ProcLayout<'a>)>,
+) -> bool {
+    let mut stack = vec![stmt];
+
+    while let Some(stmt) = stack.pop() {
+        match stmt {
+            Stmt::Let(_, expr, _, cont) => {
+                if let Expr::Call(call) = expr {
+                    match &call.call_type {
+                        CallType::ByName {
+                            name,
+                            ret_layout,
+                            arg_layouts,
+                            ..
+                        } => callees.push((
+                            name.name(),
+                            ProcLayout {
+                                arguments: arg_layouts,
+                                result: *ret_layout,
+                                niche: Niche::NONE,
+                            },
+                        )),
+                        CallType::HigherOrder(HigherOrderLowLevel {
+                            passed_function:
+                                PassedFunction {
+                                    name,
+                                    argument_layouts,
+                                    return_layout,
+                                    ..
+                                },
+                            ..
+                        }) => callees.push((
+                            name.name(),
+                            ProcLayout {
+                                arguments: argument_layouts,
+                                result: *return_layout,
+                                niche: Niche::NONE,
+                            },
+                        )),
+                        CallType::ByPointer { .. } | CallType::Foreign { .. } => return false,
+                        CallType::LowLevel { .. } => {}
+                    }
+                }
+
+                stack.push(cont);
+            }
+            Stmt::Switch {
+                branches,
+                default_branch,
+                ..
+            } => {
+                stack.extend(branches.iter().map(|(_, _, branch)| branch));
+                stack.push(default_branch.1);
+            }
+            Stmt::Join {
+                body, remainder, ..
+            } => {
+                stack.push(body);
+                stack.push(remainder);
+            }
+            Stmt::Refcounting(_, cont)
+            | Stmt::Expect {
+                remainder: cont, ..
+            }
+            | Stmt::Dbg {
+                remainder: cont, ..
+            } => stack.push(cont),
+            Stmt::Ret(_) | Stmt::Jump(_, _) | Stmt::Crash(_, _) => {}
+        }
+    }
+
+    true
+}
+
+fn vectorize_stmt<'a>(
+    arena: &'a Bump,
+    vectorizable: &MutSet<ProcLayout<'a>>,
+    stmt: &Stmt<'a>,
+) -> Stmt<'a> {
+    match stmt {
+        Stmt::Let(symbol, expr, layout, cont) => {
+            let expr = match expr {
+                Expr::Call(call) if is_vectorizable_call(vectorizable, call) => Expr::Call(Call {
+                    call_type: CallType::LowLevel {
+                        op: LowLevel::ListContainsNum,
+                        update_mode: UpdateModeId::BACKEND_DUMMY,
+                    },
+                    arguments: call.arguments,
+                }),
+                _ => expr.clone(),
+            };
+
+            Stmt::Let(
+                *symbol,
+                expr,
+                *layout,
+                arena.alloc(vectorize_stmt(arena, vectorizable, cont)),
+            )
+        }
+        Stmt::Switch {
+            cond_symbol,
+            cond_layout,
+            branches,
+            default_branch,
+            ret_layout,
+        } => Stmt::Switch {
+            cond_symbol: *cond_symbol,
+            cond_layout: *cond_layout,
+            branches: branches
+                .iter()
+                .map(|(id, info, branch)| {
+                    (
+                        *id,
+                        info.clone(),
+                        vectorize_stmt(arena, vectorizable, branch),
+                    )
+                })
+                .collect_in::<Vec<_>>(arena)
+                .into_bump_slice(),
+            default_branch: (
+                default_branch.0.clone(),
+                arena.alloc(vectorize_stmt(arena, vectorizable, default_branch.1)),
+            ),
+            ret_layout: *ret_layout,
+        },
+        Stmt::Refcounting(modify_rc, cont) => Stmt::Refcounting(
+            *modify_rc,
+            arena.alloc(vectorize_stmt(arena, vectorizable, cont)),
+        ),
+        Stmt::Expect {
+            condition,
+            region,
+            lookups,
+            variables,
+            remainder,
+        } => Stmt::Expect {
+            condition: *condition,
+            region: *region,
+            lookups,
+            variables,
+            remainder: arena.alloc(vectorize_stmt(arena, vectorizable, remainder)),
+        },
+        Stmt::Dbg {
+            source_location,
+            source,
+            symbol,
+            variable,
+            remainder,
+        } => Stmt::Dbg {
+            source_location,
+            source,
+            symbol: *symbol,
+            variable: *variable,
+            remainder: arena.alloc(vectorize_stmt(arena, vectorizable, remainder)),
+        },
+        Stmt::Join {
+            id,
+            parameters,
+            body,
+            remainder,
+        } => Stmt::Join {
+            id: *id,
+            parameters,
+            body: arena.alloc(vectorize_stmt(arena, vectorizable, body)),
+            remainder: arena.alloc(vectorize_stmt(arena, vectorizable, remainder)),
+        },
+        Stmt::Ret(_) | Stmt::Jump(_, _) | Stmt::Crash(_, _) => stmt.clone(),
+    }
+}
+
+fn is_vectorizable_call<'a>(vectorizable: &MutSet<ProcLayout<'a>>, call: &Call<'a>) -> bool {
+    match call.call_type {
+        CallType::ByName {
+            name,
+            ret_layout,
+            arg_layouts,
+            ..
+        } => {
+            name.name() == Symbol::LIST_CONTAINS
+                && vectorizable.contains(&ProcLayout {
+                    arguments: arg_layouts,
+                    result: ret_layout,
+                    niche: Niche::NONE,
+                })
+        }
+        _ => false,
+    }
+}
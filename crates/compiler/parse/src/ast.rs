@@ -518,6 +518,9 @@ pub enum Expr<'a> {
     // The "crash" keyword
     Crash,
 
+    /// A typed hole, e.g. `???` or `?name`. The name is empty for `???`.
+    TypedHole(&'a str),
+
     // Tags
     Tag(&'a str),
 
@@ -951,6 +954,7 @@ impl<'a, 'b> RecursiveValueDefIter<'a, 'b> {
                 | Var { .. }
                 | Underscore(_)
                 | Crash
+                | TypedHole(_)
                 | Dbg
                 | Try
                 | Tag(_)
@@ -2442,7 +2446,8 @@ impl<'a> Malformed for Expr<'a> {
             Tag(_) |
             OpaqueRef(_) |
             SingleQuote(_) | // This is just a &str - not a bunch of segments
-            Crash => false,
+            Crash |
+            TypedHole(_) => false,
 
             Str(inner) => inner.is_malformed(),
 
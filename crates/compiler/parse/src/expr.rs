@@ -217,6 +217,7 @@ fn loc_term<'a>() -> impl Parser<'a, Loc<Expr<'a>>, EExpr<'a>> {
                     positive_number_literal_help()
                 )),
                 loc(crash_kw()),
+                loc(typed_hole()),
                 loc(specialize_err(EExpr::Dbg, dbg_kw())),
                 loc(try_kw()),
                 // In some contexts we want to parse the `_` as an expression, so it can then be turned into a
@@ -355,6 +356,27 @@ fn crash_kw<'a>() -> impl Parser<'a, Expr<'a>, EExpr<'a>> {
     .trace("crash_kw")
 }
 
+/// A typed hole, either `???` or a `?` that is immediately followed by a name, like `?total`.
+///
+/// Like a unary minus, a `?` that is followed by whitespace is an operator instead.
+fn typed_hole<'a>() -> impl Parser<'a, Expr<'a>, EExpr<'a>> {
+    (move |arena: &'a Bump, state: State<'a>, min_indent: u32| {
+        let start = state.pos();
+
+        if state.bytes().starts_with(b"???") {
+            return Ok((MadeProgress, Expr::TypedHole(""), state.advance(3)));
+        }
+
+        let (_, _, next_state) = byte(b'?', EExpr::Start).parse(arena, state, min_indent)?;
+
+        match lowercase_ident().parse(arena, next_state, min_indent) {
+            Ok((_, name, state)) => Ok((MadeProgress, Expr::TypedHole(name), state)),
+            Err(_) => Err((NoProgress, EExpr::Start(start))),
+        }
+    })
+    .trace("typed_hole")
+}
+
 fn loc_possibly_negative_or_negated_term<'a>(
     check_for_arrow: CheckForArrow,
     allow_negate: bool,
@@ -2191,6 +2213,7 @@ fn expr_to_pattern_help<'a>(arena: &'a Bump, expr: &Expr<'a>) -> Result<Pattern<
         | Expr::UnaryOp(_, _)
         | Expr::TrySuffix { .. }
         | Expr::Crash
+        | Expr::TypedHole(_)
         | Expr::RecordBuilder { .. } => return Err(()),
 
         Expr::Str(string) => Pattern::StrLiteral(string),
@@ -11,9 +11,9 @@ use crate::parser::Progress::{self, *};
 use crate::parser::{
     and, backtrackable, byte, collection_trailing_sep_e, increment_min_indent, loc, map,
     map_with_arena, optional, reset_min_indent, skip_first, skip_second, specialize_err, succeed,
-    then, two_bytes, zero_or_more, EExposes, EHeader, EImports, EPackageEntry, EPackageName,
-    EPackages, EParams, EProvides, ERequires, ETypedIdent, Parser, SourceError, SpaceProblem,
-    SyntaxError,
+    then, two_bytes, zero_or_more, EExposes, EHeader, EImports, EManifest, EManifestField,
+    EPackageEntry, EPackageName, EPackages, EParams, EProvides, ERequires, ETypedIdent, Parser,
+    SourceError, SpaceProblem, SyntaxError,
 };
 use crate::pattern::record_pattern_fields;
 use crate::state::State;
@@ -419,6 +419,7 @@ fn package_header<'a>() -> impl Parser<'a, PackageHeader<'a>, EHeader<'a>> {
         exposes: specialize_err(EHeader::Exposes, exposes_module_collection()),
         before_packages: space0_e(EHeader::IndentStart),
         packages: specialize_err(EHeader::Packages, loc(packages_collection())),
+        manifest: optional(specialize_err(EHeader::Manifest, manifest())),
     })
     .trace("package_header")
 }
@@ -460,6 +461,7 @@ fn old_package_header<'a>() -> impl Parser<'a, PackageHeader<'a>, EHeader<'a>> {
                 exposes: old.exposes.item,
                 before_packages,
                 packages: old.packages.map(|kw| kw.item),
+                manifest: None,
             }
         },
     )
@@ -780,6 +782,74 @@ fn packages_collection<'a>(
     )
 }
 
+#[inline(always)]
+fn manifest<'a>(
+) -> impl Parser<'a, KeywordItem<'a, ManifestKeyword, ManifestFields<'a>>, EManifest<'a>> {
+    record!(KeywordItem {
+        keyword: spaces_around_keyword(
+            ManifestKeyword,
+            EManifest::Manifest,
+            EManifest::IndentManifest,
+            EManifest::IndentListStart
+        ),
+        item: loc(collection_trailing_sep_e(
+            byte(b'{', EManifest::ListStart),
+            specialize_err(EManifest::Field, loc(manifest_field())),
+            byte(b',', EManifest::ListEnd),
+            byte(b'}', EManifest::ListEnd),
+            Spaced::SpaceBefore,
+        )),
+    })
+    .trace("manifest")
+}
+
+fn manifest_field<'a>() -> impl Parser<'a, Spaced<'a, ManifestField<'a>>, EManifestField<'a>> {
+    map_with_arena(
+        and(
+            and(
+                loc(specialize_err(
+                    |_, pos| EManifestField::Name(pos),
+                    lowercase_ident(),
+                )),
+                skip_second(
+                    space0_e(EManifestField::IndentValue),
+                    byte(b':', EManifestField::Colon),
+                ),
+            ),
+            and(space0_e(EManifestField::IndentValue), loc(manifest_value())),
+        ),
+        |arena, ((name, spaces_before_colon), (spaces_after_colon, value))| {
+            Spaced::Item(ManifestField {
+                name,
+                spaces_after_name: merge_spaces(arena, spaces_before_colon, spaces_after_colon),
+                value,
+            })
+        },
+    )
+}
+
+fn manifest_value<'a>() -> impl Parser<'a, ManifestValue<'a>, EManifestField<'a>> {
+    one_of![
+        map(
+            specialize_err(EManifestField::Str, parse_str_literal()),
+            ManifestValue::Str
+        ),
+        map(
+            collection_trailing_sep_e(
+                byte(b'[', EManifestField::Value),
+                loc(map(
+                    specialize_err(EManifestField::Str, parse_str_literal()),
+                    Spaced::Item
+                )),
+                byte(b',', EManifestField::ListEnd),
+                byte(b']', EManifestField::ListEnd),
+                Spaced::SpaceBefore,
+            ),
+            ManifestValue::List
+        ),
+    ]
+}
+
 #[inline(always)]
 fn imports<'a>() -> impl Parser<
     'a,
@@ -1187,6 +1257,7 @@ keywords! {
     ProvidesKeyword => "provides",
     ToKeyword => "to",
     PlatformKeyword => "platform",
+    ManifestKeyword => "manifest",
     // Deprecated
     ImportsKeyword => "imports",
 }
@@ -1268,6 +1339,25 @@ pub struct PackageHeader<'a> {
     pub exposes: Collection<'a, Loc<Spaced<'a, ModuleName<'a>>>>,
     pub before_packages: &'a [CommentOrNewline<'a>],
     pub packages: Loc<Collection<'a, Loc<Spaced<'a, PackageEntry<'a>>>>>,
+    pub manifest: Option<KeywordItem<'a, ManifestKeyword, ManifestFields<'a>>>,
+}
+
+pub type ManifestFields<'a> = Loc<Collection<'a, Loc<Spaced<'a, ManifestField<'a>>>>>;
+
+/// One field of a package's manifest, e.g. `license: "UPL-1.0"`
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ManifestField<'a> {
+    pub name: Loc<&'a str>,
+    pub spaces_after_name: &'a [CommentOrNewline<'a>],
+    pub value: Loc<ManifestValue<'a>>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ManifestValue<'a> {
+    /// e.g. `"1.2.0"`
+    Str(StrLiteral<'a>),
+    /// e.g. `["Ada Lovelace", "Grace Hopper"]`
+    List(Collection<'a, Loc<Spaced<'a, StrLiteral<'a>>>>),
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -0,0 +1,27 @@
+//! The limit on how deeply expressions may be nested.
+//!
+//! Formatting and canonicalization both walk expressions recursively, so code that's nested
+//! deeply enough (usually machine-generated) would otherwise overflow the stack. Past this limit
+//! they stop descending and report that the code is nested too deeply instead of crashing.
+
+use std::sync::OnceLock;
+
+/// The default limit. This leaves plenty of room for handwritten code while keeping the
+/// recursion well within the 8MB stacks the compiler runs on, even in debug builds.
+pub const DEFAULT_MAX_NESTING_DEPTH: usize = 256;
+
+/// The environment variable that overrides [DEFAULT_MAX_NESTING_DEPTH].
+pub const MAX_NESTING_DEPTH_ENV_VAR: &str = "ROC_MAX_NESTING_DEPTH";
+
+/// How deeply expressions may be nested, which is [DEFAULT_MAX_NESTING_DEPTH] unless the
+/// `ROC_MAX_NESTING_DEPTH` environment variable says otherwise.
+pub fn max_nesting_depth() -> usize {
+    static MAX_NESTING_DEPTH: OnceLock<usize> = OnceLock::new();
+
+    *MAX_NESTING_DEPTH.get_or_init(|| match std::env::var(MAX_NESTING_DEPTH_ENV_VAR) {
+        Ok(env_str) => env_str
+            .parse::<usize>()
+            .unwrap_or(DEFAULT_MAX_NESTING_DEPTH),
+        Err(_) => DEFAULT_MAX_NESTING_DEPTH,
+    })
+}
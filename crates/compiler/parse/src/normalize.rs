@@ -14,17 +14,18 @@ use crate::{
     },
     header::{
         AppHeader, ExposedName, ExposesKeyword, HostedHeader, ImportsEntry, ImportsKeyword,
-        KeywordItem, ModuleHeader, ModuleName, ModuleParams, PackageEntry, PackageHeader,
-        PackageKeyword, PackageName, PackagesKeyword, PlatformHeader, PlatformKeyword,
-        PlatformRequires, ProvidesKeyword, ProvidesTo, RequiresKeyword, To, ToKeyword, TypedIdent,
+        KeywordItem, ManifestField, ManifestKeyword, ManifestValue, ModuleHeader, ModuleName,
+        ModuleParams, PackageEntry, PackageHeader, PackageKeyword, PackageName, PackagesKeyword,
+        PlatformHeader, PlatformKeyword, PlatformRequires, ProvidesKeyword, ProvidesTo,
+        RequiresKeyword, To, ToKeyword, TypedIdent,
     },
     ident::{BadIdent, UppercaseIdent},
     parser::{
         EAbility, EClosure, EExpect, EExposes, EExpr, EHeader, EIf, EImport, EImportParams,
-        EImports, EInParens, EList, EPackageEntry, EPackageName, EPackages, EParams, EPattern,
-        EProvides, ERecord, ERequires, EReturn, EString, EType, ETypeAbilityImpl, ETypeApply,
-        ETypeInParens, ETypeInlineAlias, ETypeRecord, ETypeTagUnion, ETypedIdent, EWhen, PInParens,
-        PList, PRecord, SyntaxError,
+        EImports, EInParens, EList, EManifest, EManifestField, EPackageEntry, EPackageName,
+        EPackages, EParams, EPattern, EProvides, ERecord, ERequires, EReturn, EString, EType,
+        ETypeAbilityImpl, ETypeApply, ETypeInParens, ETypeInlineAlias, ETypeRecord, ETypeTagUnion,
+        ETypedIdent, EWhen, PInParens, PList, PRecord, SyntaxError,
     },
 };
 
@@ -64,6 +65,7 @@ keywords! {
     ProvidesKeyword,
     ToKeyword,
     PlatformKeyword,
+    ManifestKeyword,
 }
 
 impl<'a> Normalize<'a> for Defs<'a> {
@@ -161,6 +163,7 @@ impl<'a> Normalize<'a> for Header<'a> {
                 exposes: header.exposes.normalize(arena),
                 before_packages: &[],
                 packages: header.packages.normalize(arena),
+                manifest: header.manifest.normalize(arena),
             }),
             Header::Platform(header) => Header::Platform(PlatformHeader {
                 before_name: &[],
@@ -278,6 +281,25 @@ impl<'a> Normalize<'a> for PackageEntry<'a> {
     }
 }
 
+impl<'a> Normalize<'a> for ManifestField<'a> {
+    fn normalize(&self, arena: &'a Bump) -> Self {
+        ManifestField {
+            name: self.name.normalize(arena),
+            spaces_after_name: &[],
+            value: self.value.normalize(arena),
+        }
+    }
+}
+
+impl<'a> Normalize<'a> for ManifestValue<'a> {
+    fn normalize(&self, arena: &'a Bump) -> Self {
+        match *self {
+            ManifestValue::Str(a) => ManifestValue::Str(a.normalize(arena)),
+            ManifestValue::List(a) => ManifestValue::List(a.normalize(arena)),
+        }
+    }
+}
+
 impl<'a> Normalize<'a> for ImportsEntry<'a> {
     fn normalize(&self, arena: &'a Bump) -> Self {
         match *self {
@@ -723,6 +745,7 @@ impl<'a> Normalize<'a> for Expr<'a> {
                 arena.alloc(b.normalize(arena)),
             ),
             Expr::Crash => Expr::Crash,
+            Expr::TypedHole(name) => Expr::TypedHole(name),
             Expr::Defs(a, b) => fold_defs(arena, a.defs(), b.value.normalize(arena)),
             Expr::Dbg => Expr::Dbg,
             Expr::DbgStmt {
@@ -1666,6 +1689,40 @@ impl<'a> Normalize<'a> for EPackages<'a> {
     }
 }
 
+impl<'a> Normalize<'a> for EManifest<'a> {
+    fn normalize(&self, arena: &'a Bump) -> Self {
+        match self {
+            EManifest::Space(inner_err, _) => EManifest::Space(*inner_err, Position::zero()),
+            EManifest::Manifest(_) => EManifest::Manifest(Position::zero()),
+            EManifest::IndentManifest(_) => EManifest::IndentManifest(Position::zero()),
+            EManifest::IndentListStart(_) => EManifest::IndentListStart(Position::zero()),
+            EManifest::ListStart(_) => EManifest::ListStart(Position::zero()),
+            EManifest::ListEnd(_) => EManifest::ListEnd(Position::zero()),
+            EManifest::Field(inner_err, _) => {
+                EManifest::Field(inner_err.normalize(arena), Position::zero())
+            }
+        }
+    }
+}
+
+impl<'a> Normalize<'a> for EManifestField<'a> {
+    fn normalize(&self, arena: &'a Bump) -> Self {
+        match self {
+            EManifestField::Space(inner_err, _) => {
+                EManifestField::Space(*inner_err, Position::zero())
+            }
+            EManifestField::Name(_) => EManifestField::Name(Position::zero()),
+            EManifestField::Colon(_) => EManifestField::Colon(Position::zero()),
+            EManifestField::IndentValue(_) => EManifestField::IndentValue(Position::zero()),
+            EManifestField::Value(_) => EManifestField::Value(Position::zero()),
+            EManifestField::Str(inner_err, _) => {
+                EManifestField::Str(inner_err.normalize(arena), Position::zero())
+            }
+            EManifestField::ListEnd(_) => EManifestField::ListEnd(Position::zero()),
+        }
+    }
+}
+
 impl<'a> Normalize<'a> for EHeader<'a> {
     fn normalize(&self, arena: &'a Bump) -> Self {
         match self {
@@ -1687,6 +1744,9 @@ impl<'a> Normalize<'a> for EHeader<'a> {
             EHeader::Packages(inner_err, _) => {
                 EHeader::Packages(inner_err.normalize(arena), Position::zero())
             }
+            EHeader::Manifest(inner_err, _) => {
+                EHeader::Manifest(inner_err.normalize(arena), Position::zero())
+            }
             EHeader::Space(inner_err, _) => EHeader::Space(*inner_err, Position::zero()),
             EHeader::Start(_) => EHeader::Start(Position::zero()),
             EHeader::ModuleName(_) => EHeader::ModuleName(Position::zero()),
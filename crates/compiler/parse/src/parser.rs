@@ -113,6 +113,8 @@ impl_space_problem! {
     EInParens<'a>,
     EClosure<'a>,
     EList<'a>,
+    EManifest<'a>,
+    EManifestField<'a>,
     EPackageEntry<'a>,
     EPackages<'a>,
     EPattern<'a>,
@@ -142,6 +144,7 @@ pub enum EHeader<'a> {
     Imports(EImports, Position),
     Requires(ERequires<'a>, Position),
     Packages(EPackages<'a>, Position),
+    Manifest(EManifest<'a>, Position),
 
     Space(BadInputError, Position),
     Start(Position),
@@ -163,6 +166,7 @@ impl<'a> EHeader<'a> {
             EHeader::Imports(_, pos) => Region::from_pos(*pos),
             EHeader::Requires(requires, _pos) => requires.get_region(),
             EHeader::Packages(packages, _pos) => packages.get_region(),
+            EHeader::Manifest(manifest, _pos) => manifest.get_region(),
             EHeader::Space(_, pos) => Region::from_pos(*pos),
             EHeader::Start(pos) => Region::from_pos(*pos),
             EHeader::ModuleName(pos) => Region::from_pos(*pos),
@@ -391,6 +395,58 @@ impl<'a> EPackageEntry<'a> {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EManifest<'a> {
+    Space(BadInputError, Position),
+    Manifest(Position),
+    IndentManifest(Position),
+    IndentListStart(Position),
+    ListStart(Position),
+    ListEnd(Position),
+    Field(EManifestField<'a>, Position),
+}
+
+impl<'a> EManifest<'a> {
+    pub fn get_region(&self) -> Region {
+        let pos = match self {
+            EManifest::Field(field, _) => return field.get_region(),
+            EManifest::Space(_, p)
+            | EManifest::Manifest(p)
+            | EManifest::IndentManifest(p)
+            | EManifest::IndentListStart(p)
+            | EManifest::ListStart(p)
+            | EManifest::ListEnd(p) => p,
+        };
+        Region::from_pos(*pos)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EManifestField<'a> {
+    Space(BadInputError, Position),
+    Name(Position),
+    Colon(Position),
+    IndentValue(Position),
+    Value(Position),
+    Str(EString<'a>, Position),
+    ListEnd(Position),
+}
+
+impl<'a> EManifestField<'a> {
+    pub fn get_region(&self) -> Region {
+        let pos = match self {
+            EManifestField::Space(_, p)
+            | EManifestField::Name(p)
+            | EManifestField::Colon(p)
+            | EManifestField::IndentValue(p)
+            | EManifestField::Value(p)
+            | EManifestField::Str(_, p)
+            | EManifestField::ListEnd(p) => p,
+        };
+        Region::from_pos(*pos)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EImports {
     Open(Position),
@@ -4,12 +4,13 @@ use std::path::PathBuf;
 use roc_collections::all::MutSet;
 use roc_module::called_via::BinOp;
 use roc_module::ident::{Ident, Lowercase, ModuleName, TagName};
-use roc_module::symbol::{ModuleId, Symbol};
+use roc_module::symbol::{Interns, ModuleId, Symbol};
 use roc_parse::ast::Base;
 use roc_parse::pattern::PatternType;
 use roc_region::all::{Loc, Region};
 use roc_types::types::{AliasKind, EarlyReturnKind};
 
+use crate::fix::Fix;
 use crate::Severity;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -32,13 +33,30 @@ pub enum ShadowKind {
     Ability(Symbol),
 }
 
+/// How a pattern binds a name, which decides how to ignore the name instead.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BindingKind {
+    /// A plain name, like `x` or the `x` in `Ok x`
+    Identifier,
+    /// A record field shorthand, like the `x` in `{ x }`, which is also the name of the field
+    RecordField,
+    /// Any other binding, like the `y` in `x as y` or the `x` in `{ x ? 0 }`
+    Other,
+}
+
 /// Problems that can occur in the course of canonicalization.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Problem {
-    UnusedDef(Symbol, Region),
+    /// The symbol of a def that nothing uses, the region of its name, and the region of the whole
+    /// def if removing it can't change what the program does
+    UnusedDef(Symbol, Region, Option<Region>),
     UnusedImport(Symbol, Region),
     UnusedModuleImport(ModuleId, Region),
-    ExposedButNotDefined(Symbol),
+    ExposedButNotDefined {
+        symbol: Symbol,
+        /// Removes the names that aren't defined from the header
+        fix: Option<Fix>,
+    },
     ImportNameConflict {
         name: ModuleName,
         is_alias: bool,
@@ -56,8 +74,9 @@ pub enum Problem {
     /// First symbol is the name of the closure with that argument
     /// Bool is whether the closure is anonymous
     /// Second symbol is the name of the argument that is unused
-    UnusedArgument(Symbol, bool, Symbol, Region),
-    UnusedBranchDef(Symbol, Region),
+    UnusedArgument(Symbol, bool, Symbol, Region, BindingKind),
+    /// A name that a destructuring def or a `when` branch binds, but nothing uses
+    UnusedPatternBinding(Symbol, Region, BindingKind),
     DefsOnlyUsedInRecursion(usize, Region),
     PrecedenceProblem(PrecedenceProblem),
     // Example: (5 = 1 + 2) is an unsupported pattern in an assignment; Int patterns aren't allowed in assignments!
@@ -67,6 +86,15 @@ pub enum Problem {
         shadow: Loc<Ident>,
         kind: ShadowKind,
     },
+    /// A def gives a name that is in scope a new value computed from the old one, like
+    /// `x = x + 1`. The new symbol replaces the old one in the rest of the scope.
+    IntentionalShadowing {
+        original_region: Region,
+        shadow: Loc<Ident>,
+        symbol: Symbol,
+        /// Gives the new value a name of its own
+        fix: Fix,
+    },
     CyclicAlias(Symbol, Region, Vec<Symbol>, AliasKind),
     BadRecursion(Vec<CycleEntry>),
     PhantomTypeArgument {
@@ -156,6 +184,11 @@ pub enum Problem {
     AbilityUsedAsType(Lowercase, Symbol, Region),
     NestedSpecialization(Symbol, Region),
     IllegalDerivedAbility(Region),
+    MissingDeriveHooks {
+        region: Region,
+        ability: Symbol,
+        hooks: Vec<Lowercase>,
+    },
     ImplementationNotFound {
         member: Symbol,
         region: Region,
@@ -264,6 +297,13 @@ pub enum Problem {
         record_region: Region,
     },
     InterpolatedStringNotAllowed(Region),
+    /// A warning that the warning configuration of the package turns into an error
+    DeniedWarning {
+        code: &'static str,
+        warning: Box<Problem>,
+    },
+    /// A `# @warnings` directive that doesn't parse, above the header of the root module
+    InvalidWarningDirective(Box<str>),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -278,7 +318,7 @@ impl Problem {
         use Severity::{Fatal, RuntimeError, Warning};
 
         match self {
-            Problem::UnusedDef(_, _) => Warning,
+            Problem::UnusedDef(_, _, _) => Warning,
             Problem::UnusedImport(_, _) => Warning,
             Problem::UnusedModuleImport(_, _) => Warning,
             Problem::InterpolatedStringNotAllowed(_) => RuntimeError,
@@ -286,12 +326,13 @@ impl Problem {
             Problem::ExplicitBuiltinImport(_, _) => Warning,
             Problem::ExplicitBuiltinTypeImport(_, _) => Warning,
             Problem::ImportShadowsSymbol { .. } => RuntimeError,
-            Problem::ExposedButNotDefined(_) => RuntimeError,
-            Problem::UnusedArgument(_, _, _, _) => Warning,
-            Problem::UnusedBranchDef(_, _) => Warning,
+            Problem::ExposedButNotDefined { .. } => RuntimeError,
+            Problem::UnusedArgument(_, _, _, _, _) => Warning,
+            Problem::UnusedPatternBinding(_, _, _) => Warning,
             Problem::PrecedenceProblem(_) => RuntimeError,
             Problem::UnsupportedPattern(_, _) => RuntimeError,
             Problem::Shadowing { .. } => RuntimeError,
+            Problem::IntentionalShadowing { .. } => Warning,
             Problem::CyclicAlias(..) => RuntimeError,
             Problem::BadRecursion(_) => RuntimeError,
             Problem::PhantomTypeArgument { .. } => Warning,
@@ -321,6 +362,7 @@ impl Problem {
             Problem::AbilityUsedAsType(_, _, _) => RuntimeError,
             Problem::NestedSpecialization(_, _) => RuntimeError, // Ideally, could be compiled
             Problem::IllegalDerivedAbility(_) => RuntimeError,
+            Problem::MissingDeriveHooks { .. } => RuntimeError,
             Problem::ImplementationNotFound { .. } => RuntimeError,
             Problem::NotAnAbilityMember { .. } => RuntimeError,
             Problem::OptionalAbilityImpl { .. } => RuntimeError,
@@ -355,9 +397,53 @@ impl Problem {
             }
             Problem::EmptyTupleType(_) => Warning,
             Problem::UnboundTypeVarsInAs(_) => Warning,
+            Problem::DeniedWarning { .. } => RuntimeError,
+            Problem::InvalidWarningDirective(_) => Warning,
         }
     }
 
+    /// The code that the level of this warning is configured by, which is the title of its
+    /// report in kebab-case. Errors don't have a code, since they can't be silenced.
+    pub fn warning_code(&self) -> Option<&'static str> {
+        let code = match self {
+            Problem::UnusedDef(_, _, _) => "unused-definition",
+            Problem::UnusedPatternBinding(_, _, _) => "unused-pattern-binding",
+            Problem::UnusedImport(_, _) | Problem::UnusedModuleImport(_, _) => "unused-import",
+            Problem::ExplicitBuiltinImport(_, _) | Problem::ExplicitBuiltinTypeImport(_, _) => {
+                "explicit-builtin-import"
+            }
+            Problem::UnusedArgument(_, _, _, _, _) => "unused-argument",
+            Problem::IntentionalShadowing { .. } => "intentional-shadowing",
+            Problem::PhantomTypeArgument { .. } => "unused-type-alias-parameter",
+            Problem::DuplicateRecordFieldValue { .. } => "duplicate-field-name",
+            Problem::DuplicateImplementsAbility { .. } => "duplicate-bound-ability",
+            Problem::DuplicateImpl { .. } => "duplicate-implementation",
+            Problem::NotAnAbility(_) => "not-an-ability",
+            Problem::ImplementsNonRequired { .. } => "unnecessary-implementations",
+            Problem::NoIdentifiersIntroduced(_) => "unnecessary-definition",
+            Problem::OverloadedSpecialization { .. } => "overloaded-specialization",
+            Problem::UnnecessaryOutputWildcard { .. } => "unnecessary-wildcard",
+            Problem::UnderAppliedTry { .. } => "underapplied-try",
+            // The title is plural when there's more than one such def.
+            Problem::DefsOnlyUsedInRecursion(_, _) => "definition-only-used-in-recursion",
+            Problem::ReturnOutsideOfFunction { return_kind, .. } => match return_kind {
+                EarlyReturnKind::Return => "return-outside-of-function",
+                EarlyReturnKind::Try => "try-outside-of-function",
+            },
+            Problem::StatementsAfterReturn { .. } => "unreachable-code",
+            Problem::ReturnAtEndOfFunction { .. } => "unnecessary-return",
+            Problem::UnsuffixedEffectfulRecordField(_) => "missing-exclamation",
+            Problem::SuffixedPureRecordField(_) => "unnecessary-exclamation",
+            Problem::EmptyTupleType(_) => "empty-tuple-type",
+            Problem::UnboundTypeVarsInAs(_) => "unbound-type-variables-in-as",
+            _ => return None,
+        };
+
+        debug_assert_eq!(self.severity(), Severity::Warning);
+
+        Some(code)
+    }
+
     /// Returns a Region value from the Problem, if possible.
     /// Some problems have more than one region; in those cases,
     /// this tries to pick the one that's closest to the original
@@ -366,7 +452,7 @@ impl Problem {
     /// on their Region being outside the expression currently being evaluated.
     pub fn region(&self) -> Option<Region> {
         match self {
-            Problem::UnusedDef(_, region)
+            Problem::UnusedDef(_, region, _)
             | Problem::Shadowing {
                 original_region: region,
                 ..
@@ -381,8 +467,12 @@ impl Problem {
             | Problem::InterpolatedStringNotAllowed(region)
             | Problem::ExplicitBuiltinTypeImport(_, region)
             | Problem::ImportShadowsSymbol { region, .. }
-            | Problem::UnusedArgument(_, _, _, region)
-            | Problem::UnusedBranchDef(_, region)
+            | Problem::UnusedArgument(_, _, _, region, _)
+            | Problem::UnusedPatternBinding(_, region, _)
+            | Problem::IntentionalShadowing {
+                shadow: Loc { region, .. },
+                ..
+            }
             | Problem::PrecedenceProblem(PrecedenceProblem::BothNonAssociative(region, _, _))
             | Problem::UnsupportedPattern(_, region)
             | Problem::CyclicAlias(_, region, _, _)
@@ -447,6 +537,7 @@ impl Problem {
             | Problem::AbilityUsedAsType(_, _, region)
             | Problem::NestedSpecialization(_, region)
             | Problem::IllegalDerivedAbility(region)
+            | Problem::MissingDeriveHooks { region, .. }
             | Problem::ImplementationNotFound { region, .. }
             | Problem::NotAnAbilityMember { region, .. }
             | Problem::OptionalAbilityImpl { region, .. }
@@ -491,7 +582,45 @@ impl Problem {
                 Some(runtime_error.region()).filter(|region| region.is_empty())
             }
 
-            Problem::FileProblem { .. } | Problem::ExposedButNotDefined(_) => None,
+            Problem::DeniedWarning { warning, .. } => warning.region(),
+
+            Problem::FileProblem { .. }
+            | Problem::ExposedButNotDefined { .. }
+            | Problem::InvalidWarningDirective(_) => None,
+        }
+    }
+
+    /// A change to the source code that resolves this problem without changing what the program
+    /// does, if there is one.
+    pub fn fix(&self, interns: &Interns) -> Option<Fix> {
+        match self {
+            Problem::UnusedDef(symbol, _, Some(def_region)) => Some(Fix::replace(
+                format!("Remove `{}`", symbol.as_str(interns)),
+                *def_region,
+                String::new(),
+            )),
+            Problem::UnusedArgument(_, _, symbol, region, kind)
+            | Problem::UnusedPatternBinding(symbol, region, kind) => {
+                let name = symbol.as_str(interns);
+
+                match kind {
+                    BindingKind::Identifier => Some(Fix::replace(
+                        format!("Prefix `{name}` with an underscore"),
+                        *region,
+                        format!("_{name}"),
+                    )),
+                    BindingKind::RecordField => Some(Fix::replace(
+                        format!("Ignore the `{name}` field"),
+                        *region,
+                        format!("{name}: _"),
+                    )),
+                    BindingKind::Other => None,
+                }
+            }
+            Problem::IntentionalShadowing { fix, .. } => Some(fix.clone()),
+            Problem::ExposedButNotDefined { fix, .. } => fix.clone(),
+            Problem::DeniedWarning { warning, .. } => warning.fix(interns),
+            _ => None,
         }
     }
 }
@@ -591,6 +720,9 @@ pub enum RuntimeError {
         /// If the unfound variable is `name` and there's an ignored variable called `_name`,
         /// this is the region where `_name` is defined (for the error message)
         underscored_suggestion_region: Option<Region>,
+        /// The names in scope whose types fit where the unfound variable is used.
+        /// Only known once the module is type checked; empty until then.
+        type_compatible_suggestions: MutSet<Box<str>>,
     },
     OpaqueNotDefined {
         usage: Loc<Ident>,
@@ -689,8 +821,15 @@ pub enum RuntimeError {
     },
 
     NonFunctionHostedAnnotation(Region),
+    /// A hosted function marked `# @c` takes or returns a type that can't be passed to C
+    UnsupportedCFunctionType(Region),
     InvalidTupleIndex(Region),
     IngestedFilePathError(Region),
+    /// An expression nested more deeply than the compiler is willing to recurse
+    NestingTooDeep {
+        region: Region,
+        max_depth: usize,
+    },
 }
 
 impl RuntimeError {
@@ -742,7 +881,9 @@ impl RuntimeError {
             }
             | RuntimeError::ReadIngestedFileError { region, .. }
             | RuntimeError::InvalidUnicodeCodePt(region)
-            | RuntimeError::NonFunctionHostedAnnotation(region) => *region,
+            | RuntimeError::NonFunctionHostedAnnotation(region)
+            | RuntimeError::UnsupportedCFunctionType(region)
+            | RuntimeError::NestingTooDeep { region, .. } => *region,
 
             RuntimeError::UnresolvedTypeVar
             | RuntimeError::ErroneousType
@@ -0,0 +1,32 @@
+//! Fixes for problems, which an editor or tool can apply to the source code without asking,
+//! because they don't change what the program does.
+use roc_region::all::Region;
+
+/// A change to the source code of a module that resolves a problem.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Fix {
+    /// What the fix does, like "Prefix `x` with an underscore"
+    pub title: String,
+    /// The edits to make, which don't overlap
+    pub edits: Vec<Edit>,
+}
+
+/// Replace the source code in a region of a module.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Edit {
+    pub region: Region,
+    pub replacement: String,
+}
+
+impl Fix {
+    /// A fix that replaces the source code in one region.
+    pub fn replace(title: String, region: Region, replacement: String) -> Self {
+        Fix {
+            title,
+            edits: vec![Edit {
+                region,
+                replacement,
+            }],
+        }
+    }
+}
@@ -3,6 +3,8 @@
 // See github.com/roc-lang/roc/issues/800 for discussion of the large_enum_variant check.
 #![allow(clippy::large_enum_variant)]
 pub mod can;
+pub mod fix;
+pub mod mono;
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Severity {
@@ -19,3 +21,41 @@ pub enum Severity {
     /// (e.g. unused def, unused import)
     Warning,
 }
+
+/// The codes of all the warnings, which the warning configuration of a package can allow or deny
+/// by name. A warning's code is the title of its report in kebab-case, e.g. `unused-import` for
+/// `UNUSED IMPORT`.
+pub const WARNING_CODES: &[&str] = &[
+    "definition-only-used-in-recursion",
+    "duplicate-bound-ability",
+    "duplicate-field-name",
+    "duplicate-implementation",
+    "effect-in-pure-function",
+    "effect-in-top-level",
+    "empty-tuple-type",
+    "explicit-builtin-import",
+    "intentional-shadowing",
+    "leftover-statement",
+    "missing-exclamation",
+    "not-an-ability",
+    "overloaded-specialization",
+    "redundant-pattern",
+    "return-outside-of-function",
+    "try-outside-of-function",
+    "typed-hole",
+    "unbound-type-variables-in-as",
+    "underapplied-try",
+    "unexpected-module-params",
+    "unmatchable-pattern",
+    "unnecessary-definition",
+    "unnecessary-exclamation",
+    "unnecessary-implementations",
+    "unnecessary-return",
+    "unnecessary-wildcard",
+    "unreachable-code",
+    "unused-argument",
+    "unused-definition",
+    "unused-import",
+    "unused-pattern-binding",
+    "unused-type-alias-parameter",
+];
@@ -0,0 +1,87 @@
+use roc_module::low_level::LowLevel;
+use roc_module::symbol::Symbol;
+use roc_region::all::Region;
+
+use crate::Severity;
+
+/// A problem found in the specialized program, after type checking succeeded.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Problem {
+    /// A def marked `# @tailrec` makes a call to itself that isn't a tail call.
+    NotTailRecursive {
+        symbol: Symbol,
+        region: Region,
+        call: NonTailCall,
+    },
+    /// A def or expect marked `# @comptime` couldn't be evaluated while compiling, or the
+    /// condition of the expect is false.
+    Comptime(ComptimeFailure),
+}
+
+impl Problem {
+    pub fn severity(&self) -> Severity {
+        match self {
+            Problem::NotTailRecursive { .. } | Problem::Comptime(_) => Severity::RuntimeError,
+        }
+    }
+
+    pub fn symbol(&self) -> Symbol {
+        match self {
+            Problem::NotTailRecursive { symbol, .. } => *symbol,
+            Problem::Comptime(failure) => failure.symbol,
+        }
+    }
+
+    pub fn region(&self) -> Region {
+        match self {
+            Problem::NotTailRecursive { region, .. } => *region,
+            Problem::Comptime(failure) => failure.region,
+        }
+    }
+}
+
+/// A call a proc makes to itself that wasn't turned into a jump, so the proc can't compile to a
+/// loop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NonTailCall {
+    /// The result is passed to this function.
+    PassedTo(Symbol),
+    PassedToLowLevel(LowLevel),
+    /// The result is stored in a record, tag, list, etc.
+    Stored,
+    /// The result is passed to a join point, e.g. because the call is in a branch of an `if` or
+    /// `when` whose result is used afterwards.
+    PassedToJoinPoint,
+    /// The call is followed by refcount decrements, e.g. of a value the proc borrowed.
+    Refcounting,
+    Other,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ComptimeProblem {
+    /// The def is a function, so it doesn't have a single value
+    IsFunction,
+    /// Evaluating the def crashed with this message
+    Crashed(String),
+    /// The def uses an operation the interpreter doesn't support, described in prose
+    Unsupported(String),
+    /// The def calls a function that has no implementation in the program, e.g. a host function
+    UnsupportedCall(Symbol),
+    /// The evaluation didn't finish before running this many statements
+    OutOfFuel { fuel: usize },
+    /// The evaluation made more than this many nested calls
+    TooDeep { max_depth: usize },
+    /// The value contains a function, which can't be embedded in the program
+    ContainsFunction,
+    /// The condition of the expect is false
+    ExpectFailed,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ComptimeFailure {
+    pub symbol: Symbol,
+    pub region: Region,
+    pub problem: ComptimeProblem,
+    /// Whether this is an `expect`, rather than a def
+    pub is_expect: bool,
+}
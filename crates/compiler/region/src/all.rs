@@ -364,25 +364,94 @@ where
     }
 }
 
+/// What the column of a [LineColumn] counts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColumnUnit {
+    /// UTF-8 bytes, like a [Position] does
+    Bytes,
+    /// UTF-16 code units, like the language server protocol does
+    Utf16,
+    /// Characters as they are displayed, so that an accent or an emoji made of several code points
+    /// takes up a single column
+    Graphemes,
+}
+
+/// Converts between byte offsets and lines and columns in a source file. It's built once per file,
+/// and converts columns to and from any [ColumnUnit].
 #[derive(Debug, Clone)]
 pub struct LineInfo {
     line_offsets: Vec<u32>,
+    /// The non-ASCII characters of the file, ordered by offset. Columns only need them when they
+    /// count something other than bytes.
+    wide_chars: Vec<WideChar>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct WideChar {
+    offset: u32,
+    len_utf8: u8,
+    len_utf16: u8,
+    /// Whether it's displayed as part of the character before it
+    joins_previous: bool,
+}
+
+impl WideChar {
+    fn len(&self, unit: ColumnUnit) -> u32 {
+        match unit {
+            ColumnUnit::Bytes => self.len_utf8 as u32,
+            ColumnUnit::Utf16 => self.len_utf16 as u32,
+            ColumnUnit::Graphemes => u32::from(!self.joins_previous),
+        }
+    }
 }
 
 impl LineInfo {
     pub fn new(src: &str) -> LineInfo {
         let mut line_offsets = vec![0];
         line_offsets.extend(src.match_indices('\n').map(|(offset, _)| offset as u32 + 1));
-        LineInfo { line_offsets }
+
+        let mut wide_chars = Vec::new();
+
+        if !src.is_ascii() {
+            let mut joiner = GraphemeJoiner::default();
+
+            for (offset, c) in src.char_indices() {
+                let joins_previous = joiner.joins(c);
+
+                if !c.is_ascii() {
+                    wide_chars.push(WideChar {
+                        offset: offset as u32,
+                        len_utf8: c.len_utf8() as u8,
+                        len_utf16: c.len_utf16() as u8,
+                        joins_previous,
+                    });
+                }
+            }
+        }
+
+        LineInfo {
+            line_offsets,
+            wide_chars,
+        }
     }
 
     pub fn convert_offset(&self, offset: u32) -> LineColumn {
+        self.convert_offset_in(offset, ColumnUnit::Bytes)
+    }
+
+    pub fn convert_offset_in(&self, offset: u32, unit: ColumnUnit) -> LineColumn {
         let search = self.line_offsets.binary_search(&offset);
         let line = match search {
             Ok(i) => i,
             Err(i) => i - 1,
         };
-        let column = offset - self.line_offsets[line];
+        let line_start = self.line_offsets[line];
+        let mut column = offset - line_start;
+        if unit != ColumnUnit::Bytes {
+            for c in self.wide_chars_between(line_start, offset) {
+                column = (column + c.len(unit)).saturating_sub(c.len_utf8 as u32);
+            }
+        }
         LineColumn {
             line: line as u32,
             column,
@@ -393,27 +462,134 @@ impl LineInfo {
         self.convert_offset(pos.offset)
     }
 
+    pub fn convert_pos_in(&self, pos: Position, unit: ColumnUnit) -> LineColumn {
+        self.convert_offset_in(pos.offset, unit)
+    }
+
     pub fn convert_region(&self, region: Region) -> LineColumnRegion {
+        self.convert_region_in(region, ColumnUnit::Bytes)
+    }
+
+    pub fn convert_region_in(&self, region: Region, unit: ColumnUnit) -> LineColumnRegion {
         LineColumnRegion {
-            start: self.convert_pos(region.start()),
-            end: self.convert_pos(region.end()),
+            start: self.convert_pos_in(region.start(), unit),
+            end: self.convert_pos_in(region.end(), unit),
         }
     }
 
     pub fn convert_line_column(&self, lc: LineColumn) -> Position {
-        let offset = self.line_offsets[lc.line as usize] + lc.column;
-        Position::new(offset)
+        self.convert_line_column_in(lc, ColumnUnit::Bytes)
+    }
+
+    /// The position of a line and column. A column in the middle of a character is taken to be at
+    /// the start of it.
+    pub fn convert_line_column_in(&self, lc: LineColumn, unit: ColumnUnit) -> Position {
+        let line_start = self.line_offsets[lc.line as usize];
+
+        if unit == ColumnUnit::Bytes {
+            return Position::new(line_start + lc.column);
+        }
+
+        let line_end = self
+            .line_offsets
+            .get(lc.line as usize + 1)
+            .copied()
+            .unwrap_or(u32::MAX);
+
+        let mut offset = line_start;
+        let mut column = 0;
+
+        for c in self.wide_chars_between(line_start, line_end) {
+            let ascii_len = c.offset - offset;
+
+            if column + ascii_len > lc.column {
+                break;
+            }
+
+            column += ascii_len;
+            offset = c.offset;
+
+            if column + c.len(unit) > lc.column {
+                return Position::new(offset);
+            }
+
+            column += c.len(unit);
+            offset += c.len_utf8 as u32;
+        }
+
+        Position::new(offset + (lc.column - column))
     }
 
     pub fn convert_line_column_region(&self, lc_region: LineColumnRegion) -> Region {
-        let start = self.convert_line_column(lc_region.start);
-        let end = self.convert_line_column(lc_region.end);
+        self.convert_line_column_region_in(lc_region, ColumnUnit::Bytes)
+    }
+
+    pub fn convert_line_column_region_in(
+        &self,
+        lc_region: LineColumnRegion,
+        unit: ColumnUnit,
+    ) -> Region {
+        let start = self.convert_line_column_in(lc_region.start, unit);
+        let end = self.convert_line_column_in(lc_region.end, unit);
         Region::new(start, end)
     }
 
     pub fn num_lines(&self) -> u32 {
         self.line_offsets.len() as u32
     }
+
+    fn wide_chars_between(&self, start: u32, end: u32) -> &[WideChar] {
+        let from = self.wide_chars.partition_point(|c| c.offset < start);
+        let to = self.wide_chars.partition_point(|c| c.offset < end);
+
+        &self.wide_chars[from..to]
+    }
+}
+
+/// How many columns the text takes up when it's displayed, counting in [ColumnUnit::Graphemes].
+pub fn display_width(text: &str) -> u32 {
+    let mut joiner = GraphemeJoiner::default();
+
+    text.chars().filter(|&c| !joiner.joins(c)).count() as u32
+}
+
+/// Tells which characters are displayed as part of the one before them.
+///
+/// This covers combining marks, variation selectors, emoji modifiers and sequences, and flags,
+/// which is what source code is likely to contain, rather than all of Unicode's grapheme cluster
+/// rules.
+#[derive(Debug, Default)]
+struct GraphemeJoiner {
+    after_zero_width_joiner: bool,
+    /// Whether the previous character starts a flag, which takes two regional indicators
+    in_flag: bool,
+}
+
+impl GraphemeJoiner {
+    fn joins(&mut self, c: char) -> bool {
+        let is_regional_indicator = matches!(c, '\u{1F1E6}'..='\u{1F1FF}');
+
+        let joins = self.after_zero_width_joiner
+            || (is_regional_indicator && self.in_flag)
+            || matches!(
+                c,
+                '\u{0300}'..='\u{036F}'
+                    | '\u{1AB0}'..='\u{1AFF}'
+                    | '\u{1DC0}'..='\u{1DFF}'
+                    | '\u{200C}'..='\u{200D}'
+                    | '\u{20D0}'..='\u{20FF}'
+                    | '\u{FE00}'..='\u{FE0F}'
+                    | '\u{FE20}'..='\u{FE2F}'
+                    | '\u{1F3FB}'..='\u{1F3FF}'
+                    | '\u{E0020}'..='\u{E007F}'
+                    | '\u{E0100}'..='\u{E01EF}'
+            );
+
+        self.after_zero_width_joiner = c == '\u{200D}';
+        self.in_flag = is_regional_indicator && !self.in_flag;
+
+        joins
+    }
 }
 
 #[test]
@@ -476,3 +652,41 @@ fn test_line_info() {
 
     check_correctness(&["", ""]);
 }
+
+#[test]
+fn test_line_info_columns() {
+    // `é` is an `e` followed by a combining accent, and `🙂` takes two UTF-16 code units
+    let src = "x = \"e\u{0301}🙂\"\nok\n🇬🇧 z";
+    let info = LineInfo::new(src);
+
+    let z = src.find('z').unwrap() as u32;
+    let closing_quote = src.rfind('"').unwrap() as u32;
+
+    let column = |offset, unit| info.convert_offset_in(offset, unit).column;
+
+    assert_eq!(column(closing_quote, ColumnUnit::Bytes), 12);
+    assert_eq!(column(closing_quote, ColumnUnit::Utf16), 9);
+    assert_eq!(column(closing_quote, ColumnUnit::Graphemes), 7);
+    assert_eq!(column(z, ColumnUnit::Bytes), 9);
+    assert_eq!(column(z, ColumnUnit::Utf16), 5);
+    assert_eq!(column(z, ColumnUnit::Graphemes), 2);
+
+    for unit in [ColumnUnit::Bytes, ColumnUnit::Utf16, ColumnUnit::Graphemes] {
+        for offset in [0, closing_quote, closing_quote + 1, z, src.len() as u32] {
+            let lc = info.convert_offset_in(offset, unit);
+
+            assert_eq!(info.convert_line_column_in(lc, unit), Position::new(offset));
+        }
+    }
+
+    // A column in the middle of `🙂` is at the start of it
+    let smile = src.find('🙂').unwrap() as u32;
+    let middle = LineColumn { line: 0, column: 8 };
+    assert_eq!(
+        info.convert_line_column_in(middle, ColumnUnit::Utf16),
+        Position::new(smile)
+    );
+
+    assert_eq!(display_width("e\u{0301}🙂"), 2);
+    assert_eq!(display_width("👩\u{200D}💻 🇬🇧"), 3);
+}
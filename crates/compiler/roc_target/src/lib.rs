@@ -7,6 +7,7 @@ use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 use roc_error_macros::user_error;
+use strum::IntoEnumIterator;
 use strum_macros::{EnumCount, EnumIter};
 use target_lexicon::Triple;
 
@@ -227,6 +228,23 @@ impl Target {
         format!("libapp.{}", self.dynamic_library_file_ext())
     }
 
+    /// The targets that the platform in the same directory as `platform_main_roc` has a prebuilt host for,
+    /// for either linking strategy. Generic hosts (like `libhost.a`) are not included, because their file
+    /// names don't say which target they were built for.
+    pub fn targets_with_prebuilt_host(platform_main_roc: &Path) -> Vec<Target> {
+        Target::iter()
+            .filter(|target| {
+                let exists =
+                    |file_name: String| platform_main_roc.with_file_name(file_name).exists();
+
+                exists(target.prebuilt_static_library())
+                    || exists(target.prebuilt_static_object())
+                    || (exists(target.prebuilt_surgical_host())
+                        && exists(target.metadata_file_name()))
+            })
+            .collect()
+    }
+
     /// Search for a prebuilt legacy host in the platform main directory.
     pub fn find_legacy_host(&self, platform_main_roc: &Path) -> Result<PathBuf, String> {
         let static_library_path = platform_main_roc.with_file_name(self.prebuilt_static_library());
@@ -23,6 +23,10 @@ use roc_unify::unify::MustImplementConstraints;
 use roc_unify::unify::{MustImplementAbility, Obligated};
 use roc_unify::Env as UEnv;
 
+use crate::ability_trace::{
+    traced_type, AbilityTrace, Candidate, ObligationTrace, ResolutionDecision, ResolutionTrace,
+    TracedType,
+};
 use crate::env::InferenceEnv;
 use crate::{aliases::Aliases, to_var::type_to_var};
 
@@ -112,6 +116,15 @@ type ObligationResult = Result<(), Unfulfilled>;
 pub struct ObligationCache {
     impl_cache: VecMap<ImplKey, ObligationResult>,
     derive_cache: VecMap<RequestedDeriveKey, ObligationResult>,
+    /// Only set when ability resolution is traced.
+    tracer: Option<Tracer>,
+}
+
+struct Tracer {
+    home: ModuleId,
+    /// The candidates looked at for the obligations being checked.
+    candidates: Vec<Candidate>,
+    trace: AbilityTrace,
 }
 
 enum ReadCache {
@@ -124,6 +137,95 @@ pub struct CheckedDerives {
 }
 
 impl ObligationCache {
+    /// A cache that also records a trace of the obligations that aren't fulfilled, and the
+    /// ability members that aren't resolved to a specialization.
+    pub fn traced(home: ModuleId) -> Self {
+        Self {
+            tracer: Some(Tracer {
+                home,
+                candidates: Vec::new(),
+                trace: AbilityTrace::default(),
+            }),
+            ..Default::default()
+        }
+    }
+
+    pub fn take_trace(&mut self) -> Option<AbilityTrace> {
+        self.tracer.take().map(|tracer| tracer.trace)
+    }
+
+    pub(crate) fn trace_resolution(
+        &mut self,
+        subs: &mut Subs,
+        member: Symbol,
+        specialization_var: Variable,
+        result: &Result<Resolved, ResolveError>,
+    ) {
+        let Some(tracer) = self.tracer.as_mut() else {
+            return;
+        };
+
+        if let Some(decision) = ResolutionDecision::from_result(result) {
+            tracer.trace.resolutions.push(ResolutionTrace {
+                module: tracer.home,
+                member,
+                specialization_type: traced_type(subs, specialization_var),
+                decision,
+            });
+        }
+    }
+
+    fn trace_candidate(&mut self, candidate: impl FnOnce() -> Candidate) {
+        if let Some(tracer) = self.tracer.as_mut() {
+            tracer.candidates.push(candidate());
+        }
+    }
+
+    fn traced_candidates(&self) -> usize {
+        self.tracer
+            .as_ref()
+            .map_or(0, |tracer| tracer.candidates.len())
+    }
+
+    /// Finish tracing an obligation whose candidates were traced from `candidates_start` on.
+    fn trace_obligation(
+        &mut self,
+        candidates_start: usize,
+        region: Option<Region>,
+        result: &ObligationResult,
+    ) {
+        let Some(tracer) = self.tracer.as_mut() else {
+            return;
+        };
+
+        let candidates = tracer.candidates.split_off(candidates_start);
+
+        if let Err(unfulfilled) = result {
+            let (typ, ability, region) = match unfulfilled {
+                Unfulfilled::OpaqueDoesNotImplement { typ, ability } => {
+                    (TracedType::Opaque(*typ), *ability, region)
+                }
+                Unfulfilled::AdhocUnderivable { typ, ability, .. } => {
+                    (TracedType::Structural(typ.clone()), *ability, region)
+                }
+                Unfulfilled::OpaqueUnderivable {
+                    ability,
+                    opaque,
+                    derive_region,
+                    ..
+                } => (TracedType::Opaque(*opaque), *ability, Some(*derive_region)),
+            };
+
+            tracer.trace.obligations.push(ObligationTrace {
+                module: tracer.home,
+                region,
+                ability,
+                typ,
+                candidates,
+            });
+        }
+    }
+
     #[must_use]
     pub fn check_derives(
         &mut self,
@@ -166,11 +268,11 @@ impl ObligationCache {
     ) -> Vec<TypeError> {
         let must_implement = must_implement.get_unique();
 
-        let mut get_unfulfilled = |must_implement: &[MustImplementAbility]| {
+        let mut get_unfulfilled = |must_implement: &[MustImplementAbility], region| {
             must_implement
                 .iter()
                 .filter_map(|mia| {
-                    self.check_one(subs, abilities_store, *mia)
+                    self.check_one(subs, abilities_store, *mia, Some(region))
                         .as_ref()
                         .err()
                         .cloned()
@@ -193,7 +295,7 @@ impl ObligationCache {
                 incomplete_not_in_context.extend(must_implement);
             }
             BadExpr(region, category, var) => {
-                let unfulfilled = get_unfulfilled(&must_implement);
+                let unfulfilled = get_unfulfilled(&must_implement, region);
 
                 if !unfulfilled.is_empty() {
                     // Demote the bad variable that exposed this problem to an error, both so
@@ -210,7 +312,7 @@ impl ObligationCache {
                 }
             }
             BadPattern(region, category, var) => {
-                let unfulfilled = get_unfulfilled(&must_implement);
+                let unfulfilled = get_unfulfilled(&must_implement, region);
 
                 if !unfulfilled.is_empty() {
                     // Demote the bad variable that exposed this problem to an error, both so
@@ -234,7 +336,7 @@ impl ObligationCache {
             // If the obligation is already cached, we must have already reported it in another
             // context.
             if !self.has_cached(mia) && !reported_in_context.contains(&mia) {
-                if let Err(unfulfilled) = self.check_one(subs, abilities_store, mia) {
+                if let Err(unfulfilled) = self.check_one(subs, abilities_store, mia, None) {
                     problems.push(TypeError::UnfulfilledAbility(unfulfilled.clone()));
                 }
             }
@@ -248,15 +350,21 @@ impl ObligationCache {
         subs: &mut Subs,
         abilities_store: &AbilitiesStore,
         mia: MustImplementAbility,
+        region: Option<Region>,
     ) -> ObligationResult {
         let MustImplementAbility { typ, ability } = mia;
+        let candidates_start = self.traced_candidates();
 
-        match typ {
+        let result = match typ {
             Obligated::Adhoc(var) => self.check_adhoc(subs, abilities_store, var, ability),
             Obligated::Opaque(opaque) => self
                 .check_opaque_and_read(abilities_store, opaque, ability)
                 .clone(),
-        }
+        };
+
+        self.trace_obligation(candidates_start, region, &result);
+
+        result
     }
 
     fn has_cached(&self, mia: MustImplementAbility) -> bool {
@@ -336,6 +444,15 @@ impl ObligationCache {
             None => Some(UnderivableReason::NotABuiltin),
         };
 
+        self.trace_candidate(|| Candidate::Derive {
+            typ: traced_type(subs, var),
+            ability,
+            result: match &opt_underivable {
+                None => Ok(()),
+                Some(reason) => Err(reason.clone()),
+            },
+        });
+
         if let Some(underivable_reason) = opt_underivable {
             let error_type = subs.var_to_error_type(var, Polarity::OF_VALUE);
 
@@ -373,12 +490,19 @@ impl ObligationCache {
     }
 
     fn check_impl(&mut self, abilities_store: &AbilitiesStore, impl_key: ImplKey) {
-        if self.impl_cache.get(&impl_key).is_some() {
+        let ImplKey { opaque, ability } = impl_key;
+
+        if let Some(cached) = self.impl_cache.get(&impl_key) {
+            let found = cached.is_ok();
+            self.trace_candidate(|| Candidate::Cached {
+                opaque,
+                ability,
+                found,
+            });
+
             return;
         }
 
-        let ImplKey { opaque, ability } = impl_key;
-
         // Every type has the Inspect ability automatically, even opaques with no `implements` declaration.
         let is_inspect = ability == Symbol::INSPECT_INSPECT_ABILITY;
         let has_known_impl =
@@ -395,7 +519,22 @@ impl ObligationCache {
             _ => false,
         };
 
-        let obligation_result = if has_known_impl || builtin_opaque_impl_ok() {
+        self.trace_candidate(|| Candidate::DeclaredImpl {
+            opaque,
+            ability,
+            found: has_known_impl,
+        });
+
+        let has_builtin_impl = !has_known_impl && builtin_opaque_impl_ok();
+        if !has_known_impl {
+            self.trace_candidate(|| Candidate::BuiltinOpaqueImpl {
+                opaque,
+                ability,
+                found: has_builtin_impl,
+            });
+        }
+
+        let obligation_result = if has_known_impl || has_builtin_impl {
             Ok(())
         } else {
             Err(Unfulfilled::OpaqueDoesNotImplement {
@@ -439,6 +578,7 @@ impl ObligationCache {
 
         // Now we check whether the structural type behind the opaque is derivable, since that's
         // what we'll need to generate an implementation for during codegen.
+        let candidates_start = self.traced_candidates();
         let real_var_result =
             self.check_adhoc(subs, abilities_store, opaque_real_var, derive_key.ability);
 
@@ -458,6 +598,7 @@ impl ObligationCache {
             },
             _ => internal_error!("unexpected underivable result"),
         });
+        self.trace_obligation(candidates_start, None, &root_result);
 
         // Remove the derive result because the specialization check should take priority.
         let check_has_fake = self.impl_cache.remove(&impl_key);
@@ -0,0 +1,274 @@
+//! A structured trace of how ability obligations are checked and how uses of ability members
+//! are resolved to an implementation, for debugging why an implementation wasn't chosen.
+//!
+//! Tracing is off unless [`crate::module::SolveConfig::trace_abilities`] is set. Only the
+//! obligations that weren't fulfilled and the resolutions that didn't pick an implementation
+//! are kept, since those are the ones that need explaining.
+
+use roc_derive_key::DeriveError;
+use roc_module::symbol::{Interns, ModuleId, Symbol};
+use roc_region::all::Region;
+use roc_solve_problem::{NotDerivableContext, UnderivableReason};
+use roc_types::subs::{Subs, Variable};
+use roc_types::types::{write_error_type, ErrorType, Polarity};
+use serde_json::{json, Value};
+
+use crate::ability::{ResolveError, Resolved};
+
+#[derive(Debug, Default, Clone)]
+pub struct AbilityTrace {
+    pub obligations: Vec<ObligationTrace>,
+    pub resolutions: Vec<ResolutionTrace>,
+}
+
+/// How an `implements` obligation that wasn't fulfilled was checked.
+#[derive(Debug, Clone)]
+pub struct ObligationTrace {
+    pub module: ModuleId,
+    /// Where the obligation came from, if it's tied to an expression, pattern or `implements`
+    /// clause.
+    pub region: Option<Region>,
+    pub ability: Symbol,
+    pub typ: TracedType,
+    /// Every implementation that was looked for, in order. Checking a structural type looks for
+    /// implementations for the types nested in it too.
+    pub candidates: Vec<Candidate>,
+}
+
+#[derive(Debug, Clone)]
+pub enum TracedType {
+    Opaque(Symbol),
+    Structural(ErrorType),
+}
+
+/// One place an ability implementation was looked for.
+#[derive(Debug, Clone)]
+pub enum Candidate {
+    /// The opaque type lists the ability in its `implements` clause.
+    DeclaredImpl {
+        opaque: Symbol,
+        ability: Symbol,
+        found: bool,
+    },
+    /// Some builtin opaque types implement abilities without listing them.
+    BuiltinOpaqueImpl {
+        opaque: Symbol,
+        ability: Symbol,
+        found: bool,
+    },
+    /// An earlier check of the same opaque type and ability was reused.
+    Cached {
+        opaque: Symbol,
+        ability: Symbol,
+        found: bool,
+    },
+    /// Structural types get builtin abilities derived, if every type nested in them can
+    /// derive the ability too.
+    Derive {
+        typ: ErrorType,
+        ability: Symbol,
+        result: Result<(), UnderivableReason>,
+    },
+}
+
+/// A use of an ability member that wasn't resolved to a specialization the user wrote.
+#[derive(Debug, Clone)]
+pub struct ResolutionTrace {
+    pub module: ModuleId,
+    pub member: Symbol,
+    /// The type the member is used at.
+    pub specialization_type: ErrorType,
+    pub decision: ResolutionDecision,
+}
+
+#[derive(Debug, Clone)]
+pub enum ResolutionDecision {
+    /// The type implementing the ability is structural or a builtin opaque type, so the
+    /// implementation is derived.
+    Derived,
+    /// No type implementing the ability was found. Either the type isn't known yet, e.g. because
+    /// it's a type variable and the member is resolved when it's specialized, or the type doesn't
+    /// implement the ability.
+    Unresolved,
+    /// The member's ability isn't a builtin ability, so it can't be derived.
+    NotDerivableAbility,
+    /// The type has a variable that isn't known yet, so whether it can derive the ability isn't
+    /// known either.
+    UnboundVar,
+    /// The type can't derive the ability.
+    Underivable,
+}
+
+impl ResolutionDecision {
+    /// Whether a resolution is worth tracing; uses that resolved to a user's specialization are
+    /// not.
+    pub fn from_result(result: &Result<Resolved, ResolveError>) -> Option<Self> {
+        match result {
+            Ok(Resolved::Specialization(_)) => None,
+            Ok(Resolved::Derive(_)) => Some(Self::Derived),
+            Err(ResolveError::NoTypeImplementingSpecialization) => Some(Self::Unresolved),
+            Err(ResolveError::NonDerivableAbility(_)) => Some(Self::NotDerivableAbility),
+            Err(ResolveError::DeriveError(DeriveError::UnboundVar)) => Some(Self::UnboundVar),
+            Err(ResolveError::DeriveError(DeriveError::Underivable)) => Some(Self::Underivable),
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Derived => "derived",
+            Self::Unresolved => "unresolved",
+            Self::NotDerivableAbility => "not-derivable-ability",
+            Self::UnboundVar => "unbound-var",
+            Self::Underivable => "underivable",
+        }
+    }
+}
+
+/// The type of a variable, for a trace. Naming the variable's unnamed type variables is rolled
+/// back, so tracing doesn't affect the rest of solving.
+pub(crate) fn traced_type(subs: &mut Subs, var: Variable) -> ErrorType {
+    let snapshot = subs.snapshot();
+    let error_type = subs.var_to_error_type(var, Polarity::OF_VALUE);
+    subs.rollback_to(snapshot);
+
+    error_type
+}
+
+impl AbilityTrace {
+    pub fn is_empty(&self) -> bool {
+        self.obligations.is_empty() && self.resolutions.is_empty()
+    }
+
+    pub fn extend(&mut self, other: AbilityTrace) {
+        self.obligations.extend(other.obligations);
+        self.resolutions.extend(other.resolutions);
+    }
+
+    /// One JSON object per line: first the unfulfilled obligations, then the resolutions.
+    pub fn to_json_lines(&self, interns: &Interns) -> String {
+        let obligations = self.obligations.iter().map(|obligation| {
+            json!({
+                "kind": "obligation",
+                "module": obligation.module.to_ident_str(interns).as_str(),
+                "region": obligation.region.map(region_to_json),
+                "ability": symbol_name(obligation.ability, interns),
+                "type": traced_type_to_json(&obligation.typ, interns),
+                "candidates": obligation
+                    .candidates
+                    .iter()
+                    .map(|candidate| candidate_to_json(candidate, interns))
+                    .collect::<Vec<_>>(),
+            })
+        });
+
+        let resolutions = self.resolutions.iter().map(|resolution| {
+            json!({
+                "kind": "resolution",
+                "module": resolution.module.to_ident_str(interns).as_str(),
+                "member": symbol_name(resolution.member, interns),
+                "type": write_error_type(interns, resolution.specialization_type.clone()),
+                "decision": resolution.decision.as_str(),
+            })
+        });
+
+        obligations
+            .chain(resolutions)
+            .map(|value| format!("{value}\n"))
+            .collect()
+    }
+}
+
+fn symbol_name(symbol: Symbol, interns: &Interns) -> String {
+    format!(
+        "{}.{}",
+        symbol.module_string(interns),
+        symbol.as_str(interns)
+    )
+}
+
+fn region_to_json(region: Region) -> Value {
+    json!({
+        "start": region.start().offset,
+        "end": region.end().offset,
+    })
+}
+
+fn traced_type_to_json(typ: &TracedType, interns: &Interns) -> Value {
+    match typ {
+        TracedType::Opaque(opaque) => json!({ "opaque": symbol_name(*opaque, interns) }),
+        TracedType::Structural(typ) => {
+            json!({ "structural": write_error_type(interns, typ.clone()) })
+        }
+    }
+}
+
+fn candidate_to_json(candidate: &Candidate, interns: &Interns) -> Value {
+    match candidate {
+        Candidate::DeclaredImpl {
+            opaque,
+            ability,
+            found,
+        }
+        | Candidate::BuiltinOpaqueImpl {
+            opaque,
+            ability,
+            found,
+        }
+        | Candidate::Cached {
+            opaque,
+            ability,
+            found,
+        } => {
+            let kind = match candidate {
+                Candidate::DeclaredImpl { .. } => "declared-impl",
+                Candidate::BuiltinOpaqueImpl { .. } => "builtin-opaque-impl",
+                _ => "cached",
+            };
+
+            json!({
+                "kind": kind,
+                "opaque": symbol_name(*opaque, interns),
+                "ability": symbol_name(*ability, interns),
+                "found": found,
+            })
+        }
+        Candidate::Derive {
+            typ,
+            ability,
+            result,
+        } => {
+            let (derivable, reason, nested) = match result {
+                Ok(()) => (true, None, None),
+                Err(UnderivableReason::NotABuiltin) => (false, Some("not-a-builtin-ability"), None),
+                Err(UnderivableReason::SurfaceNotDerivable(context)) => {
+                    (false, Some(context_str(context)), None)
+                }
+                Err(UnderivableReason::NestedNotDerivable(nested, context)) => (
+                    false,
+                    Some(context_str(context)),
+                    Some(write_error_type(interns, nested.clone())),
+                ),
+            };
+
+            json!({
+                "kind": "derive",
+                "type": write_error_type(interns, typ.clone()),
+                "ability": symbol_name(*ability, interns),
+                "derivable": derivable,
+                "reason": reason,
+                "nested_type": nested,
+            })
+        }
+    }
+}
+
+fn context_str(context: &NotDerivableContext) -> &'static str {
+    match context {
+        NotDerivableContext::NoContext => "not-derivable",
+        NotDerivableContext::Function => "function",
+        NotDerivableContext::UnboundVar => "unbound-var",
+        NotDerivableContext::Opaque(_) => "opaque-without-impl",
+        NotDerivableContext::DecodeOptionalRecordField(_) => "optional-record-field",
+        NotDerivableContext::Eq(_) => "not-eq",
+    }
+}
@@ -7,6 +7,7 @@
 #![allow(clippy::too_many_arguments)]
 
 pub mod ability;
+pub mod ability_trace;
 pub mod module;
 pub mod solve;
 pub mod specialize;
@@ -1,3 +1,4 @@
+use crate::ability_trace::AbilityTrace;
 use crate::solve::RunSolveOutput;
 use crate::FunctionKind;
 use crate::{aliases::Aliases, solve};
@@ -37,6 +38,13 @@ impl<T> Solved<T> {
 pub struct SolvedModule {
     pub problems: Vec<TypeError>,
 
+    /// The values in scope that fit where names that aren't in scope were used,
+    /// for ranking the suggestions in those problems.
+    pub compatible_suggestions: solve::CompatibleSuggestions,
+
+    /// Why ability obligations weren't fulfilled, if [SolveConfig::trace_abilities] was set
+    pub ability_trace: Option<AbilityTrace>,
+
     /// all aliases and their definitions. this has to include non-exposed aliases
     /// because exposed aliases can depend on non-exposed ones)
     pub aliases: MutMap<Symbol, (bool, Alias)>,
@@ -86,13 +94,19 @@ pub struct SolveConfig<'a> {
     /// Module params
     pub module_params: Option<ModuleParams>,
     pub module_params_vars: VecMap<ModuleId, Variable>,
+
+    /// Record why ability obligations weren't fulfilled and how ability members were resolved.
+    /// See [crate::ability_trace].
+    pub trace_abilities: bool,
 }
 
 pub struct SolveOutput {
     pub subs: Solved<Subs>,
     pub scope: solve::Scope,
     pub errors: Vec<TypeError>,
+    pub compatible_suggestions: solve::CompatibleSuggestions,
     pub resolved_abilities_store: AbilitiesStore,
+    pub ability_trace: Option<AbilityTrace>,
 
     #[cfg(debug_assertions)]
     pub checkmate: Option<roc_checkmate::Collector>,
@@ -125,6 +139,8 @@ pub fn run_solve(
     let RunSolveOutput {
         solved,
         scope,
+        compatible_suggestions,
+        ability_trace,
         #[cfg(debug_assertions)]
         checkmate,
     } = solve::run(
@@ -139,7 +155,9 @@ pub fn run_solve(
         subs: solved,
         scope,
         errors: problems,
+        compatible_suggestions,
         resolved_abilities_store: abilities_store,
+        ability_trace,
         #[cfg(debug_assertions)]
         checkmate,
     }
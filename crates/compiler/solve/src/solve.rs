@@ -2,6 +2,7 @@ use crate::ability::{
     resolve_ability_specialization, type_implementing_specialization, AbilityImplError,
     CheckedDerives, ObligationCache, PendingDerivesTable, Resolved,
 };
+use crate::ability_trace::AbilityTrace;
 use crate::deep_copy::deep_copy_var_in;
 use crate::env::{DerivedEnv, InferenceEnv};
 use crate::module::{SolveConfig, Solved};
@@ -16,23 +17,25 @@ use roc_can::abilities::{AbilitiesStore, MemberSpecializationInfo};
 use roc_can::constraint::Constraint::{self, *};
 use roc_can::constraint::{
     Cycle, FxCallConstraint, FxSuffixConstraint, FxSuffixKind, Generalizable, LetConstraint,
-    OpportunisticResolve, TryTargetConstraint,
+    OpportunisticResolve, TryTargetConstraint, TypedHoleConstraint,
 };
 use roc_can::expected::{Expected, PExpected};
 use roc_can::module::ModuleParams;
 use roc_collections::{VecMap, VecSet};
 use roc_error_macros::internal_error;
-use roc_module::ident::IdentSuffix;
+use roc_module::ident::{IdentSuffix, Lowercase};
 use roc_module::symbol::{ModuleId, Symbol};
 use roc_problem::can::CycleEntry;
 use roc_region::all::{Loc, Region};
-use roc_solve_problem::TypeError;
+use roc_solve_problem::{HoleCompletion, TypeError};
 use roc_solve_schema::UnificationMode;
 use roc_types::subs::{
     self, Content, ErrorTypeContext, FlatType, GetSubsSlice, Mark, OptVariable, Rank, Subs, TagExt,
     UlsOfVar, Variable,
 };
-use roc_types::types::{Category, Polarity, Reason, RecordField, Type, TypeExtension, Types, Uls};
+use roc_types::types::{
+    Category, ErrorType, Polarity, Reason, RecordField, Type, TypeExtension, Types, Uls,
+};
 use roc_unify::unify::{
     unify, unify_introduced_ability_specialization, Obligated, SpecializationLsetCollector,
     Unified::*,
@@ -100,6 +103,8 @@ struct State {
 pub struct RunSolveOutput {
     pub solved: Solved<Subs>,
     pub scope: Scope,
+    pub compatible_suggestions: CompatibleSuggestions,
+    pub ability_trace: Option<AbilityTrace>,
 
     #[cfg(debug_assertions)]
     pub checkmate: Option<roc_checkmate::Collector>,
@@ -124,7 +129,7 @@ fn run_help(
 ) -> RunSolveOutput {
     let subs = &mut owned_subs;
     let SolveConfig {
-        home: _,
+        home,
         constraints,
         root_constraint,
         mut types,
@@ -135,6 +140,7 @@ fn run_help(
         module_params,
         module_params_vars,
         host_exposed_symbols,
+        trace_abilities,
         ..
     } = config;
 
@@ -143,7 +149,11 @@ fn run_help(
     let rank = Rank::toplevel();
     let arena = Bump::new();
 
-    let mut obligation_cache = ObligationCache::default();
+    let mut obligation_cache = if trace_abilities {
+        ObligationCache::traced(home)
+    } else {
+        ObligationCache::default()
+    };
     let mut awaiting_specializations = AwaitingSpecializations::default();
 
     let derived_env = DerivedEnv {
@@ -177,11 +187,15 @@ fn run_help(
     } = obligation_cache.check_derives(env.subs, abilities_store, pending_derives);
     problems.extend(derives_problems);
 
+    let mut compatible_suggestions = Vec::new();
+
     let state = solve(
         &mut env,
+        home,
         types,
         rank,
         problems,
+        &mut compatible_suggestions,
         aliases,
         &root_constraint,
         abilities_store,
@@ -194,6 +208,8 @@ fn run_help(
 
     RunSolveOutput {
         scope: state.scope,
+        compatible_suggestions,
+        ability_trace: obligation_cache.take_trace(),
         #[cfg(debug_assertions)]
         checkmate: env.checkmate,
         solved: Solved(owned_subs),
@@ -228,9 +244,11 @@ enum Work<'a> {
 
 fn solve(
     env: &mut InferenceEnv,
+    home: ModuleId,
     mut can_types: Types,
     rank: Rank,
     problems: &mut Vec<TypeError>,
+    compatible_suggestions: &mut CompatibleSuggestions,
     aliases: &mut Aliases,
     constraint: &Constraint,
     abilities_store: &mut AbilitiesStore,
@@ -255,6 +273,10 @@ fn solve(
         mark: Mark::NONE.next(),
     };
 
+    let mut typed_holes = Vec::new();
+    let mut not_in_scope = Vec::new();
+    let mut accessed_fields = Vec::new();
+
     while let Some(work_item) = stack.pop() {
         let (scope, rank, constraint) = match work_item {
             Work::Constraint {
@@ -634,6 +656,33 @@ fn solve(
                     }
                 }
             }
+            NotInScope(expectation_index, region) => {
+                let expectation = &env.constraints.expectations[expectation_index.index()];
+
+                let expected = either_type_index_to_var(
+                    env,
+                    rank,
+                    problems,
+                    abilities_store,
+                    obligation_cache,
+                    &mut can_types,
+                    aliases,
+                    *expectation.get_type_ref(),
+                );
+
+                // Like with typed holes, wait for the whole module to be solved
+                // before checking which values in scope fit.
+                not_in_scope.push(PendingNotInScope {
+                    var: expected,
+                    region: *region,
+                    in_scope: scope
+                        .vars_by_symbol()
+                        .filter(|(symbol, _)| symbol.module_id() == home)
+                        .collect(),
+                });
+
+                state
+            }
             And(slice) => {
                 let it = env.constraints.constraints[slice.indices()].iter().rev();
                 for sub_constraint in it {
@@ -821,6 +870,29 @@ fn solve(
                     }
                 }
             }
+            AccessedField(field_var, region) => {
+                accessed_fields.push((*field_var, *region));
+
+                state
+            }
+            TypedHole(index) => {
+                let TypedHoleConstraint { var, name, region } =
+                    &env.constraints.typed_hole_constraints[index.index()];
+
+                // The types in scope aren't final until the whole module is solved,
+                // so only remember which variables to report for now.
+                typed_holes.push(PendingTypedHole {
+                    var: *var,
+                    name: name.clone(),
+                    region: *region,
+                    in_scope: scope
+                        .vars_by_symbol()
+                        .filter(|(symbol, _)| symbol.module_id() == home)
+                        .collect(),
+                });
+
+                state
+            }
             FlexToPure(variable) => {
                 let content = env.subs.get_content_without_compacting(*variable);
 
@@ -1390,12 +1462,21 @@ fn solve(
                 member,
                 specialization_id,
             }) => {
-                if let Ok(Resolved::Specialization(specialization)) = resolve_ability_specialization(
+                let resolved = resolve_ability_specialization(
                     env.subs,
                     abilities_store,
                     member,
                     specialization_variable,
-                ) {
+                );
+
+                obligation_cache.trace_resolution(
+                    env.subs,
+                    member,
+                    specialization_variable,
+                    &resolved,
+                );
+
+                if let Ok(Resolved::Specialization(specialization)) = resolved {
                     abilities_store.insert_resolved(specialization_id, specialization);
                 }
 
@@ -1614,9 +1695,186 @@ fn solve(
         };
     }
 
+    for hole in typed_holes {
+        let problem = report_typed_hole(env, hole);
+        problems.push(problem);
+    }
+
+    for lookup in not_in_scope {
+        if let Some(symbols) = values_fitting_lookup(env, &lookup) {
+            compatible_suggestions.push((lookup.region, symbols));
+        }
+    }
+
+    if !problems.is_empty() {
+        for (field_var, region) in accessed_fields {
+            fill_in_missing_field_type(env, problems, field_var, region);
+        }
+    }
+
     state
 }
 
+/// For every lookup of a name that isn't in scope, the values in scope whose types
+/// fit where the name was used.
+pub type CompatibleSuggestions = Vec<(Region, Vec<Symbol>)>;
+
+/// When a record doesn't have a field that's accessed, the type of that field in the
+/// error is whatever was known when the record was checked, which is usually nothing.
+/// Fill in the type the field ended up being used at, so that the report can suggest
+/// the fields with that type first.
+fn fill_in_missing_field_type(
+    env: &mut InferenceEnv<'_>,
+    problems: &mut [TypeError],
+    field_var: Variable,
+    region: Region,
+) {
+    for problem in problems.iter_mut() {
+        if let TypeError::BadExpr(
+            problem_region,
+            _,
+            ErrorType::Record(found_fields, _),
+            Expected::NoExpectation(ErrorType::Record(expected_fields, _)),
+        ) = problem
+        {
+            if *problem_region != region {
+                continue;
+            }
+
+            for (field, expected_field) in expected_fields.iter_mut() {
+                if !found_fields.contains_key(field) {
+                    *expected_field.as_inner_mut() =
+                        env.subs.var_to_error_type(field_var, Polarity::OF_VALUE);
+                }
+            }
+        }
+    }
+}
+
+struct PendingNotInScope {
+    var: Variable,
+    region: Region,
+    in_scope: Vec<(Symbol, Variable)>,
+}
+
+fn values_fitting_lookup(
+    env: &mut InferenceEnv<'_>,
+    lookup: &PendingNotInScope,
+) -> Option<Vec<Symbol>> {
+    // Every value fits where nothing is known about the expected type,
+    // so that can't help rank anything.
+    if matches!(
+        env.subs.get_content_without_compacting(lookup.var),
+        Content::FlexVar(_) | Content::FlexAbleVar(..) | Content::Error
+    ) {
+        return None;
+    }
+
+    let symbols: Vec<_> = lookup
+        .in_scope
+        .iter()
+        .filter(|&&(_, binding_var)| unifies_without_side_effects(env, lookup.var, binding_var))
+        .map(|&(symbol, _)| symbol)
+        .collect();
+
+    (!symbols.is_empty()).then_some(symbols)
+}
+
+struct PendingTypedHole {
+    var: Variable,
+    name: Option<Lowercase>,
+    region: Region,
+    in_scope: Vec<(Symbol, Variable)>,
+}
+
+fn report_typed_hole(env: &mut InferenceEnv<'_>, hole: PendingTypedHole) -> TypeError {
+    let PendingTypedHole {
+        var,
+        name,
+        region,
+        in_scope,
+    } = hole;
+
+    let expects_type_variable = matches!(
+        env.subs.get_content_without_compacting(var),
+        Content::FlexVar(_) | Content::FlexAbleVar(..)
+    );
+
+    // Every value fits in a hole whose type isn't known yet, so only suggest completions
+    // when there's something to go on.
+    let completions = if expects_type_variable {
+        Vec::new()
+    } else {
+        in_scope
+            .iter()
+            .filter_map(|&(symbol, binding_var)| {
+                let arity = hole_completion_arity(env, var, binding_var)?;
+
+                Some(HoleCompletion { symbol, arity })
+            })
+            .collect()
+    };
+
+    let polarity = Polarity::OF_VALUE;
+    let bindings = in_scope
+        .into_iter()
+        .map(|(symbol, binding_var)| (symbol, env.subs.var_to_error_type(binding_var, polarity)))
+        .collect();
+
+    TypeError::TypedHole {
+        region,
+        name,
+        expected: env.subs.var_to_error_type(var, polarity),
+        bindings,
+        completions,
+    }
+}
+
+/// How many arguments a value in scope has to be applied to for the result to fit in the hole,
+/// or `None` if it doesn't fit at all.
+fn hole_completion_arity(
+    env: &mut InferenceEnv<'_>,
+    hole_var: Variable,
+    binding_var: Variable,
+) -> Option<usize> {
+    if unifies_without_side_effects(env, hole_var, binding_var) {
+        return Some(0);
+    }
+
+    match chase_alias_content(env.subs, binding_var).1 {
+        Content::Structure(FlatType::Func(arguments, _closure, ret, _fx)) => {
+            let arity = arguments.len();
+            let ret = *ret;
+
+            unifies_without_side_effects(env, hole_var, ret).then_some(arity)
+        }
+        _ => None,
+    }
+}
+
+fn unifies_without_side_effects(
+    env: &mut InferenceEnv<'_>,
+    var1: Variable,
+    var2: Variable,
+) -> bool {
+    let snapshot = env.subs.snapshot();
+
+    let unifies = matches!(
+        unify(
+            &mut env.uenv(),
+            var1,
+            var2,
+            UnificationMode::EQ,
+            Polarity::OF_VALUE,
+        ),
+        Success { .. }
+    );
+
+    env.subs.rollback_to(snapshot);
+
+    unifies
+}
+
 fn check_named_variables_are_generalized(
     env: &mut InferenceEnv<'_>,
     problems: &mut Vec<TypeError>,
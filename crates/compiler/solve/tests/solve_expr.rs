@@ -38,8 +38,8 @@ mod solve_expr {
         can_problems.retain(|prob| {
             !matches!(
                 prob,
-                roc_problem::can::Problem::UnusedDef(_, _)
-                    | roc_problem::can::Problem::UnusedBranchDef(..)
+                roc_problem::can::Problem::UnusedDef(..)
+                    | roc_problem::can::Problem::UnusedPatternBinding(..)
             )
         });
 
@@ -3188,6 +3188,42 @@ mod solve_expr {
         );
     }
 
+    #[test]
+    fn sqrt_dec() {
+        infer_eq_without_problem(
+            indoc!(
+                r"
+                Num.sqrt_dec
+                "
+            ),
+            "Dec -> Dec",
+        );
+    }
+
+    #[test]
+    fn exp_dec() {
+        infer_eq_without_problem(
+            indoc!(
+                r"
+                Num.exp_dec
+                "
+            ),
+            "Dec -> Dec",
+        );
+    }
+
+    #[test]
+    fn pow_dec() {
+        infer_eq_without_problem(
+            indoc!(
+                r"
+                Num.pow_dec
+                "
+            ),
+            "Dec, Dec -> Dec",
+        );
+    }
+
     #[test]
     fn ceiling() {
         infer_eq_without_problem(
@@ -3862,6 +3898,138 @@ mod solve_expr {
         );
     }
 
+    #[test]
+    fn str_graphemes() {
+        infer_eq_without_problem(
+            indoc!(
+                r"
+                Str.graphemes
+                "
+            ),
+            "Str -> List Str",
+        );
+    }
+
+    #[test]
+    fn str_count_graphemes() {
+        infer_eq_without_problem(
+            indoc!(
+                r"
+                Str.count_graphemes
+                "
+            ),
+            "Str -> U64",
+        );
+    }
+
+    #[test]
+    fn str_truncate() {
+        infer_eq_without_problem(
+            indoc!(
+                r"
+                Str.truncate
+                "
+            ),
+            "Str, U64 -> Str",
+        );
+    }
+
+    #[test]
+    fn str_with_uppercased() {
+        infer_eq_without_problem(
+            indoc!(
+                r"
+                Str.with_uppercased
+                "
+            ),
+            "Str -> Str",
+        );
+    }
+
+    #[test]
+    fn str_with_lowercased() {
+        infer_eq_without_problem(
+            indoc!(
+                r"
+                Str.with_lowercased
+                "
+            ),
+            "Str -> Str",
+        );
+    }
+
+    #[test]
+    fn str_caseless_equals() {
+        infer_eq_without_problem(
+            indoc!(
+                r"
+                Str.caseless_equals
+                "
+            ),
+            "Str, Str -> Bool",
+        );
+    }
+
+    #[test]
+    fn list_sort_by_keys() {
+        infer_eq_without_problem(
+            indoc!(
+                r"
+                List.sort_by_keys
+                "
+            ),
+            "List a, List (a, a -> [EQ, GT, LT]) -> List a",
+        );
+    }
+
+    #[test]
+    fn dict_union() {
+        infer_eq_without_problem(
+            indoc!(
+                r"
+                Dict.union
+                "
+            ),
+            "Dict k v, Dict k v -> Dict k v where k implements Hash & Eq",
+        );
+    }
+
+    #[test]
+    fn dict_intersect() {
+        infer_eq_without_problem(
+            indoc!(
+                r"
+                Dict.intersect
+                "
+            ),
+            "Dict k a, Dict k b -> Dict k a where k implements Hash & Eq",
+        );
+    }
+
+    #[test]
+    fn dict_difference() {
+        infer_eq_without_problem(
+            indoc!(
+                r"
+                Dict.difference
+                "
+            ),
+            "Dict k a, Dict k b -> Dict k a where k implements Hash & Eq",
+        );
+    }
+
+    #[test]
+    fn decode_feed() {
+        infer_eq_without_problem(
+            indoc!(
+                r"
+                Decode.feed
+                "
+            ),
+            "DecodePartial val fmt, List U8 -> { partial : DecodePartial val fmt, values : List val } where fmt implements DecoderFormatting",
+        );
+    }
+
     #[test]
     fn list_take_first() {
         infer_eq_without_problem(
@@ -51,6 +51,19 @@ pub enum TypeError {
     SuffixedPureFunction(Region, FxSuffixKind),
     InvalidTryTarget(Region, ErrorType, TryKind),
     TypeIsNotGeneralized(Region, ErrorType, Generalizable),
+    TypedHole {
+        region: Region,
+        name: Option<Lowercase>,
+        expected: ErrorType,
+        /// The values defined in this module that are in scope at the hole, with their types.
+        bindings: Vec<(Symbol, ErrorType)>,
+        completions: Vec<HoleCompletion>,
+    },
+    /// A warning that the warning configuration of the package turns into an error
+    DeniedWarning {
+        code: &'static str,
+        warning: Box<TypeError>,
+    },
 }
 
 impl TypeError {
@@ -82,9 +95,38 @@ impl TypeError {
             TypeError::SuffixedPureFunction(_, _) => Warning,
             TypeError::InvalidTryTarget(_, _, _) => RuntimeError,
             TypeError::TypeIsNotGeneralized(..) => RuntimeError,
+            // The program still runs, and crashes if it reaches the hole.
+            TypeError::TypedHole { .. } => Warning,
+            TypeError::DeniedWarning { .. } => RuntimeError,
         }
     }
 
+    /// The code that the level of this warning is configured by, which is the title of its
+    /// report in kebab-case. Errors don't have a code, since they can't be silenced.
+    pub fn warning_code(&self) -> Option<&'static str> {
+        let code = match self {
+            TypeError::Exhaustive(roc_exhaustive::Error::Redundant { .. }) => "redundant-pattern",
+            TypeError::Exhaustive(roc_exhaustive::Error::Unmatchable { .. }) => {
+                "unmatchable-pattern"
+            }
+            TypeError::UnexpectedModuleParams(..) => "unexpected-module-params",
+            TypeError::FxInPureFunction(..) => "effect-in-pure-function",
+            TypeError::FxInTopLevel(..) => "effect-in-top-level",
+            TypeError::ExpectedEffectful(_, ExpectEffectfulReason::Stmt) => "leftover-statement",
+            TypeError::ExpectedEffectful(_, ExpectEffectfulReason::Ignored) => {
+                "unnecessary-definition"
+            }
+            TypeError::UnsuffixedEffectfulFunction(..) => "missing-exclamation",
+            TypeError::SuffixedPureFunction(..) => "unnecessary-exclamation",
+            TypeError::TypedHole { .. } => "typed-hole",
+            _ => return None,
+        };
+
+        debug_assert_eq!(self.severity(), Severity::Warning);
+
+        Some(code)
+    }
+
     pub fn region(&self) -> Option<Region> {
         match self {
             TypeError::BadExpr(region, ..)
@@ -104,16 +146,26 @@ impl TypeError {
             | TypeError::UnsuffixedEffectfulFunction(region, _)
             | TypeError::SuffixedPureFunction(region, _)
             | TypeError::InvalidTryTarget(region, _, _)
-            | TypeError::TypeIsNotGeneralized(region, _, _) => Some(*region),
+            | TypeError::TypeIsNotGeneralized(region, _, _)
+            | TypeError::TypedHole { region, .. } => Some(*region),
             TypeError::UnfulfilledAbility(ab, ..) => ab.region(),
             TypeError::Exhaustive(e) => Some(e.region()),
             TypeError::CircularDef(c) => c.first().map(|ce| ce.symbol_region),
             TypeError::IngestedFileBadUtf8(_, _) => None,
             TypeError::IngestedFileUnsupportedType(_, _) => None,
+            TypeError::DeniedWarning { warning, .. } => warning.region(),
         }
     }
 }
 
+/// A value in scope that has the type a typed hole expects, once it's applied to `arity`
+/// arguments.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct HoleCompletion {
+    pub symbol: Symbol,
+    pub arity: usize,
+}
+
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub enum Unfulfilled {
     /// No claimed implementation of an ability for an opaque type.
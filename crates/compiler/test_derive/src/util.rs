@@ -440,6 +440,7 @@ fn check_derived_typechecks_and_golden(
         module_params: None,
         module_params_vars: imported_param_vars,
         host_exposed_symbols: None,
+        trace_abilities: false,
 
         #[cfg(debug_assertions)]
         checkmate: None,
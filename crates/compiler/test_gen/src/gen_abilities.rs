@@ -1223,6 +1223,134 @@ fn decode_list_of_strings() {
     });
 }
 
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn binary_round_trip_record() {
+    crate::helpers::with_larger_debug_stack(|| {
+        assert_evals_to!(
+            indoc!(
+                r#"
+                app "test" provides [main] to "./platform"
+
+                main =
+                    input = { name: "Ada", tags: ["x", "yz"], pos: (-3i32, 1_000u16), balance: 12.75dec }
+
+                    when Binary.from_bytes (Binary.to_bytes input) is
+                        Ok decoded -> decoded == input
+                        Err _ -> Bool.false
+                "#
+            ),
+            true,
+            bool
+        )
+    });
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn binary_encode_tag_union() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            Shape : [Circle U8, Rect U8 U8]
+
+            main =
+                shape : Shape
+                shape = Rect 3 4
+
+                Binary.to_bytes shape
+            "#
+        ),
+        RocList::from_slice(&[1u8, 4, b'R', b'e', b'c', b't', 2, 1, 3, 1, 4]),
+        RocList<u8>
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn binary_rejects_other_versions() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            main =
+                decoded : Result U64 _
+                decoded = Binary.from_bytes [Binary.format_version + 1, 0, 0, 0, 0, 0, 0, 0, 0]
+
+                when decoded is
+                    Err (UnsupportedVersion _) -> Bool.true
+                    _ -> Bool.false
+            "#
+        ),
+        true,
+        bool
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn decode_partial_in_chunks() {
+    crate::helpers::with_larger_debug_stack(|| {
+        assert_evals_to!(
+            &formatdoc!(
+                r#"
+                app "test" provides [main] to "./platform"
+
+                {TAG_LEN_ENCODER_FMT}
+
+                main =
+                    state0 : Decode.DecodePartial U64 _
+                    state0 = Decode.partial tag_len_fmt
+
+                    {{ values: first, partial: state1 }} = Decode.feed state0 (Str.to_utf8 "n1 n2")
+                    {{ values: second, partial: state2 }} = Decode.feed state1 (Str.to_utf8 "3 n4")
+                    {{ values: third, partial: state3 }} = Decode.feed state2 (Str.to_utf8 " n5 ")
+
+                    when Decode.finish state3 is
+                        Ok last -> [first, second, third, last]
+                        Err _ -> []
+                "#
+            ),
+            RocList::from_slice(&[
+                RocList::from_slice(&[1u64]),
+                RocList::from_slice(&[23]),
+                RocList::from_slice(&[4]),
+                RocList::from_slice(&[5]),
+            ]),
+            RocList<RocList<u64>>
+        )
+    });
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn decode_partial_finish_incomplete() {
+    crate::helpers::with_larger_debug_stack(|| {
+        assert_evals_to!(
+            &formatdoc!(
+                r#"
+                app "test" provides [main] to "./platform"
+
+                {TAG_LEN_ENCODER_FMT}
+
+                main =
+                    {{ values, partial }} = Decode.feed (Decode.partial tag_len_fmt) (Str.to_utf8 "s3 abc s5 de")
+
+                    when Decode.finish partial is
+                        Ok _ -> "should have failed"
+                        Err TooShort -> Str.join_with values ","
+                        Err (Leftover _) -> "leftover"
+                "#
+            ),
+            RocStr::from("abc"),
+            RocStr
+        )
+    });
+}
+
 #[test]
 #[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
 fn encode_then_decode_list_of_strings() {
@@ -2327,6 +2455,62 @@ mod inspect {
         );
     }
 
+    #[test]
+    #[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+    fn record_wider_than_a_line() {
+        assert_evals_to!(
+            indoc!(
+                r#"
+            app "test" provides [main] to "./platform"
+
+            main =
+                Inspect.to_str {
+                    name: "an extraordinarily long name",
+                    tags: ["first", "second", "third"],
+                    point: { x: 1, y: 2 },
+                    status: Active 42,
+                }
+            "#
+            ),
+            RocStr::from(indoc!(
+                r#"
+                {
+                    name: "an extraordinarily long name",
+                    point: {x: 1, y: 2},
+                    status: (Active 42),
+                    tags: ["first", "second", "third"],
+                }"#
+            )),
+            RocStr
+        );
+    }
+
+    #[test]
+    #[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+    fn nested_list_wider_than_a_line() {
+        assert_evals_to!(
+            indoc!(
+                r#"
+            app "test" provides [main] to "./platform"
+
+            main =
+                Inspect.to_str { items: ["aaaaaaaaaaaaaaaaaaaaaaaaaaaaaa", "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbb", "cccccccccccccccccccccccccccccc"] }
+            "#
+            ),
+            RocStr::from(indoc!(
+                r#"
+                {
+                    items: [
+                        "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+                        "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbb",
+                        "cccccccccccccccccccccccccccccc",
+                    ],
+                }"#
+            )),
+            RocStr
+        );
+    }
+
     #[test]
     #[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
     fn opaque_automatic() {
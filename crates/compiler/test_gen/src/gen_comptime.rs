@@ -0,0 +1,513 @@
+//! The comptime interpreter in `roc_mono::comptime` has its own implementation of the builtins
+//! that constants are usually built with. Each program here is run twice: once with its
+//! `# @comptime` defs evaluated by the interpreter and embedded in the program, and once with the
+//! directives removed, so that the LLVM backend computes them when the program runs. Both have to
+//! give the same value.
+#![cfg(feature = "gen-llvm")]
+
+use crate::helpers::llvm::assert_evals_to;
+
+#[allow(unused_imports)]
+use indoc::indoc;
+#[allow(unused_imports)]
+use roc_std::{RocDec, RocList, RocStr};
+
+macro_rules! assert_comptime_evals_to {
+    ($src:expr, $expected:expr, $ty:ty) => {{
+        let src: &str = $src;
+        let expected = $expected;
+
+        assert!(
+            src.contains("# @comptime\n"),
+            "no def is marked `# @comptime`"
+        );
+
+        assert_evals_to!(src, expected.clone(), $ty);
+        assert_evals_to!(&src.replace("# @comptime\n", ""), expected, $ty);
+    }};
+}
+
+#[test]
+fn lookup_table() {
+    assert_comptime_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            # @comptime
+            squares : List U64
+            squares = List.map (List.range { start: At 0, end: Before 6 }) \n -> n * n
+
+            main = squares
+            "#
+        ),
+        RocList::from_slice(&[0u64, 1, 4, 9, 16, 25]),
+        RocList<u64>
+    );
+}
+
+#[test]
+fn recursive_function() {
+    assert_comptime_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            fib : U64 -> U64
+            fib = \n -> if n < 2 then n else fib (n - 1) + fib (n - 2)
+
+            # @comptime
+            fibs : List U64
+            fibs = List.map (List.range { start: At 0, end: Before 12 }) fib
+
+            main = fibs
+            "#
+        ),
+        RocList::from_slice(&[0u64, 1, 1, 2, 3, 5, 8, 13, 21, 34, 55, 89]),
+        RocList<u64>
+    );
+}
+
+#[test]
+fn strings() {
+    assert_comptime_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            # @comptime
+            banner : Str
+            banner =
+                words = Str.split_on "  comptime,interpreter  " "," |> List.map Str.trim
+
+                Str.join_with words " and "
+                |> Str.concat (Str.repeat "!" 3)
+                |> Str.with_ascii_uppercased
+
+            main = banner
+            "#
+        ),
+        RocStr::from("COMPTIME AND INTERPRETER!!!"),
+        RocStr
+    );
+}
+
+#[test]
+fn string_lengths() {
+    assert_comptime_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            # @comptime
+            total : U64
+            total = List.walk ["a", "bb", "ccc", "🦅"] 0 \acc, s -> acc + Str.count_utf8_bytes s
+
+            main = total
+            "#
+        ),
+        10,
+        u64
+    );
+}
+
+#[test]
+fn int_to_str() {
+    assert_comptime_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            # @comptime
+            labels : List Str
+            labels = List.map [-128i8, 0, 127] Num.to_str
+
+            main = labels
+            "#
+        ),
+        RocList::from_slice(&[RocStr::from("-128"), RocStr::from("0"), RocStr::from("127"),]),
+        RocList<RocStr>
+    );
+}
+
+#[test]
+fn wrapping_arithmetic() {
+    assert_comptime_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            # @comptime
+            wrapped : List U8
+            wrapped = [Num.add_wrap 250u8 10, Num.sub_wrap 3u8 5, Num.mul_wrap 16u8 17]
+
+            main = wrapped
+            "#
+        ),
+        RocList::from_slice(&[4u8, 254, 16]),
+        RocList<u8>
+    );
+}
+
+#[test]
+fn dec_arithmetic() {
+    assert_comptime_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            # @comptime
+            ratio : Dec
+            ratio = (1.5 * 2.25 - 0.375) / 4
+
+            main = ratio
+            "#
+        ),
+        RocDec::from_str("0.75").unwrap(),
+        RocDec
+    );
+}
+
+#[test]
+fn record_of_constants() {
+    assert_comptime_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            # @comptime
+            config : { ports : List U16, name : Str }
+            config = {
+                ports: List.range { start: At 8080, end: Length 3 },
+                name: Str.concat "server-" (Num.to_str 1u8),
+            }
+
+            main = Str.concat config.name (Num.to_str (List.sum config.ports))
+            "#
+        ),
+        RocStr::from("server-124243"),
+        RocStr
+    );
+}
+
+#[test]
+fn tag_union() {
+    assert_comptime_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            Shape : [Circle U64, Rect U64 U64, Empty]
+
+            area : Shape -> U64
+            area = \shape ->
+                when shape is
+                    Circle r -> 3 * r * r
+                    Rect w h -> w * h
+                    Empty -> 0
+
+            # @comptime
+            shapes : List Shape
+            shapes = [Circle 2, Rect 3 4, Empty]
+
+            main = List.map shapes area
+            "#
+        ),
+        RocList::from_slice(&[12u64, 12, 0]),
+        RocList<u64>
+    );
+}
+
+#[test]
+fn str_predicates() {
+    assert_comptime_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            # @comptime
+            checks : List Bool
+            checks = [
+                Str.is_empty "",
+                Str.is_empty "a",
+                Str.starts_with "comptime" "comp",
+                Str.ends_with "comptime" "comp",
+                Str.caseless_ascii_equals "AbC" "aBc",
+                Str.caseless_equals "ΣΊΣΥΦΟΣ" "σίσυφος",
+                Str.contains "comptime" "pti",
+            ]
+
+            main = checks
+            "#
+        ),
+        RocList::from_slice(&[true, false, true, false, true, true, true]),
+        RocList<bool>
+    );
+}
+
+#[test]
+fn str_transformations() {
+    assert_comptime_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            # @comptime
+            results : List Str
+            results = [
+                Str.trim_start "  a ",
+                Str.trim_end " a  ",
+                Str.with_ascii_lowercased "ABC é",
+                Str.with_uppercased "école",
+                Str.with_lowercased "ÉCOLE",
+                Str.drop_prefix "comptime" "comp",
+                Str.replace_each "a-b-c" "-" "+",
+                Str.reserve "x" 10 |> Str.concat "y",
+                Str.with_capacity 8 |> Str.concat "z" |> Str.release_excess_capacity,
+            ]
+
+            main = results
+            "#
+        ),
+        RocList::from_slice(&[
+            RocStr::from("a "),
+            RocStr::from(" a"),
+            RocStr::from("abc é"),
+            RocStr::from("ÉCOLE"),
+            RocStr::from("école"),
+            RocStr::from("time"),
+            RocStr::from("a+b+c"),
+            RocStr::from("xy"),
+            RocStr::from("z"),
+        ]),
+        RocList<RocStr>
+    );
+}
+
+#[test]
+fn str_bytes() {
+    assert_comptime_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            # @comptime
+            bytes : List U8
+            bytes = List.concat (Str.to_utf8 "é!") (List.concat_utf8 [1] "é")
+
+            main = bytes
+            "#
+        ),
+        RocList::from_slice(&[195u8, 169, 33, 1, 195, 169]),
+        RocList<u8>
+    );
+}
+
+#[test]
+fn list_operations() {
+    assert_comptime_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            # @comptime
+            numbers : List U64
+            numbers =
+                base = List.with_capacity 4 |> List.append 3 |> List.append 1 |> List.prepend 4
+
+                List.concat base (List.sublist [10, 11, 12, 13] { start: 1, len: 2 })
+                |> List.drop_at 1
+                |> List.swap 0 3
+                |> List.set 1 7
+                |> List.reserve 10
+                |> List.release_excess_capacity
+                |> List.append (List.len [1, 2, 3])
+                |> List.append (List.get [5, 6] 1 |> Result.with_default 0)
+                |> List.append (List.get [5, 6] 2 |> Result.with_default 0)
+
+            main = numbers
+            "#
+        ),
+        RocList::from_slice(&[12u64, 7, 11, 4, 3, 6, 0]),
+        RocList<u64>
+    );
+}
+
+#[test]
+fn integer_arithmetic() {
+    assert_comptime_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            order : I64, I64 -> I64
+            order = \a, b ->
+                when Num.compare a b is
+                    LT -> -1
+                    EQ -> 0
+                    GT -> 1
+
+            # @comptime
+            results : List I64
+            results = [
+                Num.div_trunc (-7) 2,
+                Num.div_ceil 7 2,
+                Num.rem (-7) 2,
+                Num.abs (-5),
+                Num.neg 5,
+                Num.pow_int 3 4,
+                Num.to_i64 (Num.to_u8 300u16),
+                order 3 5,
+                if Num.is_multiple_of 12 4 then 1 else 0,
+                if 3 <= 3 && 4 > 3 && !(2 >= 5) && 1 < 2 then 1 else 0,
+            ]
+
+            main = results
+            "#
+        ),
+        RocList::from_slice(&[-3i64, 4, -1, 5, -5, 81, 44, -1, 1, 1]),
+        RocList<i64>
+    );
+}
+
+#[test]
+fn checked_and_saturated_arithmetic() {
+    assert_comptime_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            # @comptime
+            results : List U8
+            results = [
+                Num.add_checked 250u8 10 |> Result.with_default 0,
+                Num.sub_checked 5u8 3 |> Result.with_default 0,
+                Num.mul_checked 16u8 17 |> Result.with_default 1,
+                Num.add_saturated 250u8 10,
+                Num.sub_saturated 3u8 5,
+                Num.mul_saturated 16u8 17,
+            ]
+
+            main = results
+            "#
+        ),
+        RocList::from_slice(&[0u8, 2, 1, 255, 0, 255]),
+        RocList<u8>
+    );
+}
+
+#[test]
+fn integer_bits() {
+    assert_comptime_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            # @comptime
+            results : List U8
+            results = [
+                Num.bitwise_and 0b1100u8 0b1010,
+                Num.bitwise_or 0b1100u8 0b1010,
+                Num.bitwise_xor 0b1100u8 0b1010,
+                Num.shift_left_by 1u8 3,
+                Num.shift_right_zf_by 0b1111_0000u8 2,
+                Num.to_u8 (Num.shift_right_by -16i8 2),
+                Num.count_leading_zero_bits 1u8,
+                Num.count_trailing_zero_bits 8u8,
+                Num.count_one_bits 0b1011u8,
+            ]
+
+            main = results
+            "#
+        ),
+        RocList::from_slice(&[8u8, 14, 6, 8, 60, 252, 7, 3, 3]),
+        RocList<u8>
+    );
+}
+
+#[test]
+fn float_functions() {
+    // The results are rounded, so that the last bits of libm functions, which may differ between
+    // implementations, don't matter.
+    assert_comptime_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            # @comptime
+            results : List I64
+            results = [
+                Num.round (Num.sqrt 2f64 * 1000),
+                Num.round (Num.log 10f64 * 1000),
+                Num.round (Num.sin 1f64 * 1000),
+                Num.round (Num.cos 1f64 * 1000),
+                Num.round (Num.tan 1f64 * 1000),
+                Num.round (Num.asin 0.5f64 * 1000),
+                Num.round (Num.acos 0.5f64 * 1000),
+                Num.round (Num.atan 1f64 * 1000),
+                Num.round (Num.pow 2f64 0.5 * 1000),
+                Num.round (Num.to_f64 7i64 / 2),
+                Num.round (Num.to_f64 1.5f32 * 2),
+                Num.floor -2.5f64,
+                Num.ceiling -2.5f64,
+                Num.round 2.5f64,
+            ]
+
+            main = results
+            "#
+        ),
+        RocList::from_slice(&[
+            1414i64, 2303, 841, 540, 1557, 524, 1047, 785, 1414, 4, 3, -3, -2, 3
+        ]),
+        RocList<i64>
+    );
+}
+
+#[test]
+fn float_predicates() {
+    assert_comptime_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            # @comptime
+            checks : List Bool
+            checks = [
+                Num.is_nan (0f64 / 0),
+                Num.is_infinite (1f64 / 0),
+                Num.is_finite 1f64,
+                Num.is_finite (1f64 / 0),
+            ]
+
+            main = checks
+            "#
+        ),
+        RocList::from_slice(&[true, true, true, false]),
+        RocList<bool>
+    );
+}
+
+#[test]
+fn structural_equality() {
+    assert_comptime_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            # @comptime
+            checks : List Bool
+            checks = [
+                [1, 2] == [1, 2],
+                "a" != "b",
+                { x: 1 } == { x: 2 },
+                Ok 1 == Err "x",
+                !(1 == 2),
+            ]
+
+            main = checks
+            "#
+        ),
+        RocList::from_slice(&[true, true, false, false, true]),
+        RocList<bool>
+    );
+}
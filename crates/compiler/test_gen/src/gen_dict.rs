@@ -13,7 +13,7 @@ use crate::helpers::llvm::assert_evals_to;
 use crate::helpers::wasm::assert_evals_to;
 
 use indoc::indoc;
-use roc_std::{RocList, RocStr};
+use roc_std::{RocList, RocResult, RocStr};
 
 #[test]
 #[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
@@ -523,7 +523,7 @@ fn remove_all_prefer_first() {
                 |> Dict.values
             "
         ),
-        RocList::from_slice(&[1, 5, 3]),
+        RocList::from_slice(&[1, 3, 5]),
         RocList<i64>
     );
 }
@@ -550,3 +550,172 @@ fn walk_sum_keys() {
         i64
     );
 }
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn remove_keeps_insertion_order() {
+    assert_evals_to!(
+        indoc!(
+            r"
+            Dict.empty {}
+                |> Dict.insert 1 10
+                |> Dict.insert 2 20
+                |> Dict.insert 3 30
+                |> Dict.insert 4 40
+                |> Dict.remove 2
+                |> Dict.insert 2 50
+                |> Dict.remove 1
+                |> Dict.values
+            "
+        ),
+        RocList::from_slice(&[30, 40, 50]),
+        RocList<i64>
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn keep_if_keeps_insertion_order() {
+    assert_evals_to!(
+        indoc!(
+            r"
+            Dict.from_list [(1, 1), (2, 2), (3, 3), (4, 4), (5, 5), (6, 6), (7, 7)]
+                |> Dict.keep_if \(k, _) -> k % 2 == 1
+                |> Dict.keys
+            "
+        ),
+        RocList::from_slice(&[1, 3, 5, 7]),
+        RocList<i64>
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn union() {
+    assert_evals_to!(
+        indoc!(
+            r"
+            dict1 : Dict.Dict I64 I64
+            dict1 = Dict.from_list [(1, 1), (2, 2), (3, 3)]
+
+            dict2 : Dict.Dict I64 I64
+            dict2 = Dict.from_list [(4, 400), (2, 200), (5, 500)]
+
+            combined = Dict.union dict1 dict2
+
+            (Dict.keys combined, Dict.values combined, Dict.get combined 5)
+            "
+        ),
+        (
+            RocList::from_slice(&[1, 2, 3, 4, 5]),
+            RocList::from_slice(&[1, 200, 3, 400, 500]),
+            RocResult::ok(500),
+        ),
+        (RocList<i64>, RocList<i64>, RocResult<i64, ()>)
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn union_grows_capacity() {
+    assert_evals_to!(
+        indoc!(
+            r"
+            dict1 : Dict.Dict U64 {}
+            dict1 = List.range { start: At 0, end: Before 10 } |> List.map (\k -> (k, {})) |> Dict.from_list
+
+            dict2 : Dict.Dict U64 {}
+            dict2 = List.range { start: At 5, end: Before 100 } |> List.map (\k -> (k, {})) |> Dict.from_list
+
+            combined = Dict.union dict1 dict2
+
+            all_present = List.all (List.range { start: At 0, end: Before 100 }) \k -> Dict.contains combined k
+
+            all_present && Dict.len combined == 100
+            "
+        ),
+        true,
+        bool
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn intersect() {
+    assert_evals_to!(
+        indoc!(
+            r"
+            dict1 : Dict.Dict I64 Str
+            dict1 = Dict.from_list [(1, "a"), (2, "b"), (3, "c"), (4, "d")]
+
+            dict2 : Dict.Dict I64 {}
+            dict2 = Dict.from_list [(4, {}), (0, {}), (2, {})]
+
+            shared = Dict.intersect dict1 dict2
+
+            (Dict.keys shared, Dict.get shared 1 |> Result.is_err)
+            "
+        ),
+        (RocList::from_slice(&[2, 4]), true),
+        (RocList<i64>, bool)
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn difference() {
+    assert_evals_to!(
+        indoc!(
+            r"
+            dict1 : Dict.Dict I64 I64
+            dict1 = Dict.from_list [(1, 1), (2, 2), (3, 3), (4, 4), (5, 5)]
+
+            dict2 : Dict.Dict I64 Str
+            dict2 = Dict.from_list [(4, "x"), (0, "y"), (2, "z")]
+
+            remaining = Dict.difference dict1 dict2
+
+            (Dict.values remaining, Dict.contains remaining 5, Dict.contains remaining 2)
+            "
+        ),
+        (RocList::from_slice(&[1, 3, 5]), true, false),
+        (RocList<i64>, bool, bool)
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn hash_problems_none() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            Dict.hash_problems ["", "a", "b", "ab", "a"]
+            "#
+        ),
+        RocList::<RocStr>::empty(),
+        RocList<RocStr>
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn hash_problems_eq_ignores_hashed_field() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            Id := { id : U64, label : Str } implements [Eq { is_eq: ids_eq }, Hash]
+
+            ids_eq = \@Id(a), @Id(b) -> a.id == b.id
+
+            main =
+                Dict.hash_problems [@Id({ id: 1, label: "a" }), @Id({ id: 1, label: "b" }), @Id({ id: 2, label: "a" })]
+            "#
+        ),
+        RocList::from_slice(&[RocStr::from(
+            "Values 0 and 1 are equal, but hash differently."
+        )]),
+        RocList<RocStr>
+    );
+}
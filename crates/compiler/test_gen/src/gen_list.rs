@@ -3164,6 +3164,50 @@ fn list_sort_with() {
     );
 }
 
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn list_sort_with_is_stable() {
+    let expected: Vec<u64> = (0..300)
+        .step_by(3)
+        .chain((1..300).step_by(3))
+        .chain((2..300).step_by(3))
+        .collect();
+
+    assert_evals_to!(
+        indoc!(
+            r#"
+            List.range { start: At 0, end: Length 300 }
+            |> List.map (\index -> { key: index % 3, index })
+            |> List.sort_with (\a, b -> Num.compare a.key b.key)
+            |> List.map .index
+            "#
+        ),
+        RocList::from_slice(&expected),
+        RocList<u64>
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn list_sort_by_keys() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            [{ age: 30, score: 1, id: 0 }, { age: 25, score: 1, id: 1 }, { age: 30, score: 2, id: 2 }, { age: 25, score: 1, id: 3 }]
+            |> List.sort_by_keys [\a, b -> Num.compare a.age b.age, \a, b -> Num.compare b.score a.score]
+            |> List.map .id
+            "#
+        ),
+        RocList::from_slice(&[1, 3, 2, 0]),
+        RocList<i64>
+    );
+    assert_evals_to!(
+        "List.sort_by_keys [3, 1, 2] []",
+        RocList::from_slice(&[3, 1, 2]),
+        RocList<i64>
+    );
+}
+
 #[test]
 #[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn list_sort_asc() {
@@ -4287,3 +4331,77 @@ fn list_concat_utf8() {
         RocList<u8>
     )
 }
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn list_set_on_static_literal_copies() {
+    // The literal is static data, so setting an element must copy it rather than write to it,
+    // or the second call would return the modified list.
+    assert_evals_to!(
+        indoc!(
+            r#"
+            table = \{} -> [1, 2, 3]
+
+            first = List.set (table {}) 0 99
+            second = table {}
+
+            List.concat first second
+            "#
+        ),
+        RocList::from_slice(&[99u64, 2, 3, 1, 2, 3]),
+        RocList<u64>
+    )
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn list_append_on_static_literal_copies() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            table = \{} -> [1, 2, 3]
+
+            first = List.append (table {}) 4
+            second = table {}
+
+            List.concat first second
+            "#
+        ),
+        RocList::from_slice(&[1u64, 2, 3, 4, 1, 2, 3]),
+        RocList<u64>
+    )
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn static_literal_f32() {
+    assert_evals_to!(
+        "[1.5f32, -2.25, 3.0]",
+        RocList::from_slice(&[1.5f32, -2.25, 3.0]),
+        RocList<f32>
+    )
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn static_literal_i8() {
+    assert_evals_to!(
+        "[-128i8, -1, 0, 127]",
+        RocList::from_slice(&[-128i8, -1, 0, 127]),
+        RocList<i8>
+    )
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn static_literal_dec() {
+    assert_evals_to!(
+        "[1.5dec, -0.25, 3]",
+        RocList::from_slice(&[
+            RocDec::from_str("1.5").unwrap(),
+            RocDec::from_str("-0.25").unwrap(),
+            RocDec::from(3),
+        ]),
+        RocList<RocDec>
+    )
+}
@@ -635,7 +635,7 @@ fn gen_float_eq() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn gen_add_dec() {
     assert_evals_to!(
         indoc!(
@@ -1868,6 +1868,55 @@ fn pow_f64() {
 #[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn pow_dec() {
     assert_evals_to!("Num.pow 2.0dec 2.0dec", RocDec::from(4), RocDec);
+    assert_evals_to!(
+        "Num.pow_dec 1.05 10.5",
+        RocDec::from_str("1.669120304352457735").unwrap(),
+        RocDec
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn sqrt_dec() {
+    assert_evals_to!("Num.sqrt 16dec", RocDec::from(4), RocDec);
+    assert_evals_to!(
+        "Num.sqrt_dec 2",
+        RocDec::from_str("1.414213562373095049").unwrap(),
+        RocDec
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[should_panic(expected = r#"Roc failed with message: "Decimal square root of a negative number!"#)]
+fn sqrt_dec_negative() {
+    assert_evals_to!("Num.sqrt_dec -1", RocDec::from(0), RocDec);
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn log_dec() {
+    assert_evals_to!("Num.log 1dec", RocDec::from(0), RocDec);
+    assert_evals_to!(
+        "Num.log_dec 2",
+        RocDec::from_str("0.693147180559945309").unwrap(),
+        RocDec
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn exp_dec() {
+    assert_evals_to!(
+        "Num.exp_dec 1",
+        RocDec::from_str("2.718281828459045235").unwrap(),
+        RocDec
+    );
+    assert_evals_to!(
+        "Num.exp_dec -1.5",
+        RocDec::from_str("0.223130160148429829").unwrap(),
+        RocDec
+    );
 }
 
 #[test]
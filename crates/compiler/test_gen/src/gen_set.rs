@@ -120,7 +120,7 @@ fn union() {
                 |> Set.to_list
             "
         ),
-        RocList::from_slice(&[1, 3, 4, 2]),
+        RocList::from_slice(&[1, 2, 3, 4]),
         RocList<i64>
     );
 }
@@ -310,3 +310,21 @@ fn resolve_set_eq_issue_4671() {
         bool
     );
 }
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn to_list_keeps_insertion_order() {
+    assert_evals_to!(
+        indoc!(
+            r"
+            Set.from_list [5, 3, 8, 1, 9]
+                |> Set.remove 3
+                |> Set.insert 3
+                |> Set.remove 8
+                |> Set.to_list
+            "
+        ),
+        RocList::from_slice(&[5, 1, 9, 3]),
+        RocList<i64>
+    );
+}
@@ -2455,3 +2455,94 @@ fn caseless_ascii_equals() {
         bool
     );
 }
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+fn graphemes() {
+    assert_evals_to!(
+        r#"
+        Str.graphemes("é🇨🇦👩‍👩‍👦‍👦\r\n")
+        "#,
+        RocList::from_slice(&[
+            RocStr::from("é"),
+            RocStr::from("🇨🇦"),
+            RocStr::from("👩‍👩‍👦‍👦"),
+            RocStr::from("\r\n"),
+        ]),
+        RocList<RocStr>
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+fn count_graphemes() {
+    assert_evals_to!(
+        r#"
+        Str.count_graphemes("e\u(301)👍🏽!")
+        "#,
+        3,
+        u64
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+fn truncate_keeps_whole_graphemes() {
+    assert_evals_to!(
+        r#"
+        Str.truncate("👩‍👩‍👦‍👦 family", 1)
+        "#,
+        RocStr::from("👩‍👩‍👦‍👦"),
+        RocStr
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+fn with_uppercased() {
+    assert_evals_to!(
+        r#"
+        Str.with_uppercased("café ǆ ı straße")
+        "#,
+        RocStr::from("CAFÉ Ǆ I STRAßE"),
+        RocStr
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn with_uppercased_non_zero_refcount() {
+    assert_evals_to!(
+        r#"
+        original = "ελληνικά ελληνικά ελληνικά ελληνικά ελληνικά ελληνικά"
+        res = Str.with_uppercased(original)
+        Str.drop_prefix(res, original)
+        "#,
+        RocStr::from("ΕΛΛΗΝΙΚΆ ΕΛΛΗΝΙΚΆ ΕΛΛΗΝΙΚΆ ΕΛΛΗΝΙΚΆ ΕΛΛΗΝΙΚΆ ΕΛΛΗΝΙΚΆ"),
+        RocStr
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+fn with_lowercased() {
+    assert_evals_to!(
+        r#"
+        Str.with_lowercased("CAFÉ İ")
+        "#,
+        RocStr::from("café i"),
+        RocStr
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+fn caseless_equals() {
+    assert_evals_to!(
+        r#"
+        Str.caseless_equals("ΣΊΣΥΦΟΣ", "σίσυφος")
+        "#,
+        true,
+        bool
+    );
+}
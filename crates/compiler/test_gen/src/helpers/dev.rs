@@ -56,12 +56,18 @@ pub fn helper(
     }
 
     let load_config = LoadConfig {
-        target: roc_target::Target::LinuxX64,
+        // The same target the module is built for below, e.g. aarch64 on Apple Silicon
+        target: target_lexicon::Triple::host().into(),
         render: roc_reporting::report::RenderTarget::ColorTerminal,
         palette: roc_reporting::report::DEFAULT_PALETTE,
         threading: Threading::Single,
         exec_mode: ExecutionMode::Executable,
         function_kind: FunctionKind::LambdaSet,
+        emit_match_trees: false,
+        trace_abilities: false,
+        profile: None,
+        doc_examples: 0,
+        comptime_limits: roc_load::ComptimeLimits::default(),
     };
     let loaded = roc_load::load_and_monomorphize_from_str(
         arena,
@@ -160,7 +166,10 @@ pub fn helper(
         for problem in can_problems.into_iter() {
             // Ignore "unused" problems
             match problem {
-                UnusedDef(_, _) | UnusedArgument(_, _, _, _) | UnusedModuleImport(_, _) => {
+                UnusedDef(..)
+                | UnusedArgument(..)
+                | UnusedPatternBinding(..)
+                | UnusedModuleImport(_, _) => {
                     delayed_errors.push(problem);
                     continue;
                 }
@@ -221,6 +230,7 @@ pub fn helper(
             builtins_host_tempfile.path().to_str().unwrap(),
         ],
         LinkType::Dylib,
+        false,
     )
     .expect("failed to link dynamic library");
 
@@ -76,6 +76,11 @@ fn create_llvm_module<'a>(
         palette: DEFAULT_PALETTE,
         threading: Threading::Single,
         exec_mode: ExecutionMode::Executable,
+        emit_match_trees: false,
+        trace_abilities: false,
+        profile: None,
+        doc_examples: 0,
+        comptime_limits: roc_load::ComptimeLimits::default(),
     };
     let loaded = roc_load::load_and_monomorphize_from_str(
         arena,
@@ -108,6 +113,14 @@ fn create_llvm_module<'a>(
         ..
     } = loaded;
 
+    // A `# @comptime` def that can't be evaluated keeps its body, and would quietly be computed
+    // when the program runs instead
+    let comptime_errors = roc_reporting::cli::report_mono_problems(
+        &loaded.sources,
+        &interns,
+        roc_mono::comptime::comptime_problems(&loaded.comptime_failures, &interns),
+    );
+
     let mut lines = Vec::new();
     // errors whose reporting we delay (so we can see that code gen generates runtime errors)
     let mut delayed_errors = Vec::new();
@@ -135,12 +148,13 @@ fn create_llvm_module<'a>(
         for problem in can_problems.into_iter() {
             match problem {
                 // Ignore "unused" problems
-                UnusedDef(_, _)
-                | UnusedArgument(_, _, _, _)
+                UnusedDef(..)
+                | UnusedArgument(..)
+                | UnusedPatternBinding(..)
                 | UnusedModuleImport(_, _)
                 | RuntimeError(_)
                 | UnsupportedPattern(_, _)
-                | ExposedButNotDefined(_) => {
+                | ExposedButNotDefined { .. } => {
                     let report = can_problem(&alloc, &line_info, module_path.clone(), problem);
                     let mut buf = String::new();
 
@@ -182,6 +196,10 @@ fn create_llvm_module<'a>(
         }
     }
 
+    if comptime_errors > 0 && !config.ignore_problems {
+        panic!("{comptime_errors} comptime defs couldn't be evaluated");
+    }
+
     let builder = context.create_builder();
     let module = roc_gen_llvm::llvm::build::module_from_builtins(target, context, "app");
 
@@ -229,6 +247,10 @@ fn create_llvm_module<'a>(
         mode: config.mode,
         // important! we don't want any procedures to get the C calling convention
         exposed_to_host: MutSet::default(),
+        coverage: None,
+        profile_hints: None,
+        deterministic: false,
+        proc_names: Default::default(),
     };
 
     // Add roc_alloc, roc_realloc, and roc_dealloc, since the repl has no
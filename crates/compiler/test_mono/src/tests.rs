@@ -20,6 +20,7 @@ use roc_load::LoadMonomorphizedError;
 use roc_load::Threading;
 use roc_module::symbol::Interns;
 use roc_module::symbol::Symbol;
+use roc_mono::ir::text::{parse, render_procs, render_text_procs, TextExpr, TextStmt};
 use roc_mono::ir::Proc;
 use roc_mono::ir::ProcLayout;
 use roc_mono::layout::STLayoutInterner;
@@ -158,6 +159,11 @@ fn compiles_to_ir(test_name: &str, src: &str, mode: &str, allow_type_errors: boo
         render: roc_reporting::report::RenderTarget::Generic,
         palette: roc_reporting::report::DEFAULT_PALETTE,
         exec_mode,
+        emit_match_trees: false,
+        trace_abilities: false,
+        profile: None,
+        doc_examples: 0,
+        comptime_limits: roc_load::ComptimeLimits::default(),
     };
     let loaded = roc_load::load_and_monomorphize_from_str(
         arena,
@@ -232,26 +238,7 @@ fn verify_procedures<'a>(
     procedures: MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
     opt_main_fn_symbol: Option<Symbol>,
 ) {
-    let mut procs_string = procedures
-        .values()
-        .map(|proc| proc.to_pretty(&interner, 200, false))
-        .collect::<Vec<_>>();
-
-    let opt_main_fn = opt_main_fn_symbol.map(|main_fn_symbol| {
-        let index = procedures
-            .keys()
-            .position(|(s, _)| *s == main_fn_symbol)
-            .unwrap();
-        procs_string.swap_remove(index)
-    });
-
-    procs_string.sort();
-
-    if let Some(main_fn) = opt_main_fn {
-        procs_string.push(main_fn);
-    }
-
-    let result = procs_string.join("\n");
+    let result = render_procs(procedures.values(), &interner, opt_main_fn_symbol);
 
     let path = format!("generated/{test_name}.txt");
     std::fs::create_dir_all("generated").unwrap();
@@ -2266,6 +2253,24 @@ fn crash() {
     )
 }
 
+#[mono_test(allow_type_errors = "true")]
+fn typed_hole() {
+    indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        get_infallible = \result -> when result is
+            Ok x -> x
+            _ -> ?fallback
+
+        main =
+            x : [Ok U64, Err Str]
+            x = Ok 78
+            get_infallible x
+        "#
+    )
+}
+
 #[mono_test]
 fn function_pointer_lambda_set() {
     indoc!(
@@ -3711,3 +3716,854 @@ fn return_annotated() {
         "#
     )
 }
+
+#[mono_test]
+fn str_concat_accumulator_in_walk() {
+    indoc!(
+        r#"
+        List.walk ["a", "b", "c"] "" \acc, s -> "$(acc), $(s)"
+        "#
+    )
+}
+
+#[mono_test]
+fn str_concat_accumulator_in_loop() {
+    indoc!(
+        r#"
+        repeat : Str, U64 -> Str
+        repeat = \acc, n ->
+            if n == 0 then
+                acc
+            else
+                repeat (Str.concat acc "ab") (n - 1)
+
+        repeat "" 10
+        "#
+    )
+}
+
+#[mono_test]
+fn derive_ability_with_hooks() {
+    indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        Show implements show : a -> Str where a implements Show
+
+        show_record : List (Str, Str) -> Str
+        show_record = \fields -> List.walk fields "" \acc, (name, value) -> Str.concat acc (Str.concat name value)
+
+        show_tag : Str, List Str -> Str
+        show_tag = \name, payloads -> List.walk payloads name Str.concat
+
+        Name := Str implements [Show { show: show_name }]
+
+        show_name = \@Name name -> name
+
+        Pet := [None, Cat Name] implements [Show]
+
+        Person := { name : Name, pet : Pet } implements [Show]
+
+        main = show (@Person { name: @Name "Ada", pet: @Pet (Cat (@Name "Tom")) })
+        "#
+    )
+}
+
+#[mono_test]
+fn comptime_lookup_table() {
+    indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        # @comptime
+        table : { squares : List U64, label : Str }
+        table = {
+            squares: List.map (List.range { start: At 0, end: Before 6 }) \n -> n * n,
+            label: Str.concat "squares" "!",
+        }
+
+        main = List.get table.squares 3
+        "#
+    )
+}
+
+/// Renders the reports for the comptime defs and expects that failed, the way the CLI does but
+/// without colors.
+fn comptime_reports(loaded: &roc_load::MonomorphizedModule) -> Vec<String> {
+    use roc_region::all::LineInfo;
+    use roc_reporting::report::{mono_problem, RocDocAllocator};
+
+    let (path, src) = &loaded.sources[&loaded.module_id];
+    let src_lines: Vec<&str> = src.split('\n').collect();
+    let lines = LineInfo::new(src);
+
+    roc_mono::comptime::comptime_problems(&loaded.comptime_failures, &loaded.interns)
+        .into_iter()
+        .map(|problem| {
+            let alloc = RocDocAllocator::new(&src_lines, loaded.module_id, &loaded.interns);
+            let mut buf = String::new();
+
+            mono_problem(&alloc, &lines, path.clone(), problem).render_ci(&mut buf, &alloc);
+            buf.push('\n');
+
+            buf
+        })
+        .collect()
+}
+
+#[test]
+fn comptime_failure() {
+    use roc_packaging::cache::RocCacheDir;
+    use std::path::PathBuf;
+
+    let src = indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        # @comptime
+        countdown : List U8
+        countdown = List.map [2, 1, 0] \n -> n - 1
+
+        main = List.len countdown
+        "#
+    );
+
+    let load_config = LoadConfig {
+        target: TARGET,
+        function_kind: FunctionKind::LambdaSet,
+        threading: Threading::Single,
+        render: roc_reporting::report::RenderTarget::Generic,
+        palette: roc_reporting::report::DEFAULT_PALETTE,
+        exec_mode: ExecutionMode::Executable,
+        emit_match_trees: false,
+        trace_abilities: false,
+        profile: None,
+        doc_examples: 0,
+        comptime_limits: roc_load::ComptimeLimits::default(),
+    };
+
+    let arena = &Bump::new();
+    let loaded = roc_load::load_and_monomorphize_from_str(
+        arena,
+        PathBuf::from("Test.roc"),
+        src,
+        PathBuf::from("fake/test/path"),
+        None,
+        RocCacheDir::Disallowed,
+        load_config,
+    )
+    .unwrap();
+
+    let errors = comptime_reports(&loaded);
+
+    assert_eq!(
+        errors,
+        [indoc!(
+            r#"
+            ── NOT A CONSTANT in Test.roc ──────────────────────────────────────────────────
+
+            `countdown` is marked `# @comptime`, but it can't be evaluated while
+            compiling:
+
+            5│  countdown = List.map [2, 1, 0] \n -> n - 1
+                ^^^^^^^^^
+
+            It crashed with "Integer subtraction overflowed!".
+
+            Tip: Remove the directive to compute `countdown` when the program runs
+            instead.
+            "#
+        )]
+    );
+}
+
+#[test]
+fn comptime_out_of_fuel() {
+    use roc_packaging::cache::RocCacheDir;
+    use std::path::PathBuf;
+
+    let src = indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        sum : U64, U64 -> U64
+        sum = \n, total ->
+            if n == 0 then total else sum (n - 1) (total + n)
+
+        # @comptime
+        total : U64
+        total = sum 1_000_000 0
+
+        main = total
+        "#
+    );
+
+    let load_config = LoadConfig {
+        target: TARGET,
+        function_kind: FunctionKind::LambdaSet,
+        threading: Threading::Single,
+        render: roc_reporting::report::RenderTarget::Generic,
+        palette: roc_reporting::report::DEFAULT_PALETTE,
+        exec_mode: ExecutionMode::Executable,
+        emit_match_trees: false,
+        trace_abilities: false,
+        profile: None,
+        doc_examples: 0,
+        comptime_limits: roc_load::ComptimeLimits {
+            fuel: 1_000,
+            ..Default::default()
+        },
+    };
+
+    let arena = &Bump::new();
+    let loaded = roc_load::load_and_monomorphize_from_str(
+        arena,
+        PathBuf::from("Test.roc"),
+        src,
+        PathBuf::from("fake/test/path"),
+        None,
+        RocCacheDir::Disallowed,
+        load_config,
+    )
+    .unwrap();
+
+    let errors = comptime_reports(&loaded);
+
+    assert_eq!(
+        errors,
+        [indoc!(
+            r#"
+            ── NOT A CONSTANT in Test.roc ──────────────────────────────────────────────────
+
+            `total` is marked `# @comptime`, but it can't be evaluated while
+            compiling:
+
+            9│  total = sum 1_000_000 0
+                ^^^^^
+
+            Evaluating it didn't finish within 1000 steps. If it's supposed to
+            take that long, raise the limit with `--comptime-fuel`.
+
+            Tip: Remove the directive to compute `total` when the program runs
+            instead.
+            "#
+        )]
+    );
+}
+
+#[test]
+fn comptime_expect() {
+    use roc_packaging::cache::RocCacheDir;
+    use std::path::PathBuf;
+
+    let src = indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        ports = [8080, 8081]
+
+        # @comptime
+        expect List.len ports > 0
+
+        # Every port must be unprivileged
+        # @comptime
+        expect
+            List.all ports \port -> port < 1024
+
+        main = List.len ports
+        "#
+    );
+
+    let load_config = LoadConfig {
+        target: TARGET,
+        function_kind: FunctionKind::LambdaSet,
+        threading: Threading::Single,
+        render: roc_reporting::report::RenderTarget::Generic,
+        palette: roc_reporting::report::DEFAULT_PALETTE,
+        exec_mode: ExecutionMode::Executable,
+        emit_match_trees: false,
+        trace_abilities: false,
+        profile: None,
+        doc_examples: 0,
+        comptime_limits: roc_load::ComptimeLimits::default(),
+    };
+
+    let arena = &Bump::new();
+    let loaded = roc_load::load_and_monomorphize_from_str(
+        arena,
+        PathBuf::from("Test.roc"),
+        src,
+        PathBuf::from("fake/test/path"),
+        None,
+        RocCacheDir::Disallowed,
+        load_config,
+    )
+    .unwrap();
+
+    let errors = comptime_reports(&loaded);
+
+    assert_eq!(
+        errors,
+        [indoc!(
+            r#"
+            ── COMPTIME EXPECT FAILED in Test.roc ──────────────────────────────────────────
+
+            This `expect` is marked `# @comptime`, and its condition is false:
+
+            10│>  expect
+            11│>      List.all ports \port -> port < 1024
+
+            The program isn't built until it holds.
+            "#
+        )]
+    );
+}
+
+#[mono_test]
+fn dead_record_fields() {
+    indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        main =
+            config = { name: "roc", verbose: Bool.false, retries: 3u8, tags: ["a", "b"] }
+
+            if config.verbose then config.name else "quiet"
+        "#
+    )
+}
+
+#[test]
+fn dead_field_report() {
+    use roc_packaging::cache::RocCacheDir;
+    use std::path::PathBuf;
+
+    let src = indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        main =
+            config = { name: "roc", retries: 3u8, tags: ["a", "b"] }
+            pair = (config.retries, 1.5f64)
+
+            pair.0
+        "#
+    );
+
+    let load_config = LoadConfig {
+        target: TARGET,
+        function_kind: FunctionKind::LambdaSet,
+        threading: Threading::Single,
+        render: roc_reporting::report::RenderTarget::Generic,
+        palette: roc_reporting::report::DEFAULT_PALETTE,
+        exec_mode: ExecutionMode::Executable,
+        emit_match_trees: false,
+        trace_abilities: false,
+        profile: None,
+        doc_examples: 0,
+        comptime_limits: roc_load::ComptimeLimits::default(),
+    };
+
+    let arena = &Bump::new();
+    let loaded = roc_load::load_and_monomorphize_from_str(
+        arena,
+        PathBuf::from("Test.roc"),
+        src,
+        PathBuf::from("fake/test/path"),
+        None,
+        RocCacheDir::Disallowed,
+        load_config,
+    )
+    .unwrap();
+
+    let report = roc_mono::dead_fields::dead_field_report(
+        &loaded.dead_fields,
+        &loaded.layout_interner,
+        &loaded.interns,
+    );
+
+    assert_eq!(
+        report,
+        indoc!(
+            "
+            # roc dead field report v1
+            #UserApp.main	{Float64, U8}	#0 : Float64	15
+            #UserApp.main	{Str, List Str, U8}	name : Str, tags : List Str	55
+            "
+        )
+    );
+    assert_eq!(
+        roc_mono::dead_fields::dead_field_summary(&loaded.dead_fields),
+        "Removed 3 unread fields from 2 records, which made them 70 bytes smaller in total"
+    );
+}
+
+#[test]
+fn specialization_report() {
+    use roc_mono::specialization_report::{SpecializationReport, SpecializationSize};
+    use roc_packaging::cache::RocCacheDir;
+    use std::path::PathBuf;
+
+    let src = indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        id = \x -> x
+
+        main = (id 42i64, id "roc")
+        "#
+    );
+
+    let load_config = LoadConfig {
+        target: TARGET,
+        function_kind: FunctionKind::LambdaSet,
+        threading: Threading::Single,
+        render: roc_reporting::report::RenderTarget::Generic,
+        palette: roc_reporting::report::DEFAULT_PALETTE,
+        exec_mode: ExecutionMode::Executable,
+        emit_match_trees: false,
+        trace_abilities: false,
+        profile: None,
+        doc_examples: 0,
+        comptime_limits: roc_load::ComptimeLimits::default(),
+    };
+
+    let arena = &Bump::new();
+    let loaded = roc_load::load_and_monomorphize_from_str(
+        arena,
+        PathBuf::from("Test.roc"),
+        src,
+        PathBuf::from("fake/test/path"),
+        None,
+        RocCacheDir::Disallowed,
+        load_config,
+    )
+    .unwrap();
+
+    let mut report =
+        SpecializationReport::new(&loaded.procedures, &loaded.layout_interner, &loaded.interns);
+
+    let id = report
+        .defs
+        .iter()
+        .find(|def| def.name == "#UserApp.id")
+        .unwrap();
+
+    assert_eq!(
+        id.specializations,
+        [
+            SpecializationSize {
+                layout: "(I64) -> I64".to_string(),
+                size: 1,
+            },
+            SpecializationSize {
+                layout: "(Str) -> Str".to_string(),
+                size: 1,
+            },
+        ]
+    );
+    assert_eq!(id.total_size(), 2);
+    assert_eq!(id.inlined, None);
+
+    // Only `id` has a function left in the final module, so `main` was inlined
+    let surviving = roc_mono::mangle::mangle("#UserApp", "id", 7);
+    report.mark_inlined([surviving.as_str()]);
+
+    let rendered = report.to_string();
+    let id_rows: Vec<_> = rendered
+        .lines()
+        .filter(|line| line.starts_with("#UserApp.id\t"))
+        .collect();
+
+    assert!(rendered.starts_with("# roc specialization report v1\n"));
+    assert_eq!(
+        id_rows,
+        [
+            "#UserApp.id\t(I64) -> I64\t1\tno",
+            "#UserApp.id\t(Str) -> Str\t1\tno",
+        ]
+    );
+    assert!(rendered
+        .lines()
+        .any(|line| line.starts_with("#UserApp.main\t") && line.ends_with("\tyes")));
+}
+
+/// The captures the closure capture lint finds in `src`, the names of the captured values, and
+/// the lint's warnings.
+fn closure_capture_lint(
+    src: &str,
+) -> (
+    Vec<roc_mono::closure_captures::ClosureCaptures>,
+    Vec<Vec<String>>,
+    Vec<String>,
+) {
+    use roc_mono::closure_captures::{capture_warnings, closure_captures};
+    use roc_packaging::cache::RocCacheDir;
+    use std::path::PathBuf;
+
+    let load_config = LoadConfig {
+        target: TARGET,
+        function_kind: FunctionKind::LambdaSet,
+        threading: Threading::Single,
+        render: roc_reporting::report::RenderTarget::Generic,
+        palette: roc_reporting::report::DEFAULT_PALETTE,
+        exec_mode: ExecutionMode::Executable,
+        emit_match_trees: false,
+        trace_abilities: false,
+        profile: None,
+        doc_examples: 0,
+        comptime_limits: roc_load::ComptimeLimits::default(),
+    };
+
+    let arena = &Bump::new();
+    let loaded = roc_load::load_and_monomorphize_from_str(
+        arena,
+        PathBuf::from("Test.roc"),
+        src,
+        PathBuf::from("fake/test/path"),
+        None,
+        RocCacheDir::Disallowed,
+        load_config,
+    )
+    .unwrap();
+
+    let captures = closure_captures(
+        &loaded.procedures,
+        &loaded.layout_interner,
+        &loaded.closures,
+        &loaded.interns,
+    );
+    let names = captures
+        .iter()
+        .map(|closure| {
+            let mut names: Vec<_> = closure
+                .captures
+                .iter()
+                .map(|capture| capture.symbol.unwrap().as_str(&loaded.interns).to_string())
+                .collect();
+            names.sort();
+
+            names
+        })
+        .collect();
+    let warnings = capture_warnings(
+        &captures,
+        &loaded.closures,
+        &loaded.sources,
+        &loaded.interns,
+    );
+
+    (captures, names, warnings)
+}
+
+#[test]
+fn closure_with_large_stack_captures() {
+    let (captures, names, warnings) = closure_capture_lint(indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        main =
+            name = "roc"
+            items = [1u8, 2u8, 3u8]
+            rate = 1.5dec
+            describe = \x -> "${name} ${Num.to_str (rate + x)} ${Num.to_str (List.len items)}"
+
+            describe 2.5dec
+        "#
+    ));
+
+    assert_eq!(captures.len(), 1);
+    assert_eq!(names[0], ["items", "name", "rate"]);
+
+    let closure = &captures[0];
+
+    // A Str and a List (24 bytes each), and a Dec (16 bytes)
+    assert_eq!(closure.size, 64);
+    assert_eq!(closure.heap_allocated, None);
+    assert!(closure.is_expensive());
+
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].starts_with("── EXPENSIVE CLOSURE ─ Test.roc:"));
+    assert!(warnings[0].contains(
+        "This closure captures 3 values, which take up 64 bytes that get copied every time the \
+         closure is passed around."
+    ));
+}
+
+#[test]
+fn closure_with_small_stack_captures_is_not_reported() {
+    let (captures, names, warnings) = closure_capture_lint(indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        main =
+            offset = 1u64
+            add = \x -> x + offset
+
+            add 2u64
+        "#
+    ));
+
+    assert_eq!(names, [["offset"]]);
+    assert_eq!(captures[0].size, 8);
+    assert!(!captures[0].is_expensive());
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn closure_with_heap_allocated_captures() {
+    let (captures, names, warnings) = closure_capture_lint(indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        compose = \forward -> \f, g ->
+           if forward
+           then \x -> g (f x)
+           else \x -> f (g x)
+
+        identity = \x -> x
+        exclaim = \s -> "${s}!"
+        whisper = \s -> "(${s})"
+
+        main =
+            res: Str -> Str
+            res = List.walk [ exclaim, whisper ] identity (compose Bool.true)
+            res "hello"
+        "#
+    ));
+
+    // Both closures that `compose` returns capture `f`, which can be one of those closures
+    let recursive: Vec<_> = captures
+        .iter()
+        .zip(names.iter())
+        .filter(|(closure, _)| {
+            closure.heap_allocated
+                == Some(roc_mono::closure_captures::HeapAllocated::RecursiveLambdaSet)
+        })
+        .collect();
+
+    assert_eq!(recursive.len(), 2);
+
+    for (closure, names) in recursive {
+        assert_eq!(names, &["f", "g"]);
+        assert!(closure.is_expensive());
+    }
+
+    assert_eq!(
+        warnings
+            .iter()
+            .filter(|warning| warning.contains(
+                "This closure captures 2 values, which are copied to a new heap allocation every \
+                 time it's created, because it can capture other closures of its own type."
+            ))
+            .count(),
+        2
+    );
+}
+
+#[test]
+fn match_tree_dot() {
+    use roc_packaging::cache::RocCacheDir;
+    use std::path::PathBuf;
+
+    let src = promote_expr_to_module(indoc!(
+        r#"
+        classify = \list ->
+            when list is
+                [1, ..] -> "starts with one"
+                [_, 2] -> "ends with two"
+                _ if List.is_empty list -> "empty"
+                _ -> "other"
+
+        classify [1, 2]
+        "#
+    ));
+
+    let load_config = LoadConfig {
+        target: TARGET,
+        function_kind: FunctionKind::LambdaSet,
+        threading: Threading::Single,
+        render: roc_reporting::report::RenderTarget::Generic,
+        palette: roc_reporting::report::DEFAULT_PALETTE,
+        exec_mode: ExecutionMode::Executable,
+        emit_match_trees: true,
+        trace_abilities: false,
+        profile: None,
+        doc_examples: 0,
+        comptime_limits: roc_load::ComptimeLimits::default(),
+    };
+
+    let arena = &Bump::new();
+    let loaded = roc_load::load_and_monomorphize_from_str(
+        arena,
+        PathBuf::from("Test.roc"),
+        &src,
+        PathBuf::from("fake/test/path"),
+        None,
+        RocCacheDir::Disallowed,
+        load_config,
+    )
+    .unwrap();
+
+    let dot =
+        roc_mono::ir::match_trees_to_dot(&loaded.match_trees, &loaded.sources, &loaded.interns);
+
+    assert_eq!(
+        dot,
+        indoc!(
+            r##"
+            digraph "#UserApp.classify (Test.roc:5:9)" {
+              node [fontname="monospace"];
+              n0 [shape=diamond, label="List.len cond == 2"];
+              n1 [shape=diamond, label="cond[1] == 2"];
+              n2 [shape=diamond, label="cond[0] == 1"];
+              b0 [shape=box, label="branch 1\n(join point)"];
+              n2 -> b0 [label="yes"];
+              b1 [shape=box, label="branch 2"];
+              n2 -> b1 [label="no", style=dashed];
+              n1 -> n2 [label="yes"];
+              n3 [shape=diamond, label="cond[0] == 1"];
+              n3 -> b0 [label="yes"];
+              n4 [shape=diamond, label="guard"];
+              b2 [shape=box, label="branch 3\n(join point)"];
+              n4 -> b2 [label="yes"];
+              b3 [shape=box, label="branch 4\n(join point)"];
+              n4 -> b3 [label="no", style=dashed];
+              n3 -> n4 [label="no", style=dashed];
+              n1 -> n3 [label="no", style=dashed];
+              n0 -> n1 [label="yes"];
+              n5 [shape=diamond, label="List.len cond >= 1"];
+              n6 [shape=diamond, label="cond[0] == 1"];
+              n6 -> b0 [label="yes"];
+              n7 [shape=diamond, label="guard"];
+              n7 -> b2 [label="yes"];
+              n7 -> b3 [label="no", style=dashed];
+              n6 -> n7 [label="no", style=dashed];
+              n5 -> n6 [label="yes"];
+              n8 [shape=diamond, label="guard"];
+              n8 -> b2 [label="yes"];
+              n8 -> b3 [label="no", style=dashed];
+              n5 -> n8 [label="no", style=dashed];
+              n0 -> n5 [label="no", style=dashed];
+            }
+            "##
+        )
+    );
+}
+
+#[test]
+fn lcov_of_when() {
+    use roc_mono::coverage::CoverageMap;
+    use roc_packaging::cache::RocCacheDir;
+    use std::path::PathBuf;
+
+    let src = indoc!(
+        r#"
+        interface Test exposes [] imports []
+
+        describe = \n ->
+            when n is
+                0 -> "zero"
+                1 -> "one"
+                _ -> "many"
+
+        expect describe 1 == "one"
+        "#
+    );
+
+    let load_config = LoadConfig {
+        target: TARGET,
+        function_kind: FunctionKind::LambdaSet,
+        threading: Threading::Single,
+        render: roc_reporting::report::RenderTarget::Generic,
+        palette: roc_reporting::report::DEFAULT_PALETTE,
+        exec_mode: ExecutionMode::Test,
+        emit_match_trees: false,
+        trace_abilities: false,
+        profile: None,
+        doc_examples: 0,
+        comptime_limits: roc_load::ComptimeLimits::default(),
+    };
+
+    let arena = &Bump::new();
+    let loaded = roc_load::load_and_monomorphize_from_str(
+        arena,
+        PathBuf::from("Test.roc"),
+        src,
+        PathBuf::from("fake/test/path"),
+        None,
+        RocCacheDir::Disallowed,
+        load_config,
+    )
+    .unwrap();
+
+    let coverage = CoverageMap::new(loaded.procedures.values());
+    let mut counts = vec![1; coverage.len()];
+
+    // Take each branch a different number of times, to tell them apart
+    for (cond_symbol, tag) in loaded.branch_regions.keys() {
+        if let Some(counter) = coverage.branch_counter(*cond_symbol, *tag) {
+            counts[counter as usize] = match tag {
+                Some(0) => 3,
+                Some(1) => 2,
+                _ => 1,
+            };
+        }
+    }
+
+    let lcov = coverage.to_lcov(
+        &counts,
+        &loaded.def_regions,
+        &loaded.branch_regions,
+        &loaded.sources,
+        &loaded.interns,
+    );
+    let lines: Vec<&str> = lcov.lines().collect();
+    let branches: Vec<&str> = lines
+        .iter()
+        .copied()
+        .filter(|line| line.starts_with("BRDA:"))
+        .collect();
+
+    assert!(lines.contains(&"FN:3,describe"), "{lcov}");
+    assert!(lines.contains(&"FNDA:1,describe"), "{lcov}");
+
+    // Every branch is on the line of its own body, rather than on the line of `describe`
+    assert_eq!(
+        branches,
+        ["BRDA:5,0,0,3", "BRDA:6,0,1,2", "BRDA:7,0,2,1"],
+        "{lcov}"
+    );
+
+    for line in ["DA:3,1", "DA:5,3", "DA:6,2", "DA:7,1"] {
+        assert!(lines.contains(&line), "{line} is missing from\n{lcov}");
+    }
+}
+#[test]
+fn mono_text_round_trips() {
+    for entry in std::fs::read_dir("generated").unwrap() {
+        let path = entry.unwrap().path();
+        let src = std::fs::read_to_string(&path).unwrap();
+        let procs = parse(&src).unwrap_or_else(|err| panic!("{}: {err}", path.display()));
+
+        assert_eq!(render_text_procs(&procs), src, "{}", path.display());
+    }
+}
+
+#[test]
+fn mono_text_parses_structure() {
+    let src = std::fs::read_to_string("generated/ir_int_add.txt").unwrap();
+    let procs = parse(&src).unwrap();
+    let main = procs.last().unwrap();
+
+    assert_eq!(main.name, "Test.0");
+    assert!(main.args.is_empty());
+    assert_eq!(main.callees(), ["Num.19", "Num.19", "List.6", "Num.19"]);
+
+    let TextStmt::Let { layout, expr, .. } = &main.body else {
+        panic!("expected a let, got {:?}", main.body);
+    };
+
+    assert_eq!(layout, "List I64");
+    assert!(matches!(expr, TextExpr::Array(elems) if elems.len() == 2));
+}
@@ -0,0 +1,8 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use test_syntax::fuzz::{self, Target};
+use test_syntax::test_helpers::InputKind;
+
+fuzz_target!(|data: &[u8]| {
+    fuzz::run(Target::Canonicalize, InputKind::Expr, data);
+});
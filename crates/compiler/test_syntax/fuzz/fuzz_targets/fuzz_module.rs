@@ -1,14 +1,8 @@
 #![no_main]
-use bumpalo::Bump;
 use libfuzzer_sys::fuzz_target;
-use test_syntax::test_helpers::Input;
+use test_syntax::fuzz::{self, Target};
+use test_syntax::test_helpers::InputKind;
 
 fuzz_target!(|data: &[u8]| {
-    if let Ok(input) = std::str::from_utf8(data) {
-        let input = Input::Full(input);
-        let arena = Bump::new();
-        if input.parse_in(&arena).is_ok() {
-            input.check_invariants(|_| (), true, None);
-        }
-    }
+    fuzz::run(Target::Format, InputKind::Full, data);
 });
@@ -0,0 +1,42 @@
+//! Run one of the fuzz targets in `test_syntax::fuzz` on a single input, read from a file or
+//! from stdin. This is how AFL drives a target, and it's also handy for reproducing a crash
+//! that cargo-fuzz found without needing a nightly toolchain.
+//!
+//! Typical usage:
+//! `cargo run --bin fuzz_target -- canonicalize expr <crashing_input>`
+//!
+//! or, with AFL (see https://rust-fuzz.github.io/book/afl.html):
+//! `cargo afl build --bin fuzz_target && cargo afl fuzz -i in -o out target/debug/fuzz_target format full -`
+
+use std::io::Read;
+
+use test_syntax::{fuzz, fuzz::Target, test_helpers::InputKind};
+
+fn main() {
+    let args = std::env::args().collect::<Vec<String>>();
+    if args.len() != 4 {
+        eprintln!(
+            "Usage: {} [parse|format|canonicalize] [expr|full|moduledefs|header|pattern] <input>",
+            args[0]
+        );
+        std::process::exit(1);
+    }
+
+    let target = Target::from_name(&args[1]).unwrap_or_else(|| {
+        eprintln!("Invalid target: {}", args[1]);
+        std::process::exit(1);
+    });
+    let kind = InputKind::from_name(&args[2]).unwrap_or_else(|| {
+        eprintln!("Invalid input kind: {}", args[2]);
+        std::process::exit(1);
+    });
+
+    let mut data = Vec::new();
+    if args[3] == "-" {
+        std::io::stdin().read_to_end(&mut data).unwrap();
+    } else {
+        data = std::fs::read(&args[3]).unwrap();
+    }
+
+    fuzz::run(target, kind, &data);
+}
@@ -9,32 +9,50 @@
 //! of the input that still triggers the bug.
 //!
 //! Note that `--release` is important, as this tool is very slow in debug mode.
+//!
+//! Pass `--target` to minimize a crash found by one of the fuzz targets with that same target,
+//! and `--write-regression <name>` to save the result under `tests/fuzz_regressions`, where
+//! `cargo test -p test_syntax` will pick it up.
 
 use std::io::Read;
 
 use test_syntax::{
-    minimize::{print_minimizations, Options},
+    fuzz::Target,
+    minimize::{minimize, write_regression_test, Options},
     test_helpers::InputKind,
 };
 
 fn main() {
     let args = std::env::args().collect::<Vec<String>>();
-    if args.len() < 3 || args.len() > 5 {
-        eprintln!("Usage: {} [--minimize-full-error] [--minimize-initial-parse-error] [expr|full|moduledefs|header|pattern] <input>", args[0]);
+    if args.len() < 3 {
+        eprintln!("Usage: {} [--minimize-full-error] [--minimize-initial-parse-error] [--target parse|format|canonicalize] [--write-regression <name>] [expr|full|moduledefs|header|pattern] <input>", args[0]);
         std::process::exit(1);
     }
 
     let mut options = Options {
         kind: InputKind::Expr,
+        target: Target::Canonicalize,
         minimize_full_error: false,
         minimize_initial_parse_error: false,
     };
+    let mut regression_name = None;
 
     let mut index = 1;
     while index < args.len() - 2 {
         match args[index].as_str() {
             "--minimize-full-error" => options.minimize_full_error = true,
             "--minimize-initial-parse-error" => options.minimize_initial_parse_error = true,
+            "--target" if index + 1 < args.len() - 2 => {
+                index += 1;
+                options.target = Target::from_name(&args[index]).unwrap_or_else(|| {
+                    eprintln!("Invalid target: {}", args[index]);
+                    std::process::exit(1);
+                });
+            }
+            "--write-regression" if index + 1 < args.len() - 2 => {
+                index += 1;
+                regression_name = Some(args[index].clone());
+            }
             _ => {
                 eprintln!("Invalid option: {}", args[index]);
                 std::process::exit(1);
@@ -43,17 +61,10 @@ fn main() {
         index += 1;
     }
 
-    options.kind = match args[index].as_str() {
-        "expr" => InputKind::Expr,
-        "full" => InputKind::Full,
-        "moduledefs" => InputKind::ModuleDefs,
-        "header" => InputKind::Header,
-        "pattern" => InputKind::Pattern,
-        _ => {
-            eprintln!("Invalid input kind: {}", args[index]);
-            std::process::exit(1);
-        }
-    };
+    options.kind = InputKind::from_name(&args[index]).unwrap_or_else(|| {
+        eprintln!("Invalid input kind: {}", args[index]);
+        std::process::exit(1);
+    });
     let input = &args[index + 1];
     let mut buf = String::new();
     let text = if input == "-" {
@@ -63,6 +74,20 @@ fn main() {
         std::fs::read_to_string(input).unwrap()
     };
 
-    let found_error = print_minimizations(&text, options);
-    std::process::exit(if found_error { 0 } else { 1 });
+    let Some(minimized) = minimize(&text, options) else {
+        std::process::exit(1);
+    };
+
+    eprintln!("Final result:");
+    println!("{}", minimized);
+
+    if let Some(name) = regression_name {
+        match write_regression_test(&name, &minimized, options) {
+            Ok(path) => eprintln!("Wrote regression test to {}", path.display()),
+            Err(err) => {
+                eprintln!("Unable to write regression test: {}", err);
+                std::process::exit(1);
+            }
+        }
+    }
 }
@@ -0,0 +1,77 @@
+//! Fuzzing entry points for the parser, formatter, and canonicalizer.
+//!
+//! Each [Target] takes arbitrary bytes and panics if they expose a bug. They don't depend on any
+//! particular fuzzer, so the same functions back the cargo-fuzz (libFuzzer) targets in `fuzz/`,
+//! the `fuzz_target` binary (which reads its input from stdin or a file, like AFL expects),
+//! and the regression tests in `tests/fuzz_regressions`.
+
+use std::fmt;
+
+use bumpalo::Bump;
+use roc_parse::ast::Malformed;
+
+use crate::test_helpers::InputKind;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Target {
+    /// The parser must not panic, whether or not the input is valid.
+    Parse,
+    /// Valid input must format to code that parses to the same AST, and formatting that code
+    /// again must not change it.
+    Format,
+    /// Everything [Target::Format] checks, and canonicalizing valid input must not panic.
+    Canonicalize,
+}
+
+impl Target {
+    pub const ALL: [Target; 3] = [Target::Parse, Target::Format, Target::Canonicalize];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Target::Parse => "parse",
+            Target::Format => "format",
+            Target::Canonicalize => "canonicalize",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Target::ALL.into_iter().find(|target| target.name() == name)
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Run the target on one input. Inputs that aren't UTF-8 are ignored, since the parser only
+/// ever sees a `&str`.
+pub fn run(target: Target, kind: InputKind, data: &[u8]) {
+    if let Ok(text) = std::str::from_utf8(data) {
+        run_str(target, kind, text);
+    }
+}
+
+pub fn run_str(target: Target, kind: InputKind, text: &str) {
+    let input = kind.with_text(text);
+    let arena = Bump::new();
+
+    let ast = match input.parse_in(&arena) {
+        Ok(ast) => ast,
+        // Syntax errors are fine; only panics are bugs.
+        Err(_) => return,
+    };
+
+    // Malformed ASTs can't be formatted or canonicalized faithfully, so there's nothing to check.
+    if target == Target::Parse || ast.is_malformed() {
+        return;
+    }
+
+    let canonicalize_mode = match target {
+        Target::Canonicalize => Some(false),
+        Target::Parse | Target::Format => None,
+    };
+
+    input.check_invariants(|_| (), true, canonicalize_mode);
+}
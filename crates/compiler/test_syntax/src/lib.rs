@@ -1,2 +1,4 @@
+pub mod fuzz;
 pub mod minimize;
+pub mod num_literals;
 pub mod test_helpers;
@@ -5,22 +5,41 @@
 //! Most users will want to use the binary instead of this module directly.
 //! e.g. `cargo run --release --bin minimize -- full <file_that_triggers_parsing_bug>`
 
+use crate::fuzz::Target;
 use crate::test_helpers::{Input, InputKind};
 use bumpalo::Bump;
-use roc_fmt::MigrationFlags;
+use roc_fmt::{DbgMigration, MigrationFlags};
 use roc_parse::{ast::Malformed, normalize::Normalize};
+use std::path::{Path, PathBuf};
 
 #[derive(Copy, Clone, Debug)]
 pub struct Options {
     pub kind: InputKind,
+    /// Which stages to run; a crash found by one fuzz target should be minimized with that
+    /// target, so that it doesn't turn into a crash in a later stage along the way.
+    pub target: Target,
     pub minimize_full_error: bool,
     pub minimize_initial_parse_error: bool,
 }
 
 pub fn print_minimizations(text: &str, options: Options) -> bool {
+    match minimize(text, options) {
+        Some(minimized) => {
+            eprintln!("Final result:");
+            println!("{}", minimized);
+
+            true
+        }
+        None => false,
+    }
+}
+
+/// Shrink the input for as long as it keeps failing with the same error, and return the
+/// smallest input found. Returns `None` if the input doesn't fail in the first place.
+pub fn minimize(text: &str, options: Options) -> Option<String> {
     let Some(original_error) = round_trip_once_and_extract_error(text, options) else {
         eprintln!("No error found");
-        return false;
+        return None;
     };
 
     eprintln!("Error found: {}", original_error);
@@ -57,10 +76,33 @@ pub fn print_minimizations(text: &str, options: Options) -> bool {
         }
     }
 
-    eprintln!("Final result:");
-    println!("{}", s);
+    Some(s)
+}
+
+/// The directory that `tests/test_fuzz_regressions.rs` reads its test cases from.
+pub fn regressions_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fuzz_regressions")
+}
+
+/// Save a (minimized) failing input as a regression test, so that the bug it found stays fixed.
+///
+/// The file is named like `{name}.{target}.{kind}.roc`, which is what the regression tests use to
+/// decide how to run it. Returns the path that was written.
+pub fn write_regression_test(name: &str, text: &str, options: Options) -> std::io::Result<PathBuf> {
+    let dir = regressions_dir();
+
+    std::fs::create_dir_all(&dir)?;
+
+    let path = dir.join(format!(
+        "{}.{}.{}.roc",
+        name,
+        options.target.name(),
+        options.kind.name()
+    ));
+
+    std::fs::write(&path, text)?;
 
-    true
+    Ok(path)
 }
 
 fn make_replacements(s: &str, update: &Update) -> Option<String> {
@@ -120,12 +162,19 @@ fn round_trip_once(input: Input<'_>, options: Options) -> Option<String> {
         }
     }
 
+    if options.target == Target::Parse {
+        return None;
+    }
+
     let flags = MigrationFlags {
         snakify: false,
         parens_and_commas: false,
+        dbg: DbgMigration::Keep,
+        transforms: &[],
     };
 
-    let output = actual.format(flags);
+    // Code nested too deeply to format isn't a bug
+    let output = actual.try_format(flags)?;
 
     let reparsed_ast = match output.as_ref().parse_in(&arena) {
         Ok(r) => r,
@@ -151,6 +200,10 @@ fn round_trip_once(input: Input<'_>, options: Options) -> Option<String> {
         return Some("Formatting not stable".to_string());
     }
 
+    if options.target != Target::Canonicalize {
+        return None;
+    }
+
     let text = input.as_str();
     let res = std::panic::catch_unwind(|| {
         let new_arena = Bump::new();
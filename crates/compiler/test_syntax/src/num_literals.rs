@@ -0,0 +1,221 @@
+//! [proptest] strategies that generate random numeric literals, along with the value each one
+//! should canonicalize to.
+//!
+//! The literals cover every base, every width suffix, negative numbers, floats with and without
+//! exponents, and underscores between digits. The value is worked out from the digits before any
+//! underscores or suffixes are added, so it doesn't depend on the code being tested.
+
+use bumpalo::Bump;
+use proptest::prelude::*;
+use roc_can::expr::IntValue;
+use roc_can::num::{
+    finish_parsing_base, finish_parsing_float, finish_parsing_num, ParsedNumResult,
+};
+use roc_parse::ast::{Base, Expr};
+
+use crate::test_helpers::{Input, Output};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NumValue {
+    Int(IntValue),
+    Float(f64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumLiteral {
+    /// The literal as it would appear in Roc source code, e.g. `-0x7f_ffi8`
+    pub text: String,
+    pub value: NumValue,
+}
+
+/// Integer suffixes, with whether the type is signed and how many bits it has
+const INT_SUFFIXES: [(&str, bool, u32); 10] = [
+    ("u8", false, 8),
+    ("u16", false, 16),
+    ("u32", false, 32),
+    ("u64", false, 64),
+    ("u128", false, 128),
+    ("i8", true, 8),
+    ("i16", true, 16),
+    ("i32", true, 32),
+    ("i64", true, 64),
+    ("i128", true, 128),
+];
+
+const FLOAT_SUFFIXES: [&str; 3] = ["dec", "f32", "f64"];
+
+/// Any integer or float literal.
+pub fn num_literal() -> impl Strategy<Value = NumLiteral> {
+    prop_oneof![int_literal(), float_literal()]
+}
+
+/// An integer literal in any base, with or without a suffix, that's in range for its type.
+pub fn int_literal() -> impl Strategy<Value = NumLiteral> {
+    let suffix = prop_oneof![
+        Just(None),
+        (0..INT_SUFFIXES.len()).prop_map(|index| Some(INT_SUFFIXES[index]))
+    ];
+    let base = prop_oneof![
+        Just(Base::Decimal),
+        Just(Base::Hex),
+        Just(Base::Octal),
+        Just(Base::Binary),
+    ];
+    // Mostly small numbers, since that's what people write, but every width needs its extremes too
+    let magnitude = prop_oneof![0..=255u128, 0..=u32::MAX as u128, any::<u128>()];
+
+    (suffix, base, magnitude, any::<bool>(), any::<bool>())
+        .prop_map(|(suffix, base, magnitude, is_negative, uppercase)| {
+            let (is_signed, bits) = match suffix {
+                Some((_, is_signed, bits)) => (is_signed, bits),
+                // Unsuffixed literals can be anything from i128::MIN to u128::MAX
+                None => (true, 129),
+            };
+            let is_negative = is_negative && is_signed;
+            let max = if is_negative {
+                1u128 << (bits - 1).min(127)
+            } else if is_signed && bits <= 128 {
+                (1u128 << (bits - 1)) - 1
+            } else {
+                u128::MAX >> (128 - bits.min(128))
+            };
+            let magnitude = match max.checked_add(1) {
+                Some(modulus) => magnitude % modulus,
+                None => magnitude,
+            };
+
+            let value = if is_negative {
+                IntValue::I128((magnitude as i128).wrapping_neg().to_ne_bytes())
+            } else if magnitude <= i128::MAX as u128 {
+                IntValue::I128((magnitude as i128).to_ne_bytes())
+            } else {
+                IntValue::U128(magnitude.to_ne_bytes())
+            };
+
+            let digits = match base {
+                Base::Decimal => format!("{magnitude}"),
+                Base::Hex if uppercase => format!("{magnitude:X}"),
+                Base::Hex => format!("{magnitude:x}"),
+                Base::Octal => format!("{magnitude:o}"),
+                Base::Binary => format!("{magnitude:b}"),
+            };
+
+            (
+                is_negative,
+                base,
+                digits,
+                suffix.map(|(suffix, _, _)| suffix),
+                value,
+            )
+        })
+        // Hex digits can spell out a float suffix, which would change what the literal means
+        .prop_filter(
+            "unsuffixed hex digits that end like a float suffix",
+            |(_, base, digits, suffix, _)| {
+                !(*base == Base::Hex
+                    && suffix.is_none()
+                    && FLOAT_SUFFIXES
+                        .iter()
+                        .any(|float_suffix| digits.ends_with(float_suffix)))
+            },
+        )
+        .prop_flat_map(|(is_negative, base, digits, suffix, value)| {
+            with_underscores(digits).prop_map(move |digits| {
+                let sign = if is_negative { "-" } else { "" };
+                let prefix = match base {
+                    Base::Decimal => "",
+                    Base::Hex => "0x",
+                    Base::Octal => "0o",
+                    Base::Binary => "0b",
+                };
+
+                NumLiteral {
+                    text: format!("{sign}{prefix}{digits}{}", suffix.unwrap_or("")),
+                    value: NumValue::Int(value),
+                }
+            })
+        })
+}
+
+/// A decimal float literal, optionally with an exponent and a float suffix.
+pub fn float_literal() -> impl Strategy<Value = NumLiteral> {
+    let suffix = prop_oneof![
+        Just(None),
+        (0..FLOAT_SUFFIXES.len()).prop_map(|index| Some(FLOAT_SUFFIXES[index]))
+    ];
+    let whole = prop_oneof![0..10u64, any::<u64>()];
+    let exponent = proptest::option::of(-30..=30i32);
+
+    (any::<bool>(), whole, "[0-9]{1,8}", exponent, suffix).prop_flat_map(
+        |(is_negative, whole, fraction, exponent, suffix)| {
+            let sign = if is_negative { "-" } else { "" };
+            let exponent = exponent
+                .map(|exponent| format!("e{exponent}"))
+                .unwrap_or_default();
+            let value = format!("{sign}{whole}.{fraction}{exponent}")
+                .parse::<f64>()
+                .expect("generated an invalid float");
+
+            (
+                with_underscores(whole.to_string()),
+                with_underscores(fraction),
+            )
+                .prop_map(move |(whole, fraction)| NumLiteral {
+                    text: format!("{sign}{whole}.{fraction}{exponent}{}", suffix.unwrap_or("")),
+                    value: NumValue::Float(value),
+                })
+        },
+    )
+}
+
+/// Put underscores between some of the digits.
+fn with_underscores(digits: String) -> impl Strategy<Value = String> {
+    let gaps = digits.len().saturating_sub(1);
+
+    proptest::collection::vec(prop::bool::weighted(0.2), gaps).prop_map(move |underscores| {
+        let mut text = String::with_capacity(digits.len() * 2);
+
+        for (index, digit) in digits.chars().enumerate() {
+            if index > 0 && underscores[index - 1] {
+                text.push('_');
+            }
+
+            text.push(digit);
+        }
+
+        text
+    })
+}
+
+/// Parse and canonicalize a numeric literal, the same way `roc_can` does for number expressions.
+pub fn parse_value(text: &str) -> Result<NumValue, String> {
+    let arena = Bump::new();
+    let expr = match Input::Expr(text).parse_in(&arena) {
+        Ok(Output::Expr(loc_expr)) => loc_expr.value,
+        Ok(other) => return Err(format!("{text} parsed as {other:?}")),
+        Err(err) => return Err(format!("{text} failed to parse: {err:?}")),
+    };
+
+    match expr {
+        Expr::Num(string) => match finish_parsing_num(string) {
+            Ok((_, ParsedNumResult::Int(value, _) | ParsedNumResult::UnknownNum(value, _))) => {
+                Ok(NumValue::Int(value))
+            }
+            Ok((_, ParsedNumResult::Float(value, _))) => Ok(NumValue::Float(value)),
+            Err((_, err)) => Err(format!("{text} is not a valid number: {err:?}")),
+        },
+        Expr::Float(string) => match finish_parsing_float(string) {
+            Ok((_, value, _)) => Ok(NumValue::Float(value)),
+            Err((_, err)) => Err(format!("{text} is not a valid float: {err:?}")),
+        },
+        Expr::NonBase10Int {
+            string,
+            base,
+            is_negative,
+        } => match finish_parsing_base(string, base, is_negative) {
+            Ok((value, _)) => Ok(NumValue::Int(value)),
+            Err((_, err)) => Err(format!("{text} is not a valid integer: {err:?}")),
+        },
+        other => Err(format!("{text} parsed as {other:?}")),
+    }
+}
@@ -7,7 +7,7 @@ use roc_can::scope::Scope;
 use roc_can_solo::env::SoloEnv;
 use roc_can_solo::scope::SoloScope;
 use roc_error_macros::set_panic_not_exit;
-use roc_fmt::{annotation::Formattable, header::fmt_header, MigrationFlags};
+use roc_fmt::{annotation::Formattable, header::fmt_header, DbgMigration, MigrationFlags};
 use roc_module::ident::QualifiedModuleName;
 use roc_module::symbol::{IdentIds, Interns, ModuleIds, PackageModuleIds, Symbol};
 use roc_parse::ast::ValueDef;
@@ -70,6 +70,29 @@ impl InputKind {
             InputKind::Pattern => Input::Pattern(text),
         }
     }
+
+    /// The name used for this kind on the command line and in snapshot filenames,
+    /// e.g. `moduledefs` in `import.moduledefs.roc`
+    pub fn name(self) -> &'static str {
+        match self {
+            InputKind::Header => "header",
+            InputKind::ModuleDefs => "moduledefs",
+            InputKind::Expr => "expr",
+            InputKind::Full => "full",
+            InputKind::Pattern => "pattern",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "header" => Some(InputKind::Header),
+            "moduledefs" => Some(InputKind::ModuleDefs),
+            "expr" => Some(InputKind::Expr),
+            "full" => Some(InputKind::Full),
+            "pattern" => Some(InputKind::Pattern),
+            _ => None,
+        }
+    }
 }
 
 // Owned version of `Input`
@@ -110,9 +133,15 @@ pub enum Output<'a> {
 
 impl<'a> Output<'a> {
     pub fn format(&self, flags: MigrationFlags) -> InputOwned {
+        self.try_format(flags)
+            .expect("the code was nested too deeply to format")
+    }
+
+    /// Format the output, or `None` if it was nested too deeply to format all of it.
+    pub fn try_format(&self, flags: MigrationFlags) -> Option<InputOwned> {
         let arena = Bump::new();
         let mut buf = Buf::new_in(&arena, flags);
-        match self {
+        let formatted = match self {
             Output::Header(header) => {
                 fmt_header(&mut buf, header);
                 buf.fmt_end_of_file();
@@ -137,7 +166,9 @@ impl<'a> Output<'a> {
                 patt.format(&mut buf, 0);
                 InputOwned::Pattern(buf.as_str().to_string())
             }
-        }
+        };
+
+        (!buf.nesting_too_deep()).then_some(formatted)
     }
 
     pub fn debug_format_inner(&self) -> String {
@@ -346,6 +377,8 @@ impl<'a> Input<'a> {
             MigrationFlags {
                 snakify: false,
                 parens_and_commas: false,
+                dbg: DbgMigration::Keep,
+                transforms: &[],
             },
         );
     }
@@ -367,7 +400,11 @@ impl<'a> Input<'a> {
             panic!("Unexpected parse failure when parsing this for formatting:\n\n{}\n\nParse error was:\n\n{:#?}\n\n", self.as_str(), err);
         });
 
-        let output = actual.format(flags);
+        let output = match actual.try_format(flags) {
+            Some(output) => output,
+            // The formatter refuses code nested past its limit rather than overflowing the stack
+            None => return,
+        };
 
         handle_formatted_output(output.as_ref());
 
@@ -0,0 +1,4 @@
+x = ???
+y = List.map ?items ???
+z = a ?? ?fallback
+x
\ No newline at end of file
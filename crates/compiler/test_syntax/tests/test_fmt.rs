@@ -6,7 +6,7 @@ mod test_fmt {
     use bumpalo::Bump;
     use roc_fmt::def::fmt_defs;
     use roc_fmt::header::fmt_header;
-    use roc_fmt::{Buf, MigrationFlags};
+    use roc_fmt::{Buf, DbgMigration, MigrationFlags};
     use roc_parse::ast::{Defs, Header, SpacesBefore};
     use roc_parse::header::{self, parse_module_defs};
     use roc_parse::state::State;
@@ -99,7 +99,7 @@ mod test_fmt {
             Ok((actual, state)) => {
                 use roc_parse::normalize::Normalize;
 
-                let flags = MigrationFlags { snakify: false, parens_and_commas: false };
+                let flags = MigrationFlags { snakify: false, parens_and_commas: false, dbg: DbgMigration::Keep, transforms: &[] };
                 let mut buf = Buf::new_in(&arena, flags);
 
                 fmt_module_and_defs(&arena, src, &actual, state, &mut buf);
@@ -166,6 +166,34 @@ mod test_fmt {
         module_formats_to(input, input);
     }
 
+    /// Like [module_formats_to], for a buffer that knows the source it formats and is set up by
+    /// `configure`.
+    fn module_formats_to_with(input: &str, expected: &str, configure: impl Fn(&mut Buf)) {
+        let format = |src: &str| {
+            let arena = Bump::new();
+            let (header, state) = header::parse_header(&arena, State::new(src.as_bytes())).unwrap();
+
+            let flags = MigrationFlags {
+                snakify: false,
+                parens_and_commas: false,
+                dbg: DbgMigration::Keep,
+                transforms: &[],
+            };
+            let mut buf = Buf::new_in(&arena, flags);
+            buf.set_source(src);
+            configure(&mut buf);
+
+            fmt_module_and_defs(&arena, src, &header, state, &mut buf);
+
+            buf.as_str().trim().to_string()
+        };
+
+        let output = format(input.trim());
+
+        assert_multiline_str_eq!(output.as_str(), format(&output).as_str());
+        assert_multiline_str_eq!(expected.trim(), output.as_str());
+    }
+
     // STRING LITERALS
 
     #[test]
@@ -396,6 +424,8 @@ mod test_fmt {
             MigrationFlags {
                 snakify: true,
                 parens_and_commas: false,
+                dbg: DbgMigration::Keep,
+                transforms: &[],
             },
         );
 
@@ -426,6 +456,8 @@ mod test_fmt {
             MigrationFlags {
                 snakify: true,
                 parens_and_commas: false,
+                dbg: DbgMigration::Keep,
+                transforms: &[],
             },
         );
 
@@ -646,6 +678,111 @@ mod test_fmt {
         );
     }
 
+    #[test]
+    fn align_trailing_comments_on_record_fields() {
+        module_formats_to_with(
+            indoc!(
+                r"
+                module []
+
+                Config : {
+                    name : Str, # shown in the title bar
+                    retries : U8, # before giving up
+                    timeout_in_milliseconds : U64, # per request
+                    verbose : Bool,
+                    callback_that_is_much_too_long_to_align_with : Str -> Str, # skipped
+                }
+
+                Level : [
+                    Debug, # everything
+                    Info,
+                    Warning, # only problems
+                ]
+                "
+            ),
+            indoc!(
+                r"
+                module []
+
+                Config : {
+                    name : Str,                    # shown in the title bar
+                    retries : U8,                  # before giving up
+                    timeout_in_milliseconds : U64, # per request
+                    verbose : Bool,
+                    callback_that_is_much_too_long_to_align_with : Str -> Str, # skipped
+                }
+
+                Level : [
+                    Debug,   # everything
+                    Info,
+                    Warning, # only problems
+                ]
+                "
+            ),
+            |buf| buf.align_trailing_comments(40),
+        );
+    }
+
+    #[test]
+    fn fmt_skip_leaves_one_def_alone() {
+        module_formats_to_with(
+            indoc!(
+                r"
+                module []
+
+                # fmt: skip
+                identity  =  [ 1,0,0,
+                               0,1,0,
+                               0,0,1 ]   # a 3x3 matrix
+                zero  =  0
+                "
+            ),
+            indoc!(
+                r"
+                module []
+
+                # fmt: skip
+                identity  =  [ 1,0,0,
+                               0,1,0,
+                               0,0,1 ] # a 3x3 matrix
+                zero = 0
+                "
+            ),
+            |_| {},
+        );
+    }
+
+    #[test]
+    fn fmt_off_leaves_defs_alone_until_fmt_on() {
+        module_formats_to_with(
+            indoc!(
+                r"
+                module []
+
+                a  =  1
+                # fmt: off
+                b  =  2
+                Pair   :   (U8,U8)
+                # fmt: on
+                c  =  3
+                "
+            ),
+            indoc!(
+                r"
+                module []
+
+                a = 1
+                # fmt: off
+                b  =  2
+                Pair   :   (U8,U8)
+                # fmt: on
+                c = 3
+                "
+            ),
+            |_| {},
+        );
+    }
+
     #[test]
     fn record_allow_blank_line_before_and_after_comment() {
         expr_formats_same(indoc!(
@@ -686,6 +823,8 @@ mod test_fmt {
             MigrationFlags {
                 snakify: true,
                 parens_and_commas: false,
+                dbg: DbgMigration::Keep,
+                transforms: &[],
             },
         );
 
@@ -704,6 +843,8 @@ mod test_fmt {
             MigrationFlags {
                 snakify: true,
                 parens_and_commas: false,
+                dbg: DbgMigration::Keep,
+                transforms: &[],
             },
         );
 
@@ -1462,6 +1603,8 @@ mod test_fmt {
             MigrationFlags {
                 snakify: true,
                 parens_and_commas: false,
+                dbg: DbgMigration::Keep,
+                transforms: &[],
             },
         );
         // Old syntax
@@ -1505,6 +1648,8 @@ mod test_fmt {
             MigrationFlags {
                 snakify: true,
                 parens_and_commas: false,
+                dbg: DbgMigration::Keep,
+                transforms: &[],
             },
         );
     }
@@ -2197,6 +2342,8 @@ mod test_fmt {
             MigrationFlags {
                 snakify: true,
                 parens_and_commas: false,
+                dbg: DbgMigration::Keep,
+                transforms: &[],
             },
         );
         expr_formats_same(indoc!(
@@ -2232,6 +2379,8 @@ mod test_fmt {
             MigrationFlags {
                 snakify: true,
                 parens_and_commas: false,
+                dbg: DbgMigration::Keep,
+                transforms: &[],
             },
         );
 
@@ -2812,6 +2961,8 @@ mod test_fmt {
             MigrationFlags {
                 snakify: true,
                 parens_and_commas: false,
+                dbg: DbgMigration::Keep,
+                transforms: &[],
             },
         );
 
@@ -2839,6 +2990,8 @@ mod test_fmt {
             MigrationFlags {
                 snakify: true,
                 parens_and_commas: false,
+                dbg: DbgMigration::Keep,
+                transforms: &[],
             },
         );
     }
@@ -4093,6 +4246,8 @@ mod test_fmt {
             MigrationFlags {
                 snakify: true,
                 parens_and_commas: true,
+                dbg: DbgMigration::Keep,
+                transforms: &[],
             },
         );
     }
@@ -5610,6 +5765,8 @@ mod test_fmt {
             MigrationFlags {
                 snakify: true,
                 parens_and_commas: true,
+                dbg: DbgMigration::Keep,
+                transforms: &[],
             },
         );
     }
@@ -5622,6 +5779,8 @@ mod test_fmt {
             MigrationFlags {
                 snakify: true,
                 parens_and_commas: true,
+                dbg: DbgMigration::Keep,
+                transforms: &[],
             },
         );
     }
@@ -5755,6 +5914,37 @@ mod test_fmt {
         );
     }
 
+    #[test]
+    fn package_header_with_manifest() {
+        module_formats_same(indoc!(
+            r#"
+                package [Json, Json.Decode] {} manifest {
+                    name: "json",
+                    version: "1.2.0",
+                    license: "UPL-1.0",
+                    authors: ["Ada Lovelace", "Grace Hopper"],
+                }"#
+        ));
+    }
+
+    #[test]
+    fn package_manifest_joins_header_line() {
+        module_formats_to(
+            indoc!(
+                r#"
+                package [Json]
+                    {}
+                    manifest { name: "json", version: "1.2.0" }
+                "#
+            ),
+            indoc!(
+                r#"
+                package [Json] {} manifest { name: "json", version: "1.2.0" }
+                "#
+            ),
+        );
+    }
+
     #[test]
     fn single_line_app() {
         module_formats_same(indoc!(
@@ -6131,6 +6321,8 @@ mod test_fmt {
             MigrationFlags {
                 snakify: true,
                 parens_and_commas: false,
+                dbg: DbgMigration::Keep,
+                transforms: &[],
             },
         );
     }
@@ -6187,6 +6379,8 @@ mod test_fmt {
             MigrationFlags {
                 snakify: false,
                 parens_and_commas: true,
+                dbg: DbgMigration::Keep,
+                transforms: &[],
             },
         );
     }
@@ -6344,6 +6538,8 @@ mod test_fmt {
             MigrationFlags {
                 snakify: true,
                 parens_and_commas: false,
+                dbg: DbgMigration::Keep,
+                transforms: &[],
             },
         );
         expr_formats_to(
@@ -6951,6 +7147,111 @@ mod test_fmt {
         );
     }
 
+    #[test]
+    fn nested_as_patterns() {
+        expr_formats_same(indoc!(
+            r"
+            when result is
+                Ok (Pair a b as pair) as whole ->
+                    f a b pair whole
+
+                (Err e as err) as outer -> g e err outer
+            "
+        ));
+    }
+
+    #[test]
+    fn as_pattern_with_comment() {
+        expr_formats_same(indoc!(
+            r"
+            when result is
+                Ok (Pair a b) as # the whole result
+                    whole ->
+                    f a b whole
+
+                _ -> x
+            "
+        ));
+    }
+
+    #[test]
+    fn as_pattern_drops_newlines_without_comments() {
+        expr_formats_to(
+            indoc!(
+                r"
+                when list is
+                    [first, .. as rest]
+                        as
+                            all ->
+                        f first rest all
+                "
+            ),
+            indoc!(
+                r"
+                when list is
+                    [first, .. as rest] as all ->
+                        f first rest all
+                "
+            ),
+        );
+    }
+
+    #[test]
+    fn dbg_migration_removes_dbg() {
+        expr_formats_to_with_flags(
+            indoc!(
+                r"
+                x = dbg (f y)
+                # check the total
+                dbg x
+
+                z = dbg x
+                z
+                "
+            ),
+            indoc!(
+                r"
+                x = f y
+                # check the total
+
+                z = x
+                z
+                "
+            ),
+            MigrationFlags {
+                snakify: false,
+                parens_and_commas: false,
+                dbg: DbgMigration::Remove,
+                transforms: &[],
+            },
+        );
+    }
+
+    #[test]
+    fn dbg_migration_lifts_inline_dbg() {
+        expr_formats_to_with_flags(
+            indoc!(
+                r"
+                total = dbg (sum items)
+                total
+                "
+            ),
+            indoc!(
+                r"
+                total = sum items
+                dbg total
+                total
+                "
+            ),
+            MigrationFlags {
+                snakify: false,
+                parens_and_commas: false,
+                dbg: DbgMigration::Lift,
+                transforms: &[],
+            },
+        );
+    }
+
     #[test]
     fn preserve_annotated_body() {
         expr_formats_same(indoc!(
@@ -7160,4 +7461,44 @@ mod test_fmt {
     fn pattern_tag_apply_with_pnc_multi_arg() {
         pattern_formats_same(indoc!("Ok(a, b)"));
     }
+
+    fn format_expr_with_max_nesting_depth(input: &str, max_depth: usize) -> (String, bool) {
+        use roc_fmt::annotation::Formattable;
+        use test_syntax::test_helpers::Output;
+
+        let arena = Bump::new();
+        let loc_expr = match Input::Expr(input).parse_in(&arena) {
+            Ok(Output::Expr(loc_expr)) => loc_expr,
+            other => panic!("{input} didn't parse as an expression: {other:?}"),
+        };
+
+        let mut buf = Buf::new_in(
+            &arena,
+            MigrationFlags {
+                snakify: false,
+                parens_and_commas: false,
+                dbg: DbgMigration::Keep,
+                transforms: &[],
+            },
+        );
+        buf.set_max_nesting_depth(max_depth);
+        loc_expr.format(&mut buf, 0);
+
+        (buf.as_str().to_string(), buf.nesting_too_deep())
+    }
+
+    #[test]
+    fn nesting_within_limit() {
+        assert_eq!(
+            format_expr_with_max_nesting_depth("[[[1]]]", 4),
+            ("[[[1]]]".to_string(), false)
+        );
+    }
+
+    #[test]
+    fn nesting_too_deep() {
+        let (_, too_deep) = format_expr_with_max_nesting_depth("[[[[[[1]]]]]]", 3);
+
+        assert!(too_deep);
+    }
 }
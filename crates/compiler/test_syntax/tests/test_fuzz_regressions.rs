@@ -0,0 +1,57 @@
+#[cfg(test)]
+mod test_fuzz_regressions {
+    use test_syntax::fuzz::{self, Target};
+    use test_syntax::minimize::regressions_dir;
+    use test_syntax::test_helpers::InputKind;
+
+    /// Splits `{name}.{target}.{kind}.roc` into its target and kind.
+    fn target_and_kind(file_name: &str) -> (Target, InputKind) {
+        let mut parts = file_name
+            .strip_suffix(".roc")
+            .unwrap_or_else(|| panic!("{file_name} is not a .roc file"))
+            .rsplit('.');
+
+        let kind = parts.next().and_then(InputKind::from_name);
+        let target = parts.next().and_then(Target::from_name);
+
+        match (target, kind) {
+            (Some(target), Some(kind)) => (target, kind),
+            _ => panic!(
+                "{file_name} should be named like `name.target.kind.roc`, \
+                where target is parse, format or canonicalize, \
+                and kind is expr, full, moduledefs, header or pattern"
+            ),
+        }
+    }
+
+    #[test]
+    fn fuzz_regressions() {
+        for entry in std::fs::read_dir(regressions_dir()).unwrap() {
+            let path = entry.unwrap().path();
+            let file_name = path.file_name().unwrap().to_str().unwrap();
+
+            if !file_name.ends_with(".roc") {
+                continue;
+            }
+
+            let (target, kind) = target_and_kind(file_name);
+            let text = std::fs::read_to_string(&path).unwrap();
+
+            eprintln!("Running {target} on {}", path.display());
+
+            fuzz::run_str(target, kind, &text);
+        }
+    }
+
+    #[test]
+    fn regression_file_names() {
+        assert_eq!(
+            target_and_kind("unclosed_record.format.expr.roc"),
+            (Target::Format, InputKind::Expr)
+        );
+        assert_eq!(
+            target_and_kind("a.b.canonicalize.moduledefs.roc"),
+            (Target::Canonicalize, InputKind::ModuleDefs)
+        );
+    }
+}
@@ -0,0 +1,47 @@
+#[cfg(test)]
+mod test_num_literals {
+    use proptest::prelude::*;
+    use roc_test_utils::assert_multiline_str_eq;
+    use test_syntax::num_literals::{float_literal, int_literal, num_literal, parse_value};
+    use test_syntax::test_helpers::Input;
+
+    proptest! {
+        /// Parsing, formatting, reparsing and canonicalizing a literal must neither panic nor
+        /// change it; the formatter keeps numeric literals exactly as they were written.
+        #[test]
+        fn literals_round_trip(literal in num_literal()) {
+            Input::Expr(&literal.text).check_invariants(
+                |output| assert_multiline_str_eq!(literal.text.as_str(), output.as_str()),
+                true,
+                Some(false),
+            );
+        }
+
+        #[test]
+        fn int_values_are_preserved(literal in int_literal()) {
+            prop_assert_eq!(parse_value(&literal.text), Ok(literal.value));
+        }
+
+        #[test]
+        fn float_values_are_preserved(literal in float_literal()) {
+            prop_assert_eq!(parse_value(&literal.text), Ok(literal.value));
+        }
+    }
+
+    #[test]
+    fn extremes() {
+        for text in [
+            "-170_141_183_460_469_231_731_687_303_715_884_105_728",
+            "340_282_366_920_938_463_463_374_607_431_768_211_455",
+            "0xFFFF_FFFF_FFFF_FFFF_FFFF_FFFF_FFFF_FFFFu128",
+            "-0x80i8",
+            "0b1111_1111u8",
+            "-0o200i8",
+        ] {
+            assert!(parse_value(text).is_ok(), "{text} should be valid");
+        }
+
+        assert!(parse_value("0x100u8").is_err());
+        assert!(parse_value("-0x81i8").is_err());
+    }
+}
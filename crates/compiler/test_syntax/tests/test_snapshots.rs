@@ -760,6 +760,7 @@ mod test_snapshots {
         pass/type_signature_def.expr,
         pass/type_signature_function_def.expr,
         pass/type_tuple_where_annotation.expr,
+        pass/typed_hole.expr,
         pass/unary_negation.expr,
         pass/unary_negation_access.expr, // Regression test for https://github.com/roc-lang/roc/issues/509
         pass/unary_negation_arg.expr,
@@ -3530,6 +3530,7 @@ pub enum Category {
     AbilityMemberSpecialization(Symbol),
 
     Crash,
+    TypedHole,
 
     Expect,
     Dbg,
@@ -3798,6 +3799,30 @@ fn write_error_type_help(
         Error => buf.push('?'),
         FlexVar(name) => buf.push_str(name.as_str()),
         RigidVar(name) => buf.push_str(name.as_str()),
+        InferenceVar => buf.push('_'),
+        EffectfulFunc => buf.push_str("EffectfulFunc"),
+        FlexAbleVar(name, abilities) | RigidAbleVar(name, abilities) => {
+            let write_parens = parens == Parens::InTypeParam;
+
+            if write_parens {
+                buf.push('(');
+            }
+            buf.push_str(name.as_str());
+            write!(buf, " {} ", roc_parse::keyword::IMPLEMENTS).unwrap();
+
+            let mut it = abilities.into_sorted_iter().peekable();
+
+            while let Some(ability) = it.next() {
+                buf.push_str(ability.as_str(interns));
+                if it.peek().is_some() {
+                    buf.push_str(" & ");
+                }
+            }
+
+            if write_parens {
+                buf.push(')');
+            }
+        }
         Type(symbol, arguments) => {
             let write_parens = parens == Parens::InTypeParam && !arguments.is_empty();
 
@@ -3843,11 +3868,14 @@ fn write_error_type_help(
                 }
             }
         }
+        Alias(symbol, arguments, _actual, _) => {
+            write_error_type_help(interns, Type(symbol, arguments), buf, parens);
+        }
         Function(arguments, _closure, fx, result) => {
             let write_parens = parens != Parens::Unnecessary;
 
             if write_parens {
-                buf.push(')');
+                buf.push('(');
             }
 
             let mut it = arguments.into_iter().peekable();
@@ -3871,9 +3899,11 @@ fn write_error_type_help(
             }
         }
         Record(fields, ext) => {
-            buf.push('{');
+            buf.push_str("{ ");
 
-            for (label, field) in fields {
+            let mut it = fields.into_iter().peekable();
+
+            while let Some((label, field)) = it.next() {
                 use RecordField::*;
 
                 buf.push_str(label.as_str());
@@ -3890,14 +3920,79 @@ fn write_error_type_help(
                 };
 
                 write_error_type_help(interns, content, buf, Parens::Unnecessary);
+
+                if it.peek().is_some() {
+                    buf.push_str(", ");
+                }
             }
 
-            buf.push('}');
+            buf.push_str(" }");
+            write_type_ext(ext, buf);
+        }
+        Tuple(elems, ext) => {
+            buf.push('(');
+
+            let mut it = elems.into_iter().peekable();
+
+            while let Some((_index, elem)) = it.next() {
+                write_error_type_help(interns, elem, buf, Parens::Unnecessary);
+
+                if it.peek().is_some() {
+                    buf.push_str(", ");
+                }
+            }
+
+            buf.push(')');
             write_type_ext(ext, buf);
         }
+        TagUnion(tags, ext, _pol) => {
+            write_error_tags(interns, tags, buf);
+            write_type_ext(ext, buf);
+        }
+        RecursiveTagUnion(rec, tags, ext, _pol) => {
+            write_error_tags(interns, tags, buf);
+            write_type_ext(ext, buf);
+
+            buf.push_str(" as ");
+
+            write_error_type_help(interns, *rec, buf, Parens::Unnecessary);
+        }
+        Range(types) => {
+            buf.push('<');
+
+            let mut it = types.into_iter().peekable();
+            while let Some(typ) = it.next() {
+                write_error_type_help(interns, typ, buf, Parens::Unnecessary);
+
+                if it.peek().is_some() {
+                    buf.push_str(", ");
+                }
+            }
+
+            buf.push('>');
+        }
+    }
+}
+
+fn write_error_tags(interns: &Interns, tags: SendMap<TagName, Vec<ErrorType>>, buf: &mut String) {
+    buf.push('[');
+
+    let mut it = tags.into_iter().peekable();
 
-        other => todo!("cannot format {:?} yet", other),
+    while let Some((tag, args)) = it.next() {
+        buf.push_str(tag.as_ident_str().as_str());
+
+        for arg in args {
+            buf.push(' ');
+            write_error_type_help(interns, arg, buf, Parens::InTypeParam);
+        }
+
+        if it.peek().is_some() {
+            buf.push_str(", ");
+        }
     }
+
+    buf.push(']');
 }
 
 pub fn write_debug_error_type(error_type: ErrorType) -> String {
@@ -6,8 +6,9 @@ use bumpalo::Bump;
 use roc_can::scope::Scope;
 use roc_collections::VecSet;
 use roc_highlight::highlight_roc_code_inline;
+use roc_load::docs::{notice_file, ModuleDocumentation, PackageMetadata, RecordField};
 use roc_load::docs::{DocEntry, TypeAnnotation};
-use roc_load::docs::{ModuleDocumentation, RecordField};
+use roc_load::manifest::read_package_manifest;
 use roc_load::{ExecutionMode, LoadConfig, LoadedModule, LoadingProblem, Threading};
 use roc_module::symbol::{Interns, ModuleId, Symbol};
 use roc_packaging::cache::{self, RocCacheDir};
@@ -22,13 +23,27 @@ use std::path::{Path, PathBuf};
 
 const LINK_SVG: &str = include_str!("./static/link.svg");
 
-pub fn generate_docs_html(root_file: PathBuf, build_dir: &Path, maybe_root_dir: Option<String>) {
-    let mut loaded_module = load_module_for_docs(root_file);
+pub fn generate_docs_html(
+    root_file: PathBuf,
+    build_dir: &Path,
+    maybe_root_dir: Option<String>,
+    max_examples: usize,
+) {
+    // Packages without a manifest (including platforms) just don't show any metadata.
+    let package_metadata = read_package_manifest(&root_file)
+        .ok()
+        .flatten()
+        .map(|manifest| PackageMetadata::from(&manifest));
+
+    let mut loaded_module = load_module_for_docs(root_file, max_examples);
     let exposed_module_docs = get_exposed_module_docs(&mut loaded_module);
 
     // TODO get these from the platform's source file rather than hardcoding them!
     // github.com/roc-lang/roc/issues/5712
-    let package_name = "Documentation".to_string();
+    let package_name = match &package_metadata {
+        Some(metadata) => metadata.name.clone(),
+        None => "Documentation".to_string(),
+    };
 
     // Clear out the generated-docs dir (we'll create a fresh one at the end)
     if build_dir.exists() {
@@ -136,6 +151,15 @@ pub fn generate_docs_html(root_file: PathBuf, build_dir: &Path, maybe_root_dir:
             .expect("TODO gracefully handle failing to write llms.txt");
     }
 
+    // A NOTICE file, so that anyone vendoring the package has its license and source at hand
+    if let Some(metadata) = &package_metadata {
+        fs::write(
+            build_dir.join("NOTICE"),
+            notice_file(std::slice::from_ref(metadata)),
+        )
+        .expect("TODO gracefully handle failing to write NOTICE");
+    }
+
     let all_exposed_symbols = {
         let mut set = VecSet::default();
 
@@ -161,7 +185,7 @@ pub fn generate_docs_html(root_file: PathBuf, build_dir: &Path, maybe_root_dir:
             .replace("<!-- Package Name String -->", package_name.as_str())
             .replace(
                 "<!-- Module Docs -->",
-                render_package_index(&exposed_module_docs).as_str(),
+                render_package_index(&exposed_module_docs, package_metadata.as_ref()).as_str(),
             );
 
         fs::write(build_dir.join("index.html"), rendered_package).unwrap_or_else(|error| {
@@ -228,7 +252,10 @@ fn page_title(package_name: &str, module_name: &str) -> String {
     format!("<title>{module_name} - {package_name}</title>")
 }
 
-fn render_package_index(docs_by_module: &[(ModuleId, ModuleDocumentation)]) -> String {
+fn render_package_index(
+    docs_by_module: &[(ModuleId, ModuleDocumentation)],
+    package_metadata: Option<&PackageMetadata>,
+) -> String {
     // The list items containing module links
     let mut module_list_buf = String::new();
 
@@ -273,6 +300,10 @@ fn render_package_index(docs_by_module: &[(ModuleId, ModuleDocumentation)]) -> S
     // The HTML for the index page
     let mut index_buf = String::new();
 
+    if let Some(metadata) = package_metadata {
+        index_buf.push_str(&render_package_metadata(metadata));
+    }
+
     push_html(
         &mut index_buf,
         "div",
@@ -290,6 +321,65 @@ fn render_package_index(docs_by_module: &[(ModuleId, ModuleDocumentation)]) -> S
     index_buf
 }
 
+/// The version, license and source of the package, from its manifest.
+fn render_package_metadata(metadata: &PackageMetadata) -> String {
+    let mut rows_buf = String::new();
+    let mut push_row = |label: &str, value: &str| {
+        let mut row_buf = String::new();
+
+        push_html(&mut row_buf, "dt", [], label);
+        push_html(&mut row_buf, "dd", [], value);
+
+        rows_buf.push_str(&row_buf);
+    };
+
+    push_row("Version", &escape_html(&metadata.version));
+
+    if let Some(license) = &metadata.license {
+        push_row("License", &escape_html(license));
+    }
+
+    if let Some(source_url) = &metadata.source_url {
+        let source_url = escape_html(source_url);
+        let mut link_buf = String::new();
+
+        push_html(
+            &mut link_buf,
+            "a",
+            [("href", source_url.as_str())],
+            &source_url,
+        );
+        push_row("Source", &link_buf);
+    }
+
+    if !metadata.authors.is_empty() {
+        push_row("Authors", &escape_html(&metadata.authors.join(", ")));
+    }
+
+    let mut buf = String::new();
+
+    if let Some(description) = &metadata.description {
+        push_html(
+            &mut buf,
+            "p",
+            [("class", "package-description")],
+            escape_html(description),
+        );
+    }
+
+    push_html(&mut buf, "dl", [("class", "package-metadata")], rows_buf);
+
+    buf
+}
+
+/// Manifest values are plain text, unlike doc comments, which are Markdown.
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
 fn render_module_documentation(
     module_id: ModuleId,
     module: &ModuleDocumentation,
@@ -343,6 +433,18 @@ fn render_module_documentation(
                     }
                     content.push_str(highlight_roc_code_inline(anno_buf.as_str()).as_str());
 
+                    if type_ann.is_effectful() {
+                        push_html(
+                            &mut content,
+                            "span",
+                            [
+                                ("class", "effectful-badge"),
+                                ("title", "Calling this function can perform effects"),
+                            ],
+                            "effectful",
+                        );
+                    }
+
                     push_html(
                         &mut buf,
                         "h3",
@@ -362,6 +464,27 @@ fn render_module_documentation(
                         );
                     }
 
+                    for example in &doc_def.examples {
+                        buf.push_str(roc_highlight::highlight_roc_code(example).as_str());
+                    }
+
+                    render_implementations(
+                        &mut buf,
+                        "Implemented by",
+                        &doc_def.implemented_by,
+                        &root_module.interns,
+                        all_exposed_symbols,
+                        maybe_root_dir,
+                    );
+                    render_implementations(
+                        &mut buf,
+                        "Implements",
+                        &doc_def.implements,
+                        &root_module.interns,
+                        all_exposed_symbols,
+                        maybe_root_dir,
+                    );
+
                     buf.push_str("</section>");
                 }
             }
@@ -445,6 +568,46 @@ fn base_url(maybe_root_dir: Option<&str>) -> String {
     }
 }
 
+/// Lists the abilities an opaque type implements, or the opaque types implementing an ability.
+fn render_implementations(
+    buf: &mut String,
+    label: &str,
+    symbols: &[Symbol],
+    interns: &Interns,
+    all_exposed_symbols: &VecSet<Symbol>,
+    maybe_root_dir: Option<&str>,
+) {
+    if symbols.is_empty() {
+        return;
+    }
+
+    let mut content = format!("{label}: ");
+
+    for (index, symbol) in symbols.iter().enumerate() {
+        if index > 0 {
+            content.push_str(", ");
+        }
+
+        let module_name = symbol.module_string(interns).as_str();
+        let ident = symbol.as_str(interns);
+        let name = format!("{module_name}.{ident}");
+
+        // Only link to entries that have a page of their own.
+        if symbol.is_builtin() || all_exposed_symbols.contains(symbol) {
+            let mut href = base_url(maybe_root_dir);
+            href.push_str(&module_name.replace('.', "/"));
+            href.push('#');
+            href.push_str(ident);
+
+            push_html(&mut content, "a", [("href", href.as_str())], name.as_str());
+        } else {
+            push_html(&mut content, "code", [], name.as_str());
+        }
+    }
+
+    push_html(buf, "p", [("class", "implementations")], content.as_str());
+}
+
 // TODO render version as well
 fn render_name_link(name: &str, maybe_root_dir: Option<&str>) -> String {
     let mut buf = String::new();
@@ -595,7 +758,25 @@ fn render_search_type_ahead<'a, I: Iterator<Item = &'a ModuleDocumentation>>(mod
                         &entry_contents_buf,
                     );
 
-                    push_html(&mut buf, "li", [("role", "option")], &anchor_buf);
+                    // Lets search filter by `is:pure` and `is:effectful`
+                    let type_ann = &doc_def.type_annotation;
+                    let effect = if type_ann.is_effectful() {
+                        Some("effectful")
+                    } else if type_ann.is_function() {
+                        Some("pure")
+                    } else {
+                        None
+                    };
+
+                    match effect {
+                        Some(effect) => push_html(
+                            &mut buf,
+                            "li",
+                            [("role", "option"), ("data-effect", effect)],
+                            &anchor_buf,
+                        ),
+                        None => push_html(&mut buf, "li", [("role", "option")], &anchor_buf),
+                    }
                 }
             }
         }
@@ -685,7 +866,7 @@ fn llm_prompt<'a, I: Iterator<Item = &'a ModuleDocumentation>>(
     buf
 }
 
-pub fn load_module_for_docs(filename: PathBuf) -> LoadedModule {
+pub fn load_module_for_docs(filename: PathBuf, max_examples: usize) -> LoadedModule {
     let arena = Bump::new();
     let load_config = LoadConfig {
         target: roc_target::Target::LinuxX64, // This is just type-checking for docs, so "target" doesn't matter
@@ -694,6 +875,11 @@ pub fn load_module_for_docs(filename: PathBuf) -> LoadedModule {
         palette: roc_reporting::report::DEFAULT_PALETTE,
         threading: Threading::AllAvailable,
         exec_mode: ExecutionMode::Check,
+        emit_match_trees: false,
+        trace_abilities: false,
+        profile: None,
+        doc_examples: max_examples,
+        comptime_limits: roc_load::ComptimeLimits::default(),
     };
     match roc_load::load_and_typecheck(
         &arena,
@@ -117,7 +117,17 @@ const setupSearch = () => {
       topSearchResultListItem = undefined;
       let text = searchBox.value.toLowerCase(); // Search is case-insensitive.
 
-      if (text === "") {
+      // `is:pure` and `is:effectful` only show functions with a `->` or `=>` arrow respectively,
+      // e.g. `is:pure list` finds pure functions to do with lists.
+      let effectFilter = undefined;
+      text = text
+        .replace(/\bis:(pure|effectful)\b/g, (_, effect) => {
+          effectFilter = effect;
+          return "";
+        })
+        .trim();
+
+      if (text === "" && effectFilter === undefined) {
         searchTypeAhead.classList.add("hidden");
       } else {
         let totalResults = 0;
@@ -137,8 +147,9 @@ const setupSearch = () => {
           const qualifiedEntryName = `${entryModule}.${entryName}`;
 
           if (
-            qualifiedEntryName.includes(text) ||
-            entrySignature?.includes(text.replace(/\s+/g, ""))
+            (effectFilter === undefined || entry.dataset.effect === effectFilter) &&
+            (qualifiedEntryName.includes(text) ||
+              entrySignature?.includes(text.replace(/\s+/g, "")))
           ) {
             totalResults++;
             entry.classList.remove("hidden");
@@ -1,32 +1,606 @@
 app [make_glue] { pf: platform "../platform/main.roc" }
 
 import pf.Types exposing [Types]
-# import pf.Shape exposing [Shape, RocFn]
 import pf.File exposing [File]
-# import pf.TypeId exposing [TypeId]
+import pf.TypeId exposing [TypeId]
 
-## generate placeholder glue for now that only works for our one C test
+## Generates a C header with the platform's types and entry points.
+##
+## Every type gets static assertions that its size and alignment (and, for tag unions, the
+## offset of the discriminant) match what the Roc compiler computed, so a mismatch between
+## the header and the compiler is a compile error in the host rather than memory corruption.
 make_glue : List Types -> Result (List File) Str
-make_glue = \_types_by_arch ->
-    Ok([{ name: "roc_app.h", content: placeholder_glue }])
+make_glue = \types_by_arch ->
+    arch_sections =
+        List.walk(types_by_arch, "", \content, types ->
+            Str.concat(content, generate_arch_section(types)))
+
+    Ok([{ name: "roc_app.h", content: "${file_header}${arch_sections}${file_footer}" }])
+
+generate_arch_section : Types -> Str
+generate_arch_section = \types ->
+    arch = (Types.target(types)).architecture
+    arch_macro = arch_predefined_macro(arch)
+    tag_mask = if ptr_size(types) == 4 then "3" else "7"
+
+    # Pointer-based types (recursive tag unions) and enumerations don't depend on other types,
+    # and other types can contain them by value, so they go first.
+    pointer_types = Types.walk_shapes(types, "", \buf, shape, id ->
+        when shape is
+            TagUnion(Enumeration({ name, tags, size })) ->
+                generate_enumeration(buf, name, tags, size)
+
+            TagUnion(Recursive({ name, tags })) ->
+                generate_pointer_tag_union(buf, types, id, name, List.map(tags, .name), TagInPointer)
+
+            TagUnion(NullableWrapped({ name, index_of_null_tag, tags })) ->
+                generate_pointer_tag_union(buf, types, id, name, List.map(tags, .name), NullOrTagInPointer(index_of_null_tag))
+
+            TagUnion(NullableUnwrapped({ name, null_tag, non_null_tag })) ->
+                generate_pointer_tag_union(buf, types, id, name, [null_tag, non_null_tag], NullIs(null_tag))
+
+            TagUnion(NonNullableUnwrapped({ name, tag_name })) ->
+                generate_pointer_tag_union(buf, types, id, name, [tag_name], NoTag)
+
+            _ -> buf)
+
+    # Every other type is added to Types after the types it contains, so walking the shapes
+    # in order declares each type before it's used.
+    value_types = Types.walk_shapes(types, "", \buf, shape, id ->
+        if Types.size(types, id) == 0 then
+            buf
+        else
+            when shape is
+                Struct({ name, fields: HasNoClosure(fields) }) ->
+                    generate_struct(buf, types, id, name, fields)
+
+                TagUnionPayload({ name, fields: HasNoClosure(fields) }) ->
+                    generate_struct(buf, types, id, name, List.map(fields, \{ name: field_name, id: field_id } -> { name: "f${field_name}", id: field_id }))
+
+                TagUnion(SingleTagStruct({ name, payload: HasNoClosure(fields) })) ->
+                    generate_struct(buf, types, id, name, List.map_with_index(fields, \{ id: field_id }, index -> { name: "f${Num.to_str(index)}", id: field_id }))
+
+                TagUnion(NonRecursive({ name, tags, discriminant_size, discriminant_offset })) ->
+                    generate_non_recursive_tag_union(buf, types, id, name, tags, discriminant_size, discriminant_offset)
+
+                RocResult(ok_id, err_id) ->
+                    generate_result(buf, types, id, ok_id, err_id)
+
+                Struct({ name, fields: HasClosure(_) })
+                | TagUnionPayload({ name, fields: HasClosure(_) })
+                | TagUnion(SingleTagStruct({ name, payload: HasClosure(_) })) ->
+                    generate_opaque(buf, types, id, name, "contains functions")
+
+                RocDict(_, _) ->
+                    generate_opaque(buf, types, id, type_name(types, id), "a Dict")
+
+                RocSet(_) ->
+                    generate_opaque(buf, types, id, type_name(types, id), "a Set")
+
+                Function(roc_fn) ->
+                    if roc_fn.is_toplevel then
+                        buf
+                    else
+                        generate_opaque(buf, types, id, type_name(types, id), "the captures of a function")
+
+                TagUnion(_)
+                | RecursivePointer(_)
+                | Unit
+                | Unsized
+                | EmptyTagUnion
+                | Num(_)
+                | Bool
+                | RocStr
+                | RocList(_)
+                | RocBox(_) ->
+                    # These are either declared above, or in the file header.
+                    buf)
+
+    entry_points =
+        List.walk(Types.entry_points(types), "", \buf, T(name, id) -> generate_entry_point(buf, types, name, id))
+
+    """
+    #if defined(${arch_macro})
+
+    #define ROC_TAG_MASK ((uintptr_t)${tag_mask})
+
+    ${pointer_types}${value_types}${entry_points}
+    #endif // ${arch_macro}
+
+    """
+
+generate_struct : Str, Types, TypeId, Str, List { name : Str, id : TypeId } -> Str
+generate_struct = \buf, types, id, name, fields ->
+    field_lines =
+        fields
+        |> List.walk("", \accum, { name: field_name, id: field_id } ->
+            if Types.size(types, field_id) == 0 then
+                # C doesn't have zero-sized types, and Roc doesn't store them.
+                accum
+            else
+                Str.concat(accum, "${indent}${type_name(types, field_id)} ${escape_kw(field_name)};\n"))
+
+    """
+    ${buf}typedef struct ${name} {
+    ${field_lines}} ${name};
+    ${layout_assertions(types, id, name)}
+
 
-placeholder_glue =
     """
+
+generate_enumeration : Str, Str, List Str, U32 -> Str
+generate_enumeration = \buf, name, tags, size ->
+    constants =
+        List.walk_with_index(tags, "", \accum, tag_name, index ->
+            Str.concat(accum, "${indent}${name}_${tag_name} = ${Num.to_str(index)},\n"))
+
+    """
+    ${buf}typedef ${unsigned_int(size)} ${name};
+
+    enum {
+    ${constants}};
+
+
+    """
+
+## A tag union whose values are a single pointer to a heap-allocated payload.
+## Depending on how many tags it has, the tag is stored in the low bits of the pointer, or is
+## implied by whether the pointer is NULL.
+generate_pointer_tag_union = \buf, types, id, name, tag_names, encoding ->
+    discriminants =
+        List.walk_with_index(tag_names, "", \accum, tag_name, index ->
+            Str.concat(accum, "${indent}${name}_${tag_name} = ${Num.to_str(index)},\n"))
+
+    helpers =
+        when encoding is
+            TagInPointer ->
+                """
+                // The tag is stored in the low bits of the pointer.
+                static inline unsigned int ${name}_discriminant(${name} value)
+                {
+                    return (unsigned int)((uintptr_t)value.pointer & ROC_TAG_MASK);
+                }
+
+                static inline void *${name}_payload(${name} value)
+                {
+                    return (void *)((uintptr_t)value.pointer & ~ROC_TAG_MASK);
+                }
+                """
+
+            NullOrTagInPointer(null_index) ->
+                """
+                // NULL is ${name}_${list_get_or_empty(tag_names, null_index)}, and the other tags are stored in the low bits of the pointer.
+                static inline unsigned int ${name}_discriminant(${name} value)
+                {
+                    return value.pointer == NULL
+                        ? ${Num.to_str(null_index)}
+                        : (unsigned int)((uintptr_t)value.pointer & ROC_TAG_MASK);
+                }
+
+                static inline void *${name}_payload(${name} value)
+                {
+                    return (void *)((uintptr_t)value.pointer & ~ROC_TAG_MASK);
+                }
+                """
+
+            NullIs(null_tag) ->
+                """
+                // NULL is ${name}_${null_tag}, and any other pointer points to the other tag's payload.
+                static inline unsigned int ${name}_discriminant(${name} value)
+                {
+                    return value.pointer == NULL ? ${name}_${null_tag} : 1 - ${name}_${null_tag};
+                }
+
+                static inline void *${name}_payload(${name} value)
+                {
+                    return value.pointer;
+                }
+                """
+
+            NoTag ->
+                """
+                static inline void *${name}_payload(${name} value)
+                {
+                    return value.pointer;
+                }
+                """
+
+    """
+    ${buf}// A reference-counted pointer to the payload of one of these tags.
+    typedef struct ${name} {
+        void *pointer;
+    } ${name};
+    ${layout_assertions(types, id, name)}
+
+    enum {
+    ${discriminants}};
+
+    ${helpers}
+
+
+    """
+
+## Tag unions without recursion are stored inline: the payload of the current tag,
+## followed by the discriminant.
+generate_non_recursive_tag_union = \buf, types, id, name, tags, discriminant_size, discriminant_offset ->
+    discriminants =
+        List.walk_with_index(tags, "", \accum, { name: tag_name }, index ->
+            Str.concat(accum, "${indent}${name}_${tag_name} = ${Num.to_str(index)},\n"))
+
+    payload_fields =
+        List.walk(tags, "", \accum, { name: tag_name, payload } ->
+            when payload is
+                Some(payload_id) if Types.size(types, payload_id) > 0 ->
+                    Str.concat(accum, "${indent}${indent}${type_name(types, payload_id)} ${escape_kw(tag_name)};\n")
+
+                _ -> accum)
+
+    union_str =
+        if Str.is_empty(payload_fields) then
+            ""
+        else
+            "${indent}union {\n${payload_fields}${indent}} payload;\n"
+
+    discriminant_str =
+        if discriminant_size == 0 then
+            ""
+        else
+            "${indent}${unsigned_int(discriminant_size)} discriminant;\n"
+
+    offset_assertion =
+        if discriminant_size == 0 then
+            ""
+        else
+            "\n_Static_assert(offsetof(${name}, discriminant) == ${Num.to_str(discriminant_offset)}, \"${name} has a different layout in Roc\");"
+
+    """
+    ${buf}typedef struct ${name} {
+    ${union_str}${discriminant_str}} ${name};
+    ${layout_assertions(types, id, name)}${offset_assertion}
+
+    enum {
+    ${discriminants}};
+
+
+    """
+
+## `Result ok err` is a tag union `[Err err, Ok ok]`, so `Err` has discriminant 0.
+generate_result = \buf, types, id, ok_id, err_id ->
+    name = type_name(types, id)
+
+    payload_field = \field_id, field_name ->
+        if Types.size(types, field_id) == 0 then
+            ""
+        else
+            "${indent}${indent}${type_name(types, field_id)} ${field_name};\n"
+
+    payload_fields = Str.concat(payload_field(err_id, "err"), payload_field(ok_id, "ok"))
+
+    union_str =
+        if Str.is_empty(payload_fields) then
+            ""
+        else
+            "${indent}union {\n${payload_fields}${indent}} payload;\n"
+
+    """
+    ${buf}typedef struct ${name} {
+    ${union_str}${indent}uint8_t is_ok;
+    } ${name};
+    ${layout_assertions(types, id, name)}
+
+
+    """
+
+## A type that C code can't use directly, but that still needs the correct size and alignment,
+## e.g. to pass it back to Roc.
+generate_opaque = \buf, types, id, name, description ->
+    size = Num.to_str(Types.size(types, id))
+    align = Num.to_str(Types.alignment(types, id))
+
+    """
+    ${buf}// Opaque, because it's ${description}.
+    typedef struct ${name} {
+        _Alignas(${align}) uint8_t bytes[${size}];
+    } ${name};
+
+
+    """
+
+layout_assertions : Types, TypeId, Str -> Str
+layout_assertions = \types, id, name ->
+    size = Num.to_str(Types.size(types, id))
+    align = Num.to_str(Types.alignment(types, id))
+
+    """
+    _Static_assert(sizeof(${name}) == ${size}, "${name} has a different size in Roc");
+    _Static_assert(_Alignof(${name}) == ${align}, "${name} has a different alignment in Roc");
+    """
+
+generate_entry_point : Str, Types, Str, TypeId -> Str
+generate_entry_point = \buf, types, name, id ->
+    (args, ret_id) =
+        when Types.shape(types, id) is
+            Function(roc_fn) -> (roc_fn.args, roc_fn.ret)
+            _ -> ([], id)
+
+    when Types.shape(types, ret_id) is
+        Function(_) ->
+            Str.concat(buf, "// ${name} returns a function, which isn't supported by C glue yet.\n\n")
+
+        _ ->
+            generate_entry_point_help(buf, types, name, args, ret_id)
+
+generate_entry_point_help : Str, Types, Str, List TypeId, TypeId -> Str
+generate_entry_point_help = \buf, types, name, args, ret_id ->
+    returns_unit = Types.size(types, ret_id) == 0
+    ret_type = if returns_unit then "uint8_t" else type_name(types, ret_id)
+
+    extern_args =
+        to_arg_str(args, types, \arg_id, index ->
+            if passed_by_pointer(types, arg_id) then
+                "${type_name(types, arg_id)} *arg${Num.to_str(index)}"
+            else
+                "${type_name(types, arg_id)} arg${Num.to_str(index)}")
+
+    wrapper_args =
+        to_arg_str(args, types, \arg_id, index ->
+            "${type_name(types, arg_id)} arg${Num.to_str(index)}")
+
+    call_args =
+        to_arg_str(args, types, \arg_id, index ->
+            if passed_by_pointer(types, arg_id) then
+                "&arg${Num.to_str(index)}"
+            else
+                "arg${Num.to_str(index)}")
+
+    extern_params = if Str.is_empty(extern_args) then "" else ", ${extern_args}"
+    wrapper_params = if Str.is_empty(wrapper_args) then "void" else wrapper_args
+    call_params = if Str.is_empty(call_args) then "" else ", ${call_args}"
+
+    wrapper =
+        if returns_unit then
+            """
+            static inline void roc_${name}(${wrapper_params})
+            {
+                uint8_t ret;
+
+                roc__${name}_1_exposed_generic(&ret${call_params});
+            }
+            """
+        else
+            """
+            static inline ${ret_type} roc_${name}(${wrapper_params})
+            {
+                ${ret_type} ret;
+
+                roc__${name}_1_exposed_generic(&ret${call_params});
+
+                return ret;
+            }
+            """
+
+    """
+    ${buf}extern void roc__${name}_1_exposed_generic(${ret_type} *ret${extern_params});
+
+    // Roc takes ownership of the arguments, and the caller owns the returned value.
+    ${wrapper}
+
+
+    """
+
+## Matches how the compiler passes arguments to exposed functions: Str, List and
+## records bigger than 4 machine words are passed by pointer, and everything else by value.
+passed_by_pointer : Types, TypeId -> Bool
+passed_by_pointer = \types, id ->
+    when Types.shape(types, id) is
+        RocStr | RocList(_) | Unsized -> Bool.true
+        Struct(_) | TagUnionPayload(_) | TagUnion(SingleTagStruct(_)) | RocDict(_, _) | RocSet(_) ->
+            Types.size(types, id) > 4 * ptr_size(types)
+
+        _ -> Bool.false
+
+ptr_size : Types -> U32
+ptr_size = \types ->
+    when (Types.target(types)).architecture is
+        Aarch32 | Wasm32 | X86x32 -> 4
+        Aarch64 | X86x64 -> 8
+
+type_name : Types, TypeId -> Str
+type_name = \types, id ->
+    when Types.shape(types, id) is
+        Unit | EmptyTagUnion -> "uint8_t"
+        Unsized -> "RocList"
+        RocStr -> "RocStr"
+        Bool -> "bool"
+        Num(U8) -> "uint8_t"
+        Num(U16) -> "uint16_t"
+        Num(U32) -> "uint32_t"
+        Num(U64) -> "uint64_t"
+        Num(U128) -> "unsigned __int128"
+        Num(I8) -> "int8_t"
+        Num(I16) -> "int16_t"
+        Num(I32) -> "int32_t"
+        Num(I64) -> "int64_t"
+        Num(I128) -> "__int128"
+        Num(F32) -> "float"
+        Num(F64) -> "double"
+        Num(Dec) -> "RocDec"
+        RocList(_) -> "RocList"
+        RocBox(_) -> "RocBox"
+        RocDict(_, _) -> "RocDict_${type_id_str(id)}"
+        RocSet(_) -> "RocSet_${type_id_str(id)}"
+        RocResult(_, _) -> "RocResult_${type_id_str(id)}"
+        RecursivePointer(content) -> type_name(types, content)
+        Struct({ name }) -> name
+        TagUnionPayload({ name }) -> name
+        TagUnion(Enumeration({ name })) -> name
+        TagUnion(NonRecursive({ name })) -> name
+        TagUnion(Recursive({ name })) -> name
+        TagUnion(NullableWrapped({ name })) -> name
+        TagUnion(NullableUnwrapped({ name })) -> name
+        TagUnion(NonNullableUnwrapped({ name })) -> name
+        TagUnion(SingleTagStruct({ name })) -> name
+        Function({ function_name }) -> function_name
+
+type_id_str : TypeId -> Str
+type_id_str = \id -> Num.to_str(TypeId.type_id_to_u64(id))
+
+unsigned_int : U32 -> Str
+unsigned_int = \bytes ->
+    when bytes is
+        1 -> "uint8_t"
+        2 -> "uint16_t"
+        4 -> "uint32_t"
+        _ -> "uint64_t"
+
+list_get_or_empty : List Str, U16 -> Str
+list_get_or_empty = \list, index ->
+    when List.get(list, Num.int_cast(index)) is
+        Ok(elem) -> elem
+        Err(OutOfBounds) -> ""
+
+to_arg_str : List TypeId, Types, (TypeId, U64 -> Str) -> Str
+to_arg_str = \args, types, fmt ->
+    List.walk_with_index(args, "", \state, arg_id, index ->
+        # Drop zero-sized args; nothing gets passed for them anyway.
+        if Types.size(types, arg_id) == 0 then
+            state
+        else
+            arg_str = fmt(arg_id, index)
+
+            if Str.is_empty(state) then
+                arg_str
+            else
+                "${state}, ${arg_str}")
+
+arch_predefined_macro = \arch ->
+    when arch is
+        Aarch32 -> "__arm__"
+        Aarch64 -> "__aarch64__"
+        Wasm32 -> "__wasm32__"
+        X86x32 -> "__i386__"
+        X86x64 -> "__x86_64__"
+
+escape_kw = \input ->
+    if Set.contains(reserved_keywords, input) then
+        "${input}_"
+    else
+        input
+
+reserved_keywords = Set.from_list([
+    "auto",
+    "break",
+    "case",
+    "char",
+    "const",
+    "continue",
+    "default",
+    "do",
+    "double",
+    "else",
+    "enum",
+    "extern",
+    "float",
+    "for",
+    "goto",
+    "if",
+    "inline",
+    "int",
+    "long",
+    "register",
+    "restrict",
+    "return",
+    "short",
+    "signed",
+    "sizeof",
+    "static",
+    "struct",
+    "switch",
+    "typedef",
+    "union",
+    "unsigned",
+    "void",
+    "volatile",
+    "while",
+    "bool",
+    "true",
+    "false",
+])
+
+indent = "    "
+
+file_header =
+    """
+    // ⚠️ GENERATED CODE ⚠️ - this entire file was generated by the `roc glue` CLI command
+    //
+    // Ownership conventions:
+    //
+    // - Str, List, Box, and recursive tag unions point to heap allocations, which are reference counted.
+    //   The reference count is stored in the pointer-sized integer just before the allocation's data.
+    // - Roc takes ownership of the arguments passed to it, and decrements their reference counts itself.
+    //   Increment the reference count of a value before passing it to Roc if the host keeps using it.
+    // - The host owns the values Roc returns, and must free them (with roc_dealloc) when it's done with them.
+
     #ifndef ROC_APP_H
     #define ROC_APP_H
 
+    #include <stdbool.h>
+    #include <stddef.h>
     #include <stdint.h>
 
-    extern void roc__main_for_host_1_exposed_generic(uint8_t *ret);
+    // Every host has to implement these:
+    //
+    // void *roc_alloc(size_t size, unsigned int alignment);
+    // void *roc_realloc(void *ptr, size_t new_size, size_t old_size, unsigned int alignment);
+    // void roc_dealloc(void *ptr, unsigned int alignment);
+    // void roc_panic(RocStr *msg, unsigned int tag_id);
+    // void roc_dbg(RocStr *loc, RocStr *msg, RocStr *src);
+    // void *roc_memset(void *str, int c, size_t n);
+
+    // Strings with fewer bytes than the size of this struct are stored inline ("small strings"):
+    // the last byte is the length with its highest bit set, and the other bytes are the contents.
+    typedef struct RocStr {
+        char *bytes;
+        size_t len;
+        size_t capacity;
+    } RocStr;
 
-    uint8_t roc_main_for_host()
+    // The element type of a list is shown in the Roc type of the value that contains it.
+    typedef struct RocList {
+        void *elements;
+        size_t length;
+        size_t capacity;
+    } RocList;
+
+    typedef void *RocBox;
+
+    // A fixed-point decimal number: the value multiplied by 10^18.
+    typedef struct RocDec {
+        __int128 value;
+    } RocDec;
+
+    static inline bool roc_str_is_small(const RocStr *str)
     {
-        uint8_t ret;
+        return ((const int8_t *)str)[sizeof(RocStr) - 1] < 0;
+    }
 
-        roc__main_for_host_1_exposed_generic(&ret);
+    static inline size_t roc_str_len(const RocStr *str)
+    {
+        return roc_str_is_small(str)
+            ? (size_t)(((const uint8_t *)str)[sizeof(RocStr) - 1] & 0x7f)
+            : str->len;
+    }
 
-        return ret;
+    static inline const char *roc_str_bytes(const RocStr *str)
+    {
+        return roc_str_is_small(str) ? (const char *)str : str->bytes;
     }
 
-    #endif
+
+    """
+
+file_footer =
+    """
+    #endif // ROC_APP_H
+
     """
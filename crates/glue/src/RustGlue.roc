@@ -60,7 +60,7 @@ convert_types_to_file = \types ->
                     generate_struct(buf, types, id, name, name_tag_union_payload_fields(fields), Public)
 
                 TagUnion(Enumeration({ name, tags, size })) ->
-                    generate_enumeration(buf, types, type, name, tags, size)
+                    generate_enumeration(buf, types, id, type, name, tags, size)
 
                 TagUnion(NonRecursive({ name, tags, discriminant_size, discriminant_offset })) ->
                     if !(List.is_empty(tags)) then
@@ -82,7 +82,7 @@ convert_types_to_file = \types ->
                     generate_nullable_unwrapped(buf, types, id, name, null_tag, non_null_tag, non_null_payload, which_tag_is_null)
 
                 TagUnion(SingleTagStruct({ name, tag_name, payload })) ->
-                    generate_single_tag_struct(buf, types, name, tag_name, payload)
+                    generate_single_tag_struct(buf, types, id, name, tag_name, payload)
 
                 TagUnion(NonNullableUnwrapped({ name, tag_name, payload })) ->
                     generate_non_nullable_unwrapped(buf, types, name, tag_name, payload, 0, 0, None)
@@ -321,6 +321,17 @@ generate_struct = \buf, types, id, name, struct_fields, visibility ->
     |> Str.concat("#[repr(${repr})]\n${pub}struct ${escaped_name} {\n")
     |> generate_struct_fields(types, Public, struct_fields)
     |> Str.concat("}\n\n")
+    |> \b ->
+        # The Rust types of closures and unsized values don't have the same layout as in Roc
+        has_roc_layout =
+            when struct_fields is
+                HasNoClosure(fields) -> fields_have_roc_layout(types, fields)
+                HasClosure(_) -> Bool.false
+
+        if has_roc_layout then
+            generate_layout_checks(b, types, id, escaped_name)
+        else
+            b
     |> generate_roc_refcounted(types, struct_type, escaped_name)
 
 generate_struct_fields = \buf, types, visibility, struct_fields ->
@@ -343,6 +354,28 @@ generate_struct_field_without_closure = \types, visibility ->
 
         Str.concat(accum, "${indent}${pub} ${escaped_field_name}: ${type_str},\n")
 
+fields_have_roc_layout = \types, fields ->
+    List.all(fields, \{ id } ->
+        when Types.shape(types, id) is
+            Function(_) | Unsized -> Bool.false
+            _ -> Bool.true)
+
+# Makes the Rust compiler verify that the generated type has the layout the Roc compiler computed.
+generate_layout_checks : Str, Types, TypeId, Str -> Str
+generate_layout_checks = \buf, types, id, escaped_name ->
+    size_of_self = Num.to_str(Types.size(types, id))
+    align_of_self = Num.to_str(Types.alignment(types, id))
+
+    Str.concat(
+        buf,
+        """
+        const _SIZE_CHECK_${escaped_name}: () = assert!(core::mem::size_of::<${escaped_name}>() == ${size_of_self});
+        const _ALIGN_CHECK_${escaped_name}: () = assert!(core::mem::align_of::<${escaped_name}>() == ${align_of_self});
+
+
+        """,
+    )
+
 name_tag_union_payload_fields = \payload_fields ->
     # Tag union payloads have numbered fields, so we prefix them
     # with an "f" because Rust doesn't allow struct fields to be numbers.
@@ -355,10 +388,11 @@ name_tag_union_payload_fields = \payload_fields ->
             renamed_fields = List.map(fields, \{ name, id, accessors } -> { name: "f${name}", id, accessors })
             HasClosure(renamed_fields)
 
-generate_enumeration = \buf, types, enum_type, name, tags, tag_bytes ->
+generate_enumeration = \buf, types, id, enum_type, name, tags, tag_bytes ->
     escaped_name = escape_kw(name)
 
     repr_bits = tag_bytes * 8 |> Num.to_str
+    repr_type = "u${repr_bits}"
 
     buf
     |> generate_derive_str(types, enum_type, ExcludeDebug)
@@ -378,6 +412,26 @@ generate_enumeration = \buf, types, enum_type, name, tags, tag_bytes ->
     )
     |> \b -> List.walk(tags, b, generate_enum_tags_debug(name))
     |> Str.concat("${indent}${indent}}\n${indent}}\n}\n\n")
+    |> generate_layout_checks(types, id, escaped_name)
+    |> Str.concat(
+        """
+        impl From<${escaped_name}> for ${repr_type} {
+            fn from(tag: ${escaped_name}) -> Self {
+                tag as ${repr_type}
+            }
+        }
+
+        impl TryFrom<${repr_type}> for ${escaped_name} {
+            /// The discriminant, if it isn't the discriminant of any tag.
+            type Error = ${repr_type};
+
+            fn try_from(discriminant: ${repr_type}) -> Result<Self, Self::Error> {
+                match discriminant {
+
+        """,
+    )
+    |> \b -> List.walk_with_index(tags, b, generate_enum_tags_try_from)
+    |> Str.concat("${indent}${indent}${indent}_ => Err(discriminant),\n${indent}${indent}}\n${indent}}\n}\n\n")
     |> generate_roc_refcounted(types, enum_type, escaped_name)
 
 generate_enum_tags = \accum, name, index ->
@@ -385,6 +439,11 @@ generate_enum_tags = \accum, name, index ->
 
     Str.concat(accum, "${indent}${name} = ${index_str},\n")
 
+generate_enum_tags_try_from = \accum, tag_name, index ->
+    index_str = Num.to_str(index)
+
+    Str.concat(accum, "${indent}${indent}${indent}${index_str} => Ok(Self::${tag_name}),\n")
+
 generate_enum_tags_debug = \name ->
     \accum, tag_name ->
         Str.concat(accum, "${indent}${indent}${indent}Self::${tag_name} => f.write_str(\"${name}::${tag_name}\"),\n")
@@ -1557,7 +1616,7 @@ generate_nullable_unwrapped = \buf, types, tag_unionid, name, null_tag, non_null
     """
     |> generate_roc_refcounted(types, union_type, name)
 
-generate_single_tag_struct = \buf, types, name, tag_name, payload ->
+generate_single_tag_struct = \buf, types, id, name, tag_name, payload ->
     # Store single-tag unions as structs rather than enums,
     # because they have only one alternative. However, still
     # offer the usual tag union APIs.
@@ -1594,6 +1653,11 @@ generate_single_tag_struct = \buf, types, name, tag_name, payload ->
                     generate_zero_element_single_tag_struct(b, escaped_name, tag_name)
                 else
                     generate_multi_element_single_tag_struct(b, types, escaped_name, tag_name, fields, as_struct_fields)
+            |> \b ->
+                if fields_have_roc_layout(types, fields) then
+                    generate_layout_checks(b, types, id, escaped_name)
+                else
+                    b
             |> generate_roc_refcounted(types, TagUnion(SingleTagStruct({ name, tag_name, payload })), escaped_name)
 
         HasClosure(_) ->
@@ -1729,6 +1793,35 @@ generate_multi_element_single_tag_struct = \buf, types, name, tag_name, payload_
 
         """,
     )
+    |> \b ->
+        field_types = List.map(payload_fields, \{ id } -> type_name(types, id))
+        fields =
+            List.map_with_index(payload_fields, \_, index ->
+                index_str = Num.to_str(index)
+
+                "f${index_str}")
+        payload_type = as_rust_tuple(field_types)
+        payload_pattern = as_rust_tuple(fields)
+        args_str = Str.join_with(fields, ", ")
+
+        Str.concat(
+            b,
+            """
+            impl From<${payload_type}> for ${name} {
+                fn from(${payload_pattern}: ${payload_type}) -> Self {
+                    Self::${tag_name}(${args_str})
+                }
+            }
+
+            impl From<${name}> for ${payload_type} {
+                fn from(tag: ${name}) -> Self {
+                    tag.into_${tag_name}()
+                }
+            }
+
+
+            """,
+        )
 
 as_rust_tuple = \list ->
     # If there is 1 element in the list we just return it
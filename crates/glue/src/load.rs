@@ -61,6 +61,16 @@ pub fn generate(
                 emit_debug_info: false,
                 emit_llvm_ir: false,
                 fuzz: false,
+                deterministic: false,
+                emit_js_glue: false,
+                emit_frame_table: false,
+                emit_mono_ir: false,
+                emit_specialization_report: false,
+                warn_closure_captures: false,
+                emit_borrow_report: false,
+                emit_dead_field_report: false,
+                emit_match_trees: false,
+                emit_size_report: false,
             };
 
             let load_config = standard_load_config(
@@ -106,6 +116,7 @@ pub fn generate(
                     problems,
                     total_time,
                     expect_metadata: _,
+                    profile: _,
                 }) => {
                     // TODO: Should binary_path be update to deal with extensions?
                     use roc_target::OperatingSystem;
@@ -430,6 +441,11 @@ pub fn load_types(
             palette: DEFAULT_PALETTE,
             threading,
             exec_mode: ExecutionMode::Check,
+            emit_match_trees: false,
+            trace_abilities: false,
+            profile: None,
+            doc_examples: 0,
+            comptime_limits: roc_load::ComptimeLimits::default(),
         },
     )
     .unwrap_or_else(|problem| match problem {
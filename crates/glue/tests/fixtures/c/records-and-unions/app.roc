@@ -0,0 +1,3 @@
+app [main] { pf: platform "platform.roc" }
+
+main = { count: 42, label: "hello", shape: Rect(2, 3) }
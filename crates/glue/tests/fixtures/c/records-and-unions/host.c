@@ -0,0 +1,41 @@
+#include <assert.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "test_glue/roc_app.h"
+
+void *roc_alloc(size_t size, unsigned int alignment) { return malloc(size); }
+
+void *roc_realloc(void *ptr, size_t new_size, size_t old_size, unsigned int alignment)
+{
+    return realloc(ptr, new_size);
+}
+
+void roc_dealloc(void *ptr, unsigned int alignment) { free(ptr); }
+
+void roc_panic(RocStr *msg, unsigned int tag_id)
+{
+    fprintf(stderr, "Roc crashed: %.*s\n", (int)roc_str_len(msg), roc_str_bytes(msg));
+    exit(1);
+}
+
+void roc_dbg(RocStr *loc, RocStr *msg, RocStr *src) {}
+
+void *roc_memset(void *str, int c, size_t n) { return memset(str, c, n); }
+
+int main(void)
+{
+    Drawing drawing = roc_main_for_host();
+
+    printf("count = %llu, label = %.*s, shape = %s\n",
+        (unsigned long long)drawing.count,
+        (int)roc_str_len(&drawing.label),
+        roc_str_bytes(&drawing.label),
+        drawing.shape.discriminant == Shape_Rect ? "Rect" : "not Rect");
+
+    assert(drawing.count == 42);
+    assert(drawing.shape.discriminant == Shape_Rect);
+
+    return 0;
+}
@@ -0,0 +1,13 @@
+platform "test-platform"
+    requires {} { main : _ }
+    exposes []
+    packages {}
+    imports []
+    provides [main_for_host]
+
+Shape : [Circle F64, Dot, Rect F64 F64]
+
+Drawing : { count : U64, label : Str, shape : Shape }
+
+main_for_host : Drawing
+main_for_host = main
@@ -28,6 +28,12 @@ pub extern "C" fn rust_main() {
 
     assert_eq!(set.len(), 1);
 
+    // Verify the conversions to and from the discriminant. Tags are sorted alphabetically.
+    assert_eq!(u8::from(roc_app::MyEnum::Bar), 0);
+    assert_eq!(u8::from(tag_union), 2);
+    assert_eq!(roc_app::MyEnum::try_from(1u8), Ok(roc_app::MyEnum::Baz));
+    assert_eq!(roc_app::MyEnum::try_from(3u8), Err(3));
+
     println!(
         "tag_union was: {:?}, Bar is: {:?}, Baz is: {:?}",
         tag_union,
@@ -0,0 +1,3 @@
+app [main] { pf: platform "platform.roc" }
+
+main = Labeled("hello", 42)
@@ -0,0 +1,11 @@
+platform "test-platform"
+    requires {} { main : _ }
+    exposes []
+    packages {}
+    imports []
+    provides [main_for_host]
+
+Labeled : [Labeled Str U64]
+
+main_for_host : Labeled
+main_for_host = main
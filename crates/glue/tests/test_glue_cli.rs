@@ -103,6 +103,9 @@ mod glue_cli_tests {
         rust_single_tag_union:"rust/single-tag-union" => indoc!(r#"
             tag_union was: SingleTagUnion::OneTag
         "#),
+        rust_single_tag_payload:"rust/single-tag-payload" => indoc!(r#"
+            tag_union was: Labeled::Labeled("hello", 42), label = hello, count = 42
+        "#),
         rust_union_with_padding:"rust/union-with-padding" => indoc!(r#"
             tag_union was: NonRecursive::Foo("This is a test")
             `Foo "small str"` is: NonRecursive::Foo("small str")
@@ -169,6 +172,9 @@ mod glue_cli_tests {
         c_hello_world:"c/hello-world" => indoc!(r#"
             main_for_host = 42
         "#),
+        c_records_and_unions:"c/records-and-unions" => indoc!(r#"
+            count = 42, label = hello, shape = Rect
+        "#),
     }
 
     fn check_for_tests(all_fixtures: &mut roc_collections::VecSet<String>) {
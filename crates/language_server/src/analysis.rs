@@ -2,6 +2,7 @@ use std::{
     collections::HashMap,
     path::{Path, PathBuf},
     sync::Arc,
+    time::Instant,
 };
 
 use bumpalo::Bump;
@@ -12,12 +13,13 @@ use roc_collections::{MutMap, MutSet, VecMap};
 use roc_load::{docs::ModuleDocumentation, CheckedModule, LoadedModule};
 use roc_module::symbol::{Interns, ModuleId, Symbol};
 use roc_packaging::cache::{self, RocCacheDir};
-use roc_region::all::LineInfo;
+use roc_region::all::{LineInfo, Region};
+use roc_reporting::group::{group_problems, ProblemGroups};
 use roc_reporting::report::RocDocAllocator;
 use roc_solve_problem::TypeError;
 use roc_types::subs::{Subs, Variable};
 
-use tower_lsp::lsp_types::{Diagnostic, SemanticTokenType, Url};
+use tower_lsp::lsp_types::{Diagnostic, Location, SemanticTokenType, Url};
 
 mod analysed_doc;
 mod annotation_visitor;
@@ -27,7 +29,9 @@ mod semantic_tokens;
 mod tokens;
 mod utils;
 
-use crate::convert::diag::{IntoLspDiagnostic, ProblemFmt};
+use crate::convert::diag::{add_problem_group, IntoLspDiagnostic, ProblemFmt};
+use crate::convert::ToRange;
+use crate::stats::Stats;
 
 pub(crate) use self::analysed_doc::{AnalyzedDocument, DocInfo};
 use self::{analysed_doc::ModuleIdToUrl, tokens::Token};
@@ -101,11 +105,12 @@ pub struct AnalysisResult {
     diagnostics: Vec<Diagnostic>,
 }
 
-pub(crate) fn global_analysis(doc_info: DocInfo) -> Vec<AnalyzedDocument> {
+pub(crate) fn global_analysis(doc_info: DocInfo, stats: &Stats) -> Vec<AnalyzedDocument> {
     let fi = doc_info.url.to_file_path().unwrap();
     let src_dir = find_src_dir(&fi).to_path_buf();
 
     let arena = Bump::new();
+    let start = Instant::now();
     let loaded = roc_load::load_and_typecheck_str(
         &arena,
         fi,
@@ -119,6 +124,8 @@ pub(crate) fn global_analysis(doc_info: DocInfo) -> Vec<AnalyzedDocument> {
         roc_reporting::report::DEFAULT_PALETTE,
     );
 
+    stats.record_analysis(start.elapsed(), loaded.as_ref().ok());
+
     let module = match loaded {
         Ok(module) => module,
         Err(problem) => {
@@ -163,6 +170,11 @@ pub(crate) fn global_analysis(doc_info: DocInfo) -> Vec<AnalyzedDocument> {
     });
 
     let exposed_imports = resolve_exposed_imports(exposed_imports, &exposes);
+    let problem_groups = group_problems(&mut can_problems, &mut type_problems);
+    let line_infos = sources
+        .iter()
+        .map(|(module_id, (_, source))| (*module_id, LineInfo::new(source)))
+        .collect();
 
     let modules_info = Arc::new(ModulesInfo::from_loaded_module(
         exposes,
@@ -175,6 +187,8 @@ pub(crate) fn global_analysis(doc_info: DocInfo) -> Vec<AnalyzedDocument> {
         module_id_to_url: module_id_to_url_from_sources(&sources),
         can_problems: &mut can_problems,
         type_problems: &mut type_problems,
+        problem_groups,
+        line_infos,
         declarations_by_id: &mut declarations_by_id,
         typechecked: &mut typechecked,
         root_module: &mut root_module,
@@ -265,6 +279,9 @@ struct AnalyzedDocumentBuilder<'a> {
     module_id_to_url: ModuleIdToUrl,
     can_problems: &'a mut MutMap<ModuleId, Vec<roc_problem::can::Problem>>,
     type_problems: &'a mut MutMap<ModuleId, Vec<TypeError>>,
+    problem_groups: ProblemGroups,
+    /// For the locations of problems in other modules
+    line_infos: MutMap<ModuleId, LineInfo>,
     declarations_by_id: &'a mut MutMap<ModuleId, Declarations>,
     typechecked: &'a mut MutMap<ModuleId, CheckedModule>,
     root_module: &'a mut Option<RootModule>,
@@ -373,14 +390,35 @@ impl<'a> AnalyzedDocumentBuilder<'a> {
 
         let type_problems = self.type_problems.remove(&module_id).unwrap_or_default();
 
+        let locate = |module_id: ModuleId, region: Region| {
+            Some(Location {
+                uri: self.module_id_to_url.get(&module_id)?.clone(),
+                range: region.to_range(self.line_infos.get(&module_id)?),
+            })
+        };
+
         for can_problem in can_problems {
-            if let Some(diag) = can_problem.into_lsp_diagnostic(&fmt) {
+            let group = self.problem_groups.of_can_problem(module_id, &can_problem);
+
+            if let Some(mut diag) = can_problem.into_lsp_diagnostic(&fmt) {
+                if let Some(group) = group {
+                    add_problem_group(&mut diag, group, locate);
+                }
+
                 all_problems.push(diag);
             }
         }
 
         for type_problem in type_problems {
-            if let Some(diag) = type_problem.into_lsp_diagnostic(&fmt) {
+            let group = self
+                .problem_groups
+                .of_type_problem(module_id, &type_problem);
+
+            if let Some(mut diag) = type_problem.into_lsp_diagnostic(&fmt) {
+                if let Some(group) = group {
+                    add_problem_group(&mut diag, group, locate);
+                }
+
                 all_problems.push(diag);
             }
         }
@@ -1,7 +1,8 @@
 use log::{debug, info};
 
 use roc_cli::{annotation_edit, annotation_edits};
-use roc_fmt::MigrationFlags;
+use roc_fmt::{DbgMigration, MigrationFlags};
+use roc_load::extract::{extract_function, ExtractError};
 use std::collections::HashMap;
 
 use bumpalo::Bump;
@@ -105,8 +106,10 @@ impl DocInfo {
         let flags = MigrationFlags {
             snakify: false,
             parens_and_commas: false,
+            dbg: DbgMigration::Keep,
+            transforms: &[],
         };
-        let fmt = ast.fmt(flags);
+        let fmt = ast.fmt(flags)?;
 
         if source == fmt.as_str() {
             None
@@ -378,6 +381,69 @@ impl AnalyzedDocument {
         })
     }
 
+    /// Move the selected expression into a new top-level function, named `extracted` unless
+    /// that's taken.
+    pub fn extract_function(&self, range: Range) -> Option<CodeAction> {
+        let AnalyzedModule {
+            module_id,
+            interns,
+            subs,
+            declarations,
+            ..
+        } = self.module()?;
+
+        let region = range.to_region(self.line_info());
+
+        if region.is_empty() {
+            return None;
+        }
+
+        let mut count = 1;
+        let mut suffix = "";
+
+        let edits = loop {
+            let name = match count {
+                1 => format!("extracted{suffix}"),
+                _ => format!("extracted{count}{suffix}"),
+            };
+
+            match extract_function(
+                &self.doc_info.source,
+                declarations,
+                subs,
+                *module_id,
+                interns,
+                region,
+                &name,
+            ) {
+                Ok(edits) => break edits,
+                Err(ExtractError::InvalidName {
+                    effectful: true, ..
+                }) if suffix.is_empty() => suffix = "!",
+                Err(ExtractError::NameTaken { .. }) if count < 100 => count += 1,
+                Err(_) => return None,
+            }
+        };
+
+        let edits = edits
+            .into_iter()
+            .map(|edit| TextEdit {
+                range: edit.region.to_range(self.line_info()),
+                new_text: edit.replacement,
+            })
+            .collect();
+
+        Some(CodeAction {
+            title: "Extract function".to_owned(),
+            edit: Some(WorkspaceEdit::new(HashMap::from([(
+                self.url().clone(),
+                edits,
+            )]))),
+            kind: Some(CodeActionKind::REFACTOR_EXTRACT),
+            ..Default::default()
+        })
+    }
+
     fn annotate_declaration(&self, decl: FoundDeclaration) -> Option<CodeAction> {
         let AnalyzedModule {
             module_id,
@@ -15,6 +15,7 @@ mod format;
 
 pub struct Ast<'a> {
     arena: &'a Bump,
+    src: &'a str,
     module: SpacesBefore<'a, Header<'a>>,
     defs: Defs<'a>,
 }
@@ -37,11 +38,14 @@ impl<'a> Ast<'a> {
             },
             defs,
             arena,
+            src,
         })
     }
 
-    pub fn fmt(&self, flags: MigrationFlags) -> FormattedAst<'a> {
+    /// Format the module, or `None` if it's nested too deeply to format all of it.
+    pub fn fmt(&self, flags: MigrationFlags) -> Option<FormattedAst<'a>> {
         let mut buf = Buf::new_in(self.arena, flags);
+        buf.set_source(self.src);
 
         roc_fmt::header::fmt_header(&mut buf, &self.module);
 
@@ -49,7 +53,11 @@ impl<'a> Ast<'a> {
 
         buf.fmt_end_of_file();
 
-        FormattedAst::new(buf)
+        if buf.nesting_too_deep() {
+            return None;
+        }
+
+        Some(FormattedAst::new(buf))
     }
 
     pub fn semantic_tokens(&self) -> impl IntoIterator<Item = Loc<Token>> + '_ {
@@ -1,4 +1,4 @@
-use roc_region::all::{LineColumn, LineInfo, Loc};
+use roc_region::all::{ColumnUnit, LineColumn, LineInfo, Loc};
 use tower_lsp::lsp_types::SemanticToken;
 
 use super::tokens::Token;
@@ -22,9 +22,15 @@ pub fn arrange_semantic_tokens(
         value: token,
     } in tokens
     {
-        let length = region.len();
+        let LineColumn { line, column } =
+            line_info.convert_pos_in(region.start(), ColumnUnit::Utf16);
 
-        let LineColumn { line, column } = line_info.convert_pos(region.start());
+        let end = line_info.convert_pos_in(region.end(), ColumnUnit::Utf16);
+        let length = if end.line == line {
+            end.column - column
+        } else {
+            region.len()
+        };
 
         let delta_line = line - last_line;
         let delta_start = if delta_line == 0 {
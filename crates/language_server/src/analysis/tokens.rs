@@ -10,9 +10,9 @@ use roc_parse::{
         StrLiteral, Tag, TypeAnnotation, TypeDef, TypeHeader, ValueDef, WhenBranch,
     },
     header::{
-        AppHeader, ExposedName, HostedHeader, ImportsEntry, ModuleHeader, ModuleName, ModuleParams,
-        PackageEntry, PackageHeader, PackageName, PlatformHeader, PlatformRequires, ProvidesTo, To,
-        TypedIdent,
+        AppHeader, ExposedName, HostedHeader, ImportsEntry, ManifestField, ManifestValue,
+        ModuleHeader, ModuleName, ModuleParams, PackageEntry, PackageHeader, PackageName,
+        PlatformHeader, PlatformRequires, ProvidesTo, To, TypedIdent,
     },
     ident::{Accessor, UppercaseIdent},
 };
@@ -266,10 +266,16 @@ impl IterTokens for PackageHeader<'_> {
             exposes,
             before_packages: _,
             packages,
+            manifest,
         } = self;
 
         (exposes.iter_tokens(arena).into_iter())
             .chain(packages.value.iter_tokens(arena))
+            .chain(
+                manifest
+                    .iter()
+                    .flat_map(|manifest| manifest.item.value.iter_tokens(arena)),
+            )
             .collect_in(arena)
     }
 }
@@ -333,6 +339,28 @@ impl IterTokens for Loc<Spaced<'_, PackageEntry<'_>>> {
     }
 }
 
+impl IterTokens for Loc<Spaced<'_, ManifestField<'_>>> {
+    fn iter_tokens<'a>(&self, arena: &'a Bump) -> BumpVec<'a, Loc<Token>> {
+        let ManifestField {
+            name,
+            spaces_after_name: _,
+            value,
+        } = self.value.item();
+
+        let value = match value.value {
+            ManifestValue::Str(_) => onetoken(Token::String, value.region, arena),
+            ManifestValue::List(items) => items
+                .iter()
+                .map(|item| Loc::at(item.region, Token::String))
+                .collect_in(arena),
+        };
+
+        (onetoken(Token::Field, name.region, arena).into_iter())
+            .chain(value)
+            .collect_in(arena)
+    }
+}
+
 impl IterTokens for Loc<Spaced<'_, TypedIdent<'_>>> {
     fn iter_tokens<'a>(&self, arena: &'a Bump) -> BumpVec<'a, Loc<Token>> {
         self.value.item().iter_tokens(arena)
@@ -666,6 +694,7 @@ impl IterTokens for Loc<Expr<'_>> {
             Expr::Var { .. } => onetoken(Token::Variable, region, arena),
             Expr::Underscore(_) => onetoken(Token::Variable, region, arena),
             Expr::Crash => onetoken(Token::Keyword, region, arena),
+            Expr::TypedHole(_) => onetoken(Token::Variable, region, arena),
             Expr::Tag(_) => onetoken(Token::Tag, region, arena),
             Expr::OpaqueRef(_) => onetoken(Token::Type, region, arena),
             Expr::Closure(patterns, body) => (patterns.iter_tokens(arena).into_iter())
@@ -1,4 +1,4 @@
-use roc_region::all::{LineColumn, LineColumnRegion, LineInfo, Region};
+use roc_region::all::{ColumnUnit, LineColumn, LineColumnRegion, LineInfo, Region};
 use tower_lsp::lsp_types::{Position, Range};
 
 pub(crate) trait ToRange {
@@ -11,7 +11,7 @@ impl ToRange for Region {
     type Feed = LineInfo;
 
     fn to_range(&self, line_info: &LineInfo) -> Range {
-        let LineColumnRegion { start, end } = line_info.convert_region(*self);
+        let LineColumnRegion { start, end } = line_info.convert_region_in(*self, ColumnUnit::Utf16);
         Range {
             start: Position {
                 line: start.line,
@@ -46,7 +46,7 @@ impl ToRegion for Range {
             },
         };
 
-        line_info.convert_line_column_region(lc_region)
+        line_info.convert_line_column_region_in(lc_region, ColumnUnit::Utf16)
     }
 }
 
@@ -64,7 +64,7 @@ impl ToRocPosition for tower_lsp::lsp_types::Position {
             line: self.line,
             column: self.character,
         };
-        line_info.convert_line_column(lc)
+        line_info.convert_line_column_in(lc, ColumnUnit::Utf16)
     }
 }
 
@@ -75,9 +75,14 @@ pub(crate) mod diag {
     use roc_region::all::{LineInfo, Region};
     use roc_solve_problem::TypeError;
 
+    use roc_module::symbol::ModuleId;
     use roc_problem::Severity;
+    use roc_reporting::error::diff::TypeDiff;
+    use roc_reporting::group::ProblemGroup;
     use roc_reporting::report::RocDocAllocator;
-    use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity};
+    use tower_lsp::lsp_types::{
+        Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, Location, NumberOrString,
+    };
 
     use super::ToRange;
 
@@ -163,6 +168,9 @@ pub(crate) mod diag {
                 LoadingProblem::UnrecognizedPackageShorthand { shorthand, .. } => {
                     format!("Unrecognized package shorthand: {}", shorthand)
                 }
+                LoadingProblem::InvalidManifest { problem, .. } => {
+                    format!("Invalid package manifest: {}", problem)
+                }
             };
 
             Some(Diagnostic {
@@ -179,6 +187,30 @@ pub(crate) mod diag {
         }
     }
 
+    /// Point the diagnostic of a primary problem at the problems that were folded into it, see
+    /// [roc_reporting::group].
+    pub fn add_problem_group(
+        diagnostic: &mut Diagnostic,
+        group: &ProblemGroup,
+        locate: impl Fn(ModuleId, Region) -> Option<Location>,
+    ) {
+        diagnostic.message.push_str(&format!(
+            "\n\nThere are {} more problems like this one, which probably have the same cause.",
+            group.folded
+        ));
+        diagnostic.related_information = Some(
+            group
+                .locations
+                .iter()
+                .filter_map(|(module_id, region)| locate(*module_id, *region))
+                .map(|location| DiagnosticRelatedInformation {
+                    location,
+                    message: "A problem like this one".to_string(),
+                })
+                .collect(),
+        );
+    }
+
     pub struct ProblemFmt<'a> {
         pub alloc: &'a RocDocAllocator<'a>,
         pub line_info: &'a LineInfo,
@@ -205,13 +237,16 @@ pub(crate) mod diag {
             );
 
             let severity = report.severity.into_lsp_severity();
+            let code = report
+                .explain_code()
+                .map(|code| NumberOrString::String(code.to_owned()));
             let mut msg = String::new();
             report.render_language_server(&mut msg, fmt.alloc);
 
             Some(Diagnostic {
                 range,
                 severity: Some(severity),
-                code: None,
+                code,
                 code_description: None,
                 source: None,
                 message: msg,
@@ -234,6 +269,8 @@ pub(crate) mod diag {
                 ))
                 .to_range(fmt.line_info);
 
+            let diff = TypeDiff::of_problem(&self);
+
             let report = roc_reporting::report::type_problem(
                 fmt.alloc,
                 fmt.line_info,
@@ -242,6 +279,9 @@ pub(crate) mod diag {
             )?;
 
             let severity = report.severity.into_lsp_severity();
+            let code = report
+                .explain_code()
+                .map(|code| NumberOrString::String(code.to_owned()));
 
             let mut msg = String::new();
             report.render_language_server(&mut msg, fmt.alloc);
@@ -249,13 +289,13 @@ pub(crate) mod diag {
             Some(Diagnostic {
                 range,
                 severity: Some(severity),
-                code: None,
+                code,
                 code_description: None,
                 source: None,
                 message: msg,
                 related_information: None,
                 tags: None,
-                data: None,
+                data: diff.map(|diff| diff.to_json(fmt.alloc)),
             })
         }
     }
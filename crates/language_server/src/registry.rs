@@ -14,6 +14,7 @@ use tower_lsp::lsp_types::{
 };
 
 use crate::analysis::{AnalyzedDocument, DocInfo};
+use crate::stats::Stats;
 
 #[derive(Debug)]
 pub(crate) struct DocumentPair {
@@ -52,6 +53,7 @@ impl Default for RegistryConfig {
 pub(crate) struct Registry {
     documents: Mutex<HashMap<Url, DocumentPair>>,
     config: RegistryConfig,
+    pub(crate) stats: Arc<Stats>,
 }
 
 impl Registry {
@@ -59,9 +61,16 @@ impl Registry {
         Self {
             documents: Default::default(),
             config,
+            stats: Default::default(),
         }
     }
 
+    /// The stats of the server, in the OpenMetrics text format.
+    pub async fn stats(&self) -> String {
+        let documents_tracked = self.documents.lock().await.len();
+        self.stats.render(documents_tracked)
+    }
+
     pub async fn get_latest_version(&self, url: &Url) -> Option<i32> {
         self.documents.lock().await.get(url).map(|x| x.info.version)
     }
@@ -147,7 +156,7 @@ impl Registry {
     ///Tries to get the latest document from analysis.
     ///Gives up and returns none after 5 seconds.
     async fn latest_document_by_url(&self, url: &Url) -> Option<Arc<AnalyzedDocument>> {
-        tokio::time::timeout(self.config.latest_document_timeout, async {
+        let latest = tokio::time::timeout(self.config.latest_document_timeout, async {
             //TODO: This should really be a condvar that is triggered by the latest being ready, this will do for now though
             loop {
                 let docs = self.documents.lock().await;
@@ -160,8 +169,13 @@ impl Registry {
                 tokio::task::yield_now().await;
             }
         })
-        .await
-        .ok()
+        .await;
+
+        if latest.is_err() {
+            self.stats.record_latest_document_timeout();
+        }
+
+        latest.ok()
     }
 
     pub async fn diagnostics(&self, url: &Url) -> Vec<Diagnostic> {
@@ -225,6 +239,9 @@ impl Registry {
         if let Some(edit) = document.annotate(range) {
             responses.push(CodeActionOrCommand::CodeAction(edit));
         }
+        if let Some(extract) = document.extract_function(range) {
+            responses.push(CodeActionOrCommand::CodeAction(extract));
+        }
         Some(responses)
     }
 }
@@ -11,10 +11,12 @@ use tower_lsp::lsp_types::*;
 use tower_lsp::{Client, LanguageServer, LspService, Server};
 
 use crate::analysis::{global_analysis, DocInfo};
+use crate::stats::DUMP_STATS_COMMAND;
 
 mod analysis;
 mod convert;
 mod registry;
+mod stats;
 
 struct RocServer {
     pub state: RocServerState,
@@ -104,6 +106,12 @@ impl RocServer {
             },
         };
         let code_action_provider = CodeActionProviderCapability::Simple(true);
+        let execute_command_provider = ExecuteCommandOptions {
+            commands: vec![DUMP_STATS_COMMAND.to_string()],
+            work_done_progress_options: WorkDoneProgressOptions {
+                work_done_progress: None,
+            },
+        };
         ServerCapabilities {
             text_document_sync: Some(text_document_sync),
             hover_provider: Some(hover_provider),
@@ -112,6 +120,7 @@ impl RocServer {
             semantic_tokens_provider: Some(semantic_tokens_provider),
             completion_provider: Some(completion_provider),
             code_action_provider: Some(code_action_provider),
+            execute_command_provider: Some(execute_command_provider),
             ..ServerCapabilities::default()
         }
     }
@@ -143,6 +152,18 @@ impl RocServerState {
 
     async fn close(&self, _fi: Url) {}
 
+    pub async fn execute_command(&self, command: &str) -> Result<Option<serde_json::Value>> {
+        match command {
+            DUMP_STATS_COMMAND => {
+                let stats = self.registry.stats().await;
+                Ok(Some(serde_json::Value::String(stats)))
+            }
+            command => Err(jsonrpc::Error::invalid_params(format!(
+                "Unknown command: {command}"
+            ))),
+        }
+    }
+
     pub async fn change(
         &self,
         fi: &Url,
@@ -156,6 +177,8 @@ impl RocServerState {
             .apply_doc_info_changes(fi.clone(), doc_info.clone())
             .await;
 
+        self.registry.stats.record_change();
+
         debug!(
             "V{:?}:finished updating docinfo, starting analysis ",
             version
@@ -175,9 +198,12 @@ impl RocServerState {
                 return Err("Not latest version skipping analysis".to_string());
             }
 
+            let stats = inner_ref.registry.stats.clone();
             let results = match tokio::time::timeout(
                 Duration::from_secs(60),
-                tokio::task::spawn_blocking(|| catch_unwind(|| global_analysis(doc_info))),
+                tokio::task::spawn_blocking(move || {
+                    catch_unwind(|| global_analysis(doc_info, &stats))
+                }),
             )
             .await
             {
@@ -215,6 +241,11 @@ impl RocServerState {
             Ok(())
         }
         .await;
+
+        if updating_result.is_err() {
+            self.registry.stats.record_discarded_analysis();
+        }
+
         debug!("V{:?}:finished document change process", version);
         updating_result
     }
@@ -352,6 +383,13 @@ impl LanguageServer for RocServer {
 
         unwind_async(self.state.registry.code_actions(&text_document.uri, range)).await
     }
+
+    async fn execute_command(
+        &self,
+        params: ExecuteCommandParams,
+    ) -> Result<Option<serde_json::Value>> {
+        self.state.execute_command(&params.command).await
+    }
 }
 
 async fn unwind_async<Fut, T>(future: Fut) -> tower_lsp::jsonrpc::Result<T>
@@ -801,4 +839,43 @@ mod tests {
         "#]]
         .assert_debug_eq(&edit);
     }
+
+    /// The value of the sample with this name in a stats dump
+    fn stat(stats: &str, sample: &str) -> f64 {
+        stats
+            .lines()
+            .find_map(|line| line.strip_prefix(sample)?.strip_prefix(' '))
+            .unwrap_or_else(|| panic!("No {sample} in the stats:\n{stats}"))
+            .parse()
+            .unwrap()
+    }
+
+    /// Test that the dump stats command counts the analyses for opening and then changing a
+    /// document
+    #[tokio::test]
+    async fn test_dump_stats_after_open_and_change() {
+        let doc = DOC_LIT.to_string() + "main = 1\n";
+        let (inner, url) = test_setup(doc.clone()).await;
+
+        inner.change(&url, doc + "other = 2\n", 1).await.unwrap();
+
+        let Some(serde_json::Value::String(stats)) =
+            inner.execute_command(DUMP_STATS_COMMAND).await.unwrap()
+        else {
+            panic!("The stats should be dumped as a string");
+        };
+        info!("Stats are:\n{0}", stats);
+
+        assert_eq!(stat(&stats, "rocls_changes_total"), 2.0);
+        assert_eq!(stat(&stats, "rocls_analyses_discarded_total"), 0.0);
+        assert_eq!(stat(&stats, "rocls_analysis_seconds_count"), 2.0);
+        assert!(stat(&stats, "rocls_analysis_seconds_sum") > 0.0);
+        assert!(stat(&stats, "rocls_documents_tracked") >= 1.0);
+        // Each analysis loads Test.roc, and whatever it imports
+        assert!(stat(&stats, "rocls_modules_analysed_total") >= 2.0);
+        assert!(stat(&stats, "rocls_phase_seconds_total{phase=\"parse\"}") > 0.0);
+        assert_eq!(stat(&stats, "rocls_latest_document_timeouts_total"), 0.0);
+
+        assert!(inner.execute_command("roc.unknown").await.is_err());
+    }
 }
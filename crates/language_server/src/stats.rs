@@ -0,0 +1,173 @@
+//! Counters and timings that explain how the language server spends its time, for diagnosing a
+//! slow editor experience.
+//!
+//! They are dumped in the [OpenMetrics](https://openmetrics.io) text format with the
+//! [DUMP_STATS_COMMAND] command, so they can be read as they are or fed to any tool that scrapes
+//! Prometheus metrics.
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use roc_load::LoadedModule;
+
+/// The `workspace/executeCommand` command that returns the stats.
+pub(crate) const DUMP_STATS_COMMAND: &str = "roc.dumpStats";
+
+/// The compiler phases whose time is summed over all the modules of every analysis.
+const PHASES: [&str; 4] = ["parse", "canonicalize", "constrain", "solve"];
+
+#[derive(Debug, Default)]
+pub(crate) struct Stats {
+    /// Changes to a document, each of which starts an analysis
+    changes: AtomicU64,
+    /// Analyses thrown away because a newer change came in, or because they failed
+    analyses_discarded: AtomicU64,
+    analyses: Timing,
+    /// Requests that gave up waiting for the latest analysis of a document
+    latest_document_timeouts: AtomicU64,
+    modules_analysed: AtomicU64,
+    phases: [Timing; PHASES.len()],
+}
+
+/// How often something took place, and how long it took in total.
+#[derive(Debug, Default)]
+struct Timing {
+    count: AtomicU64,
+    nanos: AtomicU64,
+}
+
+impl Timing {
+    fn record(&self, duration: Duration) {
+        let nanos = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.nanos.fetch_add(nanos, Ordering::Relaxed);
+    }
+
+    fn seconds(&self) -> f64 {
+        self.nanos.load(Ordering::Relaxed) as f64 / 1e9
+    }
+}
+
+impl Stats {
+    pub(crate) fn record_change(&self) {
+        self.changes.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_discarded_analysis(&self) {
+        self.analyses_discarded.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_latest_document_timeout(&self) {
+        self.latest_document_timeouts
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record an analysis that loaded `loaded`, or failed to load anything when it's `None`.
+    pub(crate) fn record_analysis(&self, duration: Duration, loaded: Option<&LoadedModule>) {
+        self.analyses.record(duration);
+
+        let Some(loaded) = loaded else {
+            return;
+        };
+
+        for timing in loaded.timings.values() {
+            let phases = [
+                timing.read_roc_file + timing.parse_header + timing.parse_body,
+                timing.canonicalize_solo + timing.canonicalize,
+                timing.constrain,
+                timing.solve,
+            ];
+
+            for (phase, duration) in self.phases.iter().zip(phases) {
+                phase.record(duration);
+            }
+        }
+
+        self.modules_analysed
+            .fetch_add(loaded.timings.len() as u64, Ordering::Relaxed);
+    }
+
+    /// The stats in the OpenMetrics text format.
+    pub(crate) fn render(&self, documents_tracked: usize) -> String {
+        let count = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+
+        let mut buf = String::new();
+
+        metric(
+            &mut buf,
+            "rocls_documents_tracked",
+            "gauge",
+            "Documents the server has an analysis for, including the modules they import.",
+            &[("", documents_tracked.to_string())],
+        );
+        metric(
+            &mut buf,
+            "rocls_changes",
+            "counter",
+            "Document changes, each of which starts an analysis.",
+            &[("_total", count(&self.changes).to_string())],
+        );
+        metric(
+            &mut buf,
+            "rocls_analyses_discarded",
+            "counter",
+            "Analyses thrown away because a newer change came in, or because they failed.",
+            &[("_total", count(&self.analyses_discarded).to_string())],
+        );
+        metric(
+            &mut buf,
+            "rocls_analysis_seconds",
+            "summary",
+            "The time it took to load and type check a document and the modules it imports.",
+            &[
+                ("_count", count(&self.analyses.count).to_string()),
+                ("_sum", self.analyses.seconds().to_string()),
+            ],
+        );
+        metric(
+            &mut buf,
+            "rocls_modules_analysed",
+            "counter",
+            "Modules loaded by all the analyses.",
+            &[("_total", count(&self.modules_analysed).to_string())],
+        );
+
+        let phases: Vec<_> = PHASES
+            .iter()
+            .zip(self.phases.iter())
+            .map(|(name, timing)| (format!("_total{{phase=\"{name}\"}}"), timing.seconds()))
+            .collect();
+
+        metric(
+            &mut buf,
+            "rocls_phase_seconds",
+            "counter",
+            "The time spent in each phase of the compiler, summed over all the modules analysed.",
+            &phases
+                .iter()
+                .map(|(suffix, seconds)| (suffix.as_str(), seconds.to_string()))
+                .collect::<Vec<_>>(),
+        );
+        metric(
+            &mut buf,
+            "rocls_latest_document_timeouts",
+            "counter",
+            "Requests that gave up waiting for the latest analysis of a document.",
+            &[("_total", count(&self.latest_document_timeouts).to_string())],
+        );
+
+        buf.push_str("# EOF\n");
+
+        buf
+    }
+}
+
+fn metric(buf: &mut String, name: &str, kind: &str, help: &str, samples: &[(&str, String)]) {
+    writeln!(buf, "# TYPE {name} {kind}").unwrap();
+    writeln!(buf, "# HELP {name} {help}").unwrap();
+
+    for (suffix, value) in samples {
+        writeln!(buf, "{name}{suffix} {value}").unwrap();
+    }
+}
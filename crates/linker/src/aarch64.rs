@@ -0,0 +1,200 @@
+//! Encoding and decoding the few AArch64 instructions that surgical linking has to patch.
+//!
+//! Unlike x86, AArch64 doesn't store addresses as plain little endian integers: branch offsets,
+//! `adrp` pages and page offsets are bit fields of fixed size instructions, and page offsets of
+//! loads and stores are scaled by the size of the access.
+
+/// The size of a `__stubs` entry: `adrp x16, page; ldr x16, [x16, offset]; br x16`.
+pub(crate) const STUB_SIZE: usize = 12;
+
+const NOP: u32 = 0xd503_201f;
+const B: u32 = 0x1400_0000;
+
+pub(crate) fn read_inst(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+pub(crate) fn write_inst(bytes: &mut [u8], offset: usize, inst: u32) {
+    bytes[offset..offset + 4].copy_from_slice(&inst.to_le_bytes());
+}
+
+fn sign_extend(value: u64, bits: u32) -> i64 {
+    ((value << (64 - bits)) as i64) >> (64 - bits)
+}
+
+/// Point the `b` or `bl` at `pc` to `target`. Branches reach ±128 MiB.
+pub(crate) fn patch_branch26(inst: u32, pc: u64, target: u64) -> Option<u32> {
+    let delta = target.wrapping_sub(pc) as i64;
+
+    if delta % 4 != 0 || !(-(1 << 27)..(1 << 27)).contains(&delta) {
+        return None;
+    }
+
+    Some((inst & 0xfc00_0000) | ((delta >> 2) as u32 & 0x03ff_ffff))
+}
+
+/// Point the `adrp` at `pc` to the 4 KiB page of `target`. Pages reach ±4 GiB.
+pub(crate) fn patch_adrp(inst: u32, pc: u64, target: u64) -> Option<u32> {
+    let pages = ((target >> 12) as i64).wrapping_sub((pc >> 12) as i64);
+
+    if !(-(1 << 20)..(1 << 20)).contains(&pages) {
+        return None;
+    }
+
+    let immlo = (pages as u32 & 0b11) << 29;
+    let immhi = ((pages >> 2) as u32 & 0x7_ffff) << 5;
+
+    Some((inst & 0x9f00_001f) | immlo | immhi)
+}
+
+/// Put the offset of `target` within its page into an `add`, or into a load or store with an
+/// unsigned offset, which counts in units of the access size.
+pub(crate) fn patch_pageoff12(inst: u32, target: u64) -> Option<u32> {
+    let low12 = (target & 0xfff) as u32;
+
+    let shift = if is_add_immediate(inst) {
+        0
+    } else if is_load_store_unsigned_offset(inst) {
+        let size = inst >> 30;
+        let is_128_bit = size == 0 && inst & 0x0480_0000 == 0x0480_0000;
+
+        if is_128_bit {
+            4
+        } else {
+            size
+        }
+    } else {
+        return None;
+    };
+
+    if low12 & ((1 << shift) - 1) != 0 {
+        return None;
+    }
+
+    Some((inst & !(0xfff << 10)) | ((low12 >> shift) << 10))
+}
+
+/// Turn `ldr xt, [xn, #offset]` of a GOT entry into `add xt, xn, #offset`, so that the code
+/// computes the address of the symbol itself. Used when a symbol turns out not to need a GOT entry.
+pub(crate) fn relax_got_load(inst: u32) -> Option<u32> {
+    is_ldr_x_unsigned_offset(inst).then_some(0x9100_0000 | (inst & 0x3ff))
+}
+
+/// The address of the GOT entry that the `__stubs` entry at `pc` jumps through.
+pub(crate) fn stub_got_address(stub: &[u8], pc: u64) -> Option<u64> {
+    let adrp = read_inst(stub, 0);
+    let ldr = read_inst(stub, 4);
+
+    if adrp & 0x9f00_0000 != 0x9000_0000 || !is_ldr_x_unsigned_offset(ldr) {
+        return None;
+    }
+
+    let immlo = ((adrp >> 29) & 0b11) as u64;
+    let immhi = ((adrp >> 5) & 0x7_ffff) as u64;
+    let pages = sign_extend((immhi << 2) | immlo, 21);
+    let page = (pc & !0xfff).wrapping_add((pages << 12) as u64);
+    let offset = ((ldr >> 10) & 0xfff) as u64 * 8;
+
+    Some(page + offset)
+}
+
+/// A `__stubs` entry at `pc` that branches straight to `target` instead of through the GOT.
+pub(crate) fn direct_stub(pc: u64, target: u64) -> Option<[u8; STUB_SIZE]> {
+    let mut stub = [0; STUB_SIZE];
+
+    write_inst(&mut stub, 0, patch_branch26(B, pc, target)?);
+    write_inst(&mut stub, 4, NOP);
+    write_inst(&mut stub, 8, NOP);
+
+    Some(stub)
+}
+
+fn is_add_immediate(inst: u32) -> bool {
+    inst & 0x7f80_0000 == 0x1100_0000
+}
+
+fn is_load_store_unsigned_offset(inst: u32) -> bool {
+    inst & 0x3b00_0000 == 0x3900_0000
+}
+
+fn is_ldr_x_unsigned_offset(inst: u32) -> bool {
+    inst & 0xffc0_0000 == 0xf940_0000
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // bl #0
+    const BL: u32 = 0x9400_0000;
+    // adrp x16, #0
+    const ADRP_X16: u32 = 0x9000_0010;
+    // ldr x16, [x16]
+    const LDR_X16: u32 = 0xf940_0210;
+    // add x0, x1, #0
+    const ADD_X0_X1: u32 = 0x9100_0020;
+    // ldr w0, [x1]
+    const LDR_W0_X1: u32 = 0xb940_0020;
+    // ldr q0, [x1]
+    const LDR_Q0_X1: u32 = 0x3dc0_0020;
+
+    #[test]
+    fn branches() {
+        assert_eq!(patch_branch26(BL, 0x1000, 0x2000), Some(0x9400_0400));
+        // backwards
+        assert_eq!(patch_branch26(BL, 0x2000, 0x1ffc), Some(0x97ff_ffff));
+        assert_eq!(patch_branch26(BL, 0, 1 << 27), None);
+        assert_eq!(patch_branch26(BL, 0, 2), None);
+    }
+
+    #[test]
+    fn pages() {
+        assert_eq!(patch_adrp(ADRP_X16, 0x1_0ffc, 0x1_1000), Some(0xb000_0010));
+        assert_eq!(patch_adrp(ADRP_X16, 0x1_1000, 0x1_0fff), Some(0xf0ff_fff0));
+        assert_eq!(patch_adrp(ADRP_X16, 0, 1 << 33), None);
+    }
+
+    #[test]
+    fn page_offsets() {
+        assert_eq!(patch_pageoff12(ADD_X0_X1, 0x5123), Some(0x9104_8c20));
+        assert_eq!(patch_pageoff12(LDR_W0_X1, 0x5124), Some(0xb941_2420));
+        assert_eq!(patch_pageoff12(LDR_X16, 0x5128), Some(0xf940_9610));
+        assert_eq!(patch_pageoff12(LDR_Q0_X1, 0x5120), Some(0x3dc0_4820));
+        // misaligned for the access size
+        assert_eq!(patch_pageoff12(LDR_W0_X1, 0x5122), None);
+        assert_eq!(patch_pageoff12(BL, 0x5122), None);
+    }
+
+    #[test]
+    fn got_relaxation() {
+        let ldr = patch_pageoff12(LDR_X16, 0x5128).unwrap();
+        let add = patch_pageoff12(relax_got_load(ldr).unwrap(), 0x5128).unwrap();
+
+        // add x16, x16, #0x128
+        assert_eq!(add, 0x9104_a210);
+        assert_eq!(relax_got_load(ADD_X0_X1), None);
+    }
+
+    #[test]
+    fn stubs() {
+        let mut stub = [0; STUB_SIZE];
+
+        write_inst(
+            &mut stub,
+            0,
+            patch_adrp(ADRP_X16, 0x1_0000, 0x2_4008).unwrap(),
+        );
+        write_inst(&mut stub, 4, patch_pageoff12(LDR_X16, 0x2_4008).unwrap());
+        // br x16
+        write_inst(&mut stub, 8, 0xd61f_0200);
+
+        assert_eq!(stub_got_address(&stub, 0x1_0000), Some(0x2_4008));
+        assert_eq!(stub_got_address(&[0; STUB_SIZE], 0x1_0000), None);
+
+        let direct = direct_stub(0x1_0000, 0x3_0000).unwrap();
+
+        assert_eq!(read_inst(&direct, 0), 0x1400_8000);
+        assert_eq!(read_inst(&direct, 4), NOP);
+        assert_eq!(stub_got_address(&direct, 0x1_0000), None);
+    }
+}
@@ -0,0 +1,474 @@
+//! Reading and patching the `LC_DYLD_CHAINED_FIXUPS` data of Mach-O executables.
+//!
+//! Since macOS 12, ld64 no longer describes rebases and binds with `LC_DYLD_INFO` opcodes.
+//! Instead, the pointers that need fixing up form chains through each page of the data segments:
+//! every pointer says whether dyld has to slide it (a rebase) or fill in the address of an
+//! imported symbol (a bind), and how far away the next pointer of the chain is. The data in
+//! `__LINKEDIT` just says where each chain starts and which symbols are imported.
+//!
+//! The host calls into the app through imports of the app's stub library, so surgery turns the
+//! binds of those imports into rebases to the app's functions.
+use object::macho;
+use roc_error_macros::internal_error;
+use serde::{Deserialize, Serialize};
+
+use crate::align_by_constraint;
+
+pub(crate) const DYLD_CHAINED_PTR_64: u16 = 2;
+pub(crate) const DYLD_CHAINED_PTR_64_OFFSET: u16 = 6;
+
+const DYLD_CHAINED_PTR_START_NONE: u16 = 0xffff;
+
+const DYLD_CHAINED_IMPORT: u32 = 1;
+const DYLD_CHAINED_IMPORT_ADDEND: u32 = 2;
+const DYLD_CHAINED_IMPORT_ADDEND64: u32 = 3;
+
+/// Both supported pointer formats count the distance to the next pointer in 4 byte steps.
+const STRIDE: u64 = 4;
+
+/// Where a segment is in memory and in the file, in load command order.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SegmentLayout {
+    pub vmaddr: u64,
+    pub fileoff: u64,
+}
+
+#[derive(Debug)]
+pub(crate) struct ChainedImport {
+    pub name: String,
+    /// The 1-based index of the dylib that defines it, or one of the `BIND_SPECIAL_DYLIB_*`s.
+    pub lib_ordinal: i32,
+    file_offset: usize,
+}
+
+/// A pointer that dyld fills in with the address of an import.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub(crate) struct ChainedBind {
+    pub file_offset: usize,
+    pub vmaddr: u64,
+    pub import: usize,
+    pub pointer_format: u16,
+}
+
+#[derive(Debug)]
+pub(crate) struct ChainedFixups {
+    imports_format: u32,
+    pub imports: Vec<ChainedImport>,
+    pub binds: Vec<ChainedBind>,
+}
+
+fn read_u16(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes(data[offset..offset + 2].try_into().unwrap())
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+fn write_u32(data: &mut [u8], offset: usize, value: u32) {
+    data[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+fn write_u64(data: &mut [u8], offset: usize, value: u64) {
+    data[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+}
+
+/// Read the chained fixups at `fixups_offset` in the file, and walk every chain to find the binds.
+pub(crate) fn parse(
+    data: &[u8],
+    fixups_offset: usize,
+    segments: &[SegmentLayout],
+) -> ChainedFixups {
+    let header = &data[fixups_offset..];
+    let fixups_version = read_u32(header, 0);
+    let starts_offset = read_u32(header, 4) as usize;
+    let imports_offset = read_u32(header, 8) as usize;
+    let symbols_offset = read_u32(header, 12) as usize;
+    let imports_count = read_u32(header, 16) as usize;
+    let imports_format = read_u32(header, 20);
+    let symbols_format = read_u32(header, 24);
+
+    if fixups_version != 0 || symbols_format != 0 {
+        internal_error!(
+            "Unsupported chained fixups: version {fixups_version}, symbols format {symbols_format}"
+        );
+    }
+
+    let import_size = match imports_format {
+        DYLD_CHAINED_IMPORT => 4,
+        DYLD_CHAINED_IMPORT_ADDEND => 8,
+        DYLD_CHAINED_IMPORT_ADDEND64 => 16,
+        other => internal_error!("Unsupported chained fixups imports format: {other}"),
+    };
+
+    let imports = (0..imports_count)
+        .map(|index| {
+            let file_offset = fixups_offset + imports_offset + index * import_size;
+            let (lib_ordinal, name_offset) = if imports_format == DYLD_CHAINED_IMPORT_ADDEND64 {
+                let raw = read_u64(data, file_offset);
+
+                (
+                    special_ordinal((raw & 0xffff) as u32, 16),
+                    (raw >> 32) as usize,
+                )
+            } else {
+                let raw = read_u32(data, file_offset);
+
+                (special_ordinal(raw & 0xff, 8), (raw >> 9) as usize)
+            };
+
+            let name_start = fixups_offset + symbols_offset + name_offset;
+            let name_len = data[name_start..]
+                .iter()
+                .position(|byte| *byte == 0)
+                .unwrap_or_else(|| internal_error!("Unterminated chained fixups import name"));
+            let name = String::from_utf8_lossy(&data[name_start..name_start + name_len]);
+
+            ChainedImport {
+                name: name.into_owned(),
+                lib_ordinal,
+                file_offset,
+            }
+        })
+        .collect();
+
+    let starts = fixups_offset + starts_offset;
+    let seg_count = read_u32(data, starts) as usize;
+    let mut binds = Vec::new();
+
+    for (index, segment) in segments.iter().enumerate().take(seg_count) {
+        let seg_info_offset = read_u32(data, starts + 4 + index * 4) as usize;
+
+        if seg_info_offset == 0 {
+            continue;
+        }
+
+        let seg_info = starts + seg_info_offset;
+        let page_size = read_u16(data, seg_info + 4) as u64;
+        let pointer_format = read_u16(data, seg_info + 6);
+        let page_count = read_u16(data, seg_info + 20) as usize;
+
+        if !matches!(
+            pointer_format,
+            DYLD_CHAINED_PTR_64 | DYLD_CHAINED_PTR_64_OFFSET
+        ) {
+            internal_error!("Unsupported chained fixups pointer format: {pointer_format}");
+        }
+
+        for page in 0..page_count {
+            let page_start = read_u16(data, seg_info + 22 + page * 2);
+
+            if page_start == DYLD_CHAINED_PTR_START_NONE {
+                continue;
+            }
+
+            let mut offset_in_segment = page as u64 * page_size + page_start as u64;
+
+            loop {
+                let file_offset = (segment.fileoff + offset_in_segment) as usize;
+                let pointer = read_u64(data, file_offset);
+
+                if pointer >> 63 == 1 {
+                    binds.push(ChainedBind {
+                        file_offset,
+                        vmaddr: segment.vmaddr + offset_in_segment,
+                        import: (pointer & 0xff_ffff) as usize,
+                        pointer_format,
+                    });
+                }
+
+                match (pointer >> 51) & 0xfff {
+                    0 => break,
+                    next => offset_in_segment += next * STRIDE,
+                }
+            }
+        }
+    }
+
+    ChainedFixups {
+        imports_format,
+        imports,
+        binds,
+    }
+}
+
+/// Ordinals are stored unsigned, with the special (negative) ones counting down from the max.
+fn special_ordinal(raw: u32, bits: u32) -> i32 {
+    let max = (1 << bits) - 1;
+
+    if raw > max - 16 {
+        raw as i32 - max as i32 - 1
+    } else {
+        raw as i32
+    }
+}
+
+impl ChainedFixups {
+    /// Change which dylib an import is looked up in. A weak import that isn't found resolves to
+    /// null instead of stopping the program from launching.
+    fn set_import_ordinal(&self, data: &mut [u8], import: usize, lib_ordinal: i32, weak: bool) {
+        let file_offset = self.imports[import].file_offset;
+
+        if self.imports_format == DYLD_CHAINED_IMPORT_ADDEND64 {
+            let raw = read_u64(data, file_offset) & !0x1_ffff;
+            let ordinal = (lib_ordinal as u64) & 0xffff;
+
+            write_u64(data, file_offset, raw | ordinal | ((weak as u64) << 16));
+        } else {
+            let raw = read_u32(data, file_offset) & !0x1ff;
+            let ordinal = (lib_ordinal as u32) & 0xff;
+
+            write_u32(data, file_offset, raw | ordinal | ((weak as u32) << 8));
+        }
+    }
+
+    /// Stop looking up the given imports in the dylib with `ordinal`, which isn't loaded anymore,
+    /// and move the imports of later dylibs up one ordinal to fill its place.
+    pub(crate) fn unlink_dylib(&self, data: &mut [u8], ordinal: i32, app_imports: &[usize]) {
+        for (index, import) in self.imports.iter().enumerate() {
+            if app_imports.contains(&index) {
+                self.set_import_ordinal(
+                    data,
+                    index,
+                    macho::BIND_SPECIAL_DYLIB_FLAT_LOOKUP as i32,
+                    true,
+                );
+            } else if import.lib_ordinal > ordinal {
+                let weak = self.is_weak(data, index);
+
+                self.set_import_ordinal(data, index, import.lib_ordinal - 1, weak);
+            }
+        }
+    }
+
+    fn is_weak(&self, data: &[u8], import: usize) -> bool {
+        let file_offset = self.imports[import].file_offset;
+
+        if self.imports_format == DYLD_CHAINED_IMPORT_ADDEND64 {
+            read_u64(data, file_offset) & (1 << 16) != 0
+        } else {
+            read_u32(data, file_offset) & (1 << 8) != 0
+        }
+    }
+}
+
+/// Turn a bind into a rebase to `target`, an address in the unslid image, keeping its place in
+/// the chain. `image_base` is the address of the `__TEXT` segment.
+pub(crate) fn rebase_bind(data: &mut [u8], bind: &ChainedBind, target: u64, image_base: u64) {
+    let next = (read_u64(data, bind.file_offset) >> 51) & 0xfff;
+    let target = match bind.pointer_format {
+        DYLD_CHAINED_PTR_64 => target,
+        DYLD_CHAINED_PTR_64_OFFSET => target - image_base,
+        other => internal_error!("Unsupported chained fixups pointer format: {other}"),
+    };
+
+    if target >> 36 != 0 {
+        internal_error!("Chained fixup rebase target out of range: {target:#x}");
+    }
+
+    write_u64(data, bind.file_offset, target | (next << 51));
+}
+
+/// Add `count` segments without fixups at segment index `at` to the fixups data, which has to
+/// describe every segment of the image. Returns the grown data, padded to 8 bytes.
+pub(crate) fn insert_segments(fixups: &[u8], at: usize, count: usize) -> Vec<u8> {
+    let starts = read_u32(fixups, 4) as usize;
+    let seg_count = read_u32(fixups, starts) as usize;
+    let offsets_end = starts + 4 + seg_count * 4;
+
+    // Keep the `dyld_chained_starts_in_segment`s after the offsets 8 byte aligned.
+    let growth = align_by_constraint(count * 4, 8);
+    let shift = |offset: u32| {
+        if offset == 0 {
+            0
+        } else {
+            offset + growth as u32
+        }
+    };
+
+    let mut out = Vec::with_capacity(fixups.len() + growth + 8);
+
+    out.extend_from_slice(&fixups[..starts]);
+
+    for field in [8, 12] {
+        let offset = read_u32(fixups, field) as usize;
+
+        if offset > starts {
+            write_u32(&mut out, field, (offset + growth) as u32);
+        }
+    }
+
+    out.extend_from_slice(&((seg_count + count) as u32).to_le_bytes());
+
+    for index in 0..seg_count {
+        if index == at {
+            out.resize(out.len() + count * 4, 0);
+        }
+
+        let offset = read_u32(fixups, starts + 4 + index * 4);
+
+        out.extend_from_slice(&shift(offset).to_le_bytes());
+    }
+
+    if at >= seg_count {
+        out.resize(out.len() + count * 4, 0);
+    }
+
+    out.resize(offsets_end + growth, 0);
+    out.extend_from_slice(&fixups[offsets_end..]);
+    out.resize(align_by_constraint(out.len(), 8), 0);
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fixups for two segments: `__TEXT` without fixups, and a one page `__DATA_CONST` at file
+    /// offset 0x100 with a chain of a bind to `_a`, a rebase and a bind to `_b`.
+    fn example() -> (Vec<u8>, Vec<SegmentLayout>) {
+        let mut file = vec![0u8; 0x200];
+
+        // chain: bind `_a` -> +8 -> rebase -> +16 -> bind `_b`
+        write_u64(&mut file, 0x100, 1 << 63 | 2 << 51);
+        write_u64(&mut file, 0x108, 0x4000 | 4 << 51);
+        write_u64(&mut file, 0x118, 1 << 63 | 1);
+
+        let fixups = 0x140;
+        let mut blob = vec![0u8; 0];
+        let push32 = |blob: &mut Vec<u8>, value: u32| blob.extend_from_slice(&value.to_le_bytes());
+
+        // header
+        for value in [0, 28, 0, 0, 2, DYLD_CHAINED_IMPORT, 0] {
+            push32(&mut blob, value);
+        }
+        // starts in image, followed by one starts in segment
+        push32(&mut blob, 2);
+        push32(&mut blob, 0);
+        push32(&mut blob, 12);
+        push32(&mut blob, 24);
+        blob.extend_from_slice(&0x4000u16.to_le_bytes());
+        blob.extend_from_slice(&DYLD_CHAINED_PTR_64_OFFSET.to_le_bytes());
+        blob.extend_from_slice(&0x4000u64.to_le_bytes());
+        push32(&mut blob, 0);
+        blob.extend_from_slice(&1u16.to_le_bytes());
+        blob.extend_from_slice(&0u16.to_le_bytes());
+        blob.resize(align_by_constraint(blob.len(), 4), 0);
+
+        // imports: `_a` from dylib 2, `_b` from dylib 3
+        let imports = blob.len() as u32;
+        write_u32(&mut blob, 8, imports);
+        push32(&mut blob, 2);
+        push32(&mut blob, 3 | 3 << 9);
+
+        let symbols = blob.len() as u32;
+        write_u32(&mut blob, 12, symbols);
+        blob.extend_from_slice(b"_a\0_b\0");
+
+        file[fixups..fixups + blob.len()].copy_from_slice(&blob);
+
+        let segments = vec![
+            SegmentLayout {
+                vmaddr: 0x1_0000_0000,
+                fileoff: 0,
+            },
+            SegmentLayout {
+                vmaddr: 0x1_0000_4000,
+                fileoff: 0x100,
+            },
+        ];
+
+        (file, segments)
+    }
+
+    #[test]
+    fn parse_walks_chains() {
+        let (file, segments) = example();
+        let fixups = parse(&file, 0x140, &segments);
+
+        let names: Vec<_> = fixups.imports.iter().map(|i| i.name.as_str()).collect();
+        assert_eq!(names, ["_a", "_b"]);
+        assert_eq!(fixups.imports[1].lib_ordinal, 3);
+
+        assert_eq!(
+            fixups.binds,
+            [
+                ChainedBind {
+                    file_offset: 0x100,
+                    vmaddr: 0x1_0000_4000,
+                    import: 0,
+                    pointer_format: DYLD_CHAINED_PTR_64_OFFSET,
+                },
+                ChainedBind {
+                    file_offset: 0x118,
+                    vmaddr: 0x1_0000_4018,
+                    import: 1,
+                    pointer_format: DYLD_CHAINED_PTR_64_OFFSET,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn rebase_keeps_chain() {
+        let (mut file, segments) = example();
+        let fixups = parse(&file, 0x140, &segments);
+
+        rebase_bind(&mut file, &fixups.binds[0], 0x1_0000_8000, 0x1_0000_0000);
+
+        assert_eq!(read_u64(&file, 0x100), 0x8000 | 2 << 51);
+        assert_eq!(parse(&file, 0x140, &segments).binds.len(), 1);
+    }
+
+    #[test]
+    fn unlink_dylib_moves_ordinals() {
+        let (mut file, segments) = example();
+        let fixups = parse(&file, 0x140, &segments);
+
+        fixups.unlink_dylib(&mut file, 2, &[0]);
+
+        let fixups = parse(&file, 0x140, &segments);
+        assert_eq!(
+            fixups.imports[0].lib_ordinal,
+            macho::BIND_SPECIAL_DYLIB_FLAT_LOOKUP as i32
+        );
+        assert!(fixups.is_weak(&file, 0));
+        assert_eq!(fixups.imports[1].lib_ordinal, 2);
+        assert!(!fixups.is_weak(&file, 1));
+    }
+
+    #[test]
+    fn insert_segments_keeps_fixups() {
+        let (mut file, mut segments) = example();
+        let blob = file[0x140..0x1a0].to_vec();
+        let grown = insert_segments(&blob, 1, 3);
+
+        assert_eq!(grown.len() % 8, 0);
+        assert_eq!(read_u32(&grown, 28), 5);
+
+        // the Roc segments go between `__TEXT` and `__DATA_CONST`
+        segments.insert(
+            1,
+            SegmentLayout {
+                vmaddr: 0x1_0001_0000,
+                fileoff: 0x1000,
+            },
+        );
+        segments.insert(1, segments[1]);
+        segments.insert(1, segments[1]);
+
+        file.resize(0x140, 0);
+        file.extend_from_slice(&grown);
+
+        let fixups = parse(&file, 0x140, &segments);
+
+        assert_eq!(fixups.imports.len(), 2);
+        assert_eq!(fixups.imports[1].name, "_b");
+        assert_eq!(fixups.binds.len(), 2);
+    }
+}
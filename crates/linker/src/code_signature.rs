@@ -0,0 +1,242 @@
+//! Ad-hoc code signatures for Mach-O executables.
+//!
+//! On Apple Silicon the kernel refuses to run code that isn't signed, and an ad-hoc signature
+//! (without a certificate) is enough for that. ld64 adds one to every arm64 executable, but
+//! surgery changes the executable, so the signature of the host no longer matches and has to be
+//! made again. An ad-hoc signature is just a code directory with a SHA-256 hash of every 4 KiB
+//! page of the file before the signature.
+//!
+//! The signature has to be the last thing in the file, at the end of `__LINKEDIT`, which in turn
+//! has to be the last segment.
+//!
+//! The blobs are big endian, unlike the rest of the file.
+
+const CSMAGIC_EMBEDDED_SIGNATURE: u32 = 0xfade_0cc0;
+const CSMAGIC_CODEDIRECTORY: u32 = 0xfade_0c02;
+const CSSLOT_CODEDIRECTORY: u32 = 0;
+
+/// The code directory version that has the executable segment fields.
+const CS_SUPPORTSEXECSEG: u32 = 0x20400;
+const CS_ADHOC: u32 = 0x2;
+const CS_LINKER_SIGNED: u32 = 0x20000;
+const CS_HASHTYPE_SHA256: u8 = 2;
+const CS_EXECSEG_MAIN_BINARY: u64 = 0x1;
+
+const PAGE_SIZE_LOG2: u8 = 12;
+const PAGE_SIZE: usize = 1 << PAGE_SIZE_LOG2;
+const HASH_SIZE: usize = 32;
+
+const SUPER_BLOB_SIZE: usize = 12;
+const BLOB_INDEX_SIZE: usize = 8;
+const CODE_DIRECTORY_SIZE: usize = 88;
+
+/// The signature goes at an offset that is a multiple of this.
+pub(crate) const SIGNATURE_ALIGNMENT: usize = 16;
+
+/// The size of the signature for a file whose first `code_limit` bytes are signed.
+pub(crate) fn signature_size(identifier: &str, code_limit: usize) -> usize {
+    let code_slots = code_limit.div_ceil(PAGE_SIZE);
+    let size = SUPER_BLOB_SIZE
+        + BLOB_INDEX_SIZE
+        + CODE_DIRECTORY_SIZE
+        + identifier.len()
+        + 1
+        + code_slots * HASH_SIZE;
+
+    crate::align_by_constraint(size, SIGNATURE_ALIGNMENT)
+}
+
+/// Sign the first `code_limit` bytes of `file`, and write the signature right after them.
+/// The `LC_CODE_SIGNATURE` command and the size of `__LINKEDIT` must already account for it,
+/// since they are part of what is signed. `exec_segment` is the file range of `__TEXT`.
+pub(crate) fn write_adhoc_signature(
+    file: &mut [u8],
+    code_limit: usize,
+    identifier: &str,
+    exec_segment: (u64, u64),
+) {
+    let (code, signature) = file.split_at_mut(code_limit);
+    let size = signature_size(identifier, code_limit);
+    let code_slots = code_limit.div_ceil(PAGE_SIZE);
+    let code_directory_size = CODE_DIRECTORY_SIZE + identifier.len() + 1 + code_slots * HASH_SIZE;
+    let ident_offset = CODE_DIRECTORY_SIZE;
+    let hash_offset = ident_offset + identifier.len() + 1;
+
+    let mut blob = Vec::with_capacity(size);
+    let push32 = |blob: &mut Vec<u8>, value: u32| blob.extend_from_slice(&value.to_be_bytes());
+
+    push32(&mut blob, CSMAGIC_EMBEDDED_SIGNATURE);
+    push32(
+        &mut blob,
+        (SUPER_BLOB_SIZE + BLOB_INDEX_SIZE + code_directory_size) as u32,
+    );
+    push32(&mut blob, 1);
+    push32(&mut blob, CSSLOT_CODEDIRECTORY);
+    push32(&mut blob, (SUPER_BLOB_SIZE + BLOB_INDEX_SIZE) as u32);
+
+    push32(&mut blob, CSMAGIC_CODEDIRECTORY);
+    push32(&mut blob, code_directory_size as u32);
+    push32(&mut blob, CS_SUPPORTSEXECSEG);
+    push32(&mut blob, CS_ADHOC | CS_LINKER_SIGNED);
+    push32(&mut blob, hash_offset as u32);
+    push32(&mut blob, ident_offset as u32);
+    // special slots
+    push32(&mut blob, 0);
+    push32(&mut blob, code_slots as u32);
+    push32(&mut blob, code_limit as u32);
+    blob.extend_from_slice(&[HASH_SIZE as u8, CS_HASHTYPE_SHA256, 0, PAGE_SIZE_LOG2]);
+    // spare2, scatter offset, team offset, spare3
+    for _ in 0..4 {
+        push32(&mut blob, 0);
+    }
+    // codeLimit64 is only used for files over 4 GiB
+    blob.extend_from_slice(&0u64.to_be_bytes());
+    blob.extend_from_slice(&exec_segment.0.to_be_bytes());
+    blob.extend_from_slice(&exec_segment.1.to_be_bytes());
+    blob.extend_from_slice(&CS_EXECSEG_MAIN_BINARY.to_be_bytes());
+
+    debug_assert_eq!(blob.len(), SUPER_BLOB_SIZE + BLOB_INDEX_SIZE + ident_offset);
+
+    blob.extend_from_slice(identifier.as_bytes());
+    blob.push(0);
+
+    for page in code.chunks(PAGE_SIZE) {
+        blob.extend_from_slice(&sha256(page));
+    }
+
+    blob.resize(size, 0);
+    signature[..size].copy_from_slice(&blob);
+}
+
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// SHA-256, the only hash the signature needs, which isn't worth a dependency.
+fn sha256(data: &[u8]) -> [u8; HASH_SIZE] {
+    let mut state: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+        0x5be0cd19,
+    ];
+
+    let mut padded = data.to_vec();
+    padded.push(0x80);
+    padded.resize(crate::align_by_constraint(padded.len() + 8, 64) - 8, 0);
+    padded.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+    for chunk in padded.chunks_exact(64) {
+        let mut w = [0u32; 64];
+
+        for (i, word) in chunk.chunks_exact(4).enumerate() {
+            w[i] = u32::from_be_bytes(word.try_into().unwrap());
+        }
+
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+
+        for i in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = h
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(K[i])
+                .wrapping_add(w[i]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+
+        for (value, new) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *value = value.wrapping_add(new);
+        }
+    }
+
+    let mut hash = [0; HASH_SIZE];
+
+    for (bytes, value) in hash.chunks_exact_mut(4).zip(state) {
+        bytes.copy_from_slice(&value.to_be_bytes());
+    }
+
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+
+    #[test]
+    fn sha256_test_vectors() {
+        assert_eq!(
+            hex(&sha256(b"")),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            hex(&sha256(b"abc")),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            hex(&sha256(
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+            )),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+    }
+
+    #[test]
+    fn signature_layout() {
+        let code_limit = PAGE_SIZE + 100;
+        let size = signature_size("app", code_limit);
+        let mut file = vec![0xab; code_limit + size];
+
+        write_adhoc_signature(&mut file, code_limit, "app", (0, 0x4000));
+
+        let signature = &file[code_limit..];
+        let read32 =
+            |offset: usize| u32::from_be_bytes(signature[offset..offset + 4].try_into().unwrap());
+
+        assert_eq!(size % SIGNATURE_ALIGNMENT, 0);
+        assert_eq!(read32(0), CSMAGIC_EMBEDDED_SIGNATURE);
+
+        let code_directory = read32(16) as usize;
+        let hash_offset = code_directory + read32(code_directory + 16) as usize;
+        let ident_offset = code_directory + read32(code_directory + 20) as usize;
+
+        assert_eq!(read32(code_directory), CSMAGIC_CODEDIRECTORY);
+        assert_eq!(read32(code_directory + 28), 2);
+        assert_eq!(&signature[ident_offset..ident_offset + 4], b"app\0");
+        assert_eq!(
+            &signature[hash_offset + HASH_SIZE..hash_offset + 2 * HASH_SIZE],
+            sha256(&[0xab; 100])
+        );
+    }
+}
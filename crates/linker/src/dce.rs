@@ -0,0 +1,378 @@
+//! Dead code elimination for surgical linking, and a report of what ends up in the executable.
+//!
+//! The dev backends emit every specialization into a section of its own, so any app section that
+//! the host can't reach (through the app functions it calls, and from there through relocations)
+//! can be left out of the executable. Sections of the app that were compiled as one big `.text`,
+//! like the LLVM backend's, are kept whole.
+//!
+//! Only the app's sections are removed here, and only for ELF so far; Mach-O and PE apps are
+//! linked in whole. The platform is already a linked executable when it's preprocessed, so its
+//! unused sections have to be removed by the linker that builds it (e.g. with `--gc-sections`,
+//! which cargo passes by default), not by the surgical linker.
+//!
+//! The size report lists every symbol of the app and the platform with the number of bytes it
+//! adds, one line per symbol:
+//!
+//! ```text
+//! # roc size report v1
+//! {size in bytes}\t{app|platform|removed}\t{symbol name}
+//! ```
+//!
+//! Lines are sorted from largest to smallest. `removed` marks app symbols that were dropped.
+use std::fmt::{self, Write};
+
+use object::{Object, ObjectSection, ObjectSymbol, RelocationTarget, SectionIndex, SymbolKind};
+use roc_collections::all::{MutMap, MutSet};
+
+/// The first line of every size report, so tools can reject files in an unknown format.
+pub const SIZE_REPORT_HEADER: &str = "# roc size report v1";
+
+/// The app sections that can be reached from the functions the host calls. If the host calls
+/// none of the app's symbols, we can't tell what is used, so every section is live.
+pub(crate) fn live_sections(
+    app_obj: &object::File,
+    app_functions: &[String],
+) -> MutSet<SectionIndex> {
+    let mut stack: Vec<SectionIndex> = app_obj
+        .symbols()
+        .filter(|sym| {
+            sym.name()
+                .is_ok_and(|name| app_functions.iter().any(|func| func == name))
+        })
+        .filter_map(|sym| sym.section_index())
+        .collect();
+
+    if stack.is_empty() {
+        return app_obj.sections().map(|sec| sec.index()).collect();
+    }
+
+    let mut live = MutSet::default();
+
+    while let Some(index) = stack.pop() {
+        if !live.insert(index) {
+            continue;
+        }
+
+        let Ok(section) = app_obj.section_by_index(index) else {
+            continue;
+        };
+
+        for (_, reloc) in section.relocations() {
+            let target = match reloc.target() {
+                RelocationTarget::Symbol(symbol) => app_obj
+                    .symbol_by_index(symbol)
+                    .ok()
+                    .and_then(|sym| sym.section_index()),
+                RelocationTarget::Section(section) => Some(section),
+                _ => None,
+            };
+
+            stack.extend(target);
+        }
+    }
+
+    live
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeStatus {
+    /// Defined by the app and linked in.
+    App,
+    /// Defined by the platform.
+    Platform,
+    /// Defined by the app, but unreachable from the host, so it was left out.
+    Removed,
+}
+
+impl SizeStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            SizeStatus::App => "app",
+            SizeStatus::Platform => "platform",
+            SizeStatus::Removed => "removed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SizeEntry {
+    pub name: String,
+    pub size: u64,
+    pub status: SizeStatus,
+}
+
+/// How many bytes each symbol of the app and the platform contributes to the executable.
+#[derive(Debug, Clone, Default)]
+pub struct SizeReport {
+    entries: Vec<SizeEntry>,
+}
+
+impl SizeReport {
+    pub fn entries(&self) -> &[SizeEntry] {
+        &self.entries
+    }
+
+    pub(crate) fn add_app_symbols(&mut self, app_obj: &object::File, live: &MutSet<SectionIndex>) {
+        for (name, section, size) in symbol_sizes(app_obj, app_obj.symbols()) {
+            let status = if live.contains(&section) {
+                SizeStatus::App
+            } else {
+                SizeStatus::Removed
+            };
+
+            self.entries.push(SizeEntry { name, size, status });
+        }
+    }
+
+    /// Platform symbols come from the symbol table of the preprocessed host, or from its dynamic
+    /// symbol table if it was stripped.
+    pub(crate) fn add_platform_symbols(&mut self, host_obj: &object::File) {
+        let mut sizes = symbol_sizes(host_obj, host_obj.symbols());
+
+        if sizes.is_empty() {
+            sizes = symbol_sizes(host_obj, host_obj.dynamic_symbols());
+        }
+
+        for (name, _, size) in sizes {
+            self.entries.push(SizeEntry {
+                name,
+                size,
+                status: SizeStatus::Platform,
+            });
+        }
+    }
+
+    /// The total size of the symbols with the given status.
+    pub fn total(&self, status: SizeStatus) -> u64 {
+        self.entries
+            .iter()
+            .filter(|entry| entry.status == status)
+            .map(|entry| entry.size)
+            .sum()
+    }
+
+    pub fn summary(&self) -> String {
+        let removed = self
+            .entries
+            .iter()
+            .filter(|entry| entry.status == SizeStatus::Removed)
+            .count();
+
+        format!(
+            "app: {} bytes, platform: {} bytes, removed {} unused app symbols ({} bytes)",
+            self.total(SizeStatus::App),
+            self.total(SizeStatus::Platform),
+            removed,
+            self.total(SizeStatus::Removed),
+        )
+    }
+}
+
+impl fmt::Display for SizeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut entries: Vec<&SizeEntry> = self.entries.iter().collect();
+
+        entries.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+
+        let mut buf = String::new();
+
+        writeln!(buf, "{SIZE_REPORT_HEADER}")?;
+
+        for entry in entries {
+            writeln!(
+                buf,
+                "{}\t{}\t{}",
+                entry.size,
+                entry.status.as_str(),
+                entry.name
+            )?;
+        }
+
+        f.write_str(&buf)
+    }
+}
+
+/// The name, section and size of every named function and data symbol. The dev backends don't
+/// record symbol sizes, so a symbol without one extends to the next symbol or the section end.
+fn symbol_sizes<'data: 'file, 'file>(
+    obj: &'file object::File<'data>,
+    symbols: impl Iterator<Item = object::Symbol<'data, 'file>>,
+) -> Vec<(String, SectionIndex, u64)> {
+    let mut by_section: MutMap<SectionIndex, Vec<(u64, u64, String)>> = MutMap::default();
+
+    for sym in symbols {
+        if !matches!(sym.kind(), SymbolKind::Text | SymbolKind::Data) || !sym.is_definition() {
+            continue;
+        }
+
+        let (Some(section), Ok(name)) = (sym.section_index(), sym.name()) else {
+            continue;
+        };
+
+        if !name.is_empty() {
+            by_section.entry(section).or_default().push((
+                sym.address(),
+                sym.size(),
+                name.to_string(),
+            ));
+        }
+    }
+
+    let mut sizes = Vec::new();
+
+    for (section, mut syms) in by_section {
+        let Ok(sec) = obj.section_by_index(section) else {
+            continue;
+        };
+        let section_end = sec.address() + sec.size();
+
+        syms.sort();
+
+        for (i, (address, size, name)) in syms.iter().enumerate() {
+            let size = if *size > 0 {
+                *size
+            } else {
+                let next = syms[i + 1..]
+                    .iter()
+                    .map(|(next, _, _)| *next)
+                    .find(|next| next > address)
+                    .unwrap_or(section_end);
+
+                next.saturating_sub(*address)
+            };
+
+            sizes.push((name.clone(), section, size));
+        }
+    }
+
+    sizes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use object::write;
+    use object::{
+        Architecture, BinaryFormat, Endianness, RelocationEncoding, RelocationFlags,
+        RelocationKind, SectionKind, SymbolFlags, SymbolScope,
+    };
+
+    /// An app object like the dev backends emit: every proc in a section of its own.
+    /// `roc__main_1_exposed` calls `Num_add_1`, and nothing calls `List_map_3`.
+    fn dev_backend_app() -> Vec<u8> {
+        let mut obj =
+            write::Object::new(BinaryFormat::Elf, Architecture::X86_64, Endianness::Little);
+        let add_proc = |obj: &mut write::Object, name: &str, size: u64| {
+            let section = obj.add_section(
+                Vec::new(),
+                format!(".text.{name}").into_bytes(),
+                SectionKind::Text,
+            );
+
+            obj.append_section_data(section, &vec![0x90; size as usize], 16);
+
+            let symbol = obj.add_symbol(write::Symbol {
+                name: name.as_bytes().to_vec(),
+                value: 0,
+                size,
+                kind: SymbolKind::Text,
+                scope: SymbolScope::Linkage,
+                weak: false,
+                section: write::SymbolSection::Section(section),
+                flags: SymbolFlags::None,
+            });
+
+            (section, symbol)
+        };
+
+        let (main, _) = add_proc(&mut obj, "roc__main_1_exposed", 32);
+        let (_, add) = add_proc(&mut obj, "Num_add_1", 16);
+        let _ = add_proc(&mut obj, "List_map_3", 48);
+
+        obj.add_relocation(
+            main,
+            write::Relocation {
+                offset: 1,
+                symbol: add,
+                addend: -4,
+                flags: RelocationFlags::Generic {
+                    kind: RelocationKind::Relative,
+                    encoding: RelocationEncoding::Generic,
+                    size: 32,
+                },
+            },
+        )
+        .unwrap();
+
+        obj.write().unwrap()
+    }
+
+    #[test]
+    fn unreachable_sections_are_removed() {
+        let bytes = dev_backend_app();
+        let app_obj = object::File::parse(bytes.as_slice()).unwrap();
+        let live = live_sections(&app_obj, &["roc__main_1_exposed".to_string()]);
+        let mut report = SizeReport::default();
+
+        report.add_app_symbols(&app_obj, &live);
+
+        let mut entries: Vec<_> = report
+            .entries()
+            .iter()
+            .map(|entry| (entry.name.as_str(), entry.size, entry.status))
+            .collect();
+
+        entries.sort_by_key(|(name, _, _)| *name);
+
+        assert_eq!(
+            entries,
+            [
+                ("List_map_3", 48, SizeStatus::Removed),
+                ("Num_add_1", 16, SizeStatus::App),
+                ("roc__main_1_exposed", 32, SizeStatus::App),
+            ]
+        );
+        assert!(report.to_string().contains("48\tremoved\tList_map_3\n"));
+    }
+
+    #[test]
+    fn everything_is_live_without_app_functions() {
+        let bytes = dev_backend_app();
+        let app_obj = object::File::parse(bytes.as_slice()).unwrap();
+        let live = live_sections(&app_obj, &[]);
+
+        assert_eq!(live.len(), app_obj.sections().count());
+    }
+
+    #[test]
+    fn render_sorts_by_size() {
+        let report = SizeReport {
+            entries: vec![
+                SizeEntry {
+                    name: "roc__mainForHost_1_exposed".to_string(),
+                    size: 48,
+                    status: SizeStatus::App,
+                },
+                SizeEntry {
+                    name: "main".to_string(),
+                    size: 120,
+                    status: SizeStatus::Platform,
+                },
+                SizeEntry {
+                    name: "List_map_3".to_string(),
+                    size: 48,
+                    status: SizeStatus::Removed,
+                },
+            ],
+        };
+
+        assert_eq!(
+            report.to_string(),
+            "# roc size report v1\n120\tplatform\tmain\n48\tremoved\tList_map_3\n48\tapp\troc__mainForHost_1_exposed\n"
+        );
+        assert_eq!(
+            report.summary(),
+            "app: 48 bytes, platform: 120 bytes, removed 1 unused app symbols (48 bytes)"
+        );
+    }
+}
@@ -7,7 +7,7 @@ use object::{
     ObjectSymbol, RelocationFlags, RelocationKind, RelocationTarget, Section, SectionIndex,
     SectionKind, Symbol, SymbolIndex, SymbolSection,
 };
-use roc_collections::all::MutMap;
+use roc_collections::all::{MutMap, MutSet};
 use roc_error_macros::{internal_error, user_error};
 use serde::{Deserialize, Serialize};
 use std::{
@@ -18,6 +18,7 @@ use std::{
     time::Instant,
 };
 
+use crate::dce::{live_sections, SizeReport};
 use crate::util::{is_roc_definition, is_roc_undefined, report_timing};
 use crate::{
     align_by_constraint, align_to_offset_by_constraint, load_struct_inplace,
@@ -1122,7 +1123,8 @@ pub(crate) fn surgery_elf(
     executable_path: &Path,
     verbose: bool,
     time: bool,
-) {
+    emit_size_report: bool,
+) -> Option<SizeReport> {
     let app_obj = match object::File::parse(roc_app_bytes) {
         Ok(obj) => obj,
         Err(err) => {
@@ -1158,7 +1160,22 @@ pub(crate) fn surgery_elf(
     let out_gen_start = Instant::now();
     let mut offset = 0;
 
-    surgery_elf_help(verbose, &md, &mut exec_mmap, &mut offset, app_obj);
+    let live = live_sections(&app_obj, &md.app_functions);
+
+    // The platform symbols have to be read before surgery moves the section header table.
+    let size_report = emit_size_report.then(|| {
+        let mut report = SizeReport::default();
+
+        match object::File::parse(&exec_mmap[..md.exec_len as usize]) {
+            Ok(host_obj) => report.add_platform_symbols(&host_obj),
+            Err(err) => eprintln!("Leaving platform symbols out of the size report: {err}"),
+        }
+        report.add_app_symbols(&app_obj, &live);
+
+        report
+    });
+
+    surgery_elf_help(verbose, &md, &mut exec_mmap, &mut offset, app_obj, &live);
 
     let out_gen_duration = out_gen_start.elapsed();
     let flushing_data_start = Instant::now();
@@ -1202,6 +1219,8 @@ pub(crate) fn surgery_elf(
         report_timing("Other", total_duration.saturating_sub(sum));
         report_timing("Total", total_duration);
     }
+
+    size_report
 }
 
 fn surgery_elf_help(
@@ -1210,6 +1229,7 @@ fn surgery_elf_help(
     exec_mmap: &mut MmapMut,
     offset_ref: &mut usize, // TODO return this instead of taking a mutable reference to it
     app_obj: object::File,
+    live: &MutSet<SectionIndex>,
 ) {
     let elf64 = exec_mmap[4] == 2;
     let litte_endian = exec_mmap[5] == 1;
@@ -1253,22 +1273,38 @@ fn surgery_elf_help(
 
     // First decide on sections locations and then recode every exact symbol locations.
 
+    // Sections that the host can't reach are left out entirely.
+    if verbose {
+        for sec in app_obj
+            .sections()
+            .filter(|sec| !live.contains(&sec.index()))
+        {
+            println!(
+                "Dropping unreachable section: {}",
+                sec.name().unwrap_or_default()
+            );
+        }
+    }
+
     // TODO: In the future Roc may use a data section to store memoized toplevel thunks
     // in development builds for caching the results of top-level constants
     let rodata_sections: Vec<Section> = app_obj
         .sections()
         .filter(|sec| sec.name().unwrap_or_default().starts_with(".rodata"))
+        .filter(|sec| live.contains(&sec.index()))
         .collect();
 
     // bss section is like rodata section, but it has zero file size and non-zero virtual size.
     let bss_sections: Vec<Section> = app_obj
         .sections()
         .filter(|sec| sec.name().unwrap_or_default().starts_with(".bss"))
+        .filter(|sec| live.contains(&sec.index()))
         .collect();
 
     let text_sections: Vec<Section> = app_obj
         .sections()
         .filter(|sec| sec.name().unwrap_or_default().starts_with(".text"))
+        .filter(|sec| live.contains(&sec.index()))
         .collect();
     if text_sections.is_empty() {
         internal_error!("No text sections found. This application has no code.");
@@ -1873,6 +1909,7 @@ mod tests {
             &dir.join("final"),
             false,
             false,
+            false,
         );
     }
 
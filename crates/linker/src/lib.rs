@@ -13,6 +13,10 @@ use std::cmp::Ordering;
 use std::mem;
 use std::path::{Path, PathBuf};
 
+mod aarch64;
+mod chained_fixups;
+mod code_signature;
+mod dce;
 mod elf;
 mod macho;
 mod pe;
@@ -20,6 +24,8 @@ mod util;
 
 mod generate_dylib;
 
+pub use dce::{SizeEntry, SizeReport, SizeStatus, SIZE_REPORT_HEADER};
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum LinkType {
     // These numbers correspond to the --lib and --no-link flags
@@ -40,9 +46,9 @@ pub fn support_level(link_type: LinkType, target: Target) -> SupportLevel {
         match target {
             Target::LinuxX64 => SupportLevel::Full,
             Target::WinX64 => SupportLevel::Full,
-            // macho support is incomplete
+            Target::MacArm64 => SupportLevel::Full,
+            // x86 macho support is incomplete
             Target::MacX64 => SupportLevel::None,
-            Target::MacArm64 => SupportLevel::Wip,
             _ => SupportLevel::None,
         }
     } else {
@@ -50,19 +56,24 @@ pub fn support_level(link_type: LinkType, target: Target) -> SupportLevel {
     }
 }
 
+/// Link the app into the preprocessed host. Parts of the app that the host can't reach are left
+/// out. If `emit_size_report` is set, also returns how much each symbol contributes to the
+/// executable; this is only supported for ELF so far.
 pub fn link_preprocessed_host(
     target: Target,
     roc_app_bytes: &[u8],
     binary_path: &Path,
     metadata: PathBuf,
     verbose: bool,
-) {
+    emit_size_report: bool,
+) -> Option<SizeReport> {
     surgery(
         roc_app_bytes,
         &metadata,
         binary_path,
         verbose,
         false,
+        emit_size_report,
         target,
     )
 }
@@ -368,12 +379,18 @@ fn surgery(
     executable_path: &Path,
     verbose: bool,
     time: bool,
+    emit_size_report: bool,
     target: Target,
-) {
+) -> Option<SizeReport> {
     match target.arch_os() {
-        (_, OperatingSystem::Linux) => {
-            crate::elf::surgery_elf(roc_app_bytes, metadata_path, executable_path, verbose, time);
-        }
+        (_, OperatingSystem::Linux) => crate::elf::surgery_elf(
+            roc_app_bytes,
+            metadata_path,
+            executable_path,
+            verbose,
+            time,
+            emit_size_report,
+        ),
 
         (_, OperatingSystem::Mac) => {
             crate::macho::surgery_macho(
@@ -383,10 +400,14 @@ fn surgery(
                 verbose,
                 time,
             );
+
+            None
         }
 
         (_, OperatingSystem::Windows) => {
             crate::pe::surgery_pe(executable_path, metadata_path, roc_app_bytes);
+
+            None
         }
 
         (Architecture::Wasm32, _) => {
@@ -18,10 +18,13 @@ use std::{
     time::Instant,
 };
 
+use crate::aarch64::{self, STUB_SIZE};
+use crate::chained_fixups::{self, ChainedBind, SegmentLayout};
+use crate::code_signature::{self, SIGNATURE_ALIGNMENT};
 use crate::util::{is_roc_definition, is_roc_undefined, report_timing};
 use crate::{
-    align_by_constraint, align_to_offset_by_constraint, load_struct_inplace,
-    load_struct_inplace_mut, load_structs_inplace, open_mmap, open_mmap_mut,
+    align_by_constraint, load_struct_inplace, load_struct_inplace_mut, load_structs_inplace,
+    load_structs_inplace_mut, open_mmap, open_mmap_mut,
 };
 
 const MIN_SECTION_ALIGNMENT: usize = 0x40;
@@ -65,7 +68,10 @@ struct Metadata {
     roc_symbol_vaddresses: MutMap<String, u64>,
     exec_len: usize,
     load_align_constraint: usize,
-    last_vaddr: u64,
+    // Address of the __TEXT segment.
+    image_base: u64,
+    // Chained fixups binding app functions, which surgery turns into rebases.
+    chained_app_binds: Vec<(String, ChainedBind)>,
     // Offset just after the last load command.
     // TODO: this is easy to re-calculate on the fly from just the header.
     end_of_load_commands: usize,
@@ -331,14 +337,14 @@ pub(crate) fn preprocess_macho_le(
     let symbol_and_plt_processing_start = Instant::now();
     let plt_section_name = "__stubs";
 
-    let (plt_address, plt_offset) = match exec_obj.section_by_name(plt_section_name) {
+    let (plt_address, plt_offset, plt_size) = match exec_obj.section_by_name(plt_section_name) {
         Some(section) => {
             let file_offset = if let Some((file_offset, _)) = section.file_range() {
                 file_offset
             } else {
                 internal_error!("Could not get file range for {section:+x?}");
             };
-            (section.address(), file_offset)
+            (section.address(), file_offset, section.size())
         }
         None => {
             internal_error!("Failed to find PLT section. Probably an malformed executable.");
@@ -353,6 +359,9 @@ pub(crate) fn preprocess_macho_le(
 
     let mut app_func_addresses: MutMap<u64, &str> = MutMap::default();
     let mut macho_load_so_offset = None;
+    let mut app_dylib_ordinal = None;
+    let mut segments = Vec::new();
+    let mut chained_fixups_offset = None;
 
     {
         use macho::{DyldInfoCommand, DylibCommand, Section64, SegmentCommand64};
@@ -422,6 +431,7 @@ pub(crate) fn preprocess_macho_le(
         offset = mem::size_of_val(exec_header);
 
         let shared_lib_filename = shared_lib.file_name();
+        let mut dylib_ordinal = 0;
 
         if verbose {
             println!("\nInput load commands:");
@@ -443,6 +453,18 @@ pub(crate) fn preprocess_macho_le(
                     if &info.segname[0.."__LINKEDIT".len()] == b"__LINKEDIT" {
                         md.linkedit_segment = offset;
                     }
+                    if &info.segname[0.."__TEXT\0".len()] == b"__TEXT\0" {
+                        md.image_base = info.vmaddr.get(LE);
+                    }
+                    segments.push(SegmentLayout {
+                        vmaddr: info.vmaddr.get(LE),
+                        fileoff: info.fileoff.get(LE),
+                    });
+                }
+                macho::LC_DYLD_CHAINED_FIXUPS => {
+                    let info =
+                        load_struct_inplace::<macho::LinkeditDataCommand<LE>>(exec_data, offset);
+                    chained_fixups_offset = Some(info.dataoff.get(LE) as usize);
                 }
                 macho::LC_DYLD_INFO_ONLY => {
                     let info = load_struct_inplace::<DyldInfoCommand<LE>>(exec_data, offset);
@@ -476,7 +498,14 @@ pub(crate) fn preprocess_macho_le(
                         }
                     }
                 }
-                macho::LC_LOAD_DYLIB => {
+                macho::LC_LOAD_DYLIB
+                | macho::LC_LOAD_WEAK_DYLIB
+                | macho::LC_REEXPORT_DYLIB
+                | macho::LC_LOAD_UPWARD_DYLIB
+                | macho::LC_LAZY_LOAD_DYLIB => {
+                    // Binds refer to dylibs by their 1-based index among these commands.
+                    dylib_ordinal += 1;
+
                     let info = load_struct_inplace::<DylibCommand<LE>>(exec_data, offset);
                     let name_offset = info.dylib.name.offset.get(LE) as usize;
                     let str_start_index = offset + name_offset;
@@ -509,6 +538,7 @@ pub(crate) fn preprocess_macho_le(
 
                     if path.file_name() == shared_lib_filename {
                         macho_load_so_offset = Some(offset);
+                        app_dylib_ordinal = Some(dylib_ordinal);
                     }
                 }
                 _ => {}
@@ -518,6 +548,48 @@ pub(crate) fn preprocess_macho_le(
         }
     }
 
+    // Since macOS 12, the host imports app functions through chained fixups instead of lazy binds.
+    let chained_fixups = chained_fixups_offset
+        .map(|fixups_offset| chained_fixups::parse(exec_data, fixups_offset, &segments));
+    let mut app_imports = Vec::new();
+
+    if let Some(fixups) = &chained_fixups {
+        app_imports = (0..fixups.imports.len())
+            .filter(|&import| {
+                app_syms
+                    .iter()
+                    .any(|sym| sym.name() == Ok(&fixups.imports[import].name))
+            })
+            .collect();
+
+        for bind in fixups.binds.iter() {
+            if app_imports.contains(&bind.import) {
+                let name = fixups.imports[bind.import].name.clone();
+                md.chained_app_binds.push((name, bind.clone()));
+            }
+        }
+
+        if arch == Architecture::Aarch64 {
+            // Each stub jumps through the GOT entry that the bind fills in.
+            let stubs = &exec_data[plt_offset as usize..(plt_offset + plt_size) as usize];
+
+            for (i, stub) in stubs.chunks_exact(STUB_SIZE).enumerate() {
+                let stub_address = plt_address + (i * STUB_SIZE) as u64;
+                let got_address = aarch64::stub_got_address(stub, stub_address);
+
+                if let Some((name, _)) = md
+                    .chained_app_binds
+                    .iter()
+                    .find(|(_, bind)| Some(bind.vmaddr) == got_address)
+                {
+                    let stub_offset = plt_offset + (i * STUB_SIZE) as u64;
+                    md.plt_addresses
+                        .insert(name.clone(), (stub_offset, stub_address));
+                }
+            }
+        }
+    }
+
     for sym in app_syms.iter() {
         let name = sym.name().unwrap().to_string();
         md.app_functions.push(name.clone());
@@ -538,9 +610,13 @@ pub(crate) fn preprocess_macho_le(
     // look at the text (i.e. code) sections and see collect work needs to be done
     let text_disassembly_start = Instant::now();
 
-    let mut surgeries = Surgeries::new(&app_syms, app_func_addresses);
-    surgeries.append_text_sections(exec_data, &exec_obj, verbose);
-    md.surgeries = surgeries.surgeries;
+    // On arm64, calls to app functions keep going through their stubs, which surgery turns into
+    // direct branches, so there is no code to disassemble.
+    if arch == Architecture::X86_64 {
+        let mut surgeries = Surgeries::new(&app_syms, app_func_addresses);
+        surgeries.append_text_sections(exec_data, &exec_obj, verbose);
+        md.surgeries = surgeries.surgeries;
+    }
 
     let text_disassembly_duration = text_disassembly_start.elapsed();
 
@@ -560,7 +636,7 @@ pub(crate) fn preprocess_macho_le(
 
     md.load_align_constraint = page_size(arch);
 
-    let out_mmap = gen_macho_le(
+    let mut out_mmap = gen_macho_le(
         exec_data,
         &mut md,
         preprocessed_path,
@@ -568,6 +644,11 @@ pub(crate) fn preprocess_macho_le(
         verbose,
     );
 
+    // The fixups data didn't move, but the load command of the app's dylib is gone.
+    if let (Some(fixups), Some(ordinal)) = (&chained_fixups, app_dylib_ordinal) {
+        fixups.unlink_dylib(&mut out_mmap, ordinal, &app_imports);
+    }
+
     let platform_gen_duration = platform_gen_start.elapsed();
 
     if verbose {
@@ -951,7 +1032,14 @@ pub(crate) fn surgery_macho(
     let loading_metadata_duration = loading_metadata_start.elapsed();
 
     let load_and_mmap_start = Instant::now();
-    let max_out_len = md.exec_len + roc_app_bytes.len() + md.load_align_constraint;
+    // Every app segment and __LINKEDIT may need a page of padding, and the chained fixups grow
+    // a little. The file is truncated to its final size afterwards.
+    let unsigned_len = md.exec_len
+        + roc_app_bytes.len()
+        + (NUM_ROC_SEGMENTS as usize + 2) * md.load_align_constraint
+        + SIGNATURE_ALIGNMENT * 4;
+    let max_out_len = unsigned_len
+        + code_signature::signature_size(&signature_identifier(executable_path), unsigned_len);
     let mut exec_mmap = open_mmap_mut(executable_path, max_out_len);
     let load_and_mmap_duration = load_and_mmap_start.elapsed();
 
@@ -979,6 +1067,12 @@ pub(crate) fn surgery_macho(
     // Also drop files to to ensure data is fully written here.
     drop(exec_mmap);
 
+    std::fs::OpenOptions::new()
+        .write(true)
+        .open(executable_path)
+        .and_then(|file| file.set_len(offset as u64))
+        .unwrap_or_else(|e| internal_error!("{}", e));
+
     let flushing_data_duration = flushing_data_start.elapsed();
 
     // Make sure the final executable has permision to execute.
@@ -1016,7 +1110,7 @@ pub(crate) fn surgery_macho(
 #[allow(clippy::too_many_arguments)]
 fn surgery_macho_help(
     _metadata_filename: &Path,
-    _out_filename: &Path,
+    out_filename: &Path,
     verbose: bool,
     _time: bool,
     md: &Metadata,
@@ -1024,17 +1118,23 @@ fn surgery_macho_help(
     offset_ref: &mut usize, // TODO return this instead of taking a mutable reference to it
     app_obj: object::File,
 ) {
-    let mut offset = align_by_constraint(md.exec_len, MIN_SECTION_ALIGNMENT);
-    // let new_rodata_section_offset = offset;
+    let is_arm64 = load_struct_inplace::<macho::MachHeader64<LE>>(exec_mmap, 0)
+        .cputype
+        .get(LE)
+        == macho::CPU_TYPE_ARM64;
+
+    // The app segments go where __LINKEDIT was, and __LINKEDIT moves after them, because it has
+    // to stay the last segment for the code signature.
+    let (linkedit_fileoff, linkedit_vmaddr) = {
+        let cmd =
+            load_struct_inplace::<macho::SegmentCommand64<LE>>(exec_mmap, md.linkedit_segment);
+        (cmd.fileoff.get(LE) as usize, cmd.vmaddr.get(LE) as usize)
+    };
+    let linkedit = exec_mmap[linkedit_fileoff..md.exec_len].to_vec();
+    exec_mmap[linkedit_fileoff..].fill(0);
 
-    // Align physical and virtual address of new segment.
-    let mut virt_offset =
-        align_to_offset_by_constraint(md.last_vaddr as usize, offset, md.load_align_constraint);
-    let new_rodata_section_vaddr = virt_offset;
-    if verbose {
-        println!();
-        println!("New Virtual Rodata Section Address: {new_rodata_section_vaddr:+x?}");
-    }
+    let mut offset = linkedit_fileoff;
+    let mut virt_offset = linkedit_vmaddr;
 
     // First decide on sections locations and then recode every exact symbol locations.
 
@@ -1080,49 +1180,70 @@ fn surgery_macho_help(
         println!();
     }
 
-    // Calculate addresses and load symbols.
-    // Note, it is important the bss sections come after the rodata sections.
-    for sec in rodata_sections
-        .iter()
-        .chain(bss_sections.iter())
-        .chain(text_sections.iter())
-    {
-        offset = align_by_constraint(offset, MIN_SECTION_ALIGNMENT);
-        virt_offset = align_to_offset_by_constraint(virt_offset, offset, md.load_align_constraint);
-        if verbose {
-            println!(
-                "Section, {}, is being put at offset: {:+x}(virt: {:+x})",
-                sec.name().unwrap(),
-                offset,
-                virt_offset
-            );
-        }
-        section_offset_map.insert(sec.index(), (offset, virt_offset));
-        for sym in symbols.iter() {
-            if sym.section() == SymbolSection::Section(sec.index()) {
-                let name = sym.name().unwrap_or_default().to_string();
-                if !md.roc_symbol_vaddresses.contains_key(&name) {
-                    symbol_vaddr_map.insert(sym.index(), virt_offset + sym.address() as usize);
-                }
-                if md.app_functions.contains(&name) {
-                    app_func_vaddr_map.insert(name.clone(), virt_offset + sym.address() as usize);
-                    app_func_size_map.insert(name, sym.size());
+    // Calculate addresses and load symbols, one page aligned segment at a time, in the order of
+    // their load commands.
+    for (segment_cmd, sections) in [
+        (md.roc_text_segment, &text_sections),
+        (md.roc_data_const_segment, &rodata_sections),
+        (md.roc_data_segment, &bss_sections),
+    ] {
+        let segment_offset = offset;
+        let segment_vaddr = virt_offset;
+
+        for sec in sections.iter() {
+            offset = align_by_constraint(offset, MIN_SECTION_ALIGNMENT);
+            virt_offset = align_by_constraint(virt_offset, MIN_SECTION_ALIGNMENT);
+            if sec.kind() != SectionKind::UninitializedData {
+                virt_offset = segment_vaddr + (offset - segment_offset);
+            }
+            if verbose {
+                println!(
+                    "Section, {}, is being put at offset: {:+x}(virt: {:+x})",
+                    sec.name().unwrap(),
+                    offset,
+                    virt_offset
+                );
+            }
+            section_offset_map.insert(sec.index(), (offset, virt_offset));
+            for sym in symbols.iter() {
+                if sym.section() == SymbolSection::Section(sec.index()) {
+                    let name = sym.name().unwrap_or_default().to_string();
+                    // Symbol addresses in object files include the address of their section.
+                    let sym_vaddr = virt_offset + (sym.address() - sec.address()) as usize;
+                    if !md.roc_symbol_vaddresses.contains_key(&name) {
+                        symbol_vaddr_map.insert(sym.index(), sym_vaddr);
+                    }
+                    if md.app_functions.contains(&name) {
+                        app_func_vaddr_map.insert(name.clone(), sym_vaddr);
+                        app_func_size_map.insert(name, sym.size());
+                    }
                 }
             }
+            let section_size = match sec.file_range() {
+                Some((_, size)) => size,
+                None => 0,
+            };
+            if sec.kind() == SectionKind::UninitializedData {
+                // bss sections only modify the virtual size.
+                virt_offset += sec.size() as usize;
+            } else if section_size != sec.size() {
+                internal_error!( "We do not deal with non bss sections that have different on disk and in memory sizes");
+            } else {
+                offset += section_size as usize;
+                virt_offset += sec.size() as usize;
+            }
         }
-        let section_size = match sec.file_range() {
-            Some((_, size)) => size,
-            None => 0,
-        };
-        if sec.name().unwrap_or_default().starts_with("__bss") {
-            // bss sections only modify the virtual size.
-            virt_offset += sec.size() as usize;
-        } else if section_size != sec.size() {
-            internal_error!( "We do not deal with non bss sections that have different on disk and in memory sizes");
-        } else {
-            offset += section_size as usize;
-            virt_offset += sec.size() as usize;
-        }
+
+        set_roc_segment(
+            exec_mmap,
+            segment_cmd,
+            (segment_offset, offset - segment_offset),
+            (segment_vaddr, virt_offset - segment_vaddr),
+            md.load_align_constraint,
+        );
+
+        offset = align_by_constraint(offset, md.load_align_constraint);
+        virt_offset = align_by_constraint(virt_offset, md.load_align_constraint);
     }
     if verbose {
         println!("Data Relocation Offsets: {symbol_vaddr_map:+x?}");
@@ -1207,20 +1328,23 @@ fn surgery_macho_help(
                             target_offset - virt_base as i64 + rel.1.addend()
                         }
                         RelocationKind::Absolute => {
+                            let Some(subtractor) = subtractor.take() else {
+                                // An absolute address would have to be rebased when the
+                                // executable is loaded at a random address.
+                                eprintln!("The surgical linker does not yet support absolute addresses in app data on macOS.");
+                                eprintln!("As a workaround, use `--linker=legacy`.");
+                                std::process::exit(1);
+                            };
+
                             target_offset + rel.1.addend()
-                                - subtractor
-                                    .take()
-                                    .map(|index| {
-                                        get_target_offset(
-                                            index,
-                                            &app_obj,
-                                            md,
-                                            &symbol_vaddr_map,
-                                            verbose,
-                                        )
-                                        .unwrap_or(0)
-                                    })
-                                    .unwrap()
+                                - get_target_offset(
+                                    subtractor,
+                                    &app_obj,
+                                    md,
+                                    &symbol_vaddr_map,
+                                    verbose,
+                                )
+                                .unwrap_or(0)
                         }
                         RelocationKind::Unknown => {
                             if let RelocationFlags::MachO { r_type, .. } = rel.1.flags() {
@@ -1233,6 +1357,55 @@ fn surgery_macho_help(
                                         }
                                         continue;
                                     }
+                                    macho::ARM64_RELOC_BRANCH26
+                                    | macho::ARM64_RELOC_PAGE21
+                                    | macho::ARM64_RELOC_PAGEOFF12
+                                    | macho::ARM64_RELOC_GOT_LOAD_PAGE21
+                                    | macho::ARM64_RELOC_GOT_LOAD_PAGEOFF12
+                                        if is_arm64 =>
+                                    {
+                                        let target = (target_offset + rel.1.addend()) as u64;
+                                        let pc = virt_base as u64;
+                                        let inst = aarch64::read_inst(exec_mmap, base);
+                                        let patched = match r_type {
+                                            macho::ARM64_RELOC_BRANCH26 => {
+                                                aarch64::patch_branch26(inst, pc, target)
+                                            }
+                                            macho::ARM64_RELOC_PAGE21
+                                            | macho::ARM64_RELOC_GOT_LOAD_PAGE21 => {
+                                                aarch64::patch_adrp(inst, pc, target)
+                                            }
+                                            macho::ARM64_RELOC_PAGEOFF12 => {
+                                                aarch64::patch_pageoff12(inst, target)
+                                            }
+                                            // Every symbol is in the executable itself, so
+                                            // compute its address instead of loading it from
+                                            // a GOT entry.
+                                            _ => aarch64::relax_got_load(inst).and_then(|inst| {
+                                                aarch64::patch_pageoff12(inst, target)
+                                            }),
+                                        };
+                                        if verbose {
+                                            println!("\t\tRelocation base location: {base:+x} (virt: {virt_base:+x})");
+                                            println!("\t\tFinal relocation target: {target:+x}");
+                                        }
+                                        match patched {
+                                            Some(inst) => {
+                                                aarch64::write_inst(exec_mmap, base, inst)
+                                            }
+                                            None => internal_error!(
+                                                "Can't encode {} at {virt_base:+x} to {target:+x}",
+                                                format_reloc_type(r_type)
+                                            ),
+                                        }
+                                        continue;
+                                    }
+                                    _ if is_arm64 => {
+                                        internal_error!(
+                                            "Relocation type not yet supported: {}",
+                                            format_reloc_type(r_type)
+                                        );
+                                    }
                                     _ => {
                                         if verbose {
                                             println!(
@@ -1279,7 +1452,7 @@ fn surgery_macho_help(
 
     // Flush app only data to speed up write to disk.
     exec_mmap
-        .flush_async_range(md.exec_len, offset - md.exec_len)
+        .flush_async_range(linkedit_fileoff, offset - linkedit_fileoff)
         .unwrap_or_else(|e| internal_error!("{}", e));
 
     // TODO: look into merging symbol tables, debug info, and eh frames to enable better debugger experience.
@@ -1424,24 +1597,51 @@ fn surgery_macho_help(
             }
         }
 
+        // The host may also take the address of the function through its GOT entry.
+        for (_, bind) in md
+            .chained_app_binds
+            .iter()
+            .filter(|(name, _)| name == func_name)
+        {
+            chained_fixups::rebase_bind(exec_mmap, bind, func_virt_offset, md.image_base);
+        }
+
         // Replace plt call code with just a jump.
         // This is a backup incase we missed a call to the plt.
-        if let Some((plt_off, plt_vaddr)) = md.plt_addresses.get(func_name) {
-            let plt_off = *plt_off as usize;
-            let plt_vaddr = *plt_vaddr;
-            let jmp_inst_len = 5;
-            let target =
-                (func_virt_offset as i64 - (plt_vaddr as i64 + jmp_inst_len as i64)) as i32;
-            if verbose {
-                println!("\tPLT: {plt_off:+x}, {plt_vaddr:+x}");
-                println!("\tTarget Jump: {target:+x}");
+        // On arm64 calls aren't patched, so this is how all of them reach the app.
+        match md.plt_addresses.get(func_name) {
+            Some((plt_off, plt_vaddr)) if is_arm64 => {
+                let plt_off = *plt_off as usize;
+                let stub =
+                    aarch64::direct_stub(*plt_vaddr, func_virt_offset).unwrap_or_else(|| {
+                        internal_error!(
+                            "Function, {}, is out of branch range of its stub",
+                            func_name
+                        );
+                    });
+                if verbose {
+                    println!("\tStub: {plt_off:+x}, {plt_vaddr:+x}");
+                }
+                exec_mmap[plt_off..plt_off + STUB_SIZE].copy_from_slice(&stub);
             }
-            let data = target.to_le_bytes();
-            exec_mmap[plt_off] = 0xE9;
-            exec_mmap[plt_off + 1..plt_off + jmp_inst_len].copy_from_slice(&data);
-            for i in jmp_inst_len..PLT_ADDRESS_OFFSET as usize {
-                exec_mmap[plt_off + i] = 0x90;
+            Some((plt_off, plt_vaddr)) => {
+                let plt_off = *plt_off as usize;
+                let plt_vaddr = *plt_vaddr;
+                let jmp_inst_len = 5;
+                let target =
+                    (func_virt_offset as i64 - (plt_vaddr as i64 + jmp_inst_len as i64)) as i32;
+                if verbose {
+                    println!("\tPLT: {plt_off:+x}, {plt_vaddr:+x}");
+                    println!("\tTarget Jump: {target:+x}");
+                }
+                let data = target.to_le_bytes();
+                exec_mmap[plt_off] = 0xE9;
+                exec_mmap[plt_off + 1..plt_off + jmp_inst_len].copy_from_slice(&data);
+                for i in jmp_inst_len..PLT_ADDRESS_OFFSET as usize {
+                    exec_mmap[plt_off + i] = 0x90;
+                }
             }
+            None => {}
         }
 
         // Commented out because it doesn't apply to mach-o
@@ -1463,7 +1663,226 @@ fn surgery_macho_help(
         // }
     }
 
-    *offset_ref = offset;
+    *offset_ref = append_linkedit(
+        exec_mmap,
+        md,
+        &linkedit,
+        linkedit_fileoff,
+        (offset, virt_offset),
+        &signature_identifier(out_filename),
+    );
+}
+
+/// The offsets and kinds of all load commands.
+fn load_commands(bytes: &[u8]) -> Vec<(u32, usize)> {
+    let header = load_struct_inplace::<macho::MachHeader64<LE>>(bytes, 0);
+    let mut offset = mem::size_of_val(header);
+
+    (0..header.ncmds.get(LE))
+        .map(|_| {
+            let info = load_struct_inplace::<macho::LoadCommand<LE>>(bytes, offset);
+            let cmd = (info.cmd.get(LE), offset);
+            offset += info.cmdsize.get(LE) as usize;
+            cmd
+        })
+        .collect()
+}
+
+/// Point a Roc segment load command at the file and memory ranges surgery put the app at. Its last
+/// section covers the whole segment, and any sections before it are left empty.
+fn set_roc_segment(
+    bytes: &mut [u8],
+    offset: usize,
+    (fileoff, filesize): (usize, usize),
+    (vmaddr, vmsize): (usize, usize),
+    load_align_constraint: usize,
+) {
+    let cmd = load_struct_inplace_mut::<macho::SegmentCommand64<LE>>(bytes, offset);
+    cmd.vmaddr.set(LE, vmaddr as u64);
+    cmd.vmsize.set(
+        LE,
+        align_by_constraint(vmsize, load_align_constraint) as u64,
+    );
+    cmd.fileoff.set(LE, fileoff as u64);
+    cmd.filesize.set(LE, filesize as u64);
+    let nsects = cmd.nsects.get(LE) as usize;
+
+    let sections = load_structs_inplace_mut::<macho::Section64<LE>>(
+        bytes,
+        offset + mem::size_of::<macho::SegmentCommand64<LE>>(),
+        nsects,
+    );
+    for (i, section) in sections.iter_mut().enumerate() {
+        section.addr.set(LE, vmaddr as u64);
+        section
+            .align
+            .set(LE, MIN_SECTION_ALIGNMENT.trailing_zeros());
+        if i == nsects - 1 {
+            section.size.set(LE, vmsize as u64);
+        }
+        if section.flags.get(LE) & macho::SECTION_TYPE != macho::S_ZEROFILL {
+            section.offset.set(LE, fileoff as u32);
+        }
+    }
+}
+
+/// Put the contents of __LINKEDIT, which were saved when the app took their place, back after the
+/// app segments, and sign the executable again if it was signed. Returns the new end of the file.
+fn append_linkedit(
+    exec_mmap: &mut [u8],
+    md: &Metadata,
+    linkedit: &[u8],
+    old_fileoff: usize,
+    (new_fileoff, new_vmaddr): (usize, usize),
+    identifier: &str,
+) -> usize {
+    let find_cmd = |cmd| {
+        md.linkedit_offsets
+            .iter()
+            .find(|(linkedit_cmd, _)| *linkedit_cmd == cmd)
+            .map(|(_, cmd_offset)| *cmd_offset)
+    };
+
+    // The old signature doesn't match anymore, and is always the last thing in __LINKEDIT.
+    let code_signature_cmd = find_cmd(macho::LC_CODE_SIGNATURE);
+    let mut contents = match code_signature_cmd {
+        Some(cmd_offset) => {
+            let cmd = load_struct_inplace::<macho::LinkeditDataCommand<LE>>(exec_mmap, cmd_offset);
+            linkedit[..cmd.dataoff.get(LE) as usize - old_fileoff].to_vec()
+        }
+        None => linkedit.to_vec(),
+    };
+
+    // The chained fixups have to list the Roc segments too. That makes them grow, and moves
+    // everything after them.
+    let mut fixups_growth = None;
+    if let Some(cmd_offset) = find_cmd(macho::LC_DYLD_CHAINED_FIXUPS) {
+        let segment_index = load_commands(exec_mmap)
+            .iter()
+            .filter(|(cmd, offset)| *cmd == macho::LC_SEGMENT_64 && *offset < md.roc_text_segment)
+            .count();
+        let cmd = load_struct_inplace_mut::<macho::LinkeditDataCommand<LE>>(exec_mmap, cmd_offset);
+        let start = cmd.dataoff.get(LE) as usize - old_fileoff;
+        let size = cmd.datasize.get(LE) as usize;
+
+        let mut grown = chained_fixups::insert_segments(
+            &contents[start..start + size],
+            segment_index,
+            NUM_ROC_SEGMENTS as usize,
+        );
+        // Keep the tables after them 8 byte aligned.
+        grown.resize(size + align_by_constraint(grown.len() - size, 8), 0);
+
+        cmd.datasize.set(LE, grown.len() as u32);
+        fixups_growth = Some((start + size, grown.len() - size));
+        contents.splice(start..start + size, grown);
+    }
+
+    let relocate = |field: &mut object::U32<LE>| {
+        let old = field.get(LE) as usize;
+        // Tables that aren't there have offset 0.
+        if old != 0 {
+            let relative = old - old_fileoff;
+            let relative = match fixups_growth {
+                Some((fixups_end, growth)) if relative >= fixups_end => relative + growth,
+                _ => relative,
+            };
+            field.set(LE, (new_fileoff + relative) as u32);
+        }
+    };
+
+    for (cmd, cmd_offset) in md.linkedit_offsets.iter().copied() {
+        match cmd {
+            macho::LC_SYMTAB => {
+                let info =
+                    load_struct_inplace_mut::<macho::SymtabCommand<LE>>(exec_mmap, cmd_offset);
+                relocate(&mut info.symoff);
+                relocate(&mut info.stroff);
+            }
+            macho::LC_DYSYMTAB => {
+                let info =
+                    load_struct_inplace_mut::<macho::DysymtabCommand<LE>>(exec_mmap, cmd_offset);
+                relocate(&mut info.tocoff);
+                relocate(&mut info.modtaboff);
+                relocate(&mut info.extrefsymoff);
+                relocate(&mut info.indirectsymoff);
+                relocate(&mut info.extreloff);
+                relocate(&mut info.locreloff);
+            }
+            macho::LC_FUNCTION_STARTS
+            | macho::LC_DATA_IN_CODE
+            | macho::LC_DYLD_EXPORTS_TRIE
+            | macho::LC_DYLD_CHAINED_FIXUPS => {
+                let info = load_struct_inplace_mut::<macho::LinkeditDataCommand<LE>>(
+                    exec_mmap, cmd_offset,
+                );
+                relocate(&mut info.dataoff);
+            }
+            macho::LC_DYLD_INFO | macho::LC_DYLD_INFO_ONLY => {
+                let info =
+                    load_struct_inplace_mut::<macho::DyldInfoCommand<LE>>(exec_mmap, cmd_offset);
+                relocate(&mut info.rebase_off);
+                relocate(&mut info.bind_off);
+                relocate(&mut info.weak_bind_off);
+                relocate(&mut info.lazy_bind_off);
+                relocate(&mut info.export_off);
+            }
+            _ => {}
+        }
+    }
+
+    exec_mmap[new_fileoff..new_fileoff + contents.len()].copy_from_slice(&contents);
+    let mut end = new_fileoff + contents.len();
+
+    let signature_offset = code_signature_cmd.map(|cmd_offset| {
+        let signature_offset = align_by_constraint(end, SIGNATURE_ALIGNMENT);
+        let size = code_signature::signature_size(identifier, signature_offset);
+        let info = load_struct_inplace_mut::<macho::LinkeditDataCommand<LE>>(exec_mmap, cmd_offset);
+        info.dataoff.set(LE, signature_offset as u32);
+        info.datasize.set(LE, size as u32);
+        end = signature_offset + size;
+        signature_offset
+    });
+
+    let segment =
+        load_struct_inplace_mut::<macho::SegmentCommand64<LE>>(exec_mmap, md.linkedit_segment);
+    segment.vmaddr.set(LE, new_vmaddr as u64);
+    segment.vmsize.set(
+        LE,
+        align_by_constraint(end - new_fileoff, md.load_align_constraint) as u64,
+    );
+    segment.fileoff.set(LE, new_fileoff as u64);
+    segment.filesize.set(LE, (end - new_fileoff) as u64);
+
+    // Signing has to come last, since the signature covers the load commands too.
+    if let Some(signature_offset) = signature_offset {
+        let text_segment = load_commands(exec_mmap)
+            .into_iter()
+            .filter(|(cmd, _)| *cmd == macho::LC_SEGMENT_64)
+            .map(|(_, offset)| {
+                load_struct_inplace::<macho::SegmentCommand64<LE>>(exec_mmap, offset)
+            })
+            .find(|segment| &segment.segname[.."__TEXT\0".len()] == b"__TEXT\0")
+            .map(|segment| (segment.fileoff.get(LE), segment.filesize.get(LE)))
+            .unwrap_or_else(|| internal_error!("The host has no __TEXT segment"));
+
+        code_signature::write_adhoc_signature(
+            &mut exec_mmap[..end],
+            signature_offset,
+            identifier,
+            text_segment,
+        );
+    }
+
+    end
+}
+
+/// The identifier in the code signature, which is the name of the executable like ld64 uses.
+fn signature_identifier(executable_path: &Path) -> String {
+    executable_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
 }
 
 fn get_target_offset(
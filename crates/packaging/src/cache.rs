@@ -1,11 +1,10 @@
 #[cfg(not(target_family = "wasm"))]
 use {
-    crate::https::{self, PackageMetadata, Problem},
+    crate::fetch::{default_fetcher, PackageFetcher},
+    crate::https::{PackageMetadata, Problem},
     roc_error_macros::internal_error,
     std::fs,
 };
-#[cfg(not(target_family = "wasm"))]
-const MAX_DOWNLOAD_BYTES: u64 = 32 * 1_000_000_000; // GB
 
 use std::path::{Path, PathBuf};
 
@@ -79,6 +78,16 @@ fn nixos_error_if_dynamic(url: &str, dest_dir: &Path) {
 pub fn install_package<'a>(
     roc_cache_dir: RocCacheDir<'_>,
     url: &'a str,
+) -> Result<(PathBuf, Option<&'a str>), Problem> {
+    install_package_with(default_fetcher()?.as_ref(), roc_cache_dir, url)
+}
+
+/// Like [install_package], but downloads packages that aren't cached yet using the given fetcher.
+#[cfg(not(target_family = "wasm"))]
+pub fn install_package_with<'a>(
+    fetcher: &dyn PackageFetcher,
+    roc_cache_dir: RocCacheDir<'_>,
+    url: &'a str,
 ) -> Result<(PathBuf, Option<&'a str>), Problem> {
     use std::io::ErrorKind;
 
@@ -113,8 +122,7 @@ pub fn install_package<'a>(
                 );
                 let tempdir = tempfile::tempdir().map_err(Problem::IoErr)?;
                 let tempdir_path = tempdir.path();
-                let downloaded_hash =
-                    https::download_and_hash(url, tempdir_path, MAX_DOWNLOAD_BYTES)?;
+                let downloaded_hash = fetcher.fetch(url, tempdir_path)?;
 
                 // Download the tarball into memory and verify it.
                 // The tarball name is the hash of its contents.
@@ -0,0 +1,134 @@
+//! Credentials for downloading packages from private servers.
+//!
+//! The credentials file is read from the path in the [CREDENTIALS_ENV_VAR] environment variable.
+//! Each line is a host, followed by the value of the `Authorization` header to send to that host:
+//!
+//! ```text
+//! # comments and blank lines are ignored
+//! packages.example.com Bearer abc123
+//! internal.example.com:8443 Basic dXNlcjpwYXNz
+//! ```
+//!
+//! Credentials are only ever sent to the exact host (and port) they're listed for.
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+pub const CREDENTIALS_ENV_VAR: &str = "ROC_CREDENTIALS_FILE";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Credentials {
+    authorization_by_host: HashMap<String, String>,
+}
+
+#[derive(Debug)]
+pub enum CredentialsProblem {
+    IoErr(io::Error),
+    InvalidLine { line_number: usize },
+}
+
+impl Credentials {
+    pub fn parse(src: &str) -> Result<Self, CredentialsProblem> {
+        let mut authorization_by_host = HashMap::new();
+
+        for (index, line) in src.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            // Don't include the line in the problem, since it contains a secret
+            match line.split_once(char::is_whitespace) {
+                Some((host, authorization)) if !authorization.trim().is_empty() => {
+                    authorization_by_host
+                        .insert(host.to_ascii_lowercase(), authorization.trim().to_string());
+                }
+                _ => {
+                    return Err(CredentialsProblem::InvalidLine {
+                        line_number: index + 1,
+                    })
+                }
+            }
+        }
+
+        Ok(Self {
+            authorization_by_host,
+        })
+    }
+
+    pub fn read(path: &Path) -> Result<Self, CredentialsProblem> {
+        fs::read_to_string(path)
+            .map_err(CredentialsProblem::IoErr)
+            .and_then(|src| Self::parse(&src))
+    }
+
+    /// Reads the file in the [CREDENTIALS_ENV_VAR] environment variable, if it's set.
+    pub fn from_env() -> Result<Self, CredentialsProblem> {
+        match std::env::var_os(CREDENTIALS_ENV_VAR) {
+            Some(path) => Self::read(Path::new(&path)),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// The `Authorization` header to send when downloading the given URL, if any.
+    /// Credentials are never sent over plain HTTP.
+    pub fn authorization_for(&self, url: &str) -> Option<&str> {
+        let without_protocol = url.strip_prefix("https://")?;
+        let host = without_protocol
+            .split(['/', '#', '?'])
+            .next()
+            .unwrap_or_default();
+
+        self.authorization_by_host
+            .get(&host.to_ascii_lowercase())
+            .map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn scoped_to_host() {
+        let credentials = Credentials::parse(
+            "# private servers\n\npackages.example.com Bearer abc\nexample.com:8443   Basic xyz\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            credentials.authorization_for("https://packages.example.com/pkgs/hash.tar.br"),
+            Some("Bearer abc")
+        );
+        assert_eq!(
+            credentials.authorization_for("https://Packages.Example.com/hash.tar"),
+            Some("Bearer abc")
+        );
+        assert_eq!(
+            credentials.authorization_for("https://example.com:8443/hash.tar#main.roc"),
+            Some("Basic xyz")
+        );
+        assert_eq!(
+            credentials.authorization_for("https://example.com/hash.tar"),
+            None
+        );
+        assert_eq!(
+            credentials.authorization_for("https://packages.example.com.evil.com/hash.tar"),
+            None
+        );
+        assert_eq!(
+            credentials.authorization_for("http://packages.example.com/hash.tar"),
+            None
+        );
+    }
+
+    #[test]
+    fn invalid_line() {
+        assert!(matches!(
+            Credentials::parse("example.com Bearer abc\nexample.org\n"),
+            Err(CredentialsProblem::InvalidLine { line_number: 2 })
+        ));
+    }
+}
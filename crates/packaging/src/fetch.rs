@@ -0,0 +1,199 @@
+//! Where packages are downloaded from. By default that's the URL itself, but packages can also
+//! be served from a local mirror (e.g. a company-internal copy of the packages it uses),
+//! or from memory in tests that must not hit the network.
+use crate::credentials::Credentials;
+use crate::https::{self, decompress_into, Encoding, Problem};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+/// If set, packages are read from this directory instead of being downloaded. See [MirrorFetcher].
+pub const PACKAGE_MIRROR_ENV_VAR: &str = "ROC_PACKAGE_MIRROR";
+
+const MAX_DOWNLOAD_BYTES: u64 = 32 * 1_000_000_000; // GB
+
+pub trait PackageFetcher {
+    /// Unpack the tarball at the given URL into `dest_dir`, and return the base64url-encoded
+    /// BLAKE3 hash of the (decompressed) tarball, so the caller can verify it against the URL.
+    fn fetch(&self, url: &str, dest_dir: &Path) -> Result<String, Problem>;
+}
+
+/// Downloads packages from their URL.
+#[derive(Debug, Clone)]
+pub struct HttpsFetcher {
+    pub max_download_bytes: u64,
+    pub credentials: Credentials,
+}
+
+impl Default for HttpsFetcher {
+    fn default() -> Self {
+        Self {
+            max_download_bytes: MAX_DOWNLOAD_BYTES,
+            credentials: Credentials::default(),
+        }
+    }
+}
+
+impl PackageFetcher for HttpsFetcher {
+    fn fetch(&self, url: &str, dest_dir: &Path) -> Result<String, Problem> {
+        https::download_and_hash(
+            url,
+            dest_dir,
+            self.max_download_bytes,
+            self.credentials.authorization_for(url),
+        )
+    }
+}
+
+/// Reads packages from a directory that mirrors the hosts they're published on, e.g.
+/// `https://example.com/pkgs/abc.tar.br` is read from `<root>/example.com/pkgs/abc.tar.br`.
+#[derive(Debug, Clone)]
+pub struct MirrorFetcher {
+    pub root: PathBuf,
+}
+
+impl PackageFetcher for MirrorFetcher {
+    fn fetch(&self, url: &str, dest_dir: &Path) -> Result<String, Problem> {
+        let path = self.root.join(url_path(url));
+        let file = File::open(path).map_err(|err| match err.kind() {
+            std::io::ErrorKind::NotFound => Problem::NotFound,
+            _ => Problem::IoErr(err),
+        })?;
+
+        decompress_into(dest_dir, Encoding::new("", url)?, BufReader::new(file))
+    }
+}
+
+/// Serves packages from memory, for tests.
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryFetcher {
+    /// The (possibly compressed) tarball for each URL, without the URL's fragment
+    pub tarballs: HashMap<String, Vec<u8>>,
+}
+
+impl PackageFetcher for InMemoryFetcher {
+    fn fetch(&self, url: &str, dest_dir: &Path) -> Result<String, Problem> {
+        let bytes = self.tarballs.get(url_path(url)).ok_or(Problem::NotFound)?;
+
+        decompress_into(dest_dir, Encoding::new("", url)?, bytes.as_slice())
+    }
+}
+
+/// A [MirrorFetcher] if the [PACKAGE_MIRROR_ENV_VAR] environment variable is set, and otherwise
+/// an [HttpsFetcher] that uses the credentials from [Credentials::from_env].
+pub fn default_fetcher() -> Result<Box<dyn PackageFetcher>, Problem> {
+    match std::env::var_os(PACKAGE_MIRROR_ENV_VAR) {
+        Some(root) => Ok(Box::new(MirrorFetcher { root: root.into() })),
+        None => Ok(Box::new(HttpsFetcher {
+            credentials: Credentials::from_env().map_err(Problem::InvalidCredentials)?,
+            ..Default::default()
+        })),
+    }
+}
+
+/// The URL without its protocol and fragment, e.g. `example.com/pkgs/abc.tar.br`
+fn url_path(url: &str) -> &str {
+    let without_protocol = url.split_once("://").map_or(url, |(_, rest)| rest);
+
+    without_protocol
+        .split_once('#')
+        .map_or(without_protocol, |(before_fragment, _)| before_fragment)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cache::{install_package_with, RocCacheDir};
+
+    /// A tarball containing a single main.roc, and the URL it would be published at.
+    fn package() -> (String, Vec<u8>) {
+        let mut builder = tar::Builder::new(Vec::new());
+        let contents = b"package [] {}";
+        let mut header = tar::Header::new_gnu();
+
+        header.set_size(contents.len() as u64);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, "main.roc", &contents[..])
+            .unwrap();
+
+        let bytes = builder.into_inner().unwrap();
+        let hash = base64_url::encode(blake3::hash(&bytes).as_bytes());
+
+        (format!("https://example.com/pkgs/{hash}.tar"), bytes)
+    }
+
+    #[test]
+    fn install_from_memory() {
+        let (url, bytes) = package();
+        let fetcher = InMemoryFetcher {
+            tarballs: HashMap::from([(url_path(&url).to_string(), bytes)]),
+        };
+        let cache_dir = tempfile::tempdir().unwrap();
+
+        let (package_dir, root_module) =
+            install_package_with(&fetcher, RocCacheDir::Persistent(cache_dir.path()), &url)
+                .unwrap();
+
+        assert!(package_dir.starts_with(cache_dir.path()));
+        assert!(package_dir.join("main.roc").exists());
+        assert_eq!(root_module, None);
+    }
+
+    #[test]
+    fn install_from_mirror() {
+        let (url, bytes) = package();
+        let mirror_dir = tempfile::tempdir().unwrap();
+        let mirror_path = mirror_dir.path().join(url_path(&url));
+
+        std::fs::create_dir_all(mirror_path.parent().unwrap()).unwrap();
+        std::fs::write(&mirror_path, bytes).unwrap();
+
+        let fetcher = MirrorFetcher {
+            root: mirror_dir.path().to_path_buf(),
+        };
+        let cache_dir = tempfile::tempdir().unwrap();
+        let url_with_fragment = format!("{url}#main.roc");
+
+        let (package_dir, root_module) = install_package_with(
+            &fetcher,
+            RocCacheDir::Persistent(cache_dir.path()),
+            &url_with_fragment,
+        )
+        .unwrap();
+
+        assert!(package_dir.join("main.roc").exists());
+        assert_eq!(root_module, Some("main.roc"));
+    }
+
+    #[test]
+    fn install_rejects_wrong_hash() {
+        let (url, mut bytes) = package();
+
+        // still a valid tarball, but with different contents
+        bytes.extend([0; 512]);
+
+        let fetcher = InMemoryFetcher {
+            tarballs: HashMap::from([(url_path(&url).to_string(), bytes)]),
+        };
+        let cache_dir = tempfile::tempdir().unwrap();
+
+        let result =
+            install_package_with(&fetcher, RocCacheDir::Persistent(cache_dir.path()), &url);
+
+        assert!(matches!(result, Err(Problem::InvalidContentHash { .. })));
+    }
+
+    #[test]
+    fn missing_package() {
+        let cache_dir = tempfile::tempdir().unwrap();
+        let result = install_package_with(
+            &InMemoryFetcher::default(),
+            RocCacheDir::Persistent(cache_dir.path()),
+            "https://example.com/pkgs/hash.tar.br",
+        );
+
+        assert!(matches!(result, Err(Problem::NotFound)));
+    }
+}
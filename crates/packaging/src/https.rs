@@ -3,6 +3,7 @@ use std::{
     path::Path,
 };
 
+use crate::credentials::CredentialsProblem;
 use crate::tarball::Compression;
 
 // gzip should be the most widely supported, and brotli offers the highest compression.
@@ -241,23 +242,41 @@ pub enum Problem {
     /// The Content-Length header of the response exceeded max_download_bytes
     DownloadTooBig(u64),
     NotFound,
+    /// The server replied 401 Unauthorized or 403 Forbidden
+    Unauthorized {
+        status: u16,
+        sent_credentials: bool,
+    },
+    InvalidCredentials(CredentialsProblem),
 }
 
+/// If `authorization` is given, it's sent as the value of the `Authorization` header.
 pub fn download_and_hash(
     url: &str,
     dest_dir: &Path,
     max_download_bytes: u64,
+    authorization: Option<&str>,
 ) -> Result<String, Problem> {
     // TODO apparently it really improves performance to construct a Client once and then reuse it,
     // instead of making a new Client for every request.
     // Per https://github.com/seanmonstar/reqwest/issues/1454#issuecomment-1026076701
-    let resp = reqwest::blocking::Client::new()
-        .get(url)
-        .send()
-        .map_err(Problem::HttpErr)?;
+    let mut request = reqwest::blocking::Client::new().get(url);
+
+    if let Some(authorization) = authorization {
+        request = request.header(reqwest::header::AUTHORIZATION, authorization);
+    }
 
-    if resp.status() == reqwest::StatusCode::NOT_FOUND {
-        return Err(Problem::NotFound);
+    let resp = request.send().map_err(Problem::HttpErr)?;
+
+    match resp.status() {
+        reqwest::StatusCode::NOT_FOUND => return Err(Problem::NotFound),
+        status @ (reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN) => {
+            return Err(Problem::Unauthorized {
+                status: status.as_u16(),
+                sent_credentials: authorization.is_some(),
+            });
+        }
+        _ => {}
     }
 
     // Some servers don't return Content-Length - e.g. Netlify seems to only sometimes return it.
@@ -290,7 +309,7 @@ pub fn download_and_hash(
 
 /// The content encodings we support
 #[derive(Debug, Clone, Copy, PartialEq)]
-enum Encoding {
+pub(crate) enum Encoding {
     Gzip,
     Brotli,
     Deflate,
@@ -366,7 +385,7 @@ fn hash_and_unpack(dest_dir: &Path, reader: impl Read) -> Result<String, Problem
 /// write them to the given writer, and return the base64url-encoded BLAKE3 hash of what was written.
 /// This both writes and hashes incrementally as it reads, so the only extra work that's done
 /// at the end is base64url-encoding the final hash.
-fn decompress_into(
+pub(crate) fn decompress_into(
     dest_dir: &Path,
     encoding: Encoding,
     reader: impl Read,
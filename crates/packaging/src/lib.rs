@@ -1,4 +1,12 @@
 pub mod cache;
 #[cfg(not(target_family = "wasm"))]
+pub mod credentials;
+#[cfg(not(target_family = "wasm"))]
+pub mod fetch;
+#[cfg(not(target_family = "wasm"))]
 pub mod https;
+#[cfg(not(target_family = "wasm"))]
+pub mod lockfile;
+pub mod resolve;
+pub mod semver;
 pub mod tarball;
@@ -0,0 +1,419 @@
+//! `roc.lock` files, which pin the contents of every package an app or package depends on.
+//!
+//! Package URLs already end in the BLAKE3 hash of their tarball, which is checked on download.
+//! The lockfile additionally records a hash of the files that were extracted from each tarball,
+//! so that a build can verify that the cached copy of a package hasn't been modified since it was locked.
+//!
+//! Packages that were picked by [crate::resolve] from a version constraint also record the name
+//! and version they were resolved to, so that later resolutions keep them unless they have to change.
+use crate::cache::{install_package, RocCacheDir};
+use crate::https::Problem;
+use crate::resolve::{Dependency, Resolution};
+use crate::semver::Version;
+use std::collections::BTreeMap;
+use std::fmt::{self, Display};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+pub const LOCKFILE_NAME: &str = "roc.lock";
+
+const HEADER: &str = "# This file is generated by roc. Do not edit it by hand.";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockedPackage {
+    pub url: String,
+    /// See [tree_hash]
+    pub tree_hash: String,
+    /// The name and version this package was resolved to, if it was depended on through a
+    /// version constraint rather than by its URL.
+    pub release: Option<(String, Version)>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Lockfile {
+    /// Sorted by URL, so that the file doesn't change when the order of the dependencies does.
+    pub packages: Vec<LockedPackage>,
+}
+
+#[derive(Debug)]
+pub enum LockfileProblem {
+    IoErr(io::Error),
+    InvalidLine {
+        line_number: usize,
+        line: String,
+    },
+    Install {
+        url: String,
+        problem: Problem,
+    },
+    TreeHashMismatch {
+        url: String,
+        expected: String,
+        actual: String,
+    },
+    NotLocked(String),
+    /// No package in the lockfile was resolved to a version that satisfies this dependency.
+    Unresolved(Dependency),
+}
+
+impl Lockfile {
+    pub fn parse(src: &str) -> Result<Self, LockfileProblem> {
+        let mut packages = Vec::new();
+
+        for (index, line) in src.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid_line = || LockfileProblem::InvalidLine {
+                line_number: index + 1,
+                line: line.to_string(),
+            };
+
+            let mut fields = line.split_whitespace();
+
+            let (url, tree_hash) = match (fields.next(), fields.next()) {
+                (Some(url), Some(tree_hash)) => (url, tree_hash),
+                _ => return Err(invalid_line()),
+            };
+
+            let release = match fields.next() {
+                Some(release) => {
+                    let (name, version) = release.split_once('@').ok_or_else(invalid_line)?;
+                    let version = Version::parse(version).map_err(|_| invalid_line())?;
+
+                    Some((name.to_string(), version))
+                }
+                None => None,
+            };
+
+            if fields.next().is_some() {
+                return Err(invalid_line());
+            }
+
+            packages.push(LockedPackage {
+                url: url.to_string(),
+                tree_hash: tree_hash.to_string(),
+                release,
+            });
+        }
+
+        packages.sort_by(|a, b| a.url.cmp(&b.url));
+
+        Ok(Self { packages })
+    }
+
+    /// Returns an empty lockfile if there is no file at the given path.
+    pub fn read(path: &Path) -> Result<Self, LockfileProblem> {
+        match fs::read_to_string(path) {
+            Ok(src) => Self::parse(&src),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(LockfileProblem::IoErr(err)),
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), LockfileProblem> {
+        fs::write(path, self.to_string()).map_err(LockfileProblem::IoErr)
+    }
+
+    pub fn get(&self, url: &str) -> Option<&LockedPackage> {
+        self.packages
+            .binary_search_by(|package| package.url.as_str().cmp(url))
+            .ok()
+            .map(|index| &self.packages[index])
+    }
+
+    /// The version each package was resolved to, for [crate::resolve::resolve] to prefer.
+    pub fn locked_versions(&self) -> BTreeMap<String, Version> {
+        self.packages
+            .iter()
+            .filter_map(|package| package.release.clone())
+            .collect()
+    }
+
+    /// Install every package (downloading it if it's not in the cache yet), and lock the current contents of each.
+    pub fn update<'a>(
+        roc_cache_dir: RocCacheDir<'_>,
+        urls: impl IntoIterator<Item = &'a str>,
+    ) -> Result<Self, LockfileProblem> {
+        let mut packages = Vec::new();
+
+        for url in urls {
+            let tree_hash = installed_tree_hash(roc_cache_dir, url)?;
+
+            packages.push(LockedPackage {
+                url: url.to_string(),
+                tree_hash,
+                release: None,
+            });
+        }
+
+        packages.sort_by(|a, b| a.url.cmp(&b.url));
+        packages.dedup_by(|a, b| a.url == b.url);
+
+        Ok(Self { packages })
+    }
+
+    /// Like [Lockfile::update], but also locks the packages of a [Resolution], recording the
+    /// version each one was resolved to.
+    pub fn update_resolved<'a>(
+        roc_cache_dir: RocCacheDir<'_>,
+        urls: impl IntoIterator<Item = &'a str>,
+        resolution: &Resolution,
+    ) -> Result<Self, LockfileProblem> {
+        let mut lockfile = Self::update(roc_cache_dir, urls)?;
+
+        for resolved in resolution.packages.iter() {
+            let tree_hash = installed_tree_hash(roc_cache_dir, &resolved.url)?;
+            let release = Some((resolved.name.clone(), resolved.version.clone()));
+
+            match lockfile
+                .packages
+                .binary_search_by(|package| package.url.cmp(&resolved.url))
+            {
+                Ok(index) => lockfile.packages[index].release = release,
+                Err(index) => lockfile.packages.insert(
+                    index,
+                    LockedPackage {
+                        url: resolved.url.clone(),
+                        tree_hash,
+                        release,
+                    },
+                ),
+            }
+        }
+
+        Ok(lockfile)
+    }
+
+    /// Install every package in `urls` (downloading it if it's not in the cache yet), and check
+    /// that each one is locked, and that its contents match the lockfile.
+    pub fn verify<'a>(
+        &self,
+        roc_cache_dir: RocCacheDir<'_>,
+        urls: impl IntoIterator<Item = &'a str>,
+    ) -> Result<(), LockfileProblem> {
+        for url in urls {
+            let locked = self
+                .get(url)
+                .ok_or_else(|| LockfileProblem::NotLocked(url.to_string()))?;
+            let actual = installed_tree_hash(roc_cache_dir, url)?;
+
+            if actual != locked.tree_hash {
+                return Err(LockfileProblem::TreeHashMismatch {
+                    url: url.to_string(),
+                    expected: locked.tree_hash.clone(),
+                    actual,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check that each dependency was resolved to a version it allows, and then [Lockfile::verify]
+    /// the package it was resolved to.
+    pub fn verify_dependencies(
+        &self,
+        roc_cache_dir: RocCacheDir<'_>,
+        dependencies: &[Dependency],
+    ) -> Result<(), LockfileProblem> {
+        for dependency in dependencies {
+            let resolved = self.packages.iter().find(|package| {
+                package.release.as_ref().is_some_and(|(name, version)| {
+                    *name == dependency.name && dependency.req.matches(version)
+                })
+            });
+
+            match resolved {
+                Some(package) => self.verify(roc_cache_dir, [package.url.as_str()])?,
+                None => return Err(LockfileProblem::Unresolved(dependency.clone())),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for Lockfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{HEADER}")?;
+
+        for package in self.packages.iter() {
+            match &package.release {
+                Some((name, version)) => {
+                    writeln!(f, "{} {} {name}@{version}", package.url, package.tree_hash)?
+                }
+                None => writeln!(f, "{} {}", package.url, package.tree_hash)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn installed_tree_hash(
+    roc_cache_dir: RocCacheDir<'_>,
+    url: &str,
+) -> Result<String, LockfileProblem> {
+    let (package_dir, _) =
+        install_package(roc_cache_dir, url).map_err(|problem| LockfileProblem::Install {
+            url: url.to_string(),
+            problem,
+        })?;
+
+    tree_hash(&package_dir).map_err(LockfileProblem::IoErr)
+}
+
+/// The base64url-encoded BLAKE3 hash of the relative paths and contents of all the files in a directory.
+pub fn tree_hash(dir: &Path) -> io::Result<String> {
+    let mut files = Vec::new();
+
+    for entry in walkdir::WalkDir::new(dir) {
+        let entry = entry?;
+
+        if entry.file_type().is_file() {
+            let relative_path = entry
+                .path()
+                .strip_prefix(dir)
+                .expect("walkdir only yields paths inside the directory");
+
+            // Always use forward slashes, so that the hash is the same on every OS
+            let relative_path = relative_path
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+
+            files.push((relative_path, entry.into_path()));
+        }
+    }
+
+    files.sort();
+
+    let mut hasher = blake3::Hasher::new();
+
+    for (relative_path, path) in files {
+        let bytes = fs::read(path)?;
+
+        // Lengths are included so that different path/content splits can't produce the same input.
+        hasher.update(&(relative_path.len() as u64).to_le_bytes());
+        hasher.update(relative_path.as_bytes());
+        hasher.update(&(bytes.len() as u64).to_le_bytes());
+        hasher.update(&bytes);
+    }
+
+    Ok(base64_url::encode(hasher.finalize().as_bytes()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const URL: &str = "https://example.com/roc-packages/hash.tar.br";
+
+    /// Puts a package in the cache as if it had been downloaded from [URL].
+    fn cached_package(cache_dir: &Path) -> std::path::PathBuf {
+        let package_dir = cache_dir.join("example.com/roc-packages/hash");
+
+        fs::create_dir_all(package_dir.join("Sub")).unwrap();
+        fs::write(package_dir.join("main.roc"), "package [Sub.Foo] {}").unwrap();
+        fs::write(package_dir.join("Sub/Foo.roc"), "module [foo]\n\nfoo = 1").unwrap();
+
+        package_dir
+    }
+
+    #[test]
+    fn roundtrip() {
+        let lockfile = Lockfile {
+            packages: vec![
+                LockedPackage {
+                    url: "https://a.example.com/x.tar.br".to_string(),
+                    tree_hash: "abc".to_string(),
+                    release: None,
+                },
+                LockedPackage {
+                    url: "https://b.example.com/y.tar.gz".to_string(),
+                    tree_hash: "def".to_string(),
+                    release: Some(("json".to_string(), Version::new(1, 2, 0))),
+                },
+            ],
+        };
+
+        assert_eq!(Lockfile::parse(&lockfile.to_string()).unwrap(), lockfile);
+        assert!(lockfile.get("https://b.example.com/y.tar.gz").is_some());
+        assert!(lockfile.get("https://c.example.com/z.tar.gz").is_none());
+        assert_eq!(
+            lockfile.locked_versions(),
+            BTreeMap::from([("json".to_string(), Version::new(1, 2, 0))])
+        );
+    }
+
+    #[test]
+    fn invalid_line() {
+        let src = format!("{HEADER}\n{URL}\n");
+
+        assert!(matches!(
+            Lockfile::parse(&src),
+            Err(LockfileProblem::InvalidLine { line_number: 2, .. })
+        ));
+
+        let src = format!("{HEADER}\n{URL} abc json@1.2\n");
+
+        assert!(matches!(
+            Lockfile::parse(&src),
+            Err(LockfileProblem::InvalidLine { line_number: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn verify_detects_modified_package() {
+        let cache_dir = tempfile::tempdir().unwrap();
+        let roc_cache_dir = RocCacheDir::Persistent(cache_dir.path());
+        let package_dir = cached_package(cache_dir.path());
+
+        let lockfile = Lockfile::update(roc_cache_dir, [URL]).unwrap();
+
+        assert!(lockfile.verify(roc_cache_dir, [URL]).is_ok());
+        assert!(matches!(
+            lockfile.verify(roc_cache_dir, ["https://example.com/other/hash.tar.br"]),
+            Err(LockfileProblem::NotLocked(_))
+        ));
+
+        fs::write(package_dir.join("Sub/Foo.roc"), "module [foo]\n\nfoo = 2").unwrap();
+
+        assert!(matches!(
+            lockfile.verify(roc_cache_dir, [URL]),
+            Err(LockfileProblem::TreeHashMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn verify_dependencies_needs_an_allowed_version() {
+        let cache_dir = tempfile::tempdir().unwrap();
+        let roc_cache_dir = RocCacheDir::Persistent(cache_dir.path());
+
+        cached_package(cache_dir.path());
+
+        let mut lockfile = Lockfile::update(roc_cache_dir, [URL]).unwrap();
+
+        lockfile.packages[0].release = Some(("json".to_string(), Version::new(1, 3, 1)));
+
+        let dependency = |src| Dependency::parse(src).unwrap();
+
+        assert!(lockfile
+            .verify_dependencies(roc_cache_dir, &[dependency("json ^1.2")])
+            .is_ok());
+        assert!(matches!(
+            lockfile.verify_dependencies(roc_cache_dir, &[dependency("json ^2.0")]),
+            Err(LockfileProblem::Unresolved(_))
+        ));
+        assert!(matches!(
+            lockfile.verify_dependencies(roc_cache_dir, &[dependency("csv")]),
+            Err(LockfileProblem::Unresolved(_))
+        ));
+    }
+}
@@ -0,0 +1,536 @@
+//! Picks one version of every package in a dependency graph, such that each package's version
+//! satisfies the [VersionReq] of everything that depends on it.
+//!
+//! Resolution is deterministic: packages are resolved in order of their names, and the newest
+//! allowed version of each one is tried first, except that a version recorded in the lockfile is
+//! kept for as long as it's still allowed. That way, adding a dependency doesn't upgrade any
+//! others unless it has to.
+use crate::semver::{SemverProblem, Version, VersionReq};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt::{self, Display};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dependency {
+    pub name: String,
+    pub req: VersionReq,
+}
+
+impl Dependency {
+    /// Parses a dependency the way package manifests list them: a package name, followed by a
+    /// [VersionReq], e.g. `json ^1.2`. A name on its own allows any version.
+    pub fn parse(src: &str) -> Result<Self, SemverProblem> {
+        let (name, req) = match src.trim().split_once(char::is_whitespace) {
+            Some((name, req)) => (name, VersionReq::parse(req)?),
+            None => (src.trim(), VersionReq::ANY),
+        };
+
+        if name.is_empty() {
+            return Err(SemverProblem::InvalidConstraint(src.to_string()));
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            req,
+        })
+    }
+}
+
+impl Display for Dependency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.name, self.req)
+    }
+}
+
+/// One published version of a package.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Release {
+    pub version: Version,
+    /// Where to download this version from; see [crate::https]
+    pub url: String,
+    pub dependencies: Vec<Dependency>,
+}
+
+/// Where the resolver finds out which versions of a package exist.
+pub trait PackageIndex {
+    /// Every published release of the package, in any order. Empty if there is no such package.
+    fn releases(&self, name: &str) -> Vec<Release>;
+}
+
+/// A [PackageIndex] that holds all of its releases in memory.
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryIndex {
+    packages: BTreeMap<String, Vec<Release>>,
+}
+
+impl InMemoryIndex {
+    pub fn add(&mut self, name: impl Into<String>, release: Release) {
+        self.packages.entry(name.into()).or_default().push(release);
+    }
+}
+
+impl PackageIndex for InMemoryIndex {
+    fn releases(&self, name: &str) -> Vec<Release> {
+        self.packages.get(name).cloned().unwrap_or_default()
+    }
+}
+
+/// The fields of a metadata file written by `roc publish` that the resolver needs.
+#[derive(Deserialize)]
+struct PublishedMetadata {
+    name: String,
+    version: String,
+    #[serde(default)]
+    dependencies: Vec<String>,
+    bundle: String,
+}
+
+/// Reads an index of every release in a package registry, from a directory of the metadata
+/// files `roc publish` writes next to each bundle. Each bundle is downloaded from `registry_url`.
+pub fn read_index(dir: &Path, registry_url: &str) -> io::Result<InMemoryIndex> {
+    let mut index = InMemoryIndex::default();
+    let invalid = |path: &Path, problem: &dyn Display| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "{} is not valid package metadata: {problem}",
+                path.display()
+            ),
+        )
+    };
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+
+        let metadata: PublishedMetadata =
+            serde_json::from_slice(&fs::read(&path)?).map_err(|err| invalid(&path, &err))?;
+        let version = Version::parse(&metadata.version).map_err(|err| invalid(&path, &err))?;
+        let dependencies = metadata
+            .dependencies
+            .iter()
+            .map(|dependency| Dependency::parse(dependency))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| invalid(&path, &err))?;
+
+        index.add(
+            metadata.name,
+            Release {
+                version,
+                url: format!("{}/{}", registry_url.trim_end_matches('/'), metadata.bundle),
+                dependencies,
+            },
+        );
+    }
+
+    Ok(index)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedPackage {
+    pub name: String,
+    pub version: Version,
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Resolution {
+    /// Sorted by name
+    pub packages: Vec<ResolvedPackage>,
+}
+
+impl Resolution {
+    pub fn get(&self, name: &str) -> Option<&ResolvedPackage> {
+        self.packages
+            .binary_search_by(|package| package.name.as_str().cmp(name))
+            .ok()
+            .map(|index| &self.packages[index])
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Requirer {
+    /// The app or package being resolved
+    Root,
+    Package {
+        name: String,
+        version: Version,
+    },
+}
+
+impl Display for Requirer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Requirer::Root => write!(f, "your package"),
+            Requirer::Package { name, version } => write!(f, "{name} {version}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Requirement {
+    pub required_by: Requirer,
+    pub req: VersionReq,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResolveProblem {
+    UnknownPackage {
+        name: String,
+        required_by: Requirer,
+    },
+    /// No version of the package satisfies all of these requirements at once.
+    Conflict {
+        name: String,
+        requirements: Vec<Requirement>,
+        /// Every published version, newest first
+        available: Vec<Version>,
+    },
+}
+
+impl Display for ResolveProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResolveProblem::UnknownPackage { name, required_by } => {
+                write!(
+                    f,
+                    "{required_by} depends on {name}, but there is no package with that name."
+                )
+            }
+            ResolveProblem::Conflict {
+                name,
+                requirements,
+                available,
+            } => {
+                writeln!(
+                    f,
+                    "No version of {name} satisfies everything that depends on it:"
+                )?;
+
+                for requirement in requirements {
+                    writeln!(
+                        f,
+                        "    {} requires {name} {}",
+                        requirement.required_by, requirement.req
+                    )?;
+                }
+
+                if available.is_empty() {
+                    write!(f, "There are no published versions of {name}.")
+                } else {
+                    let available: Vec<String> = available.iter().map(Version::to_string).collect();
+
+                    write!(
+                        f,
+                        "The published versions of {name} are: {}",
+                        available.join(", ")
+                    )
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+struct State {
+    selected: BTreeMap<String, Release>,
+    requirements: BTreeMap<String, Vec<Requirement>>,
+}
+
+/// Resolves the dependencies of the root package, preferring the `locked` version of each
+/// package if it's still allowed. If resolution fails, the problem explains the first conflict
+/// that was found, which is usually the one that caused it.
+pub fn resolve(
+    index: &impl PackageIndex,
+    root: &[Dependency],
+    locked: &BTreeMap<String, Version>,
+) -> Result<Resolution, ResolveProblem> {
+    let mut state = State::default();
+
+    for dependency in root {
+        state
+            .requirements
+            .entry(dependency.name.clone())
+            .or_default()
+            .push(Requirement {
+                required_by: Requirer::Root,
+                req: dependency.req.clone(),
+            });
+    }
+
+    let mut first_problem = None;
+
+    match solve(index, locked, state, &mut first_problem) {
+        Some(state) => Ok(Resolution {
+            packages: state
+                .selected
+                .into_iter()
+                .map(|(name, release)| ResolvedPackage {
+                    name,
+                    version: release.version,
+                    url: release.url,
+                })
+                .collect(),
+        }),
+        None => Err(first_problem.expect("resolution only fails after recording a problem")),
+    }
+}
+
+fn solve(
+    index: &impl PackageIndex,
+    locked: &BTreeMap<String, Version>,
+    state: State,
+    first_problem: &mut Option<ResolveProblem>,
+) -> Option<State> {
+    let name = match state
+        .requirements
+        .keys()
+        .find(|name| !state.selected.contains_key(*name))
+    {
+        Some(name) => name.clone(),
+        None => return Some(state),
+    };
+
+    let requirements = &state.requirements[&name];
+    let releases = index.releases(&name);
+
+    if releases.is_empty() {
+        first_problem.get_or_insert_with(|| ResolveProblem::UnknownPackage {
+            name: name.clone(),
+            required_by: requirements[0].required_by.clone(),
+        });
+
+        return None;
+    }
+
+    let mut candidates: Vec<&Release> = releases
+        .iter()
+        .filter(|release| {
+            requirements
+                .iter()
+                .all(|requirement| requirement.req.matches(&release.version))
+        })
+        .collect();
+
+    if candidates.is_empty() {
+        first_problem.get_or_insert_with(|| conflict(&name, requirements.clone(), &releases));
+
+        return None;
+    }
+
+    // Newest first, but try the locked version before any others
+    candidates.sort_by(|a, b| b.version.cmp(&a.version));
+    candidates.sort_by_key(|release| Some(&release.version) != locked.get(&name));
+
+    'candidates: for release in candidates {
+        let mut next = state.clone();
+        let required_by = Requirer::Package {
+            name: name.clone(),
+            version: release.version.clone(),
+        };
+
+        for dependency in release.dependencies.iter() {
+            let requirements = next
+                .requirements
+                .entry(dependency.name.clone())
+                .or_default();
+
+            requirements.push(Requirement {
+                required_by: required_by.clone(),
+                req: dependency.req.clone(),
+            });
+
+            // This dependency may already have been resolved to a version it doesn't allow
+            if let Some(selected) = next.selected.get(&dependency.name) {
+                if !dependency.req.matches(&selected.version) {
+                    let requirements = requirements.clone();
+
+                    first_problem.get_or_insert_with(|| {
+                        conflict(
+                            &dependency.name,
+                            requirements,
+                            &index.releases(&dependency.name),
+                        )
+                    });
+
+                    continue 'candidates;
+                }
+            }
+        }
+
+        next.selected.insert(name.clone(), release.clone());
+
+        if let Some(solved) = solve(index, locked, next, first_problem) {
+            return Some(solved);
+        }
+    }
+
+    None
+}
+
+fn conflict(name: &str, requirements: Vec<Requirement>, releases: &[Release]) -> ResolveProblem {
+    let mut available: Vec<Version> = releases
+        .iter()
+        .map(|release| release.version.clone())
+        .collect();
+
+    available.sort_by(|a, b| b.cmp(a));
+
+    ResolveProblem::Conflict {
+        name: name.to_string(),
+        requirements,
+        available,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn dep(name: &str, req: &str) -> Dependency {
+        Dependency {
+            name: name.to_string(),
+            req: VersionReq::parse(req).unwrap(),
+        }
+    }
+
+    fn release(version: &str, dependencies: Vec<Dependency>) -> Release {
+        Release {
+            version: Version::parse(version).unwrap(),
+            url: format!("https://example.com/{version}.tar.br"),
+            dependencies,
+        }
+    }
+
+    fn versions(resolution: &Resolution) -> Vec<(&str, String)> {
+        resolution
+            .packages
+            .iter()
+            .map(|package| (package.name.as_str(), package.version.to_string()))
+            .collect()
+    }
+
+    fn index() -> InMemoryIndex {
+        let mut index = InMemoryIndex::default();
+
+        index.add("json", release("1.2.0", vec![]));
+        index.add("json", release("1.3.1", vec![]));
+        index.add("json", release("2.0.0", vec![]));
+        index.add("csv", release("0.2.0", vec![dep("json", "^1.2")]));
+        index.add("csv", release("0.3.0", vec![dep("json", "^2.0")]));
+
+        index
+    }
+
+    #[test]
+    fn picks_newest_allowed_versions() {
+        let resolution = resolve(&index(), &[dep("csv", "^0.3")], &BTreeMap::new()).unwrap();
+
+        assert_eq!(
+            versions(&resolution),
+            [("csv", "0.3.0".to_string()), ("json", "2.0.0".to_string())]
+        );
+        assert_eq!(
+            resolution.get("json").unwrap().url,
+            "https://example.com/2.0.0.tar.br"
+        );
+    }
+
+    #[test]
+    fn backtracks_to_an_older_version() {
+        let root = [dep("csv", "*"), dep("json", "^1.2")];
+        let resolution = resolve(&index(), &root, &BTreeMap::new()).unwrap();
+
+        assert_eq!(
+            versions(&resolution),
+            [("csv", "0.2.0".to_string()), ("json", "1.3.1".to_string())]
+        );
+    }
+
+    #[test]
+    fn keeps_locked_versions() {
+        let locked = BTreeMap::from([("json".to_string(), Version::new(1, 2, 0))]);
+        let resolution = resolve(&index(), &[dep("json", "^1.2")], &locked).unwrap();
+
+        assert_eq!(versions(&resolution), [("json", "1.2.0".to_string())]);
+
+        // A locked version that's no longer allowed is replaced
+        let resolution = resolve(&index(), &[dep("json", "^2")], &locked).unwrap();
+
+        assert_eq!(versions(&resolution), [("json", "2.0.0".to_string())]);
+    }
+
+    #[test]
+    fn explains_conflicts() {
+        let root = [dep("csv", "^0.3"), dep("json", "^1.2")];
+        let problem = resolve(&index(), &root, &BTreeMap::new()).unwrap_err();
+
+        assert_eq!(
+            problem.to_string(),
+            "No version of json satisfies everything that depends on it:\n    \
+            your package requires json ^1.2\n    \
+            csv 0.3.0 requires json ^2.0\n\
+            The published versions of json are: 2.0.0, 1.3.1, 1.2.0"
+        );
+    }
+
+    #[test]
+    fn parse_dependency() {
+        assert_eq!(Dependency::parse("json ^1.2").unwrap(), dep("json", "^1.2"));
+        assert_eq!(
+            Dependency::parse("json >=1.0.0, <3.0.0").unwrap(),
+            dep("json", ">=1.0.0, <3.0.0")
+        );
+        assert_eq!(Dependency::parse("json").unwrap(), dep("json", "*"));
+        assert!(Dependency::parse("json 1.x.y").is_err());
+        assert_eq!(dep("json", "^1.2").to_string(), "json ^1.2");
+    }
+
+    #[test]
+    fn index_of_published_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+
+        std::fs::write(
+            dir.path().join("abc.json"),
+            r#"{ "name": "csv", "version": "0.2.0", "dependencies": ["json ^1.2"], "bundle": "abc.tar.br" }"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("def.json"),
+            r#"{ "name": "json", "version": "1.3.1", "bundle": "def.tar.br" }"#,
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("def.tar.br"), "").unwrap();
+
+        let index = read_index(dir.path(), "https://example.com/packages/").unwrap();
+        let resolution = resolve(&index, &[dep("csv", "*")], &BTreeMap::new()).unwrap();
+
+        assert_eq!(
+            versions(&resolution),
+            [("csv", "0.2.0".to_string()), ("json", "1.3.1".to_string())]
+        );
+        assert_eq!(
+            resolution.get("json").unwrap().url,
+            "https://example.com/packages/def.tar.br"
+        );
+    }
+
+    #[test]
+    fn unknown_package() {
+        let problem = resolve(&index(), &[dep("xml", "^1")], &BTreeMap::new()).unwrap_err();
+
+        assert_eq!(
+            problem,
+            ResolveProblem::UnknownPackage {
+                name: "xml".to_string(),
+                required_by: Requirer::Root,
+            }
+        );
+    }
+}
@@ -0,0 +1,511 @@
+//! Semantic versions (like `1.2.3` or `2.0.0-beta.1`) and the constraints packages use to say
+//! which versions of a dependency they are compatible with (like `^1.2` or `>=1.0.0, <3.0.0`).
+//!
+//! Constraints follow the same rules as Cargo's:
+//!
+//! - `^1.2.3` (or just `1.2.3`) allows any version that doesn't change the leftmost nonzero part,
+//!   so `>=1.2.3, <2.0.0`, and `^0.2.3` means `>=0.2.3, <0.3.0`.
+//! - `~1.2.3` allows patch updates, so `>=1.2.3, <1.3.0`.
+//! - `=1.2.3` allows only that version, `*` allows any version, and `>`, `>=`, `<` and `<=`
+//!   compare as usual.
+//! - A prerelease like `2.0.0-beta.1` is only allowed by a constraint that mentions a prerelease
+//!   of the same `major.minor.patch`, so that `^1.0` never picks up an unstable release.
+use std::cmp::Ordering;
+use std::fmt::{self, Display};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    /// The dot-separated identifiers after the `-`, e.g. `["beta", "1"]` for `2.0.0-beta.1`
+    pub pre: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SemverProblem {
+    InvalidVersion(String),
+    InvalidConstraint(String),
+}
+
+impl Display for SemverProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SemverProblem::InvalidVersion(version) => write!(
+                f,
+                "\"{version}\" is not a valid version; versions look like 1.2.3 or 1.2.3-beta.1"
+            ),
+            SemverProblem::InvalidConstraint(constraint) => write!(
+                f,
+                "\"{constraint}\" is not a valid version constraint; constraints look like ^1.2, ~1.2.3 or >=1.0.0, <2.0.0"
+            ),
+        }
+    }
+}
+
+impl Version {
+    pub const fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+            pre: Vec::new(),
+        }
+    }
+
+    pub fn parse(src: &str) -> Result<Self, SemverProblem> {
+        let invalid = || SemverProblem::InvalidVersion(src.to_string());
+
+        let (numbers, pre) = match src.split_once('-') {
+            Some((numbers, pre)) => (numbers, Some(pre)),
+            None => (src, None),
+        };
+
+        let mut parts = numbers.split('.').map(parse_number);
+        let (major, minor, patch) = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(Some(major)), Some(Some(minor)), Some(Some(patch)), None) => {
+                (major, minor, patch)
+            }
+            _ => return Err(invalid()),
+        };
+
+        let pre = match pre {
+            Some(pre) => {
+                let identifiers: Vec<String> = pre.split('.').map(str::to_string).collect();
+
+                if identifiers.iter().any(|identifier| {
+                    identifier.is_empty()
+                        || !identifier
+                            .chars()
+                            .all(|c| c.is_ascii_alphanumeric() || c == '-')
+                }) {
+                    return Err(invalid());
+                }
+
+                identifiers
+            }
+            None => Vec::new(),
+        };
+
+        Ok(Self {
+            major,
+            minor,
+            patch,
+            pre,
+        })
+    }
+
+    pub fn is_prerelease(&self) -> bool {
+        !self.pre.is_empty()
+    }
+
+    fn same_release(&self, other: &Version) -> bool {
+        (self.major, self.minor, self.patch) == (other.major, other.minor, other.patch)
+    }
+}
+
+/// Digits only, without a leading zero, like semver requires.
+fn parse_number(src: &str) -> Option<u64> {
+    if src.is_empty()
+        || !src.bytes().all(|b| b.is_ascii_digit())
+        || (src.len() > 1 && src.starts_with('0'))
+    {
+        None
+    } else {
+        src.parse().ok()
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (self.pre.is_empty(), other.pre.is_empty()) {
+                (true, true) => Ordering::Equal,
+                // A prerelease comes before the release it's for
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => compare_pre(&self.pre, &other.pre),
+            })
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Numeric identifiers compare as numbers and sort before alphanumeric ones, which compare as
+/// strings. If one list is a prefix of the other, the shorter one comes first.
+fn compare_pre(a: &[String], b: &[String]) -> Ordering {
+    for (a, b) in a.iter().zip(b.iter()) {
+        let ordering = match (a.parse::<u64>(), b.parse::<u64>()) {
+            (Ok(a), Ok(b)) => a.cmp(&b),
+            (Ok(_), Err(_)) => Ordering::Less,
+            (Err(_), Ok(_)) => Ordering::Greater,
+            (Err(_), Err(_)) => a.cmp(b),
+        };
+
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+
+    a.len().cmp(&b.len())
+}
+
+impl Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+
+        if !self.pre.is_empty() {
+            write!(f, "-{}", self.pre.join("."))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Exact,
+    Greater,
+    GreaterEq,
+    Less,
+    LessEq,
+    Tilde,
+    Caret,
+}
+
+/// One part of a [VersionReq], e.g. `>=1.0.0`. Missing minor and patch numbers are kept as
+/// `None`, since `^1` and `^1.0.0` allow different versions when the major version is 0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Comparator {
+    op: Op,
+    major: u64,
+    minor: Option<u64>,
+    patch: Option<u64>,
+    pre: Vec<String>,
+}
+
+impl Comparator {
+    fn parse(src: &str) -> Option<Self> {
+        let src = src.trim();
+        let (op, rest) = [
+            (">=", Op::GreaterEq),
+            ("<=", Op::LessEq),
+            (">", Op::Greater),
+            ("<", Op::Less),
+            ("=", Op::Exact),
+            ("~", Op::Tilde),
+            ("^", Op::Caret),
+        ]
+        .into_iter()
+        .find_map(|(prefix, op)| src.strip_prefix(prefix).map(|rest| (op, rest)))
+        .unwrap_or((Op::Caret, src));
+
+        let rest = rest.trim_start();
+        let (numbers, pre) = match rest.split_once('-') {
+            Some((numbers, pre)) => (numbers, Some(pre)),
+            None => (rest, None),
+        };
+
+        let mut parts = numbers.split('.');
+        let major = parse_number(parts.next()?)?;
+        let minor = parts.next().map(parse_number);
+        let patch = parts.next().map(parse_number);
+
+        if parts.next().is_some() || minor == Some(None) || patch == Some(None) {
+            return None;
+        }
+
+        let (minor, patch) = (minor.flatten(), patch.flatten());
+
+        let pre = match pre {
+            // A prerelease only makes sense on a full version
+            Some(pre) if patch.is_some() => Version::parse(&format!("0.0.0-{pre}")).ok()?.pre,
+            Some(_) => return None,
+            None => Vec::new(),
+        };
+
+        Some(Self {
+            op,
+            major,
+            minor,
+            patch,
+            pre,
+        })
+    }
+
+    fn lowest(&self) -> Version {
+        Version {
+            major: self.major,
+            minor: self.minor.unwrap_or(0),
+            patch: self.patch.unwrap_or(0),
+            pre: self.pre.clone(),
+        }
+    }
+
+    fn matches(&self, version: &Version) -> bool {
+        let lowest = self.lowest();
+
+        match self.op {
+            Op::Exact => match (self.minor, self.patch) {
+                (Some(_), Some(_)) => *version == lowest,
+                (Some(minor), None) => version.major == self.major && version.minor == minor,
+                (None, _) => version.major == self.major,
+            },
+            Op::Greater => match (self.minor, self.patch) {
+                (Some(_), Some(_)) => *version > lowest,
+                (Some(minor), None) => (version.major, version.minor) > (self.major, minor),
+                (None, _) => version.major > self.major,
+            },
+            Op::GreaterEq => *version >= lowest,
+            Op::Less => *version < lowest,
+            Op::LessEq => match (self.minor, self.patch) {
+                (Some(_), Some(_)) => *version <= lowest,
+                (Some(minor), None) => (version.major, version.minor) <= (self.major, minor),
+                (None, _) => version.major <= self.major,
+            },
+            Op::Tilde => {
+                *version >= lowest
+                    && match self.minor {
+                        Some(minor) => version.major == self.major && version.minor == minor,
+                        None => version.major == self.major,
+                    }
+            }
+            Op::Caret => {
+                *version >= lowest
+                    && match (self.major, self.minor, self.patch) {
+                        (0, Some(0), Some(patch)) => {
+                            version.major == 0 && version.minor == 0 && version.patch == patch
+                        }
+                        (0, Some(minor), _) => version.major == 0 && version.minor == minor,
+                        (major, _, _) => version.major == major,
+                    }
+            }
+        }
+    }
+}
+
+impl Display for Comparator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let op = match self.op {
+            Op::Exact => "=",
+            Op::Greater => ">",
+            Op::GreaterEq => ">=",
+            Op::Less => "<",
+            Op::LessEq => "<=",
+            Op::Tilde => "~",
+            Op::Caret => "^",
+        };
+
+        write!(f, "{op}{}", self.major)?;
+
+        if let Some(minor) = self.minor {
+            write!(f, ".{minor}")?;
+        }
+
+        if let Some(patch) = self.patch {
+            write!(f, ".{patch}")?;
+        }
+
+        if !self.pre.is_empty() {
+            write!(f, "-{}", self.pre.join("."))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Which versions of a package are acceptable, e.g. `^1.2` or `>=1.0.0, <3.0.0`. A version has
+/// to satisfy every comma-separated comparator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionReq {
+    comparators: Vec<Comparator>,
+}
+
+impl VersionReq {
+    /// Allows any version that isn't a prerelease.
+    pub const ANY: Self = Self {
+        comparators: Vec::new(),
+    };
+
+    pub fn parse(src: &str) -> Result<Self, SemverProblem> {
+        let trimmed = src.trim();
+
+        if trimmed == "*" {
+            return Ok(Self::ANY);
+        }
+
+        let comparators = trimmed
+            .split(',')
+            .map(Comparator::parse)
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| SemverProblem::InvalidConstraint(src.to_string()))?;
+
+        Ok(Self { comparators })
+    }
+
+    /// Requires exactly this version.
+    pub fn exact(version: &Version) -> Self {
+        Self {
+            comparators: vec![Comparator {
+                op: Op::Exact,
+                major: version.major,
+                minor: Some(version.minor),
+                patch: Some(version.patch),
+                pre: version.pre.clone(),
+            }],
+        }
+    }
+
+    pub fn matches(&self, version: &Version) -> bool {
+        if version.is_prerelease()
+            && !self.comparators.iter().any(|comparator| {
+                comparator.patch.is_some()
+                    && comparator.lowest().is_prerelease()
+                    && comparator.lowest().same_release(version)
+            })
+        {
+            return false;
+        }
+
+        self.comparators
+            .iter()
+            .all(|comparator| comparator.matches(version))
+    }
+}
+
+impl Display for VersionReq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.comparators.is_empty() {
+            return write!(f, "*");
+        }
+
+        for (index, comparator) in self.comparators.iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+
+            write!(f, "{comparator}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn v(src: &str) -> Version {
+        Version::parse(src).unwrap()
+    }
+
+    fn req(src: &str) -> VersionReq {
+        VersionReq::parse(src).unwrap()
+    }
+
+    #[test]
+    fn parse_versions() {
+        assert_eq!(v("1.2.3"), Version::new(1, 2, 3));
+        assert_eq!(v("1.2.3-beta.1").pre, vec!["beta", "1"]);
+        assert_eq!(v("1.2.3-beta.1").to_string(), "1.2.3-beta.1");
+
+        for invalid in [
+            "1.2",
+            "1.2.3.4",
+            "01.2.3",
+            "1.2.x",
+            "1.2.3-",
+            "1.2.3-beta..1",
+            "",
+        ] {
+            assert!(Version::parse(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn order_versions() {
+        let mut versions = vec![
+            v("1.0.0"),
+            v("1.0.0-rc.1"),
+            v("0.9.10"),
+            v("1.0.0-alpha"),
+            v("1.0.0-alpha.1"),
+            v("0.9.9"),
+            v("1.0.0-alpha.beta"),
+        ];
+
+        versions.sort();
+
+        let sorted: Vec<String> = versions.iter().map(Version::to_string).collect();
+
+        assert_eq!(
+            sorted,
+            [
+                "0.9.9",
+                "0.9.10",
+                "1.0.0-alpha",
+                "1.0.0-alpha.1",
+                "1.0.0-alpha.beta",
+                "1.0.0-rc.1",
+                "1.0.0"
+            ]
+        );
+    }
+
+    #[test]
+    fn caret_and_tilde() {
+        assert!(req("^1.2").matches(&v("1.9.0")));
+        assert!(req("1.2.3").matches(&v("1.2.4")));
+        assert!(!req("^1.2.3").matches(&v("2.0.0")));
+        assert!(!req("^1.2.3").matches(&v("1.2.2")));
+
+        assert!(req("^0.2.3").matches(&v("0.2.9")));
+        assert!(!req("^0.2.3").matches(&v("0.3.0")));
+        assert!(!req("^0.0.3").matches(&v("0.0.4")));
+        assert!(req("^0").matches(&v("0.9.0")));
+
+        assert!(req("~1.2.3").matches(&v("1.2.9")));
+        assert!(!req("~1.2.3").matches(&v("1.3.0")));
+        assert!(req("~1").matches(&v("1.9.0")));
+    }
+
+    #[test]
+    fn ranges() {
+        let range = req(">=1.0.0, <3.0.0");
+
+        assert!(range.matches(&v("1.0.0")));
+        assert!(range.matches(&v("2.9.9")));
+        assert!(!range.matches(&v("3.0.0")));
+        assert!(!range.matches(&v("0.9.0")));
+
+        assert!(req("<=1.2").matches(&v("1.2.9")));
+        assert!(!req(">1.2").matches(&v("1.2.9")));
+        assert!(req("=1.2").matches(&v("1.2.5")));
+        assert!(req("*").matches(&v("42.0.0")));
+    }
+
+    #[test]
+    fn prereleases_need_to_be_asked_for() {
+        assert!(!req("^1.0").matches(&v("1.1.0-beta.1")));
+        assert!(!req("*").matches(&v("1.0.0-rc.1")));
+        assert!(req("^1.1.0-beta.1").matches(&v("1.1.0-beta.2")));
+        assert!(req("^1.1.0-beta.1").matches(&v("1.2.0")));
+        assert!(!req("^1.1.0-beta.1").matches(&v("1.2.0-beta.1")));
+    }
+
+    #[test]
+    fn display_roundtrips() {
+        for src in ["^1.2", "~1.2.3", ">=1.0.0, <3.0.0", "=2.0.0-rc.1", "*"] {
+            assert_eq!(req(src).to_string(), src);
+        }
+
+        assert_eq!(req("1.2").to_string(), "^1.2");
+        assert!(VersionReq::parse("^1.x").is_err());
+        assert!(VersionReq::parse("^1.2-beta").is_err());
+    }
+}
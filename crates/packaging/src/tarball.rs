@@ -64,6 +64,17 @@ impl<'a> TryFrom<&'a str> for Compression {
 /// so the caller can obtain the path to the file by calling
 /// Path::with_file_name(returned_string) on the Path argument it provided.
 pub fn build(path_to_main: &Path, compression: Compression) -> io::Result<String> {
+    let dest_dir = path_to_main.parent().unwrap_or_else(|| Path::new(""));
+
+    build_in(path_to_main, compression, dest_dir)
+}
+
+/// Like [build], but writes the .tar file to `dest_dir` instead of next to the .roc file.
+pub fn build_in(
+    path_to_main: &Path,
+    compression: Compression,
+    dest_dir: &Path,
+) -> io::Result<String> {
     let mut archive_bytes = Vec::new();
 
     write_archive(path_to_main, &mut archive_bytes)?;
@@ -79,7 +90,7 @@ pub fn build(path_to_main: &Path, compression: Compression) -> io::Result<String
 
     // Write the bytes to disk.
     {
-        let dest_path = path_to_main.with_file_name(&filename);
+        let dest_path = dest_dir.join(&filename);
         let mut file = File::create(&dest_path).unwrap_or_else(|err| {
             panic!(
                 "Unable to open {} for writing - error was: {:?}",
@@ -215,6 +215,10 @@ fn mono_module_to_dylib_llvm<'a>(
         mode: LlvmBackendMode::GenTest, // so roc_panic is generated
         // important! we don't want any procedures to get the C calling convention
         exposed_to_host: MutSet::default(),
+        coverage: None,
+        profile_hints: None,
+        deterministic: false,
+        proc_names: Default::default(),
     };
 
     // Add roc_alloc, roc_realloc, and roc_dealloc, since the repl has no
@@ -330,6 +334,7 @@ fn mono_module_to_dylib_asm<'a>(
             builtins_host_tempfile.path().to_str().unwrap(),
         ],
         roc_build::link::LinkType::Dylib,
+        false,
     )
     .expect("failed to link dynamic library");
 
@@ -8,7 +8,10 @@ use roc_mono::ir::OptLevel;
 use roc_repl_eval::gen::Problems;
 use roc_repl_ui::colors::{CYAN, END_COL};
 use roc_repl_ui::repl_state::{ReplAction, ReplState};
-use roc_repl_ui::{format_output, is_incomplete, CONT_PROMPT, PROMPT, SHORT_INSTRUCTIONS, TIPS};
+use roc_repl_ui::{
+    format_input, format_loaded, format_output, is_incomplete, CONT_PROMPT, PROMPT,
+    SHORT_INSTRUCTIONS, TIPS,
+};
 use roc_reporting::report::{
     strip_colors, to_file_problem_report_string, ANSI_STYLE_CODES, DEFAULT_PALETTE,
 };
@@ -98,15 +101,19 @@ pub fn main(has_color: bool, has_header: bool) -> i32 {
             Ok(line) => {
                 let line = line.trim();
 
-                editor.add_history_entry(line);
+                arena.reset();
+
+                // Multi-line input gets formatted before it goes into the history,
+                // so that recalling it later shows it the way roc format would.
+                editor.add_history_entry(format_input(&arena, line).unwrap_or(line));
+
+                arena.reset();
 
                 let repl_state = &mut editor
                     .helper_mut()
                     .expect("Editor helper was not set")
                     .state;
 
-                arena.reset();
-
                 let action = std::panic::catch_unwind(AssertUnwindSafe(|| {
                     repl_state.step(&arena, line, target, DEFAULT_PALETTE)
                 }))
@@ -121,6 +128,22 @@ pub fn main(has_color: bool, has_header: bool) -> i32 {
                             println!("{}", strip_colors_if_necessary(&output));
                         }
                     }
+                    ReplAction::TypeOf {
+                        opt_output,
+                        problems,
+                    } => {
+                        let output = format_output(ANSI_STYLE_CODES, opt_output, problems);
+
+                        if !output.is_empty() {
+                            println!("{}", strip_colors_if_necessary(&output));
+                        }
+                    }
+                    ReplAction::Loaded { filename, idents } => {
+                        println!("{}", format_loaded(&filename, &idents));
+                    }
+                    ReplAction::Reset => {
+                        println!("Cleared all definitions.");
+                    }
                     ReplAction::Exit => {
                         return 0;
                     }
@@ -13,6 +13,7 @@ use roc_region::all::LineInfo;
 use roc_reporting::report::{can_problem, type_problem, RocDocAllocator};
 use roc_solve::FunctionKind;
 use roc_target::Target;
+use roc_types::pretty_print::{name_and_print_var, DebugPrint};
 
 #[derive(Debug)]
 pub struct ReplOutput {
@@ -29,6 +30,8 @@ pub fn format_answer<'a>(arena: &'a Bump, answer: Expr<'_>) -> &'a str {
                 roc_fmt::MigrationFlags {
                     snakify: false,
                     parens_and_commas: false,
+                    dbg: roc_fmt::DbgMigration::Keep,
+                    transforms: &[],
                 },
             );
 
@@ -75,6 +78,11 @@ pub fn compile_to_mono<'a, 'i, I: Iterator<Item = &'i str>>(
             palette,
             threading: Threading::Single,
             exec_mode: ExecutionMode::Executable,
+            emit_match_trees: false,
+            trace_abilities: false,
+            profile: None,
+            doc_examples: 0,
+            comptime_limits: roc_load::ComptimeLimits::default(),
         },
     );
 
@@ -174,6 +182,20 @@ pub fn compile_to_mono<'a, 'i, I: Iterator<Item = &'i str>>(
     (Some(loaded), problems)
 }
 
+/// Pretty-print the type of the expression that was compiled into `repl_output`.
+pub fn repl_output_type(loaded: &mut MonomorphizedModule<'_>) -> Option<String> {
+    let (_, main_fn_var) = loaded.exposed_to_host.top_level_values.iter().next()?;
+    let main_fn_var = *main_fn_var;
+
+    Some(name_and_print_var(
+        main_fn_var,
+        &mut loaded.subs,
+        loaded.module_id,
+        &loaded.interns,
+        DebugPrint::NOTHING,
+    ))
+}
+
 fn promote_expr_to_module<'a, 'i, I: Iterator<Item = &'i str>>(
     arena: &'a Bump,
     defs: I,
@@ -0,0 +1,307 @@
+//! Benchmarks are top-level `expect`s with a `# bench` comment on the line right above them:
+//!
+//! ```roc
+//! # bench
+//! expect List.sort_asc (List.range { start: At 0, end: Before 10_000 }) |> List.len == 10_000
+//! ```
+//!
+//! `roc test` runs them once like any other expect, and `roc test --bench` runs only them,
+//! repeatedly, and reports statistics about how long each one took.
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use roc_region::all::Region;
+
+use crate::results::ExpectOutcome;
+
+/// The comment that marks an expect as a benchmark.
+pub const BENCH_MARKER: &str = "# bench";
+
+/// Whether the expect at the given region is a benchmark; see [BENCH_MARKER].
+pub fn is_benchmark(source: &str, region: Region) -> bool {
+    let start = (region.start().offset as usize).min(source.len());
+    let before = &source[..start];
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+
+    before[..line_start]
+        .lines()
+        .next_back()
+        .is_some_and(|line| line.trim() == BENCH_MARKER)
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BenchConfig {
+    /// How long to run each benchmark before measuring it, so that caches are warm. This is
+    /// also used to work out how many times to run it per sample.
+    pub warmup_time: Duration,
+    /// Fast benchmarks are run repeatedly in each sample until it takes at least this long, so
+    /// that timer resolution doesn't dominate the measurement.
+    pub min_sample_time: Duration,
+    pub samples: usize,
+}
+
+impl Default for BenchConfig {
+    fn default() -> Self {
+        Self {
+            warmup_time: Duration::from_millis(200),
+            min_sample_time: Duration::from_millis(10),
+            samples: 30,
+        }
+    }
+}
+
+impl BenchConfig {
+    /// How many times to run a benchmark per sample, given how long one run took on average
+    /// while warming up.
+    pub fn iterations_per_sample(&self, mean_warmup_time: Duration) -> u64 {
+        if mean_warmup_time.is_zero() {
+            return 1_000;
+        }
+
+        let iterations = self.min_sample_time.as_nanos() / mean_warmup_time.as_nanos();
+
+        iterations.clamp(1, 1_000_000) as u64
+    }
+}
+
+/// Statistics about how long a single run of a benchmark took.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BenchStats {
+    pub mean: Duration,
+    pub median: Duration,
+    pub std_dev: Duration,
+    pub min: Duration,
+    pub max: Duration,
+}
+
+impl BenchStats {
+    /// Returns `None` if there are no samples.
+    pub fn from_samples(samples: &[Duration]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+
+        let nanos: Vec<f64> = sorted
+            .iter()
+            .map(|sample| sample.as_nanos() as f64)
+            .collect();
+        let count = nanos.len() as f64;
+        let mean = nanos.iter().sum::<f64>() / count;
+        let variance = if nanos.len() > 1 {
+            nanos.iter().map(|n| (n - mean).powi(2)).sum::<f64>() / (count - 1.0)
+        } else {
+            0.0
+        };
+        let median = if nanos.len() % 2 == 0 {
+            (nanos[nanos.len() / 2 - 1] + nanos[nanos.len() / 2]) / 2.0
+        } else {
+            nanos[nanos.len() / 2]
+        };
+
+        let from_nanos = |n: f64| Duration::from_nanos(n.round() as u64);
+
+        Some(Self {
+            mean: from_nanos(mean),
+            median: from_nanos(median),
+            std_dev: from_nanos(variance.sqrt()),
+            min: sorted[0],
+            max: sorted[sorted.len() - 1],
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BenchResult {
+    pub module: String,
+    /// The first line of the expect's condition, like [crate::results::ExpectResult::name]
+    pub name: String,
+    pub path: PathBuf,
+    /// 1-based, to match what editors show.
+    pub line: u32,
+    /// Benchmarks are only measured if their first run passes.
+    pub outcome: ExpectOutcome,
+    /// The rendered report explaining why the benchmark failed or panicked.
+    pub failure: Option<String>,
+    pub iterations_per_sample: u64,
+    /// `None` if the benchmark didn't pass.
+    pub stats: Option<BenchStats>,
+}
+
+pub fn bench_results_to_json(results: &[BenchResult]) -> String {
+    let millis = |duration: Duration| duration.as_secs_f64() * 1000.0;
+
+    let benchmarks: Vec<_> = results
+        .iter()
+        .map(|result| {
+            let stats = result.stats.map(|stats| {
+                serde_json::json!({
+                    "mean_ms": millis(stats.mean),
+                    "median_ms": millis(stats.median),
+                    "std_dev_ms": millis(stats.std_dev),
+                    "min_ms": millis(stats.min),
+                    "max_ms": millis(stats.max),
+                })
+            });
+
+            serde_json::json!({
+                "module": result.module,
+                "name": result.name,
+                "path": result.path.display().to_string(),
+                "line": result.line,
+                "outcome": result.outcome.as_str(),
+                "failure": result.failure,
+                "iterations_per_sample": result.iterations_per_sample,
+                "stats": stats,
+            })
+        })
+        .collect();
+
+    let json = serde_json::json!({ "benchmarks": benchmarks });
+
+    serde_json::to_string_pretty(&json).expect("JSON values always serialize")
+}
+
+/// A table with a row per benchmark, for people to read.
+pub fn render_bench_results(results: &[BenchResult]) -> String {
+    let name_width = results
+        .iter()
+        .map(|result| result.module.len() + result.name.len() + 3)
+        .max()
+        .unwrap_or(0)
+        .max("benchmark".len());
+
+    let mut buf = String::new();
+
+    writeln!(
+        buf,
+        "{:<name_width$}  {:>10}  {:>10}  {:>10}  {:>10}",
+        "benchmark", "mean", "median", "± std dev", "min"
+    )
+    .unwrap();
+
+    for result in results {
+        let name = format!("{}: {}", result.module, result.name);
+
+        match result.stats {
+            Some(stats) => writeln!(
+                buf,
+                "{name:<name_width$}  {:>10}  {:>10}  {:>10}  {:>10}",
+                format_duration(stats.mean),
+                format_duration(stats.median),
+                format_duration(stats.std_dev),
+                format_duration(stats.min),
+            )
+            .unwrap(),
+            None => writeln!(buf, "{name:<name_width$}  {}", result.outcome.as_str()).unwrap(),
+        }
+    }
+
+    buf
+}
+
+/// Formats a duration with 3 significant digits and the largest unit that keeps it above 1.
+fn format_duration(duration: Duration) -> String {
+    let nanos = duration.as_nanos() as f64;
+
+    let (value, unit) = if nanos < 1_000.0 {
+        (nanos, "ns")
+    } else if nanos < 1_000_000.0 {
+        (nanos / 1_000.0, "µs")
+    } else if nanos < 1_000_000_000.0 {
+        (nanos / 1_000_000.0, "ms")
+    } else {
+        (nanos / 1_000_000_000.0, "s")
+    };
+
+    if value < 10.0 {
+        format!("{value:.2} {unit}")
+    } else if value < 100.0 {
+        format!("{value:.1} {unit}")
+    } else {
+        format!("{value:.0} {unit}")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use roc_region::all::Position;
+
+    fn region_at(source: &str, text: &str) -> Region {
+        let start = source.find(text).unwrap() as u32;
+
+        Region::new(
+            Position::new(start),
+            Position::new(start + text.len() as u32),
+        )
+    }
+
+    #[test]
+    fn marker() {
+        let source = "x = 1\n\n# bench\nexpect x == 1\n\nexpect x != 2\n";
+
+        assert!(is_benchmark(source, region_at(source, "expect x == 1")));
+        assert!(!is_benchmark(source, region_at(source, "expect x != 2")));
+
+        let source = "expect x == 1\n";
+
+        assert!(!is_benchmark(source, region_at(source, "expect x == 1")));
+    }
+
+    #[test]
+    fn stats() {
+        let samples = [4, 1, 3, 2].map(Duration::from_micros);
+        let stats = BenchStats::from_samples(&samples).unwrap();
+
+        assert_eq!(stats.mean, Duration::from_nanos(2_500));
+        assert_eq!(stats.median, Duration::from_nanos(2_500));
+        assert_eq!(stats.min, Duration::from_micros(1));
+        assert_eq!(stats.max, Duration::from_micros(4));
+        // The sample standard deviation of 1, 2, 3, 4 is √(5/3)
+        assert_eq!(stats.std_dev, Duration::from_nanos(1_291));
+
+        assert_eq!(BenchStats::from_samples(&[]), None);
+    }
+
+    #[test]
+    fn iterations_per_sample() {
+        let config = BenchConfig::default();
+
+        assert_eq!(
+            config.iterations_per_sample(Duration::from_micros(10)),
+            1_000
+        );
+        assert_eq!(config.iterations_per_sample(Duration::from_secs(1)), 1);
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(format_duration(Duration::from_nanos(512)), "512 ns");
+        assert_eq!(format_duration(Duration::from_nanos(1_234)), "1.23 µs");
+        assert_eq!(format_duration(Duration::from_micros(45_600)), "45.6 ms");
+    }
+
+    #[test]
+    fn json() {
+        let result = BenchResult {
+            module: "Test".to_string(),
+            name: "sort big_list |> List.len == 10".to_string(),
+            path: PathBuf::from("Test.roc"),
+            line: 4,
+            outcome: ExpectOutcome::Passed,
+            failure: None,
+            iterations_per_sample: 100,
+            stats: BenchStats::from_samples(&[Duration::from_millis(2)]),
+        };
+
+        let value: serde_json::Value =
+            serde_json::from_str(&bench_results_to_json(&[result])).unwrap();
+
+        assert_eq!(value["benchmarks"][0]["iterations_per_sample"], 100);
+        assert_eq!(value["benchmarks"][0]["stats"]["mean_ms"], 2.0);
+    }
+}
@@ -15,6 +15,10 @@ use {
 #[cfg(not(windows))]
 mod app;
 #[cfg(not(windows))]
+pub mod bench;
+#[cfg(not(windows))]
+pub mod results;
+#[cfg(not(windows))]
 pub mod run;
 
 #[cfg(not(windows))]
@@ -124,6 +128,11 @@ mod test {
             palette: DEFAULT_PALETTE,
             threading: Threading::Single,
             exec_mode: ExecutionMode::Test,
+            emit_match_trees: false,
+            trace_abilities: false,
+            profile: None,
+            doc_examples: 0,
+            comptime_limits: roc_load::ComptimeLimits::default(),
         };
         let loaded = match roc_load::load_and_monomorphize_from_str(
             arena,
@@ -147,9 +156,15 @@ mod test {
 
         let interns = loaded.interns.clone();
 
-        let (dy_lib, expects_by_module, layout_interner) =
-            expect_mono_module_to_dylib(arena, target, loaded, opt_level, LlvmBackendMode::CliTest)
-                .unwrap();
+        let (dy_lib, expects_by_module, layout_interner) = expect_mono_module_to_dylib(
+            arena,
+            target,
+            loaded,
+            opt_level,
+            LlvmBackendMode::CliTest,
+            None,
+        )
+        .unwrap();
 
         let arena = &bumpalo::Bump::new();
         let interns = arena.alloc(interns);
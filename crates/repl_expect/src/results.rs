@@ -0,0 +1,295 @@
+//! Structured results of running top-level `expect`s, so that CI tools can consume them.
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use roc_region::all::{LineInfo, Region};
+
+/// Selects which top-level `expect`s get run.
+#[derive(Debug, Default, Clone)]
+pub struct ExpectFilter {
+    /// Only run expects whose name contains this string.
+    pub name: Option<String>,
+    /// Only run expects in the module with exactly this name.
+    pub module: Option<String>,
+}
+
+impl ExpectFilter {
+    pub fn matches(&self, module_name: &str, expect_name: &str) -> bool {
+        let name_matches = match &self.name {
+            Some(name) => expect_name.contains(name.as_str()),
+            None => true,
+        };
+
+        let module_matches = match &self.module {
+            Some(module) => module_name == module,
+            None => true,
+        };
+
+        name_matches && module_matches
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpectOutcome {
+    Passed,
+    Failed,
+    /// The expect crashed before its condition could be checked.
+    Panicked,
+}
+
+impl ExpectOutcome {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            ExpectOutcome::Passed => "passed",
+            ExpectOutcome::Failed => "failed",
+            ExpectOutcome::Panicked => "panicked",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ExpectResult {
+    pub module: String,
+    /// The first line of the expect's condition, e.g. `List.len [] == 0`
+    pub name: String,
+    pub path: PathBuf,
+    /// 1-based, to match what editors show.
+    pub line: u32,
+    pub outcome: ExpectOutcome,
+    pub duration: Duration,
+    /// Everything the expect printed using `dbg`.
+    pub dbg_output: String,
+    /// The rendered report explaining why the expect failed or panicked.
+    pub failure: Option<String>,
+}
+
+/// The name and (1-based) line number of the expect at the given region.
+pub fn expect_name(source: &str, region: Region) -> (String, u32) {
+    let line = LineInfo::new(source).convert_region(region).start.line + 1;
+
+    let start = region.start().offset as usize;
+    let end = (region.end().offset as usize).min(source.len());
+    let first_line = source
+        .get(start..end)
+        .and_then(|src| src.lines().next())
+        .unwrap_or_default()
+        .trim();
+    let name = first_line
+        .strip_prefix("expect")
+        .unwrap_or(first_line)
+        .trim();
+
+    (name.to_string(), line)
+}
+
+pub fn results_to_json(results: &[ExpectResult]) -> String {
+    let tests: Vec<_> = results
+        .iter()
+        .map(|result| {
+            serde_json::json!({
+                "module": result.module,
+                "name": result.name,
+                "path": result.path.display().to_string(),
+                "line": result.line,
+                "outcome": result.outcome.as_str(),
+                "duration_ms": result.duration.as_secs_f64() * 1000.0,
+                "dbg_output": result.dbg_output,
+                "failure": result.failure,
+            })
+        })
+        .collect();
+
+    let count = |outcome| {
+        results
+            .iter()
+            .filter(|result| result.outcome == outcome)
+            .count()
+    };
+
+    let json = serde_json::json!({
+        "passed": count(ExpectOutcome::Passed),
+        "failed": count(ExpectOutcome::Failed),
+        "panicked": count(ExpectOutcome::Panicked),
+        "tests": tests,
+    });
+
+    serde_json::to_string_pretty(&json).expect("JSON values always serialize")
+}
+
+/// Render the results in the JUnit XML format, with one `<testsuite>` per module.
+pub fn results_to_junit_xml(results: &[ExpectResult]) -> String {
+    let mut modules: Vec<&str> = Vec::new();
+
+    for result in results {
+        if !modules.contains(&result.module.as_str()) {
+            modules.push(&result.module);
+        }
+    }
+
+    let mut buf = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites>\n");
+
+    for module in modules {
+        let suite: Vec<_> = results
+            .iter()
+            .filter(|result| result.module == module)
+            .collect();
+        let failures = suite
+            .iter()
+            .filter(|result| result.outcome == ExpectOutcome::Failed)
+            .count();
+        let errors = suite
+            .iter()
+            .filter(|result| result.outcome == ExpectOutcome::Panicked)
+            .count();
+        let time: f64 = suite
+            .iter()
+            .map(|result| result.duration.as_secs_f64())
+            .sum();
+
+        writeln!(
+            buf,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{failures}\" errors=\"{errors}\" time=\"{time:.6}\">",
+            xml_escape(module),
+            suite.len(),
+        )
+        .unwrap();
+
+        for result in suite {
+            writeln!(
+                buf,
+                "    <testcase classname=\"{}\" name=\"{}\" file=\"{}\" line=\"{}\" time=\"{:.6}\">",
+                xml_escape(module),
+                xml_escape(&result.name),
+                xml_escape(&result.path.display().to_string()),
+                result.line,
+                result.duration.as_secs_f64(),
+            )
+            .unwrap();
+
+            let failure = result.failure.as_deref().unwrap_or_default();
+
+            match result.outcome {
+                ExpectOutcome::Passed => {}
+                ExpectOutcome::Failed => {
+                    writeln!(
+                        buf,
+                        "      <failure message=\"expect failed\">{}</failure>",
+                        xml_escape(failure)
+                    )
+                    .unwrap();
+                }
+                ExpectOutcome::Panicked => {
+                    writeln!(
+                        buf,
+                        "      <error message=\"expect panicked\">{}</error>",
+                        xml_escape(failure)
+                    )
+                    .unwrap();
+                }
+            }
+
+            if !result.dbg_output.is_empty() {
+                writeln!(
+                    buf,
+                    "      <system-err>{}</system-err>",
+                    xml_escape(&result.dbg_output)
+                )
+                .unwrap();
+            }
+
+            buf.push_str("    </testcase>\n");
+        }
+
+        buf.push_str("  </testsuite>\n");
+    }
+
+    buf.push_str("</testsuites>\n");
+
+    buf
+}
+
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // The rendered reports contain ANSI escape codes, which are not allowed in XML.
+            '\u{1b}' => escaped.push_str("\\u{1b}"),
+            _ => escaped.push(ch),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn result(module: &str, name: &str, outcome: ExpectOutcome) -> ExpectResult {
+        ExpectResult {
+            module: module.to_string(),
+            name: name.to_string(),
+            path: PathBuf::from("Test.roc"),
+            line: 3,
+            outcome,
+            duration: Duration::from_millis(2),
+            dbg_output: String::new(),
+            failure: None,
+        }
+    }
+
+    #[test]
+    fn filter() {
+        let filter = ExpectFilter {
+            name: Some("len".to_string()),
+            module: Some("Test".to_string()),
+        };
+
+        assert!(filter.matches("Test", "List.len [] == 0"));
+        assert!(!filter.matches("Other", "List.len [] == 0"));
+        assert!(!filter.matches("Test", "1 == 1"));
+        assert!(ExpectFilter::default().matches("Other", "1 == 1"));
+    }
+
+    #[test]
+    fn name_from_source() {
+        let source = "x = 1\n\nexpect x == 1\n";
+        let region = Region::new(
+            roc_region::all::Position::new(7),
+            roc_region::all::Position::new(20),
+        );
+
+        assert_eq!(expect_name(source, region), ("x == 1".to_string(), 3));
+    }
+
+    #[test]
+    fn junit_xml() {
+        let mut failed = result("Test", "a < b", ExpectOutcome::Failed);
+        failed.failure = Some("a & b".to_string());
+
+        let xml = results_to_junit_xml(&[result("Test", "1 == 1", ExpectOutcome::Passed), failed]);
+
+        assert!(xml.contains(
+            "<testsuite name=\"Test\" tests=\"2\" failures=\"1\" errors=\"0\" time=\"0.004000\">"
+        ));
+        assert!(xml.contains("name=\"a &lt; b\""));
+        assert!(xml.contains("<failure message=\"expect failed\">a &amp; b</failure>"));
+    }
+
+    #[test]
+    fn json() {
+        let json = results_to_json(&[result("Test", "1 == 1", ExpectOutcome::Passed)]);
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(value["passed"], 1);
+        assert_eq!(value["tests"][0]["outcome"], "passed");
+        assert_eq!(value["tests"][0]["line"], 3);
+    }
+}
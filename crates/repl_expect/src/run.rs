@@ -2,6 +2,7 @@ use std::sync::{
     atomic::{AtomicBool, AtomicU32},
     Arc,
 };
+use std::time::Instant;
 
 use bumpalo::collections::Vec as BumpVec;
 use bumpalo::Bump;
@@ -18,6 +19,7 @@ use roc_gen_llvm::{
 use roc_load::{Expectations, MonomorphizedModule};
 use roc_module::symbol::{Interns, ModuleId, Symbol};
 use roc_mono::{
+    coverage::{CoverageMap, COVERAGE_COUNTERS},
     ir::OptLevel,
     layout::{GlobalLayoutInterner, STLayoutInterner},
 };
@@ -26,6 +28,9 @@ use roc_reporting::{error::expect::Renderer, report::RenderTarget};
 use roc_target::Target;
 use roc_types::subs::Subs;
 
+use crate::bench::{is_benchmark, BenchConfig, BenchResult, BenchStats};
+use crate::results::{expect_name, ExpectFilter, ExpectOutcome, ExpectResult};
+
 pub struct ExpectMemory<'a> {
     ptr: *mut u8,
     length: usize,
@@ -171,14 +176,248 @@ pub(crate) fn run_expects_with_memory<'a, W: std::io::Write>(
         )?;
 
         match result {
-            true => passed += 1,
-            false => failed += 1,
+            ExpectOutcome::Passed => passed += 1,
+            ExpectOutcome::Failed | ExpectOutcome::Panicked => failed += 1,
         }
     }
 
     Ok((failed, passed))
 }
 
+/// Like [run_toplevel_expects], but only runs the expects selected by the filter,
+/// and returns a structured result for each one instead of writing out failures.
+#[allow(clippy::too_many_arguments)]
+pub fn run_toplevel_expects_with_results<'a>(
+    render_target: RenderTarget,
+    arena: &'a Bump,
+    interns: &'a Interns,
+    layout_interner: &GlobalLayoutInterner<'a>,
+    lib: &libloading::Library,
+    expectations: &mut VecMap<ModuleId, Expectations>,
+    expects: ExpectFunctions<'_>,
+    filter: &ExpectFilter,
+) -> std::io::Result<Vec<ExpectResult>> {
+    let shm_name = format!("/roc_expect_buffer_{}", std::process::id());
+    let mut memory = ExpectMemory::create_or_reuse_mmap(&shm_name);
+
+    memory.set_shared_buffer(lib);
+
+    let mut results = Vec::with_capacity(expects.pure.len());
+
+    for expect in expects.pure {
+        let module_id = expect.symbol.module_id();
+        let module = module_id.to_ident_str(interns).as_str().to_string();
+        let path = expectations.get(&module_id).unwrap().path.clone();
+        let source = std::fs::read_to_string(&path)?;
+        let (name, line) = expect_name(&source, expect.region);
+
+        if !filter.matches(&module, &name) {
+            continue;
+        }
+
+        let mut failure_buf = Vec::new();
+        let start_time = Instant::now();
+
+        let (outcome, dbg_output) = capture_stderr(|| {
+            run_expect_pure(
+                &mut failure_buf,
+                render_target,
+                arena,
+                interns,
+                layout_interner,
+                lib,
+                expectations,
+                &mut memory,
+                expect,
+            )
+        })?;
+
+        let duration = start_time.elapsed();
+        let outcome = outcome?;
+
+        let failure = match outcome {
+            ExpectOutcome::Passed => None,
+            ExpectOutcome::Failed | ExpectOutcome::Panicked => {
+                Some(String::from_utf8_lossy(&failure_buf).trim_end().to_string())
+            }
+        };
+
+        results.push(ExpectResult {
+            module,
+            name,
+            path,
+            line,
+            outcome,
+            duration,
+            dbg_output,
+            failure,
+        });
+    }
+
+    Ok(results)
+}
+
+/// Run the benchmarks (see [crate::bench]) selected by the filter, and measure how long each
+/// one takes. The other expects are skipped.
+#[allow(clippy::too_many_arguments)]
+pub fn run_toplevel_benchmarks<'a>(
+    render_target: RenderTarget,
+    arena: &'a Bump,
+    interns: &'a Interns,
+    layout_interner: &GlobalLayoutInterner<'a>,
+    lib: &libloading::Library,
+    expectations: &mut VecMap<ModuleId, Expectations>,
+    expects: ExpectFunctions<'_>,
+    filter: &ExpectFilter,
+    config: &BenchConfig,
+) -> std::io::Result<Vec<BenchResult>> {
+    let shm_name = format!("/roc_expect_buffer_{}", std::process::id());
+    let mut memory = ExpectMemory::create_or_reuse_mmap(&shm_name);
+
+    memory.set_shared_buffer(lib);
+
+    let mut results = Vec::new();
+
+    for expect in expects.pure {
+        let module_id = expect.symbol.module_id();
+        let module = module_id.to_ident_str(interns).as_str().to_string();
+        let path = expectations.get(&module_id).unwrap().path.clone();
+        let source = std::fs::read_to_string(&path)?;
+        let (name, line) = expect_name(&source, expect.region);
+
+        if !is_benchmark(&source, expect.region) || !filter.matches(&module, &name) {
+            continue;
+        }
+
+        let mut failure_buf = Vec::new();
+        let mut run = |writer: &mut Vec<u8>| {
+            run_expect_pure(
+                writer,
+                render_target,
+                arena,
+                interns,
+                layout_interner,
+                lib,
+                expectations,
+                &mut memory,
+                expect,
+            )
+        };
+
+        // Benchmarks can't use `dbg` to print anything useful thousands of times, so drop it.
+        let (measured, _dbg_output) = capture_stderr(|| -> std::io::Result<_> {
+            // Only measure benchmarks that pass, since a failing one is measuring the wrong thing.
+            let outcome = run(&mut failure_buf)?;
+
+            if outcome != ExpectOutcome::Passed {
+                return Ok((outcome, 0, Vec::new()));
+            }
+
+            // Passing runs don't write anything, so this never grows
+            let mut sink = Vec::new();
+            let warmup_start = Instant::now();
+            let mut warmup_runs = 0u32;
+
+            while warmup_runs == 0 || warmup_start.elapsed() < config.warmup_time {
+                run(&mut sink)?;
+                warmup_runs += 1;
+            }
+
+            let iterations = config.iterations_per_sample(warmup_start.elapsed() / warmup_runs);
+            let mut samples = Vec::with_capacity(config.samples);
+
+            for _ in 0..config.samples {
+                let sample_start = Instant::now();
+
+                for _ in 0..iterations {
+                    run(&mut sink)?;
+                }
+
+                samples.push(sample_start.elapsed() / iterations as u32);
+            }
+
+            Ok((outcome, iterations, samples))
+        })?;
+
+        let (outcome, iterations_per_sample, samples) = measured?;
+
+        let failure = match outcome {
+            ExpectOutcome::Passed => None,
+            ExpectOutcome::Failed | ExpectOutcome::Panicked => {
+                Some(String::from_utf8_lossy(&failure_buf).trim_end().to_string())
+            }
+        };
+
+        results.push(BenchResult {
+            module,
+            name,
+            path,
+            line,
+            outcome,
+            failure,
+            iterations_per_sample,
+            stats: BenchStats::from_samples(&samples),
+        });
+    }
+
+    Ok(results)
+}
+
+/// Run the given function with stderr redirected to a temporary file, and return what got written.
+/// `dbg` writes straight to stderr from inside the compiled code, so this is how we capture it.
+fn capture_stderr<T>(f: impl FnOnce() -> T) -> std::io::Result<(T, String)> {
+    use std::io::{Read, Seek, SeekFrom, Write};
+    use std::os::unix::io::AsRawFd;
+
+    let path = std::env::temp_dir().join(format!("roc_expect_dbg_{}", std::process::id()));
+    let mut file = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&path)?;
+
+    std::io::stderr().flush()?;
+
+    let original_stderr = unsafe { libc::dup(libc::STDERR_FILENO) };
+
+    if original_stderr == -1 {
+        let _ = std::fs::remove_file(&path);
+
+        return Err(std::io::Error::last_os_error());
+    }
+
+    if unsafe { libc::dup2(file.as_raw_fd(), libc::STDERR_FILENO) } == -1 {
+        let error = std::io::Error::last_os_error();
+
+        unsafe { libc::close(original_stderr) };
+        let _ = std::fs::remove_file(&path);
+
+        return Err(error);
+    }
+
+    let answer = f();
+
+    // Put stderr back before anything can fail, so an error doesn't leave it redirected
+    let flushed = std::io::stderr().flush();
+
+    unsafe {
+        libc::dup2(original_stderr, libc::STDERR_FILENO);
+        libc::close(original_stderr);
+    }
+
+    flushed?;
+
+    let mut bytes = Vec::new();
+
+    file.seek(SeekFrom::Start(0))?;
+    file.read_to_end(&mut bytes)?;
+
+    let _ = std::fs::remove_file(&path);
+
+    Ok((answer, String::from_utf8_lossy(&bytes).into_owned()))
+}
+
 #[allow(clippy::too_many_arguments)]
 fn run_expect_pure<'a, W: std::io::Write>(
     writer: &mut W,
@@ -190,7 +429,7 @@ fn run_expect_pure<'a, W: std::io::Write>(
     expectations: &mut VecMap<ModuleId, Expectations>,
     shared_memory: &mut ExpectMemory,
     expect: ToplevelExpect<'_>,
-) -> std::io::Result<bool> {
+) -> std::io::Result<ExpectOutcome> {
     use roc_gen_llvm::try_run_jit_function;
 
     let sequence = ExpectSequence::new(shared_memory.ptr.cast());
@@ -209,8 +448,10 @@ fn run_expect_pure<'a, W: std::io::Write>(
 
         let renderer = Renderer::new(arena, interns, render_target, module_id, filename, &source);
 
-        if let Err((roc_panic_message, _roc_panic_tag)) = result {
+        let outcome = if let Err((roc_panic_message, _roc_panic_tag)) = result {
             renderer.render_panic(writer, &roc_panic_message, expect.region)?;
+
+            ExpectOutcome::Panicked
         } else {
             let mut offset = ExpectSequence::START_OFFSET;
 
@@ -227,13 +468,15 @@ fn run_expect_pure<'a, W: std::io::Write>(
                     offset,
                 )?;
             }
-        }
+
+            ExpectOutcome::Failed
+        };
 
         writeln!(writer)?;
 
-        Ok(false)
+        Ok(outcome)
     } else {
-        Ok(true)
+        Ok(ExpectOutcome::Passed)
     }
 }
 
@@ -439,6 +682,20 @@ impl ExpectFrame {
     }
 }
 
+/// Read how often each coverage point was hit, after the expects have run.
+pub fn coverage_counts(lib: &libloading::Library, coverage: &CoverageMap) -> Vec<u64> {
+    let name = format!("{COVERAGE_COUNTERS}\0");
+
+    match unsafe { lib.get::<*const u64>(name.as_bytes()) } {
+        Ok(counters) => {
+            let counters: *const u64 = *counters;
+
+            unsafe { std::slice::from_raw_parts(counters, coverage.len()) }.to_vec()
+        }
+        Err(_) => vec![0; coverage.len()],
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct ToplevelExpect<'a> {
     pub name: &'a str,
@@ -457,6 +714,7 @@ pub fn expect_mono_module_to_dylib<'a>(
     loaded: MonomorphizedModule<'a>,
     opt_level: OptLevel,
     mode: LlvmBackendMode,
+    coverage: Option<&'a CoverageMap>,
 ) -> Result<
     (
         libloading::Library,
@@ -496,12 +754,20 @@ pub fn expect_mono_module_to_dylib<'a>(
         mode,
         // important! we don't want any procedures to get the C calling convention
         exposed_to_host: MutSet::default(),
+        coverage,
+        profile_hints: None,
+        deterministic: false,
+        proc_names: Default::default(),
     };
 
     // Add roc_alloc, roc_realloc, and roc_dealloc, since the repl has no
     // platform to provide them.
     add_default_roc_externs(&env);
 
+    if let Some(coverage) = coverage {
+        roc_gen_llvm::llvm::build::add_coverage_counters(&env, coverage);
+    }
+
     let expects_symbols = toplevel_expects
         .iter()
         .map(|(module_id, expects)| {
@@ -1,9 +1,9 @@
 use bumpalo::Bump;
 use indoc::indoc;
 use roc_repl_cli::{evaluate, ReplHelper};
-use roc_repl_ui::is_incomplete;
 use roc_repl_ui::repl_state::{ReplAction, ReplState};
-use roc_reporting::report::DEFAULT_PALETTE;
+use roc_repl_ui::{format_input, format_output, is_incomplete};
+use roc_reporting::report::{ANSI_STYLE_CODES, DEFAULT_PALETTE};
 use rustyline::Editor;
 use target_lexicon::Triple;
 
@@ -145,6 +145,88 @@ fn standalone_annotation() {
     assert!(matches!(action, ReplAction::Nothing));
 }
 
+#[test]
+fn redefinition() {
+    let mut state = ReplState::new();
+
+    complete("x = 5", &mut state, "5 : Num *");
+    complete_with_warning("x = \"five\"", &mut state, "\"five\" : Str");
+    complete("x", &mut state, "\"five\" : Str");
+}
+
+#[test]
+fn type_of() {
+    let mut state = ReplState::new();
+
+    complete("x = 5", &mut state, "5 : Num *");
+    type_of(":type x", &mut state, "x : Num *");
+    type_of(
+        ":type Str.concat",
+        &mut state,
+        "Str.concat : Str, Str -> Str",
+    );
+}
+
+#[test]
+fn reset() {
+    let mut state = ReplState::new();
+
+    complete("x = 5", &mut state, "5 : Num *");
+
+    let arena = Bump::new();
+    let target = Triple::host().into();
+    let action = state.step(&arena, ":reset", target, DEFAULT_PALETTE);
+    assert!(matches!(action, ReplAction::Reset));
+
+    // Now that the old `x` is gone, redefining it should not warn.
+    complete("x = 6", &mut state, "6 : Num *");
+}
+
+#[test]
+fn unclosed_delimiters_continue() {
+    assert!(is_incomplete("[1, 2,"));
+    assert!(is_incomplete("foo = (1 +"));
+    assert!(is_incomplete("{ a: \"}\","));
+    assert!(!is_incomplete("\"[\""));
+    assert!(!is_incomplete("[1, 2] # ["));
+}
+
+#[test]
+fn trailing_operator_continues() {
+    assert!(is_incomplete("x ="));
+    assert!(is_incomplete("1 +"));
+    assert!(is_incomplete("[1, 2] |> List.map \\n ->"));
+    assert!(is_incomplete("if Bool.true then"));
+    assert!(!is_incomplete("1 + 1"));
+    assert!(!is_incomplete(":type x ="));
+
+    // A blank line submits the input, even if it's unfinished
+    assert!(!is_incomplete("x =\n"));
+}
+
+#[test]
+fn multiline_def() {
+    let mut state = ReplState::new();
+    let mut input = "x =".to_string();
+
+    incomplete(&mut input);
+
+    input.push_str("    1 +");
+    incomplete(&mut input);
+
+    input.push_str("    2");
+    complete(&input, &mut state, "3 : Num *");
+}
+
+#[test]
+fn format_submitted_input() {
+    let arena = Bump::new();
+
+    assert_eq!(format_input(&arena, "x=[1,2 , 3]"), Some("x = [1, 2, 3]"));
+    assert_eq!(format_input(&arena, "1+   1"), Some("1 + 1"));
+    assert_eq!(format_input(&arena, "x = ("), None);
+}
+
 /// validate and step the given input, then check the Result vs the output
 /// with ANSI escape codes stripped.
 fn complete(input: &str, state: &mut ReplState, expected_start: &str) {
@@ -205,3 +287,53 @@ fn error(input: &str, state: &mut ReplState, expected_step_result: String) {
         }
     }
 }
+
+/// Like `complete`, but expects a redefinition warning to be printed before the output.
+fn complete_with_warning(input: &str, state: &mut ReplState, expected_end: &str) {
+    assert!(!is_incomplete(input));
+    let arena = Bump::new();
+    let target = Triple::host().into();
+    let action = state.step(&arena, input, target, DEFAULT_PALETTE);
+
+    match action {
+        ReplAction::Eval { opt_mono, problems } => {
+            assert_eq!(problems.warnings.len(), 1);
+
+            let string = evaluate(opt_mono, problems, target);
+            let escaped =
+                std::string::String::from_utf8(strip_ansi_escapes::strip(string.trim()).unwrap())
+                    .unwrap();
+
+            assert!(escaped.contains("REDEFINED VALUE"));
+            assert!(escaped.ends_with(expected_end));
+        }
+        _ => {
+            panic!("Unexpected action: {:?}", action);
+        }
+    }
+}
+
+/// step the given `:type` command, then check the printed type with ANSI escape codes stripped.
+fn type_of(input: &str, state: &mut ReplState, expected: &str) {
+    assert!(!is_incomplete(input));
+    let arena = Bump::new();
+    let target = Triple::host().into();
+    let action = state.step(&arena, input, target, DEFAULT_PALETTE);
+
+    match action {
+        ReplAction::TypeOf {
+            opt_output,
+            problems,
+        } => {
+            let string = format_output(ANSI_STYLE_CODES, opt_output, problems);
+            let escaped =
+                std::string::String::from_utf8(strip_ansi_escapes::strip(string.trim()).unwrap())
+                    .unwrap();
+
+            assert_eq!(expected, escaped);
+        }
+        _ => {
+            panic!("Unexpected action: {:?}", action);
+        }
+    }
+}
@@ -7,6 +7,9 @@ use bumpalo::Bump;
 use colors::{CYAN, END_COL, GREEN};
 use const_format::concatcp;
 use repl_state::{parse_src, ParseOutcome};
+use roc_fmt::annotation::{Formattable, Newlines, Parens};
+use roc_fmt::def::fmt_defs;
+use roc_fmt::{Buf, DbgMigration, MigrationFlags};
 use roc_parse::ast::{Expr, ExtractSpaces, ValueDef};
 use roc_repl_eval::gen::{Problems, ReplOutput};
 use roc_reporting::report::StyleCodes;
@@ -48,6 +51,27 @@ pub const TIPS: &str = concatcp!(
             ":help",
             END_COL,
             " shows this text again\n",
+            CYAN,
+            "  - ",
+            END_COL,
+            GREEN,
+            ":type expr",
+            END_COL,
+            " shows the type of an expression without evaluating it\n",
+            CYAN,
+            "  - ",
+            END_COL,
+            GREEN,
+            ":load path.roc",
+            END_COL,
+            " adds the definitions in a file\n",
+            CYAN,
+            "  - ",
+            END_COL,
+            GREEN,
+            ":reset",
+            END_COL,
+            " forgets all definitions\n",
         )
     }
 );
@@ -58,7 +82,39 @@ pub const SHORT_INSTRUCTIONS: &str = "Enter an expression, or :help, or :q to qu
 pub const PROMPT: &str = concatcp!(CYAN, "»", END_COL, " ");
 pub const CONT_PROMPT: &str = concatcp!(CYAN, "…", END_COL, " ");
 
+/// Tokens which can't end an expression, so a line ending in one of them must continue
+/// onto the next line.
+const CONTINUATION_SUFFIXES: &[&str] = &[
+    "=", "->", "<-", "|>", ",", ":", "\\", "+", "-", "*", "/", "//", "%", "^", "++", "&&", "||",
+    "==", "!=", "<", ">", "<=", ">=", " is", " then", " else",
+];
+
 pub fn is_incomplete(input: &str) -> bool {
+    // An empty line always submits, even if the input is unfinished.
+    if input.ends_with('\n') {
+        return is_incomplete_after_parse(input);
+    }
+
+    if unclosed_delimiters(input) > 0 {
+        return true;
+    }
+
+    let last_line = input.lines().last().unwrap_or_default();
+    let last_line = strip_comment(last_line).trim_end();
+
+    // `:type` and `:load` take the rest of the line as an argument, so they can end in anything.
+    if !last_line.starts_with(':')
+        && CONTINUATION_SUFFIXES
+            .iter()
+            .any(|suffix| last_line.ends_with(suffix) || last_line == suffix.trim_start())
+    {
+        return true;
+    }
+
+    is_incomplete_after_parse(input)
+}
+
+fn is_incomplete_after_parse(input: &str) -> bool {
     let arena = Bump::new();
 
     match parse_src(&arena, input) {
@@ -84,10 +140,108 @@ pub fn is_incomplete(input: &str) -> bool {
                 false
             }
         }
-        ParseOutcome::Empty | ParseOutcome::Help | ParseOutcome::Exit | ParseOutcome::SyntaxErr => {
-            false
+        ParseOutcome::Empty
+        | ParseOutcome::Help
+        | ParseOutcome::Exit
+        | ParseOutcome::Reset
+        | ParseOutcome::TypeOf(_)
+        | ParseOutcome::Load(_)
+        | ParseOutcome::SyntaxErr => false,
+    }
+}
+
+/// How many `(`, `[`, and `{` delimiters are still open at the end of the input.
+/// Delimiters inside of string literals, character literals, and comments don't count.
+fn unclosed_delimiters(input: &str) -> usize {
+    let mut depth: usize = 0;
+    let mut chars = input.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth = depth.saturating_sub(1),
+            '#' => {
+                // Skip the rest of the comment
+                for ch in chars.by_ref() {
+                    if ch == '\n' {
+                        break;
+                    }
+                }
+            }
+            '"' | '\'' => {
+                // Skip the rest of the literal, including escaped quotes.
+                // Triple-quoted strings work out the same way, since `"""` opens
+                // and immediately closes an empty string before the real one starts.
+                while let Some(inner) = chars.next() {
+                    if inner == '\\' {
+                        chars.next();
+                    } else if inner == ch {
+                        break;
+                    }
+                }
+            }
+            _ => {}
         }
     }
+
+    depth
+}
+
+fn strip_comment(line: &str) -> &str {
+    let mut in_str = false;
+    let mut escaped = false;
+
+    for (index, ch) in line.char_indices() {
+        match ch {
+            _ if escaped => escaped = false,
+            '\\' if in_str => escaped = true,
+            '"' => in_str = !in_str,
+            '#' if !in_str => return &line[..index],
+            _ => {}
+        }
+    }
+
+    line
+}
+
+/// Format the submitted input with roc_fmt, so that it goes into the history nicely formatted.
+/// Returns None if the input isn't something we can format (e.g. it has a syntax error).
+pub fn format_input<'a>(arena: &'a Bump, input: &'a str) -> Option<&'a str> {
+    let (defs, opt_expr) = match parse_src(arena, input) {
+        ParseOutcome::DefsAndExpr(defs, opt_expr) => (defs, opt_expr),
+        _ => return None,
+    };
+
+    let mut buf = Buf::new_in(
+        arena,
+        MigrationFlags {
+            snakify: false,
+            parens_and_commas: false,
+            dbg: DbgMigration::Keep,
+            transforms: &[],
+        },
+    );
+
+    fmt_defs(&mut buf, &defs, 0);
+
+    if let Some(expr) = opt_expr {
+        if !defs.is_empty() {
+            buf.ensure_ends_with_newline();
+        }
+
+        expr.format_with_options(&mut buf, Parens::NotNeeded, Newlines::Yes, 0);
+    }
+
+    buf.fmt_end_of_file();
+
+    Some(buf.into_bump_str().trim_end())
+}
+
+pub fn format_loaded(filename: &std::path::Path, idents: &[String]) -> String {
+    match idents.len() {
+        0 => format!("Loaded {}", filename.display()),
+        _ => format!("Loaded {}: {}", filename.display(), idents.join(", ")),
+    }
 }
 
 pub fn format_output(
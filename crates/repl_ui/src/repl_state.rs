@@ -4,15 +4,18 @@ use std::{fs, io};
 use bumpalo::Bump;
 use roc_collections::MutSet;
 use roc_load::MonomorphizedModule;
+use roc_module::symbol::{Interns, ModuleIds};
 use roc_parse::ast::{Defs, Expr, Pattern, StrLiteral, TypeDef, TypeHeader, ValueDef};
 use roc_parse::expr::parse_repl_defs_and_optional_expr;
 use roc_parse::parser::EWhen;
 use roc_parse::parser::{EClosure, EExpr, EPattern};
 use roc_parse::state::State;
+use roc_problem::Severity;
 use roc_region::all::Loc;
-use roc_repl_eval::gen::{compile_to_mono, Problems};
-use roc_reporting::report::Palette;
+use roc_repl_eval::gen::{compile_to_mono, repl_output_type, Problems, ReplOutput};
+use roc_reporting::report::{Annotation, Palette, Report, RocDocAllocator};
 use roc_target::Target;
+use ven_pretty::DocAllocator;
 
 #[derive(Debug, Clone, PartialEq)]
 enum PastDef {
@@ -20,6 +23,10 @@ enum PastDef {
     Import(String),
 }
 
+/// The defs and imports entered so far in a REPL session.
+///
+/// They are only kept as source: every evaluation compiles all of them again, along with the new
+/// input, so the time an evaluation takes grows with the size of the session.
 pub struct ReplState {
     past_defs: Vec<PastDef>,
     past_def_idents: MutSet<String>,
@@ -38,6 +45,17 @@ pub enum ReplAction<'a> {
         opt_mono: Option<MonomorphizedModule<'a>>,
         problems: Problems,
     },
+    /// The result of `:type expr` - the expression is type-checked but not evaluated.
+    TypeOf {
+        opt_output: Option<ReplOutput>,
+        problems: Problems,
+    },
+    /// The defs from a `:load`ed file were added to the session.
+    Loaded {
+        filename: PathBuf,
+        idents: Vec<String>,
+    },
+    Reset,
     Exit,
     Help,
     FileProblem {
@@ -66,6 +84,15 @@ impl ReplState {
         let src: &str = match parse_src(arena, line) {
             ParseOutcome::Empty | ParseOutcome::Help => return ReplAction::Help,
             ParseOutcome::Exit => return ReplAction::Exit,
+            ParseOutcome::Reset => {
+                self.reset();
+
+                return ReplAction::Reset;
+            }
+            ParseOutcome::TypeOf(expr_src) => {
+                return self.type_of(arena, expr_src, target, palette)
+            }
+            ParseOutcome::Load(path) => return self.load(arena, path, target, palette),
             ParseOutcome::Incomplete | ParseOutcome::SyntaxErr => {
                 pending_past_def = None;
 
@@ -257,36 +284,276 @@ impl ReplState {
             }
         };
 
-        let (opt_mono, problems) = compile_to_mono(
+        // A def being redefined must not be compiled alongside its previous definition,
+        // or the new one would be reported as shadowing the old one.
+        let redefined = pending_past_def
+            .as_ref()
+            .filter(|(ident, _)| self.past_def_idents.contains(ident))
+            .map(|(ident, _)| ident.as_str());
+
+        let (opt_mono, mut problems) = compile_to_mono(
             arena,
-            self.past_defs.iter().map(|past_def| match past_def {
-                PastDef::Def { ident: _, src } => src.as_str(),
-                PastDef::Import(src) => src.as_str(),
-            }),
+            self.past_def_sources(redefined),
             src,
             target,
             palette,
         );
 
         if let Some((ident, src)) = pending_past_def {
+            if self.past_def_idents.contains(&ident) && problems.errors.is_empty() {
+                problems
+                    .warnings
+                    .push(redefinition_warning(&ident, &palette));
+            }
+
             self.add_past_def(ident, src);
         }
 
         ReplAction::Eval { opt_mono, problems }
     }
 
+    /// Forget every def, type, and import entered so far in this session.
+    pub fn reset(&mut self) {
+        self.past_defs.clear();
+        self.past_def_idents.clear();
+    }
+
     fn add_past_def(&mut self, ident: String, src: String) {
         let existing_idents = &mut self.past_def_idents;
 
-        existing_idents.insert(ident.clone());
+        if !existing_idents.insert(ident.clone()) {
+            // This is a redefinition, which replaces the previous def with this name.
+            self.past_defs.retain(|past_def| match past_def {
+                PastDef::Def {
+                    ident: existing, ..
+                } => existing != &ident,
+                PastDef::Import(_) => true,
+            });
+        }
 
         self.past_defs.push(PastDef::Def { ident, src });
     }
+
+    /// The sources of all the past defs, except for the one named `skip_ident` (if any).
+    fn past_def_sources<'s>(
+        &'s self,
+        skip_ident: Option<&'s str>,
+    ) -> impl Iterator<Item = &'s str> {
+        self.past_defs
+            .iter()
+            .filter_map(move |past_def| match past_def {
+                PastDef::Def { ident, src } => {
+                    if Some(ident.as_str()) == skip_ident {
+                        None
+                    } else {
+                        Some(src.as_str())
+                    }
+                }
+                PastDef::Import(src) => Some(src.as_str()),
+            })
+    }
+
+    fn type_of<'a>(
+        &self,
+        arena: &'a Bump,
+        expr_src: &str,
+        target: Target,
+        palette: Palette,
+    ) -> ReplAction<'a> {
+        let (opt_mono, problems) = compile_to_mono(
+            arena,
+            self.past_def_sources(None),
+            expr_src,
+            target,
+            palette,
+        );
+
+        let opt_output = opt_mono.and_then(|mut mono| {
+            repl_output_type(&mut mono).map(|expr_type| ReplOutput {
+                expr: expr_src.to_string(),
+                expr_type,
+            })
+        });
+
+        ReplAction::TypeOf {
+            opt_output,
+            problems,
+        }
+    }
+
+    fn load<'a>(
+        &mut self,
+        arena: &'a Bump,
+        path: &str,
+        target: Target,
+        palette: Palette,
+    ) -> ReplAction<'a> {
+        let filename = PathBuf::from(path);
+
+        let file_src = match fs::read_to_string(&filename) {
+            Ok(file_src) => file_src,
+            Err(err) => {
+                return ReplAction::FileProblem {
+                    filename,
+                    error: err.kind(),
+                };
+            }
+        };
+
+        let file_src: &'a str = arena.alloc_str(file_src.trim_end());
+
+        let defs = match parse_src(arena, file_src) {
+            ParseOutcome::DefsAndExpr(defs, None) => defs,
+            ParseOutcome::Empty => Defs::default(),
+            _ => {
+                // Let the compiler report whatever is wrong with the file's contents.
+                let (_, problems) = compile_to_mono(
+                    arena,
+                    self.past_def_sources(None),
+                    file_src,
+                    target,
+                    palette,
+                );
+
+                return ReplAction::Eval {
+                    opt_mono: None,
+                    problems,
+                };
+            }
+        };
+
+        // Compile the file's defs as a block (ending in an empty record), so that any errors
+        // in them get reported now rather than being filtered out as part of the past defs.
+        let mut block = bumpalo::collections::String::with_capacity_in(file_src.len() + 4, arena);
+
+        block.push_str(file_src);
+        block.push_str("\n{}");
+
+        let (_, mut problems) =
+            compile_to_mono(arena, self.past_def_sources(None), &block, target, palette);
+
+        if !problems.errors.is_empty() {
+            return ReplAction::Eval {
+                opt_mono: None,
+                problems,
+            };
+        }
+
+        // None of the defs are used by the trailing `{}`, so unused def warnings are just noise.
+        problems.warnings.clear();
+
+        let mut idents = Vec::new();
+
+        for def in defs.loc_defs() {
+            let (ident, def_src) = match def {
+                Ok(td) => match td.value {
+                    TypeDef::Alias {
+                        header:
+                            TypeHeader {
+                                name: Loc { value: ident, .. },
+                                ..
+                            },
+                        ..
+                    }
+                    | TypeDef::Opaque {
+                        header:
+                            TypeHeader {
+                                name: Loc { value: ident, .. },
+                                ..
+                            },
+                        ..
+                    }
+                    | TypeDef::Ability {
+                        header:
+                            TypeHeader {
+                                name: Loc { value: ident, .. },
+                                ..
+                            },
+                        ..
+                    } => (Some(ident), &file_src[td.byte_range()]),
+                },
+                Err(vd) => match vd.value {
+                    ValueDef::Annotation(
+                        Loc {
+                            value: Pattern::Identifier { ident },
+                            ..
+                        },
+                        _,
+                    ) => (Some(ident), &file_src[vd.byte_range()]),
+                    ValueDef::Body(
+                        Loc {
+                            value: Pattern::Identifier { ident },
+                            ..
+                        },
+                        _,
+                    )
+                    | ValueDef::AnnotatedBody {
+                        body_pattern:
+                            Loc {
+                                value: Pattern::Identifier { ident },
+                                ..
+                            },
+                        ..
+                    } => (Some(*ident), &file_src[vd.byte_range()]),
+                    _ => (None, &file_src[vd.byte_range()]),
+                },
+            };
+
+            match ident {
+                Some(ident) => {
+                    let ident = ident.trim_end().to_string();
+
+                    self.add_past_def(ident.clone(), def_src.to_string());
+                    idents.push(ident);
+                }
+                None => {
+                    self.past_defs.push(PastDef::Import(def_src.to_string()));
+                }
+            }
+        }
+
+        ReplAction::Loaded { filename, idents }
+    }
+}
+
+fn redefinition_warning(ident: &str, palette: &Palette) -> String {
+    let src_lines: Vec<&str> = Vec::new();
+    let mut module_ids = ModuleIds::default();
+    let module_id = module_ids.get_or_insert(&"Repl".into());
+    let interns = Interns::default();
+    let alloc = RocDocAllocator::new(&src_lines, module_id, &interns);
+
+    let doc = alloc.stack([
+        alloc.concat([
+            alloc.reflow("This replaces the earlier definition of "),
+            alloc.string(ident.to_string()).annotate(Annotation::Symbol),
+            alloc.reflow(" from this session."),
+        ]),
+        alloc.reflow("Past definitions that refer to it will now use this new definition."),
+    ]);
+
+    let report = Report {
+        filename: PathBuf::from("replfile.roc"),
+        doc,
+        title: "REDEFINED VALUE".to_string(),
+        severity: Severity::Warning,
+    };
+
+    let mut buf = String::new();
+
+    report.render_color_terminal(&mut buf, &alloc, palette);
+
+    buf
 }
 
 #[derive(Debug, PartialEq)]
 pub enum ParseOutcome<'a> {
     DefsAndExpr(Defs<'a>, Option<Loc<Expr<'a>>>),
+    /// `:type expr`
+    TypeOf(&'a str),
+    /// `:load path/to/file.roc`
+    Load(&'a str),
+    Reset,
     Incomplete,
     SyntaxErr,
     Empty,
@@ -307,9 +574,19 @@ fn parse_outcome_for_error(e: EExpr<'_>) -> ParseOutcome<'_> {
 }
 
 pub fn parse_src<'a>(arena: &'a Bump, line: &'a str) -> ParseOutcome<'a> {
-    match line.trim().to_lowercase().as_str() {
+    let trimmed = line.trim();
+
+    // These commands take an argument, which must keep its original case.
+    if let Some(expr_src) = trimmed.strip_prefix(":type ") {
+        return ParseOutcome::TypeOf(expr_src.trim());
+    } else if let Some(path) = trimmed.strip_prefix(":load ") {
+        return ParseOutcome::Load(path.trim());
+    }
+
+    match trimmed.to_lowercase().as_str() {
         "" => ParseOutcome::Empty,
         ":help" => ParseOutcome::Help,
+        ":reset" => ParseOutcome::Reset,
         // These are all common things beginners try.
         // Let people exit the repl easily!
         // If you really need to evaluate `exit` for some reason,
@@ -207,6 +207,14 @@ pub async fn entrypoint_from_js(src: String) -> String {
             "The web version of the REPL cannot import files... for now!".to_string()
         }
         ReplAction::Nothing => String::new(),
+        ReplAction::Loaded { .. } => {
+            "The web version of the REPL cannot load files... for now!".to_string()
+        }
+        ReplAction::Reset => "Cleared all definitions.".to_string(),
+        ReplAction::TypeOf {
+            opt_output,
+            problems,
+        } => format_output(HTML_STYLE_CODES, opt_output, problems),
         ReplAction::Eval { opt_mono, problems } => {
             let opt_output = match opt_mono {
                 Some(mono) => eval_wasm(arena, target, mono).await,
@@ -3,9 +3,10 @@ use std::path::PathBuf;
 use roc_collections::MutMap;
 use roc_module::symbol::{Interns, ModuleId};
 use roc_problem::can::Problem;
-use roc_region::all::LineInfo;
+use roc_region::all::{LineInfo, Region};
 use roc_solve_problem::TypeError;
 
+use crate::group::group_problems;
 use crate::report::ANSI_STYLE_CODES;
 
 #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
@@ -70,6 +71,13 @@ pub fn report_problems(
     use roc_problem::Severity::*;
 
     let palette = DEFAULT_PALETTE;
+    let groups = group_problems(can_problems, type_problems);
+    let locate = |module_id: ModuleId, region: Region| {
+        let (path, src) = &sources[&module_id];
+        let start = LineInfo::new(src).convert_pos(region.start());
+
+        format!("{}:{}:{}", path.display(), start.line + 1, start.column + 1)
+    };
     let mut total_problems = 0;
 
     for problems in can_problems.values() {
@@ -93,17 +101,29 @@ pub fn report_problems(
 
         let lines = LineInfo::new(&src_lines.join("\n"));
 
-        // Report parsing and canonicalization problems
-        let alloc = RocDocAllocator::new(&src_lines, *home, interns);
+        // Report parsing and canonicalization problems. Warnings are only printed when there are
+        // no errors, so only the errors refer back to the types that earlier reports printed.
+        let error_alloc = RocDocAllocator::new(&src_lines, *home, interns).with_shared_types();
+        let warning_alloc = RocDocAllocator::new(&src_lines, *home, interns);
 
         let problems = type_problems.remove(home).unwrap_or_default();
 
         for problem in problems {
-            if let Some(report) = type_problem(&alloc, &lines, module_path.clone(), problem) {
+            let group = groups.of_type_problem(*home, &problem).cloned();
+            let alloc = match problem.severity() {
+                Warning => &warning_alloc,
+                RuntimeError | Fatal => &error_alloc,
+            };
+
+            if let Some(mut report) = type_problem(alloc, &lines, module_path.clone(), problem) {
+                if let Some(group) = group {
+                    report.doc = alloc.stack([report.doc, group.note(alloc, locate)]);
+                }
+
                 let severity = report.severity;
                 let mut buf = String::new();
 
-                report.render_color_terminal(&mut buf, &alloc, &palette);
+                report.render_color_terminal(&mut buf, alloc, &palette);
 
                 match severity {
                     Warning => {
@@ -137,11 +157,21 @@ pub fn report_problems(
         ordered.extend(shadowing_errs);
 
         for problem in ordered.into_iter() {
-            let report = can_problem(&alloc, &lines, module_path.clone(), problem);
+            let group = groups.of_can_problem(*home, &problem).cloned();
+            let alloc = match problem.severity() {
+                Warning => &warning_alloc,
+                RuntimeError | Fatal => &error_alloc,
+            };
+            let mut report = can_problem(alloc, &lines, module_path.clone(), problem);
+
+            if let Some(group) = group {
+                report.doc = alloc.stack([report.doc, group.note(alloc, locate)]);
+            }
+
             let severity = report.severity;
             let mut buf = String::new();
 
-            report.render_color_terminal(&mut buf, &alloc, &palette);
+            report.render_color_terminal(&mut buf, alloc, &palette);
 
             match severity {
                 Warning => {
@@ -194,3 +224,45 @@ pub fn report_problems(
         warnings: warnings.len(),
     }
 }
+
+/// Prints the reports for the problems found in the specialized program, e.g. a def marked
+/// `# @tailrec` that isn't tail recursive, to stderr in the order they're given. Returns the
+/// number of errors.
+pub fn report_mono_problems(
+    sources: &MutMap<ModuleId, (PathBuf, Box<str>)>,
+    interns: &Interns,
+    problems: Vec<roc_problem::mono::Problem>,
+) -> usize {
+    use crate::report::{mono_problem, Report, RocDocAllocator, DEFAULT_PALETTE};
+
+    let palette = DEFAULT_PALETTE;
+    let mut modules: MutMap<ModuleId, (Vec<&str>, LineInfo)> = MutMap::default();
+    let mut errors = 0;
+
+    for problem in problems {
+        let home = problem.symbol().module_id();
+        let (module_path, src) = &sources[&home];
+        let (src_lines, lines) = modules.entry(home).or_insert_with(|| {
+            let src_lines: Vec<&str> = src.split('\n').collect();
+            let lines = LineInfo::new(&src_lines.join("\n"));
+
+            (src_lines, lines)
+        });
+
+        let alloc = RocDocAllocator::new(src_lines, home, interns);
+        let report = mono_problem(&alloc, lines, module_path.clone(), problem);
+        let mut buf = String::new();
+
+        report.render_color_terminal(&mut buf, &alloc, &palette);
+
+        eprintln!("\n{buf}\n");
+
+        errors += 1;
+    }
+
+    if errors > 0 {
+        eprintln!("{}\u{001B}[0m\n", Report::horizontal_rule(&palette));
+    }
+
+    errors
+}
@@ -3,8 +3,8 @@ use roc_module::ident::{Ident, Lowercase, ModuleName};
 use roc_module::symbol::DERIVABLE_ABILITIES;
 use roc_problem::can::PrecedenceProblem::BothNonAssociative;
 use roc_problem::can::{
-    BadPattern, CycleEntry, ExtensionTypeKind, FloatErrorKind, IntErrorKind, Problem, RuntimeError,
-    ScopeModuleSource, ShadowKind,
+    BadPattern, BindingKind, CycleEntry, ExtensionTypeKind, FloatErrorKind, IntErrorKind, Problem,
+    RuntimeError, ScopeModuleSource, ShadowKind,
 };
 use roc_problem::Severity;
 use roc_region::all::{LineColumn, LineColumnRegion, LineInfo, Loc, Region};
@@ -19,6 +19,7 @@ const SYNTAX_PROBLEM: &str = "SYNTAX PROBLEM";
 const NAMING_PROBLEM: &str = "NAMING PROBLEM";
 const UNRECOGNIZED_NAME: &str = "UNRECOGNIZED NAME";
 const UNUSED_DEF: &str = "UNUSED DEFINITION";
+const UNUSED_PATTERN_BINDING: &str = "UNUSED PATTERN BINDING";
 const UNUSED_IMPORT: &str = "UNUSED IMPORT";
 const IMPORT_NAME_CONFLICT: &str = "IMPORT NAME CONFLICT";
 const EXPLICIT_BUILTIN_IMPORT: &str = "EXPLICIT BUILTIN IMPORT";
@@ -33,6 +34,7 @@ const DUPLICATE_TAG_NAME: &str = "DUPLICATE TAG NAME";
 const INVALID_UNICODE: &str = "INVALID UNICODE";
 pub const CIRCULAR_DEF: &str = "CIRCULAR DEFINITION";
 const DUPLICATE_NAME: &str = "DUPLICATE NAME";
+const INTENTIONAL_SHADOWING: &str = "INTENTIONAL SHADOWING";
 const VALUE_NOT_EXPOSED: &str = "NOT EXPOSED";
 const MODULE_NOT_IMPORTED: &str = "MODULE NOT IMPORTED";
 const INGESTED_FILE_ERROR: &str = "INGESTED FILE ERROR";
@@ -68,6 +70,7 @@ const UNNECESSARY_EXCLAMATION: &str = "UNNECESSARY EXCLAMATION";
 const EMPTY_TUPLE_TYPE: &str = "EMPTY TUPLE TYPE";
 const UNBOUND_TYPE_VARS_IN_AS: &str = "UNBOUND TYPE VARIABLES IN AS";
 const INTERPOLATED_STRING_NOT_ALLOWED: &str = "INTERPOLATED STRING NOT ALLOWED";
+const INVALID_WARNING_DIRECTIVE: &str = "INVALID WARNING DIRECTIVE";
 
 pub fn can_problem<'b>(
     alloc: &'b RocDocAllocator<'b>,
@@ -80,7 +83,7 @@ pub fn can_problem<'b>(
     let severity = problem.severity();
 
     match problem {
-        Problem::UnusedDef(symbol, region) => {
+        Problem::UnusedDef(symbol, region, _) => {
             let line =
                 r#" then remove it so future readers of your code don't wonder why it is there."#;
 
@@ -276,7 +279,7 @@ pub fn can_problem<'b>(
 
             title = "DEFINITIONS ONLY USED IN RECURSION".to_string();
         }
-        Problem::ExposedButNotDefined(symbol) => {
+        Problem::ExposedButNotDefined { symbol, .. } => {
             doc = alloc.stack([
                 alloc.symbol_unqualified(symbol).append(
                     alloc.reflow(" is listed as exposed, but it isn't defined in this module."),
@@ -291,8 +294,20 @@ pub fn can_problem<'b>(
 
             title = MISSING_DEFINITION.to_string();
         }
-        Problem::UnusedArgument(closure_symbol, is_anonymous, argument_symbol, region) => {
+        Problem::UnusedArgument(closure_symbol, is_anonymous, argument_symbol, region, kind) => {
             let line = "\". Adding an underscore at the start of a variable name is a way of saying that the variable is not used.";
+            let ignore = match kind {
+                BindingKind::RecordField => alloc.concat([
+                    alloc.reflow(", ignore its field, like this: \""),
+                    alloc.symbol_unqualified(argument_symbol),
+                    alloc.text(": _\"."),
+                ]),
+                BindingKind::Identifier | BindingKind::Other => alloc.concat([
+                    alloc.reflow(", prefix it with an underscore, like this: \"_"),
+                    alloc.symbol_unqualified(argument_symbol),
+                    alloc.reflow(line),
+                ]),
+            };
 
             doc = alloc.stack([
                 alloc.concat([
@@ -317,33 +332,41 @@ pub fn can_problem<'b>(
                     } else {
                         alloc.symbol_unqualified(closure_symbol)
                     },
-                    alloc.reflow(", prefix it with an underscore, like this: \"_"),
-                    alloc.symbol_unqualified(argument_symbol),
-                    alloc.reflow(line),
+                    ignore,
                 ]),
             ]);
 
             title = UNUSED_ARG.to_string();
         }
-        Problem::UnusedBranchDef(symbol, region) => {
+        Problem::UnusedPatternBinding(symbol, region, kind) => {
+            let name = symbol.as_str(alloc.interns);
+
             doc = alloc.stack([
                 alloc.concat([
                     alloc.symbol_unqualified(symbol),
-                    alloc.reflow(" is not used in this "),
-                    alloc.keyword("when"),
-                    alloc.reflow(" branch."),
+                    alloc.reflow(" is not used anywhere in your code."),
                 ]),
                 alloc.region(lines.convert_region(region), severity),
                 alloc.concat([
                     alloc.reflow("If you don't need to use "),
                     alloc.symbol_unqualified(symbol),
-                    alloc.reflow(", prefix it with an underscore, like \"_"),
-                    alloc.reflow(symbol.as_str(alloc.interns)),
-                    alloc.reflow("\", or replace it with just an \"_\"."),
+                    match kind {
+                        BindingKind::Identifier => alloc.concat([
+                            alloc.reflow(", prefix it with an underscore, like \"_"),
+                            alloc.reflow(name),
+                            alloc.reflow("\", or replace it with just an \"_\"."),
+                        ]),
+                        BindingKind::RecordField => alloc.concat([
+                            alloc.reflow(", ignore its field, like \""),
+                            alloc.reflow(name),
+                            alloc.reflow(": _\", or leave the field out of the pattern."),
+                        ]),
+                        BindingKind::Other => alloc.reflow(", remove it from the pattern."),
+                    },
                 ]),
             ]);
 
-            title = UNUSED_DEF.to_string();
+            title = UNUSED_PATTERN_BINDING.to_string();
         }
         Problem::PrecedenceProblem(BothNonAssociative(region, left_bin_op, right_bin_op)) => {
             doc = alloc.stack([
@@ -415,6 +438,38 @@ pub fn can_problem<'b>(
             doc = res_doc;
             title = res_title.to_string();
         }
+        Problem::IntentionalShadowing {
+            original_region,
+            shadow,
+            fix,
+            ..
+        } => {
+            // The first edit of the fix renames the def.
+            let new_name = &fix.edits[0].replacement;
+
+            doc = alloc.stack([
+                alloc
+                    .text("The ")
+                    .append(alloc.ident(shadow.value.clone()))
+                    .append(alloc.reflow(" name is first defined here:")),
+                alloc.region(lines.convert_region(original_region), severity),
+                alloc.reflow("Then it's given a new value, computed from the old one, here:"),
+                alloc.region(lines.convert_region(shadow.region), severity),
+                alloc.concat([
+                    alloc.reflow("From here on, "),
+                    alloc.ident(shadow.value.clone()),
+                    alloc.reflow(
+                        " means the new value. Giving the new value a name of its own, like ",
+                    ),
+                    alloc.ident(Ident::from(new_name.as_str())),
+                    alloc.reflow(", makes it clear which value each use of "),
+                    alloc.ident(shadow.value),
+                    alloc.reflow(" means."),
+                ]),
+            ]);
+
+            title = INTENTIONAL_SHADOWING.to_string();
+        }
         Problem::CyclicAlias(symbol, region, others, alias_kind) => {
             let answer = crate::error::r#type::cyclic_alias(
                 alloc, lines, symbol, region, others, alias_kind, severity,
@@ -1025,13 +1080,44 @@ pub fn can_problem<'b>(
             doc = alloc.stack([
                 alloc.reflow("This ability cannot be derived:"),
                 alloc.region(lines.convert_region(region), severity),
-                alloc.reflow("Only builtin abilities can be derived."),
+                alloc.reflow(
+                    "Only builtin abilities, and abilities with deriving hooks, can be derived.",
+                ),
                 alloc
                     .note("The builtin abilities are ")
                     .append(list_builtin_abilities(alloc)),
             ]);
             title = ILLEGAL_DERIVE.to_string();
         }
+        Problem::MissingDeriveHooks {
+            region,
+            ability,
+            hooks,
+        } => {
+            doc = alloc.stack([
+                alloc.reflow("This ability cannot be derived for this type:"),
+                alloc.region(lines.convert_region(region), severity),
+                alloc.concat([
+                    alloc.symbol_unqualified(ability),
+                    alloc.reflow(
+                        " has deriving hooks, but deriving it for this type needs these hooks too:",
+                    ),
+                ]),
+                alloc.type_block(
+                    alloc.intersperse(
+                        hooks
+                            .into_iter()
+                            .map(|hook| alloc.ident(hook.as_str().into())),
+                        alloc.string(",".to_string()).append(alloc.space()),
+                    ),
+                ),
+                alloc.tip().append(alloc.reflow(
+                    "Deriving hooks are functions defined next to the ability. For each member \
+                    `m`, `m_record` derives it for records, and `m_tag` for tag unions.",
+                )),
+            ]);
+            title = ILLEGAL_DERIVE.to_string();
+        }
         Problem::NotAnAbility(region) => {
             doc = alloc.stack([
                 alloc.reflow("This identifier is not an ability in scope:"),
@@ -1488,6 +1574,34 @@ pub fn can_problem<'b>(
 
             title = INTERPOLATED_STRING_NOT_ALLOWED.to_string();
         }
+        Problem::DeniedWarning { code, warning } => {
+            let report = can_problem(alloc, lines, filename.clone(), *warning);
+
+            doc = alloc.stack([report.doc, alloc.denied_warning_note(code)]);
+            title = report.title;
+        }
+        Problem::InvalidWarningDirective(directive) => {
+            doc = alloc.stack([
+                alloc.reflow("I don't understand this warning directive:"),
+                alloc.string(directive.to_string()).indent(4),
+                alloc.reflow(
+                    "A warning directive sets the level of a warning to allow, warn or deny, \
+                    for the whole package or for one of its modules. For example:",
+                ),
+                alloc.parser_suggestion(
+                    "# @warnings deny unused-import\n# @warnings Parser.Internal allow all",
+                ),
+                alloc.concat([
+                    alloc.reflow("The warning is named by its code, like "),
+                    alloc.keyword("unused-definition"),
+                    alloc.reflow(" for UNUSED DEFINITION reports, or "),
+                    alloc.keyword("all"),
+                    alloc.reflow(" for every warning."),
+                ]),
+            ]);
+
+            title = INVALID_WARNING_DIRECTIVE.to_string();
+        }
     };
 
     Report {
@@ -2120,6 +2234,7 @@ fn pretty_runtime_error<'b>(
             loc_name,
             suggestion_options: options,
             underscored_suggestion_region,
+            type_compatible_suggestions,
         } => {
             doc = not_found(
                 alloc,
@@ -2128,6 +2243,7 @@ fn pretty_runtime_error<'b>(
                 &loc_name.value,
                 options,
                 underscored_suggestion_region,
+                type_compatible_suggestions,
                 severity,
             );
             title = UNRECOGNIZED_NAME;
@@ -2771,6 +2887,61 @@ fn pretty_runtime_error<'b>(
 
             title = "NON-FUNCTION HOSTED ANNOTATION";
         }
+        RuntimeError::UnsupportedCFunctionType(region) => {
+            doc = alloc.stack([
+                alloc.concat([
+                    alloc.reflow("This function is marked "),
+                    alloc.keyword("# @c"),
+                    alloc.reflow(", but its type can't be passed to C:"),
+                ]),
+                alloc.region(lines.convert_region(region), severity),
+                alloc.concat([
+                    alloc
+                        .reflow("C functions can only take and return integers of up to 64 bits, "),
+                    alloc.type_str("F32"),
+                    alloc.reflow(", "),
+                    alloc.type_str("F64"),
+                    alloc.reflow(", and "),
+                    alloc.type_str("Bool"),
+                    alloc.reflow(", with "),
+                    alloc.type_str("{}"),
+                    alloc.reflow(" for no arguments or no return value."),
+                ]),
+                alloc.concat([
+                    alloc.tip(),
+                    alloc.reflow("Remove the directive to have the host provide a "),
+                    alloc.keyword("roc_fx_"),
+                    alloc.reflow(" function instead, which can take any type."),
+                ]),
+            ]);
+
+            title = "UNSUPPORTED C FUNCTION TYPE";
+        }
+        RuntimeError::NestingTooDeep { region, max_depth } => {
+            // The region covers everything nested inside, which can be huge, so only point
+            // at where it starts.
+            let start = Region::from_pos(region.start());
+
+            doc = alloc.stack([
+                alloc.concat([
+                    alloc.reflow("This expression is nested more than "),
+                    alloc.string(max_depth.to_string()),
+                    alloc.reflow(" levels deep, which is more than I can handle:"),
+                ]),
+                alloc.region(lines.convert_region(start), severity),
+                alloc.reflow(
+                    "Try splitting the outer expressions into smaller defs, so there's less nesting in each one.",
+                ),
+                alloc.concat([
+                    alloc.tip(),
+                    alloc.reflow("If the code is generated and really needs to be this deep, set the "),
+                    alloc.keyword(roc_parse::nesting::MAX_NESTING_DEPTH_ENV_VAR),
+                    alloc.reflow(" environment variable to raise the limit."),
+                ]),
+            ]);
+
+            title = "NESTING TOO DEEP";
+        }
     }
 
     (doc, title)
@@ -2833,11 +3004,13 @@ fn not_found<'b>(
     name: &Ident,
     options: MutSet<Box<str>>,
     underscored_suggestion_region: Option<Region>,
+    type_compatible: MutSet<Box<str>>,
     severity: Severity,
 ) -> RocDocBuilder<'b> {
-    let mut suggestions = suggest::sort(
+    let mut suggestions = suggest::sort_by_fit(
         name.as_inline_str().as_str(),
         options.iter().map(|v| v.as_ref()).collect(),
+        |option| type_compatible.contains(*option),
     );
     suggestions.truncate(4);
 
@@ -0,0 +1,391 @@
+//! Structural diffs of the types in a type mismatch.
+//!
+//! A mismatch between two large records is hard to read when both of them are printed in full,
+//! especially when a field whose type is a record itself differs in only one of its own fields.
+//! A [RecordDiff] lines the fields of the two records up by name, however they are ordered, and
+//! goes into the fields that are records on both sides, so that a report can show only the fields
+//! that were added, removed or changed.
+//!
+//! A [TagUnionDiff] does the same for the tags of two tag unions, and also points out the tags
+//! whose payloads have a different number of values, and the tags that look like misspellings of
+//! the ones that were expected. Editors get either kind of diff as JSON, through [TypeDiff].
+use crate::error::r#type::{
+    error_type_to_doc, ext_has_fixed_fields, should_show_diff, should_show_payload_diff,
+    SharedTypes,
+};
+use crate::report::{CiWrite, RocDocAllocator};
+use roc_collections::all::SendMap;
+use roc_module::ident::{Lowercase, TagName};
+use roc_solve_problem::TypeError;
+use roc_types::types::{AliasKind, ErrorType, RecordField, TypeExt};
+use serde_json::{json, Value};
+
+/// Records with at least this many fields on either side of a mismatch are shown as a diff.
+pub(crate) const RECORD_DIFF_MIN_FIELDS: usize = 8;
+
+/// An unexpected tag is taken for a misspelling of an expected one when they are at most this
+/// many edits apart.
+const TAG_TYPO_MAX_DISTANCE: usize = 2;
+
+/// How a record that was found differs from the one that was expected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordDiff {
+    /// Fields that are expected, but weren't found
+    pub missing: Vec<(Lowercase, RecordField<ErrorType>)>,
+    /// Fields that were found, but aren't expected
+    pub extra: Vec<(Lowercase, RecordField<ErrorType>)>,
+    pub changed: Vec<FieldDiff>,
+    /// How many fields are the same in both records
+    pub same: usize,
+    pub found_ext: TypeExt,
+    pub expected_ext: TypeExt,
+}
+
+/// A field that is in both records, with a different type in each.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldDiff {
+    pub field: Lowercase,
+    pub found: RecordField<ErrorType>,
+    pub expected: RecordField<ErrorType>,
+    /// How the field differs when it's a record on both sides, and required or optional on both
+    pub nested: Option<RecordDiff>,
+}
+
+impl RecordDiff {
+    /// The diff of two record types, or `None` when either one isn't a record. Fields are only
+    /// missing when the found record can't have more fields, and extra when the expected one
+    /// can't.
+    pub fn new(found: &ErrorType, expected: &ErrorType) -> Option<Self> {
+        let (
+            ErrorType::Record(found_fields, found_ext),
+            ErrorType::Record(expected_fields, expected_ext),
+        ) = (found, expected)
+        else {
+            return None;
+        };
+
+        let mut diff = RecordDiff {
+            missing: Vec::new(),
+            extra: Vec::new(),
+            changed: Vec::new(),
+            same: 0,
+            found_ext: found_ext.clone(),
+            expected_ext: expected_ext.clone(),
+        };
+
+        for (field, found) in found_fields.iter() {
+            match expected_fields.get(field) {
+                Some(expected)
+                    if is_optional(found) != is_optional(expected)
+                        || should_show_diff(found.as_inner(), expected.as_inner()) =>
+                {
+                    let same_kind = is_optional(found) == is_optional(expected);
+                    let nested = RecordDiff::new(found.as_inner(), expected.as_inner())
+                        .filter(|nested| same_kind && !nested.is_empty());
+
+                    diff.changed.push(FieldDiff {
+                        field: field.clone(),
+                        found: found.clone(),
+                        expected: expected.clone(),
+                        nested,
+                    });
+                }
+                Some(_) => diff.same += 1,
+                None if ext_has_fixed_fields(expected_ext) => {
+                    diff.extra.push((field.clone(), found.clone()));
+                }
+                None => {}
+            }
+        }
+
+        if ext_has_fixed_fields(found_ext) {
+            for (field, expected) in expected_fields.iter() {
+                if !found_fields.contains_key(field) {
+                    diff.missing.push((field.clone(), expected.clone()));
+                }
+            }
+        }
+
+        diff.missing.sort_by(|(a, _), (b, _)| a.cmp(b));
+        diff.extra.sort_by(|(a, _), (b, _)| a.cmp(b));
+        diff.changed.sort_by(|a, b| a.field.cmp(&b.field));
+
+        Some(diff)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty() && self.extra.is_empty() && self.changed.is_empty()
+    }
+
+    /// Whether the records are large enough to show as a diff.
+    pub(crate) fn is_worth_showing(found: &ErrorType, expected: &ErrorType) -> bool {
+        let field_count = |tipe: &ErrorType| match tipe {
+            ErrorType::Record(fields, _) => fields.len(),
+            _ => 0,
+        };
+
+        field_count(found).max(field_count(expected)) >= RECORD_DIFF_MIN_FIELDS
+    }
+}
+
+fn is_optional(tipe: &RecordField<ErrorType>) -> bool {
+    matches!(
+        tipe,
+        RecordField::Optional(_) | RecordField::RigidOptional(_)
+    )
+}
+
+/// How a tag union that was found differs from the one that was expected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagUnionDiff {
+    /// Tags that are expected, but weren't found
+    pub missing: Vec<(TagName, Vec<ErrorType>)>,
+    /// Tags that were found, but aren't expected
+    pub extra: Vec<(TagName, Vec<ErrorType>)>,
+    pub changed: Vec<TagDiff>,
+    /// How many tags are the same in both tag unions
+    pub same: usize,
+    /// Extra tags, each with the missing tag it's most likely a misspelling of
+    pub typos: Vec<(TagName, TagName)>,
+}
+
+/// A tag that is in both tag unions, with a different payload in each.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagDiff {
+    pub tag: TagName,
+    pub found: Vec<ErrorType>,
+    pub expected: Vec<ErrorType>,
+}
+
+impl TagDiff {
+    /// Whether the payloads have a different number of values, rather than values of different
+    /// types.
+    pub fn is_arity_mismatch(&self) -> bool {
+        self.found.len() != self.expected.len()
+    }
+}
+
+impl TagUnionDiff {
+    /// The diff of two tag union types, or `None` when either one isn't a tag union. Like for
+    /// records, tags are only missing when the found tag union can't have more tags, and extra
+    /// when the expected one can't.
+    pub fn new(found: &ErrorType, expected: &ErrorType) -> Option<Self> {
+        let ((found_tags, found_ext), (expected_tags, expected_ext)) =
+            (tags_of(found)?, tags_of(expected)?);
+
+        let mut diff = TagUnionDiff {
+            missing: Vec::new(),
+            extra: Vec::new(),
+            changed: Vec::new(),
+            same: 0,
+            typos: Vec::new(),
+        };
+
+        for (tag, found) in found_tags.iter() {
+            match expected_tags.get(tag) {
+                Some(expected) if should_show_payload_diff(found, expected) => {
+                    diff.changed.push(TagDiff {
+                        tag: tag.clone(),
+                        found: found.clone(),
+                        expected: expected.clone(),
+                    });
+                }
+                Some(_) => diff.same += 1,
+                None if ext_has_fixed_fields(expected_ext) => {
+                    diff.extra.push((tag.clone(), found.clone()));
+                }
+                None => {}
+            }
+        }
+
+        if ext_has_fixed_fields(found_ext) {
+            for (tag, expected) in expected_tags.iter() {
+                if !found_tags.contains_key(tag) {
+                    diff.missing.push((tag.clone(), expected.clone()));
+                }
+            }
+        }
+
+        diff.missing.sort_by(|(a, _), (b, _)| a.cmp(b));
+        diff.extra.sort_by(|(a, _), (b, _)| a.cmp(b));
+        diff.changed.sort_by(|a, b| a.tag.cmp(&b.tag));
+
+        for (typo, _) in diff.extra.iter() {
+            let nearest = diff
+                .missing
+                .iter()
+                .map(|(tag, _)| {
+                    let distance = distance::damerau_levenshtein(typo.0.as_str(), tag.0.as_str());
+
+                    (distance, tag)
+                })
+                .min_by_key(|(distance, _)| *distance);
+
+            if let Some((distance, tag)) = nearest {
+                if distance <= TAG_TYPO_MAX_DISTANCE {
+                    diff.typos.push((typo.clone(), tag.clone()));
+                }
+            }
+        }
+
+        Some(diff)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty() && self.extra.is_empty() && self.changed.is_empty()
+    }
+}
+
+type Tags = SendMap<TagName, Vec<ErrorType>>;
+
+fn tags_of(tipe: &ErrorType) -> Option<(&Tags, &TypeExt)> {
+    match tipe {
+        ErrorType::TagUnion(tags, ext, _) | ErrorType::RecursiveTagUnion(_, tags, ext, _) => {
+            Some((tags, ext))
+        }
+        _ => None,
+    }
+}
+
+/// The structured diff of a type mismatch, for editors to show alongside the report.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypeDiff {
+    Record(RecordDiff),
+    TagUnion(TagUnionDiff),
+}
+
+impl TypeDiff {
+    /// The diff of two records or two tag unions, looking through structural aliases, or `None`
+    /// when they are something else or don't differ in their fields or tags.
+    pub fn new(found: &ErrorType, expected: &ErrorType) -> Option<Self> {
+        let (found, expected) = (without_aliases(found), without_aliases(expected));
+
+        if let Some(diff) = RecordDiff::new(found, expected) {
+            return (!diff.is_empty()).then_some(TypeDiff::Record(diff));
+        }
+
+        TagUnionDiff::new(found, expected)
+            .filter(|diff| !diff.is_empty())
+            .map(TypeDiff::TagUnion)
+    }
+
+    /// The diff of the found and expected types of a mismatch.
+    pub fn of_problem(problem: &TypeError) -> Option<Self> {
+        match problem {
+            TypeError::BadExpr(_, _, found, expected) => {
+                TypeDiff::new(found, expected.get_type_ref())
+            }
+            TypeError::BadPattern(_, _, found, expected) => {
+                TypeDiff::new(found, expected.get_type_ref())
+            }
+            _ => None,
+        }
+    }
+
+    /// The diff as JSON, with every type printed the way a report would print it.
+    pub fn to_json<'b>(&self, alloc: &'b RocDocAllocator<'b>) -> Value {
+        // Print every type in full, without changing which types the next report shares.
+        let shared_types = alloc.shared_types.replace(SharedTypes::default());
+
+        let json = match self {
+            TypeDiff::Record(diff) => record_diff_to_json(alloc, diff),
+            TypeDiff::TagUnion(diff) => tag_union_diff_to_json(alloc, diff),
+        };
+
+        alloc.shared_types.replace(shared_types);
+
+        json
+    }
+}
+
+fn without_aliases(tipe: &ErrorType) -> &ErrorType {
+    match tipe {
+        ErrorType::Alias(_, _, real, AliasKind::Structural) => without_aliases(real),
+        _ => tipe,
+    }
+}
+
+fn record_diff_to_json<'b>(alloc: &'b RocDocAllocator<'b>, diff: &RecordDiff) -> Value {
+    let field_to_json = |(field, tipe): &(Lowercase, RecordField<ErrorType>)| {
+        json!({
+            "field": field.as_str(),
+            "type": type_to_string(alloc, tipe.as_inner()),
+            "optional": is_optional(tipe),
+        })
+    };
+
+    let changed: Vec<_> = diff
+        .changed
+        .iter()
+        .map(|field| {
+            json!({
+                "field": field.field.as_str(),
+                "found": type_to_string(alloc, field.found.as_inner()),
+                "expected": type_to_string(alloc, field.expected.as_inner()),
+                "nested": field.nested.as_ref().map(|nested| record_diff_to_json(alloc, nested)),
+            })
+        })
+        .collect();
+
+    json!({
+        "kind": "record",
+        "missing": diff.missing.iter().map(field_to_json).collect::<Vec<_>>(),
+        "extra": diff.extra.iter().map(field_to_json).collect::<Vec<_>>(),
+        "changed": changed,
+        "same": diff.same,
+    })
+}
+
+fn tag_union_diff_to_json<'b>(alloc: &'b RocDocAllocator<'b>, diff: &TagUnionDiff) -> Value {
+    let payload_to_json = |payload: &[ErrorType]| {
+        payload
+            .iter()
+            .map(|tipe| type_to_string(alloc, tipe))
+            .collect::<Vec<_>>()
+    };
+    let tag_to_json = |(tag, payload): &(TagName, Vec<ErrorType>)| {
+        json!({
+            "tag": tag.0.as_str(),
+            "payload": payload_to_json(payload),
+        })
+    };
+
+    let changed: Vec<_> = diff
+        .changed
+        .iter()
+        .map(|tag| {
+            json!({
+                "tag": tag.tag.0.as_str(),
+                "found": payload_to_json(&tag.found),
+                "expected": payload_to_json(&tag.expected),
+                "arity_mismatch": tag.is_arity_mismatch(),
+            })
+        })
+        .collect();
+
+    let typos: Vec<_> = diff
+        .typos
+        .iter()
+        .map(|(found, expected)| json!({ "found": found.0.as_str(), "expected": expected.0.as_str() }))
+        .collect();
+
+    json!({
+        "kind": "tag_union",
+        "missing": diff.missing.iter().map(tag_to_json).collect::<Vec<_>>(),
+        "extra": diff.extra.iter().map(tag_to_json).collect::<Vec<_>>(),
+        "changed": changed,
+        "same": diff.same,
+        "typos": typos,
+    })
+}
+
+fn type_to_string<'b>(alloc: &'b RocDocAllocator<'b>, tipe: &ErrorType) -> String {
+    let mut buf = String::new();
+
+    error_type_to_doc(alloc, tipe.clone())
+        .1
+        .render_raw(1000, &mut CiWrite::new(&mut buf))
+        .expect("<buffer is not a utf-8 encoded string>");
+
+    buf
+}
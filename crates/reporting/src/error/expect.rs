@@ -1,7 +1,7 @@
 use std::path::PathBuf;
 
 use bumpalo::Bump;
-use roc_fmt::MigrationFlags;
+use roc_fmt::{DbgMigration, MigrationFlags};
 use roc_module::symbol::{Interns, ModuleId, Symbol};
 use roc_parse::ast::Expr;
 use roc_problem::Severity;
@@ -63,6 +63,8 @@ impl<'a> Renderer<'a> {
             MigrationFlags {
                 snakify: false,
                 parens_and_commas: false,
+                dbg: DbgMigration::Keep,
+                transforms: &[],
             },
         );
         expr.format(&mut buf, 0);
@@ -213,6 +215,8 @@ impl<'a> Renderer<'a> {
             MigrationFlags {
                 snakify: false,
                 parens_and_commas: false,
+                dbg: DbgMigration::Keep,
+                transforms: &[],
             },
         );
         {
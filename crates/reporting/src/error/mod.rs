@@ -1,4 +1,6 @@
 pub mod canonicalize;
+pub mod diff;
 pub mod expect;
+pub mod mono;
 pub mod parse;
 pub mod r#type;
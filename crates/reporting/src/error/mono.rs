@@ -0,0 +1,181 @@
+use roc_problem::mono::{ComptimeFailure, ComptimeProblem, NonTailCall, Problem};
+use roc_region::all::LineInfo;
+use std::path::PathBuf;
+
+use crate::report::{Report, RocDocAllocator, RocDocBuilder};
+
+const NOT_TAIL_RECURSIVE: &str = "NOT TAIL RECURSIVE";
+const NOT_A_CONSTANT: &str = "NOT A CONSTANT";
+const COMPTIME_EXPECT_FAILED: &str = "COMPTIME EXPECT FAILED";
+
+pub fn mono_problem<'b>(
+    alloc: &'b RocDocAllocator<'b>,
+    lines: &LineInfo,
+    filename: PathBuf,
+    problem: Problem,
+) -> Report<'b> {
+    let severity = problem.severity();
+    let region = alloc.region(lines.convert_region(problem.region()), severity);
+
+    let (title, doc) = match problem {
+        Problem::NotTailRecursive { symbol, call, .. } => {
+            let doc = alloc.stack([
+                alloc.concat([
+                    alloc.symbol_unqualified(symbol),
+                    alloc.reflow(" is marked "),
+                    alloc.keyword("# @tailrec"),
+                    alloc.reflow(", but one of its calls to itself isn't a tail call:"),
+                ]),
+                region,
+                non_tail_call_reason(alloc, call),
+                alloc.concat([
+                    alloc.tip(),
+                    alloc.reflow("Make the recursive call the last thing "),
+                    alloc.symbol_unqualified(symbol),
+                    alloc.reflow(
+                        " does, e.g. by passing the partial result along in an extra argument.",
+                    ),
+                ]),
+            ]);
+
+            (NOT_TAIL_RECURSIVE, doc)
+        }
+        Problem::Comptime(ComptimeFailure {
+            problem: ComptimeProblem::ExpectFailed,
+            ..
+        }) => {
+            let doc = alloc.stack([
+                alloc.concat([
+                    alloc.reflow("This "),
+                    alloc.keyword("expect"),
+                    alloc.reflow(" is marked "),
+                    alloc.keyword("# @comptime"),
+                    alloc.reflow(", and its condition is false:"),
+                ]),
+                region,
+                alloc.reflow("The program isn't built until it holds."),
+            ]);
+
+            (COMPTIME_EXPECT_FAILED, doc)
+        }
+        Problem::Comptime(ComptimeFailure {
+            symbol,
+            problem,
+            is_expect,
+            ..
+        }) => {
+            let (subject, hint) = if is_expect {
+                (
+                    alloc.concat([alloc.reflow("This "), alloc.keyword("expect")]),
+                    alloc.concat([
+                        alloc.reflow("Remove the directive to check it with "),
+                        alloc.keyword("roc test"),
+                        alloc.reflow(" instead."),
+                    ]),
+                )
+            } else {
+                (
+                    alloc.symbol_unqualified(symbol),
+                    alloc.concat([
+                        alloc.reflow("Remove the directive to compute "),
+                        alloc.symbol_unqualified(symbol),
+                        alloc.reflow(" when the program runs instead."),
+                    ]),
+                )
+            };
+
+            let doc = alloc.stack([
+                alloc.concat([
+                    subject,
+                    alloc.reflow(" is marked "),
+                    alloc.keyword("# @comptime"),
+                    alloc.reflow(", but it can't be evaluated while compiling:"),
+                ]),
+                region,
+                comptime_reason(alloc, problem),
+                alloc.concat([alloc.tip(), hint]),
+            ]);
+
+            (NOT_A_CONSTANT, doc)
+        }
+    };
+
+    Report {
+        title: title.to_string(),
+        filename,
+        doc,
+        severity,
+    }
+}
+
+fn non_tail_call_reason<'b>(
+    alloc: &'b RocDocAllocator<'b>,
+    call: NonTailCall,
+) -> RocDocBuilder<'b> {
+    match call {
+        NonTailCall::PassedTo(callee) => alloc.concat([
+            alloc.reflow("Its result is passed to "),
+            alloc.symbol_qualified(callee),
+            alloc.reflow("."),
+        ]),
+        NonTailCall::PassedToLowLevel(op) => alloc.concat([
+            alloc.reflow("Its result is passed to the builtin operation "),
+            alloc.string(format!("{op:?}")),
+            alloc.reflow("."),
+        ]),
+        NonTailCall::Stored => alloc.reflow("Its result is stored in a record, tag or list."),
+        NonTailCall::PassedToJoinPoint => alloc.concat([
+            alloc.reflow("It's in a branch of an "),
+            alloc.keyword("if"),
+            alloc.reflow(" or "),
+            alloc.keyword("when"),
+            alloc.reflow(" whose result is used afterwards."),
+        ]),
+        NonTailCall::Refcounting => {
+            alloc.reflow("Values have to be freed after it returns, because it borrows them.")
+        }
+        NonTailCall::Other => alloc.reflow("More work is done after it returns."),
+    }
+}
+
+fn comptime_reason<'b>(
+    alloc: &'b RocDocAllocator<'b>,
+    problem: ComptimeProblem,
+) -> RocDocBuilder<'b> {
+    match problem {
+        ComptimeProblem::IsFunction => {
+            alloc.reflow("It's a function, so it doesn't have a single value.")
+        }
+        ComptimeProblem::Crashed(message) => alloc.concat([
+            alloc.reflow("It crashed with "),
+            alloc.string(format!("{message:?}")),
+            alloc.reflow("."),
+        ]),
+        ComptimeProblem::Unsupported(what) => alloc.concat([
+            alloc.reflow("It uses "),
+            alloc.string(what),
+            alloc.reflow(", which can't be evaluated while compiling."),
+        ]),
+        ComptimeProblem::UnsupportedCall(callee) => alloc.concat([
+            alloc.reflow("It calls "),
+            alloc.symbol_qualified(callee),
+            alloc.reflow(", which has no implementation in the program."),
+        ]),
+        ComptimeProblem::OutOfFuel { fuel } => alloc.concat([
+            alloc.reflow("Evaluating it didn't finish within "),
+            alloc.string(fuel.to_string()),
+            alloc.reflow(" steps. If it's supposed to take that long, raise the limit with "),
+            alloc.keyword("--comptime-fuel"),
+            alloc.reflow("."),
+        ]),
+        ComptimeProblem::TooDeep { max_depth } => alloc.concat([
+            alloc.reflow("Evaluating it made more than "),
+            alloc.string(max_depth.to_string()),
+            alloc.reflow(" nested calls."),
+        ]),
+        ComptimeProblem::ContainsFunction => {
+            alloc.reflow("Its value contains a function, which can't be embedded in the program.")
+        }
+        ComptimeProblem::ExpectFailed => alloc.reflow("Its condition is false."),
+    }
+}
@@ -1,3 +1,4 @@
+use roc_can::header_repair::missing_header_fix;
 use roc_parse::parser::{ENumber, EReturn, ESingleQuote, EString, FileError, PList, SyntaxError};
 use roc_problem::Severity;
 use roc_region::all::{LineColumn, LineColumnRegion, LineInfo, Position, Region};
@@ -3689,6 +3690,10 @@ fn to_header_report<'a>(
             to_packages_report(alloc, lines, filename, packages, *pos)
         }
 
+        EHeader::Manifest(manifest, pos) => {
+            to_manifest_report(alloc, lines, filename, manifest, *pos)
+        }
+
         EHeader::IndentStart(pos) => {
             let surroundings = Region::new(start, *pos);
             let region = LineColumnRegion::from_pos(lines.convert_pos(*pos));
@@ -3729,17 +3734,34 @@ fn to_header_report<'a>(
                 vec![alloc.reflow(r"I am expecting a header, but the file is not UTF-8 encoded.")]
             };
 
-            let doc = alloc.stack(preamble.into_iter().chain([alloc.concat([
-                alloc.reflow("I am expecting a module keyword next, one of "),
-                alloc.keyword("interface"),
-                alloc.reflow(", "),
-                alloc.keyword("app"),
-                alloc.reflow(", "),
-                alloc.keyword("package"),
-                alloc.reflow(" or "),
-                alloc.keyword("platform"),
-                alloc.reflow("."),
-            ])]));
+            // If the rest of the file is fine, suggest a header that exposes all its defs
+            let suggested_header = missing_header_fix(&alloc.src_lines.join("\n"))
+                .and_then(|fix| fix.edits.into_iter().next())
+                .map(|edit| {
+                    alloc.stack([
+                        alloc.reflow("If this is meant to be a module, it could start with:"),
+                        alloc
+                            .text(edit.replacement.trim_end().to_string())
+                            .indent(4),
+                    ])
+                });
+
+            let doc = alloc.stack(
+                preamble
+                    .into_iter()
+                    .chain([alloc.concat([
+                        alloc.reflow("I am expecting a module keyword next, one of "),
+                        alloc.keyword("interface"),
+                        alloc.reflow(", "),
+                        alloc.keyword("app"),
+                        alloc.reflow(", "),
+                        alloc.keyword("package"),
+                        alloc.reflow(" or "),
+                        alloc.keyword("platform"),
+                        alloc.reflow("."),
+                    ])])
+                    .chain(suggested_header),
+            );
 
             Report {
                 filename,
@@ -4494,6 +4516,96 @@ fn to_packages_report<'a>(
     }
 }
 
+fn to_manifest_report<'a>(
+    alloc: &'a RocDocAllocator<'a>,
+    lines: &LineInfo,
+    filename: PathBuf,
+    parse_problem: &roc_parse::parser::EManifest<'a>,
+    start: Position,
+) -> Report<'a> {
+    use roc_parse::parser::{EManifest, EManifestField};
+
+    let severity = Severity::RuntimeError;
+
+    match *parse_problem {
+        EManifest::ListEnd(pos) => {
+            let surroundings = Region::new(start, pos);
+            let region = LineColumnRegion::from_pos(lines.convert_pos(pos));
+
+            let doc = alloc.stack([
+                alloc.reflow(
+                    r"I am partway through parsing a package manifest, but I got stuck here:",
+                ),
+                alloc.region_with_subregion(lines.convert_region(surroundings), region, severity),
+                alloc.concat([
+                    alloc.reflow("I am expecting a comma or the end of the manifest, like")
+                ]),
+                alloc
+                    .parser_suggestion("manifest { name: \"json\", version: \"1.0.0\" }")
+                    .indent(4),
+            ]);
+
+            Report {
+                filename,
+                doc,
+                title: "WEIRD MANIFEST".to_string(),
+                severity,
+            }
+        }
+
+        EManifest::Field(
+            EManifestField::Colon(pos) | EManifestField::Value(pos) | EManifestField::Str(_, pos),
+            _,
+        ) => {
+            let surroundings = Region::new(start, pos);
+            let region = LineColumnRegion::from_pos(lines.convert_pos(pos));
+
+            let doc = alloc.stack([
+                alloc.reflow(r"I am partway through parsing a package manifest field, but I got stuck here:"),
+                alloc.region_with_subregion(lines.convert_region(surroundings), region, severity),
+                alloc.concat([
+                    alloc.reflow("Each field of a manifest is a name, a colon, and then either a string or a list of strings, like"),
+                ]),
+                alloc
+                    .parser_suggestion("license: \"UPL-1.0\",\nauthors: [\"Ada Lovelace\"],")
+                    .indent(4),
+            ]);
+
+            Report {
+                filename,
+                doc,
+                title: "WEIRD MANIFEST FIELD".to_string(),
+                severity,
+            }
+        }
+
+        EManifest::Space(error, pos) => to_space_report(alloc, lines, filename, &error, pos),
+        EManifest::Field(EManifestField::Space(error, pos), _) => {
+            to_space_report(alloc, lines, filename, &error, pos)
+        }
+
+        // If you're adding or changing syntax, please handle the case with a
+        // good error message above instead of adding more unhandled cases below.
+        EManifest::Manifest(pos)
+        | EManifest::IndentManifest(pos)
+        | EManifest::IndentListStart(pos)
+        | EManifest::ListStart(pos)
+        | EManifest::Field(
+            EManifestField::Name(pos)
+            | EManifestField::IndentValue(pos)
+            | EManifestField::ListEnd(pos),
+            _,
+        ) => to_unhandled_parse_error_report(
+            alloc,
+            lines,
+            filename,
+            format!("{:?}", parse_problem),
+            pos,
+            start,
+        ),
+    }
+}
+
 fn to_space_report<'a>(
     alloc: &'a RocDocAllocator<'a>,
     lines: &LineInfo,
@@ -1,6 +1,7 @@
 #![allow(clippy::too_many_arguments)]
 
 use crate::error::canonicalize::{to_circular_def_doc, CIRCULAR_DEF};
+use crate::error::diff::RecordDiff;
 use crate::report::{Annotation, Report, RocDocAllocator, RocDocBuilder};
 use itertools::EitherOrBoth;
 use itertools::Itertools;
@@ -17,7 +18,7 @@ use roc_module::symbol::Symbol;
 use roc_problem::Severity;
 use roc_region::all::{LineInfo, Region};
 use roc_solve_problem::{
-    NotDerivableContext, NotDerivableEq, TypeError, UnderivableReason, Unfulfilled,
+    HoleCompletion, NotDerivableContext, NotDerivableEq, TypeError, UnderivableReason, Unfulfilled,
 };
 use roc_std::RocDec;
 use roc_types::pretty_print::{Parens, WILDCARD};
@@ -25,6 +26,8 @@ use roc_types::types::{
     AbilitySet, AliasKind, Category, EarlyReturnKind, ErrorType, IndexOrField, PatternCategory,
     Polarity, Reason, RecordField, TypeExt,
 };
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 use std::path::PathBuf;
 use ven_pretty::{text, DocAllocator};
 
@@ -41,6 +44,23 @@ pub fn type_problem<'b>(
     lines: &LineInfo,
     filename: PathBuf,
     problem: TypeError,
+) -> Option<Report<'b>> {
+    alloc.shared_types.borrow_mut().start_report();
+
+    let report = type_problem_help(alloc, lines, filename, problem);
+
+    if report.is_none() {
+        alloc.shared_types.borrow_mut().discard_report();
+    }
+
+    report
+}
+
+fn type_problem_help<'b>(
+    alloc: &'b RocDocAllocator<'b>,
+    lines: &LineInfo,
+    filename: PathBuf,
+    problem: TypeError,
 ) -> Option<Report<'b>> {
     use TypeError::*;
 
@@ -545,6 +565,129 @@ pub fn type_problem<'b>(
                 severity,
             })
         }
+        TypedHole {
+            region,
+            name,
+            expected,
+            bindings,
+            completions,
+        } => Some(to_typed_hole_report(
+            alloc,
+            lines,
+            filename,
+            region,
+            name,
+            expected,
+            bindings,
+            completions,
+            severity,
+        )),
+        DeniedWarning { code, warning } => {
+            let mut report = type_problem(alloc, lines, filename, *warning)?;
+
+            report.doc = alloc.stack([report.doc, alloc.denied_warning_note(code)]);
+            report.severity = severity;
+
+            Some(report)
+        }
+    }
+}
+
+/// The most values in scope that a typed hole report lists, innermost first.
+const MAX_HOLE_BINDINGS: usize = 6;
+
+fn to_typed_hole_report<'b>(
+    alloc: &'b RocDocAllocator<'b>,
+    lines: &LineInfo,
+    filename: PathBuf,
+    region: Region,
+    name: Option<Lowercase>,
+    expected: ErrorType,
+    bindings: Vec<(Symbol, ErrorType)>,
+    completions: Vec<HoleCompletion>,
+    severity: Severity,
+) -> Report<'b> {
+    let intro = match name {
+        Some(name) => alloc.concat([
+            alloc.reflow("The hole "),
+            alloc
+                .string(format!("?{name}"))
+                .annotate(Annotation::Keyword),
+            alloc.reflow(" needs a value of type:"),
+        ]),
+        None => alloc.reflow("This hole needs a value of type:"),
+    };
+
+    let mut stack = vec![
+        intro,
+        alloc.region(lines.convert_region(region), severity),
+        alloc.type_block(error_type_to_doc(alloc, expected)),
+    ];
+
+    // Bindings are in the order they were introduced, so the innermost ones are last
+    let bindings: Vec<_> = bindings
+        .into_iter()
+        .rev()
+        .filter(|(symbol, _)| !symbol.is_generated(alloc.interns))
+        .collect();
+
+    if !bindings.is_empty() {
+        let omitted = bindings.len().saturating_sub(MAX_HOLE_BINDINGS);
+        let listed = bindings
+            .into_iter()
+            .take(MAX_HOLE_BINDINGS)
+            .map(|(symbol, typ)| {
+                alloc.concat([
+                    alloc.symbol_unqualified(symbol),
+                    alloc.text(" : "),
+                    error_type_to_doc(alloc, typ),
+                ])
+            });
+
+        stack.push(alloc.reflow("These values are in scope:"));
+        stack.push(alloc.type_block(alloc.vcat(listed)));
+
+        if omitted > 0 {
+            stack.push(if omitted == 1 {
+                alloc.reflow("1 more value is in scope, but isn't shown.")
+            } else {
+                alloc.string(format!(
+                    "{omitted} more values are in scope, but aren't shown."
+                ))
+            });
+        }
+    }
+
+    let completions: Vec<_> = completions
+        .into_iter()
+        .rev()
+        .filter(|completion| !completion.symbol.is_generated(alloc.interns))
+        .map(|HoleCompletion { symbol, arity }| {
+            let name = symbol.as_str(alloc.interns);
+
+            if arity == 0 {
+                alloc.text(name)
+            } else {
+                alloc.string(format!("{name}({})", vec!["???"; arity].join(", ")))
+            }
+        })
+        .collect();
+
+    if !completions.is_empty() {
+        stack.push(alloc.reflow("These would fit in the hole:"));
+        stack.push(alloc.vcat(completions).indent(4));
+    }
+
+    stack.push(alloc.concat([
+        alloc.tip(),
+        alloc.reflow("The program still runs, but it crashes if it evaluates this hole."),
+    ]));
+
+    Report {
+        title: "TYPED HOLE".to_string(),
+        filename,
+        doc: alloc.stack(stack),
+        severity,
     }
 }
 
@@ -796,6 +939,101 @@ pub fn cyclic_alias<'b>(
     (doc, "CYCLIC ALIAS".to_string())
 }
 
+/// Record builders are desugared into nested calls to their mapper, so a plain "argument
+/// mismatch" would point at code the user never wrote. Describe the problem in terms of the
+/// builder's fields instead.
+#[allow(clippy::too_many_arguments)]
+fn report_record_builder_arg_mismatch<'b>(
+    alloc: &'b RocDocAllocator<'b>,
+    lines: &LineInfo,
+    filename: PathBuf,
+    severity: Severity,
+    category: &Category,
+    found: ErrorType,
+    expected_type: ErrorType,
+    expr_region: roc_region::all::Region,
+    region: roc_region::all::Region,
+    name: Option<Symbol>,
+    arg_index: HumanIndex,
+) -> Report<'b> {
+    let mapper = || match name {
+        None => alloc.text("the mapper"),
+        Some(symbol) => alloc.concat([
+            alloc.text("the "),
+            alloc.symbol_unqualified(symbol),
+            alloc.text(" mapper"),
+        ]),
+    };
+
+    // The first two arguments are the values of the fields (or of the fields after them,
+    // already combined), and the last is the closure the desugaring generated.
+    let (problem, this_is, instead_of, hint) = if arg_index.to_zero_based() < 2 {
+        (
+            alloc.concat([
+                alloc.reflow("This field's value doesn't have the type "),
+                mapper(),
+                alloc.reflow(" expects:"),
+            ]),
+            alloc.text("The field's value is"),
+            alloc.concat([
+                alloc.text("But "),
+                mapper(),
+                alloc.string(format!(
+                    " needs its {} argument to be:",
+                    arg_index.ordinal()
+                )),
+            ]),
+            alloc.concat([
+                alloc.tip(),
+                alloc.reflow("Every field in a record builder must be something "),
+                mapper(),
+                alloc.reflow(" can combine, like all parsers or all decoders."),
+            ]),
+        )
+    } else {
+        (
+            alloc.concat([
+                alloc.reflow("This record builder can't combine its fields with "),
+                mapper(),
+                alloc.text(":"),
+            ]),
+            alloc.reflow("The function combining two fields is"),
+            alloc.concat([
+                alloc.text("But "),
+                mapper(),
+                alloc.reflow(" needs its last argument to be:"),
+            ]),
+            alloc.concat([
+                alloc.tip(),
+                alloc.reflow(
+                    "The last argument of a mapper should be a function that takes two values \
+                    and combines them. The record builder uses it to put its fields together.",
+                ),
+            ]),
+        )
+    };
+
+    let mut report = report_mismatch(
+        alloc,
+        lines,
+        filename,
+        severity,
+        category,
+        found,
+        expected_type,
+        expr_region,
+        Some(region),
+        problem,
+        this_is,
+        instead_of,
+        Some(hint),
+    );
+
+    report.title = "RECORD BUILDER MISMATCH".to_string();
+
+    report
+}
+
 fn report_mismatch<'b>(
     alloc: &'b RocDocAllocator<'b>,
     lines: &LineInfo,
@@ -944,6 +1182,7 @@ fn to_expr_report<'b>(
                             field,
                             "",
                             expr_region,
+                            expected_fields.get(field).map(RecordField::as_inner),
                             found_fields,
                             found_ext,
                         );
@@ -1423,6 +1662,13 @@ fn to_expr_report<'b>(
                                 field,
                                 ":",
                                 *field_region,
+                                match expected_type.clone().unwrap_structural_alias() {
+                                    ErrorType::Record(updated_fields, _) => updated_fields
+                                        .get(field)
+                                        .map(|updated| updated.as_inner().clone()),
+                                    _ => None,
+                                }
+                                .as_ref(),
                                 actual_fields,
                                 ext,
                             ),
@@ -1517,7 +1763,47 @@ fn to_expr_report<'b>(
                         ]),
                     };
 
-                    if n < arity as usize {
+                    if called_via == CalledVia::RecordBuilder {
+                        let this_mapper = match name {
+                            None => alloc.text("This record builder's mapper"),
+                            Some(symbol) => alloc.concat([
+                                alloc.text("The "),
+                                alloc.symbol_unqualified(symbol),
+                                alloc.text(" mapper"),
+                            ]),
+                        };
+
+                        let lines = vec![
+                            alloc.concat([
+                                this_mapper,
+                                alloc.string(format!(
+                                    " takes {}, but record builders call their mapper with {}:",
+                                    if n == 1 {
+                                        "1 argument".into()
+                                    } else {
+                                        format!("{n} arguments")
+                                    },
+                                    arity
+                                )),
+                            ]),
+                            alloc.region(lines.convert_region(expr_region), severity),
+                            alloc.concat([
+                                alloc.reflow("A mapper before the "),
+                                alloc.backwards_arrow(),
+                                alloc.reflow(
+                                    " is given the values of two fields, and a function that \
+                                    combines them into one value.",
+                                ),
+                            ]),
+                        ];
+
+                        Report {
+                            filename,
+                            title: "WRONG MAPPER ARITY".to_string(),
+                            doc: alloc.stack(lines),
+                            severity,
+                        }
+                    } else if n < arity as usize {
                         let lines = vec![
                             alloc.concat([
                                 this_function,
@@ -1578,6 +1864,22 @@ fn to_expr_report<'b>(
             } => {
                 let ith = arg_index.ordinal();
 
+                if called_via == CalledVia::RecordBuilder {
+                    return report_record_builder_arg_mismatch(
+                        alloc,
+                        lines,
+                        filename,
+                        severity,
+                        &category,
+                        found,
+                        expected_type,
+                        expr_region,
+                        region,
+                        name,
+                        arg_index,
+                    );
+                }
+
                 let this_function = match (called_via, name) {
                     (CalledVia::Space, Some(symbole)) => alloc.symbol_unqualified(symbole),
                     (CalledVia::BinOp(op), _) => alloc.binop(op),
@@ -2067,7 +2369,18 @@ fn type_comparison<'b>(
     instead_of: RocDocBuilder<'b>,
     context_hints: Option<RocDocBuilder<'b>>,
 ) -> RocDocBuilder<'b> {
-    let comparison = to_comparison(alloc, actual, expected);
+    let record_diff = RecordDiff::new(&actual, &expected)
+        .filter(|diff| !diff.is_empty() && RecordDiff::is_worth_showing(&actual, &expected));
+    let mut comparison = to_comparison(alloc, actual, expected);
+
+    // Large records only show the fields that differ, down to the fields of nested records.
+    if let Some(record_diff) = record_diff {
+        let diff = record_diff_to_diff(alloc, record_diff);
+
+        comparison.actual = alloc.type_block(type_with_able_vars(alloc, diff.left, diff.left_able));
+        comparison.expected =
+            alloc.type_block(type_with_able_vars(alloc, diff.right, diff.right_able));
+    }
 
     let mut lines = vec![
         i_am_seeing,
@@ -2304,6 +2617,9 @@ fn format_category<'b>(
         Crash => {
             internal_error!("calls to crash should be unconditionally admitted in any context, unexpected reachability!");
         }
+        TypedHole => {
+            internal_error!("typed holes should be unconditionally admitted in any context, unexpected reachability!");
+        }
 
         Storage(..) | Unknown => (
             alloc.concat([this_is, alloc.text(" a value")]),
@@ -2657,6 +2973,7 @@ pub enum Problem {
     FieldsMissing(Vec<Lowercase>),
     TagTypo(TagName, Vec<TagName>),
     TagsMissing(Vec<TagName>),
+    TagArityMismatch(TagName, usize, usize),
     BadRigidVar(Lowercase, ErrorType, Option<AbilitySet>),
     OptionalRequiredMismatch(Lowercase),
     OpaqueComparedToNonOpaque,
@@ -2729,6 +3046,19 @@ pub mod suggest {
 
         options
     }
+
+    /// Like [`sort`], but the options that fit where the typo is used come first.
+    pub fn sort_by_fit<T, F>(typo: &str, options: Vec<T>, fits: F) -> Vec<T>
+    where
+        T: ToStr,
+        F: Fn(&T) -> bool,
+    {
+        let mut options = sort(typo, options);
+
+        options.sort_by_key(|option| !fits(option));
+
+        options
+    }
 }
 
 pub struct Comparison<'b> {
@@ -2857,17 +3187,267 @@ struct Context {
     able_variables: AbleVariables,
 }
 
+/// Records, tuples and tag unions with at least this many types in them are large. When the
+/// allocator shares types (see [RocDocAllocator::with_shared_types]), a large type is printed in
+/// full by the first report of a module that has it, and later reports print it as
+/// `{ …as in a previous error }`, which keeps an error storm about one huge record readable.
+/// Either way, the doc of a large type is only built once, see [TypeDocs].
+const SHARED_TYPE_MIN_SIZE: usize = 40;
+
+/// How many large types the reports of a module remember. Once that many have been printed, the
+/// ones that come after are always printed in full.
+const SHARED_TYPES_CAPACITY: usize = 64;
+
+fn content_hash(tipe: &ErrorType) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    tipe.hash(&mut hasher);
+
+    hasher.finish()
+}
+
+/// The large types printed by the reports of a module, by the hash of their content.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SharedTypes {
+    /// Whether later reports refer back to the large types that earlier ones printed
+    enabled: bool,
+    /// The hashes of the types printed so far, with the ones the current report printed last
+    hashes: [u64; SHARED_TYPES_CAPACITY],
+    len: usize,
+    /// How many of the hashes were printed by the reports before the current one
+    printed: usize,
+    /// How many times a type was printed as a reference to a previous report
+    references: usize,
+}
+
+impl Default for SharedTypes {
+    fn default() -> Self {
+        SharedTypes {
+            enabled: false,
+            hashes: [0; SHARED_TYPES_CAPACITY],
+            len: 0,
+            printed: 0,
+            references: 0,
+        }
+    }
+}
+
+impl SharedTypes {
+    pub(crate) fn enable(&mut self) {
+        self.enabled = true;
+    }
+
+    fn start_report(&mut self) {
+        self.printed = self.len;
+    }
+
+    /// Forgets the types the current report printed, because it isn't shown.
+    fn discard_report(&mut self) {
+        self.len = self.printed;
+    }
+
+    /// Whether a previous report printed this type. If not, the current one prints it.
+    fn is_printed(&mut self, tipe: &ErrorType) -> bool {
+        if !self.enabled {
+            return false;
+        }
+
+        let hash = content_hash(tipe);
+
+        if self.hashes[..self.printed].contains(&hash) {
+            self.references += 1;
+
+            return true;
+        }
+
+        if self.len < SHARED_TYPES_CAPACITY && !self.hashes[self.printed..self.len].contains(&hash)
+        {
+            self.hashes[self.len] = hash;
+            self.len += 1;
+        }
+
+        false
+    }
+}
+
+/// The docs of the large types rendered by an allocator, by the hash of their content, so that a
+/// type that's in many reports is only walked once. The docs are stored without the lifetime of
+/// the allocator, which owns them.
+#[derive(Debug, Default)]
+pub(crate) struct TypeDocs {
+    docs: Vec<(u64, Parens, TypeDoc, AbleVariables)>,
+}
+
+impl TypeDocs {
+    fn get<'b>(
+        &self,
+        alloc: &'b RocDocAllocator<'b>,
+        hash: u64,
+        parens: Parens,
+    ) -> Option<(RocDocBuilder<'b>, AbleVariables)> {
+        self.docs
+            .iter()
+            .find(|(h, p, _, _)| *h == hash && *p == parens)
+            .map(|(_, _, doc, able_variables)| (doc.to_doc(alloc), able_variables.clone()))
+    }
+
+    fn insert(&mut self, hash: u64, parens: Parens, doc: TypeDoc, able_variables: AbleVariables) {
+        if self.docs.len() < SHARED_TYPES_CAPACITY {
+            self.docs.push((hash, parens, doc, able_variables));
+        }
+    }
+}
+
+/// A copy of a type's doc that owns its text.
+#[derive(Debug)]
+enum TypeDoc {
+    Nil,
+    Append(Box<TypeDoc>, Box<TypeDoc>),
+    Group(Box<TypeDoc>),
+    FlatAlt(Box<TypeDoc>, Box<TypeDoc>),
+    Nest(isize, Box<TypeDoc>),
+    Line,
+    Text(Box<str>),
+    Annotated(Annotation, Box<TypeDoc>),
+}
+
+impl TypeDoc {
+    /// Copies a doc, unless it lays itself out depending on the column it's at.
+    fn from_doc<'b>(
+        doc: &ven_pretty::Doc<'b, ven_pretty::BoxDoc<'b, Annotation>, Annotation>,
+    ) -> Option<Self> {
+        use ven_pretty::Doc;
+
+        let copy = |doc: &ven_pretty::BoxDoc<'b, Annotation>| Self::from_doc(doc).map(Box::new);
+
+        Some(match doc {
+            Doc::Nil => TypeDoc::Nil,
+            Doc::Append(left, right) => TypeDoc::Append(copy(left)?, copy(right)?),
+            Doc::Group(doc) => TypeDoc::Group(copy(doc)?),
+            Doc::FlatAlt(broken, flat) => TypeDoc::FlatAlt(copy(broken)?, copy(flat)?),
+            Doc::Nest(offset, doc) => TypeDoc::Nest(*offset, copy(doc)?),
+            Doc::Line => TypeDoc::Line,
+            Doc::OwnedText(text) => TypeDoc::Text(text.clone()),
+            Doc::BorrowedText(text) => TypeDoc::Text((*text).into()),
+            Doc::SmallText(text) => TypeDoc::Text(text.as_str().into()),
+            Doc::Annotated(annotation, doc) => TypeDoc::Annotated(*annotation, copy(doc)?),
+            Doc::Union(..) | Doc::Column(_) | Doc::Nesting(_) => return None,
+        })
+    }
+
+    fn to_doc<'b>(&self, alloc: &'b RocDocAllocator<'b>) -> RocDocBuilder<'b> {
+        match self {
+            TypeDoc::Nil => alloc.nil(),
+            TypeDoc::Append(left, right) => left.to_doc(alloc).append(right.to_doc(alloc)),
+            TypeDoc::Group(doc) => doc.to_doc(alloc).group(),
+            TypeDoc::FlatAlt(broken, flat) => broken.to_doc(alloc).flat_alt(flat.to_doc(alloc)),
+            TypeDoc::Nest(offset, doc) => doc.to_doc(alloc).nest(*offset),
+            TypeDoc::Line => alloc.hardline(),
+            TypeDoc::Text(text) => alloc.text(text.to_string()),
+            TypeDoc::Annotated(annotation, doc) => doc.to_doc(alloc).annotate(*annotation),
+        }
+    }
+}
+
+/// Whether there are at least `size` types in this one, counting the ones that get printed.
+fn has_size(tipe: &ErrorType, size: usize) -> bool {
+    fn count(tipe: &ErrorType, remaining: &mut usize) {
+        use ErrorType::*;
+
+        if *remaining == 0 {
+            return;
+        }
+
+        *remaining -= 1;
+
+        match tipe {
+            Type(_, args) | Alias(_, args, _, _) | Range(args) => {
+                args.iter().for_each(|arg| count(arg, remaining))
+            }
+            Function(args, _, _, ret) => {
+                args.iter().for_each(|arg| count(arg, remaining));
+                count(ret, remaining);
+            }
+            Record(fields, _) => fields
+                .values()
+                .for_each(|field| count(field.as_inner(), remaining)),
+            Tuple(elems, _) => elems.iter().for_each(|(_, elem)| count(elem, remaining)),
+            TagUnion(tags, _, _) | RecursiveTagUnion(_, tags, _, _) => tags
+                .values()
+                .flatten()
+                .for_each(|arg| count(arg, remaining)),
+            Infinite | FlexVar(_) | RigidVar(_) | InferenceVar | EffectfulFunc
+            | FlexAbleVar(..) | RigidAbleVar(..) | Error => {}
+        }
+    }
+
+    let mut remaining = size;
+    count(tipe, &mut remaining);
+
+    remaining == 0
+}
+
+/// `{ …as in a previous error }` and the like, for a large record, tuple or tag union that a
+/// previous report printed in full.
+fn shared_type_to_doc<'b>(
+    alloc: &'b RocDocAllocator<'b>,
+    tipe: &ErrorType,
+) -> Option<RocDocBuilder<'b>> {
+    let (open, close) = match tipe {
+        ErrorType::Record(..) => ("{ ", " }"),
+        ErrorType::Tuple(..) => ("( ", " )"),
+        ErrorType::TagUnion(..) | ErrorType::RecursiveTagUnion(..) => ("[ ", " ]"),
+        _ => return None,
+    };
+
+    if !has_size(tipe, SHARED_TYPE_MIN_SIZE) || !alloc.shared_types.borrow_mut().is_printed(tipe) {
+        return None;
+    }
+
+    Some(
+        alloc
+            .text(open)
+            .append(alloc.ellipsis())
+            .append(alloc.text("as in a previous error"))
+            .append(alloc.text(close)),
+    )
+}
+
 pub fn to_doc<'b>(
     alloc: &'b RocDocAllocator<'b>,
     parens: Parens,
     tipe: ErrorType,
 ) -> (RocDocBuilder<'b>, AbleVariables) {
+    if let Some(doc) = shared_type_to_doc(alloc, &tipe) {
+        return (doc, Vec::new());
+    }
+
+    let large = has_size(&tipe, SHARED_TYPE_MIN_SIZE).then(|| content_hash(&tipe));
+
+    if let Some(hash) = large {
+        if let Some(rendered) = alloc.type_docs.borrow().get(alloc, hash, parens) {
+            return rendered;
+        }
+    }
+
+    let references = alloc.shared_types.borrow().references;
     let mut ctx = Context::default();
 
     let mut generated_name_usages = VecMap::default();
     count_generated_name_usages(&mut generated_name_usages, [&tipe]);
     let doc = to_doc_help(&mut ctx, &generated_name_usages, alloc, parens, tipe);
 
+    // A doc that refers to a previous report can't be reused by a report that doesn't follow it
+    if let Some(hash) = large {
+        if alloc.shared_types.borrow().references == references {
+            if let Some(copy) = TypeDoc::from_doc(&doc.1) {
+                alloc
+                    .type_docs
+                    .borrow_mut()
+                    .insert(hash, parens, copy, ctx.able_variables.clone());
+            }
+        }
+    }
+
     (doc, ctx.able_variables)
 }
 
@@ -2888,6 +3468,10 @@ fn to_doc_help<'b>(
 ) -> RocDocBuilder<'b> {
     use ErrorType::*;
 
+    if let Some(doc) = shared_type_to_doc(alloc, &tipe) {
+        return doc;
+    }
+
     match tipe {
         Function(args, _, fx, ret) => report_text::function(
             alloc,
@@ -3559,7 +4143,7 @@ where
     }
 }
 
-fn ext_has_fixed_fields(ext: &TypeExt) -> bool {
+pub(crate) fn ext_has_fixed_fields(ext: &TypeExt) -> bool {
     match ext {
         TypeExt::Closed => true,
         TypeExt::FlexOpen(_) => false,
@@ -3765,8 +4349,76 @@ fn diff_record<'b>(
     }
 }
 
+/// The records of a [RecordDiff], with only the fields that were added, removed or changed.
+fn record_diff_to_diff<'b>(
+    alloc: &'b RocDocAllocator<'b>,
+    diff: RecordDiff,
+) -> Diff<RocDocBuilder<'b>> {
+    let mut left = Vec::new();
+    let mut right = Vec::new();
+    let mut left_able = Vec::new();
+    let mut right_able = Vec::new();
+
+    let field_doc = |tipe: &RecordField<ErrorType>, able: &mut AbleVariables| {
+        let (doc, able_vars) = to_doc(alloc, Parens::Unnecessary, tipe.as_inner().clone());
+        able.extend(able_vars);
+
+        tipe.replace(doc)
+    };
+
+    for change in diff.changed {
+        match change.nested {
+            Some(nested) => {
+                let nested = record_diff_to_diff(alloc, nested);
+
+                left.push((change.field.clone(), change.found.replace(nested.left)));
+                right.push((change.field, change.expected.replace(nested.right)));
+                left_able.extend(nested.left_able);
+                right_able.extend(nested.right_able);
+            }
+            None => {
+                left.push((
+                    change.field.clone(),
+                    field_doc(&change.found, &mut left_able),
+                ));
+                right.push((change.field, field_doc(&change.expected, &mut right_able)));
+            }
+        }
+    }
+
+    for (field, tipe) in diff.extra {
+        left.push((field, field_doc(&tipe, &mut left_able)));
+    }
+
+    for (field, tipe) in diff.missing {
+        right.push((field, field_doc(&tipe, &mut right_able)));
+    }
+
+    let to_record = |mut fields: Vec<(Lowercase, RecordField<RocDocBuilder<'b>>)>, ext| {
+        fields.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        report_text::record(
+            alloc,
+            fields
+                .into_iter()
+                .map(|(field, tipe)| (alloc.string(field.as_str().to_string()), tipe))
+                .collect(),
+            record_ext_to_doc(alloc, ext),
+            diff.same,
+        )
+    };
+
+    Diff {
+        left: to_record(left, diff.found_ext),
+        right: to_record(right, diff.expected_ext),
+        status: Status::Different(vec![]),
+        left_able,
+        right_able,
+    }
+}
+
 /// This is a helper for should_show_field_diff - see its doc comment for details.
-fn should_show_diff(t1: &ErrorType, t2: &ErrorType) -> bool {
+pub(crate) fn should_show_diff(t1: &ErrorType, t2: &ErrorType) -> bool {
     use ErrorType::*;
 
     match (t1, t2) {
@@ -4029,6 +4681,26 @@ fn should_show_field_diff(
     }
 }
 
+/// Of the tags that were found but aren't expected, the one that's closest to any of the
+/// expected tags that weren't found, and so the one most likely to be misspelled.
+fn likeliest_tag_typo(
+    found: &[(TagName, Vec<ErrorType>)],
+    expected: &SendMap<TagName, Vec<ErrorType>>,
+) -> TagName {
+    found
+        .iter()
+        .map(|(tag, _)| tag)
+        .min_by_key(|typo| {
+            expected
+                .keys()
+                .map(|tag| distance::damerau_levenshtein(typo.0.as_str(), tag.0.as_str()))
+                .min()
+                .unwrap_or(usize::MAX)
+        })
+        .cloned()
+        .unwrap_or_else(|| internal_error!("a tag typo needs at least one found tag"))
+}
+
 fn same_tag_name_overlap_diff<'b>(
     alloc: &'b RocDocAllocator<'b>,
     field: TagName,
@@ -4084,10 +4756,20 @@ fn same_tag_name_overlap_diff<'b>(
         }
     }
 
+    let status = if left_doc.len() != right_doc.len() {
+        Status::Different(vec![Problem::TagArityMismatch(
+            field.clone(),
+            left_doc.len(),
+            right_doc.len(),
+        )])
+    } else {
+        Status::Similar
+    };
+
     Diff {
         left: (field.clone(), alloc.tag_name(field.clone()), left_doc),
         right: (field.clone(), alloc.tag_name(field), right_doc),
-        status: Status::Similar,
+        status,
         left_able,
         right_able,
     }
@@ -4181,8 +4863,8 @@ fn diff_tag_union<'b>(
             // At least one tag appeared only on the left, and also
             // at least one tag appeared only on the right. There's a chance this is
             // because of a typo, so we'll suggest that as a hint.
-            (Some((f, _, _, _)), Some(_)) => Status::Different(vec![Problem::TagTypo(
-                f.clone(),
+            (Some(_), Some(_)) => Status::Different(vec![Problem::TagTypo(
+                likeliest_tag_typo(&tags_in_left_only, &tags_in_right_only),
                 tags_in_right_only.keys().cloned().collect(),
             )]),
             // At least one tag appeared only on the left, but all of the tags
@@ -4338,7 +5020,7 @@ fn diff_tag_union<'b>(
     }
 }
 
-fn should_show_payload_diff(errs1: &[ErrorType], errs2: &[ErrorType]) -> bool {
+pub(crate) fn should_show_payload_diff(errs1: &[ErrorType], errs2: &[ErrorType]) -> bool {
     if errs1.len() == errs2.len() {
         errs1
             .iter()
@@ -4914,6 +5596,22 @@ fn type_problem_to_pretty<'b>(
                 }
             }
         }
+        (TagArityMismatch(tag_name, found, expected), _) => {
+            let payload_values = |n: usize| match n {
+                1 => "1 payload value".to_string(),
+                n => format!("{n} payload values"),
+            };
+
+            alloc.tip().append(alloc.concat([
+                alloc.reflow("The "),
+                alloc.tag_name(tag_name),
+                alloc.reflow(" tag has "),
+                alloc.text(payload_values(found)),
+                alloc.reflow(" here, but it should have "),
+                alloc.text(payload_values(expected)),
+                alloc.reflow("."),
+            ]))
+        }
         (ArityMismatch(found, expected), _) => {
             let line = if found < expected {
                 format!(
@@ -5259,6 +5957,7 @@ fn report_record_field_typo<'b>(
     field: &Lowercase,
     field_suffix: &str,
     field_region: Region,
+    expected_field_type: Option<&ErrorType>,
     actual_fields: SendMap<Lowercase, RecordField<ErrorType>>,
     ext: TypeExt,
 ) -> Report<'b> {
@@ -5281,9 +5980,14 @@ fn report_record_field_typo<'b>(
         ])
     };
 
-    let mut suggestions = suggest::sort(
+    // Fields with the type the missing one is used at are the more likely typos.
+    let mut suggestions = suggest::sort_by_fit(
         field.as_str(),
         actual_fields.into_iter().collect::<Vec<_>>(),
+        |(_, field_type)| {
+            expected_field_type
+                .is_some_and(|expected| error_type_fits(field_type.as_inner(), expected))
+        },
     );
 
     let doc = alloc.stack([
@@ -5337,6 +6041,65 @@ fn report_record_field_typo<'b>(
     }
 }
 
+/// Whether a value of the `found` type could be used where the `expected` type is needed,
+/// going only by how the types are reported. Type variables fit anything.
+fn error_type_fits(found: &ErrorType, expected: &ErrorType) -> bool {
+    use ErrorType::*;
+
+    let all_fit = |found: &[ErrorType], expected: &[ErrorType]| {
+        found.len() == expected.len()
+            && found
+                .iter()
+                .zip(expected)
+                .all(|(found, expected)| error_type_fits(found, expected))
+    };
+
+    match (found, expected) {
+        (Alias(found_symbol, found_args, _, _), Alias(expected_symbol, expected_args, _, _))
+            if found_symbol == expected_symbol =>
+        {
+            all_fit(found_args, expected_args)
+        }
+        (Alias(_, _, real, AliasKind::Structural), _) => error_type_fits(real, expected),
+        (_, Alias(_, _, real, AliasKind::Structural)) => error_type_fits(found, real),
+        (FlexVar(_) | FlexAbleVar(..) | InferenceVar | Infinite | Error | Range(_), _)
+        | (_, FlexVar(_) | FlexAbleVar(..) | InferenceVar | Infinite | Error | Range(_)) => true,
+        (RigidVar(found_name) | RigidAbleVar(found_name, _), RigidVar(expected_name))
+        | (RigidVar(found_name) | RigidAbleVar(found_name, _), RigidAbleVar(expected_name, _)) => {
+            found_name == expected_name
+        }
+        (Type(found_symbol, found_args), Type(expected_symbol, expected_args)) => {
+            found_symbol == expected_symbol && all_fit(found_args, expected_args)
+        }
+        (Record(found_fields, _), Record(expected_fields, _)) => {
+            expected_fields.iter().all(|(name, expected_field)| {
+                found_fields.get(name).map_or(true, |found_field| {
+                    error_type_fits(found_field.as_inner(), expected_field.as_inner())
+                })
+            })
+        }
+        (Tuple(found_elems, _), Tuple(expected_elems, _)) => {
+            found_elems.len() == expected_elems.len()
+                && found_elems
+                    .iter()
+                    .zip(expected_elems)
+                    .all(|((_, found), (_, expected))| error_type_fits(found, expected))
+        }
+        (
+            TagUnion(found_tags, _, _) | RecursiveTagUnion(_, found_tags, _, _),
+            TagUnion(expected_tags, _, _) | RecursiveTagUnion(_, expected_tags, _, _),
+        ) => expected_tags.iter().all(|(tag, expected_args)| {
+            found_tags
+                .get(tag)
+                .map_or(true, |found_args| all_fit(found_args, expected_args))
+        }),
+        (Function(found_args, _, _, found_ret), Function(expected_args, _, _, expected_ret)) => {
+            all_fit(found_args, expected_args) && error_type_fits(found_ret, expected_ret)
+        }
+        _ => false,
+    }
+}
+
 fn exhaustive_problem<'a>(
     alloc: &'a RocDocAllocator<'a>,
     lines: &LineInfo,
@@ -5422,6 +6185,35 @@ fn exhaustive_problem<'a>(
                 }
             }
         },
+        OnlyGuarded {
+            overall_region,
+            patterns,
+        } => {
+            let doc = alloc.stack([
+                alloc.concat([
+                    alloc.reflow("This "),
+                    alloc.keyword("when"),
+                    alloc.reflow(" only handles some possibilities in branches with an "),
+                    alloc.keyword("if"),
+                    alloc.reflow(" guard:"),
+                ]),
+                alloc.region(lines.convert_region(overall_region), severity),
+                alloc.reflow("These patterns are only handled by guarded branches:"),
+                unhandled_patterns_to_doc_block(alloc, patterns),
+                alloc.concat([
+                    alloc.reflow("If none of those guards are true, I would have to crash! Add a "),
+                    alloc.keyword("_"),
+                    alloc.reflow(" branch without a guard at the end to handle everything else."),
+                ]),
+            ]);
+
+            Report {
+                filename,
+                title: "UNSAFE PATTERN".to_string(),
+                doc,
+                severity,
+            }
+        }
         Redundant {
             overall_region,
             branch_region,
@@ -0,0 +1,299 @@
+//! Longer explanations of the problems the compiler reports, similar to `rustc --explain`.
+//!
+//! Every kind of report that has an explanation is identified by a code, which is its
+//! title in kebab-case. For example, the explanation for `TYPE MISMATCH` reports has the
+//! code `type-mismatch`. Editors can use the code to show a "learn more" panel next to
+//! a report.
+
+/// A longer explanation of one kind of report.
+#[derive(Debug)]
+pub struct ExplainDoc {
+    /// Identifies the kind of report, e.g. `type-mismatch`.
+    pub code: &'static str,
+    /// The title of the reports this explains, e.g. `TYPE MISMATCH`.
+    pub title: &'static str,
+    /// One sentence describing what the report means.
+    pub summary: &'static str,
+    /// Why the problem happens and how to fix it. Paragraphs are separated by blank lines.
+    pub explanation: &'static str,
+    pub examples: &'static [ExplainExample],
+}
+
+/// A small program that gets the report being explained.
+///
+/// The programs are type checked as part of the reporting tests, so they stay in sync with
+/// the compiler. A program that doesn't start with a module header is the body of `main`.
+#[derive(Debug)]
+pub struct ExplainExample {
+    pub description: &'static str,
+    /// A program that gets a report with the explained title.
+    pub program: &'static str,
+    /// The same program with the problem fixed, if there's a fix worth showing.
+    pub fixed: Option<&'static str>,
+}
+
+impl ExplainDoc {
+    /// Render the explanation as markdown, e.g. for an editor's hover panel.
+    pub fn to_markdown(&self) -> String {
+        let mut buf = format!(
+            "# {} (`{}`)\n\n{}\n\n{}\n",
+            self.title, self.code, self.summary, self.explanation
+        );
+
+        for example in self.examples {
+            buf.push_str(&format!(
+                "\n## Example\n\n{}\n\n```roc\n{}```\n",
+                example.description, example.program
+            ));
+
+            if let Some(fixed) = example.fixed {
+                buf.push_str(&format!("\nFixed:\n\n```roc\n{fixed}```\n"));
+            }
+        }
+
+        buf
+    }
+}
+
+/// The explanation for a code, e.g. `type-mismatch`. Report titles like `TYPE MISMATCH`
+/// are accepted too.
+pub fn explain(code: &str) -> Option<&'static ExplainDoc> {
+    let code = code_for_title(code);
+
+    EXPLANATIONS.iter().find(|doc| doc.code == code)
+}
+
+/// Every explanation the compiler has.
+pub fn explanations() -> &'static [ExplainDoc] {
+    EXPLANATIONS
+}
+
+/// The code for reports with the given title, e.g. `type-mismatch` for `TYPE MISMATCH`.
+pub fn code_for_title(title: &str) -> String {
+    title
+        .trim()
+        .split(|c: char| c.is_whitespace() || c == '-' || c == '_')
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+const EXPLANATIONS: &[ExplainDoc] = &[
+    ExplainDoc {
+        code: "type-mismatch",
+        title: "TYPE MISMATCH",
+        summary: "A value is used somewhere that needs a different type.",
+        explanation: "\
+Every value has a type, and every place a value is used expects a certain type. This report \
+shows the value, the type it has, and the type that was expected where it's used.
+
+Type annotations are the most common source of expectations, but function arguments, `if` \
+conditions, and the branches of an `if` or `when` all expect types too. When the report \
+points at a value that looks right, the expectation may come from somewhere else; adding \
+annotations to the surrounding definitions helps narrow it down.
+
+The same report is used when a value's type doesn't implement an ability that's needed, for \
+example comparing two functions with `==`. Functions can't be compared, so their types don't \
+implement `Eq`.",
+        examples: &[
+            ExplainExample {
+                description:
+                    "The annotation says `greeting` is a `Str`, but it's defined as a number.",
+                program: "\
+greeting : Str
+greeting = 42
+
+greeting
+",
+                fixed: Some(
+                    "\
+greeting : Str
+greeting = \"Hello\"
+
+greeting
+",
+                ),
+            },
+            ExplainExample {
+                description: "Functions don't implement `Eq`, so they can't be compared with `==`.",
+                program: "\
+same = \\a, b -> a == b
+
+same (\\x -> x) (\\y -> y)
+",
+                fixed: Some(
+                    "\
+same = \\a, b -> a == b
+
+same 1 1
+",
+                ),
+            },
+        ],
+    },
+    ExplainDoc {
+        code: "unrecognized-name",
+        title: "UNRECOGNIZED NAME",
+        summary: "A name is used that isn't defined or imported.",
+        explanation: "\
+Names have to be defined before they can be used, either in the current scope or at the top \
+level of the module, or imported from another module.
+
+The report suggests similar names that are in scope. The names whose type fits where the \
+unrecognized name is used come first. If the name comes from another module, check that the \
+module is imported and that it exposes the name.",
+        examples: &[ExplainExample {
+            description: "`heigth` is a typo of `height`.",
+            program: "\
+width = 4
+height = 2
+
+width * heigth
+",
+            fixed: Some(
+                "\
+width = 4
+height = 2
+
+width * height
+",
+            ),
+        }],
+    },
+    ExplainDoc {
+        code: "too-many-args",
+        title: "TOO MANY ARGS",
+        summary: "A function is called with more arguments than it takes.",
+        explanation: "\
+A function's type says how many arguments it takes. Calling it with more arguments than that \
+is an error, because the extra arguments would have nowhere to go.
+
+This often happens when parentheses are missing around a nested call, e.g. \
+`Num.to_str Num.abs x` instead of `Num.to_str (Num.abs x)`.",
+        examples: &[ExplainExample {
+            description: "`double` takes one argument, but it's given two.",
+            program: "\
+double = \\n -> n * 2
+
+double 1 2
+",
+            fixed: Some(
+                "\
+double = \\n -> n * 2
+
+double 1
+",
+            ),
+        }],
+    },
+    ExplainDoc {
+        code: "too-few-args",
+        title: "TOO FEW ARGS",
+        summary: "A function is called with fewer arguments than it takes.",
+        explanation: "\
+Roc functions aren't curried, so a function has to be given all of its arguments at once. To \
+supply some of the arguments now and the rest later, wrap the call in a lambda, e.g. \
+`\\b -> add 1 b`.",
+        examples: &[ExplainExample {
+            description: "`add` takes two arguments, but it's given one.",
+            program: "\
+add = \\a, b -> a + b
+
+add 1
+",
+            fixed: Some(
+                "\
+add = \\a, b -> a + b
+
+add 1 2
+",
+            ),
+        }],
+    },
+    ExplainDoc {
+        code: "circular-type",
+        title: "CIRCULAR TYPE",
+        summary: "A value's type would have to contain itself.",
+        explanation: "\
+Some expressions can only be given a type that's infinitely large, because the type would have \
+to contain itself. The most common cause is passing a function to itself, or putting a value \
+inside of itself.
+
+Types can only refer to themselves through a named recursive type, like a tag union that \
+contains itself. Defining one of those and wrapping the value in one of its tags usually \
+fixes the problem.",
+        examples: &[ExplainExample {
+            description: "Applying `g` to itself means `g`'s type would have to contain itself.",
+            program: "\
+f = \\g -> g g
+
+f
+",
+            fixed: None,
+        }],
+    },
+    ExplainDoc {
+        code: "incomplete-ability-implementation",
+        title: "INCOMPLETE ABILITY IMPLEMENTATION",
+        summary: "A type says it implements an ability, but doesn't implement all of its members.",
+        explanation: "\
+An ability is a set of functions, called its members. When an opaque type lists an ability \
+in its `implements` clause, it has to provide an implementation of every one of the \
+ability's members.
+
+For abilities that can be derived, like `Eq` or `Hash`, leaving out the implementations \
+entirely asks the compiler to derive them instead.",
+        examples: &[ExplainExample {
+            description: "`MEq` has two members, but `Id` only implements `eq`.",
+            program: "\
+module [Id]
+
+MEq implements
+    eq : a, a -> Bool where a implements MEq
+    le : a, a -> Bool where a implements MEq
+
+Id := U64 implements [MEq { eq }]
+
+eq = \\@Id m, @Id n -> m == n
+",
+            fixed: Some(
+                "\
+module [Id]
+
+MEq implements
+    eq : a, a -> Bool where a implements MEq
+    le : a, a -> Bool where a implements MEq
+
+Id := U64 implements [MEq { eq, le }]
+
+eq = \\@Id m, @Id n -> m == n
+
+le = \\@Id m, @Id n -> m <= n
+",
+            ),
+        }],
+    },
+    ExplainDoc {
+        code: "typed-hole",
+        title: "TYPED HOLE",
+        summary: "A typed hole marks code that hasn't been written yet.",
+        explanation: "\
+A typed hole, written `???` or `?name`, stands in for an expression. The report shows the \
+type the hole needs to have, the values in scope, and which of them would fit in its place, \
+possibly after applying them to some arguments.
+
+Programs with typed holes still run, but they crash if they evaluate a hole.",
+        examples: &[ExplainExample {
+            description: "The hole needs to be a function from numbers to something.",
+            program: "\
+List.map [1, 2, 3] ???
+",
+            fixed: Some(
+                "\
+List.map [1, 2, 3] Num.to_str
+",
+            ),
+        }],
+    },
+];
@@ -0,0 +1,195 @@
+//! Grouping problems that have the same cause.
+//!
+//! One mistake often causes many problems. A type alias that doesn't match how it's used makes
+//! every use of it a mismatch between the same two types, and a misspelled name is unrecognized
+//! everywhere it's used. Reporting each of those in full buries the other problems, so the
+//! first one is reported as the primary problem, together with how many more problems like it
+//! there are and where a few of them are. The others are not reported on their own.
+//!
+//! Problems are alike when they are the same kind of problem about the same thing: the same
+//! name, or the same pair of mismatched types. Problems in different modules can be alike.
+use crate::report::{RocDocAllocator, RocDocBuilder};
+use roc_collections::MutMap;
+use roc_module::symbol::ModuleId;
+use roc_problem::can::{Problem, RuntimeError};
+use roc_region::all::Region;
+use roc_solve_problem::TypeError;
+use ven_pretty::DocAllocator;
+
+/// How many of the folded problems to point out in the primary problem's report.
+pub const MAX_LOCATIONS: usize = 3;
+
+/// The problems that were folded into a primary problem.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProblemGroup {
+    /// How many problems were folded into the primary one
+    pub folded: usize,
+    /// Where the first [MAX_LOCATIONS] of them are
+    pub locations: Vec<(ModuleId, Region)>,
+}
+
+impl ProblemGroup {
+    /// A note for the report of the primary problem. `locate` renders a location of a folded
+    /// problem, like `Main.roc:3:5`.
+    pub fn note<'b>(
+        &self,
+        alloc: &'b RocDocAllocator<'b>,
+        locate: impl Fn(ModuleId, Region) -> String,
+    ) -> RocDocBuilder<'b> {
+        let count = match self.folded {
+            1 => "1 more problem".to_string(),
+            n => format!("{n} more problems"),
+        };
+        let locations = self
+            .locations
+            .iter()
+            .map(|(module_id, region)| alloc.string(locate(*module_id, *region)));
+
+        alloc.stack([
+            alloc.concat([
+                alloc.note("I found "),
+                alloc.string(count),
+                alloc.reflow(" like this one, which probably have the same cause. For example:"),
+            ]),
+            alloc.vcat(locations).indent(4),
+        ])
+    }
+}
+
+/// The groups that [group_problems] found, by primary problem.
+#[derive(Debug, Default)]
+pub struct ProblemGroups {
+    by_primary: MutMap<(ModuleId, Region, String), ProblemGroup>,
+}
+
+impl ProblemGroups {
+    /// The problems folded into this canonicalization problem of `module_id`, if any.
+    pub fn of_can_problem(&self, module_id: ModuleId, problem: &Problem) -> Option<&ProblemGroup> {
+        let (key, region) = can_problem_key(problem)?;
+
+        self.by_primary.get(&(module_id, region, key))
+    }
+
+    /// The problems folded into this type problem of `module_id`, if any.
+    pub fn of_type_problem(
+        &self,
+        module_id: ModuleId,
+        problem: &TypeError,
+    ) -> Option<&ProblemGroup> {
+        let (key, region) = type_problem_key(problem)?;
+
+        self.by_primary.get(&(module_id, region, key))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_primary.is_empty()
+    }
+}
+
+/// Remove the problems that are like an earlier one, and return which problem each of them was
+/// folded into. Modules are visited in the order they were loaded, and canonicalization problems
+/// come before type problems.
+pub fn group_problems(
+    can_problems: &mut MutMap<ModuleId, Vec<Problem>>,
+    type_problems: &mut MutMap<ModuleId, Vec<TypeError>>,
+) -> ProblemGroups {
+    let mut module_ids: Vec<ModuleId> = can_problems
+        .keys()
+        .chain(type_problems.keys())
+        .copied()
+        .collect();
+    module_ids.sort_by_key(|module_id| module_id.to_zero_indexed());
+    module_ids.dedup();
+
+    let mut primaries: MutMap<String, (ModuleId, Region)> = MutMap::default();
+    let mut groups = ProblemGroups::default();
+
+    let mut fold = |module_id: ModuleId, key: Option<(String, Region)>| {
+        let Some((key, region)) = key else {
+            return false;
+        };
+
+        match primaries.get(&key) {
+            Some(&(primary_module, primary_region)) => {
+                let group = groups
+                    .by_primary
+                    .entry((primary_module, primary_region, key))
+                    .or_insert_with(|| ProblemGroup {
+                        folded: 0,
+                        locations: Vec::new(),
+                    });
+
+                group.folded += 1;
+
+                if group.locations.len() < MAX_LOCATIONS {
+                    group.locations.push((module_id, region));
+                }
+
+                true
+            }
+            None => {
+                primaries.insert(key, (module_id, region));
+
+                false
+            }
+        }
+    };
+
+    for module_id in module_ids {
+        if let Some(problems) = can_problems.get_mut(&module_id) {
+            problems.retain(|problem| !fold(module_id, can_problem_key(problem)));
+        }
+
+        if let Some(problems) = type_problems.get_mut(&module_id) {
+            problems.retain(|problem| !fold(module_id, type_problem_key(problem)));
+        }
+    }
+
+    groups
+}
+
+/// What a canonicalization problem is about and where it is, for the kinds of problems that one
+/// mistake can cause many of.
+fn can_problem_key(problem: &Problem) -> Option<(String, Region)> {
+    use RuntimeError::*;
+
+    let Problem::RuntimeError(runtime_error) = problem else {
+        return None;
+    };
+
+    let key = match runtime_error {
+        LookupNotInScope { loc_name, .. } => format!("not in scope: {}", loc_name.value),
+        ValueNotExposed {
+            module_name, ident, ..
+        } => format!("not exposed: {}.{}", module_name.as_str(), ident),
+        ModuleNotImported { module_name, .. } => {
+            format!("not imported: {}", module_name.as_str())
+        }
+        OpaqueNotDefined { usage, .. } => format!("opaque not defined: {}", usage.value),
+        _ => return None,
+    };
+
+    Some((key, runtime_error.region()))
+}
+
+/// What a type problem is about and where it is, for mismatches between types, which one wrong
+/// type can cause many of.
+fn type_problem_key(problem: &TypeError) -> Option<(String, Region)> {
+    match problem {
+        TypeError::BadExpr(region, _, found, expected) => Some((
+            format!(
+                "expression mismatch: {found:?} / {:?}",
+                expected.get_type_ref()
+            ),
+            *region,
+        )),
+        TypeError::BadPattern(region, _, found, expected) => Some((
+            format!(
+                "pattern mismatch: {found:?} / {:?}",
+                expected.get_type_ref()
+            ),
+            *region,
+        )),
+        _ => None,
+    }
+}
@@ -5,4 +5,6 @@
 
 pub mod cli;
 pub mod error;
+pub mod explain;
+pub mod group;
 pub mod report;
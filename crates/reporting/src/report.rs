@@ -2,7 +2,8 @@ use roc_module::ident::Ident;
 use roc_module::ident::{Lowercase, ModuleName, TagName, Uppercase};
 use roc_module::symbol::{Interns, ModuleId, ModuleIds, PQModuleName, PackageQualified, Symbol};
 use roc_problem::Severity;
-use roc_region::all::LineColumnRegion;
+use roc_region::all::{display_width, LineColumnRegion};
+use std::cell::RefCell;
 use std::path::{Path, PathBuf};
 use std::{fmt, io};
 use ven_pretty::{text, BoxAllocator, DocAllocator, DocBuilder, Render, RenderAnnotated};
@@ -10,11 +11,17 @@ use ven_pretty::{text, BoxAllocator, DocAllocator, DocBuilder, Render, RenderAnn
 #[cfg(not(target_family = "wasm"))]
 use byte_unit::Byte;
 #[cfg(not(target_family = "wasm"))]
+use roc_packaging::credentials::{CredentialsProblem, CREDENTIALS_ENV_VAR};
+#[cfg(not(target_family = "wasm"))]
 use roc_packaging::https::Problem;
+#[cfg(not(target_family = "wasm"))]
+use roc_packaging::lockfile::LockfileProblem;
 
 pub use crate::error::canonicalize::can_problem;
+pub use crate::error::mono::mono_problem;
 pub use crate::error::parse::parse_problem;
 pub use crate::error::r#type::type_problem;
+use crate::error::r#type::{SharedTypes, TypeDocs};
 
 #[cfg(windows)]
 const CYCLE_ELEMENTS: [&str; 4] = ["+-----+", "|     ", "|     |", "+-<---+"];
@@ -127,10 +134,17 @@ pub struct Report<'b> {
 }
 
 impl<'b> Report<'b> {
+    /// The code of this report's longer explanation, if it has one.
+    ///
+    /// See [`crate::explain::explain`].
+    pub fn explain_code(&self) -> Option<&'static str> {
+        crate::explain::explain(&self.title).map(|doc| doc.code)
+    }
+
     pub fn render(
         self,
         target: RenderTarget,
-        buf: &'b mut String,
+        buf: &mut String,
         alloc: &'b RocDocAllocator<'b>,
         palette: &'b Palette,
     ) {
@@ -357,6 +371,9 @@ pub struct RocDocAllocator<'a> {
     pub src_lines: &'a [&'a str],
     pub home: ModuleId,
     pub interns: &'a Interns,
+    /// The large types the reports made with this allocator printed so far
+    pub(crate) shared_types: RefCell<SharedTypes>,
+    pub(crate) type_docs: RefCell<TypeDocs>,
 }
 
 pub type RocDocBuilder<'b> = DocBuilder<'b, RocDocAllocator<'b>, Annotation>;
@@ -393,9 +410,20 @@ impl<'a> RocDocAllocator<'a> {
             home,
             src_lines,
             interns,
+            shared_types: Default::default(),
+            type_docs: Default::default(),
         }
     }
 
+    /// Makes the type error reports of this allocator print a large type in full only once, and
+    /// refer back to that report afterwards. Only use this when every report it makes is shown,
+    /// in the order they're made, in the same output.
+    pub fn with_shared_types(self) -> Self {
+        self.shared_types.borrow_mut().enable();
+
+        self
+    }
+
     /// vertical concatenation. Adds a newline between elements
     pub fn vcat<A, I>(&'a self, docs: I) -> DocBuilder<'a, Self, A>
     where
@@ -610,6 +638,15 @@ impl<'a> RocDocAllocator<'a> {
             .append(line)
     }
 
+    /// Why a warning with the given code is reported as an error.
+    pub fn denied_warning_note(&'a self, code: &'a str) -> DocBuilder<'a, Self, Annotation> {
+        self.concat([
+            self.note("This warning is an error, because "),
+            self.keyword(code),
+            self.reflow(" is denied in the warning configuration of this package."),
+        ])
+    }
+
     pub fn region_all_the_things(
         &'a self,
         region: LineColumnRegion,
@@ -680,27 +717,34 @@ impl<'a> RocDocAllocator<'a> {
 
         if error_highlight_line {
             let overlapping = sub_region2.start().column < sub_region1.end().column;
+            let line = region.start().line;
 
             let highlight = if overlapping {
-                self.text(
-                    ERROR_UNDERLINE
-                        .repeat((sub_region2.end().column - sub_region1.start().column) as usize),
-                )
+                self.text(ERROR_UNDERLINE.repeat(self.display_columns(
+                    line,
+                    sub_region1.start().column,
+                    sub_region2.end().column,
+                )))
             } else {
-                let highlight1 = ERROR_UNDERLINE
-                    .repeat((sub_region1.end().column - sub_region1.start().column) as usize);
+                let highlight1 = ERROR_UNDERLINE.repeat(self.display_columns(
+                    line,
+                    sub_region1.start().column,
+                    sub_region1.end().column,
+                ));
                 let highlight2 = if sub_region1 == sub_region2 {
                     "".repeat(0)
                 } else {
-                    ERROR_UNDERLINE
-                        .repeat((sub_region2.end().column - sub_region2.start().column) as usize)
+                    ERROR_UNDERLINE.repeat(self.display_columns(
+                        line,
+                        sub_region2.start().column,
+                        sub_region2.end().column,
+                    ))
                 };
-                let in_between = " ".repeat(
-                    (sub_region2
-                        .start()
-                        .column
-                        .saturating_sub(sub_region1.end().column)) as usize,
-                );
+                let in_between = " ".repeat(self.display_columns(
+                    line,
+                    sub_region1.end().column,
+                    sub_region2.start().column,
+                ));
 
                 self.text(highlight1)
                     .append(self.text(in_between))
@@ -715,7 +759,7 @@ impl<'a> RocDocAllocator<'a> {
                 .append(if sub_region1.is_empty() && sub_region2.is_empty() {
                     self.nil()
                 } else {
-                    self.text(" ".repeat(sub_region1.start().column as usize))
+                    self.text(" ".repeat(self.display_columns(line, 0, sub_region1.start().column)))
                         .indent(indent)
                         .append(highlight)
                         .annotate(error_annotation)
@@ -818,8 +862,12 @@ impl<'a> RocDocAllocator<'a> {
         }
 
         if error_highlight_line {
-            let highlight_text = ERROR_UNDERLINE
-                .repeat((sub_region.end().column - sub_region.start().column) as usize);
+            let line = sub_region.start().line;
+            let highlight_text = ERROR_UNDERLINE.repeat(self.display_columns(
+                line,
+                sub_region.start().column,
+                sub_region.end().column,
+            ));
 
             let highlight_line = self
                 .line()
@@ -829,7 +877,7 @@ impl<'a> RocDocAllocator<'a> {
                 .append(if highlight_text.is_empty() {
                     self.nil()
                 } else {
-                    self.text(" ".repeat(sub_region.start().column as usize))
+                    self.text(" ".repeat(self.display_columns(line, 0, sub_region.start().column)))
                         .indent(indent)
                         .append(self.text(highlight_text).annotate(annotation))
                 });
@@ -840,6 +888,20 @@ impl<'a> RocDocAllocator<'a> {
         result
     }
 
+    /// How many columns a line of the source takes up between two byte columns when it's
+    /// displayed, so that underlines line up with the text they point at.
+    fn display_columns(&self, line: u32, start: u32, end: u32) -> usize {
+        let text = self
+            .src_lines
+            .get(line as usize)
+            .and_then(|line| line.get(start as usize..end as usize));
+
+        match text {
+            Some(text) => display_width(text) as usize,
+            None => end.saturating_sub(start) as usize,
+        }
+    }
+
     pub fn region(
         &'a self,
         region: LineColumnRegion,
@@ -1688,9 +1750,207 @@ pub fn to_https_problem_report<'b>(
                 severity: Severity::Fatal,
             }
         }
+        Problem::Unauthorized {
+            status,
+            sent_credentials,
+        } => {
+            let tip = if sent_credentials {
+                alloc.concat([
+                    alloc.tip(),
+                    alloc.reflow(r"Check that the credentials for this host in the file at "),
+                    alloc.keyword(CREDENTIALS_ENV_VAR),
+                    alloc.reflow(r" are correct and haven't expired."),
+                ])
+            } else {
+                alloc.concat([
+                    alloc.tip(),
+                    alloc.reflow(r"If this package is on a private server, put the credentials for its host in a file, "),
+                    alloc.reflow(r"and set the "),
+                    alloc.keyword(CREDENTIALS_ENV_VAR),
+                    alloc.reflow(r" environment variable to the path of that file."),
+                ])
+            };
+            let doc = alloc.stack([
+                alloc.reflow(r"I tried to download from this URL:"),
+                alloc
+                    .string((&url).to_string())
+                    .annotate(Annotation::Url)
+                    .indent(4),
+                alloc.concat([
+                    alloc.reflow(r"But the server refused to give me the file ("),
+                    alloc.string(status.to_string()),
+                    alloc.reflow(r")."),
+                ]),
+                tip,
+            ]);
+
+            Report {
+                filename,
+                doc,
+                title: "UNAUTHORIZED".to_string(),
+                severity: Severity::Fatal,
+            }
+        }
+        Problem::InvalidCredentials(credentials_problem) => {
+            let problem = match credentials_problem {
+                CredentialsProblem::IoErr(io_error) => alloc.concat([
+                    alloc.reflow(r"But I could not read it: "),
+                    alloc.string(io_error.to_string()),
+                ]),
+                CredentialsProblem::InvalidLine { line_number } => alloc.concat([
+                    alloc.reflow(r"But line "),
+                    alloc.string(line_number.to_string()),
+                    alloc.reflow(r" is not a host followed by an Authorization header value."),
+                ]),
+            };
+            let doc = alloc.stack([
+                alloc.concat([
+                    alloc.reflow(r"I tried to read the credentials file given by the "),
+                    alloc.keyword(CREDENTIALS_ENV_VAR),
+                    alloc.reflow(r" environment variable, to download from this URL:"),
+                ]),
+                alloc
+                    .string((&url).to_string())
+                    .annotate(Annotation::Url)
+                    .indent(4),
+                problem,
+            ]);
+
+            Report {
+                filename,
+                doc,
+                title: "INVALID CREDENTIALS FILE".to_string(),
+                severity: Severity::Fatal,
+            }
+        }
     }
 }
 
+/// A report for a package that doesn't match the `roc.lock` at `lockfile_path`, or for a lockfile
+/// that can't be read. `filename` is the module that depends on the package.
+#[cfg(not(target_family = "wasm"))]
+pub fn to_lockfile_problem_report_string(
+    problem: LockfileProblem,
+    lockfile_path: &Path,
+    filename: PathBuf,
+) -> String {
+    let problem = match problem {
+        LockfileProblem::Install { url, problem } => {
+            return to_https_problem_report_string(&url, problem, filename);
+        }
+        problem => problem,
+    };
+
+    let src_lines: Vec<&str> = Vec::new();
+    let mut module_ids = ModuleIds::default();
+    let module_id = module_ids.get_or_insert(&"find module name somehow?".into());
+    let interns = Interns::default();
+    let alloc = RocDocAllocator::new(&src_lines, module_id, &interns);
+    let lockfile = || {
+        alloc
+            .string(lockfile_path.display().to_string())
+            .annotate(Annotation::Module)
+            .indent(4)
+    };
+    let update_tip = || {
+        alloc.concat([
+            alloc.tip(),
+            alloc.reflow(r"If you changed the dependencies on purpose, run "),
+            alloc.keyword(r"roc lock"),
+            alloc.reflow(r" to update the lockfile."),
+        ])
+    };
+
+    let (title, doc) = match problem {
+        LockfileProblem::IoErr(io_error) => (
+            "UNREADABLE LOCKFILE",
+            alloc.stack([
+                alloc.reflow(r"I tried to read this lockfile:"),
+                lockfile(),
+                alloc.concat([
+                    alloc.reflow(r"But I could not read it: "),
+                    alloc.string(io_error.to_string()),
+                ]),
+            ]),
+        ),
+        LockfileProblem::InvalidLine { line_number, line } => (
+            "INVALID LOCKFILE",
+            alloc.stack([
+                alloc.reflow(r"I tried to read this lockfile:"),
+                lockfile(),
+                alloc.concat([
+                    alloc.reflow(r"But line "),
+                    alloc.string(line_number.to_string()),
+                    alloc.reflow(r" is not a URL followed by a hash:"),
+                ]),
+                alloc.string(line).indent(4),
+                update_tip(),
+            ]),
+        ),
+        LockfileProblem::NotLocked(url) => (
+            "PACKAGE NOT LOCKED",
+            alloc.stack([
+                alloc.reflow(r"This package is not in the lockfile:"),
+                alloc.string(url).annotate(Annotation::Url).indent(4),
+                alloc.reflow(r"Every package that is depended on by its URL has to be locked in:"),
+                lockfile(),
+                update_tip(),
+            ]),
+        ),
+        LockfileProblem::TreeHashMismatch {
+            url,
+            expected,
+            actual,
+        } => (
+            "PACKAGE DOES NOT MATCH THE LOCKFILE",
+            alloc.stack([
+                alloc.reflow(r"The files of this package are not the ones that were locked:"),
+                alloc.string(url).annotate(Annotation::Url).indent(4),
+                alloc.concat([
+                    alloc.reflow(r"The lockfile expects them to hash to "),
+                    alloc.string(expected).annotate(Annotation::Emphasized),
+                    alloc.reflow(r", but they hash to "),
+                    alloc.string(actual).annotate(Annotation::Emphasized),
+                    alloc.reflow(r"."),
+                ]),
+                alloc.concat([
+                    alloc.tip(),
+                    alloc.reflow(r"The copy of the package in the cache may have been modified. "),
+                    alloc.reflow(r"Deleting it makes me download it again."),
+                ]),
+            ]),
+        ),
+        LockfileProblem::Unresolved(dependency) => (
+            "PACKAGE NOT RESOLVED",
+            alloc.stack([
+                alloc.reflow(r"The package manifest depends on this package:"),
+                alloc.string(dependency.to_string()).indent(4),
+                alloc.reflow(r"But no version of it that satisfies that is locked in:"),
+                lockfile(),
+                alloc.concat([
+                    alloc.tip(),
+                    alloc.reflow(r"Run "),
+                    alloc.keyword(r"roc lock"),
+                    alloc.reflow(r" with the index of the package registry to resolve it."),
+                ]),
+            ]),
+        ),
+        LockfileProblem::Install { .. } => unreachable!("reported as an HTTPS problem above"),
+    };
+
+    let report = Report {
+        filename,
+        doc,
+        title: title.to_string(),
+        severity: Severity::Fatal,
+    };
+    let mut buf = String::new();
+
+    report.render_color_terminal(&mut buf, &alloc, &DEFAULT_PALETTE);
+
+    buf
+}
+
 pub fn to_file_problem_report_string(
     filename: PathBuf,
     error: io::ErrorKind,
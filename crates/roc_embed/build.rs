@@ -0,0 +1,7 @@
+fn main() {
+    // The Roc libraries the integration tests load resolve `roc_alloc` and the other functions
+    // in the runtime module from the test executable, so it has to export them.
+    if std::env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("linux") {
+        println!("cargo:rustc-link-arg-tests=-rdynamic");
+    }
+}
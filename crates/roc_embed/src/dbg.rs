@@ -0,0 +1,163 @@
+//! Routing for the output of Roc's `dbg`. By default it's printed to stderr, like other hosts do,
+//! but a host can install a handler to send it somewhere else, e.g. into its own logging system.
+//!
+//! ```no_run
+//! use roc_embed::dbg;
+//!
+//! dbg::set_handler(|event| {
+//!     println!(
+//!         "dbg #{} at {}:{} on {:?}: {} = {}",
+//!         event.id, event.location.file, event.location.line, event.thread, event.source, event.value
+//!     );
+//! });
+//! ```
+//!
+//! The handler is called on the thread that ran the `dbg`, and is shared by all threads.
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::thread::{self, ThreadId};
+
+/// Where in the Roc source a `dbg` is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DbgLocation {
+    /// The path of the module, as the compiler saw it.
+    pub file: String,
+    /// The 1-based line number, or 0 if the compiler didn't provide one.
+    pub line: u32,
+}
+
+impl DbgLocation {
+    /// Parses the `path:line` location the compiler passes to `roc_dbg`.
+    pub fn parse(location: &str) -> Self {
+        match location.rsplit_once(':') {
+            Some((file, line)) => match line.parse() {
+                Ok(line) => DbgLocation {
+                    file: file.to_string(),
+                    line,
+                },
+                Err(_) => DbgLocation {
+                    file: location.to_string(),
+                    line: 0,
+                },
+            },
+            None => DbgLocation {
+                file: location.to_string(),
+                line: 0,
+            },
+        }
+    }
+}
+
+impl fmt::Display for DbgLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.file, self.line)
+    }
+}
+
+/// One value printed by a `dbg`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DbgEvent {
+    /// Numbers the `dbg` calls in the order they happened in this process, starting at 0.
+    pub id: u64,
+    pub location: DbgLocation,
+    /// The source code of the expression that was printed.
+    pub source: String,
+    /// The value, rendered by `Inspect.to_str`.
+    pub value: String,
+    pub thread: ThreadId,
+    pub thread_name: Option<String>,
+}
+
+impl fmt::Display for DbgEvent {
+    /// The same format `roc dev` and the bundled platforms use.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {} = {}", self.location, self.source, self.value)
+    }
+}
+
+type Handler = Arc<dyn Fn(&DbgEvent) + Send + Sync>;
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+static HANDLER: RwLock<Option<Handler>> = RwLock::new(None);
+
+/// Sends the output of every following `dbg` to `handler` instead of stderr.
+pub fn set_handler(handler: impl Fn(&DbgEvent) + Send + Sync + 'static) {
+    *HANDLER.write().unwrap_or_else(|err| err.into_inner()) = Some(Arc::new(handler));
+}
+
+/// Goes back to printing the output of `dbg` to stderr.
+pub fn reset_handler() {
+    *HANDLER.write().unwrap_or_else(|err| err.into_inner()) = None;
+}
+
+pub(crate) fn report(location: &str, source: &str, value: &str) {
+    let current = thread::current();
+    let event = DbgEvent {
+        id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+        location: DbgLocation::parse(location),
+        source: source.to_string(),
+        value: value.to_string(),
+        thread: current.id(),
+        thread_name: current.name().map(str::to_string),
+    };
+
+    // Clone the handler, so that it can call set_handler itself without deadlocking
+    let handler = HANDLER
+        .read()
+        .unwrap_or_else(|err| err.into_inner())
+        .clone();
+
+    match handler {
+        Some(handler) => handler(&event),
+        None => eprintln!("{event}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn parse_location() {
+        assert_eq!(
+            DbgLocation::parse("/home/me/app/main.roc:12"),
+            DbgLocation {
+                file: "/home/me/app/main.roc".to_string(),
+                line: 12
+            }
+        );
+        assert_eq!(
+            DbgLocation::parse("C:\\app\\main.roc:7"),
+            DbgLocation {
+                file: "C:\\app\\main.roc".to_string(),
+                line: 7
+            }
+        );
+        assert_eq!(
+            DbgLocation::parse("main.roc"),
+            DbgLocation {
+                file: "main.roc".to_string(),
+                line: 0
+            }
+        );
+    }
+
+    #[test]
+    fn handler_receives_events() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let captured = events.clone();
+
+        set_handler(move |event| captured.lock().unwrap().push(event.clone()));
+        report("main.roc:3", "x + 1", "42");
+        report("main.roc:4", "name", "\"Ada\"");
+        reset_handler();
+
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 2);
+        assert!(events[0].id < events[1].id);
+        assert_eq!(events[0].thread, thread::current().id());
+        assert_eq!(events[1].to_string(), "[main.roc:4] name = \"Ada\"");
+    }
+}
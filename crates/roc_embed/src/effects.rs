@@ -0,0 +1,316 @@
+//! Record and replay of the effects a Roc app performs, for deterministic tests of effectful code.
+//!
+//! A host implements each of its effects (the `roc_fx_*` functions the platform's hosted module
+//! declares) by passing the effect's name, arguments, and implementation to [perform]:
+//!
+//! ```
+//! use roc_embed::effects;
+//! use roc_std::RocStr;
+//!
+//! #[no_mangle]
+//! pub extern "C" fn roc_fx_get_env(name: &RocStr) -> RocStr {
+//!     effects::perform("get_env", name.as_str(), |name| {
+//!         RocStr::from(std::env::var(name).unwrap_or_default().as_str())
+//!     })
+//! }
+//! ```
+//!
+//! Normally that just runs the implementation. Inside [record], every effect is also appended to an
+//! [EffectTrace], which can be saved as JSON. Inside [replay], effects don't run at all: each one
+//! returns the result from the trace instead, so a test gets the same results on every run.
+//!
+//! The mode is per thread, since Roc calls the effects on the thread that called into Roc.
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::fmt;
+
+/// An effect that was performed, with its arguments and result.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EffectCall {
+    pub name: String,
+    pub args: serde_json::Value,
+    pub result: serde_json::Value,
+}
+
+/// The effects performed during a run, in order.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EffectTrace {
+    pub effects: Vec<EffectCall>,
+}
+
+impl EffectTrace {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("an effect trace can always be serialized")
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+/// A replayed run that didn't perform the same effects as the recorded one.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReplayError {
+    /// The effect at this index had a different name or arguments than in the trace.
+    /// The replay continued with the recorded result.
+    Diverged {
+        index: usize,
+        expected: Box<EffectCall>,
+        name: String,
+        args: serde_json::Value,
+    },
+    /// The run finished before performing all the effects in the trace.
+    Unperformed { performed: usize, recorded: usize },
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Diverged {
+                index,
+                expected,
+                name,
+                args,
+            } => write!(
+                f,
+                "Effect #{index} was `{name}` with arguments {args}, but the trace has `{}` with arguments {}",
+                expected.name, expected.args
+            ),
+            ReplayError::Unperformed {
+                performed,
+                recorded,
+            } => write!(
+                f,
+                "The run performed {performed} effects, but the trace has {recorded}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+enum Mode {
+    Live,
+    Recording(EffectTrace),
+    Replaying {
+        trace: EffectTrace,
+        next: usize,
+        divergence: Option<ReplayError>,
+    },
+}
+
+thread_local! {
+    static MODE: RefCell<Mode> = const { RefCell::new(Mode::Live) };
+}
+
+/// Runs `f`, and returns the effects it performed along with its result.
+pub fn record<T>(f: impl FnOnce() -> T) -> (T, EffectTrace) {
+    let previous = MODE.replace(Mode::Recording(EffectTrace::default()));
+    let answer = f();
+
+    match MODE.replace(previous) {
+        Mode::Recording(trace) => (answer, trace),
+        Mode::Live | Mode::Replaying { .. } => unreachable!(),
+    }
+}
+
+/// Runs `f`, with every effect returning its result from `trace` instead of running.
+///
+/// Effects are matched to the trace by position. If one has a different name or arguments than the
+/// recorded effect, the recorded result is still used (if it has the right type), so the run can
+/// finish, and this returns the first such difference. If `f` performs more effects than the trace
+/// has, or a recorded result has the wrong type, there is no value to return to Roc, so this prints
+/// an error and aborts, like a Roc `crash` does.
+pub fn replay<T>(trace: &EffectTrace, f: impl FnOnce() -> T) -> Result<T, ReplayError> {
+    let previous = MODE.replace(Mode::Replaying {
+        trace: trace.clone(),
+        next: 0,
+        divergence: None,
+    });
+    let answer = f();
+
+    match MODE.replace(previous) {
+        Mode::Replaying {
+            divergence: Some(err),
+            ..
+        } => Err(err),
+        Mode::Replaying { trace, next, .. } if next < trace.effects.len() => {
+            Err(ReplayError::Unperformed {
+                performed: next,
+                recorded: trace.effects.len(),
+            })
+        }
+        Mode::Replaying { .. } => Ok(answer),
+        Mode::Live | Mode::Recording(_) => unreachable!(),
+    }
+}
+
+/// Performs an effect named `name` by calling `run` with `args`, unless it's being replayed.
+/// See the [module documentation](self).
+pub fn perform<A, R>(name: &str, args: A, run: impl FnOnce(A) -> R) -> R
+where
+    A: Serialize,
+    R: Serialize + DeserializeOwned,
+{
+    enum Action<R> {
+        Run,
+        Record,
+        Replayed(R),
+    }
+
+    let action = MODE.with_borrow_mut(|mode| match mode {
+        Mode::Live => Action::Run,
+        Mode::Recording(_) => Action::Record,
+        Mode::Replaying {
+            trace,
+            next,
+            divergence,
+        } => Action::Replayed(replay_effect(trace, next, divergence, name, &args)),
+    });
+
+    match action {
+        Action::Run => run(args),
+        Action::Record => {
+            // The mode isn't borrowed while the effect runs, in case it calls back into Roc.
+            let args_json = to_json(name, &args);
+            let result = run(args);
+            let result_json = to_json(name, &result);
+
+            MODE.with_borrow_mut(|mode| {
+                if let Mode::Recording(trace) = mode {
+                    trace.effects.push(EffectCall {
+                        name: name.to_string(),
+                        args: args_json,
+                        result: result_json,
+                    });
+                }
+            });
+
+            result
+        }
+        Action::Replayed(result) => result,
+    }
+}
+
+fn replay_effect<R: DeserializeOwned>(
+    trace: &EffectTrace,
+    next: &mut usize,
+    divergence: &mut Option<ReplayError>,
+    name: &str,
+    args: &impl Serialize,
+) -> R {
+    let index = *next;
+    let args_json = to_json(name, args);
+
+    let Some(expected) = trace.effects.get(index) else {
+        replay_failed(format_args!(
+            "Effect #{index} was `{name}` with arguments {args_json}, but the trace only has {} effects",
+            trace.effects.len()
+        ));
+    };
+
+    if divergence.is_none() && (expected.name != name || expected.args != args_json) {
+        *divergence = Some(ReplayError::Diverged {
+            index,
+            expected: Box::new(expected.clone()),
+            name: name.to_string(),
+            args: args_json,
+        });
+    }
+
+    *next += 1;
+
+    match R::deserialize(&expected.result) {
+        Ok(result) => result,
+        Err(err) => replay_failed(format_args!(
+            "The recorded result of effect #{index} (`{}`) doesn't have the type `{name}` returns: {err}",
+            expected.name
+        )),
+    }
+}
+
+fn to_json(name: &str, value: &impl Serialize) -> serde_json::Value {
+    serde_json::to_value(value).unwrap_or_else(|err| {
+        replay_failed(format_args!(
+            "Could not serialize a value of effect `{name}`: {err}"
+        ))
+    })
+}
+
+fn replay_failed(msg: fmt::Arguments) -> ! {
+    // Unwinding through Roc's stack frames is not possible
+    eprintln!("{msg}");
+    std::process::abort()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use roc_std::RocStr;
+
+    fn read_line(prompt: &str, line: &str) -> RocStr {
+        perform("read_line", prompt, |_| RocStr::from(line))
+    }
+
+    fn run(line: &str) -> String {
+        let first = read_line("name?", line);
+        let second = read_line("again?", line);
+
+        format!("{first} {second}")
+    }
+
+    #[test]
+    fn live_effects_run() {
+        assert_eq!(run("live"), "live live");
+    }
+
+    #[test]
+    fn replay_returns_recorded_results() {
+        let (answer, trace) = record(|| run("recorded"));
+
+        assert_eq!(answer, "recorded recorded");
+        assert_eq!(trace.effects.len(), 2);
+        assert_eq!(trace.effects[1].args, serde_json::json!("again?"));
+        assert_eq!(trace.effects[1].result, serde_json::json!("recorded"));
+
+        let trace = EffectTrace::from_json(&trace.to_json()).unwrap();
+
+        assert_eq!(replay(&trace, || run("replayed")), Ok(answer));
+    }
+
+    #[test]
+    fn replay_reports_divergence() {
+        let (_, trace) = record(|| run("recorded"));
+
+        let answer = replay(&trace, || {
+            read_line("name?", "replayed");
+            read_line("something else?", "replayed")
+        });
+
+        assert_eq!(
+            answer,
+            Err(ReplayError::Diverged {
+                index: 1,
+                expected: Box::new(trace.effects[1].clone()),
+                name: "read_line".to_string(),
+                args: serde_json::json!("something else?"),
+            })
+        );
+    }
+
+    #[test]
+    fn replay_reports_unperformed_effects() {
+        let (_, trace) = record(|| run("recorded"));
+
+        let answer = replay(&trace, || read_line("name?", "replayed"));
+
+        assert_eq!(
+            answer,
+            Err(ReplayError::Unperformed {
+                performed: 1,
+                recorded: 2
+            })
+        );
+    }
+}
@@ -0,0 +1,430 @@
+//! Heap profiling of the allocations a Roc app makes through `roc_alloc`, to diagnose memory growth
+//! in long-running apps.
+//!
+//! While profiling is on, every allocation is recorded with its size, its kind, and the Roc def it
+//! was made in. `roc_alloc` doesn't say what an allocation is for, so the kind and the def are
+//! found by walking the native stack, which makes allocations much slower. The def is only known
+//! for libraries with a frame table (see [crate::stack_trace]).
+//!
+//! ```no_run
+//! use roc_embed::heap_profile;
+//!
+//! heap_profile::start();
+//! // ...call into Roc...
+//! let profile = heap_profile::stop();
+//!
+//! eprintln!("{profile}");
+//! ```
+//!
+//! Alternatively, setting the `ROC_HEAP_PROFILE` environment variable makes [crate::RocApp::load]
+//! start profiling, and print the profile when the process exits.
+use std::backtrace::Backtrace;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use crate::stack_trace::{self, RocFrame};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AllocKind {
+    Str,
+    List,
+    /// Boxes and captured closure environments, which Roc code allocates directly.
+    BoxOrCapture,
+    /// Allocations whose purpose couldn't be determined from the stack, e.g. ones made by the host.
+    Unknown,
+}
+
+impl fmt::Display for AllocKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            AllocKind::Str => "Str",
+            AllocKind::List => "List",
+            AllocKind::BoxOrCapture => "Box or closure capture",
+            AllocKind::Unknown => "unknown",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HeapStats {
+    pub live_bytes: usize,
+    pub live_allocations: usize,
+}
+
+impl HeapStats {
+    fn add(&mut self, bytes: usize) {
+        self.live_bytes += bytes;
+        self.live_allocations += 1;
+    }
+
+    fn remove(&mut self, bytes: usize) {
+        self.live_bytes -= bytes;
+        self.live_allocations -= 1;
+    }
+}
+
+/// The live allocations at one point in time, from allocations made while profiling.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HeapProfile {
+    pub total: HeapStats,
+    /// The most bytes that were live at once.
+    pub peak_bytes: usize,
+    /// How many allocations were made in total, including ones that were freed since.
+    pub allocations_made: usize,
+    pub by_kind: BTreeMap<AllocKind, HeapStats>,
+    /// By the qualified name of the innermost Roc def on the stack, if it's known.
+    pub by_def: BTreeMap<String, HeapStats>,
+}
+
+impl fmt::Display for HeapProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Roc heap: {} bytes live in {} allocations (peak {} bytes, {} allocations made)",
+            self.total.live_bytes,
+            self.total.live_allocations,
+            self.peak_bytes,
+            self.allocations_made
+        )?;
+
+        let mut sections: [(&str, Vec<(String, HeapStats)>); 2] = [
+            (
+                "By kind",
+                self.by_kind
+                    .iter()
+                    .map(|(kind, stats)| (kind.to_string(), *stats))
+                    .collect(),
+            ),
+            (
+                "By def",
+                self.by_def
+                    .iter()
+                    .map(|(name, stats)| (name.clone(), *stats))
+                    .collect(),
+            ),
+        ];
+
+        for (title, rows) in sections.iter_mut() {
+            rows.retain(|(_, stats)| stats.live_allocations > 0);
+
+            if rows.is_empty() {
+                continue;
+            }
+
+            // The biggest first, since those are the ones worth looking into
+            rows.sort_by_key(|(_, stats)| std::cmp::Reverse(stats.live_bytes));
+
+            writeln!(f, "{title}:")?;
+
+            for (name, stats) in rows.iter() {
+                writeln!(
+                    f,
+                    "    {:>12} bytes in {:>6} allocations  {name}",
+                    stats.live_bytes, stats.live_allocations
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// An allocation that hasn't been freed yet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LiveAllocation {
+    pub address: usize,
+    pub size: usize,
+    pub kind: AllocKind,
+    /// The innermost Roc def on the stack when it was allocated, if it's known.
+    pub def: Option<RocFrame>,
+}
+
+struct Allocation {
+    size: usize,
+    kind: AllocKind,
+    def: Option<RocFrame>,
+}
+
+#[derive(Default)]
+struct Profiler {
+    allocations: HashMap<usize, Allocation>,
+    profile: HeapProfile,
+    /// Deallocations of pointers that weren't allocated while profiling
+    untracked_frees: usize,
+}
+
+impl Profiler {
+    fn insert(&mut self, ptr: usize, allocation: Allocation) {
+        let profile = &mut self.profile;
+
+        profile.total.add(allocation.size);
+        profile.peak_bytes = profile.peak_bytes.max(profile.total.live_bytes);
+        profile.allocations_made += 1;
+        profile
+            .by_kind
+            .entry(allocation.kind)
+            .or_default()
+            .add(allocation.size);
+
+        if let Some(def) = &allocation.def {
+            profile
+                .by_def
+                .entry(def.name.clone())
+                .or_default()
+                .add(allocation.size);
+        }
+
+        self.allocations.insert(ptr, allocation);
+    }
+
+    fn remove(&mut self, ptr: usize) -> Option<Allocation> {
+        // Allocations made before profiling started aren't tracked
+        let allocation = self.allocations.remove(&ptr)?;
+        let profile = &mut self.profile;
+
+        profile.total.remove(allocation.size);
+
+        if let Some(stats) = profile.by_kind.get_mut(&allocation.kind) {
+            stats.remove(allocation.size);
+        }
+
+        if let Some(stats) = allocation
+            .def
+            .as_ref()
+            .and_then(|def| profile.by_def.get_mut(&def.name))
+        {
+            stats.remove(allocation.size);
+        }
+
+        Some(allocation)
+    }
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static PROFILER: Mutex<Option<Profiler>> = Mutex::new(None);
+
+/// Starts recording allocations, discarding the data of any earlier profiling run.
+pub fn start() {
+    *PROFILER.lock().unwrap() = Some(Profiler::default());
+    ENABLED.store(true, Ordering::SeqCst);
+}
+
+/// Stops recording allocations, and returns the profile of the allocations that are still live.
+pub fn stop() -> HeapProfile {
+    ENABLED.store(false, Ordering::SeqCst);
+
+    PROFILER
+        .lock()
+        .unwrap()
+        .take()
+        .map(|profiler| profiler.profile)
+        .unwrap_or_default()
+}
+
+/// The environment variable that makes [crate::RocApp::load] start profiling.
+pub const PROFILE_ENV_VAR: &str = "ROC_HEAP_PROFILE";
+
+/// Starts profiling if [PROFILE_ENV_VAR] is set, and prints the profile when the process exits.
+pub(crate) fn start_from_env() {
+    extern "C" fn print_profile() {
+        eprintln!("{}", stop());
+    }
+
+    let is_set = std::env::var_os(PROFILE_ENV_VAR).is_some_and(|value| !value.is_empty());
+
+    if is_set && !ENABLED.swap(true, Ordering::SeqCst) {
+        start();
+
+        unsafe { libc::atexit(print_profile) };
+    }
+}
+
+/// The profile so far, without stopping. Returns None if profiling isn't on.
+pub fn snapshot() -> Option<HeapProfile> {
+    let profiler = PROFILER.lock().unwrap();
+
+    profiler.as_ref().map(|profiler| profiler.profile.clone())
+}
+
+pub(crate) fn record_alloc(ptr: *mut core::ffi::c_void, size: usize) {
+    if !ENABLED.load(Ordering::Relaxed) || ptr.is_null() {
+        return;
+    }
+
+    let backtrace = Backtrace::force_capture().to_string();
+    let allocation = Allocation {
+        size,
+        kind: classify(&backtrace),
+        def: stack_trace::innermost_roc_def(&backtrace),
+    };
+
+    if let Some(profiler) = PROFILER.lock().unwrap().as_mut() {
+        profiler.insert(ptr as usize, allocation);
+    }
+}
+
+pub(crate) fn record_realloc(
+    old: *mut core::ffi::c_void,
+    new: *mut core::ffi::c_void,
+    size: usize,
+) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+
+    if let Some(profiler) = PROFILER.lock().unwrap().as_mut() {
+        if let Some(allocation) = profiler.remove(old as usize) {
+            if !new.is_null() {
+                profiler.insert(new as usize, Allocation { size, ..allocation });
+                // A reallocation isn't a new allocation
+                profiler.profile.allocations_made -= 1;
+            }
+        }
+    }
+}
+
+pub(crate) fn record_dealloc(ptr: *mut core::ffi::c_void) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+
+    if let Some(profiler) = PROFILER.lock().unwrap().as_mut() {
+        if profiler.remove(ptr as usize).is_none() {
+            profiler.untracked_frees += 1;
+        }
+    }
+}
+
+/// The allocations that haven't been freed, and how many pointers were freed that weren't
+/// allocated while profiling. Returns None if profiling isn't on.
+pub(crate) fn live_allocations() -> Option<(Vec<LiveAllocation>, usize)> {
+    let profiler = PROFILER.lock().unwrap();
+    let profiler = profiler.as_ref()?;
+
+    let mut allocations: Vec<_> = profiler
+        .allocations
+        .iter()
+        .map(|(address, allocation)| LiveAllocation {
+            address: *address,
+            size: allocation.size,
+            kind: allocation.kind,
+            def: allocation.def.clone(),
+        })
+        .collect();
+
+    allocations.sort_by_key(|allocation| allocation.address);
+
+    Some((allocations, profiler.untracked_frees))
+}
+
+/// What an allocation is for, based on the functions on the stack when it was made.
+fn classify(backtrace: &str) -> AllocKind {
+    for function_name in stack_trace::function_names(backtrace) {
+        // Zig builtins are named e.g. `roc_builtins.str.concat`, Roc builtins are mangled names
+        // that demangle to e.g. `Str.concat`, and roc_std functions are named e.g.
+        // `roc_std::roc_str::RocStr::from_slice_unchecked`.
+        let roc_name = stack_trace::demangle(function_name).unwrap_or_default();
+
+        if function_name.starts_with("roc_builtins.str") || roc_name.starts_with("Str.") {
+            return AllocKind::Str;
+        } else if function_name.starts_with("roc_builtins.list") || roc_name.starts_with("List.") {
+            return AllocKind::List;
+        } else if function_name.starts_with("roc_std::roc_str") {
+            return AllocKind::Str;
+        } else if function_name.starts_with("roc_std::roc_list") {
+            return AllocKind::List;
+        } else if stack_trace::is_roc_function(function_name) {
+            return AllocKind::BoxOrCapture;
+        }
+    }
+
+    AllocKind::Unknown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allocations_are_classified_by_caller() {
+        let str_concat = "   0: roc_embed::runtime::roc_alloc
+   1: roc_builtins.str.concat
+   2: _Roc4Main5greet_h0000000000000003
+";
+        let list_literal = "   0: roc_alloc
+   1: roc_builtins.utils.allocate_with_refcount
+   2: _Roc4List6append_h000000000000001a
+   3: _Roc4Main4main_h0000000000000001
+";
+        let host = "   0: roc_alloc
+   1: roc_std::roc_str::RocStr::from_slice_unchecked
+   2: host::main
+";
+
+        assert_eq!(classify(str_concat), AllocKind::Str);
+        assert_eq!(classify(list_literal), AllocKind::List);
+        assert_eq!(classify(host), AllocKind::Str);
+        assert_eq!(
+            classify("   0: roc_alloc\n   1: host::main\n"),
+            AllocKind::Unknown
+        );
+    }
+
+    #[test]
+    fn live_bytes_are_tracked_until_freed() {
+        let mut profiler = Profiler::default();
+        let allocation = |size, kind, def: &str| Allocation {
+            size,
+            kind,
+            def: Some(RocFrame {
+                name: def.to_string(),
+                location: "main.roc:1:1".to_string(),
+            }),
+        };
+
+        profiler.insert(0x10, allocation(100, AllocKind::Str, "Main.greet"));
+        profiler.insert(0x20, allocation(50, AllocKind::List, "Main.main"));
+        profiler.remove(0x10);
+        profiler.insert(0x30, allocation(30, AllocKind::Str, "Main.greet"));
+        // Wasn't allocated while profiling
+        profiler.remove(0x40);
+
+        let profile = &profiler.profile;
+
+        assert_eq!(
+            profile.total,
+            HeapStats {
+                live_bytes: 80,
+                live_allocations: 2
+            }
+        );
+        assert_eq!(profile.peak_bytes, 150);
+        assert_eq!(profile.allocations_made, 3);
+        assert_eq!(profile.by_kind[&AllocKind::Str].live_bytes, 30);
+        assert_eq!(profile.by_def["Main.main"].live_bytes, 50);
+    }
+
+    #[test]
+    fn roc_alloc_is_profiled() {
+        use crate::runtime::{roc_alloc, roc_dealloc, roc_realloc};
+
+        start();
+
+        let profile = unsafe {
+            let ptr = roc_alloc(64, 8);
+            let ptr = roc_realloc(ptr, 128, 64, 8);
+            let profile = snapshot().unwrap();
+
+            roc_dealloc(ptr, 8);
+
+            profile
+        };
+
+        assert_eq!(profile.total.live_bytes, 128);
+        assert_eq!(profile.allocations_made, 1);
+        assert_eq!(profile.by_kind[&AllocKind::Unknown].live_allocations, 1);
+        assert_eq!(stop().total.live_bytes, 0);
+    }
+}
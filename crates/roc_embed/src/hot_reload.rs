@@ -0,0 +1,182 @@
+//! Reloading the app while the host runs, for `roc run --hot`.
+//!
+//! `roc run --hot` rebuilds the app as a new shared library whenever one of its source files
+//! changes, and lists the latest one in a manifest file, whose path it passes to the host in the
+//! `ROC_HOT_RELOAD` environment variable:
+//!
+//! ```text
+//! # roc hot reload v1
+//! {generation}\t{path to the shared library}
+//! ```
+//!
+//! A host polls the manifest at a safe point, where no Roc code is running and it holds no Roc
+//! values (e.g. between two frames of a game, or two requests of a server), and switches to the
+//! app it gets back:
+//!
+//! ```no_run
+//! use roc_embed::hot_reload::HotReload;
+//! use roc_embed::RocApp;
+//! use std::path::Path;
+//!
+//! let mut app = unsafe { RocApp::load(Path::new("libapp.so")) }.unwrap();
+//! let mut hot_reload = HotReload::from_env();
+//!
+//! loop {
+//!     let _: i64 = unsafe { app.call("update_for_host", (0i64,)) }.unwrap();
+//!
+//!     if let Some(hot_reload) = hot_reload.as_mut() {
+//!         match unsafe { hot_reload.poll() } {
+//!             Ok(Some(new_app)) => app = new_app,
+//!             Ok(None) => {}
+//!             Err(err) => eprintln!("{err}"),
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! Replacing the [RocApp] unloads the previous library, so values it returned can't be used
+//! afterwards; not even constants, which point into the library.
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{EmbedError, RocApp};
+
+/// The environment variable `roc run --hot` puts the manifest's path in. The same as in the
+/// `hot_reload` module of `roc_cli`, which writes the manifest.
+pub const HOT_RELOAD_ENV_VAR: &str = "ROC_HOT_RELOAD";
+
+/// The first line of every manifest, so hosts can reject files in an unknown format.
+pub const MANIFEST_HEADER: &str = "# roc hot reload v1";
+
+/// Watches a hot reload manifest for new builds of the app.
+#[derive(Debug)]
+pub struct HotReload {
+    manifest_path: PathBuf,
+    generation: u64,
+}
+
+impl HotReload {
+    /// Watches the manifest `roc run --hot` told the host about, if the program runs under it.
+    pub fn from_env() -> Option<Self> {
+        std::env::var_os(HOT_RELOAD_ENV_VAR).map(|path| Self::new(PathBuf::from(path)))
+    }
+
+    pub fn new(manifest_path: PathBuf) -> Self {
+        Self {
+            manifest_path,
+            generation: 0,
+        }
+    }
+
+    /// The generation of the last library [HotReload::poll] returned, or 0 if it didn't yet.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Loads the latest build of the app, if it's newer than the last one this returned.
+    ///
+    /// A library that fails to load isn't tried again, since the next rebuild replaces it;
+    /// the host keeps running the app it has until then.
+    ///
+    /// # Safety
+    /// Like [RocApp::load]. Call this at a safe point, where no Roc code is running.
+    pub unsafe fn poll(&mut self) -> Result<Option<RocApp>, EmbedError> {
+        let Some((generation, lib_path)) = self.read_manifest()? else {
+            return Ok(None);
+        };
+
+        if generation <= self.generation {
+            return Ok(None);
+        }
+
+        self.generation = generation;
+
+        RocApp::load(&lib_path).map(Some)
+    }
+
+    /// The latest generation and its library, if the app was rebuilt yet.
+    fn read_manifest(&self) -> Result<Option<(u64, PathBuf)>, EmbedError> {
+        let problem = |message: String| EmbedError::HotReloadManifest {
+            path: self.manifest_path.clone(),
+            message,
+        };
+        let contents = fs::read_to_string(&self.manifest_path)
+            .map_err(|err| problem(format!("it could not be read: {err}")))?;
+        let mut lines = contents.lines();
+
+        if lines.next() != Some(MANIFEST_HEADER) {
+            return Err(problem(format!(
+                "it does not start with `{MANIFEST_HEADER}`"
+            )));
+        }
+
+        let Some(line) = lines.next() else {
+            return Ok(None);
+        };
+
+        match line.split_once('\t') {
+            Some((generation, lib_path)) => match generation.parse() {
+                Ok(generation) => Ok(Some((generation, Path::new(lib_path).to_path_buf()))),
+                Err(_) => Err(problem(format!("`{generation}` is not a generation"))),
+            },
+            None => Err(problem(format!("`{line}` is not a generation and a path"))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_manifest(dir: &Path, contents: &str) -> HotReload {
+        let manifest_path = dir.join("manifest");
+
+        fs::write(&manifest_path, contents).unwrap();
+
+        HotReload::new(manifest_path)
+    }
+
+    #[test]
+    fn nothing_to_reload_before_a_rebuild() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut hot_reload = write_manifest(dir.path(), "# roc hot reload v1\n");
+
+        assert!(matches!(unsafe { hot_reload.poll() }, Ok(None)));
+        assert_eq!(hot_reload.generation(), 0);
+    }
+
+    #[test]
+    fn unknown_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut hot_reload = write_manifest(dir.path(), "# roc hot reload v2\n");
+
+        assert!(matches!(
+            unsafe { hot_reload.poll() },
+            Err(EmbedError::HotReloadManifest { .. })
+        ));
+
+        let mut hot_reload = write_manifest(dir.path(), "# roc hot reload v1\nlatest\n");
+
+        assert!(matches!(
+            unsafe { hot_reload.poll() },
+            Err(EmbedError::HotReloadManifest { .. })
+        ));
+    }
+
+    #[test]
+    fn a_library_that_fails_to_load_is_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let lib_path = dir.path().join("app-2.so");
+        let mut hot_reload = write_manifest(
+            dir.path(),
+            &format!("# roc hot reload v1\n2\t{}\n", lib_path.display()),
+        );
+
+        assert!(matches!(
+            unsafe { hot_reload.poll() },
+            Err(EmbedError::Load(_))
+        ));
+        assert_eq!(hot_reload.generation(), 2);
+        assert!(matches!(unsafe { hot_reload.poll() }, Ok(None)));
+    }
+}
@@ -0,0 +1,167 @@
+//! Leak checking, which verifies that every allocation made through `roc_alloc` was freed again.
+//! Roc frees a value when its refcount drops to zero, so a leak means a refcount never got there;
+//! usually because the host forgot to drop a value it got from Roc, or incremented a refcount
+//! without a matching decrement. Freeing a pointer twice is reported as well.
+//!
+//! ```no_run
+//! use roc_embed::leak_check;
+//!
+//! leak_check::start();
+//! // ...call into Roc, and drop everything it returned...
+//!
+//! if let Err(leaks) = leak_check::check() {
+//!     panic!("{leaks}");
+//! }
+//! ```
+//!
+//! Alternatively, setting the `ROC_LEAK_CHECK` environment variable makes [crate::RocApp::load]
+//! start checking, and exit the process with an error when it ends with leaks.
+//!
+//! This uses the same tracking as [crate::heap_profile], so it's just as slow, and starting one
+//! resets the other. Allocation sites are only known for libraries with a frame table
+//! (see [crate::stack_trace]).
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::heap_profile::{self, LiveAllocation};
+
+/// The environment variable that makes [crate::RocApp::load] start leak checking.
+pub const LEAK_CHECK_ENV_VAR: &str = "ROC_LEAK_CHECK";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Leaks {
+    /// The allocations that haven't been freed.
+    pub allocations: Vec<LiveAllocation>,
+    /// How many times a pointer was freed that wasn't allocated while checking, e.g. because
+    /// it had already been freed.
+    pub untracked_frees: usize,
+}
+
+impl fmt::Display for Leaks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.allocations.is_empty() {
+            let bytes: usize = self
+                .allocations
+                .iter()
+                .map(|allocation| allocation.size)
+                .sum();
+
+            writeln!(
+                f,
+                "{} allocations ({bytes} bytes) were never freed:",
+                self.allocations.len()
+            )?;
+
+            for allocation in self.allocations.iter() {
+                write!(
+                    f,
+                    "    {:#x}: {} bytes ({})",
+                    allocation.address, allocation.size, allocation.kind
+                )?;
+
+                match &allocation.def {
+                    Some(def) => writeln!(f, " allocated in {} ({})", def.name, def.location)?,
+                    None => writeln!(f)?,
+                }
+            }
+        }
+
+        if self.untracked_frees > 0 {
+            writeln!(
+                f,
+                "{} pointers were freed that weren't allocated while leak checking, e.g. because they were freed twice",
+                self.untracked_frees
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for Leaks {}
+
+/// Starts tracking allocations, forgetting about earlier ones.
+pub fn start() {
+    heap_profile::start();
+}
+
+/// Checks that every allocation made since [start] has been freed. This can be called at any
+/// point where the host holds no Roc values, so that all of their refcounts should be zero.
+///
+/// Returns Ok if leak checking isn't on.
+pub fn check() -> Result<(), Leaks> {
+    let Some((allocations, untracked_frees)) = heap_profile::live_allocations() else {
+        return Ok(());
+    };
+
+    if allocations.is_empty() && untracked_frees == 0 {
+        Ok(())
+    } else {
+        Err(Leaks {
+            allocations,
+            untracked_frees,
+        })
+    }
+}
+
+/// Starts leak checking if [LEAK_CHECK_ENV_VAR] is set, and checks for leaks when the process exits.
+pub(crate) fn start_from_env() {
+    static STARTED: AtomicBool = AtomicBool::new(false);
+
+    extern "C" fn check_at_exit() {
+        if let Err(leaks) = check() {
+            eprintln!("Leak check failed: {leaks}");
+
+            // Exiting again from an exit handler is undefined behavior, so skip the other handlers
+            unsafe { libc::_exit(1) };
+        }
+    }
+
+    let is_set = std::env::var_os(LEAK_CHECK_ENV_VAR).is_some_and(|value| !value.is_empty());
+
+    if is_set && !STARTED.swap(true, Ordering::SeqCst) {
+        start();
+
+        unsafe { libc::atexit(check_at_exit) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::heap_profile::AllocKind;
+    use crate::stack_trace::RocFrame;
+
+    #[test]
+    fn leaks_report_sites() {
+        let leaks = Leaks {
+            allocations: vec![
+                LiveAllocation {
+                    address: 0x1000,
+                    size: 40,
+                    kind: AllocKind::Str,
+                    def: Some(RocFrame {
+                        name: "Main.greet".to_string(),
+                        location: "main.roc:5:1".to_string(),
+                    }),
+                },
+                LiveAllocation {
+                    address: 0x2000,
+                    size: 24,
+                    kind: AllocKind::Unknown,
+                    def: None,
+                },
+            ],
+            untracked_frees: 1,
+        };
+
+        assert_eq!(
+            leaks.to_string(),
+            "2 allocations (64 bytes) were never freed:
+    0x1000: 40 bytes (Str) allocated in Main.greet (main.roc:5:1)
+    0x2000: 24 bytes (unknown)
+1 pointers were freed that weren't allocated while leak checking, e.g. because they were freed twice
+"
+        );
+    }
+}
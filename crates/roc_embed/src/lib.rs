@@ -0,0 +1,757 @@
+//! Loads a Roc app that was built as a shared library (with `roc build --lib`), and calls the
+//! functions its platform exposes to the host. This lets Rust programs use Roc as an embedded
+//! scripting or configuration language, without writing a platform host in another language.
+//!
+//! Values are passed using the types in [roc_std], plus records declared with [roc_record!],
+//! which checks at compile time that the Rust struct has the same memory layout as the Roc record,
+//! and tag unions declared with [roc_tag_union!], which are returned from Roc as a [RocUnion].
+//!
+//! The shared library expects the program that loads it to provide `roc_alloc` and the other
+//! functions every Roc host has to implement. This crate defines them, but they are only visible
+//! to the library if the executable exports its symbols, e.g. with
+//! `println!("cargo:rustc-link-arg=-rdynamic")` in the executable's build script.
+//!
+//! A Roc `crash` (or a panic in the Roc standard library) prints its message and aborts the
+//! process, because it can't unwind through Roc code. If the library was built with
+//! `roc build --lib --stack-traces`, it also prints a Roc stack trace; see [stack_trace].
+//!
+//! The [effects] module can record the effects a Roc app performs, and replay them in tests.
+//! The [heap_profile] module can report what the memory a Roc app allocated is used for, and
+//! [leak_check] can verify that the host dropped all the Roc values it was given. Output from
+//! `dbg` goes to stderr unless the host routes it elsewhere with [dbg::set_handler]. With
+//! [hot_reload], a host run by `roc run --hot` can switch to each new build of the app.
+//!
+//! In debug builds, memory Roc frees is overwritten first, so that using a value after its
+//! refcount reached zero fails loudly. The host and app can also be run under a sanitizer: build
+//! the app with `ROC_SANITIZERS=address` (or `thread` or `memory`) set, using a `roc` built with
+//! the `sanitizers` feature, and the host with the matching `-Zsanitizer` flag.
+use core::ffi::c_void;
+use core::fmt;
+use core::mem::{ManuallyDrop, MaybeUninit};
+use libloading::Library;
+use roc_std::{RocBox, RocDec, RocList, RocRefcounted, RocResult, RocStr, I128, U128};
+use std::path::{Path, PathBuf};
+
+pub mod dbg;
+pub mod effects;
+pub mod heap_profile;
+pub mod hot_reload;
+pub mod leak_check;
+mod runtime;
+pub mod stack_trace;
+
+pub use roc_std;
+
+/// Types whose Rust representation has the same memory layout as a Roc type, so that they can be
+/// passed to and returned from Roc functions.
+///
+/// # Safety
+/// Implementing this for a type with a different layout than the corresponding Roc type is
+/// undefined behavior. Use [roc_record!] rather than implementing it for records by hand.
+pub unsafe trait RocAbi: Sized {
+    /// How exposed functions take this type as an argument: either the value itself,
+    /// or a pointer to it.
+    type Arg;
+
+    /// The argument to pass for this value. Roc takes ownership of it.
+    fn to_arg(value: &ManuallyDrop<Self>) -> Self::Arg;
+}
+
+/// Whether a value is passed to exposed functions by pointer. See [RocAbi::Arg].
+#[doc(hidden)]
+pub struct Passing<const BY_REFERENCE: bool>;
+
+#[doc(hidden)]
+pub trait PassAs<T> {
+    type Arg;
+
+    fn to_arg(value: &ManuallyDrop<T>) -> Self::Arg;
+}
+
+impl<T> PassAs<T> for Passing<true> {
+    type Arg = *const T;
+
+    fn to_arg(value: &ManuallyDrop<T>) -> *const T {
+        &**value
+    }
+}
+
+impl<T> PassAs<T> for Passing<false> {
+    type Arg = T;
+
+    fn to_arg(value: &ManuallyDrop<T>) -> T {
+        // The caller doesn't drop the value, so this moves it to Roc.
+        unsafe { core::ptr::read(&**value) }
+    }
+}
+
+/// Records are passed by reference if they're bigger than 4 machine words, like in the compiler.
+#[doc(hidden)]
+pub const fn record_passed_by_reference<T>() -> bool {
+    core::mem::size_of::<T>() > 4 * core::mem::size_of::<usize>()
+}
+
+macro_rules! impl_roc_abi {
+    ($by_reference:literal, $($ty:ty),*) => {
+        $(unsafe impl RocAbi for $ty {
+            type Arg = <Passing<$by_reference> as PassAs<$ty>>::Arg;
+
+            fn to_arg(value: &ManuallyDrop<Self>) -> Self::Arg {
+                <Passing<$by_reference> as PassAs<$ty>>::to_arg(value)
+            }
+        })*
+    };
+}
+
+impl_roc_abi!(
+    false,
+    (),
+    bool,
+    u8,
+    u16,
+    u32,
+    u64,
+    U128,
+    i8,
+    i16,
+    i32,
+    i64,
+    I128,
+    f32,
+    f64,
+    RocDec
+);
+impl_roc_abi!(true, RocStr);
+
+unsafe impl<T: RocAbi + RocRefcounted> RocAbi for RocList<T> {
+    type Arg = *const Self;
+
+    fn to_arg(value: &ManuallyDrop<Self>) -> Self::Arg {
+        <Passing<true> as PassAs<Self>>::to_arg(value)
+    }
+}
+
+unsafe impl<T: RocAbi + RocRefcounted> RocAbi for RocBox<T> {
+    type Arg = Self;
+
+    fn to_arg(value: &ManuallyDrop<Self>) -> Self::Arg {
+        <Passing<false> as PassAs<Self>>::to_arg(value)
+    }
+}
+
+unsafe impl<T: RocAbi, E: RocAbi> RocAbi for RocResult<T, E> {
+    type Arg = Self;
+
+    fn to_arg(value: &ManuallyDrop<Self>) -> Self::Arg {
+        <Passing<false> as PassAs<Self>>::to_arg(value)
+    }
+}
+
+/// A field of a [RocRecord], as declared in its Rust struct.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecordField {
+    pub name: &'static str,
+    pub align: usize,
+    pub offset: usize,
+}
+
+/// A Rust struct with the layout of a Roc record. Implemented by [roc_record!].
+pub trait RocRecord: RocAbi {
+    /// In declaration order
+    const FIELDS: &'static [RecordField];
+}
+
+/// Declares a `#[repr(C)]` struct that can be passed to and from Roc as a record.
+/// It can also be used in a [RocList] or [RocBox], since it implements [RocRefcounted].
+///
+/// Roc stores a record's fields sorted by alignment (largest first), and then by name. The struct's
+/// fields have to be declared in that order; otherwise, this fails to compile.
+///
+/// ```
+/// use roc_embed::roc_record;
+/// use roc_std::RocStr;
+///
+/// roc_record! {
+///     pub struct Config {
+///         pub name: RocStr,
+///         pub retries: u32,
+///         pub verbose: bool,
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! roc_record {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident {
+            $($field_vis:vis $field:ident : $ty:ty),* $(,)?
+        }
+    ) => {
+        $(#[$attr])*
+        #[repr(C)]
+        $vis struct $name {
+            $($field_vis $field: $ty),*
+        }
+
+        unsafe impl $crate::RocAbi for $name {
+            type Arg = <$crate::Passing<{ $crate::record_passed_by_reference::<$name>() }> as $crate::PassAs<$name>>::Arg;
+
+            fn to_arg(value: &::core::mem::ManuallyDrop<Self>) -> Self::Arg {
+                <$crate::Passing<{ $crate::record_passed_by_reference::<$name>() }> as $crate::PassAs<$name>>::to_arg(value)
+            }
+        }
+
+        impl $crate::roc_std::RocRefcounted for $name {
+            fn inc(&mut self) {
+                $($crate::roc_std::RocRefcounted::inc(&mut self.$field);)*
+            }
+
+            fn dec(&mut self) {
+                $($crate::roc_std::RocRefcounted::dec(&mut self.$field);)*
+            }
+
+            fn is_refcounted() -> bool {
+                false $(|| <$ty as $crate::roc_std::RocRefcounted>::is_refcounted())*
+            }
+        }
+
+        impl $crate::RocRecord for $name {
+            const FIELDS: &'static [$crate::RecordField] = &[
+                $($crate::RecordField {
+                    name: stringify!($field),
+                    align: ::core::mem::align_of::<$ty>(),
+                    offset: ::core::mem::offset_of!($name, $field),
+                }),*
+            ];
+        }
+
+        const _: () = {
+            fn assert_roc_abi<T: $crate::RocAbi>() {}
+
+            fn assert_fields() {
+                $(assert_roc_abi::<$ty>();)*
+            }
+
+            let _ = assert_fields;
+
+            assert!(
+                $crate::is_roc_field_order(<$name as $crate::RocRecord>::FIELDS),
+                concat!(
+                    "The fields of ",
+                    stringify!($name),
+                    " must be sorted by alignment (largest first) and then by name, to match the layout of a Roc record."
+                )
+            );
+        };
+    };
+}
+
+/// Whether the fields are in the order Roc stores them in: sorted by alignment (largest first),
+/// and then by name. Zero-sized fields are ignored, since they don't take up any space.
+pub const fn is_roc_field_order(fields: &[RecordField]) -> bool {
+    let mut index = 1;
+
+    while index < fields.len() {
+        let (prev, field) = (fields[index - 1], fields[index]);
+        let in_order = prev.align > field.align
+            || (prev.align == field.align && str_lt(prev.name, field.name))
+            || prev.offset == field.offset;
+
+        if !in_order {
+            return false;
+        }
+
+        index += 1;
+    }
+
+    true
+}
+
+const fn str_lt(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    let mut index = 0;
+
+    while index < a.len() && index < b.len() {
+        if a[index] != b[index] {
+            return a[index] < b[index];
+        }
+
+        index += 1;
+    }
+
+    a.len() < b.len()
+}
+
+/// The discriminant Roc uses for a tag, given the names of all the tags in its tag union.
+/// Tags are numbered in alphabetical order.
+pub fn tag_id(tag_names: &[&str], tag: &str) -> Option<u16> {
+    let mut sorted = tag_names.to_vec();
+
+    sorted.sort_unstable();
+    sorted.dedup();
+
+    sorted.binary_search(&tag).ok().map(|index| index as u16)
+}
+
+/// The discriminant Roc uses for `tag`, which is one of `tag_names`: the number of tags that
+/// come before it alphabetically. Used by [roc_tag_union!] to number the tags at compile time.
+#[doc(hidden)]
+pub const fn tag_index(tag_names: &[&str], tag: &str) -> u8 {
+    let mut index = 0;
+    let mut before = 0;
+
+    while index < tag_names.len() {
+        if str_lt(tag_names[index], tag) {
+            before += 1;
+        }
+
+        index += 1;
+    }
+
+    before
+}
+
+/// Whether the tag names are all different, and there are few enough of them for a `u8` tag id.
+#[doc(hidden)]
+pub const fn is_valid_tag_union(tag_names: &[&str]) -> bool {
+    if tag_names.len() < 2 || tag_names.len() > 256 {
+        return false;
+    }
+
+    let mut index = 0;
+
+    while index < tag_names.len() {
+        let mut other = index + 1;
+
+        while other < tag_names.len() {
+            let (a, b) = (tag_names[index], tag_names[other]);
+
+            if !str_lt(a, b) && !str_lt(b, a) {
+                return false;
+            }
+
+            other += 1;
+        }
+
+        index += 1;
+    }
+
+    true
+}
+
+/// A Rust enum that corresponds to a Roc tag union. Implemented by [roc_tag_union!].
+pub trait RocTagUnion: Sized {
+    /// The union with Roc's memory layout: the payloads overlapping each other, followed by the
+    /// tag id.
+    #[doc(hidden)]
+    type Repr;
+
+    #[doc(hidden)]
+    fn into_repr(self) -> Self::Repr;
+
+    /// # Safety
+    /// The tag id of `repr` must be one of this union's, and its payload must be that tag's.
+    #[doc(hidden)]
+    unsafe fn from_repr(repr: Self::Repr) -> Self;
+}
+
+/// A tag union with the memory layout Roc uses for it, which can be returned from a Roc function.
+/// Convert it to the Rust enum with [RocUnion::into_inner] to look at its tag and payload.
+///
+/// Exposed functions can't take a tag union as an argument yet, because the compiler passes them
+/// by pointer to Roc functions but by value to the host, and doesn't convert between the two.
+#[repr(transparent)]
+pub struct RocUnion<T: RocTagUnion>(ManuallyDrop<T::Repr>);
+
+impl<T: RocTagUnion> RocUnion<T> {
+    pub fn new(value: T) -> Self {
+        RocUnion(ManuallyDrop::new(value.into_repr()))
+    }
+
+    pub fn into_inner(self) -> T {
+        let mut this = ManuallyDrop::new(self);
+
+        unsafe { T::from_repr(ManuallyDrop::take(&mut this.0)) }
+    }
+}
+
+impl<T: RocTagUnion> From<T> for RocUnion<T> {
+    fn from(value: T) -> Self {
+        RocUnion::new(value)
+    }
+}
+
+impl<T: RocTagUnion + fmt::Debug> fmt::Debug for RocUnion<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Converting moves the payload out, so look at a copy that isn't dropped.
+        let value = ManuallyDrop::new(unsafe { T::from_repr(core::ptr::read(&*self.0)) });
+
+        fmt::Debug::fmt(&*value, f)
+    }
+}
+
+impl<T: RocTagUnion> Drop for RocUnion<T> {
+    fn drop(&mut self) {
+        // Drops the payload, which decrements its refcount if it's e.g. a Str.
+        drop(unsafe { T::from_repr(ManuallyDrop::take(&mut self.0)) });
+    }
+}
+
+unsafe impl<T: RocTagUnion> RocAbi for RocUnion<T> {
+    type Arg = Self;
+
+    fn to_arg(value: &ManuallyDrop<Self>) -> Self::Arg {
+        <Passing<false> as PassAs<Self>>::to_arg(value)
+    }
+}
+
+/// Declares a Rust enum for a Roc tag union, which Roc functions return as a [RocUnion].
+///
+/// Every tag can have at most one payload, which has to be a [RocAbi] type; use a record for
+/// several values. Roc numbers the tags alphabetically, so the variants can be declared in any
+/// order. The union needs at least two tags, since Roc doesn't store a tag id for a single tag.
+///
+/// ```
+/// use roc_embed::{roc_tag_union, RocUnion};
+/// use roc_std::RocStr;
+///
+/// roc_tag_union! {
+///     #[derive(Debug, PartialEq)]
+///     pub enum Shape {
+///         Circle(f64),
+///         Named(RocStr),
+///         Empty,
+///     }
+/// }
+///
+/// let shape = RocUnion::new(Shape::Circle(1.5));
+///
+/// assert_eq!(shape.into_inner(), Shape::Circle(1.5));
+/// ```
+#[macro_export]
+macro_rules! roc_tag_union {
+    (
+        $(#[$attr:meta])*
+        $vis:vis enum $name:ident {
+            $($variant:ident $(($payload:ty))?),* $(,)?
+        }
+    ) => {
+        $(#[$attr])*
+        $vis enum $name {
+            $($variant $(($payload))?),*
+        }
+
+        const _: () = {
+            use ::core::mem::ManuallyDrop;
+
+            const TAG_NAMES: &[&str] = &[$(stringify!($variant)),*];
+
+            #[allow(non_snake_case)]
+            #[repr(C)]
+            pub union Payload {
+                $($variant: ManuallyDrop<$crate::__roc_tag_payload_type!($($payload)?)>),*
+            }
+
+            #[repr(C)]
+            pub struct Repr {
+                payload: Payload,
+                tag: u8,
+            }
+
+            impl $crate::RocTagUnion for $name {
+                type Repr = Repr;
+
+                fn into_repr(self) -> Repr {
+                    match self {
+                        $($crate::__roc_tag_pattern!($name::$variant, payload $(, $payload)?) => Repr {
+                            payload: Payload {
+                                $variant: ManuallyDrop::new(
+                                    $crate::__roc_tag_payload!(payload $(, $payload)?)
+                                ),
+                            },
+                            tag: $crate::tag_index(TAG_NAMES, stringify!($variant)),
+                        },)*
+                    }
+                }
+
+                unsafe fn from_repr(repr: Repr) -> Self {
+                    $(if repr.tag == $crate::tag_index(TAG_NAMES, stringify!($variant)) {
+                        let payload = ManuallyDrop::into_inner(repr.payload.$variant);
+
+                        return $crate::__roc_tag_variant!($name::$variant, payload $(, $payload)?);
+                    })*
+
+                    unreachable!(
+                        "{} is not a tag id of {}",
+                        repr.tag,
+                        stringify!($name)
+                    )
+                }
+            }
+
+            fn assert_roc_abi<T: $crate::RocAbi>() {}
+
+            fn assert_payloads() {
+                $($(assert_roc_abi::<$payload>();)?)*
+            }
+
+            let _ = assert_payloads;
+
+            assert!(
+                $crate::is_valid_tag_union(TAG_NAMES),
+                concat!(
+                    "The tags of ",
+                    stringify!($name),
+                    " must have different names, and there must be between 2 and 256 of them."
+                )
+            );
+        };
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __roc_tag_payload_type {
+    () => {
+        ()
+    };
+    ($payload:ty) => {
+        $payload
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __roc_tag_pattern {
+    ($name:ident :: $variant:ident, $binding:ident) => {
+        $name::$variant
+    };
+    ($name:ident :: $variant:ident, $binding:ident, $payload:ty) => {
+        $name::$variant($binding)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __roc_tag_payload {
+    ($binding:ident) => {
+        ()
+    };
+    ($binding:ident, $payload:ty) => {
+        $binding
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __roc_tag_variant {
+    ($name:ident :: $variant:ident, $binding:ident) => {{
+        let () = $binding;
+
+        $name::$variant
+    }};
+    ($name:ident :: $variant:ident, $binding:ident, $payload:ty) => {
+        $name::$variant($binding)
+    };
+}
+
+/// The symbol the Roc compiler gives to a function the platform exposes to the host
+/// (the part of the platform header's `provides` named e.g. `main_for_host`).
+pub fn exposed_symbol_name(name: &str) -> String {
+    format!("roc__{name}_1_exposed_generic")
+}
+
+#[derive(Debug)]
+pub enum EmbedError {
+    Load(libloading::Error),
+    MissingFunction { name: String, symbol: String },
+    HotReloadManifest { path: PathBuf, message: String },
+}
+
+impl fmt::Display for EmbedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EmbedError::Load(err) => write!(f, "Failed to load the Roc app: {err}"),
+            EmbedError::MissingFunction { name, symbol } => write!(
+                f,
+                "The Roc app does not expose a function named `{name}` (there is no `{symbol}` symbol in the library)"
+            ),
+            EmbedError::HotReloadManifest { path, message } => write!(
+                f,
+                "Failed to read the hot reload manifest at {}, because {message}",
+                path.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for EmbedError {}
+
+/// Arguments to an exposed Roc function: a tuple of up to four [RocAbi] values.
+pub trait RocArgs {
+    /// # Safety
+    /// `function` must be an exposed Roc function that takes these arguments and returns `R`.
+    #[doc(hidden)]
+    unsafe fn call_exposed<R: RocAbi>(self, function: *const c_void) -> R;
+}
+
+macro_rules! impl_roc_args {
+    ($($arg:ident),*) => {
+        impl<$($arg: RocAbi),*> RocArgs for ($($arg,)*) {
+            #[allow(non_snake_case)]
+            unsafe fn call_exposed<R: RocAbi>(self, function: *const c_void) -> R {
+                // Exposed functions write their return value through the first argument.
+                // Str, List, and large records are passed by pointer, and everything else by value.
+                let function: unsafe extern "C" fn(*mut R, $(<$arg as RocAbi>::Arg),*) =
+                    core::mem::transmute(function);
+                let ($($arg,)*) = self;
+                // Roc takes ownership of the arguments, and decrements their refcounts itself.
+                $(let $arg = ManuallyDrop::new($arg);)*
+                let mut ret = MaybeUninit::<R>::uninit();
+
+                function(ret.as_mut_ptr(), $($arg::to_arg(&$arg)),*);
+
+                ret.assume_init()
+            }
+        }
+    };
+}
+
+impl_roc_args!();
+impl_roc_args!(A);
+impl_roc_args!(A, B);
+impl_roc_args!(A, B, C);
+impl_roc_args!(A, B, C, D);
+
+/// A Roc app that was built as a shared library.
+#[derive(Debug)]
+pub struct RocApp {
+    lib: Library,
+}
+
+impl RocApp {
+    /// # Safety
+    /// Loading a library runs its initialization code, so it must be a library built by Roc.
+    pub unsafe fn load(path: &Path) -> Result<Self, EmbedError> {
+        runtime::link();
+        heap_profile::start_from_env();
+        leak_check::start_from_env();
+
+        if let Some(frame_table) = stack_trace::FrameTable::load(path) {
+            stack_trace::register(frame_table);
+        }
+
+        Library::new(path)
+            .map(|lib| RocApp { lib })
+            .map_err(EmbedError::Load)
+    }
+
+    /// Whether the platform exposes a function with this name to the host.
+    pub fn exposes(&self, name: &str) -> bool {
+        self.exposed_function(name).is_ok()
+    }
+
+    /// Calls the function the platform exposes to the host with the given name.
+    ///
+    /// # Safety
+    /// The Roc function's argument and return types must correspond to `Args` and `R`.
+    /// This can't be checked at runtime, because the library doesn't include any type information.
+    pub unsafe fn call<Args: RocArgs, R: RocAbi>(
+        &self,
+        name: &str,
+        args: Args,
+    ) -> Result<R, EmbedError> {
+        let function = self.exposed_function(name)?;
+
+        Ok(args.call_exposed(function))
+    }
+
+    fn exposed_function(&self, name: &str) -> Result<*const c_void, EmbedError> {
+        let symbol = exposed_symbol_name(name);
+
+        unsafe { self.lib.get::<*const c_void>(symbol.as_bytes()) }
+            .map(|function| *function)
+            .map_err(|_| EmbedError::MissingFunction {
+                name: name.to_string(),
+                symbol,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    roc_record! {
+        #[derive(Debug)]
+        struct Config {
+            limits: RocList<u64>,
+            name: RocStr,
+            retries: u32,
+            unit: (),
+            verbose: bool,
+        }
+    }
+
+    #[test]
+    fn record_fields_in_roc_order() {
+        let names: Vec<_> = Config::FIELDS.iter().map(|field| field.name).collect();
+
+        assert_eq!(names, ["limits", "name", "retries", "unit", "verbose"]);
+        assert!(is_roc_field_order(Config::FIELDS));
+
+        let misordered = [Config::FIELDS[1], Config::FIELDS[0]];
+
+        assert!(!is_roc_field_order(&misordered));
+    }
+
+    #[test]
+    fn tag_ids_are_alphabetical() {
+        let tags = ["Ok", "Err", "Pending"];
+
+        assert_eq!(tag_id(&tags, "Err"), Some(0));
+        assert_eq!(tag_id(&tags, "Ok"), Some(1));
+        assert_eq!(tag_id(&tags, "Pending"), Some(2));
+        assert_eq!(tag_id(&tags, "Missing"), None);
+    }
+
+    roc_tag_union! {
+        #[derive(Debug, PartialEq)]
+        enum Outcome {
+            Ok(i64),
+            Err(RocStr),
+        }
+    }
+
+    #[test]
+    fn tag_unions_have_the_layout_of_roc_result() {
+        type Expected = RocResult<i64, RocStr>;
+
+        assert_eq!(tag_index(&["Ok", "Err"], "Err"), 0);
+        assert_eq!(tag_index(&["Ok", "Err"], "Ok"), 1);
+        assert_eq!(
+            core::mem::size_of::<RocUnion<Outcome>>(),
+            core::mem::size_of::<Expected>()
+        );
+
+        let ok: RocUnion<Outcome> = unsafe { core::mem::transmute(Expected::ok(5)) };
+        let err: RocUnion<Outcome> =
+            unsafe { core::mem::transmute(Expected::err(RocStr::from("nope"))) };
+
+        assert_eq!(ok.into_inner(), Outcome::Ok(5));
+        assert_eq!(err.into_inner(), Outcome::Err(RocStr::from("nope")));
+        assert_eq!(
+            RocUnion::new(Outcome::Err(RocStr::from("back"))).into_inner(),
+            Outcome::Err(RocStr::from("back"))
+        );
+        assert!(!is_valid_tag_union(&["Ok"]));
+        assert!(!is_valid_tag_union(&["Ok", "Ok"]));
+    }
+
+    #[test]
+    fn missing_library() {
+        let result = unsafe { RocApp::load(Path::new("/does/not/exist/libapp.so")) };
+
+        assert!(matches!(result, Err(EmbedError::Load(_))));
+    }
+}
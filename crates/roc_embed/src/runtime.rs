@@ -0,0 +1,127 @@
+//! The functions every Roc host has to provide. Roc's standard library calls these,
+//! so the shared library resolves them from the executable that loads it.
+use crate::heap_profile;
+use core::ffi::c_void;
+use roc_std::RocStr;
+
+/// # Safety
+/// This just delegates to libc::malloc, so it's equally safe.
+#[no_mangle]
+pub unsafe extern "C" fn roc_alloc(size: usize, _alignment: u32) -> *mut c_void {
+    let ptr = libc::malloc(size);
+
+    heap_profile::record_alloc(ptr, size);
+
+    ptr
+}
+
+/// # Safety
+/// This just delegates to libc::realloc, so it's equally safe.
+#[no_mangle]
+pub unsafe extern "C" fn roc_realloc(
+    c_ptr: *mut c_void,
+    new_size: usize,
+    _old_size: usize,
+    _alignment: u32,
+) -> *mut c_void {
+    let ptr = libc::realloc(c_ptr, new_size);
+
+    heap_profile::record_realloc(c_ptr, ptr, new_size);
+
+    ptr
+}
+
+/// # Safety
+/// This just delegates to libc::free, so it's equally safe.
+#[no_mangle]
+pub unsafe extern "C" fn roc_dealloc(c_ptr: *mut c_void, _alignment: u32) {
+    heap_profile::record_dealloc(c_ptr);
+
+    poison_freed(c_ptr);
+
+    libc::free(c_ptr)
+}
+
+/// In debug builds, overwrites memory Roc is about to free, so that a use after free (e.g. of a
+/// value whose refcount was decremented once too often) reads an obviously wrong refcount or
+/// length, instead of often still working. Sanitizers catch these on their own, but also poison
+/// the memory, which is fine: under ASan, `malloc_usable_size` is exactly the allocated size.
+#[cfg(all(debug_assertions, target_os = "linux"))]
+unsafe fn poison_freed(c_ptr: *mut c_void) {
+    if !c_ptr.is_null() {
+        libc::memset(c_ptr, 0xDD, libc::malloc_usable_size(c_ptr));
+    }
+}
+
+#[cfg(not(all(debug_assertions, target_os = "linux")))]
+unsafe fn poison_freed(_c_ptr: *mut c_void) {}
+
+/// # Safety
+/// This just delegates to libc::memset, so it's equally safe.
+#[no_mangle]
+pub unsafe extern "C" fn roc_memset(dst: *mut c_void, c: i32, n: usize) -> *mut c_void {
+    libc::memset(dst, c, n)
+}
+
+/// # Safety
+/// `msg` must point to a valid RocStr.
+#[no_mangle]
+pub unsafe extern "C" fn roc_panic(msg: *mut c_void, tag_id: u32) {
+    let msg = &*(msg as *const RocStr);
+
+    match tag_id {
+        0 => eprintln!("Roc standard library hit a panic: {msg}"),
+        _ => eprintln!("Roc app crashed: {msg}"),
+    }
+
+    if let Some(stack_trace) = crate::stack_trace::current_stack_trace() {
+        eprintln!("{stack_trace}");
+    }
+
+    // Unwinding through Roc's stack frames is not possible
+    std::process::abort()
+}
+
+/// # Safety
+/// All three arguments must point to valid RocStrs.
+#[no_mangle]
+pub unsafe extern "C" fn roc_dbg(loc: *mut c_void, msg: *mut c_void, src: *mut c_void) {
+    let loc = &*(loc as *const RocStr);
+    let msg = &*(msg as *const RocStr);
+    let src = &*(src as *const RocStr);
+
+    crate::dbg::report(loc.as_str(), src.as_str(), msg.as_str());
+}
+
+/// Makes sure the linker keeps the functions above, even though nothing in Rust calls them.
+pub(crate) fn link() {
+    let functions = [
+        roc_alloc as *const (),
+        roc_realloc as *const (),
+        roc_dealloc as *const (),
+        roc_memset as *const (),
+        roc_panic as *const (),
+        roc_dbg as *const (),
+    ];
+
+    core::hint::black_box(functions);
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[cfg(all(debug_assertions, target_os = "linux"))]
+    fn freed_memory_is_poisoned() {
+        unsafe {
+            let ptr = libc::malloc(32);
+            libc::memset(ptr, 0x11, 32);
+
+            super::poison_freed(ptr);
+
+            let bytes = core::slice::from_raw_parts(ptr as *const u8, 32);
+            assert!(bytes.iter().all(|byte| *byte == 0xDD));
+
+            libc::free(ptr);
+        }
+    }
+}
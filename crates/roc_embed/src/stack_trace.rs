@@ -0,0 +1,295 @@
+//! Roc stack traces for crashes, using the frame table `roc build --stack-traces` writes next to
+//! the library. The table maps the names of the native functions on the stack back to Roc defs.
+//!
+//! The native stack is symbolicated with [std::backtrace], so this needs the library's symbol
+//! table: it doesn't work if the library was stripped.
+use std::backtrace::Backtrace;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Has to match the header the compiler writes.
+const FRAME_TABLE_HEADER: &str = "# roc frame table v2";
+
+/// A Roc def, and where it is in the source code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RocFrame {
+    /// The qualified name of the def, e.g. `Main.parse`.
+    pub name: String,
+    /// The path, line and column of the def, e.g. `main.roc:12:1`.
+    pub location: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct FrameTable {
+    /// By qualified name, e.g. `Main.parse`
+    frames: HashMap<String, RocFrame>,
+}
+
+impl FrameTable {
+    /// Returns None if this isn't a frame table in a format this version understands.
+    pub fn parse(src: &str) -> Option<Self> {
+        let mut lines = src.lines();
+
+        if lines.next()? != FRAME_TABLE_HEADER {
+            return None;
+        }
+
+        let mut frames = HashMap::new();
+
+        for line in lines {
+            let mut fields = line.split('\t');
+
+            let (Some(name), Some(location)) = (fields.next(), fields.next()) else {
+                return None;
+            };
+
+            frames.insert(
+                name.to_string(),
+                RocFrame {
+                    name: name.to_string(),
+                    location: location.to_string(),
+                },
+            );
+        }
+
+        Some(FrameTable { frames })
+    }
+
+    /// Loads the frame table for the given executable or library, if it has one.
+    pub fn load(binary_path: &Path) -> Option<Self> {
+        let mut path = binary_path.as_os_str().to_owned();
+
+        path.push(".roc-frames");
+
+        Self::parse(&std::fs::read_to_string(path).ok()?)
+    }
+
+    /// The Roc def that the native function with this name was generated from.
+    pub fn lookup(&self, function_name: &str) -> Option<&RocFrame> {
+        self.frames.get(&demangle(function_name)?)
+    }
+
+    /// The Roc frames of a rendered backtrace, most recent call first.
+    pub fn roc_frames(&self, backtrace: &str) -> Vec<&RocFrame> {
+        function_names(backtrace)
+            .filter_map(|function_name| self.lookup(function_name))
+            .collect()
+    }
+}
+
+/// The names of the functions in a rendered backtrace, most recent call first.
+pub fn function_names(backtrace: &str) -> impl Iterator<Item = &str> {
+    backtrace.lines().filter_map(|line| {
+        // Frames look like `  12: function_name`, and are followed by their location (if known)
+        let (index, function_name) = line.trim_start().split_once(": ")?;
+
+        if index.bytes().all(|byte| byte.is_ascii_digit()) {
+            Some(function_name.trim_end())
+        } else {
+            None
+        }
+    })
+}
+
+/// The `Module.def` a mangled function name was generated for, ignoring the suffixes that tell
+/// specializations apart. This has to match the mangling in the compiler's `roc_mono::mangle`:
+/// `_Roc`, the module and the def (each prefixed with its length, and with any characters other
+/// than `[A-Za-z0-9_.]` escaped as `$xx`), and then `_h` and a 16 digit hash.
+pub fn demangle(function_name: &str) -> Option<String> {
+    fn length_prefixed(text: &str) -> Option<(&str, &str)> {
+        let digits = text.bytes().take_while(u8::is_ascii_digit).count();
+        let len: usize = text[..digits].parse().ok()?;
+        let rest = &text[digits..];
+
+        if len == 0 {
+            return None;
+        }
+
+        Some((rest.get(..len)?, rest.get(len..)?))
+    }
+
+    fn unescape(text: &str, bytes: &mut Vec<u8>) -> Option<()> {
+        let mut rest = text.as_bytes();
+
+        while let Some((&byte, after)) = rest.split_first() {
+            if byte == b'$' {
+                let hex = std::str::from_utf8(after.get(..2)?).ok()?;
+                bytes.push(u8::from_str_radix(hex, 16).ok()?);
+                rest = &after[2..];
+            } else if byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'.' {
+                bytes.push(byte);
+                rest = after;
+            } else {
+                return None;
+            }
+        }
+
+        Some(())
+    }
+
+    let rest = function_name.strip_prefix("_Roc")?;
+    let (module, rest) = length_prefixed(rest)?;
+    let (def, rest) = length_prefixed(rest)?;
+    let rest = rest.strip_prefix("_h")?;
+
+    let (digits, suffix) = (rest.get(..16)?, rest.get(16..)?);
+
+    if !digits.bytes().all(|byte| byte.is_ascii_hexdigit())
+        || !(suffix.is_empty() || suffix.starts_with(['$', '.']))
+    {
+        return None;
+    }
+
+    let mut bytes = Vec::new();
+
+    unescape(module, &mut bytes)?;
+    bytes.push(b'.');
+    unescape(def, &mut bytes)?;
+
+    String::from_utf8(bytes).ok()
+}
+
+static FRAME_TABLES: Mutex<Vec<FrameTable>> = Mutex::new(Vec::new());
+
+/// Makes crashes print a stack trace for the Roc functions in this table.
+/// [crate::RocApp::load] does this for the library's frame table, if it has one.
+pub fn register(table: FrameTable) {
+    FRAME_TABLES.lock().unwrap().push(table);
+}
+
+/// Whether the native function with this name is a Roc function in a registered frame table.
+pub fn is_roc_function(function_name: &str) -> bool {
+    FRAME_TABLES
+        .lock()
+        .map(|tables| {
+            tables
+                .iter()
+                .any(|table| table.lookup(function_name).is_some())
+        })
+        .unwrap_or(false)
+}
+
+/// The innermost Roc def in a rendered backtrace, if any of its functions are in a registered
+/// frame table.
+pub fn innermost_roc_def(backtrace: &str) -> Option<RocFrame> {
+    let tables = FRAME_TABLES.lock().ok()?;
+
+    function_names(backtrace).find_map(|function_name| {
+        tables
+            .iter()
+            .find_map(|table| table.lookup(function_name))
+            .cloned()
+    })
+}
+
+/// Renders the Roc frames of the current stack, or returns None if none of them are Roc functions
+/// in a registered frame table.
+pub fn current_stack_trace() -> Option<String> {
+    let tables = FRAME_TABLES.lock().ok()?;
+
+    if tables.is_empty() {
+        return None;
+    }
+
+    let backtrace = Backtrace::force_capture().to_string();
+    let frames: Vec<_> = tables
+        .iter()
+        .flat_map(|table| table.roc_frames(&backtrace))
+        .collect();
+
+    if frames.is_empty() {
+        return None;
+    }
+
+    let mut buf = String::from("Roc stack trace (most recent call first):\n");
+
+    for frame in frames {
+        writeln!(buf, "    {} ({})", frame.name, frame.location).unwrap();
+    }
+
+    Some(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TABLE: &str = "# roc frame table v2
+Main.main\tmain.roc:3:1
+Main.parse_line\tmain.roc:10:1
+Main.read!\tmain.roc:14:1
+";
+
+    #[test]
+    fn lookup_demangles_and_ignores_specialization() {
+        let table = FrameTable::parse(TABLE).unwrap();
+        let parse_line = Some(&RocFrame {
+            name: "Main.parse_line".to_string(),
+            location: "main.roc:10:1".to_string(),
+        });
+
+        assert_eq!(
+            table.lookup("_Roc4Main10parse_line_h0123456789abcdef"),
+            parse_line
+        );
+        assert_eq!(
+            table.lookup("_Roc4Main10parse_line_hfedcba9876543210$1"),
+            parse_line
+        );
+        assert_eq!(
+            table.lookup("_Roc4Main10parse_line_h0123456789abcdef.llvm.7"),
+            parse_line
+        );
+        assert_eq!(
+            table
+                .lookup("_Roc4Main7read$21_h0000000000000001")
+                .map(|frame| frame.name.as_str()),
+            Some("Main.read!")
+        );
+        assert_eq!(table.lookup("_Roc4Main5parse_h0123456789abcdef"), None);
+        assert_eq!(table.lookup("Main_parse_line_1a2b3c"), None);
+        assert_eq!(table.lookup("roc_panic"), None);
+    }
+
+    #[test]
+    fn demangle_rejects_other_names() {
+        assert_eq!(
+            demangle("_Roc4Json6decode_h0123456789abcdef"),
+            Some("Json.decode".to_string())
+        );
+        assert_eq!(demangle("_Roc4Json6decode_h0123"), None);
+        assert_eq!(demangle("_Roc4Json6decode_h0123456789abcdefx"), None);
+        assert_eq!(demangle("_Roc9Json6decode_h0123456789abcdef"), None);
+        assert_eq!(demangle("_RNvCs1234_7mycrate3foo"), None);
+    }
+
+    #[test]
+    fn unknown_format_is_rejected() {
+        assert!(
+            FrameTable::parse("# roc frame table v1\nMain_main\tMain.main\tmain.roc:3:1\n")
+                .is_none()
+        );
+        assert!(FrameTable::parse("# roc frame table v2\nMain.main\n").is_none());
+    }
+
+    #[test]
+    fn roc_frames_of_backtrace() {
+        let table = FrameTable::parse(TABLE).unwrap();
+        let backtrace = "   0: std::backtrace::Backtrace::force_capture
+             at /rustc/library/std/src/backtrace.rs:312:9
+   1: roc_panic
+   2: _Roc4Main10parse_line_h0123456789abcdef
+   3: _Roc4Main4main_h00000000000000ff
+   4: roc__main_for_host_1_exposed_generic
+";
+        let names: Vec<_> = table
+            .roc_frames(backtrace)
+            .into_iter()
+            .map(|frame| frame.name.as_str())
+            .collect();
+
+        assert_eq!(names, ["Main.parse_line", "Main.main"]);
+    }
+}
@@ -0,0 +1,142 @@
+//! Builds the Roc app in `fixtures/embed_app` as a shared library, and calls the functions its
+//! platform exposes through [RocApp].
+use bumpalo::Bump;
+use roc_build::link::{LinkType, LinkingStrategy};
+use roc_build::program::{
+    build_file, handle_error_module, handle_loading_problem, standard_load_config, BuildFileError,
+    BuildOrdering, BuiltFile, CodeGenBackend, CodeGenOptions,
+};
+use roc_embed::hot_reload::{HotReload, MANIFEST_HEADER};
+use roc_embed::{roc_tag_union, RocApp, RocUnion};
+use roc_gen_llvm::llvm::build::LlvmBackendMode;
+use roc_load::Threading;
+use roc_mono::ir::OptLevel;
+use roc_packaging::cache::{self, RocCacheDir};
+use roc_std::RocStr;
+use roc_target::{OperatingSystem, Target};
+use std::path::{Path, PathBuf};
+use target_lexicon::Triple;
+
+roc_tag_union! {
+    #[derive(Debug, PartialEq)]
+    enum Size {
+        Negative,
+        Small(i64),
+        Large(RocStr),
+    }
+}
+
+fn build_lib(out_dir: &Path) -> PathBuf {
+    let target: Target = Triple::host().into();
+    let app_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/embed_app/main.roc");
+    let code_gen_options = CodeGenOptions {
+        backend: CodeGenBackend::Llvm(LlvmBackendMode::Binary),
+        opt_level: OptLevel::Development,
+        emit_debug_info: false,
+        emit_llvm_ir: false,
+        fuzz: false,
+        deterministic: false,
+        emit_js_glue: false,
+        emit_frame_table: false,
+        emit_mono_ir: false,
+        emit_specialization_report: false,
+        warn_closure_captures: false,
+        emit_borrow_report: false,
+        emit_dead_field_report: false,
+        emit_match_trees: false,
+        emit_size_report: false,
+    };
+    let arena = Bump::new();
+    let built = build_file(
+        &arena,
+        target,
+        app_path.clone(),
+        code_gen_options,
+        false,
+        LinkType::Dylib,
+        LinkingStrategy::Legacy,
+        false,
+        true,
+        None,
+        RocCacheDir::Persistent(cache::roc_cache_packages_dir().as_path()),
+        standard_load_config(target, BuildOrdering::BuildIfChecks, Threading::Single),
+        Some(out_dir),
+        false,
+    );
+
+    let binary_path = match built {
+        Ok(BuiltFile { binary_path, .. }) => binary_path,
+        Err(BuildFileError::ErrorModule { module, total_time }) => {
+            handle_error_module(module, total_time, app_path.as_os_str(), false).unwrap();
+            panic!("the fixture app has errors");
+        }
+        Err(BuildFileError::LoadingProblem(problem)) => {
+            handle_loading_problem(problem).unwrap();
+            panic!("the fixture app failed to load");
+        }
+    };
+
+    match target.operating_system() {
+        OperatingSystem::Windows => binary_path.with_extension("dll"),
+        OperatingSystem::Mac => binary_path.with_extension("dylib"),
+        _ => binary_path.with_extension("so"),
+    }
+}
+
+#[test]
+#[cfg_attr(windows, ignore)]
+fn calls_exposed_functions() {
+    let out_dir = tempfile::tempdir().unwrap();
+    let lib_path = build_lib(out_dir.path());
+    let app = unsafe { RocApp::load(&lib_path) }.unwrap();
+
+    assert!(app.exposes("double_for_host"));
+    assert!(!app.exposes("triple_for_host"));
+
+    let doubled: i64 = unsafe { app.call("double_for_host", (21i64,)) }.unwrap();
+
+    assert_eq!(doubled, 42);
+
+    let greeting: RocStr =
+        unsafe { app.call("greet_for_host", (RocStr::from("embedded Roc"),)) }.unwrap();
+
+    assert_eq!(greeting.as_str(), "Hello, embedded Roc!");
+
+    let classify = |n: i64| -> Size {
+        let size: RocUnion<Size> = unsafe { app.call("classify_for_host", (n,)) }.unwrap();
+
+        size.into_inner()
+    };
+
+    assert_eq!(classify(-3), Size::Negative);
+    assert_eq!(classify(7), Size::Small(7));
+    assert_eq!(classify(1234), Size::Large(RocStr::from("1234")));
+}
+
+#[test]
+#[cfg_attr(windows, ignore)]
+fn hot_reload_loads_each_new_generation() {
+    let out_dir = tempfile::tempdir().unwrap();
+    let lib_path = build_lib(out_dir.path());
+    let manifest_path = out_dir.path().join("manifest");
+    let mut hot_reload = HotReload::new(manifest_path.clone());
+
+    std::fs::write(&manifest_path, format!("{MANIFEST_HEADER}\n")).unwrap();
+
+    assert!(unsafe { hot_reload.poll() }.unwrap().is_none());
+
+    std::fs::write(
+        &manifest_path,
+        format!("{MANIFEST_HEADER}\n1\t{}\n", lib_path.display()),
+    )
+    .unwrap();
+
+    let app = unsafe { hot_reload.poll() }
+        .unwrap()
+        .expect("generation 1 should be loaded");
+    let doubled: i64 = unsafe { app.call("double_for_host", (4i64,)) }.unwrap();
+
+    assert_eq!(doubled, 8);
+    assert_eq!(hot_reload.generation(), 1);
+    assert!(unsafe { hot_reload.poll() }.unwrap().is_none());
+}
@@ -0,0 +1,13 @@
+app [greet, double, classify] { pf: platform "platform/main.roc" }
+
+greet = \name -> "Hello, $(name)!"
+
+double = \n -> n * 2
+
+classify = \n ->
+    if n < 0 then
+        Negative
+    else if n < 100 then
+        Small(n)
+    else
+        Large(Num.to_str(n))
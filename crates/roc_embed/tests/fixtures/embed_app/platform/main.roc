@@ -0,0 +1,15 @@
+platform "embed_app"
+    requires {} { greet : Str -> Str, double : I64 -> I64, classify : I64 -> [Large Str, Negative, Small I64] }
+    exposes []
+    packages {}
+    imports []
+    provides [greet_for_host, double_for_host, classify_for_host]
+
+greet_for_host : Str -> Str
+greet_for_host = \name -> greet(name)
+
+double_for_host : I64 -> I64
+double_for_host = \n -> double(n)
+
+classify_for_host : I64 -> [Large Str, Negative, Small I64]
+classify_for_host = \n -> classify(n)
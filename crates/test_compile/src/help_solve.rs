@@ -50,6 +50,7 @@ impl SolvedExpr {
             module_params: None,
             module_params_vars: VecMap::default(),
             host_exposed_symbols: None,
+            trace_abilities: false,
             #[cfg(debug_assertions)]
             checkmate: None,
         };
@@ -0,0 +1,323 @@
+//! Recording [crate::phase_span]s in the [Chrome trace event format], so that a build can be
+//! opened as a flamegraph in `chrome://tracing`, [Perfetto] or [speedscope], with one track per
+//! thread.
+//!
+//! Every span becomes a complete (`"ph": "X"`) event when it is dropped, with its fields as the
+//! event's arguments.
+//!
+//! [Chrome trace event format]: https://docs.google.com/document/d/1CvAClvFfyA5R-PhYUmn5OOQtYMH4h6I0nSsKchNAySU
+//! [Perfetto]: https://ui.perfetto.dev
+//! [speedscope]: https://www.speedscope.app
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+use std::thread::ThreadId;
+use std::time::Instant;
+
+/// The trace being recorded, if any. There is at most one per process.
+static TRACE: OnceLock<Trace> = OnceLock::new();
+
+struct Trace {
+    start: Instant,
+    file: Mutex<TraceFile>,
+}
+
+struct TraceFile {
+    writer: Box<dyn Write + Send>,
+    is_empty: bool,
+    is_finished: bool,
+    /// A small number for every thread that recorded a span, since thread ids of the standard
+    /// library can't be turned into numbers on stable Rust.
+    threads: HashMap<ThreadId, u64>,
+}
+
+impl TraceFile {
+    fn write_event(&mut self, event: &str) {
+        let separator = if self.is_empty { "[\n" } else { ",\n" };
+
+        self.is_empty = false;
+        // A trace that misses events is still useful, so keep compiling.
+        let _ = write!(self.writer, "{separator}{event}");
+    }
+
+    fn thread_id(&mut self) -> u64 {
+        let thread = std::thread::current();
+
+        if let Some(id) = self.threads.get(&thread.id()) {
+            return *id;
+        }
+
+        let id = self.threads.len() as u64;
+        let mut event = format!(
+            "{{\"ph\":\"M\",\"name\":\"thread_name\",\"pid\":1,\"tid\":{id},\"args\":{{\"name\":"
+        );
+
+        write_json_string(&mut event, thread.name().unwrap_or("worker"));
+        event.push_str("}}");
+
+        self.write_event(&event);
+        self.threads.insert(thread.id(), id);
+
+        id
+    }
+
+    /// Closes the JSON array and flushes the file. Spans that end afterwards are left out.
+    fn finish(&mut self) {
+        if self.is_finished {
+            return;
+        }
+
+        let opening = if self.is_empty { "[" } else { "" };
+
+        self.is_finished = true;
+        let _ = writeln!(self.writer, "{opening}\n]");
+        let _ = self.writer.flush();
+    }
+}
+
+impl Trace {
+    fn new(writer: Box<dyn Write + Send>) -> Self {
+        Trace {
+            start: Instant::now(),
+            file: Mutex::new(TraceFile {
+                writer,
+                is_empty: true,
+                is_finished: false,
+                threads: HashMap::new(),
+            }),
+        }
+    }
+
+    fn enter(&'static self, name: &'static str, args: SpanArgs) -> PhaseSpan {
+        PhaseSpan {
+            open: Some(OpenSpan {
+                trace: self,
+                name,
+                args,
+                start: Instant::now(),
+            }),
+        }
+    }
+
+    fn record(&self, span: OpenSpan) {
+        let end = Instant::now();
+        let start = span.start.duration_since(self.start).as_secs_f64() * 1e6;
+        let duration = end.duration_since(span.start).as_secs_f64() * 1e6;
+        let mut file = self.file.lock().unwrap();
+
+        if file.is_finished {
+            return;
+        }
+
+        let thread_id = file.thread_id();
+        let mut event = String::from("{\"ph\":\"X\",\"name\":");
+
+        write_json_string(&mut event, span.name);
+        write!(
+            event,
+            ",\"cat\":\"roc\",\"pid\":1,\"tid\":{thread_id},\"ts\":{start:.3},\"dur\":{duration:.3},\"args\":{{{}}}}}",
+            span.args.0
+        )
+        .unwrap();
+
+        file.write_event(&event);
+    }
+}
+
+/// The fields of a span, as the members of a JSON object.
+#[doc(hidden)]
+#[derive(Default)]
+pub struct SpanArgs(String);
+
+impl SpanArgs {
+    pub fn push(&mut self, field: &str, value: &str) {
+        if !self.0.is_empty() {
+            self.0.push(',');
+        }
+
+        write_json_string(&mut self.0, field);
+        self.0.push(':');
+        write_json_string(&mut self.0, value);
+    }
+}
+
+fn write_json_string(buf: &mut String, string: &str) {
+    buf.push('"');
+
+    for c in string.chars() {
+        match c {
+            '"' => buf.push_str("\\\""),
+            '\\' => buf.push_str("\\\\"),
+            '\n' => buf.push_str("\\n"),
+            '\t' => buf.push_str("\\t"),
+            c if c.is_control() => write!(buf, "\\u{:04x}", c as u32).unwrap(),
+            c => buf.push(c),
+        }
+    }
+
+    buf.push('"');
+}
+
+struct OpenSpan {
+    trace: &'static Trace,
+    name: &'static str,
+    args: SpanArgs,
+    start: Instant,
+}
+
+/// A phase of the compiler, which is recorded when this is dropped. Made with [crate::phase_span].
+#[must_use]
+pub struct PhaseSpan {
+    open: Option<OpenSpan>,
+}
+
+impl PhaseSpan {
+    /// Only calls `args` if a trace is being recorded.
+    #[inline]
+    pub fn enter(name: &'static str, args: impl FnOnce() -> SpanArgs) -> Self {
+        match TRACE.get() {
+            Some(trace) => trace.enter(name, args()),
+            None => PhaseSpan { open: None },
+        }
+    }
+}
+
+impl Drop for PhaseSpan {
+    fn drop(&mut self) {
+        if let Some(span) = self.open.take() {
+            span.trace.record(span);
+        }
+    }
+}
+
+/// Finishes the trace when dropped.
+pub struct ChromeTraceGuard {
+    trace: &'static Trace,
+}
+
+impl Drop for ChromeTraceGuard {
+    fn drop(&mut self) {
+        self.trace.file.lock().unwrap().finish();
+    }
+}
+
+/// Finishes the trace being recorded, if any. Call this before the process is replaced or exits
+/// without running destructors, since events are buffered until then.
+pub fn finish_chrome_trace() {
+    if let Some(trace) = TRACE.get() {
+        trace.file.lock().unwrap().finish();
+    }
+}
+
+/// Starts recording a trace to `path`, which is finished when the returned guard is dropped.
+pub fn start(path: &Path) -> io::Result<ChromeTraceGuard> {
+    let writer = BufWriter::new(File::create(path)?);
+
+    if TRACE.set(Trace::new(Box::new(writer))).is_err() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            "a trace is already being recorded",
+        ));
+    }
+
+    Ok(ChromeTraceGuard {
+        trace: TRACE.get().unwrap(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[derive(Clone, Default)]
+    struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn trace() -> (&'static Trace, SharedBuf) {
+        let buf = SharedBuf::default();
+        let trace = Box::leak(Box::new(Trace::new(Box::new(buf.clone()))));
+
+        (trace, buf)
+    }
+
+    fn events(buf: &SharedBuf) -> Vec<serde_json::Value> {
+        let json = String::from_utf8(buf.0.lock().unwrap().clone()).unwrap();
+
+        serde_json::from_str(&json).unwrap()
+    }
+
+    fn complete_event<'a>(events: &'a [serde_json::Value], name: &str) -> &'a serde_json::Value {
+        events
+            .iter()
+            .find(|event| event["ph"] == "X" && event["name"] == name)
+            .unwrap_or_else(|| panic!("no event for {name} in {events:?}"))
+    }
+
+    #[test]
+    fn spans_nest() {
+        let (trace, buf) = trace();
+
+        {
+            let _load = trace.enter("load", SpanArgs::default());
+            let mut args = SpanArgs::default();
+            args.push("module", "Main \"quoted\"");
+
+            drop(trace.enter("solve", args));
+        }
+
+        std::thread::Builder::new()
+            .name("specializer".into())
+            .spawn(move || drop(trace.enter("specialize", SpanArgs::default())))
+            .unwrap()
+            .join()
+            .unwrap();
+
+        trace.file.lock().unwrap().finish();
+
+        let events = events(&buf);
+        let load = complete_event(&events, "load");
+        let solve = complete_event(&events, "solve");
+        let specialize = complete_event(&events, "specialize");
+        let end = |event: &serde_json::Value| {
+            event["ts"].as_f64().unwrap() + event["dur"].as_f64().unwrap()
+        };
+
+        assert_eq!(solve["tid"], load["tid"]);
+        assert!(solve["ts"].as_f64() >= load["ts"].as_f64());
+        assert!(end(solve) <= end(load));
+        assert_eq!(
+            solve["args"],
+            serde_json::json!({ "module": "Main \"quoted\"" })
+        );
+
+        assert_ne!(specialize["tid"], load["tid"]);
+        assert!(events.iter().any(|event| event["ph"] == "M"
+            && event["tid"] == specialize["tid"]
+            && event["args"]["name"] == "specializer"));
+    }
+
+    #[test]
+    fn finished_trace_is_valid_json() {
+        let (trace, buf) = trace();
+        let late = trace.enter("late", SpanArgs::default());
+
+        trace.file.lock().unwrap().finish();
+        trace.file.lock().unwrap().finish();
+        drop(late);
+
+        assert!(events(&buf).is_empty());
+    }
+}
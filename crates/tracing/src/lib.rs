@@ -13,29 +13,59 @@
 //! Tracing is only turned on in debug builds. Use the provided [setup_tracing] macro to turn on
 //! tracing at an executable's entry point.
 //!
+//! The phases of the compiler (loading, solving, specializing and generating code) are also marked
+//! with [phase_span]s. If ROC_CHROME_TRACE=<filepath> is specified, those spans are written to
+//! <filepath> in the Chrome trace event format, which shows where a build spent its time as a
+//! flamegraph with one track per thread. Phase spans don't go through `tracing`, so they also
+//! work in release builds, where they cost a single check when no trace is being recorded.
+//!
 //! [directive-syntax]: https://docs.rs/tracing-subscriber/latest/tracing_subscriber/filter/struct.EnvFilter.html#directives
 
 /// Sets up tracing of a Roc executable. The value of this macro must be bound to a variable that
 /// is not dropped until tracing has completed.
 ///
 /// This macro should only be invoked at an executable's entry point.
-/// Tracing will only be enabled in debug builds.
+/// Logging will only be enabled in debug builds.
 #[macro_export]
 macro_rules! setup_tracing {
     () => {
         if cfg!(debug_assertions) {
             $crate::setup_tracing()
         } else {
-            $crate::TracingGuards::NONE
+            $crate::setup_chrome_trace()
         }
     };
 }
 
+/// Marks a phase of the compiler until the returned guard is dropped, for the Chrome trace.
+/// The fields are recorded with their [Debug] representation, which is only computed when a
+/// trace is being recorded.
+///
+/// ```
+/// let module_name = "Main";
+/// let _span = roc_tracing::phase_span!("parse", module = ?module_name);
+/// ```
+#[macro_export]
+macro_rules! phase_span {
+    ($name:literal $(, $field:ident = ?$value:expr)* $(,)?) => {
+        $crate::PhaseSpan::enter($name, || {
+            #[allow(unused_mut)]
+            let mut args = $crate::SpanArgs::default();
+            $(args.push(stringify!($field), &format!("{:?}", $value));)*
+            args
+        })
+    };
+}
+
+pub use chrome::{finish_chrome_trace, PhaseSpan, SpanArgs};
 pub use tracing::debug;
 pub use tracing::info;
 
+mod chrome;
+
 const ENV_FILTER: &str = "ROC_LOG";
 const LOGTO_VAR: &str = "ROC_LOGTO";
+const CHROME_TRACE_VAR: &str = "ROC_CHROME_TRACE";
 
 use tracing_subscriber::{fmt, prelude::*, EnvFilter, Layer, Registry};
 
@@ -43,16 +73,46 @@ use tracing_subscriber::{fmt, prelude::*, EnvFilter, Layer, Registry};
 /// Must not be dropped until all tracing is complete.
 pub struct TracingGuards {
     _file_appender_guard: Option<tracing_appender::non_blocking::WorkerGuard>,
+    _chrome_trace_guard: Option<chrome::ChromeTraceGuard>,
 }
 
 impl TracingGuards {
     pub const NONE: TracingGuards = TracingGuards {
         _file_appender_guard: None,
+        _chrome_trace_guard: None,
     };
 }
 
+/// Starts recording a Chrome trace, if ROC_CHROME_TRACE is set.
+fn start_chrome_trace() -> Option<chrome::ChromeTraceGuard> {
+    let path = std::env::var_os(CHROME_TRACE_VAR)?;
+
+    match chrome::start(path.as_ref()) {
+        Ok(guard) => Some(guard),
+        Err(err) => {
+            eprintln!(
+                "Not recording a trace, because {} could not be created: {err}",
+                std::path::Path::new(&path).display()
+            );
+
+            None
+        }
+    }
+}
+
+/// Sets up only the Chrome trace, for builds without logging.
+#[must_use]
+pub fn setup_chrome_trace() -> TracingGuards {
+    TracingGuards {
+        _file_appender_guard: None,
+        _chrome_trace_guard: start_chrome_trace(),
+    }
+}
+
 #[must_use]
 pub fn setup_tracing() -> TracingGuards {
+    let chrome_trace_guard = start_chrome_trace();
+
     if let Ok(file) = std::env::var(LOGTO_VAR) {
         let _ = std::fs::remove_file(&file);
         let file_appender = tracing_appender::rolling::never(".", file);
@@ -66,6 +126,7 @@ pub fn setup_tracing() -> TracingGuards {
 
         TracingGuards {
             _file_appender_guard: Some(guard),
+            _chrome_trace_guard: chrome_trace_guard,
         }
     } else {
         let stderr_layer = fmt::Layer::default()
@@ -74,6 +135,9 @@ pub fn setup_tracing() -> TracingGuards {
 
         Registry::default().with(stderr_layer).init();
 
-        TracingGuards::NONE
+        TracingGuards {
+            _file_appender_guard: None,
+            _chrome_trace_guard: chrome_trace_guard,
+        }
     }
 }